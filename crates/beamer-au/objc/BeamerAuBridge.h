@@ -547,6 +547,197 @@ bool beamer_au_get_parameter_info(
     BeamerAuParameterInfo* out_info
 );
 
+/**
+ * Get the number of parameters exposed to the host.
+ *
+ * Excludes internal (GUI-only) parameters that have not been promoted.
+ * Use for the AUParameterTree and kAudioUnitProperty_ParameterList; the
+ * WebView keeps using beamer_au_get_parameter_count.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return Number of host parameters (0 if instance is invalid).
+ */
+uint32_t beamer_au_get_host_parameter_count(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Get information about a host-exposed parameter by host index.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance    Handle to the plugin instance.
+ * @param host_index  Host parameter index (0 to host count-1).
+ * @param out_info    Pointer to structure to fill with parameter info.
+ *
+ * @return true if successful, false if index out of range or instance invalid.
+ */
+bool beamer_au_get_host_parameter_info(
+    BeamerAuInstanceHandle _Nullable instance,
+    uint32_t host_index,
+    BeamerAuParameterInfo* out_info
+);
+
+/**
+ * Check whether the host parameter list changed since the last call.
+ *
+ * Returns true once after the GUI promoted or demoted a parameter, or a
+ * loaded state restored different promotions. The wrapper should rebuild
+ * its parameter list and notify the host.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the list changed (the flag is cleared).
+ */
+bool beamer_au_take_parameter_list_changed(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Check whether the plugin marked its state dirty since the last call.
+ *
+ * Returns true once after the plugin changed state outside its parameters
+ * (e.g. loaded a sample). The wrapper should notify the host so the project
+ * prompts to save. Saving the state clears the mark.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the state was marked dirty (the flag is cleared).
+ */
+bool beamer_au_take_state_dirty(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Check whether a parameter constraint moved a value since the last call.
+ *
+ * Returns true once after enforcing the plugin's parameter constraints
+ * adjusted a parameter the host did not set (e.g. attack pushed release
+ * along), the audio thread applied a crossfaded preset, or a displayed
+ * processing statistic (DSP load, voices, underruns) changed. The wrapper
+ * should push the current values to the host.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if a value was adjusted (the flag is cleared).
+ */
+bool beamer_au_take_parameters_adjusted(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Store the name the host gave this instance.
+ *
+ * Forward AUv2 kAudioUnitProperty_ContextName or the AUv3 contextName
+ * (usually the track name). The plugin reads it via HostServices.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @param name     UTF-8 name, or NULL to clear it.
+ */
+void beamer_au_set_instance_name(BeamerAuInstanceHandle _Nullable instance, const char* _Nullable name);
+
+/**
+ * Get the display name suggested by the plugin (e.g. the loaded preset).
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance   Handle to the plugin instance.
+ * @param out_buffer Buffer to write the name (UTF-8, null-terminated).
+ * @param buffer_len Size of out_buffer in bytes.
+ *
+ * @return Number of bytes written (excluding null terminator), 0 if the
+ *         plugin suggests no display name.
+ */
+uint32_t beamer_au_get_display_name(
+    BeamerAuInstanceHandle _Nullable instance,
+    char* out_buffer,
+    uint32_t buffer_len
+);
+
+/**
+ * Check whether the suggested display name changed since the last call.
+ *
+ * The wrapper should notify the host (AUv2 NickName listeners, AUv3
+ * audioUnitShortName KVO).
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the display name changed (the flag is cleared).
+ */
+bool beamer_au_take_display_name_changed(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Check whether the plugin announced a latency change since the last call.
+ *
+ * Set by HostServices::latency_changed(). The wrapper should notify the host
+ * (AUv2 kAudioUnitProperty_Latency listeners, AUv3 latency KVO).
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the latency changed (the flag is cleared).
+ */
+bool beamer_au_take_latency_changed(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Check whether the plugin's bus layout changed since the last call.
+ *
+ * Returns true once after a loaded state or a GUI edit selected a different
+ * bus layout (e.g. "stereo out" vs "16 outs"). The wrapper should notify the
+ * host; the new bus counts are reported once render resources are
+ * deallocated.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the layout changed (the flag is cleared).
+ */
+bool beamer_au_take_bus_layout_changed(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Apply a newly selected oversampling factor.
+ *
+ * Prepares the processor again if the plugin's parameters select another
+ * oversampling factor than it runs with. Call after state loads, GUI edits
+ * and while polling parameters; the wrapper should then notify the host
+ * that the latency changed.
+ *
+ * Thread Safety: Must be called from the main thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the processor was prepared again.
+ */
+bool beamer_au_apply_oversampling(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Receives each property the host should re-read, as an AUv2
+ * AudioUnitPropertyID (AUv3 wrappers map it to the KVO key).
+ *
+ * @param context The context passed to beamer_au_dispatch_property_changes.
+ * @param property kAudioUnitProperty_Latency.
+ */
+typedef void (*BeamerAuPropertyListener)(void* _Nullable context, uint32_t property);
+
+/**
+ * Apply a pending oversampling change and report the properties that
+ * changed since the last call.
+ *
+ * Calls listener with kAudioUnitProperty_Latency after
+ * HostServices::latency_changed() or a new oversampling factor. Call it
+ * from a main-thread timer that runs while render resources are
+ * allocated, so changes announced from the audio thread reach the host
+ * with the editor closed.
+ *
+ * Thread Safety: Must be called from the main thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @param listener Receives the changed properties during this call.
+ * @param context Passed to listener.
+ */
+void beamer_au_dispatch_property_changes(BeamerAuInstanceHandle _Nullable instance,
+                                         BeamerAuPropertyListener _Nonnull listener,
+                                         void* _Nullable context);
+
 /**
  * Get a parameter's current normalized value.
  *
@@ -1026,6 +1217,63 @@ double beamer_au_param_string_to_normalized(
  */
 char* _Nullable beamer_au_param_info_json(BeamerAuInstanceHandle _Nullable instance);
 
+/**
+ * Get the plugin's frame meter values as a JSON object for `_onFrame`.
+ *
+ * Reading resets the peak meters. Returns a heap-allocated null-terminated C
+ * string that the caller must free with `beamer_au_free_string`. Returns NULL
+ * on failure.
+ *
+ * Thread Safety: Call from the main thread (frame clock callback).
+ *
+ * @param instance Handle to the plugin instance.
+ * @return JSON object string, or NULL on failure. Caller must free with beamer_au_free_string.
+ */
+char* _Nullable beamer_au_frame_meters_json(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Get the host transport as a JSON object for `_onFrame`.
+ *
+ * The position is extrapolated from the last render block to now. Returns a
+ * heap-allocated null-terminated C string that the caller must free with
+ * `beamer_au_free_string`. Returns NULL on failure.
+ *
+ * Thread Safety: Call from the main thread (frame clock callback).
+ *
+ * @param instance Handle to the plugin instance.
+ * @return JSON object string, or NULL on failure. Caller must free with beamer_au_free_string.
+ */
+char* _Nullable beamer_au_frame_playhead_json(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Take the next notification for the GUI as a script for the WebView.
+ *
+ * Returns NULL when no notification is due (delivery is rate limited).
+ * Otherwise returns a heap-allocated null-terminated C string that the caller
+ * must free with `beamer_au_free_string`.
+ *
+ * Thread Safety: Call from the main thread (GUI sync timer).
+ *
+ * @param instance Handle to the plugin instance.
+ * @return Script string, or NULL. Caller must free with beamer_au_free_string.
+ */
+char* _Nullable beamer_au_take_notification_script(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Take the frames of the plugin's GUI data channels as a script for the
+ * WebView (an `audio-data` event).
+ *
+ * Returns NULL when no delivery is due (the channels' rate applies) or no
+ * channel has frames. Otherwise returns a heap-allocated null-terminated C
+ * string that the caller must free with `beamer_au_free_string`.
+ *
+ * Thread Safety: Call from the main thread (GUI sync timer).
+ *
+ * @param instance Handle to the plugin instance.
+ * @return Script string, or NULL. Caller must free with beamer_au_free_string.
+ */
+char* _Nullable beamer_au_take_gui_data_script(BeamerAuInstanceHandle _Nullable instance);
+
 /**
  * Free a string returned by a beamer_au function (e.g., beamer_au_param_info_json,
  * beamer_au_on_invoke).
@@ -1048,6 +1296,18 @@ void beamer_au_free_string(char* _Nullable ptr);
  */
 void beamer_au_param_set_from_ui(BeamerAuInstanceHandle _Nullable instance, uint32_t param_id, double value);
 
+/**
+ * Record the start or end of a WebView UI gesture on a parameter.
+ *
+ * Host values are not reported as automation while a gesture is active.
+ * The ObjC wrapper still sends the gesture events to the host.
+ *
+ * @param instance Handle to the plugin instance.
+ * @param param_id Parameter ID.
+ * @param begin true for a gesture start, false for its end.
+ */
+void beamer_au_param_gesture_from_ui(BeamerAuInstanceHandle _Nullable instance, uint32_t param_id, bool begin);
+
 /**
  * Handle an invoke call from JavaScript.
  *
@@ -1089,6 +1349,78 @@ void beamer_au_on_event(BeamerAuInstanceHandle _Nullable instance,
                         const uint8_t* _Nonnull data_json,
                         size_t data_json_len);
 
+// =============================================================================
+// MARK: - Native Editor
+// =============================================================================
+
+/** Opaque handle to an open native editor (`GuiBackend::Native`). */
+typedef void* BeamerAuNativeEditorHandle;
+
+/** Edit kinds passed to BeamerAuEditCallback. */
+#define BEAMER_AU_EDIT_BEGIN 0
+#define BEAMER_AU_EDIT_VALUE 1
+#define BEAMER_AU_EDIT_END 2
+
+/**
+ * Called for each native editor edit. The wrapper applies it and notifies
+ * the host, like a WebView param:begin/param:set/param:end message.
+ *
+ * @param context The context passed to beamer_au_native_editor_open.
+ * @param kind One of the BEAMER_AU_EDIT_* constants.
+ * @param param_id Parameter ID.
+ * @param value Normalized value for BEAMER_AU_EDIT_VALUE, 0 otherwise.
+ */
+typedef void (*BeamerAuEditCallback)(void* _Nullable context, uint32_t kind, uint32_t param_id, double value);
+
+/**
+ * Check if the plugin's editor is native rather than a WebView.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the plugin has a GUI drawn by a native editor.
+ */
+bool beamer_au_has_native_gui(BeamerAuInstanceHandle _Nullable instance);
+
+/**
+ * Create a native editor and open it in `parent`.
+ *
+ * Thread Safety: Main thread only.
+ *
+ * @param instance Handle to the plugin instance.
+ * @param parent NSView to draw into; must outlive the editor.
+ * @param callback Receives the editor's edits until it is closed.
+ * @param context Passed to callback.
+ * @return The editor, or NULL if it could not be opened.
+ */
+BeamerAuNativeEditorHandle _Nullable beamer_au_native_editor_open(BeamerAuInstanceHandle _Nullable instance,
+                                                                  void* _Nonnull parent,
+                                                                  BeamerAuEditCallback _Nonnull callback,
+                                                                  void* _Nullable context);
+
+/**
+ * Get the size of an open native editor in points.
+ *
+ * @param editor The editor.
+ * @param width Pointer to receive the width.
+ * @param height Pointer to receive the height.
+ */
+void beamer_au_native_editor_get_size(BeamerAuNativeEditorHandle _Nullable editor,
+                                      uint32_t* _Nonnull width,
+                                      uint32_t* _Nonnull height);
+
+/**
+ * Resize an open native editor (in points) after its view was resized.
+ *
+ * Thread Safety: Main thread only.
+ */
+void beamer_au_native_editor_resize(BeamerAuNativeEditorHandle _Nullable editor, uint32_t width, uint32_t height);
+
+/**
+ * Close and free a native editor. The handle is invalid afterwards.
+ *
+ * Thread Safety: Main thread only.
+ */
+void beamer_au_native_editor_close(BeamerAuNativeEditorHandle _Nullable editor);
+
 // =============================================================================
 // MARK: - WebView C-ABI (beamer-webview)
 // =============================================================================
@@ -1167,6 +1499,32 @@ void beamer_webview_destroy(void* _Nullable handle);
  */
 void beamer_webview_eval_js(void* _Nonnull handle, const uint8_t* _Nonnull script, size_t len);
 
+/**
+ * Start calling `callback` once per display refresh (CVDisplayLink).
+ *
+ * Replaces a running frame clock. The callback runs on the main thread with
+ * the refresh time in milliseconds (monotonic). Refreshes are dropped while
+ * the previous one is still queued.
+ *
+ * @param handle   Opaque WebView handle.
+ * @param callback Called once per refresh.
+ * @param context  Opaque pointer passed to the callback. Must stay valid until
+ *                 the clock is stopped or the WebView is destroyed.
+ * @return true if the clock started, false if no display link was available.
+ */
+bool beamer_webview_start_frame_clock(
+    void* _Nonnull handle,
+    void (* _Nonnull callback)(void* context, double time_ms),
+    void* _Nullable context
+);
+
+/**
+ * Stop the frame clock. No frame callback runs after this returns.
+ *
+ * @param handle Opaque WebView handle.
+ */
+void beamer_webview_stop_frame_clock(void* _Nonnull handle);
+
 /**
  * Create a WebView with IPC support, serving embedded assets.
  *
@@ -1311,6 +1669,42 @@ uint32_t beamer_au_get_output_bus_channel_count(
     uint32_t bus_index
 );
 
+/**
+ * Get the AudioChannelLayoutTag for an input bus.
+ *
+ * Describes the bus speakers (mono, stereo, 5.1, 7.1, 7.1.4, ambisonics or
+ * discrete) at the negotiated channel count once resources are allocated,
+ * otherwise at the declared one.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance  Handle to the plugin instance.
+ * @param bus_index Index of the input bus.
+ *
+ * @return Layout tag (0 if bus index is invalid).
+ */
+uint32_t beamer_au_get_input_bus_layout_tag(
+    BeamerAuInstanceHandle _Nullable instance,
+    uint32_t bus_index
+);
+
+/**
+ * Get the AudioChannelLayoutTag for an output bus.
+ *
+ * See beamer_au_get_input_bus_layout_tag.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance  Handle to the plugin instance.
+ * @param bus_index Index of the output bus.
+ *
+ * @return Layout tag (0 if bus index is invalid).
+ */
+uint32_t beamer_au_get_output_bus_layout_tag(
+    BeamerAuInstanceHandle _Nullable instance,
+    uint32_t bus_index
+);
+
 /**
  * Check if a proposed channel configuration is valid.
  *
@@ -1446,6 +1840,21 @@ bool beamer_au_get_preset_info(
     BeamerAuPresetInfo* _Nonnull out_info
 );
 
+/**
+ * Check whether the factory preset list changed since the last call.
+ *
+ * Returns true once after presets were added, removed or renamed at runtime
+ * (e.g. user presets changed on disk). The wrapper should rebuild its preset
+ * list and notify the host (AUv2 FactoryPresets listeners, AUv3
+ * factoryPresets KVO).
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance.
+ * @return true if the list changed (the flag is cleared).
+ */
+bool beamer_au_take_preset_list_changed(BeamerAuInstanceHandle _Nullable instance);
+
 /**
  * Apply a factory preset by index.
  *
@@ -1457,13 +1866,32 @@ bool beamer_au_get_preset_info(
  * @param instance      Handle to the plugin instance.
  * @param preset_index  Preset index (0 to count-1).
  *
- * @return true if the preset was applied successfully, false if index out of range.
+ * When a preset crossfade is configured and render resources are allocated,
+ * the preset is queued and applied by the audio thread inside the fade (see
+ * beamer_au_get_preset_crossfade_ms()).
+ *
+ * @return true if the preset was applied (or queued), false if index out of range.
  */
 bool beamer_au_apply_preset(
     BeamerAuInstanceHandle _Nullable instance,
     uint32_t preset_index
 );
 
+/**
+ * Get the configured preset crossfade duration in milliseconds.
+ *
+ * When non-zero and render resources are allocated, beamer_au_apply_preset()
+ * only queues the preset; the audio thread applies it inside the fade.
+ * beamer_au_take_parameters_adjusted() then returns true, and the wrapper
+ * pushes the new values to the host.
+ *
+ * Thread Safety: Can be called from any thread.
+ *
+ * @param instance Handle to the plugin instance (currently unused).
+ * @return Crossfade duration in milliseconds (0 if disabled).
+ */
+float beamer_au_get_preset_crossfade_ms(BeamerAuInstanceHandle _Nullable instance);
+
 // =============================================================================
 // MARK: - MIDI Support
 // =============================================================================
@@ -36,8 +36,13 @@ use std::sync::{Arc, Mutex};
 
 use crate::buffer_storage::ProcessBufferStorage;
 use crate::buffers::AudioBufferList;
+use crate::channel_layout::speaker_layout_to_tag;
 use crate::error::os_status;
-use beamer_core::{BusType, CachedBusConfig, CachedBusInfo, ParameterUnit, WebViewHandler, MAX_BUSES};
+use beamer_core::{
+    Activation, AutomationPreview, Autosave, BusType, CachedBusConfig, CachedBusInfo, ConstraintState, ControllerPageState, CrashGuard, EditorHost, FrameMeters, GuiBackend, GuiDataChannels, HostServices, NativeEditor, Notifications,
+    ParameterChangeSource, ParameterExposure, ParameterLog, MidiMonitor, MidiPanic, ParameterUnit, ParentWindow, Size, NoteRanges, PresetCrossfade, ProcessWatchdog, ProcessingStats, ResponseCurves, StandardTrims, StoreWithPanic, StoreWithStats, StoreWithTrims, WebViewHandler, MAX_BUSES,
+};
+use beamer_core::host_trace::{self, HostCall};
 use crate::factory;
 use crate::instance::AuPluginInstance;
 use crate::render::{
@@ -418,6 +423,65 @@ pub struct BeamerInstanceHandle {
     /// Cached WebView handler, captured at instance creation to avoid
     /// locking the plugin mutex on every invoke/event call.
     webview_handler: Option<Arc<dyn WebViewHandler>>,
+    /// Cached editor backend, for creating native editors.
+    editor: GuiBackend,
+    /// Cached crash guard, shared with the plugin, for the safe mode invoke.
+    crash_guard: CrashGuard,
+    /// Cached host parameter exposure, shared with the plugin, for the
+    /// host-facing parameter list and the promote/demote invokes.
+    parameter_exposure: ParameterExposure,
+    /// Cached controller page selection, shared with the plugin, for the
+    /// page invokes.
+    controller_pages: ControllerPageState,
+    /// Cached standard trims, shared with the plugin, listed after the
+    /// plugin's parameters by every parameter function.
+    standard_trims: StandardTrims,
+    /// Cached processing statistics, shared with the plugin, timed around
+    /// render calls and listed after the standard trims.
+    processing_stats: ProcessingStats,
+    /// Cached MIDI panic trigger, shared with the plugin, listed after the
+    /// processing statistics and requested by the panic invoke.
+    midi_panic: MidiPanic,
+    /// Cached parameter constraints, shared with the plugin, enforced after
+    /// host and GUI edits.
+    parameter_constraints: ConstraintState,
+    /// Cached preset crossfade, shared with the plugin, to report presets
+    /// the audio thread applied.
+    preset_crossfade: Arc<PresetCrossfade>,
+    /// Cached MIDI response curves, shared with the plugin, for the curve
+    /// invokes.
+    response_curves: ResponseCurves,
+    /// Copy of the plugin's note ranges for the note range invoke.
+    note_ranges: NoteRanges,
+    /// Cached automation tracker, shared with the plugin, for host and GUI
+    /// edits and the automation state invoke.
+    automation_preview: AutomationPreview,
+    /// Cached autosave, shared with the plugin, for snapshots and the
+    /// recovery invokes.
+    autosave: Autosave,
+    /// Cached parameter change log, shared with the plugin, for host and GUI
+    /// edits and the log invokes.
+    parameter_log: ParameterLog,
+    /// Cached MIDI monitor, shared with the plugin, for the monitor invokes.
+    midi_monitor: MidiMonitor,
+    /// Cached host notification handle, shared with the plugin, for state
+    /// dirty marks.
+    host_services: HostServices,
+    /// Cached license activation, shared with the plugin, for the
+    /// activation invokes.
+    activation: Activation,
+    /// Cached frame meters, shared with the plugin, for the GUI frame clock.
+    frame_meters: FrameMeters,
+    /// Cached frame streams, shared with the plugin, for the GUI sync timer.
+    gui_data: GuiDataChannels,
+    /// Cached notification queue, shared with the plugin, for the GUI sync
+    /// timer.
+    notifications: Notifications,
+    /// Cached deadline watchdog, shared with the plugin, for timing render
+    /// calls and reporting overruns from the GUI sync timer.
+    watchdog: ProcessWatchdog,
+    /// Cached instance ID for the host call trace (`host-trace` feature).
+    trace_id: u32,
     /// Cached ParameterStore pointer for lock-free parameter access.
     ///
     /// Updated on the main thread during instance creation, allocate and
@@ -490,13 +554,47 @@ unsafe fn with_param_store<R>(
         // the 60Hz webview timer. Audio-thread callers only run between allocate
         // and deallocate when the pointer is stable.
         let store = unsafe { handle.param_store.get() };
-        return Some(f(store));
+        return Some(with_trims(handle, store, f));
     }
 
     // Fallback: should not happen, but handle gracefully.
     let plugin = lock_plugin(handle).ok()?;
     let store = plugin.parameter_store().ok()?;
-    Some(f(store))
+    Some(with_trims(handle, store, f))
+}
+
+/// Call `f` with `store` followed by the handle's standard trims,
+/// processing statistics and MIDI panic, if enabled.
+fn with_trims<R>(
+    handle: &BeamerInstanceHandle,
+    store: &dyn ParameterStore,
+    f: impl FnOnce(&dyn ParameterStore) -> R,
+) -> R {
+    let with_panic = |store: &dyn ParameterStore| {
+        if !handle.midi_panic.is_enabled() {
+            return f(store);
+        }
+        f(&StoreWithPanic {
+            plugin: store,
+            panic: &handle.midi_panic,
+        })
+    };
+    let with_stats = |store: &dyn ParameterStore| {
+        if !handle.processing_stats.is_enabled() {
+            return with_panic(store);
+        }
+        with_panic(&StoreWithStats {
+            plugin: store,
+            stats: &handle.processing_stats,
+        })
+    };
+    if !handle.standard_trims.is_enabled() {
+        return with_stats(store);
+    }
+    with_stats(&StoreWithTrims {
+        plugin: store,
+        trims: &handle.standard_trims,
+    })
 }
 
 /// Copy a Rust string into a fixed-size C char array.
@@ -623,7 +721,30 @@ pub extern "C" fn beamer_au_create_instance() -> BeamerAuInstanceHandle {
         // Cache the WebView handler and ParameterStore pointer before wrapping
         // in the Mutex so we don't need to lock on every invoke/event/param call.
         let webview_handler = plugin.webview_handler();
+        let editor = plugin.editor();
+        let crash_guard = plugin.crash_guard();
+        let parameter_exposure = plugin.parameter_exposure();
+        let controller_pages = plugin.controller_pages();
+        let standard_trims = plugin.standard_trims();
+        let processing_stats = plugin.processing_stats();
+        let midi_panic = plugin.midi_panic();
+        let parameter_constraints = plugin.parameter_constraints();
+        let preset_crossfade = plugin.preset_crossfade();
+        let response_curves = plugin.response_curves();
+        let note_ranges = plugin.note_ranges().clone();
+        let automation_preview = plugin.automation_preview();
+        let autosave = plugin.autosave();
+        let parameter_log = plugin.parameter_log();
+        let midi_monitor = plugin.midi_monitor();
+        let host_services = plugin.host_services();
+        let activation = plugin.activation();
+        let frame_meters = plugin.frame_meters();
+        let gui_data = plugin.gui_data();
+        let notifications = plugin.notifications();
+        let watchdog = plugin.watchdog();
+        let trace_id = plugin.trace_id();
         let param_store = ParamStorePtr::capture(plugin.as_ref());
+        host_trace::trace(trace_id, HostCall::Create);
 
         let handle = Box::new(BeamerInstanceHandle {
             plugin: Arc::new(Mutex::new(plugin)),
@@ -633,6 +754,28 @@ pub extern "C" fn beamer_au_create_instance() -> BeamerAuInstanceHandle {
             max_frames: 1024,
             bus_config: None,
             webview_handler,
+            editor,
+            crash_guard,
+            parameter_exposure,
+            controller_pages,
+            standard_trims,
+            processing_stats,
+            midi_panic,
+            parameter_constraints,
+            preset_crossfade,
+            response_curves,
+            note_ranges,
+            automation_preview,
+            autosave,
+            parameter_log,
+            midi_monitor,
+            host_services,
+            activation,
+            frame_meters,
+            gui_data,
+            notifications,
+            watchdog,
+            trace_id,
             param_store,
         });
 
@@ -669,7 +812,8 @@ pub extern "C" fn beamer_au_destroy_instance(instance: BeamerAuInstanceHandle) {
         // SAFETY: instance validated non-null above. Caller guarantees it was
         // returned by beamer_au_create_instance and not previously destroyed.
         // Box::from_raw takes ownership and will drop the instance.
-        let _ = unsafe { Box::from_raw(instance) };
+        let handle = unsafe { Box::from_raw(instance) };
+        host_trace::trace(handle.trace_id, HostCall::Destroy);
     }));
 }
 
@@ -752,9 +896,26 @@ pub extern "C" fn beamer_au_allocate_render_resources(
         handle.sample_format = sample_format;
         handle.sample_rate = sample_rate;
         handle.max_frames = max_frames;
+        handle.watchdog.set_sample_rate(sample_rate);
+        handle.processing_stats.set_sample_rate(sample_rate);
 
         // Convert bus configuration (bus counts already validated above)
         let rust_bus_config = bus_config_from_c(c_bus_config);
+        let main_channels =
+            |buses: &[CachedBusInfo]| buses.first().map_or(0, |bus| bus.channel_count as u32);
+        host_trace::trace(
+            handle.trace_id,
+            HostCall::SetBusArrangements {
+                inputs: rust_bus_config.input_buses.len() as u32,
+                outputs: rust_bus_config.output_buses.len() as u32,
+                main_input_channels: main_channels(&rust_bus_config.input_buses),
+                main_output_channels: main_channels(&rust_bus_config.output_buses),
+            },
+        );
+        host_trace::trace(
+            handle.trace_id,
+            HostCall::AllocateRenderResources { sample_rate, max_frames },
+        );
 
         // Validate bus configuration
         if let Err(_e) = rust_bus_config.validate() {
@@ -796,7 +957,8 @@ pub extern "C" fn beamer_au_allocate_render_resources(
         let sysex_buffer_size = config.sysex_buffer_size;
 
         // Create the render block based on sample format
-        // Note: We don't store host block pointers here - they're passed per-render call
+        // Note: We don't store host block pointers here - the musical context and
+        // transport state blocks are passed per-render call
         let render_block: Arc<dyn RenderBlockTrait> = match handle.sample_format {
             BeamerAuSampleFormat::Float32 => {
                 let storage = ProcessBufferStorage::<f32>::allocate_from_config(
@@ -806,8 +968,6 @@ pub extern "C" fn beamer_au_allocate_render_resources(
                 Arc::from(create_render_block_f32(
                     Arc::clone(&handle.plugin),
                     storage,
-                    None, // musical_context_block passed at render time
-                    None, // transport_state_block passed at render time
                     None, // schedule_midi_event_block passed at render time
                     max_frames,
                     sample_rate,
@@ -824,8 +984,6 @@ pub extern "C" fn beamer_au_allocate_render_resources(
                     Arc::clone(&handle.plugin),
                     storage,
                     None,
-                    None,
-                    None,
                     max_frames,
                     sample_rate,
                     sysex_slots,
@@ -884,6 +1042,9 @@ pub extern "C" fn beamer_au_deallocate_render_resources(instance: BeamerAuInstan
         // SAFETY: instance validated non-null above. Caller guarantees valid pointer
         // from beamer_au_create_instance that hasn't been destroyed.
         let handle = unsafe { &mut *instance };
+        host_trace::trace(handle.trace_id, HostCall::DeallocateRenderResources);
+        // Log overruns that no open editor collected
+        let _ = handle.watchdog.take_report();
 
         // Use try_write() to avoid blocking if render is in progress.
         // This prevents a TOCTOU race condition where we could deallocate
@@ -1011,10 +1172,11 @@ pub extern "C" fn beamer_au_is_prepared(instance: BeamerAuInstanceHandle) -> boo
 ///   allocated buffers sized for at least `frame_count` frames
 /// - `events` may be null if there are no events to process
 /// - `pull_input_block` may be null for generator plugins that don't need input
-/// - Context block pointers (`_musical_context_block`, `_transport_state_block`,
+/// - Context block pointers (`musical_context_block`, `transport_state_block`,
 ///   `_schedule_midi_block`) may be null if those features aren't used
 /// - This function validates `instance`, `action_flags`, `timestamp` and
-///   `output_data` are non-null; returns `K_AUDIO_UNIT_ERR_INVALID_PARAMETER` if any are null
+///   `output_data` are non-null; returns `K_AUDIO_UNIT_ERR_INVALID_PARAMETER` if any are null.
+///   `output_data` may be null when `frame_count` is 0 (a parameter flush).
 /// - Thread safety: Designed for real-time audio thread; uses non-blocking
 ///   `try_read()` to avoid blocking if resource allocation is in progress
 /// - Uses `catch_unwind` to prevent panics from crossing the FFI boundary
@@ -1029,8 +1191,8 @@ pub extern "C" fn beamer_au_render(
     events: *const AURenderEvent,
     pull_input_block: *const c_void,
     input_data: *const AudioBufferList, // Input buffer list from ObjC (after pulling)
-    _musical_context_block: *const c_void,
-    _transport_state_block: *const c_void,
+    musical_context_block: *const c_void,
+    transport_state_block: *const c_void,
     _schedule_midi_block: *const c_void,
 ) -> i32 {
     // Validate instance handle
@@ -1038,8 +1200,9 @@ pub extern "C" fn beamer_au_render(
         return os_status::K_AUDIO_UNIT_ERR_INVALID_PARAMETER;
     }
 
-    // Validate critical pointers required for rendering
-    if action_flags.is_null() || timestamp.is_null() || output_data.is_null() {
+    // Validate critical pointers required for rendering. A zero-frame
+    // parameter flush may come without output buffers.
+    if action_flags.is_null() || timestamp.is_null() || (output_data.is_null() && frame_count > 0) {
         return os_status::K_AUDIO_UNIT_ERR_INVALID_PARAMETER;
     }
 
@@ -1074,16 +1237,21 @@ pub extern "C" fn beamer_au_render(
             None => return os_status::K_AUDIO_UNIT_ERR_UNINITIALIZED,
         };
 
-        render_block.process(
-            action_flags,
-            timestamp,
-            frame_count,
-            output_bus_number as i32,
-            output_data,
-            events,
-            pull_input_block,
-            input_data,
-        )
+        let _stats = handle.processing_stats.start(frame_count as usize);
+        handle.watchdog.measure(frame_count as usize, || {
+            render_block.process(
+                action_flags,
+                timestamp,
+                frame_count,
+                output_bus_number as i32,
+                output_data,
+                events,
+                pull_input_block,
+                input_data,
+                musical_context_block,
+                transport_state_block,
+            )
+        })
     }));
 
     result.unwrap_or(os_status::K_AUDIO_UNIT_ERR_RENDER)
@@ -1101,6 +1269,7 @@ pub extern "C" fn beamer_au_render(
 #[no_mangle]
 pub extern "C" fn beamer_au_reset(instance: BeamerAuInstanceHandle) {
     with_instance_void!(instance, |handle| {
+        host_trace::trace(handle.trace_id, HostCall::Reset);
         if let Ok(mut plugin) = lock_plugin(handle) {
             plugin.reset();
         }
@@ -1153,51 +1322,396 @@ pub extern "C" fn beamer_au_get_parameter_info(
         // SAFETY: out_info was validated as non-null at function start.
         let out = unsafe { &mut *out_info };
         // SAFETY: handle validated by with_instance! macro.
+        unsafe { with_param_store(handle, |store| fill_parameter_info(store, index as usize, out)) }
+            .unwrap_or(false)
+    })
+}
+
+/// Get the number of parameters exposed to the host.
+///
+/// Unlike [`beamer_au_get_parameter_count`], this excludes internal
+/// parameters that have not been promoted. Use it for the host-facing
+/// parameter list (AUParameterTree, `kAudioUnitProperty_ParameterList`);
+/// the WebView keeps using the full list.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `0`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_get_host_parameter_count(instance: BeamerAuInstanceHandle) -> u32 {
+    with_instance!(instance, 0, |handle| {
+        if !handle.parameter_exposure.has_internal() {
+            return beamer_au_get_parameter_count(instance);
+        }
+        let framework_count =
+            handle.standard_trims.count() + handle.processing_stats.count() + handle.midi_panic.count();
+        (handle.parameter_exposure.count() + framework_count) as u32
+    })
+}
+
+/// Get information about a host-exposed parameter by host index.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - `out_info` must be a valid pointer to a `BeamerAuParameterInfo` struct,
+///   or null (in which case this function returns `false`)
+/// - Thread safety: Safe to call from any thread; lock-free via cached pointer
+#[no_mangle]
+pub extern "C" fn beamer_au_get_host_parameter_info(
+    instance: BeamerAuInstanceHandle,
+    host_index: u32,
+    out_info: *mut BeamerAuParameterInfo,
+) -> bool {
+    if out_info.is_null() {
+        return false;
+    }
+
+    with_instance!(instance, false, |handle| {
+        if !handle.parameter_exposure.has_internal() {
+            return beamer_au_get_parameter_info(instance, host_index, out_info);
+        }
+        // SAFETY: out_info was validated as non-null at function start.
+        let out = unsafe { &mut *out_info };
+        let exposed = handle.parameter_exposure.count();
+        let host_index = host_index as usize;
+        // SAFETY: handle validated by with_instance! macro.
         unsafe {
             with_param_store(handle, |store| {
-                let param_info = match store.info(index as usize) {
-                    Some(info) => info,
-                    None => return false,
-                };
-
-                // Fill output struct.
-                out.id = param_info.id;
-                copy_str_to_char_array(param_info.name, &mut out.name);
-                copy_str_to_char_array(param_info.units, &mut out.units);
-                out.unit_type = param_info.unit as u32;
-                // Compute min/max actual values from normalized range.
-                out.min_value = store.normalized_to_plain(param_info.id, 0.0) as f32;
-                out.max_value = store.normalized_to_plain(param_info.id, 1.0) as f32;
-                // Convert default and current values to actual (plain) values.
-                out.default_value =
-                    store.normalized_to_plain(param_info.id, param_info.default_normalized) as f32;
-                out.current_value = store
-                    .normalized_to_plain(param_info.id, store.get_normalized(param_info.id))
-                    as f32;
-                out.step_count = param_info.step_count;
-                // Convert ParameterFlags to u32 bitfield.
-                out.flags = {
-                    let mut flags = 0u32;
-                    if param_info.flags.can_automate {
-                        flags |= 1 << 0; // BeamerAuParameterFlagAutomatable
-                    }
-                    if param_info.flags.is_hidden {
-                        flags |= 1 << 1; // BeamerAuParameterFlagHidden
-                    }
-                    if param_info.flags.is_readonly {
-                        flags |= 1 << 2; // BeamerAuParameterFlagReadOnly
-                    }
-                    flags
+                // Standard trims, processing statistics and MIDI panic
+                // follow the exposed parameters, at the end of the store
+                let index = if host_index < exposed {
+                    handle.parameter_exposure.store_index(host_index)
+                } else {
+                    let framework_count = handle.standard_trims.count()
+                        + handle.processing_stats.count()
+                        + handle.midi_panic.count();
+                    let plugin_count = store.count() - framework_count;
+                    Some(plugin_count + host_index - exposed)
                 };
-                out.group_id = param_info.group_id;
-
-                true
+                index.is_some_and(|index| fill_parameter_info(store, index, out))
             })
         }
         .unwrap_or(false)
     })
 }
 
+/// Returns true once after the host-facing parameter list changed.
+///
+/// Set when the GUI promotes or demotes a parameter, or when a loaded state
+/// restores different promotions. The wrapper rebuilds its parameter list
+/// and notifies the host.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_take_parameter_list_changed(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| handle.parameter_exposure.take_changed())
+}
+
+/// Returns true once after the plugin marked its state dirty.
+///
+/// Set by `HostServices::mark_state_dirty()` when state outside the
+/// parameters changed. The wrapper notifies the host so the project prompts
+/// to save. Cleared when the host saves the state.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_take_state_dirty(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| handle.host_services.take_state_dirty())
+}
+
+/// Returns true once after a parameter constraint moved a value, the audio
+/// thread applied a crossfaded preset, a displayed processing statistic
+/// changed, or the host set MIDI Panic (which reads Off again).
+///
+/// Set when enforcing the plugin's constraints after automation, a host or
+/// GUI edit, or a preset adjusted a parameter the host did not set, and when
+/// a preset crossfade applied its preset at the end of the fade-out. The
+/// wrapper pushes the current values to the host (AUv3 parameter tree,
+/// AUv2 parameter listeners).
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_take_parameters_adjusted(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| {
+        // Take all flags
+        handle.parameter_constraints.take_adjusted()
+            | handle.preset_crossfade.take_applied()
+            | handle.processing_stats.take_changed()
+            | handle.midi_panic.take_released()
+    })
+}
+
+/// Store the name the host gave this instance (AUv2 `ContextName`, AUv3
+/// `contextName`).
+///
+/// The plugin reads it through `HostServices::instance_name()`.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function does nothing)
+/// - `instance` must not have been destroyed
+/// - `name` must be a valid null-terminated UTF-8 string, or null to clear
+///   the name
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_set_instance_name(instance: BeamerAuInstanceHandle, name: *const c_char) {
+    with_instance_void!(instance, |handle| {
+        let name = if name.is_null() {
+            None
+        } else {
+            // SAFETY: name is non-null and the caller guarantees a valid C string.
+            Some(unsafe { CStr::from_ptr(name) }.to_string_lossy())
+        };
+        handle.host_services.set_instance_name(name.as_deref());
+    })
+}
+
+/// Copy the display name suggested by the plugin into `out_buffer`.
+///
+/// Returns the length written (without the terminator), or `0` if the
+/// plugin suggests no display name.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `0`)
+/// - `instance` must not have been destroyed
+/// - `out_buffer` must be a valid pointer to a writable buffer of at least
+///   `buffer_len` bytes, or null (in which case this function returns `0`)
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_get_display_name(
+    instance: BeamerAuInstanceHandle,
+    out_buffer: *mut c_char,
+    buffer_len: u32,
+) -> u32 {
+    if out_buffer.is_null() || buffer_len == 0 {
+        return 0;
+    }
+    with_instance!(instance, 0, |handle| {
+        let Some(name) = handle.host_services.display_name() else {
+            return 0;
+        };
+        let bytes = name.as_bytes();
+        let copy_len = bytes.len().min(buffer_len as usize - 1);
+        // SAFETY: out_buffer validated non-null above. Caller guarantees buffer_len
+        // bytes are writable. copy_len < buffer_len ensures we don't overflow.
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), out_buffer as *mut u8, copy_len);
+            *out_buffer.add(copy_len) = 0;
+        }
+        copy_len as u32
+    })
+}
+
+/// Returns true once after the plugin suggested a different display name.
+///
+/// The wrapper notifies the host (AUv2 `NickName` property listeners, AUv3
+/// `audioUnitShortName` KVO).
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_take_display_name_changed(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| handle.host_services.take_display_name_changed())
+}
+
+/// Returns true once after the plugin announced a latency change.
+///
+/// Set by `HostServices::latency_changed()`. The wrapper notifies the host
+/// (AUv2 `kAudioUnitProperty_Latency` listeners, AUv3 `latency` KVO) so it
+/// re-reads the latency.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_take_latency_changed(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| handle.host_services.take_latency_changed())
+}
+
+/// Returns true once after the plugin's parameters select a different bus
+/// layout. The wrapper should notify the host (AUv2 element count, AUv3
+/// bus arrays); the new counts are reported once render resources are
+/// deallocated.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread; uses mutex for synchronization
+#[no_mangle]
+pub extern "C" fn beamer_au_take_bus_layout_changed(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| {
+        match lock_plugin(handle) {
+            Ok(mut plugin) => plugin.take_bus_layout_changed(),
+            Err(_) => false,
+        }
+    })
+}
+
+/// Prepare the processor again if the plugin's parameters select another
+/// oversampling factor. Returns true if it did; the wrapper should then
+/// notify the host that the latency changed.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Must be called from the main thread (rewrites the cached parameter store)
+#[no_mangle]
+pub extern "C" fn beamer_au_apply_oversampling(instance: BeamerAuInstanceHandle) -> bool {
+    if instance.is_null() {
+        return false;
+    }
+
+    catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: instance validated non-null above. Caller guarantees valid pointer
+        // from beamer_au_create_instance that hasn't been destroyed.
+        let handle = unsafe { &mut *instance };
+        let changed = match lock_plugin(handle) {
+            Ok(plugin) => plugin.oversampling_changed(),
+            Err(_) => false,
+        };
+        if !changed {
+            return false;
+        }
+
+        // Invalidate the cached pointer before re-preparing moves the
+        // parameters, and refresh it afterwards.
+        handle.param_store = ParamStorePtr::null();
+        let plugin_arc = Arc::clone(&handle.plugin);
+        let mut plugin = match plugin_arc.lock() {
+            Ok(guard) => guard,
+            Err(_) => return false,
+        };
+        let applied = plugin.apply_oversampling();
+        handle.param_store = ParamStorePtr::capture(plugin.as_ref());
+        applied
+    }))
+    .unwrap_or(false)
+}
+
+/// `kAudioUnitProperty_Latency` (AudioUnitProperties.h).
+pub const K_AUDIO_UNIT_PROPERTY_LATENCY: u32 = 12;
+
+/// Receives each property the host should re-read, as an AUv2
+/// `AudioUnitPropertyID` (AUv3 wrappers map it to the KVO key).
+pub type BeamerAuPropertyListener = unsafe extern "C" fn(context: *mut c_void, property: u32);
+
+/// Apply a pending oversampling change and report the properties that
+/// changed since the last call to `listener`: `kAudioUnitProperty_Latency`
+/// after `HostServices::latency_changed()` or a new oversampling factor.
+///
+/// The wrappers call it from a main-thread timer that runs while render
+/// resources are allocated, so a change announced from the audio thread
+/// reaches the host with the editor closed.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function does nothing)
+/// - `instance` must not have been destroyed
+/// - Must be called from the main thread (see `beamer_au_apply_oversampling`)
+#[no_mangle]
+pub extern "C" fn beamer_au_dispatch_property_changes(
+    instance: BeamerAuInstanceHandle,
+    listener: BeamerAuPropertyListener,
+    context: *mut c_void,
+) {
+    let oversampling_applied = beamer_au_apply_oversampling(instance);
+    with_instance_void!(instance, |handle| {
+        dispatch_property_changes(&handle.host_services, oversampling_applied, |property| {
+            // SAFETY: The wrapper passes a listener valid for this call.
+            unsafe { listener(context, property) }
+        })
+    })
+}
+
+/// Report the properties the host should re-read to `notify`.
+fn dispatch_property_changes(host_services: &HostServices, oversampling_applied: bool, mut notify: impl FnMut(u32)) {
+    // Take the flag even if oversampling already changed the latency
+    let latency_changed = host_services.take_latency_changed();
+    if latency_changed || oversampling_applied {
+        notify(K_AUDIO_UNIT_PROPERTY_LATENCY);
+    }
+}
+
+/// Fill `out` with the info of the parameter at store `index`.
+fn fill_parameter_info(
+    store: &dyn ParameterStore,
+    index: usize,
+    out: &mut BeamerAuParameterInfo,
+) -> bool {
+    let param_info = match store.info(index) {
+        Some(info) => info,
+        None => return false,
+    };
+
+    // Fill output struct.
+    out.id = param_info.id;
+    copy_str_to_char_array(param_info.name, &mut out.name);
+    copy_str_to_char_array(param_info.units, &mut out.units);
+    out.unit_type = param_info.unit as u32;
+    // Compute min/max actual values from normalized range.
+    out.min_value = store.normalized_to_plain(param_info.id, 0.0) as f32;
+    out.max_value = store.normalized_to_plain(param_info.id, 1.0) as f32;
+    // Convert default and current values to actual (plain) values.
+    out.default_value =
+        store.normalized_to_plain(param_info.id, param_info.default_normalized) as f32;
+    out.current_value =
+        store.normalized_to_plain(param_info.id, store.get_normalized(param_info.id)) as f32;
+    out.step_count = param_info.step_count;
+    // Convert ParameterFlags to u32 bitfield.
+    out.flags = {
+        let mut flags = 0u32;
+        if param_info.flags.can_automate {
+            flags |= 1 << 0; // BeamerAuParameterFlagAutomatable
+        }
+        if param_info.flags.is_hidden {
+            flags |= 1 << 1; // BeamerAuParameterFlagHidden
+        }
+        if param_info.flags.is_readonly {
+            flags |= 1 << 2; // BeamerAuParameterFlagReadOnly
+        }
+        flags
+    };
+    out.group_id = param_info.group_id;
+
+    true
+}
+
 /// Get a parameter's current normalized value.
 ///
 /// # Safety
@@ -1235,10 +1749,12 @@ pub extern "C" fn beamer_au_set_parameter_value(
     value: f32,
 ) {
     with_instance_void!(instance, |handle| {
+        host_trace::trace(handle.trace_id, HostCall::SetParameter { id: param_id, value: value as f64 });
         // SAFETY: handle validated by with_instance_void! macro.
         unsafe {
             with_param_store(handle, |store| {
                 store.set_normalized(param_id, value as f64);
+                handle.parameter_constraints.enforce(store, param_id, |_, _| {});
             });
         }
     })
@@ -1329,8 +1845,13 @@ pub extern "C" fn beamer_au_set_parameter_value_au(
                         if info.id == param_id {
                             if info.unit == ParameterUnit::Indexed && info.step_count > 0 {
                                 is_indexed = true;
-                                let normalized = value / info.step_count as f32;
-                                store.set_normalized(param_id, normalized as f64);
+                                let normalized = (value / info.step_count as f32) as f64;
+                                host_trace::trace(handle.trace_id, HostCall::SetParameter { id: param_id, value: normalized });
+                                if store.get_normalized(param_id) != normalized {
+                                    handle.parameter_log.record(param_id, normalized, ParameterChangeSource::Host);
+                                }
+                                store.set_normalized(param_id, normalized);
+                                handle.automation_preview.host_value(param_id, normalized);
                             }
                             break;
                         }
@@ -1340,8 +1861,15 @@ pub extern "C" fn beamer_au_set_parameter_value_au(
                 // For all other parameters, convert actual (plain) value to normalized.
                 if !is_indexed {
                     let normalized = store.plain_to_normalized(param_id, value as f64);
+                    host_trace::trace(handle.trace_id, HostCall::SetParameter { id: param_id, value: normalized });
+                    // Hosts echo values they already delivered; only log actual changes.
+                    if store.get_normalized(param_id) != normalized {
+                        handle.parameter_log.record(param_id, normalized, ParameterChangeSource::Host);
+                    }
                     store.set_normalized(param_id, normalized);
+                    handle.automation_preview.host_value(param_id, normalized);
                 }
+                handle.parameter_constraints.enforce(store, param_id, |_, _| {});
             });
         }
     })
@@ -1708,6 +2236,10 @@ pub extern "C" fn beamer_au_get_state(
         };
 
         let state = plugin.save_state();
+        host_trace::trace(handle.trace_id, HostCall::GetState { bytes: state.len() });
+        handle.autosave.record_host_state(&state);
+        // The saved state already holds any change marked dirty.
+        handle.host_services.take_state_dirty();
         let copy_len = state.len().min(size as usize);
 
         if copy_len > 0 {
@@ -1749,11 +2281,15 @@ pub extern "C" fn beamer_au_set_state(
             &[]
         };
 
+        host_trace::trace(handle.trace_id, HostCall::SetState { bytes: state_slice.len() });
         let mut plugin = match lock_plugin(handle) {
             Ok(guard) => guard,
             Err(status) => return status,
         };
 
+        // Offer edits made on top of this state before a crash.
+        handle.autosave.claim(state_slice);
+
         match plugin.load_state(state_slice) {
             Ok(()) => os_status::NO_ERR,
             Err(e) => {
@@ -1993,21 +2529,55 @@ fn get_bus_channel_count_impl(
     })
 }
 
-/// Get the number of input buses the plugin supports.
-///
-/// # Safety
-///
-/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`, or null
-/// - Thread safety: Safe to call from any thread
-#[no_mangle]
-pub extern "C" fn beamer_au_get_input_bus_count(instance: BeamerAuInstanceHandle) -> u32 {
-    get_bus_count_impl(instance, BusDirection::Input)
-}
+fn get_bus_layout_tag_impl(
+    instance: BeamerAuInstanceHandle,
+    bus_index: u32,
+    direction: BusDirection,
+) -> u32 {
+    with_instance!(instance, 0, |handle| {
+        let plugin = match lock_plugin(handle) {
+            Ok(guard) => guard,
+            Err(_) => return 0,
+        };
 
-/// Get the number of output buses the plugin supports.
-///
-/// # Safety
-///
+        let declared = match direction {
+            BusDirection::Input => plugin.declared_input_bus_info(bus_index as usize),
+            BusDirection::Output => plugin.declared_output_bus_info(bus_index as usize),
+        };
+        let Some(mut info) = declared else {
+            return 0;
+        };
+
+        // If resources are allocated, describe the host-negotiated width.
+        if let Some(cfg) = handle.bus_config.as_ref() {
+            let negotiated = match direction {
+                BusDirection::Input => cfg.input_bus_info(bus_index as usize),
+                BusDirection::Output => cfg.output_bus_info(bus_index as usize),
+            };
+            if let Some(negotiated) = negotiated {
+                info.channel_count = negotiated.channel_count as u32;
+            }
+        }
+
+        speaker_layout_to_tag(info.layout())
+    })
+}
+
+/// Get the number of input buses the plugin supports.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`, or null
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_get_input_bus_count(instance: BeamerAuInstanceHandle) -> u32 {
+    get_bus_count_impl(instance, BusDirection::Input)
+}
+
+/// Get the number of output buses the plugin supports.
+///
+/// # Safety
+///
 /// - `instance` must be a valid pointer returned by `beamer_au_create_instance`, or null
 /// - Thread safety: Safe to call from any thread
 #[no_mangle]
@@ -2043,6 +2613,34 @@ pub extern "C" fn beamer_au_get_output_bus_channel_count(
     get_bus_channel_count_impl(instance, bus_index, BusDirection::Output)
 }
 
+/// Get the `AudioChannelLayoutTag` for an input bus.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`, or null
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_get_input_bus_layout_tag(
+    instance: BeamerAuInstanceHandle,
+    bus_index: u32,
+) -> u32 {
+    get_bus_layout_tag_impl(instance, bus_index, BusDirection::Input)
+}
+
+/// Get the `AudioChannelLayoutTag` for an output bus.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`, or null
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_get_output_bus_layout_tag(
+    instance: BeamerAuInstanceHandle,
+    bus_index: u32,
+) -> u32 {
+    get_bus_layout_tag_impl(instance, bus_index, BusDirection::Output)
+}
+
 /// Check if a proposed channel configuration is valid.
 ///
 /// Validates a channel configuration against the plugin's declared capabilities.
@@ -2098,6 +2696,11 @@ pub extern "C" fn beamer_au_is_channel_config_valid(
                                 .declared_output_bus_info(0)
                                 .map(|info| info.channel_count)
                                 .unwrap_or(2);
+                            if (main_input_channels, main_output_channels) != (input, output) {
+                                // Other channel counts the plugin agrees to
+                                // (Descriptor::accepts_bus_layout)
+                                return plugin.accepts_main_channels(main_input_channels, main_output_channels);
+                            }
                             (input, output)
                         }
                         Err(_) => (2, 2), // Lock failed, fall back to stereo
@@ -2122,10 +2725,18 @@ pub extern "C" fn beamer_au_is_channel_config_valid(
                     // Caller guarantees valid pointer from beamer_au_create_instance.
                     let handle = unsafe { &*instance };
                     match lock_plugin(handle) {
-                        Ok(plugin) => plugin
-                            .declared_output_bus_info(0)
-                            .map(|info| info.channel_count)
-                            .unwrap_or(2),
+                        Ok(plugin) => {
+                            let output = plugin
+                                .declared_output_bus_info(0)
+                                .map(|info| info.channel_count)
+                                .unwrap_or(2);
+                            if main_output_channels != output {
+                                // Other channel counts the plugin agrees to
+                                // (Descriptor::accepts_bus_layout)
+                                return plugin.accepts_main_channels(0, main_output_channels);
+                            }
+                            output
+                        }
                         Err(_) => 2, // Lock failed, fall back to stereo
                     }
                 };
@@ -2146,8 +2757,8 @@ pub extern "C" fn beamer_au_is_channel_config_valid(
 ///
 /// # Capability Semantics
 ///
-/// - **Effects (`aufx`)**: Return `[-1, -1]` meaning "any matching configuration"
-///   where input and output channel counts must be equal.
+/// - **Effects (`aufx`)**: Return the declared main `[in, out]`, followed by
+///   the mono/stereo variants accepted by `Descriptor::accepts_bus_layout`.
 /// - **Instruments (`aumu`)**: Return `[0, N]` where N is the declared output
 ///   channel count. Instruments have no audio input, only MIDI.
 /// - **MIDI Processors (`aumi`)**: Similar to effects, `[-1, -1]`.
@@ -2190,34 +2801,41 @@ pub extern "C" fn beamer_au_get_channel_capabilities(
                 // the main bus capability. Sidechain channels are handled separately.
                 //
                 // If no instance available, fall back to stereo.
-                let (input_ch, output_ch) = if instance.is_null() {
-                    (2, 2) // Default to stereo
-                } else {
-                    // SAFETY: instance validated non-null in the else branch.
+                let mut supported = vec![(2, 2)];
+                if !instance.is_null() {
+                    // SAFETY: instance validated non-null above.
                     // Caller guarantees valid pointer from beamer_au_create_instance.
                     let handle = unsafe { &*instance };
-                    match lock_plugin(handle) {
-                        Ok(plugin) => {
-                            let input = plugin
-                                .declared_input_bus_info(0)
-                                .map(|info| info.channel_count as i32)
-                                .unwrap_or(2);
-                            let output = plugin
-                                .declared_output_bus_info(0)
-                                .map(|info| info.channel_count as i32)
-                                .unwrap_or(2);
-                            (input, output)
+                    if let Ok(plugin) = lock_plugin(handle) {
+                        let input = plugin
+                            .declared_input_bus_info(0)
+                            .map(|info| info.channel_count)
+                            .unwrap_or(2);
+                        let output = plugin
+                            .declared_output_bus_info(0)
+                            .map(|info| info.channel_count)
+                            .unwrap_or(2);
+                        supported = vec![(input, output)];
+                        // Mono and stereo variants the plugin agrees to
+                        // (Descriptor::accepts_bus_layout)
+                        for candidate in [(1, 1), (1, 2), (2, 2), (2, 1)] {
+                            if candidate != (input, output)
+                                && plugin.accepts_main_channels(candidate.0, candidate.1)
+                            {
+                                supported.push(candidate);
+                            }
                         }
-                        Err(_) => (2, 2), // Lock failed, fall back to stereo
                     }
-                };
+                }
 
-                // Report explicit channel configuration for the main bus
-                capabilities.count = 1;
-                capabilities.capabilities[0] = BeamerAuChannelCapability {
-                    input_channels: input_ch,
-                    output_channels: output_ch,
-                };
+                // Report explicit channel configurations for the main bus
+                capabilities.count = supported.len() as u32;
+                for (capability, (input_ch, output_ch)) in capabilities.capabilities.iter_mut().zip(supported) {
+                    *capability = BeamerAuChannelCapability {
+                        input_channels: input_ch as i32,
+                        output_channels: output_ch as i32,
+                    };
+                }
             }
             beamer_core::config::Category::Instrument | beamer_core::config::Category::Generator => {
                 // Instruments and generators: query the declared output channel count from the plugin
@@ -2572,7 +3190,7 @@ pub extern "C" fn beamer_au_get_preset_info(
     }
 
     with_instance!(instance, false, |handle| {
-        let plugin = match lock_plugin(handle) {
+        let mut plugin = match lock_plugin(handle) {
             Ok(guard) => guard,
             Err(_) => return false,
         };
@@ -2589,6 +3207,28 @@ pub extern "C" fn beamer_au_get_preset_info(
     })
 }
 
+/// Returns true once after presets were added, removed or renamed at runtime.
+///
+/// Set when `FactoryPresets::count()` or `names_version()` changes (e.g. user
+/// presets changed on disk). The wrapper rebuilds its factory preset list
+/// and notifies the host.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+/// - `instance` must not have been destroyed
+/// - Thread safety: Safe to call from any thread; uses mutex for synchronization
+#[no_mangle]
+pub extern "C" fn beamer_au_take_preset_list_changed(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| {
+        match lock_plugin(handle) {
+            Ok(mut plugin) => plugin.take_preset_list_changed(),
+            Err(_) => false,
+        }
+    })
+}
+
 /// Apply a factory preset by index.
 ///
 /// This sets all parameters defined in the preset to their preset values.
@@ -2614,6 +3254,28 @@ pub extern "C" fn beamer_au_apply_preset(
     })
 }
 
+/// Get the configured preset crossfade duration in milliseconds.
+///
+/// When non-zero, `beamer_au_apply_preset` queues the preset for the audio
+/// thread while render resources are allocated. Once the audio thread
+/// applies it, `beamer_au_take_parameters_adjusted` reports it and the
+/// wrapper pushes the new values to the host.
+///
+/// # Safety
+///
+/// - `_instance` parameter is currently unused but accepted for API consistency
+/// - Thread safety: Safe to call from any thread
+#[no_mangle]
+pub extern "C" fn beamer_au_get_preset_crossfade_ms(_instance: BeamerAuInstanceHandle) -> f32 {
+    let result = catch_unwind(|| {
+        factory::plugin_config()
+            .map(|config| config.preset_crossfade_ms)
+            .unwrap_or(0.0)
+    });
+
+    result.unwrap_or(0.0)
+}
+
 // =============================================================================
 // WebView IPC Parameter Sync
 // =============================================================================
@@ -2814,6 +3476,132 @@ pub extern "C" fn beamer_au_param_info_json(
     }
 }
 
+/// Get the frame meter values as a JSON object for the WebView frame clock.
+///
+/// Reading resets the peak meters. Returns a heap-allocated null-terminated
+/// C string that the caller must free with `beamer_au_free_string`. Returns
+/// null on failure.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns null)
+#[no_mangle]
+pub extern "C" fn beamer_au_frame_meters_json(
+    instance: BeamerAuInstanceHandle,
+) -> *mut c_char {
+    if instance.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: instance validated non-null above.
+        let handle = unsafe { &*instance };
+        CString::new(handle.frame_meters.frame_json()).ok()
+    }));
+
+    match result {
+        Ok(Some(cstr)) => cstr.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Take the next notification for the GUI as a script for the WebView.
+///
+/// A pending watchdog overrun report (`"beamer:overrun"` event) comes
+/// first. Returns null when nothing is due (rate limiting applies).
+/// Otherwise returns a heap-allocated null-terminated C string that the
+/// caller must free with `beamer_au_free_string`.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns null)
+#[no_mangle]
+pub extern "C" fn beamer_au_take_notification_script(
+    instance: BeamerAuInstanceHandle,
+) -> *mut c_char {
+    if instance.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: instance validated non-null above.
+        let handle = unsafe { &*instance };
+        handle
+            .watchdog
+            .take_script()
+            .or_else(|| handle.notifications.take_script())
+            .and_then(|script| CString::new(script).ok())
+    }));
+
+    match result {
+        Ok(Some(cstr)) => cstr.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Take the frames of the plugin's GUI data channels as a script for the
+/// WebView (an `"audio-data"` event).
+///
+/// Returns null when no delivery is due (the channels' rate applies) or no
+/// channel has frames. Otherwise returns a heap-allocated null-terminated C
+/// string that the caller must free with `beamer_au_free_string`.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns null)
+#[no_mangle]
+pub extern "C" fn beamer_au_take_gui_data_script(
+    instance: BeamerAuInstanceHandle,
+) -> *mut c_char {
+    if instance.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: instance validated non-null above.
+        let handle = unsafe { &*instance };
+        handle.gui_data.take_script().and_then(|script| CString::new(script).ok())
+    }));
+
+    match result {
+        Ok(Some(cstr)) => cstr.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Get the host transport as a JSON object for the WebView frame clock.
+///
+/// The position is extrapolated from the last render block to now. Returns a
+/// heap-allocated null-terminated C string that the caller must free with
+/// `beamer_au_free_string`. Returns null on failure.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns null)
+#[no_mangle]
+pub extern "C" fn beamer_au_frame_playhead_json(
+    instance: BeamerAuInstanceHandle,
+) -> *mut c_char {
+    if instance.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: instance validated non-null above.
+        let handle = unsafe { &*instance };
+        CString::new(handle.frame_meters.playhead().position().to_json()).ok()
+    }));
+
+    match result {
+        Ok(Some(cstr)) => cstr.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
+
 /// Free a string returned by a beamer_au function (e.g., `beamer_au_param_info_json`,
 /// `beamer_au_on_invoke`).
 ///
@@ -2858,8 +3646,213 @@ pub extern "C" fn beamer_au_param_set_from_ui(
         unsafe {
             with_param_store(handle, |store| {
                 store.set_normalized(param_id, value);
+                handle.parameter_constraints.enforce(store, param_id, |_, _| {});
             });
         }
+        handle.automation_preview.user_value(param_id, value);
+        handle.parameter_log.record(param_id, value, ParameterChangeSource::Gui);
+
+        // Snapshot for autosave, unless the render path holds the plugin.
+        if handle.autosave.is_due() {
+            if let Ok(plugin) = handle.plugin.try_lock() {
+                handle.autosave.snapshot(plugin.save_state());
+            }
+        }
+    }));
+}
+
+/// Record the start or end of a WebView UI gesture on a parameter.
+///
+/// While a gesture is active, host values for the parameter are not treated
+/// as automation. The ObjC wrapper still sends the gesture events to the host.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function does nothing)
+#[no_mangle]
+pub extern "C" fn beamer_au_param_gesture_from_ui(
+    instance: BeamerAuInstanceHandle,
+    param_id: u32,
+    begin: bool,
+) {
+    with_instance_void!(instance, |handle| {
+        if begin {
+            handle.automation_preview.begin_gesture(param_id);
+        } else {
+            handle.automation_preview.end_gesture(param_id);
+        }
+    })
+}
+
+// =============================================================================
+// Native Editor
+// =============================================================================
+
+/// Edit kinds passed to [`BeamerAuEditCallback`].
+pub const BEAMER_AU_EDIT_BEGIN: u32 = 0;
+pub const BEAMER_AU_EDIT_VALUE: u32 = 1;
+pub const BEAMER_AU_EDIT_END: u32 = 2;
+
+/// Hands a native editor edit to the ObjC wrapper, which applies it and
+/// notifies the host like a WebView `param:begin`/`param:set`/`param:end`
+/// message.
+///
+/// `kind` is one of the `BEAMER_AU_EDIT_*` constants; `value` is the
+/// normalized value for `BEAMER_AU_EDIT_VALUE` and 0 otherwise.
+pub type BeamerAuEditCallback =
+    unsafe extern "C" fn(context: *mut c_void, kind: u32, param_id: u32, value: f64);
+
+/// An open native editor, owned by the ObjC view.
+pub struct BeamerAuNativeEditor {
+    editor: Box<dyn NativeEditor>,
+}
+
+/// [`EditorHost`] for native editors: reads parameters through the cached
+/// store and passes edits to the ObjC wrapper.
+struct AuEditorHost {
+    instance: BeamerAuInstanceHandle,
+    callback: BeamerAuEditCallback,
+    context: *mut c_void,
+}
+
+impl EditorHost for AuEditorHost {
+    fn with_parameters(&self, f: &mut dyn FnMut(&dyn ParameterStore)) {
+        // SAFETY: The instance outlives its editors (the view closes the
+        // editor before the audio unit is disposed).
+        let handle = unsafe { &*self.instance };
+        // SAFETY: handle points to a live BeamerInstanceHandle.
+        unsafe { with_param_store(handle, |store| f(store)) };
+    }
+
+    fn begin_edit(&self, id: u32) {
+        // SAFETY: callback and context are valid while the editor is open.
+        unsafe { (self.callback)(self.context, BEAMER_AU_EDIT_BEGIN, id, 0.0) };
+    }
+
+    fn set_parameter(&self, id: u32, normalized: f64) {
+        // SAFETY: See begin_edit().
+        unsafe { (self.callback)(self.context, BEAMER_AU_EDIT_VALUE, id, normalized) };
+    }
+
+    fn end_edit(&self, id: u32) {
+        // SAFETY: See begin_edit().
+        unsafe { (self.callback)(self.context, BEAMER_AU_EDIT_END, id, 0.0) };
+    }
+}
+
+/// Check if the plugin's editor is native (`GuiBackend::Native`) rather
+/// than a WebView.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns `false`)
+#[no_mangle]
+pub extern "C" fn beamer_au_has_native_gui(instance: BeamerAuInstanceHandle) -> bool {
+    with_instance!(instance, false, |handle| {
+        beamer_au_has_gui(instance) && matches!(handle.editor, GuiBackend::Native(_))
+    })
+}
+
+/// Create a native editor and open it in `parent`.
+///
+/// Edits are passed to `callback` for the wrapper to apply. Returns null
+/// if the plugin has no native editor or it could not be opened. Close the
+/// editor with `beamer_au_native_editor_close`.
+///
+/// # Safety
+///
+/// - `instance` must be a valid pointer returned by `beamer_au_create_instance`,
+///   or null (in which case this function returns null)
+/// - `parent` must be an NSView that outlives the editor
+/// - `callback` and `context` must stay valid until the editor is closed
+/// - Must be called from the main thread
+#[no_mangle]
+pub unsafe extern "C" fn beamer_au_native_editor_open(
+    instance: BeamerAuInstanceHandle,
+    parent: *mut c_void,
+    callback: BeamerAuEditCallback,
+    context: *mut c_void,
+) -> *mut BeamerAuNativeEditor {
+    if parent.is_null() {
+        return ptr::null_mut();
+    }
+    with_instance!(instance, ptr::null_mut(), |handle| {
+        let Some(mut editor) = handle.editor.create_native() else {
+            return ptr::null_mut();
+        };
+        let host = Box::new(AuEditorHost { instance, callback, context });
+        // SAFETY: parent outlives the editor per the caller's contract.
+        if !unsafe { editor.open(ParentWindow::AppKit(parent), host) } {
+            return ptr::null_mut();
+        }
+        editor.gui_opened();
+        Box::into_raw(Box::new(BeamerAuNativeEditor { editor }))
+    })
+}
+
+/// Get the size of an open native editor in points.
+///
+/// # Safety
+///
+/// - `editor` must be a pointer returned by `beamer_au_native_editor_open`,
+///   or null (in which case this function does nothing)
+/// - `width` and `height` must be valid pointers
+#[no_mangle]
+pub unsafe extern "C" fn beamer_au_native_editor_get_size(
+    editor: *mut BeamerAuNativeEditor,
+    width: *mut u32,
+    height: *mut u32,
+) {
+    if editor.is_null() || width.is_null() || height.is_null() {
+        return;
+    }
+    // SAFETY: editor is a live pointer from beamer_au_native_editor_open.
+    let size = unsafe { &*editor }.editor.gui_size();
+    // SAFETY: width and height are valid per the caller's contract.
+    unsafe {
+        *width = size.width;
+        *height = size.height;
+    }
+}
+
+/// Resize an open native editor (in points), after the host resized its
+/// view.
+///
+/// # Safety
+///
+/// - `editor` must be a pointer returned by `beamer_au_native_editor_open`,
+///   or null (in which case this function does nothing)
+/// - Must be called from the main thread
+#[no_mangle]
+pub unsafe extern "C" fn beamer_au_native_editor_resize(editor: *mut BeamerAuNativeEditor, width: u32, height: u32) {
+    if editor.is_null() {
+        return;
+    }
+    // SAFETY: editor is a live pointer from beamer_au_native_editor_open.
+    let editor = unsafe { &mut *editor };
+    let _ = catch_unwind(AssertUnwindSafe(|| editor.editor.gui_resized(Size::new(width, height))));
+}
+
+/// Close and free a native editor.
+///
+/// # Safety
+///
+/// - `editor` must be a pointer returned by `beamer_au_native_editor_open`,
+///   or null (in which case this function does nothing)
+/// - `editor` is invalid after this call
+/// - Must be called from the main thread
+#[no_mangle]
+pub unsafe extern "C" fn beamer_au_native_editor_close(editor: *mut BeamerAuNativeEditor) {
+    if editor.is_null() {
+        return;
+    }
+    // SAFETY: editor was created by Box::into_raw in beamer_au_native_editor_open.
+    let mut editor = unsafe { Box::from_raw(editor) };
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        editor.editor.gui_closed();
+        editor.editor.close();
     }));
 }
 
@@ -2904,6 +3897,218 @@ pub unsafe extern "C" fn beamer_au_on_invoke(
         let handle = unsafe { &*instance };
 
         let json = match &handle.webview_handler {
+            // Built-in invokes not handled natively by the ObjC layer.
+            _ if method_str == beamer_core::logging::LOG_TAIL_INVOKE => {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let tail = beamer_core::logging::log_tail_invoke(&args);
+                format!(r#"{{"ok":{}}}"#, serde_json::to_string(&tail).unwrap_or_default())
+            }
+            _ if method_str == beamer_core::crash_guard::SAFE_MODE_INVOKE => {
+                let safe_mode = beamer_core::crash_guard::safe_mode_invoke(&handle.crash_guard);
+                format!(r#"{{"ok":{}}}"#, safe_mode)
+            }
+            _ if method_str == beamer_core::parameter_groups::PARAMETER_GROUPS_INVOKE => {
+                let groups = lock_plugin(handle)
+                    .ok()
+                    .and_then(|plugin| {
+                        plugin
+                            .parameter_groups()
+                            .ok()
+                            .map(beamer_core::parameter_groups::groups_json)
+                    })
+                    .unwrap_or_else(|| serde_json::Value::Array(Vec::new()));
+                format!(r#"{{"ok":{}}}"#, groups)
+            }
+            _ if method_str == beamer_core::state_hash::STATE_HASH_INVOKE => {
+                let data = lock_plugin(handle)
+                    .map(|plugin| plugin.save_state())
+                    .unwrap_or_default();
+                let hash = beamer_core::state_hash::state_hash_invoke(&data);
+                format!(r#"{{"ok":{}}}"#, hash)
+            }
+            _ if method_str == beamer_core::automation_preview::AUTOMATION_STATE_INVOKE => {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let state = beamer_core::automation_preview::automation_state_invoke(
+                    &handle.automation_preview,
+                    &args,
+                );
+                format!(r#"{{"ok":{}}}"#, state)
+            }
+            // Promote/demote: the ObjC layer polls the changed flag afterwards.
+            _ if matches!(
+                method_str,
+                beamer_core::parameter_exposure::PROMOTE_PARAMETER_INVOKE
+                    | beamer_core::parameter_exposure::DEMOTE_PARAMETER_INVOKE
+                    | beamer_core::parameter_exposure::PROMOTED_PARAMETERS_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let value = beamer_core::parameter_exposure::exposure_invoke(
+                    &handle.parameter_exposure,
+                    method_str,
+                    &args,
+                )
+                .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::controller_pages::CONTROLLER_PAGES_INVOKE
+                    | beamer_core::controller_pages::SELECT_CONTROLLER_PAGE_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let value = beamer_core::controller_pages::controller_pages_invoke(
+                    &handle.controller_pages,
+                    method_str,
+                    &args,
+                )
+                .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::host_services::INSTANCE_NAME_INVOKE
+                    | beamer_core::host_services::SET_DISPLAY_NAME_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let value = beamer_core::host_services::host_services_invoke(
+                    &handle.host_services,
+                    method_str,
+                    &args,
+                )
+                .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if method_str == beamer_core::note_ranges::NOTE_RANGES_INVOKE => {
+                let value = handle.note_ranges.to_json();
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::response_curves::RESPONSE_CURVES_INVOKE
+                    | beamer_core::response_curves::SET_RESPONSE_CURVE_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let value = beamer_core::response_curves::response_curves_invoke(
+                    &handle.response_curves,
+                    method_str,
+                    &args,
+                )
+                .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::autosave::AUTOSAVE_INVOKE | beamer_core::autosave::RESOLVE_AUTOSAVE_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let value = beamer_core::autosave::autosave_invoke(&handle.autosave, method_str, &args)
+                    .unwrap_or(serde_json::Value::Null);
+                // The user chose to restore the autosaved state.
+                if let Some(data) = handle.autosave.take_accepted() {
+                    match lock_plugin(handle).map(|mut plugin| plugin.load_state(&data)) {
+                        Ok(Err(e)) => log::error!("Failed to restore autosaved state: {}", e),
+                        Err(_) => log::error!("Failed to restore autosaved state: plugin busy"),
+                        Ok(Ok(())) => {}
+                    }
+                }
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::preset_file::EXPORT_PRESET_FILE_INVOKE
+                    | beamer_core::preset_file::IMPORT_PRESET_FILE_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let result = match factory::plugin_config() {
+                    Some(config) => beamer_core::preset_file::preset_file_invoke(
+                        config,
+                        method_str,
+                        &args,
+                        || lock_plugin(handle).map(|plugin| plugin.save_state()).unwrap_or_default(),
+                        |data| match lock_plugin(handle) {
+                            Ok(mut plugin) => plugin.load_state(data).map_err(|e| e.to_string()),
+                            Err(_) => Err("plugin busy".to_string()),
+                        },
+                    )
+                    .unwrap_or(Ok(serde_json::Value::Null)),
+                    None => Err("plugin config not registered".to_string()),
+                };
+                match result {
+                    Ok(value) => format!(r#"{{"ok":{}}}"#, value),
+                    Err(msg) => {
+                        let escaped = serde_json::to_string(&msg).unwrap_or_default();
+                        format!(r#"{{"err":{}}}"#, escaped)
+                    }
+                }
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::parameter_log::PARAMETER_LOG_INVOKE
+                    | beamer_core::parameter_log::CLEAR_PARAMETER_LOG_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                // SAFETY: handle points to a live BeamerInstanceHandle.
+                let value = unsafe {
+                    with_param_store(handle, |store| {
+                        beamer_core::parameter_log::parameter_log_invoke(
+                            &handle.parameter_log,
+                            store,
+                            method_str,
+                            &args,
+                        )
+                    })
+                }
+                .flatten()
+                .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::midi_monitor::MIDI_MONITOR_INVOKE
+                    | beamer_core::midi_monitor::CLEAR_MIDI_MONITOR_INVOKE
+            ) =>
+            {
+                let args: Vec<serde_json::Value> =
+                    serde_json::from_str(args_str).unwrap_or_default();
+                let value = beamer_core::midi_monitor::midi_monitor_invoke(
+                    &handle.midi_monitor,
+                    method_str,
+                    &args,
+                )
+                .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if method_str == beamer_core::midi_panic::MIDI_PANIC_INVOKE => {
+                let value = beamer_core::midi_panic::midi_panic_invoke(&handle.midi_panic, method_str)
+                    .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
+            _ if matches!(
+                method_str,
+                beamer_core::activation::ACTIVATION_INVOKE
+                    | beamer_core::activation::RETRY_ACTIVATION_INVOKE
+            ) =>
+            {
+                let value = beamer_core::activation::activation_invoke(&handle.activation, method_str)
+                    .unwrap_or(serde_json::Value::Null);
+                format!(r#"{{"ok":{}}}"#, value)
+            }
             Some(h) => {
                 let args: Vec<serde_json::Value> =
                     serde_json::from_str(args_str).unwrap_or_default();
@@ -3079,6 +4284,34 @@ mod tests {
         beamer_au_reset(ptr::null_mut());
     }
 
+    #[test]
+    fn test_latency_change_from_processing_is_dispatched() {
+        let host_services = HostServices::new();
+        let mut properties = Vec::new();
+        dispatch_property_changes(&host_services, false, |property| properties.push(property));
+        assert!(properties.is_empty());
+
+        // Announced on the audio thread, dispatched once on the main thread
+        let plugin = host_services.clone();
+        std::thread::spawn(move || plugin.latency_changed()).join().unwrap();
+        dispatch_property_changes(&host_services, false, |property| properties.push(property));
+        dispatch_property_changes(&host_services, false, |property| properties.push(property));
+        assert_eq!(properties, [K_AUDIO_UNIT_PROPERTY_LATENCY]);
+
+        // Both causes at once are one notification
+        properties.clear();
+        host_services.latency_changed();
+        dispatch_property_changes(&host_services, true, |property| properties.push(property));
+        assert_eq!(properties, [K_AUDIO_UNIT_PROPERTY_LATENCY]);
+        assert!(!host_services.take_latency_changed());
+
+        beamer_au_dispatch_property_changes(ptr::null_mut(), unexpected_property, ptr::null_mut());
+    }
+
+    unsafe extern "C" fn unexpected_property(_context: *mut c_void, property: u32) {
+        panic!("property {property} reported without an instance");
+    }
+
     /// Helper to create a valid bus config for testing
     fn create_valid_bus_config() -> BeamerAuBusConfig {
         let mut config = BeamerAuBusConfig {
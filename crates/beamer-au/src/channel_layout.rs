@@ -0,0 +1,89 @@
+//! Conversion between speaker layouts and AU channel layout tags.
+//!
+//! `AudioChannelLayoutTag` values pack a layout id in the upper 16 bits and
+//! the channel count in the lower 16. The named layouts use tags whose
+//! channel order matches [`SpeakerLayout`]:
+//!
+//! | Layout | Tag | Order |
+//! |--------|-----|-------|
+//! | Mono | `kAudioChannelLayoutTag_Mono` | M |
+//! | Stereo | `kAudioChannelLayoutTag_Stereo` | L R |
+//! | 5.1 | `kAudioChannelLayoutTag_MPEG_5_1_A` | L R C LFE Ls Rs |
+//! | 7.1 | `kAudioChannelLayoutTag_MPEG_7_1_C` | L R C LFE Ls Rs Rls Rrs |
+//! | 7.1.4 | `kAudioChannelLayoutTag_Atmos_7_1_4` | 7.1 + Vhl Vhr Ltr Rtr |
+//! | Ambisonics | `kAudioChannelLayoutTag_HOA_ACN_SN3D` | ACN |
+//!
+//! Discrete layouts use `kAudioChannelLayoutTag_DiscreteInOrder`.
+
+use beamer_core::{AmbisonicOrder, SpeakerLayout};
+
+/// `kAudioChannelLayoutTag_Mono`
+pub const LAYOUT_TAG_MONO: u32 = (100 << 16) | 1;
+/// `kAudioChannelLayoutTag_Stereo`
+pub const LAYOUT_TAG_STEREO: u32 = (101 << 16) | 2;
+/// `kAudioChannelLayoutTag_MPEG_5_1_A`
+pub const LAYOUT_TAG_5_1: u32 = (121 << 16) | 6;
+/// `kAudioChannelLayoutTag_MPEG_7_1_C`
+pub const LAYOUT_TAG_7_1: u32 = (129 << 16) | 8;
+/// `kAudioChannelLayoutTag_Atmos_7_1_4`
+pub const LAYOUT_TAG_7_1_4: u32 = (192 << 16) | 12;
+/// `kAudioChannelLayoutTag_HOA_ACN_SN3D`, without the channel count.
+pub const LAYOUT_TAG_HOA_ACN_SN3D: u32 = 190 << 16;
+/// `kAudioChannelLayoutTag_DiscreteInOrder`, without the channel count.
+pub const LAYOUT_TAG_DISCRETE_IN_ORDER: u32 = 147 << 16;
+
+/// Channel layout tag for `layout`.
+pub const fn speaker_layout_to_tag(layout: SpeakerLayout) -> u32 {
+    match layout {
+        SpeakerLayout::Mono => LAYOUT_TAG_MONO,
+        SpeakerLayout::Stereo => LAYOUT_TAG_STEREO,
+        SpeakerLayout::Surround51 => LAYOUT_TAG_5_1,
+        SpeakerLayout::Surround71 => LAYOUT_TAG_7_1,
+        SpeakerLayout::Surround714 => LAYOUT_TAG_7_1_4,
+        SpeakerLayout::Ambisonic(order) => LAYOUT_TAG_HOA_ACN_SN3D | order.channel_count(),
+        SpeakerLayout::Discrete(count) => LAYOUT_TAG_DISCRETE_IN_ORDER | count,
+    }
+}
+
+/// Speaker layout for a channel layout tag, if it is one of the tags above.
+pub fn speaker_layout_from_tag(tag: u32) -> Option<SpeakerLayout> {
+    let count = tag & 0xFFFF;
+    match tag {
+        LAYOUT_TAG_MONO => Some(SpeakerLayout::Mono),
+        LAYOUT_TAG_STEREO => Some(SpeakerLayout::Stereo),
+        LAYOUT_TAG_5_1 => Some(SpeakerLayout::Surround51),
+        LAYOUT_TAG_7_1 => Some(SpeakerLayout::Surround71),
+        LAYOUT_TAG_7_1_4 => Some(SpeakerLayout::Surround714),
+        _ if tag & 0xFFFF_0000 == LAYOUT_TAG_HOA_ACN_SN3D => {
+            AmbisonicOrder::from_channel_count(count).map(SpeakerLayout::Ambisonic)
+        }
+        _ if tag & 0xFFFF_0000 == LAYOUT_TAG_DISCRETE_IN_ORDER => Some(SpeakerLayout::Discrete(count)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_round_trip() {
+        for layout in [
+            SpeakerLayout::Mono,
+            SpeakerLayout::Stereo,
+            SpeakerLayout::Surround51,
+            SpeakerLayout::Surround71,
+            SpeakerLayout::Surround714,
+            SpeakerLayout::Ambisonic(AmbisonicOrder::Third),
+            SpeakerLayout::Discrete(5),
+        ] {
+            let tag = speaker_layout_to_tag(layout);
+            assert_eq!(tag & 0xFFFF, layout.channel_count());
+            assert_eq!(speaker_layout_from_tag(tag), Some(layout));
+        }
+        // HOA with a channel count that is no full order
+        assert_eq!(speaker_layout_from_tag(LAYOUT_TAG_HOA_ACN_SN3D | 5), None);
+        // kAudioChannelLayoutTag_UseChannelDescriptions
+        assert_eq!(speaker_layout_from_tag(0), None);
+    }
+}
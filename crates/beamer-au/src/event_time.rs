@@ -0,0 +1,105 @@
+//! Conversion of AU render event times to buffer sample offsets.
+//!
+//! `AURenderEvent` times (`AUEventSampleTime`) come in two forms:
+//!
+//! - **Absolute**: a sample time on the same timeline as the render
+//!   `AudioTimeStamp::mSampleTime`. Subtracting the buffer start gives the offset.
+//! - **Immediate**: `AUEventSampleTimeImmediate` plus a small relative offset,
+//!   used by hosts and `scheduleMIDIEventBlock` callers for "as soon as
+//!   possible" events.
+//!
+//! Both are normalized to `0..frame_count`, the same range VST3 reports in
+//! `Event::sampleOffset`. Late events land on the first sample and events past
+//! the buffer on the last, so nothing is dropped and order is preserved.
+
+/// `AUEventSampleTimeImmediate` (`0xffffffff00000000`).
+pub const AU_EVENT_SAMPLE_TIME_IMMEDIATE: i64 = 0xffff_ffff_0000_0000_u64 as i64;
+
+/// Smallest range above [`AU_EVENT_SAMPLE_TIME_IMMEDIATE`] treated as a
+/// relative offset. Apple documents "small (< 4096)" offsets; larger buffers
+/// extend the window to the buffer length.
+const IMMEDIATE_OFFSET_WINDOW: usize = 4096;
+
+/// Buffer start on the event timeline, from the render timestamp.
+///
+/// Core Audio sample times are `f64` and may carry rounding noise (e.g.
+/// `1023.9999`). Rounding instead of truncating keeps absolute events from
+/// jittering one sample early between buffers.
+pub fn buffer_start_sample(sample_time: f64) -> i64 {
+    if sample_time.is_finite() {
+        sample_time.round() as i64
+    } else {
+        0
+    }
+}
+
+/// Convert an `AUEventSampleTime` to a sample offset within the current buffer.
+///
+/// * `event_sample_time` - Raw event time from the render event
+/// * `buffer_start` - Result of [`buffer_start_sample()`] for this render call
+/// * `frame_count` - Number of frames in this render call
+pub fn event_sample_offset(event_sample_time: i64, buffer_start: i64, frame_count: usize) -> u32 {
+    if frame_count == 0 {
+        return 0;
+    }
+
+    let window = frame_count.max(IMMEDIATE_OFFSET_WINDOW) as i64;
+    let immediate_offset = event_sample_time.wrapping_sub(AU_EVENT_SAMPLE_TIME_IMMEDIATE);
+    let relative = if (0..window).contains(&immediate_offset) {
+        immediate_offset
+    } else {
+        event_sample_time.saturating_sub(buffer_start)
+    };
+
+    relative.clamp(0, frame_count as i64 - 1) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_times_are_relative_to_buffer_start() {
+        let start = buffer_start_sample(48_000.0);
+        assert_eq!(event_sample_offset(48_000, start, 512), 0);
+        assert_eq!(event_sample_offset(48_100, start, 512), 100);
+        assert_eq!(event_sample_offset(48_511, start, 512), 511);
+    }
+
+    #[test]
+    fn immediate_times_are_offsets() {
+        let start = buffer_start_sample(10_000_000.0);
+        assert_eq!(event_sample_offset(AU_EVENT_SAMPLE_TIME_IMMEDIATE, start, 256), 0);
+        assert_eq!(event_sample_offset(AU_EVENT_SAMPLE_TIME_IMMEDIATE + 37, start, 256), 37);
+        // Beyond the buffer: clamped, not wrapped
+        assert_eq!(event_sample_offset(AU_EVENT_SAMPLE_TIME_IMMEDIATE + 300, start, 256), 255);
+        // Large buffers widen the immediate window
+        assert_eq!(event_sample_offset(AU_EVENT_SAMPLE_TIME_IMMEDIATE + 5000, start, 8192), 5000);
+    }
+
+    #[test]
+    fn late_and_early_events_are_clamped() {
+        let start = buffer_start_sample(1_000.0);
+        assert_eq!(event_sample_offset(990, start, 128), 0);
+        assert_eq!(event_sample_offset(5_000, start, 128), 127);
+        assert_eq!(event_sample_offset(i64::MIN, start, 128), 0);
+        assert_eq!(event_sample_offset(i64::MAX, start, 128), 127);
+        assert_eq!(event_sample_offset(1_010, start, 0), 0);
+    }
+
+    #[test]
+    fn sample_times_beyond_u32_are_preserved() {
+        let start_time = 5_000_000_000.0;
+        let start = buffer_start_sample(start_time);
+        assert_eq!(event_sample_offset(5_000_000_064, start, 128), 64);
+    }
+
+    #[test]
+    fn fractional_buffer_start_is_rounded() {
+        let start = buffer_start_sample(1023.9999);
+        assert_eq!(start, 1024);
+        assert_eq!(event_sample_offset(1024, start, 64), 0);
+        assert_eq!(event_sample_offset(1030, start, 64), 6);
+        assert_eq!(buffer_start_sample(f64::NAN), 0);
+    }
+}
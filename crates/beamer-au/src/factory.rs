@@ -6,7 +6,7 @@
 
 use std::sync::OnceLock;
 
-use beamer_core::Config;
+use beamer_core::{Config, PluginFormat};
 
 use crate::instance::AuPluginInstance;
 
@@ -21,7 +21,8 @@ static FACTORY_CONFIG: OnceLock<&'static Config> = OnceLock::new();
 
 /// Register factory and config.
 ///
-/// Called by the `export_au!` macro during module initialization.
+/// Called by the `export_au!` macro during module initialization. The
+/// config's AU override is applied here.
 ///
 /// # Panics
 ///
@@ -35,10 +36,14 @@ pub fn register_factory(
         .set(factory)
         .expect("AU factory already registered - only one plugin per binary is supported");
 
+    let plugin_config = plugin_config.resolve(PluginFormat::Au);
     FACTORY_CONFIG
         .set(plugin_config)
         .expect("AU factory config already registered");
 
+    beamer_core::logging::init(plugin_config);
+    beamer_core::host_trace::init();
+
     log::debug!(
         "AU factory registered: {} ({} {})",
         plugin_config.name,
@@ -18,7 +18,8 @@ use std::sync::Arc;
 
 use crate::error::{PluginError, PluginResult};
 use beamer_core::{
-    CachedBusConfig, MidiEvent, ParameterGroups, ParameterStore, ProcessContext, WebViewHandler,
+    Activation, AutomationPreview, Autosave, CachedBusConfig, ConstraintState, ControllerPageState, CrashGuard, FrameMeters, GuiBackend, GuiDataChannels, HostServices, MidiEvent, NoteRanges, Notifications, ParameterExposure, ParameterGroups, PresetCrossfade,
+    MidiMonitor, MidiPanic, ParameterLog, ParameterStore, ProcessContext, ProcessWatchdog, ProcessingStats, ResponseCurves, StandardTrims, Transport, WatchdogSettings, WebViewHandler,
 };
 
 /// Type-erased interface for AU plugin instances.
@@ -108,14 +109,15 @@ pub trait AuPluginInstance: Send + 'static {
 
     /// Reset DSP state (clear delay lines, reset filters, etc.).
     ///
-    /// Called when the transport stops/starts or when the plugin is
-    /// activated/deactivated.
+    /// Called for the AU reset selector (`AudioUnitReset` / AUv3 `reset`).
+    /// Implementations forward to `Processor::reset()`.
     fn reset(&mut self);
 
     /// Get the tail length in samples.
     ///
     /// Returns the number of samples the plugin will continue to output
-    /// after input has stopped (e.g., reverb/delay tail).
+    /// after input has stopped (e.g., reverb/delay tail), or `u32::MAX`
+    /// for an infinite tail ([`TailTime::Infinite`](beamer_core::TailTime)).
     fn tail_samples(&self) -> u32;
 
     /// Get the processing latency in samples.
@@ -150,6 +152,40 @@ pub trait AuPluginInstance: Send + 'static {
     /// Returns information about an output bus the plugin declares.
     fn declared_output_bus_info(&self, index: usize) -> Option<beamer_core::BusInfo>;
 
+    /// Returns true if the plugin runs with `main_input_channels` and
+    /// `main_output_channels` on its main buses, aux buses as declared.
+    ///
+    /// The declared layout is always accepted; others are left to
+    /// `Descriptor::accepts_bus_layout`.
+    fn accepts_main_channels(&self, main_input_channels: u32, main_output_channels: u32) -> bool;
+
+    /// Returns true once after the parameters select a different bus layout
+    /// (`Descriptor::bus_layout_id`).
+    ///
+    /// Polled by the wrappers after state loads and GUI edits. They notify
+    /// the host, which picks up the new buses when it re-initializes. The
+    /// default never reports a change.
+    fn take_bus_layout_changed(&mut self) -> bool {
+        false
+    }
+
+    /// Returns whether the parameters select another oversampling factor or
+    /// quality mode than the prepared processor runs with
+    /// (`Descriptor::oversampling`, `Descriptor::quality`).
+    fn oversampling_changed(&self) -> bool {
+        false
+    }
+
+    /// Prepare the processor again if another oversampling factor or
+    /// quality mode was selected. Returns true if it did, after which the latency may differ.
+    ///
+    /// Runs on the main thread with the plugin lock held, so the render
+    /// path skips blocks until it returns. The default never reports a
+    /// change.
+    fn apply_oversampling(&mut self) -> bool {
+        false
+    }
+
     /// Process audio (f32).
     ///
     /// Only valid when prepared. Returns error if not in prepared state.
@@ -348,6 +384,11 @@ pub trait AuPluginInstance: Send + 'static {
         None // Default implementation
     }
 
+    /// Start a block of `num_samples`, before [`process_midi`](Self::process_midi).
+    ///
+    /// Forwards to the processor's `begin_block()`.
+    fn begin_block(&mut self, _num_samples: usize) {}
+
     /// Process MIDI events (input → output transformation).
     ///
     /// This method allows plugins to process, transform, or generate MIDI events.
@@ -368,6 +409,40 @@ pub trait AuPluginInstance: Send + 'static {
         }
     }
 
+    /// Append transport-locked MIDI clock and MMC events to `output`.
+    ///
+    /// Called after [`process_midi`](Self::process_midi) with the block's
+    /// host transport. Implementations apply the plugin's
+    /// `Processor::midi_clock_output()` policy. The default generates nothing.
+    fn generate_midi_clock(
+        &mut self,
+        _transport: &Transport,
+        _sample_rate: f64,
+        _num_samples: usize,
+        _output: &mut crate::render::MidiBuffer,
+    ) {
+    }
+
+    /// Detect transport start/stop/relocation for this block.
+    ///
+    /// Called before [`process_midi`](Self::process_midi) with the block's
+    /// host transport. Implementations run a `TransportTracker` and apply the
+    /// plugin's `Processor::relocation_policy()`. The default does nothing.
+    fn track_transport(&mut self, _transport: &Transport, _num_samples: usize) {}
+
+    /// Store the processor's `Processor::active_voices()` in the processing
+    /// statistics.
+    ///
+    /// Called once per block before [`process_midi`](Self::process_midi).
+    /// The default does nothing.
+    fn record_active_voices(&self) {}
+
+    /// Call the processor's `Processor::panic()`.
+    ///
+    /// Called before [`process_midi`](Self::process_midi) in the block
+    /// that applies a requested MIDI panic. The default does nothing.
+    fn panic(&mut self) {}
+
     // =========================================================================
     // WebView Handler
     // =========================================================================
@@ -380,6 +455,187 @@ pub trait AuPluginInstance: Send + 'static {
         None
     }
 
+    /// Returns the editor backend (`Descriptor::editor()`).
+    ///
+    /// The bridge caches it to create native editors for the AU view.
+    fn editor(&self) -> GuiBackend {
+        GuiBackend::WebView
+    }
+
+    /// Returns the crash guard used when restoring state.
+    ///
+    /// The bridge caches it to answer the GUI's safe mode query without
+    /// locking the plugin.
+    fn crash_guard(&self) -> CrashGuard {
+        CrashGuard::default()
+    }
+
+    /// Returns the host parameter exposure (internal/promoted parameters).
+    ///
+    /// The bridge caches it to build the host-facing parameter list and to
+    /// handle the GUI's promote/demote calls without locking the plugin.
+    /// The default exposes every parameter.
+    fn parameter_exposure(&self) -> ParameterExposure {
+        ParameterExposure::default()
+    }
+
+    /// Returns the hardware controller page selection.
+    ///
+    /// The bridge caches it to handle the GUI's page invokes without
+    /// locking the plugin. The default has no pages.
+    fn controller_pages(&self) -> ControllerPageState {
+        ControllerPageState::default()
+    }
+
+    /// Returns the framework's input/output trim and polarity parameters.
+    ///
+    /// The bridge caches it to list the trims after the plugin's parameters
+    /// and route their IDs. The default is disabled.
+    fn standard_trims(&self) -> StandardTrims {
+        StandardTrims::default()
+    }
+
+    /// Returns the framework's read-only processing statistics.
+    ///
+    /// The bridge caches it to time render calls, list the statistics after
+    /// the standard trims and route their IDs. The default is disabled.
+    fn processing_stats(&self) -> ProcessingStats {
+        ProcessingStats::default()
+    }
+
+    /// Returns the constraints between the plugin's parameters.
+    ///
+    /// The bridge caches it to enforce the constraints after host and GUI
+    /// edits without locking the plugin. The default has no constraints.
+    fn parameter_constraints(&self) -> ConstraintState {
+        ConstraintState::default()
+    }
+
+    /// Returns the declicking fade for host preset changes.
+    ///
+    /// The bridge caches it to learn when the audio thread applied a
+    /// crossfaded preset, so the wrapper can report the new values to the
+    /// host. The default is disabled.
+    fn preset_crossfade(&self) -> Arc<PresetCrossfade> {
+        Arc::default()
+    }
+
+    /// Returns the MIDI velocity and controller response curves.
+    ///
+    /// The render block applies them to incoming MIDI, and the bridge caches
+    /// them to handle the GUI's curve invokes without locking the plugin.
+    /// The default has no curves.
+    fn response_curves(&self) -> ResponseCurves {
+        ResponseCurves::default()
+    }
+
+    /// Returns the playable note range and keyboard splits.
+    ///
+    /// The render block drops notes outside the range when the plugin asks
+    /// for filtering, and the bridge caches a copy for the GUI's note range
+    /// invoke.
+    fn note_ranges(&self) -> &NoteRanges;
+
+    /// Returns the host automation versus GUI value tracker.
+    ///
+    /// The bridge caches it to record host and GUI edits and to answer the
+    /// GUI's automation state query without locking the plugin. The default
+    /// tracks no parameters.
+    fn automation_preview(&self) -> AutomationPreview {
+        AutomationPreview::default()
+    }
+
+    /// Returns the crash-safe autosave.
+    ///
+    /// The bridge caches it to snapshot the state on GUI edits, track host
+    /// saves and restores, and answer the GUI's recovery invokes. The
+    /// default is disabled.
+    fn autosave(&self) -> Autosave {
+        Autosave::default()
+    }
+
+    /// Returns the parameter change log.
+    ///
+    /// The bridge caches it to record host and GUI edits and answer the
+    /// GUI's log invokes. The default is disabled.
+    fn parameter_log(&self) -> ParameterLog {
+        ParameterLog::default()
+    }
+
+    /// Returns the MIDI monitor.
+    ///
+    /// The render path records the MIDI in and out of each block, and the
+    /// bridge caches it to answer the GUI's monitor invokes. The default is
+    /// disabled.
+    fn midi_monitor(&self) -> MidiMonitor {
+        MidiMonitor::default()
+    }
+
+    /// Returns the MIDI panic trigger.
+    ///
+    /// The render path applies requested panics, and the bridge caches it
+    /// to list the hidden MIDI Panic parameter after the processing
+    /// statistics and answer the GUI's panic invoke. The default is
+    /// disabled.
+    fn midi_panic(&self) -> MidiPanic {
+        MidiPanic::default()
+    }
+
+    /// Returns the plugin's host notification handle.
+    ///
+    /// The bridge caches it so the wrapper can forward state dirty marks.
+    fn host_services(&self) -> HostServices {
+        HostServices::default()
+    }
+
+    /// Returns the license activation.
+    ///
+    /// The bridge caches it to answer the GUI's activation invokes. The
+    /// default has no check.
+    fn activation(&self) -> Activation {
+        Activation::default()
+    }
+
+    /// Returns the meters sent with each GUI frame.
+    ///
+    /// The bridge caches them to answer the frame clock without locking
+    /// the plugin. The default has no meters.
+    fn frame_meters(&self) -> FrameMeters {
+        FrameMeters::default()
+    }
+
+    /// Returns the plugin's frame streams for the GUI.
+    ///
+    /// The bridge caches them to deliver `audio-data` events from the GUI
+    /// sync timer. The default has no channels.
+    fn gui_data(&self) -> GuiDataChannels {
+        GuiDataChannels::default()
+    }
+
+    /// Returns the queue of messages for the GUI.
+    ///
+    /// The bridge caches it to deliver notifications from the GUI sync
+    /// timer without locking the plugin.
+    fn notifications(&self) -> Notifications {
+        Notifications::default()
+    }
+
+    /// Returns the deadline watchdog for render calls.
+    ///
+    /// The bridge caches it to time render calls and deliver its reports
+    /// without locking the plugin. The default times nothing.
+    fn watchdog(&self) -> ProcessWatchdog {
+        ProcessWatchdog::new(WatchdogSettings::disabled())
+    }
+
+    /// Returns the instance ID used in the host call trace.
+    ///
+    /// The bridge caches it to trace host calls without locking the plugin.
+    /// Always 0 without the `host-trace` feature.
+    fn trace_id(&self) -> u32 {
+        0
+    }
+
     // =========================================================================
     // Factory Presets
     // =========================================================================
@@ -393,11 +649,21 @@ pub trait AuPluginInstance: Send + 'static {
 
     /// Returns preset info (number, name) for given index.
     ///
-    /// Returns `None` if the index is out of range.
-    fn preset_info(&self, _index: u32) -> Option<(i32, &str)> {
+    /// Returns `None` if the index is out of range. Names come from a
+    /// preallocated cache, so repeated queries don't allocate.
+    fn preset_info(&mut self, _index: u32) -> Option<(i32, &str)> {
         None
     }
 
+    /// Returns true once after presets were added, removed or renamed at
+    /// runtime (`FactoryPresets::names_version()`).
+    ///
+    /// Polled by the wrappers, which rebuild their preset list and notify
+    /// the host. The default never reports a change.
+    fn take_preset_list_changed(&mut self) -> bool {
+        false
+    }
+
     /// Applies a factory preset by index.
     ///
     /// Only parameters specified in the preset are modified; other parameters
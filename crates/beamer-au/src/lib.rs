@@ -82,8 +82,10 @@ extern crate beamer_webview;
 // Platform-independent modules
 // =============================================================================
 
+pub mod channel_layout;
 pub mod config;
 pub mod error;
+pub mod event_time;
 
 // Re-exports
 pub use config::FourCharCode;
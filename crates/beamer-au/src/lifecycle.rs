@@ -33,8 +33,8 @@
 //! Standard Beamer setups (Nothing, SampleRate, BufferSetup, FullSetup) are provided.
 
 use beamer_core::{
-    BusLayout, CachedBusConfig, ConversionBuffers, Descriptor, HasParameters, MidiCcConfig,
-    PluginSetup, Processor,
+    BusLayout, CachedBusConfig, ConversionBuffers, CrashGuard, Descriptor, HasParameters,
+    MidiCcConfig, PluginSetup, PrecisionPolicy, Processor, Quality, SampleRateAdapter,
 };
 use log;
 
@@ -162,6 +162,24 @@ impl<P: Descriptor> AuState<P> {
         }
     }
 
+    /// Transition to Unprepared, keeping the processor's full state.
+    ///
+    /// Used when the processor is rebuilt for a new bus layout
+    /// (`Descriptor::bus_layout_id`): `Processor::save_state` is kept as the
+    /// pending state and loaded into the next processor, so state beyond
+    /// parameters survives the rebuild.
+    pub fn unprepare_keeping_state(&mut self) -> Result<(), String> {
+        let saved = match self {
+            Self::Prepared { processor, .. } => processor.save_state().ok(),
+            _ => None,
+        };
+        self.unprepare()?;
+        if let Self::Unprepared { pending_state, .. } = self {
+            *pending_state = pending_state.take().or(saved);
+        }
+        Ok(())
+    }
+
     /// Get reference to MIDI CC state (only when prepared).
     ///
     /// Used by render block to track MIDI CC changes and update parameter smoothing.
@@ -171,6 +189,14 @@ impl<P: Descriptor> AuState<P> {
             _ => None,
         }
     }
+
+    /// Get mutable reference to MIDI CC state (only when prepared).
+    pub fn midi_cc_state_mut(&mut self) -> Option<&mut beamer_core::MidiCcState> {
+        match self {
+            Self::Prepared { midi_cc_state, .. } => midi_cc_state.as_deref_mut(),
+            _ => None,
+        }
+    }
 }
 
 impl<P: Descriptor> Default for AuState<P> {
@@ -183,17 +209,34 @@ impl<P: Descriptor> Default for AuState<P> {
 ///
 /// Creates a HostSetup with all available information, then uses the
 /// `PluginSetup::extract` method to extract only what the plugin needs.
-fn build_setup<S: PluginSetup>(sample_rate: f64, max_frames: u32, layout: &BusLayout) -> S {
+/// The processor sees `processing_rate` and a block size scaled to it.
+fn build_setup<S: PluginSetup>(
+    sample_rate: f64,
+    processing_rate: f64,
+    max_frames: u32,
+    layout: &BusLayout,
+    quality: Quality,
+) -> S {
     use beamer_core::{HostSetup, ProcessMode};
 
+    let max_frames = if processing_rate == sample_rate {
+        max_frames as usize
+    } else {
+        SampleRateAdapter::<f32>::processing_block_size(
+            max_frames as usize,
+            processing_rate / sample_rate,
+        )
+    };
+
     // AU doesn't distinguish offline rendering at the API level,
     // so we default to Realtime
     let host_setup = HostSetup::new(
-        sample_rate,
-        max_frames as usize,
+        processing_rate,
+        max_frames,
         layout.clone(),
         ProcessMode::Realtime,
-    );
+    )
+    .with_quality(quality);
 
     S::extract(&host_setup)
 }
@@ -202,10 +245,15 @@ fn build_setup<S: PluginSetup>(sample_rate: f64, max_frames: u32, layout: &BusLa
 ///
 /// This is shared between initial preparation and re-preparation paths to avoid
 /// code duplication.
+///
+/// `processing_ratio` is processing rate / host rate. Mixed-precision aux
+/// conversion runs inside the resampler, so its buffers are sized for the
+/// processing-rate block.
 fn allocate_processing_resources(
-    supports_f64: bool,
+    policy: PrecisionPolicy,
     midi_cc_config: Option<MidiCcConfig>,
     max_frames: u32,
+    processing_ratio: f64,
     layout: &BusLayout,
     bus_config: &CachedBusConfig,
 ) -> (
@@ -213,10 +261,25 @@ fn allocate_processing_resources(
     Option<Box<beamer_core::MidiCcState>>,
     Box<beamer_core::MidiBuffer>,
 ) {
-    // Pre-allocate conversion buffers if processor doesn't support f64
-    let conversion_buffers = if !supports_f64 {
-        let input_channels = layout.main_input_channels as usize;
-        let output_channels = layout.main_output_channels as usize;
+    // Pre-allocate conversion buffers for the buses processed at f32
+    let conversion_buffers = if !policy.aux_is_f64() {
+        // The main bus stays at f64 for F64MainF32Aux
+        let (input_channels, output_channels) = if policy.main_is_f64() {
+            (0, 0)
+        } else {
+            (
+                layout.main_input_channels as usize,
+                layout.main_output_channels as usize,
+            )
+        };
+        let frames = if policy.main_is_f64() {
+            (max_frames as usize).max(SampleRateAdapter::<f64>::processing_block_size(
+                max_frames as usize,
+                processing_ratio,
+            ))
+        } else {
+            max_frames as usize
+        };
 
         // Build aux bus channel counts from CachedBusConfig
         // Skip main bus (index 0) to get aux buses only
@@ -238,7 +301,7 @@ fn allocate_processing_resources(
             output_channels,
             &aux_input_channels,
             &aux_output_channels,
-            max_frames as usize,
+            frames,
         ))
     } else {
         None
@@ -260,12 +323,17 @@ impl<P: Descriptor> AuState<P> {
     /// Transition from Unprepared to Prepared.
     ///
     /// Accepts `CachedBusConfig` to derive actual aux bus channel counts for
-    /// proper conversion buffer allocation.
+    /// proper conversion buffer allocation. A pending state is loaded through
+    /// `crash_guard`. The processor is prepared at `processing_rate`, which
+    /// differs from the host `sample_rate` when `Config::sample_rates`
+    /// forces resampling.
     pub fn prepare(
         &mut self,
         sample_rate: f64,
+        processing_rate: f64,
         max_frames: u32,
         bus_config: &CachedBusConfig,
+        crash_guard: &CrashGuard,
     ) -> Result<(), String> {
         // Convert CachedBusConfig to BusLayout for plugin config
         let layout = bus_config.to_bus_layout();
@@ -279,24 +347,32 @@ impl<P: Descriptor> AuState<P> {
                 // Capture MIDI CC config before consuming the plugin
                 let midi_cc_config = plugin.midi_cc_config();
 
-                let plugin_setup = build_setup::<P::Setup>(sample_rate, max_frames, &layout);
+                let plugin_setup = build_setup::<P::Setup>(
+                    sample_rate,
+                    processing_rate,
+                    max_frames,
+                    &layout,
+                    P::quality(plugin.parameters()),
+                );
                 let mut processor = plugin.prepare(plugin_setup);
 
                 // Apply any pending state that was set before preparation
+                use beamer_core::parameter_types::Parameters;
                 if let Some(data) = pending_state {
-                    if let Err(e) = processor.load_state(&data) {
+                    if let Err(e) = crash_guard.load_state(&mut processor, &data) {
                         log::warn!("Failed to load pending state: {:?}", e);
                     }
-                    use beamer_core::parameter_types::Parameters;
-                    processor.parameters_mut().set_sample_rate(sample_rate);
                     processor.parameters_mut().reset_smoothing();
                 }
+                // Smoothers and TimeInSamples parameters follow the processing rate
+                processor.parameters_mut().set_sample_rate(processing_rate);
 
                 let (conversion_buffers, midi_cc_state, midi_output_buffer) =
                     allocate_processing_resources(
-                        processor.supports_double_precision(),
+                        processor.precision_policy(),
                         midi_cc_config,
                         max_frames,
+                        processing_rate / sample_rate,
                         &layout,
                         bus_config,
                     );
@@ -320,14 +396,23 @@ impl<P: Descriptor> AuState<P> {
                 // Capture MIDI CC config before consuming the plugin
                 let midi_cc_config = plugin.midi_cc_config();
 
-                let plugin_setup = build_setup::<P::Setup>(sample_rate, max_frames, &layout);
-                let new_processor = plugin.prepare(plugin_setup);
+                let plugin_setup = build_setup::<P::Setup>(
+                    sample_rate,
+                    processing_rate,
+                    max_frames,
+                    &layout,
+                    P::quality(plugin.parameters()),
+                );
+                let mut new_processor = plugin.prepare(plugin_setup);
+                use beamer_core::parameter_types::Parameters;
+                new_processor.parameters_mut().set_sample_rate(processing_rate);
 
                 let (conversion_buffers, midi_cc_state, midi_output_buffer) =
                     allocate_processing_resources(
-                        new_processor.supports_double_precision(),
+                        new_processor.precision_policy(),
                         midi_cc_config,
                         max_frames,
+                        processing_rate / sample_rate,
                         &layout,
                         bus_config,
                     );
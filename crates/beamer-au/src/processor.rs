@@ -34,10 +34,14 @@ use std::sync::Arc;
 use crate::error::{PluginError, PluginResult};
 use crate::instance::AuPluginInstance;
 use crate::lifecycle::AuState;
+use beamer_core::host_trace::{self, HostCall};
 use beamer_core::{
-    AuxiliaryBuffers, Buffer, CachedBusConfig, Descriptor, FactoryPresets, HasParameters,
-    MidiEvent, NoPresets, ParameterGroups, ParameterStore, ProcessContext, Processor, Transport,
-    WebViewHandler,
+    descriptor_snapshot, negotiate_bus_layout, Activation, ActivationGate, AutomationPreview, AuxiliaryBuffers, Autosave, Buffer, CachedBusConfig,
+    ChannelMixStage,
+    ConstraintState, ControlRate, ControllerPageState, CrashGuard, Descriptor, HostServices,
+    FactoryPresets, FrameMeters, GuiBackend, GuiDataChannels, HasParameters, MidiClockGenerator, MidiEvent, MpeState, NoPresets, NoteRanges, Notifications, NotifyLevel, Oversampling, ParameterExposure, Quality,
+    ParameterGroups, ParameterChangeSource, ParameterLog, MidiMonitor, MidiPanic, ParameterStore, PresetCrossfade, PresetNameCache, ProcessContext, ProcessWatchdog, ProcessingStats, Processor, ResponseCurves,
+    SampleRateAdapter, SpeakerLayout, StandardTrims, Transport, TransportTracker, TrimStage, WebViewHandler,
 };
 
 /// Generic AU processor wrapper.
@@ -58,6 +62,93 @@ where
     /// Cached WebView handler from the Descriptor. Captured at construction
     /// so it remains accessible after prepare() consumes the Descriptor.
     webview_handler: Option<Arc<dyn WebViewHandler>>,
+    /// Editor backend from the Descriptor, captured like the WebView handler.
+    editor: GuiBackend,
+    /// Declicking fade for host preset changes (`Config::preset_crossfade_ms`),
+    /// shared with the bridge to report applied presets to the host.
+    preset_crossfade: Arc<PresetCrossfade>,
+    /// Preallocated preset names so repeated preset list queries don't allocate.
+    preset_names: PresetNameCache,
+    /// Transport-locked MIDI clock/MMC output (`Processor::midi_clock_output`).
+    midi_clock: MidiClockGenerator,
+    /// Detects transport start/stop/relocation for `Processor::relocation_policy`.
+    transport_tracker: TransportTracker,
+    /// Per-note MPE tracking (`Descriptor::mpe_config`).
+    mpe_state: Option<MpeState>,
+    /// Playable note range and splits (`Descriptor::note_ranges`).
+    note_ranges: NoteRanges,
+    /// Resamplers around process() when the host rate is outside
+    /// `Config::sample_rates` (f32 also serves the f64→f32 conversion path).
+    resampler_f32: Option<SampleRateAdapter<f32>>,
+    resampler_f64: Option<SampleRateAdapter<f64>>,
+    /// Input/output trim and polarity (`Config::standard_trims`), applied
+    /// around process() at the host rate.
+    standard_trims: StandardTrims,
+    trims_f32: Option<TrimStage<f32>>,
+    trims_f64: Option<TrimStage<f64>>,
+    /// Read-only DSP load, voice count and underruns
+    /// (`Config::processing_stats`), timed by the bridge.
+    processing_stats: ProcessingStats,
+    /// Main input converted to the output width inside process()
+    /// (`Config::channel_mix`).
+    channel_mix_f32: Option<ChannelMixStage<f32>>,
+    channel_mix_f64: Option<ChannelMixStage<f64>>,
+    /// Control-rate ticks around process() (`Processor::control_interval`).
+    control_rate: ControlRate,
+    /// Bus layout the host was last told about (`Descriptor::bus_layout_id`).
+    bus_layout_id: u32,
+    /// Bus layout the processor was prepared with.
+    prepared_bus_layout_id: u32,
+    /// Oversampling the processor was prepared with (`Descriptor::oversampling`).
+    oversampling: Oversampling,
+    /// Quality mode the processor was prepared with (`Descriptor::quality`).
+    quality: Quality,
+    /// Rate the processor runs at (host rate, resampling and oversampling),
+    /// for recomputing `RateDependent` values after a state load.
+    processing_rate: f64,
+    /// Buses from the last allocation, to prepare again with another
+    /// oversampling factor or quality mode.
+    bus_config: Option<CachedBusConfig>,
+    /// Speaker layouts of the main input and output buses from the last
+    /// allocation, reported through `Buffer`.
+    speaker_layouts: (SpeakerLayout, SpeakerLayout),
+    /// Safe mode for states that repeatedly crash on load.
+    crash_guard: CrashGuard,
+    /// Which parameters the host sees (internal parameters are GUI-only
+    /// unless promoted).
+    parameter_exposure: ParameterExposure,
+    /// Hardware controller pages (selected page, saved with the state).
+    controller_pages: ControllerPageState,
+    /// Constraints between parameters, enforced after every set. Shared
+    /// with the bridge for host and GUI edits.
+    parameter_constraints: ConstraintState,
+    /// Velocity and controller response curves, applied to MIDI input and
+    /// CC readouts. Shared with the bridge for GUI edits.
+    response_curves: ResponseCurves,
+    /// Host automation versus GUI values, fed from render events.
+    automation_preview: AutomationPreview,
+    /// Crash-safe autosave (disabled unless the descriptor opts in).
+    autosave: Autosave,
+    /// Parameter change log (disabled unless the descriptor opts in).
+    parameter_log: ParameterLog,
+    /// MIDI monitor (disabled unless the descriptor opts in).
+    midi_monitor: MidiMonitor,
+    /// MIDI panic trigger (enabled for plugins that want MIDI).
+    midi_panic: MidiPanic,
+    /// Plugin requests for the host, shared with the descriptor.
+    host_services: HostServices,
+    /// License activation check and the output policy until it succeeds.
+    activation: ActivationGate,
+    /// Meters sent with each GUI frame, shared with the descriptor.
+    frame_meters: FrameMeters,
+    /// Frame streams for the GUI, shared with the descriptor.
+    gui_data: GuiDataChannels,
+    /// Messages for the GUI, shared with the descriptor.
+    notifications: Notifications,
+    /// Deadline watchdog for render calls (`Config::watchdog`).
+    watchdog: ProcessWatchdog,
+    /// Instance ID in the host call trace (`host-trace` feature).
+    trace_id: u32,
     _presets: PhantomData<Presets>,
 }
 
@@ -66,6 +157,49 @@ where
     P: Descriptor,
     Presets: FactoryPresets<Parameters = <P as HasParameters>::Parameters>,
 {
+    /// Oversampling factor the current parameters select.
+    fn selected_oversampling(&self) -> Option<Oversampling> {
+        match &self.state {
+            AuState::Unprepared { plugin, .. } => Some(P::oversampling(plugin.parameters())),
+            AuState::Prepared { processor, .. } => Some(P::oversampling(processor.parameters())),
+            AuState::Transitioning => None,
+        }
+    }
+
+    /// Quality mode the current parameters select.
+    fn selected_quality(&self) -> Option<Quality> {
+        match &self.state {
+            AuState::Unprepared { plugin, .. } => Some(P::quality(plugin.parameters())),
+            AuState::Prepared { processor, .. } => Some(P::quality(processor.parameters())),
+            AuState::Transitioning => None,
+        }
+    }
+
+    /// Apply the preset a finished crossfade fade-out is waiting for.
+    ///
+    /// Shared start of every `process*` variant, ahead of the plugin's block.
+    fn begin_preset_crossfade(&mut self) {
+        let Some(processor) = self.state.processor_mut() else {
+            return;
+        };
+        if let Some(index) = self.preset_crossfade.begin() {
+            Presets::apply(index, processor.parameters());
+            self.parameter_constraints.enforce_all(processor.parameters(), |_, _| {});
+            self.parameter_log.record_preset(index);
+        }
+    }
+
+    /// Hand the block's MIDI to the event splitter, if the processor splits
+    /// blocks at events.
+    fn record_split_midi(&mut self, midi_events: &[MidiEvent]) {
+        let Some(processor) = self.state.processor_mut() else {
+            return;
+        };
+        if let Some(split_blocks) = self.control_rate.split_blocks_for(processor) {
+            split_blocks.set_midi(midi_events);
+        }
+    }
+
     /// Create a new AU processor.
     ///
     /// The processor starts in the Unprepared state with a default
@@ -78,9 +212,85 @@ where
         // the live plugin, not a discarded throw-away copy.
         let descriptor = P::default();
         let handler = descriptor.webview_handler();
+        let editor = descriptor.editor();
+        let parameter_exposure = ParameterExposure::new(descriptor.parameters());
+        let controller_pages =
+            ControllerPageState::new(&descriptor.controller_pages(), descriptor.parameters());
+        let parameter_constraints =
+            ConstraintState::new(&descriptor.parameter_constraints(), descriptor.parameters());
+        let response_curves = descriptor.response_curves();
+        let mpe_state = descriptor.mpe_config().map(MpeState::new);
+        let note_ranges = descriptor.note_ranges();
+        let automation_preview = AutomationPreview::new(descriptor.parameters());
+        let autosave = crate::factory::plugin_config()
+            .map(|config| Autosave::new(config, descriptor.autosave_interval()))
+            .unwrap_or_default();
+        let parameter_log = descriptor.parameter_log();
+        let midi_monitor = descriptor.midi_monitor();
+        let midi_panic = descriptor.midi_panic();
+        let host_services = descriptor.host_services();
+        let activation = ActivationGate::new(descriptor.activation());
+        activation.activation().start();
+        let frame_meters = descriptor.frame_meters();
+        let gui_data = descriptor.gui_data();
+        let notifications = descriptor.notifications();
+        let watchdog = ProcessWatchdog::new(
+            crate::factory::plugin_config().map_or_else(Default::default, |config| config.watchdog),
+        )
+        .with_notifications(notifications.clone());
+        let bus_layout_id = P::bus_layout_id(descriptor.parameters());
+        let mut preset_names = PresetNameCache::new();
+        preset_names.reserve(Presets::count());
+        preset_names.take_list_changed::<Presets>();
         Self {
             state: AuState::with_descriptor(descriptor),
             webview_handler: handler,
+            editor,
+            preset_crossfade: Arc::new(PresetCrossfade::new()),
+            preset_names,
+            midi_clock: MidiClockGenerator::new(),
+            transport_tracker: TransportTracker::new(),
+            mpe_state,
+            note_ranges,
+            resampler_f32: None,
+            resampler_f64: None,
+            standard_trims: StandardTrims::enabled(
+                crate::factory::plugin_config().is_some_and(|config| config.standard_trims),
+            ),
+            trims_f32: None,
+            trims_f64: None,
+            processing_stats: ProcessingStats::enabled(
+                crate::factory::plugin_config().is_some_and(|config| config.processing_stats),
+            ),
+            channel_mix_f32: None,
+            channel_mix_f64: None,
+            control_rate: ControlRate::new(),
+            bus_layout_id,
+            prepared_bus_layout_id: bus_layout_id,
+            oversampling: Oversampling::Off,
+            quality: Quality::Normal,
+            processing_rate: 0.0,
+            bus_config: None,
+            speaker_layouts: (SpeakerLayout::Stereo, SpeakerLayout::Stereo),
+            crash_guard: crate::factory::plugin_config()
+                .map(CrashGuard::new)
+                .unwrap_or_default(),
+            parameter_exposure,
+            controller_pages,
+            parameter_constraints,
+            response_curves,
+            automation_preview,
+            autosave,
+            parameter_log,
+            midi_monitor,
+            midi_panic,
+            host_services,
+            activation,
+            frame_meters,
+            gui_data,
+            notifications,
+            watchdog,
+            trace_id: host_trace::next_instance_id(),
             _presets: PhantomData,
         }
     }
@@ -108,13 +318,93 @@ where
         max_frames: u32,
         bus_config: &CachedBusConfig,
     ) -> PluginResult<()> {
+        let config = crate::factory::plugin_config();
+        self.oversampling = self.selected_oversampling().unwrap_or_default();
+        self.quality = self.selected_quality().unwrap_or_default();
+        let processing_rate = config
+            .map(|config| config.sample_rates.processing_rate(sample_rate))
+            .unwrap_or(sample_rate);
+        let processing_rate = self.oversampling.apply(processing_rate);
         self.state
-            .prepare(sample_rate, max_frames, bus_config)
-            .map_err(PluginError::InitializationFailed)
+            .prepare(sample_rate, processing_rate, max_frames, bus_config, &self.crash_guard)
+            .map_err(PluginError::InitializationFailed)?;
+        self.processing_rate = processing_rate;
+        // A state loaded before preparation is applied by prepare()
+        if let Some(processor) = self.state.processor_mut() {
+            self.prepared_bus_layout_id = P::bus_layout_id(processor.parameters());
+            processor.recompute_rate_dependent(processing_rate);
+            self.parameter_constraints.enforce_all(processor.parameters(), |_, _| {});
+        }
+        if let Some(cc_state) = self.state.midi_cc_state_mut() {
+            cc_state.set_response_curves(self.response_curves.clone());
+        }
+        let crossfade_ms = config.map(|config| config.preset_crossfade_ms).unwrap_or(0.0);
+        self.preset_crossfade.prepare(crossfade_ms, sample_rate);
+        self.activation.prepare(sample_rate);
+
+        let channels = |buses: &[beamer_core::CachedBusInfo]| -> Vec<usize> {
+            buses.iter().map(|bus| bus.channel_count).collect()
+        };
+        let inputs = channels(&bus_config.input_buses);
+        let outputs = channels(&bus_config.output_buses);
+        self.bus_config = Some(bus_config.clone());
+        // Declared layouts at the negotiated widths
+        let layout = |info: Option<beamer_core::BusInfo>, channels: Option<&usize>| {
+            info.map(|mut info| {
+                info.channel_count = channels.copied().unwrap_or(0) as u32;
+                info.layout()
+            })
+            .unwrap_or_default()
+        };
+        self.speaker_layouts = (
+            layout(self.declared_input_bus_info(0), inputs.first()),
+            layout(self.declared_output_bus_info(0), outputs.first()),
+        );
+        let max_frames = max_frames as usize;
+        self.resampler_f32 =
+            SampleRateAdapter::new(sample_rate, processing_rate, max_frames, &inputs, &outputs);
+        self.resampler_f64 =
+            SampleRateAdapter::new(sample_rate, processing_rate, max_frames, &inputs, &outputs);
+        let main_inputs = inputs.first().copied().unwrap_or(0);
+        self.trims_f32 = TrimStage::new(&self.standard_trims, main_inputs, max_frames);
+        self.trims_f64 = TrimStage::new(&self.standard_trims, main_inputs, max_frames);
+        let channel_mix = config.and_then(|config| config.channel_mix);
+        let main_outputs = outputs.first().copied().unwrap_or(0);
+        let mix_frames = max_frames.max(SampleRateAdapter::<f32>::processing_block_size(
+            max_frames,
+            processing_rate / sample_rate,
+        ));
+        self.channel_mix_f32 = ChannelMixStage::new(channel_mix, main_inputs, main_outputs, mix_frames);
+        self.channel_mix_f64 = ChannelMixStage::new(channel_mix, main_inputs, main_outputs, mix_frames);
+        self.control_rate.reset();
+        if processing_rate != sample_rate {
+            log::info!(
+                "Resampling from host rate {} Hz to {} Hz",
+                sample_rate,
+                processing_rate
+            );
+        }
+        Ok(())
     }
 
     fn deallocate_render_resources(&mut self) {
-        let _ = self.state.unprepare();
+        let layout_changed = self
+            .state
+            .processor()
+            .is_some_and(|processor| P::bus_layout_id(processor.parameters()) != self.prepared_bus_layout_id);
+        // A new bus layout rebuilds the processor; carry its state over
+        let _ = if layout_changed {
+            self.state.unprepare_keeping_state()
+        } else {
+            self.state.unprepare()
+        };
+        self.resampler_f32 = None;
+        self.resampler_f64 = None;
+        self.trims_f32 = None;
+        self.trims_f64 = None;
+        self.channel_mix_f32 = None;
+        self.channel_mix_f64 = None;
+        self.bus_config = None;
     }
 
     fn is_prepared(&self) -> bool {
@@ -167,26 +457,43 @@ where
             }
             AuState::Prepared { processor, .. } => {
                 // Use processor's save_state which includes custom state
-                processor.save_state().unwrap_or_default()
+                let mut data = processor.save_state().unwrap_or_default();
+                self.parameter_exposure.append_state(&mut data);
+                self.controller_pages.append_state(&mut data);
+                self.standard_trims.append_state(&mut data);
+                self.response_curves.append_state(&mut data);
+                data
             }
             AuState::Transitioning => Vec::new(),
         }
     }
 
     fn load_state(&mut self, data: &[u8]) -> PluginResult<()> {
+        // Restore the response curves, standard trims, the controller page
+        // and host promotions, stripping their trailers in reverse order.
+        let data = &data[..self.response_curves.load_state(data)];
+        let data = &data[..self.standard_trims.load_state(data)];
+        let data = &data[..self.controller_pages.load_state(data)];
+        let data = &data[..self.parameter_exposure.load_state(data)];
         match &mut self.state {
             AuState::Unprepared { pending_state, .. } => {
                 // Defer loading until prepare() is called
                 *pending_state = Some(data.to_vec());
+                self.parameter_log.record_state();
                 Ok(())
             }
             AuState::Prepared { processor, .. } => {
                 // Load state immediately and reset smoothing
-                processor
-                    .load_state(data)
-                    .map_err(|e| PluginError::StateError(e.to_string()))?;
+                self.crash_guard.load_state(processor, data).map_err(|e| {
+                    self.notifications
+                        .notify(NotifyLevel::Error, format!("The saved state could not be loaded: {e}"));
+                    PluginError::StateError(e.to_string())
+                })?;
                 use beamer_core::parameter_types::Parameters;
                 processor.parameters_mut().reset_smoothing();
+                processor.recompute_rate_dependent(self.processing_rate);
+                self.parameter_constraints.enforce_all(processor.parameters(), |_, _| {});
+                self.parameter_log.record_state();
                 Ok(())
             }
             AuState::Transitioning => {
@@ -197,69 +504,155 @@ where
 
     fn reset(&mut self) {
         if let Some(processor) = self.state.processor_mut() {
-            // Full reset sequence: deactivate then reactivate
-            // This matches VST3 behavior and beamer_core documentation
-            processor.set_active(false);
-            processor.set_active(true);
+            // Default is a full deactivate/activate cycle, matching VST3
+            processor.reset();
+        }
+        if let Some(resampler) = &mut self.resampler_f32 {
+            resampler.reset();
+        }
+        if let Some(resampler) = &mut self.resampler_f64 {
+            resampler.reset();
+        }
+        if let Some(trims) = &mut self.trims_f32 {
+            trims.reset();
+        }
+        if let Some(trims) = &mut self.trims_f64 {
+            trims.reset();
+        }
+        self.control_rate.reset();
+        if let Some(mpe_state) = &mut self.mpe_state {
+            mpe_state.reset();
         }
     }
 
     fn tail_samples(&self) -> u32 {
+        // Include the bypass ramp like VST3's getTailSamples. An infinite
+        // tail stays u32::MAX, which the ObjC/C wrappers report as INFINITY.
         self.state
             .processor()
-            .map(|p| p.tail_samples())
+            .map(|p| p.tail_time().to_samples().saturating_add(p.bypass_ramp_samples()))
             .unwrap_or(0)
     }
 
     fn latency_samples(&self) -> u32 {
-        self.state
+        let latency = self
+            .state
             .processor()
             .map(|p| p.latency_samples())
-            .unwrap_or(0)
+            .unwrap_or(0);
+        match &self.resampler_f32 {
+            Some(resampler) => resampler.latency_samples(latency),
+            None => latency,
+        }
     }
 
     fn supports_native_double_precision(&self) -> bool {
         self.state
             .processor()
-            .map(|p| p.supports_double_precision())
+            .map(|p| p.precision_policy().main_is_f64())
             .unwrap_or(false)
     }
 
     fn declared_input_bus_count(&self) -> usize {
         match &self.state {
             AuState::Unprepared { plugin, .. } => plugin.input_bus_count(),
-            // Deterministic fallback that doesn't disrupt the prepared processor.
-            _ => P::default().input_bus_count(),
+            // Snapshot of the current parameters; doesn't disrupt the prepared processor.
+            AuState::Prepared { processor, .. } => {
+                descriptor_snapshot::<P>(processor.parameters()).input_bus_count()
+            }
+            AuState::Transitioning => P::default().input_bus_count(),
         }
     }
 
     fn declared_output_bus_count(&self) -> usize {
         match &self.state {
             AuState::Unprepared { plugin, .. } => plugin.output_bus_count(),
-            _ => P::default().output_bus_count(),
+            AuState::Prepared { processor, .. } => {
+                descriptor_snapshot::<P>(processor.parameters()).output_bus_count()
+            }
+            AuState::Transitioning => P::default().output_bus_count(),
         }
     }
 
     fn declared_input_bus_info(&self, index: usize) -> Option<beamer_core::BusInfo> {
         match &self.state {
             AuState::Unprepared { plugin, .. } => plugin.input_bus_info(index),
-            _ => P::default().input_bus_info(index),
+            AuState::Prepared { processor, .. } => {
+                descriptor_snapshot::<P>(processor.parameters()).input_bus_info(index)
+            }
+            AuState::Transitioning => P::default().input_bus_info(index),
         }
     }
 
     fn declared_output_bus_info(&self, index: usize) -> Option<beamer_core::BusInfo> {
         match &self.state {
             AuState::Unprepared { plugin, .. } => plugin.output_bus_info(index),
-            _ => P::default().output_bus_info(index),
+            AuState::Prepared { processor, .. } => {
+                descriptor_snapshot::<P>(processor.parameters()).output_bus_info(index)
+            }
+            AuState::Transitioning => P::default().output_bus_info(index),
+        }
+    }
+
+    fn accepts_main_channels(&self, main_input_channels: u32, main_output_channels: u32) -> bool {
+        match &self.state {
+            AuState::Unprepared { plugin, .. } => {
+                accepts_main_channels(plugin, main_input_channels, main_output_channels)
+            }
+            AuState::Prepared { processor, .. } => accepts_main_channels(
+                &descriptor_snapshot::<P>(processor.parameters()),
+                main_input_channels,
+                main_output_channels,
+            ),
+            AuState::Transitioning => {
+                accepts_main_channels(&P::default(), main_input_channels, main_output_channels)
+            }
         }
     }
 
+    fn take_bus_layout_changed(&mut self) -> bool {
+        let id = match &self.state {
+            AuState::Unprepared { plugin, .. } => P::bus_layout_id(plugin.parameters()),
+            AuState::Prepared { processor, .. } => P::bus_layout_id(processor.parameters()),
+            AuState::Transitioning => return false,
+        };
+        if id == self.bus_layout_id {
+            return false;
+        }
+        self.bus_layout_id = id;
+        true
+    }
+
+    fn oversampling_changed(&self) -> bool {
+        self.state.is_prepared()
+            && (self.selected_oversampling() != Some(self.oversampling)
+                || self.selected_quality() != Some(self.quality))
+    }
+
+    fn apply_oversampling(&mut self) -> bool {
+        if !self.oversampling_changed() {
+            return false;
+        }
+        let (Some(sample_rate), Some(max_frames), Some(bus_config)) =
+            (self.state.sample_rate(), self.state.max_frames(), self.bus_config.clone())
+        else {
+            return false;
+        };
+        self.deallocate_render_resources();
+        if let Err(e) = self.allocate_render_resources(sample_rate, max_frames, &bus_config) {
+            log::error!("Failed to prepare with new oversampling factor or quality: {:?}", e);
+        }
+        true
+    }
+
     fn process(
         &mut self,
         inputs: &[&[f32]],
         outputs: &mut [&mut [f32]],
         num_samples: usize,
     ) -> PluginResult<()> {
+        self.begin_preset_crossfade();
+
         // Get processor and sample_rate from prepared state
         let (processor, sample_rate) = match &mut self.state {
             AuState::Prepared {
@@ -279,7 +672,8 @@ where
         // The Buffer::new takes iterators, so we convert slices to iterators
         let input_iter = inputs.iter().copied();
         let output_iter = outputs.iter_mut().map(|s| &mut **s);
-        let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+        let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+            .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
         // Build AuxiliaryBuffers (empty for now - sidechain support is future work)
         let mut aux = AuxiliaryBuffers::empty();
@@ -290,7 +684,21 @@ where
         let context = ProcessContext::new(sample_rate, num_samples, transport);
 
         // Call the actual processor
-        processor.process(&mut buffer, &mut aux, &context);
+        TrimStage::run(
+            self.trims_f32.as_mut(),
+            &mut buffer,
+            &mut aux,
+            &context,
+            |b, a, c| {
+                SampleRateAdapter::run(self.resampler_f32.as_mut(), b, a, c, |b, a, c| {
+                    ChannelMixStage::run(self.channel_mix_f32.as_mut(), b, a, c, |b, a, c| {
+                        self.control_rate.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                    })
+                })
+            },
+        );
+        self.preset_crossfade.finish(&mut buffer);
+        self.activation.finish(&mut buffer);
 
         Ok(())
     }
@@ -301,6 +709,8 @@ where
         outputs: &mut [&mut [f64]],
         num_samples: usize,
     ) -> PluginResult<()> {
+        self.begin_preset_crossfade();
+
         // Get processor, sample_rate and conversion_buffers from prepared state
         let (processor, sample_rate, conversion_buffers) = match &mut self.state {
             AuState::Prepared {
@@ -317,18 +727,41 @@ where
             }
         };
 
-        // Check if processor supports native f64 processing
-        if processor.supports_double_precision() {
-            // Native f64 processing
+        // Check if processor runs its main bus at f64 natively
+        let policy = processor.precision_policy();
+        if policy.main_is_f64() {
+            // Native f64 processing (aux buses converted for F64MainF32Aux)
             let input_iter = inputs.iter().copied();
             let output_iter = outputs.iter_mut().map(|s| &mut **s);
-            let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+            let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+                .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
             let mut aux = AuxiliaryBuffers::empty();
             let transport = Transport::default();
             let context = ProcessContext::new(sample_rate, num_samples, transport);
 
-            processor.process_f64(&mut buffer, &mut aux, &context);
+            TrimStage::run(
+                self.trims_f64.as_mut(),
+                &mut buffer,
+                &mut aux,
+                &context,
+                |b, a, c| {
+                    SampleRateAdapter::run(self.resampler_f64.as_mut(), b, a, c, |b, a, c| {
+                        ChannelMixStage::run(self.channel_mix_f64.as_mut(), b, a, c, |b, a, c| {
+                            self.control_rate.run(processor, b, a, c, |p, b, a, c| {
+                                match conversion_buffers.as_mut() {
+                                    Some(conversion) if !policy.aux_is_f64() => {
+                                        conversion.with_f32_aux(a, |a| p.process_f64_mixed(b, a, c))
+                                    }
+                                    _ => p.process_f64(b, a, c),
+                                }
+                            })
+                        })
+                    })
+                },
+            );
+            self.preset_crossfade.finish(&mut buffer);
+            self.activation.finish(&mut buffer);
         } else {
             // Convert f64 → f32 using pre-allocated buffers, process, convert back
             let conversion = conversion_buffers.as_mut().expect(
@@ -358,13 +791,28 @@ where
 
             let input_iter = input_f32_slices.iter().copied();
             let output_iter = output_f32_slices.iter_mut().map(|s| &mut **s);
-            let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+            let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+                .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
             let mut aux = AuxiliaryBuffers::empty();
             let transport = Transport::default();
             let context = ProcessContext::new(sample_rate, num_samples, transport);
 
-            processor.process(&mut buffer, &mut aux, &context);
+            TrimStage::run(
+                self.trims_f32.as_mut(),
+                &mut buffer,
+                &mut aux,
+                &context,
+                |b, a, c| {
+                    SampleRateAdapter::run(self.resampler_f32.as_mut(), b, a, c, |b, a, c| {
+                        ChannelMixStage::run(self.channel_mix_f32.as_mut(), b, a, c, |b, a, c| {
+                            self.control_rate.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                        })
+                    })
+                },
+            );
+            self.preset_crossfade.finish(&mut buffer);
+            self.activation.finish(&mut buffer);
 
             // Convert f32 → f64 back to output
             for (ch_idx, output_ch) in outputs.iter_mut().enumerate() {
@@ -385,6 +833,8 @@ where
         outputs: &mut [&mut [f64]],
         context: &ProcessContext,
     ) -> PluginResult<()> {
+        self.begin_preset_crossfade();
+
         // Get processor and conversion_buffers from prepared state
         let (processor, conversion_buffers) = match &mut self.state {
             AuState::Prepared {
@@ -402,15 +852,38 @@ where
 
         let num_samples = context.num_samples;
 
-        // Check if processor supports native f64 processing
-        if processor.supports_double_precision() {
-            // Native f64 processing
+        // Check if processor runs its main bus at f64 natively
+        let policy = processor.precision_policy();
+        if policy.main_is_f64() {
+            // Native f64 processing (aux buses converted for F64MainF32Aux)
             let input_iter = inputs.iter().copied();
             let output_iter = outputs.iter_mut().map(|s| &mut **s);
-            let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+            let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+                .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
             let mut aux = AuxiliaryBuffers::empty();
-            processor.process_f64(&mut buffer, &mut aux, context);
+            TrimStage::run(
+                self.trims_f64.as_mut(),
+                &mut buffer,
+                &mut aux,
+                context,
+                |b, a, c| {
+                    SampleRateAdapter::run(self.resampler_f64.as_mut(), b, a, c, |b, a, c| {
+                        ChannelMixStage::run(self.channel_mix_f64.as_mut(), b, a, c, |b, a, c| {
+                            self.control_rate.run(processor, b, a, c, |p, b, a, c| {
+                                match conversion_buffers.as_mut() {
+                                    Some(conversion) if !policy.aux_is_f64() => {
+                                        conversion.with_f32_aux(a, |a| p.process_f64_mixed(b, a, c))
+                                    }
+                                    _ => p.process_f64(b, a, c),
+                                }
+                            })
+                        })
+                    })
+                },
+            );
+            self.preset_crossfade.finish(&mut buffer);
+            self.activation.finish(&mut buffer);
         } else {
             // Convert f64 → f32 using pre-allocated buffers, process, convert back
             let conversion = conversion_buffers.as_mut().expect(
@@ -440,10 +913,25 @@ where
 
             let input_iter = input_f32_slices.iter().copied();
             let output_iter = output_f32_slices.iter_mut().map(|s| &mut **s);
-            let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+            let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+                .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
             let mut aux = AuxiliaryBuffers::empty();
-            processor.process(&mut buffer, &mut aux, context);
+            TrimStage::run(
+                self.trims_f32.as_mut(),
+                &mut buffer,
+                &mut aux,
+                context,
+                |b, a, c| {
+                    SampleRateAdapter::run(self.resampler_f32.as_mut(), b, a, c, |b, a, c| {
+                        ChannelMixStage::run(self.channel_mix_f32.as_mut(), b, a, c, |b, a, c| {
+                            self.control_rate.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                        })
+                    })
+                },
+            );
+            self.preset_crossfade.finish(&mut buffer);
+            self.activation.finish(&mut buffer);
 
             // Convert f32 → f64 back to output
             for (ch_idx, output_ch) in outputs.iter_mut().enumerate() {
@@ -458,6 +946,34 @@ where
         Ok(())
     }
 
+    fn process_with_midi(
+        &mut self,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        aux_inputs: &[Vec<&[f32]>],
+        aux_outputs: &mut [Vec<&mut [f32]>],
+        midi_events: &[MidiEvent],
+        context: &ProcessContext,
+    ) -> PluginResult<()> {
+        // Split at MIDI for processors that handle events between sub-blocks
+        // (the render already splits at parameter events)
+        self.record_split_midi(midi_events);
+        self.process_with_aux(inputs, outputs, aux_inputs, aux_outputs, context)
+    }
+
+    fn process_with_midi_f64(
+        &mut self,
+        inputs: &[&[f64]],
+        outputs: &mut [&mut [f64]],
+        aux_inputs: &[Vec<&[f64]>],
+        aux_outputs: &mut [Vec<&mut [f64]>],
+        midi_events: &[MidiEvent],
+        context: &ProcessContext,
+    ) -> PluginResult<()> {
+        self.record_split_midi(midi_events);
+        self.process_with_aux_f64(inputs, outputs, aux_inputs, aux_outputs, context)
+    }
+
     fn process_with_aux(
         &mut self,
         inputs: &[&[f32]],
@@ -466,6 +982,8 @@ where
         aux_outputs: &mut [Vec<&mut [f32]>],
         context: &ProcessContext,
     ) -> PluginResult<()> {
+        self.begin_preset_crossfade();
+
         // Get processor from prepared state
         let processor = match &mut self.state {
             AuState::Prepared { processor, .. } => processor,
@@ -482,7 +1000,8 @@ where
         // Build Buffer from input/output slices
         let input_iter = inputs.iter().copied();
         let output_iter = outputs.iter_mut().map(|s| &mut **s);
-        let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+        let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+            .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
         // Build AuxiliaryBuffers from aux input/output slices
         let aux_input_iter = aux_inputs.iter().map(|bus| bus.iter().copied());
@@ -492,7 +1011,21 @@ where
         let mut aux = AuxiliaryBuffers::new(aux_input_iter, aux_output_iter, num_samples);
 
         // Call the actual processor
-        processor.process(&mut buffer, &mut aux, context);
+        TrimStage::run(
+            self.trims_f32.as_mut(),
+            &mut buffer,
+            &mut aux,
+            context,
+            |b, a, c| {
+                SampleRateAdapter::run(self.resampler_f32.as_mut(), b, a, c, |b, a, c| {
+                    ChannelMixStage::run(self.channel_mix_f32.as_mut(), b, a, c, |b, a, c| {
+                        self.control_rate.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                    })
+                })
+            },
+        );
+        self.preset_crossfade.finish(&mut buffer);
+        self.activation.finish(&mut buffer);
 
         Ok(())
     }
@@ -505,6 +1038,8 @@ where
         aux_outputs: &mut [Vec<&mut [f64]>],
         context: &ProcessContext,
     ) -> PluginResult<()> {
+        self.begin_preset_crossfade();
+
         // Get processor and conversion_buffers from prepared state
         let (processor, conversion_buffers) = match &mut self.state {
             AuState::Prepared {
@@ -522,12 +1057,14 @@ where
 
         let num_samples = context.num_samples;
 
-        // Check if processor supports native f64 processing
-        if processor.supports_double_precision() {
-            // Native f64 processing
+        // Check if processor runs its main bus at f64 natively
+        let policy = processor.precision_policy();
+        if policy.main_is_f64() {
+            // Native f64 processing (aux buses converted for F64MainF32Aux)
             let input_iter = inputs.iter().copied();
             let output_iter = outputs.iter_mut().map(|s| &mut **s);
-            let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+            let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+                .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
             let aux_input_iter = aux_inputs.iter().map(|bus| bus.iter().copied());
             let aux_output_iter = aux_outputs
@@ -535,7 +1072,28 @@ where
                 .map(|bus| bus.iter_mut().map(|s| &mut **s));
             let mut aux = AuxiliaryBuffers::new(aux_input_iter, aux_output_iter, num_samples);
 
-            processor.process_f64(&mut buffer, &mut aux, context);
+            TrimStage::run(
+                self.trims_f64.as_mut(),
+                &mut buffer,
+                &mut aux,
+                context,
+                |b, a, c| {
+                    SampleRateAdapter::run(self.resampler_f64.as_mut(), b, a, c, |b, a, c| {
+                        ChannelMixStage::run(self.channel_mix_f64.as_mut(), b, a, c, |b, a, c| {
+                            self.control_rate.run(processor, b, a, c, |p, b, a, c| {
+                                match conversion_buffers.as_mut() {
+                                    Some(conversion) if !policy.aux_is_f64() => {
+                                        conversion.with_f32_aux(a, |a| p.process_f64_mixed(b, a, c))
+                                    }
+                                    _ => p.process_f64(b, a, c),
+                                }
+                            })
+                        })
+                    })
+                },
+            );
+            self.preset_crossfade.finish(&mut buffer);
+            self.activation.finish(&mut buffer);
         } else {
             // Convert f64 → f32 using pre-allocated buffers, process, convert back
             let conversion = conversion_buffers.as_mut().expect(
@@ -590,7 +1148,8 @@ where
             // Build Buffer and AuxiliaryBuffers
             let input_iter = input_f32_slices.iter().copied();
             let output_iter = output_f32_slices.iter_mut().map(|s| &mut **s);
-            let mut buffer = Buffer::new(input_iter, output_iter, num_samples);
+            let mut buffer = Buffer::new(input_iter, output_iter, num_samples)
+                .with_speaker_layouts(self.speaker_layouts.0, self.speaker_layouts.1);
 
             let aux_input_iter = aux_input_f32_slices.iter().map(|bus| bus.iter().copied());
             let aux_output_iter = aux_output_f32_slices
@@ -598,7 +1157,21 @@ where
                 .map(|bus| bus.iter_mut().map(|s| &mut **s));
             let mut aux = AuxiliaryBuffers::new(aux_input_iter, aux_output_iter, num_samples);
 
-            processor.process(&mut buffer, &mut aux, context);
+            TrimStage::run(
+                self.trims_f32.as_mut(),
+                &mut buffer,
+                &mut aux,
+                context,
+                |b, a, c| {
+                    SampleRateAdapter::run(self.resampler_f32.as_mut(), b, a, c, |b, a, c| {
+                        ChannelMixStage::run(self.channel_mix_f32.as_mut(), b, a, c, |b, a, c| {
+                            self.control_rate.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                        })
+                    })
+                },
+            );
+            self.preset_crossfade.finish(&mut buffer);
+            self.activation.finish(&mut buffer);
 
             // Convert main outputs f32 → f64
             for (ch_idx, output_ch) in outputs.iter_mut().enumerate() {
@@ -677,10 +1250,22 @@ where
             // Convert AU parameter address to beamer parameter ID
             // AU parameter addresses map directly to beamer parameter IDs
             let param_id = event.parameter_address as u32;
+            host_trace::record(
+                self.trace_id,
+                HostCall::ProcessParameter {
+                    id: param_id,
+                    value: event.value as f64,
+                    sample_offset: event.sample_offset,
+                },
+            );
 
-            if let Some(param) = processor.parameters_mut().by_id(param_id) {
+            let param = processor.parameters_mut().by_id(param_id);
+            if let Some(param) = param.or_else(|| self.standard_trims.by_id(param_id)) {
                 param.set_normalized(event.value as f64);
+                self.automation_preview.host_value(param_id, event.value as f64);
+                self.parameter_log.record(param_id, event.value as f64, ParameterChangeSource::Automation);
             }
+            self.parameter_constraints.enforce(processor.parameters(), param_id, |_, _| {});
         }
 
         // Apply parameter ramps
@@ -689,10 +1274,23 @@ where
         // uses a fixed time constant configured at parameter construction.
         for event in ramps {
             let param_id = event.parameter_address as u32;
+            host_trace::record(
+                self.trace_id,
+                HostCall::ProcessParameter {
+                    id: param_id,
+                    value: event.end_value as f64,
+                    sample_offset: event.sample_offset,
+                },
+            );
 
-            if let Some(param) = processor.parameters_mut().by_id(param_id) {
+            let param = processor.parameters_mut().by_id(param_id);
+            if let Some(param) = param.or_else(|| self.standard_trims.by_id(param_id)) {
                 param.set_normalized(event.end_value as f64);
+                self.automation_preview.host_value(param_id, event.end_value as f64);
+                self.parameter_log
+                    .record(param_id, event.end_value as f64, ParameterChangeSource::Automation);
             }
+            self.parameter_constraints.enforce(processor.parameters(), param_id, |_, _| {});
         }
 
         Ok(())
@@ -702,9 +1300,21 @@ where
         self.state.midi_cc_state()
     }
 
+    fn begin_block(&mut self, num_samples: usize) {
+        if let Some(processor) = self.state.processor_mut() {
+            processor.begin_block(num_samples);
+        }
+    }
+
     fn process_midi(&mut self, input: &[MidiEvent], output: &mut crate::render::MidiBuffer) {
         use beamer_core::MidiEventKind;
 
+        // Collapse MPE member channels into per-note events
+        let input = match &mut self.mpe_state {
+            Some(mpe_state) => mpe_state.process(input),
+            None => input,
+        };
+
         // Check if we have factory presets for automatic MIDI PC mapping
         let preset_count = Presets::count();
 
@@ -757,8 +1367,15 @@ where
                     .filter_map(|event| {
                         if let MidiEventKind::ProgramChange(pc) = &event.event {
                             if (pc.program as usize) < preset_count {
-                                // Apply the factory preset
-                                Presets::apply(pc.program as usize, processor.parameters());
+                                // Apply the factory preset (faded when a
+                                // preset crossfade is configured)
+                                if self.preset_crossfade.is_enabled() {
+                                    self.preset_crossfade.request(pc.program as usize);
+                                } else {
+                                    Presets::apply(pc.program as usize, processor.parameters());
+                                    self.parameter_constraints.enforce_all(processor.parameters(), |_, _| {});
+                                    self.parameter_log.record_preset(pc.program as usize);
+                                }
                                 // Filter out this event - it's been handled
                                 return None;
                             }
@@ -770,6 +1387,9 @@ where
 
                 // Process remaining events through the plugin
                 processor.process_midi(&filtered, core_output);
+                processor
+                    .midi_thru()
+                    .apply(&filtered, core_output, |e| processor.consumes_midi(e));
 
                 // Move events to AU's MidiBuffer (avoids cloning Box<SysEx>).
                 for event in core_output.drain() {
@@ -781,6 +1401,9 @@ where
 
         // No PC filtering needed - process all events directly
         processor.process_midi(input, core_output);
+        processor
+            .midi_thru()
+            .apply(input, core_output, |e| processor.consumes_midi(e));
 
         // Move events to AU's MidiBuffer (avoids cloning Box<SysEx>).
         for event in core_output.drain() {
@@ -788,20 +1411,166 @@ where
         }
     }
 
+    fn generate_midi_clock(
+        &mut self,
+        transport: &Transport,
+        sample_rate: f64,
+        num_samples: usize,
+        output: &mut crate::render::MidiBuffer,
+    ) {
+        let (processor, core_output) = match &mut self.state {
+            AuState::Prepared {
+                processor,
+                midi_output_buffer,
+                ..
+            } => (processor, midi_output_buffer.as_mut()),
+            _ => return,
+        };
+
+        core_output.clear();
+        self.midi_clock.generate(
+            processor.midi_clock_output(),
+            transport,
+            sample_rate,
+            num_samples,
+            core_output,
+        );
+        if core_output.is_empty() {
+            return;
+        }
+
+        // Merge into the plugin's output, keeping sample order.
+        for event in core_output.drain() {
+            let _ = output.push(event);
+        }
+        output.sort_by_sample_offset();
+    }
+
+    fn track_transport(&mut self, transport: &Transport, num_samples: usize) {
+        if let Some(sample_rate) = self.state.sample_rate() {
+            self.frame_meters.playhead().update(transport, sample_rate, num_samples);
+        }
+        if let Some(processor) = self.state.processor_mut() {
+            self.transport_tracker.dispatch(processor, transport, num_samples);
+        }
+    }
+
+    fn record_active_voices(&self) {
+        if let Some(processor) = self.state.processor() {
+            self.processing_stats.set_active_voices(processor.active_voices());
+        }
+    }
+
+    fn panic(&mut self) {
+        if let Some(processor) = self.state.processor_mut() {
+            processor.panic();
+        }
+    }
+
     fn webview_handler(&self) -> Option<Arc<dyn WebViewHandler>> {
         self.webview_handler.clone()
     }
 
+    fn editor(&self) -> GuiBackend {
+        self.editor.clone()
+    }
+
+    fn crash_guard(&self) -> CrashGuard {
+        self.crash_guard.clone()
+    }
+
+    fn parameter_exposure(&self) -> ParameterExposure {
+        self.parameter_exposure.clone()
+    }
+
+    fn controller_pages(&self) -> ControllerPageState {
+        self.controller_pages.clone()
+    }
+
+    fn parameter_constraints(&self) -> ConstraintState {
+        self.parameter_constraints.clone()
+    }
+
+    fn preset_crossfade(&self) -> Arc<PresetCrossfade> {
+        Arc::clone(&self.preset_crossfade)
+    }
+
+    fn response_curves(&self) -> ResponseCurves {
+        self.response_curves.clone()
+    }
+
+    fn note_ranges(&self) -> &NoteRanges {
+        &self.note_ranges
+    }
+
+    fn standard_trims(&self) -> StandardTrims {
+        self.standard_trims.clone()
+    }
+
+    fn processing_stats(&self) -> ProcessingStats {
+        self.processing_stats.clone()
+    }
+
+    fn automation_preview(&self) -> AutomationPreview {
+        self.automation_preview.clone()
+    }
+
+    fn autosave(&self) -> Autosave {
+        self.autosave.clone()
+    }
+
+    fn parameter_log(&self) -> ParameterLog {
+        self.parameter_log.clone()
+    }
+
+    fn midi_monitor(&self) -> MidiMonitor {
+        self.midi_monitor.clone()
+    }
+
+    fn midi_panic(&self) -> MidiPanic {
+        self.midi_panic.clone()
+    }
+
+    fn host_services(&self) -> HostServices {
+        self.host_services.clone()
+    }
+
+    fn activation(&self) -> Activation {
+        self.activation.activation().clone()
+    }
+
+    fn frame_meters(&self) -> FrameMeters {
+        self.frame_meters.clone()
+    }
+
+    fn gui_data(&self) -> GuiDataChannels {
+        self.gui_data.clone()
+    }
+
+    fn notifications(&self) -> Notifications {
+        self.notifications.clone()
+    }
+
+    fn watchdog(&self) -> ProcessWatchdog {
+        self.watchdog.clone()
+    }
+
+    fn trace_id(&self) -> u32 {
+        self.trace_id
+    }
+
     fn preset_count(&self) -> u32 {
         Presets::count() as u32
     }
 
-    fn preset_info(&self, index: u32) -> Option<(i32, &str)> {
-        if (index as usize) < Presets::count() {
-            Presets::info(index as usize).map(|info| (index as i32, info.name))
-        } else {
-            None
-        }
+    fn preset_info(&mut self, index: u32) -> Option<(i32, &str)> {
+        self.preset_names
+            .name::<Presets>(index as usize)
+            .map(|name| (index as i32, name))
+    }
+
+    fn take_preset_list_changed(&mut self) -> bool {
+        self.preset_names.take_list_changed::<Presets>()
     }
 
     fn apply_preset(&self, index: u32) -> bool {
@@ -809,13 +1578,45 @@ where
         // Hosts may re-send the same preset and skipping would break preset 0.
         let params = match &self.state {
             AuState::Unprepared { plugin, .. } => plugin.parameters(),
-            AuState::Prepared { processor, .. } => processor.parameters(),
+            AuState::Prepared { processor, .. } => {
+                // While rendering, let the audio thread apply it inside the
+                // declicking fade.
+                if self.preset_crossfade.is_enabled() {
+                    if (index as usize) >= Presets::count() {
+                        return false;
+                    }
+                    self.preset_crossfade.request(index as usize);
+                    return true;
+                }
+                processor.parameters()
+            }
             AuState::Transitioning => return false,
         };
-        Presets::apply(index as usize, params)
+        let applied = Presets::apply(index as usize, params);
+        if applied {
+            self.parameter_constraints.enforce_all(params, |_, _| {});
+            self.parameter_log.record_preset(index as usize);
+        }
+        applied
     }
 }
 
+/// Returns true if `plugin` runs with the given main channel counts and
+/// its declared aux buses.
+fn accepts_main_channels<P: Descriptor>(plugin: &P, main_input_channels: u32, main_output_channels: u32) -> bool {
+    let channels = |main: u32, info: Option<beamer_core::BusInfo>, bus: usize| match bus {
+        0 => main,
+        _ => info.map_or(0, |info| info.channel_count),
+    };
+    let inputs: Vec<u32> = (0..plugin.input_bus_count())
+        .map(|bus| channels(main_input_channels, plugin.input_bus_info(bus), bus))
+        .collect();
+    let outputs: Vec<u32> = (0..plugin.output_bus_count())
+        .map(|bus| channels(main_output_channels, plugin.output_bus_info(bus), bus))
+        .collect();
+    negotiate_bus_layout(plugin, &inputs, &outputs).is_some()
+}
+
 /// Factory function type for creating AU processor instances.
 ///
 /// Used by the export macro to register plugin factories.
@@ -867,18 +1668,10 @@ mod tests {
                 id,
                 name,
                 value: AtomicU64::new(default.to_bits()),
-                info: ParameterInfo {
-                    id,
-                    string_id: "",
-                    name,
-                    short_name: name,
-                    units: "dB",
-                    unit: ParameterUnit::Decibels,
-                    step_count: 0,
-                    default_normalized: default,
-                    flags: ParameterFlags::default(),
-                    group_id: 0,
-                },
+                info: ParameterInfo::new(id, name)
+                    .with_units("dB")
+                    .with_unit(ParameterUnit::Decibels)
+                    .with_default(default),
             }
         }
     }
@@ -1039,6 +1832,7 @@ mod tests {
     #[derive(Default)]
     struct TestPlugin {
         parameters: TestParameters,
+        host: HostServices,
     }
 
     impl Default for TestParameters {
@@ -1067,9 +1861,15 @@ mod tests {
         fn prepare(self, _setup: ()) -> Self::Processor {
             TestProcessor {
                 parameters: self.parameters,
+                host: self.host,
+                latency: 0,
             }
         }
 
+        fn host_services(&self) -> HostServices {
+            self.host.clone()
+        }
+
         fn input_bus_count(&self) -> usize {
             1
         }
@@ -1088,6 +1888,9 @@ mod tests {
 
     struct TestProcessor {
         parameters: TestParameters,
+        host: HostServices,
+        /// Set by loading state, like a saved lookahead time.
+        latency: u32,
     }
 
     impl HasParameters for TestProcessor {
@@ -1119,9 +1922,17 @@ mod tests {
             Ok(vec![])
         }
 
-        fn load_state(&mut self, _data: &[u8]) -> PluginResult<()> {
+        fn load_state(&mut self, data: &[u8]) -> PluginResult<()> {
+            if let Some(&latency) = data.first() {
+                self.latency = latency as u32;
+                self.host.latency_changed();
+            }
             Ok(())
         }
+
+        fn latency_samples(&self) -> u32 {
+            self.latency
+        }
     }
 
     // =========================================================================
@@ -1213,6 +2024,55 @@ mod tests {
             .set_normalized(gain_id, value);
     }
 
+    // =========================================================================
+    // Latency Change Tests
+    // =========================================================================
+
+    #[test]
+    fn latency_change_reaches_the_wrapper() {
+        let mut processor = create_prepared_processor();
+        // The bridge takes the flag from the same handle
+        let wrapper = processor.host_services();
+        assert_eq!(processor.latency_samples(), 0);
+        assert!(!wrapper.take_latency_changed());
+
+        processor.load_state(&[64]).expect("Failed to load state");
+        assert!(wrapper.take_latency_changed());
+        assert!(!wrapper.take_latency_changed());
+        assert_eq!(processor.latency_samples(), 64);
+    }
+
+    // =========================================================================
+    // Preset Crossfade Tests
+    // =========================================================================
+
+    #[test]
+    fn crossfaded_preset_is_reported_to_the_wrapper() {
+        let mut processor = create_prepared_processor();
+        // The bridge takes the flag from the same fade
+        let wrapper = processor.preset_crossfade();
+        processor.preset_crossfade.prepare(2.0, 44100.0);
+        set_gain_normalized(&mut processor, 1.0);
+
+        // Queued for the audio thread, not applied yet
+        assert!(processor.apply_preset(1));
+        assert!(!wrapper.take_applied());
+        assert_eq!(get_gain_normalized(&processor), 1.0);
+
+        // One block fades out, the next applies the preset at silence
+        let input = vec![0.0f32; 64];
+        let mut left = vec![0.0f32; 64];
+        let mut right = vec![0.0f32; 64];
+        for _ in 0..3 {
+            processor
+                .process(&[&input[..], &input[..]], &mut [&mut left[..], &mut right[..]], 64)
+                .expect("Failed to process");
+        }
+        assert!((get_gain_normalized(&processor) - 0.25).abs() < 0.01);
+        assert!(wrapper.take_applied());
+        assert!(!wrapper.take_applied());
+    }
+
     // =========================================================================
     // MIDI Program Change → Factory Preset Tests
     // =========================================================================
@@ -24,7 +24,7 @@
 //! ## Block Types Called
 //!
 //! 1. **AUHostMusicalContextBlock** (transport.rs): Query tempo, time signature, position
-//! 2. **AUHostTransportStateBlock** (render.rs): Query play/stop/record state
+//! 2. **AUHostTransportStateBlock** (transport.rs): Query play/stop/record/cycle state
 //! 3. **AURenderPullInputBlock** (render.rs): Pull audio from auxiliary buses
 //! 4. **AUScheduleMIDIEventBlock** (render.rs): Send MIDI output to host
 
@@ -37,11 +37,12 @@ use std::sync::{Arc, Mutex};
 use crate::buffer_storage::{ProcessBufferStorage, ProcessBufferStorageAuExt};
 use crate::buffers::{AudioBuffer, AudioBufferList};
 use crate::error::os_status;
+use crate::event_time::{buffer_start_sample, event_sample_offset};
 use crate::instance::AuPluginInstance;
 use crate::objc_block;
 use crate::transport::extract_transport_from_au;
 use beamer_core::{
-    MidiEvent, MidiEventKind, ProcessContext, Sample, SysExOutputPool, MAX_BUSES, MAX_CHANNELS,
+    MidiDirection, MidiEvent, MidiEventKind, ProcessContext, Sample, SysExOutputPool, MAX_BUSES, MAX_CHANNELS,
 };
 
 // =============================================================================
@@ -129,6 +130,12 @@ impl MidiBuffer {
     pub fn has_overflowed(&self) -> bool {
         self.events.len() >= self.capacity
     }
+
+    /// Keep only the events for which `keep` returns true, in order.
+    #[inline]
+    pub fn retain(&mut self, keep: impl FnMut(&MidiEvent) -> bool) {
+        self.events.retain(keep);
+    }
 }
 
 impl Default for MidiBuffer {
@@ -476,19 +483,20 @@ pub struct AudioTimeStamp {
 ///
 /// These flags are returned from the AUHostTransportStateBlock callback
 /// to indicate the current transport state (playing, recording, cycling).
+/// Pointer sized, matching the `NSUInteger`-backed `AUHostTransportStateFlags`.
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct AUHostTransportStateFlags(pub u32);
+pub struct AUHostTransportStateFlags(pub usize);
 
 impl AUHostTransportStateFlags {
     /// Transport state has changed since last query
-    pub const CHANGED: u32 = 1 << 0;
+    pub const CHANGED: usize = 1 << 0;
     /// Transport is moving (playing/recording)
-    pub const MOVING: u32 = 1 << 1;
+    pub const MOVING: usize = 1 << 1;
     /// Transport is currently recording
-    pub const RECORDING: u32 = 1 << 2;
+    pub const RECORDING: usize = 1 << 2;
     /// Transport is cycling (looping)
-    pub const CYCLING: u32 = 1 << 3;
+    pub const CYCLING: usize = 1 << 3;
 
     /// Check if transport is moving (playing)
     #[inline]
@@ -654,11 +662,17 @@ const MAX_EVENTS_PER_BUFFER: usize = 4096;
 /// Extract MIDI events from AU render event linked list.
 ///
 /// Iterates through the event list and converts MIDI events to beamer format.
-/// Handles both legacy MIDI 1.0 events and MIDI 2.0 UMP events.
+/// Handles both legacy MIDI 1.0 events and MIDI 2.0 UMP events. Event times
+/// are converted to offsets in `0..frame_count` (see [`event_sample_offset`]).
 ///
 /// # Safety
 /// The `event_list` pointer must be valid or null.
-pub unsafe fn extract_midi_events(event_list: *const AURenderEvent, buffer: &mut MidiBuffer) {
+pub unsafe fn extract_midi_events(
+    event_list: *const AURenderEvent,
+    buffer: &mut MidiBuffer,
+    buffer_start: i64,
+    frame_count: usize,
+) {
     let mut event_ptr = event_list;
     let mut iterations = 0;
 
@@ -677,7 +691,8 @@ pub unsafe fn extract_midi_events(event_list: *const AURenderEvent, buffer: &mut
                 // SAFETY: event_type == 8 (Midi), so midi field is the active union variant.
                 let midi_event = unsafe { &event.midi };
                 if midi_event.length >= 1 {
-                    let sample_offset = midi_event.event_sample_time as u32;
+                    let sample_offset =
+                        event_sample_offset(midi_event.event_sample_time, buffer_start, frame_count);
                     let status = midi_event.data[0] & 0xF0;
                     let channel = midi_event.data[0] & 0x0F;
                     let data1 = if midi_event.length >= 2 {
@@ -702,7 +717,8 @@ pub unsafe fn extract_midi_events(event_list: *const AURenderEvent, buffer: &mut
             10 => {
                 // AURenderEventType::MidiEventList
                 // SAFETY: head is always safe to access.
-                let sample_offset = unsafe { event.head.event_sample_time as u32 };
+                let sample_offset =
+                    event_sample_offset(unsafe { event.head.event_sample_time }, buffer_start, frame_count);
                 // Get pointer to MIDIEventList (immediately after AUMIDIEventList header)
                 // SAFETY: For MidiEventList events, the MIDIEventList follows the header.
                 let event_list_ptr = unsafe {
@@ -730,7 +746,19 @@ pub unsafe fn extract_midi_events(event_list: *const AURenderEvent, buffer: &mut
                     // Parse UMP words
                     for &word in words {
                         let message_type = (word >> 28) & 0xF;
-                        if message_type == 2 {
+                        if message_type == 1 {
+                            // System real-time/common in UMP format
+                            let status = ((word >> 16) & 0xFF) as u8;
+                            if let Some(beamer_event) = MidiEvent::from_midi1_bytes(
+                                sample_offset,
+                                status & 0xF0,
+                                status & 0x0F,
+                                0,
+                                0,
+                            ) {
+                                buffer.push(beamer_event);
+                            }
+                        } else if message_type == 2 {
                             // MIDI 1.0 Channel Voice in UMP format
                             let status = ((word >> 16) & 0xF0) as u8;
                             let channel = ((word >> 16) & 0x0F) as u8;
@@ -817,13 +845,16 @@ fn update_midi_cc_state(
 /// Extract parameter events from AU render event linked list.
 ///
 /// Iterates through the event list and extracts parameter change and ramp events.
-/// MIDI and other event types are ignored by this function.
+/// MIDI and other event types are ignored by this function. Event times are
+/// converted to offsets in `0..frame_count` (see [`event_sample_offset`]).
 ///
 /// # Safety
 /// The `event_list` pointer must be valid or null.
 pub unsafe fn extract_parameter_events(
     event_list: *const AURenderEvent,
     buffer: &mut ParameterEventBuffer,
+    buffer_start: i64,
+    frame_count: usize,
 ) {
     buffer.clear();
 
@@ -844,7 +875,11 @@ pub unsafe fn extract_parameter_events(
                 // SAFETY: event_type == 1 (Parameter), so parameter field is the active variant.
                 let param_event = unsafe { &event.parameter };
                 buffer.immediate.push(AuParameterEvent {
-                    sample_offset: param_event.event_sample_time as u32,
+                    sample_offset: event_sample_offset(
+                        param_event.event_sample_time,
+                        buffer_start,
+                        frame_count,
+                    ),
                     parameter_address: param_event.parameter_address,
                     value: param_event.value,
                 });
@@ -854,7 +889,11 @@ pub unsafe fn extract_parameter_events(
                 // SAFETY: event_type == 2 (ParameterRamp), so ramp field is the active variant.
                 let ramp_event = unsafe { &event.ramp };
                 buffer.ramps.push(AuParameterRampEvent {
-                    sample_offset: ramp_event.event_sample_time as u32,
+                    sample_offset: event_sample_offset(
+                        ramp_event.event_sample_time,
+                        buffer_start,
+                        frame_count,
+                    ),
                     parameter_address: ramp_event.parameter_address,
                     start_value: ramp_event.value,
                     end_value: ramp_event.end_value,
@@ -904,6 +943,8 @@ pub trait RenderBlockTrait: Send + Sync {
     /// * `event_list` - Linked list of render events (MIDI, parameter changes)
     /// * `pull_input_block` - Block to pull aux bus inputs
     /// * `input_data` - Input audio buffer list (already pulled by ObjC)
+    /// * `musical_context_block` - Host musical context block (or null)
+    /// * `transport_state_block` - Host transport state block (or null)
     fn process(
         &self,
         action_flags: *mut u32,
@@ -914,6 +955,8 @@ pub trait RenderBlockTrait: Send + Sync {
         event_list: *const AURenderEvent,
         pull_input_block: *const c_void,
         input_data: *const AudioBufferList,
+        musical_context_block: *const c_void,
+        transport_state_block: *const c_void,
     ) -> i32;
 
     /// Get a raw pointer to this render block.
@@ -938,10 +981,6 @@ pub struct RenderBlock<S: Sample> {
     midi_buffer: UnsafeCell<MidiBuffer>,
     /// Pre-allocated parameter event buffer for zero-allocation parameter automation
     parameter_events: UnsafeCell<ParameterEventBuffer>,
-    /// Musical context block from AU host for transport info
-    musical_context_block: Option<*const c_void>,
-    /// Transport state block from AU host for playback state (is_playing, etc.)
-    transport_state_block: Option<*const c_void>,
     /// Current sample rate for ProcessContext
     sample_rate: f64,
     /// Pre-allocated AudioBufferList structures for pulling aux input buses
@@ -1051,8 +1090,6 @@ impl<S: Sample> RenderBlock<S> {
     ///
     /// * `plugin` - Arc-wrapped plugin instance for audio processing
     /// * `storage` - Pre-allocated buffer storage (created from bus config)
-    /// * `musical_context_block` - Optional AU host musical context block for transport info
-    /// * `transport_state_block` - Optional AU host transport state block for playback state
     /// * `schedule_midi_event_block` - Optional AU host MIDI output block (for instruments/MIDI effects)
     /// * `max_frames` - Maximum frames per render call
     /// * `sample_rate` - Current sample rate in Hz
@@ -1062,8 +1099,6 @@ impl<S: Sample> RenderBlock<S> {
     pub fn new(
         plugin: Arc<Mutex<Box<dyn AuPluginInstance>>>,
         storage: ProcessBufferStorage<S>,
-        musical_context_block: Option<*const c_void>,
-        transport_state_block: Option<*const c_void>,
         schedule_midi_event_block: Option<*const c_void>,
         max_frames: u32,
         sample_rate: f64,
@@ -1103,8 +1138,6 @@ impl<S: Sample> RenderBlock<S> {
             storage: UnsafeCell::new(storage),
             midi_buffer: UnsafeCell::new(MidiBuffer::with_capacity(1024)),
             parameter_events: UnsafeCell::new(ParameterEventBuffer::new()),
-            musical_context_block,
-            transport_state_block,
             sample_rate,
             aux_input_buffer_lists: UnsafeCell::new(aux_input_buffer_lists),
             midi_output: UnsafeCell::new(MidiBuffer::with_capacity(1024)),
@@ -1323,8 +1356,9 @@ impl<S: Sample> RenderBlock<S> {
     /// - 0xC0: Program Change
     /// - 0xD0: Channel Pressure (Aftertouch)
     /// - 0xE0: Pitch Bend Change
+    /// - 0xF8-0xFF: System Real-Time (clock, start/stop; no channel)
     ///
-    /// All status bytes are OR'd with the channel (0x00-0x0F) to create the final status byte.
+    /// All channel status bytes are OR'd with the channel (0x00-0x0F) to create the final status byte.
     fn encode_midi_event(event: &MidiEventKind) -> Option<[u8; 3]> {
         match event {
             MidiEventKind::NoteOn(note) => Some([
@@ -1364,6 +1398,8 @@ impl<S: Sample> RenderBlock<S> {
                 pc.program & 0x7F,
                 0, // Unused third byte (2-byte message)
             ]),
+            // Single status byte; see output_all_midi_events for the length
+            MidiEventKind::SystemRealtime(message) => Some([message.status(), 0, 0]),
             // SysEx requires separate handling via output_sysex_to_host
             MidiEventKind::SysEx(_) => None,
             // The following event types don't have standard MIDI 1.0 wire encodings
@@ -1438,6 +1474,7 @@ impl<S: Sample> RenderBlock<S> {
                             MidiEventKind::ProgramChange(_) | MidiEventKind::ChannelPressure(_) => {
                                 2
                             }
+                            MidiEventKind::SystemRealtime(_) => 1,
                             _ => 3,
                         };
                         if !self.output_midi_to_host(&bytes[..len], sample_offset) {
@@ -1453,6 +1490,67 @@ impl<S: Sample> RenderBlock<S> {
         dropped
     }
 
+    /// Send the block's MIDI output to the host and log anything dropped.
+    fn send_midi_output(&self, midi_output: &MidiBuffer, sysex_pool: &mut SysExOutputPool) {
+        // Handle MIDI output via scheduleMIDIEventBlock (if available)
+        //
+        // AU MIDI output depends on component type:
+        // - `aumu` (Music Device/Instrument): MIDI output supported via scheduleMIDIEventBlock
+        // - `aumf` (MIDI Effect): MIDI output supported
+        // - `aufx` (Effect): MIDI output NOT typically supported by hosts
+        //
+        // For effects, most hosts don't provide scheduleMIDIEventBlock, so MIDI output
+        // events will be dropped with a warning.
+
+        // First, allocate SysEx messages to the pool for stable pointers
+        for midi_event in midi_output.iter() {
+            if let MidiEventKind::SysEx(sysex) = &midi_event.event {
+                // Allocate from pool for stable pointer during output
+                let _ = sysex_pool.allocate_slice(sysex.as_slice());
+            }
+        }
+
+        // Now output all MIDI events to the host
+        let dropped_events = self.output_all_midi_events(midi_output, sysex_pool);
+
+        // Log warnings for dropped events
+        if dropped_events > 0 {
+            if self.schedule_midi_event_block.is_none() {
+                // No MIDI output block - this is expected for effect plugins (aufx)
+                // Only log at debug level to avoid spamming for effect plugins that
+                // generate MIDI output (which is unusual but possible)
+                log::debug!(
+                    "AU MIDI output not available: {} events dropped. \
+                     MIDI output is only supported for instrument (aumu) and MIDI effect (aumf) plugins. \
+                     Effects (aufx) typically do not support MIDI output.",
+                    dropped_events
+                );
+            } else {
+                // Block is available but events still dropped (shouldn't happen)
+                log::warn!(
+                    "MIDI output error: {} events could not be sent to host",
+                    dropped_events
+                );
+            }
+        }
+
+        // Check for MIDI output buffer overflow
+        if midi_output.has_overflowed() {
+            log::warn!(
+                "MIDI output buffer overflow: {} events reached capacity, some events were dropped",
+                midi_output.len()
+            );
+        }
+
+        // Check for SysEx pool overflow
+        if sysex_pool.has_overflowed() {
+            log::warn!(
+                "SysEx output pool overflow: {} slots exhausted, some SysEx messages were dropped",
+                sysex_pool.capacity()
+            );
+        }
+    }
+
     /// Process audio through this render block (generic implementation).
     ///
     /// This is the core audio processing function that would be called
@@ -1473,12 +1571,19 @@ impl<S: Sample> RenderBlock<S> {
         event_list: *const AURenderEvent,
         pull_input_block: *const c_void,
         input_data: *const AudioBufferList,
+        musical_context_block: *const c_void,
+        transport_state_block: *const c_void,
     ) -> i32 {
         let num_samples = frame_count as usize;
 
         // AU multi-output bus handling: the host calls render once per output bus.
         // Bus 0 triggers a full render; bus N>0 copies cached aux output data.
         if output_bus_number > 0 {
+            // Parameter flushes are handled on bus 0; aux buses have no audio.
+            if num_samples == 0 {
+                return os_status::NO_ERR;
+            }
+
             // SAFETY: AU hosts call render sequentially per instance, always
             // completing bus 0 before calling bus N>0.
             let last_time = unsafe { *self.last_render_sample_time.get() };
@@ -1546,42 +1651,89 @@ impl<S: Sample> RenderBlock<S> {
         let sysex_pool = unsafe { &mut *self.sysex_output_pool.get() };
         sysex_pool.clear();
 
+        // AU event times are absolute sample positions (or AUEventSampleTimeImmediate
+        // plus an offset). They are converted to offsets within [0, frame_count)
+        // while extracting, matching VST3's Event::sampleOffset.
+        // SAFETY: bridge.rs validates timestamp non-null before calling process_impl.
+        let buffer_start = buffer_start_sample(unsafe { Self::sample_time_from(timestamp) });
+
         // Extract MIDI events from the AU render event linked list
         // SAFETY: event_list is valid for this render call (provided by AU host)
         unsafe {
-            extract_midi_events(event_list, midi_buffer);
-        }
-
-        // Convert absolute sample times to relative buffer offsets.
-        //
-        // AU's eventSampleTime is an ABSOLUTE sample position (like the transport).
-        // We need to subtract the timestamp's sample_time (the buffer start position)
-        // to get a relative offset within [0, frame_count).
-        // SAFETY: bridge.rs validates timestamp non-null before calling process_impl.
-        let buffer_start_sample = unsafe { (*timestamp).sample_time as i64 };
-
-        for event in midi_buffer.events.iter_mut() {
-            let absolute_time = event.sample_offset as i64;
-            let relative_offset = absolute_time - buffer_start_sample;
-
-            // Clamp to buffer bounds (handle late/early events gracefully)
-            event.sample_offset = relative_offset.clamp(0, (num_samples - 1) as i64) as u32;
+            extract_midi_events(event_list, midi_buffer, buffer_start, num_samples);
         }
 
         // Ensure events are ordered by sample offset so we can slice them efficiently
         // during sub-block processing (sample-accurate automation).
         midi_buffer.sort_by_sample_offset();
 
+        // Record what the host delivered, before any framework processing
+        let midi_monitor = plugin_guard.midi_monitor();
+        midi_monitor.record_all(MidiDirection::Input, midi_buffer.iter());
+
         // Update MIDI CC state from incoming events
         // This allows plugins to query current CC values via context.midi_cc()
         if let Some(cc_state) = plugin_guard.midi_cc_state() {
             update_midi_cc_state(midi_buffer, cc_state);
         }
 
+        // Shape velocities and controller values with the response curves.
+        // The CC state keeps the raw values and applies the curves on read.
+        plugin_guard.response_curves().apply(midi_buffer.as_mut_slice());
+
+        // Drop notes outside the playable range, if the plugin asked for it
+        let note_ranges = plugin_guard.note_ranges();
+        if note_ranges.is_filtering() {
+            midi_buffer.retain(|e| note_ranges.allows(e));
+        }
+
+        // Extract transport info from the AU host blocks
+        // SAFETY: bridge.rs validates timestamp non-null before calling process_impl.
+        // The host blocks are valid (or null) for the duration of this render call.
+        let transport = unsafe {
+            extract_transport_from_au(
+                musical_context_block,
+                transport_state_block,
+                (*timestamp).sample_time,
+                self.sample_rate,
+            )
+        };
+
+        // A zero-length render is a parameter flush: hosts use it to deliver
+        // parameter changes (and sometimes MIDI) while no audio is running.
+        // It doesn't advance the transport.
+        let is_flush = num_samples == 0;
+
+        // Transport events and the plugin's relocation policy, before MIDI and audio
+        if !is_flush {
+            plugin_guard.track_transport(&transport, num_samples);
+        }
+
+        // Voice count for the processing statistics, from the previous block
+        plugin_guard.record_active_voices();
+
+        // MIDI panic: the processor's hook, then All Sound Off and All Notes
+        // Off on every channel ahead of the block's events
+        if plugin_guard.midi_panic().take() {
+            plugin_guard.panic();
+            let host_events = midi_buffer.len();
+            for event in beamer_core::panic_events(0) {
+                midi_buffer.push(event);
+            }
+            let panic_events = midi_buffer.len() - host_events;
+            midi_buffer.as_mut_slice().rotate_right(panic_events);
+        }
+
         // Process MIDI events (input → output transformation)
         // This allows plugins to transform, generate, or pass through MIDI
+        plugin_guard.begin_block(num_samples);
         plugin_guard.process_midi(midi_buffer.as_slice(), midi_output);
 
+        // Append MIDI clock/MMC locked to the host transport
+        if !is_flush {
+            plugin_guard.generate_midi_clock(&transport, self.sample_rate, num_samples, midi_output);
+        }
+
         // Clear and extract parameter events from AU event list
         // SAFETY: Same reasoning as storage - single-threaded render calls
         let parameter_events = unsafe { &mut *self.parameter_events.get() };
@@ -1590,106 +1742,22 @@ impl<S: Sample> RenderBlock<S> {
         // Extract parameter events from the AU render event linked list
         // SAFETY: event_list is valid for this render call (provided by AU host)
         unsafe {
-            extract_parameter_events(event_list, parameter_events);
+            extract_parameter_events(event_list, parameter_events, buffer_start, num_samples);
         }
 
         // Sort by sample offset to enable sample-accurate application via sub-block processing.
         parameter_events.immediate.sort_by_key(|e| e.sample_offset);
         parameter_events.ramps.sort_by_key(|e| e.sample_offset);
 
-        // Extract transport info from AU host
-        // SAFETY: timestamp and transport_state_block are valid for this render call
-        let transport = unsafe {
-            // Extract is_playing from transport state block if available
-            let is_playing = match self.transport_state_block {
-                Some(block) => {
-                    // AUHostTransportStateBlock signature (from Apple's Audio Unit v3 API):
-                    // BOOL (^)(AUHostTransportStateFlags *outTransportStateFlags,
-                    //          double *outCurrentSamplePosition,
-                    //          double *outCycleStartBeatPosition,
-                    //          double *outCycleEndBeatPosition)
-                    //
-                    // Define the function signature that matches Apple's AUHostTransportStateBlock.
-                    // The first parameter is the block pointer itself (Objective-C block convention).
-                    type TransportStateBlockFn = unsafe extern "C" fn(
-                        *const c_void, // Block pointer itself (Objective-C convention)
-                        *mut u32,      // outTransportStateFlags (AUHostTransportStateFlags)
-                        *mut f64,      // outCurrentSamplePosition
-                        *mut f64,      // outCycleStartBeatPosition
-                        *mut f64,      // outCycleEndBeatPosition
-                    ) -> bool; // Returns true if successful
-
-                    let mut flags: u32 = 0;
-                    let mut current_sample_pos: f64 = 0.0;
-                    let mut cycle_start: f64 = 0.0;
-                    let mut cycle_end: f64 = 0.0;
-
-                    // SAFETY: This transmute is required because Rust doesn't have native Objective-C block support.
-                    //
-                    // Why this transmute is needed:
-                    // - AU hosts provide callbacks as Objective-C blocks (*const c_void)
-                    // - Objective-C blocks are callable objects with a function pointer at a known offset
-                    // - We must call this function with the correct signature to retrieve transport state
-                    //
-                    // Invariants that must hold:
-                    // 1. `block` must be a valid AUHostTransportStateBlock provided by the AU host
-                    // 2. The block must remain valid for the duration of this render callback
-                    // 3. The function signature must exactly match Apple's documented AUHostTransportStateBlock:
-                    //    - First arg: block pointer itself (Objective-C convention)
-                    //    - outTransportStateFlags: bitfield indicating state (playing, recording, cycling)
-                    //    - outCurrentSamplePosition: current sample position in timeline
-                    //    - outCycleStartBeatPosition: loop start position in beats
-                    //    - outCycleEndBeatPosition: loop end position in beats
-                    // 4. The block must be called from the AU render thread only
-                    //
-                    // What could go wrong:
-                    // - If block pointer is invalid/corrupted → undefined behavior (crash likely)
-                    // - If signature doesn't match → argument misalignment, undefined behavior
-                    // - If called from wrong thread → race conditions (violates AU threading model)
-                    // - If block is used after render callback → use-after-free
-                    //
-                    // Why this is safe in practice:
-                    // - AU hosts guarantee the block is valid during the render callback
-                    // - Our signature matches Apple's documented API exactly
-                    // - We only call from within render callback, never store the pointer
-                    // - The block is host-provided, not user-created
-                    //
-                    // Alternative approach:
-                    // - Use `block2` crate for proper Objective-C block handling (adds dependency)
-                    let invoke = objc_block::invoke_ptr(block);
-                    let block_fn: TransportStateBlockFn = std::mem::transmute(invoke);
-
-                    // Call the block to retrieve transport state
-                    let success = block_fn(
-                        block,
-                        &mut flags,
-                        &mut current_sample_pos,
-                        &mut cycle_start,
-                        &mut cycle_end,
-                    );
-
-                    // If call succeeded, check if MOVING flag is set
-                    if success {
-                        AUHostTransportStateFlags(flags).is_playing()
-                    } else {
-                        false
-                    }
-                }
-                None => false, // No transport state block, default to stopped
-            };
-
-            // SAFETY: bridge.rs validates timestamp non-null before calling process_impl.
-            let sample_position = (*timestamp).sample_time as i64;
-
-            match self.musical_context_block {
-                Some(block) => extract_transport_from_au(block, sample_position, is_playing),
-                None => beamer_core::Transport {
-                    project_time_samples: Some(sample_position),
-                    is_playing,
-                    ..Default::default()
-                },
-            }
-        };
+        // Parameter flush: every event sits at offset 0. Apply them and send
+        // the MIDI output without touching the (possibly null) audio buffers.
+        if is_flush {
+            let _ = plugin_guard
+                .apply_parameter_events(&parameter_events.immediate, &parameter_events.ramps);
+            midi_monitor.record_all(MidiDirection::Output, midi_output.iter());
+            self.send_midi_output(midi_output, sysex_pool);
+            return os_status::NO_ERR;
+        }
 
         // Collect pointers from AudioBufferList
         // SAFETY: output_data is valid for the duration of this render call
@@ -2086,63 +2154,8 @@ impl<S: Sample> RenderBlock<S> {
             block_start = next_boundary;
         }
 
-        // Handle MIDI output via scheduleMIDIEventBlock (if available)
-        //
-        // AU MIDI output depends on component type:
-        // - `aumu` (Music Device/Instrument): MIDI output supported via scheduleMIDIEventBlock
-        // - `aumf` (MIDI Effect): MIDI output supported
-        // - `aufx` (Effect): MIDI output NOT typically supported by hosts
-        //
-        // For effects, most hosts don't provide scheduleMIDIEventBlock, so MIDI output
-        // events will be dropped with a warning.
-
-        // First, allocate SysEx messages to the pool for stable pointers
-        for midi_event in midi_output.iter() {
-            if let MidiEventKind::SysEx(sysex) = &midi_event.event {
-                // Allocate from pool for stable pointer during output
-                let _ = sysex_pool.allocate_slice(sysex.as_slice());
-            }
-        }
-
-        // Now output all MIDI events to the host
-        let dropped_events = self.output_all_midi_events(midi_output, sysex_pool);
-
-        // Log warnings for dropped events
-        if dropped_events > 0 {
-            if self.schedule_midi_event_block.is_none() {
-                // No MIDI output block - this is expected for effect plugins (aufx)
-                // Only log at debug level to avoid spamming for effect plugins that
-                // generate MIDI output (which is unusual but possible)
-                log::debug!(
-                    "AU MIDI output not available: {} events dropped. \
-                     MIDI output is only supported for instrument (aumu) and MIDI effect (aumf) plugins. \
-                     Effects (aufx) typically do not support MIDI output.",
-                    dropped_events
-                );
-            } else {
-                // Block is available but events still dropped (shouldn't happen)
-                log::warn!(
-                    "MIDI output error: {} events could not be sent to host",
-                    dropped_events
-                );
-            }
-        }
-
-        // Check for MIDI output buffer overflow
-        if midi_output.has_overflowed() {
-            log::warn!(
-                "MIDI output buffer overflow: {} events reached capacity, some events were dropped",
-                midi_output.len()
-            );
-        }
-
-        // Check for SysEx pool overflow
-        if sysex_pool.has_overflowed() {
-            log::warn!(
-                "SysEx output pool overflow: {} slots exhausted, some SysEx messages were dropped",
-                sysex_pool.capacity()
-            );
-        }
+        midi_monitor.record_all(MidiDirection::Output, midi_output.iter());
+        self.send_midi_output(midi_output, sysex_pool);
 
         // Record the sample time for this render cycle so that subsequent
         // aux bus calls (output_bus_number > 0) can detect they belong to
@@ -2288,6 +2301,8 @@ impl<S: Sample> RenderBlockTrait for RenderBlock<S> {
         event_list: *const AURenderEvent,
         pull_input_block: *const c_void,
         input_data: *const AudioBufferList,
+        musical_context_block: *const c_void,
+        transport_state_block: *const c_void,
     ) -> i32 {
         self.process_impl(
             action_flags,
@@ -2298,6 +2313,8 @@ impl<S: Sample> RenderBlockTrait for RenderBlock<S> {
             event_list,
             pull_input_block,
             input_data,
+            musical_context_block,
+            transport_state_block,
         )
     }
 
@@ -2318,8 +2335,6 @@ impl<S: Sample> RenderBlockTrait for RenderBlock<S> {
 ///
 /// * `plugin` - Arc-wrapped plugin instance
 /// * `storage` - Pre-allocated buffer storage (created from bus config)
-/// * `musical_context_block` - Optional AU host musical context block for transport info
-/// * `transport_state_block` - Optional AU host transport state block for playback state
 /// * `schedule_midi_event_block` - Optional AU host MIDI output block (for instruments/MIDI effects)
 /// * `max_frames` - Maximum frames per render call
 /// * `sample_rate` - Current sample rate in Hz
@@ -2329,8 +2344,6 @@ impl<S: Sample> RenderBlockTrait for RenderBlock<S> {
 pub fn create_render_block_f32(
     plugin: Arc<Mutex<Box<dyn AuPluginInstance>>>,
     storage: ProcessBufferStorage<f32>,
-    musical_context_block: Option<*const c_void>,
-    transport_state_block: Option<*const c_void>,
     schedule_midi_event_block: Option<*const c_void>,
     max_frames: u32,
     sample_rate: f64,
@@ -2340,8 +2353,6 @@ pub fn create_render_block_f32(
     Box::new(RenderBlock::<f32>::new(
         plugin,
         storage,
-        musical_context_block,
-        transport_state_block,
         schedule_midi_event_block,
         max_frames,
         sample_rate,
@@ -2358,8 +2369,6 @@ pub fn create_render_block_f32(
 ///
 /// * `plugin` - Arc-wrapped plugin instance
 /// * `storage` - Pre-allocated buffer storage (created from bus config)
-/// * `musical_context_block` - Optional AU host musical context block for transport info
-/// * `transport_state_block` - Optional AU host transport state block for playback state
 /// * `schedule_midi_event_block` - Optional AU host MIDI output block (for instruments/MIDI effects)
 /// * `max_frames` - Maximum frames per render call
 /// * `sample_rate` - Current sample rate in Hz
@@ -2369,8 +2378,6 @@ pub fn create_render_block_f32(
 pub fn create_render_block_f64(
     plugin: Arc<Mutex<Box<dyn AuPluginInstance>>>,
     storage: ProcessBufferStorage<f64>,
-    musical_context_block: Option<*const c_void>,
-    transport_state_block: Option<*const c_void>,
     schedule_midi_event_block: Option<*const c_void>,
     max_frames: u32,
     sample_rate: f64,
@@ -2380,8 +2387,6 @@ pub fn create_render_block_f64(
     Box::new(RenderBlock::<f64>::new(
         plugin,
         storage,
-        musical_context_block,
-        transport_state_block,
         schedule_midi_event_block,
         max_frames,
         sample_rate,
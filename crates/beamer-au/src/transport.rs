@@ -4,70 +4,75 @@
 //! AU host's musical context. This enables tempo-synced effects, sequencers,
 //! and other plugins that need timing information.
 //!
-//! # AU Musical Context
+//! # AU Host Blocks
 //!
-//! Audio Units provide transport info via `AUHostMusicalContextBlock`, a block
-//! that the host provides to query tempo, time signature, beat position, etc.
+//! Audio Units provide transport info via two host blocks:
+//!
+//! - `AUHostMusicalContextBlock`: tempo, time signature, beat position and
+//!   the position of the current bar.
+//! - `AUHostTransportStateBlock`: playing/recording/cycling flags, the
+//!   timeline sample position and the cycle range.
 //!
 //! Unlike VST3's ProcessContext (which provides all info in a struct), AU
-//! requires calling a block to retrieve the information.
+//! requires calling the blocks to retrieve the information. AUv3 hosts set
+//! them on the `AUAudioUnit`; the AUv2 wrapper adapts the host's
+//! `kAudioUnitProperty_HostCallbacks` to the same block signatures, so both
+//! formats fill [`Transport`] the same way as VST3.
 
 use beamer_core::Transport;
 use std::ffi::c_void;
 
 use crate::objc_block;
+use crate::render::AUHostTransportStateFlags;
 
-/// Extract transport information from AU's host musical context.
-///
-/// Queries the AU host for tempo, time signature, position and playback state.
-/// Returns `Transport::default()` if the context block is unavailable or if
-/// the query fails.
-///
-/// # Arguments
+/// MIDI beat clock resolution (pulses per quarter note).
+const MIDI_CLOCKS_PER_BEAT: f64 = 24.0;
+
+/// Values reported by the host's `AUHostMusicalContextBlock`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct HostMusicalContext {
+    /// Tempo in BPM.
+    pub tempo: f64,
+    /// Time signature numerator.
+    pub time_sig_numerator: f64,
+    /// Time signature denominator.
+    pub time_sig_denominator: isize,
+    /// Current position in quarter notes.
+    pub beat_position: f64,
+    /// Samples from the start of the buffer to the next beat.
+    pub sample_offset_to_next_beat: isize,
+    /// Position of the current bar's downbeat in quarter notes.
+    pub measure_downbeat_position: f64,
+}
+
+/// Values reported by the host's `AUHostTransportStateBlock`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct HostTransportState {
+    /// Playing/recording/cycling flags.
+    pub flags: AUHostTransportStateFlags,
+    /// Timeline position in samples.
+    pub sample_position: f64,
+    /// Cycle start in quarter notes.
+    pub cycle_start_beats: f64,
+    /// Cycle end in quarter notes.
+    pub cycle_end_beats: f64,
+}
+
+/// Call the host's `AUHostMusicalContextBlock`.
 ///
-/// * `musical_context_block` - Pointer to the AU host's musical context block
-/// * `sample_position` - Current sample position from AudioTimeStamp
-/// * `is_playing` - Whether transport is currently playing (from action flags)
+/// Returns `None` if the block is null or the host reports failure.
 ///
 /// # Safety
 ///
-/// Must be called from within render callback with valid pointers.
-/// The `musical_context_block` must point to a valid `AUHostMusicalContextBlock`
-/// or be null.
-///
-/// # Returns
-///
-/// A `Transport` struct populated with available information, or default values
-/// if the host doesn't provide transport info.
-///
-/// # Example
-///
-/// ```ignore
-/// // In render callback:
-/// let transport = unsafe {
-///     extract_transport_from_au(
-///         host_musical_context_block,
-///         timestamp.sample_time as i64,
-///         is_playing,
-///     )
-/// };
-/// ```
-pub unsafe fn extract_transport_from_au(
-    musical_context_block: *const c_void,
-    sample_position: i64,
-    is_playing: bool,
-) -> Transport {
-    // If no musical context block provided, return default transport
-    if musical_context_block.is_null() {
-        return Transport {
-            project_time_samples: Some(sample_position),
-            is_playing,
-            ..Default::default()
-        };
+/// Must be called from within the render callback. `block` must point to a
+/// valid `AUHostMusicalContextBlock` or be null.
+pub(crate) unsafe fn query_musical_context(block: *const c_void) -> Option<HostMusicalContext> {
+    if block.is_null() {
+        return None;
     }
 
     // AUHostMusicalContextBlock signature (from Apple's Audio Unit v3 API):
-    // OSStatus (^)(
+    // BOOL (^)(
     //     double *outCurrentTempo,
     //     double *outTimeSignatureNumerator,
     //     NSInteger *outTimeSignatureDenominator,
@@ -75,16 +80,8 @@ pub unsafe fn extract_transport_from_au(
     //     NSInteger *outSampleOffsetToNextBeat,
     //     double *outCurrentMeasureDownbeatPosition
     // )
-
-    let mut tempo: f64 = 0.0;
-    let mut time_sig_numerator: f64 = 0.0;
-    let mut time_sig_denominator: isize = 0;
-    let mut beat_position: f64 = 0.0;
-    let mut sample_offset_to_next_beat: isize = 0;
-    let mut measure_downbeat_position: f64 = 0.0;
-
-    // Define the function signature that matches Apple's AUHostMusicalContextBlock.
-    // The first parameter is the block pointer itself (Objective-C block convention).
+    //
+    // The block returns YES on success (it is not an OSStatus).
     type MusicalContextBlockFn = unsafe extern "C" fn(
         *const c_void, // Block pointer itself (implicit in Objective-C blocks)
         *mut f64,      // outCurrentTempo
@@ -93,167 +90,374 @@ pub unsafe fn extract_transport_from_au(
         *mut f64,      // outCurrentBeatPosition
         *mut isize,    // outSampleOffsetToNextBeat (NSInteger)
         *mut f64,      // outCurrentMeasureDownbeatPosition
-    ) -> i32; // OSStatus (0 = success)
+    ) -> bool; // BOOL (YES = success)
+
+    let mut context = HostMusicalContext {
+        tempo: 0.0,
+        time_sig_numerator: 0.0,
+        time_sig_denominator: 0,
+        beat_position: 0.0,
+        sample_offset_to_next_beat: 0,
+        measure_downbeat_position: 0.0,
+    };
 
     // SAFETY: This transmute is required because Rust doesn't have native Objective-C block support.
     //
-    // Why this transmute is needed:
-    // - AU hosts provide callbacks as Objective-C blocks (*const c_void)
-    // - Objective-C blocks have a C function pointer at a known offset in their structure
-    // - We must call this function pointer with the correct signature
-    //
     // Invariants that must hold:
-    // 1. `musical_context_block` must be a valid AUHostMusicalContextBlock provided by the AU host
+    // 1. `block` must be a valid AUHostMusicalContextBlock provided by the AU host
     // 2. The block must remain valid for the duration of this render callback
     // 3. The function signature must exactly match Apple's documented AUHostMusicalContextBlock:
     //    - First arg: block pointer itself (Objective-C convention)
     //    - Remaining args: output parameters as documented in AU API
     // 4. The block must be called from the same thread that received it (AU render thread)
     //
-    // What could go wrong:
-    // - If the block pointer is invalid or corrupted → undefined behavior (likely crash)
-    // - If the signature doesn't match → argument misalignment, undefined behavior
-    // - If called from wrong thread → potential race conditions (violates AU threading model)
-    // - If the block is used after the render callback returns → use-after-free
-    //
     // Why this is safe in practice:
     // - AU hosts guarantee the block pointer is valid during the render callback
     // - Our signature matches Apple's documented API exactly
     // - We only call from within the render callback, never store the pointer
-    // - The block is provided by the host, not created by us
-    //
-    // Alternative approach:
-    // - Use the `block2` crate for proper Objective-C block handling (adds dependency)
-    // - This would eliminate the transmute but requires understanding block2's API
-    //
-    // SAFETY: invoke_ptr extracts the function pointer from the Objective-C block.
-    // Caller guarantees musical_context_block is a valid AUHostMusicalContextBlock.
-    let invoke = unsafe { objc_block::invoke_ptr(musical_context_block) };
-    // SAFETY: Transmute is sound because invoke pointer has the exact signature
-    // documented in Apple's AU API. See detailed SAFETY comment above.
+    let invoke = unsafe { objc_block::invoke_ptr(block) };
+    // SAFETY: See above; the invoke pointer has the documented signature.
     let block_fn: MusicalContextBlockFn = unsafe { std::mem::transmute(invoke) };
 
-    // SAFETY: All output pointers are valid stack variables. The block is called
-    // on the AU render thread as required by Apple's threading model.
-    let result = unsafe {
+    // SAFETY: All output pointers are valid fields of a stack variable. The
+    // block is called on the AU render thread as required by Apple's threading model.
+    let success = unsafe {
         block_fn(
-            musical_context_block,
-            &mut tempo,
-            &mut time_sig_numerator,
-            &mut time_sig_denominator,
-            &mut beat_position,
-            &mut sample_offset_to_next_beat,
-            &mut measure_downbeat_position,
+            block,
+            &mut context.tempo,
+            &mut context.time_sig_numerator,
+            &mut context.time_sig_denominator,
+            &mut context.beat_position,
+            &mut context.sample_offset_to_next_beat,
+            &mut context.measure_downbeat_position,
         )
     };
 
-    // If the call succeeded (result == 0), populate Transport
-    if result == 0 {
-        Transport {
-            // Tempo and time signature
-            tempo: if tempo > 0.0 { Some(tempo) } else { None },
-            time_sig_numerator: if time_sig_numerator > 0.0 {
-                Some(time_sig_numerator as i32)
-            } else {
-                None
-            },
-            time_sig_denominator: if time_sig_denominator > 0 {
-                Some(time_sig_denominator as i32)
-            } else {
-                None
-            },
-
-            // Position information
-            project_time_samples: Some(sample_position),
-            project_time_beats: if beat_position >= 0.0 {
-                Some(beat_position)
-            } else {
-                None
-            },
-            bar_position_beats: if measure_downbeat_position >= 0.0 {
-                Some(beat_position - measure_downbeat_position)
-            } else {
-                None
-            },
-
-            // Playback state
-            is_playing,
-
-            // Fields not provided by AU (remain None)
-            is_recording: false,    // AU doesn't provide recording state
-            is_cycle_active: false, // AU doesn't provide loop info directly
-            cycle_start_beats: None,
-            cycle_end_beats: None,
-            system_time_ns: None,
-            continuous_time_samples: None,
-            samples_to_next_clock: if sample_offset_to_next_beat > 0 {
-                Some(sample_offset_to_next_beat as i32)
-            } else {
-                None
-            },
-            smpte_offset_subframes: None, // AU doesn't provide SMPTE
-            frame_rate: None,
-        }
-    } else {
-        // Query failed, return minimal transport with just sample position
-        Transport {
-            project_time_samples: Some(sample_position),
-            is_playing,
-            ..Default::default()
+    success.then_some(context)
+}
+
+/// Call the host's `AUHostTransportStateBlock`.
+///
+/// Returns `None` if the block is null or the host reports failure.
+///
+/// # Safety
+///
+/// Must be called from within the render callback. `block` must point to a
+/// valid `AUHostTransportStateBlock` or be null.
+pub(crate) unsafe fn query_transport_state(block: *const c_void) -> Option<HostTransportState> {
+    if block.is_null() {
+        return None;
+    }
+
+    // AUHostTransportStateBlock signature (from Apple's Audio Unit v3 API):
+    // BOOL (^)(AUHostTransportStateFlags *outTransportStateFlags,
+    //          double *outCurrentSamplePosition,
+    //          double *outCycleStartBeatPosition,
+    //          double *outCycleEndBeatPosition)
+    //
+    // AUHostTransportStateFlags is an NS_OPTIONS(NSUInteger), i.e. pointer sized.
+    type TransportStateBlockFn = unsafe extern "C" fn(
+        *const c_void, // Block pointer itself (Objective-C convention)
+        *mut usize,    // outTransportStateFlags (AUHostTransportStateFlags)
+        *mut f64,      // outCurrentSamplePosition
+        *mut f64,      // outCycleStartBeatPosition
+        *mut f64,      // outCycleEndBeatPosition
+    ) -> bool; // BOOL (YES = success)
+
+    let mut flags: usize = 0;
+    let mut sample_position: f64 = 0.0;
+    let mut cycle_start_beats: f64 = 0.0;
+    let mut cycle_end_beats: f64 = 0.0;
+
+    // SAFETY: Same invariants as in `query_musical_context`: `block` is a valid
+    // AUHostTransportStateBlock for this render call and the signature matches
+    // Apple's documented API.
+    let invoke = unsafe { objc_block::invoke_ptr(block) };
+    // SAFETY: See above; the invoke pointer has the documented signature.
+    let block_fn: TransportStateBlockFn = unsafe { std::mem::transmute(invoke) };
+
+    // SAFETY: All output pointers are valid stack variables.
+    let success = unsafe {
+        block_fn(
+            block,
+            &mut flags,
+            &mut sample_position,
+            &mut cycle_start_beats,
+            &mut cycle_end_beats,
+        )
+    };
+
+    success.then_some(HostTransportState {
+        flags: AUHostTransportStateFlags(flags),
+        sample_position,
+        cycle_start_beats,
+        cycle_end_beats,
+    })
+}
+
+/// Build a [`Transport`] from the values reported by the AU host.
+///
+/// Fields map to the same meaning as the VST3 ProcessContext:
+///
+/// | Transport field | AU source |
+/// |-----------------|-----------|
+/// | `tempo`, `time_sig_*` | Musical context |
+/// | `project_time_beats` | Musical context beat position |
+/// | `bar_position_beats` | Musical context measure downbeat |
+/// | `samples_to_next_clock` | Derived from the next beat offset and tempo |
+/// | `project_time_samples` | Transport state sample position (render timestamp if unavailable) |
+/// | `is_playing`, `is_recording`, `is_cycle_active` | Transport state flags |
+/// | `cycle_start_beats`, `cycle_end_beats` | Transport state (while cycling) |
+/// | `continuous_time_samples` | Render timestamp sample time |
+///
+/// `sample_time` is the render timestamp's sample time, which increases
+/// monotonically and does not follow locates or loops.
+pub(crate) fn build_transport(
+    musical: Option<&HostMusicalContext>,
+    state: Option<&HostTransportState>,
+    sample_time: f64,
+    sample_rate: f64,
+) -> Transport {
+    let mut transport = Transport {
+        project_time_samples: Some(sample_time as i64),
+        continuous_time_samples: Some(sample_time as i64),
+        ..Default::default()
+    };
+
+    if let Some(context) = musical {
+        transport.tempo = (context.tempo > 0.0).then_some(context.tempo);
+        transport.time_sig_numerator =
+            (context.time_sig_numerator > 0.0).then_some(context.time_sig_numerator.round() as i32);
+        transport.time_sig_denominator =
+            (context.time_sig_denominator > 0).then_some(context.time_sig_denominator as i32);
+        transport.project_time_beats = Some(context.beat_position);
+        // The bar position is meaningless without a time signature.
+        transport.bar_position_beats = transport
+            .time_sig_numerator
+            .map(|_| context.measure_downbeat_position);
+        transport.samples_to_next_clock = samples_to_next_clock(context, sample_rate);
+    }
+
+    if let Some(state) = state {
+        transport.project_time_samples = Some(state.sample_position as i64);
+        transport.is_playing = state.flags.is_playing();
+        transport.is_recording = state.flags.is_recording();
+        transport.is_cycle_active = state.flags.is_cycling();
+        if transport.is_cycle_active {
+            transport.cycle_start_beats = Some(state.cycle_start_beats);
+            transport.cycle_end_beats = Some(state.cycle_end_beats);
         }
     }
+
+    transport
+}
+
+/// Samples until the next 24 PPQN MIDI clock, derived from the host's
+/// offset to the next beat.
+fn samples_to_next_clock(context: &HostMusicalContext, sample_rate: f64) -> Option<i32> {
+    if context.tempo <= 0.0 || sample_rate <= 0.0 || context.sample_offset_to_next_beat < 0 {
+        return None;
+    }
+    let samples_per_clock = sample_rate * 60.0 / context.tempo / MIDI_CLOCKS_PER_BEAT;
+    let offset = context.sample_offset_to_next_beat as f64 % samples_per_clock;
+    Some(offset.round() as i32)
+}
+
+/// Extract transport information from the AU host blocks.
+///
+/// Queries the host for tempo, time signature, position and playback state.
+/// Either block may be null; missing information is left at its default
+/// (`project_time_samples` falls back to the render timestamp).
+///
+/// # Arguments
+///
+/// * `musical_context_block` - The host's `AUHostMusicalContextBlock` (or null)
+/// * `transport_state_block` - The host's `AUHostTransportStateBlock` (or null)
+/// * `sample_time` - Sample time from the render `AudioTimeStamp`
+/// * `sample_rate` - Current sample rate in Hz
+///
+/// # Safety
+///
+/// Must be called from within the render callback. Each block pointer must
+/// point to a valid block of its type or be null.
+pub unsafe fn extract_transport_from_au(
+    musical_context_block: *const c_void,
+    transport_state_block: *const c_void,
+    sample_time: f64,
+    sample_rate: f64,
+) -> Transport {
+    // SAFETY: Caller guarantees both block pointers are valid or null.
+    let (musical, state) = unsafe {
+        (
+            query_musical_context(musical_context_block),
+            query_transport_state(transport_state_block),
+        )
+    };
+    build_transport(musical.as_ref(), state.as_ref(), sample_time, sample_rate)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    /// Extract transport from AU with simplified signature.
-    ///
-    /// Test helper for creating basic transport info without a musical context block.
-    fn create_basic_transport(sample_position: i64, is_playing: bool) -> Transport {
-        Transport {
-            project_time_samples: Some(sample_position),
-            is_playing,
-            ..Default::default()
+    /// Block literal with the same header layout as an Objective-C block.
+    #[repr(C)]
+    struct FakeBlock {
+        isa: *const c_void,
+        flags: u32,
+        reserved: u32,
+        invoke: *const c_void,
+        descriptor: *const c_void,
+    }
+
+    impl FakeBlock {
+        fn new(invoke: *const c_void) -> Self {
+            Self {
+                isa: std::ptr::null(),
+                flags: 0,
+                reserved: 0,
+                invoke,
+                descriptor: std::ptr::null(),
+            }
+        }
+    }
+
+    unsafe extern "C" fn musical_context_invoke(
+        _block: *const c_void,
+        tempo: *mut f64,
+        numerator: *mut f64,
+        denominator: *mut isize,
+        beat: *mut f64,
+        offset_to_next_beat: *mut isize,
+        downbeat: *mut f64,
+    ) -> bool {
+        // SAFETY: query_musical_context passes valid output pointers.
+        unsafe {
+            *tempo = 120.0;
+            *numerator = 3.0;
+            *denominator = 4;
+            *beat = 7.5;
+            *offset_to_next_beat = 11025;
+            *downbeat = 6.0;
         }
+        true
     }
 
-    /// Check if AU provides musical context.
-    ///
-    /// Test helper for verifying musical context block availability.
-    ///
-    /// # Safety
-    ///
-    /// The `musical_context_block` pointer must be valid or null.
-    unsafe fn has_musical_context(musical_context_block: *const c_void) -> bool {
-        !musical_context_block.is_null()
+    unsafe extern "C" fn transport_state_invoke(
+        _block: *const c_void,
+        flags: *mut usize,
+        sample_position: *mut f64,
+        cycle_start: *mut f64,
+        cycle_end: *mut f64,
+    ) -> bool {
+        // SAFETY: query_transport_state passes valid output pointers.
+        unsafe {
+            *flags = AUHostTransportStateFlags::MOVING
+                | AUHostTransportStateFlags::RECORDING
+                | AUHostTransportStateFlags::CYCLING;
+            *sample_position = 330750.0;
+            *cycle_start = 4.0;
+            *cycle_end = 12.0;
+        }
+        true
+    }
+
+    unsafe extern "C" fn failing_transport_state_invoke(
+        _block: *const c_void,
+        _flags: *mut usize,
+        _sample_position: *mut f64,
+        _cycle_start: *mut f64,
+        _cycle_end: *mut f64,
+    ) -> bool {
+        false
     }
 
     #[test]
-    fn test_basic_transport() {
-        let transport = create_basic_transport(44100, true);
-        assert_eq!(transport.project_time_samples, Some(44100));
-        assert!(transport.is_playing);
+    fn test_null_blocks() {
+        // SAFETY: Null blocks are explicitly allowed.
+        let transport =
+            unsafe { extract_transport_from_au(std::ptr::null(), std::ptr::null(), 512.0, 44100.0) };
+        assert_eq!(transport.project_time_samples, Some(512));
+        assert_eq!(transport.continuous_time_samples, Some(512));
+        assert!(!transport.is_playing);
         assert_eq!(transport.tempo, None);
     }
 
     #[test]
-    fn test_null_context() {
-        // SAFETY: Passing a null pointer is explicitly safe per the function documentation.
-        // The function checks for null and returns a default Transport with the provided sample position.
-        let transport = unsafe { extract_transport_from_au(std::ptr::null(), 0, false) };
-        assert_eq!(transport.project_time_samples, Some(0));
+    fn test_blocks_fill_transport() {
+        let musical = FakeBlock::new(musical_context_invoke as *const c_void);
+        let state = FakeBlock::new(transport_state_invoke as *const c_void);
+
+        // SAFETY: Both fake blocks have the block header layout and an invoke
+        // function with the documented signature.
+        let transport = unsafe {
+            extract_transport_from_au(
+                &musical as *const FakeBlock as *const c_void,
+                &state as *const FakeBlock as *const c_void,
+                1024.0,
+                44100.0,
+            )
+        };
+
+        assert_eq!(transport.tempo, Some(120.0));
+        assert_eq!(transport.time_sig_numerator, Some(3));
+        assert_eq!(transport.time_sig_denominator, Some(4));
+        assert_eq!(transport.project_time_beats, Some(7.5));
+        assert_eq!(transport.bar_position_beats, Some(6.0));
+        assert_eq!(transport.project_time_samples, Some(330750));
+        assert_eq!(transport.continuous_time_samples, Some(1024));
+        assert!(transport.is_playing);
+        assert!(transport.is_recording);
+        assert!(transport.is_cycle_active);
+        assert_eq!(transport.cycle_start_beats, Some(4.0));
+        assert_eq!(transport.cycle_end_beats, Some(12.0));
+    }
+
+    #[test]
+    fn test_failed_query_is_ignored() {
+        let state = FakeBlock::new(failing_transport_state_invoke as *const c_void);
+
+        // SAFETY: The fake block has the block header layout and a valid invoke function.
+        let transport = unsafe {
+            extract_transport_from_au(
+                std::ptr::null(),
+                &state as *const FakeBlock as *const c_void,
+                256.0,
+                48000.0,
+            )
+        };
+        assert_eq!(transport.project_time_samples, Some(256));
         assert!(!transport.is_playing);
     }
 
     #[test]
-    fn test_has_musical_context() {
-        // SAFETY: Passing a null pointer is valid and expected - the function checks for null.
-        // This test verifies the null-checking behavior of has_musical_context.
-        unsafe {
-            assert!(!has_musical_context(std::ptr::null()));
-            // Can't test non-null case without actual AU host
-        }
+    fn test_cycle_range_only_while_cycling() {
+        let state = HostTransportState {
+            flags: AUHostTransportStateFlags(AUHostTransportStateFlags::MOVING),
+            sample_position: 0.0,
+            cycle_start_beats: 4.0,
+            cycle_end_beats: 8.0,
+        };
+        let transport = build_transport(None, Some(&state), 0.0, 44100.0);
+        assert!(transport.is_playing);
+        assert!(!transport.is_cycle_active);
+        assert_eq!(transport.cycle_start_beats, None);
+    }
+
+    #[test]
+    fn test_samples_to_next_clock() {
+        // 120 BPM at 48 kHz: 24000 samples per beat, 1000 per MIDI clock.
+        let context = HostMusicalContext {
+            tempo: 120.0,
+            time_sig_numerator: 4.0,
+            time_sig_denominator: 4,
+            beat_position: 0.0,
+            sample_offset_to_next_beat: 2500,
+            measure_downbeat_position: 0.0,
+        };
+        assert_eq!(samples_to_next_clock(&context, 48000.0), Some(500));
+
+        let no_tempo = HostMusicalContext { tempo: 0.0, ..context };
+        assert_eq!(samples_to_next_clock(&no_tempo, 48000.0), None);
     }
 }
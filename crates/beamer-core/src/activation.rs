@@ -0,0 +1,449 @@
+//! License and activation checks for commercial plugins.
+//!
+//! A license check may read files, verify signatures or ask a server, none
+//! of which may happen on the audio thread or hold up the host while it
+//! instantiates the plugin. [`Activation`] runs the check on a background
+//! thread, and the wrapper enforces an [`ActivationPolicy`] on the output
+//! until the check succeeds.
+//!
+//! # Enabling
+//!
+//! The descriptor returns an [`Activation`] from
+//! [`Descriptor::activation()`](crate::Descriptor::activation). The wrapper
+//! starts the check once, when the plugin is instantiated:
+//!
+//! ```ignore
+//! fn activation(&self) -> Activation {
+//!     Activation::new(|| license::verify(&license::path()))
+//!         .with_policy(ActivationPolicy::Noise)
+//! }
+//! ```
+//!
+//! The check returns `Err` with a message for the user when the plugin is
+//! not activated. Keep a clone in the descriptor to query
+//! [`is_activated()`](Activation::is_activated) from plugin code, e.g. to
+//! disable saving, or to [`start()`](Activation::start) the check again after
+//! the user entered a license key.
+//!
+//! # Enforcement
+//!
+//! Until the check succeeds (including while it runs), the wrapper applies
+//! the policy to the main outputs after `process()`:
+//!
+//! | Policy | Output |
+//! |--------|--------|
+//! | [`Silence`](ActivationPolicy::Silence) | Silence |
+//! | [`Noise`](ActivationPolicy::Noise) | Processed audio with a short noise burst every 20 seconds |
+//! | [`Full`](ActivationPolicy::Full) | Processed audio (the plugin decides what to limit) |
+//!
+//! # GUI Notification
+//!
+//! The WebView runtime exposes the result as `__BEAMER__.activation()`,
+//! sends a `beamer:activation` event when the check finishes, and retries
+//! with `__BEAMER__.retryActivation()`.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::buffer::Buffer;
+use crate::sample::Sample;
+
+/// Built-in WebView invoke method that returns the activation status.
+pub const ACTIVATION_INVOKE: &str = "_beamer/activation";
+
+/// Built-in WebView invoke method that runs the activation check again.
+pub const RETRY_ACTIVATION_INVOKE: &str = "_beamer/retryActivation";
+
+/// Time between noise bursts under [`ActivationPolicy::Noise`].
+const NOISE_PERIOD_SECONDS: f64 = 20.0;
+
+/// Length of a noise burst.
+const NOISE_BURST_SECONDS: f64 = 0.5;
+
+/// Peak level of the noise burst (about -20 dBFS).
+const NOISE_LEVEL: f64 = 0.1;
+
+const STATE_PENDING: u8 = 0;
+const STATE_ACTIVATED: u8 = 1;
+const STATE_UNACTIVATED: u8 = 2;
+
+/// What the wrapper does with the output while the plugin is not activated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActivationPolicy {
+    /// Output silence.
+    #[default]
+    Silence,
+    /// Process normally but mix in a short noise burst every 20 seconds.
+    Noise,
+    /// Process normally; the plugin limits features itself.
+    Full,
+}
+
+impl ActivationPolicy {
+    /// Name used in the GUI status.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Silence => "silence",
+            Self::Noise => "noise",
+            Self::Full => "full",
+        }
+    }
+
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Noise,
+            2 => Self::Full,
+            _ => Self::Silence,
+        }
+    }
+
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::Silence => 0,
+            Self::Noise => 1,
+            Self::Full => 2,
+        }
+    }
+}
+
+/// Result of the activation check so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationState {
+    /// The check has not finished yet.
+    Pending,
+    /// The check succeeded (or the plugin has no check).
+    Activated,
+    /// The check failed.
+    Unactivated,
+}
+
+impl ActivationState {
+    /// Name used in the GUI status.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Activated => "activated",
+            Self::Unactivated => "unactivated",
+        }
+    }
+}
+
+/// Activation check function. Returns a message for the user on failure.
+type ActivationCheck = dyn Fn() -> Result<(), String> + Send + Sync;
+
+/// Per-instance activation check and its result.
+///
+/// Cloning is cheap (reference counted), so the descriptor, the wrapper and
+/// its WebView IPC context share one result. The default instance has no
+/// check and is always activated.
+#[derive(Clone, Default)]
+pub struct Activation {
+    inner: Option<Arc<ActivationInner>>,
+}
+
+struct ActivationInner {
+    check: Box<ActivationCheck>,
+    policy: AtomicU8,
+    state: AtomicU8,
+    running: AtomicBool,
+    message: Mutex<Option<String>>,
+}
+
+impl std::fmt::Debug for Activation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Activation")
+            .field("enabled", &self.is_enabled())
+            .field("state", &self.state())
+            .field("policy", &self.policy())
+            .finish()
+    }
+}
+
+impl Activation {
+    /// Create an activation with a check and the
+    /// [`Silence`](ActivationPolicy::Silence) policy.
+    ///
+    /// The check runs on a background thread and returns `Err` with a
+    /// message for the user when the plugin is not activated. A panic counts
+    /// as a failure.
+    pub fn new(check: impl Fn() -> Result<(), String> + Send + Sync + 'static) -> Self {
+        Self {
+            inner: Some(Arc::new(ActivationInner {
+                check: Box::new(check),
+                policy: AtomicU8::new(ActivationPolicy::default().to_u8()),
+                state: AtomicU8::new(STATE_PENDING),
+                running: AtomicBool::new(false),
+                message: Mutex::new(None),
+            })),
+        }
+    }
+
+    /// Set what the wrapper does with the output while not activated.
+    pub fn with_policy(self, policy: ActivationPolicy) -> Self {
+        if let Some(inner) = &self.inner {
+            inner.policy.store(policy.to_u8(), Ordering::Relaxed);
+        }
+        self
+    }
+
+    /// Whether the plugin has an activation check.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Run the check on a background thread.
+    ///
+    /// The wrapper calls this when the plugin is instantiated; call it again
+    /// to re-check, e.g. after the user entered a license key. Does nothing
+    /// while a check is running. The previous result stays in effect until
+    /// the new check finishes.
+    pub fn start(&self) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        if inner.running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let worker = Arc::clone(inner);
+        let spawned = std::thread::Builder::new()
+            .name("beamer-activation".into())
+            .spawn(move || worker.run());
+        if let Err(e) = spawned {
+            log::warn!("Activation thread not started: {e}");
+            inner.finish(Err(format!("Activation check could not start: {e}")));
+        }
+    }
+
+    /// Result of the check so far. Real-time safe.
+    pub fn state(&self) -> ActivationState {
+        match &self.inner {
+            None => ActivationState::Activated,
+            Some(inner) => match inner.state.load(Ordering::Acquire) {
+                STATE_ACTIVATED => ActivationState::Activated,
+                STATE_UNACTIVATED => ActivationState::Unactivated,
+                _ => ActivationState::Pending,
+            },
+        }
+    }
+
+    /// Whether the check succeeded. Real-time safe.
+    pub fn is_activated(&self) -> bool {
+        self.state() == ActivationState::Activated
+    }
+
+    /// Whether a check is running.
+    pub fn is_running(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.running.load(Ordering::Acquire))
+    }
+
+    /// Message from the last failed check.
+    pub fn message(&self) -> Option<String> {
+        let inner = self.inner.as_ref()?;
+        inner.message.lock().ok()?.clone()
+    }
+
+    /// What the wrapper does with the output while not activated.
+    pub fn policy(&self) -> ActivationPolicy {
+        self.inner
+            .as_ref()
+            .map(|inner| ActivationPolicy::from_u8(inner.policy.load(Ordering::Relaxed)))
+            .unwrap_or_default()
+    }
+}
+
+impl ActivationInner {
+    fn run(&self) {
+        let result = catch_unwind(AssertUnwindSafe(|| (self.check)()))
+            .unwrap_or_else(|_| Err("Activation check failed".to_string()));
+        self.finish(result);
+    }
+
+    fn finish(&self, result: Result<(), String>) {
+        let (state, message) = match result {
+            Ok(()) => (STATE_ACTIVATED, None),
+            Err(message) => {
+                log::warn!("Plugin not activated: {message}");
+                (STATE_UNACTIVATED, Some(message))
+            }
+        };
+        if let Ok(mut slot) = self.message.lock() {
+            *slot = message;
+        }
+        self.state.store(state, Ordering::Release);
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+/// JSON description of the activation status for the GUI.
+pub fn activation_json(activation: &Activation) -> serde_json::Value {
+    serde_json::json!({
+        "enabled": activation.is_enabled(),
+        "state": activation.state().as_str(),
+        "running": activation.is_running(),
+        "message": activation.message(),
+        "policy": activation.policy().as_str(),
+    })
+}
+
+/// Handle the built-in activation WebView calls.
+///
+/// Returns `None` if `method` is not an activation method.
+pub fn activation_invoke(activation: &Activation, method: &str) -> Option<serde_json::Value> {
+    match method {
+        ACTIVATION_INVOKE => Some(activation_json(activation)),
+        RETRY_ACTIVATION_INVOKE => {
+            activation.start();
+            Some(activation_json(activation))
+        }
+        _ => None,
+    }
+}
+
+/// Applies the [`ActivationPolicy`] to the wrapper's output.
+///
+/// Owned by the wrapper, one per instance. Real-time safe.
+#[derive(Debug, Default)]
+pub struct ActivationGate {
+    activation: Activation,
+    /// Samples per noise period (0 until prepared).
+    period: AtomicU64,
+    /// Samples per noise burst.
+    burst: AtomicU64,
+    /// Position within the noise period.
+    position: AtomicU64,
+    /// Noise generator state.
+    seed: AtomicU32,
+}
+
+impl ActivationGate {
+    /// Create a gate for `activation`.
+    pub fn new(activation: Activation) -> Self {
+        Self {
+            activation,
+            seed: AtomicU32::new(0x9E37_79B9),
+            ..Self::default()
+        }
+    }
+
+    /// The activation this gate enforces.
+    pub fn activation(&self) -> &Activation {
+        &self.activation
+    }
+
+    /// Set the sample rate for the noise timing.
+    pub fn prepare(&self, sample_rate: f64) {
+        self.period
+            .store((NOISE_PERIOD_SECONDS * sample_rate) as u64, Ordering::Relaxed);
+        self.burst
+            .store((NOISE_BURST_SECONDS * sample_rate) as u64, Ordering::Relaxed);
+        self.position.store(0, Ordering::Relaxed);
+    }
+
+    /// Finish a block by applying the policy to the plugin's output.
+    pub fn finish<S: Sample>(&self, buffer: &mut Buffer<S>) {
+        if self.activation.is_activated() {
+            return;
+        }
+        match self.activation.policy() {
+            ActivationPolicy::Full => {}
+            ActivationPolicy::Silence => buffer.clear_outputs(),
+            ActivationPolicy::Noise => self.add_noise(buffer),
+        }
+    }
+
+    fn add_noise<S: Sample>(&self, buffer: &mut Buffer<S>) {
+        let period = self.period.load(Ordering::Relaxed);
+        if period == 0 {
+            return;
+        }
+        let burst = self.burst.load(Ordering::Relaxed);
+        let start = self.position.load(Ordering::Relaxed);
+        let num_samples = buffer.num_samples();
+        self.position
+            .store((start + num_samples as u64) % period, Ordering::Relaxed);
+        if start >= burst && start + num_samples as u64 <= period {
+            return;
+        }
+
+        let mut seed = self.seed.load(Ordering::Relaxed);
+        for output in buffer.outputs_mut() {
+            for (i, sample) in output.iter_mut().enumerate() {
+                if (start + i as u64) % period >= burst {
+                    continue;
+                }
+                // xorshift32
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                let noise = (seed as f64 / u32::MAX as f64 * 2.0 - 1.0) * NOISE_LEVEL;
+                *sample = *sample + S::from_f64(noise);
+            }
+        }
+        self.seed.store(seed, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait_for_result(activation: &Activation) -> ActivationState {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while activation.is_running() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        activation.state()
+    }
+
+    #[test]
+    fn check_runs_in_background_and_retries() {
+        let licensed = Arc::new(AtomicBool::new(false));
+        let key = Arc::clone(&licensed);
+        let activation = Activation::new(move || {
+            if key.load(Ordering::Relaxed) {
+                Ok(())
+            } else {
+                Err("No license found".to_string())
+            }
+        })
+        .with_policy(ActivationPolicy::Noise);
+        assert_eq!(activation.state(), ActivationState::Pending);
+
+        activation.start();
+        assert_eq!(wait_for_result(&activation), ActivationState::Unactivated);
+        assert_eq!(activation.message().as_deref(), Some("No license found"));
+        assert_eq!(activation_json(&activation)["policy"], "noise");
+
+        licensed.store(true, Ordering::Relaxed);
+        activation_invoke(&activation, RETRY_ACTIVATION_INVOKE);
+        assert_eq!(wait_for_result(&activation), ActivationState::Activated);
+        assert_eq!(activation.message(), None);
+        assert!(Activation::default().is_activated());
+    }
+
+    #[test]
+    fn gate_silences_until_activated() {
+        let activation = Activation::new(|| Err("Trial expired".to_string()));
+        let gate = ActivationGate::new(activation.clone());
+        gate.prepare(48000.0);
+
+        let input = [0.5f32; 16];
+        let mut output = [0.5f32; 16];
+        let mut buffer = Buffer::new([&input[..]], [&mut output[..]], 16);
+        gate.finish(&mut buffer);
+        assert!(output.iter().all(|&s| s == 0.0));
+
+        // Noise policy: the burst at the start of the period changes the output
+        let gate = ActivationGate::new(activation.with_policy(ActivationPolicy::Noise));
+        gate.prepare(48000.0);
+        let mut output = [0.5f32; 16];
+        let mut buffer = Buffer::new([&input[..]], [&mut output[..]], 16);
+        gate.finish(&mut buffer);
+        assert!(output.iter().any(|&s| s != 0.5));
+        assert!(output.iter().all(|&s| (s - 0.5).abs() <= 0.11));
+    }
+}
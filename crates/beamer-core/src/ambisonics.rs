@@ -0,0 +1,149 @@
+//! Ambisonic orders for spatial plugins with a user-selected bus width.
+//!
+//! An [`AmbisonicOrderParameter`] selects the order. The descriptor reports
+//! buses of the matching width and returns the order from
+//! [`Descriptor::bus_layout_id`](crate::Descriptor::bus_layout_id), so the
+//! wrappers renegotiate the buses with the host when it changes:
+//!
+//! ```ignore
+//! #[derive(Parameters)]
+//! pub struct EncoderParameters {
+//!     #[parameter(id = "order", name = "Order")]
+//!     pub order: AmbisonicOrderParameter,
+//! }
+//!
+//! impl Descriptor for EncoderDescriptor {
+//!     type Setup = (SampleRate, MainOutputChannels);
+//!
+//!     fn input_bus_info(&self, index: usize) -> Option<BusInfo> {
+//!         (index == 0).then(|| BusInfo::mono("Input"))
+//!     }
+//!     fn output_bus_info(&self, index: usize) -> Option<BusInfo> {
+//!         (index == 0).then(|| self.parameters.order.get().bus("Output"))
+//!     }
+//!     fn bus_layout_id(parameters: &EncoderParameters) -> u32 {
+//!         parameters.order.get().order()
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! The processor is rebuilt with the new width at the next activation:
+//! `prepare()` sees the new channel counts, scratch buffers are allocated
+//! for them, and the old processor's state is carried over through
+//! `save_state`/`load_state`.
+
+use crate::parameter_types::{EnumParameter, EnumParameterValue};
+use crate::plugin::{BusInfo, BusType};
+use crate::speaker_layout::SpeakerLayout;
+
+/// Ambisonic order, up to the fourth (25 channels, within
+/// [`MAX_CHANNELS`](crate::MAX_CHANNELS)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AmbisonicOrder {
+    /// First order, 4 channels.
+    #[default]
+    First,
+    /// Second order, 9 channels.
+    Second,
+    /// Third order, 16 channels.
+    Third,
+    /// Fourth order, 25 channels.
+    Fourth,
+}
+
+const VALUES: [AmbisonicOrder; 4] = [
+    AmbisonicOrder::First,
+    AmbisonicOrder::Second,
+    AmbisonicOrder::Third,
+    AmbisonicOrder::Fourth,
+];
+
+const NAMES: &[&str] = &["1st Order", "2nd Order", "3rd Order", "4th Order"];
+
+impl AmbisonicOrder {
+    /// The order as a number (1 for first order).
+    pub const fn order(self) -> u32 {
+        self as u32 + 1
+    }
+
+    /// Number of channels, `(order + 1)²`.
+    pub const fn channel_count(self) -> u32 {
+        let n = self.order() + 1;
+        n * n
+    }
+
+    /// The order with `channel_count` channels, if any.
+    pub fn from_channel_count(channel_count: u32) -> Option<Self> {
+        VALUES.into_iter().find(|order| order.channel_count() == channel_count)
+    }
+
+    /// A main bus of this order's width.
+    pub const fn bus(self, name: &'static str) -> BusInfo {
+        BusInfo {
+            name,
+            bus_type: BusType::Main,
+            channel_count: self.channel_count(),
+            is_default_active: true,
+            speaker_layout: Some(SpeakerLayout::Ambisonic(self)),
+        }
+    }
+}
+
+impl EnumParameterValue for AmbisonicOrder {
+    const COUNT: usize = VALUES.len();
+    const DEFAULT_INDEX: usize = 0;
+
+    fn from_index(index: usize) -> Option<Self> {
+        VALUES.get(index).copied()
+    }
+
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn default_value() -> Self {
+        Self::First
+    }
+
+    fn name(index: usize) -> &'static str {
+        NAMES.get(index).copied().unwrap_or("")
+    }
+
+    fn names() -> &'static [&'static str] {
+        NAMES
+    }
+}
+
+/// Enum parameter selecting an [`AmbisonicOrder`].
+///
+/// Recognized by `#[derive(Parameters)]` like any `EnumParameter`. Host
+/// automation does not renegotiate the buses, so build it with
+/// `.non_automatable()`.
+pub type AmbisonicOrderParameter = EnumParameter<AmbisonicOrder>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_counts_follow_the_order() {
+        let counts: Vec<u32> = VALUES.iter().map(|order| order.channel_count()).collect();
+        assert_eq!(counts, [4, 9, 16, 25]);
+        assert_eq!(AmbisonicOrder::from_channel_count(16), Some(AmbisonicOrder::Third));
+        assert_eq!(AmbisonicOrder::from_channel_count(2), None);
+        assert_eq!(AmbisonicOrder::Second.bus("Output").channel_count, 9);
+        assert!(AmbisonicOrder::Fourth.channel_count() as usize <= crate::MAX_CHANNELS);
+    }
+
+    #[test]
+    fn indices_and_names_agree() {
+        for (index, name) in NAMES.iter().enumerate() {
+            let order = AmbisonicOrder::from_index(index).unwrap();
+            assert_eq!(order.to_index(), index);
+            assert_eq!(order.order(), index as u32 + 1);
+            assert_eq!(AmbisonicOrder::name(index), *name);
+        }
+        assert_eq!(AmbisonicOrderParameter::new("Order").get(), AmbisonicOrder::First);
+    }
+}
@@ -0,0 +1,488 @@
+//! Automatic gain matching with attack/release envelope followers.
+//!
+//! Saturation, EQ and compression change loudness as a side effect, which
+//! makes A/B comparisons unfair ("louder sounds better"). [`AutoGain`]
+//! measures the loudness of the plugin's input and output over a short window
+//! and applies makeup gain so the output matches the input. It is meant as a
+//! drop-in for a "gain match" toggle.
+//!
+//! # Overview
+//!
+//! - [`EnvelopeFollower`] - Attack/release follower over signal power
+//! - [`AutoGain`] - Input/output loudness matching with smoothed makeup gain
+//! - [`AutoGainMeter`] - Levels and applied makeup gain, readable from the GUI
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::{AutoGain, Buffer};
+//!
+//! struct MySaturator {
+//!     auto_gain: AutoGain,
+//! }
+//!
+//! impl Processor for MySaturator {
+//!     fn process(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers, context: &ProcessContext) {
+//!         self.auto_gain.set_enabled(self.parameters.gain_match.get());
+//!
+//!         // Measure before the DSP overwrites the input (in-place buffers)
+//!         self.auto_gain.measure_input(buffer);
+//!         self.saturate(buffer);
+//!         self.auto_gain.apply(buffer);
+//!     }
+//! }
+//! ```
+//!
+//! # GUI Events
+//!
+//! When a [`WebViewHandle`] is attached via [`AutoGainMeter::attach_webview()`],
+//! [`AutoGainMeter::notify()`] emits a `"beamer:autoGain"` event with the
+//! payload `{ "enabled": bool, "inputDb": number, "outputDb": number,
+//! "makeupDb": number }`. Emitting allocates, so call it from the GUI side
+//! (for example in response to a WebView event), never from `process()`.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "webview-bridge")]
+use std::sync::Mutex;
+
+use crate::buffer::Buffer;
+use crate::sample::Sample;
+#[cfg(feature = "webview-bridge")]
+use crate::webview_handle::WebViewHandle;
+
+/// Event name emitted to the WebView with the current auto-gain levels.
+pub const AUTO_GAIN_EVENT: &str = "beamer:autoGain";
+
+/// Default attack time of the loudness followers in milliseconds.
+pub const DEFAULT_ATTACK_MS: f64 = 10.0;
+
+/// Default release time of the loudness followers in milliseconds.
+pub const DEFAULT_RELEASE_MS: f64 = 300.0;
+
+/// Default limit for the applied makeup gain in dB (both directions).
+pub const DEFAULT_MAX_GAIN_DB: f64 = 24.0;
+
+/// Time constant for smoothing the applied gain in milliseconds.
+const GAIN_SMOOTHING_MS: f64 = 50.0;
+
+/// Input power below this (-80 dB) holds the current gain instead of
+/// chasing silence and boosting noise tails.
+const SILENCE_POWER: f64 = 1e-8;
+
+/// Smallest power reported by the meters, to avoid `-inf` dB.
+const MIN_POWER: f64 = 1e-12;
+
+/// One-pole coefficient for a time constant in milliseconds.
+fn time_coeff(time_ms: f64, sample_rate: f64) -> f64 {
+    if time_ms <= 0.0 || sample_rate <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time_ms * 0.001 * sample_rate)).exp()
+    }
+}
+
+/// Power (mean square) to dB.
+fn power_to_db(power: f64) -> f64 {
+    10.0 * power.max(MIN_POWER).log10()
+}
+
+// =============================================================================
+// EnvelopeFollower
+// =============================================================================
+
+/// Attack/release envelope follower over signal power.
+///
+/// Feed it squared samples (or the mean square across channels); the result
+/// is a smoothed mean square, so `sqrt()` gives an RMS level. Rising input is
+/// tracked with the attack time, falling input with the release time.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower {
+    attack_ms: f64,
+    release_ms: f64,
+    attack_coeff: f64,
+    release_coeff: f64,
+    envelope: f64,
+}
+
+impl EnvelopeFollower {
+    /// Create a follower. Call [`set_sample_rate()`](Self::set_sample_rate)
+    /// before processing.
+    pub fn new(attack_ms: f64, release_ms: f64) -> Self {
+        Self {
+            attack_ms,
+            release_ms,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            envelope: 0.0,
+        }
+    }
+
+    /// Recompute the coefficients for a new sample rate.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.attack_coeff = time_coeff(self.attack_ms, sample_rate);
+        self.release_coeff = time_coeff(self.release_ms, sample_rate);
+    }
+
+    /// Advance by one sample and return the new envelope.
+    #[inline]
+    pub fn process(&mut self, power: f64) -> f64 {
+        let coeff = if power > self.envelope {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.envelope = power + coeff * (self.envelope - power);
+        self.envelope
+    }
+
+    /// Current envelope (mean square).
+    #[inline]
+    pub fn value(&self) -> f64 {
+        self.envelope
+    }
+
+    /// Reset the envelope to zero.
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+    }
+}
+
+// =============================================================================
+// AutoGainMeter
+// =============================================================================
+
+/// Levels and makeup gain published by [`AutoGain`].
+///
+/// Cloning is cheap (reference counted). The audio thread only stores
+/// atomics, so a GUI can read the values at any time.
+#[derive(Clone, Default)]
+pub struct AutoGainMeter {
+    inner: Arc<MeterInner>,
+}
+
+#[derive(Default)]
+struct MeterInner {
+    enabled: AtomicBool,
+    /// Input loudness in dB, stored as f64 bits.
+    input_db: AtomicU64,
+    /// Output loudness (after makeup gain) in dB, stored as f64 bits.
+    output_db: AtomicU64,
+    /// Applied makeup gain in dB, stored as f64 bits.
+    makeup_db: AtomicU64,
+    /// Optional WebView for meter events. Only touched off the audio thread.
+    #[cfg(feature = "webview-bridge")]
+    webview: Mutex<Option<WebViewHandle>>,
+}
+
+impl std::fmt::Debug for AutoGainMeter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutoGainMeter")
+            .field("enabled", &self.is_enabled())
+            .field("input_db", &self.input_db())
+            .field("output_db", &self.output_db())
+            .field("makeup_db", &self.makeup_db())
+            .finish()
+    }
+}
+
+impl AutoGainMeter {
+    /// Whether gain matching is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Input loudness (RMS over the follower window) in dB.
+    pub fn input_db(&self) -> f64 {
+        f64::from_bits(self.inner.input_db.load(Ordering::Relaxed))
+    }
+
+    /// Output loudness after makeup gain in dB.
+    pub fn output_db(&self) -> f64 {
+        f64::from_bits(self.inner.output_db.load(Ordering::Relaxed))
+    }
+
+    /// Makeup gain currently applied in dB (0.0 when disabled).
+    pub fn makeup_db(&self) -> f64 {
+        f64::from_bits(self.inner.makeup_db.load(Ordering::Relaxed))
+    }
+
+    /// Attach a WebView handle to receive meter events.
+    #[cfg(feature = "webview-bridge")]
+    pub fn attach_webview(&self, handle: WebViewHandle) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = Some(handle);
+        }
+    }
+
+    /// Detach the WebView handle. Further notifications are not emitted.
+    #[cfg(feature = "webview-bridge")]
+    pub fn detach_webview(&self) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = None;
+        }
+    }
+
+    /// Emit the current values to the attached WebView, if any.
+    ///
+    /// **Not audio-thread safe.**
+    pub fn notify(&self) {
+        #[cfg(feature = "webview-bridge")]
+        {
+            let Ok(webview) = self.inner.webview.lock() else {
+                return;
+            };
+            if let Some(handle) = webview.as_ref() {
+                handle.emit(
+                    AUTO_GAIN_EVENT,
+                    &serde_json::json!({
+                        "enabled": self.is_enabled(),
+                        "inputDb": self.input_db(),
+                        "outputDb": self.output_db(),
+                        "makeupDb": self.makeup_db(),
+                    }),
+                );
+            }
+        }
+    }
+
+    fn store(&self, enabled: bool, input_power: f64, output_power: f64, gain: f64) {
+        let inner = &self.inner;
+        inner.enabled.store(enabled, Ordering::Relaxed);
+        inner.input_db.store(power_to_db(input_power).to_bits(), Ordering::Relaxed);
+        inner.output_db.store(power_to_db(output_power).to_bits(), Ordering::Relaxed);
+        inner.makeup_db.store(power_to_db(gain * gain).to_bits(), Ordering::Relaxed);
+    }
+}
+
+// =============================================================================
+// AutoGain
+// =============================================================================
+
+/// Matches output loudness to input loudness with smoothed makeup gain.
+///
+/// Call [`measure_input()`](Self::measure_input) before processing and
+/// [`apply()`](Self::apply) after. Both followers keep running while gain
+/// matching is disabled, so the meters stay live and enabling it does not
+/// jump. Toggling ramps the gain instead of switching it.
+///
+/// This struct performs no heap allocations while processing and is safe
+/// to use in `process()`.
+#[derive(Debug, Clone)]
+pub struct AutoGain {
+    input: EnvelopeFollower,
+    output: EnvelopeFollower,
+    /// Currently applied linear gain.
+    gain: f64,
+    gain_coeff: f64,
+    max_gain: f64,
+    enabled: bool,
+    meter: AutoGainMeter,
+}
+
+impl Default for AutoGain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AutoGain {
+    /// Create an auto-gain with the default times and gain limit, disabled.
+    pub fn new() -> Self {
+        Self {
+            input: EnvelopeFollower::new(DEFAULT_ATTACK_MS, DEFAULT_RELEASE_MS),
+            output: EnvelopeFollower::new(DEFAULT_ATTACK_MS, DEFAULT_RELEASE_MS),
+            gain: 1.0,
+            gain_coeff: 0.0,
+            max_gain: 10f64.powf(DEFAULT_MAX_GAIN_DB / 20.0),
+            enabled: false,
+            meter: AutoGainMeter::default(),
+        }
+    }
+
+    /// Set the attack and release times of the loudness followers.
+    ///
+    /// Longer release gives a steadier "integrated" match; shorter release
+    /// follows the material more closely.
+    pub fn with_times(mut self, attack_ms: f64, release_ms: f64) -> Self {
+        self.input = EnvelopeFollower::new(attack_ms, release_ms);
+        self.output = EnvelopeFollower::new(attack_ms, release_ms);
+        self
+    }
+
+    /// Limit the makeup gain to +/- `max_gain_db`.
+    pub fn with_max_gain_db(mut self, max_gain_db: f64) -> Self {
+        self.max_gain = 10f64.powf(max_gain_db.abs() / 20.0);
+        self
+    }
+
+    /// Recompute the follower and smoothing coefficients.
+    ///
+    /// Call from `prepare()` or whenever the sample rate changes.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.input.set_sample_rate(sample_rate);
+        self.output.set_sample_rate(sample_rate);
+        self.gain_coeff = time_coeff(GAIN_SMOOTHING_MS, sample_rate);
+    }
+
+    /// Turn gain matching on or off. The gain ramps to its new target.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Whether gain matching is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Applied makeup gain in dB.
+    pub fn makeup_gain_db(&self) -> f64 {
+        power_to_db(self.gain * self.gain)
+    }
+
+    /// Shared meter for the GUI.
+    pub fn meter(&self) -> &AutoGainMeter {
+        &self.meter
+    }
+
+    /// Reset the followers and return to unity gain.
+    pub fn reset(&mut self) {
+        self.input.reset();
+        self.output.reset();
+        self.gain = 1.0;
+    }
+
+    /// Track the input loudness. Call before processing the buffer.
+    pub fn measure_input<S: Sample>(&mut self, buffer: &Buffer<S>) {
+        let channels = buffer.num_input_channels();
+        if channels == 0 {
+            return;
+        }
+        let scale = 1.0 / channels as f64;
+        for i in 0..buffer.num_samples() {
+            let power: f64 = buffer.inputs().map(|input| input[i].to_f64().powi(2)).sum();
+            self.input.process(power * scale);
+        }
+    }
+
+    /// Track the output loudness and apply makeup gain in place.
+    ///
+    /// Call after processing, with the same buffer passed to
+    /// [`measure_input()`](Self::measure_input).
+    pub fn apply<S: Sample>(&mut self, buffer: &mut Buffer<S>) {
+        let channels = buffer.num_output_channels();
+        if channels == 0 {
+            return;
+        }
+        let scale = 1.0 / channels as f64;
+        let input_power = self.input.value();
+
+        for i in 0..buffer.num_samples() {
+            let mut power = 0.0;
+            for ch in 0..channels {
+                power += buffer.output(ch)[i].to_f64().powi(2);
+            }
+            let output_power = self.output.process(power * scale);
+
+            let target = if !self.enabled {
+                1.0
+            } else if input_power < SILENCE_POWER || output_power < SILENCE_POWER {
+                self.gain
+            } else {
+                (input_power / output_power)
+                    .sqrt()
+                    .clamp(1.0 / self.max_gain, self.max_gain)
+            };
+            self.gain = target + self.gain_coeff * (self.gain - target);
+
+            let gain = S::from_f64(self.gain);
+            for ch in 0..channels {
+                let sample = &mut buffer.output(ch)[i];
+                *sample = *sample * gain;
+            }
+        }
+
+        let output_power = self.output.value() * self.gain * self.gain;
+        self.meter.store(self.enabled, input_power, output_power, self.gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+    const BLOCK: usize = 256;
+
+    /// Run `blocks` of a constant-amplitude signal through `auto_gain`, with
+    /// the "DSP" scaling the output by `dsp_gain`.
+    fn run(auto_gain: &mut AutoGain, amplitude: f32, dsp_gain: f32, blocks: usize) -> f32 {
+        let input = [amplitude; BLOCK];
+        let mut output = [0.0f32; BLOCK];
+        for _ in 0..blocks {
+            let mut buffer = Buffer::new([&input[..]], [&mut output[..]], BLOCK);
+            auto_gain.measure_input(&buffer);
+            for (i, o) in buffer.zip_channels() {
+                for (x, y) in i.iter().zip(o.iter_mut()) {
+                    *y = *x * dsp_gain;
+                }
+            }
+            auto_gain.apply(&mut buffer);
+        }
+        output[BLOCK - 1]
+    }
+
+    fn auto_gain() -> AutoGain {
+        let mut auto_gain = AutoGain::new();
+        auto_gain.set_sample_rate(SAMPLE_RATE);
+        auto_gain
+    }
+
+    #[test]
+    fn follower_attacks_and_releases() {
+        let mut follower = EnvelopeFollower::new(1.0, 100.0);
+        follower.set_sample_rate(SAMPLE_RATE);
+        for _ in 0..480 {
+            follower.process(1.0);
+        }
+        assert!(follower.value() > 0.99);
+        for _ in 0..480 {
+            follower.process(0.0);
+        }
+        // 10 ms into a 100 ms release: still most of the way up
+        assert!(follower.value() > 0.85 && follower.value() < 0.95);
+    }
+
+    #[test]
+    fn matches_output_to_input() {
+        let mut auto_gain = auto_gain();
+        auto_gain.set_enabled(true);
+        let out = run(&mut auto_gain, 0.5, 2.0, 400);
+        assert!((out - 0.5).abs() < 0.01, "output {out}");
+        assert!((auto_gain.makeup_gain_db() + 6.02).abs() < 0.1);
+        assert!((auto_gain.meter().makeup_db() + 6.02).abs() < 0.1);
+        assert!((auto_gain.meter().output_db() - auto_gain.meter().input_db()).abs() < 0.1);
+    }
+
+    #[test]
+    fn disabled_passes_through_and_meters() {
+        let mut auto_gain = auto_gain();
+        let out = run(&mut auto_gain, 0.5, 2.0, 200);
+        assert_eq!(out, 1.0);
+        assert_eq!(auto_gain.meter().makeup_db(), 0.0);
+        assert!(!auto_gain.meter().is_enabled());
+        assert!((auto_gain.meter().output_db() - auto_gain.meter().input_db() - 6.02).abs() < 0.1);
+    }
+
+    #[test]
+    fn gain_is_limited_and_held_in_silence() {
+        let mut auto_gain = auto_gain().with_max_gain_db(6.0);
+        auto_gain.set_sample_rate(SAMPLE_RATE);
+        auto_gain.set_enabled(true);
+        run(&mut auto_gain, 0.5, 0.01, 400);
+        assert!((auto_gain.makeup_gain_db() - 6.0).abs() < 0.1);
+
+        run(&mut auto_gain, 0.0, 1.0, 400);
+        assert!((auto_gain.makeup_gain_db() - 6.0).abs() < 0.1);
+    }
+}
@@ -0,0 +1,241 @@
+//! Host automation versus user value, for GUIs.
+//!
+//! A parameter has two sources of truth while a session plays back: the value
+//! the host delivers with each process call (its automation lane) and the
+//! value the user last set from the plugin GUI. VST3 keeps these apart as the
+//! processor and edit-controller values; AU has a single value per
+//! parameter. [`AutomationPreview`] tracks both for every parameter so a GUI
+//! can show "automation is driving this knob" without guessing from value
+//! changes.
+//!
+//! The wrapper feeds it from two sides:
+//!
+//! - **Audio thread:** [`AutomationPreview::host_value`] for every parameter
+//!   change the host delivers with a process/render call.
+//! - **GUI:** [`AutomationPreview::user_value`],
+//!   [`AutomationPreview::begin_gesture`] and
+//!   [`AutomationPreview::end_gesture`] from the WebView `param:set`,
+//!   `param:begin` and `param:end` messages.
+//!
+//! A parameter counts as *automated* once the host delivers a value that
+//! differs from the user's value while no gesture is active. Hosts that echo
+//! GUI edits back through the process call (VST3 `performEdit`, touch-mode
+//! write) deliver the user's own value and do not trip it. The flag clears
+//! as soon as the user edits the parameter again.
+//!
+//! # GUI Invoke
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`AUTOMATION_STATE_INVOKE`] | `[id]` | `{hostValue, userValue, automated, touched}` or `null` |
+//!
+//! Values are normalized; `hostValue`/`userValue` are `null` until the host
+//! or user has set the parameter.
+//!
+//! **Audio-thread safe.** Every field is atomic and the ID lookup is a binary
+//! search over a table built once at construction.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::parameter_store::ParameterStore;
+use crate::types::ParameterId;
+
+/// Built-in WebView invoke method that returns a parameter's automation state.
+pub const AUTOMATION_STATE_INVOKE: &str = "_beamer/automationState";
+
+/// Values closer than this are treated as the same (float round-trips
+/// through the host).
+const VALUE_EPSILON: f64 = 1e-6;
+
+/// Bit pattern marking "no value yet" (a quiet NaN).
+const NO_VALUE: u64 = 0x7ff8_0000_0000_0000;
+
+/// Per-instance host/user value tracking for every parameter.
+///
+/// Cloning is cheap (reference counted), so the wrapper can share one
+/// tracker between its audio path and its WebView IPC context.
+#[derive(Clone, Debug, Default)]
+pub struct AutomationPreview {
+    inner: Arc<[(ParameterId, Slot)]>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    host: AtomicU64,
+    user: AtomicU64,
+    touched: AtomicBool,
+    automated: AtomicBool,
+}
+
+impl Default for Slot {
+    fn default() -> Self {
+        Self {
+            host: AtomicU64::new(NO_VALUE),
+            user: AtomicU64::new(NO_VALUE),
+            touched: AtomicBool::new(false),
+            automated: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Snapshot of one parameter's automation state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutomationState {
+    /// Last normalized value delivered by the host, if any.
+    pub host_value: Option<f64>,
+    /// Last normalized value set from the GUI, if any.
+    pub user_value: Option<f64>,
+    /// True while host automation overrides the user's value.
+    pub automated: bool,
+    /// True while the user holds a GUI gesture on the parameter.
+    pub touched: bool,
+}
+
+impl AutomationPreview {
+    /// Build a tracker for every parameter in `store`.
+    pub fn new(store: &dyn ParameterStore) -> Self {
+        Self::from_ids((0..store.count()).filter_map(|i| store.info(i).map(|info| info.id)))
+    }
+
+    fn from_ids(ids: impl Iterator<Item = ParameterId>) -> Self {
+        let mut ids: Vec<ParameterId> = ids.collect();
+        ids.sort_unstable();
+        ids.dedup();
+        Self {
+            inner: ids.into_iter().map(|id| (id, Slot::default())).collect(),
+        }
+    }
+
+    /// Record a value delivered by the host. Call from the audio thread.
+    pub fn host_value(&self, id: ParameterId, value: f64) {
+        let Some(slot) = self.slot(id) else { return };
+        slot.host.store(value.to_bits(), Ordering::Relaxed);
+        if slot.touched.load(Ordering::Relaxed) {
+            return;
+        }
+        let user = f64::from_bits(slot.user.load(Ordering::Relaxed));
+        if user.is_nan() || (user - value).abs() > VALUE_EPSILON {
+            slot.automated.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a value set from the GUI. Clears the automated flag.
+    pub fn user_value(&self, id: ParameterId, value: f64) {
+        let Some(slot) = self.slot(id) else { return };
+        slot.user.store(value.to_bits(), Ordering::Relaxed);
+        slot.automated.store(false, Ordering::Relaxed);
+    }
+
+    /// The user grabbed the parameter in the GUI.
+    pub fn begin_gesture(&self, id: ParameterId) {
+        if let Some(slot) = self.slot(id) {
+            slot.touched.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// The user released the parameter in the GUI.
+    pub fn end_gesture(&self, id: ParameterId) {
+        if let Some(slot) = self.slot(id) {
+            slot.touched.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Current state of a parameter, or `None` for an unknown ID.
+    pub fn state(&self, id: ParameterId) -> Option<AutomationState> {
+        let slot = self.slot(id)?;
+        let value = |bits: &AtomicU64| {
+            let v = f64::from_bits(bits.load(Ordering::Relaxed));
+            (!v.is_nan()).then_some(v)
+        };
+        Some(AutomationState {
+            host_value: value(&slot.host),
+            user_value: value(&slot.user),
+            automated: slot.automated.load(Ordering::Relaxed),
+            touched: slot.touched.load(Ordering::Relaxed),
+        })
+    }
+
+    fn slot(&self, id: ParameterId) -> Option<&Slot> {
+        self.inner
+            .binary_search_by_key(&id, |&(pid, _)| pid)
+            .ok()
+            .map(|index| &self.inner[index].1)
+    }
+}
+
+/// Handle the built-in [`AUTOMATION_STATE_INVOKE`] WebView call.
+pub fn automation_state_invoke(
+    preview: &AutomationPreview,
+    args: &[serde_json::Value],
+) -> serde_json::Value {
+    let state = args
+        .first()
+        .and_then(|v| v.as_u64())
+        .and_then(|id| preview.state(id as ParameterId));
+    match state {
+        Some(state) => serde_json::json!({
+            "hostValue": state.host_value,
+            "userValue": state.user_value,
+            "automated": state.automated,
+            "touched": state.touched,
+        }),
+        None => serde_json::Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_change_marks_automated_until_user_edit() {
+        let preview = AutomationPreview::from_ids([3, 1, 2].into_iter());
+        preview.user_value(2, 0.5);
+        preview.host_value(2, 0.75);
+
+        let state = preview.state(2).unwrap();
+        assert_eq!(state.host_value, Some(0.75));
+        assert_eq!(state.user_value, Some(0.5));
+        assert!(state.automated);
+
+        preview.user_value(2, 0.25);
+        assert!(!preview.state(2).unwrap().automated);
+        assert_eq!(preview.state(1).unwrap().host_value, None);
+        assert_eq!(preview.state(9), None);
+    }
+
+    #[test]
+    fn test_echoed_and_touched_values_are_not_automation() {
+        let preview = AutomationPreview::from_ids([1].into_iter());
+        // The host echoing the GUI edit back is not automation.
+        preview.user_value(1, 0.5);
+        preview.host_value(1, 0.5);
+        assert!(!preview.state(1).unwrap().automated);
+
+        // Neither is anything the host sends while the user holds the knob.
+        preview.begin_gesture(1);
+        preview.host_value(1, 0.9);
+        let state = preview.state(1).unwrap();
+        assert!(state.touched);
+        assert!(!state.automated);
+        preview.end_gesture(1);
+        assert!(!preview.state(1).unwrap().touched);
+    }
+
+    #[test]
+    fn test_invoke() {
+        let preview = AutomationPreview::from_ids([7].into_iter());
+        preview.host_value(7, 1.0);
+        assert_eq!(
+            automation_state_invoke(&preview, &[serde_json::Value::from(7)]),
+            serde_json::json!({
+                "hostValue": 1.0,
+                "userValue": null,
+                "automated": true,
+                "touched": false,
+            })
+        );
+        assert_eq!(automation_state_invoke(&preview, &[]), serde_json::Value::Null);
+    }
+}
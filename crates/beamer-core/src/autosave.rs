@@ -0,0 +1,598 @@
+//! Time-based autosave and crash recovery of plugin state.
+//!
+//! Hosts only ask for the plugin state when the user saves the project, so
+//! a host crash loses every tweak since the last save. [`Autosave`] keeps a
+//! rolling snapshot of the state in a per-instance temp file and offers it
+//! back after a crash.
+//!
+//! # How It Works
+//!
+//! Autosave is opt-in through
+//! [`Descriptor::autosave_interval()`](crate::Descriptor::autosave_interval).
+//! The wrapper takes a snapshot with `save_state()` on the main thread, at
+//! most once per interval (on host and GUI parameter edits, and from the
+//! GUI sync timer while the editor is open). A background thread writes it
+//! atomically and touches the file once per interval as a heartbeat, so the
+//! audio thread and the host's thread never wait for the disk.
+//!
+//! Each file also lists the last few states the host saved or restored
+//! (by hash). A clean shutdown removes the file.
+//!
+//! # Recovery
+//!
+//! A file whose heartbeat is older than three intervals (at least 30
+//! seconds) belongs to an instance that died. When the host restores a
+//! state, the instance claims the newest orphan that lists this state, i.e.
+//! edits made on top of exactly this project, unless the snapshot equals the
+//! restored state (nothing was lost). An instance the host never restores
+//! claims an orphan of an instance that was never restored either (a new,
+//! unsaved project).
+//!
+//! The recovered state is not applied automatically. The GUI is told
+//! through the [`AUTOSAVE_EVENT`] event and decides with
+//! `__BEAMER__.resolveAutosave(restore)`; declining deletes the snapshot.
+//!
+//! Files live in the system temp directory:
+//! `<temp>/beamer-autosave/<vendor>/<plugin>/<pid>-<n>.autosave`.
+//!
+//! **Not audio-thread safe.** Snapshots allocate and hash the state.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::logging;
+
+/// Built-in WebView invoke method that returns the pending recovery, if any.
+pub const AUTOSAVE_INVOKE: &str = "_beamer/autosave";
+
+/// Built-in WebView invoke method that restores (`true`) or discards
+/// (`false`) the pending recovery.
+pub const RESOLVE_AUTOSAVE_INVOKE: &str = "_beamer/resolveAutosave";
+
+/// Event sent to the GUI when a recovery becomes available.
+pub const AUTOSAVE_EVENT: &str = "beamer:autosaveRecovery";
+
+/// Shortest time without a heartbeat before a file counts as orphaned.
+const MIN_STALE: Duration = Duration::from_secs(30);
+
+/// Shortest supported interval.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// File header magic.
+const MAGIC: &[u8; 4] = b"BASV";
+
+/// Header before the host state list: magic, flags, list length.
+const HEADER_LEN: usize = 4 + 1 + 1;
+
+/// Header flag: the host restored a state into the instance.
+const FLAG_RESTORED: u8 = 1;
+
+/// Host states remembered per instance.
+const MAX_HOST_STATES: usize = 16;
+
+/// Instance counter for unique file names within a process.
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(0);
+
+/// A recovered state offered to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutosaveRecovery {
+    /// When the snapshot was written.
+    pub saved_at: SystemTime,
+    /// The state blob, as the wrapper's `getState()` writes it.
+    pub data: Vec<u8>,
+}
+
+/// Per-instance autosave.
+///
+/// Cloning is cheap (reference counted), so the wrapper can share it with
+/// its WebView IPC context. The default instance is disabled: every call is
+/// a no-op.
+#[derive(Clone, Debug, Default)]
+pub struct Autosave {
+    inner: Option<Arc<AutosaveInner>>,
+}
+
+#[derive(Debug)]
+struct AutosaveInner {
+    /// Directory shared by all instances of the plugin.
+    directory: PathBuf,
+    /// This instance's file.
+    path: PathBuf,
+    interval: Duration,
+    state: Mutex<AutosaveState>,
+}
+
+#[derive(Debug, Default)]
+struct AutosaveState {
+    /// Hashes of the last states the host saved or restored, oldest first.
+    host_states: Vec<u64>,
+    /// Whether the host restored a state into this instance.
+    restored: bool,
+    /// When the last snapshot was taken.
+    last_snapshot: Option<Instant>,
+    /// Snapshot waiting for the writer thread.
+    pending: Option<Vec<u8>>,
+    /// Hash of the state on disk (or known to the host).
+    written: Option<u64>,
+    /// Recovery offered to the user.
+    recovery: Option<AutosaveRecovery>,
+    /// Recovery the user accepted, waiting for the wrapper to apply it.
+    accepted: Option<Vec<u8>>,
+    /// Whether an orphan has been looked for.
+    searched: bool,
+    /// Set when a recovery becomes available, cleared by `take_changed()`.
+    changed: bool,
+}
+
+impl Autosave {
+    /// Create an autosave for a plugin, or a disabled one if `interval` is
+    /// `None`.
+    ///
+    /// Intervals below one second are raised to one second.
+    pub fn new(config: &Config, interval: Option<Duration>) -> Self {
+        let Some(interval) = interval else {
+            return Self::default();
+        };
+        let directory = std::env::temp_dir()
+            .join("beamer-autosave")
+            .join(logging::sanitize_file_name(config.vendor))
+            .join(logging::sanitize_file_name(config.name));
+        let autosave = Self::with_directory(directory, interval);
+        if let Some(inner) = &autosave.inner {
+            spawn_writer(Arc::downgrade(inner));
+        }
+        autosave
+    }
+
+    fn with_directory(directory: PathBuf, interval: Duration) -> Self {
+        let instance = NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed);
+        let path = directory.join(format!("{}-{instance}.autosave", std::process::id()));
+        Self {
+            inner: Some(Arc::new(AutosaveInner {
+                directory,
+                path,
+                interval: interval.max(MIN_INTERVAL),
+                state: Mutex::new(AutosaveState::default()),
+            })),
+        }
+    }
+
+    /// Returns true if autosave is enabled for this instance.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Returns true if a snapshot is due.
+    ///
+    /// Check this before calling `save_state()`, so the state is only
+    /// serialized once per interval.
+    pub fn is_due(&self) -> bool {
+        self.with_state(false, |inner, state| {
+            state
+                .last_snapshot
+                .is_none_or(|last| last.elapsed() >= inner.interval)
+        })
+    }
+
+    /// Hand a snapshot to the writer thread.
+    pub fn snapshot(&self, data: Vec<u8>) {
+        self.with_state((), |_, state| {
+            state.last_snapshot = Some(Instant::now());
+            state.pending = Some(data);
+        });
+    }
+
+    /// Record a state the host just saved.
+    ///
+    /// Hosts also save for undo, so this doesn't delete the file; it only
+    /// lets a later restore of this state find the snapshot.
+    pub fn record_host_state(&self, data: &[u8]) {
+        self.with_state((), |_, state| state.record_host_state(state_hash(data)));
+    }
+
+    /// Record a state the host restores and claim the orphaned snapshot
+    /// taken on top of it, if any.
+    pub fn claim(&self, data: &[u8]) {
+        self.with_state((), |inner, state| {
+            let hash = state_hash(data);
+            state.record_host_state(hash);
+            state.restored = true;
+            state.searched = true;
+            if let Some(recovery) = inner.claim_orphan(Some(hash)) {
+                log::info!("Found an autosaved state from a previous session");
+                state.recovery = Some(recovery);
+                state.changed = true;
+            }
+        });
+    }
+
+    /// The recovery offered to the user, if any.
+    ///
+    /// An instance the host never restored looks for an orphan of a
+    /// never-restored instance on the first call.
+    pub fn recovery(&self) -> Option<AutosaveRecovery> {
+        self.with_state(None, |inner, state| {
+            if !state.searched && !state.restored {
+                state.searched = true;
+                state.recovery = inner.claim_orphan(None);
+            }
+            state.recovery.clone()
+        })
+    }
+
+    /// Accept (`restore = true`) or discard the pending recovery.
+    ///
+    /// An accepted state is returned by [`take_accepted()`](Self::take_accepted)
+    /// for the wrapper to apply. Returns false if there was nothing to resolve.
+    pub fn resolve(&self, restore: bool) -> bool {
+        self.with_state(false, |_, state| match state.recovery.take() {
+            Some(recovery) => {
+                if restore {
+                    state.accepted = Some(recovery.data);
+                }
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Take the state the user chose to restore.
+    pub fn take_accepted(&self) -> Option<Vec<u8>> {
+        self.with_state(None, |_, state| state.accepted.take())
+    }
+
+    /// Returns true (once) after a recovery became available.
+    ///
+    /// The wrapper polls this to send [`AUTOSAVE_EVENT`] to an open GUI.
+    pub fn take_changed(&self) -> bool {
+        self.with_state(false, |_, state| std::mem::take(&mut state.changed))
+    }
+
+    fn with_state<R>(&self, disabled: R, f: impl FnOnce(&AutosaveInner, &mut AutosaveState) -> R) -> R {
+        let Some(inner) = &self.inner else {
+            return disabled;
+        };
+        let Ok(mut state) = inner.state.lock() else {
+            return disabled;
+        };
+        f(inner, &mut state)
+    }
+}
+
+/// JSON description of the pending recovery for the GUI.
+pub fn recovery_json(recovery: Option<&AutosaveRecovery>) -> serde_json::Value {
+    let saved_at = recovery
+        .and_then(|r| r.saved_at.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+    serde_json::json!({
+        "available": recovery.is_some(),
+        "savedAt": saved_at,
+    })
+}
+
+/// Handle the built-in autosave WebView calls.
+///
+/// Returns `None` if `method` is not an autosave method. After
+/// [`RESOLVE_AUTOSAVE_INVOKE`] the wrapper applies
+/// [`Autosave::take_accepted()`].
+pub fn autosave_invoke(
+    autosave: &Autosave,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    match method {
+        AUTOSAVE_INVOKE => Some(recovery_json(autosave.recovery().as_ref())),
+        RESOLVE_AUTOSAVE_INVOKE => {
+            let restore = args.first().and_then(|v| v.as_bool()).unwrap_or(false);
+            Some(serde_json::Value::from(autosave.resolve(restore)))
+        }
+        _ => None,
+    }
+}
+
+impl AutosaveState {
+    fn record_host_state(&mut self, hash: u64) {
+        self.host_states.retain(|&h| h != hash);
+        if self.host_states.len() == MAX_HOST_STATES {
+            self.host_states.remove(0);
+        }
+        self.host_states.push(hash);
+        // The host has this state; snapshotting it again is pointless.
+        self.written = Some(hash);
+    }
+}
+
+impl AutosaveInner {
+    /// Write a pending snapshot, or touch the file as a heartbeat.
+    fn tick(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let Some(data) = state.pending.take() else {
+            drop(state);
+            self.touch();
+            return;
+        };
+        let hash = state_hash(&data);
+        if state.written == Some(hash) {
+            drop(state);
+            self.touch();
+            return;
+        }
+        state.written = Some(hash);
+        let flags = if state.restored { FLAG_RESTORED } else { 0 };
+        let host_states = state.host_states.clone();
+        drop(state);
+        self.write(flags, &host_states, &data);
+    }
+
+    /// Refresh the heartbeat, if the file exists.
+    fn touch(&self) {
+        let _ = fs::File::options()
+            .write(true)
+            .open(&self.path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+    }
+
+    /// Write the file atomically (temp file and rename).
+    fn write(&self, flags: u8, host_states: &[u64], data: &[u8]) {
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mut contents = Vec::with_capacity(HEADER_LEN + 8 * (host_states.len() + 1) + data.len());
+        contents.extend_from_slice(MAGIC);
+        contents.push(flags);
+        contents.push(host_states.len() as u8);
+        for hash in host_states {
+            contents.extend_from_slice(&hash.to_le_bytes());
+        }
+        contents.extend_from_slice(&saved_at.to_le_bytes());
+        contents.extend_from_slice(data);
+
+        let temp = self.path.with_extension("tmp");
+        let result = fs::create_dir_all(&self.directory)
+            .and_then(|_| fs::write(&temp, &contents))
+            .and_then(|_| fs::rename(&temp, &self.path));
+        if let Err(e) = result {
+            log::debug!("Autosave not written: {e}");
+        }
+    }
+
+    /// Time without a heartbeat after which a file is orphaned.
+    fn stale_after(&self) -> Duration {
+        (self.interval * 3).max(MIN_STALE)
+    }
+
+    /// Find, read and delete the newest orphan taken on top of the restored
+    /// state `restored`, or of a never-restored instance for `None`.
+    fn claim_orphan(&self, restored: Option<u64>) -> Option<AutosaveRecovery> {
+        let entries = fs::read_dir(&self.directory).ok()?;
+        let mut best: Option<(PathBuf, AutosaveRecovery)> = None;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path == self.path || path.extension().is_none_or(|ext| ext != "autosave") {
+                continue;
+            }
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= self.stale_after());
+            if !stale {
+                continue;
+            }
+            let Some(file) = read_file(&path) else {
+                // Not ours or truncated: clean it up.
+                let _ = fs::remove_file(&path);
+                continue;
+            };
+            let matches = match restored {
+                Some(hash) => file.host_states.contains(&hash),
+                None => !file.restored,
+            };
+            if !matches {
+                continue;
+            }
+            let recovery = file.recovery;
+            // Nothing was lost: the host restores exactly this state.
+            if restored == Some(state_hash(&recovery.data)) {
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+            if best.as_ref().is_none_or(|(_, b)| recovery.saved_at > b.saved_at) {
+                best = Some((path, recovery));
+            }
+        }
+
+        let (path, recovery) = best?;
+        let _ = fs::remove_file(path);
+        Some(recovery)
+    }
+}
+
+impl Drop for AutosaveInner {
+    fn drop(&mut self) {
+        // Clean shutdown: nothing to recover.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Run the writer until the autosave is dropped.
+fn spawn_writer(inner: Weak<AutosaveInner>) {
+    let spawned = std::thread::Builder::new()
+        .name("beamer-autosave".into())
+        .spawn(move || loop {
+            let Some(interval) = inner.upgrade().map(|inner| inner.interval) else {
+                return;
+            };
+            std::thread::sleep(interval);
+            match inner.upgrade() {
+                Some(inner) => inner.tick(),
+                None => return,
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Autosave thread not started: {e}");
+    }
+}
+
+/// Contents of an autosave file.
+struct AutosaveFile {
+    restored: bool,
+    host_states: Vec<u64>,
+    recovery: AutosaveRecovery,
+}
+
+/// Parse an autosave file.
+fn read_file(path: &Path) -> Option<AutosaveFile> {
+    let contents = fs::read(path).ok()?;
+    if contents.len() < HEADER_LEN || &contents[..4] != MAGIC {
+        return None;
+    }
+    let count = contents[5] as usize;
+    let (hashes, rest) = contents[HEADER_LEN..].split_at_checked(8 * count)?;
+    let (seconds, data) = rest.split_at_checked(8)?;
+    let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap_or_default());
+    Some(AutosaveFile {
+        restored: contents[4] & FLAG_RESTORED != 0,
+        host_states: hashes.chunks_exact(8).map(read_u64).collect(),
+        recovery: AutosaveRecovery {
+            saved_at: UNIX_EPOCH + Duration::from_secs(read_u64(seconds)),
+            data: data.to_vec(),
+        },
+    })
+}
+
+/// FNV-1a 64-bit hash of a state blob.
+fn state_hash(data: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("beamer-autosave-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    /// Age every file in `directory` past the stale threshold.
+    fn age_files(directory: &Path) {
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        for entry in fs::read_dir(directory).unwrap().flatten() {
+            let file = fs::File::options().write(true).open(entry.path()).unwrap();
+            file.set_modified(old).unwrap();
+        }
+    }
+
+    fn inner(autosave: &Autosave) -> &AutosaveInner {
+        autosave.inner.as_ref().unwrap()
+    }
+
+    /// Write the pending snapshot and keep the file after the instance dies.
+    fn crash(autosave: Autosave) -> PathBuf {
+        inner(&autosave).tick();
+        let path = inner(&autosave).path.clone();
+        let kept = fs::read(&path).unwrap();
+        drop(autosave);
+        fs::write(&path, kept).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_orphan_claimed_by_restored_state() {
+        let directory = temp_directory("claim");
+        let crashed = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        crashed.claim(b"project");
+        crashed.snapshot(b"project with edits".to_vec());
+        let orphan = crash(crashed);
+
+        // Fresh files belong to live instances.
+        let other = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        other.claim(b"project");
+        assert!(other.recovery().is_none());
+
+        age_files(&directory);
+        let unrelated = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        unrelated.claim(b"another project");
+        assert!(unrelated.recovery().is_none());
+        let fresh = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        assert!(fresh.recovery().is_none());
+
+        let reopened = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        reopened.claim(b"project");
+        assert!(reopened.take_changed());
+        let recovery = reopened.recovery().unwrap();
+        assert_eq!(recovery.data, b"project with edits");
+        assert!(!orphan.exists());
+
+        assert!(reopened.resolve(true));
+        assert_eq!(reopened.take_accepted().unwrap(), b"project with edits");
+        assert!(reopened.recovery().is_none());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_saved_states_and_unrestored_instances() {
+        let directory = temp_directory("saved");
+
+        // New project, saved once, edited further, then the host crashes.
+        let crashed = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        crashed.record_host_state(b"saved");
+        crashed.snapshot(b"saved".to_vec());
+        inner(&crashed).tick();
+        assert!(!inner(&crashed).path.exists(), "the host already has this state");
+        crashed.snapshot(b"edited after save".to_vec());
+        crash(crashed);
+
+        // A snapshot identical to the restored state is discarded.
+        let same = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        same.claim(b"unchanged");
+        same.snapshot(b"edited".to_vec());
+        inner(&same).tick();
+        same.snapshot(b"unchanged".to_vec());
+        crash(same);
+
+        age_files(&directory);
+        let reopened = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        reopened.claim(b"saved");
+        assert_eq!(reopened.recovery().unwrap().data, b"edited after save");
+
+        let unchanged = Autosave::with_directory(directory.clone(), Duration::from_secs(5));
+        unchanged.claim(b"unchanged");
+        assert!(unchanged.recovery().is_none());
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 0);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_disabled_is_inert() {
+        let autosave = Autosave::default();
+        assert!(!autosave.is_enabled());
+        assert!(!autosave.is_due());
+        autosave.snapshot(vec![1, 2, 3]);
+        autosave.claim(b"state");
+        assert!(autosave.recovery().is_none());
+        assert_eq!(
+            autosave_invoke(&autosave, AUTOSAVE_INVOKE, &[]),
+            Some(serde_json::json!({"available": false, "savedAt": null}))
+        );
+        assert_eq!(autosave_invoke(&autosave, "other", &[]), None);
+    }
+}
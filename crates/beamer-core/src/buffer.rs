@@ -78,7 +78,11 @@
 //! }
 //! ```
 
+use crate::channel_mix::{mix_channels, ChannelMix};
+#[cfg(feature = "dsp")]
+use crate::gain_matrix::GainMatrix;
 use crate::sample::Sample;
+use crate::speaker_layout::{Speaker, SpeakerLayout};
 use crate::types::{MAX_AUX_BUSES, MAX_CHANNELS};
 
 // =============================================================================
@@ -106,6 +110,10 @@ use crate::types::{MAX_AUX_BUSES, MAX_CHANNELS};
 /// - Stereo: 0 = Left, 1 = Right
 /// - Surround: 0 = Left, 1 = Right, 2 = Center, etc.
 ///
+/// [`input_layout`](Self::input_layout) and [`output_layout`](Self::output_layout)
+/// name the speakers of the main buses; use
+/// [`output_channel_of`](Self::output_channel_of) to find e.g. the LFE channel.
+///
 /// # Real-Time Safety
 ///
 /// This struct uses fixed-size stack storage. No heap allocations occur
@@ -122,6 +130,10 @@ pub struct Buffer<'a, S: Sample = f32> {
     num_output_channels: usize,
     /// Number of samples in this processing block
     num_samples: usize,
+    /// Speaker layout of the main input bus, if declared
+    input_layout: Option<SpeakerLayout>,
+    /// Speaker layout of the main output bus, if declared
+    output_layout: Option<SpeakerLayout>,
 }
 
 impl<'a, S: Sample> Buffer<'a, S> {
@@ -156,9 +168,23 @@ impl<'a, S: Sample> Buffer<'a, S> {
             num_input_channels,
             num_output_channels,
             num_samples,
+            input_layout: None,
+            output_layout: None,
         }
     }
 
+    /// Set the speaker layouts of the main buses.
+    ///
+    /// Called by the wrappers with the prepared bus layouts. Without it, or
+    /// when a layout's width differs from the channel count, the usual
+    /// layout for the channel count is reported.
+    #[inline]
+    pub fn with_speaker_layouts(mut self, input: SpeakerLayout, output: SpeakerLayout) -> Self {
+        self.input_layout = Some(input);
+        self.output_layout = Some(output);
+        self
+    }
+
     // =========================================================================
     // Buffer Info
     // =========================================================================
@@ -193,6 +219,42 @@ impl<'a, S: Sample> Buffer<'a, S> {
         self.num_input_channels == 1 && self.num_output_channels == 1
     }
 
+    /// Speaker layout of the input channels.
+    #[inline]
+    pub fn input_layout(&self) -> SpeakerLayout {
+        resolve_layout(self.input_layout, self.num_input_channels)
+    }
+
+    /// Speaker layout of the output channels.
+    #[inline]
+    pub fn output_layout(&self) -> SpeakerLayout {
+        resolve_layout(self.output_layout, self.num_output_channels)
+    }
+
+    /// Speaker of input channel `channel`.
+    #[inline]
+    pub fn input_speaker(&self, channel: usize) -> Option<Speaker> {
+        self.input_layout().speaker(channel)
+    }
+
+    /// Speaker of output channel `channel`.
+    #[inline]
+    pub fn output_speaker(&self, channel: usize) -> Option<Speaker> {
+        self.output_layout().speaker(channel)
+    }
+
+    /// Input channel carrying `speaker`, if any.
+    #[inline]
+    pub fn input_channel_of(&self, speaker: Speaker) -> Option<usize> {
+        self.input_layout().channel_of(speaker)
+    }
+
+    /// Output channel carrying `speaker`, if any.
+    #[inline]
+    pub fn output_channel_of(&self, speaker: Speaker) -> Option<usize> {
+        self.output_layout().channel_of(speaker)
+    }
+
     // =========================================================================
     // Channel Access
     // =========================================================================
@@ -235,10 +297,96 @@ impl<'a, S: Sample> Buffer<'a, S> {
             .map(|ch| &mut ch[..n])
     }
 
+    // =========================================================================
+    // Fixed Channel Views
+    // =========================================================================
+
+    /// View the buffer as exactly `N` input and `N` output channels.
+    ///
+    /// Returns `None` if the runtime layout has a different channel count
+    /// on either side, so the caller can fall back to the generic path. DSP
+    /// written against the returned [`FixedBuffer`] indexes fixed-size
+    /// arrays, which the compiler unrolls without per-channel checks.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if let Some(mut stereo) = buffer.as_channels::<2>() {
+    ///     stereo.process_frames(|[l, r]| [(l + r) * half, (l - r) * half]);
+    /// } else {
+    ///     // Any other layout
+    ///     for (input, output) in buffer.zip_channels() { /* ... */ }
+    /// }
+    /// ```
+    #[inline]
+    pub fn as_channels<const N: usize>(&mut self) -> Option<FixedBuffer<'_, S, N>> {
+        if self.num_input_channels != N || self.num_output_channels != N {
+            return None;
+        }
+        let n = self.num_samples;
+        let inputs: [&[S]; N] =
+            std::array::from_fn(|ch| self.inputs[ch].map_or(&[][..], |input| &input[..n]));
+        let mut slots = self.outputs[..N].iter_mut();
+        let outputs: [&mut [S]; N] = std::array::from_fn(|_| {
+            slots
+                .next()
+                .and_then(|opt| opt.as_mut())
+                .map_or(Default::default(), |output| &mut output[..n])
+        });
+
+        // A missing channel slot would leave a short slice.
+        if inputs.iter().any(|ch| ch.len() != n) || outputs.iter().any(|ch| ch.len() != n) {
+            return None;
+        }
+        Some(FixedBuffer {
+            inputs,
+            outputs,
+            num_samples: n,
+        })
+    }
+
+    /// View the buffer as mono (1 in, 1 out). See [`as_channels()`](Self::as_channels).
+    #[inline]
+    pub fn as_mono(&mut self) -> Option<FixedBuffer<'_, S, 1>> {
+        self.as_channels::<1>()
+    }
+
+    /// View the buffer as stereo (2 in, 2 out). See [`as_channels()`](Self::as_channels).
+    #[inline]
+    pub fn as_stereo(&mut self) -> Option<FixedBuffer<'_, S, 2>> {
+        self.as_channels::<2>()
+    }
+
     // =========================================================================
     // Iterators
     // =========================================================================
 
+    /// Walk the block one sample frame at a time, with the aux buses.
+    ///
+    /// Each step yields the main input frame, the main output frame and the
+    /// aux input frames for the same sample, so sample-accurate code (gates,
+    /// duckers, envelope followers keyed from a sidechain) needs no parallel
+    /// indices. The frames borrow the buffer, so this is a `while let` loop
+    /// rather than an `Iterator`:
+    ///
+    /// ```ignore
+    /// let mut frames = buffer.frames(aux);
+    /// while let Some((input, mut output, aux)) = frames.next_frame() {
+    ///     let gain = self.ducker.process(aux.sidechain(0).to_f64());
+    ///     for ch in 0..output.num_channels() {
+    ///         output.set(ch, input.sample(ch) * S::from_f64(gain));
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn frames<'f>(&'f mut self, aux: &'f AuxiliaryBuffers<'f, S>) -> Frames<'f, 'a, S> {
+        Frames {
+            buffer: self,
+            aux,
+            index: 0,
+        }
+    }
+
     /// Iterate over all input channels.
     #[inline]
     pub fn inputs(&self) -> impl Iterator<Item = &[S]> + '_ {
@@ -286,10 +434,39 @@ impl<'a, S: Sample> Buffer<'a, S> {
             })
     }
 
+    // =========================================================================
+    // Sub-Blocks
+    // =========================================================================
+
+    /// Borrow samples `start..start + len` of every channel as a shorter
+    /// buffer.
+    ///
+    /// The range is clamped to the block, and the channel counts stay the
+    /// same. Used by the wrappers to split a host block (for control-rate
+    /// ticks). Real-time safe.
+    pub fn slice(&mut self, start: usize, len: usize) -> Buffer<'_, S> {
+        let start = start.min(self.num_samples);
+        let end = start.saturating_add(len).min(self.num_samples);
+        let inputs = self.inputs[..self.num_input_channels]
+            .iter()
+            .map(|ch| ch.and_then(|s| s.get(start..end)).unwrap_or_default());
+        let outputs = self.outputs[..self.num_output_channels].iter_mut().map(|ch| {
+            ch.as_deref_mut()
+                .and_then(|s| s.get_mut(start..end))
+                .unwrap_or_default()
+        });
+        Buffer::new(inputs, outputs, end - start)
+    }
+
     // =========================================================================
     // Bulk Operations
     // =========================================================================
 
+    /// Active input channel slots, for [`KeySignal`](crate::graph::KeySignal).
+    pub(crate) fn input_slots(&self) -> &[Option<&'a [S]>] {
+        &self.inputs[..self.num_input_channels]
+    }
+
     /// Copy all input channels to output channels.
     ///
     /// Useful for bypass or passthrough. Only copies channels that exist
@@ -304,6 +481,48 @@ impl<'a, S: Sample> Buffer<'a, S> {
         }
     }
 
+    /// Mix all input channels into the output channels with `mix`.
+    ///
+    /// Like [`copy_to_output()`](Self::copy_to_output), but adapts the
+    /// channel count (mono in, stereo out and the other way round) instead
+    /// of leaving unmatched outputs untouched. See [`ChannelMix`].
+    pub fn mix_to_output(&mut self, mix: ChannelMix) {
+        let n = self.num_samples;
+        let mut inputs: [&[S]; MAX_CHANNELS] = [&[]; MAX_CHANNELS];
+        for (slot, input) in inputs.iter_mut().zip(&self.inputs[..self.num_input_channels]) {
+            *slot = input.map_or(&[], |input| &input[..n]);
+        }
+        let mut outputs: [&mut [S]; MAX_CHANNELS] = Default::default();
+        let count = self.num_output_channels;
+        for (slot, output) in outputs.iter_mut().zip(&mut self.outputs[..count]) {
+            if let Some(output) = output.as_mut() {
+                *slot = &mut output[..n];
+            }
+        }
+        mix_channels(mix, &inputs[..self.num_input_channels], &mut outputs[..count]);
+    }
+
+    /// Mix all input channels into the output channels through `matrix`.
+    ///
+    /// Output `o` becomes the sum of every input `i` times the matrix gain
+    /// `(o, i)`; outputs beyond the matrix are silent. See [`GainMatrix`].
+    #[cfg(feature = "dsp")]
+    pub fn apply_gain_matrix(&mut self, matrix: &mut GainMatrix<S>) {
+        let n = self.num_samples;
+        let mut inputs: [&[S]; MAX_CHANNELS] = [&[]; MAX_CHANNELS];
+        for (slot, input) in inputs.iter_mut().zip(&self.inputs[..self.num_input_channels]) {
+            *slot = input.map_or(&[], |input| &input[..n]);
+        }
+        let mut outputs: [&mut [S]; MAX_CHANNELS] = Default::default();
+        let count = self.num_output_channels;
+        for (slot, output) in outputs.iter_mut().zip(&mut self.outputs[..count]) {
+            if let Some(output) = output.as_mut() {
+                *slot = &mut output[..n];
+            }
+        }
+        matrix.process(&inputs[..self.num_input_channels], &mut outputs[..count]);
+    }
+
     /// Clear all output channels to silence.
     pub fn clear_outputs(&mut self) {
         let n = self.num_samples;
@@ -327,6 +546,267 @@ impl<'a, S: Sample> Buffer<'a, S> {
     }
 }
 
+/// The declared layout if it matches `channels`, otherwise the usual one.
+fn resolve_layout(declared: Option<SpeakerLayout>, channels: usize) -> SpeakerLayout {
+    match declared {
+        Some(layout) if layout.channel_count() as usize == channels => layout,
+        _ => SpeakerLayout::from_channel_count(channels as u32),
+    }
+}
+
+// =============================================================================
+// FixedBuffer - Compile-Time Channel Count
+// =============================================================================
+
+/// Main buffer view with a channel count known at compile time.
+///
+/// Created by [`Buffer::as_channels()`], [`Buffer::as_stereo()`] or
+/// [`Buffer::as_mono()`]. Every channel slice holds exactly
+/// [`num_samples()`](Self::num_samples) samples, and channels live in
+/// fixed-size arrays, so per-frame loops compile to straight-line code.
+pub struct FixedBuffer<'b, S: Sample, const N: usize> {
+    inputs: [&'b [S]; N],
+    outputs: [&'b mut [S]; N],
+    num_samples: usize,
+}
+
+impl<'b, S: Sample, const N: usize> FixedBuffer<'b, S, N> {
+    /// Number of samples in this processing block.
+    #[inline]
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    /// Input channel `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel >= N`.
+    #[inline]
+    pub fn input(&self, channel: usize) -> &[S] {
+        self.inputs[channel]
+    }
+
+    /// Output channel `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel >= N`.
+    #[inline]
+    pub fn output(&mut self, channel: usize) -> &mut [S] {
+        self.outputs[channel]
+    }
+
+    /// All input channels.
+    #[inline]
+    pub fn inputs(&self) -> &[&'b [S]; N] {
+        &self.inputs
+    }
+
+    /// All output channels.
+    #[inline]
+    pub fn outputs_mut(&mut self) -> &mut [&'b mut [S]; N] {
+        &mut self.outputs
+    }
+
+    /// Input and output channels at once, for loops that read one and
+    /// write the other.
+    #[inline]
+    pub fn split(&mut self) -> (&[&'b [S]; N], &mut [&'b mut [S]; N]) {
+        (&self.inputs, &mut self.outputs)
+    }
+
+    /// Input samples of frame `index`, one per channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= num_samples()`.
+    #[inline]
+    pub fn frame(&self, index: usize) -> [S; N] {
+        std::array::from_fn(|ch| self.inputs[ch][index])
+    }
+
+    /// Write output frame `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= num_samples()`.
+    #[inline]
+    pub fn set_frame(&mut self, index: usize, frame: [S; N]) {
+        for (output, sample) in self.outputs.iter_mut().zip(frame) {
+            output[index] = sample;
+        }
+    }
+
+    /// Run `f` on every input frame and write the result to the output.
+    #[inline]
+    pub fn process_frames(&mut self, mut f: impl FnMut([S; N]) -> [S; N]) {
+        for index in 0..self.num_samples {
+            let frame = f(self.frame(index));
+            self.set_frame(index, frame);
+        }
+    }
+
+    /// Copy the input channels to the output channels.
+    pub fn copy_to_output(&mut self) {
+        for (output, input) in self.outputs.iter_mut().zip(self.inputs) {
+            output.copy_from_slice(input);
+        }
+    }
+}
+
+// =============================================================================
+// Frames - Per-Sample Access Across Buffers
+// =============================================================================
+
+/// Per-sample walk over a [`Buffer`] and its [`AuxiliaryBuffers`].
+///
+/// Created by [`Buffer::frames()`].
+pub struct Frames<'f, 'a, S: Sample = f32> {
+    buffer: &'f mut Buffer<'a, S>,
+    aux: &'f AuxiliaryBuffers<'f, S>,
+    index: usize,
+}
+
+impl<'f, 'a, S: Sample> Frames<'f, 'a, S> {
+    /// Advance to the next sample. Returns `None` at the end of the block.
+    #[inline]
+    pub fn next_frame(&mut self) -> Option<(InputFrame<'_, S>, OutputFrame<'_, 'a, S>, AuxFrame<'_, S>)> {
+        let index = self.index;
+        if index >= self.buffer.num_samples {
+            return None;
+        }
+        self.index += 1;
+
+        let buffer = &mut *self.buffer;
+        Some((
+            InputFrame {
+                channels: &buffer.inputs[..buffer.num_input_channels],
+                index,
+            },
+            OutputFrame {
+                channels: &mut buffer.outputs[..buffer.num_output_channels],
+                index,
+            },
+            AuxFrame { aux: self.aux, index },
+        ))
+    }
+
+    /// Number of frames left in the block.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.buffer.num_samples - self.index
+    }
+}
+
+/// One sample of every main input channel.
+#[derive(Clone, Copy)]
+pub struct InputFrame<'f, S: Sample = f32> {
+    channels: &'f [Option<&'f [S]>],
+    index: usize,
+}
+
+impl<'f, S: Sample> InputFrame<'f, S> {
+    /// Sample index within the block.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of input channels.
+    #[inline]
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Sample of `channel`, or `S::ZERO` if the channel doesn't exist.
+    #[inline]
+    pub fn sample(&self, channel: usize) -> S {
+        self.channels
+            .get(channel)
+            .and_then(|ch| ch.as_ref())
+            .and_then(|ch| ch.get(self.index))
+            .copied()
+            .unwrap_or(S::ZERO)
+    }
+}
+
+/// One sample of every main output channel.
+pub struct OutputFrame<'f, 'a, S: Sample = f32> {
+    channels: &'f mut [Option<&'a mut [S]>],
+    index: usize,
+}
+
+impl<'f, 'a, S: Sample> OutputFrame<'f, 'a, S> {
+    /// Sample index within the block.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of output channels.
+    #[inline]
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Current output sample of `channel`, or `S::ZERO` if the channel
+    /// doesn't exist.
+    #[inline]
+    pub fn get(&self, channel: usize) -> S {
+        self.channels
+            .get(channel)
+            .and_then(|ch| ch.as_ref())
+            .and_then(|ch| ch.get(self.index))
+            .copied()
+            .unwrap_or(S::ZERO)
+    }
+
+    /// Write the output sample of `channel`. Ignored if the channel doesn't
+    /// exist.
+    #[inline]
+    pub fn set(&mut self, channel: usize, value: S) {
+        if let Some(sample) = self
+            .channels
+            .get_mut(channel)
+            .and_then(|ch| ch.as_mut())
+            .and_then(|ch| ch.get_mut(self.index))
+        {
+            *sample = value;
+        }
+    }
+}
+
+/// One sample of every aux input bus.
+#[derive(Clone, Copy)]
+pub struct AuxFrame<'f, S: Sample = f32> {
+    aux: &'f AuxiliaryBuffers<'f, S>,
+    index: usize,
+}
+
+impl<'f, S: Sample> AuxFrame<'f, S> {
+    /// Number of aux input buses.
+    #[inline]
+    pub fn num_buses(&self) -> usize {
+        self.aux.num_input_buses()
+    }
+
+    /// Sample of `channel` on aux input `bus`, or `S::ZERO` if the bus or
+    /// channel doesn't exist (e.g. the host didn't connect a sidechain).
+    #[inline]
+    pub fn input(&self, bus: usize, channel: usize) -> S {
+        self.aux
+            .input(bus)
+            .map_or(S::ZERO, |input| input.sample(channel, self.index))
+    }
+
+    /// Sample of `channel` on the sidechain (aux input 0).
+    #[inline]
+    pub fn sidechain(&self, channel: usize) -> S {
+        self.input(0, channel)
+    }
+}
+
 // =============================================================================
 // AuxiliaryBuffers - Sidechain and Aux Buses
 // =============================================================================
@@ -452,6 +932,34 @@ impl<'a, S: Sample> AuxiliaryBuffers<'a, S> {
         }
     }
 
+    /// Borrow samples `start..start + len` of every bus as shorter buffers.
+    ///
+    /// The counterpart of [`Buffer::slice()`]; bus and channel counts stay
+    /// the same. Real-time safe.
+    pub fn slice(&mut self, start: usize, len: usize) -> AuxiliaryBuffers<'_, S> {
+        let start = start.min(self.num_samples);
+        let end = start.saturating_add(len).min(self.num_samples);
+        let inputs = self.inputs[..self.num_input_buses]
+            .iter()
+            .zip(self.input_channel_counts.iter())
+            .map(|(bus, &count)| {
+                bus[..count]
+                    .iter()
+                    .map(|ch| ch.and_then(|s| s.get(start..end)).unwrap_or_default())
+            });
+        let outputs = self.outputs[..self.num_output_buses]
+            .iter_mut()
+            .zip(self.output_channel_counts.iter())
+            .map(|(bus, &count)| {
+                bus[..count].iter_mut().map(|ch| {
+                    ch.as_deref_mut()
+                        .and_then(|s| s.get_mut(start..end))
+                        .unwrap_or_default()
+                })
+            });
+        AuxiliaryBuffers::new(inputs, outputs, end - start)
+    }
+
     // =========================================================================
     // Info
     // =========================================================================
@@ -620,6 +1128,11 @@ pub struct AuxInput<'a, S: Sample = f32> {
 }
 
 impl<'a, S: Sample> AuxInput<'a, S> {
+    /// Channel slots, for [`KeySignal`](crate::graph::KeySignal).
+    pub(crate) fn slots(&self) -> &'a [Option<&'a [S]>] {
+        self.channels
+    }
+
     /// Number of samples in each channel.
     #[inline]
     pub fn num_samples(&self) -> usize {
@@ -824,3 +1337,74 @@ impl<'borrow, 'data, S: Sample> AuxOutput<'borrow, 'data, S> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn as_channels_matches_layout_only() {
+        let (left, right) = ([1.0f32, 2.0], [3.0f32, 4.0]);
+        let (mut out_l, mut out_r) = ([0.0f32; 2], [0.0f32; 2]);
+        let mut buffer = Buffer::new([&left[..], &right[..]], [&mut out_l[..], &mut out_r[..]], 2);
+
+        assert!(buffer.as_mono().is_none());
+        assert!(buffer.as_channels::<6>().is_none());
+        let mut stereo = buffer.as_stereo().unwrap();
+        stereo.process_frames(|[l, r]| [r, l]);
+        assert_eq!(stereo.frame(1), [2.0, 4.0]);
+        assert_eq!((out_l, out_r), ([3.0, 4.0], [1.0, 2.0]));
+    }
+
+    #[test]
+    fn mismatched_input_and_output_counts_fall_back() {
+        let input = [0.5f32; 4];
+        let (mut out_l, mut out_r) = ([0.0f32; 4], [0.0f32; 4]);
+        let mut buffer = Buffer::new([&input[..]], [&mut out_l[..], &mut out_r[..]], 3);
+        assert!(buffer.as_mono().is_none());
+        assert!(buffer.as_stereo().is_none());
+
+        let mut out = [0.0f32; 4];
+        let mut buffer = Buffer::new([&input[..]], [&mut out[..]], 3);
+        let mut mono = buffer.as_mono().unwrap();
+        assert_eq!(mono.num_samples(), 3);
+        mono.copy_to_output();
+        assert_eq!(out, [0.5, 0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn frames_zip_main_and_sidechain() {
+        let input = [1.0f32, 2.0, 3.0];
+        let key = [0.0f32, 1.0, 0.5];
+        let mut out = [0.0f32; 3];
+        let mut buffer = Buffer::new([&input[..]], [&mut out[..]], 3);
+        let aux = AuxiliaryBuffers::new([[&key[..]]], std::iter::empty::<[&mut [f32]; 0]>(), 3);
+
+        let mut frames = buffer.frames(&aux);
+        assert_eq!(frames.remaining(), 3);
+        while let Some((input, mut output, aux)) = frames.next_frame() {
+            assert_eq!(aux.input(1, 0), 0.0);
+            output.set(0, input.sample(0) * aux.sidechain(0));
+            output.set(1, 9.0);
+        }
+        assert_eq!(out, [0.0, 2.0, 1.5]);
+    }
+
+    #[test]
+    fn speaker_layouts_locate_channels() {
+        let input = [0.0f32; 2];
+        let mut outputs = [[0.0f32; 2]; 6];
+        let buffer = Buffer::new([&input[..]], outputs.iter_mut().map(|o| &mut o[..]), 2);
+        assert_eq!(buffer.input_layout(), SpeakerLayout::Mono);
+        assert_eq!(buffer.output_layout(), SpeakerLayout::Surround51);
+        assert_eq!(buffer.output_channel_of(Speaker::Lfe), Some(3));
+        assert_eq!(buffer.output_speaker(2), Some(Speaker::Center));
+
+        // A declared layout wins when its width matches
+        let discrete = SpeakerLayout::Discrete(6);
+        let buffer = Buffer::new([&input[..]], outputs.iter_mut().map(|o| &mut o[..]), 2)
+            .with_speaker_layouts(SpeakerLayout::Stereo, discrete);
+        assert_eq!(buffer.input_layout(), SpeakerLayout::Mono);
+        assert_eq!(buffer.output_layout(), discrete);
+        assert_eq!(buffer.output_channel_of(Speaker::Lfe), None);
+    }
+}
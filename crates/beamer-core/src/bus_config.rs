@@ -4,6 +4,7 @@
 //! extracted from the plugin or host. This avoids repeated queries and provides
 //! fast access during audio processing.
 
+use crate::parameter_store::ParameterStore;
 use crate::plugin::{BusInfo, BusLayout, BusType, Descriptor};
 use crate::types::{MAX_BUSES, MAX_CHANNELS};
 
@@ -38,6 +39,62 @@ impl CachedBusInfo {
     }
 }
 
+/// Build a default descriptor carrying `parameters`' current values.
+///
+/// Used by wrappers to answer bus queries for a parameter-dependent layout
+/// (see [`Descriptor::bus_layout_id`]) while the real descriptor is consumed
+/// by a prepared processor.
+pub fn descriptor_snapshot<D: Descriptor>(parameters: &D::Parameters) -> D {
+    let descriptor = D::default();
+    let target = descriptor.parameters();
+    for index in 0..parameters.count() {
+        if let Some(info) = parameters.info(index) {
+            target.set_normalized(info.id, parameters.get_normalized(info.id));
+        }
+    }
+    descriptor
+}
+
+/// Check channel counts proposed by the host against `descriptor`.
+///
+/// `input_channels` and `output_channels` hold one count per bus (main
+/// first). Returns the layout to prepare with, or `None` if the bus counts
+/// differ from the declared ones or the plugin rejects the channel counts
+/// (see [`Descriptor::accepts_bus_layout`]). The declared layout is always
+/// accepted.
+pub fn negotiate_bus_layout<D: Descriptor>(
+    descriptor: &D,
+    input_channels: &[u32],
+    output_channels: &[u32],
+) -> Option<BusLayout> {
+    if input_channels.len() != descriptor.input_bus_count()
+        || output_channels.len() != descriptor.output_bus_count()
+    {
+        return None;
+    }
+
+    let arranged = |info: Option<BusInfo>, channel_count: u32| {
+        info.map(|info| BusInfo {
+            channel_count,
+            ..info
+        })
+    };
+    let inputs: Option<Vec<BusInfo>> = input_channels
+        .iter()
+        .enumerate()
+        .map(|(bus, &channels)| arranged(descriptor.input_bus_info(bus), channels))
+        .collect();
+    let outputs: Option<Vec<BusInfo>> = output_channels
+        .iter()
+        .enumerate()
+        .map(|(bus, &channels)| arranged(descriptor.output_bus_info(bus), channels))
+        .collect();
+
+    let layout = BusLayout::from_buses(&inputs?, &outputs?);
+    (layout == BusLayout::from_plugin(descriptor) || descriptor.accepts_bus_layout(&layout))
+        .then_some(layout)
+}
+
 /// Cached bus configuration from plugin or host.
 ///
 /// Stores bus and channel information for fast access during audio processing.
@@ -143,6 +200,8 @@ impl CachedBusConfig {
                 .unwrap_or(0),
             aux_input_count: self.input_bus_count.saturating_sub(1),
             aux_output_count: self.output_bus_count.saturating_sub(1),
+            aux_input_channel_counts: self.input_buses.iter().skip(1).map(|b| b.channel_count as u32).collect(),
+            aux_output_channel_counts: self.output_buses.iter().skip(1).map(|b| b.channel_count as u32).collect(),
         }
     }
 
@@ -226,7 +285,7 @@ mod tests {
         let config = CachedBusConfig::new(
             vec![
                 CachedBusInfo::new(2, BusType::Main),
-                CachedBusInfo::new(2, BusType::Aux),
+                CachedBusInfo::new(1, BusType::Aux),
             ],
             vec![CachedBusInfo::new(2, BusType::Main)],
         );
@@ -235,6 +294,30 @@ mod tests {
         assert_eq!(layout.main_output_channels, 2);
         assert_eq!(layout.aux_input_count, 1);
         assert_eq!(layout.aux_output_count, 0);
+        assert_eq!(layout.aux_input_channels(0), 1);
+        assert_eq!(layout.aux_input_channels(1), 0);
+        assert_eq!(layout.aux_output_channels(0), 0);
+    }
+
+    #[test]
+    fn test_aux_channel_setup_types() {
+        use crate::plugin::{AuxInputChannels, AuxOutputChannels, HostSetup, PluginSetup, ProcessMode};
+
+        let sidechain = BusInfo {
+            name: "Sidechain",
+            bus_type: BusType::Aux,
+            channel_count: 1,
+            is_default_active: true,
+            speaker_layout: None,
+        };
+        let layout = BusLayout::from_buses(&[BusInfo::default(), sidechain], &[BusInfo::default()]);
+        let host = HostSetup::new(48000.0, 512, layout.clone(), ProcessMode::Realtime);
+
+        assert_eq!(AuxInputChannels::<0>::extract(&host), AuxInputChannels(1));
+        assert_eq!(AuxInputChannels::<1>::extract(&host), AuxInputChannels(0));
+        assert_eq!(AuxOutputChannels::<0>::extract(&host), AuxOutputChannels(0));
+        assert_eq!(BusLayout::extract(&host), layout);
+        assert_eq!(layout.aux_input_count, 1);
     }
 
     #[test]
@@ -244,12 +327,110 @@ mod tests {
             bus_type: BusType::Aux,
             channel_count: 4,
             is_default_active: true,
+            speaker_layout: None,
         };
         let cached = CachedBusInfo::from_bus_info(&bus_info);
         assert_eq!(cached.channel_count, 4);
         assert_eq!(cached.bus_type, BusType::Aux);
     }
 
+    #[test]
+    fn test_bus_layout_helpers() {
+        let declared = BusLayout {
+            main_input_channels: 2,
+            main_output_channels: 2,
+            aux_input_count: 1,
+            aux_output_count: 0,
+            aux_input_channel_counts: vec![2],
+            aux_output_channel_counts: vec![],
+        };
+        assert_eq!(declared.input_bus_channels(0), 2);
+        assert_eq!(declared.input_bus_channels(1), 2);
+        assert_eq!(declared.output_bus_channels(1), 0);
+
+        let mono_unconnected = BusLayout {
+            main_input_channels: 1,
+            main_output_channels: 1,
+            aux_input_channel_counts: vec![0],
+            ..declared.clone()
+        };
+        assert!(mono_unconnected.main_is_mono_or_stereo());
+        assert!(mono_unconnected.aux_within(&declared));
+
+        let surround_sidechain = BusLayout {
+            main_input_channels: 6,
+            aux_input_channel_counts: vec![6],
+            ..declared.clone()
+        };
+        assert!(!surround_sidechain.main_is_mono_or_stereo());
+        assert!(!surround_sidechain.aux_within(&declared));
+    }
+
+    #[test]
+    fn test_negotiate_bus_layout() {
+        use crate::buffer::{AuxiliaryBuffers, Buffer};
+        use crate::parameter_store::NoParameters;
+        use crate::plugin::{HasParameters, Processor};
+        use crate::process_context::ProcessContext;
+
+        /// Stereo effect with a sidechain that also runs mono.
+        #[derive(Default)]
+        struct Ducker {
+            parameters: NoParameters,
+        }
+
+        impl HasParameters for Ducker {
+            type Parameters = NoParameters;
+            fn parameters(&self) -> &NoParameters {
+                &self.parameters
+            }
+            fn parameters_mut(&mut self) -> &mut NoParameters {
+                &mut self.parameters
+            }
+            fn set_parameters(&mut self, parameters: NoParameters) {
+                self.parameters = parameters;
+            }
+        }
+
+        impl Descriptor for Ducker {
+            type Setup = ();
+            type Processor = Ducker;
+            fn prepare(self, _: ()) -> Ducker {
+                self
+            }
+            fn input_bus_count(&self) -> usize {
+                2
+            }
+            fn input_bus_info(&self, index: usize) -> Option<BusInfo> {
+                match index {
+                    0 => Some(BusInfo::stereo("Input")),
+                    1 => Some(BusInfo::aux("Sidechain", 2)),
+                    _ => None,
+                }
+            }
+            fn accepts_bus_layout(&self, layout: &BusLayout) -> bool {
+                layout.main_is_mono_or_stereo() && layout.aux_within(&BusLayout::from_plugin(self))
+            }
+        }
+
+        impl Processor for Ducker {
+            type Descriptor = Ducker;
+            fn process(&mut self, _buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {}
+        }
+
+        let ducker = Ducker::default();
+        let declared = negotiate_bus_layout(&ducker, &[2, 2], &[2]).unwrap();
+        assert_eq!(declared, BusLayout::from_plugin(&ducker));
+
+        let mono = negotiate_bus_layout(&ducker, &[1, 0], &[1]).unwrap();
+        assert_eq!(mono.input_bus_channels(0), 1);
+        assert_eq!(mono.input_bus_channels(1), 0);
+        assert_eq!(mono.main_output_channels, 1);
+
+        assert_eq!(negotiate_bus_layout(&ducker, &[6, 2], &[6]), None);
+        assert_eq!(negotiate_bus_layout(&ducker, &[2], &[2]), None);
+    }
+
     #[test]
     fn test_empty_config() {
         let config = CachedBusConfig::new(vec![], vec![]);
@@ -0,0 +1,288 @@
+//! Downmix, upmix and channel remapping between buses of different widths.
+//!
+//! A plugin whose main input and output widths differ (stereo in, mono out
+//! or the other way round) has to decide how channels fold together. Doing
+//! it ad hoc in every plugin gives inconsistent levels, so this module
+//! offers the standard laws as [`ChannelMix`]:
+//!
+//! | Law | Downmix (n channels → 1) | Upmix (1 channel → n) |
+//! |-----|--------------------------|-----------------------|
+//! | [`Average`](ChannelMix::Average) | sum × 1/n | copy |
+//! | [`EqualPower`](ChannelMix::EqualPower) | sum × 1/√n (−3 dB for stereo) | copy × 1/√n |
+//! | [`Sum`](ChannelMix::Sum) | sum | copy |
+//! | [`First`](ChannelMix::First) | first channel only | copy |
+//! | [`Map`](ChannelMix::Map) | output `i` takes input `map[i]` | same |
+//!
+//! Wider-to-narrower folds input `i` into output `i % outputs` (5 channels
+//! into stereo: 0, 2, 4 → left, 1, 3 → right); narrower-to-wider repeats the
+//! inputs cyclically.
+//!
+//! # Manual Use
+//!
+//! ```ignore
+//! // Bypass that keeps levels consistent for mono-in/stereo-out
+//! BypassAction::Passthrough => buffer.mix_to_output(ChannelMix::EqualPower),
+//! ```
+//!
+//! [`mix_channels()`] works on plain slices.
+//!
+//! # Wrapper Support
+//!
+//! With [`Config::with_channel_mix`](crate::Config::with_channel_mix) (or
+//! `channel_mix` in `Config.toml`) the wrappers run a [`ChannelMixStage`]
+//! right around `process()`: when the main input and output widths differ,
+//! the main input is converted to the output's width first, so
+//! `buffer.num_input_channels()` equals `buffer.num_output_channels()`.
+//! A [`Map`](ChannelMix::Map) is applied even when the widths match.
+
+use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::process_context::ProcessContext;
+use crate::sample::Sample;
+use crate::types::MAX_CHANNELS;
+
+/// How channels are combined when widths differ. See the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelMix {
+    /// Average on downmix, copy on upmix. Keeps the level of correlated
+    /// (mono-compatible) material.
+    #[default]
+    Average,
+    /// −3 dB pan law: keeps the power of uncorrelated material.
+    EqualPower,
+    /// Unity gain in both directions. Correlated material gets louder on
+    /// downmix.
+    Sum,
+    /// Only the first channel of each folded group (left of a stereo pair).
+    First,
+    /// Output channel `i` takes input channel `map[i]`; outputs beyond the
+    /// map or pointing past the inputs are silent.
+    Map(&'static [usize]),
+}
+
+impl ChannelMix {
+    /// Gain applied to each of `count` inputs summed into one output.
+    pub fn downmix_gain(self, count: usize) -> f64 {
+        let count = count.max(1) as f64;
+        match self {
+            Self::Average => 1.0 / count,
+            Self::EqualPower => 1.0 / count.sqrt(),
+            Self::Sum | Self::First | Self::Map(_) => 1.0,
+        }
+    }
+
+    /// Gain applied to one input copied to `count` outputs.
+    pub fn upmix_gain(self, count: usize) -> f64 {
+        match self {
+            Self::EqualPower => 1.0 / (count.max(1) as f64).sqrt(),
+            Self::Average | Self::Sum | Self::First | Self::Map(_) => 1.0,
+        }
+    }
+
+    /// Parse the name used in `Config.toml` (`"average"`, `"equal-power"`,
+    /// `"sum"` or `"first"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "average" => Some(Self::Average),
+            "equal-power" => Some(Self::EqualPower),
+            "sum" => Some(Self::Sum),
+            "first" => Some(Self::First),
+            _ => None,
+        }
+    }
+
+    /// Fill one output channel from `inputs`.
+    fn render<S: Sample>(self, inputs: &[&[S]], output_index: usize, output_count: usize, output: &mut [S]) {
+        let len = output.len();
+        let n = inputs.len();
+        if let Self::Map(map) = self {
+            match map.get(output_index).and_then(|&i| inputs.get(i)) {
+                Some(input) => output.copy_from_slice(&input[..len]),
+                None => output.fill(S::ZERO),
+            }
+            return;
+        }
+        if n == 0 || output_count == 0 {
+            output.fill(S::ZERO);
+        } else if n <= output_count {
+            // Straight copy or upmix: repeat the inputs cyclically
+            let source = output_index % n;
+            let copies = (output_count - source).div_ceil(n);
+            let gain = if n == output_count { 1.0 } else { self.upmix_gain(copies) };
+            copy_scaled(inputs[source], output, gain);
+        } else {
+            // Downmix: fold input i into output i % output_count
+            let sources = (output_index..n).step_by(output_count);
+            if self == Self::First {
+                copy_scaled(inputs[output_index], output, 1.0);
+                return;
+            }
+            let gain = S::from_f64(self.downmix_gain(sources.len()));
+            output.fill(S::ZERO);
+            for input in sources.map(|i| inputs[i]) {
+                for (out, &sample) in output.iter_mut().zip(input) {
+                    *out = *out + sample * gain;
+                }
+            }
+        }
+    }
+}
+
+fn copy_scaled<S: Sample>(input: &[S], output: &mut [S], gain: f64) {
+    if gain == 1.0 {
+        output.copy_from_slice(&input[..output.len()]);
+    } else {
+        let gain = S::from_f64(gain);
+        for (out, &sample) in output.iter_mut().zip(input) {
+            *out = sample * gain;
+        }
+    }
+}
+
+/// Mix `inputs` into `outputs` with `mix`, adapting the channel count.
+///
+/// Every output is written (silence if there are no inputs). Inputs must be
+/// at least as long as the outputs. Real-time safe.
+pub fn mix_channels<S: Sample>(mix: ChannelMix, inputs: &[&[S]], outputs: &mut [&mut [S]]) {
+    let output_count = outputs.len();
+    for (index, output) in outputs.iter_mut().enumerate() {
+        mix.render(inputs, index, output_count, output);
+    }
+}
+
+/// Converts the main input to the output's width around `process()`.
+///
+/// Run by the wrappers when [`Config::channel_mix`](crate::Config::channel_mix)
+/// is set. Holds the converted input, so the host input stays untouched. All
+/// buffers are allocated in [`new()`](Self::new).
+pub struct ChannelMixStage<S: Sample> {
+    mix: ChannelMix,
+    /// Converted main input, one per output channel.
+    inputs: Vec<Vec<S>>,
+}
+
+impl<S: Sample> ChannelMixStage<S> {
+    /// Allocate a stage for the given main bus widths and blocks of up to
+    /// `max_frames` samples.
+    ///
+    /// Returns `None` when `mix` is `None`, the plugin has no main input, or
+    /// the widths match (unless `mix` is a [`ChannelMix::Map`]).
+    pub fn new(
+        mix: Option<ChannelMix>,
+        input_channels: usize,
+        output_channels: usize,
+        max_frames: usize,
+    ) -> Option<Self> {
+        let mix = mix?;
+        let remap = matches!(mix, ChannelMix::Map(_));
+        if input_channels == 0 || (input_channels == output_channels && !remap) {
+            return None;
+        }
+        Some(Self {
+            mix,
+            inputs: (0..output_channels).map(|_| vec![S::ZERO; max_frames]).collect(),
+        })
+    }
+
+    /// Call `process` through `stage` when present, directly otherwise.
+    pub fn run(
+        stage: Option<&mut Self>,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        match stage {
+            Some(stage) => stage.process(buffer, aux, context, process),
+            None => process(buffer, aux, context),
+        }
+    }
+
+    /// Convert the main input, then run `process` with it.
+    pub fn process(
+        &mut self,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        let num_samples = buffer.num_samples();
+        let capacity = self.inputs.first().map_or(0, Vec::len);
+        if num_samples > capacity {
+            // Larger than the declared maximum: pass through unconverted
+            process(buffer, aux, context);
+            return;
+        }
+
+        {
+            let mut inputs: [&[S]; MAX_CHANNELS] = [&[]; MAX_CHANNELS];
+            let input_count = buffer.num_input_channels().min(MAX_CHANNELS);
+            for (channel, slot) in inputs.iter_mut().take(input_count).enumerate() {
+                *slot = buffer.input(channel);
+            }
+            let mut outputs: [&mut [S]; MAX_CHANNELS] = Default::default();
+            let output_count = self.inputs.len().min(MAX_CHANNELS);
+            for (slot, channel) in outputs.iter_mut().zip(&mut self.inputs) {
+                *slot = &mut channel[..num_samples];
+            }
+            mix_channels(self.mix, &inputs[..input_count], &mut outputs[..output_count]);
+        }
+
+        let inputs = self.inputs.iter().map(|ch| &ch[..num_samples]);
+        // The converted inputs have the output's width
+        let layout = buffer.output_layout();
+        let mut converted =
+            Buffer::new(inputs, buffer.outputs_mut(), num_samples).with_speaker_layouts(layout, layout);
+        process(&mut converted, aux, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixed(mix: ChannelMix, inputs: &[&[f32]], outputs: usize) -> Vec<Vec<f32>> {
+        let mut result = vec![vec![0.0f32; inputs[0].len()]; outputs];
+        let mut slices: Vec<&mut [f32]> = result.iter_mut().map(|ch| &mut ch[..]).collect();
+        mix_channels(mix, inputs, &mut slices);
+        result
+    }
+
+    #[test]
+    fn laws_downmix_upmix_and_remap() {
+        let (left, right) = ([1.0f32, 0.5], [0.5f32, 0.0]);
+        assert_eq!(mixed(ChannelMix::Average, &[&left, &right], 1), [[0.75, 0.25]]);
+        assert_eq!(mixed(ChannelMix::Sum, &[&left, &right], 1), [[1.5, 0.5]]);
+        assert_eq!(mixed(ChannelMix::First, &[&left, &right], 1), [[1.0, 0.5]]);
+        let power = mixed(ChannelMix::EqualPower, &[&left], 2);
+        assert!((power[0][0] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(power[0], power[1]);
+        assert_eq!(mixed(ChannelMix::Average, &[&left], 2), [left, left]);
+
+        // Five channels into stereo: 0, 2, 4 → left and 1, 3 → right
+        let ones = [1.0f32; 2];
+        let five = mixed(ChannelMix::Sum, &[&ones[..]; 5], 2);
+        assert_eq!(five, [[3.0, 3.0], [2.0, 2.0]]);
+
+        let swapped = mixed(ChannelMix::Map(&[1, 0, 7]), &[&left, &right], 3);
+        assert_eq!(swapped, [right, left, [0.0, 0.0]]);
+        assert_eq!(ChannelMix::from_name("equal-power"), Some(ChannelMix::EqualPower));
+    }
+
+    #[test]
+    fn stage_presents_the_output_width() {
+        assert!(ChannelMixStage::<f32>::new(Some(ChannelMix::Average), 2, 2, 8).is_none());
+        assert!(ChannelMixStage::<f32>::new(None, 1, 2, 8).is_none());
+        let mut stage = ChannelMixStage::<f32>::new(Some(ChannelMix::Average), 1, 2, 8).unwrap();
+
+        let input = [0.25f32; 4];
+        let (mut left, mut right) = ([0.0f32; 4], [0.0f32; 4]);
+        let mut buffer = Buffer::new([&input[..]], [&mut left[..], &mut right[..]], 4);
+        let mut aux = AuxiliaryBuffers::empty();
+        let context = ProcessContext::with_empty_transport(48000.0, 4);
+        stage.process(&mut buffer, &mut aux, &context, |buffer, _, _| {
+            assert_eq!(buffer.num_input_channels(), 2);
+            buffer.copy_to_output();
+        });
+        assert_eq!((left, right), ([0.25; 4], [0.25; 4]));
+    }
+}
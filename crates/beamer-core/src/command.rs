@@ -0,0 +1,204 @@
+//! Typed commands from the GUI or background threads to the audio thread.
+//!
+//! Some actions are neither parameters nor state: "clear the delay buffer",
+//! "retrigger the LFO", "all notes off". Setting an atomic flag and polling
+//! it in `process()` works for one of them but not for a dozen, and loses
+//! requests that arrive twice in one block. A [`CommandQueue`] carries them
+//! as values of the plugin's own command type instead:
+//!
+//! ```ignore
+//! use beamer_core::command::{dispatch_commands, CommandHandler, CommandQueue};
+//!
+//! pub enum DelayCommand {
+//!     Clear,
+//!     Freeze(bool),
+//! }
+//!
+//! // Descriptor: create the queue and hand clones to the WebView handler
+//! // and (in prepare()) to the processor.
+//! let commands = CommandQueue::<DelayCommand>::new(32);
+//!
+//! // WebView handler, any thread:
+//! let _ = commands.send(DelayCommand::Clear);
+//!
+//! impl CommandHandler for DelayProcessor {
+//!     type Command = DelayCommand;
+//!
+//!     fn commands(&self) -> &CommandQueue<DelayCommand> {
+//!         &self.commands
+//!     }
+//!
+//!     fn on_command(&mut self, command: DelayCommand) {
+//!         match command {
+//!             DelayCommand::Clear => self.line.clear(),
+//!             DelayCommand::Freeze(on) => self.frozen = on,
+//!         }
+//!     }
+//! }
+//!
+//! impl Processor for DelayProcessor {
+//!     fn apply_commands(&mut self) {
+//!         dispatch_commands(self);
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! The wrappers call [`Processor::apply_commands()`](crate::Processor::apply_commands)
+//! at the start of every block, before control ticks and `process()`, so a
+//! command takes effect at the block's first sample.
+//!
+//! # Real-Time Safety
+//!
+//! The queue is a bounded lock-free ring: [`CommandQueue::send()`] and
+//! [`CommandQueue::pop()`] never lock or allocate. A full queue hands the
+//! command back to the sender. [`dispatch_commands()`] applies at most one
+//! queue's worth per block, so a sender that keeps sending can't stall the
+//! audio thread. Commands are dropped on the audio thread, so keep them
+//! plain values (no `Box`, `Vec` or `String`).
+
+use std::sync::Arc;
+
+use crate::gui_data::Ring;
+
+/// A bounded lock-free queue of commands to the audio thread.
+///
+/// Cloning is cheap (reference counted): senders and the processor each
+/// hold a clone. Any number of threads may send.
+pub struct CommandQueue<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> Clone for CommandQueue<T> {
+    fn clone(&self) -> Self {
+        Self { ring: Arc::clone(&self.ring) }
+    }
+}
+
+impl<T> std::fmt::Debug for CommandQueue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandQueue").field("capacity", &self.ring.capacity()).finish()
+    }
+}
+
+impl<T: Send + 'static> CommandQueue<T> {
+    /// Create a queue holding up to `capacity` pending commands (rounded up
+    /// to a power of two, at least 2). Allocates.
+    pub fn new(capacity: usize) -> Self {
+        Self { ring: Arc::new(Ring::new(capacity)) }
+    }
+
+    /// Queue a command for the next block. Returns the command if the
+    /// queue is full.
+    ///
+    /// Real-time safe.
+    #[inline]
+    pub fn send(&self, command: T) -> Result<(), T> {
+        self.ring.push(command)
+    }
+
+    /// Take the oldest pending command.
+    ///
+    /// Real-time safe.
+    #[inline]
+    pub fn pop(&self) -> Option<T> {
+        self.ring.pop()
+    }
+
+    /// Drop all pending commands, e.g. when the processor is reset.
+    pub fn clear(&self) {
+        while self.ring.pop().is_some() {}
+    }
+
+    /// Maximum number of pending commands.
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+}
+
+/// A processor that applies commands from a [`CommandQueue`].
+pub trait CommandHandler {
+    /// The plugin's command type.
+    type Command: Send + 'static;
+
+    /// The queue commands arrive on.
+    fn commands(&self) -> &CommandQueue<Self::Command>;
+
+    /// Apply one command. Called on the audio thread by
+    /// [`dispatch_commands()`], oldest command first. Must be real-time safe.
+    fn on_command(&mut self, command: Self::Command);
+}
+
+/// Apply the pending commands of `handler`, at most one queue's worth.
+/// Returns the number applied.
+///
+/// Call from [`Processor::apply_commands()`](crate::Processor::apply_commands).
+/// Real-time safe.
+pub fn dispatch_commands<H: CommandHandler + ?Sized>(handler: &mut H) -> usize {
+    let limit = handler.commands().capacity();
+    let mut applied = 0;
+    while applied < limit {
+        let Some(command) = handler.commands().pop() else { break };
+        handler.on_command(command);
+        applied += 1;
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Command {
+        Clear,
+        Retrigger(u8),
+    }
+
+    struct Voice {
+        commands: CommandQueue<Command>,
+        applied: Vec<Command>,
+    }
+
+    impl CommandHandler for Voice {
+        type Command = Command;
+
+        fn commands(&self) -> &CommandQueue<Command> {
+            &self.commands
+        }
+
+        fn on_command(&mut self, command: Command) {
+            // A command sent while dispatching waits for the next block.
+            if command == Command::Clear {
+                let _ = self.commands.send(Command::Retrigger(0));
+            }
+            self.applied.push(command);
+        }
+    }
+
+    #[test]
+    fn test_commands_apply_in_order_and_bounded() {
+        let commands = CommandQueue::new(4);
+        let gui = commands.clone();
+        assert_eq!(gui.send(Command::Retrigger(1)), Ok(()));
+        assert_eq!(gui.send(Command::Clear), Ok(()));
+        assert_eq!(gui.send(Command::Retrigger(2)), Ok(()));
+        assert_eq!(gui.send(Command::Retrigger(3)), Ok(()));
+        assert_eq!(gui.send(Command::Retrigger(4)), Err(Command::Retrigger(4)));
+
+        let mut voice = Voice { commands, applied: Vec::new() };
+        assert_eq!(dispatch_commands(&mut voice), 4);
+        assert_eq!(
+            voice.applied,
+            [Command::Retrigger(1), Command::Clear, Command::Retrigger(2), Command::Retrigger(3)]
+        );
+
+        voice.applied.clear();
+        assert_eq!(dispatch_commands(&mut voice), 1);
+        assert_eq!(voice.applied, [Command::Retrigger(0)]);
+
+        let _ = gui.send(Command::Clear);
+        gui.clear();
+        assert_eq!(dispatch_commands(&mut voice), 0);
+    }
+}
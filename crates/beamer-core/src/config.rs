@@ -299,6 +299,70 @@ pub const DEFAULT_SYSEX_SLOTS: usize = 16;
 /// Default SysEx buffer size in bytes per slot.
 pub const DEFAULT_SYSEX_BUFFER_SIZE: usize = 512;
 
+/// Sample rates a plugin's DSP can run at.
+///
+/// When the host runs at a rate outside this set, the wrappers prepare the
+/// processor at the nearest supported rate and resample around `process()`
+/// (see [`SampleRateAdapter`](crate::SampleRateAdapter)).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SupportedSampleRates {
+    /// Any host rate (no resampling).
+    #[default]
+    Any,
+    /// Every rate in `min..=max` Hz.
+    Range {
+        /// Lowest supported rate in Hz.
+        min: f64,
+        /// Highest supported rate in Hz.
+        max: f64,
+    },
+    /// Only these exact rates in Hz.
+    List(&'static [f64]),
+}
+
+impl SupportedSampleRates {
+    /// Returns true if the DSP can run at `sample_rate` directly.
+    pub fn supports(&self, sample_rate: f64) -> bool {
+        match *self {
+            Self::Any => true,
+            Self::Range { min, max } => (min..=max).contains(&sample_rate),
+            Self::List(rates) => rates.iter().any(|&r| (r - sample_rate).abs() < 0.5),
+        }
+    }
+
+    /// The rate to prepare the processor at for a given host rate.
+    ///
+    /// Returns `host_rate` when supported, otherwise the closest supported
+    /// rate (by ratio). An empty list falls back to `host_rate`.
+    pub fn processing_rate(&self, host_rate: f64) -> f64 {
+        if self.supports(host_rate) {
+            return host_rate;
+        }
+        match *self {
+            Self::Any => host_rate,
+            Self::Range { min, max } => host_rate.clamp(min, max),
+            Self::List(rates) => rates
+                .iter()
+                .copied()
+                .min_by(|a, b| {
+                    let da = (a / host_rate).ln().abs();
+                    let db = (b / host_rate).ln().abs();
+                    da.total_cmp(&db)
+                })
+                .unwrap_or(host_rate),
+        }
+    }
+}
+
+/// Plugin format built by a wrapper, for per-format config overrides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginFormat {
+    /// Audio Unit (AUv2 and AUv3).
+    Au,
+    /// VST3.
+    Vst3,
+}
+
 /// Unified plugin configuration.
 ///
 /// Contains all plugin metadata: shared fields (name, vendor, category),
@@ -306,6 +370,11 @@ pub const DEFAULT_SYSEX_BUFFER_SIZE: usize = 512;
 /// (VST3 component UIDs). The VST3 component UID is derived automatically
 /// from the AU codes via FNV-1a hash unless explicitly overridden.
 ///
+/// Fields that need a different value in one format (a category string a
+/// host expects, a SysEx budget) are changed with
+/// [`override_au()`](Self::override_au) and
+/// [`override_vst3()`](Self::override_vst3) instead of a second config.
+///
 /// # Example
 ///
 /// ```ignore
@@ -361,6 +430,17 @@ pub struct Config {
     /// Maximum size of each SysEx message in bytes (AU and VST3).
     pub sysex_buffer_size: usize,
 
+    /// Duration of the declicking fade applied around host preset/program
+    /// changes, in milliseconds. 0 applies presets immediately.
+    pub preset_crossfade_ms: f32,
+
+    /// Add the framework's Input Trim, Output Trim and Phase Invert
+    /// parameters and apply them around `process()`.
+    pub standard_trims: bool,
+
+    /// Publish DSP load, voice count and underruns as read-only parameters.
+    pub processing_stats: bool,
+
     /// Embedded web assets for the GUI. Set by the proc macro from the
     /// webview directory contents.
     pub gui_assets: Option<&'static crate::EmbeddedAssets>,
@@ -379,6 +459,32 @@ pub struct Config {
     /// flashing while web content renders.
     /// All-zero means no override (platform default).
     pub gui_background_color: [u8; 4],
+
+    /// Additional named editor views besides the main `"editor"` view.
+    pub gui_views: &'static [crate::gui::GuiView],
+
+    /// Default log filter (e.g. `"info"` or `"warn,my_plugin=debug"`).
+    /// When set, the wrappers install the per-plugin file logger. The
+    /// `BEAMER_LOG` environment variable overrides this value.
+    pub log_filter: Option<&'static str>,
+
+    /// Sample rates the DSP supports. Outside them, the wrappers resample
+    /// around `process()` and report the added latency.
+    pub sample_rates: SupportedSampleRates,
+
+    /// How the wrappers convert the main input to the output's width when
+    /// they differ. `None` passes the buses through as negotiated.
+    pub channel_mix: Option<crate::ChannelMix>,
+
+    /// When the wrappers report `process()` calls that overrun their
+    /// deadline. On by default.
+    pub watchdog: crate::WatchdogSettings,
+
+    /// Changes applied to the config the AU wrapper uses.
+    pub au_override: Option<fn(Config) -> Config>,
+
+    /// Changes applied to the config the VST3 wrapper uses.
+    pub vst3_override: Option<fn(Config) -> Config>,
 }
 
 /// Helper to convert a string literal to a 4-byte array at compile time.
@@ -521,14 +627,33 @@ impl Config {
             vst3_controller_id: None,
             sysex_slots: DEFAULT_SYSEX_SLOTS,
             sysex_buffer_size: DEFAULT_SYSEX_BUFFER_SIZE,
+            preset_crossfade_ms: 0.0,
+            standard_trims: false,
+            processing_stats: false,
             gui_assets: None,
             gui_url: None,
             gui_width: 0,
             gui_height: 0,
             gui_background_color: [0; 4],
+            gui_views: &[],
+            log_filter: None,
+            sample_rates: SupportedSampleRates::Any,
+            channel_mix: None,
+            watchdog: crate::WatchdogSettings::new(),
+            au_override: None,
+            vst3_override: None,
         }
     }
 
+    /// Set the plugin name.
+    ///
+    /// Mostly useful inside a per-format override; the name passed to
+    /// [`new()`](Self::new) is used otherwise.
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
     /// Set the vendor name.
     #[doc(hidden)]
     pub const fn with_vendor(mut self, vendor: &'static str) -> Self {
@@ -596,6 +721,40 @@ impl Config {
         self
     }
 
+    /// Set the additional named editor views.
+    #[doc(hidden)]
+    pub const fn with_gui_views(mut self, views: &'static [crate::gui::GuiView]) -> Self {
+        self.gui_views = views;
+        self
+    }
+
+    /// Set the default log filter and enable the per-plugin file logger.
+    ///
+    /// See [`crate::logging`] for the filter syntax. The `BEAMER_LOG`
+    /// environment variable overrides this value at runtime.
+    #[doc(hidden)]
+    pub const fn with_log_filter(mut self, filter: &'static str) -> Self {
+        self.log_filter = Some(filter);
+        self
+    }
+
+    /// Look up an editor view by the name the host requested.
+    ///
+    /// `"editor"` resolves to the main view (`index.html` at the configured
+    /// GUI size); other names are looked up in [`gui_views`](Self::gui_views).
+    /// Returns `None` for unknown names or when the plugin has no GUI.
+    pub fn gui_view(&self, name: &str) -> Option<crate::gui::GuiView> {
+        use crate::gui::{GuiView, EDITOR_PAGE, EDITOR_VIEW};
+
+        if !self.has_gui {
+            return None;
+        }
+        if name == EDITOR_VIEW {
+            return Some(GuiView::new(EDITOR_VIEW, EDITOR_PAGE, self.gui_width, self.gui_height));
+        }
+        self.gui_views.iter().find(|view| view.name == name).copied()
+    }
+
     /// Set the plugin subcategories.
     ///
     /// Subcategories provide more specific classification beyond the main category.
@@ -654,6 +813,145 @@ impl Config {
         self
     }
 
+    /// Apply host preset and program changes through a short fade (AU and VST3).
+    ///
+    /// The wrapper fades the output out over the first half of `ms` with the
+    /// old parameters, applies the preset at silence, and fades back in with
+    /// the new parameters. See [`PresetCrossfade`](crate::PresetCrossfade).
+    /// Default is 0 (presets apply immediately).
+    #[doc(hidden)]
+    pub const fn with_preset_crossfade(mut self, ms: f32) -> Self {
+        self.preset_crossfade_ms = ms;
+        self
+    }
+
+    /// Add standard Input Trim, Output Trim and Phase Invert parameters
+    /// (AU and VST3).
+    ///
+    /// The wrapper trims the main input before `process()` and trims and
+    /// inverts the main output after it, so plugin code stays unaware of
+    /// them. The parameter IDs are fixed across products. See
+    /// [`StandardTrims`](crate::StandardTrims).
+    pub const fn with_standard_trims(mut self) -> Self {
+        self.standard_trims = true;
+        self
+    }
+
+    /// Publish DSP load, voice count and underruns as read-only parameters
+    /// (AU and VST3).
+    ///
+    /// Hosts and control surfaces can show the plugin's health without
+    /// opening its GUI. The voice count comes from
+    /// [`Processor::active_voices()`](crate::Processor::active_voices). See
+    /// [`ProcessingStats`](crate::ProcessingStats).
+    pub const fn with_processing_stats(mut self) -> Self {
+        self.processing_stats = true;
+        self
+    }
+
+    /// Declare the range of sample rates the DSP supports (AU and VST3).
+    ///
+    /// At other host rates the wrapper prepares the processor at the nearest
+    /// rate in the range and resamples around `process()`, adding latency.
+    pub const fn with_sample_rate_range(mut self, min: f64, max: f64) -> Self {
+        self.sample_rates = SupportedSampleRates::Range { min, max };
+        self
+    }
+
+    /// Declare the exact sample rates the DSP supports (AU and VST3).
+    ///
+    /// At other host rates the wrapper prepares the processor at the closest
+    /// listed rate and resamples around `process()`, adding latency.
+    pub const fn with_sample_rates(mut self, rates: &'static [f64]) -> Self {
+        self.sample_rates = SupportedSampleRates::List(rates);
+        self
+    }
+
+    /// Convert the main input to the output's width before `process()`
+    /// when they differ (AU and VST3).
+    ///
+    /// A stereo-in/mono-out plugin then sees a downmixed mono input, a
+    /// mono-in/stereo-out plugin an upmixed stereo input. See
+    /// [`ChannelMix`](crate::ChannelMix).
+    pub const fn with_channel_mix(mut self, mix: crate::ChannelMix) -> Self {
+        self.channel_mix = Some(mix);
+        self
+    }
+
+    /// Configure the deadline watchdog (AU and VST3).
+    ///
+    /// See [`watchdog`](crate::watchdog). Pass
+    /// [`WatchdogSettings::disabled()`](crate::WatchdogSettings::disabled)
+    /// to stop timing `process()`.
+    pub const fn with_watchdog(mut self, settings: crate::WatchdogSettings) -> Self {
+        self.watchdog = settings;
+        self
+    }
+
+    /// Change the config the AU wrapper uses.
+    ///
+    /// `apply` receives the shared config and returns the AU one. Use it for
+    /// metadata and behavior that differ between formats, so both formats
+    /// still share one static config:
+    ///
+    /// ```ignore
+    /// pub static CONFIG: Config = Config::new("My Synth", Category::Instrument, "Mfgr", "msyn")
+    ///     .with_subcategories(&[Subcategory::Synth])
+    ///     .override_au(|config| config.with_sysex_slots(64))
+    ///     .override_vst3(|config| config.with_subcategories(&[Subcategory::Synth, Subcategory::Stereo]));
+    /// ```
+    ///
+    /// Identity fields (codes and UIDs) can be overridden too, but then the
+    /// formats no longer identify as the same plugin in hosts that pair them.
+    pub const fn override_au(mut self, apply: fn(Config) -> Config) -> Self {
+        self.au_override = Some(apply);
+        self
+    }
+
+    /// Change the config the VST3 wrapper uses.
+    ///
+    /// See [`override_au()`](Self::override_au).
+    pub const fn override_vst3(mut self, apply: fn(Config) -> Config) -> Self {
+        self.vst3_override = Some(apply);
+        self
+    }
+
+    /// The config as seen by the wrapper for `format`, with its override
+    /// applied.
+    ///
+    /// The result has no overrides left.
+    pub fn for_format(&self, format: PluginFormat) -> Config {
+        let apply = match format {
+            PluginFormat::Au => self.au_override,
+            PluginFormat::Vst3 => self.vst3_override,
+        };
+        let mut config = self.clone();
+        config.au_override = None;
+        config.vst3_override = None;
+        if let Some(apply) = apply {
+            config = apply(config);
+            config.au_override = None;
+            config.vst3_override = None;
+        }
+        config
+    }
+
+    /// [`for_format()`](Self::for_format) for a static config.
+    ///
+    /// Returns `self` when `format` has no override. Otherwise the
+    /// overridden config is leaked, so call this once per format (the
+    /// wrappers do so when their factory is created).
+    pub fn resolve(&'static self, format: PluginFormat) -> &'static Config {
+        let has_override = match format {
+            PluginFormat::Au => self.au_override.is_some(),
+            PluginFormat::Vst3 => self.vst3_override.is_some(),
+        };
+        if !has_override {
+            return self;
+        }
+        Box::leak(Box::new(self.for_format(format)))
+    }
+
     /// Get VST3 component UID as [u32; 4].
     ///
     /// Returns the explicit override if set via `with_vst3_id()`, otherwise
@@ -708,3 +1006,67 @@ impl Config {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::GuiView;
+
+    static VIEWS: [GuiView; 1] = [GuiView::new("mini", "mini.html", 240, 120)];
+
+    #[test]
+    fn test_gui_view_lookup() {
+        let config = Config::new("Test", Category::Effect, "Test", "test")
+            .with_gui()
+            .with_gui_size(800, 600)
+            .with_gui_views(&VIEWS);
+
+        let editor = config.gui_view("editor").unwrap();
+        assert_eq!((editor.page, editor.width, editor.height), ("index.html", 800, 600));
+        assert_eq!(config.gui_view("mini"), Some(VIEWS[0]));
+        assert_eq!(config.gui_view("unknown"), None);
+    }
+
+    #[test]
+    fn test_gui_view_requires_gui() {
+        let config = Config::new("Test", Category::Effect, "Test", "test").with_gui_views(&VIEWS);
+        assert_eq!(config.gui_view("editor"), None);
+        assert_eq!(config.gui_view("mini"), None);
+    }
+
+    static OVERRIDDEN: Config = Config::new("Test", Category::Effect, "Test", "test")
+        .with_subcategories(&[Subcategory::Dynamics])
+        .override_au(|config| config.with_name("Test AU").with_sysex_slots(64))
+        .override_vst3(|config| {
+            config.with_subcategories(&[Subcategory::Dynamics, Subcategory::Mono])
+        });
+
+    #[test]
+    fn test_format_overrides() {
+        let au = OVERRIDDEN.for_format(PluginFormat::Au);
+        assert_eq!((au.name, au.sysex_slots), ("Test AU", 64));
+        assert_eq!(au.vst3_subcategories(), "Fx|Dynamics");
+        assert!(au.au_override.is_none() && au.vst3_override.is_none());
+
+        let vst3 = OVERRIDDEN.resolve(PluginFormat::Vst3);
+        assert_eq!((vst3.name, vst3.sysex_slots), ("Test", DEFAULT_SYSEX_SLOTS));
+        assert_eq!(vst3.vst3_subcategories(), "Fx|Dynamics|Mono");
+
+        static PLAIN: Config = Config::new("Test", Category::Effect, "Test", "test");
+        assert!(std::ptr::eq(PLAIN.resolve(PluginFormat::Au), &PLAIN));
+    }
+
+    #[test]
+    fn test_processing_rate() {
+        let range = SupportedSampleRates::Range { min: 44_100.0, max: 96_000.0 };
+        assert_eq!(range.processing_rate(48_000.0), 48_000.0);
+        assert_eq!(range.processing_rate(192_000.0), 96_000.0);
+        assert_eq!(range.processing_rate(22_050.0), 44_100.0);
+
+        let list = SupportedSampleRates::List(&[44_100.0, 48_000.0]);
+        assert!(list.supports(48_000.0));
+        assert_eq!(list.processing_rate(88_200.0), 48_000.0);
+        assert_eq!(list.processing_rate(32_000.0), 44_100.0);
+        assert_eq!(SupportedSampleRates::Any.processing_rate(8_000.0), 8_000.0);
+    }
+}
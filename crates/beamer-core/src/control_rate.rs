@@ -0,0 +1,340 @@
+//! Control-rate ticks decoupled from the audio rate.
+//!
+//! Synths spend much of their CPU on work that doesn't need sample
+//! resolution: envelopes, LFOs, modulation matrices, filter coefficient
+//! updates and meter ballistics. A processor that returns a non-zero
+//! [`Processor::control_interval`] gets [`Processor::control_tick`] called
+//! every `interval` samples, and the wrappers split `process()` at the tick
+//! positions:
+//!
+//! ```text
+//! host block:   |<-------------------- 300 samples -------------------->|
+//! interval 128: tick | process(128) | tick | process(128) | tick | process(44)
+//! next block:   process(84) | tick | process(128) | ...
+//! ```
+//!
+//! The tick grid runs continuously across host blocks, so the control rate
+//! is `sample_rate / interval` regardless of the host's block size. The
+//! first block after activation (or an interval change) starts with a tick.
+//!
+//! # Overview
+//!
+//! - [`ControlContext`] - Passed to every tick (position, rate, transport)
+//! - [`ControlRate`] - The block splitter the wrappers run around `process()`
+//!
+//! # Example
+//!
+//! ```ignore
+//! impl Processor for MySynth {
+//!     fn control_interval(&self) -> usize {
+//!         64
+//!     }
+//!
+//!     fn control_tick(&mut self, context: ControlContext) {
+//!         let dt = context.tick_duration() as f32;
+//!         let lfo = self.lfo.advance(dt);
+//!         for voice in &mut self.voices {
+//!             voice.envelope.advance(dt);
+//!             voice.filter.set_cutoff(self.cutoff * (1.0 + lfo * self.depth));
+//!         }
+//!     }
+//!
+//!     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!         // At most `interval` samples, with coefficients fixed for the run
+//!     }
+//! }
+//! ```
+//!
+//! # Limitations
+//!
+//! - `process()` sees blocks of at most `interval` samples, and the
+//!   context's transport is advanced to each sub-block's start.
+//! - MIDI is still delivered once per host block through `process_midi()`,
+//!   with offsets relative to the host block, unless the processor also
+//!   splits at events ([`crate::split_block`]).
+//!
+//! # Real-Time Safety
+//!
+//! Splitting reborrows the host buffers ([`Buffer::slice`]) and never
+//! allocates.
+
+use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::parameter_types::Parameters;
+use crate::plugin::Processor;
+use crate::process_context::{ProcessContext, Transport};
+use crate::sample::Sample;
+use crate::split_block::SplitBlockProcessor;
+
+/// Information passed to [`Processor::control_tick`].
+#[derive(Debug, Clone, Copy)]
+pub struct ControlContext {
+    /// Sample rate in Hz (the rate `process()` runs at).
+    pub sample_rate: f64,
+    /// Samples between two ticks.
+    pub interval: usize,
+    /// Ticks since activation or the last interval change, starting at 0.
+    pub tick: u64,
+    /// Position of the tick within the current host block.
+    pub block_offset: usize,
+    /// Host transport, advanced to the tick position.
+    pub transport: Transport,
+}
+
+impl ControlContext {
+    /// Ticks per second.
+    #[inline]
+    pub fn control_rate(&self) -> f64 {
+        self.sample_rate / self.interval as f64
+    }
+
+    /// Time between two ticks in seconds.
+    #[inline]
+    pub fn tick_duration(&self) -> f64 {
+        self.interval as f64 / self.sample_rate
+    }
+}
+
+/// Calls control ticks and splits blocks at the tick positions.
+///
+/// Owned by the format wrappers, one per plugin instance. Also splits at
+/// the events recorded in its [`SplitBlockProcessor`].
+#[derive(Debug)]
+pub struct ControlRate {
+    /// Interval the grid was started with (0 = inactive).
+    interval: usize,
+    /// Samples until the next tick.
+    countdown: usize,
+    tick: u64,
+    events: SplitBlockProcessor,
+}
+
+impl Default for ControlRate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ControlRate {
+    /// Create a splitter whose first block starts with a tick.
+    pub fn new() -> Self {
+        Self {
+            interval: 0,
+            countdown: 0,
+            tick: 0,
+            events: SplitBlockProcessor::new(),
+        }
+    }
+
+    /// The event splitter, for the wrappers to record the block's events.
+    pub fn split_blocks_mut(&mut self) -> &mut SplitBlockProcessor {
+        &mut self.events
+    }
+
+    /// The event splitter if `processor` splits blocks at events
+    /// ([`split_blocks()`](Processor::split_blocks)), `None` otherwise.
+    ///
+    /// The wrappers record through this, so a processor that doesn't split
+    /// costs no copying of the block's MIDI and automation.
+    pub fn split_blocks_for<P: Processor>(&mut self, processor: &P) -> Option<&mut SplitBlockProcessor> {
+        processor.split_blocks().then_some(&mut self.events)
+    }
+
+    /// Restart the tick grid, so the next block starts with tick 0.
+    ///
+    /// Called by the wrappers on activation and reset.
+    pub fn reset(&mut self) {
+        self.countdown = 0;
+        self.tick = 0;
+    }
+
+    /// Run `process` for one block, calling the processor's control ticks
+    /// in between.
+    ///
+    /// Applies pending commands first
+    /// ([`Processor::apply_commands()`]).
+    ///
+    /// Advances the automatic parameter smoothers
+    /// ([`Parameters::advance_smoothing()`](crate::parameter_types::Parameters::advance_smoothing))
+    /// before every `process` call. Also splits at the recorded events when the processor's
+    /// [`split_blocks()`](Processor::split_blocks) returns true. Calls
+    /// `process` once with the whole block when neither applies.
+    pub fn run<P: Processor, S: Sample>(
+        &mut self,
+        processor: &mut P,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut P, &mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        processor.apply_commands();
+        let interval = processor.control_interval();
+        if processor.split_blocks() {
+            self.events.begin(processor.parameters(), buffer.num_samples());
+        } else {
+            self.events.clear();
+        }
+        self.split(
+            processor,
+            interval,
+            buffer,
+            aux,
+            context,
+            |processor, tick| processor.control_tick(tick),
+            |processor, events, offset| events.apply(processor, offset),
+            |processor, buffer, aux, context| {
+                processor.parameters_mut().advance_smoothing(buffer.num_samples());
+                process(processor, buffer, aux, context)
+            },
+        );
+        self.events.finish(processor.parameters());
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn split<T, S: Sample>(
+        &mut self,
+        target: &mut T,
+        interval: usize,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut tick: impl FnMut(&mut T, ControlContext),
+        mut at: impl FnMut(&mut T, &mut SplitBlockProcessor, usize) -> usize,
+        mut process: impl FnMut(&mut T, &mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        if interval != self.interval {
+            self.interval = interval;
+            self.reset();
+        }
+        let num_samples = buffer.num_samples();
+        // Zero-length flush blocks carry no time, so they never tick
+        if num_samples == 0 {
+            at(target, &mut self.events, 0);
+            process(target, buffer, aux, context);
+            return;
+        }
+
+        let mut offset = 0;
+        while offset < num_samples {
+            let next_event = at(target, &mut self.events, offset);
+            if interval > 0 && self.countdown == 0 {
+                tick(
+                    target,
+                    ControlContext {
+                        sample_rate: context.sample_rate,
+                        interval,
+                        tick: self.tick,
+                        block_offset: offset,
+                        transport: advance(&context.transport, offset, context.sample_rate),
+                    },
+                );
+                self.tick = self.tick.wrapping_add(1);
+                self.countdown = interval;
+            }
+
+            let mut len = next_event.saturating_sub(offset).min(num_samples - offset);
+            if interval > 0 {
+                len = len.min(self.countdown);
+                self.countdown -= len;
+            }
+            if len == num_samples {
+                process(target, buffer, aux, context);
+            } else {
+                let transport = advance(&context.transport, offset, context.sample_rate);
+                let sub_context = match context.midi_cc() {
+                    Some(cc) => ProcessContext::with_midi_cc(context.sample_rate, len, transport, cc),
+                    None => ProcessContext::new(context.sample_rate, len, transport),
+                };
+                process(target, &mut buffer.slice(offset, len), &mut aux.slice(offset, len), &sub_context);
+            }
+            offset += len;
+        }
+    }
+}
+
+/// The transport `offset` samples into the block.
+///
+/// Project positions only move while the transport is playing.
+fn advance(transport: &Transport, offset: usize, sample_rate: f64) -> Transport {
+    let mut transport = *transport;
+    if offset == 0 {
+        return transport;
+    }
+    let samples = offset as i64;
+    transport.continuous_time_samples = transport.continuous_time_samples.map(|t| t + samples);
+    if transport.is_playing {
+        transport.project_time_samples = transport.project_time_samples.map(|t| t + samples);
+        if let (Some(beats), Some(tempo)) = (transport.project_time_beats, transport.tempo) {
+            transport.project_time_beats = Some(beats + offset as f64 / sample_rate * tempo / 60.0);
+        }
+    }
+    transport
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        ticks: Vec<(u64, usize)>,
+        blocks: Vec<usize>,
+    }
+
+    fn run_block(control: &mut ControlRate, recorder: &mut Recorder, interval: usize, len: usize) {
+        let input = vec![0.0f32; len];
+        let mut output = vec![0.0f32; len];
+        let mut buffer = Buffer::new([&input[..]], [&mut output[..]], len);
+        let mut aux = AuxiliaryBuffers::empty();
+        let context = ProcessContext::with_empty_transport(48_000.0, len);
+        control.split(
+            recorder,
+            interval,
+            &mut buffer,
+            &mut aux,
+            &context,
+            |r, tick| r.ticks.push((tick.tick, tick.block_offset)),
+            |_, _, _| usize::MAX,
+            |r, buffer, _, context| {
+                assert_eq!(buffer.num_samples(), context.num_samples);
+                buffer.output(0).fill(1.0);
+                r.blocks.push(buffer.num_samples());
+            },
+        );
+        assert!(output.iter().all(|&s| s == 1.0));
+    }
+
+    #[test]
+    fn ticks_follow_a_grid_across_blocks() {
+        let mut control = ControlRate::new();
+        let mut recorder = Recorder::default();
+        run_block(&mut control, &mut recorder, 128, 300);
+        run_block(&mut control, &mut recorder, 128, 100);
+        run_block(&mut control, &mut recorder, 128, 0);
+
+        assert_eq!(recorder.ticks, [(0, 0), (1, 128), (2, 256), (3, 84)]);
+        assert_eq!(recorder.blocks, [128, 128, 44, 84, 16, 0]);
+    }
+
+    #[test]
+    fn interval_change_and_zero_restart_the_grid() {
+        let mut control = ControlRate::new();
+        let mut recorder = Recorder::default();
+        run_block(&mut control, &mut recorder, 0, 64);
+        run_block(&mut control, &mut recorder, 32, 40);
+        run_block(&mut control, &mut recorder, 16, 20);
+
+        assert_eq!(recorder.ticks, [(0, 0), (1, 32), (0, 0), (1, 16)]);
+        assert_eq!(recorder.blocks, [64, 32, 8, 16, 4]);
+
+        let transport = Transport {
+            tempo: Some(120.0),
+            project_time_samples: Some(1000),
+            project_time_beats: Some(4.0),
+            is_playing: true,
+            ..Default::default()
+        };
+        let advanced = advance(&transport, 24_000, 48_000.0);
+        assert_eq!(advanced.project_time_samples, Some(25_000));
+        assert_eq!(advanced.project_time_beats, Some(5.0));
+    }
+}
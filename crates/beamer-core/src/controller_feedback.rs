@@ -0,0 +1,342 @@
+//! Rate-limited MIDI output for controller feedback.
+//!
+//! Plugins that drive motorized faders, LED rings or a second instance send
+//! their parameter values back out as MIDI. Sending a CC every block for
+//! every parameter floods the host and the hardware: a smoothed parameter
+//! changes on every block, and NRPNs cost four events each.
+//! [`ControllerFeedback`] keeps the latest value per target and sends it
+//! only when it changed, at most [`with_max_rate()`](ControllerFeedback::with_max_rate)
+//! times per second per target and at most
+//! [`with_max_events_per_block()`](ControllerFeedback::with_max_events_per_block)
+//! events per block. Intermediate values are dropped; the last value always
+//! arrives.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! fn process_midi(&mut self, input: &[MidiEvent], output: &mut MidiBuffer) {
+//!     let cutoff = self.parameters.cutoff.get_normalized() as f32;
+//!     self.feedback.set(FeedbackTarget::Cc14 { channel: 0, controller: 1 }, cutoff);
+//!     self.feedback.flush(output, self.block_size);
+//! }
+//! ```
+//!
+//! `process_midi()` runs before `process()` and doesn't see the block
+//! length; passing the length of the previous block keeps the clock close
+//! enough for rate limiting. Create the feedback in `prepare()` with
+//! [`ControllerFeedback::new()`] and the session sample rate; it allocates
+//! its slots there and never on the audio thread.
+
+use crate::midi::{raw_14bit, MidiBuffer, MidiChannel, MidiEvent};
+
+/// Default per-target update rate in Hz.
+const DEFAULT_MAX_RATE: f64 = 50.0;
+
+/// Default cap on events sent per block.
+const DEFAULT_MAX_EVENTS_PER_BLOCK: usize = 64;
+
+/// A MIDI destination for a feedback value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackTarget {
+    /// 7-bit control change.
+    Cc {
+        /// MIDI channel (0-15)
+        channel: MidiChannel,
+        /// Controller number (0-127)
+        controller: u8,
+    },
+    /// 14-bit control change (MSB `controller`, LSB `controller + 32`).
+    Cc14 {
+        /// MIDI channel (0-15)
+        channel: MidiChannel,
+        /// MSB controller number (0-31)
+        controller: u8,
+    },
+    /// Non-registered parameter number with 14-bit data entry.
+    Nrpn {
+        /// MIDI channel (0-15)
+        channel: MidiChannel,
+        /// 14-bit parameter number
+        parameter: u16,
+    },
+}
+
+impl FeedbackTarget {
+    /// Number of MIDI events one update sends.
+    pub const fn event_count(&self) -> usize {
+        match self {
+            Self::Cc { .. } => 1,
+            Self::Cc14 { .. } => 2,
+            Self::Nrpn { .. } => 4,
+        }
+    }
+
+    /// Quantize a normalized value to this target's resolution.
+    fn quantize(&self, value: f32) -> u16 {
+        match self {
+            Self::Cc { .. } => (value.clamp(0.0, 1.0) * 127.0).round() as u16,
+            Self::Cc14 { .. } | Self::Nrpn { .. } => raw_14bit(value),
+        }
+    }
+
+    fn send(&self, output: &mut MidiBuffer, sample_offset: u32, raw: u16) -> bool {
+        match *self {
+            Self::Cc { channel, controller } => output.push(MidiEvent::control_change(
+                sample_offset,
+                channel,
+                controller,
+                raw as f32 / 127.0,
+            )),
+            Self::Cc14 { channel, controller } => {
+                output.send_cc14(sample_offset, channel, controller, raw as f32 / 16383.0)
+            }
+            Self::Nrpn { channel, parameter } => {
+                output.send_nrpn(sample_offset, channel, parameter, raw as f32 / 16383.0)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    target: FeedbackTarget,
+    /// Latest value not yet sent.
+    pending: Option<u16>,
+    /// Last value sent.
+    sent: Option<u16>,
+    /// Earliest sample time for the next send.
+    next_due: u64,
+}
+
+/// Change-only, rate-limited sender of controller feedback.
+///
+/// Call [`set()`](Self::set) whenever a value may have changed (every block
+/// is fine) and [`flush()`](Self::flush) once per block after all `set()`
+/// calls. Real-time safe after construction.
+#[derive(Debug, Clone)]
+pub struct ControllerFeedback {
+    slots: Vec<Slot>,
+    capacity: usize,
+    sample_rate: f64,
+    max_rate: f64,
+    interval: u64,
+    max_events_per_block: usize,
+    /// Sample time at the start of the current block.
+    now: u64,
+    /// Slot to start from in the next flush, so a full budget rotates.
+    cursor: usize,
+}
+
+impl ControllerFeedback {
+    /// Create feedback for up to `capacity` targets at `sample_rate`.
+    pub fn new(capacity: usize, sample_rate: f64) -> Self {
+        let mut feedback = Self {
+            slots: Vec::with_capacity(capacity),
+            capacity,
+            sample_rate,
+            max_rate: DEFAULT_MAX_RATE,
+            interval: 0,
+            max_events_per_block: DEFAULT_MAX_EVENTS_PER_BLOCK,
+            now: 0,
+            cursor: 0,
+        };
+        feedback.update_interval();
+        feedback
+    }
+
+    /// Limit updates per target to `hz` per second (default 50).
+    ///
+    /// Zero or negative disables the limit.
+    pub fn with_max_rate(mut self, hz: f64) -> Self {
+        self.max_rate = hz;
+        self.update_interval();
+        self
+    }
+
+    /// Limit the events sent per block (default 64).
+    ///
+    /// Counts MIDI events, so one NRPN update uses four. Updates that don't
+    /// fit wait for the next block.
+    pub fn with_max_events_per_block(mut self, max: usize) -> Self {
+        self.max_events_per_block = max;
+        self
+    }
+
+    /// Update the sample rate used to convert the rate limit to samples.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.update_interval();
+    }
+
+    fn update_interval(&mut self) {
+        self.interval = if self.max_rate > 0.0 {
+            (self.sample_rate / self.max_rate).round() as u64
+        } else {
+            0
+        };
+    }
+
+    /// Queue a normalized value (0.0-1.0) for `target`.
+    ///
+    /// Values equal to the last one sent (at the target's resolution) are
+    /// ignored. Returns `false` if `target` is new and all slots are taken.
+    pub fn set(&mut self, target: FeedbackTarget, value: f32) -> bool {
+        let raw = target.quantize(value);
+        let slot = match self.slots.iter_mut().position(|slot| slot.target == target) {
+            Some(index) => &mut self.slots[index],
+            None if self.slots.len() < self.capacity => {
+                self.slots.push(Slot {
+                    target,
+                    pending: None,
+                    sent: None,
+                    next_due: 0,
+                });
+                self.slots.last_mut().expect("slot was just pushed")
+            }
+            None => return false,
+        };
+        slot.pending = (slot.sent != Some(raw)).then_some(raw);
+        true
+    }
+
+    /// Send due updates into `output` and advance time by `num_samples`.
+    ///
+    /// An update that becomes due inside the block is sent at that sample
+    /// offset. Keeps `output` ordered by `sample_offset`.
+    pub fn flush(&mut self, output: &mut MidiBuffer, num_samples: u32) {
+        let count = self.slots.len();
+        let mut budget = self.max_events_per_block;
+        let mut sent_any = false;
+        let end = self.now + num_samples as u64;
+
+        for step in 0..count {
+            let index = (self.cursor + step) % count;
+            let slot = &mut self.slots[index];
+            let Some(raw) = slot.pending else {
+                continue;
+            };
+            if slot.next_due >= end {
+                continue;
+            }
+            let events = slot.target.event_count();
+            if events > budget {
+                // Out of budget: resume from this slot next block
+                self.cursor = index;
+                break;
+            }
+            let time = slot.next_due.max(self.now);
+            if !slot.target.send(output, (time - self.now) as u32, raw) {
+                // Output full: keep it pending
+                continue;
+            }
+            budget -= events;
+            sent_any = true;
+            slot.sent = Some(raw);
+            slot.pending = None;
+            slot.next_due = time + self.interval;
+        }
+
+        if sent_any {
+            output.sort_by_offset();
+        }
+        self.now = end;
+    }
+
+    /// Queue every value sent so far again, e.g. after a controller
+    /// reconnects or a host asks for a refresh.
+    pub fn resend_all(&mut self) {
+        for slot in &mut self.slots {
+            if slot.pending.is_none() {
+                slot.pending = slot.sent;
+            }
+            slot.sent = None;
+        }
+    }
+
+    /// Forget all targets and values.
+    pub fn reset(&mut self) {
+        self.slots.clear();
+        self.now = 0;
+        self.cursor = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CC: FeedbackTarget = FeedbackTarget::Cc {
+        channel: 0,
+        controller: 7,
+    };
+
+    #[test]
+    fn sends_changes_only_at_the_limited_rate() {
+        // 10 Hz at 1000 samples/s: one update per 100 samples
+        let mut feedback = ControllerFeedback::new(4, 1000.0).with_max_rate(10.0);
+        let mut output = MidiBuffer::new_boxed();
+
+        feedback.set(CC, 0.5);
+        feedback.flush(&mut output, 64);
+        assert_eq!(output.len(), 1);
+
+        // Changed: due at sample 100, offset 36 of the block at 64..128
+        output.clear();
+        feedback.set(CC, 0.6);
+        feedback.flush(&mut output, 64);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output.as_slice()[0].sample_offset, 36);
+
+        // Next due at 200: held back in 128..192, then only the latest value arrives
+        output.clear();
+        feedback.set(CC, 0.7);
+        feedback.flush(&mut output, 64);
+        assert!(output.is_empty());
+        feedback.set(CC, 0.8);
+        feedback.flush(&mut output, 64);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output.as_slice()[0].sample_offset, 8);
+        let value = match &output.as_slice()[0].event {
+            crate::midi::MidiEventKind::ControlChange(cc) => cc.value,
+            _ => panic!("expected ControlChange"),
+        };
+        assert_eq!((value * 127.0).round() as u8, (0.8f32 * 127.0).round() as u8);
+
+        // Same value: nothing to send
+        output.clear();
+        feedback.set(CC, 0.8);
+        feedback.flush(&mut output, 1000);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn budget_rotates_across_blocks() {
+        let mut feedback = ControllerFeedback::new(4, 48000.0)
+            .with_max_rate(0.0)
+            .with_max_events_per_block(4);
+        let mut output = MidiBuffer::new_boxed();
+        for parameter in 0..2 {
+            feedback.set(
+                FeedbackTarget::Nrpn {
+                    channel: 0,
+                    parameter,
+                },
+                1.0,
+            );
+        }
+        assert!(!ControllerFeedback::new(0, 48000.0).set(CC, 1.0));
+
+        feedback.flush(&mut output, 64);
+        assert_eq!(output.len(), 4);
+        output.clear();
+        feedback.flush(&mut output, 64);
+        assert_eq!(output.len(), 4);
+        output.clear();
+        feedback.flush(&mut output, 64);
+        assert!(output.is_empty());
+
+        feedback.resend_all();
+        feedback.flush(&mut output, 64);
+        assert_eq!(output.len(), 4);
+    }
+}
@@ -0,0 +1,469 @@
+//! Parameter pages for hardware controllers.
+//!
+//! Most MIDI controllers have a row of eight knobs. Plugins with more than
+//! eight parameters worth reaching for group them into *pages*: "Oscillator",
+//! "Filter", "Envelope", and so on, each assigning up to
+//! [`KNOBS_PER_PAGE`] parameters to the knobs. One page is selected at a time
+//! and the knobs control its parameters.
+//!
+//! Declare pages on the descriptor with [`Descriptor::controller_pages()`]:
+//!
+//! ```ignore
+//! fn controller_pages(&self) -> ControllerPages {
+//!     ControllerPages::new()
+//!         .with_page(ControllerPage::new("Filter").with_parameters(&["cutoff", "resonance", "drive"]))
+//!         .with_page(ControllerPage::new("Amp").with_parameters(&["attack", "decay", "sustain", "release"]))
+//! }
+//! ```
+//!
+//! The wrapper turns the definition into a [`ControllerPageState`], which
+//! tracks the selected page and maps knob CCs to the page's parameters.
+//!
+//! # Host Integration
+//!
+//! | Format | Mapping |
+//! |--------|---------|
+//! | VST3 | `IMidiMapping`: the knob CCs ([`DEFAULT_KNOB_CCS`] unless overridden) map to the selected page's parameters. Changing the page sends `kMidiCCAssignmentChanged`. |
+//! | AU | No host mapping interface; pages are available to the GUI and saved with the state. |
+//!
+//! # Persistence
+//!
+//! The selected page is saved with the plugin state. When the plugin has
+//! pages, the wrapper appends a trailer after any other framework trailer and
+//! strips it again before loading, so plugin code never sees it:
+//!
+//! ```text
+//! [plugin state][other trailers][page: u32 LE]["BXCP"]
+//! ```
+//!
+//! # GUI Invokes
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`CONTROLLER_PAGES_INVOKE`] | `[]` | `{ pages: [{ name, parameters }], selected, knobCcs }` |
+//! | [`SELECT_CONTROLLER_PAGE_INVOKE`] | `[index]` | `true` if the selection changed |
+//!
+//! `parameters` holds one parameter ID (or `null` for an unassigned knob) per
+//! knob.
+//!
+//! [`Descriptor::controller_pages()`]: crate::plugin::Descriptor::controller_pages
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::parameter_store::ParameterStore;
+use crate::types::ParameterId;
+
+/// Number of knobs on a controller page.
+pub const KNOBS_PER_PAGE: usize = 8;
+
+/// Knob CCs used unless [`ControllerPages::with_knob_ccs()`] overrides them.
+///
+/// CC 70-77 (Sound Controllers 1-8) are unassigned by most instruments and a
+/// common default for generic controller knobs.
+pub const DEFAULT_KNOB_CCS: [u8; KNOBS_PER_PAGE] = [70, 71, 72, 73, 74, 75, 76, 77];
+
+/// Built-in WebView invoke method that describes the pages.
+pub const CONTROLLER_PAGES_INVOKE: &str = "_beamer/controllerPages";
+
+/// Built-in WebView invoke method that selects a page.
+pub const SELECT_CONTROLLER_PAGE_INVOKE: &str = "_beamer/selectControllerPage";
+
+/// Magic bytes ending the selected-page state trailer.
+const STATE_TRAILER_MAGIC: &[u8; 4] = b"BXCP";
+
+// =============================================================================
+// Definition
+// =============================================================================
+
+/// One page of up to [`KNOBS_PER_PAGE`] parameters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ControllerPage {
+    name: String,
+    /// Parameter string IDs, one per knob.
+    knobs: [Option<&'static str>; KNOBS_PER_PAGE],
+}
+
+impl ControllerPage {
+    /// Create an empty page.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            knobs: [None; KNOBS_PER_PAGE],
+        }
+    }
+
+    /// Assign parameters to the knobs in order, by string ID.
+    ///
+    /// Parameters beyond [`KNOBS_PER_PAGE`] are ignored.
+    pub fn with_parameters(mut self, string_ids: &[&'static str]) -> Self {
+        for (knob, &string_id) in self.knobs.iter_mut().zip(string_ids) {
+            *knob = Some(string_id);
+        }
+        self
+    }
+
+    /// Assign a parameter to one knob, by string ID.
+    ///
+    /// Out-of-range knob indices are ignored.
+    pub fn with_knob(mut self, knob: usize, string_id: &'static str) -> Self {
+        if let Some(slot) = self.knobs.get_mut(knob) {
+            *slot = Some(string_id);
+        }
+        self
+    }
+
+    /// Page name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Parameter string ID assigned to a knob.
+    pub fn knob(&self, knob: usize) -> Option<&'static str> {
+        self.knobs.get(knob).copied().flatten()
+    }
+}
+
+/// Controller pages declared by a plugin.
+///
+/// Returned by `Descriptor::controller_pages()`. Empty by default, which
+/// disables the feature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControllerPages {
+    pages: Vec<ControllerPage>,
+    knob_ccs: [u8; KNOBS_PER_PAGE],
+}
+
+impl Default for ControllerPages {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ControllerPages {
+    /// Create an empty page list with [`DEFAULT_KNOB_CCS`].
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            knob_ccs: DEFAULT_KNOB_CCS,
+        }
+    }
+
+    /// Append a page.
+    pub fn with_page(mut self, page: ControllerPage) -> Self {
+        self.pages.push(page);
+        self
+    }
+
+    /// Set the CC numbers the controller's knobs send.
+    pub fn with_knob_ccs(mut self, ccs: [u8; KNOBS_PER_PAGE]) -> Self {
+        self.knob_ccs = ccs;
+        self
+    }
+
+    /// Pages in declaration order.
+    pub fn pages(&self) -> &[ControllerPage] {
+        &self.pages
+    }
+
+    /// CC numbers of the knobs.
+    pub fn knob_ccs(&self) -> [u8; KNOBS_PER_PAGE] {
+        self.knob_ccs
+    }
+
+    /// Returns true if no pages are declared.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+// =============================================================================
+// Runtime State
+// =============================================================================
+
+/// A page with its knobs resolved to parameter IDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedPage {
+    /// Page name.
+    pub name: String,
+    /// Parameter ID per knob (`None` if unassigned or unknown).
+    pub parameters: [Option<ParameterId>; KNOBS_PER_PAGE],
+}
+
+/// Per-instance controller page selection.
+///
+/// Cloning is cheap (reference counted), so the wrapper can share one state
+/// between its host interface and its WebView IPC context. The selected page
+/// is an atomic; everything else is immutable after construction.
+#[derive(Clone, Debug, Default)]
+pub struct ControllerPageState {
+    inner: Arc<PageStateInner>,
+}
+
+#[derive(Debug, Default)]
+struct PageStateInner {
+    pages: Vec<ResolvedPage>,
+    knob_ccs: [u8; KNOBS_PER_PAGE],
+    selected: AtomicUsize,
+    /// Set whenever the selection changes; consumed by the wrapper.
+    changed: AtomicBool,
+}
+
+impl ControllerPageState {
+    /// Resolve `pages` against the plugin's parameters, selecting page 0.
+    ///
+    /// Knobs naming an unknown string ID are left unassigned (and logged).
+    pub fn new(pages: &ControllerPages, store: &dyn ParameterStore) -> Self {
+        let resolve = |string_id: &str| -> Option<ParameterId> {
+            let id = (0..store.count())
+                .filter_map(|i| store.info(i))
+                .find(|info| info.string_id == string_id)
+                .map(|info| info.id);
+            if id.is_none() {
+                log::warn!("Controller page parameter '{}' not found", string_id);
+            }
+            id
+        };
+        Self::from_resolved(
+            pages
+                .pages
+                .iter()
+                .map(|page| ResolvedPage {
+                    name: page.name.clone(),
+                    parameters: page.knobs.map(|knob| knob.and_then(resolve)),
+                })
+                .collect(),
+            pages.knob_ccs,
+        )
+    }
+
+    fn from_resolved(pages: Vec<ResolvedPage>, knob_ccs: [u8; KNOBS_PER_PAGE]) -> Self {
+        Self {
+            inner: Arc::new(PageStateInner {
+                pages,
+                knob_ccs,
+                selected: AtomicUsize::new(0),
+                changed: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Returns true if the plugin declares no pages.
+    pub fn is_empty(&self) -> bool {
+        self.inner.pages.is_empty()
+    }
+
+    /// Resolved pages in declaration order.
+    pub fn pages(&self) -> &[ResolvedPage] {
+        &self.inner.pages
+    }
+
+    /// Index of the selected page.
+    pub fn selected(&self) -> usize {
+        self.inner.selected.load(Ordering::Relaxed)
+    }
+
+    /// Select a page.
+    ///
+    /// Returns true if the selection changed. Out-of-range indices are
+    /// ignored.
+    pub fn select(&self, index: usize) -> bool {
+        if index >= self.inner.pages.len() {
+            return false;
+        }
+        let previous = self.inner.selected.swap(index, Ordering::Relaxed);
+        if previous == index {
+            return false;
+        }
+        self.inner.changed.store(true, Ordering::Release);
+        true
+    }
+
+    /// Parameter controlled by a knob on the selected page.
+    pub fn knob_parameter(&self, knob: usize) -> Option<ParameterId> {
+        self.inner
+            .pages
+            .get(self.selected())
+            .and_then(|page| page.parameters.get(knob).copied().flatten())
+    }
+
+    /// Parameter a knob CC controls on the selected page.
+    pub fn cc_to_parameter(&self, cc: u8) -> Option<ParameterId> {
+        let knob = self.inner.knob_ccs.iter().position(|&knob_cc| knob_cc == cc)?;
+        self.knob_parameter(knob)
+    }
+
+    /// Returns true once after the selection changed.
+    ///
+    /// Wrappers poll this to tell the host the CC assignments changed.
+    pub fn take_changed(&self) -> bool {
+        self.inner.changed.swap(false, Ordering::AcqRel)
+    }
+
+    /// Append the selected-page trailer to a saved plugin state.
+    ///
+    /// Does nothing when the plugin has no pages.
+    pub fn append_state(&self, data: &mut Vec<u8>) {
+        if self.is_empty() {
+            return;
+        }
+        data.extend_from_slice(&(self.selected() as u32).to_le_bytes());
+        data.extend_from_slice(STATE_TRAILER_MAGIC);
+    }
+
+    /// Apply and strip the selected-page trailer from a state blob.
+    ///
+    /// Returns the length of the remaining state. A state without a trailer
+    /// selects the first page and is passed through unchanged.
+    pub fn load_state(&self, data: &[u8]) -> usize {
+        if self.is_empty() {
+            return data.len();
+        }
+        let (len, page) = parse_trailer(data).unwrap_or((data.len(), 0));
+        self.select(page.min(self.inner.pages.len() - 1));
+        len
+    }
+
+    /// `{ pages, selected, knobCcs }` for the GUI.
+    pub fn to_json(&self) -> serde_json::Value {
+        let pages: Vec<serde_json::Value> = self
+            .inner
+            .pages
+            .iter()
+            .map(|page| {
+                serde_json::json!({
+                    "name": page.name,
+                    "parameters": page.parameters.to_vec(),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "pages": pages,
+            "selected": self.selected(),
+            "knobCcs": self.inner.knob_ccs.to_vec(),
+        })
+    }
+}
+
+/// Handle the built-in controller page WebView calls.
+///
+/// Returns `None` if `method` is not one of the controller page invokes.
+pub fn controller_pages_invoke(
+    state: &ControllerPageState,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    match method {
+        CONTROLLER_PAGES_INVOKE => Some(state.to_json()),
+        SELECT_CONTROLLER_PAGE_INVOKE => {
+            let index = args.first().and_then(|v| v.as_u64());
+            Some(index.is_some_and(|index| state.select(index as usize)).into())
+        }
+        _ => None,
+    }
+}
+
+/// Parse `[page][magic]` from the end of `data`.
+fn parse_trailer(data: &[u8]) -> Option<(usize, usize)> {
+    let magic_start = data.len().checked_sub(STATE_TRAILER_MAGIC.len())?;
+    if &data[magic_start..] != STATE_TRAILER_MAGIC {
+        return None;
+    }
+    let page_start = magic_start.checked_sub(4)?;
+    let page = u32::from_le_bytes(data[page_start..magic_start].try_into().ok()?);
+    Some((page_start, page as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> ControllerPageState {
+        let mut filter = [None; KNOBS_PER_PAGE];
+        filter[0] = Some(10);
+        filter[1] = Some(11);
+        let mut amp = [None; KNOBS_PER_PAGE];
+        amp[0] = Some(20);
+        ControllerPageState::from_resolved(
+            vec![
+                ResolvedPage { name: "Filter".into(), parameters: filter },
+                ResolvedPage { name: "Amp".into(), parameters: amp },
+            ],
+            DEFAULT_KNOB_CCS,
+        )
+    }
+
+    #[test]
+    fn test_page_builder() {
+        let page = ControllerPage::new("Filter")
+            .with_parameters(&["cutoff", "resonance"])
+            .with_knob(7, "drive")
+            .with_knob(8, "ignored");
+        assert_eq!(page.knob(0), Some("cutoff"));
+        assert_eq!(page.knob(2), None);
+        assert_eq!(page.knob(7), Some("drive"));
+
+        let pages = ControllerPages::new().with_page(page);
+        assert_eq!(pages.pages().len(), 1);
+        assert_eq!(pages.knob_ccs(), DEFAULT_KNOB_CCS);
+        assert!(ControllerPages::default().is_empty());
+    }
+
+    #[test]
+    fn test_knobs_follow_selected_page() {
+        let state = state();
+        assert_eq!(state.cc_to_parameter(70), Some(10));
+        assert_eq!(state.cc_to_parameter(71), Some(11));
+        assert_eq!(state.cc_to_parameter(72), None);
+        assert_eq!(state.cc_to_parameter(1), None);
+
+        assert!(state.select(1));
+        assert!(state.take_changed());
+        assert!(!state.take_changed());
+        assert_eq!(state.cc_to_parameter(70), Some(20));
+        assert_eq!(state.cc_to_parameter(71), None);
+
+        assert!(!state.select(1));
+        assert!(!state.select(5));
+    }
+
+    #[test]
+    fn test_state_trailer_roundtrip() {
+        let state = state();
+        state.select(1);
+        let mut data = vec![1, 2, 3];
+        state.append_state(&mut data);
+
+        let restored = self::state();
+        assert_eq!(restored.load_state(&data), 3);
+        assert_eq!(restored.selected(), 1);
+
+        // A state without a trailer selects the first page.
+        assert_eq!(restored.load_state(&[1, 2, 3]), 3);
+        assert_eq!(restored.selected(), 0);
+
+        // No pages, no trailer.
+        let empty = ControllerPageState::default();
+        let mut data = vec![7];
+        empty.append_state(&mut data);
+        assert_eq!(data, vec![7]);
+    }
+
+    #[test]
+    fn test_invoke() {
+        let state = state();
+        let json = controller_pages_invoke(&state, CONTROLLER_PAGES_INVOKE, &[]).unwrap();
+        assert_eq!(json["pages"][0]["name"], "Filter");
+        assert_eq!(json["pages"][0]["parameters"][1], 11);
+        assert!(json["pages"][0]["parameters"][2].is_null());
+        assert_eq!(json["knobCcs"][0], 70);
+
+        let args = [serde_json::Value::from(1)];
+        assert_eq!(
+            controller_pages_invoke(&state, SELECT_CONTROLLER_PAGE_INVOKE, &args),
+            Some(serde_json::Value::Bool(true))
+        );
+        assert_eq!(state.selected(), 1);
+        assert_eq!(controller_pages_invoke(&state, "other", &[]), None);
+    }
+}
@@ -30,7 +30,16 @@
 //! // 2. Process with f32 buffers
 //! // 3. Convert f32 outputs back to f64
 //! ```
+//!
+//! # Precision Policies
+//!
+//! [`ConversionBuffers::allocate_for_policy()`] only allocates the buses a
+//! [`PrecisionPolicy`] converts. For [`PrecisionPolicy::F64MainF32Aux`] the
+//! main bus stays at f64 and [`ConversionBuffers::with_f32_aux()`] converts
+//! just the auxiliary buses around the `process_f64_mixed()` call.
 
+use crate::buffer::AuxiliaryBuffers;
+use crate::plugin::PrecisionPolicy;
 use crate::BusInfo;
 
 /// Pre-allocated buffers for f64↔f32 conversion.
@@ -126,6 +135,30 @@ impl ConversionBuffers {
         }
     }
 
+    /// Pre-allocate only the buses `policy` converts.
+    ///
+    /// [`PrecisionPolicy::F32`] allocates every bus (like
+    /// [`allocate_from_buses()`](Self::allocate_from_buses)),
+    /// [`PrecisionPolicy::F64MainF32Aux`] only the auxiliary buses and
+    /// [`PrecisionPolicy::F64`] nothing.
+    pub fn allocate_for_policy(
+        policy: PrecisionPolicy,
+        input_buses: &[BusInfo],
+        output_buses: &[BusInfo],
+        max_frames: usize,
+    ) -> Self {
+        let mut buffers = if policy.aux_is_f64() {
+            Self::new()
+        } else {
+            Self::allocate_from_buses(input_buses, output_buses, max_frames)
+        };
+        if policy.main_is_f64() {
+            buffers.main_input_f32.clear();
+            buffers.main_output_f32.clear();
+        }
+        buffers
+    }
+
     /// Pre-allocate buffers with explicit channel counts.
     ///
     /// Use this when you have channel counts directly rather than `BusInfo`.
@@ -287,6 +320,73 @@ impl ConversionBuffers {
     pub fn aux_output_channel_count(&self, bus: usize) -> usize {
         self.aux_output_f32.get(bus).map(|b| b.len()).unwrap_or(0)
     }
+
+    // =========================================================================
+    // Mixed precision
+    // =========================================================================
+
+    /// Run `process` with f32 copies of f64 auxiliary buses.
+    ///
+    /// Converts `aux` inputs into the auxiliary conversion buffers, calls
+    /// `process` with f32 auxiliary buffers and converts their outputs back
+    /// into `aux`. Main bus buffers are not touched, so this works with
+    /// buffers from [`allocate_for_policy()`](Self::allocate_for_policy)
+    /// with [`PrecisionPolicy::F64MainF32Aux`]. Buses or samples beyond the
+    /// allocated capacity are dropped.
+    ///
+    /// Real-time safe: no allocation.
+    pub fn with_f32_aux<R>(
+        &mut self,
+        aux: &mut AuxiliaryBuffers<f64>,
+        process: impl FnOnce(&mut AuxiliaryBuffers<f32>) -> R,
+    ) -> R {
+        let num_samples = aux.num_samples();
+
+        for (bus, channels) in self.aux_input_f32.iter_mut().enumerate() {
+            let input = aux.input(bus);
+            for (ch, buf) in channels.iter_mut().enumerate() {
+                let len = num_samples.min(buf.len());
+                match input.as_ref().filter(|input| ch < input.num_channels()) {
+                    Some(input) => {
+                        for (dst, &src) in buf[..len].iter_mut().zip(input.input(ch)) {
+                            *dst = src as f32;
+                        }
+                    }
+                    None => buf[..len].fill(0.0),
+                }
+            }
+        }
+
+        let len = |buf: &[f32]| num_samples.min(buf.len());
+        let result = {
+            let inputs = self
+                .aux_input_f32
+                .iter()
+                .map(|bus| bus.iter().map(|v| &v[..len(v)]));
+            let outputs = self.aux_output_f32.iter_mut().map(|bus| {
+                bus.iter_mut().map(|v| {
+                    let n = len(v);
+                    &mut v[..n]
+                })
+            });
+            let mut aux_f32 = AuxiliaryBuffers::new(inputs, outputs, num_samples);
+            process(&mut aux_f32)
+        };
+
+        for (bus, channels) in self.aux_output_f32.iter().enumerate() {
+            if let Some(mut output) = aux.output(bus) {
+                for (ch, buf) in channels.iter().enumerate() {
+                    if let Some(dst) = output.output_checked(ch) {
+                        for (d, &s) in dst.iter_mut().zip(&buf[..len(buf)]) {
+                            *d = s as f64;
+                        }
+                    }
+                }
+            }
+        }
+
+        result
+    }
 }
 
 impl Default for ConversionBuffers {
@@ -379,4 +479,47 @@ mod tests {
         assert!(buffers.main_input(5).is_none());
         assert!(buffers.aux_input(5, 0, 64).is_none());
     }
+
+    #[test]
+    fn test_allocate_for_policy() {
+        let input_buses = vec![BusInfo::stereo("Main In"), BusInfo::aux("Sidechain", 2)];
+        let output_buses = vec![BusInfo::stereo("Main Out")];
+
+        let f32 = ConversionBuffers::allocate_for_policy(PrecisionPolicy::F32, &input_buses, &output_buses, 64);
+        assert_eq!(f32.main_input_channel_count(), 2);
+        assert_eq!(f32.aux_input_bus_count(), 1);
+
+        let mixed = ConversionBuffers::allocate_for_policy(
+            PrecisionPolicy::F64MainF32Aux,
+            &input_buses,
+            &output_buses,
+            64,
+        );
+        assert_eq!(mixed.main_input_channel_count(), 0);
+        assert_eq!(mixed.main_output_channel_count(), 0);
+        assert_eq!(mixed.aux_input_bus_count(), 1);
+        assert_eq!(mixed.aux_input_channel_count(0), 2);
+
+        let f64 = ConversionBuffers::allocate_for_policy(PrecisionPolicy::F64, &input_buses, &output_buses, 64);
+        assert_eq!(f64.main_input_channel_count(), 0);
+        assert_eq!(f64.aux_input_bus_count(), 0);
+    }
+
+    #[test]
+    fn test_with_f32_aux_converts_aux_only() {
+        let mut buffers = ConversionBuffers::allocate(0, 0, &[1], &[1], 8);
+        let input = [0.5f64; 8];
+        let mut output = [0.0f64; 8];
+        let peak = {
+            let mut aux = AuxiliaryBuffers::new([[&input[..]]], [[&mut output[..]]], 8);
+            buffers.with_f32_aux(&mut aux, |aux_f32| {
+                let peak = aux_f32.sidechain().unwrap().peak(0);
+                aux_f32.output(0).unwrap().fill(-0.25);
+                peak
+            })
+        };
+
+        assert_eq!(peak, 0.5f32);
+        assert!(output.iter().all(|&s| s == -0.25));
+    }
 }
@@ -0,0 +1,230 @@
+//! Crash guard and safe mode for state restoration.
+//!
+//! A corrupted or pathological preset can crash the plugin every time the
+//! host restores it, which effectively bricks the session: reopening the
+//! project restores the same state and crashes again. [`CrashGuard`] detects
+//! this and loads the plugin in *safe mode* instead.
+//!
+//! # How It Works
+//!
+//! Before the wrapper applies a state blob it writes a small marker file
+//! containing a hash of the blob. The marker is removed once
+//! `Processor::load_state()` returns (successfully or with an error). If the
+//! process dies or `load_state()` panics, the marker survives, and the next
+//! attempt to load the *same* state increments its counter.
+//!
+//! Once the same state has failed [`CRASH_GUARD_THRESHOLD`] times in a row,
+//! the guard trips: the state is skipped, all parameters are reset to their
+//! defaults and the instance reports safe mode. Loading a different state
+//! clears safe mode.
+//!
+//! The marker lives next to the plugin's log file (see [`crate::logging`]):
+//!
+//! | Platform | Path |
+//! |----------|------|
+//! | macOS    | `~/Library/Logs/<vendor>/<plugin>.crashguard` |
+//! | Windows  | `%LOCALAPPDATA%\<vendor>\Logs\<plugin>.crashguard` |
+//! | Linux    | `$XDG_STATE_HOME/<vendor>/<plugin>.crashguard` (or `~/.local/state`) |
+//!
+//! # GUI Notification
+//!
+//! The WebView runtime exposes the flag as `__BEAMER__.isSafeMode()`, so the
+//! GUI can tell the user their preset was not loaded. Saving the session in
+//! safe mode stores the default state, which replaces the bad preset.
+//!
+//! **Not audio-thread safe.** Arming and disarming touch the file system.
+//! The wrappers only use the guard from `setState()` and preparation.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::PluginResult;
+use crate::logging;
+use crate::parameter_store::ParameterStore;
+use crate::plugin::Processor;
+
+/// Consecutive failed loads of the same state before safe mode kicks in.
+pub const CRASH_GUARD_THRESHOLD: u32 = 2;
+
+/// Built-in WebView invoke method that returns whether safe mode is active.
+pub const SAFE_MODE_INVOKE: &str = "_beamer/safeMode";
+
+/// Per-instance crash guard for state restoration.
+///
+/// Cloning is cheap (reference counted), so the wrapper can share the safe
+/// mode flag with its WebView IPC context.
+#[derive(Clone, Debug, Default)]
+pub struct CrashGuard {
+    inner: Arc<GuardInner>,
+}
+
+#[derive(Debug, Default)]
+struct GuardInner {
+    /// Marker file path. `None` disables the guard (no writable directory).
+    marker: Option<PathBuf>,
+    /// True when the last state load was skipped by the guard.
+    safe_mode: AtomicBool,
+}
+
+impl CrashGuard {
+    /// Create a guard whose marker lives in the plugin's log directory.
+    pub fn new(config: &Config) -> Self {
+        Self::with_marker(logging::log_directory(config.vendor).map(|dir| {
+            dir.join(format!(
+                "{}.crashguard",
+                logging::sanitize_file_name(config.name)
+            ))
+        }))
+    }
+
+    fn with_marker(marker: Option<PathBuf>) -> Self {
+        Self {
+            inner: Arc::new(GuardInner {
+                marker,
+                safe_mode: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Returns true if the last state load was skipped and defaults are in use.
+    pub fn is_safe_mode(&self) -> bool {
+        self.inner.safe_mode.load(Ordering::Acquire)
+    }
+
+    /// Load `data` into `processor` under the guard.
+    ///
+    /// If this state has already failed [`CRASH_GUARD_THRESHOLD`] times, the
+    /// state is skipped, the processor's parameters are reset to their
+    /// defaults and `Ok(())` is returned. Otherwise `load_state()` is called
+    /// and its result returned unchanged.
+    pub fn load_state<P: Processor>(&self, processor: &mut P, data: &[u8]) -> PluginResult<()> {
+        if self.arm(data) {
+            log::warn!(
+                "State failed to load {} times in a row, entering safe mode with default parameters",
+                CRASH_GUARD_THRESHOLD
+            );
+            reset_to_defaults(processor.parameters());
+            self.inner.safe_mode.store(true, Ordering::Release);
+            return Ok(());
+        }
+
+        let result = processor.load_state(data);
+        self.disarm();
+        self.inner.safe_mode.store(false, Ordering::Release);
+        result
+    }
+
+    /// Record a load attempt. Returns true if the guard tripped.
+    fn arm(&self, data: &[u8]) -> bool {
+        let Some(marker) = &self.inner.marker else {
+            return false;
+        };
+
+        let hash = state_hash(data);
+        let failures = match read_marker(marker) {
+            Some((marker_hash, count)) if marker_hash == hash => count,
+            _ => 0,
+        };
+        if failures >= CRASH_GUARD_THRESHOLD {
+            // Keep the marker so reopening the session stays in safe mode.
+            return true;
+        }
+
+        if let Some(dir) = marker.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(e) = fs::write(marker, format!("{hash:016x} {}", failures + 1)) {
+            log::debug!("Crash guard marker not written: {e}");
+        }
+        false
+    }
+
+    /// Clear the marker after a load attempt returned.
+    fn disarm(&self) {
+        if let Some(marker) = &self.inner.marker {
+            let _ = fs::remove_file(marker);
+        }
+    }
+}
+
+/// Handle the built-in [`SAFE_MODE_INVOKE`] WebView call.
+pub fn safe_mode_invoke(guard: &CrashGuard) -> serde_json::Value {
+    serde_json::Value::from(guard.is_safe_mode())
+}
+
+/// Set every parameter to its default value.
+fn reset_to_defaults(parameters: &dyn ParameterStore) {
+    for index in 0..parameters.count() {
+        if let Some(info) = parameters.info(index) {
+            parameters.set_normalized(info.id, info.default_normalized);
+        }
+    }
+}
+
+/// Parse a marker file into `(state hash, failure count)`.
+fn read_marker(path: &Path) -> Option<(u64, u32)> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut parts = contents.split_whitespace();
+    let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let count = parts.next()?.parse().ok()?;
+    Some((hash, count))
+}
+
+/// FNV-1a 64-bit hash of a state blob.
+fn state_hash(data: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_marker(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("beamer-crash-guard-{}-{name}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_arm_counts_unfinished_loads() {
+        let marker = temp_marker("count");
+        let guard = CrashGuard::with_marker(Some(marker.clone()));
+
+        // Two loads that never disarm (simulated crashes), then the guard trips.
+        assert!(!guard.arm(b"bad state"));
+        assert!(!guard.arm(b"bad state"));
+        assert!(guard.arm(b"bad state"));
+        assert_eq!(read_marker(&marker).map(|(_, c)| c), Some(CRASH_GUARD_THRESHOLD));
+
+        // A different state is loaded normally and restarts the count.
+        assert!(!guard.arm(b"other state"));
+        assert_eq!(read_marker(&marker).map(|(_, c)| c), Some(1));
+
+        guard.disarm();
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_disabled_without_marker() {
+        let guard = CrashGuard::with_marker(None);
+        for _ in 0..=CRASH_GUARD_THRESHOLD {
+            assert!(!guard.arm(b"state"));
+        }
+        assert!(!guard.is_safe_mode());
+    }
+
+    #[test]
+    fn test_state_hash_differs() {
+        assert_ne!(state_hash(b"a"), state_hash(b"b"));
+        assert_eq!(state_hash(b""), 0xcbf2_9ce4_8422_2325);
+    }
+}
@@ -0,0 +1,461 @@
+//! Linkwitz-Riley crossover networks for multiband processing.
+//!
+//! [`Crossover`] splits a buffer into 2 to 5 bands with Linkwitz-Riley
+//! filters (LR2, LR4 or LR8). Each band is all-pass compensated for the
+//! crossover points it doesn't pass through, so all bands share the same
+//! phase and summing them back gives a flat magnitude response: a multiband
+//! compressor or saturator with every band at unity is transparent apart
+//! from the network's phase shift.
+//!
+//! Bands are [`Lane`]s allocated up front, so [`Crossover::split()`] and
+//! [`Crossover::sum_into()`] are real-time safe.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::{Crossover, CrossoverSlope};
+//!
+//! impl Descriptor for MultibandDescriptor {
+//!     type Setup = (SampleRate, MaxBufferSize, MainOutputChannels);
+//!
+//!     fn prepare(self, (rate, max, channels): Self::Setup) -> MultibandProcessor {
+//!         let crossover = Crossover::new(&[120.0, 2500.0], CrossoverSlope::Lr4, rate.hz(), channels.0 as usize, max.0);
+//!         // ...
+//!     }
+//! }
+//!
+//! impl Processor for MultibandProcessor {
+//!     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!         self.crossover.set_frequency(0, self.parameters.low_mid.get());
+//!         self.crossover.split(buffer);
+//!         for (band, compressor) in self.compressors.iter_mut().enumerate() {
+//!             compressor.process(self.crossover.band_mut(band));
+//!         }
+//!         self.crossover.sum_into(buffer);
+//!     }
+//! }
+//! ```
+
+use crate::buffer::Buffer;
+use crate::filter::{BiquadCoeffs, BiquadState};
+use crate::parallel::Lane;
+use crate::sample::Sample;
+
+/// Maximum number of bands of a [`Crossover`].
+pub const MAX_CROSSOVER_BANDS: usize = 5;
+
+/// Lowest crossover frequency in Hz.
+const MIN_FREQUENCY: f64 = 10.0;
+
+/// Butterworth Q values of the two sections of a fourth-order filter.
+const BUTTERWORTH_4_Q: [f64; 2] = [0.541_196_100_146_197, 1.306_562_964_876_376_5];
+
+// =============================================================================
+// Slope
+// =============================================================================
+
+/// Linkwitz-Riley crossover slope.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CrossoverSlope {
+    /// 12 dB/octave. The high band is inverted so the bands sum flat.
+    Lr2,
+    /// 24 dB/octave.
+    #[default]
+    Lr4,
+    /// 48 dB/octave.
+    Lr8,
+}
+
+impl CrossoverSlope {
+    /// Steepness in dB per octave.
+    pub const fn db_per_octave(self) -> u32 {
+        match self {
+            Self::Lr2 => 12,
+            Self::Lr4 => 24,
+            Self::Lr8 => 48,
+        }
+    }
+
+    /// Biquad sections per low/high filter.
+    const fn sections(self) -> usize {
+        match self {
+            Self::Lr2 => 1,
+            Self::Lr4 => 2,
+            Self::Lr8 => 4,
+        }
+    }
+
+    /// Biquad sections of the all-pass matching one crossover point.
+    const fn all_pass_sections(self) -> usize {
+        match self {
+            Self::Lr2 | Self::Lr4 => 1,
+            Self::Lr8 => 2,
+        }
+    }
+}
+
+// =============================================================================
+// Filters
+// =============================================================================
+
+/// Coefficients of one crossover point.
+///
+/// A Linkwitz-Riley filter is a Butterworth filter applied twice. Low plus
+/// high is the all-pass of the Butterworth denominator, which bands below
+/// this point run through to stay in phase with the bands above it.
+#[derive(Debug, Clone, Copy, Default)]
+struct CrossoverPoint {
+    low: [BiquadCoeffs; 4],
+    high: [BiquadCoeffs; 4],
+    all_pass: [BiquadCoeffs; 2],
+}
+
+impl CrossoverPoint {
+    fn new(freq: f64, slope: CrossoverSlope, sample_rate: f64) -> Self {
+        let mut point = Self::default();
+        match slope {
+            CrossoverSlope::Lr2 => {
+                // First-order Butterworth squared is one biquad with Q = 0.5
+                point.low[0] = BiquadCoeffs::low_pass(freq, 0.5, sample_rate);
+                let high = BiquadCoeffs::high_pass(freq, 0.5, sample_rate);
+                point.high[0] = BiquadCoeffs {
+                    b0: -high.b0,
+                    b1: -high.b1,
+                    b2: -high.b2,
+                    ..high
+                };
+                point.all_pass[0] = BiquadCoeffs::first_order_all_pass(freq, sample_rate);
+            }
+            CrossoverSlope::Lr4 => {
+                let q = std::f64::consts::FRAC_1_SQRT_2;
+                point.low[..2].fill(BiquadCoeffs::low_pass(freq, q, sample_rate));
+                point.high[..2].fill(BiquadCoeffs::high_pass(freq, q, sample_rate));
+                point.all_pass[0] = BiquadCoeffs::all_pass(freq, q, sample_rate);
+            }
+            CrossoverSlope::Lr8 => {
+                for (i, q) in BUTTERWORTH_4_Q.into_iter().enumerate() {
+                    point.low[i] = BiquadCoeffs::low_pass(freq, q, sample_rate);
+                    point.low[i + 2] = point.low[i];
+                    point.high[i] = BiquadCoeffs::high_pass(freq, q, sample_rate);
+                    point.high[i + 2] = point.high[i];
+                    point.all_pass[i] = BiquadCoeffs::all_pass(freq, q, sample_rate);
+                }
+            }
+        }
+        point
+    }
+}
+
+/// Run `input` through a cascade of biquads.
+#[inline]
+fn cascade(coeffs: &[BiquadCoeffs], states: &mut [BiquadState], input: f64) -> f64 {
+    coeffs
+        .iter()
+        .zip(states)
+        .fold(input, |sample, (coeffs, state)| state.process(sample, coeffs))
+}
+
+/// Filter state of one channel.
+#[derive(Clone)]
+struct ChannelState {
+    /// Per crossover point.
+    low: Vec<[BiquadState; 4]>,
+    /// Per crossover point.
+    high: Vec<[BiquadState; 4]>,
+    /// Per band and crossover point (`band * num_points + point`).
+    all_pass: Vec<[BiquadState; 2]>,
+}
+
+// =============================================================================
+// Crossover
+// =============================================================================
+
+/// Linkwitz-Riley crossover network with 2 to 5 phase-aligned bands.
+///
+/// Bands are numbered from low to high. Band `i` holds the signal between
+/// crossover frequencies `i - 1` and `i`.
+pub struct Crossover<S: Sample = f32> {
+    frequencies: Vec<f64>,
+    slope: CrossoverSlope,
+    sample_rate: f64,
+    points: Vec<CrossoverPoint>,
+    channels: Vec<ChannelState>,
+    bands: Vec<Lane<S>>,
+    max_block_size: usize,
+}
+
+impl<S: Sample> Crossover<S> {
+    /// Create a crossover with pre-allocated bands.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequencies` - 1 to 4 ascending crossover frequencies in Hz (one
+    ///   fewer than the number of bands)
+    /// * `slope` - Filter slope
+    /// * `sample_rate` - Sample rate in Hz
+    /// * `num_channels` - Channels per band
+    /// * `max_block_size` - Largest block passed to [`split()`](Self::split)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of frequencies is out of range or they are not
+    /// ascending.
+    pub fn new(
+        frequencies: &[f64],
+        slope: CrossoverSlope,
+        sample_rate: f64,
+        num_channels: usize,
+        max_block_size: usize,
+    ) -> Self {
+        assert!(
+            (1..MAX_CROSSOVER_BANDS).contains(&frequencies.len()),
+            "a crossover needs 1 to {} frequencies",
+            MAX_CROSSOVER_BANDS - 1
+        );
+        assert!(
+            frequencies.windows(2).all(|pair| pair[0] <= pair[1]),
+            "crossover frequencies must be ascending"
+        );
+        let num_points = frequencies.len();
+        let num_bands = num_points + 1;
+        let mut crossover = Self {
+            frequencies: frequencies.to_vec(),
+            slope,
+            sample_rate,
+            points: vec![CrossoverPoint::default(); num_points],
+            channels: vec![
+                ChannelState {
+                    low: vec![Default::default(); num_points],
+                    high: vec![Default::default(); num_points],
+                    all_pass: vec![Default::default(); num_bands * num_points],
+                };
+                num_channels
+            ],
+            bands: (0..num_bands)
+                .map(|_| Lane::new(num_channels, max_block_size))
+                .collect(),
+            max_block_size,
+        };
+        crossover.update_points();
+        crossover
+    }
+
+    /// Number of bands.
+    #[inline]
+    pub fn num_bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Crossover frequencies in Hz, ascending.
+    #[inline]
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    /// Current slope.
+    #[inline]
+    pub fn slope(&self) -> CrossoverSlope {
+        self.slope
+    }
+
+    /// Move one crossover point.
+    ///
+    /// Clamped between the neighbouring points, so the bands stay in order.
+    /// Cheap enough to call every block, e.g. from a parameter.
+    pub fn set_frequency(&mut self, index: usize, hz: f64) {
+        if index >= self.frequencies.len() {
+            return;
+        }
+        let lower = if index == 0 { MIN_FREQUENCY } else { self.frequencies[index - 1] };
+        let upper = self.frequencies.get(index + 1).copied().unwrap_or(f64::MAX);
+        let hz = hz.max(lower).min(upper);
+        if hz != self.frequencies[index] {
+            self.frequencies[index] = hz;
+            self.points[index] = CrossoverPoint::new(hz, self.slope, self.sample_rate);
+        }
+    }
+
+    /// Change the slope. Clears the filter state.
+    pub fn set_slope(&mut self, slope: CrossoverSlope) {
+        if slope != self.slope {
+            self.slope = slope;
+            self.update_points();
+            self.reset();
+        }
+    }
+
+    /// Change the sample rate, e.g. after a re-prepare.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.update_points();
+    }
+
+    fn update_points(&mut self) {
+        for (point, &freq) in self.points.iter_mut().zip(&self.frequencies) {
+            *point = CrossoverPoint::new(freq.max(MIN_FREQUENCY), self.slope, self.sample_rate);
+        }
+    }
+
+    /// Get a band for processing.
+    #[inline]
+    pub fn band(&self, band: usize) -> &Lane<S> {
+        &self.bands[band]
+    }
+
+    /// Get a band mutably for processing.
+    #[inline]
+    pub fn band_mut(&mut self, band: usize) -> &mut Lane<S> {
+        &mut self.bands[band]
+    }
+
+    /// Split the buffer's input into the bands.
+    ///
+    /// Missing input channels are treated as silence. Blocks longer than
+    /// `max_block_size` are truncated.
+    pub fn split(&mut self, buffer: &Buffer<S>) {
+        let num_samples = buffer.num_samples().min(self.max_block_size);
+        let num_inputs = buffer.num_input_channels();
+        let num_points = self.points.len();
+        for band in &mut self.bands {
+            band.num_samples = num_samples;
+        }
+
+        for (ch, state) in self.channels.iter_mut().enumerate() {
+            let input = (ch < num_inputs).then(|| buffer.input(ch));
+            for i in 0..num_samples {
+                let mut rest = input.map_or(0.0, |input| input[i].to_f64());
+                let sections = self.slope.sections();
+                let all_pass_sections = self.slope.all_pass_sections();
+                for (p, point) in self.points.iter().enumerate() {
+                    let mut band = cascade(&point.low[..sections], &mut state.low[p], rest);
+                    rest = cascade(&point.high[..sections], &mut state.high[p], rest);
+                    // Match the phase of the crossover points above this band
+                    for (q, above) in self.points.iter().enumerate().skip(p + 1) {
+                        let states = &mut state.all_pass[p * num_points + q];
+                        band = cascade(&above.all_pass[..all_pass_sections], states, band);
+                    }
+                    self.bands[p].channels[ch][i] = S::from_f64(band);
+                }
+                self.bands[num_points].channels[ch][i] = S::from_f64(rest);
+            }
+        }
+    }
+
+    /// Sum all bands into the buffer's output.
+    pub fn sum_into(&self, buffer: &mut Buffer<S>) {
+        let num_samples = self.bands[0].num_samples;
+        for ch in 0..buffer.num_output_channels() {
+            let output = buffer.output(ch);
+            let num_samples = output.len().min(num_samples);
+            output[..num_samples].fill(S::ZERO);
+            for band in &self.bands {
+                let Some(channel) = band.channels.get(ch) else {
+                    continue;
+                };
+                for (out, &sample) in output[..num_samples].iter_mut().zip(&channel[..num_samples]) {
+                    *out = *out + sample;
+                }
+            }
+        }
+    }
+
+    /// Linear magnitude response of one band at `freq` Hz, e.g. for drawing
+    /// the bands in a GUI.
+    pub fn band_magnitude_at(&self, band: usize, freq: f64) -> f64 {
+        let sections = self.slope.sections();
+        let magnitude = |coeffs: &[BiquadCoeffs]| {
+            coeffs[..sections]
+                .iter()
+                .map(|c| c.magnitude_at(freq, self.sample_rate))
+                .product::<f64>()
+        };
+        // All-pass compensation doesn't change the magnitude
+        let low = self.points.get(band).map_or(1.0, |point| magnitude(&point.low));
+        let high: f64 = self.points[..band.min(self.points.len())]
+            .iter()
+            .map(|point| magnitude(&point.high))
+            .product();
+        low * high
+    }
+
+    /// Clear band audio and filter state.
+    ///
+    /// Call from `Processor::set_active(true)`.
+    pub fn reset(&mut self) {
+        for band in &mut self.bands {
+            for channel in &mut band.channels {
+                channel.fill(S::ZERO);
+            }
+        }
+        for state in &mut self.channels {
+            state.low.fill(Default::default());
+            state.high.fill(Default::default());
+            state.all_pass.fill(Default::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    const SR: f64 = 48000.0;
+
+    /// Steady-state amplitude of the summed bands for a sine at `freq`
+    /// (a multiple of 4 Hz, so the measured half holds whole cycles).
+    fn summed_amplitude(crossover: &mut Crossover<f64>, freq: f64) -> f64 {
+        let len = SR as usize / 2;
+        let input: Vec<f64> = (0..len).map(|n| (TAU * freq * n as f64 / SR).sin()).collect();
+        let mut output = vec![0.0; len];
+        crossover.reset();
+        let mut buffer = Buffer::new([input.as_slice()], [output.as_mut_slice()], len);
+        crossover.split(&buffer);
+        crossover.sum_into(&mut buffer);
+        let (mut re, mut im) = (0.0, 0.0);
+        for (n, sample) in output.iter().enumerate().skip(len / 2) {
+            let phase = TAU * freq * n as f64 / SR;
+            re += sample * phase.cos();
+            im += sample * phase.sin();
+        }
+        2.0 * re.hypot(im) / (len - len / 2) as f64
+    }
+
+    #[test]
+    fn test_bands_sum_flat() {
+        for slope in [CrossoverSlope::Lr2, CrossoverSlope::Lr4, CrossoverSlope::Lr8] {
+            let mut crossover = Crossover::<f64>::new(&[100.0, 500.0, 2000.0, 8000.0], slope, SR, 1, SR as usize / 2);
+            assert_eq!(crossover.num_bands(), 5);
+            for freq in [60.0, 100.0, 300.0, 500.0, 2000.0, 5000.0, 12000.0] {
+                let amplitude = summed_amplitude(&mut crossover, freq);
+                assert!((amplitude - 1.0).abs() < 1e-3, "{:?} at {} Hz: {}", slope, freq, amplitude);
+            }
+        }
+    }
+
+    #[test]
+    fn test_band_responses() {
+        let mut crossover = Crossover::<f32>::new(&[1000.0], CrossoverSlope::Lr4, SR, 2, 64);
+        for band in 0..2 {
+            // Linkwitz-Riley bands cross at -6 dB
+            let db = 20.0 * crossover.band_magnitude_at(band, 1000.0).log10();
+            assert!((db + 6.0206).abs() < 0.01);
+        }
+        assert!(crossover.band_magnitude_at(0, 100.0) > 0.999);
+        assert!(crossover.band_magnitude_at(1, 100.0) < 1e-3);
+
+        let input = vec![1.0f32; 64];
+        let mut output = vec![0.0f32; 64];
+        let buffer = Buffer::new([input.as_slice(), input.as_slice()], [output.as_mut_slice()], 64);
+        crossover.split(&buffer);
+        assert_eq!(crossover.band(1).num_samples(), 64);
+        assert_eq!(crossover.band(0).channel(1), crossover.band(0).channel(0));
+        // A step is low-frequency content: after it settles the high band decays
+        assert!(crossover.band(1).channel(0)[63].abs() < crossover.band(0).channel(0)[63].abs());
+
+        crossover.set_frequency(0, 2000.0);
+        assert_eq!(crossover.frequencies(), &[2000.0]);
+        crossover.set_slope(CrossoverSlope::Lr8);
+        assert_eq!(crossover.slope().db_per_octave(), 48);
+    }
+}
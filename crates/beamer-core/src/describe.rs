@@ -0,0 +1,522 @@
+//! Plugin metadata introspection for tooling.
+//!
+//! Every binary exported with `export_plugin!` (and therefore
+//! `#[beamer::export]`) carries a C-callable symbol:
+//!
+//! ```c
+//! const char *beamer_describe(void);
+//! ```
+//!
+//! It returns a NUL-terminated JSON document describing the plugin's config,
+//! parameters, groups, buses, and factory presets. xtask, installers, and CI
+//! can `dlopen` a built artifact and read it without instantiating the plugin
+//! in a host. The string is built on first call and lives for the lifetime of
+//! the loaded binary; callers must not free it.
+//!
+//! The document shape is versioned by [`DESCRIBE_SCHEMA_VERSION`]:
+//!
+//! ```json
+//! {
+//!   "schema": 1,
+//!   "beamer": "0.2.3",
+//!   "config": { "name": "...", "category": "effect", "manufacturer": "Bmer", ... },
+//!   "parameters": [{ "id": 0, "stringId": "gain", "name": "Gain", ... }],
+//!   "groups": [{ "id": 0, "name": "", "parentId": 0 }],
+//!   "buses": { "inputs": [...], "outputs": [...], "midi": false },
+//!   "presets": [{ "index": 0, "name": "Init" }]
+//! }
+//! ```
+//!
+//! Building the description only uses the unprepared [`Descriptor`] (created
+//! with `Default`), so no audio setup or host callbacks are involved.
+//!
+//! # Parameter Manual
+//!
+//! [`manual()`] renders the same document as a Markdown manual: one table
+//! per parameter group with ranges, defaults, units and flags, followed by
+//! the buses and factory presets. Binaries also export it:
+//!
+//! ```c
+//! const char *beamer_describe_markdown(void);
+//! ```
+//!
+//! A test in the plugin crate can keep a checked-in copy up to date:
+//!
+//! ```ignore
+//! #[test]
+//! fn parameter_manual() {
+//!     let manual = beamer::core::describe::manual::<MyDescriptor, MyPresets>(&CONFIG);
+//!     std::fs::write("PARAMETERS.md", manual).unwrap();
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use crate::config::{Category, Config, PluginFormat};
+use crate::parameter_groups::ParameterGroups;
+use crate::parameter_info::ParameterUnit;
+use crate::parameter_store::ParameterStore;
+use crate::plugin::{BusInfo, BusType, Descriptor};
+use crate::preset::FactoryPresets;
+
+/// Version of the JSON document returned by `beamer_describe()`.
+///
+/// Bumped when fields are removed or change meaning. Adding fields does not
+/// bump it.
+pub const DESCRIBE_SCHEMA_VERSION: u32 = 1;
+
+/// Most choices listed for a stepped list parameter.
+const MAX_CHOICES: i32 = 128;
+
+/// Build the `beamer_describe()` JSON document for a plugin.
+///
+/// Called by `export_plugin!`; tooling normally reads the exported symbol
+/// instead of calling this directly.
+pub fn describe<D, P>(config: &Config) -> String
+where
+    D: Descriptor,
+    P: FactoryPresets<Parameters = D::Parameters>,
+{
+    description::<D, P>(config).to_string()
+}
+
+/// Build the Markdown parameter manual for a plugin.
+///
+/// Renders the `beamer_describe()` document with [`manual_markdown()`].
+pub fn manual<D, P>(config: &Config) -> String
+where
+    D: Descriptor,
+    P: FactoryPresets<Parameters = D::Parameters>,
+{
+    manual_markdown(&description::<D, P>(config))
+}
+
+fn description<D, P>(config: &Config) -> serde_json::Value
+where
+    D: Descriptor,
+    P: FactoryPresets<Parameters = D::Parameters>,
+{
+    let descriptor = D::default();
+    let inputs: Vec<BusInfo> = (0..descriptor.input_bus_count())
+        .filter_map(|i| descriptor.input_bus_info(i))
+        .collect();
+    let outputs: Vec<BusInfo> = (0..descriptor.output_bus_count())
+        .filter_map(|i| descriptor.output_bus_info(i))
+        .collect();
+    let presets: Vec<&'static str> = (0..P::count())
+        .filter_map(|i| P::info(i).map(|info| info.name))
+        .collect();
+
+    let parameters = descriptor.parameters();
+    describe_parts(
+        config,
+        parameters,
+        parameters,
+        &inputs,
+        &outputs,
+        descriptor.wants_midi(),
+        &presets,
+    )
+}
+
+fn describe_parts(
+    config: &Config,
+    store: &dyn ParameterStore,
+    groups: &dyn ParameterGroups,
+    inputs: &[BusInfo],
+    outputs: &[BusInfo],
+    wants_midi: bool,
+    presets: &[&str],
+) -> serde_json::Value {
+    let parameters: Vec<serde_json::Value> = (0..store.count())
+        .filter_map(|i| {
+            let info = store.info(i)?;
+            let default = info.default_normalized;
+            let format = store.formatter_kind(info.id);
+            // Name every step of lists and switches
+            let choices = ((info.flags.is_list || format == "boolean")
+                && (1..=MAX_CHOICES).contains(&info.step_count))
+                .then(|| {
+                    (0..=info.step_count)
+                        .map(|step| {
+                            store.normalized_to_string(info.id, step as f64 / info.step_count as f64)
+                        })
+                        .collect::<Vec<_>>()
+                });
+            Some(serde_json::json!({
+                "id": info.id,
+                "stringId": info.string_id,
+                "name": info.name,
+                "shortName": info.short_name,
+                "units": info.units,
+                "unit": unit_name(info.unit),
+                "min": store.normalized_to_plain(info.id, 0.0),
+                "max": store.normalized_to_plain(info.id, 1.0),
+                "minDisplayText": store.normalized_to_string(info.id, 0.0),
+                "maxDisplayText": store.normalized_to_string(info.id, 1.0),
+                "defaultValue": default,
+                "defaultPlainValue": store.normalized_to_plain(info.id, default),
+                "defaultDisplayText": store.normalized_to_string(info.id, default),
+                "steps": info.step_count,
+                "format": format,
+                "choices": choices,
+                "groupId": info.group_id,
+                "automatable": info.flags.can_automate,
+                "readonly": info.flags.is_readonly,
+                "bypass": info.flags.is_bypass,
+                "list": info.flags.is_list,
+                "hidden": info.flags.is_hidden,
+                "persistent": info.flags.is_persistent,
+                "internal": info.flags.is_internal,
+            }))
+        })
+        .collect();
+
+    let groups: Vec<serde_json::Value> = (0..groups.group_count())
+        .filter_map(|i| groups.group_info(i))
+        .map(|group| {
+            serde_json::json!({
+                "id": group.id,
+                "name": group.name,
+                "parentId": group.parent_id,
+            })
+        })
+        .collect();
+
+    let bus = |bus: &BusInfo| {
+        serde_json::json!({
+            "name": bus.name,
+            "type": match bus.bus_type {
+                BusType::Main => "main",
+                BusType::Aux => "aux",
+            },
+            "channels": bus.channel_count,
+            "defaultActive": bus.is_default_active,
+        })
+    };
+
+    let presets: Vec<serde_json::Value> = presets
+        .iter()
+        .enumerate()
+        .map(|(index, name)| serde_json::json!({ "index": index, "name": name }))
+        .collect();
+
+    // Format-specific fields come from the configs the wrappers use
+    let au = config.for_format(PluginFormat::Au);
+    let vst3 = config.for_format(PluginFormat::Vst3);
+
+    serde_json::json!({
+        "schema": DESCRIBE_SCHEMA_VERSION,
+        "beamer": env!("CARGO_PKG_VERSION"),
+        "config": {
+            "name": config.name,
+            "category": category_name(config.category),
+            "vendor": config.vendor,
+            "url": config.url,
+            "email": config.email,
+            "version": config.version,
+            "manufacturer": config.manufacturer.as_str(),
+            "subtype": config.subtype.as_str(),
+            "auName": au.name,
+            "auComponentType": au.category.to_au_component_type(),
+            "auTags": au.au_tags(),
+            "vst3Name": vst3.name,
+            "vst3Uid": vst3.vst3_uid_parts(),
+            "vst3ControllerUid": vst3.vst3_controller_uid_parts(),
+            "vst3Category": vst3.category.to_vst3_category(),
+            "vst3Subcategories": vst3.vst3_subcategories(),
+            "hasGui": config.has_gui,
+            "guiWidth": config.gui_width,
+            "guiHeight": config.gui_height,
+        },
+        "parameters": parameters,
+        "groups": groups,
+        "buses": {
+            "inputs": inputs.iter().map(bus).collect::<Vec<_>>(),
+            "outputs": outputs.iter().map(bus).collect::<Vec<_>>(),
+            "midi": wants_midi,
+        },
+        "presets": presets,
+    })
+}
+
+/// Render a `beamer_describe()` document as a Markdown parameter manual.
+///
+/// Parameters of the root group come first, then one section per group
+/// (headed with its path, e.g. "Filter / Envelope") in declaration order.
+pub fn manual_markdown(description: &serde_json::Value) -> String {
+    let config = &description["config"];
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# {}\n", text(&config["name"]));
+    let facts: Vec<String> = [
+        text(&config["vendor"]).to_string(),
+        match text(&config["version"]) {
+            "" => String::new(),
+            version => format!("Version {version}"),
+        },
+        text(&config["category"]).replace('_', " "),
+    ]
+    .into_iter()
+    .filter(|fact| !fact.is_empty())
+    .collect();
+    if !facts.is_empty() {
+        let _ = writeln!(out, "{}\n", facts.join(" · "));
+    }
+
+    let parameters = list(&description["parameters"]);
+    let groups = list(&description["groups"]);
+    let section_ids = std::iter::once(0).chain(
+        groups
+            .iter()
+            .filter_map(|group| group["id"].as_i64())
+            .filter(|&id| id != 0),
+    );
+    for group_id in section_ids {
+        let members: Vec<&serde_json::Value> = parameters
+            .iter()
+            .filter(|p| p["groupId"].as_i64().unwrap_or(0) == group_id)
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        let heading = if group_id == 0 {
+            "Parameters".to_string()
+        } else {
+            group_path(groups, group_id)
+        };
+        let _ = writeln!(out, "## {heading}\n");
+        out.push_str("| Parameter | ID | Range | Default | Notes |\n");
+        out.push_str("|-----------|----|-------|---------|-------|\n");
+        for parameter in members {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {} | {} | {} |",
+                cell(text(&parameter["name"])),
+                text(&parameter["stringId"]),
+                cell(&range_text(parameter)),
+                cell(&with_units(text(&parameter["defaultDisplayText"]), parameter)),
+                cell(&notes(parameter).join(", ")),
+            );
+        }
+        out.push('\n');
+    }
+
+    let buses = &description["buses"];
+    let bus_rows: Vec<String> = [("input", &buses["inputs"]), ("output", &buses["outputs"])]
+        .into_iter()
+        .flat_map(|(direction, buses)| {
+            list(buses).iter().map(move |bus| {
+                let optional = if bus["defaultActive"].as_bool() == Some(false) {
+                    " (inactive by default)"
+                } else {
+                    ""
+                };
+                format!(
+                    "| {} | {} {direction}{optional} | {} |",
+                    cell(text(&bus["name"])),
+                    text(&bus["type"]),
+                    bus["channels"],
+                )
+            })
+        })
+        .collect();
+    if !bus_rows.is_empty() || buses["midi"].as_bool() == Some(true) {
+        out.push_str("## Buses\n\n");
+        if !bus_rows.is_empty() {
+            out.push_str("| Bus | Type | Channels |\n|-----|------|----------|\n");
+            for row in bus_rows {
+                let _ = writeln!(out, "{row}");
+            }
+            out.push('\n');
+        }
+        if buses["midi"].as_bool() == Some(true) {
+            out.push_str("Processes MIDI.\n\n");
+        }
+    }
+
+    let presets = list(&description["presets"]);
+    if !presets.is_empty() {
+        out.push_str("## Factory Presets\n\n| # | Name |\n|---|------|\n");
+        for preset in presets {
+            let _ = writeln!(out, "| {} | {} |", preset["index"], cell(text(&preset["name"])));
+        }
+        out.push('\n');
+    }
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn text(value: &serde_json::Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
+
+fn list(value: &serde_json::Value) -> &[serde_json::Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Escape a Markdown table cell.
+fn cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+fn with_units(value: &str, parameter: &serde_json::Value) -> String {
+    match text(&parameter["units"]) {
+        "" => value.to_string(),
+        units => format!("{value} {units}"),
+    }
+}
+
+fn range_text(parameter: &serde_json::Value) -> String {
+    if let Some(choices) = parameter["choices"].as_array() {
+        return choices.iter().map(text).collect::<Vec<_>>().join(" / ");
+    }
+    let range = format!(
+        "{} to {}",
+        text(&parameter["minDisplayText"]),
+        text(&parameter["maxDisplayText"]),
+    );
+    let range = with_units(&range, parameter);
+    match parameter["steps"].as_i64() {
+        Some(steps) if steps > 0 => format!("{range} ({} steps)", steps + 1),
+        _ => range,
+    }
+}
+
+fn notes(parameter: &serde_json::Value) -> Vec<&'static str> {
+    let flag = |name: &str| parameter[name].as_bool() == Some(true);
+    let mut notes = Vec::new();
+    if flag("bypass") {
+        notes.push("bypass");
+    }
+    if !flag("automatable") {
+        notes.push("not automatable");
+    }
+    if flag("readonly") {
+        notes.push("read-only");
+    }
+    if flag("internal") {
+        notes.push("GUI only");
+    }
+    if flag("hidden") {
+        notes.push("hidden");
+    }
+    if !flag("persistent") {
+        notes.push("not saved");
+    }
+    notes
+}
+
+/// Group names from the top level down, e.g. "Filter / Envelope".
+fn group_path(groups: &[serde_json::Value], id: i64) -> String {
+    let mut names = Vec::new();
+    let mut current = id;
+    // Bounded walk, in case of a parent cycle
+    for _ in 0..groups.len() {
+        let Some(group) = groups.iter().find(|g| g["id"].as_i64() == Some(current)) else {
+            break;
+        };
+        names.push(text(&group["name"]));
+        current = group["parentId"].as_i64().unwrap_or(0);
+        if current == 0 {
+            break;
+        }
+    }
+    names.reverse();
+    names.join(" / ")
+}
+
+fn category_name(category: Category) -> &'static str {
+    match category {
+        Category::Effect => "effect",
+        Category::Instrument => "instrument",
+        Category::MidiEffect => "midi_effect",
+        Category::Generator => "generator",
+    }
+}
+
+fn unit_name(unit: ParameterUnit) -> String {
+    format!("{unit:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameter_store::NoParameters;
+
+    #[test]
+    fn test_describe_parts() {
+        let config = Config::new("Test Gain", Category::Effect, "Bmer", "tgan")
+            .with_vendor("Beamer")
+            .with_version("1.2.3");
+        let value = describe_parts(
+            &config,
+            &NoParameters,
+            &NoParameters,
+            &[BusInfo::stereo("Input"), BusInfo {
+                name: "Sidechain",
+                bus_type: BusType::Aux,
+                channel_count: 1,
+                is_default_active: false,
+                speaker_layout: None,
+            }],
+            &[BusInfo::stereo("Output")],
+            true,
+            &["Init", "Loud"],
+        );
+
+        assert_eq!(value["schema"], DESCRIBE_SCHEMA_VERSION);
+        assert_eq!(value["config"]["name"], "Test Gain");
+        assert_eq!(value["config"]["category"], "effect");
+        assert_eq!(value["config"]["manufacturer"], "Bmer");
+        assert_eq!(value["config"]["subtype"], "tgan");
+        assert_eq!(value["config"]["version"], "1.2.3");
+        assert_eq!(value["parameters"], serde_json::json!([]));
+        assert_eq!(value["groups"][0]["id"], 0);
+        assert_eq!(value["buses"]["inputs"][1]["type"], "aux");
+        assert_eq!(value["buses"]["inputs"][1]["channels"], 1);
+        assert_eq!(value["buses"]["outputs"][0]["name"], "Output");
+        assert_eq!(value["buses"]["midi"], true);
+        assert_eq!(value["presets"][1], serde_json::json!({ "index": 1, "name": "Loud" }));
+    }
+
+    #[test]
+    fn test_manual_markdown() {
+        let description = serde_json::json!({
+            "config": { "name": "Test Synth", "vendor": "Beamer", "version": "1.0.0", "category": "instrument" },
+            "parameters": [
+                {
+                    "stringId": "gain", "name": "Gain", "units": "dB", "groupId": 0,
+                    "minDisplayText": "-60.0", "maxDisplayText": "12.0", "defaultDisplayText": "0.0",
+                    "steps": 0, "choices": null, "automatable": true, "persistent": true,
+                },
+                {
+                    "stringId": "env_mode", "name": "Mode", "units": "", "groupId": 2,
+                    "defaultDisplayText": "Fast", "steps": 1, "choices": ["Fast", "Slow"],
+                    "automatable": false, "persistent": true,
+                },
+            ],
+            "groups": [
+                { "id": 0, "name": "", "parentId": 0 },
+                { "id": 1, "name": "Filter", "parentId": 0 },
+                { "id": 2, "name": "Envelope", "parentId": 1 },
+            ],
+            "buses": { "inputs": [], "outputs": [{ "name": "Output", "type": "main", "channels": 2, "defaultActive": true }], "midi": true },
+            "presets": [{ "index": 0, "name": "Init" }],
+        });
+        let manual = manual_markdown(&description);
+
+        assert!(manual.starts_with("# Test Synth\n\nBeamer · Version 1.0.0 · instrument\n"));
+        assert!(manual.contains("## Parameters\n"));
+        assert!(manual.contains("| Gain | `gain` | -60.0 to 12.0 dB | 0.0 dB |  |"));
+        // Filter has no parameters of its own
+        assert!(!manual.contains("## Filter\n"));
+        assert!(manual.contains("## Filter / Envelope\n"));
+        assert!(manual.contains("| Mode | `env_mode` | Fast / Slow | Fast | not automatable |"));
+        assert!(manual.contains("| Output | main output | 2 |"));
+        assert!(manual.contains("Processes MIDI."));
+        assert!(manual.contains("| 0 | Init |"));
+    }
+}
@@ -0,0 +1,738 @@
+//! Disk streaming for sample libraries that don't fit in memory.
+//!
+//! Sampler plugins keep the first part of every sample (the head) in RAM so
+//! a note can start at once, and stream the rest (the tail) from disk while
+//! the head plays. This module provides the pieces:
+//!
+//! - [`DiskStreamer`] - Sample registry and the IO thread that reads tails
+//! - [`StreamedSample`] - A registered sample with its preloaded head
+//! - [`StreamVoice`] - Audio-thread playback of one sample at a time
+//! - [`StreamSource`] - Where sample frames come from ([`RawF32File`], or a
+//!   decoder of your own)
+//!
+//! Each voice owns a ring buffer that the IO thread fills ahead of playback.
+//! Starting, reading and stopping a voice only touch atomics, so they are
+//! real-time safe. The IO thread always serves the voice with the least
+//! audio buffered first, so voices close to running dry win over voices
+//! that just started. It sleeps while every ring is full, and voices wake
+//! it when they start or have room for another chunk.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Descriptor (main thread)
+//! let streamer = DiskStreamer::new(DiskStreamConfig::default());
+//! let piano_c4 = streamer.add(RawF32File::open("c4.f32", 2, 0)?)?;
+//!
+//! // Descriptor::prepare(): one voice per note of polyphony
+//! let voices: Vec<StreamVoice> = (0..32).map(|_| streamer.voice()).collect();
+//!
+//! // Processor::process_midi(): note on
+//! self.voices[free].start(&self.piano_c4);
+//!
+//! // Processor::process()
+//! let frames = voice.read(&mut outputs);
+//! if frames < num_samples { /* sample ended */ }
+//! ```
+//!
+//! If the disk falls behind, the missing frames are output as silence and
+//! playback continues on time ([`DiskStreamer::underruns()`] counts them).
+//! Voices play at the sample's own rate; pitch shifting and resampling are
+//! up to the plugin.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::thread::Thread;
+
+/// Bit 63 of a voice request: the voice is playing.
+const ACTIVE: u64 = 1 << 63;
+
+/// Positions are stored with the voice generation in the top 16 bits, so
+/// data written for a previous note is never read for the next one.
+const GENERATION_SHIFT: u32 = 48;
+const POSITION_MASK: u64 = (1 << GENERATION_SHIFT) - 1;
+
+fn pack(generation: u16, position: u64) -> u64 {
+    ((generation as u64) << GENERATION_SHIFT) | (position & POSITION_MASK)
+}
+
+/// Position for `generation`, or 0 if the value belongs to another one.
+fn unpack(value: u64, generation: u16) -> u64 {
+    if (value >> GENERATION_SHIFT) as u16 == generation {
+        value & POSITION_MASK
+    } else {
+        0
+    }
+}
+
+// =============================================================================
+// Sources
+// =============================================================================
+
+/// Source of interleaved `f32` sample frames.
+///
+/// Implemented by [`RawF32File`]; implement it for your own file format to
+/// stream decoded audio. Only the IO thread (and [`DiskStreamer::add()`])
+/// reads from a source.
+pub trait StreamSource: Send + 'static {
+    /// Number of interleaved channels.
+    fn channels(&self) -> usize;
+
+    /// Length in frames.
+    fn frames(&self) -> u64;
+
+    /// Read interleaved frames starting at `frame` into `out`.
+    ///
+    /// Returns the number of frames read, which is less than requested only
+    /// at the end of the source.
+    fn read(&mut self, frame: u64, out: &mut [f32]) -> io::Result<usize>;
+}
+
+/// A file of raw little-endian `f32` frames, e.g. the data chunk of a
+/// 32-bit float WAV file.
+pub struct RawF32File {
+    file: File,
+    channels: usize,
+    frames: u64,
+    data_offset: u64,
+    bytes: Vec<u8>,
+}
+
+impl RawF32File {
+    /// Open a file whose sample data starts `data_offset` bytes in.
+    pub fn open(path: impl AsRef<Path>, channels: usize, data_offset: u64) -> io::Result<Self> {
+        if channels == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "zero channels"));
+        }
+        let file = File::open(path)?;
+        let data_len = file.metadata()?.len().saturating_sub(data_offset);
+        Ok(Self {
+            file,
+            channels,
+            frames: data_len / (channels as u64 * 4),
+            data_offset,
+            bytes: Vec::new(),
+        })
+    }
+}
+
+impl StreamSource for RawF32File {
+    fn channels(&self) -> usize {
+        self.channels
+    }
+
+    fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    fn read(&mut self, frame: u64, out: &mut [f32]) -> io::Result<usize> {
+        let frames = ((out.len() / self.channels) as u64).min(self.frames.saturating_sub(frame)) as usize;
+        let samples = frames * self.channels;
+        self.bytes.resize(samples * 4, 0);
+        self.file.seek(SeekFrom::Start(self.data_offset + frame * self.channels as u64 * 4))?;
+        self.file.read_exact(&mut self.bytes)?;
+        for (sample, bytes) in out[..samples].iter_mut().zip(self.bytes.chunks_exact(4)) {
+            *sample = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        Ok(frames)
+    }
+}
+
+// =============================================================================
+// Streamer
+// =============================================================================
+
+/// Buffer sizes of a [`DiskStreamer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiskStreamConfig {
+    /// Frames of each sample kept in RAM. Must cover the disk latency.
+    pub preload_frames: usize,
+    /// Frames buffered ahead per voice.
+    pub ring_frames: usize,
+    /// Frames read from disk at a time.
+    pub chunk_frames: usize,
+    /// Most channels a sample may have.
+    pub max_channels: usize,
+}
+
+impl Default for DiskStreamConfig {
+    fn default() -> Self {
+        Self {
+            preload_frames: 32768,
+            ring_frames: 65536,
+            chunk_frames: 4096,
+            max_channels: 2,
+        }
+    }
+}
+
+impl DiskStreamConfig {
+    /// Set the frames kept in RAM per sample.
+    pub fn with_preload_frames(mut self, frames: usize) -> Self {
+        self.preload_frames = frames;
+        self
+    }
+
+    /// Set the frames buffered ahead per voice.
+    pub fn with_ring_frames(mut self, frames: usize) -> Self {
+        self.ring_frames = frames.max(1);
+        self
+    }
+
+    /// Set the frames read from disk at a time.
+    pub fn with_chunk_frames(mut self, frames: usize) -> Self {
+        self.chunk_frames = frames.max(1);
+        self
+    }
+
+    /// Set the most channels a sample may have.
+    pub fn with_max_channels(mut self, channels: usize) -> Self {
+        self.max_channels = channels.max(1);
+        self
+    }
+}
+
+/// A sample registered with [`DiskStreamer::add()`].
+///
+/// Cheap to clone; hand clones to the processor so voices can start it.
+#[derive(Clone)]
+pub struct StreamedSample {
+    id: u32,
+    channels: usize,
+    frames: u64,
+    head: Arc<[f32]>,
+}
+
+impl StreamedSample {
+    /// Number of channels.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Length in frames.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Frames held in RAM.
+    pub fn head_frames(&self) -> usize {
+        self.head.len() / self.channels
+    }
+}
+
+/// A registered source. The registry lock is only held to pick a source;
+/// reading locks the source alone, so registering samples doesn't wait for
+/// the disk.
+#[derive(Clone)]
+struct SourceEntry {
+    source: Arc<Mutex<Box<dyn StreamSource>>>,
+    frames: u64,
+    channels: usize,
+    head_frames: u64,
+}
+
+struct Inner {
+    config: DiskStreamConfig,
+    sources: Mutex<Vec<SourceEntry>>,
+    voices: Mutex<Vec<Arc<VoiceChannel>>>,
+    started: AtomicBool,
+    wakeup: Arc<Wakeup>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Let the IO thread notice that the streamer is gone
+        if let Some(thread) = self.wakeup.thread.get() {
+            thread.unpark();
+        }
+    }
+}
+
+/// Wakes the IO thread when it sleeps.
+#[derive(Default)]
+struct Wakeup {
+    thread: OnceLock<Thread>,
+    /// Set by the IO thread before its last look for work ahead of a sleep.
+    idle: AtomicBool,
+}
+
+impl Wakeup {
+    /// Wake the IO thread if it is (about to go) asleep. Real-time safe: a
+    /// store and, at most once per sleep, an unpark.
+    fn wake(&self) {
+        // Pairs with the fence in the IO thread: either it sees the voice's
+        // update in its scan, or this sees it idle
+        fence(Ordering::SeqCst);
+        if self.idle.load(Ordering::Relaxed) && self.idle.swap(false, Ordering::AcqRel) {
+            if let Some(thread) = self.thread.get() {
+                thread.unpark();
+            }
+        }
+    }
+}
+
+/// Sample registry and IO thread for disk streaming.
+///
+/// Cheap to clone. The IO thread starts with the first voice and ends when
+/// the last clone is dropped.
+#[derive(Clone)]
+pub struct DiskStreamer {
+    inner: Arc<Inner>,
+}
+
+impl DiskStreamer {
+    /// Create a streamer.
+    pub fn new(config: DiskStreamConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                config,
+                sources: Mutex::new(Vec::new()),
+                voices: Mutex::new(Vec::new()),
+                started: AtomicBool::new(false),
+                wakeup: Arc::default(),
+            }),
+        }
+    }
+
+    /// Register a sample, reading its head into RAM.
+    ///
+    /// Blocks while the head is read; call it from a loader thread for large
+    /// libraries.
+    pub fn add(&self, mut source: impl StreamSource) -> io::Result<StreamedSample> {
+        let channels = source.channels();
+        if channels == 0 || channels > self.inner.config.max_channels {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} channels (1 to {} supported)", channels, self.inner.config.max_channels),
+            ));
+        }
+        let frames = source.frames();
+        let head_frames = frames.min(self.inner.config.preload_frames as u64) as usize;
+        let mut head = vec![0.0; head_frames * channels];
+        let mut read = 0;
+        while read < head_frames {
+            let n = source.read(read as u64, &mut head[read * channels..])?;
+            if n == 0 {
+                break;
+            }
+            read += n;
+        }
+
+        let mut sources = self.inner.sources.lock().unwrap_or_else(|e| e.into_inner());
+        let id = sources.len() as u32;
+        sources.push(SourceEntry {
+            source: Arc::new(Mutex::new(Box::new(source))),
+            frames,
+            channels,
+            head_frames: head_frames as u64,
+        });
+        Ok(StreamedSample { id, channels, frames, head: head.into() })
+    }
+
+    /// Create a voice. Call this outside the audio thread, e.g. in
+    /// `prepare()`; the voice's ring buffer is allocated here.
+    pub fn voice(&self) -> StreamVoice {
+        let config = &self.inner.config;
+        let channel = Arc::new(VoiceChannel {
+            request: AtomicU64::new(0),
+            written: AtomicU64::new(0),
+            consumed: AtomicU64::new(0),
+            underruns: AtomicUsize::new(0),
+            ring: (0..config.ring_frames * config.max_channels).map(|_| AtomicU32::new(0)).collect(),
+        });
+        self.inner.voices.lock().unwrap_or_else(|e| e.into_inner()).push(Arc::clone(&channel));
+        if !self.inner.started.swap(true, Ordering::AcqRel) {
+            spawn_io_thread(Arc::downgrade(&self.inner));
+        }
+        StreamVoice {
+            channel,
+            wakeup: Arc::clone(&self.inner.wakeup),
+            ring_frames: config.ring_frames as u64,
+            wake_frames: config.chunk_frames.min(config.ring_frames / 2).max(1) as u64,
+            max_channels: config.max_channels,
+            sample: None,
+            generation: 0,
+            position: 0,
+        }
+    }
+
+    /// Frames output as silence because the disk fell behind, over all voices.
+    pub fn underruns(&self) -> usize {
+        let voices = self.inner.voices.lock().unwrap_or_else(|e| e.into_inner());
+        voices.iter().map(|v| v.underruns.load(Ordering::Relaxed)).sum()
+    }
+}
+
+// =============================================================================
+// IO Thread
+// =============================================================================
+
+/// State shared between a [`StreamVoice`] and the IO thread.
+struct VoiceChannel {
+    /// `ACTIVE | generation << 32 | sample id`, written by the voice.
+    request: AtomicU64,
+    /// Tail frames written, packed with the generation (IO thread).
+    written: AtomicU64,
+    /// Tail frames consumed, packed with the generation (voice).
+    consumed: AtomicU64,
+    underruns: AtomicUsize,
+    /// Interleaved `f32` bits, `ring_frames * max_channels`.
+    ring: Box<[AtomicU32]>,
+}
+
+fn spawn_io_thread(weak: Weak<Inner>) {
+    let Some(wakeup) = weak.upgrade().map(|inner| Arc::clone(&inner.wakeup)) else {
+        return;
+    };
+    let spawned = std::thread::Builder::new()
+        .name("beamer-disk-stream".into())
+        .spawn(move || {
+            let mut scratch = Vec::new();
+            loop {
+                let Some(inner) = weak.upgrade() else { return };
+                if fill_most_urgent(&inner, &mut scratch) {
+                    continue;
+                }
+                // Announce the sleep, then look once more: a voice changing
+                // after this scan sees the flag and unparks the park() below
+                inner.wakeup.idle.store(true, Ordering::Relaxed);
+                fence(Ordering::SeqCst);
+                if fill_most_urgent(&inner, &mut scratch) {
+                    inner.wakeup.idle.store(false, Ordering::Relaxed);
+                    continue;
+                }
+                drop(inner);
+                std::thread::park();
+            }
+        });
+    match spawned {
+        Ok(handle) => {
+            let _ = wakeup.thread.set(handle.thread().clone());
+        }
+        Err(e) => log::warn!("Disk streaming thread not started: {e}"),
+    }
+}
+
+/// Read one chunk for the voice with the least audio buffered. Returns false
+/// if no voice needed data.
+fn fill_most_urgent(inner: &Inner, scratch: &mut Vec<f32>) -> bool {
+    let config = &inner.config;
+    let ring_frames = config.ring_frames as u64;
+
+    // (voice, generation, sample id, source, write position, frames to read)
+    let mut most_urgent: Option<(Arc<VoiceChannel>, u16, u32, SourceEntry, u64, u64)> = None;
+    let mut least_buffered = u64::MAX;
+    {
+        let sources = inner.sources.lock().unwrap_or_else(|e| e.into_inner());
+        let mut voices = inner.voices.lock().unwrap_or_else(|e| e.into_inner());
+        // Forget voices the plugin dropped.
+        voices.retain(|voice| Arc::strong_count(voice) > 1);
+        for voice in voices.iter() {
+            let request = voice.request.load(Ordering::Acquire);
+            if request & ACTIVE == 0 {
+                continue;
+            }
+            let generation = (request >> 32) as u16;
+            let id = request as u32;
+            let Some(entry) = sources.get(id as usize) else { continue };
+            let tail_frames = entry.frames.saturating_sub(entry.head_frames);
+            let consumed = unpack(voice.consumed.load(Ordering::Acquire), generation);
+            // Skip frames the voice already passed during an underrun.
+            let written = unpack(voice.written.load(Ordering::Acquire), generation).max(consumed);
+            let buffered = written - consumed;
+            let frames = (ring_frames - buffered)
+                .min(config.chunk_frames as u64)
+                .min(tail_frames.saturating_sub(written));
+            if frames > 0 && buffered < least_buffered {
+                least_buffered = buffered;
+                most_urgent = Some((Arc::clone(voice), generation, id, entry.clone(), written, frames));
+            }
+        }
+    }
+    let Some((voice, generation, id, entry, written, frames)) = most_urgent else {
+        return false;
+    };
+
+    // Read without holding the registry
+    let channels = entry.channels;
+    scratch.resize(frames as usize * channels, 0.0);
+    let mut source = entry.source.lock().unwrap_or_else(|e| e.into_inner());
+    let read = match source.read(entry.head_frames + written, scratch) {
+        Ok(read) => read as u64,
+        Err(e) => {
+            log::warn!("Disk streaming read failed: {e}");
+            0
+        }
+    };
+    if read < frames {
+        // Read errors and short files play out as silence.
+        scratch[read as usize * channels..].fill(0.0);
+    }
+    drop(source);
+
+    for frame in 0..frames {
+        let slot = ((written + frame) % ring_frames) as usize * config.max_channels;
+        for ch in 0..channels {
+            voice.ring[slot + ch].store(scratch[frame as usize * channels + ch].to_bits(), Ordering::Relaxed);
+        }
+    }
+    // Publish only if the voice is still playing the same note.
+    if voice.request.load(Ordering::Acquire) == (ACTIVE | (generation as u64) << 32 | id as u64) {
+        voice.written.store(pack(generation, written + frames), Ordering::Release);
+    }
+    true
+}
+
+// =============================================================================
+// Voice
+// =============================================================================
+
+/// Audio-thread playback of streamed samples, one at a time.
+///
+/// Created with [`DiskStreamer::voice()`]. All methods are real-time safe.
+pub struct StreamVoice {
+    channel: Arc<VoiceChannel>,
+    wakeup: Arc<Wakeup>,
+    ring_frames: u64,
+    /// Free ring frames at which `read()` wakes the IO thread.
+    wake_frames: u64,
+    max_channels: usize,
+    sample: Option<StreamedSample>,
+    generation: u16,
+    position: u64,
+}
+
+impl StreamVoice {
+    /// Start playing `sample` from the beginning, replacing the current one.
+    pub fn start(&mut self, sample: &StreamedSample) {
+        self.generation = self.generation.wrapping_add(1);
+        self.position = 0;
+        self.sample = Some(sample.clone());
+        self.channel.consumed.store(pack(self.generation, 0), Ordering::Release);
+        self.channel.request.store(
+            ACTIVE | (self.generation as u64) << 32 | sample.id as u64,
+            Ordering::Release,
+        );
+        self.wakeup.wake();
+    }
+
+    /// Stop playback.
+    pub fn stop(&mut self) {
+        self.channel.request.store(0, Ordering::Release);
+        self.sample = None;
+    }
+
+    /// Returns true while a sample is playing.
+    pub fn is_playing(&self) -> bool {
+        self.sample.is_some()
+    }
+
+    /// Playback position in frames.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Tail frames buffered ahead of the playback position.
+    pub fn buffered(&self) -> u64 {
+        let Some(sample) = &self.sample else { return 0 };
+        let tail_position = self.position.saturating_sub(sample.head_frames() as u64);
+        unpack(self.channel.written.load(Ordering::Acquire), self.generation).saturating_sub(tail_position)
+    }
+
+    /// Write the next frames into `outputs`, one slice per channel.
+    ///
+    /// Output channels beyond the sample's wrap around (a mono sample fills
+    /// both sides of a stereo output). Returns the frames written, fewer than
+    /// the output length when the sample ends; the voice then stops. Frames
+    /// the disk hasn't delivered yet are written as silence.
+    pub fn read(&mut self, outputs: &mut [&mut [f32]]) -> usize {
+        let Some(sample) = &self.sample else { return 0 };
+        let len = outputs.iter().map(|o| o.len()).min().unwrap_or(0);
+        let head_frames = sample.head_frames() as u64;
+        let frames = (len as u64).min(sample.frames - self.position) as usize;
+        let written = unpack(self.channel.written.load(Ordering::Acquire), self.generation);
+        let mut underruns = 0;
+
+        for i in 0..frames {
+            let position = self.position + i as u64;
+            for (ch, output) in outputs.iter_mut().enumerate() {
+                let src = ch % sample.channels;
+                output[i] = if position < head_frames {
+                    sample.head[position as usize * sample.channels + src]
+                } else if position - head_frames < written {
+                    let slot = ((position - head_frames) % self.ring_frames) as usize * self.max_channels;
+                    f32::from_bits(self.channel.ring[slot + src].load(Ordering::Relaxed))
+                } else {
+                    0.0
+                };
+            }
+            if position >= head_frames && position - head_frames >= written {
+                underruns += 1;
+            }
+        }
+
+        self.position += frames as u64;
+        if underruns > 0 {
+            self.channel.underruns.fetch_add(underruns, Ordering::Relaxed);
+        }
+        if self.position >= sample.frames {
+            self.stop();
+        } else {
+            let tail_position = self.position.saturating_sub(head_frames);
+            self.channel.consumed.store(pack(self.generation, tail_position), Ordering::Release);
+            // Wake the IO thread once the ring has room for another chunk
+            // of a tail that isn't fully read yet
+            let buffered = written.saturating_sub(tail_position);
+            let tail_frames = sample.frames - head_frames;
+            if written < tail_frames && self.ring_frames - buffered.min(self.ring_frames) >= self.wake_frames {
+                self.wakeup.wake();
+            }
+        }
+        frames
+    }
+}
+
+impl Drop for StreamVoice {
+    fn drop(&mut self) {
+        self.channel.request.store(0, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Stereo ramp: left is the frame index, right its negation.
+    struct Ramp(u64);
+
+    impl StreamSource for Ramp {
+        fn channels(&self) -> usize {
+            2
+        }
+
+        fn frames(&self) -> u64 {
+            self.0
+        }
+
+        fn read(&mut self, frame: u64, out: &mut [f32]) -> io::Result<usize> {
+            let frames = ((out.len() / 2) as u64).min(self.0 - frame) as usize;
+            for i in 0..frames {
+                out[i * 2] = (frame + i as u64) as f32;
+                out[i * 2 + 1] = -((frame + i as u64) as f32);
+            }
+            Ok(frames)
+        }
+    }
+
+    #[test]
+    fn test_streams_head_and_tail_in_order() {
+        let streamer = DiskStreamer::new(
+            DiskStreamConfig::default()
+                .with_preload_frames(1000)
+                .with_ring_frames(512)
+                .with_chunk_frames(100),
+        );
+        let sample = streamer.add(Ramp(10_000)).unwrap();
+        assert_eq!(sample.head_frames(), 1000);
+
+        let mut voice = streamer.voice();
+        voice.start(&sample);
+        let (mut left, mut right) = (vec![0.0; 37], vec![0.0; 37]);
+        let mut expected = 0u64;
+        while voice.is_playing() {
+            // Wait for the disk, like a host with a generous preload would.
+            let position = voice.position();
+            while voice.buffered() < (position + 37).min(10_000).saturating_sub(position.max(1000)) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            let frames = voice.read(&mut [&mut left[..], &mut right[..]]);
+            for i in 0..frames {
+                assert_eq!(left[i], expected as f32);
+                assert_eq!(right[i], -(expected as f32));
+                expected += 1;
+            }
+        }
+        assert_eq!(expected, 10_000);
+        assert_eq!(streamer.underruns(), 0);
+    }
+
+    /// Ramp whose tail reads wait until the test opens the gate.
+    struct Gated {
+        ramp: Ramp,
+        head_frames: u64,
+        reading: std::sync::mpsc::Sender<()>,
+        /// Each message lets one read through; dropping the sender opens it.
+        gate: std::sync::mpsc::Receiver<()>,
+    }
+
+    impl StreamSource for Gated {
+        fn channels(&self) -> usize {
+            2
+        }
+
+        fn frames(&self) -> u64 {
+            self.ramp.0
+        }
+
+        fn read(&mut self, frame: u64, out: &mut [f32]) -> io::Result<usize> {
+            if frame >= self.head_frames {
+                let _ = self.reading.send(());
+                let _ = self.gate.recv_timeout(Duration::from_secs(5));
+            }
+            self.ramp.read(frame, out)
+        }
+    }
+
+    #[test]
+    fn test_slow_reads_dont_block_registration() {
+        let streamer = DiskStreamer::new(DiskStreamConfig::default().with_preload_frames(100));
+        let (reading_tx, reading) = std::sync::mpsc::channel();
+        let (gate, gate_rx) = std::sync::mpsc::channel();
+        let slow = Gated { ramp: Ramp(10_000), head_frames: 100, reading: reading_tx, gate: gate_rx };
+        let sample = streamer.add(slow).unwrap();
+
+        // Let the IO thread go to sleep first: starting the voice wakes it
+        let mut voice = streamer.voice();
+        std::thread::sleep(Duration::from_millis(20));
+        voice.start(&sample);
+        reading.recv_timeout(Duration::from_secs(5)).expect("IO thread not woken");
+
+        // The IO thread is stuck in the read; registering another sample
+        // still goes through
+        let (added_tx, added) = std::sync::mpsc::channel();
+        let registrar = streamer.clone();
+        std::thread::spawn(move || {
+            let _ = added_tx.send(registrar.add(Ramp(10)).is_ok());
+        });
+        assert_eq!(added.recv_timeout(Duration::from_secs(2)), Ok(true));
+
+        gate.send(()).unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while voice.buffered() == 0 {
+            assert!(std::time::Instant::now() < deadline, "tail not delivered");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_underrun_outputs_silence_and_keeps_time() {
+        let streamer = DiskStreamer::new(DiskStreamConfig::default().with_preload_frames(4));
+        let sample = streamer.add(Ramp(1_000_000)).unwrap();
+        let mut voice = streamer.voice();
+        voice.start(&sample);
+
+        // Read before the IO thread could have caught up with a large block.
+        let mut mono = vec![1.0; 8192];
+        assert_eq!(voice.read(&mut [&mut mono[..]]), 8192);
+        assert_eq!(&mono[..4], &[0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(voice.position(), 8192);
+        assert!(streamer.add(Ramp(10)).is_ok());
+        assert!(DiskStreamer::new(DiskStreamConfig::default().with_max_channels(1))
+            .add(Ramp(10))
+            .is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Radix-2 complex FFT.
+//!
+//! [`Fft`] precomputes twiddle factors and the bit-reversal permutation for
+//! one power-of-two size, then transforms split real/imaginary slices in
+//! place. Construction allocates; [`Fft::forward()`] and [`Fft::inverse()`]
+//! do not, so a prepared `Fft` can be used on the audio thread.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let fft = Fft::new(1024);
+//! let mut re = signal.to_vec();
+//! let mut im = vec![0.0; 1024];
+//! fft.forward(&mut re, &mut im);
+//! let magnitude = (re[k] * re[k] + im[k] * im[k]).sqrt();
+//! ```
+
+use std::f64::consts::PI;
+
+/// Precomputed radix-2 FFT of a fixed size.
+#[derive(Debug, Clone)]
+pub struct Fft {
+    size: usize,
+    /// `cos(2 pi k / size)` for `k < size / 2`.
+    cos: Vec<f64>,
+    /// `sin(2 pi k / size)` for `k < size / 2`.
+    sin: Vec<f64>,
+    /// Bit-reversed index of every position.
+    bit_reverse: Vec<usize>,
+}
+
+impl Fft {
+    /// Create an FFT for `size` points, rounded up to a power of two.
+    pub fn new(size: usize) -> Self {
+        let size = size.max(1).next_power_of_two();
+        let half = size / 2;
+        let bits = size.trailing_zeros();
+        let bit_reverse = (0..size)
+            .map(|i| if bits == 0 { 0 } else { i.reverse_bits() >> (usize::BITS - bits) })
+            .collect();
+        Self {
+            size,
+            cos: (0..half).map(|k| (2.0 * PI * k as f64 / size as f64).cos()).collect(),
+            sin: (0..half).map(|k| (2.0 * PI * k as f64 / size as f64).sin()).collect(),
+            bit_reverse,
+        }
+    }
+
+    /// Number of points.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Forward transform in place (`e^{-i...}` kernel, unscaled).
+    ///
+    /// Both slices must hold at least [`size()`](Self::size) values; extra
+    /// values are left untouched.
+    pub fn forward(&self, re: &mut [f64], im: &mut [f64]) {
+        self.transform(re, im, -1.0);
+    }
+
+    /// Inverse transform in place, scaled by `1 / size` so that
+    /// `inverse(forward(x)) == x`.
+    pub fn inverse(&self, re: &mut [f64], im: &mut [f64]) {
+        self.transform(re, im, 1.0);
+        let scale = 1.0 / self.size as f64;
+        for (r, i) in re[..self.size].iter_mut().zip(&mut im[..self.size]) {
+            *r *= scale;
+            *i *= scale;
+        }
+    }
+
+    fn transform(&self, re: &mut [f64], im: &mut [f64], sign: f64) {
+        let n = self.size;
+        let (re, im) = (&mut re[..n], &mut im[..n]);
+
+        for i in 0..n {
+            let j = self.bit_reverse[i];
+            if j > i {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let w_re = self.cos[k * stride];
+                    let w_im = sign * self.sin[k * stride];
+                    let a = start + k;
+                    let b = a + half;
+                    let t_re = re[b] * w_re - im[b] * w_im;
+                    let t_im = re[b] * w_im + im[b] * w_re;
+                    re[b] = re[a] - t_re;
+                    im[b] = im[a] - t_im;
+                    re[a] += t_re;
+                    im[a] += t_im;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sine_lands_in_its_bin() {
+        let fft = Fft::new(64);
+        let mut re: Vec<f64> = (0..64).map(|i| (2.0 * PI * 5.0 * i as f64 / 64.0).cos()).collect();
+        let mut im = vec![0.0; 64];
+        fft.forward(&mut re, &mut im);
+        let magnitude = |k: usize| (re[k] * re[k] + im[k] * im[k]).sqrt();
+        assert!((magnitude(5) - 32.0).abs() < 1e-9);
+        assert!((magnitude(59) - 32.0).abs() < 1e-9);
+        assert!(magnitude(6) < 1e-9);
+    }
+
+    #[test]
+    fn inverse_round_trips() {
+        let fft = Fft::new(100);
+        assert_eq!(fft.size(), 128);
+        let original: Vec<f64> = (0..128).map(|i| ((i * 37) % 11) as f64 - 5.0).collect();
+        let mut re = original.clone();
+        let mut im = vec![0.0; 128];
+        fft.forward(&mut re, &mut im);
+        fft.inverse(&mut re, &mut im);
+        for (a, b) in re.iter().zip(&original) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        assert!(im.iter().all(|v| v.abs() < 1e-9));
+    }
+}
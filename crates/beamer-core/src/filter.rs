@@ -0,0 +1,421 @@
+//! Biquad filters and frequency response evaluation.
+//!
+//! [`BiquadCoeffs`] holds normalized biquad coefficients designed with the
+//! standard bilinear transform (RBJ Audio EQ Cookbook), and [`BiquadState`]
+//! runs them in Direct Form II Transposed.
+//!
+//! Because the coefficients are plain data, an EQ GUI can evaluate the exact
+//! response of the filters used in processing with
+//! [`BiquadCoeffs::magnitude_at()`] and [`BiquadCoeffs::phase_at()`], or draw
+//! a whole curve for a cascade of filters with [`ResponseCurve`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let bands = [
+//!     BiquadCoeffs::low_shelf(100.0, 3.0, 0.707, sample_rate),
+//!     BiquadCoeffs::peak(1000.0, -4.0, 1.0, sample_rate),
+//! ];
+//!
+//! // Single point
+//! let db = bands[1].magnitude_db_at(1000.0, sample_rate); // ~ -4.0
+//!
+//! // Full curve (allocate once, reuse every redraw)
+//! let mut curve = ResponseCurve::new(256, 20.0, 20000.0);
+//! curve.compute(&bands, sample_rate);
+//! for (freq, db) in curve.frequencies().iter().zip(curve.magnitudes_db()) {
+//!     // plot point
+//! }
+//! ```
+
+use std::f64::consts::PI;
+
+/// Smallest magnitude reported by the dB helpers, to avoid `-inf` at zeros.
+const MIN_MAGNITUDE: f64 = 1e-12;
+
+// =============================================================================
+// Coefficients
+// =============================================================================
+
+/// Biquad filter coefficients.
+///
+/// Normalized coefficients where a0 = 1 (already divided out).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiquadCoeffs {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl Default for BiquadCoeffs {
+    /// Default to passthrough (unity gain, no filtering).
+    fn default() -> Self {
+        Self {
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+        }
+    }
+}
+
+/// Intermediate values shared by all cookbook designs.
+struct Design {
+    cos_w0: f64,
+    alpha: f64,
+}
+
+impl Design {
+    /// Frequency is clamped to 49% of sample rate to prevent Nyquist
+    /// instability, Q to a minimum of 0.01 to prevent division by zero.
+    fn new(freq: f64, q: f64, sample_rate: f64) -> Self {
+        let freq = freq.min(sample_rate * 0.49);
+        let q = q.max(0.01);
+        let w0 = 2.0 * PI * freq / sample_rate;
+        Self {
+            cos_w0: w0.cos(),
+            alpha: w0.sin() / (2.0 * q),
+        }
+    }
+}
+
+impl BiquadCoeffs {
+    /// Build normalized coefficients from unnormalized cookbook values.
+    #[inline]
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// Peaking (bell) filter.
+    ///
+    /// Q controls bandwidth (higher Q = narrower peak).
+    pub fn peak(freq: f64, gain_db: f64, q: f64, sample_rate: f64) -> Self {
+        let d = Design::new(freq, q, sample_rate);
+        let a = 10.0_f64.powf(gain_db / 40.0);
+        Self::normalized(
+            1.0 + d.alpha * a,
+            -2.0 * d.cos_w0,
+            1.0 - d.alpha * a,
+            1.0 + d.alpha / a,
+            -2.0 * d.cos_w0,
+            1.0 - d.alpha / a,
+        )
+    }
+
+    /// Low shelf filter. Use Q = 0.707 for a maximally flat shelf.
+    pub fn low_shelf(freq: f64, gain_db: f64, q: f64, sample_rate: f64) -> Self {
+        let d = Design::new(freq, q, sample_rate);
+        let a = 10.0_f64.powf(gain_db / 40.0);
+        let k = 2.0 * a.sqrt() * d.alpha;
+        Self::normalized(
+            a * ((a + 1.0) - (a - 1.0) * d.cos_w0 + k),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * d.cos_w0),
+            a * ((a + 1.0) - (a - 1.0) * d.cos_w0 - k),
+            (a + 1.0) + (a - 1.0) * d.cos_w0 + k,
+            -2.0 * ((a - 1.0) + (a + 1.0) * d.cos_w0),
+            (a + 1.0) + (a - 1.0) * d.cos_w0 - k,
+        )
+    }
+
+    /// High shelf filter. Use Q = 0.707 for a maximally flat shelf.
+    pub fn high_shelf(freq: f64, gain_db: f64, q: f64, sample_rate: f64) -> Self {
+        let d = Design::new(freq, q, sample_rate);
+        let a = 10.0_f64.powf(gain_db / 40.0);
+        let k = 2.0 * a.sqrt() * d.alpha;
+        Self::normalized(
+            a * ((a + 1.0) + (a - 1.0) * d.cos_w0 + k),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * d.cos_w0),
+            a * ((a + 1.0) + (a - 1.0) * d.cos_w0 - k),
+            (a + 1.0) - (a - 1.0) * d.cos_w0 + k,
+            2.0 * ((a - 1.0) - (a + 1.0) * d.cos_w0),
+            (a + 1.0) - (a - 1.0) * d.cos_w0 - k,
+        )
+    }
+
+    /// Second-order low-pass filter.
+    pub fn low_pass(freq: f64, q: f64, sample_rate: f64) -> Self {
+        let d = Design::new(freq, q, sample_rate);
+        let b1 = 1.0 - d.cos_w0;
+        Self::normalized(
+            b1 / 2.0,
+            b1,
+            b1 / 2.0,
+            1.0 + d.alpha,
+            -2.0 * d.cos_w0,
+            1.0 - d.alpha,
+        )
+    }
+
+    /// Second-order high-pass filter.
+    pub fn high_pass(freq: f64, q: f64, sample_rate: f64) -> Self {
+        let d = Design::new(freq, q, sample_rate);
+        let b1 = 1.0 + d.cos_w0;
+        Self::normalized(
+            b1 / 2.0,
+            -b1,
+            b1 / 2.0,
+            1.0 + d.alpha,
+            -2.0 * d.cos_w0,
+            1.0 - d.alpha,
+        )
+    }
+
+    /// Second-order all-pass filter: flat magnitude, phase turning by 360
+    /// degrees around `freq`.
+    pub fn all_pass(freq: f64, q: f64, sample_rate: f64) -> Self {
+        let d = Design::new(freq, q, sample_rate);
+        Self::normalized(
+            1.0 - d.alpha,
+            -2.0 * d.cos_w0,
+            1.0 + d.alpha,
+            1.0 + d.alpha,
+            -2.0 * d.cos_w0,
+            1.0 - d.alpha,
+        )
+    }
+
+    /// First-order all-pass filter: flat magnitude, phase turning by 180
+    /// degrees around `freq` (-90 degrees at `freq`).
+    pub fn first_order_all_pass(freq: f64, sample_rate: f64) -> Self {
+        let freq = freq.min(sample_rate * 0.49);
+        let k = (PI * freq / sample_rate).tan();
+        let c = (k - 1.0) / (k + 1.0);
+        Self {
+            b0: c,
+            b1: 1.0,
+            b2: 0.0,
+            a1: c,
+            a2: 0.0,
+        }
+    }
+
+    // =========================================================================
+    // Frequency response
+    // =========================================================================
+
+    /// Complex response `H(e^jw)` as `(numerator, denominator)` pairs of
+    /// `(re, im)`.
+    #[inline]
+    fn evaluate(&self, freq: f64, sample_rate: f64) -> ((f64, f64), (f64, f64)) {
+        let w = 2.0 * PI * freq / sample_rate;
+        let (sin1, cos1) = w.sin_cos();
+        let (sin2, cos2) = (2.0 * w).sin_cos();
+        let num = (
+            self.b0 + self.b1 * cos1 + self.b2 * cos2,
+            -(self.b1 * sin1 + self.b2 * sin2),
+        );
+        let den = (
+            1.0 + self.a1 * cos1 + self.a2 * cos2,
+            -(self.a1 * sin1 + self.a2 * sin2),
+        );
+        (num, den)
+    }
+
+    /// Linear magnitude response at `freq` Hz.
+    pub fn magnitude_at(&self, freq: f64, sample_rate: f64) -> f64 {
+        let (num, den) = self.evaluate(freq, sample_rate);
+        num.0.hypot(num.1) / den.0.hypot(den.1)
+    }
+
+    /// Magnitude response at `freq` Hz in decibels.
+    pub fn magnitude_db_at(&self, freq: f64, sample_rate: f64) -> f64 {
+        20.0 * self.magnitude_at(freq, sample_rate).max(MIN_MAGNITUDE).log10()
+    }
+
+    /// Phase response at `freq` Hz in radians, wrapped to `-PI..=PI`.
+    pub fn phase_at(&self, freq: f64, sample_rate: f64) -> f64 {
+        let (num, den) = self.evaluate(freq, sample_rate);
+        let phase = num.1.atan2(num.0) - den.1.atan2(den.0);
+        // Wrap the difference of two atan2 results back into range
+        (phase + PI).rem_euclid(2.0 * PI) - PI
+    }
+}
+
+// =============================================================================
+// State
+// =============================================================================
+
+/// Biquad filter state (Direct Form II Transposed).
+///
+/// Stores the two delay elements needed for the biquad difference equation.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BiquadState {
+    z1: f64,
+    z2: f64,
+}
+
+impl BiquadState {
+    /// Process a single sample through the filter.
+    ///
+    /// Uses Direct Form II Transposed structure:
+    /// ```text
+    /// y[n] = b0*x[n] + z1
+    /// z1 = b1*x[n] - a1*y[n] + z2
+    /// z2 = b2*x[n] - a2*y[n]
+    /// ```
+    #[inline]
+    pub fn process(&mut self, input: f64, coeffs: &BiquadCoeffs) -> f64 {
+        let output = coeffs.b0 * input + self.z1;
+        self.z1 = coeffs.b1 * input - coeffs.a1 * output + self.z2;
+        self.z2 = coeffs.b2 * input - coeffs.a2 * output;
+        output
+    }
+
+    /// Clear the delay elements.
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+// =============================================================================
+// ResponseCurve
+// =============================================================================
+
+/// Pre-allocated, log-spaced frequency response curve for a filter cascade.
+///
+/// Allocate once (e.g. when the editor opens) and call
+/// [`compute()`](Self::compute) whenever the coefficients change.
+#[derive(Debug, Clone)]
+pub struct ResponseCurve {
+    frequencies: Vec<f64>,
+    magnitudes_db: Vec<f64>,
+}
+
+impl ResponseCurve {
+    /// Create a curve with `num_points` frequencies spaced logarithmically
+    /// from `min_hz` to `max_hz` (inclusive).
+    pub fn new(num_points: usize, min_hz: f64, max_hz: f64) -> Self {
+        let min_hz = min_hz.max(f64::MIN_POSITIVE);
+        let ratio = (max_hz / min_hz).ln();
+        let last = num_points.saturating_sub(1).max(1) as f64;
+        let frequencies = (0..num_points)
+            .map(|i| min_hz * (ratio * i as f64 / last).exp())
+            .collect();
+        Self {
+            frequencies,
+            magnitudes_db: vec![0.0; num_points],
+        }
+    }
+
+    /// Evaluate the summed dB response of `filters` in series at every
+    /// frequency. Frequencies at or above Nyquist are reported as 0 dB.
+    pub fn compute(&mut self, filters: &[BiquadCoeffs], sample_rate: f64) {
+        let nyquist = sample_rate / 2.0;
+        for (db, &freq) in self.magnitudes_db.iter_mut().zip(&self.frequencies) {
+            *db = if freq >= nyquist {
+                0.0
+            } else {
+                filters
+                    .iter()
+                    .map(|f| f.magnitude_db_at(freq, sample_rate))
+                    .sum()
+            };
+        }
+    }
+
+    /// Sample frequencies in Hz.
+    #[inline]
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    /// Magnitudes in dB from the last [`compute()`](Self::compute).
+    #[inline]
+    pub fn magnitudes_db(&self) -> &[f64] {
+        &self.magnitudes_db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f64 = 48000.0;
+
+    #[test]
+    fn test_passthrough_is_flat() {
+        let coeffs = BiquadCoeffs::default();
+        for freq in [20.0, 1000.0, 20000.0] {
+            assert!((coeffs.magnitude_at(freq, SR) - 1.0).abs() < 1e-12);
+            assert!(coeffs.phase_at(freq, SR).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_peak_gain_at_center() {
+        let coeffs = BiquadCoeffs::peak(1000.0, 6.0, 1.0, SR);
+        assert!((coeffs.magnitude_db_at(1000.0, SR) - 6.0).abs() < 1e-9);
+        assert!(coeffs.magnitude_db_at(20.0, SR).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_shelves_reach_gain() {
+        let low = BiquadCoeffs::low_shelf(200.0, -6.0, 0.707, SR);
+        assert!((low.magnitude_db_at(10.0, SR) + 6.0).abs() < 0.1);
+        assert!(low.magnitude_db_at(15000.0, SR).abs() < 0.1);
+
+        let high = BiquadCoeffs::high_shelf(2000.0, 4.0, 0.707, SR);
+        assert!((high.magnitude_db_at(20000.0, SR) - 4.0).abs() < 0.1);
+        assert!(high.magnitude_db_at(20.0, SR).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_low_pass_cutoff() {
+        let coeffs = BiquadCoeffs::low_pass(1000.0, std::f64::consts::FRAC_1_SQRT_2, SR);
+        assert!((coeffs.magnitude_db_at(1000.0, SR) + 3.0103).abs() < 0.01);
+        assert!(coeffs.magnitude_db_at(20.0, SR).abs() < 0.01);
+        assert!(coeffs.magnitude_db_at(10000.0, SR) < -24.0);
+    }
+
+    #[test]
+    fn test_all_pass_is_flat() {
+        let second = BiquadCoeffs::all_pass(1000.0, 0.707, SR);
+        let first = BiquadCoeffs::first_order_all_pass(1000.0, SR);
+        for freq in [20.0, 1000.0, 20000.0] {
+            assert!((second.magnitude_at(freq, SR) - 1.0).abs() < 1e-12);
+            assert!((first.magnitude_at(freq, SR) - 1.0).abs() < 1e-12);
+        }
+        assert!((second.phase_at(1000.0, SR).abs() - PI).abs() < 1e-9);
+        assert!((first.phase_at(1000.0, SR) + PI / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_magnitude_matches_processing() {
+        let coeffs = BiquadCoeffs::high_pass(500.0, 0.707, SR);
+        let freq = 250.0;
+        let mut state = BiquadState::default();
+        let mut peak: f64 = 0.0;
+        for n in 0..(SR as usize) {
+            let x = (2.0 * PI * freq * n as f64 / SR).sin();
+            let y = state.process(x, &coeffs);
+            if n > SR as usize / 2 {
+                peak = peak.max(y.abs());
+            }
+        }
+        assert!((peak - coeffs.magnitude_at(freq, SR)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_response_curve_sums_cascade() {
+        let band = BiquadCoeffs::peak(1000.0, 3.0, 1.0, SR);
+        let mut curve = ResponseCurve::new(64, 20.0, 20000.0);
+        curve.compute(&[band, band], SR);
+
+        assert_eq!(curve.frequencies().len(), 64);
+        assert!((curve.frequencies()[0] - 20.0).abs() < 1e-9);
+        assert!((curve.frequencies()[63] - 20000.0).abs() < 1e-6);
+        for (&freq, &db) in curve.frequencies().iter().zip(curve.magnitudes_db()) {
+            assert!((db - 2.0 * band.magnitude_db_at(freq, SR)).abs() < 1e-9);
+        }
+    }
+}
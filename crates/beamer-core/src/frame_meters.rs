@@ -0,0 +1,298 @@
+//! Meter values handed to the WebView on every display refresh.
+//!
+//! Level meters and analyzers look smooth only when they redraw in step with
+//! the screen. JS timers (`setInterval`) drift against the refresh rate and
+//! compete with the host's own event loop, so the format wrappers drive the
+//! animation instead: while the page has `__BEAMER__.onFrame()` subscribers,
+//! the native view ticks once per display refresh (a `CVDisplayLink` on
+//! macOS), dispatches to the main thread and calls every subscriber with the
+//! frame time and the latest values from the descriptor's [`FrameMeters`].
+//! Frames also carry the host transport from the meters'
+//! [`Playhead`](crate::Playhead), extrapolated to the frame time.
+//!
+//! # Overview
+//!
+//! - [`FrameMeters`] - Named meter slots, written by the audio thread
+//! - [`MeterKind`] - Whether a slot keeps its last value or the peak since the last frame
+//! - [`Vectorscope`] - Stereo sample pairs for a vectorscope, added with [`FrameMeters::with_scope()`]
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::FrameMeters;
+//!
+//! const INPUT_L: usize = 0;
+//! const INPUT_R: usize = 1;
+//! const REDUCTION: usize = 2;
+//!
+//! impl Default for MyDescriptor {
+//!     fn default() -> Self {
+//!         Self {
+//!             meters: FrameMeters::new()
+//!                 .with_peak("inputL")
+//!                 .with_peak("inputR")
+//!                 .with_value("reductionDb"),
+//!             ..
+//!         }
+//!     }
+//! }
+//!
+//! impl Descriptor for MyDescriptor {
+//!     fn frame_meters(&self) -> FrameMeters {
+//!         self.meters.clone() // and pass a clone to the processor in prepare()
+//!     }
+//! }
+//!
+//! // In process():
+//! self.meters.peak(INPUT_L, buffer.peak(0));
+//! self.meters.peak(INPUT_R, buffer.peak(1));
+//! self.meters.set(REDUCTION, self.compressor.reduction_db());
+//! ```
+//!
+//! ```javascript
+//! __BEAMER__.onFrame((time, meters) => {
+//!   drawMeter(meters.inputL, meters.inputR);
+//! });
+//! ```
+//!
+//! Slots are addressed by index in declaration order, so the audio thread
+//! never looks up names. Writes are relaxed atomic stores (a compare loop for
+//! peaks) and never allocate or lock.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::playhead::Playhead;
+use crate::vectorscope::Vectorscope;
+
+/// How a meter slot combines the values written between two frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterKind {
+    /// The last value written. Kept across frames until overwritten.
+    Value,
+    /// The largest value written since the previous frame. Reset to `0.0`
+    /// when a frame reads it, so a meter falls once the audio stops.
+    Peak,
+}
+
+struct Slot {
+    name: &'static str,
+    kind: MeterKind,
+    /// Current value, stored as f32 bits.
+    bits: AtomicU32,
+}
+
+/// Named meter values shared between the audio thread and the GUI.
+///
+/// Cloning is cheap (reference counted). Declare the slots with
+/// [`with_value()`](Self::with_value) and [`with_peak()`](Self::with_peak)
+/// right after [`new()`](Self::new), before cloning. The default has no
+/// slots, and the wrappers then skip the meter payload.
+#[derive(Clone, Default)]
+pub struct FrameMeters {
+    slots: Arc<Vec<Slot>>,
+    scopes: Arc<Vec<(&'static str, Vectorscope)>>,
+    playhead: Playhead,
+}
+
+impl std::fmt::Debug for FrameMeters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        for slot in self.slots.iter() {
+            map.entry(&slot.name, &f32::from_bits(slot.bits.load(Ordering::Relaxed)));
+        }
+        map.finish()
+    }
+}
+
+impl FrameMeters {
+    /// Create meters without slots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a slot that keeps the last written value (gain reduction, a
+    /// playhead position, an envelope stage).
+    pub fn with_value(self, name: &'static str) -> Self {
+        self.with_slot(name, MeterKind::Value)
+    }
+
+    /// Add a slot that keeps the peak written since the previous frame
+    /// (sample peaks, so no transient falls between two frames).
+    pub fn with_peak(self, name: &'static str) -> Self {
+        self.with_slot(name, MeterKind::Peak)
+    }
+
+    /// Add a vectorscope, sent as `{"points":[...],"peak":..,"peakHold":..}`
+    /// under `name`. Keep a clone of `scope` to push samples into.
+    pub fn with_scope(mut self, name: &'static str, scope: Vectorscope) -> Self {
+        match Arc::get_mut(&mut self.scopes) {
+            Some(scopes) => scopes.push((name, scope)),
+            None => log::warn!("FrameMeters::with_scope() called on shared meters"),
+        }
+        self
+    }
+
+    fn with_slot(mut self, name: &'static str, kind: MeterKind) -> Self {
+        match Arc::get_mut(&mut self.slots) {
+            Some(slots) => slots.push(Slot { name, kind, bits: AtomicU32::new(0) }),
+            None => log::warn!("FrameMeters::with_slot() called on shared meters"),
+        }
+        self
+    }
+
+    /// Number of slots.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether there are no slots.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Transport sent with each frame, updated by the wrappers every block.
+    pub fn playhead(&self) -> &Playhead {
+        &self.playhead
+    }
+
+    /// Index of the slot with the given name.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.name == name)
+    }
+
+    /// Name of the slot at `index`.
+    pub fn name(&self, index: usize) -> Option<&'static str> {
+        self.slots.get(index).map(|slot| slot.name)
+    }
+
+    /// Store a value. Out-of-range indices are ignored.
+    ///
+    /// Real-time safe. On a [`MeterKind::Peak`] slot this overwrites the
+    /// peak collected so far; use [`peak()`](Self::peak) instead.
+    #[inline]
+    pub fn set(&self, index: usize, value: f32) {
+        if let Some(slot) = self.slots.get(index) {
+            slot.bits.store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Raise the slot to `value` if it is larger than the stored value.
+    ///
+    /// Meant for non-negative levels; negative and NaN values count as
+    /// `0.0`. Real-time safe.
+    #[inline]
+    pub fn peak(&self, index: usize, value: f32) {
+        if let Some(slot) = self.slots.get(index) {
+            // Non-negative floats order like their bit patterns.
+            let value = if value > 0.0 { value } else { 0.0 };
+            slot.bits.fetch_max(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// The stored value, without resetting a peak.
+    pub fn get(&self, index: usize) -> f32 {
+        self.slots
+            .get(index)
+            .map_or(0.0, |slot| f32::from_bits(slot.bits.load(Ordering::Relaxed)))
+    }
+
+    /// Read a slot for one frame, resetting it if it is a peak slot.
+    pub fn take(&self, index: usize) -> f32 {
+        self.slots.get(index).map_or(0.0, |slot| {
+            f32::from_bits(match slot.kind {
+                MeterKind::Value => slot.bits.load(Ordering::Relaxed),
+                MeterKind::Peak => slot.bits.swap(0, Ordering::Relaxed),
+            })
+        })
+    }
+
+    /// Read every slot for one frame as a JSON object (`{"inputL":0.5,...}`),
+    /// resetting the peak slots. Scopes follow the slots.
+    ///
+    /// Called by the wrappers on the main thread. Non-finite values are sent
+    /// as `0`, since JSON has no representation for them.
+    pub fn frame_json(&self) -> String {
+        let mut json = String::from("{");
+        for (i, slot) in self.slots.iter().enumerate() {
+            let value = self.take(i);
+            let value = if value.is_finite() { value } else { 0.0 };
+            if i > 0 {
+                json.push(',');
+            }
+            let name = serde_json::to_string(slot.name).unwrap_or_default();
+            let _ = write!(json, "{name}:{value}");
+        }
+        for (i, (name, scope)) in self.scopes.iter().enumerate() {
+            if i > 0 || !self.slots.is_empty() {
+                json.push(',');
+            }
+            let name = serde_json::to_string(name).unwrap_or_default();
+            let _ = write!(json, "{name}:{}", scope.frame_json());
+        }
+        json.push('}');
+        json
+    }
+
+    /// Script that delivers one frame to the JS runtime.
+    ///
+    /// `time_ms` is the frame time in milliseconds on a monotonic clock.
+    pub fn frame_script(&self, time_ms: f64) -> String {
+        format!(
+            "window.__BEAMER__._onFrame({time_ms},{},{})",
+            self.frame_json(),
+            self.playhead.position().to_json()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peaks_reset_on_read_and_values_persist() {
+        let meters = FrameMeters::new().with_peak("in").with_value("gr");
+        meters.peak(0, 0.25);
+        meters.peak(0, 0.75);
+        meters.peak(0, 0.5);
+        meters.set(1, -3.5);
+
+        assert_eq!(meters.frame_json(), r#"{"in":0.75,"gr":-3.5}"#);
+        assert_eq!(meters.frame_json(), r#"{"in":0,"gr":-3.5}"#);
+        assert_eq!(meters.index_of("gr"), Some(1));
+    }
+
+    #[test]
+    fn invalid_input_is_ignored() {
+        let meters = FrameMeters::new().with_peak("in");
+        let shared = meters.clone();
+        // Slots can't be added once the meters are shared.
+        let shared = shared.with_value("late");
+        assert_eq!(shared.len(), 1);
+
+        meters.peak(0, f32::NAN);
+        meters.peak(0, -1.0);
+        meters.set(7, 1.0);
+        assert_eq!(meters.get(0), 0.0);
+
+        meters.set(0, f32::INFINITY);
+        assert_eq!(
+            meters.frame_script(16.5),
+            r#"window.__BEAMER__._onFrame(16.5,{"in":0},{"barStart":null,"beats":null,"playing":false,"tempo":null,"timeSignature":null})"#
+        );
+        assert_eq!(FrameMeters::default().frame_json(), "{}");
+    }
+
+    #[test]
+    fn scopes_follow_slots() {
+        let scope = Vectorscope::new(4);
+        let meters = FrameMeters::new().with_value("gr").with_scope("scope", scope.clone());
+        scope.push(&[0.5f32], &[-0.25f32]);
+        assert_eq!(
+            meters.frame_json(),
+            r#"{"gr":0,"scope":{"points":[0.5000,-0.2500],"peak":0.5,"peakHold":0.5}}"#
+        );
+    }
+}
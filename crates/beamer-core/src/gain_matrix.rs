@@ -0,0 +1,333 @@
+//! Smoothed N×M channel gain matrix.
+//!
+//! Routing, downmix and monitoring sections (speaker switching, fold-down
+//! checks, cue mixes) all come down to the same operation: every output is a
+//! weighted sum of the inputs. [`GainMatrix`] holds one gain per
+//! input/output pair and applies them block-wise:
+//!
+//! ```ignore
+//! // In prepare(): 2 in, 2 out, start as a straight copy
+//! let mut matrix = GainMatrix::identity(2, 2);
+//! matrix.set_ramp_ms(20.0, setup.hz());
+//!
+//! // In process(): swap, mono or mute from the parameters
+//! let swap = self.parameters.swap.get();
+//! self.matrix.set_from(|output, input| if (output == input) != swap { 1.0 } else { 0.0 });
+//! buffer.apply_gain_matrix(&mut self.matrix);
+//! ```
+//!
+//! Gain changes ramp linearly over the [ramp time](GainMatrix::set_ramp_samples)
+//! (none by default), so switching routes doesn't click. Setting the same
+//! target again doesn't restart the ramp, so the matrix can be set from
+//! parameters every block. [`set_from_mix()`](GainMatrix::set_from_mix)
+//! starts from one of the standard [`ChannelMix`] laws.
+//!
+//! The inner loops work on fixed chunks of samples without branches, which
+//! the compiler turns into SIMD instructions. Pairs with a gain of zero are
+//! skipped. All storage is allocated in the constructors; processing and
+//! setting gains are real-time safe.
+
+use crate::channel_mix::{mix_channels, ChannelMix};
+use crate::sample::Sample;
+use crate::types::MAX_CHANNELS;
+
+/// Samples per chunk of the inner loops.
+const LANES: usize = 8;
+
+/// One input/output pair.
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    current: f64,
+    target: f64,
+    /// Gain change per sample while ramping.
+    step: f64,
+    /// Samples left in the ramp.
+    remaining: u32,
+}
+
+/// Gains from every input to every output, with smoothed changes.
+///
+/// Gains are linear and indexed `(output, input)`. See the module docs.
+pub struct GainMatrix<S: Sample = f32> {
+    inputs: usize,
+    outputs: usize,
+    /// Output-major: `output * inputs + input`.
+    cells: Vec<Cell>,
+    ramp_samples: u32,
+    _sample: std::marker::PhantomData<S>,
+}
+
+impl<S: Sample> GainMatrix<S> {
+    /// Create a silent matrix (all gains 0) from `inputs` to `outputs`
+    /// channels.
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        Self {
+            inputs,
+            outputs,
+            cells: vec![Cell::default(); inputs * outputs],
+            ramp_samples: 0,
+            _sample: std::marker::PhantomData,
+        }
+    }
+
+    /// Create a matrix passing input `i` to output `i`.
+    pub fn identity(inputs: usize, outputs: usize) -> Self {
+        let mut matrix = Self::new(inputs, outputs);
+        matrix.set_from(|output, input| if output == input { 1.0 } else { 0.0 });
+        matrix.reset();
+        matrix
+    }
+
+    /// Number of input channels.
+    pub fn num_inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Number of output channels.
+    pub fn num_outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Set the ramp time for gain changes in samples. 0 applies changes at
+    /// the start of the next block.
+    pub fn set_ramp_samples(&mut self, samples: u32) {
+        self.ramp_samples = samples;
+    }
+
+    /// Set the ramp time for gain changes in milliseconds.
+    pub fn set_ramp_ms(&mut self, ms: f64, sample_rate: f64) {
+        self.ramp_samples = (ms * 0.001 * sample_rate).round().max(0.0) as u32;
+    }
+
+    /// Set the gain from `input` to `output`, ramping from the current
+    /// value. Out of range pairs are ignored.
+    pub fn set_gain(&mut self, output: usize, input: usize, gain: f64) {
+        if output >= self.outputs || input >= self.inputs {
+            return;
+        }
+        let ramp_samples = self.ramp_samples;
+        let cell = &mut self.cells[output * self.inputs + input];
+        if cell.target == gain {
+            return;
+        }
+        cell.target = gain;
+        if ramp_samples == 0 {
+            cell.current = gain;
+            cell.remaining = 0;
+        } else {
+            cell.step = (gain - cell.current) / ramp_samples as f64;
+            cell.remaining = ramp_samples;
+        }
+    }
+
+    /// Set every gain from `gain(output, input)`.
+    pub fn set_from(&mut self, mut gain: impl FnMut(usize, usize) -> f64) {
+        for output in 0..self.outputs {
+            for input in 0..self.inputs {
+                self.set_gain(output, input, gain(output, input));
+            }
+        }
+    }
+
+    /// Set every gain from a row-major slice (`output * inputs + input`),
+    /// e.g. restored from saved state. Missing entries are 0.
+    pub fn set_targets(&mut self, gains: &[f64]) {
+        let inputs = self.inputs;
+        self.set_from(|output, input| gains.get(output * inputs + input).copied().unwrap_or(0.0));
+    }
+
+    /// Set the gains that [`mix_channels()`] uses for `mix`, e.g. as a
+    /// starting point for a custom downmix.
+    pub fn set_from_mix(&mut self, mix: ChannelMix) {
+        let inputs = self.inputs.min(MAX_CHANNELS);
+        let outputs = self.outputs.min(MAX_CHANNELS);
+        let silence = [0.0];
+        let impulse = [1.0];
+        for input in 0..inputs {
+            // Send a unit sample through input `input` alone
+            let mut sources: [&[f64]; MAX_CHANNELS] = [&silence; MAX_CHANNELS];
+            sources[input] = &impulse;
+            let mut responses = [[0.0]; MAX_CHANNELS];
+            let mut destinations: [&mut [f64]; MAX_CHANNELS] = Default::default();
+            for (slot, response) in destinations.iter_mut().zip(&mut responses) {
+                *slot = response;
+            }
+            mix_channels(mix, &sources[..inputs], &mut destinations[..outputs]);
+            for (output, response) in responses.iter().take(outputs).enumerate() {
+                self.set_gain(output, input, response[0]);
+            }
+        }
+    }
+
+    /// The current (possibly ramping) gain from `input` to `output`.
+    pub fn gain(&self, output: usize, input: usize) -> f64 {
+        self.cell(output, input).map_or(0.0, |cell| cell.current)
+    }
+
+    /// The gain from `input` to `output` once ramps finish.
+    pub fn target(&self, output: usize, input: usize) -> f64 {
+        self.cell(output, input).map_or(0.0, |cell| cell.target)
+    }
+
+    /// All target gains, row-major (`output * inputs + input`), e.g. for
+    /// saving state. Allocates.
+    pub fn targets(&self) -> Vec<f64> {
+        self.cells.iter().map(|cell| cell.target).collect()
+    }
+
+    /// Returns true while any gain is ramping.
+    pub fn is_smoothing(&self) -> bool {
+        self.cells.iter().any(|cell| cell.remaining > 0)
+    }
+
+    /// Jump every gain to its target (e.g. on activation or after loading
+    /// state).
+    pub fn reset(&mut self) {
+        for cell in &mut self.cells {
+            cell.current = cell.target;
+            cell.remaining = 0;
+        }
+    }
+
+    /// Mix `inputs` into `outputs`.
+    ///
+    /// Every output is written: outputs beyond the matrix are silent, and
+    /// missing or too short inputs count as silence. Advances the ramps by
+    /// the block length (the length of the first output).
+    pub fn process(&mut self, inputs: &[&[S]], outputs: &mut [&mut [S]]) {
+        let num_samples = outputs.first().map_or(0, |output| output.len());
+        for (output_index, output) in outputs.iter_mut().enumerate() {
+            let len = num_samples.min(output.len());
+            let output = &mut output[..len];
+            output.fill(S::ZERO);
+            if output_index >= self.outputs {
+                continue;
+            }
+            let row = output_index * self.inputs;
+            for (cell, input) in self.cells[row..row + self.inputs].iter().zip(inputs) {
+                if input.len() < output.len() {
+                    continue;
+                }
+                let input = &input[..output.len()];
+                let ramp = (cell.remaining as usize).min(output.len());
+                if ramp > 0 {
+                    accumulate_ramp(&mut output[..ramp], &input[..ramp], cell.current, cell.step);
+                }
+                if cell.target != 0.0 && ramp < output.len() {
+                    accumulate(&mut output[ramp..], &input[ramp..], S::from_f64(cell.target));
+                }
+            }
+        }
+        self.advance(num_samples);
+    }
+
+    fn cell(&self, output: usize, input: usize) -> Option<&Cell> {
+        (output < self.outputs && input < self.inputs).then(|| &self.cells[output * self.inputs + input])
+    }
+
+    /// Move the ramps forward by `samples`.
+    fn advance(&mut self, samples: usize) {
+        for cell in &mut self.cells {
+            if cell.remaining == 0 {
+                continue;
+            }
+            let ramp = (cell.remaining as usize).min(samples) as u32;
+            cell.remaining -= ramp;
+            cell.current = if cell.remaining == 0 {
+                cell.target
+            } else {
+                cell.current + cell.step * ramp as f64
+            };
+        }
+    }
+}
+
+/// `output += input * gain`.
+#[inline]
+fn accumulate<S: Sample>(output: &mut [S], input: &[S], gain: S) {
+    let mut output_chunks = output.chunks_exact_mut(LANES);
+    let mut input_chunks = input.chunks_exact(LANES);
+    for (out, inp) in (&mut output_chunks).zip(&mut input_chunks) {
+        for lane in 0..LANES {
+            out[lane] = out[lane] + inp[lane] * gain;
+        }
+    }
+    for (out, &sample) in output_chunks.into_remainder().iter_mut().zip(input_chunks.remainder()) {
+        *out = *out + sample * gain;
+    }
+}
+
+/// `output += input * gain` with the gain moving by `step` per sample,
+/// starting one step after `start`.
+#[inline]
+fn accumulate_ramp<S: Sample>(output: &mut [S], input: &[S], start: f64, step: f64) {
+    let offsets: [S; LANES] = std::array::from_fn(|lane| S::from_f64(step * (lane + 1) as f64));
+    let mut base = start;
+    let mut output_chunks = output.chunks_exact_mut(LANES);
+    let mut input_chunks = input.chunks_exact(LANES);
+    for (out, inp) in (&mut output_chunks).zip(&mut input_chunks) {
+        let gain = S::from_f64(base);
+        for lane in 0..LANES {
+            out[lane] = out[lane] + inp[lane] * (gain + offsets[lane]);
+        }
+        base += step * LANES as f64;
+    }
+    let gain = S::from_f64(base);
+    let rest = output_chunks.into_remainder().iter_mut().zip(input_chunks.remainder());
+    for ((out, &sample), offset) in rest.zip(offsets) {
+        *out = *out + sample * (gain + offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_gains_route_and_match_channel_mix() {
+        // Stereo to mono at -3 dB, plus a swapped stereo pair
+        let left = [1.0f32; 13];
+        let right = [0.5f32; 13];
+        let mut mono = [0.0f32; 13];
+        let mut matrix = GainMatrix::new(2, 1);
+        matrix.set_from_mix(ChannelMix::EqualPower);
+        assert!((matrix.gain(0, 1) - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-12);
+        matrix.process(&[&left, &right], &mut [&mut mono]);
+        let mut expected = [0.0f32; 13];
+        mix_channels(ChannelMix::EqualPower, &[&left, &right], &mut [&mut expected]);
+        assert_eq!(mono, expected);
+
+        let mut swapped = [[9.0f32; 13]; 3];
+        let [a, b, c] = &mut swapped;
+        let mut matrix = GainMatrix::new(2, 2);
+        matrix.set_from(|output, input| if output != input { 1.0 } else { 0.0 });
+        matrix.process(&[&left, &right], &mut [a, b, c]);
+        assert_eq!(swapped[0], right);
+        assert_eq!(swapped[1], left);
+        // Outputs beyond the matrix are silent
+        assert_eq!(swapped[2], [0.0; 13]);
+    }
+
+    #[test]
+    fn gain_changes_ramp_across_blocks() {
+        let input = [1.0f64; 20];
+        let mut output = [0.0f64; 20];
+        let mut matrix = GainMatrix::identity(1, 1);
+        matrix.set_ramp_samples(30);
+        matrix.set_gain(0, 0, 0.0);
+        // Setting the same target again doesn't restart the ramp
+        matrix.set_gain(0, 0, 0.0);
+
+        matrix.process(&[&input], &mut [&mut output]);
+        assert!((output[0] - 29.0 / 30.0).abs() < 1e-12);
+        assert!((output[19] - 10.0 / 30.0).abs() < 1e-12);
+        assert!(output.windows(2).all(|w| w[1] < w[0]));
+        assert!(matrix.is_smoothing());
+
+        matrix.process(&[&input], &mut [&mut output]);
+        assert!((output[9]).abs() < 1e-12);
+        assert_eq!(output[10..], [0.0; 10]);
+        assert!(!matrix.is_smoothing());
+        assert_eq!(matrix.targets(), [0.0]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Processing blocks for the `process_graph!` routing macro.
+//!
+//! Processors built from several stages (EQ into compressor, a sidechain
+//! detector keying the compressor) can describe the routing declaratively
+//! instead of wiring calls by hand:
+//!
+//! ```ignore
+//! fn process(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!     process_graph! {
+//!         main -> eq -> comp -> out;
+//!         sidechain -> follower -> comp.sidechain;
+//!     }
+//! }
+//! ```
+//!
+//! The stages are fields of the processor implementing one of the traits
+//! below. The macro checks the routing at compile time (one `main -> ... ->
+//! out` chain, every stage used once, sidechain targets on the main chain)
+//! and expands to direct trait calls, so a graph costs the same as the
+//! hand-written calls:
+//!
+//! - [`Block`] - a stage of the main chain, processing the output in place
+//! - [`KeyedBlock`] - a main chain stage with a routed sidechain (`comp.sidechain`)
+//! - [`Detector`] - a stage of a key chain (envelope followers, key filters)
+//!
+//! Key chains start at `main` (the dry main input) or `sidechain` (the
+//! sidechain bus, silent when the host connects none) and are computed
+//! before the main chain runs.
+
+use crate::buffer::{AuxInput, AuxiliaryBuffers, Buffer};
+use crate::sample::Sample;
+
+// =============================================================================
+// Traits
+// =============================================================================
+
+/// A main chain stage: processes the buffer's output channels in place.
+///
+/// The graph copies the input to the output before the first stage.
+pub trait Block<S: Sample = f32> {
+    /// Process the current block in place.
+    fn process(&mut self, buffer: &mut Buffer<S>);
+}
+
+/// A main chain stage with a sidechain input (`node.sidechain` in a graph).
+pub trait KeyedBlock<S: Sample = f32> {
+    /// Process the current block in place, keyed by `key`.
+    fn process_keyed(&mut self, buffer: &mut Buffer<S>, key: &KeySignal<'_, S>);
+}
+
+/// A key chain stage: turns its input into the key for the next stage.
+///
+/// The output usually points into storage the detector allocates up front,
+/// e.g. a mono envelope via [`KeySignal::mono()`].
+pub trait Detector<S: Sample = f32> {
+    /// Analyze `input` and return the resulting key signal.
+    fn detect<'s>(&'s mut self, input: &KeySignal<'_, S>) -> KeySignal<'s, S>;
+}
+
+// =============================================================================
+// KeySignal
+// =============================================================================
+
+#[derive(Clone, Copy)]
+enum Channels<'a, S> {
+    Slots(&'a [Option<&'a [S]>]),
+    Mono(&'a [S]),
+}
+
+/// Read-only view of a key signal: an input bus or a detector's output.
+#[derive(Clone, Copy)]
+pub struct KeySignal<'a, S: Sample = f32> {
+    channels: Channels<'a, S>,
+    num_samples: usize,
+}
+
+impl<'a, S: Sample> KeySignal<'a, S> {
+    /// A single-channel key, e.g. an envelope.
+    pub fn mono(samples: &'a [S]) -> Self {
+        Self {
+            channels: Channels::Mono(samples),
+            num_samples: samples.len(),
+        }
+    }
+
+    /// A key without channels.
+    pub fn silent(num_samples: usize) -> Self {
+        Self {
+            channels: Channels::Slots(&[]),
+            num_samples,
+        }
+    }
+
+    /// The buffer's (dry) main input.
+    pub fn main_input(buffer: &'a Buffer<'_, S>) -> Self {
+        Self {
+            channels: Channels::Slots(buffer.input_slots()),
+            num_samples: buffer.num_samples(),
+        }
+    }
+
+    /// The sidechain bus, or silence when the host connects none.
+    pub fn sidechain(aux: &'a AuxiliaryBuffers<'_, S>) -> Self {
+        aux.sidechain()
+            .map_or_else(|| Self::silent(aux.num_samples()), Self::from)
+    }
+
+    /// Number of channels.
+    #[inline]
+    pub fn num_channels(&self) -> usize {
+        match self.channels {
+            Channels::Slots(slots) => slots.len(),
+            Channels::Mono(_) => 1,
+        }
+    }
+
+    /// Number of samples in each channel.
+    #[inline]
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    /// Get a channel. Returns an empty slice if the channel doesn't exist.
+    #[inline]
+    pub fn channel(&self, index: usize) -> &'a [S] {
+        match self.channels {
+            Channels::Slots(slots) => slots
+                .get(index)
+                .copied()
+                .flatten()
+                .map_or(&[], |channel| &channel[..self.num_samples]),
+            Channels::Mono(samples) if index == 0 => samples,
+            Channels::Mono(_) => &[],
+        }
+    }
+
+    /// Get one sample. Returns `S::ZERO` if the channel or index doesn't exist.
+    #[inline]
+    pub fn sample(&self, channel: usize, index: usize) -> S {
+        self.channel(channel).get(index).copied().unwrap_or(S::ZERO)
+    }
+}
+
+impl<'a, S: Sample> From<AuxInput<'a, S>> for KeySignal<'a, S> {
+    fn from(input: AuxInput<'a, S>) -> Self {
+        Self {
+            channels: Channels::Slots(input.slots()),
+            num_samples: input.num_samples(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Peak follower writing a mono envelope.
+    struct Peak {
+        envelope: Vec<f32>,
+    }
+
+    impl Detector for Peak {
+        fn detect<'s>(&'s mut self, input: &KeySignal<'_, f32>) -> KeySignal<'s, f32> {
+            let n = input.num_samples();
+            for (i, out) in self.envelope[..n].iter_mut().enumerate() {
+                *out = (0..input.num_channels())
+                    .map(|ch| input.sample(ch, i).abs())
+                    .fold(0.0, f32::max);
+            }
+            KeySignal::mono(&self.envelope[..n])
+        }
+    }
+
+    #[test]
+    fn test_key_views() {
+        let left = [1.0f32, -2.0, 0.5];
+        let right = [0.0f32, 3.0, -1.0];
+        let mut output = [0.0f32; 3];
+        let buffer = Buffer::new([left.as_slice(), right.as_slice()], [output.as_mut_slice()], 3);
+
+        let key = KeySignal::main_input(&buffer);
+        assert_eq!(key.num_channels(), 2);
+        assert_eq!(key.channel(1), &right);
+        assert_eq!(key.sample(2, 0), 0.0);
+
+        let mut peak = Peak { envelope: vec![0.0; 8] };
+        let envelope = peak.detect(&key);
+        assert_eq!(envelope.num_channels(), 1);
+        assert_eq!(envelope.channel(0), &[1.0, 3.0, 1.0]);
+        assert!(envelope.channel(1).is_empty());
+    }
+
+    #[test]
+    fn test_missing_sidechain_is_silent() {
+        let aux = AuxiliaryBuffers::<f32>::empty();
+        let key = KeySignal::sidechain(&aux);
+        assert_eq!(key.num_channels(), 0);
+        assert_eq!(key.sample(0, 0), 0.0);
+        assert!(KeySignal::<f32>::silent(64).channel(0).is_empty());
+    }
+}
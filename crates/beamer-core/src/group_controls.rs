@@ -0,0 +1,257 @@
+//! Framework-generated bypass and solo switches for parameter groups.
+//!
+//! Multi-band and multi-module plugins usually want a bypass and a solo
+//! switch per band. Rather than hand-writing two `BoolParameter`s per group
+//! and re-implementing the solo logic in every plugin, mark the nested groups
+//! with `controls` and add a single [`GroupControls`] field:
+//!
+//! ```ignore
+//! #[derive(Parameters)]
+//! pub struct MultibandParameters {
+//!     #[nested(group = "Low", controls)]
+//!     pub low: BandParameters,
+//!
+//!     #[nested(group = "High", controls)]
+//!     pub high: BandParameters,
+//!
+//!     #[group_controls]
+//!     pub bands: GroupControls,
+//! }
+//! ```
+//!
+//! The derive macro generates a "Low Bypass"/"Low Solo" pair (and so on) as
+//! regular parameters: hosts see them, they are automatable and saved with
+//! the state. DSP code asks [`ParameterGroups::is_group_active()`] whether a
+//! band should be heard, which folds bypass and solo into one answer.
+
+use crate::parameter_groups::{GroupId, GroupInfo, ParameterGroups, ROOT_GROUP_ID};
+use crate::parameter_types::{BoolParameter, ParameterRef, Parameters};
+use crate::preset::fnv1a_hash;
+use crate::types::ParameterId;
+
+// =============================================================================
+// GroupSwitch
+// =============================================================================
+
+/// Bypass and solo parameters for a single group.
+pub struct GroupSwitch {
+    /// Name of the group these switches control.
+    group_name: &'static str,
+    /// Runtime group ID, resolved by [`GroupControls::bind_groups()`].
+    group_id: GroupId,
+    /// Bypass switch.
+    bypass: BoolParameter,
+    /// Solo switch.
+    solo: BoolParameter,
+}
+
+impl GroupSwitch {
+    /// Create the switches for a group.
+    ///
+    /// String IDs are hashed to parameter IDs the same way `#[parameter(id)]`
+    /// is. Usually called by `#[derive(Parameters)]`, which names them
+    /// `"<group>.bypass"` and `"<group>.solo"`.
+    pub fn new(
+        group_name: &'static str,
+        bypass_name: &'static str,
+        solo_name: &'static str,
+        bypass_string_id: &'static str,
+        solo_string_id: &'static str,
+    ) -> Self {
+        Self {
+            group_name,
+            group_id: ROOT_GROUP_ID,
+            bypass: BoolParameter::new(bypass_name, false)
+                .with_id(fnv1a_hash(bypass_string_id))
+                .with_string_id(bypass_string_id)
+                .no_randomize(),
+            solo: BoolParameter::new(solo_name, false)
+                .with_id(fnv1a_hash(solo_string_id))
+                .with_string_id(solo_string_id)
+                .no_randomize(),
+        }
+    }
+
+    /// Name of the controlled group.
+    pub fn group_name(&self) -> &'static str {
+        self.group_name
+    }
+
+    /// Group ID of the controlled group (root until bound).
+    pub fn group_id(&self) -> GroupId {
+        self.group_id
+    }
+
+    /// The bypass parameter.
+    pub fn bypass(&self) -> &BoolParameter {
+        &self.bypass
+    }
+
+    /// The solo parameter.
+    pub fn solo(&self) -> &BoolParameter {
+        &self.solo
+    }
+}
+
+// =============================================================================
+// GroupControls
+// =============================================================================
+
+/// Bypass and solo switches for the groups marked `#[nested(..., controls)]`.
+///
+/// Declare the field on the top-level parameter struct. Switches appear in the
+/// host inside the group they control. Groups nested inside a controlled group
+/// have no switches of their own, so check the parent group.
+#[derive(Default)]
+pub struct GroupControls {
+    switches: Vec<GroupSwitch>,
+}
+
+impl GroupControls {
+    /// Create controls from a list of switches.
+    pub fn new(switches: Vec<GroupSwitch>) -> Self {
+        Self { switches }
+    }
+
+    /// All switches, in declaration order.
+    pub fn switches(&self) -> &[GroupSwitch] {
+        &self.switches
+    }
+
+    /// Switches for a group, if it has any.
+    pub fn switch(&self, group_id: GroupId) -> Option<&GroupSwitch> {
+        if group_id == ROOT_GROUP_ID {
+            return None;
+        }
+        self.switches.iter().find(|s| s.group_id == group_id)
+    }
+
+    /// Resolve group IDs by matching switch names against top-level groups.
+    ///
+    /// Called by the generated `set_group_ids()` once the hierarchy is known,
+    /// so switches follow the runtime IDs even with deeply nested groups.
+    pub fn bind_groups(&mut self, groups: &[GroupInfo]) {
+        for switch in &mut self.switches {
+            let group_id = groups
+                .iter()
+                .find(|g| g.id != ROOT_GROUP_ID && g.parent_id == ROOT_GROUP_ID && g.name == switch.group_name)
+                .map_or(ROOT_GROUP_ID, |g| g.id);
+            switch.group_id = group_id;
+            switch.bypass.set_group_id(group_id);
+            switch.solo.set_group_id(group_id);
+        }
+    }
+}
+
+impl ParameterGroups for GroupControls {
+    fn is_group_bypassed(&self, group_id: GroupId) -> bool {
+        self.switch(group_id).is_some_and(|s| s.bypass.get())
+    }
+
+    fn is_group_soloed(&self, group_id: GroupId) -> bool {
+        self.switch(group_id).is_some_and(|s| s.solo.get())
+    }
+
+    fn any_group_soloed(&self) -> bool {
+        self.switches.iter().any(|s| s.solo.get())
+    }
+}
+
+impl Parameters for GroupControls {
+    fn count(&self) -> usize {
+        self.switches.len() * 2
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn ParameterRef> + '_> {
+        Box::new(self.switches.iter().flat_map(|s| {
+            [&s.bypass as &dyn ParameterRef, &s.solo as &dyn ParameterRef]
+        }))
+    }
+
+    fn by_id(&self, id: ParameterId) -> Option<&dyn ParameterRef> {
+        self.iter().find(|p| p.id() == id)
+    }
+
+    fn save_state_prefixed(&self, data: &mut Vec<u8>, prefix: &str) {
+        for parameter in self.iter() {
+            let string_id = parameter.info().string_id;
+            let path = if prefix.is_empty() {
+                string_id.to_string()
+            } else {
+                format!("{}/{}", prefix, string_id)
+            };
+            let path_bytes = path.as_bytes();
+            data.push(path_bytes.len() as u8);
+            data.extend_from_slice(path_bytes);
+            data.extend_from_slice(&parameter.get_normalized().to_le_bytes());
+        }
+    }
+
+    fn load_state_path(&mut self, path: &str, value: f64) -> bool {
+        match self.iter().find(|p| p.info().string_id == path) {
+            Some(parameter) => {
+                parameter.set_normalized(value.clamp(0.0, 1.0));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controls() -> GroupControls {
+        let mut controls = GroupControls::new(vec![
+            GroupSwitch::new("Low", "Low Bypass", "Low Solo", "Low.bypass", "Low.solo"),
+            GroupSwitch::new("High", "High Bypass", "High Solo", "High.bypass", "High.solo"),
+        ]);
+        controls.bind_groups(&[
+            GroupInfo::root(),
+            GroupInfo::new(1, "Low", ROOT_GROUP_ID),
+            GroupInfo::new(2, "Filter", 1),
+            GroupInfo::new(3, "High", ROOT_GROUP_ID),
+        ]);
+        controls
+    }
+
+    #[test]
+    fn binds_switches_to_top_level_groups() {
+        let controls = controls();
+        assert_eq!(controls.switches()[0].group_id(), 1);
+        assert_eq!(controls.switches()[1].group_id(), 3);
+        assert_eq!(controls.switches()[1].solo().info().group_id, 3);
+        assert_eq!(controls.count(), 4);
+        assert!(controls.by_id(fnv1a_hash("High.solo")).is_some());
+    }
+
+    #[test]
+    fn solo_silences_other_groups() {
+        let controls = controls();
+        assert!(controls.is_group_active(1));
+        assert!(controls.is_group_active(3));
+
+        controls.switches()[1].solo().set(true);
+        assert!(!controls.is_group_active(1));
+        assert!(controls.is_group_active(3));
+
+        controls.switches()[1].bypass().set(true);
+        assert!(!controls.is_group_active(3));
+    }
+
+    #[test]
+    fn state_paths_use_string_ids() {
+        let mut controls = controls();
+        controls.switches()[0].bypass().set(true);
+
+        let mut data = Vec::new();
+        controls.save_state_prefixed(&mut data, "bands");
+        assert_eq!(data[0] as usize, "bands/Low.bypass".len());
+        assert_eq!(&data[1..17], b"bands/Low.bypass");
+
+        assert!(controls.load_state_path("High.solo", 1.0));
+        assert!(!controls.load_state_path("Mid.solo", 1.0));
+        assert!(controls.switches()[1].solo().get());
+    }
+}
@@ -1,6 +1,15 @@
 //! GUI-related traits.
+//!
+//! The editor is a WebView by default. Plugins that draw their own GUI
+//! (e.g. with `beamer-egui`) return [`GuiBackend::Native`] from
+//! [`Descriptor::editor()`](crate::Descriptor::editor); the wrappers then
+//! hand the host's parent window to a [`NativeEditor`] instead.
 
-use crate::types::Size;
+use std::ffi::c_void;
+use std::sync::Arc;
+
+use crate::parameter_store::ParameterStore;
+use crate::types::{ParameterId, ParameterValue, Size};
 
 /// Size constraints for the plugin GUI.
 #[derive(Debug, Clone, Copy)]
@@ -23,6 +32,46 @@ impl Default for GuiConstraints {
     }
 }
 
+/// Name of the main editor view, as requested by hosts via `createView("editor")`.
+pub const EDITOR_VIEW: &str = "editor";
+
+/// Entry page of the main editor view within the web assets.
+pub const EDITOR_PAGE: &str = "index.html";
+
+/// A named editor view.
+///
+/// Besides the main [`EDITOR_VIEW`], plugins can declare additional views
+/// (e.g. a compact "mini" meter window) that load a different entry page
+/// from the same web assets. Declared via `[[gui_views]]` in `Config.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuiView {
+    /// View name passed by the host (e.g. "mini").
+    pub name: &'static str,
+    /// Entry page within the web assets (e.g. "mini.html").
+    pub page: &'static str,
+    /// Initial width in pixels.
+    pub width: u32,
+    /// Initial height in pixels.
+    pub height: u32,
+}
+
+impl GuiView {
+    /// Create a view description.
+    pub const fn new(name: &'static str, page: &'static str, width: u32, height: u32) -> Self {
+        Self {
+            name,
+            page,
+            width,
+            height,
+        }
+    }
+
+    /// Initial size of the view.
+    pub const fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
 /// Trait for plugin GUI callbacks.
 ///
 /// Implement this trait to provide GUI-related configuration and callbacks.
@@ -58,9 +107,139 @@ pub trait GuiDelegate: Send + Sync {
     fn gui_resized(&mut self, _new_size: Size) {}
 }
 
+/// Native parent window handed to a [`NativeEditor`] by the wrapper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParentWindow {
+    /// `NSView*` (macOS).
+    AppKit(*mut c_void),
+    /// `HWND` (Windows).
+    Win32(*mut c_void),
+    /// X11 window ID (Linux).
+    X11(u64),
+}
+
+/// The wrapper side of a [`NativeEditor`]: parameter access and host
+/// notification.
+///
+/// GUI edits go through the same path as WebView edits (constraints,
+/// automation preview, parameter log, host notification). All methods run
+/// on the UI thread.
+pub trait EditorHost {
+    /// Call `f` with the plugin's parameters, to read values and format
+    /// display text.
+    fn with_parameters(&self, f: &mut dyn FnMut(&dyn ParameterStore));
+
+    /// Start an edit gesture (mouse down on a control).
+    fn begin_edit(&self, id: ParameterId);
+
+    /// Set a parameter from the GUI and tell the host. Call between
+    /// [`begin_edit()`](Self::begin_edit) and [`end_edit()`](Self::end_edit).
+    fn set_parameter(&self, id: ParameterId, normalized: ParameterValue);
+
+    /// End an edit gesture.
+    fn end_edit(&self, id: ParameterId);
+}
+
+/// An editor that draws into a native child window instead of a WebView.
+///
+/// The wrapper creates one per editor view through the factory in
+/// [`GuiBackend::Native`] and drives it on the UI thread. The
+/// [`GuiDelegate`] methods supply its size and constraints and are called
+/// on open, close and resize as for WebView editors.
+pub trait NativeEditor: GuiDelegate {
+    /// Create the editor's child window in `parent`, sized to
+    /// [`gui_size()`](GuiDelegate::gui_size). Returns `false` if it could
+    /// not be created.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be a valid window of the current platform that stays
+    /// alive until [`close()`](Self::close).
+    unsafe fn open(&mut self, parent: ParentWindow, host: Box<dyn EditorHost>) -> bool;
+
+    /// Destroy the child window. The editor may be opened again.
+    fn close(&mut self);
+
+    /// Called about 60 times a second while open, by wrappers on platforms
+    /// where the host's run loop drives editors (VST3 on Linux). Editors
+    /// that run their own timer ignore it.
+    fn idle(&mut self) {}
+}
+
+/// Creates a [`NativeEditor`] for each editor view the host opens.
+pub type NativeEditorFactory = Arc<dyn Fn() -> Box<dyn NativeEditor> + Send + Sync>;
+
+/// GUI backend of a plugin, from [`Descriptor::editor()`](crate::Descriptor::editor).
+#[derive(Clone, Default)]
+pub enum GuiBackend {
+    /// WebView editor loading `gui_assets` or `gui_url` from `Config.toml`.
+    #[default]
+    WebView,
+    /// Native editor drawn by the plugin. Only the main [`EDITOR_VIEW`]
+    /// is available; additional views are WebView pages.
+    Native(NativeEditorFactory),
+}
+
+impl GuiBackend {
+    /// A native editor created by `create` for each editor view.
+    pub fn native<E: NativeEditor + 'static>(create: impl Fn() -> E + Send + Sync + 'static) -> Self {
+        Self::Native(Arc::new(move || Box::new(create()) as Box<dyn NativeEditor>))
+    }
+
+    /// Create a native editor, or `None` for the WebView backend.
+    pub fn create_native(&self) -> Option<Box<dyn NativeEditor>> {
+        match self {
+            Self::WebView => None,
+            Self::Native(create) => Some(create()),
+        }
+    }
+}
+
+impl std::fmt::Debug for GuiBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WebView => f.write_str("WebView"),
+            Self::Native(_) => f.write_str("Native"),
+        }
+    }
+}
+
 /// Trait for plugins that don't need a GUI.
 ///
 /// Implement this for plugins that don't have a GUI. This is the default
 /// for the basic `Processor` trait, but can be explicitly implemented
 /// to opt out of GUI support.
 pub trait NoGui {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Editor;
+
+    impl GuiDelegate for Editor {
+        fn gui_size(&self) -> Size {
+            Size::new(320, 200)
+        }
+    }
+
+    impl NativeEditor for Editor {
+        unsafe fn open(&mut self, _parent: ParentWindow, _host: Box<dyn EditorHost>) -> bool {
+            true
+        }
+
+        fn close(&mut self) {}
+    }
+
+    #[test]
+    fn test_native_backend_creates_an_editor_per_view() {
+        assert!(GuiBackend::default().create_native().is_none());
+
+        let backend = GuiBackend::native(|| Editor);
+        let first = backend.create_native().unwrap();
+        let second = backend.clone().create_native().unwrap();
+        assert_eq!(first.gui_size(), Size::new(320, 200));
+        assert_eq!(second.gui_constraints().resizable, GuiConstraints::default().resizable);
+        assert_eq!(format!("{backend:?}"), "Native");
+    }
+}
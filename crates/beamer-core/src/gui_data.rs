@@ -0,0 +1,498 @@
+//! Streams of analysis frames from the audio thread to the WebView.
+//!
+//! [`FrameMeters`](crate::FrameMeters) hold one value per slot, which suits
+//! level meters but not a spectrum, a waveform snippet or any other frame
+//! the GUI must not miss. A [`GuiDataChannel`] is a lock-free ring buffer
+//! the processor pushes whole frames into; the wrappers drain every channel
+//! from their GUI sync timer at the rate set on [`GuiDataChannels`] and
+//! deliver the frames as one `audio-data` event.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::{GuiDataChannel, GuiDataChannels};
+//!
+//! impl Default for MyDescriptor {
+//!     fn default() -> Self {
+//!         let spectrum = GuiDataChannel::<[f32; 64]>::new(8);
+//!         Self {
+//!             gui_data: GuiDataChannels::new().with_fps(30.0).with_channel("spectrum", &spectrum),
+//!             spectrum,
+//!             ..
+//!         }
+//!     }
+//! }
+//!
+//! impl Descriptor for MyDescriptor {
+//!     fn gui_data(&self) -> GuiDataChannels {
+//!         self.gui_data.clone() // and pass `spectrum` to the processor in prepare()
+//!     }
+//! }
+//!
+//! // In process(), once a new analysis frame is ready:
+//! self.spectrum.push(self.analyzer.bands());
+//! ```
+//!
+//! ```javascript
+//! __BEAMER__.on("audio-data", (data) => {
+//!   const frames = data.spectrum ?? [];
+//!   if (frames.length > 0) drawSpectrum(frames[frames.length - 1]);
+//! });
+//! ```
+//!
+//! The event carries every channel that received frames since the previous
+//! event, each as an array of frames, oldest first.
+//!
+//! # Real-Time Safety
+//!
+//! [`GuiDataChannel::push()`] never locks or allocates. When the GUI falls
+//! behind (or is closed) and the ring is full, new frames are dropped and
+//! `push()` returns false. Frame types implement [`GuiFrame`], which writes
+//! them as JSON on the main thread.
+
+use std::cell::UnsafeCell;
+use std::fmt::Write;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Event that delivers the channels' frames to `__BEAMER__.on()`.
+pub const AUDIO_DATA_EVENT: &str = "audio-data";
+
+/// Default delivery rate of [`GuiDataChannels`], in frames per second.
+pub const DEFAULT_GUI_DATA_FPS: f64 = 30.0;
+
+// =============================================================================
+// Frames
+// =============================================================================
+
+/// A frame type that can be sent through a [`GuiDataChannel`].
+///
+/// Implemented for numbers, `bool`, arrays of any length and pairs and
+/// triples of frames. Implement it for a struct by writing a JSON object:
+///
+/// ```ignore
+/// impl GuiFrame for Reduction {
+///     fn write_json(&self, json: &mut String) {
+///         json.push_str("{\"db\":");
+///         self.db.write_json(json);
+///         json.push('}');
+///     }
+/// }
+/// ```
+pub trait GuiFrame: Send + 'static {
+    /// Append the frame as JSON. Called on the main thread.
+    fn write_json(&self, json: &mut String);
+}
+
+macro_rules! float_frame {
+    ($($t:ty),*) => {$(
+        impl GuiFrame for $t {
+            /// Non-finite values are written as `0`, since JSON has no
+            /// representation for them.
+            fn write_json(&self, json: &mut String) {
+                let value = if self.is_finite() { *self } else { 0.0 };
+                let _ = write!(json, "{value}");
+            }
+        }
+    )*};
+}
+
+macro_rules! integer_frame {
+    ($($t:ty),*) => {$(
+        impl GuiFrame for $t {
+            fn write_json(&self, json: &mut String) {
+                let _ = write!(json, "{self}");
+            }
+        }
+    )*};
+}
+
+float_frame!(f32, f64);
+integer_frame!(i32, u32, i64, u64, usize, bool);
+
+impl<T: GuiFrame, const N: usize> GuiFrame for [T; N] {
+    fn write_json(&self, json: &mut String) {
+        json.push('[');
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            value.write_json(json);
+        }
+        json.push(']');
+    }
+}
+
+impl<A: GuiFrame, B: GuiFrame> GuiFrame for (A, B) {
+    fn write_json(&self, json: &mut String) {
+        write_list(&[&self.0, &self.1], json);
+    }
+}
+
+impl<A: GuiFrame, B: GuiFrame, C: GuiFrame> GuiFrame for (A, B, C) {
+    fn write_json(&self, json: &mut String) {
+        write_list(&[&self.0, &self.1, &self.2], json);
+    }
+}
+
+/// Write tuple members as a JSON array.
+fn write_list(values: &[&dyn GuiFrame], json: &mut String) {
+    json.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        value.write_json(json);
+    }
+    json.push(']');
+}
+
+// =============================================================================
+// Channel
+// =============================================================================
+
+struct Slot<T> {
+    /// Position of the push that may write this slot next; one more once
+    /// that push completed.
+    sequence: AtomicUsize,
+    frame: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Bounded lock-free queue (Vyukov's MPMC design), so clones of a channel
+/// can push and pop from any thread. Also carries
+/// [`CommandQueue`](crate::command::CommandQueue)s.
+pub(crate) struct Ring<T> {
+    slots: Box<[Slot<T>]>,
+    mask: usize,
+    push_position: AtomicUsize,
+    pop_position: AtomicUsize,
+}
+
+// SAFETY: A slot's frame is only accessed by the one push or pop that
+// claimed its position; the sequence number hands it over with
+// release/acquire ordering. Frames move between threads, so T must be Send.
+unsafe impl<T: Send> Sync for Ring<T> {}
+// SAFETY: See above.
+unsafe impl<T: Send> Send for Ring<T> {}
+
+impl<T> Ring<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(2).next_power_of_two();
+        Self {
+            slots: (0..capacity)
+                .map(|i| Slot { sequence: AtomicUsize::new(i), frame: UnsafeCell::new(MaybeUninit::uninit()) })
+                .collect(),
+            mask: capacity - 1,
+            push_position: AtomicUsize::new(0),
+            pop_position: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Queue `frame`, or hand it back if the ring is full.
+    pub(crate) fn push(&self, frame: T) -> Result<(), T> {
+        let mut position = self.push_position.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            match (sequence as isize).wrapping_sub(position as isize) {
+                0 => match self.push_position.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: The successful exchange claimed this slot
+                        // for this push alone, and its frame was popped (or
+                        // never written).
+                        unsafe { (*slot.frame.get()).write(frame) };
+                        slot.sequence.store(position.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => position = current,
+                },
+                // The slot still holds a frame from one lap ago: full.
+                diff if diff < 0 => return Err(frame),
+                _ => position = self.push_position.load(Ordering::Relaxed),
+            }
+        }
+    }
+
+    pub(crate) fn pop(&self) -> Option<T> {
+        let mut position = self.pop_position.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            match (sequence as isize).wrapping_sub(position.wrapping_add(1) as isize) {
+                0 => match self.pop_position.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: The successful exchange claimed this slot
+                        // for this pop alone, and the acquire load above saw
+                        // its push complete.
+                        let frame = unsafe { (*slot.frame.get()).assume_init_read() };
+                        slot.sequence
+                            .store(position.wrapping_add(self.mask + 1), Ordering::Release);
+                        return Some(frame);
+                    }
+                    Err(current) => position = current,
+                },
+                // Nothing pushed at this position yet: empty.
+                diff if diff < 0 => return None,
+                _ => position = self.pop_position.load(Ordering::Relaxed),
+            }
+        }
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// A lock-free queue of frames from the audio thread to the GUI.
+///
+/// Cloning is cheap (reference counted): the processor pushes into one
+/// clone and [`GuiDataChannels`] drains another.
+pub struct GuiDataChannel<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> Clone for GuiDataChannel<T> {
+    fn clone(&self) -> Self {
+        Self { ring: Arc::clone(&self.ring) }
+    }
+}
+
+impl<T> std::fmt::Debug for GuiDataChannel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuiDataChannel").field("capacity", &self.ring.slots.len()).finish()
+    }
+}
+
+impl<T: GuiFrame> GuiDataChannel<T> {
+    /// Create a channel holding up to `capacity` undelivered frames
+    /// (rounded up to a power of two, at least 2). Allocates.
+    ///
+    /// Size it for the frames produced between two deliveries, with room
+    /// for a late timer tick.
+    pub fn new(capacity: usize) -> Self {
+        Self { ring: Arc::new(Ring::new(capacity)) }
+    }
+
+    /// Queue a frame. Returns false (and drops the frame) if the channel
+    /// is full.
+    ///
+    /// Real-time safe.
+    #[inline]
+    pub fn push(&self, frame: T) -> bool {
+        self.ring.push(frame).is_ok()
+    }
+
+    /// Take the oldest undelivered frame.
+    pub fn pop(&self) -> Option<T> {
+        self.ring.pop()
+    }
+
+    /// Maximum number of undelivered frames.
+    pub fn capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+}
+
+/// A channel with its frame type erased, as [`GuiDataChannels`] holds it.
+trait ErasedChannel: Send + Sync {
+    /// Append the undelivered frames as a JSON array, at most one ring's
+    /// worth. Returns false (writing nothing) if there are none.
+    fn drain_json(&self, json: &mut String) -> bool;
+}
+
+impl<T: GuiFrame> ErasedChannel for Ring<T> {
+    fn drain_json(&self, json: &mut String) -> bool {
+        let mut count = 0;
+        while count < self.slots.len() {
+            let Some(frame) = self.pop() else { break };
+            json.push(if count == 0 { '[' } else { ',' });
+            frame.write_json(json);
+            count += 1;
+        }
+        if count > 0 {
+            json.push(']');
+        }
+        count > 0
+    }
+}
+
+// =============================================================================
+// Channels
+// =============================================================================
+
+/// The named channels the wrappers deliver to the WebView.
+///
+/// Cloning is cheap (reference counted). Add the channels right after
+/// [`new()`](Self::new), before cloning. The default has no channels, and
+/// the wrappers then send nothing.
+#[derive(Clone)]
+pub struct GuiDataChannels {
+    channels: Arc<Vec<(&'static str, Arc<dyn ErasedChannel>)>>,
+    interval: Duration,
+    /// When the last event was delivered (main thread only).
+    last_delivery: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Default for GuiDataChannels {
+    fn default() -> Self {
+        Self {
+            channels: Arc::default(),
+            interval: Duration::from_secs_f64(1.0 / DEFAULT_GUI_DATA_FPS),
+            last_delivery: Arc::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for GuiDataChannels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuiDataChannels")
+            .field("channels", &self.channels.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl GuiDataChannels {
+    /// Create a set without channels, delivered at [`DEFAULT_GUI_DATA_FPS`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver at most `fps` events per second. The wrappers' sync timers
+    /// run at 60 Hz, which caps the rate.
+    pub fn with_fps(mut self, fps: f64) -> Self {
+        let fps = if fps.is_finite() { fps.clamp(0.1, 1000.0) } else { DEFAULT_GUI_DATA_FPS };
+        self.interval = Duration::from_secs_f64(1.0 / fps);
+        self
+    }
+
+    /// Deliver `channel`'s frames under `name`. Keep a clone of the channel
+    /// to push into.
+    pub fn with_channel<T: GuiFrame>(mut self, name: &'static str, channel: &GuiDataChannel<T>) -> Self {
+        match Arc::get_mut(&mut self.channels) {
+            Some(channels) => channels.push((name, channel.ring.clone() as Arc<dyn ErasedChannel>)),
+            None => log::warn!("GuiDataChannels::with_channel() called on shared channels"),
+        }
+        self
+    }
+
+    /// Number of channels.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Whether there are no channels.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Drain every channel into a JSON object (`{"spectrum":[[...],...]}`),
+    /// or `None` if no channel has frames. Channels without frames are left
+    /// out.
+    pub fn take_json(&self) -> Option<String> {
+        let mut json = String::from("{");
+        for (name, channel) in self.channels.iter() {
+            let start = json.len();
+            if json.len() > 1 {
+                json.push(',');
+            }
+            let _ = write!(json, "{}:", serde_json::to_string(name).unwrap_or_default());
+            if !channel.drain_json(&mut json) {
+                json.truncate(start);
+            }
+        }
+        (json.len() > 1).then(|| json + "}")
+    }
+
+    /// Script that delivers the frames as an [`AUDIO_DATA_EVENT`], or
+    /// `None` if the next delivery isn't due or there are no frames.
+    ///
+    /// Called by the wrappers from their GUI sync timer.
+    pub fn take_script(&self) -> Option<String> {
+        self.take_script_at(Instant::now())
+    }
+
+    fn take_script_at(&self, now: Instant) -> Option<String> {
+        if self.channels.is_empty() {
+            return None;
+        }
+        let mut last_delivery = self.last_delivery.lock().unwrap_or_else(|e| e.into_inner());
+        // Allow a quarter interval of timer jitter, so 30 fps on a 60 Hz
+        // timer sends every second tick.
+        let due = last_delivery.is_none_or(|last| now.duration_since(last) >= self.interval * 3 / 4);
+        if !due {
+            return None;
+        }
+        let json = self.take_json()?;
+        *last_delivery = Some(now);
+        Some(format!(
+            "window.__BEAMER__._onEvent({},{json})",
+            serde_json::to_string(AUDIO_DATA_EVENT).unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_arrive_in_order_and_full_rings_drop() {
+        let channel = GuiDataChannel::<[f32; 2]>::new(3);
+        assert_eq!(channel.capacity(), 4);
+        for i in 0..5 {
+            assert_eq!(channel.push([i as f32, f32::NAN]), i < 4);
+        }
+        let levels = GuiDataChannel::<(u32, bool)>::new(2);
+        levels.push((7, true));
+        let channels = GuiDataChannels::new()
+            .with_channel("spectrum", &channel)
+            .with_channel("empty", &GuiDataChannel::<f64>::new(2))
+            .with_channel("levels", &levels);
+
+        assert_eq!(
+            channels.take_json().unwrap(),
+            r#"{"spectrum":[[0,0],[1,0],[2,0],[3,0]],"levels":[[7,true]]}"#
+        );
+        assert_eq!(channels.take_json(), None);
+        assert!(channel.push([4.0, 0.5]));
+        assert_eq!(channel.pop(), Some([4.0, 0.5]));
+        assert_eq!(GuiDataChannels::default().take_script(), None);
+    }
+
+    #[test]
+    fn delivery_is_paced() {
+        let channel = GuiDataChannel::<f32>::new(8);
+        let channels = GuiDataChannels::new().with_fps(20.0).with_channel("rms", &channel);
+        let start = Instant::now();
+        assert_eq!(channels.take_script_at(start), None); // nothing pushed
+
+        channel.push(0.5);
+        assert_eq!(
+            channels.take_script_at(start).unwrap(),
+            r#"window.__BEAMER__._onEvent("audio-data",{"rms":[0.5]})"#
+        );
+        channel.push(0.25);
+        assert_eq!(channels.take_script_at(start + Duration::from_millis(16)), None);
+        assert!(channels.take_script_at(start + Duration::from_millis(50)).is_some());
+        assert_eq!(channel.pop(), None);
+    }
+}
@@ -0,0 +1,254 @@
+//! Requests from the plugin to the host.
+//!
+//! Hosts mark a project as modified when a parameter changes. State that
+//! lives outside the parameters (a loaded sample, a recorded loop, an edited
+//! wavetable) changes without the host noticing, and closing the project
+//! loses it without a save prompt. [`HostServices::mark_state_dirty()`]
+//! tells the host.
+//!
+//! # Enabling
+//!
+//! The descriptor creates a [`HostServices`], keeps a clone for the plugin
+//! code and returns it from
+//! [`Descriptor::host_services()`](crate::Descriptor::host_services):
+//!
+//! ```ignore
+//! fn host_services(&self) -> HostServices {
+//!     self.host.clone()
+//! }
+//! ```
+//!
+//! # Host Integration
+//!
+//! [`mark_state_dirty()`](HostServices::mark_state_dirty) only sets a flag,
+//! so it may be called from any thread, including the audio thread. The
+//! wrapper forwards it from the main thread:
+//!
+//! | Format | Notification |
+//! |--------|--------------|
+//! | VST3 | `IComponentHandler2::setDirty(true)` |
+//! | AUv2 | Property change of `kAudioUnitProperty_ClassInfo` |
+//! | AUv3 | KVO change of `fullState` |
+//!
+//! The wrapper checks the flag from the GUI sync timer while the editor is
+//! open, and on VST3 also after host parameter edits. A state saved by the host in the
+//! meantime already contains the change, so saving clears a pending mark.
+//!
+//! # Latency Changes
+//!
+//! Hosts read [`Processor::latency_samples()`](crate::Processor::latency_samples)
+//! when the plugin is activated. A plugin whose latency changes while
+//! running (a lookahead time, a linear-phase mode) calls
+//! [`latency_changed()`](HostServices::latency_changed) afterwards, and the
+//! wrapper asks the host to read it again:
+//!
+//! | Format | Notification |
+//! |--------|--------------|
+//! | VST3 | `IComponentHandler::restartComponent(kLatencyChanged)` |
+//! | AUv2 | Property change of `kAudioUnitProperty_Latency` |
+//! | AUv3 | KVO change of `latency` |
+//!
+//! Like dirty marks, it only sets a flag and is forwarded from the main
+//! thread. Most hosts deactivate and reactivate the plugin to apply the new
+//! delay compensation.
+//!
+//! # Instance Names
+//!
+//! Hosts that name plugin instances after their track or channel pass the
+//! name to the wrapper, and [`instance_name()`](HostServices::instance_name)
+//! returns it. The plugin can in turn suggest a display name, e.g. the
+//! loaded preset, with
+//! [`suggest_display_name()`](HostServices::suggest_display_name):
+//!
+//! | Format | Instance name | Display name |
+//! |--------|---------------|--------------|
+//! | VST3 | `IInfoListener` channel name | Not supported (GUI only) |
+//! | AUv2 | `kAudioUnitProperty_ContextName` | `kAudioUnitProperty_NickName` |
+//! | AUv3 | `contextName` | `audioUnitShortName` (KVO) |
+//!
+//! Both are also available to the GUI through the [`INSTANCE_NAME_INVOKE`]
+//! and [`SET_DISPLAY_NAME_INVOKE`] built-in invokes.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Built-in WebView invoke method returning
+/// `{ instanceName, displayName }` (either may be `null`).
+pub const INSTANCE_NAME_INVOKE: &str = "_beamer/instanceName";
+
+/// Built-in WebView invoke method that suggests (or clears, with `null`) the
+/// display name.
+pub const SET_DISPLAY_NAME_INVOKE: &str = "_beamer/setDisplayName";
+
+/// Shared handle for host notifications.
+///
+/// Cheap to clone; all clones share one set of pending requests.
+#[derive(Clone, Debug, Default)]
+pub struct HostServices {
+    inner: Arc<HostServicesInner>,
+}
+
+#[derive(Debug, Default)]
+struct HostServicesInner {
+    state_dirty: AtomicBool,
+    latency_changed: AtomicBool,
+    instance_name: Mutex<Option<String>>,
+    display_name: Mutex<Option<String>>,
+    display_name_changed: AtomicBool,
+}
+
+fn lock(name: &Mutex<Option<String>>) -> std::sync::MutexGuard<'_, Option<String>> {
+    name.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl HostServices {
+    /// Create a handle with no pending requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tell the host that the plugin state changed outside the parameters,
+    /// so the project needs saving.
+    ///
+    /// Real-time safe. Repeated calls before the wrapper forwards the mark
+    /// result in one notification.
+    pub fn mark_state_dirty(&self) {
+        self.inner.state_dirty.store(true, Ordering::Release);
+    }
+
+    /// Returns true once after [`mark_state_dirty()`](Self::mark_state_dirty).
+    ///
+    /// Called by the wrapper.
+    pub fn take_state_dirty(&self) -> bool {
+        self.inner.state_dirty.swap(false, Ordering::AcqRel)
+    }
+
+    /// Tell the host that [`Processor::latency_samples()`](crate::Processor::latency_samples)
+    /// now returns a different value.
+    ///
+    /// Real-time safe. Call it after the new latency is in effect; repeated
+    /// calls before the wrapper forwards the change result in one
+    /// notification.
+    pub fn latency_changed(&self) {
+        self.inner.latency_changed.store(true, Ordering::Release);
+    }
+
+    /// Returns true once after [`latency_changed()`](Self::latency_changed).
+    ///
+    /// Called by the wrapper.
+    pub fn take_latency_changed(&self) -> bool {
+        self.inner.latency_changed.swap(false, Ordering::AcqRel)
+    }
+
+    /// The name the host gave this instance, usually its track or channel.
+    ///
+    /// `None` until the host provides one, and always on hosts that don't.
+    pub fn instance_name(&self) -> Option<String> {
+        lock(&self.inner.instance_name).clone()
+    }
+
+    /// Store the host-assigned instance name. Called by the wrapper.
+    pub fn set_instance_name(&self, name: Option<&str>) {
+        *lock(&self.inner.instance_name) = name.filter(|n| !n.is_empty()).map(str::to_owned);
+    }
+
+    /// Suggest a name for the host to display for this instance, e.g. the
+    /// loaded preset. `None` returns to the plugin's name.
+    ///
+    /// Not real-time safe (allocates). Hosts are free to ignore it, and VST3
+    /// has no way to pass it on.
+    pub fn suggest_display_name(&self, name: Option<&str>) {
+        let name = name.filter(|n| !n.is_empty()).map(str::to_owned);
+        let mut current = lock(&self.inner.display_name);
+        if *current != name {
+            *current = name;
+            self.inner.display_name_changed.store(true, Ordering::Release);
+        }
+    }
+
+    /// The display name suggested by the plugin, if any.
+    pub fn display_name(&self) -> Option<String> {
+        lock(&self.inner.display_name).clone()
+    }
+
+    /// Returns true once after the suggested display name changed.
+    ///
+    /// Called by the wrapper.
+    pub fn take_display_name_changed(&self) -> bool {
+        self.inner.display_name_changed.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Handle the built-in instance name WebView calls.
+///
+/// Returns `None` if `method` is not one of the instance name invokes.
+pub fn host_services_invoke(
+    services: &HostServices,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    match method {
+        INSTANCE_NAME_INVOKE => Some(serde_json::json!({
+            "instanceName": services.instance_name(),
+            "displayName": services.display_name(),
+        })),
+        SET_DISPLAY_NAME_INVOKE => {
+            services.suggest_display_name(args.first().and_then(|v| v.as_str()));
+            Some(true.into())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_mark_is_shared_and_taken_once() {
+        let services = HostServices::new();
+        let plugin = services.clone();
+        assert!(!services.take_state_dirty());
+
+        plugin.mark_state_dirty();
+        plugin.mark_state_dirty();
+        assert!(services.take_state_dirty());
+        assert!(!services.take_state_dirty());
+    }
+
+    #[test]
+    fn latency_change_is_shared_and_taken_once() {
+        let services = HostServices::new();
+        let plugin = services.clone();
+        assert!(!services.take_latency_changed());
+
+        plugin.latency_changed();
+        plugin.latency_changed();
+        // Independent of the dirty mark
+        assert!(!services.take_state_dirty());
+        assert!(services.take_latency_changed());
+        assert!(!services.take_latency_changed());
+    }
+
+    #[test]
+    fn names_are_shared_and_display_changes_flagged() {
+        let services = HostServices::new();
+        let plugin = services.clone();
+        assert_eq!(plugin.instance_name(), None);
+        services.set_instance_name(Some("Lead Vox"));
+        assert_eq!(plugin.instance_name().as_deref(), Some("Lead Vox"));
+
+        plugin.suggest_display_name(Some("Warm Pad"));
+        assert!(services.take_display_name_changed());
+        plugin.suggest_display_name(Some("Warm Pad"));
+        assert!(!services.take_display_name_changed());
+
+        let args = [serde_json::Value::Null];
+        assert_eq!(host_services_invoke(&services, SET_DISPLAY_NAME_INVOKE, &args), Some(true.into()));
+        assert!(services.take_display_name_changed());
+        assert_eq!(
+            host_services_invoke(&services, INSTANCE_NAME_INVOKE, &[]),
+            Some(serde_json::json!({ "instanceName": "Lead Vox", "displayName": null }))
+        );
+    }
+}
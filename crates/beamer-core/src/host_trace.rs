@@ -0,0 +1,542 @@
+//! Diagnostic trace of host-to-plugin calls.
+//!
+//! Host-specific bugs usually come down to call order: a state restored
+//! before `setupProcessing`, a bus activated while processing, a parameter
+//! set from the wrong thread. With the `host-trace` feature the wrappers
+//! record every lifecycle, state, bus and parameter call with its arguments,
+//! so the sequence a host used can be reconstructed from the plugin log.
+//!
+//! # Enabling
+//!
+//! Enable the feature on the `beamer` dependency (`beamer-vst3` and
+//! `beamer-au` forward it too), rebuild, and turn on the file log:
+//!
+//! ```text
+//! beamer = { version = "...", features = ["host-trace"] }
+//!
+//! BEAMER_LOG=info
+//! ```
+//!
+//! Without the feature every function here is a no-op and the wrappers pay
+//! nothing beyond an inlined branch on a constant.
+//!
+//! # Output
+//!
+//! One log line per call, numbered across all plugin instances in the
+//! process:
+//!
+//! ```text
+//! #12 [2] +1532.104ms setupProcessing(sampleRate=48000, maxBlock=512, f32, realtime)
+//! #13 [2] +1532.390ms setState(8421 bytes)
+//! #14 [2] +1540.022ms processParameter(id=3, value=0.25, offset=128)
+//! ```
+//!
+//! `[2]` is the instance (see [`next_instance_id()`]). When calls arrive
+//! faster than they are written, the log notes how many were dropped.
+//!
+//! # Real-Time Safety
+//!
+//! [`record()`] is lock-free and allocation-free, so the audio thread can
+//! trace parameter changes delivered with processing. Writing the log
+//! ([`flush()`]) formats strings and takes a lock; [`trace()`] records and
+//! flushes and is meant for the host's main-thread calls. Audio-thread
+//! entries are written with the next main-thread call.
+
+use std::fmt;
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::types::ParameterId;
+
+/// Whether the crate was built with the `host-trace` feature.
+pub const ENABLED: bool = cfg!(feature = "host-trace");
+
+/// Number of calls kept until they are written to the log.
+pub const TRACE_CAPACITY: usize = 4096;
+
+static TRACE: OnceLock<TraceRing> = OnceLock::new();
+static NEXT_INSTANCE: AtomicU32 = AtomicU32::new(1);
+
+/// A host-to-plugin call with its arguments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HostCall {
+    /// A plugin instance was created.
+    Create,
+    /// The instance was destroyed.
+    Destroy,
+    /// VST3 `initialize()`.
+    Initialize,
+    /// VST3 `terminate()`.
+    Terminate,
+    /// VST3 `setupProcessing()`.
+    SetupProcessing {
+        /// Host sample rate in Hz.
+        sample_rate: f64,
+        /// Maximum block size in samples.
+        max_block_size: u32,
+        /// 64-bit processing.
+        double_precision: bool,
+        /// Offline (bounce) processing.
+        offline: bool,
+    },
+    /// AU `allocateRenderResources`.
+    AllocateRenderResources {
+        /// Host sample rate in Hz.
+        sample_rate: f64,
+        /// Maximum frames per render call.
+        max_frames: u32,
+    },
+    /// AU `deallocateRenderResources`.
+    DeallocateRenderResources,
+    /// VST3 `setActive()`.
+    SetActive(bool),
+    /// VST3 `setProcessing()`.
+    SetProcessing(bool),
+    /// AU `reset`.
+    Reset,
+    /// VST3 `activateBus()`.
+    ActivateBus {
+        /// Input (true) or output bus.
+        input: bool,
+        /// Bus index.
+        index: u32,
+        /// Activated (true) or deactivated.
+        active: bool,
+    },
+    /// VST3 `setBusArrangements()`, AU bus format changes.
+    SetBusArrangements {
+        /// Number of input buses.
+        inputs: u32,
+        /// Number of output buses.
+        outputs: u32,
+        /// Channels of the main input bus.
+        main_input_channels: u32,
+        /// Channels of the main output bus.
+        main_output_channels: u32,
+    },
+    /// A state restore.
+    SetState {
+        /// State size in bytes.
+        bytes: usize,
+    },
+    /// A state save.
+    GetState {
+        /// State size in bytes.
+        bytes: usize,
+    },
+    /// A parameter set outside processing (controller, generic editor).
+    SetParameter {
+        /// Parameter ID.
+        id: ParameterId,
+        /// Normalized value.
+        value: f64,
+    },
+    /// A parameter change delivered with a process/render call.
+    ProcessParameter {
+        /// Parameter ID.
+        id: ParameterId,
+        /// Normalized value.
+        value: f64,
+        /// Sample offset in the block.
+        sample_offset: u32,
+    },
+}
+
+impl HostCall {
+    /// Pack into a kind byte and two payload words.
+    fn encode(self) -> (u8, u64, u64) {
+        let pair = |low: u32, high: u32| low as u64 | (high as u64) << 32;
+        match self {
+            Self::Create => (0, 0, 0),
+            Self::Destroy => (1, 0, 0),
+            Self::Initialize => (2, 0, 0),
+            Self::Terminate => (3, 0, 0),
+            Self::SetupProcessing {
+                sample_rate,
+                max_block_size,
+                double_precision,
+                offline,
+            } => (
+                4,
+                sample_rate.to_bits(),
+                pair(max_block_size, double_precision as u32 | (offline as u32) << 1),
+            ),
+            Self::AllocateRenderResources { sample_rate, max_frames } => {
+                (5, sample_rate.to_bits(), max_frames as u64)
+            }
+            Self::DeallocateRenderResources => (6, 0, 0),
+            Self::SetActive(active) => (7, active as u64, 0),
+            Self::SetProcessing(processing) => (8, processing as u64, 0),
+            Self::Reset => (9, 0, 0),
+            Self::ActivateBus { input, index, active } => {
+                (10, pair(index, input as u32), active as u64)
+            }
+            Self::SetBusArrangements {
+                inputs,
+                outputs,
+                main_input_channels,
+                main_output_channels,
+            } => (
+                11,
+                pair(inputs, outputs),
+                pair(main_input_channels, main_output_channels),
+            ),
+            Self::SetState { bytes } => (12, bytes as u64, 0),
+            Self::GetState { bytes } => (13, bytes as u64, 0),
+            Self::SetParameter { id, value } => (14, value.to_bits(), id as u64),
+            Self::ProcessParameter {
+                id,
+                value,
+                sample_offset,
+            } => (15, value.to_bits(), pair(id, sample_offset)),
+        }
+    }
+
+    fn decode(kind: u8, a: u64, b: u64) -> Option<Self> {
+        let (low, high) = (b as u32, (b >> 32) as u32);
+        Some(match kind {
+            0 => Self::Create,
+            1 => Self::Destroy,
+            2 => Self::Initialize,
+            3 => Self::Terminate,
+            4 => Self::SetupProcessing {
+                sample_rate: f64::from_bits(a),
+                max_block_size: low,
+                double_precision: high & 1 != 0,
+                offline: high & 2 != 0,
+            },
+            5 => Self::AllocateRenderResources {
+                sample_rate: f64::from_bits(a),
+                max_frames: low,
+            },
+            6 => Self::DeallocateRenderResources,
+            7 => Self::SetActive(a != 0),
+            8 => Self::SetProcessing(a != 0),
+            9 => Self::Reset,
+            10 => Self::ActivateBus {
+                input: (a >> 32) != 0,
+                index: a as u32,
+                active: b != 0,
+            },
+            11 => Self::SetBusArrangements {
+                inputs: a as u32,
+                outputs: (a >> 32) as u32,
+                main_input_channels: low,
+                main_output_channels: high,
+            },
+            12 => Self::SetState { bytes: a as usize },
+            13 => Self::GetState { bytes: a as usize },
+            14 => Self::SetParameter {
+                id: low,
+                value: f64::from_bits(a),
+            },
+            15 => Self::ProcessParameter {
+                id: low,
+                value: f64::from_bits(a),
+                sample_offset: high,
+            },
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for HostCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |on: bool| if on { "true" } else { "false" };
+        match *self {
+            Self::Create => write!(f, "create()"),
+            Self::Destroy => write!(f, "destroy()"),
+            Self::Initialize => write!(f, "initialize()"),
+            Self::Terminate => write!(f, "terminate()"),
+            Self::SetupProcessing {
+                sample_rate,
+                max_block_size,
+                double_precision,
+                offline,
+            } => write!(
+                f,
+                "setupProcessing(sampleRate={sample_rate}, maxBlock={max_block_size}, {}, {})",
+                if double_precision { "f64" } else { "f32" },
+                if offline { "offline" } else { "realtime" },
+            ),
+            Self::AllocateRenderResources { sample_rate, max_frames } => write!(
+                f,
+                "allocateRenderResources(sampleRate={sample_rate}, maxFrames={max_frames})"
+            ),
+            Self::DeallocateRenderResources => write!(f, "deallocateRenderResources()"),
+            Self::SetActive(active) => write!(f, "setActive({})", on_off(active)),
+            Self::SetProcessing(processing) => write!(f, "setProcessing({})", on_off(processing)),
+            Self::Reset => write!(f, "reset()"),
+            Self::ActivateBus { input, index, active } => write!(
+                f,
+                "activateBus({} {index}, {})",
+                if input { "input" } else { "output" },
+                on_off(active),
+            ),
+            Self::SetBusArrangements {
+                inputs,
+                outputs,
+                main_input_channels,
+                main_output_channels,
+            } => write!(
+                f,
+                "setBusArrangements(inputs={inputs}, outputs={outputs}, main={main_input_channels}in/{main_output_channels}out)"
+            ),
+            Self::SetState { bytes } => write!(f, "setState({bytes} bytes)"),
+            Self::GetState { bytes } => write!(f, "getState({bytes} bytes)"),
+            Self::SetParameter { id, value } => {
+                write!(f, "setParameter(id={id}, value={value})")
+            }
+            Self::ProcessParameter {
+                id,
+                value,
+                sample_offset,
+            } => write!(f, "processParameter(id={id}, value={value}, offset={sample_offset})"),
+        }
+    }
+}
+
+/// One recorded call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HostCallEntry {
+    /// Sequence number across all instances, starting at 0.
+    pub sequence: u64,
+    /// Microseconds since tracing started.
+    pub micros: u64,
+    /// Instance that received the call.
+    pub instance: u32,
+    /// The call.
+    pub call: HostCall,
+}
+
+impl fmt::Display for HostCallEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} [{}] +{:.3}ms {}",
+            self.sequence,
+            self.instance,
+            self.micros as f64 / 1000.0,
+            self.call
+        )
+    }
+}
+
+/// Ring buffer slot, written under a per-slot sequence number.
+#[derive(Debug, Default)]
+struct Slot {
+    /// `2 * (n + 1)` once entry `n` is complete, odd while it is written.
+    sequence: AtomicU64,
+    micros: AtomicU64,
+    /// Instance in the low 32 bits, call kind in the next 8.
+    instance_kind: AtomicU64,
+    a: AtomicU64,
+    b: AtomicU64,
+}
+
+/// Lock-free ring of host calls.
+#[derive(Debug)]
+struct TraceRing {
+    slots: Box<[Slot]>,
+    /// Number of calls ever recorded.
+    head: AtomicU64,
+    /// Calls before this number were written to the log.
+    flushed: Mutex<u64>,
+    start: Instant,
+}
+
+impl TraceRing {
+    fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity.max(1)).map(|_| Slot::default()).collect(),
+            head: AtomicU64::new(0),
+            flushed: Mutex::new(0),
+            start: Instant::now(),
+        }
+    }
+
+    #[inline]
+    fn record(&self, instance: u32, call: HostCall) {
+        let (kind, a, b) = call.encode();
+        let n = self.head.fetch_add(1, Ordering::Relaxed);
+        let slot = &self.slots[(n % self.slots.len() as u64) as usize];
+
+        slot.sequence.store(2 * n + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        slot.micros
+            .store(self.start.elapsed().as_micros() as u64, Ordering::Relaxed);
+        slot.instance_kind
+            .store(instance as u64 | (kind as u64) << 32, Ordering::Relaxed);
+        slot.a.store(a, Ordering::Relaxed);
+        slot.b.store(b, Ordering::Relaxed);
+        slot.sequence.store(2 * (n + 1), Ordering::Release);
+    }
+
+    /// Entry `n`, or `None` if it was overwritten or is being written.
+    fn entry(&self, n: u64) -> Option<HostCallEntry> {
+        let slot = &self.slots[(n % self.slots.len() as u64) as usize];
+        let expected = 2 * (n + 1);
+        if slot.sequence.load(Ordering::Acquire) != expected {
+            return None;
+        }
+        let micros = slot.micros.load(Ordering::Relaxed);
+        let instance_kind = slot.instance_kind.load(Ordering::Relaxed);
+        let a = slot.a.load(Ordering::Relaxed);
+        let b = slot.b.load(Ordering::Relaxed);
+        fence(Ordering::Acquire);
+        if slot.sequence.load(Ordering::Relaxed) != expected {
+            return None;
+        }
+        Some(HostCallEntry {
+            sequence: n,
+            micros,
+            instance: instance_kind as u32,
+            call: HostCall::decode((instance_kind >> 32) as u8, a, b)?,
+        })
+    }
+
+    /// Hand entries not yet flushed to `write`, oldest first. Returns the
+    /// number of calls that were overwritten before they could be written.
+    fn flush(&self, mut write: impl FnMut(&HostCallEntry)) -> u64 {
+        let Ok(mut flushed) = self.flushed.lock() else {
+            return 0;
+        };
+        let head = self.head.load(Ordering::Acquire);
+        let oldest = head.saturating_sub(self.slots.len() as u64);
+        let mut dropped = oldest.saturating_sub(*flushed);
+        for n in (*flushed).max(oldest)..head {
+            match self.entry(n) {
+                Some(entry) => write(&entry),
+                None => dropped += 1,
+            }
+        }
+        *flushed = head;
+        dropped
+    }
+}
+
+/// Start tracing. Called by the wrappers when the plugin factory is created.
+///
+/// Allocates the ring buffer once per process; later calls do nothing.
+/// Without the `host-trace` feature nothing is allocated.
+pub fn init() {
+    if ENABLED {
+        TRACE.get_or_init(|| TraceRing::new(TRACE_CAPACITY));
+    }
+}
+
+/// Allocate an ID identifying a plugin instance in the trace.
+pub fn next_instance_id() -> u32 {
+    if ENABLED {
+        NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed)
+    } else {
+        0
+    }
+}
+
+/// Record a call without writing the log.
+///
+/// Real-time safe. Does nothing before [`init()`] or without the
+/// `host-trace` feature.
+#[inline]
+pub fn record(instance: u32, call: HostCall) {
+    if !ENABLED {
+        return;
+    }
+    if let Some(trace) = TRACE.get() {
+        trace.record(instance, call);
+    }
+}
+
+/// Write recorded calls to the log (`beamer_core::host_trace`, info level).
+///
+/// **Not audio-thread safe.**
+pub fn flush() {
+    if !ENABLED {
+        return;
+    }
+    let Some(trace) = TRACE.get() else {
+        return;
+    };
+    let dropped = trace.flush(|entry| log::info!("{entry}"));
+    if dropped > 0 {
+        log::warn!("{dropped} host calls dropped from the trace");
+    }
+}
+
+/// Record a main-thread call and write the log.
+///
+/// **Not audio-thread safe.** Use [`record()`] there.
+pub fn trace(instance: u32, call: HostCall) {
+    if !ENABLED {
+        return;
+    }
+    record(instance, call);
+    flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_round_trip_through_the_ring() {
+        let calls = [
+            HostCall::SetupProcessing {
+                sample_rate: 44100.0,
+                max_block_size: 512,
+                double_precision: true,
+                offline: false,
+            },
+            HostCall::ActivateBus {
+                input: true,
+                index: 1,
+                active: false,
+            },
+            HostCall::SetBusArrangements {
+                inputs: 2,
+                outputs: 1,
+                main_input_channels: 2,
+                main_output_channels: 6,
+            },
+            HostCall::ProcessParameter {
+                id: 7,
+                value: 0.25,
+                sample_offset: 64,
+            },
+        ];
+        let ring = TraceRing::new(8);
+        for call in calls {
+            ring.record(3, call);
+        }
+        let mut entries = Vec::new();
+        assert_eq!(ring.flush(|entry| entries.push(*entry)), 0);
+        let recorded: Vec<_> = entries.iter().map(|entry| entry.call).collect();
+        assert_eq!(recorded, calls);
+        assert!(entries.iter().all(|entry| entry.instance == 3));
+        assert_eq!(
+            entries[0].call.to_string(),
+            "setupProcessing(sampleRate=44100, maxBlock=512, f64, realtime)"
+        );
+
+        // Flushed entries aren't written twice
+        assert_eq!(ring.flush(|_| panic!("already flushed")), 0);
+    }
+
+    #[test]
+    fn overwritten_calls_are_counted_as_dropped() {
+        let ring = TraceRing::new(2);
+        for i in 0..5 {
+            ring.record(1, HostCall::SetParameter { id: i, value: 0.0 });
+        }
+        let mut ids = Vec::new();
+        let dropped = ring.flush(|entry| {
+            if let HostCall::SetParameter { id, .. } = entry.call {
+                ids.push(id);
+            }
+        });
+        assert_eq!(dropped, 3);
+        assert_eq!(ids, [3, 4]);
+    }
+}
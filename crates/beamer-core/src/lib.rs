@@ -25,52 +25,201 @@
 //! - [`MidiEvent`] - MIDI event types
 //! - [`Transport`] - DAW transport/timing state
 //! - [`ProcessContext`] - Processing context with sample rate and transport
+//!
+//! ## Features
+//!
+//! Everything the format wrappers use is always built. Optional subsystems
+//! are behind default-on features, so minimal plugins can leave them out:
+//!
+//! | Feature | Modules |
+//! |---------|---------|
+//! | `midi` | [`controller_feedback`], [`midi_effects`], [`note_gate`] |
+//! | `smoothing` | [`smoothing`], `FloatParameter::with_smoother()`, `with_auto_smoother()` |
+//! | `groups` | [`group_controls`] |
+//! | `presets` | [`preset_folders`], [`preset_bank`] |
+//! | `webview-bridge` | [`webview_handle`] and the `attach_webview()` methods |
+//! | `dsp` | [`fft`], [`filter`], [`measurement`], [`looper`], [`disk_stream`], [`sample_edit`], [`auto_gain`], [`modulation`], [`parallel`], [`crossover`], [`gain_matrix`] |
 
+pub mod activation;
+pub mod ambisonics;
 pub mod assets;
+#[cfg(feature = "dsp")]
+pub mod auto_gain;
+pub mod autosave;
+pub mod automation_preview;
 pub mod buffer;
 pub mod buffer_storage;
 pub mod bus_config;
 pub mod bypass;
+pub mod channel_mix;
+pub mod command;
+pub mod control_rate;
+#[cfg(feature = "midi")]
+pub mod controller_feedback;
+pub mod controller_pages;
 pub mod conversion_buffers;
 pub mod config;
+pub mod crash_guard;
+#[cfg(feature = "dsp")]
+pub mod crossover;
+pub mod describe;
+#[cfg(feature = "dsp")]
+pub mod disk_stream;
+pub mod graph;
+#[cfg(feature = "groups")]
+pub mod group_controls;
 pub mod gui;
+pub mod gui_data;
+pub mod host_services;
+pub mod host_trace;
 pub mod error;
+#[cfg(feature = "dsp")]
+pub mod fft;
+#[cfg(feature = "dsp")]
+pub mod filter;
+pub mod frame_meters;
+#[cfg(feature = "dsp")]
+pub mod gain_matrix;
+pub mod logging;
+#[cfg(feature = "dsp")]
+pub mod looper;
+pub mod memory_accounting;
+#[cfg(feature = "dsp")]
+pub mod measurement;
 pub mod midi;
 pub mod midi_cc_config;
 pub mod midi_cc_state;
+pub mod midi_clock;
+#[cfg(feature = "midi")]
+pub mod midi_effects;
+pub mod midi_monitor;
+pub mod midi_panic;
+pub mod midi_thru;
+pub mod mpe;
+#[cfg(feature = "midi")]
+pub mod note_gate;
+pub mod note_ranges;
+#[cfg(feature = "dsp")]
+pub mod modulation;
+pub mod notifications;
+pub mod oversampling;
+#[cfg(feature = "dsp")]
+pub mod parallel;
+pub mod parameter_constraints;
+pub mod parameter_exposure;
 pub mod parameter_format;
 pub mod parameter_groups;
 pub mod parameter_info;
+pub mod parameter_log;
 pub mod parameter_range;
 pub mod parameter_store;
 pub mod parameter_types;
+pub mod playhead;
 pub mod plugin;
 pub mod preset;
+pub mod preset_crossfade;
+#[cfg(feature = "presets")]
+pub mod preset_bank;
+#[cfg(feature = "presets")]
+pub mod preset_folders;
+pub mod preset_file;
+pub mod preset_names;
 pub mod process_context;
+pub mod processing_stats;
+pub mod quality;
+pub mod rate_dependent;
+pub mod response_curves;
 pub mod sample;
+#[cfg(feature = "dsp")]
+pub mod sample_edit;
+pub mod sample_rate_adapter;
+pub mod script;
 pub mod setup;
+#[cfg(feature = "smoothing")]
 pub mod smoothing;
+pub mod speaker_layout;
+pub mod split_block;
+pub mod standard_trims;
+pub mod state_hash;
+pub mod state_loading;
+pub mod stress;
 pub mod sysex_pool;
+pub mod tempo_sync;
+pub mod testing;
+pub mod transport_events;
 pub mod types;
+pub mod vectorscope;
+pub mod watchdog;
+#[cfg(feature = "webview-bridge")]
 pub mod webview_handle;
 pub mod webview_handler;
 
 // Re-exports for convenience
-pub use buffer::{AuxiliaryBuffers, AuxInput, AuxOutput, Buffer};
+pub use buffer::{
+    AuxFrame, AuxiliaryBuffers, AuxInput, AuxOutput, Buffer, FixedBuffer, Frames, InputFrame, OutputFrame,
+};
 pub use buffer_storage::ProcessBufferStorage;
-pub use bus_config::{CachedBusConfig, CachedBusInfo};
+pub use bus_config::{descriptor_snapshot, negotiate_bus_layout, CachedBusConfig, CachedBusInfo};
+pub use activation::{Activation, ActivationGate, ActivationPolicy, ActivationState};
+pub use ambisonics::{AmbisonicOrder, AmbisonicOrderParameter};
 pub use assets::{EmbeddedAsset, EmbeddedAssets};
-pub use config::{Config, FourCharCode};
+#[cfg(feature = "dsp")]
+pub use auto_gain::{AutoGain, AutoGainMeter, EnvelopeFollower, AUTO_GAIN_EVENT};
+pub use automation_preview::{AutomationPreview, AutomationState};
+pub use autosave::{Autosave, AutosaveRecovery, AUTOSAVE_EVENT};
+pub use command::{dispatch_commands, CommandHandler, CommandQueue};
+pub use config::{Config, FourCharCode, PluginFormat, SupportedSampleRates};
+pub use control_rate::{ControlContext, ControlRate};
+#[cfg(feature = "midi")]
+pub use controller_feedback::{ControllerFeedback, FeedbackTarget};
+pub use controller_pages::{
+    ControllerPage, ControllerPageState, ControllerPages, KNOBS_PER_PAGE,
+};
 pub use conversion_buffers::ConversionBuffers;
+pub use crash_guard::{CrashGuard, CRASH_GUARD_THRESHOLD};
+pub use describe::DESCRIBE_SCHEMA_VERSION;
+#[cfg(feature = "dsp")]
+pub use disk_stream::{
+    DiskStreamConfig, DiskStreamer, RawF32File, StreamSource, StreamVoice, StreamedSample,
+};
 pub use bypass::{BypassAction, BypassHandler, BypassState, CrossfadeCurve};
-pub use gui::{GuiConstraints, GuiDelegate, NoGui};
+pub use channel_mix::{mix_channels, ChannelMix, ChannelMixStage};
+#[cfg(feature = "dsp")]
+pub use crossover::{Crossover, CrossoverSlope, MAX_CROSSOVER_BANDS};
+#[cfg(feature = "groups")]
+pub use group_controls::{GroupControls, GroupSwitch};
+pub use graph::{Block, Detector, KeySignal, KeyedBlock};
+pub use gui::{
+    EditorHost, GuiBackend, GuiConstraints, GuiDelegate, GuiView, NativeEditor, NativeEditorFactory, NoGui,
+    ParentWindow, EDITOR_VIEW,
+};
+pub use gui_data::{GuiDataChannel, GuiDataChannels, GuiFrame, AUDIO_DATA_EVENT};
 pub use error::{PluginError, PluginResult};
+#[cfg(feature = "dsp")]
+pub use fft::Fft;
+pub use host_services::{
+    host_services_invoke, HostServices, INSTANCE_NAME_INVOKE, SET_DISPLAY_NAME_INVOKE,
+};
+#[cfg(feature = "dsp")]
+pub use filter::{BiquadCoeffs, BiquadState, ResponseCurve};
+pub use frame_meters::{FrameMeters, MeterKind};
+#[cfg(feature = "dsp")]
+pub use gain_matrix::GainMatrix;
+#[cfg(feature = "dsp")]
+pub use looper::{LoopQuantize, Looper, LooperCommand, LooperConfig, LooperHandle, LooperState, LOOPER_EVENT};
+pub use memory_accounting::{MemoryAccounting, MemoryCharge, MemoryReport, MemorySubsystem, MemoryUsage};
+#[cfg(feature = "dsp")]
+pub use measurement::{
+    Measurement, MeasurementConfig, MeasurementHandle, MeasurementState, NoiseColor, NoiseGenerator,
+    TransferFunction, MEASUREMENT_EVENT,
+};
 pub use midi::{
     // Basic types
     cc, ChannelPressure, ControlChange, MidiBuffer, MidiChannel, MidiEvent, MidiEventKind,
-    MidiNote, NoteId, NoteOff, NoteOn, PitchBend, PolyPressure, ProgramChange,
+    MidiNote, NoteId, NoteOff, NoteOn, PitchBend, PolyPressure, ProgramChange, SystemRealtime,
     // Advanced VST3 events
-    ChordInfo, NoteExpressionInt, NoteExpressionText, NoteExpressionValue, ScaleInfo, SysEx,
+    ChordInfo, NoteExpressionInt, NoteExpressionKind, NoteExpressionText, NoteExpressionValue, ScaleInfo,
+    SysEx,
     // MIDI 2.0 types
     Midi2Controller,
     // RPN/NRPN types
@@ -91,25 +240,98 @@ pub use midi::{
     MAX_CHORD_NAME_SIZE, MAX_EXPRESSION_TEXT_SIZE, MAX_KEYSWITCH_TITLE_SIZE,
     MAX_NOTE_EXPRESSION_TITLE_SIZE, MAX_SCALE_NAME_SIZE, MAX_SYSEX_SIZE,
 };
+#[cfg(feature = "dsp")]
+pub use parallel::{Lane, ParallelSplit};
+pub use parameter_constraints::{Constraint, ConstraintMode, ConstraintState, ParameterConstraints};
+pub use parameter_exposure::ParameterExposure;
 pub use parameter_format::Formatter;
 pub use parameter_range::{LinearMapper, LogMapper, LogOffsetMapper, PowerMapper, RangeMapper};
-pub use parameter_groups::{GroupId, GroupInfo, ParameterGroups, ROOT_GROUP_ID};
+pub use parameter_groups::{GroupHints, GroupId, GroupInfo, ParameterGroups, ROOT_GROUP_ID};
 pub use parameter_info::{ParameterFlags, ParameterInfo, ParameterUnit};
+pub use parameter_log::{ParameterChangeSource, ParameterLog, ParameterLogEntry};
 pub use parameter_store::{params_to_init_json, NoParameters, ParameterStore};
-pub use parameter_types::{BoolParameter, EnumParameter, EnumParameterValue, FloatParameter, IntParameter, ParameterRef, Parameters};
+pub use parameter_types::{
+    BoolParameter, EnumParameter, EnumParameterValue, FloatParameter, IntParameter, ParameterRef, Parameters,
+    SmoothedValues, TimeInSamples,
+};
+#[cfg(feature = "smoothing")]
 pub use smoothing::{Smoother, SmoothingStyle};
+pub use speaker_layout::{Speaker, SpeakerLayout};
+pub use split_block::SplitBlockProcessor;
+pub use standard_trims::{
+    StandardTrims, StoreWithTrims, TrimStage, INPUT_TRIM_PARAMETER_ID, OUTPUT_TRIM_PARAMETER_ID,
+    PHASE_INVERT_PARAMETER_ID, STANDARD_TRIM_PARAM_BASE,
+};
+#[cfg(feature = "dsp")]
+pub use sample_edit::{SampleEdit, SampleEditError, SampleEditor, SampleReceiver, SampleSnapshot};
+pub use state_hash::{hash_state, STATE_HASH_INVOKE};
+pub use state_loading::{StateLoadStatus, StateLoader, STATE_LOAD_EVENT};
 pub use midi_cc_config::{controller, MidiCcConfig, MAX_CC_CONTROLLER};
 pub use midi_cc_state::{MidiCcState, MIDI_CC_PARAM_BASE};
+pub use quality::{Quality, QualityParameter};
+pub use rate_dependent::RateDependent;
+pub use response_curves::{
+    response_curves_invoke, CurveShape, CurveTarget, ResponseCurves, RESPONSE_CURVES_INVOKE,
+    SET_RESPONSE_CURVE_INVOKE,
+};
+pub use midi_clock::{
+    MidiClockGenerator, MidiClockOutput, MidiClockReceiver, Mmc, CLOCKS_PER_BEAT, MMC_ALL_DEVICES,
+};
+#[cfg(feature = "midi")]
+pub use midi_effects::{MidiDelay, MidiEcho, MidiHumanize, MidiScheduler};
+pub use midi_monitor::{
+    MidiDirection, MidiMonitor, MidiMonitorEntry, MidiMonitorFilter, MidiMonitorKind,
+    MidiMonitorRead,
+};
+pub use midi_panic::{
+    midi_panic_invoke, panic_events, MidiPanic, StoreWithPanic, MIDI_PANIC_INVOKE, MIDI_PANIC_PARAMETER_ID,
+    PANIC_EVENT_COUNT,
+};
+pub use midi_thru::MidiThru;
+pub use mpe::{MpeConfig, MpeState, MpeZone, DEFAULT_MPE_PITCH_BEND_RANGE, MPE_NOTE_ID_BASE};
+#[cfg(feature = "midi")]
+pub use note_gate::NoteGate;
+pub use note_ranges::{note_ranges_invoke, NoteRanges, NoteSplit, NOTE_RANGES_INVOKE};
+#[cfg(feature = "dsp")]
+pub use modulation::{ModulationTarget, SidechainModulator};
+pub use notifications::{Notification, Notifications, NotifyLevel, NOTIFICATION_EVENT};
+pub use oversampling::{Oversampling, OversamplingParameter};
+pub use playhead::{Playhead, PlayheadPosition};
 pub use plugin::{
-    AuxInputCount, AuxOutputCount, BusInfo, BusLayout, BusType, Descriptor, HasParameters,
+    AuxInputChannels, AuxInputCount, AuxOutputChannels, AuxOutputCount, BusInfo, BusLayout, BusType, Descriptor, HasParameters,
     HostSetup, MainInputChannels, MainOutputChannels, MaxBufferSize, Midi1Assignment,
-    Midi2Assignment, MidiControllerAssignment, PluginSetup, ProcessMode, Processor, SampleRate,
+    Midi2Assignment, MidiControllerAssignment, PluginSetup, PrecisionPolicy, ProcessMode, Processor, SampleRate,
+    TailTime,
 };
 pub use preset::{fnv1a_hash, FactoryPresets, NoPresets, PresetInfo, PresetValue};
+pub use preset_crossfade::PresetCrossfade;
+#[cfg(feature = "presets")]
+pub use preset_bank::{
+    preset_bank_invoke, BankImportReport, BankInfo, BankPreset, MergeStrategy, PresetBank, BANK_EXTENSION,
+    BANK_FORMAT_VERSION, EXPORT_BANK_INVOKE, IMPORT_BANK_INVOKE,
+};
+#[cfg(feature = "presets")]
+pub use preset_folders::{
+    user_preset_directory, PresetEntry, PresetFolders, DEFAULT_PRESET_EXTENSION, PRESET_FOLDERS_EVENT,
+};
+pub use preset_file::{
+    preset_file_invoke, PresetFile, PresetFileFormat, EXPORT_PRESET_FILE_INVOKE, IMPORT_PRESET_FILE_INVOKE,
+};
+pub use preset_names::{PresetNameCache, PRESET_NAME_CAPACITY};
 pub use process_context::{FrameRate, ProcessContext, Transport};
+pub use processing_stats::{
+    ProcessingStats, StatsTimer, StoreWithStats, ACTIVE_VOICES_PARAMETER_ID, DSP_LOAD_PARAMETER_ID,
+    PROCESSING_STATS_PARAM_BASE, UNDERRUNS_PARAMETER_ID,
+};
 pub use sample::Sample;
+pub use sample_rate_adapter::SampleRateAdapter;
 pub use sysex_pool::SysExOutputPool;
+pub use tempo_sync::{TempoSync, TempoSyncParameter};
+pub use transport_events::{RelocationPolicy, TimeAnchor, TransportEvent, TransportTracker};
 pub use types::{ParameterId, ParameterValue, Rect, Size, MAX_AUX_BUSES, MAX_BUSES, MAX_CHANNELS};
+pub use vectorscope::{Vectorscope, DEFAULT_PEAK_HOLD_FRAMES};
+pub use watchdog::{OverrunReport, ProcessWatchdog, WatchdogSettings, WatchdogTimer, OVERRUN_EVENT};
+#[cfg(feature = "webview-bridge")]
 pub use webview_handle::WebViewHandle;
 pub use webview_handler::WebViewHandler;
 
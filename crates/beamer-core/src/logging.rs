@@ -0,0 +1,549 @@
+//! Per-plugin file logging.
+//!
+//! Beamer crates log through the [`log`] facade but never install a logger on
+//! their own, because a plugin binary shares its process with the host and
+//! every other plugin. This module provides an opt-in logger that the format
+//! wrappers install when the plugin factory is created.
+//!
+//! # Enabling
+//!
+//! Logging is enabled when either the `BEAMER_LOG` environment variable is set
+//! or the plugin declares a filter via `Config::with_log_filter()`. The
+//! environment variable takes precedence, so users can raise verbosity for a
+//! support session without a rebuild.
+//!
+//! # Filter Syntax
+//!
+//! A comma-separated list of directives. A bare level sets the default, a
+//! `target=level` pair sets the level for a module path prefix:
+//!
+//! ```text
+//! BEAMER_LOG=info
+//! BEAMER_LOG=warn,beamer_vst3=debug,my_synth::voice=trace
+//! BEAMER_LOG=off
+//! ```
+//!
+//! # Log Location
+//!
+//! Each plugin writes to its own file, rotated when it exceeds
+//! [`MAX_LOG_FILE_SIZE`]. Up to [`MAX_ROTATED_FILES`] older files are kept.
+//!
+//! | Platform | Path |
+//! |----------|------|
+//! | macOS    | `~/Library/Logs/<vendor>/<plugin>.log` |
+//! | Windows  | `%LOCALAPPDATA%\<vendor>\Logs\<plugin>.log` |
+//! | Linux    | `$XDG_STATE_HOME/<vendor>/<plugin>.log` (or `~/.local/state`) |
+//!
+//! # Log Tail
+//!
+//! The most recent [`LOG_TAIL_CAPACITY`] lines are also kept in memory. The
+//! WebView runtime exposes them as `__BEAMER__.getLogTail(lines)`, which is
+//! useful for a support panel in the plugin GUI.
+//!
+//!
+//! # Real-Time Safety
+//!
+//! Logging never locks, allocates or touches the file on the calling thread.
+//! A record is formatted into a fixed-size line (truncated at
+//! [`MAX_LOG_LINE_LENGTH`] bytes) and pushed into a preallocated lock-free
+//! queue of [`LOG_QUEUE_CAPACITY`] lines; a background thread writes them to
+//! the file and the tail. When the queue is full the record is dropped, and
+//! the writer notes how many were lost. The log arguments themselves are
+//! still formatted on the calling thread, so keep `Display` impls used in
+//! audio-thread logging free of allocation.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::config::Config;
+use crate::gui_data::Ring;
+
+/// Environment variable controlling the log filter.
+pub const LOG_ENV_VAR: &str = "BEAMER_LOG";
+
+/// Log file size (in bytes) at which the file is rotated.
+pub const MAX_LOG_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Number of rotated log files kept next to the active one.
+pub const MAX_ROTATED_FILES: usize = 3;
+
+/// Number of recent log lines kept in memory for [`log_tail()`].
+pub const LOG_TAIL_CAPACITY: usize = 500;
+
+/// Number of formatted lines the queue to the writer thread holds.
+pub const LOG_QUEUE_CAPACITY: usize = 256;
+
+/// Maximum length of a log line in bytes. Longer lines are truncated.
+pub const MAX_LOG_LINE_LENGTH: usize = 512;
+
+/// How often the writer thread drains the queue.
+const WRITE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Built-in WebView invoke method that returns the log tail.
+pub const LOG_TAIL_INVOKE: &str = "_beamer/logTail";
+
+static LOGGER: OnceLock<FileLogger> = OnceLock::new();
+
+// =============================================================================
+// LogFilter
+// =============================================================================
+
+/// Parsed log filter: a default level plus per-target overrides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    default: LevelFilter,
+    /// Target prefixes and their levels, longest prefix first.
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// Parse a filter string (see the module docs for the syntax).
+    ///
+    /// Unknown levels are ignored. An empty string yields `Info`.
+    pub fn parse(spec: &str) -> Self {
+        let mut default = LevelFilter::Info;
+        let mut directives = Vec::new();
+
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        directives.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = part.parse() {
+                        default = level;
+                    }
+                }
+            }
+        }
+
+        directives.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Self { default, directives }
+    }
+
+    /// Get the level that applies to a log target.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .find(|(prefix, _)| {
+                target == prefix
+                    || (target.starts_with(prefix.as_str())
+                        && target[prefix.len()..].starts_with("::"))
+            })
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// The most verbose level any directive enables.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+// =============================================================================
+// FileLogger
+// =============================================================================
+
+struct FileLogger {
+    filter: LogFilter,
+    path: Option<PathBuf>,
+    /// Formatted lines waiting for the writer.
+    queue: Ring<LogLine>,
+    /// Records dropped because the queue was full, not yet reported.
+    dropped: AtomicU64,
+    /// Only locked by the writer thread and off-audio-thread readers.
+    state: Mutex<LoggerState>,
+}
+
+struct LoggerState {
+    file: Option<File>,
+    written: u64,
+    tail: VecDeque<String>,
+}
+
+/// A formatted log line in a fixed-size buffer.
+struct LogLine {
+    len: usize,
+    bytes: [u8; MAX_LOG_LINE_LENGTH],
+}
+
+impl LogLine {
+    fn new() -> Self {
+        Self { len: 0, bytes: [0; MAX_LOG_LINE_LENGTH] }
+    }
+
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl fmt::Write for LogLine {
+    /// Append `s`, truncating at a character boundary when the line is full.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = MAX_LOG_LINE_LENGTH - self.len;
+        let mut end = s.len().min(room);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.bytes[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+        self.len += end;
+        if end < s.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl FileLogger {
+    fn new(filter: LogFilter, path: Option<PathBuf>) -> Self {
+        let file = path.as_deref().and_then(open_log_file);
+        let written = file
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+        Self {
+            filter,
+            path,
+            queue: Ring::new(LOG_QUEUE_CAPACITY),
+            dropped: AtomicU64::new(0),
+            state: Mutex::new(LoggerState {
+                file,
+                written,
+                tail: VecDeque::with_capacity(LOG_TAIL_CAPACITY),
+            }),
+        }
+    }
+
+    /// Start the background thread that writes queued lines.
+    fn spawn_writer(&'static self) {
+        // Without a writer, lines are written by flush() and log_tail()
+        let _ = thread::Builder::new()
+            .name("beamer-log-writer".to_string())
+            .spawn(move || loop {
+                thread::sleep(WRITE_INTERVAL);
+                self.drain();
+            });
+    }
+
+    /// Write all queued lines to the file and the tail. Not audio-thread safe.
+    fn drain(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            self.write_line(&mut state, &format!("{dropped} log records dropped (queue full)"));
+        }
+        while let Some(line) = self.queue.pop() {
+            self.write_line(&mut state, line.as_str());
+        }
+    }
+
+    fn write_line(&self, state: &mut LoggerState, line: &str) {
+        if let Some(file) = state.file.as_mut() {
+            if writeln!(file, "{line}").is_ok() {
+                state.written += line.len() as u64 + 1;
+            }
+        }
+        if state.written > MAX_LOG_FILE_SIZE {
+            self.rotate(state);
+        }
+
+        if state.tail.len() == LOG_TAIL_CAPACITY {
+            state.tail.pop_front();
+        }
+        state.tail.push_back(line.to_string());
+    }
+
+    fn rotate(&self, state: &mut LoggerState) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        state.file = None;
+        for i in (1..MAX_ROTATED_FILES).rev() {
+            let _ = fs::rename(rotated_path(path, i), rotated_path(path, i + 1));
+        }
+        let _ = fs::rename(path, rotated_path(path, 1));
+        state.file = open_log_file(path);
+        state.written = 0;
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level_for(metadata.target())
+    }
+
+    /// Queue the record for the writer thread. Real-time safe apart from
+    /// formatting the record's arguments.
+    fn log(&self, record: &Record) {
+        use fmt::Write as _;
+
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        let mut line = LogLine::new();
+        // A full line is truncated, not an error
+        let _ = write!(
+            line,
+            "{timestamp:.3} {:<5} [{}] {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        if self.queue.push(line).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&self) {
+        self.drain();
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(file) = state.file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+fn open_log_file(path: &Path) -> Option<File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).ok()?;
+    }
+    OpenOptions::new().create(true).append(true).open(path).ok()
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+/// Replace characters that are invalid in file names on any platform.
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+// =============================================================================
+// Public API
+// =============================================================================
+
+/// Get the OS log directory for a vendor, if it can be determined.
+pub fn log_directory(vendor: &str) -> Option<PathBuf> {
+    let vendor = sanitize_file_name(vendor);
+    let env_path = |key: &str| std::env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| home.join("Library").join("Logs").join(vendor))
+    } else if cfg!(target_os = "windows") {
+        env_path("LOCALAPPDATA").map(|dir| dir.join(vendor).join("Logs"))
+    } else {
+        env_path("XDG_STATE_HOME")
+            .or_else(|| env_path("HOME").map(|home| home.join(".local").join("state")))
+            .map(|dir| dir.join(vendor))
+    }
+}
+
+/// Get the log file path for a plugin, if it can be determined.
+pub fn log_file_path(config: &Config) -> Option<PathBuf> {
+    log_directory(config.vendor)
+        .map(|dir| dir.join(format!("{}.log", sanitize_file_name(config.name))))
+}
+
+/// Install the Beamer file logger for a plugin.
+///
+/// Called by the format wrappers when the plugin factory is created. Does
+/// nothing unless `BEAMER_LOG` is set or the config declares a log filter,
+/// and does nothing if a logger is already installed in the process (by the
+/// host or another plugin). Safe to call more than once.
+pub fn init(config: &Config) {
+    let spec = match std::env::var(LOG_ENV_VAR) {
+        Ok(spec) => spec,
+        Err(_) => match config.log_filter {
+            Some(spec) => spec.to_string(),
+            None => return,
+        },
+    };
+
+    let filter = LogFilter::parse(&spec);
+    if filter.max_level() == LevelFilter::Off {
+        return;
+    }
+
+    let mut installed = false;
+    let logger = LOGGER.get_or_init(|| {
+        installed = true;
+        FileLogger::new(filter, log_file_path(config))
+    });
+    if installed && log::set_logger(logger).is_ok() {
+        logger.spawn_writer();
+        log::set_max_level(logger.filter.max_level());
+        log::info!(
+            "{} {} logging to {}",
+            config.name,
+            config.version,
+            logger
+                .path
+                .as_deref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "memory only".to_string())
+        );
+    }
+}
+
+/// Get up to `lines` of the most recent log output, oldest first.
+///
+/// Returns an empty list when the Beamer logger is not installed.
+pub fn log_tail(lines: usize) -> Vec<String> {
+    let Some(logger) = LOGGER.get() else {
+        return Vec::new();
+    };
+    logger.drain();
+    let Ok(state) = logger.state.lock() else {
+        return Vec::new();
+    };
+    let skip = state.tail.len().saturating_sub(lines);
+    state.tail.iter().skip(skip).cloned().collect()
+}
+
+/// Handle the built-in [`LOG_TAIL_INVOKE`] WebView call.
+///
+/// `args[0]` is the optional number of lines (default: all kept lines).
+pub fn log_tail_invoke(args: &[serde_json::Value]) -> serde_json::Value {
+    let lines = args
+        .first()
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(LOG_TAIL_CAPACITY);
+    serde_json::Value::from(log_tail(lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_level() {
+        let filter = LogFilter::parse("debug");
+        assert_eq!(filter.level_for("anything"), LevelFilter::Debug);
+        assert_eq!(LogFilter::parse("").level_for("x"), LevelFilter::Info);
+    }
+
+    #[test]
+    fn test_parse_target_directives() {
+        let filter = LogFilter::parse("warn, beamer_vst3=debug, my_synth::voice=trace");
+        assert_eq!(filter.level_for("beamer_vst3"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("beamer_vst3::processor"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("beamer_vst3x"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("my_synth::voice"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("my_synth"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let filter = LogFilter::parse("my_synth=error,my_synth::voice=debug");
+        assert_eq!(filter.level_for("my_synth::voice::env"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("my_synth::dsp"), LevelFilter::Error);
+    }
+
+    #[test]
+    fn test_parse_off() {
+        assert_eq!(LogFilter::parse("off").max_level(), LevelFilter::Off);
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("A/B: C?"), "A_B_ C_");
+    }
+
+    fn log(logger: &FileLogger, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(log::Level::Info)
+                .target("my_synth")
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    fn tail(logger: &FileLogger) -> Vec<String> {
+        logger.state.lock().unwrap().tail.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_records_are_queued_until_drained() {
+        let logger = FileLogger::new(LogFilter::parse("info"), None);
+        log(&logger, "first");
+        log(&logger, "second");
+        assert!(tail(&logger).is_empty());
+
+        logger.drain();
+        let lines = tail(&logger);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("INFO  [my_synth] first"));
+        assert!(lines[1].ends_with("INFO  [my_synth] second"));
+    }
+
+    #[test]
+    fn test_full_queue_drops_and_reports() {
+        let logger = FileLogger::new(LogFilter::parse("info"), None);
+        for i in 0..LOG_QUEUE_CAPACITY + 3 {
+            log(&logger, &i.to_string());
+        }
+        logger.drain();
+        let lines = tail(&logger);
+        assert_eq!(lines.len(), LOG_QUEUE_CAPACITY + 1);
+        assert_eq!(lines[0], "3 log records dropped (queue full)");
+        assert!(lines[LOG_QUEUE_CAPACITY].ends_with(&format!(" {}", LOG_QUEUE_CAPACITY - 1)));
+
+        // Reported once
+        logger.drain();
+        assert_eq!(tail(&logger).len(), LOG_QUEUE_CAPACITY + 1);
+    }
+
+    #[test]
+    fn test_long_lines_truncate_at_char_boundary() {
+        use std::fmt::Write as _;
+
+        let mut line = LogLine::new();
+        let text = "\u{e9}".repeat(MAX_LOG_LINE_LENGTH);
+        assert!(write!(line, "x{text}").is_err());
+        assert_eq!(line.as_str().len(), MAX_LOG_LINE_LENGTH - 1);
+        assert!(line.as_str().starts_with("x\u{e9}"));
+    }
+
+    #[test]
+    fn test_rotated_path() {
+        let path = Path::new("/tmp/plugin.log");
+        assert_eq!(rotated_path(path, 2), PathBuf::from("/tmp/plugin.log.2"));
+    }
+}
@@ -0,0 +1,938 @@
+//! Transport-aware loop recording with overdub.
+//!
+//! Looper plugins need the same handful of pieces: a loop buffer that is
+//! allocated up front, record/play/overdub commands that land on the bar
+//! grid, playback that stays in phase when the host locates, a waveform for
+//! the GUI, and a way to save a loop that may be tens of megabytes. This
+//! module provides them:
+//!
+//! - [`LooperHandle`] - GUI-side control: commands, status, waveform, save/restore
+//! - [`Looper`] - Audio-thread side: records, overdubs and plays the loop
+//! - [`LooperConfig`] - Maximum loop length, quantization and overdub feedback
+//!
+//! The handle is created with the descriptor (so a WebView handler can hold a
+//! clone) and the looper in `prepare()`, which preallocates the loop buffer
+//! for [`LooperConfig::max_length`] seconds. Commands are atomics picked up by
+//! the next `process()` call. While the host transport is playing, record,
+//! play and overdub (and stopping a recording) wait for the next bar or beat,
+//! so loops start on the grid and come out in whole bars.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Descriptor::prepare()
+//! let looper = Looper::new(&self.looper, sample_rate.hz(), 2);
+//!
+//! // Processor::transport_event()
+//! self.looper.transport_event(event, transport);
+//!
+//! // Processor::process()
+//! self.looper.process(buffer, context); // Input plus loop playback
+//!
+//! // WebView handler (main thread)
+//! handle.record();
+//! let status = handle.status_json();
+//! ```
+//!
+//! # Saving Loops
+//!
+//! [`LooperHandle::save()`] serializes the loop audio for `save_state()`.
+//! [`LooperHandle::restore()`] decodes it back into the loop buffer and is
+//! meant to run on a [`StateLoader`](crate::state_loading::StateLoader)
+//! worker, so `load_state()` returns immediately; the looper passes its input
+//! through until the restore is done.
+//!
+//! # GUI Events
+//!
+//! When a [`WebViewHandle`] is attached via [`LooperHandle::attach_webview()`],
+//! [`LooperHandle::notify()`] emits a `"beamer:looper"` event with the
+//! [`LooperHandle::status_json()`] payload. Emitting allocates, so call it
+//! from the GUI side, never from `process()`.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::buffer::Buffer;
+use crate::process_context::{ProcessContext, Transport};
+use crate::sample::Sample;
+use crate::state_loading::StateLoadStatus;
+use crate::transport_events::{TimeAnchor, TransportEvent};
+#[cfg(feature = "webview-bridge")]
+use crate::webview_handle::WebViewHandle;
+
+/// Event name emitted to the WebView with the looper status.
+pub const LOOPER_EVENT: &str = "beamer:looper";
+
+/// Samples per stored waveform peak.
+const PEAK_BLOCK: usize = 256;
+
+/// Number of waveform points in the GUI payload.
+const GUI_POINTS: usize = 256;
+
+/// Magic bytes at the start of a saved loop.
+const LOOP_MAGIC: &[u8; 4] = b"BLOP";
+
+/// Saved loop header: magic, channel count (u32), length (u64), sample rate (f64).
+const HEADER_LEN: usize = 4 + 4 + 8 + 8;
+
+// =============================================================================
+// Configuration and State
+// =============================================================================
+
+/// Grid that record, play and overdub commands wait for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopQuantize {
+    /// Act on the next block.
+    Off,
+    /// Wait for the next beat (time signature denominator).
+    Beat,
+    /// Wait for the next bar line.
+    #[default]
+    Bar,
+}
+
+/// Loop buffer and recording settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LooperConfig {
+    /// Longest loop in seconds. The buffer is allocated for this length.
+    pub max_length: f64,
+    /// Grid for commands while the host transport is playing.
+    pub quantize: LoopQuantize,
+    /// Gain applied to the existing loop on each overdub pass (1.0 keeps
+    /// every layer, lower values let old layers fade out).
+    pub overdub_feedback: f64,
+}
+
+impl Default for LooperConfig {
+    fn default() -> Self {
+        Self {
+            max_length: 60.0,
+            quantize: LoopQuantize::Bar,
+            overdub_feedback: 1.0,
+        }
+    }
+}
+
+impl LooperConfig {
+    /// Set the longest loop in seconds.
+    pub fn with_max_length(mut self, seconds: f64) -> Self {
+        self.max_length = seconds;
+        self
+    }
+
+    /// Set the command grid.
+    pub fn with_quantize(mut self, quantize: LoopQuantize) -> Self {
+        self.quantize = quantize;
+        self
+    }
+
+    /// Set the overdub feedback (clamped to 0.0..=1.0).
+    pub fn with_overdub_feedback(mut self, feedback: f64) -> Self {
+        self.overdub_feedback = feedback.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// What the looper is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LooperState {
+    /// No loop recorded.
+    Empty = 0,
+    /// Recording the first pass; the loop length is not known yet.
+    Recording = 1,
+    /// Playing the loop under the input.
+    Playing = 2,
+    /// Playing and adding the input to the loop.
+    Overdubbing = 3,
+    /// A loop exists but is not playing.
+    Stopped = 4,
+}
+
+impl LooperState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Recording,
+            2 => Self::Playing,
+            3 => Self::Overdubbing,
+            4 => Self::Stopped,
+            _ => Self::Empty,
+        }
+    }
+
+    /// Lowercase name used in GUI payloads.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Recording => "recording",
+            Self::Playing => "playing",
+            Self::Overdubbing => "overdubbing",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+/// Command sent from the GUI to the audio thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LooperCommand {
+    /// Start a new recording, or close the one in progress and play it.
+    Record = 1,
+    /// Play a stopped loop, close a recording, or end an overdub.
+    Play = 2,
+    /// Toggle overdubbing (closes a recording first).
+    Overdub = 3,
+    /// Stop playback (closes a recording first).
+    Stop = 4,
+    /// Erase the loop. Never quantized.
+    Clear = 5,
+}
+
+impl LooperCommand {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Record),
+            2 => Some(Self::Play),
+            3 => Some(Self::Overdub),
+            4 => Some(Self::Stop),
+            5 => Some(Self::Clear),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name used in GUI payloads.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Record => "record",
+            Self::Play => "play",
+            Self::Overdub => "overdub",
+            Self::Stop => "stop",
+            Self::Clear => "clear",
+        }
+    }
+}
+
+// =============================================================================
+// LooperHandle
+// =============================================================================
+
+/// GUI-side control of a looper.
+///
+/// Cloning is cheap (reference counted). Create it with the descriptor so a
+/// WebView handler and every [`Looper`] prepared from it share the loop.
+#[derive(Clone)]
+pub struct LooperHandle {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    config: LooperConfig,
+    state: AtomicU8,
+    /// Command waiting for the next grid boundary (0 = none).
+    pending: AtomicU8,
+    /// Last command sent; `sequence` is incremented after every store.
+    command: AtomicU8,
+    sequence: AtomicU64,
+    /// Set by `restore()` so the audio thread adopts the restored length.
+    restored: AtomicBool,
+    /// Playback (or recording) position in samples.
+    position: AtomicU64,
+    /// Loop length in samples, 0 while the first pass is recording.
+    length: AtomicU64,
+    /// Sample rate of the prepared looper, stored as f64 bits.
+    sample_rate: AtomicU64,
+    /// One buffer per channel, preallocated by `Looper::new()`. The audio
+    /// thread only uses `try_lock()`; save and restore hold the lock.
+    audio: Mutex<Vec<Vec<f32>>>,
+    /// Peak per `PEAK_BLOCK` samples (f32 bits), written by the audio thread.
+    peaks: Mutex<Arc<[AtomicU32]>>,
+    /// Optional WebView for status events. Only touched off the audio thread.
+    #[cfg(feature = "webview-bridge")]
+    webview: Mutex<Option<WebViewHandle>>,
+}
+
+impl Default for LooperHandle {
+    fn default() -> Self {
+        Self::new(LooperConfig::default())
+    }
+}
+
+impl std::fmt::Debug for LooperHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LooperHandle")
+            .field("state", &self.state())
+            .field("pending", &self.pending())
+            .field("length", &self.length())
+            .finish()
+    }
+}
+
+impl LooperHandle {
+    /// Create a handle with an empty loop.
+    pub fn new(config: LooperConfig) -> Self {
+        Self {
+            inner: Arc::new(Shared {
+                config,
+                state: AtomicU8::new(LooperState::Empty as u8),
+                pending: AtomicU8::new(0),
+                command: AtomicU8::new(0),
+                sequence: AtomicU64::new(0),
+                restored: AtomicBool::new(false),
+                position: AtomicU64::new(0),
+                length: AtomicU64::new(0),
+                sample_rate: AtomicU64::new(0.0f64.to_bits()),
+                audio: Mutex::new(Vec::new()),
+                peaks: Mutex::new(Arc::from(Vec::new())),
+                #[cfg(feature = "webview-bridge")]
+                webview: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Looper settings.
+    pub fn config(&self) -> &LooperConfig {
+        &self.inner.config
+    }
+
+    /// Send a command. The audio thread picks it up on the next block and
+    /// applies it at the next grid boundary. A newer command replaces one
+    /// that has not been applied yet.
+    pub fn send(&self, command: LooperCommand) {
+        self.inner.command.store(command as u8, Ordering::Relaxed);
+        self.inner.sequence.fetch_add(1, Ordering::Release);
+    }
+
+    /// Send [`LooperCommand::Record`].
+    pub fn record(&self) {
+        self.send(LooperCommand::Record);
+    }
+
+    /// Send [`LooperCommand::Play`].
+    pub fn play(&self) {
+        self.send(LooperCommand::Play);
+    }
+
+    /// Send [`LooperCommand::Overdub`].
+    pub fn overdub(&self) {
+        self.send(LooperCommand::Overdub);
+    }
+
+    /// Send [`LooperCommand::Stop`].
+    pub fn stop(&self) {
+        self.send(LooperCommand::Stop);
+    }
+
+    /// Send [`LooperCommand::Clear`].
+    pub fn clear(&self) {
+        self.send(LooperCommand::Clear);
+    }
+
+    /// Current state.
+    pub fn state(&self) -> LooperState {
+        LooperState::from_u8(self.inner.state.load(Ordering::Acquire))
+    }
+
+    /// Command waiting for the next grid boundary.
+    pub fn pending(&self) -> Option<LooperCommand> {
+        LooperCommand::from_u8(self.inner.pending.load(Ordering::Acquire))
+    }
+
+    /// Playback position in seconds (recorded length while recording).
+    pub fn position(&self) -> f64 {
+        self.samples_to_seconds(self.inner.position.load(Ordering::Relaxed))
+    }
+
+    /// Loop length in seconds, 0 until the first pass is closed.
+    pub fn length(&self) -> f64 {
+        self.samples_to_seconds(self.inner.length.load(Ordering::Relaxed))
+    }
+
+    /// Peak levels of the loop (or the recording so far), reduced to
+    /// `points` values. Lock-free with respect to the audio thread.
+    pub fn waveform(&self, points: usize) -> Vec<f32> {
+        let samples = match self.state() {
+            LooperState::Recording => self.inner.position.load(Ordering::Relaxed),
+            _ => self.inner.length.load(Ordering::Relaxed),
+        } as usize;
+        let Some(peaks) = self.inner.peaks.lock().ok().map(|peaks| Arc::clone(&peaks)) else {
+            return Vec::new();
+        };
+        let blocks = samples.div_ceil(PEAK_BLOCK).min(peaks.len());
+        if blocks == 0 || points == 0 {
+            return Vec::new();
+        }
+        (0..points)
+            .map(|i| {
+                let start = i * blocks / points;
+                let end = ((i + 1) * blocks / points).clamp(start + 1, blocks);
+                peaks[start..end]
+                    .iter()
+                    .map(|peak| f32::from_bits(peak.load(Ordering::Relaxed)))
+                    .fold(0.0, f32::max)
+            })
+            .collect()
+    }
+
+    /// Serialize the loop audio for `save_state()`. Returns an empty vector
+    /// if there is no loop.
+    ///
+    /// Holds the loop lock while copying, so the looper passes its input
+    /// through for the blocks this takes. **Not audio-thread safe.**
+    pub fn save(&self) -> Vec<u8> {
+        let Ok(audio) = self.inner.audio.lock() else {
+            return Vec::new();
+        };
+        let length = (self.inner.length.load(Ordering::Relaxed) as usize)
+            .min(audio.first().map_or(0, Vec::len));
+        if length == 0 {
+            return Vec::new();
+        }
+
+        let mut data = Vec::with_capacity(HEADER_LEN + audio.len() * length * 4);
+        data.extend_from_slice(LOOP_MAGIC);
+        data.extend_from_slice(&(audio.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(length as u64).to_le_bytes());
+        data.extend_from_slice(&self.inner.sample_rate.load(Ordering::Relaxed).to_le_bytes());
+        for track in audio.iter() {
+            for sample in &track[..length] {
+                data.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        data
+    }
+
+    /// Restore a loop written by [`save()`](Self::save), reporting progress
+    /// through `status`. Returns `false` if the data is not a saved loop.
+    ///
+    /// The loop comes back stopped. Loops longer than the prepared buffer are
+    /// truncated; the sample rate is not converted. Run it inside
+    /// [`StateLoader::spawn()`](crate::state_loading::StateLoader::spawn).
+    /// **Not audio-thread safe.**
+    pub fn restore(&self, data: &[u8], status: &StateLoadStatus) -> bool {
+        let Some((channels, stored, body)) = parse_loop(data) else {
+            return false;
+        };
+        let Ok(mut audio) = self.inner.audio.lock() else {
+            return false;
+        };
+        let length = stored.min(audio.first().map_or(0, Vec::len));
+        let tracks = audio.len();
+        for (ch, track) in audio.iter_mut().enumerate() {
+            // Extra loop channels get a copy of the last saved one
+            let source = &body[ch.min(channels - 1) * stored * 4..];
+            for (sample, bytes) in track[..length].iter_mut().zip(source.chunks_exact(4)) {
+                *sample = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            }
+            status.set_progress((ch + 1) as f32 / tracks as f32);
+        }
+        if let Ok(peaks) = self.inner.peaks.lock() {
+            fill_peaks(&peaks, &audio, length);
+        }
+        self.inner.length.store(length as u64, Ordering::Relaxed);
+        self.inner.restored.store(true, Ordering::Release);
+        true
+    }
+
+    /// Attach a WebView handle to receive looper events.
+    #[cfg(feature = "webview-bridge")]
+    pub fn attach_webview(&self, handle: WebViewHandle) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = Some(handle);
+        }
+    }
+
+    /// Detach the WebView handle. Further updates are not emitted.
+    #[cfg(feature = "webview-bridge")]
+    pub fn detach_webview(&self) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = None;
+        }
+    }
+
+    /// Emit the current status to the attached WebView.
+    ///
+    /// Allocates. Call from the GUI side, never from `process()`.
+    pub fn notify(&self) {
+        #[cfg(feature = "webview-bridge")]
+        {
+            let Ok(webview) = self.inner.webview.lock() else {
+                return;
+            };
+            if let Some(handle) = webview.as_ref() {
+                handle.emit(LOOPER_EVENT, &self.status_json());
+            }
+        }
+    }
+
+    /// `{ "state", "pending", "position", "length", "maxLength", "waveform" }`.
+    ///
+    /// Times are in seconds, `pending` is a command name or `null`, and
+    /// `waveform` holds peak levels (0.0..) across the loop.
+    pub fn status_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "state": self.state().as_str(),
+            "pending": self.pending().map(|command| command.as_str()),
+            "position": self.position(),
+            "length": self.length(),
+            "maxLength": self.inner.config.max_length,
+            "waveform": self.waveform(GUI_POINTS),
+        })
+    }
+
+    fn samples_to_seconds(&self, samples: u64) -> f64 {
+        let sample_rate = f64::from_bits(self.inner.sample_rate.load(Ordering::Relaxed));
+        if sample_rate > 0.0 {
+            samples as f64 / sample_rate
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Split a saved loop into channel count, length and sample data.
+fn parse_loop(data: &[u8]) -> Option<(usize, usize, &[u8])> {
+    if data.len() < HEADER_LEN || &data[..4] != LOOP_MAGIC {
+        return None;
+    }
+    let channels = u32::from_le_bytes(data[4..8].try_into().ok()?) as usize;
+    let length = usize::try_from(u64::from_le_bytes(data[8..16].try_into().ok()?)).ok()?;
+    let body = &data[HEADER_LEN..];
+    let expected = channels.checked_mul(length)?.checked_mul(4)?;
+    (channels > 0 && body.len() == expected).then_some((channels, length, body))
+}
+
+/// Recompute the waveform peaks of the first `length` samples.
+fn fill_peaks(peaks: &[AtomicU32], audio: &[Vec<f32>], length: usize) {
+    for (block, peak) in peaks.iter().enumerate() {
+        let start = (block * PEAK_BLOCK).min(length);
+        let end = (start + PEAK_BLOCK).min(length);
+        let level = audio
+            .iter()
+            .flat_map(|track| track[start..end].iter())
+            .fold(0.0f32, |level, sample| level.max(sample.abs()));
+        peak.store(level.to_bits(), Ordering::Relaxed);
+    }
+}
+
+// =============================================================================
+// Looper
+// =============================================================================
+
+/// Audio-thread side of a looper: records, overdubs and plays the loop.
+///
+/// Created in `prepare()` from the shared [`LooperHandle`]; construction
+/// allocates the loop buffer, [`process()`](Self::process) does not. A loop
+/// recorded before re-preparation is kept (and comes back stopped).
+pub struct Looper {
+    shared: Arc<Shared>,
+    peaks: Arc<[AtomicU32]>,
+    state: LooperState,
+    pending: Option<LooperCommand>,
+    /// Last command sequence seen.
+    sequence: u64,
+    position: usize,
+    length: usize,
+    capacity: usize,
+    sample_rate: f64,
+    quantize: LoopQuantize,
+    /// Gain of the loop in the output.
+    level: f32,
+    /// Project position in quarter notes where playback position 0 lies,
+    /// if the transport was playing when the loop started.
+    origin_beats: Option<f64>,
+}
+
+impl Looper {
+    /// Prepare a looper at `sample_rate` with `channels` loop channels,
+    /// allocating the loop buffer.
+    pub fn new(handle: &LooperHandle, sample_rate: f64, channels: usize) -> Self {
+        let shared = Arc::clone(&handle.inner);
+        let capacity = (shared.config.max_length.max(0.0) * sample_rate) as usize;
+        let peaks: Arc<[AtomicU32]> = (0..capacity.div_ceil(PEAK_BLOCK)).map(|_| AtomicU32::new(0)).collect();
+
+        let mut length = 0;
+        if let Ok(mut audio) = shared.audio.lock() {
+            audio.resize_with(channels, Vec::new);
+            for track in audio.iter_mut() {
+                track.resize(capacity, 0.0);
+            }
+            length = (shared.length.load(Ordering::Relaxed) as usize).min(capacity);
+            fill_peaks(&peaks, &audio, length);
+        }
+        if let Ok(mut slot) = shared.peaks.lock() {
+            *slot = Arc::clone(&peaks);
+        }
+        shared.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+
+        let looper = Self {
+            peaks,
+            state: if length > 0 { LooperState::Stopped } else { LooperState::Empty },
+            pending: None,
+            sequence: shared.sequence.load(Ordering::Acquire),
+            position: 0,
+            length,
+            capacity,
+            sample_rate,
+            quantize: shared.config.quantize,
+            level: 1.0,
+            origin_beats: None,
+            shared,
+        };
+        looper.publish();
+        looper
+    }
+
+    /// Current state.
+    pub fn state(&self) -> LooperState {
+        self.state
+    }
+
+    /// Loop length in samples, 0 until the first pass is closed.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Override the command grid, e.g. from a parameter.
+    pub fn set_quantize(&mut self, quantize: LoopQuantize) {
+        self.quantize = quantize;
+    }
+
+    /// Set the gain of the loop in the output (linear). Recording and
+    /// overdubbing are not affected.
+    pub fn set_level(&mut self, gain: f32) {
+        self.level = gain;
+    }
+
+    /// Re-align playback with the host after a start, locate or cycle wrap.
+    ///
+    /// Forward from `Processor::transport_event()`. Only loops started while
+    /// the transport was playing have a musical position to align to.
+    pub fn transport_event(&mut self, event: TransportEvent, transport: &Transport) {
+        if matches!(event, TransportEvent::Stopped)
+            || !matches!(self.state, LooperState::Playing | LooperState::Overdubbing)
+            || self.length == 0
+        {
+            return;
+        }
+        let (Some(origin), Some(anchor)) = (self.origin_beats, TimeAnchor::from_transport(transport, self.sample_rate))
+        else {
+            return;
+        };
+        let offset = anchor.beats_to_samples(anchor.beats_at(0) - origin).round() as i64;
+        self.position = offset.rem_euclid(self.length as i64) as usize;
+    }
+
+    /// Mix loop playback into the input and record or overdub as needed.
+    ///
+    /// Outputs carry the input plus the loop. If the loop is locked (a save
+    /// or restore is running), the input is passed through unchanged.
+    pub fn process<S: Sample>(&mut self, buffer: &mut Buffer<S>, context: &ProcessContext) {
+        let shared = Arc::clone(&self.shared);
+        let Ok(mut audio) = shared.audio.try_lock() else {
+            buffer.copy_to_output();
+            return;
+        };
+
+        if shared.restored.swap(false, Ordering::AcqRel) {
+            self.length = (shared.length.load(Ordering::Relaxed) as usize).min(self.capacity);
+            self.state = if self.length > 0 { LooperState::Stopped } else { LooperState::Empty };
+            self.position = 0;
+            self.pending = None;
+            self.origin_beats = None;
+        }
+
+        let sequence = shared.sequence.load(Ordering::Acquire);
+        if sequence != self.sequence {
+            self.sequence = sequence;
+            self.pending = LooperCommand::from_u8(shared.command.load(Ordering::Relaxed))
+                .filter(|&command| self.accepts(command));
+        }
+
+        let num_samples = buffer.num_samples();
+        let mut start = 0;
+        if let Some(command) = self.pending {
+            if let Some(offset) = self.boundary(command, &context.transport, num_samples) {
+                self.run(buffer, &mut audio, 0, offset);
+                self.apply(command, &context.transport, offset);
+                self.pending = None;
+                start = offset;
+            }
+        }
+        self.run(buffer, &mut audio, start, num_samples);
+        self.publish();
+    }
+
+    /// Whether `command` does anything in the current state.
+    fn accepts(&self, command: LooperCommand) -> bool {
+        use LooperState::*;
+        match command {
+            LooperCommand::Record => self.capacity > 0,
+            LooperCommand::Play => matches!(self.state, Recording | Overdubbing | Stopped),
+            LooperCommand::Overdub => self.state != Empty,
+            LooperCommand::Stop => matches!(self.state, Recording | Playing | Overdubbing),
+            LooperCommand::Clear => self.state != Empty,
+        }
+    }
+
+    /// Offset into this block where `command` takes effect, or `None` if the
+    /// next grid boundary lies beyond the block.
+    fn boundary(&self, command: LooperCommand, transport: &Transport, num_samples: usize) -> Option<usize> {
+        let quantized = match command {
+            LooperCommand::Clear => false,
+            LooperCommand::Stop => self.state == LooperState::Recording,
+            _ => true,
+        };
+        if !quantized || self.quantize == LoopQuantize::Off || !transport.is_playing {
+            return Some(0);
+        }
+        let Some(anchor) = TimeAnchor::from_transport(transport, self.sample_rate) else {
+            return Some(0);
+        };
+
+        let (numerator, denominator) = transport.time_signature().unwrap_or((4, 4));
+        let beat = 4.0 / denominator.max(1) as f64;
+        let (period, grid_origin) = match self.quantize {
+            LoopQuantize::Bar => (beat * numerator.max(1) as f64, transport.bar_position_beats.unwrap_or(0.0)),
+            _ => (beat, 0.0),
+        };
+        let phase = (anchor.beats_at(0) - grid_origin).rem_euclid(period);
+        let offset = if phase < 1e-9 { 0.0 } else { anchor.beats_to_samples(period - phase) };
+        let offset = offset.round() as usize;
+        (offset < num_samples).then_some(offset)
+    }
+
+    /// Change state for `command`, taking effect at `offset` into the block.
+    fn apply(&mut self, command: LooperCommand, transport: &Transport, offset: usize) {
+        use LooperState::*;
+        let beats = TimeAnchor::from_transport(transport, self.sample_rate)
+            .filter(|_| transport.is_playing)
+            .map(|anchor| anchor.beats_at(offset));
+
+        match (command, self.state) {
+            (LooperCommand::Record, Recording) | (LooperCommand::Play, Recording) => self.close_recording(Playing),
+            (LooperCommand::Overdub, Recording) => self.close_recording(Overdubbing),
+            (LooperCommand::Stop, Recording) => self.close_recording(Stopped),
+            (LooperCommand::Record, _) => {
+                self.reset_peaks();
+                self.state = Recording;
+                self.position = 0;
+                self.length = 0;
+                self.origin_beats = beats;
+            }
+            (LooperCommand::Play, Stopped) | (LooperCommand::Overdub, Stopped) => {
+                self.state = if command == LooperCommand::Play { Playing } else { Overdubbing };
+                self.position = 0;
+                self.origin_beats = beats;
+            }
+            (LooperCommand::Play, Overdubbing) | (LooperCommand::Overdub, Overdubbing) => self.state = Playing,
+            (LooperCommand::Overdub, Playing) => self.state = Overdubbing,
+            (LooperCommand::Stop, _) => {
+                self.state = Stopped;
+                self.position = 0;
+            }
+            (LooperCommand::Clear, _) => {
+                self.reset_peaks();
+                self.state = Empty;
+                self.position = 0;
+                self.length = 0;
+                self.origin_beats = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// End the first pass; the recorded length becomes the loop length.
+    fn close_recording(&mut self, next: LooperState) {
+        self.length = self.position;
+        self.position = 0;
+        self.state = if self.length > 0 { next } else { LooperState::Empty };
+    }
+
+    /// Process `start..end` of the block in the current state.
+    fn run<S: Sample>(&mut self, buffer: &mut Buffer<S>, audio: &mut [Vec<f32>], mut start: usize, end: usize) {
+        while start < end {
+            let count = match self.state {
+                LooperState::Recording => (end - start).min(self.capacity - self.position),
+                _ => end - start,
+            };
+            self.run_segment(buffer, audio, start, start + count);
+            start += count;
+            // Buffer full: close the loop and keep going in the new state
+            if self.state == LooperState::Recording && self.position >= self.capacity {
+                self.close_recording(LooperState::Playing);
+            }
+        }
+    }
+
+    fn run_segment<S: Sample>(&mut self, buffer: &mut Buffer<S>, audio: &mut [Vec<f32>], start: usize, end: usize) {
+        let state = self.state;
+        let feedback = self.shared.config.overdub_feedback as f32;
+        let level = self.level;
+        let length = self.length.max(1);
+
+        for (ch, (input, output)) in buffer.zip_channels().enumerate() {
+            let (input, output) = (&input[start..end], &mut output[start..end]);
+            let track = match audio.get_mut(ch) {
+                Some(track) if state != LooperState::Empty && state != LooperState::Stopped => track,
+                _ => {
+                    output.copy_from_slice(input);
+                    continue;
+                }
+            };
+
+            let mut position = self.position;
+            for (in_sample, out_sample) in input.iter().zip(output.iter_mut()) {
+                let dry = in_sample.to_f32();
+                *out_sample = match state {
+                    LooperState::Recording => {
+                        track[position] = dry;
+                        record_peak(&self.peaks, position, dry);
+                        position += 1;
+                        *in_sample
+                    }
+                    LooperState::Overdubbing => {
+                        let old = track[position];
+                        let layered = old * feedback + dry;
+                        track[position] = layered;
+                        record_peak(&self.peaks, position, layered);
+                        position = (position + 1) % length;
+                        S::from_f32(dry + old * level)
+                    }
+                    _ => {
+                        let looped = track[position];
+                        position = (position + 1) % length;
+                        S::from_f32(dry + looped * level)
+                    }
+                };
+            }
+        }
+
+        let count = end - start;
+        self.position = match state {
+            LooperState::Recording => self.position + count,
+            LooperState::Playing | LooperState::Overdubbing => (self.position + count) % length,
+            _ => self.position,
+        };
+    }
+
+    fn reset_peaks(&self) {
+        for peak in self.peaks.iter() {
+            peak.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn publish(&self) {
+        let shared = &self.shared;
+        shared.position.store(self.position as u64, Ordering::Relaxed);
+        shared.length.store(self.length as u64, Ordering::Relaxed);
+        shared.pending.store(self.pending.map_or(0, |command| command as u8), Ordering::Release);
+        shared.state.store(self.state as u8, Ordering::Release);
+    }
+}
+
+/// Raise the stored peak of the block containing `position`.
+#[inline]
+fn record_peak(peaks: &[AtomicU32], position: usize, sample: f32) {
+    // Bit patterns of non-negative floats order like the floats themselves
+    if let Some(peak) = peaks.get(position / PEAK_BLOCK) {
+        peak.fetch_max(sample.abs().to_bits(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 1000.0;
+
+    /// Run one mono block with a constant input and return the output.
+    fn block(looper: &mut Looper, value: f32, len: usize, transport: Transport) -> Vec<f32> {
+        let input = vec![value; len];
+        let mut output = vec![0.0f32; len];
+        {
+            let mut buffer = Buffer::new([&input[..]], [&mut output[..]], len);
+            let context = ProcessContext::new(SAMPLE_RATE, len, transport);
+            looper.process(&mut buffer, &context);
+        }
+        output
+    }
+
+    fn free() -> Transport {
+        Transport::default()
+    }
+
+    /// 120 BPM in 4/4 at `beats`: one beat is 500 samples at 1 kHz.
+    fn playing_at(beats: f64) -> Transport {
+        Transport {
+            is_playing: true,
+            tempo: Some(120.0),
+            time_sig_numerator: Some(4),
+            time_sig_denominator: Some(4),
+            project_time_beats: Some(beats),
+            bar_position_beats: Some((beats / 4.0).floor() * 4.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn record_then_play_loops_under_input() {
+        let handle = LooperHandle::new(LooperConfig::default().with_quantize(LoopQuantize::Off));
+        let mut looper = Looper::new(&handle, SAMPLE_RATE, 1);
+
+        handle.record();
+        assert_eq!(block(&mut looper, 0.5, 100, free()), vec![0.5; 100]);
+        assert_eq!(handle.state(), LooperState::Recording);
+
+        handle.play();
+        let output = block(&mut looper, 0.25, 100, free());
+        assert_eq!(looper.length(), 100);
+        assert_eq!(handle.state(), LooperState::Playing);
+        assert!(output.iter().all(|&s| (s - 0.75).abs() < 1e-6));
+
+        handle.overdub();
+        block(&mut looper, 0.25, 100, free());
+        handle.play();
+        let output = block(&mut looper, 0.0, 100, free());
+        assert!(output.iter().all(|&s| (s - 0.75).abs() < 1e-6));
+        assert!((handle.waveform(4)[0] - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn record_waits_for_the_bar_line() {
+        let handle = LooperHandle::default();
+        let mut looper = Looper::new(&handle, SAMPLE_RATE, 1);
+
+        // Bar lines every 2000 samples; this block starts 100 samples before one
+        handle.record();
+        block(&mut looper, 1.0, 64, playing_at(3.8));
+        assert_eq!(handle.pending(), Some(LooperCommand::Record));
+        block(&mut looper, 1.0, 64, playing_at(3.8 + 64.0 / 500.0));
+        assert_eq!(handle.state(), LooperState::Recording);
+        assert_eq!(handle.inner.position.load(Ordering::Relaxed), 28);
+    }
+
+    #[test]
+    fn save_and_restore_round_trip() {
+        let config = LooperConfig::default().with_quantize(LoopQuantize::Off);
+        let handle = LooperHandle::new(config.clone());
+        let mut looper = Looper::new(&handle, SAMPLE_RATE, 2);
+        handle.record();
+        block(&mut looper, 0.5, 50, free());
+        handle.stop();
+        block(&mut looper, 0.0, 10, free());
+        let saved = handle.save();
+        assert_eq!(saved.len(), HEADER_LEN + 2 * 50 * 4);
+
+        let other = LooperHandle::new(config);
+        let mut restored = Looper::new(&other, SAMPLE_RATE, 2);
+        assert!(!other.restore(b"nope", &StateLoadStatus::new()));
+        assert!(other.restore(&saved, &StateLoadStatus::new()));
+        block(&mut restored, 0.0, 10, free());
+        assert_eq!(other.state(), LooperState::Stopped);
+        assert_eq!(restored.length(), 50);
+
+        other.play();
+        let output = block(&mut restored, 0.0, 10, free());
+        assert_eq!(output, vec![0.5; 10]);
+    }
+}
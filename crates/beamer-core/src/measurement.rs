@@ -0,0 +1,895 @@
+//! Test-signal measurement of an acoustic or electrical transfer function.
+//!
+//! Room correction, speaker calibration and auto-EQ plugins all follow the
+//! same recipe: play a known noise burst, record what comes back through a
+//! microphone, and divide the two spectra. This module provides the pieces:
+//!
+//! - [`NoiseGenerator`] - White, pink and brown noise, real-time safe
+//! - [`MeasurementHandle`] - GUI-side control: start, cancel, progress, result
+//! - [`Measurement`] - Audio-thread side: plays the burst and records the capture
+//! - [`TransferFunction`] - Result of the analysis (magnitude, phase, coherence)
+//!
+//! The handle is created with the descriptor (so a WebView handler can hold a
+//! clone) and the recorder in `prepare()`, which preallocates the recording.
+//! [`MeasurementHandle::start()`] spawns a worker thread that waits for the
+//! audio thread to finish recording, then estimates the loop delay and the
+//! transfer function with the [`Fft`](crate::fft::Fft) module.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Descriptor::prepare()
+//! let measurement = Measurement::new(&self.measurement, sample_rate.hz());
+//!
+//! // Processor::process()
+//! let sidechain = aux.sidechain();
+//! let mic = sidechain.as_ref().map_or(&[][..], |sc| sc.input(0));
+//! if self.measurement.process(buffer, mic) {
+//!     return; // Outputs carry the test signal
+//! }
+//!
+//! // WebView handler (main thread)
+//! handle.start();
+//! let status = handle.status_json();
+//! ```
+//!
+//! # GUI Events
+//!
+//! When a [`WebViewHandle`] is attached via
+//! [`MeasurementHandle::attach_webview()`], the worker emits a
+//! `"beamer:measurement"` event while recording (`{ "state", "progress" }`)
+//! and once more with the result (`"response": { "frequencies",
+//! "magnitudesDb", "delaySamples" }`). The same payload is available from
+//! [`MeasurementHandle::status_json()`] for GUIs that poll via invoke.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::buffer::Buffer;
+use crate::fft::Fft;
+use crate::sample::Sample;
+#[cfg(feature = "webview-bridge")]
+use crate::webview_handle::WebViewHandle;
+
+/// Event name emitted to the WebView with measurement progress and results.
+pub const MEASUREMENT_EVENT: &str = "beamer:measurement";
+
+/// Number of log-spaced points in the GUI payload.
+const GUI_POINTS: usize = 256;
+
+/// How often the worker reports recording progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Smallest magnitude reported, to avoid `-inf` dB.
+const MIN_MAGNITUDE: f64 = 1e-12;
+
+// =============================================================================
+// NoiseGenerator
+// =============================================================================
+
+/// Spectral color of the test noise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoiseColor {
+    /// Flat spectrum (equal energy per Hz).
+    White,
+    /// -3 dB/octave (equal energy per octave). The usual choice for rooms.
+    #[default]
+    Pink,
+    /// -6 dB/octave. More low-frequency energy for subwoofer measurements.
+    Brown,
+}
+
+/// Noise source with a fixed seed, so measurements are reproducible.
+///
+/// Output is roughly within -1.0..=1.0 for every color. No allocations.
+#[derive(Debug, Clone)]
+pub struct NoiseGenerator {
+    color: NoiseColor,
+    seed: u64,
+    state: u64,
+    /// Paul Kellet's pink filter state.
+    pink: [f64; 7],
+    brown: f64,
+}
+
+impl NoiseGenerator {
+    /// Create a generator.
+    pub fn new(color: NoiseColor, seed: u64) -> Self {
+        let mut generator = Self {
+            color,
+            seed,
+            state: 0,
+            pink: [0.0; 7],
+            brown: 0.0,
+        };
+        generator.reset();
+        generator
+    }
+
+    /// Noise color.
+    pub fn color(&self) -> NoiseColor {
+        self.color
+    }
+
+    /// Restart the sequence from the seed.
+    pub fn reset(&mut self) {
+        // xorshift must not start at zero
+        self.state = self.seed | 1;
+        self.pink = [0.0; 7];
+        self.brown = 0.0;
+    }
+
+    /// Next sample.
+    #[inline]
+    pub fn next_sample(&mut self) -> f64 {
+        let white = self.next_white();
+        match self.color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => {
+                let p = &mut self.pink;
+                p[0] = 0.99886 * p[0] + white * 0.0555179;
+                p[1] = 0.99332 * p[1] + white * 0.0750759;
+                p[2] = 0.96900 * p[2] + white * 0.1538520;
+                p[3] = 0.86650 * p[3] + white * 0.3104856;
+                p[4] = 0.55000 * p[4] + white * 0.5329522;
+                p[5] = -0.7616 * p[5] - white * 0.0168980;
+                let pink = p[0] + p[1] + p[2] + p[3] + p[4] + p[5] + p[6] + white * 0.5362;
+                p[6] = white * 0.115926;
+                pink * 0.11
+            }
+            NoiseColor::Brown => {
+                // Leaky integrator keeps the walk from drifting off
+                self.brown = (self.brown + white * 0.02) * 0.998;
+                self.brown * 3.5
+            }
+        }
+    }
+
+    /// Uniform white noise in -1.0..1.0 (xorshift64).
+    #[inline]
+    fn next_white(&mut self) -> f64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        (x >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+}
+
+// =============================================================================
+// Configuration and State
+// =============================================================================
+
+/// Test signal and analysis settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasurementConfig {
+    /// Noise color of the burst.
+    pub color: NoiseColor,
+    /// Burst level in dBFS.
+    pub level_db: f64,
+    /// Burst length in seconds.
+    pub duration: f64,
+    /// Longest round-trip latency (output to capture) to allow for, in
+    /// seconds. Recording continues this long after the burst ends.
+    pub max_latency: f64,
+    /// FFT size of the analysis (frequency resolution is `sample_rate / fft_size`).
+    pub fft_size: usize,
+}
+
+impl Default for MeasurementConfig {
+    fn default() -> Self {
+        Self {
+            color: NoiseColor::Pink,
+            level_db: -18.0,
+            duration: 3.0,
+            max_latency: 0.5,
+            fft_size: 8192,
+        }
+    }
+}
+
+impl MeasurementConfig {
+    /// Set the noise color.
+    pub fn with_color(mut self, color: NoiseColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the burst level in dBFS.
+    pub fn with_level_db(mut self, level_db: f64) -> Self {
+        self.level_db = level_db;
+        self
+    }
+
+    /// Set the burst length in seconds.
+    pub fn with_duration(mut self, seconds: f64) -> Self {
+        self.duration = seconds;
+        self
+    }
+
+    /// Set the longest round-trip latency to allow for, in seconds.
+    pub fn with_max_latency(mut self, seconds: f64) -> Self {
+        self.max_latency = seconds;
+        self
+    }
+
+    /// Set the analysis FFT size (rounded up to a power of two).
+    pub fn with_fft_size(mut self, fft_size: usize) -> Self {
+        self.fft_size = fft_size;
+        self
+    }
+}
+
+/// Where a measurement is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MeasurementState {
+    /// Nothing running.
+    Idle = 0,
+    /// Playing the burst and recording.
+    Recording = 1,
+    /// Recording done, computing the transfer function.
+    Analyzing = 2,
+    /// Result available via [`MeasurementHandle::result()`].
+    Done = 3,
+    /// The recording could not be analyzed (not prepared, or no usable signal).
+    Failed = 4,
+}
+
+impl MeasurementState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Recording,
+            2 => Self::Analyzing,
+            3 => Self::Done,
+            4 => Self::Failed,
+            _ => Self::Idle,
+        }
+    }
+
+    /// Lowercase name used in GUI payloads.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Recording => "recording",
+            Self::Analyzing => "analyzing",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+// =============================================================================
+// TransferFunction
+// =============================================================================
+
+/// Measured response of the loop from output to capture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferFunction {
+    /// Sample rate of the recording.
+    pub sample_rate: f64,
+    /// Estimated round-trip delay in samples.
+    pub delay_samples: usize,
+    /// Bin frequencies in Hz (DC excluded).
+    pub frequencies: Vec<f64>,
+    /// Magnitude per bin in dB.
+    pub magnitudes_db: Vec<f64>,
+    /// Phase per bin in radians (after removing the estimated delay).
+    pub phases: Vec<f64>,
+    /// Magnitude-squared coherence per bin (0.0 to 1.0). Low values mean the
+    /// bin is dominated by noise or distortion and should not be trusted.
+    pub coherence: Vec<f64>,
+}
+
+impl TransferFunction {
+    /// Estimate the transfer function from `stimulus` to `response`.
+    ///
+    /// `response` may be longer than `stimulus`; the extra length bounds the
+    /// delay search. The delay is found by cross-correlation, then spectra
+    /// are averaged over Hann-windowed, half-overlapping segments of
+    /// `fft_size` samples (Welch's method). Returns `None` if `stimulus` is
+    /// shorter than one segment or silent.
+    pub fn analyze(stimulus: &[f64], response: &[f64], sample_rate: f64, fft_size: usize) -> Option<Self> {
+        let fft = Fft::new(fft_size);
+        let n = fft.size();
+        if stimulus.len() < n || response.len() < stimulus.len() || n < 4 {
+            return None;
+        }
+        let delay = estimate_delay(stimulus, response)?;
+
+        let window: Vec<f64> = (0..n)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos())
+            .collect();
+        let bins = n / 2 + 1;
+        let mut sxx = vec![0.0; bins];
+        let mut syy = vec![0.0; bins];
+        let mut sxy_re = vec![0.0; bins];
+        let mut sxy_im = vec![0.0; bins];
+        let (mut x_re, mut x_im) = (vec![0.0; n], vec![0.0; n]);
+        let (mut y_re, mut y_im) = (vec![0.0; n], vec![0.0; n]);
+
+        for start in (0..=stimulus.len() - n).step_by(n / 2) {
+            for i in 0..n {
+                x_re[i] = stimulus[start + i] * window[i];
+                y_re[i] = response[delay + start + i] * window[i];
+            }
+            x_im.fill(0.0);
+            y_im.fill(0.0);
+            fft.forward(&mut x_re, &mut x_im);
+            fft.forward(&mut y_re, &mut y_im);
+            for k in 0..bins {
+                sxx[k] += x_re[k] * x_re[k] + x_im[k] * x_im[k];
+                syy[k] += y_re[k] * y_re[k] + y_im[k] * y_im[k];
+                // conj(X) * Y
+                sxy_re[k] += x_re[k] * y_re[k] + x_im[k] * y_im[k];
+                sxy_im[k] += x_re[k] * y_im[k] - x_im[k] * y_re[k];
+            }
+        }
+
+        if sxx.iter().all(|&p| p <= 0.0) {
+            return None;
+        }
+
+        let mut result = Self {
+            sample_rate,
+            delay_samples: delay,
+            frequencies: Vec::with_capacity(bins - 1),
+            magnitudes_db: Vec::with_capacity(bins - 1),
+            phases: Vec::with_capacity(bins - 1),
+            coherence: Vec::with_capacity(bins - 1),
+        };
+        for k in 1..bins {
+            let cross = (sxy_re[k] * sxy_re[k] + sxy_im[k] * sxy_im[k]).sqrt();
+            let (magnitude, coherence) = if sxx[k] > 0.0 {
+                let coherence = if syy[k] > 0.0 { cross * cross / (sxx[k] * syy[k]) } else { 0.0 };
+                (cross / sxx[k], coherence)
+            } else {
+                (0.0, 0.0)
+            };
+            result.frequencies.push(k as f64 * sample_rate / n as f64);
+            result.magnitudes_db.push(20.0 * magnitude.max(MIN_MAGNITUDE).log10());
+            result.phases.push(sxy_im[k].atan2(sxy_re[k]));
+            result.coherence.push(coherence.clamp(0.0, 1.0));
+        }
+        Some(result)
+    }
+
+    /// Average magnitude in dB over `low_hz..high_hz`, weighted by coherence.
+    ///
+    /// Returns `None` if no bin in the range is trustworthy.
+    pub fn band_average_db(&self, low_hz: f64, high_hz: f64) -> Option<f64> {
+        let mut sum = 0.0;
+        let mut weight = 0.0;
+        for ((&freq, &db), &coherence) in self.frequencies.iter().zip(&self.magnitudes_db).zip(&self.coherence) {
+            if freq >= low_hz && freq < high_hz {
+                sum += db * coherence;
+                weight += coherence;
+            }
+        }
+        (weight > 1e-6).then(|| sum / weight)
+    }
+
+    /// Magnitude averaged into `num_points` log-spaced bands between
+    /// `min_hz` and `max_hz`, for plotting. Returns `(center_hz, db)` pairs;
+    /// bands without bins take the nearest bin.
+    pub fn log_spaced(&self, num_points: usize, min_hz: f64, max_hz: f64) -> Vec<(f64, f64)> {
+        if num_points == 0 || self.frequencies.is_empty() || min_hz <= 0.0 || max_hz <= min_hz {
+            return Vec::new();
+        }
+        let ratio = (max_hz / min_hz).powf(1.0 / num_points as f64);
+        (0..num_points)
+            .map(|i| {
+                let low = min_hz * ratio.powi(i as i32);
+                let high = low * ratio;
+                let center = (low * high).sqrt();
+                let db = self.band_average_db(low, high).unwrap_or_else(|| self.nearest_db(center));
+                (center, db)
+            })
+            .collect()
+    }
+
+    fn nearest_db(&self, freq: f64) -> f64 {
+        let bin_width = self.sample_rate / (2.0 * self.frequencies.len() as f64);
+        let index = ((freq / bin_width).round() as usize).clamp(1, self.frequencies.len()) - 1;
+        self.magnitudes_db[index]
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let points = self.log_spaced(GUI_POINTS, 20.0, (self.sample_rate / 2.0).min(20_000.0));
+        serde_json::json!({
+            "delaySamples": self.delay_samples,
+            "frequencies": points.iter().map(|p| p.0).collect::<Vec<_>>(),
+            "magnitudesDb": points.iter().map(|p| p.1).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Lag of `response` against `stimulus` with the strongest cross-correlation,
+/// searched over `0..=response.len() - stimulus.len()`.
+fn estimate_delay(stimulus: &[f64], response: &[f64]) -> Option<usize> {
+    let max_delay = response.len() - stimulus.len();
+    let fft = Fft::new(response.len() + stimulus.len());
+    let n = fft.size();
+    let (mut x_re, mut x_im) = (vec![0.0; n], vec![0.0; n]);
+    let (mut y_re, mut y_im) = (vec![0.0; n], vec![0.0; n]);
+    x_re[..stimulus.len()].copy_from_slice(stimulus);
+    y_re[..response.len()].copy_from_slice(response);
+    fft.forward(&mut x_re, &mut x_im);
+    fft.forward(&mut y_re, &mut y_im);
+    for k in 0..n {
+        // conj(X) * Y
+        let re = x_re[k] * y_re[k] + x_im[k] * y_im[k];
+        let im = x_re[k] * y_im[k] - x_im[k] * y_re[k];
+        x_re[k] = re;
+        x_im[k] = im;
+    }
+    fft.inverse(&mut x_re, &mut x_im);
+    x_re[..=max_delay]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .filter(|(_, peak)| peak.abs() > 0.0)
+        .map(|(lag, _)| lag)
+}
+
+// =============================================================================
+// MeasurementHandle
+// =============================================================================
+
+/// GUI-side control of a measurement.
+///
+/// Cloning is cheap (reference counted). Create it with the descriptor so a
+/// WebView handler and every [`Measurement`] prepared from it share state.
+#[derive(Clone)]
+pub struct MeasurementHandle {
+    inner: Arc<Shared>,
+}
+
+struct Shared {
+    config: MeasurementConfig,
+    state: AtomicU8,
+    /// Incremented by every `start()`; the audio thread restarts on change.
+    generation: AtomicU64,
+    /// Recording progress in 0.0..=1.0, stored as f32 bits.
+    progress: AtomicU32,
+    /// Sample rate of the prepared recorder, stored as f64 bits.
+    sample_rate: AtomicU64,
+    /// Preallocated by `Measurement::new()`, written by the audio thread
+    /// (`try_lock`) and read by the worker once recording is done.
+    recording: Mutex<Recording>,
+    result: Mutex<Option<Arc<TransferFunction>>>,
+    /// Optional WebView for progress events. Only touched off the audio thread.
+    #[cfg(feature = "webview-bridge")]
+    webview: Mutex<Option<WebViewHandle>>,
+}
+
+#[derive(Default)]
+struct Recording {
+    /// Burst samples followed by silence, `burst + tail` long.
+    stimulus: Vec<f32>,
+    response: Vec<f32>,
+    /// Burst length in samples.
+    burst: usize,
+}
+
+impl Default for MeasurementHandle {
+    fn default() -> Self {
+        Self::new(MeasurementConfig::default())
+    }
+}
+
+impl std::fmt::Debug for MeasurementHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeasurementHandle")
+            .field("state", &self.state())
+            .field("progress", &self.progress())
+            .finish()
+    }
+}
+
+impl MeasurementHandle {
+    /// Create an idle handle with the given settings.
+    pub fn new(config: MeasurementConfig) -> Self {
+        Self {
+            inner: Arc::new(Shared {
+                config,
+                state: AtomicU8::new(MeasurementState::Idle as u8),
+                generation: AtomicU64::new(0),
+                progress: AtomicU32::new(0.0f32.to_bits()),
+                sample_rate: AtomicU64::new(0.0f64.to_bits()),
+                recording: Mutex::new(Recording::default()),
+                result: Mutex::new(None),
+                #[cfg(feature = "webview-bridge")]
+                webview: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Measurement settings.
+    pub fn config(&self) -> &MeasurementConfig {
+        &self.inner.config
+    }
+
+    /// Current state.
+    pub fn state(&self) -> MeasurementState {
+        MeasurementState::from_u8(self.inner.state.load(Ordering::Acquire))
+    }
+
+    /// Recording progress from 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.inner.progress.load(Ordering::Relaxed))
+    }
+
+    /// Result of the last completed measurement.
+    pub fn result(&self) -> Option<Arc<TransferFunction>> {
+        self.inner.result.lock().ok().and_then(|result| result.clone())
+    }
+
+    /// Start a measurement. Returns `false` if one is already running.
+    ///
+    /// The burst starts with the next `process()` call. Spawns a worker
+    /// thread that reports progress and runs the analysis.
+    /// **Not audio-thread safe.**
+    pub fn start(&self) -> bool {
+        let state = self.state();
+        if matches!(state, MeasurementState::Recording | MeasurementState::Analyzing) {
+            return false;
+        }
+        if let Ok(mut result) = self.inner.result.lock() {
+            *result = None;
+        }
+        self.inner.progress.store(0.0f32.to_bits(), Ordering::Relaxed);
+        self.inner.state.store(MeasurementState::Recording as u8, Ordering::Release);
+        let generation = self.inner.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        self.notify();
+
+        let handle = self.clone();
+        thread::spawn(move || handle.run_worker(generation));
+        true
+    }
+
+    /// Stop a running recording. The outputs return to normal processing.
+    pub fn cancel(&self) {
+        let _ = self.inner.state.compare_exchange(
+            MeasurementState::Recording as u8,
+            MeasurementState::Idle as u8,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        self.notify();
+    }
+
+    /// Attach a WebView handle to receive measurement events.
+    #[cfg(feature = "webview-bridge")]
+    pub fn attach_webview(&self, handle: WebViewHandle) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = Some(handle);
+        }
+    }
+
+    /// Detach the WebView handle. Further updates are not emitted.
+    #[cfg(feature = "webview-bridge")]
+    pub fn detach_webview(&self) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = None;
+        }
+    }
+
+    /// `{ "state", "progress" }`, plus `"response"` once a result exists.
+    pub fn status_json(&self) -> serde_json::Value {
+        let mut status = serde_json::json!({
+            "state": self.state().as_str(),
+            "progress": self.progress(),
+        });
+        if let Some(result) = self.result() {
+            status["response"] = result.to_json();
+        }
+        status
+    }
+
+    fn notify(&self) {
+        #[cfg(feature = "webview-bridge")]
+        {
+            let Ok(webview) = self.inner.webview.lock() else {
+                return;
+            };
+            if let Some(handle) = webview.as_ref() {
+                handle.emit(MEASUREMENT_EVENT, &self.status_json());
+            }
+        }
+    }
+
+    /// Worker: report progress until the recording is done, then analyze.
+    fn run_worker(&self, generation: u64) {
+        let current = || self.inner.generation.load(Ordering::Acquire) == generation;
+        while current() && self.state() == MeasurementState::Recording {
+            thread::sleep(PROGRESS_INTERVAL);
+            self.notify();
+        }
+        if !current() || self.state() != MeasurementState::Analyzing {
+            return;
+        }
+
+        let analysis = {
+            let Ok(recording) = self.inner.recording.lock() else {
+                return;
+            };
+            let burst = recording.burst.min(recording.stimulus.len());
+            let stimulus: Vec<f64> = recording.stimulus[..burst].iter().map(|&s| s as f64).collect();
+            let response: Vec<f64> = recording.response.iter().map(|&s| s as f64).collect();
+            drop(recording);
+            let sample_rate = f64::from_bits(self.inner.sample_rate.load(Ordering::Relaxed));
+            TransferFunction::analyze(&stimulus, &response, sample_rate, self.inner.config.fft_size)
+        };
+
+        if !current() {
+            return;
+        }
+        let state = match analysis {
+            Some(result) => {
+                if let Ok(mut slot) = self.inner.result.lock() {
+                    *slot = Some(Arc::new(result));
+                }
+                MeasurementState::Done
+            }
+            None => MeasurementState::Failed,
+        };
+        self.inner.state.store(state as u8, Ordering::Release);
+        self.notify();
+    }
+}
+
+// =============================================================================
+// Measurement
+// =============================================================================
+
+/// Audio-thread side of a measurement: plays the burst and records.
+///
+/// Created in `prepare()` from the shared [`MeasurementHandle`]; construction
+/// allocates the recording, [`process()`](Self::process) does not.
+pub struct Measurement {
+    shared: Arc<Shared>,
+    generator: NoiseGenerator,
+    /// Last `start()` generation seen.
+    generation: u64,
+    recording: bool,
+    position: usize,
+    burst: usize,
+    total: usize,
+    gain: f64,
+    /// Fade-in/out length in samples, to avoid clicks at the burst edges.
+    fade: usize,
+}
+
+impl Measurement {
+    /// Prepare a recorder at `sample_rate`, allocating the recording buffers.
+    pub fn new(handle: &MeasurementHandle, sample_rate: f64) -> Self {
+        let shared = Arc::clone(&handle.inner);
+        let config = &shared.config;
+        let burst = (config.duration.max(0.0) * sample_rate) as usize;
+        let tail = (config.max_latency.max(0.0) * sample_rate) as usize;
+        let total = burst + tail;
+
+        shared.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+        if let Ok(mut recording) = shared.recording.lock() {
+            recording.stimulus = vec![0.0; total];
+            recording.response = vec![0.0; total];
+            recording.burst = burst;
+        }
+
+        Self {
+            generator: NoiseGenerator::new(config.color, 0x5eed_beef_cafe_f00d),
+            generation: shared.generation.load(Ordering::Acquire),
+            recording: false,
+            position: 0,
+            burst,
+            total,
+            gain: 10f64.powf(config.level_db / 20.0),
+            fade: ((0.01 * sample_rate) as usize).min(burst / 2),
+            shared,
+        }
+    }
+
+    /// Whether the burst is playing or the tail is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Play the test signal on every output channel and record `capture`.
+    ///
+    /// Returns `true` while measuring; the outputs then hold the test signal
+    /// and the caller should skip its own processing. `capture` is one
+    /// channel of the measurement input (usually an aux bus); an empty slice
+    /// records silence.
+    pub fn process<S: Sample>(&mut self, buffer: &mut Buffer<S>, capture: &[S]) -> bool {
+        let shared = &self.shared;
+        let generation = shared.generation.load(Ordering::Acquire);
+        let state = MeasurementState::from_u8(shared.state.load(Ordering::Acquire));
+        if generation != self.generation {
+            self.generation = generation;
+            self.recording = state == MeasurementState::Recording;
+            self.position = 0;
+            self.generator.reset();
+        } else if state != MeasurementState::Recording {
+            self.recording = false;
+        }
+        if !self.recording {
+            return false;
+        }
+
+        let Ok(mut recording) = shared.recording.try_lock() else {
+            return self.fail(buffer);
+        };
+        if recording.stimulus.len() < self.total || self.total == 0 {
+            drop(recording);
+            return self.fail(buffer);
+        }
+
+        let start = self.position;
+        let len = buffer.num_samples().min(self.total - start);
+        for i in 0..len {
+            let position = start + i;
+            let sample = if position < self.burst {
+                let edge = position.min(self.burst - 1 - position);
+                let fade = if edge < self.fade { edge as f64 / self.fade as f64 } else { 1.0 };
+                self.generator.next_sample() * self.gain * fade
+            } else {
+                0.0
+            };
+            recording.stimulus[position] = sample as f32;
+            recording.response[position] = capture.get(i).map_or(0.0, |s| s.to_f32());
+        }
+
+        for output in buffer.outputs_mut() {
+            for (out, &stimulus) in output.iter_mut().zip(&recording.stimulus[start..start + len]) {
+                *out = S::from_f32(stimulus);
+            }
+            for out in output.iter_mut().skip(len) {
+                *out = S::ZERO;
+            }
+        }
+        drop(recording);
+
+        self.position += len;
+        let progress = self.position as f32 / self.total as f32;
+        shared.progress.store(progress.to_bits(), Ordering::Relaxed);
+        if self.position >= self.total {
+            self.recording = false;
+            let _ = shared.state.compare_exchange(
+                MeasurementState::Recording as u8,
+                MeasurementState::Analyzing as u8,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+        }
+        true
+    }
+
+    fn fail<S: Sample>(&mut self, buffer: &mut Buffer<S>) -> bool {
+        self.recording = false;
+        self.shared.state.store(MeasurementState::Failed as u8, Ordering::Release);
+        buffer.clear_outputs();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::{BiquadCoeffs, BiquadState};
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn noise(color: NoiseColor, len: usize) -> Vec<f64> {
+        let mut generator = NoiseGenerator::new(color, 1234);
+        (0..len).map(|_| generator.next_sample()).collect()
+    }
+
+    /// Energy in 8-16 kHz relative to 250-500 Hz, in dB.
+    fn octave_tilt(color: NoiseColor) -> f64 {
+        let n = 4096;
+        let fft = Fft::new(n);
+        let signal = noise(color, n * 32);
+        let mut spectrum = vec![0.0; n / 2];
+        for segment in signal.chunks(n) {
+            let mut re = segment.to_vec();
+            let mut im = vec![0.0; n];
+            fft.forward(&mut re, &mut im);
+            for (k, power) in spectrum.iter_mut().enumerate() {
+                *power += re[k] * re[k] + im[k] * im[k];
+            }
+        }
+        let band = |low: f64, high: f64| -> f64 {
+            let bin = |hz: f64| (hz / SAMPLE_RATE * n as f64) as usize;
+            spectrum[bin(low)..bin(high)].iter().sum()
+        };
+        10.0 * (band(8000.0, 16000.0) / band(250.0, 500.0)).log10()
+    }
+
+    #[test]
+    fn noise_colors_tilt_the_spectrum() {
+        // Five octaves apart: white +15 dB, pink flat, brown -15 dB
+        assert!((octave_tilt(NoiseColor::White) - 15.0).abs() < 1.5);
+        assert!(octave_tilt(NoiseColor::Pink).abs() < 1.5);
+        assert!((octave_tilt(NoiseColor::Brown) + 15.0).abs() < 2.0);
+
+        let mut white = NoiseGenerator::new(NoiseColor::White, 1);
+        assert!((0..10_000).all(|_| white.next_sample().abs() <= 1.0));
+    }
+
+    #[test]
+    fn recovers_delay_and_gain() {
+        let stimulus = noise(NoiseColor::Pink, 48_000);
+        let mut response = vec![0.0; 48_000 + 1000];
+        for (i, &s) in stimulus.iter().enumerate() {
+            response[i + 37] = s * 0.5;
+        }
+        let tf = TransferFunction::analyze(&stimulus, &response, SAMPLE_RATE, 4096).unwrap();
+        assert_eq!(tf.delay_samples, 37);
+        let avg = tf.band_average_db(100.0, 10_000.0).unwrap();
+        assert!((avg + 6.02).abs() < 0.1, "average {avg}");
+        assert!(tf.coherence[100] > 0.99);
+    }
+
+    #[test]
+    fn matches_filter_response() {
+        let coeffs = BiquadCoeffs::peak(1000.0, 6.0, 1.0, SAMPLE_RATE);
+        let mut state = BiquadState::default();
+        let stimulus = noise(NoiseColor::Pink, 96_000);
+        let mut response: Vec<f64> = stimulus.iter().map(|&s| state.process(s, &coeffs)).collect();
+        response.extend(std::iter::repeat_n(0.0, 100));
+
+        let tf = TransferFunction::analyze(&stimulus, &response, SAMPLE_RATE, 8192).unwrap();
+        for freq in [200.0, 1000.0, 4000.0] {
+            let measured = tf.band_average_db(freq * 0.95, freq * 1.05).unwrap();
+            let expected = coeffs.magnitude_db_at(freq, SAMPLE_RATE);
+            assert!((measured - expected).abs() < 0.3, "{freq} Hz: {measured} vs {expected}");
+        }
+        assert_eq!(tf.log_spaced(32, 20.0, 20_000.0).len(), 32);
+    }
+
+    #[test]
+    fn loopback_measurement_end_to_end() {
+        let handle = MeasurementHandle::new(
+            MeasurementConfig::default().with_duration(0.5).with_max_latency(0.05).with_fft_size(2048),
+        );
+        let mut measurement = Measurement::new(&handle, SAMPLE_RATE);
+        assert!(handle.start());
+        assert!(!handle.start());
+
+        // Loop the output back into the capture 100 samples later at -6 dB
+        let block = 256;
+        let mut delay_line = std::collections::VecDeque::from(vec![0.0f32; 100]);
+        let input = vec![0.0f32; block];
+        let mut capture = vec![0.0f32; block];
+        let mut output = vec![0.0f32; block];
+        while handle.state() == MeasurementState::Recording {
+            let mut buffer = Buffer::new([&input[..]], [&mut output[..]], block);
+            assert!(measurement.process(&mut buffer, &capture));
+            for (c, &o) in capture.iter_mut().zip(output.iter()) {
+                delay_line.push_back(o * 0.5);
+                *c = delay_line.pop_front().unwrap();
+            }
+        }
+
+        // Capture is fed one block late
+        for _ in 0..200 {
+            if handle.state() == MeasurementState::Done {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(handle.state(), MeasurementState::Done);
+        let result = handle.result().unwrap();
+        assert_eq!(result.delay_samples, 100 + block);
+        assert!((result.band_average_db(200.0, 8000.0).unwrap() + 6.02).abs() < 0.2);
+        assert_eq!(handle.status_json()["state"], "done");
+
+        // Back to normal processing
+        let mut buffer = Buffer::new([&input[..]], [&mut output[..]], block);
+        assert!(!measurement.process(&mut buffer, &capture));
+    }
+}
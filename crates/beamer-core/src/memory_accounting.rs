@@ -0,0 +1,409 @@
+//! Heap usage attributed to plugin subsystems, with budget warnings.
+//!
+//! Sample-heavy plugins can run into the memory limit of the AUv3 extension
+//! sandbox long before a desktop host would complain. [`MemoryAccounting`]
+//! keeps a running total per [`MemorySubsystem`]: code that allocates a
+//! large block (a decoded sample, a preset bank, GUI assets, delay lines)
+//! takes a [`MemoryCharge`] for its size, and the charge is released when
+//! dropped, so the totals follow the data's lifetime.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let memory = MemoryAccounting::new()
+//!     .with_budget(MemorySubsystem::Samples, 256 << 20)
+//!     .with_total_budget(384 << 20)
+//!     .with_notifications(notifications.clone());
+//!
+//! // Loader thread
+//! let frames = decode(&path)?;
+//! let charge = memory.charge(MemorySubsystem::Samples, frames.capacity() * size_of::<f32>());
+//! self.samples.push(LoadedSample { frames, _charge: charge });
+//!
+//! // Diagnostics
+//! log::info!("{}", memory.report());
+//! ```
+//!
+//! Crossing a budget logs a warning and, with
+//! [`with_notifications()`](MemoryAccounting::with_notifications), posts a
+//! [`NotifyLevel::Warning`] to the GUI. Each budget warns once until usage
+//! falls back under it.
+//!
+//! # Threading
+//!
+//! Charges lock a mutex, so take and drop them on loader or main threads,
+//! not on the audio thread. Dropping a charge is cheap and never allocates.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::notifications::{Notifications, NotifyLevel};
+
+/// A part of the plugin that heap usage is attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemorySubsystem {
+    /// Factory and user presets, preset banks.
+    Presets,
+    /// Decoded or preloaded audio samples.
+    Samples,
+    /// Images, fonts and other GUI assets.
+    GuiAssets,
+    /// Delay lines, FFT buffers, oversampling and other DSP state.
+    DspBuffers,
+    /// Anything else, by name.
+    Other(&'static str),
+}
+
+impl MemorySubsystem {
+    /// Name used in reports and warnings.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Presets => "presets",
+            Self::Samples => "samples",
+            Self::GuiAssets => "guiAssets",
+            Self::DspBuffers => "dspBuffers",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+struct Entry {
+    subsystem: MemorySubsystem,
+    bytes: usize,
+    peak: usize,
+    budget: Option<usize>,
+    /// The budget was exceeded and has warned.
+    over: bool,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: Vec<Entry>,
+    total: usize,
+    peak_total: usize,
+    total_budget: Option<usize>,
+    over_total: bool,
+    notifications: Option<Notifications>,
+}
+
+impl Inner {
+    fn entry(&mut self, subsystem: MemorySubsystem) -> usize {
+        match self.entries.iter().position(|e| e.subsystem == subsystem) {
+            Some(index) => index,
+            None => {
+                self.entries.push(Entry { subsystem, bytes: 0, peak: 0, budget: None, over: false });
+                self.entries.len() - 1
+            }
+        }
+    }
+
+    /// Apply a change and return the warnings it triggered.
+    fn apply(&mut self, index: usize, added: usize, removed: usize) -> Vec<String> {
+        let entry = &mut self.entries[index];
+        entry.bytes = (entry.bytes + added).saturating_sub(removed);
+        entry.peak = entry.peak.max(entry.bytes);
+        self.total = (self.total + added).saturating_sub(removed);
+        self.peak_total = self.peak_total.max(self.total);
+
+        let mut warnings = Vec::new();
+        let entry = &mut self.entries[index];
+        if let Some(message) = check(&mut entry.over, entry.bytes, entry.budget, entry.subsystem.name()) {
+            warnings.push(message);
+        }
+        if let Some(message) = check(&mut self.over_total, self.total, self.total_budget, "total") {
+            warnings.push(message);
+        }
+        warnings
+    }
+}
+
+/// Warn once when `bytes` crosses `budget`, and re-arm once it falls back.
+fn check(over: &mut bool, bytes: usize, budget: Option<usize>, name: &str) -> Option<String> {
+    let exceeded = budget.is_some_and(|budget| bytes > budget);
+    let warn = exceeded && !*over;
+    *over = exceeded;
+    warn.then(|| {
+        format!(
+            "Memory over budget ({}): {} of {}",
+            name,
+            format_bytes(bytes),
+            format_bytes(budget.unwrap_or_default())
+        )
+    })
+}
+
+/// Per-instance memory accounting.
+///
+/// Cloning is cheap (reference counted); all clones share the totals.
+#[derive(Clone, Default)]
+pub struct MemoryAccounting {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl fmt::Debug for MemoryAccounting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryAccounting").field("total", &self.total()).finish()
+    }
+}
+
+impl MemoryAccounting {
+    /// Create accounting without budgets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Warn when a subsystem uses more than `bytes`.
+    pub fn with_budget(self, subsystem: MemorySubsystem, bytes: usize) -> Self {
+        {
+            let mut inner = self.lock();
+            let index = inner.entry(subsystem);
+            inner.entries[index].budget = Some(bytes);
+        }
+        self
+    }
+
+    /// Warn when all subsystems together use more than `bytes`.
+    pub fn with_total_budget(self, bytes: usize) -> Self {
+        self.lock().total_budget = Some(bytes);
+        self
+    }
+
+    /// Also post budget warnings to the GUI.
+    pub fn with_notifications(self, notifications: Notifications) -> Self {
+        self.lock().notifications = Some(notifications);
+        self
+    }
+
+    /// Attribute `bytes` to a subsystem until the returned charge is
+    /// dropped.
+    pub fn charge(&self, subsystem: MemorySubsystem, bytes: usize) -> MemoryCharge {
+        let index = self.lock().entry(subsystem);
+        let mut charge = MemoryCharge { accounting: self.clone(), index, bytes: 0 };
+        charge.resize(bytes);
+        charge
+    }
+
+    /// Bytes currently attributed to a subsystem.
+    pub fn usage(&self, subsystem: MemorySubsystem) -> usize {
+        self.lock()
+            .entries
+            .iter()
+            .find(|e| e.subsystem == subsystem)
+            .map_or(0, |e| e.bytes)
+    }
+
+    /// Bytes currently attributed to all subsystems.
+    pub fn total(&self) -> usize {
+        self.lock().total
+    }
+
+    /// Snapshot of every subsystem's usage.
+    pub fn report(&self) -> MemoryReport {
+        let inner = self.lock();
+        MemoryReport {
+            subsystems: inner
+                .entries
+                .iter()
+                .map(|e| MemoryUsage {
+                    subsystem: e.subsystem,
+                    bytes: e.bytes,
+                    peak: e.peak,
+                    budget: e.budget,
+                })
+                .collect(),
+            total: inner.total,
+            peak_total: inner.peak_total,
+            total_budget: inner.total_budget,
+        }
+    }
+
+    fn update(&self, index: usize, added: usize, removed: usize) {
+        let (warnings, notifications) = {
+            let mut inner = self.lock();
+            (inner.apply(index, added, removed), inner.notifications.clone())
+        };
+        for message in warnings {
+            log::warn!("{message}");
+            if let Some(notifications) = &notifications {
+                notifications.notify(NotifyLevel::Warning, message);
+            }
+        }
+    }
+}
+
+/// Bytes attributed to a subsystem, released when dropped.
+///
+/// Keep it next to the data it accounts for.
+#[must_use = "the charge is released when dropped"]
+#[derive(Debug)]
+pub struct MemoryCharge {
+    accounting: MemoryAccounting,
+    index: usize,
+    bytes: usize,
+}
+
+impl MemoryCharge {
+    /// Bytes this charge attributes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Change the attributed size, for data that grew or shrank.
+    pub fn resize(&mut self, bytes: usize) {
+        if bytes != self.bytes {
+            let (added, removed) = (bytes.saturating_sub(self.bytes), self.bytes.saturating_sub(bytes));
+            self.bytes = bytes;
+            self.accounting.update(self.index, added, removed);
+        }
+    }
+}
+
+impl Drop for MemoryCharge {
+    fn drop(&mut self) {
+        self.resize(0);
+    }
+}
+
+/// One subsystem's line in a [`MemoryReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The subsystem.
+    pub subsystem: MemorySubsystem,
+    /// Bytes attributed now.
+    pub bytes: usize,
+    /// Most bytes attributed at any time.
+    pub peak: usize,
+    /// Budget, if one was set.
+    pub budget: Option<usize>,
+}
+
+/// Snapshot returned by [`MemoryAccounting::report()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Usage per subsystem, in the order they were first charged or budgeted.
+    pub subsystems: Vec<MemoryUsage>,
+    /// Bytes attributed to all subsystems.
+    pub total: usize,
+    /// Most bytes attributed at any time.
+    pub peak_total: usize,
+    /// Total budget, if one was set.
+    pub total_budget: Option<usize>,
+}
+
+impl MemoryReport {
+    /// Returns true if the total or any subsystem is over its budget.
+    pub fn is_over_budget(&self) -> bool {
+        let over = |bytes: usize, budget: Option<usize>| budget.is_some_and(|b| bytes > b);
+        over(self.total, self.total_budget) || self.subsystems.iter().any(|u| over(u.bytes, u.budget))
+    }
+
+    /// The report as JSON, for sending to the GUI.
+    pub fn to_json(&self) -> serde_json::Value {
+        let subsystems: serde_json::Map<_, _> = self
+            .subsystems
+            .iter()
+            .map(|u| {
+                let usage = serde_json::json!({ "bytes": u.bytes, "peak": u.peak, "budget": u.budget });
+                (u.subsystem.name().to_string(), usage)
+            })
+            .collect();
+        serde_json::json!({
+            "subsystems": subsystems,
+            "total": self.total,
+            "peakTotal": self.peak_total,
+            "totalBudget": self.total_budget,
+        })
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = |f: &mut fmt::Formatter<'_>, name: &str, bytes, peak, budget: Option<usize>| {
+            write!(f, "{:<12} {:>10}  peak {:>10}", name, format_bytes(bytes), format_bytes(peak))?;
+            match budget {
+                Some(budget) if bytes > budget => writeln!(f, "  budget {} (over)", format_bytes(budget)),
+                Some(budget) => writeln!(f, "  budget {}", format_bytes(budget)),
+                None => writeln!(f),
+            }
+        };
+        for u in &self.subsystems {
+            line(f, u.subsystem.name(), u.bytes, u.peak, u.budget)?;
+        }
+        line(f, "total", self.total, self.peak_total, self.total_budget)
+    }
+}
+
+/// Format a byte count with a binary unit (`"1.5 MiB"`).
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_follow_their_lifetime() {
+        let memory = MemoryAccounting::new();
+        let samples = memory.charge(MemorySubsystem::Samples, 3 << 20);
+        let mut dsp = memory.charge(MemorySubsystem::DspBuffers, 4096);
+        let more = memory.clone().charge(MemorySubsystem::Samples, 1 << 20);
+        assert_eq!(memory.usage(MemorySubsystem::Samples), 4 << 20);
+
+        dsp.resize(1024);
+        drop(samples);
+        assert_eq!(memory.usage(MemorySubsystem::Samples), 1 << 20);
+        assert_eq!(memory.total(), (1 << 20) + 1024);
+
+        let report = memory.report();
+        assert_eq!(report.subsystems[0].peak, 4 << 20);
+        assert_eq!(report.peak_total, (4 << 20) + 4096);
+        assert_eq!(report.to_json()["subsystems"]["dspBuffers"]["bytes"], 1024);
+        assert!(report.to_string().contains("samples         1.0 MiB  peak    4.0 MiB"));
+
+        drop((dsp, more));
+        assert_eq!(memory.total(), 0);
+        assert_eq!(format_bytes(512), "512 B");
+    }
+
+    #[test]
+    fn budgets_warn_once_per_crossing() {
+        let notifications = Notifications::new();
+        let memory = MemoryAccounting::new()
+            .with_budget(MemorySubsystem::Presets, 1000)
+            .with_total_budget(1500)
+            .with_notifications(notifications.clone());
+
+        let mut presets = memory.charge(MemorySubsystem::Presets, 800);
+        assert_eq!(notifications.pending(), 0);
+        presets.resize(1200);
+        presets.resize(1100);
+        assert_eq!(notifications.pending(), 1);
+        assert!(memory.report().is_over_budget());
+
+        let _gui = memory.charge(MemorySubsystem::Other("wavetables"), 600);
+        assert_eq!(notifications.pending(), 2);
+
+        // Back under budget, then over again
+        presets.resize(100);
+        assert!(!memory.report().is_over_budget());
+        presets.resize(1001);
+        assert_eq!(notifications.pending(), 4);
+        let message = notifications.take().unwrap().message;
+        assert_eq!(message, "Memory over budget (presets): 1.2 KiB of 1000 B");
+    }
+}
@@ -336,6 +336,54 @@ pub struct ProgramChange {
     pub program: u8,
 }
 
+/// System real-time message (MIDI clock and sequencer control).
+///
+/// Single status byte, no channel. See [`crate::midi_clock`] for tempo
+/// estimation and transport-locked generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemRealtime {
+    /// Timing clock (0xF8), 24 per quarter note.
+    TimingClock,
+    /// Start (0xFA): play from the beginning.
+    Start,
+    /// Continue (0xFB): resume from the current position.
+    Continue,
+    /// Stop (0xFC).
+    Stop,
+    /// Active sensing (0xFE).
+    ActiveSensing,
+    /// System reset (0xFF).
+    SystemReset,
+}
+
+impl SystemRealtime {
+    /// Parse a status byte. Returns `None` for anything but 0xF8-0xFF
+    /// (and the undefined 0xF9/0xFD).
+    pub const fn from_status(status: u8) -> Option<Self> {
+        match status {
+            0xF8 => Some(Self::TimingClock),
+            0xFA => Some(Self::Start),
+            0xFB => Some(Self::Continue),
+            0xFC => Some(Self::Stop),
+            0xFE => Some(Self::ActiveSensing),
+            0xFF => Some(Self::SystemReset),
+            _ => None,
+        }
+    }
+
+    /// The status byte for this message.
+    pub const fn status(self) -> u8 {
+        match self {
+            Self::TimingClock => 0xF8,
+            Self::Start => 0xFA,
+            Self::Continue => 0xFB,
+            Self::Stop => 0xFC,
+            Self::ActiveSensing => 0xFE,
+            Self::SystemReset => 0xFF,
+        }
+    }
+}
+
 // =============================================================================
 // Advanced VST3 Events
 // =============================================================================
@@ -402,12 +450,37 @@ pub struct NoteExpressionValue {
     pub note_id: NoteId,
     /// Expression type (see [`note_expression`] module for constants).
     pub expression_type: u32,
-    /// Normalized value. Range depends on expression type:
-    /// - Most types: 0.0 to 1.0
-    /// - Tuning: -0.5 to 0.5 (semitones, can exceed for wider range)
+    /// Normalized value (0.0 to 1.0). Use [`kind()`](Self::kind) and the
+    /// decoding helpers for the plain value of the standard types.
     pub value: f64,
 }
 
+impl NoteExpressionValue {
+    /// The expression type.
+    #[inline]
+    pub const fn kind(&self) -> NoteExpressionKind {
+        NoteExpressionKind::from_type_id(self.expression_type)
+    }
+
+    /// Tuning offset in semitones (`TUNING`: 0.5 = none, ±120 at the ends).
+    #[inline]
+    pub fn tuning_semitones(&self) -> f64 {
+        self.value * 240.0 - 120.0
+    }
+
+    /// Linear gain (`VOLUME`: 0.25 = unity, 1.0 = +12 dB).
+    #[inline]
+    pub fn gain(&self) -> f64 {
+        self.value * 4.0
+    }
+
+    /// Pan position (`PAN`: -1.0 = left, 0.0 = center, 1.0 = right).
+    #[inline]
+    pub fn pan(&self) -> f64 {
+        self.value * 2.0 - 1.0
+    }
+}
+
 /// Note Expression integer value event.
 ///
 /// Used for discrete expression values.
@@ -421,6 +494,14 @@ pub struct NoteExpressionInt {
     pub value: u64,
 }
 
+impl NoteExpressionInt {
+    /// The expression type.
+    #[inline]
+    pub const fn kind(&self) -> NoteExpressionKind {
+        NoteExpressionKind::from_type_id(self.expression_type)
+    }
+}
+
 /// Note Expression text event.
 ///
 /// Used for text-based expression like phonemes for vocal synthesis.
@@ -650,6 +731,8 @@ pub mod cc {
     pub const SOSTENUTO: u8 = 66;
     /// Soft Pedal (CC67).
     pub const SOFT_PEDAL: u8 = 67;
+    /// Brightness (CC74), the MPE timbre dimension.
+    pub const BRIGHTNESS: u8 = 74;
     /// All Sound Off (CC120).
     pub const ALL_SOUND_OFF: u8 = 120;
     /// Reset All Controllers (CC121).
@@ -1231,6 +1314,12 @@ pub fn split_14bit_cc(value: f32) -> (f32, f32) {
     (msb, lsb)
 }
 
+/// Quantize a normalized value (0.0 to 1.0) to 14 bits (0-16383).
+#[inline]
+pub(crate) fn raw_14bit(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * 16383.0).round() as u16
+}
+
 /// Combines two raw 7-bit values into a 14-bit value.
 ///
 /// # Arguments
@@ -1292,6 +1381,75 @@ pub mod note_expression {
     pub const INVALID: u32 = u32::MAX;
 }
 
+/// Typed view of a note expression type ID.
+///
+/// ```ignore
+/// MidiEventKind::NoteExpressionValue(expr) => match expr.kind() {
+///     NoteExpressionKind::Tuning => voice.set_detune(expr.tuning_semitones()),
+///     NoteExpressionKind::Brightness => voice.set_cutoff(expr.value),
+///     _ => {}
+/// },
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteExpressionKind {
+    /// [`note_expression::VOLUME`]
+    Volume,
+    /// [`note_expression::PAN`]
+    Pan,
+    /// [`note_expression::TUNING`]
+    Tuning,
+    /// [`note_expression::VIBRATO`]
+    Vibrato,
+    /// [`note_expression::EXPRESSION`] (MPE pressure)
+    Expression,
+    /// [`note_expression::BRIGHTNESS`] (MPE timbre)
+    Brightness,
+    /// [`note_expression::TEXT`]
+    Text,
+    /// [`note_expression::PHONEME`]
+    Phoneme,
+    /// Any other type ID (plugin-defined types, [`note_expression::INVALID`]).
+    Custom(u32),
+}
+
+impl NoteExpressionKind {
+    /// Map a type ID to its kind.
+    pub const fn from_type_id(type_id: u32) -> Self {
+        match type_id {
+            note_expression::VOLUME => Self::Volume,
+            note_expression::PAN => Self::Pan,
+            note_expression::TUNING => Self::Tuning,
+            note_expression::VIBRATO => Self::Vibrato,
+            note_expression::EXPRESSION => Self::Expression,
+            note_expression::BRIGHTNESS => Self::Brightness,
+            note_expression::TEXT => Self::Text,
+            note_expression::PHONEME => Self::Phoneme,
+            other => Self::Custom(other),
+        }
+    }
+
+    /// The type ID.
+    pub const fn type_id(self) -> u32 {
+        match self {
+            Self::Volume => note_expression::VOLUME,
+            Self::Pan => note_expression::PAN,
+            Self::Tuning => note_expression::TUNING,
+            Self::Vibrato => note_expression::VIBRATO,
+            Self::Expression => note_expression::EXPRESSION,
+            Self::Brightness => note_expression::BRIGHTNESS,
+            Self::Text => note_expression::TEXT,
+            Self::Phoneme => note_expression::PHONEME,
+            Self::Custom(type_id) => type_id,
+        }
+    }
+}
+
+impl From<u32> for NoteExpressionKind {
+    fn from(type_id: u32) -> Self {
+        Self::from_type_id(type_id)
+    }
+}
+
 // =============================================================================
 // MIDI Event Enum
 // =============================================================================
@@ -1326,6 +1484,15 @@ pub enum MidiEventKind {
     /// Program change.
     ProgramChange(ProgramChange),
 
+    // =========================================================================
+    // System events
+    // =========================================================================
+
+    /// System real-time message (MIDI clock, start/stop).
+    ///
+    /// Carried by AU only; VST3 has no event type for it.
+    SystemRealtime(SystemRealtime),
+
     // =========================================================================
     // Advanced VST3 events
     // =========================================================================
@@ -1486,11 +1653,19 @@ impl MidiEvent {
         }
     }
 
+    /// Create a system real-time event.
+    pub const fn system_realtime(sample_offset: u32, message: SystemRealtime) -> Self {
+        Self {
+            sample_offset,
+            event: MidiEventKind::SystemRealtime(message),
+        }
+    }
+
     // =========================================================================
     // Raw MIDI 1.0 byte parsing
     // =========================================================================
 
-    /// Parse a MIDI 1.0 channel voice message from raw bytes.
+    /// Parse a MIDI 1.0 channel voice or system real-time message from raw bytes.
     ///
     /// This is the standard way to convert raw MIDI bytes (as received from
     /// plugin hosts or hardware MIDI) into beamer's `MidiEvent` format.
@@ -1505,8 +1680,8 @@ impl MidiEvent {
     ///
     /// # Returns
     ///
-    /// `Some(MidiEvent)` for supported channel voice messages, `None` for
-    /// unsupported message types (system messages, etc.)
+    /// `Some(MidiEvent)` for supported channel voice and system real-time
+    /// messages, `None` for unsupported message types (system common, SysEx)
     ///
     /// # Supported Messages
     ///
@@ -1519,6 +1694,10 @@ impl MidiEvent {
     /// | 0xC0   | Program Change   | program      | (ignored)     |
     /// | 0xD0   | Channel Pressure | pressure     | (ignored)     |
     /// | 0xE0   | Pitch Bend       | LSB          | MSB           |
+    /// | 0xF0   | System Real-Time | (ignored)    | (ignored)     |
+    ///
+    /// For 0xF0 the low nibble of the original status byte arrives in
+    /// `channel`, so `0xF8` (timing clock) is `status = 0xF0, channel = 8`.
     ///
     /// # Notes
     ///
@@ -1604,7 +1783,10 @@ impl MidiEvent {
                 let normalized = (raw_value as f32 - 8192.0) / 8192.0;
                 Some(Self::pitch_bend(sample_offset, channel, normalized))
             }
-            _ => None, // System messages, etc. not supported
+            // System common and SysEx are not supported
+            0xF0 => SystemRealtime::from_status(0xF0 | channel)
+                .map(|message| Self::system_realtime(sample_offset, message)),
+            _ => None,
         }
     }
 
@@ -1765,6 +1947,20 @@ impl MidiEvent {
             event: kind,
         }
     }
+
+    /// Returns true for note-related events (note on/off, poly pressure,
+    /// and per-note expression).
+    pub const fn is_note_event(&self) -> bool {
+        matches!(
+            self.event,
+            MidiEventKind::NoteOn(_)
+                | MidiEventKind::NoteOff(_)
+                | MidiEventKind::PolyPressure(_)
+                | MidiEventKind::NoteExpressionValue(_)
+                | MidiEventKind::NoteExpressionInt(_)
+                | MidiEventKind::NoteExpressionText(_)
+        )
+    }
 }
 
 /// Maximum number of MIDI events per buffer.
@@ -1870,6 +2066,91 @@ impl MidiBuffer {
         }
     }
 
+    /// Push a 14-bit controller as an MSB/LSB pair.
+    ///
+    /// `controller` is the MSB controller (0-31); the LSB goes to
+    /// `controller + 32`. `value` is normalized (0.0-1.0) and sent at 14-bit
+    /// resolution, MSB first. Both events are pushed or neither: returns
+    /// `false` (and sets the overflow flag) if the buffer has no room for the
+    /// pair, or if `controller` has no LSB pair.
+    pub fn send_cc14(
+        &mut self,
+        sample_offset: u32,
+        channel: MidiChannel,
+        controller: u8,
+        value: f32,
+    ) -> bool {
+        if controller >= 32 || !self.reserve(2) {
+            return false;
+        }
+        let (msb, lsb) = split_14bit_raw(raw_14bit(value));
+        self.push(MidiEvent::control_change(sample_offset, channel, controller, msb as f32 / 127.0));
+        self.push(MidiEvent::control_change(sample_offset, channel, controller + 32, lsb as f32 / 127.0));
+        true
+    }
+
+    /// Push an NRPN: parameter select (CC 99/98) and 14-bit data entry
+    /// (CC 6/38).
+    ///
+    /// `parameter` is the 14-bit parameter number, `value` is normalized
+    /// (0.0-1.0). All four events are pushed or none: returns `false` (and
+    /// sets the overflow flag) if the buffer has no room.
+    pub fn send_nrpn(
+        &mut self,
+        sample_offset: u32,
+        channel: MidiChannel,
+        parameter: u16,
+        value: f32,
+    ) -> bool {
+        self.send_parameter_number(sample_offset, channel, (cc::NRPN_MSB, cc::NRPN_LSB), parameter, value)
+    }
+
+    /// Push an RPN: parameter select (CC 101/100) and 14-bit data entry
+    /// (CC 6/38). See [`send_nrpn()`](Self::send_nrpn).
+    pub fn send_rpn(
+        &mut self,
+        sample_offset: u32,
+        channel: MidiChannel,
+        parameter: u16,
+        value: f32,
+    ) -> bool {
+        self.send_parameter_number(sample_offset, channel, (cc::RPN_MSB, cc::RPN_LSB), parameter, value)
+    }
+
+    fn send_parameter_number(
+        &mut self,
+        sample_offset: u32,
+        channel: MidiChannel,
+        (select_msb, select_lsb): (u8, u8),
+        parameter: u16,
+        value: f32,
+    ) -> bool {
+        if !self.reserve(4) {
+            return false;
+        }
+        let (parameter_msb, parameter_lsb) = split_14bit_raw(parameter);
+        let (data_msb, data_lsb) = split_14bit_raw(raw_14bit(value));
+        for (controller, raw) in [
+            (select_msb, parameter_msb),
+            (select_lsb, parameter_lsb),
+            (cc::DATA_ENTRY_MSB, data_msb),
+            (cc::DATA_ENTRY_LSB, data_lsb),
+        ] {
+            self.push(MidiEvent::control_change(sample_offset, channel, controller, raw as f32 / 127.0));
+        }
+        true
+    }
+
+    /// Check that `count` more events fit, setting the overflow flag if not.
+    fn reserve(&mut self, count: usize) -> bool {
+        if MAX_MIDI_EVENTS - self.len >= count {
+            true
+        } else {
+            self.overflowed = true;
+            false
+        }
+    }
+
     /// Iterate over events in the buffer.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &MidiEvent> {
@@ -1884,6 +2165,43 @@ impl MidiBuffer {
         &self.events[..self.len]
     }
 
+    /// Get the events as a mutable slice, e.g. to reshape values in place.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [MidiEvent] {
+        &mut self.events[..self.len]
+    }
+
+    /// Keep only the events for which `keep` returns true, in order.
+    ///
+    /// Removed events are moved to the end of the backing array, so this
+    /// never allocates or drops SysEx data on the audio thread.
+    pub fn retain(&mut self, mut keep: impl FnMut(&MidiEvent) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if keep(&self.events[i]) {
+                self.events.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+
+    /// Stable-sort the buffered events by `sample_offset`.
+    ///
+    /// Events with equal offsets keep their relative order. Uses an in-place
+    /// insertion sort, so it never allocates and is cheap for the mostly
+    /// sorted buffers produced by merging two sorted event streams.
+    pub fn sort_by_offset(&mut self) {
+        let events = &mut self.events[..self.len];
+        for i in 1..events.len() {
+            let mut j = i;
+            while j > 0 && events[j - 1].sample_offset > events[j].sample_offset {
+                events.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
     /// Move all events out of the buffer, leaving it empty.
     ///
     /// Returns an iterator that yields owned `MidiEvent` values by moving them
@@ -1967,13 +2285,15 @@ impl NoteExpressionTypeFlags {
 }
 
 /// Value description for a Note Expression type.
+///
+/// All values are normalized (0.0 to 1.0), like the event values.
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct NoteExpressionValueDesc {
-    /// Minimum value (usually 0.0).
+    /// Minimum normalized value (usually 0.0).
     pub minimum: f64,
-    /// Maximum value (usually 1.0).
+    /// Maximum normalized value (usually 1.0).
     pub maximum: f64,
-    /// Default/center value.
+    /// Default normalized value.
     pub default_value: f64,
     /// Number of discrete steps (0 = continuous).
     pub step_count: i32,
@@ -1990,22 +2310,35 @@ impl NoteExpressionValueDesc {
         }
     }
 
-    /// Create a continuous bipolar value description (-1.0 to 1.0, center at 0.0).
+    /// Create a continuous bipolar value description (0.0 to 1.0, default
+    /// at the 0.5 center).
     pub const fn bipolar() -> Self {
         Self {
-            minimum: -1.0,
+            minimum: 0.0,
             maximum: 1.0,
-            default_value: 0.0,
+            default_value: 0.5,
             step_count: 0,
         }
     }
 
-    /// Create a tuning value description (in semitones).
-    pub const fn tuning(range_semitones: f64) -> Self {
+    /// Create a tuning value description limited to ±`range_semitones`
+    /// (see [`NoteExpressionValue::tuning_semitones()`]).
+    pub fn tuning(range_semitones: f64) -> Self {
+        let half = (range_semitones / 240.0).clamp(0.0, 0.5);
         Self {
-            minimum: -range_semitones,
-            maximum: range_semitones,
-            default_value: 0.0,
+            minimum: 0.5 - half,
+            maximum: 0.5 + half,
+            default_value: 0.5,
+            step_count: 0,
+        }
+    }
+
+    /// Create a volume value description (default 0.25, unity gain).
+    pub const fn volume() -> Self {
+        Self {
+            minimum: 0.0,
+            maximum: 1.0,
+            default_value: 0.25,
             step_count: 0,
         }
     }
@@ -2065,6 +2398,52 @@ impl NoteExpressionTypeInfo {
         info
     }
 
+    /// Info for a standard expression with its usual title, units and range.
+    ///
+    /// Hosts only send the expressions a plugin advertises, so an MPE synth
+    /// returns these from `note_expression_info()`:
+    ///
+    /// ```ignore
+    /// const EXPRESSIONS: [NoteExpressionKind; 3] =
+    ///     [NoteExpressionKind::Tuning, NoteExpressionKind::Brightness, NoteExpressionKind::Expression];
+    ///
+    /// fn note_expression_count(&self, _bus: i32, _channel: i16) -> usize {
+    ///     EXPRESSIONS.len()
+    /// }
+    ///
+    /// fn note_expression_info(&self, _bus: i32, _channel: i16, index: usize) -> Option<NoteExpressionTypeInfo> {
+    ///     EXPRESSIONS.get(index).map(|&kind| NoteExpressionTypeInfo::standard(kind))
+    /// }
+    /// ```
+    ///
+    /// Tuning covers ±48 semitones; custom kinds get a unipolar range and
+    /// an empty title.
+    pub fn standard(kind: NoteExpressionKind) -> Self {
+        let (title, short_title) = match kind {
+            NoteExpressionKind::Volume => ("Volume", "Vol"),
+            NoteExpressionKind::Pan => ("Pan", "Pan"),
+            NoteExpressionKind::Tuning => ("Tuning", "Tune"),
+            NoteExpressionKind::Vibrato => ("Vibrato", "Vib"),
+            NoteExpressionKind::Expression => ("Expression", "Expr"),
+            NoteExpressionKind::Brightness => ("Brightness", "Brt"),
+            NoteExpressionKind::Text => ("Text", "Text"),
+            NoteExpressionKind::Phoneme => ("Phoneme", "Phon"),
+            NoteExpressionKind::Custom(_) => ("", ""),
+        };
+        let info = Self::new(kind.type_id(), title, short_title);
+        match kind {
+            NoteExpressionKind::Volume => info.with_value_desc(NoteExpressionValueDesc::volume()),
+            NoteExpressionKind::Pan => info
+                .with_value_desc(NoteExpressionValueDesc::bipolar())
+                .with_flags(NoteExpressionTypeFlags::IS_BIPOLAR),
+            NoteExpressionKind::Tuning => info
+                .with_value_desc(NoteExpressionValueDesc::tuning(48.0))
+                .with_flags(NoteExpressionTypeFlags::IS_BIPOLAR)
+                .with_units("semitones"),
+            _ => info,
+        }
+    }
+
     /// Set the title.
     pub fn set_title(&mut self, title: &str) {
         let bytes = title.as_bytes();
@@ -2421,6 +2800,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn send_cc14_and_nrpn_push_complete_messages() {
+        let mut buf = MidiBuffer::new_boxed();
+        assert!(buf.send_cc14(3, 1, cc::MOD_WHEEL, 1.0));
+        assert!(!buf.send_cc14(3, 1, 40, 1.0));
+        assert!(buf.send_nrpn(5, 1, combine_14bit_raw(2, 9), 0.0));
+
+        let ccs: Vec<(u32, u8, u8)> = buf
+            .iter()
+            .map(|event| match &event.event {
+                MidiEventKind::ControlChange(c) => {
+                    (event.sample_offset, c.controller, (c.value * 127.0).round() as u8)
+                }
+                _ => panic!("expected ControlChange"),
+            })
+            .collect();
+        assert_eq!(
+            ccs,
+            [(3, 1, 127), (3, 33, 127), (5, 99, 2), (5, 98, 9), (5, 6, 0), (5, 38, 0)]
+        );
+
+        // All-or-nothing when the buffer is nearly full
+        while buf.len() < MAX_MIDI_EVENTS - 3 {
+            buf.push(MidiEvent::default());
+        }
+        assert!(!buf.send_nrpn(0, 0, 1, 0.5));
+        assert_eq!(buf.len(), MAX_MIDI_EVENTS - 3);
+        assert!(buf.has_overflowed());
+    }
+
+    #[test]
+    fn note_expression_kind_and_decoding() {
+        let event = MidiEvent::note_expression_value(0, 7, note_expression::TUNING, 0.5 + 2.0 / 240.0);
+        let MidiEventKind::NoteExpressionValue(expr) = event.event else {
+            panic!("expected NoteExpressionValue");
+        };
+        assert_eq!(expr.kind(), NoteExpressionKind::Tuning);
+        assert!((expr.tuning_semitones() - 2.0).abs() < 1e-9);
+
+        for type_id in [note_expression::VOLUME, note_expression::PHONEME, 100_042] {
+            assert_eq!(NoteExpressionKind::from(type_id).type_id(), type_id);
+        }
+        assert_eq!(NoteExpressionKind::from(100_042), NoteExpressionKind::Custom(100_042));
+
+        let tuning = NoteExpressionTypeInfo::standard(NoteExpressionKind::Tuning);
+        assert_eq!(tuning.type_id, note_expression::TUNING);
+        assert_eq!(tuning.value_desc.default_value, 0.5);
+        assert!((tuning.value_desc.maximum - 0.7).abs() < 1e-9);
+    }
+
     #[test]
     fn midi_buffer_new_boxed_clear() {
         let mut buf = MidiBuffer::new_boxed();
@@ -2429,4 +2858,21 @@ mod tests {
         assert!(buf.is_empty());
         assert!(!buf.has_overflowed());
     }
+
+    #[test]
+    fn midi_buffer_retain_keeps_order() {
+        let mut buf = MidiBuffer::new_boxed();
+        for pitch in [60, 20, 62, 21, 64] {
+            buf.push(MidiEvent::note_on(0, 0, pitch, 0.8, pitch as i32, 0.0, 0));
+        }
+        buf.retain(|e| matches!(&e.event, MidiEventKind::NoteOn(n) if n.pitch >= 60));
+        let pitches: Vec<u8> = buf
+            .iter()
+            .filter_map(|e| match &e.event {
+                MidiEventKind::NoteOn(n) => Some(n.pitch),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pitches, [60, 62, 64]);
+    }
 }
@@ -14,6 +14,7 @@ use crate::midi_cc_config::{controller, MidiCcConfig, MAX_CC_CONTROLLER};
 use crate::parameter_groups::{GroupInfo, ParameterGroups, ROOT_GROUP_ID};
 use crate::parameter_info::{ParameterFlags, ParameterInfo, ParameterUnit};
 use crate::parameter_store::ParameterStore;
+use crate::response_curves::{CurveTarget, ResponseCurves};
 use crate::types::{ParameterId, ParameterValue};
 
 // =============================================================================
@@ -59,6 +60,8 @@ pub struct MidiCcState {
     parameter_infos: Vec<CcParameterInfo>,
     /// Total enabled controller count
     enabled_count: usize,
+    /// Response curves applied when reading values (stored values stay raw)
+    curves: ResponseCurves,
 }
 
 /// Internal storage for parameter info
@@ -101,9 +104,24 @@ impl MidiCcState {
             values,
             parameter_infos,
             enabled_count,
+            curves: ResponseCurves::new(),
         }
     }
 
+    /// Shape the values returned by the accessors with `curves`.
+    ///
+    /// The wrapper passes the instance's response curves so plugins read the
+    /// same shaped values they receive as MIDI events.
+    pub fn with_response_curves(mut self, curves: ResponseCurves) -> Self {
+        self.curves = curves;
+        self
+    }
+
+    /// Replace the response curves used by the accessors.
+    pub fn set_response_curves(&mut self, curves: ResponseCurves) {
+        self.curves = curves;
+    }
+
     // =========================================================================
     // Value Access (for plugins via ProcessContext)
     // =========================================================================
@@ -114,8 +132,8 @@ impl MidiCcState {
     #[inline]
     pub fn pitch_bend(&self) -> f32 {
         if self.enabled[controller::PITCH_BEND as usize] {
-            let normalized = self.get_normalized_internal(controller::PITCH_BEND);
-            (normalized * 2.0 - 1.0) as f32
+            let normalized = self.shaped(controller::PITCH_BEND);
+            normalized * 2.0 - 1.0
         } else {
             0.0
         }
@@ -127,7 +145,7 @@ impl MidiCcState {
     #[inline]
     pub fn aftertouch(&self) -> f32 {
         if self.enabled[controller::AFTERTOUCH as usize] {
-            self.get_normalized_internal(controller::AFTERTOUCH) as f32
+            self.shaped(controller::AFTERTOUCH)
         } else {
             0.0
         }
@@ -147,7 +165,7 @@ impl MidiCcState {
     #[inline]
     pub fn cc(&self, cc: u8) -> f32 {
         if (cc as usize) < MAX_CC_CONTROLLER && self.enabled[cc as usize] {
-            self.get_normalized_internal(cc) as f32
+            self.shaped(cc)
         } else {
             0.0
         }
@@ -259,8 +277,12 @@ impl MidiCcState {
                 is_bypass: false,
                 is_list: false,
                 is_hidden: true, // Hidden from DAW parameter list
+                is_persistent: true,
+                is_randomizable: false,
+                is_internal: false,
             },
             group_id: ROOT_GROUP_ID,
+            randomize_range: (0.0, 1.0),
         }
     }
 
@@ -274,6 +296,13 @@ impl MidiCcState {
         }
     }
 
+    /// Current value of `controller` after its response curve.
+    #[inline]
+    fn shaped(&self, controller: u8) -> f32 {
+        let value = self.get_normalized_internal(controller) as f32;
+        self.curves.shape(CurveTarget::Controller(controller), value)
+    }
+
     fn set_normalized_internal(&self, controller: u8, value: f64) {
         let idx = controller as usize;
         if idx < MAX_CC_CONTROLLER {
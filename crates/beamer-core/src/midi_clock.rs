@@ -0,0 +1,593 @@
+//! MIDI clock and MIDI Machine Control (MMC).
+//!
+//! Hardware-integration plugins often need to follow an external clock or
+//! drive drum machines and sequencers from the host transport. This module
+//! covers both directions:
+//!
+//! - **Consumption:** [`MidiClockReceiver`] estimates tempo from incoming
+//!   timing clocks and tracks start/stop (real-time messages or MMC).
+//! - **Generation:** [`MidiClockOutput`] is a declarative policy, like
+//!   [`MidiThru`](crate::MidiThru). The wrapper runs a [`MidiClockGenerator`]
+//!   against the host transport after `process_midi()` and merges the clock
+//!   and start/stop events into the MIDI output, sample-accurately.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! impl Processor for MyClockSource {
+//!     // Timing clock locked to the host, plus MMC play/stop to all devices.
+//!     fn midi_clock_output(&self) -> MidiClockOutput {
+//!         MidiClockOutput::clock().with_mmc(MMC_ALL_DEVICES)
+//!     }
+//! }
+//!
+//! impl Processor for MyClockFollower {
+//!     fn process_midi(&mut self, input: &[MidiEvent], _output: &mut MidiBuffer) {
+//!         for event in input {
+//!             self.clock_in.receive(event);
+//!         }
+//!     }
+//!
+//!     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, context: &ProcessContext) {
+//!         let tempo = self.clock_in.tempo().unwrap_or(120.0);
+//!         // ...
+//!         self.clock_in.end_block(context.num_samples);
+//!     }
+//! }
+//! ```
+//!
+//! # Format Support
+//!
+//! VST3 has no event type for system real-time messages, so timing clock
+//! and start/stop/continue only travel through AU. MMC is SysEx and works
+//! in both formats.
+
+use crate::midi::{MidiBuffer, MidiEvent, MidiEventKind, SystemRealtime};
+use crate::process_context::Transport;
+
+/// MIDI clocks per quarter note.
+pub const CLOCKS_PER_BEAT: f64 = 24.0;
+
+/// MMC device ID that addresses every device.
+pub const MMC_ALL_DEVICES: u8 = 0x7F;
+
+/// Clock positions closer than this (in ticks) to the block start count as
+/// inside the block, absorbing rounding in the host's beat position.
+const TICK_EPSILON: f64 = 1e-6;
+
+/// Without a timing clock for this long, the receiver reports no tempo.
+const CLOCK_TIMEOUT_SECONDS: f64 = 0.5;
+
+/// Number of clock intervals averaged for the tempo estimate (one beat).
+const TEMPO_WINDOW: usize = CLOCKS_PER_BEAT as usize;
+
+// =============================================================================
+// MMC
+// =============================================================================
+
+/// MIDI Machine Control transport command.
+///
+/// Sent as SysEx `F0 7F <device> 06 <command> F7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mmc {
+    /// Stop (0x01).
+    Stop,
+    /// Play (0x02).
+    Play,
+    /// Deferred play (0x03): play once the device is ready.
+    DeferredPlay,
+    /// Fast forward (0x04).
+    FastForward,
+    /// Rewind (0x05).
+    Rewind,
+    /// Record strobe / punch in (0x06).
+    RecordStrobe,
+    /// Record exit / punch out (0x07).
+    RecordExit,
+    /// Pause (0x09).
+    Pause,
+}
+
+impl Mmc {
+    /// The MMC command byte.
+    pub const fn command(self) -> u8 {
+        match self {
+            Self::Stop => 0x01,
+            Self::Play => 0x02,
+            Self::DeferredPlay => 0x03,
+            Self::FastForward => 0x04,
+            Self::Rewind => 0x05,
+            Self::RecordStrobe => 0x06,
+            Self::RecordExit => 0x07,
+            Self::Pause => 0x09,
+        }
+    }
+
+    /// Parse an MMC command byte.
+    pub const fn from_command(command: u8) -> Option<Self> {
+        match command {
+            0x01 => Some(Self::Stop),
+            0x02 => Some(Self::Play),
+            0x03 => Some(Self::DeferredPlay),
+            0x04 => Some(Self::FastForward),
+            0x05 => Some(Self::Rewind),
+            0x06 => Some(Self::RecordStrobe),
+            0x07 => Some(Self::RecordExit),
+            0x09 => Some(Self::Pause),
+            _ => None,
+        }
+    }
+
+    /// Encode as a complete SysEx message for `device_id`.
+    pub const fn to_sysex(self, device_id: u8) -> [u8; 6] {
+        [0xF0, 0x7F, device_id & 0x7F, 0x06, self.command(), 0xF7]
+    }
+
+    /// Parse an MMC SysEx message, returning `(device_id, command)`.
+    ///
+    /// Accepts the message with or without the F0/F7 framing bytes.
+    pub fn from_sysex(data: &[u8]) -> Option<(u8, Self)> {
+        let data = data.strip_prefix(&[0xF0]).unwrap_or(data);
+        let data = data.strip_suffix(&[0xF7]).unwrap_or(data);
+        match *data {
+            [0x7F, device_id, 0x06, command] => Some((device_id, Self::from_command(command)?)),
+            _ => None,
+        }
+    }
+
+    /// Create a SysEx event carrying this command.
+    ///
+    /// Allocates like every SysEx event (see [`MidiEvent::sysex`]).
+    pub fn to_event(self, sample_offset: u32, device_id: u8) -> MidiEvent {
+        MidiEvent::sysex(sample_offset, &self.to_sysex(device_id))
+    }
+}
+
+impl MidiEvent {
+    /// Parse this event as an MMC command, returning `(device_id, command)`.
+    pub fn as_mmc(&self) -> Option<(u8, Mmc)> {
+        match &self.event {
+            MidiEventKind::SysEx(sysex) => Mmc::from_sysex(sysex.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+// =============================================================================
+// Clock input
+// =============================================================================
+
+/// Tempo and transport state recovered from incoming MIDI clock.
+///
+/// Feed every input event to [`receive`](Self::receive) from `process_midi()`
+/// and call [`end_block`](Self::end_block) once per `process()` so event
+/// offsets map onto a continuous timeline. The tempo is the average of the
+/// last beat's worth of clock intervals.
+///
+/// Real-time safe: fixed-size state, no allocation.
+#[derive(Debug, Clone)]
+pub struct MidiClockReceiver {
+    sample_rate: f64,
+    /// Absolute sample position of the current block start.
+    block_start: u64,
+    /// Absolute sample position of the last timing clock.
+    last_clock: Option<u64>,
+    /// Ring buffer of recent clock intervals in samples.
+    intervals: [f64; TEMPO_WINDOW],
+    interval_count: usize,
+    interval_next: usize,
+    running: bool,
+    /// Timing clocks received while running since the last Start.
+    clocks: u64,
+}
+
+impl MidiClockReceiver {
+    /// Create a receiver for the given sample rate.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            block_start: 0,
+            last_clock: None,
+            intervals: [0.0; TEMPO_WINDOW],
+            interval_count: 0,
+            interval_next: 0,
+            running: false,
+            clocks: 0,
+        }
+    }
+
+    /// Update the sample rate (call from `setup()`). Resets the estimate.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        *self = Self::new(sample_rate);
+    }
+
+    /// Forget the tempo estimate and transport state.
+    pub fn reset(&mut self) {
+        self.set_sample_rate(self.sample_rate);
+    }
+
+    /// Handle one input event.
+    ///
+    /// Returns true if the event was a clock, start/stop/continue or MMC
+    /// transport message.
+    pub fn receive(&mut self, event: &MidiEvent) -> bool {
+        match &event.event {
+            MidiEventKind::SystemRealtime(SystemRealtime::TimingClock) => {
+                let at = self.block_start + event.sample_offset as u64;
+                if let Some(last) = self.last_clock {
+                    if at > last && ((at - last) as f64) < self.timeout_samples() {
+                        self.push_interval((at - last) as f64);
+                    }
+                }
+                self.last_clock = Some(at);
+                if self.running {
+                    self.clocks += 1;
+                }
+                true
+            }
+            MidiEventKind::SystemRealtime(SystemRealtime::Start) => {
+                self.running = true;
+                self.clocks = 0;
+                true
+            }
+            MidiEventKind::SystemRealtime(SystemRealtime::Continue) => {
+                self.running = true;
+                true
+            }
+            MidiEventKind::SystemRealtime(SystemRealtime::Stop) => {
+                self.running = false;
+                true
+            }
+            MidiEventKind::SysEx(_) => match event.as_mmc() {
+                Some((_, Mmc::Play | Mmc::DeferredPlay)) => {
+                    self.running = true;
+                    true
+                }
+                Some((_, Mmc::Stop | Mmc::Pause)) => {
+                    self.running = false;
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Advance the timeline past the current block.
+    pub fn end_block(&mut self, num_samples: usize) {
+        self.block_start += num_samples as u64;
+        if let Some(last) = self.last_clock {
+            if (self.block_start - last) as f64 >= self.timeout_samples() {
+                self.last_clock = None;
+                self.interval_count = 0;
+            }
+        }
+    }
+
+    /// Estimated tempo in BPM, or `None` without a recent clock.
+    pub fn tempo(&self) -> Option<f64> {
+        if self.interval_count == 0 {
+            return None;
+        }
+        let sum: f64 = self.intervals[..self.interval_count].iter().sum();
+        let samples_per_clock = sum / self.interval_count as f64;
+        Some(self.sample_rate * 60.0 / (samples_per_clock * CLOCKS_PER_BEAT))
+    }
+
+    /// True between Start/Continue (or MMC play) and Stop.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Quarter notes elapsed since the last Start, counted in clocks.
+    pub fn position_beats(&self) -> f64 {
+        self.clocks as f64 / CLOCKS_PER_BEAT
+    }
+
+    fn push_interval(&mut self, interval: f64) {
+        self.intervals[self.interval_next] = interval;
+        self.interval_next = (self.interval_next + 1) % TEMPO_WINDOW;
+        self.interval_count = (self.interval_count + 1).min(TEMPO_WINDOW);
+    }
+
+    fn timeout_samples(&self) -> f64 {
+        self.sample_rate * CLOCK_TIMEOUT_SECONDS
+    }
+}
+
+// =============================================================================
+// Clock output
+// =============================================================================
+
+/// MIDI clock output policy applied by the format wrapper.
+///
+/// Returned from [`Processor::midi_clock_output`](crate::Processor::midi_clock_output).
+/// The default generates nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MidiClockOutput {
+    /// Send timing clock and start/stop/continue locked to the host transport.
+    pub clock: bool,
+    /// Send MMC play/stop to this device ID on transport changes.
+    pub mmc_device: Option<u8>,
+}
+
+impl MidiClockOutput {
+    /// Timing clock and start/stop/continue, no MMC.
+    pub const fn clock() -> Self {
+        Self {
+            clock: true,
+            mmc_device: None,
+        }
+    }
+
+    /// MMC play/stop only.
+    pub const fn mmc(device_id: u8) -> Self {
+        Self {
+            clock: false,
+            mmc_device: Some(device_id),
+        }
+    }
+
+    /// Also send MMC play/stop to `device_id`.
+    pub const fn with_mmc(self, device_id: u8) -> Self {
+        Self {
+            mmc_device: Some(device_id),
+            ..self
+        }
+    }
+
+    /// Returns true if anything is generated.
+    pub const fn is_enabled(&self) -> bool {
+        self.clock || self.mmc_device.is_some()
+    }
+}
+
+/// Transport-locked MIDI clock generator, owned by the wrapper.
+///
+/// Clock ticks land on the host's musical grid (every 1/24 quarter note of
+/// `Transport::project_time_beats`), so they follow tempo changes, loops and
+/// locates. Playback starting at beat 0 sends Start, anywhere else Continue.
+#[derive(Debug, Clone, Default)]
+pub struct MidiClockGenerator {
+    was_playing: bool,
+    /// Index of the last emitted clock on the beat grid.
+    last_tick: Option<i64>,
+}
+
+impl MidiClockGenerator {
+    /// Create a generator with the transport stopped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append this block's clock and transport events to `output`.
+    ///
+    /// Real-time safe apart from MMC events, which are SysEx (one small
+    /// allocation per transport change).
+    pub fn generate(
+        &mut self,
+        policy: MidiClockOutput,
+        transport: &Transport,
+        sample_rate: f64,
+        num_samples: usize,
+        output: &mut MidiBuffer,
+    ) {
+        if !policy.is_enabled() {
+            *self = Self::default();
+            return;
+        }
+        let before = output.len();
+
+        let playing = transport.is_playing;
+        if playing != self.was_playing {
+            self.was_playing = playing;
+            self.last_tick = None;
+            if playing {
+                if policy.clock {
+                    let at_start = transport
+                        .project_time_beats
+                        .is_none_or(|beats| beats.abs() < TICK_EPSILON);
+                    let message = if at_start {
+                        SystemRealtime::Start
+                    } else {
+                        SystemRealtime::Continue
+                    };
+                    output.push(MidiEvent::system_realtime(0, message));
+                }
+                if let Some(device_id) = policy.mmc_device {
+                    output.push(Mmc::Play.to_event(0, device_id));
+                }
+            } else {
+                if policy.clock {
+                    output.push(MidiEvent::system_realtime(0, SystemRealtime::Stop));
+                }
+                if let Some(device_id) = policy.mmc_device {
+                    output.push(Mmc::Stop.to_event(0, device_id));
+                }
+            }
+        }
+
+        if playing && policy.clock {
+            self.push_clocks(transport, sample_rate, num_samples, output);
+        }
+
+        if output.len() != before {
+            output.sort_by_offset();
+        }
+    }
+
+    fn push_clocks(
+        &mut self,
+        transport: &Transport,
+        sample_rate: f64,
+        num_samples: usize,
+        output: &mut MidiBuffer,
+    ) {
+        let (Some(tempo), Some(beats)) = (transport.tempo, transport.project_time_beats) else {
+            return;
+        };
+        if tempo <= 0.0 || sample_rate <= 0.0 {
+            return;
+        }
+
+        let ticks_per_sample = tempo * CLOCKS_PER_BEAT / (60.0 * sample_rate);
+        let start = beats * CLOCKS_PER_BEAT;
+        let mut tick = (start - TICK_EPSILON).ceil() as i64;
+        // Contiguous playback: don't repeat the clock the previous block
+        // already sent at its very end. Loops and locates jump further.
+        if let Some(last) = self.last_tick {
+            if tick <= last && last - tick < 2 {
+                tick = last + 1;
+            }
+        }
+
+        loop {
+            let offset = ((tick as f64 - start) / ticks_per_sample).max(0.0);
+            if offset >= num_samples as f64 {
+                break;
+            }
+            output.push(MidiEvent::system_realtime(
+                offset as u32,
+                SystemRealtime::TimingClock,
+            ));
+            self.last_tick = Some(tick);
+            tick += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(beats: f64, tempo: f64) -> Transport {
+        Transport {
+            tempo: Some(tempo),
+            project_time_beats: Some(beats),
+            is_playing: true,
+            ..Default::default()
+        }
+    }
+
+    fn realtime(output: &MidiBuffer) -> Vec<(u32, SystemRealtime)> {
+        output
+            .iter()
+            .filter_map(|e| match e.event {
+                MidiEventKind::SystemRealtime(m) => Some((e.sample_offset, m)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_realtime_from_midi1_bytes() {
+        let clock = MidiEvent::from_midi1_bytes(7, 0xF0, 0x08, 0, 0);
+        assert_eq!(clock, Some(MidiEvent::system_realtime(7, SystemRealtime::TimingClock)));
+        // System common (song position pointer) is not supported.
+        assert_eq!(MidiEvent::from_midi1_bytes(0, 0xF0, 0x02, 0, 0), None);
+    }
+
+    #[test]
+    fn test_mmc_sysex_roundtrip() {
+        let bytes = Mmc::Play.to_sysex(MMC_ALL_DEVICES);
+        assert_eq!(bytes, [0xF0, 0x7F, 0x7F, 0x06, 0x02, 0xF7]);
+        assert_eq!(Mmc::from_sysex(&bytes), Some((0x7F, Mmc::Play)));
+        // Unframed payloads parse too.
+        assert_eq!(Mmc::from_sysex(&[0x7F, 0x10, 0x06, 0x01]), Some((0x10, Mmc::Stop)));
+        assert_eq!(Mmc::from_sysex(&[0xF0, 0x7E, 0x7F, 0x06, 0x01, 0xF7]), None);
+        assert_eq!(Mmc::Pause.to_event(3, 1).as_mmc(), Some((1, Mmc::Pause)));
+    }
+
+    #[test]
+    fn test_generator_clocks_follow_beat_grid() {
+        // 120 BPM at 48 kHz: 24000 samples per beat, 1000 per clock.
+        let mut generator = MidiClockGenerator::new();
+        let mut output = MidiBuffer::new_boxed();
+        generator.generate(MidiClockOutput::clock(), &playing(0.0, 120.0), 48000.0, 2500, &mut output);
+        assert_eq!(
+            realtime(&output),
+            vec![
+                (0, SystemRealtime::Start),
+                (0, SystemRealtime::TimingClock),
+                (1000, SystemRealtime::TimingClock),
+                (2000, SystemRealtime::TimingClock),
+            ]
+        );
+
+        // Next block starts mid-interval: first clock lands on the grid.
+        output.clear();
+        let beats = 2500.0 / 24000.0;
+        generator.generate(MidiClockOutput::clock(), &playing(beats, 120.0), 48000.0, 1000, &mut output);
+        assert_eq!(realtime(&output), vec![(500, SystemRealtime::TimingClock)]);
+
+        output.clear();
+        let stopped = Transport::default();
+        generator.generate(MidiClockOutput::clock(), &stopped, 48000.0, 1000, &mut output);
+        assert_eq!(realtime(&output), vec![(0, SystemRealtime::Stop)]);
+    }
+
+    #[test]
+    fn test_generator_no_duplicate_clock_at_block_boundary() {
+        let mut generator = MidiClockGenerator::new();
+        let mut output = MidiBuffer::new_boxed();
+        // The second clock lands just inside the end of the block.
+        let beats = (24.0 + 1e-7) / CLOCKS_PER_BEAT;
+        generator.generate(MidiClockOutput::clock(), &playing(beats, 120.0), 48000.0, 1000, &mut output);
+        assert_eq!(
+            realtime(&output),
+            vec![
+                (0, SystemRealtime::Continue),
+                (0, SystemRealtime::TimingClock),
+                (999, SystemRealtime::TimingClock),
+            ]
+        );
+
+        // The host reports the next block a hair before that clock.
+        output.clear();
+        let beats = (25.0 - 1e-7) / CLOCKS_PER_BEAT;
+        generator.generate(MidiClockOutput::clock(), &playing(beats, 120.0), 48000.0, 1000, &mut output);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_generator_mmc_on_transport_change() {
+        let mut generator = MidiClockGenerator::new();
+        let mut output = MidiBuffer::new_boxed();
+        let policy = MidiClockOutput::mmc(MMC_ALL_DEVICES);
+        generator.generate(policy, &playing(4.0, 120.0), 48000.0, 512, &mut output);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output.as_slice()[0].as_mmc(), Some((0x7F, Mmc::Play)));
+
+        output.clear();
+        generator.generate(policy, &playing(4.1, 120.0), 48000.0, 512, &mut output);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_receiver_estimates_tempo() {
+        // 100 BPM at 48 kHz: 1200 samples per clock.
+        let mut receiver = MidiClockReceiver::new(48000.0);
+        assert_eq!(receiver.tempo(), None);
+
+        receiver.receive(&MidiEvent::system_realtime(0, SystemRealtime::Start));
+        assert!(receiver.is_running());
+        for block in 0..10 {
+            for offset in [100, 1300] {
+                let clock = MidiEvent::system_realtime(offset, SystemRealtime::TimingClock);
+                assert!(receiver.receive(&clock));
+            }
+            if block < 9 {
+                receiver.end_block(2400);
+            }
+        }
+        assert!((receiver.tempo().unwrap() - 100.0).abs() < 1e-9);
+        assert!((receiver.position_beats() - 20.0 / 24.0).abs() < 1e-12);
+
+        receiver.receive(&Mmc::Stop.to_event(0, 0));
+        assert!(!receiver.is_running());
+
+        // Clock lost: no tempo after the timeout.
+        receiver.end_block(48000);
+        assert_eq!(receiver.tempo(), None);
+    }
+}
@@ -0,0 +1,521 @@
+//! Building blocks for MIDI effects: scheduling, delay, echo and humanize.
+//!
+//! MIDI effects move events in time, often past the end of the current
+//! block. [`MidiScheduler`] holds events until they are due and hands them
+//! out with block-relative offsets; the effects build on it:
+//!
+//! | Type | Does |
+//! |------|------|
+//! | [`MidiDelay`] | Delays every event by a fixed time |
+//! | [`MidiEcho`] | Repeats notes with decaying velocity (feedback) |
+//! | [`MidiHumanize`] | Randomizes timing, velocity and aftertouch from a seed |
+//!
+//! `process_midi()` doesn't see the block length, so store it in
+//! [`Processor::begin_block()`](crate::Processor::begin_block) and chain the
+//! effects through a scratch buffer:
+//!
+//! ```ignore
+//! fn begin_block(&mut self, num_samples: usize) {
+//!     self.block_samples = num_samples;
+//! }
+//!
+//! fn process_midi(&mut self, input: &[MidiEvent], output: &mut MidiBuffer) {
+//!     self.humanized.clear();
+//!     self.humanize.process(input, self.block_samples, &mut self.humanized);
+//!     self.echo.process(self.humanized.as_slice(), self.block_samples, output);
+//! }
+//! ```
+//!
+//! All storage is allocated in the constructors; processing is real-time
+//! safe (apart from cloning SysEx data) and keeps the output ordered by
+//! sample offset. Note-offs follow their note-ons, so no effect cuts notes
+//! short or leaves them hanging. Call `reset()` from
+//! [`Processor::panic()`](crate::Processor::panic) and on deactivation to
+//! drop pending events.
+
+use crate::midi::{MidiBuffer, MidiEvent, MidiEventKind, MAX_MIDI_EVENTS};
+
+/// Lowest velocity of an echo; quieter repeats are not sent.
+const MIN_ECHO_VELOCITY: f32 = 1.0 / 127.0;
+
+/// Holds MIDI events until they are due.
+///
+/// Events are scheduled relative to the current block and released in the
+/// block they fall into, in due order (events due at the same time keep the
+/// order they were scheduled in). Call [`release()`](Self::release) once per
+/// block, also for blocks without new events.
+pub struct MidiScheduler {
+    /// Running sample position of the current block's first sample.
+    position: u64,
+    /// Scheduled events as (due position, event), sorted by due position.
+    queue: Vec<(u64, MidiEvent)>,
+}
+
+impl MidiScheduler {
+    /// Create a scheduler holding up to [`MAX_MIDI_EVENTS`] events.
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_MIDI_EVENTS)
+    }
+
+    /// Create a scheduler holding up to `capacity` events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            position: 0,
+            queue: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of events waiting to be released.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Maximum number of waiting events.
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Schedule `event` for `delay` samples after its offset in the current
+    /// block.
+    ///
+    /// Returns false (dropping the event) if the scheduler is full.
+    pub fn schedule(&mut self, event: MidiEvent, delay: u64) -> bool {
+        if self.queue.len() == self.queue.capacity() {
+            return false;
+        }
+        let due = self.position + event.sample_offset as u64 + delay;
+        let index = self.queue.partition_point(|(d, _)| *d <= due);
+        self.queue.insert(index, (due, event));
+        true
+    }
+
+    /// Append the events due in the current block of `num_samples` to
+    /// `output` and advance to the next block.
+    ///
+    /// The output is kept ordered by sample offset. Events the output has
+    /// no room for are dropped (see [`MidiBuffer::has_overflowed()`]).
+    pub fn release(&mut self, num_samples: usize, output: &mut MidiBuffer) {
+        let end = self.position + num_samples as u64;
+        let count = self.queue.partition_point(|(due, _)| *due < end);
+        if count > 0 {
+            let block_start = self.position;
+            for (due, mut event) in self.queue.drain(..count) {
+                event.sample_offset = due.saturating_sub(block_start) as u32;
+                output.push(event);
+            }
+            output.sort_by_offset();
+        }
+        self.position = end;
+    }
+
+    /// Drop all waiting events and restart the time line.
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.queue.clear();
+    }
+}
+
+impl Default for MidiScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Delays every event by a fixed number of samples.
+pub struct MidiDelay {
+    delay: u64,
+    scheduler: MidiScheduler,
+}
+
+impl MidiDelay {
+    /// Create a delay of `delay_samples`.
+    pub fn new(delay_samples: u32) -> Self {
+        Self {
+            delay: delay_samples as u64,
+            scheduler: MidiScheduler::new(),
+        }
+    }
+
+    /// Create a delay of `ms` milliseconds.
+    pub fn from_ms(ms: f64, sample_rate: f64) -> Self {
+        Self::new(ms_to_samples(ms, sample_rate))
+    }
+
+    /// The delay in samples.
+    pub fn delay_samples(&self) -> u32 {
+        self.delay as u32
+    }
+
+    /// Change the delay. Applies to events arriving from now on; notes
+    /// already waiting keep their time, so shortening the delay while notes
+    /// are held can shorten them.
+    pub fn set_delay_samples(&mut self, delay_samples: u32) {
+        self.delay = delay_samples as u64;
+    }
+
+    /// Number of events waiting.
+    pub fn pending(&self) -> usize {
+        self.scheduler.pending()
+    }
+
+    /// Drop all waiting events.
+    pub fn reset(&mut self) {
+        self.scheduler.reset();
+    }
+
+    /// Delay one block of events into `output`.
+    ///
+    /// `input` must be sorted by sample offset. Events that don't fit the
+    /// scheduler pass through undelayed.
+    pub fn process(&mut self, input: &[MidiEvent], num_samples: usize, output: &mut MidiBuffer) {
+        for event in input {
+            if !self.scheduler.schedule(event.clone(), self.delay) {
+                output.push(event.clone());
+            }
+        }
+        self.scheduler.release(num_samples, output);
+    }
+}
+
+/// Repeats notes with decaying velocity.
+///
+/// Every note-on passes through and is repeated every
+/// [delay](Self::set_delay_samples), each repeat at
+/// [feedback](Self::set_feedback) times the previous velocity, until
+/// [max_repeats](Self::set_max_repeats) or the velocity drops below 1/127.
+/// Note-offs are repeated as often as their note-on, so every echo keeps the
+/// original note length. Other events pass through once.
+pub struct MidiEcho {
+    delay: u64,
+    feedback: f32,
+    max_repeats: u8,
+    scheduler: MidiScheduler,
+    /// Repeats of the last note-on per channel (16) and pitch (128).
+    repeats: Vec<u8>,
+}
+
+impl MidiEcho {
+    /// Create an echo repeating every `delay_samples` with `feedback`
+    /// (0.0-1.0) up to `max_repeats` times.
+    pub fn new(delay_samples: u32, feedback: f32, max_repeats: u8) -> Self {
+        Self {
+            delay: delay_samples as u64,
+            feedback: feedback.clamp(0.0, 1.0),
+            max_repeats,
+            scheduler: MidiScheduler::new(),
+            repeats: vec![0; 16 * 128],
+        }
+    }
+
+    /// The time between repeats in samples.
+    pub fn delay_samples(&self) -> u32 {
+        self.delay as u32
+    }
+
+    /// Change the time between repeats. Applies to notes arriving from now
+    /// on.
+    pub fn set_delay_samples(&mut self, delay_samples: u32) {
+        self.delay = delay_samples as u64;
+    }
+
+    /// Velocity of each repeat relative to the previous one.
+    pub fn feedback(&self) -> f32 {
+        self.feedback
+    }
+
+    /// Change the feedback (clamped to 0.0-1.0). 0.0 turns the echo off.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(0.0, 1.0);
+    }
+
+    /// Maximum number of repeats per note.
+    pub fn max_repeats(&self) -> u8 {
+        self.max_repeats
+    }
+
+    /// Change the maximum number of repeats per note.
+    pub fn set_max_repeats(&mut self, max_repeats: u8) {
+        self.max_repeats = max_repeats;
+    }
+
+    /// Number of repeats waiting.
+    pub fn pending(&self) -> usize {
+        self.scheduler.pending()
+    }
+
+    /// Drop all waiting repeats.
+    pub fn reset(&mut self) {
+        self.scheduler.reset();
+        self.repeats.fill(0);
+    }
+
+    /// Echo one block of events into `output`.
+    ///
+    /// `input` must be sorted by sample offset. Repeats of new notes are
+    /// only scheduled while the scheduler is at most half full, so the
+    /// matching note-offs find room.
+    pub fn process(&mut self, input: &[MidiEvent], num_samples: usize, output: &mut MidiBuffer) {
+        for event in input {
+            output.push(event.clone());
+            let Some((slot, is_on)) = note_slot(event) else {
+                continue;
+            };
+            if is_on {
+                let velocity = note_velocity(event);
+                let mut level = velocity;
+                let mut repeats = 0;
+                while repeats < self.max_repeats && self.scheduler.pending() < self.scheduler.capacity() / 2 {
+                    level *= self.feedback;
+                    if level < MIN_ECHO_VELOCITY {
+                        break;
+                    }
+                    repeats += 1;
+                    self.scheduler.schedule(echo(event, level), self.delay * repeats as u64);
+                }
+                self.repeats[slot] = repeats;
+            } else {
+                let repeats = std::mem::take(&mut self.repeats[slot]);
+                for repeat in 1..=repeats as u64 {
+                    self.scheduler.schedule(echo(event, note_velocity(event)), self.delay * repeat);
+                }
+            }
+        }
+        self.scheduler.release(num_samples, output);
+    }
+}
+
+/// Randomizes timing, velocity and aftertouch.
+///
+/// Each note-on is delayed by a random 0 to
+/// [timing](Self::set_timing_samples) samples and its velocity scaled by up
+/// to ±[velocity](Self::set_velocity); note-offs and polyphonic pressure
+/// follow their note-on's delay, so note lengths are kept. Channel and
+/// polyphonic pressure are scaled by up to ±[aftertouch](Self::set_aftertouch).
+/// Other events pass through unchanged.
+///
+/// The random sequence is fully determined by the seed: the same input from
+/// a [reset](Self::reset) humanizer always gives the same output, so renders
+/// are repeatable.
+pub struct MidiHumanize {
+    seed: u64,
+    state: u64,
+    timing: u32,
+    velocity: f32,
+    aftertouch: f32,
+    scheduler: MidiScheduler,
+    /// Delay of the last note-on per channel (16) and pitch (128).
+    delays: Vec<u32>,
+}
+
+impl MidiHumanize {
+    /// Create a humanizer with the random sequence `seed`, initially
+    /// leaving events unchanged.
+    pub fn new(seed: u64) -> Self {
+        let mut humanize = Self {
+            seed,
+            state: 0,
+            timing: 0,
+            velocity: 0.0,
+            aftertouch: 0.0,
+            scheduler: MidiScheduler::new(),
+            delays: vec![0; 16 * 128],
+        };
+        humanize.reset();
+        humanize
+    }
+
+    /// Set the maximum note delay in samples.
+    pub fn set_timing_samples(&mut self, samples: u32) {
+        self.timing = samples;
+    }
+
+    /// Set the maximum note delay in milliseconds.
+    pub fn set_timing_ms(&mut self, ms: f64, sample_rate: f64) {
+        self.timing = ms_to_samples(ms, sample_rate);
+    }
+
+    /// Set the velocity variation (0.0-1.0; 0.1 = ±10 %).
+    pub fn set_velocity(&mut self, amount: f32) {
+        self.velocity = amount.clamp(0.0, 1.0);
+    }
+
+    /// Set the aftertouch variation (0.0-1.0).
+    pub fn set_aftertouch(&mut self, amount: f32) {
+        self.aftertouch = amount.clamp(0.0, 1.0);
+    }
+
+    /// Number of delayed events waiting.
+    pub fn pending(&self) -> usize {
+        self.scheduler.pending()
+    }
+
+    /// Drop waiting events and restart the random sequence from the seed.
+    pub fn reset(&mut self) {
+        // xorshift must not start at zero
+        self.state = self.seed | 1;
+        self.scheduler.reset();
+        self.delays.fill(0);
+    }
+
+    /// Humanize one block of events into `output`.
+    ///
+    /// `input` must be sorted by sample offset. Notes that don't fit the
+    /// scheduler pass through undelayed.
+    pub fn process(&mut self, input: &[MidiEvent], num_samples: usize, output: &mut MidiBuffer) {
+        for event in input {
+            let mut event = event.clone();
+            let delay = match &mut event.event {
+                MidiEventKind::NoteOn(on) if on.velocity > 0.0 => {
+                    let delay = (self.next_random() * (self.timing as f64 + 1.0)) as u32;
+                    let delay = delay.min(self.timing);
+                    on.velocity = self.vary(on.velocity, self.velocity).max(MIN_ECHO_VELOCITY);
+                    self.delays[slot(on.channel, on.pitch)] = delay;
+                    delay
+                }
+                MidiEventKind::NoteOn(on) => std::mem::take(&mut self.delays[slot(on.channel, on.pitch)]),
+                MidiEventKind::NoteOff(off) => std::mem::take(&mut self.delays[slot(off.channel, off.pitch)]),
+                MidiEventKind::PolyPressure(pressure) => {
+                    pressure.pressure = self.vary(pressure.pressure, self.aftertouch);
+                    self.delays[slot(pressure.channel, pressure.pitch)]
+                }
+                MidiEventKind::ChannelPressure(pressure) => {
+                    pressure.pressure = self.vary(pressure.pressure, self.aftertouch);
+                    0
+                }
+                _ => 0,
+            };
+            if delay == 0 || !self.scheduler.schedule(event.clone(), delay as u64) {
+                output.push(event);
+            }
+        }
+        self.scheduler.release(num_samples, output);
+    }
+
+    /// `value` scaled by a random factor within ±`amount`, clamped to 0-1.
+    fn vary(&mut self, value: f32, amount: f32) -> f32 {
+        if amount == 0.0 {
+            return value;
+        }
+        let factor = 1.0 + amount * (self.next_random() as f32 * 2.0 - 1.0);
+        (value * factor).clamp(0.0, 1.0)
+    }
+
+    /// Next random value in 0.0..1.0 (xorshift64).
+    fn next_random(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn ms_to_samples(ms: f64, sample_rate: f64) -> u32 {
+    (ms * 0.001 * sample_rate).round().max(0.0) as u32
+}
+
+/// Index into a channel/pitch table.
+fn slot(channel: u8, pitch: u8) -> usize {
+    (channel as usize & 15) * 128 + (pitch as usize & 127)
+}
+
+/// Channel/pitch slot of a note event and whether it starts a note.
+/// Velocity 0 note-ons count as note-offs.
+fn note_slot(event: &MidiEvent) -> Option<(usize, bool)> {
+    match &event.event {
+        MidiEventKind::NoteOn(on) => Some((slot(on.channel, on.pitch), on.velocity > 0.0)),
+        MidiEventKind::NoteOff(off) => Some((slot(off.channel, off.pitch), false)),
+        _ => None,
+    }
+}
+
+fn note_velocity(event: &MidiEvent) -> f32 {
+    match &event.event {
+        MidiEventKind::NoteOn(on) => on.velocity,
+        MidiEventKind::NoteOff(off) => off.velocity,
+        _ => 0.0,
+    }
+}
+
+/// A repeat of a note event at `velocity`, without the original's note ID
+/// so hosts don't confuse it with the sounding note.
+fn echo(event: &MidiEvent, velocity: f32) -> MidiEvent {
+    let mut echo = event.clone();
+    match &mut echo.event {
+        MidiEventKind::NoteOn(on) => {
+            on.velocity = velocity;
+            on.note_id = -1;
+        }
+        MidiEventKind::NoteOff(off) => off.note_id = -1,
+        _ => {}
+    }
+    echo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(output: &MidiBuffer) -> Vec<(u32, bool, f32)> {
+        output
+            .iter()
+            .filter_map(|e| note_slot(e).map(|(_, on)| (e.sample_offset, on, note_velocity(e))))
+            .collect()
+    }
+
+    #[test]
+    fn delay_and_echo_carry_events_across_blocks() {
+        let mut output = MidiBuffer::new_boxed();
+        let mut delay = MidiDelay::new(100);
+        delay.process(&[MidiEvent::note_on(10, 0, 60, 0.8, 7, 0.0, 0)], 64, &mut output);
+        assert!(output.is_empty());
+        delay.process(&[MidiEvent::note_off(0, 0, 60, 0.0, 7, 0.0)], 64, &mut output);
+        assert_eq!(notes(&output), [(46, true, 0.8)]);
+        assert_eq!(delay.pending(), 1);
+
+        let mut echo = MidiEcho::new(50, 0.5, 8);
+        output.clear();
+        echo.process(
+            &[MidiEvent::note_on(0, 0, 60, 0.8, 7, 0.0, 0), MidiEvent::note_off(20, 0, 60, 0.0, 7, 0.0)],
+            128,
+            &mut output,
+        );
+        // Dry note, then the first two repeats (each 20 samples long)
+        assert_eq!(
+            notes(&output),
+            [(0, true, 0.8), (20, false, 0.0), (50, true, 0.4), (70, false, 0.0), (100, true, 0.2), (120, false, 0.0)]
+        );
+        // Repeats decay below 1/127 after six: four more note-ons and
+        // note-offs are waiting
+        assert_eq!(echo.pending(), 8);
+        echo.reset();
+        assert_eq!(echo.pending(), 0);
+    }
+
+    #[test]
+    fn humanize_is_repeatable_and_keeps_note_lengths() {
+        let input = [
+            MidiEvent::note_on(0, 0, 60, 0.5, -1, 0.0, 0),
+            MidiEvent::note_off(30, 0, 60, 0.0, -1, 0.0),
+            MidiEvent::note_on(40, 0, 64, 0.5, -1, 0.0, 0),
+            MidiEvent::note_off(50, 0, 64, 0.0, -1, 0.0),
+        ];
+        let mut humanize = MidiHumanize::new(42);
+        humanize.set_timing_samples(20);
+        humanize.set_velocity(0.2);
+
+        let mut first = MidiBuffer::new_boxed();
+        humanize.process(&input, 256, &mut first);
+        humanize.reset();
+        let mut second = MidiBuffer::new_boxed();
+        humanize.process(&input, 256, &mut second);
+        assert_eq!(first.as_slice(), second.as_slice());
+
+        let notes = notes(&first);
+        assert_eq!(notes.len(), 4);
+        for (on, off) in [(0, 30), (40, 50)] {
+            let on_at = notes.iter().find(|n| n.1 && n.0 >= on && n.0 <= on + 20).unwrap().0;
+            assert!(notes.iter().any(|n| !n.1 && n.0 == on_at + off - on));
+        }
+        assert!(notes.iter().filter(|n| n.1).all(|n| (0.4..=0.6).contains(&n.2)));
+    }
+}
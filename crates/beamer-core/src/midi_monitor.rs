@@ -0,0 +1,549 @@
+//! MIDI monitor for diagnosing host MIDI delivery.
+//!
+//! Hosts differ in what MIDI they deliver: some drop note-offs on transport
+//! stop, send running CCs as 0/127 or repeat program changes on load. The
+//! [`MidiMonitor`] records every event as the host delivered it (before
+//! response curves, note range filtering and MPE collapsing) and every
+//! event the plugin sends back, so a GUI can show the raw traffic.
+//!
+//! # Enabling
+//!
+//! The monitor is opt-in: the descriptor creates one with
+//! [`MidiMonitor::new()`] and returns it from
+//! [`Descriptor::midi_monitor()`](crate::Descriptor::midi_monitor). A
+//! plugin can ship it enabled or create it only in debug builds.
+//!
+//! # Real-Time Safety
+//!
+//! [`record()`](MidiMonitor::record) is lock-free and allocation-free. The
+//! oldest entries are overwritten once the buffer is full; readers notice
+//! the gap through [`MidiMonitorRead::dropped`]. Reading allocates and
+//! belongs on the main thread.
+//!
+//! # GUI Access
+//!
+//! The WebView runtime streams the monitor with
+//! `__BEAMER__.midiMonitor.on(callback, filter)`, which polls
+//! [`MIDI_MONITOR_INVOKE`] with a cursor so each entry arrives once.
+
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::midi::{MidiChannel, MidiEvent, MidiEventKind, NoteId};
+
+/// Built-in WebView invoke method that returns the entries after a cursor.
+pub const MIDI_MONITOR_INVOKE: &str = "_beamer/midiMonitor";
+
+/// Built-in WebView invoke method that clears the monitor.
+pub const CLEAR_MIDI_MONITOR_INVOKE: &str = "_beamer/clearMidiMonitor";
+
+/// Whether an event came from the host or went to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MidiDirection {
+    /// Delivered by the host.
+    Input = 0,
+    /// Sent by the plugin.
+    Output = 1,
+}
+
+impl MidiDirection {
+    /// Short lowercase name, as used in the JSON dump.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Output => "output",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "input" => Some(Self::Input),
+            "output" => Some(Self::Output),
+            _ => None,
+        }
+    }
+}
+
+/// Event type of a monitor entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MidiMonitorKind {
+    /// Note on.
+    NoteOn = 0,
+    /// Note off.
+    NoteOff = 1,
+    /// Polyphonic key pressure.
+    PolyPressure = 2,
+    /// Control change.
+    ControlChange = 3,
+    /// Pitch bend.
+    PitchBend = 4,
+    /// Channel pressure.
+    ChannelPressure = 5,
+    /// Program change.
+    ProgramChange = 6,
+    /// System real-time message.
+    SystemRealtime = 7,
+    /// System exclusive message.
+    SysEx = 8,
+    /// Per-note expression (value, integer or text).
+    NoteExpression = 9,
+    /// Chord from the host's chord track.
+    Chord = 10,
+    /// Scale from the host.
+    Scale = 11,
+}
+
+const KINDS: [MidiMonitorKind; 12] = [
+    MidiMonitorKind::NoteOn,
+    MidiMonitorKind::NoteOff,
+    MidiMonitorKind::PolyPressure,
+    MidiMonitorKind::ControlChange,
+    MidiMonitorKind::PitchBend,
+    MidiMonitorKind::ChannelPressure,
+    MidiMonitorKind::ProgramChange,
+    MidiMonitorKind::SystemRealtime,
+    MidiMonitorKind::SysEx,
+    MidiMonitorKind::NoteExpression,
+    MidiMonitorKind::Chord,
+    MidiMonitorKind::Scale,
+];
+
+impl MidiMonitorKind {
+    /// Short camelCase name, as used in the JSON dump and filters.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NoteOn => "noteOn",
+            Self::NoteOff => "noteOff",
+            Self::PolyPressure => "polyPressure",
+            Self::ControlChange => "controlChange",
+            Self::PitchBend => "pitchBend",
+            Self::ChannelPressure => "channelPressure",
+            Self::ProgramChange => "programChange",
+            Self::SystemRealtime => "systemRealtime",
+            Self::SysEx => "sysEx",
+            Self::NoteExpression => "noteExpression",
+            Self::Chord => "chord",
+            Self::Scale => "scale",
+        }
+    }
+
+    /// Returns true if entries of this kind carry a MIDI channel.
+    pub fn has_channel(self) -> bool {
+        (self as u8) <= Self::ProgramChange as u8
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        KINDS.into_iter().find(|kind| kind.as_str() == name)
+    }
+}
+
+/// One monitored event, reduced to what a monitor displays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiMonitorEntry {
+    /// Position in the monitor (increases by one per recorded event).
+    pub sequence: u64,
+    /// When the event was recorded.
+    pub time: SystemTime,
+    /// From or to the host.
+    pub direction: MidiDirection,
+    /// Sample offset within its block.
+    pub sample_offset: u32,
+    /// Event type.
+    pub kind: MidiMonitorKind,
+    /// MIDI channel (0-15), 0 for kinds without one.
+    pub channel: MidiChannel,
+    /// Pitch, controller number, program, real-time status byte, SysEx
+    /// length, expression type, or chord/scale mask.
+    pub data: u32,
+    /// Velocity, controller value, bend, pressure or expression value;
+    /// the root for chords and scales; `NaN` when the kind has none.
+    pub value: f64,
+    /// Host note ID for note events and expressions, -1 otherwise.
+    pub note_id: NoteId,
+}
+
+/// Reduce `event` to `(kind, channel, data, value, note_id)`.
+fn summarize(event: &MidiEventKind) -> (MidiMonitorKind, MidiChannel, u32, f64, NoteId) {
+    use MidiMonitorKind as K;
+    match event {
+        MidiEventKind::NoteOn(e) => (K::NoteOn, e.channel, e.pitch as u32, e.velocity as f64, e.note_id),
+        MidiEventKind::NoteOff(e) => (K::NoteOff, e.channel, e.pitch as u32, e.velocity as f64, e.note_id),
+        MidiEventKind::PolyPressure(e) => {
+            (K::PolyPressure, e.channel, e.pitch as u32, e.pressure as f64, e.note_id)
+        }
+        MidiEventKind::ControlChange(e) => {
+            (K::ControlChange, e.channel, e.controller as u32, e.value as f64, -1)
+        }
+        MidiEventKind::PitchBend(e) => (K::PitchBend, e.channel, 0, e.value as f64, -1),
+        MidiEventKind::ChannelPressure(e) => (K::ChannelPressure, e.channel, 0, e.pressure as f64, -1),
+        MidiEventKind::ProgramChange(e) => (K::ProgramChange, e.channel, e.program as u32, f64::NAN, -1),
+        MidiEventKind::SystemRealtime(e) => (K::SystemRealtime, 0, e.status() as u32, f64::NAN, -1),
+        MidiEventKind::SysEx(e) => (K::SysEx, 0, e.len as u32, f64::NAN, -1),
+        MidiEventKind::NoteExpressionValue(e) => {
+            (K::NoteExpression, 0, e.expression_type, e.value, e.note_id)
+        }
+        MidiEventKind::NoteExpressionInt(e) => {
+            (K::NoteExpression, 0, e.expression_type, e.value as f64, e.note_id)
+        }
+        MidiEventKind::NoteExpressionText(e) => {
+            (K::NoteExpression, 0, e.expression_type, f64::NAN, e.note_id)
+        }
+        MidiEventKind::ChordInfo(e) => (K::Chord, 0, e.mask as u32, e.root as f64, -1),
+        MidiEventKind::ScaleInfo(e) => (K::Scale, 0, e.mask as u32, e.root as f64, -1),
+    }
+}
+
+/// Ring buffer slot, written under a per-slot sequence number.
+#[derive(Debug, Default)]
+struct Slot {
+    /// `2 * (n + 1)` once entry `n` is complete, odd while it is written.
+    sequence: AtomicU64,
+    /// Microseconds since the monitor was created.
+    micros: AtomicU64,
+    /// Kind in the low 8 bits, channel in the next 8, direction in the
+    /// next 8, data in the high 32.
+    header: AtomicU64,
+    /// Sample offset in the low 32 bits, note ID in the high 32.
+    offset_note: AtomicU64,
+    value: AtomicU64,
+}
+
+#[derive(Debug)]
+struct MonitorInner {
+    slots: Box<[Slot]>,
+    /// Number of entries ever recorded.
+    head: AtomicU64,
+    /// Entries before this number were cleared.
+    cleared: AtomicU64,
+    start: Instant,
+    start_time: SystemTime,
+}
+
+/// Lock-free log of the MIDI going in and out of the plugin.
+///
+/// Cloning is cheap (reference counted), so the descriptor, the wrapper
+/// and its WebView IPC context share one monitor. The default monitor is
+/// disabled and records nothing.
+#[derive(Clone, Debug, Default)]
+pub struct MidiMonitor {
+    inner: Option<Arc<MonitorInner>>,
+}
+
+/// Result of [`MidiMonitor::read`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiMonitorRead {
+    /// Entries after the cursor, oldest first.
+    pub entries: Vec<MidiMonitorEntry>,
+    /// Cursor for the next read.
+    pub next: u64,
+    /// Entries after the cursor that were overwritten before this read.
+    pub dropped: u64,
+}
+
+impl MidiMonitor {
+    /// Create a monitor holding the last `capacity` events.
+    ///
+    /// Allocates; create it with the descriptor. A capacity of 0 gives a
+    /// disabled monitor.
+    pub fn new(capacity: usize) -> Self {
+        if capacity == 0 {
+            return Self::default();
+        }
+        Self {
+            inner: Some(Arc::new(MonitorInner {
+                slots: (0..capacity).map(|_| Slot::default()).collect(),
+                head: AtomicU64::new(0),
+                cleared: AtomicU64::new(0),
+                start: Instant::now(),
+                start_time: SystemTime::now(),
+            })),
+        }
+    }
+
+    /// Returns true if events are recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Record one event.
+    ///
+    /// Real-time safe.
+    #[inline]
+    pub fn record(&self, direction: MidiDirection, event: &MidiEvent) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let (kind, channel, data, value, note_id) = summarize(&event.event);
+        let n = inner.head.fetch_add(1, Ordering::Relaxed);
+        let slot = &inner.slots[(n % inner.slots.len() as u64) as usize];
+
+        slot.sequence.store(2 * n + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        slot.micros
+            .store(inner.start.elapsed().as_micros() as u64, Ordering::Relaxed);
+        slot.header.store(
+            kind as u64 | (channel as u64) << 8 | (direction as u64) << 16 | (data as u64) << 32,
+            Ordering::Relaxed,
+        );
+        slot.offset_note.store(
+            event.sample_offset as u64 | (note_id as u32 as u64) << 32,
+            Ordering::Relaxed,
+        );
+        slot.value.store(value.to_bits(), Ordering::Relaxed);
+        slot.sequence.store(2 * (n + 1), Ordering::Release);
+    }
+
+    /// Record a block of events.
+    ///
+    /// Real-time safe.
+    #[inline]
+    pub fn record_all<'a>(&self, direction: MidiDirection, events: impl IntoIterator<Item = &'a MidiEvent>) {
+        if self.inner.is_none() {
+            return;
+        }
+        for event in events {
+            self.record(direction, event);
+        }
+    }
+
+    /// Forget all recorded entries.
+    pub fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            inner
+                .cleared
+                .store(inner.head.load(Ordering::Acquire), Ordering::Release);
+        }
+    }
+
+    /// Entries from sequence `since` on, oldest first, at most `limit` of
+    /// them (the most recent ones).
+    ///
+    /// Pass the previous read's [`next`](MidiMonitorRead::next) to get each
+    /// entry once. Entries being overwritten while this runs are skipped.
+    pub fn read(&self, since: u64, limit: usize) -> MidiMonitorRead {
+        let Some(inner) = &self.inner else {
+            return MidiMonitorRead { entries: Vec::new(), next: 0, dropped: 0 };
+        };
+        let head = inner.head.load(Ordering::Acquire);
+        let cleared = inner.cleared.load(Ordering::Acquire);
+        let since = since.max(cleared).min(head);
+        let oldest = head.saturating_sub(inner.slots.len() as u64).max(since);
+        let first = oldest.max(head.saturating_sub(limit as u64));
+
+        let entries = (first..head)
+            .filter_map(|n| {
+                let slot = &inner.slots[(n % inner.slots.len() as u64) as usize];
+                let expected = 2 * (n + 1);
+                if slot.sequence.load(Ordering::Acquire) != expected {
+                    return None;
+                }
+                let micros = slot.micros.load(Ordering::Relaxed);
+                let header = slot.header.load(Ordering::Relaxed);
+                let offset_note = slot.offset_note.load(Ordering::Relaxed);
+                let value = f64::from_bits(slot.value.load(Ordering::Relaxed));
+                fence(Ordering::Acquire);
+                if slot.sequence.load(Ordering::Relaxed) != expected {
+                    return None;
+                }
+                Some(MidiMonitorEntry {
+                    sequence: n,
+                    time: inner.start_time + Duration::from_micros(micros),
+                    direction: if (header >> 16) as u8 == 0 {
+                        MidiDirection::Input
+                    } else {
+                        MidiDirection::Output
+                    },
+                    sample_offset: offset_note as u32,
+                    kind: *KINDS.get(header as u8 as usize)?,
+                    channel: (header >> 8) as u8,
+                    data: (header >> 32) as u32,
+                    value,
+                    note_id: (offset_note >> 32) as u32 as i32,
+                })
+            })
+            .collect();
+
+        MidiMonitorRead { entries, next: head, dropped: oldest - since }
+    }
+}
+
+/// Which entries a GUI wants to see. An empty filter passes everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MidiMonitorFilter {
+    /// Only this direction.
+    pub direction: Option<MidiDirection>,
+    /// Only these kinds (all if empty).
+    pub kinds: Vec<MidiMonitorKind>,
+    /// Bit `n` set: channel `n` passes (all if 0). Kinds without a channel
+    /// always pass.
+    pub channels: u16,
+}
+
+impl MidiMonitorFilter {
+    /// Parse the JSON filter of the WebView API,
+    /// `{direction: "input", kinds: ["noteOn"], channels: [0, 9]}`.
+    /// Unknown names are ignored.
+    pub fn from_json(json: &serde_json::Value) -> Self {
+        let names = |key| json.get(key).and_then(|v| v.as_array()).into_iter().flatten();
+        Self {
+            direction: json.get("direction").and_then(|v| v.as_str()).and_then(MidiDirection::parse),
+            kinds: names("kinds")
+                .filter_map(|v| v.as_str().and_then(MidiMonitorKind::parse))
+                .collect(),
+            channels: names("channels")
+                .filter_map(|v| v.as_u64())
+                .filter(|&channel| channel < 16)
+                .fold(0, |mask, channel| mask | 1 << channel),
+        }
+    }
+
+    /// Returns true if `entry` passes.
+    pub fn matches(&self, entry: &MidiMonitorEntry) -> bool {
+        self.direction.is_none_or(|direction| direction == entry.direction)
+            && (self.kinds.is_empty() || self.kinds.contains(&entry.kind))
+            && (self.channels == 0
+                || !entry.kind.has_channel()
+                || self.channels & 1 << entry.channel != 0)
+    }
+}
+
+fn entry_json(entry: &MidiMonitorEntry) -> serde_json::Value {
+    let time = entry
+        .time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0);
+    let mut json = serde_json::json!({
+        "sequence": entry.sequence,
+        "time": time,
+        "direction": entry.direction.as_str(),
+        "sampleOffset": entry.sample_offset,
+        "kind": entry.kind.as_str(),
+        "data": entry.data,
+    });
+    if entry.kind.has_channel() {
+        json["channel"] = serde_json::Value::from(entry.channel);
+    }
+    if !entry.value.is_nan() {
+        json["value"] = serde_json::Value::from(entry.value);
+    }
+    if entry.note_id >= 0 {
+        json["noteId"] = serde_json::Value::from(entry.note_id);
+    }
+    json
+}
+
+/// JSON read of the monitor from cursor `since`:
+/// `{entries: [...], next, dropped}`, entries filtered by `filter`.
+pub fn midi_monitor_json(
+    monitor: &MidiMonitor,
+    since: u64,
+    filter: &MidiMonitorFilter,
+    limit: usize,
+) -> serde_json::Value {
+    let read = monitor.read(since, limit);
+    let entries = read
+        .entries
+        .iter()
+        .filter(|entry| filter.matches(entry))
+        .map(entry_json)
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "entries": entries,
+        "next": read.next,
+        "dropped": read.dropped,
+        "enabled": monitor.is_enabled(),
+    })
+}
+
+/// Handle the built-in MIDI monitor WebView calls.
+///
+/// Returns `None` if `method` is not a MIDI monitor method.
+/// [`MIDI_MONITOR_INVOKE`] takes an optional cursor, filter object and
+/// entry limit.
+pub fn midi_monitor_invoke(
+    monitor: &MidiMonitor,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    match method {
+        MIDI_MONITOR_INVOKE => {
+            let since = args.first().and_then(|v| v.as_u64()).unwrap_or(0);
+            let filter = args
+                .get(1)
+                .map(MidiMonitorFilter::from_json)
+                .unwrap_or_default();
+            let limit = args
+                .get(2)
+                .and_then(|v| v.as_u64())
+                .map_or(usize::MAX, |limit| limit as usize);
+            Some(midi_monitor_json(monitor, since, &filter, limit))
+        }
+        CLEAR_MIDI_MONITOR_INVOKE => {
+            monitor.clear();
+            Some(serde_json::Value::Null)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_reads_each_entry_once() {
+        let monitor = MidiMonitor::new(4);
+        monitor.record(MidiDirection::Input, &MidiEvent::note_on(3, 1, 60, 0.5, 7, 0.0, 0));
+        monitor.record(MidiDirection::Output, &MidiEvent::control_change(0, 2, 74, 0.25));
+
+        let first = monitor.read(0, usize::MAX);
+        assert_eq!(first.entries.len(), 2);
+        assert_eq!(first.dropped, 0);
+        let note = first.entries[0];
+        assert_eq!(note.kind, MidiMonitorKind::NoteOn);
+        assert_eq!((note.channel, note.data, note.value, note.note_id), (1, 60, 0.5, 7));
+        assert_eq!(note.sample_offset, 3);
+        assert_eq!(first.entries[1].direction, MidiDirection::Output);
+
+        assert!(monitor.read(first.next, usize::MAX).entries.is_empty());
+
+        // Overrun: 5 new entries in a ring of 4
+        for pitch in 0..5 {
+            monitor.record(MidiDirection::Input, &MidiEvent::note_off(0, 0, pitch, 0.0, -1, 0.0));
+        }
+        let second = monitor.read(first.next, usize::MAX);
+        assert_eq!(second.dropped, 1);
+        let pitches: Vec<_> = second.entries.iter().map(|e| e.data).collect();
+        assert_eq!(pitches, [1, 2, 3, 4]);
+
+        monitor.clear();
+        assert!(monitor.read(0, usize::MAX).entries.is_empty());
+        assert!(!MidiMonitor::default().is_enabled());
+    }
+
+    #[test]
+    fn test_invoke_filters() {
+        let monitor = MidiMonitor::new(16);
+        monitor.record(MidiDirection::Input, &MidiEvent::note_on(0, 0, 60, 1.0, -1, 0.0, 0));
+        monitor.record(MidiDirection::Input, &MidiEvent::note_on(0, 9, 36, 1.0, -1, 0.0, 0));
+        monitor.record(MidiDirection::Input, &MidiEvent::control_change(0, 9, 1, 0.5));
+        monitor.record(MidiDirection::Output, &MidiEvent::note_on(0, 9, 38, 1.0, -1, 0.0, 0));
+
+        let filter = serde_json::json!({"direction": "input", "kinds": ["noteOn"], "channels": [9]});
+        let json = midi_monitor_invoke(&monitor, MIDI_MONITOR_INVOKE, &[0.into(), filter]).unwrap();
+        let entries = json["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["data"], 36);
+        assert_eq!(entries[0]["channel"], 9);
+        assert!(entries[0].get("noteId").is_none());
+        assert_eq!(json["next"], 4);
+
+        midi_monitor_invoke(&monitor, CLEAR_MIDI_MONITOR_INVOKE, &[]);
+        let json = midi_monitor_invoke(&monitor, MIDI_MONITOR_INVOKE, &[]).unwrap();
+        assert!(json["entries"].as_array().unwrap().is_empty());
+        assert!(midi_monitor_invoke(&monitor, "other", &[]).is_none());
+    }
+}
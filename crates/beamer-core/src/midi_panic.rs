@@ -0,0 +1,320 @@
+//! Built-in MIDI panic: silence every voice on request.
+//!
+//! Every instrument needs a way out of stuck notes, and hosts differ in
+//! whether (and how) they offer one. Plugins whose descriptor
+//! [wants MIDI](crate::Descriptor::wants_midi) get a panic path without any
+//! plugin code:
+//!
+//! | Trigger | How |
+//! |---------|-----|
+//! | Host | Hidden MIDI Panic parameter ([`MIDI_PANIC_PARAMETER_ID`]) set to On |
+//! | WebView | `__BEAMER__.midiPanic()` ([`MIDI_PANIC_INVOKE`]) |
+//! | Rust | [`MidiPanic::request()`] from any thread |
+//!
+//! At the start of the next block the wrapper calls
+//! [`Processor::panic()`](crate::Processor::panic) and puts All Sound Off
+//! (CC 120) and All Notes Off (CC 123) on all 16 channels ahead of the
+//! block's events passed to
+//! [`process_midi()`](crate::Processor::process_midi). Plugins that already
+//! handle those controllers need nothing else; `panic()` is the place to
+//! reset anything else (delay lines, envelopes, arpeggiators).
+//!
+//! The parameter is a momentary trigger: it always reads Off, is not saved
+//! and the wrappers report it back to Off after a host set it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::midi::{cc, MidiChannel, MidiEvent};
+use crate::parameter_info::{ParameterFlags, ParameterInfo};
+use crate::parameter_store::ParameterStore;
+use crate::types::{ParameterId, ParameterValue};
+
+/// Parameter ID of MIDI Panic.
+///
+/// Above the [processing statistics](crate::PROCESSING_STATS_PARAM_BASE)
+/// and below the VST3 limit of 2³¹.
+pub const MIDI_PANIC_PARAMETER_ID: ParameterId = 0x1100_0200;
+
+/// Built-in WebView invoke method that requests a panic.
+pub const MIDI_PANIC_INVOKE: &str = "_beamer/midiPanic";
+
+/// Number of events a panic puts ahead of the block: two per channel.
+pub const PANIC_EVENT_COUNT: usize = 2 * 16;
+
+/// Shared MIDI panic trigger.
+///
+/// Cheap to clone; all clones share the request. The default is disabled
+/// and has no parameter.
+#[derive(Clone, Default)]
+pub struct MidiPanic {
+    inner: Option<Arc<PanicTrigger>>,
+}
+
+struct PanicTrigger {
+    info: ParameterInfo,
+    /// A panic was requested and not yet applied.
+    pending: AtomicBool,
+    /// The host set the parameter On since the last check; its displayed
+    /// value must return to Off.
+    released: AtomicBool,
+}
+
+impl MidiPanic {
+    /// Create an enabled trigger.
+    pub fn new() -> Self {
+        let info = ParameterInfo::new(MIDI_PANIC_PARAMETER_ID, "MIDI Panic")
+            .with_string_id("_beamer_midi_panic")
+            .with_short_name("Panic")
+            .with_steps(1)
+            .with_flags(ParameterFlags {
+                is_hidden: true,
+                is_persistent: false,
+                is_randomizable: false,
+                ..ParameterFlags::default()
+            });
+        Self {
+            inner: Some(Arc::new(PanicTrigger {
+                info,
+                pending: AtomicBool::new(false),
+                released: AtomicBool::new(false),
+            })),
+        }
+    }
+
+    /// Enabled when `enabled`, the disabled default otherwise.
+    pub fn enabled(enabled: bool) -> Self {
+        if enabled {
+            Self::new()
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Returns true if the trigger is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Returns true if `id` is the MIDI Panic parameter ID.
+    pub fn is_panic_parameter(id: ParameterId) -> bool {
+        id == MIDI_PANIC_PARAMETER_ID
+    }
+
+    /// Request a panic at the start of the next block. Ignored when
+    /// disabled.
+    ///
+    /// Real-time safe; callable from any thread.
+    pub fn request(&self) {
+        if let Some(inner) = self.inner.as_deref() {
+            inner.pending.store(true, Ordering::Release);
+        }
+    }
+
+    /// Returns true once after a panic was requested. Called by the
+    /// wrappers at the start of each block.
+    ///
+    /// Real-time safe.
+    pub fn take(&self) -> bool {
+        self.inner
+            .as_deref()
+            .is_some_and(|inner| inner.pending.swap(false, Ordering::Acquire))
+    }
+
+    /// Returns true once after the host set the parameter On, so wrappers
+    /// can report it back to Off.
+    pub fn take_released(&self) -> bool {
+        self.inner
+            .as_deref()
+            .is_some_and(|inner| inner.released.swap(false, Ordering::Acquire))
+    }
+}
+
+/// All Sound Off and All Notes Off on every channel, at `sample_offset`.
+pub fn panic_events(sample_offset: u32) -> impl Iterator<Item = MidiEvent> {
+    (0..16).flat_map(move |channel: MidiChannel| {
+        [cc::ALL_SOUND_OFF, cc::ALL_NOTES_OFF]
+            .map(|controller| MidiEvent::control_change(sample_offset, channel, controller, 0.0))
+    })
+}
+
+/// Handle the built-in MIDI panic WebView call.
+///
+/// Returns `None` if `method` is not [`MIDI_PANIC_INVOKE`].
+pub fn midi_panic_invoke(panic: &MidiPanic, method: &str) -> Option<serde_json::Value> {
+    if method != MIDI_PANIC_INVOKE {
+        return None;
+    }
+    panic.request();
+    Some(serde_json::Value::Bool(panic.is_enabled()))
+}
+
+impl ParameterStore for MidiPanic {
+    fn count(&self) -> usize {
+        if self.is_enabled() {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn info(&self, index: usize) -> Option<&ParameterInfo> {
+        let inner = self.inner.as_deref()?;
+        (index == 0).then_some(&inner.info)
+    }
+
+    fn get_normalized(&self, _id: ParameterId) -> ParameterValue {
+        // Momentary: always reads Off
+        0.0
+    }
+
+    fn set_normalized(&self, id: ParameterId, value: ParameterValue) {
+        let Some(inner) = self.inner.as_deref() else {
+            return;
+        };
+        if Self::is_panic_parameter(id) && value >= 0.5 {
+            inner.pending.store(true, Ordering::Release);
+            inner.released.store(true, Ordering::Release);
+        }
+    }
+
+    fn normalized_to_string(&self, _id: ParameterId, normalized: ParameterValue) -> String {
+        if normalized >= 0.5 { "On" } else { "Off" }.to_string()
+    }
+
+    fn string_to_normalized(&self, _id: ParameterId, string: &str) -> Option<ParameterValue> {
+        match string.trim().to_ascii_lowercase().as_str() {
+            "on" | "1" => Some(1.0),
+            "off" | "0" => Some(0.0),
+            _ => None,
+        }
+    }
+
+    fn normalized_to_plain(&self, _id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+        normalized.round()
+    }
+
+    fn plain_to_normalized(&self, _id: ParameterId, plain: ParameterValue) -> ParameterValue {
+        plain.clamp(0.0, 1.0).round()
+    }
+
+    fn formatter_kind(&self, _id: ParameterId) -> &'static str {
+        "bool"
+    }
+}
+
+/// A parameter store followed by the MIDI Panic parameter, as one store.
+///
+/// Used by wrappers that hand a single [`ParameterStore`] to the host
+/// bridge. IDs are routed to the panic trigger first, indices to the plugin
+/// first.
+pub struct StoreWithPanic<'a> {
+    /// The plugin's parameters (and any other framework parameters).
+    pub plugin: &'a dyn ParameterStore,
+    /// The panic trigger (may be disabled).
+    pub panic: &'a MidiPanic,
+}
+
+impl StoreWithPanic<'_> {
+    fn store(&self, id: ParameterId) -> &dyn ParameterStore {
+        if self.panic.is_enabled() && MidiPanic::is_panic_parameter(id) {
+            self.panic
+        } else {
+            self.plugin
+        }
+    }
+}
+
+impl ParameterStore for StoreWithPanic<'_> {
+    fn count(&self) -> usize {
+        self.plugin.count() + self.panic.count()
+    }
+
+    fn info(&self, index: usize) -> Option<&ParameterInfo> {
+        let plugin_count = self.plugin.count();
+        if index < plugin_count {
+            self.plugin.info(index)
+        } else {
+            self.panic.info(index - plugin_count)
+        }
+    }
+
+    fn get_normalized(&self, id: ParameterId) -> ParameterValue {
+        self.store(id).get_normalized(id)
+    }
+
+    fn set_normalized(&self, id: ParameterId, value: ParameterValue) {
+        self.store(id).set_normalized(id, value)
+    }
+
+    fn normalized_to_string(&self, id: ParameterId, normalized: ParameterValue) -> String {
+        self.store(id).normalized_to_string(id, normalized)
+    }
+
+    fn string_to_normalized(&self, id: ParameterId, string: &str) -> Option<ParameterValue> {
+        self.store(id).string_to_normalized(id, string)
+    }
+
+    fn normalized_to_plain(&self, id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+        self.store(id).normalized_to_plain(id, normalized)
+    }
+
+    fn plain_to_normalized(&self, id: ParameterId, plain: ParameterValue) -> ParameterValue {
+        self.store(id).plain_to_normalized(id, plain)
+    }
+
+    fn formatter_kind(&self, id: ParameterId) -> &'static str {
+        self.store(id).formatter_kind(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::MidiEventKind;
+
+    #[test]
+    fn parameter_and_invoke_request_one_panic() {
+        let panic = MidiPanic::new();
+        assert!(!panic.take());
+
+        panic.set_normalized(MIDI_PANIC_PARAMETER_ID, 1.0);
+        assert_eq!(panic.get_normalized(MIDI_PANIC_PARAMETER_ID), 0.0);
+        assert!(panic.take());
+        assert!(!panic.take());
+        assert!(panic.take_released());
+
+        // Setting it back to Off doesn't trigger
+        panic.set_normalized(MIDI_PANIC_PARAMETER_ID, 0.0);
+        assert!(!panic.take());
+
+        assert_eq!(midi_panic_invoke(&panic, MIDI_PANIC_INVOKE), Some(serde_json::Value::Bool(true)));
+        assert!(panic.take());
+        assert!(!panic.take_released());
+        assert!(midi_panic_invoke(&panic, "_beamer/other").is_none());
+
+        let disabled = MidiPanic::default();
+        disabled.request();
+        assert!(!disabled.take());
+        assert_eq!(disabled.count(), 0);
+    }
+
+    #[test]
+    fn events_cover_every_channel_and_parameter_is_hidden() {
+        let events: Vec<_> = panic_events(0).collect();
+        assert_eq!(events.len(), PANIC_EVENT_COUNT);
+        let MidiEventKind::ControlChange(last) = &events[PANIC_EVENT_COUNT - 1].event else {
+            panic!("expected a control change");
+        };
+        assert_eq!((last.channel, last.controller), (15, cc::ALL_NOTES_OFF));
+
+        let panic = MidiPanic::new();
+        let info = panic.info(0).unwrap();
+        assert!(info.flags.is_hidden && !info.flags.is_persistent);
+
+        let trims = crate::StandardTrims::new();
+        let store = StoreWithPanic { plugin: &trims, panic: &panic };
+        assert_eq!(store.count(), 4);
+        assert_eq!(store.info(3).unwrap().id, MIDI_PANIC_PARAMETER_ID);
+    }
+}
@@ -0,0 +1,127 @@
+//! Declarative MIDI pass-through policy.
+//!
+//! Most MIDI-consuming plugins want the same thing from pass-through: forward
+//! everything to the next device except the notes they played themselves.
+//! Writing that by hand in [`Processor::process_midi`](crate::Processor::process_midi)
+//! is easy to get subtly wrong (dropped CCs, duplicated events, out-of-order
+//! output). [`MidiThru`] lets the plugin declare the policy instead, and the
+//! format wrapper applies it after `process_midi()` returns.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! impl Processor for MySynth {
+//!     fn wants_midi(&self) -> bool {
+//!         true
+//!     }
+//!
+//!     // Forward CCs, pitch bend, etc. but not the notes we play.
+//!     fn midi_thru(&self) -> MidiThru {
+//!         MidiThru::ExceptConsumed
+//!     }
+//!
+//!     fn process_midi(&mut self, input: &[MidiEvent], _output: &mut MidiBuffer) {
+//!         // Consume notes; no need to copy anything to the output.
+//!     }
+//! }
+//! ```
+//!
+//! Which events count as "consumed" is decided by
+//! [`Processor::consumes_midi`](crate::Processor::consumes_midi), which
+//! defaults to [`MidiEvent::is_note_event`].
+
+use crate::midi::{MidiBuffer, MidiEvent};
+
+/// MIDI pass-through policy applied by the format wrapper.
+///
+/// Thru events are merged with whatever the plugin wrote in `process_midi()`
+/// and the combined output is kept ordered by `sample_offset`. At equal
+/// offsets, plugin-generated events come first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MidiThru {
+    /// Forward every input event.
+    All,
+    /// Forward every input event the plugin did not consume.
+    ExceptConsumed,
+    /// The wrapper forwards nothing; `process_midi()` has full control.
+    ///
+    /// This is the default, so plugins that copy events themselves (including
+    /// the default `process_midi()` implementation) are unaffected.
+    #[default]
+    None,
+}
+
+impl MidiThru {
+    /// Append the pass-through events for `input` to `output`.
+    ///
+    /// `consumes` is only consulted for [`MidiThru::ExceptConsumed`].
+    /// Real-time safe apart from cloning SysEx events (see
+    /// [`Processor::process_midi`](crate::Processor::process_midi)).
+    pub fn apply(
+        self,
+        input: &[MidiEvent],
+        output: &mut MidiBuffer,
+        consumes: impl Fn(&MidiEvent) -> bool,
+    ) {
+        let before = output.len();
+        match self {
+            MidiThru::None => return,
+            MidiThru::All => {
+                for event in input {
+                    output.push(event.clone());
+                }
+            }
+            MidiThru::ExceptConsumed => {
+                for event in input.iter().filter(|e| !consumes(e)) {
+                    output.push(event.clone());
+                }
+            }
+        }
+        if output.len() != before {
+            output.sort_by_offset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> Vec<MidiEvent> {
+        vec![
+            MidiEvent::note_on(0, 0, 60, 0.8, -1, 0.0, 0),
+            MidiEvent::control_change(4, 0, 1, 0.5),
+            MidiEvent::note_off(8, 0, 60, 0.0, -1, 0.0),
+        ]
+    }
+
+    #[test]
+    fn test_none_forwards_nothing() {
+        let mut output = MidiBuffer::new_boxed();
+        MidiThru::None.apply(&input(), &mut output, |_| false);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_all_forwards_everything() {
+        let mut output = MidiBuffer::new_boxed();
+        MidiThru::All.apply(&input(), &mut output, |_| true);
+        assert_eq!(output.as_slice(), input().as_slice());
+    }
+
+    #[test]
+    fn test_except_consumed_filters_and_keeps_order() {
+        let mut output = MidiBuffer::new_boxed();
+        // Plugin-generated event between the thru events.
+        output.push(MidiEvent::control_change(6, 0, 7, 1.0));
+        output.push(MidiEvent::control_change(4, 0, 11, 1.0));
+
+        MidiThru::ExceptConsumed.apply(&input(), &mut output, MidiEvent::is_note_event);
+
+        let offsets: Vec<u32> = output.iter().map(|e| e.sample_offset).collect();
+        assert_eq!(offsets, vec![4, 4, 6]);
+        // Plugin event precedes the thru event at the same offset.
+        assert_eq!(output.as_slice()[0], MidiEvent::control_change(4, 0, 11, 1.0));
+        assert_eq!(output.as_slice()[1], input()[1]);
+    }
+}
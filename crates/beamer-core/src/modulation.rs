@@ -0,0 +1,279 @@
+//! Audio-rate parameter modulation from auxiliary inputs.
+//!
+//! [`SidechainModulator`] turns the level of an aux input bus into a
+//! per-sample modulation signal in `0.0..=1.0`, using the same
+//! [`EnvelopeFollower`] as [`AutoGain`](crate::AutoGain). [`ModulationTarget`]
+//! applies such a signal to any parameter in its normalized domain, so that
+//! "duck this parameter when the sidechain is loud" works for reverb mix,
+//! filter cutoff, width or anything else without a dedicated ducker.
+//!
+//! # Overview
+//!
+//! - [`SidechainModulator`] - Aux input level to a `0..1` modulation buffer
+//! - [`ModulationTarget`] - Per-sample modulation of a parameter's values
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::{ModulationTarget, SidechainModulator};
+//!
+//! struct MyReverb {
+//!     ducker: SidechainModulator,
+//!     mix_values: Vec<f64>,
+//! }
+//!
+//! impl Processor for MyReverb {
+//!     fn process(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers, context: &ProcessContext) {
+//!         let frames = buffer.num_samples();
+//!         let modulation = self.ducker.process(aux, frames);
+//!
+//!         // Smoothed mix, pulled down by up to 80% while the sidechain is hot
+//!         let mix = &mut self.mix_values[..frames];
+//!         self.parameters.mix.fill_smoothed(mix);
+//!         ModulationTarget::new(-0.8).apply(&self.parameters.mix, mix, modulation);
+//!     }
+//! }
+//! ```
+//!
+//! Both types only touch preallocated memory after
+//! [`SidechainModulator::prepare()`], so they are safe on the audio thread.
+
+use crate::auto_gain::EnvelopeFollower;
+use crate::buffer::AuxiliaryBuffers;
+use crate::parameter_types::ParameterRef;
+use crate::sample::Sample;
+
+/// Default attack time of the sidechain follower in milliseconds.
+pub const DEFAULT_MODULATION_ATTACK_MS: f64 = 5.0;
+
+/// Default release time of the sidechain follower in milliseconds.
+pub const DEFAULT_MODULATION_RELEASE_MS: f64 = 150.0;
+
+/// Smallest power considered, to avoid `-inf` dB.
+const MIN_POWER: f64 = 1e-12;
+
+// =============================================================================
+// SidechainModulator
+// =============================================================================
+
+/// Envelope follower on an aux input bus, producing a modulation signal.
+///
+/// Each sample, the loudest channel of the bus drives an attack/release
+/// follower. The envelope is mapped from a dB window (`floor_db..ceiling_db`)
+/// to `0.0..=1.0`: at or below the floor the output is 0, at or above the
+/// ceiling it is 1. [`inverted()`](Self::inverted) flips this, so the output
+/// falls while the sidechain is loud.
+///
+/// If the bus is missing (host didn't connect a sidechain), the follower
+/// sees silence and releases to the floor.
+#[derive(Debug, Clone)]
+pub struct SidechainModulator {
+    follower: EnvelopeFollower,
+    bus: usize,
+    floor_db: f64,
+    ceiling_db: f64,
+    inverted: bool,
+    output: Vec<f64>,
+}
+
+impl Default for SidechainModulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SidechainModulator {
+    /// Create a modulator on the sidechain bus (aux input 0) with a -48..0 dB
+    /// window and the default follower times.
+    pub fn new() -> Self {
+        Self {
+            follower: EnvelopeFollower::new(DEFAULT_MODULATION_ATTACK_MS, DEFAULT_MODULATION_RELEASE_MS),
+            bus: 0,
+            floor_db: -48.0,
+            ceiling_db: 0.0,
+            inverted: false,
+            output: Vec::new(),
+        }
+    }
+
+    /// Read from aux input bus `bus` instead of the sidechain.
+    pub fn with_bus(mut self, bus: usize) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    /// Set the follower attack and release times in milliseconds.
+    ///
+    /// Takes effect with the next [`prepare()`](Self::prepare).
+    pub fn with_times(mut self, attack_ms: f64, release_ms: f64) -> Self {
+        self.follower = EnvelopeFollower::new(attack_ms, release_ms);
+        self
+    }
+
+    /// Set the level window mapped to `0.0..=1.0`.
+    pub fn with_range_db(mut self, floor_db: f64, ceiling_db: f64) -> Self {
+        self.floor_db = floor_db;
+        self.ceiling_db = ceiling_db.max(floor_db + f64::EPSILON);
+        self
+    }
+
+    /// Output 1 for silence and 0 for a loud sidechain.
+    pub fn inverted(mut self) -> Self {
+        self.inverted = true;
+        self
+    }
+
+    /// Set the sample rate and allocate the modulation buffer.
+    ///
+    /// Call from `Descriptor::prepare()`, not from the audio thread.
+    pub fn prepare(&mut self, sample_rate: f64, max_block_size: usize) {
+        self.follower.set_sample_rate(sample_rate);
+        self.output = vec![0.0; max_block_size];
+    }
+
+    /// Follow `num_samples` of the aux bus and return one modulation value
+    /// per sample.
+    ///
+    /// Blocks longer than the prepared maximum are truncated to it.
+    pub fn process<S: Sample>(&mut self, aux: &AuxiliaryBuffers<S>, num_samples: usize) -> &[f64] {
+        let frames = num_samples.min(self.output.len());
+        let input = aux.input(self.bus);
+
+        for i in 0..frames {
+            let power = input.as_ref().map_or(0.0, |bus| {
+                (0..bus.num_channels())
+                    .filter_map(|ch| bus.input(ch).get(i))
+                    .map(|s| {
+                        let s = s.to_f64();
+                        s * s
+                    })
+                    .fold(0.0, f64::max)
+            });
+            let envelope = self.follower.process(power);
+            self.output[i] = self.map(envelope);
+        }
+
+        &self.output[..frames]
+    }
+
+    /// Current modulation value (the last sample of the previous block).
+    pub fn value(&self) -> f64 {
+        self.map(self.follower.value())
+    }
+
+    /// Reset the follower to silence.
+    pub fn reset(&mut self) {
+        self.follower.reset();
+    }
+
+    fn map(&self, power: f64) -> f64 {
+        let db = 10.0 * power.max(MIN_POWER).log10();
+        let amount = ((db - self.floor_db) / (self.ceiling_db - self.floor_db)).clamp(0.0, 1.0);
+        if self.inverted {
+            1.0 - amount
+        } else {
+            amount
+        }
+    }
+}
+
+// =============================================================================
+// ModulationTarget
+// =============================================================================
+
+/// Applies a modulation signal to a parameter.
+///
+/// Modulation is added in the normalized domain, scaled by `depth`
+/// (`-1.0..=1.0`), and clamped to the parameter range. Working normalized
+/// means the parameter's own mapping applies: a log-scaled cutoff sweeps in
+/// octaves, a dB gain in decibels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModulationTarget {
+    depth: f64,
+}
+
+impl ModulationTarget {
+    /// Create a target with the given depth. Negative depths pull the
+    /// parameter down (ducking).
+    pub fn new(depth: f64) -> Self {
+        Self {
+            depth: depth.clamp(-1.0, 1.0),
+        }
+    }
+
+    /// Modulation depth.
+    pub fn depth(&self) -> f64 {
+        self.depth
+    }
+
+    /// Modulate plain parameter values in place.
+    ///
+    /// `values` are plain values (for example from
+    /// [`FloatParameter::fill_smoothed()`](crate::FloatParameter::fill_smoothed)),
+    /// `modulation` one value per sample. Samples beyond the shorter of the
+    /// two are left untouched.
+    pub fn apply(&self, parameter: &dyn ParameterRef, values: &mut [f64], modulation: &[f64]) {
+        for (value, &amount) in values.iter_mut().zip(modulation) {
+            let normalized = parameter.plain_to_normalized(*value) + self.depth * amount;
+            *value = parameter.normalized_to_plain(normalized.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Modulated plain value for a single base value and modulation amount.
+    pub fn value(&self, parameter: &dyn ParameterRef, base: f64, amount: f64) -> f64 {
+        let normalized = parameter.plain_to_normalized(base) + self.depth * amount;
+        parameter.normalized_to_plain(normalized.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameter_types::FloatParameter;
+
+    fn run(modulator: &mut SidechainModulator, level: f32, blocks: usize) -> f64 {
+        let input = vec![level; 64];
+        let mut last = 0.0;
+        for _ in 0..blocks {
+            let aux = AuxiliaryBuffers::new([[&input[..], &input[..]]], std::iter::empty::<[&mut [f32]; 0]>(), 64);
+            last = *modulator.process(&aux, 64).last().unwrap();
+        }
+        last
+    }
+
+    #[test]
+    fn steady_sidechain_maps_into_window() {
+        let mut modulator = SidechainModulator::new().with_range_db(-48.0, 0.0);
+        modulator.prepare(48000.0, 64);
+        // -6 dB constant level sits 42 dB above the floor
+        let amount = run(&mut modulator, 0.5, 200);
+        assert!((amount - 42.0 / 48.0).abs() < 0.01, "{amount}");
+    }
+
+    #[test]
+    fn inverted_ducks_and_missing_bus_releases() {
+        let mut modulator = SidechainModulator::new().inverted();
+        modulator.prepare(48000.0, 64);
+        assert!(run(&mut modulator, 1.0, 200) < 0.01);
+
+        let aux = AuxiliaryBuffers::<f32>::new(
+            std::iter::empty::<[&[f32]; 0]>(),
+            std::iter::empty::<[&mut [f32]; 0]>(),
+            64,
+        );
+        for _ in 0..2000 {
+            modulator.process(&aux, 64);
+        }
+        assert!(modulator.value() > 0.99);
+    }
+
+    #[test]
+    fn target_modulates_in_normalized_domain() {
+        let parameter = FloatParameter::new("Mix", 1.0, 0.0..=1.0);
+        let target = ModulationTarget::new(-0.5);
+        let mut values = [1.0, 1.0, 0.2];
+        target.apply(&parameter, &mut values, &[0.0, 1.0, 1.0]);
+        assert_eq!(values, [1.0, 0.5, 0.0]);
+        assert_eq!(target.value(&parameter, 0.8, 0.5), 0.55);
+    }
+}
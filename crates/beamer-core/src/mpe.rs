@@ -0,0 +1,404 @@
+//! MPE zones and per-note channel tracking.
+//!
+//! MPE (MIDI Polyphonic Expression) controllers play every note on its own
+//! MIDI channel, so pitch bend, channel pressure and CC 74 on that channel
+//! belong to a single note. Instead of tracking channels in every synth, a
+//! plugin declares its zones with [`Descriptor::mpe_config()`] and the
+//! wrapper runs an [`MpeState`] over the MIDI input before `process_midi()`:
+//!
+//! ```ignore
+//! fn mpe_config(&self) -> Option<MpeConfig> {
+//!     Some(MpeConfig::lower_zone(15).with_pitch_bend_range(48.0))
+//! }
+//! ```
+//!
+//! On a zone's member channels:
+//!
+//! | Input | Delivered as |
+//! |-------|--------------|
+//! | `NoteOn`, `NoteOff` | The same event with a unique note ID per note |
+//! | Pitch bend | `NoteExpressionValue` with [`NoteExpressionKind::Tuning`] |
+//! | Channel pressure | `NoteExpressionValue` with [`NoteExpressionKind::Expression`] |
+//! | CC 74 | `NoteExpressionValue` with [`NoteExpressionKind::Brightness`] |
+//!
+//! Expressions go to every note sounding on the channel. Values sent before
+//! a note starts (MPE controllers send the initial pitch, pressure and
+//! timbre first) follow the note's `NoteOn` as expression events at the
+//! same offset. Everything else, including the master channel's messages
+//! for the whole zone, passes through unchanged, so VST3 note expressions
+//! and MPE reach the synth through the same events.
+//!
+//! [`Descriptor::mpe_config()`]: crate::plugin::Descriptor::mpe_config
+//! [`NoteExpressionKind::Tuning`]: crate::midi::NoteExpressionKind::Tuning
+//! [`NoteExpressionKind::Expression`]: crate::midi::NoteExpressionKind::Expression
+//! [`NoteExpressionKind::Brightness`]: crate::midi::NoteExpressionKind::Brightness
+
+use crate::midi::{
+    cc, note_expression, MidiChannel, MidiEvent, MidiEventKind, MidiNote, NoteId, MAX_MIDI_EVENTS,
+};
+
+/// Default member channel pitch bend range in semitones (the MPE default).
+pub const DEFAULT_MPE_PITCH_BEND_RANGE: f32 = 48.0;
+
+/// First note ID assigned to MPE notes, above the IDs hosts usually send.
+pub const MPE_NOTE_ID_BASE: NoteId = 0x4000_0000;
+
+/// Notes tracked per member channel; a further note replaces the oldest.
+const MAX_NOTES_PER_CHANNEL: usize = 8;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// An MPE zone: a master channel and the member channels next to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MpeZone {
+    /// Number of member channels (1 to 15).
+    pub member_channels: u8,
+    /// Member channel pitch bend range in semitones.
+    pub pitch_bend_range: f32,
+}
+
+impl MpeZone {
+    /// A zone with `member_channels` members and the default bend range.
+    pub fn new(member_channels: u8) -> Self {
+        Self {
+            member_channels: member_channels.clamp(1, 15),
+            pitch_bend_range: DEFAULT_MPE_PITCH_BEND_RANGE,
+        }
+    }
+}
+
+/// MPE zone configuration, returned from
+/// [`Descriptor::mpe_config()`](crate::plugin::Descriptor::mpe_config).
+///
+/// The lower zone's master is channel 1 (index 0) with members counting up;
+/// the upper zone's master is channel 16 (index 15) with members counting
+/// down. When both zones are set they share the 14 channels in between.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MpeConfig {
+    /// Lower zone, if any.
+    pub lower: Option<MpeZone>,
+    /// Upper zone, if any.
+    pub upper: Option<MpeZone>,
+}
+
+impl Default for MpeConfig {
+    /// A lower zone over all 15 member channels.
+    fn default() -> Self {
+        Self::lower_zone(15)
+    }
+}
+
+impl MpeConfig {
+    /// Only a lower zone with `member_channels` members.
+    pub fn lower_zone(member_channels: u8) -> Self {
+        Self {
+            lower: Some(MpeZone::new(member_channels)),
+            upper: None,
+        }
+    }
+
+    /// Only an upper zone with `member_channels` members.
+    pub fn upper_zone(member_channels: u8) -> Self {
+        Self {
+            lower: None,
+            upper: Some(MpeZone::new(member_channels)),
+        }
+    }
+
+    /// Add a lower zone. The upper zone shrinks if both don't fit.
+    pub fn with_lower_zone(mut self, member_channels: u8) -> Self {
+        let zone = MpeZone::new(member_channels);
+        if let Some(upper) = &mut self.upper {
+            upper.member_channels = upper.member_channels.min(14 - zone.member_channels.min(13));
+        }
+        self.lower = Some(zone);
+        self
+    }
+
+    /// Add an upper zone. The lower zone shrinks if both don't fit.
+    pub fn with_upper_zone(mut self, member_channels: u8) -> Self {
+        let zone = MpeZone::new(member_channels);
+        if let Some(lower) = &mut self.lower {
+            lower.member_channels = lower.member_channels.min(14 - zone.member_channels.min(13));
+        }
+        self.upper = Some(zone);
+        self
+    }
+
+    /// Set the member channel pitch bend range of both zones, in semitones.
+    pub fn with_pitch_bend_range(mut self, semitones: f32) -> Self {
+        for zone in [&mut self.lower, &mut self.upper].into_iter().flatten() {
+            zone.pitch_bend_range = semitones.clamp(0.0, 96.0);
+        }
+        self
+    }
+
+    /// The zone `channel` (0-15) is a member channel of.
+    pub fn member_zone(&self, channel: MidiChannel) -> Option<&MpeZone> {
+        if let Some(lower) = &self.lower {
+            if (1..=lower.member_channels).contains(&channel) {
+                return Some(lower);
+            }
+        }
+        if let Some(upper) = &self.upper {
+            if (15 - upper.member_channels..15).contains(&channel) {
+                return Some(upper);
+            }
+        }
+        None
+    }
+}
+
+// =============================================================================
+// MpeState
+// =============================================================================
+
+#[derive(Clone, Copy, Default)]
+struct MemberChannel {
+    /// Pitch bend (-1.0 to 1.0).
+    bend: f32,
+    /// Channel pressure since the channel was last reset.
+    pressure: Option<f32>,
+    /// CC 74 since the channel was last reset.
+    timbre: Option<f32>,
+    /// Sounding notes, oldest first.
+    notes: [(MidiNote, NoteId); MAX_NOTES_PER_CHANNEL],
+    num_notes: usize,
+}
+
+/// Turns member channel MIDI into per-note events for an [`MpeConfig`].
+///
+/// Owned by the wrapper; runs on the audio thread. The output buffer is
+/// allocated up front, so processing doesn't allocate (except for copying
+/// SysEx events, which are boxed).
+pub struct MpeState {
+    config: MpeConfig,
+    channels: [MemberChannel; 16],
+    next_note_id: NoteId,
+    output: Vec<MidiEvent>,
+}
+
+impl std::fmt::Debug for MpeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MpeState").field("config", &self.config).finish()
+    }
+}
+
+impl MpeState {
+    /// Create the state for `config`.
+    pub fn new(config: MpeConfig) -> Self {
+        Self {
+            config,
+            channels: [MemberChannel::default(); 16],
+            next_note_id: MPE_NOTE_ID_BASE,
+            output: Vec::with_capacity(MAX_MIDI_EVENTS),
+        }
+    }
+
+    /// The zone configuration.
+    pub fn config(&self) -> &MpeConfig {
+        &self.config
+    }
+
+    /// Forget sounding notes and channel values (transport reset, deactivation).
+    pub fn reset(&mut self) {
+        self.channels = [MemberChannel::default(); 16];
+    }
+
+    /// Rewrite a block of events (sorted by offset). Returns the events to
+    /// pass to `process_midi()`, valid until the next call.
+    pub fn process(&mut self, input: &[MidiEvent]) -> &[MidiEvent] {
+        self.output.clear();
+        for event in input {
+            self.process_event(event);
+        }
+        &self.output
+    }
+
+    fn process_event(&mut self, event: &MidiEvent) {
+        let offset = event.sample_offset;
+        let (channel, zone) = match &event.event {
+            MidiEventKind::NoteOn(e) => (e.channel, self.config.member_zone(e.channel)),
+            MidiEventKind::NoteOff(e) => (e.channel, self.config.member_zone(e.channel)),
+            MidiEventKind::PitchBend(e) => (e.channel, self.config.member_zone(e.channel)),
+            MidiEventKind::ChannelPressure(e) => (e.channel, self.config.member_zone(e.channel)),
+            MidiEventKind::ControlChange(e) if e.controller == cc::BRIGHTNESS => {
+                (e.channel, self.config.member_zone(e.channel))
+            }
+            _ => (0, None),
+        };
+        let Some(&zone) = zone else {
+            self.push(event.clone());
+            return;
+        };
+        let state = &mut self.channels[channel as usize & 15];
+
+        match &event.event {
+            MidiEventKind::NoteOn(note_on) if note_on.velocity > 0.0 => {
+                let note_id = self.next_note_id;
+                self.next_note_id = self.next_note_id.checked_add(1).unwrap_or(MPE_NOTE_ID_BASE);
+                if state.num_notes == MAX_NOTES_PER_CHANNEL {
+                    state.notes.copy_within(1.., 0);
+                    state.num_notes -= 1;
+                }
+                state.notes[state.num_notes] = (note_on.pitch, note_id);
+                state.num_notes += 1;
+
+                let state = *state;
+                let mut note_on = *note_on;
+                note_on.note_id = note_id;
+                self.push(event.clone().with(MidiEventKind::NoteOn(note_on)));
+                if state.bend != 0.0 {
+                    let value = tuning_value(state.bend, zone.pitch_bend_range);
+                    self.push(MidiEvent::note_expression_value(offset, note_id, note_expression::TUNING, value));
+                }
+                if let Some(pressure) = state.pressure {
+                    self.push(MidiEvent::note_expression_value(
+                        offset,
+                        note_id,
+                        note_expression::EXPRESSION,
+                        f64::from(pressure),
+                    ));
+                }
+                if let Some(timbre) = state.timbre {
+                    self.push(MidiEvent::note_expression_value(
+                        offset,
+                        note_id,
+                        note_expression::BRIGHTNESS,
+                        f64::from(timbre),
+                    ));
+                }
+            }
+            MidiEventKind::NoteOn(_) | MidiEventKind::NoteOff(_) => {
+                let pitch = match &event.event {
+                    MidiEventKind::NoteOn(e) => e.pitch,
+                    MidiEventKind::NoteOff(e) => e.pitch,
+                    _ => unreachable!(),
+                };
+                let notes = &state.notes[..state.num_notes];
+                let Some(index) = notes.iter().position(|&(p, _)| p == pitch) else {
+                    // Started before the state was created or reset
+                    self.push(event.clone());
+                    return;
+                };
+                let note_id = notes[index].1;
+                state.notes.copy_within(index + 1..state.num_notes, index);
+                state.num_notes -= 1;
+                let kind = match &event.event {
+                    MidiEventKind::NoteOn(e) => MidiEventKind::NoteOn(crate::midi::NoteOn { note_id, ..*e }),
+                    MidiEventKind::NoteOff(e) => MidiEventKind::NoteOff(crate::midi::NoteOff { note_id, ..*e }),
+                    _ => unreachable!(),
+                };
+                self.push(event.clone().with(kind));
+            }
+            MidiEventKind::PitchBend(bend) => {
+                state.bend = bend.value;
+                let value = tuning_value(bend.value, zone.pitch_bend_range);
+                self.push_expression(channel, offset, note_expression::TUNING, value);
+            }
+            MidiEventKind::ChannelPressure(pressure) => {
+                state.pressure = Some(pressure.pressure);
+                let value = f64::from(pressure.pressure);
+                self.push_expression(channel, offset, note_expression::EXPRESSION, value);
+            }
+            MidiEventKind::ControlChange(timbre) => {
+                state.timbre = Some(timbre.value);
+                let value = f64::from(timbre.value);
+                self.push_expression(channel, offset, note_expression::BRIGHTNESS, value);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Send an expression to every note sounding on `channel`.
+    fn push_expression(&mut self, channel: MidiChannel, offset: u32, expression_type: u32, value: f64) {
+        let state = self.channels[channel as usize & 15];
+        for &(_, note_id) in &state.notes[..state.num_notes] {
+            self.push(MidiEvent::note_expression_value(offset, note_id, expression_type, value));
+        }
+    }
+
+    fn push(&mut self, event: MidiEvent) {
+        if self.output.len() < self.output.capacity() {
+            self.output.push(event);
+        }
+    }
+}
+
+/// Normalized tuning expression for a pitch bend (see
+/// [`NoteExpressionValue::tuning_semitones()`](crate::midi::NoteExpressionValue::tuning_semitones)).
+fn tuning_value(bend: f32, range_semitones: f32) -> f64 {
+    (0.5 + f64::from(bend) * f64::from(range_semitones) / 240.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::NoteExpressionKind;
+
+    fn expressions(events: &[MidiEvent]) -> Vec<(NoteId, NoteExpressionKind, f64)> {
+        events
+            .iter()
+            .filter_map(|e| match &e.event {
+                MidiEventKind::NoteExpressionValue(x) => Some((x.note_id, x.kind(), x.value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_member_channels_become_note_expressions() {
+        let mut mpe = MpeState::new(MpeConfig::lower_zone(15));
+        let input = [
+            // Initial timbre before the note, on member channel 2
+            MidiEvent::control_change(0, 2, cc::BRIGHTNESS, 0.25),
+            MidiEvent::note_on(0, 2, 60, 0.8, 60, 0.0, 0),
+            MidiEvent::note_on(0, 3, 60, 0.8, 60, 0.0, 0),
+            MidiEvent::pitch_bend(10, 2, 0.5),
+            MidiEvent::channel_pressure(20, 3, 0.75),
+            // Master channel: zone-wide, unchanged
+            MidiEvent::pitch_bend(30, 0, 0.5),
+            MidiEvent::note_off(40, 2, 60, 0.0, 60, 0.0),
+        ];
+        let output = mpe.process(&input).to_vec();
+
+        let ids: Vec<NoteId> = output
+            .iter()
+            .filter_map(|e| match &e.event {
+                MidiEventKind::NoteOn(n) => Some(n.note_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, [MPE_NOTE_ID_BASE, MPE_NOTE_ID_BASE + 1]);
+        assert_eq!(
+            expressions(&output),
+            [
+                (ids[0], NoteExpressionKind::Brightness, 0.25),
+                (ids[0], NoteExpressionKind::Tuning, 0.6),
+                (ids[1], NoteExpressionKind::Expression, 0.75),
+            ]
+        );
+        assert!(output.contains(&MidiEvent::pitch_bend(30, 0, 0.5)));
+        assert!(matches!(
+            output.last().unwrap().event,
+            MidiEventKind::NoteOff(off) if off.note_id == ids[0]
+        ));
+    }
+
+    #[test]
+    fn test_zones() {
+        let both = MpeConfig::lower_zone(15).with_upper_zone(4);
+        assert_eq!(both.lower.unwrap().member_channels, 10);
+        assert!(both.member_zone(0).is_none());
+        assert!(both.member_zone(10).is_some());
+        assert!(both.member_zone(11).is_some());
+        assert!(both.member_zone(15).is_none());
+
+        // Channels outside the zone pass through unchanged
+        let mut mpe = MpeState::new(MpeConfig::lower_zone(3));
+        let input = [MidiEvent::note_on(0, 5, 60, 0.8, 60, 0.0, 0), MidiEvent::pitch_bend(1, 5, 1.0)];
+        assert_eq!(mpe.process(&input), input);
+    }
+}
@@ -0,0 +1,224 @@
+//! Minimum note length for drum and one-shot instruments.
+//!
+//! Sequencers and pads often send a note-on and its note-off in the same
+//! block, sometimes at the same sample offset. A voice that is released
+//! before it renders a single sample never makes a sound. [`NoteGate`] sits
+//! between the incoming events and the voice handling and holds back each
+//! note-off until the note has sounded for a minimum number of samples,
+//! carrying it into later blocks when needed:
+//!
+//! ```ignore
+//! fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!     self.note_gate.process(&self.pending_events, buffer.num_samples(), &mut self.gated_events);
+//!     for event in &self.gated_events {
+//!         // Trigger and release voices at event.sample_offset
+//!     }
+//!     self.pending_events.clear();
+//! }
+//! ```
+//!
+//! Notes are tracked per channel and pitch. A new note-on for a pitch whose
+//! note-off is still held back releases the old note first, at the new
+//! note's offset, so retriggered notes are never cut short by a stale
+//! note-off. Velocity 0 note-ons count as note-offs.
+
+use crate::midi::{MidiEvent, MidiEventKind, MAX_MIDI_EVENTS};
+
+/// Marks a channel/pitch with no sounding note.
+const NOT_SOUNDING: u64 = u64::MAX;
+
+/// Holds back note-offs until notes reach a minimum length.
+///
+/// All storage is allocated in [`new()`](Self::new); [`process()`](Self::process)
+/// is real-time safe as long as the output has room for the block's events
+/// (passing SysEx through clones its data).
+pub struct NoteGate {
+    min_samples: u64,
+    /// Running sample position of the current block's first sample.
+    position: u64,
+    /// Note-on position per channel (16) and pitch (128).
+    started: Vec<u64>,
+    /// Held back note-offs as (due position, event), sorted by due position.
+    deferred: Vec<(u64, MidiEvent)>,
+}
+
+impl NoteGate {
+    /// Create a gate enforcing notes of at least `min_samples`.
+    pub fn new(min_samples: u32) -> Self {
+        Self {
+            min_samples: min_samples as u64,
+            position: 0,
+            started: vec![NOT_SOUNDING; 16 * 128],
+            deferred: Vec::with_capacity(MAX_MIDI_EVENTS),
+        }
+    }
+
+    /// Create a gate enforcing notes of at least `ms` milliseconds.
+    pub fn from_ms(ms: f64, sample_rate: f64) -> Self {
+        Self::new((ms * 0.001 * sample_rate).round().max(0.0) as u32)
+    }
+
+    /// The minimum note length in samples.
+    pub fn min_samples(&self) -> u32 {
+        self.min_samples as u32
+    }
+
+    /// Change the minimum note length (e.g. after a sample rate change).
+    /// Applies to notes started from now on.
+    pub fn set_min_samples(&mut self, min_samples: u32) {
+        self.min_samples = min_samples as u64;
+    }
+
+    /// Number of note-offs currently held back.
+    pub fn pending(&self) -> usize {
+        self.deferred.len()
+    }
+
+    /// Forget all sounding notes and held back note-offs.
+    ///
+    /// Call when the voices are reset (e.g. on `set_active(false)`), so no
+    /// stale note-offs reach the next playback.
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.started.fill(NOT_SOUNDING);
+        self.deferred.clear();
+    }
+
+    /// Gate one block of events.
+    ///
+    /// `input` must be sorted by sample offset (as delivered by the
+    /// wrappers). `output` is cleared and receives the input events plus the
+    /// note-offs due in this block, in sample order, with held back
+    /// note-offs moved to where the note reaches its minimum length. Call
+    /// once per block, also for blocks without events, so held back
+    /// note-offs are released on time.
+    pub fn process(&mut self, input: &[MidiEvent], num_samples: usize, output: &mut Vec<MidiEvent>) {
+        output.clear();
+        for event in input {
+            let offset = event.sample_offset.min(num_samples.saturating_sub(1) as u32);
+            let at = self.position + offset as u64;
+            self.release_due(at + 1, output);
+
+            let Some((slot, is_on)) = note_slot(event) else {
+                output.push(event.clone());
+                continue;
+            };
+            if is_on {
+                // Release a held back note-off of the same pitch first
+                if let Some(index) = self.deferred.iter().position(|(_, off)| note_slot(off).map(|(s, _)| s) == Some(slot)) {
+                    let (_, mut off) = self.deferred.remove(index);
+                    off.sample_offset = offset;
+                    output.push(off);
+                }
+                self.started[slot] = at;
+                output.push(event.clone());
+                continue;
+            }
+
+            let started = std::mem::replace(&mut self.started[slot], NOT_SOUNDING);
+            let due = started.saturating_add(self.min_samples);
+            if started == NOT_SOUNDING || due <= at || self.deferred.len() == self.deferred.capacity() {
+                output.push(event.clone());
+            } else {
+                let index = self.deferred.partition_point(|(d, _)| *d <= due);
+                self.deferred.insert(index, (due, event.clone()));
+            }
+        }
+        self.release_due(self.position + num_samples as u64, output);
+        self.position += num_samples as u64;
+    }
+
+    /// Move held back note-offs due before `end` to the output.
+    fn release_due(&mut self, end: u64, output: &mut Vec<MidiEvent>) {
+        let count = self.deferred.partition_point(|(due, _)| *due < end);
+        let block_start = self.position;
+        for (due, mut off) in self.deferred.drain(..count) {
+            off.sample_offset = due.saturating_sub(block_start) as u32;
+            output.push(off);
+        }
+    }
+}
+
+/// Index into the channel/pitch table and whether the event starts a note.
+fn note_slot(event: &MidiEvent) -> Option<(usize, bool)> {
+    let (channel, pitch, is_on) = match &event.event {
+        MidiEventKind::NoteOn(on) => (on.channel, on.pitch, on.velocity > 0.0),
+        MidiEventKind::NoteOff(off) => (off.channel, off.pitch, false),
+        _ => return None,
+    };
+    Some(((channel as usize & 15) * 128 + (pitch as usize & 127), is_on))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::{NoteOff, NoteOn};
+
+    fn on(offset: u32, pitch: u8) -> MidiEvent {
+        MidiEvent {
+            sample_offset: offset,
+            event: MidiEventKind::NoteOn(NoteOn {
+                channel: 0,
+                pitch,
+                velocity: 1.0,
+                note_id: pitch as i32,
+                tuning: 0.0,
+                length: 0,
+            }),
+        }
+    }
+
+    fn off(offset: u32, pitch: u8) -> MidiEvent {
+        MidiEvent {
+            sample_offset: offset,
+            event: MidiEventKind::NoteOff(NoteOff {
+                channel: 0,
+                pitch,
+                velocity: 0.0,
+                note_id: pitch as i32,
+                tuning: 0.0,
+            }),
+        }
+    }
+
+    fn summary(events: &[MidiEvent]) -> Vec<(u32, bool)> {
+        events.iter().map(|e| (e.sample_offset, note_slot(e).unwrap().1)).collect()
+    }
+
+    #[test]
+    fn defers_note_offs_within_and_across_blocks() {
+        let mut gate = NoteGate::new(100);
+        let mut output = Vec::new();
+
+        // Zero-length note: the note-off moves 100 samples later, ahead of
+        // an unrelated later note-on. The short note at 200 is held into
+        // the next block (due at 300, offset 44).
+        gate.process(&[on(10, 36), off(10, 36), on(200, 38), off(255, 38)], 256, &mut output);
+        assert_eq!(summary(&output), [(10, true), (110, false), (200, true)]);
+        assert_eq!(gate.pending(), 1);
+        gate.process(&[], 256, &mut output);
+        assert_eq!(summary(&output), [(44, false)]);
+
+        // Long enough notes pass unchanged
+        gate.process(&[on(0, 40), off(150, 40)], 256, &mut output);
+        assert_eq!(summary(&output), [(0, true), (150, false)]);
+        assert_eq!(gate.pending(), 0);
+    }
+
+    #[test]
+    fn retrigger_releases_the_held_note_first() {
+        let mut gate = NoteGate::from_ms(10.0, 48000.0);
+        assert_eq!(gate.min_samples(), 480);
+        let mut output = Vec::new();
+
+        gate.process(&[on(0, 36), off(1, 36), on(64, 36), off(65, 36)], 128, &mut output);
+        assert_eq!(summary(&output), [(0, true), (64, false), (64, true)]);
+
+        gate.process(&[], 512, &mut output);
+        assert_eq!(summary(&output), [(416, false)]);
+
+        gate.process(&[on(0, 36), off(0, 36)], 64, &mut output);
+        gate.reset();
+        assert_eq!(gate.pending(), 0);
+    }
+}
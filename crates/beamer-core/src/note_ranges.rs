@@ -0,0 +1,249 @@
+//! Playable note ranges and keyboard splits for instruments.
+//!
+//! Sampled instruments rarely cover all 128 notes, and many split the
+//! keyboard into zones ("Bass" below C3, "Lead" above). Declare the range
+//! and splits on the descriptor with [`Descriptor::note_ranges()`]:
+//!
+//! ```ignore
+//! fn note_ranges(&self) -> NoteRanges {
+//!     NoteRanges::new(21, 108) // Piano: A0 to C8
+//!         .with_split("Bass", 21, 59)
+//!         .with_split("Lead", 60, 108)
+//!         .filtering()
+//! }
+//! ```
+//!
+//! Splits may overlap (layers) and may leave gaps; a note belongs to the
+//! first split that contains it.
+//!
+//! # Filtering
+//!
+//! With [`filtering()`](NoteRanges::filtering), the wrapper drops note-on,
+//! note-off and polyphonic pressure events outside the playable range before
+//! MPE handling and `process_midi()`, so the plugin never sees them. Other
+//! events pass through. Without it, the range is metadata only.
+//!
+//! # Host Integration
+//!
+//! | Format | Metadata |
+//! |--------|----------|
+//! | VST3 | `IUnitInfo` program pitch names: each note in a split is named after the split, so hosts that show pitch names label the keys |
+//! | AU | No note range interface; the ranges are available to the GUI |
+//!
+//! Neither format has a way to report the playable range itself; keyswitch
+//! ranges remain [`Descriptor::keyswitch_info()`].
+//!
+//! # GUI Invokes
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`NOTE_RANGES_INVOKE`] | `[]` | `{ low, high, filtering, splits: [{ name, low, high }] }` |
+//!
+//! The `<beamer-keyboard>` element reads it to shade unplayable keys and
+//! color the splits.
+//!
+//! [`Descriptor::note_ranges()`]: crate::plugin::Descriptor::note_ranges
+//! [`Descriptor::keyswitch_info()`]: crate::plugin::Descriptor::keyswitch_info
+
+use crate::midi::{MidiEvent, MidiEventKind, MidiNote};
+
+/// Built-in WebView invoke method that describes the note ranges.
+pub const NOTE_RANGES_INVOKE: &str = "_beamer/noteRanges";
+
+/// Highest MIDI note number.
+const MAX_NOTE: MidiNote = 127;
+
+/// A named zone of the keyboard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteSplit {
+    /// Display name, e.g. "Bass".
+    pub name: String,
+    /// Lowest note of the split (inclusive).
+    pub low: MidiNote,
+    /// Highest note of the split (inclusive).
+    pub high: MidiNote,
+}
+
+impl NoteSplit {
+    /// Returns true if `pitch` lies within the split.
+    pub fn contains(&self, pitch: MidiNote) -> bool {
+        (self.low..=self.high).contains(&pitch)
+    }
+}
+
+/// The playable note range of an instrument and its keyboard splits.
+///
+/// The default covers all 128 notes with no splits and no filtering.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteRanges {
+    low: MidiNote,
+    high: MidiNote,
+    splits: Vec<NoteSplit>,
+    filtering: bool,
+}
+
+impl Default for NoteRanges {
+    fn default() -> Self {
+        Self::new(0, MAX_NOTE)
+    }
+}
+
+impl NoteRanges {
+    /// Playable range from `low` to `high` (inclusive).
+    ///
+    /// Notes above 127 are clamped, and swapped bounds are put in order.
+    pub fn new(low: MidiNote, high: MidiNote) -> Self {
+        let (low, high) = ordered(low, high);
+        Self {
+            low,
+            high,
+            splits: Vec::new(),
+            filtering: false,
+        }
+    }
+
+    /// Add a named split from `low` to `high` (inclusive).
+    pub fn with_split(mut self, name: impl Into<String>, low: MidiNote, high: MidiNote) -> Self {
+        let (low, high) = ordered(low, high);
+        self.splits.push(NoteSplit {
+            name: name.into(),
+            low,
+            high,
+        });
+        self
+    }
+
+    /// Have the wrapper drop notes outside the playable range.
+    pub fn filtering(mut self) -> Self {
+        self.filtering = true;
+        self
+    }
+
+    /// Lowest playable note.
+    pub fn low(&self) -> MidiNote {
+        self.low
+    }
+
+    /// Highest playable note.
+    pub fn high(&self) -> MidiNote {
+        self.high
+    }
+
+    /// The splits, in declaration order.
+    pub fn splits(&self) -> &[NoteSplit] {
+        &self.splits
+    }
+
+    /// Returns true if the wrapper drops notes outside the range.
+    pub fn is_filtering(&self) -> bool {
+        self.filtering
+    }
+
+    /// Returns true if `pitch` is playable.
+    pub fn contains(&self, pitch: MidiNote) -> bool {
+        (self.low..=self.high).contains(&pitch)
+    }
+
+    /// The first split containing `pitch`, if any.
+    pub fn split_for(&self, pitch: MidiNote) -> Option<&NoteSplit> {
+        self.splits.iter().find(|split| split.contains(pitch))
+    }
+
+    /// Returns true if the wrapper passes `event` on to the plugin.
+    ///
+    /// Always true without [`filtering()`](Self::filtering); otherwise false
+    /// for notes and polyphonic pressure outside the range.
+    pub fn allows(&self, event: &MidiEvent) -> bool {
+        if !self.filtering {
+            return true;
+        }
+        match &event.event {
+            MidiEventKind::NoteOn(e) => self.contains(e.pitch),
+            MidiEventKind::NoteOff(e) => self.contains(e.pitch),
+            MidiEventKind::PolyPressure(e) => self.contains(e.pitch),
+            _ => true,
+        }
+    }
+
+    /// Serialize for the [`NOTE_RANGES_INVOKE`] result.
+    pub fn to_json(&self) -> serde_json::Value {
+        let splits: Vec<serde_json::Value> = self
+            .splits
+            .iter()
+            .map(|split| {
+                serde_json::json!({
+                    "name": split.name,
+                    "low": split.low,
+                    "high": split.high,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "low": self.low,
+            "high": self.high,
+            "filtering": self.filtering,
+            "splits": splits,
+        })
+    }
+}
+
+/// Handle the built-in note range WebView call.
+///
+/// Returns `None` if `method` is not the note range invoke.
+pub fn note_ranges_invoke(ranges: &NoteRanges, method: &str) -> Option<serde_json::Value> {
+    match method {
+        NOTE_RANGES_INVOKE => Some(ranges.to_json()),
+        _ => None,
+    }
+}
+
+/// Clamp to valid notes and put the bounds in order.
+fn ordered(low: MidiNote, high: MidiNote) -> (MidiNote, MidiNote) {
+    let (low, high) = (low.min(MAX_NOTE), high.min(MAX_NOTE));
+    (low.min(high), low.max(high))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piano() -> NoteRanges {
+        NoteRanges::new(21, 108)
+            .with_split("Bass", 21, 59)
+            .with_split("Lead", 60, 108)
+            .with_split("Pad", 72, 84)
+    }
+
+    #[test]
+    fn ranges_and_splits() {
+        let ranges = piano();
+        assert!(ranges.contains(21) && ranges.contains(108));
+        assert!(!ranges.contains(20) && !ranges.contains(109));
+        assert_eq!(ranges.split_for(40).unwrap().name, "Bass");
+        // Overlapping splits: the first one wins
+        assert_eq!(ranges.split_for(72).unwrap().name, "Lead");
+        assert!(ranges.split_for(10).is_none());
+
+        let swapped = NoteRanges::new(200, 60);
+        assert_eq!((swapped.low(), swapped.high()), (60, 127));
+        assert_eq!(NoteRanges::default().to_json()["splits"], serde_json::json!([]));
+        assert_eq!(
+            note_ranges_invoke(&ranges, NOTE_RANGES_INVOKE).unwrap()["splits"][1]["low"],
+            60
+        );
+    }
+
+    #[test]
+    fn filtering_drops_notes_outside_the_range() {
+        let events = [
+            MidiEvent::note_on(0, 0, 10, 0.8, 1, 0.0, 0),
+            MidiEvent::note_on(0, 0, 60, 0.8, 2, 0.0, 0),
+            MidiEvent::poly_pressure(0, 0, 120, 0.5, 3),
+            MidiEvent::note_off(0, 0, 10, 0.0, 1, 0.0),
+            MidiEvent::control_change(0, 0, 1, 0.5),
+        ];
+        let allowed = |ranges: &NoteRanges| events.iter().filter(|e| ranges.allows(e)).count();
+        assert_eq!(allowed(&piano()), 5);
+        assert_eq!(allowed(&piano().filtering()), 2);
+    }
+}
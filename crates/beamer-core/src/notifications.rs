@@ -0,0 +1,298 @@
+//! User-facing notifications from Rust to the WebView GUI.
+//!
+//! File-load failures, activation problems and overload warnings need a
+//! consistent way to reach the user. [`Notifications`] is a per-instance
+//! queue that any thread can post to with [`notify()`](Notifications::notify).
+//! While an editor is open, the format wrappers take one notification per
+//! GUI sync tick and send it to the page as the [`NOTIFICATION_EVENT`]
+//! event:
+//!
+//! ```javascript
+//! __BEAMER__.on("beamer:notification", ({ level, message, count }) => {
+//!   showBanner(level, message);
+//! });
+//! ```
+//!
+//! Pages that don't subscribe get the runtime's built-in toast in the
+//! bottom-right corner. The wrappers post their own errors here too (a
+//! saved state that fails to load).
+//!
+//! # Example
+//!
+//! ```ignore
+//! impl Descriptor for MySampler {
+//!     fn notifications(&self) -> Notifications {
+//!         self.notifications.clone() // and pass a clone to the processor
+//!     }
+//! }
+//!
+//! // Loader thread
+//! if let Err(e) = load_sample(&path) {
+//!     notifications.notify(NotifyLevel::Error, format!("Couldn't load {}: {e}", path.display()));
+//! }
+//!
+//! // Audio thread
+//! if voices_stolen {
+//!     self.notifications.notify_static(NotifyLevel::Warning, "Voice limit reached");
+//! }
+//! ```
+//!
+//! # Rate Limiting
+//!
+//! - Identical notifications waiting in the queue are merged, with `count`
+//!   telling how many were posted.
+//! - At most one notification is delivered per [`MIN_INTERVAL`].
+//! - A notification identical to one delivered less than [`REPEAT_WINDOW`]
+//!   ago is dropped.
+//! - At most [`CAPACITY`] notifications wait; further ones are dropped.
+//!
+//! # Real-Time Safety
+//!
+//! [`notify()`](Notifications::notify) locks and logs, so it belongs on
+//! non-audio threads. [`notify_static()`](Notifications::notify_static)
+//! only tries the lock and never allocates, so it is safe on the audio
+//! thread (the notification is dropped if the GUI is reading at that
+//! moment).
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// WebView event carrying a notification.
+pub const NOTIFICATION_EVENT: &str = "beamer:notification";
+
+/// Most notifications waiting for delivery.
+pub const CAPACITY: usize = 16;
+
+/// Shortest time between two delivered notifications.
+pub const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a delivered notification suppresses identical ones.
+pub const REPEAT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Severity of a notification. Selects the toast color and log level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotifyLevel {
+    /// Something the user may want to know (a preset was saved).
+    Info,
+    /// Something degraded but still working (CPU overload, voice stealing).
+    Warning,
+    /// Something failed (a file or state couldn't be loaded).
+    Error,
+}
+
+impl NotifyLevel {
+    /// Lowercase name, as sent to the GUI.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A notification waiting for, or taken for, delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Severity.
+    pub level: NotifyLevel,
+    /// Text shown to the user.
+    pub message: Cow<'static, str>,
+    /// How many identical notifications were merged into this one.
+    pub count: u32,
+}
+
+impl Notification {
+    /// JSON object sent with [`NOTIFICATION_EVENT`].
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "level": self.level.as_str(),
+            "message": self.message,
+            "count": self.count,
+        })
+    }
+}
+
+struct Inner {
+    pending: VecDeque<Notification>,
+    /// Level, message and time of the last delivered notification.
+    last: Option<(NotifyLevel, Cow<'static, str>, Instant)>,
+    /// Earliest time the next notification may be delivered.
+    next_delivery: Option<Instant>,
+}
+
+impl Inner {
+    fn push(&mut self, level: NotifyLevel, message: Cow<'static, str>) {
+        if let Some(existing) = self
+            .pending
+            .iter_mut()
+            .find(|n| n.level == level && n.message == message)
+        {
+            existing.count = existing.count.saturating_add(1);
+        } else if self.pending.len() < CAPACITY {
+            // Capacity is reserved up front, so this doesn't allocate
+            self.pending.push_back(Notification { level, message, count: 1 });
+        }
+    }
+
+    fn take(&mut self, now: Instant) -> Option<Notification> {
+        if self.next_delivery.is_some_and(|at| now < at) {
+            return None;
+        }
+        while let Some(notification) = self.pending.pop_front() {
+            let repeated = self.last.as_ref().is_some_and(|(level, message, at)| {
+                *level == notification.level
+                    && *message == notification.message
+                    && now.duration_since(*at) < REPEAT_WINDOW
+            });
+            if repeated {
+                continue;
+            }
+            self.last = Some((notification.level, notification.message.clone(), now));
+            self.next_delivery = Some(now + MIN_INTERVAL);
+            return Some(notification);
+        }
+        None
+    }
+}
+
+/// Notification queue shared between the plugin and the GUI.
+///
+/// Cloning is cheap (reference counted). Each plugin instance has its own
+/// queue; the default is an empty one.
+#[derive(Clone)]
+pub struct Notifications {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                pending: VecDeque::with_capacity(CAPACITY),
+                last: None,
+                next_delivery: None,
+            })),
+        }
+    }
+}
+
+impl std::fmt::Debug for Notifications {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notifications")
+            .field("pending", &self.lock().pending.len())
+            .finish()
+    }
+}
+
+impl Notifications {
+    /// Create an empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Post a notification and write it to the log.
+    ///
+    /// Callable from any non-audio thread. Delivered once an editor is
+    /// open, subject to the rate limits in the module docs.
+    pub fn notify(&self, level: NotifyLevel, message: impl Into<Cow<'static, str>>) {
+        let message = message.into();
+        match level {
+            NotifyLevel::Info => log::info!("Notification: {message}"),
+            NotifyLevel::Warning => log::warn!("Notification: {message}"),
+            NotifyLevel::Error => log::error!("Notification: {message}"),
+        }
+        self.lock().push(level, message);
+    }
+
+    /// Post a notification with a static message without blocking.
+    ///
+    /// Real-time safe: doesn't allocate, log or wait for the lock. Dropped
+    /// if the GUI holds the lock at that moment; a condition that persists
+    /// is reported by the next call.
+    pub fn notify_static(&self, level: NotifyLevel, message: &'static str) {
+        if let Ok(mut inner) = self.inner.try_lock() {
+            inner.push(level, Cow::Borrowed(message));
+        }
+    }
+
+    /// Number of notifications waiting for delivery.
+    pub fn pending(&self) -> usize {
+        self.lock().pending.len()
+    }
+
+    /// Take the next notification if the rate limit allows one now.
+    ///
+    /// Called by the wrappers on the main thread.
+    pub fn take(&self) -> Option<Notification> {
+        self.lock().take(Instant::now())
+    }
+
+    /// Script that delivers the next notification to the JS runtime, or
+    /// `None` if nothing is due.
+    pub fn take_script(&self) -> Option<String> {
+        self.take().map(|notification| {
+            format!(
+                "window.__BEAMER__._onEvent({},{})",
+                serde_json::to_string(NOTIFICATION_EVENT).unwrap_or_default(),
+                notification.to_json(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_notifications_merge_and_delivery_is_paced() {
+        let notifications = Notifications::new();
+        notifications.notify(NotifyLevel::Error, "Missing sample");
+        notifications.notify_static(NotifyLevel::Warning, "Overload");
+        notifications.notify(NotifyLevel::Error, String::from("Missing sample"));
+        assert_eq!(notifications.pending(), 2);
+
+        let mut inner = notifications.lock();
+        let start = Instant::now();
+        let first = inner.take(start).unwrap();
+        assert_eq!((first.level, first.count), (NotifyLevel::Error, 2));
+        assert_eq!(inner.take(start + MIN_INTERVAL / 2), None);
+        let second = inner.take(start + MIN_INTERVAL).unwrap();
+        assert_eq!(second.message, "Overload");
+        assert_eq!(
+            second.to_json(),
+            serde_json::json!({"level": "warning", "message": "Overload", "count": 1})
+        );
+    }
+
+    #[test]
+    fn repeats_are_suppressed_and_capacity_is_bounded() {
+        let notifications = Notifications::new();
+        let start = Instant::now();
+        notifications.notify_static(NotifyLevel::Warning, "Overload");
+        assert!(notifications.lock().take(start).is_some());
+
+        // Same message again within the repeat window
+        notifications.notify_static(NotifyLevel::Warning, "Overload");
+        assert_eq!(notifications.lock().take(start + Duration::from_secs(1)), None);
+        assert_eq!(notifications.pending(), 0);
+        notifications.notify_static(NotifyLevel::Warning, "Overload");
+        assert!(notifications.lock().take(start + REPEAT_WINDOW).is_some());
+
+        let notifications = Notifications::new();
+        for i in 0..CAPACITY + 4 {
+            notifications.notify(NotifyLevel::Info, format!("Message {i}"));
+        }
+        assert_eq!(notifications.pending(), CAPACITY);
+        assert!(notifications.take_script().unwrap().starts_with(
+            r#"window.__BEAMER__._onEvent("beamer:notification",{"#
+        ));
+    }
+}
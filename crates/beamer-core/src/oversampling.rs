@@ -0,0 +1,136 @@
+//! Host-automatable oversampling.
+//!
+//! Oversampling trades CPU for fewer aliasing artifacts in nonlinear
+//! processing. Changing the factor changes the processing rate, so filters,
+//! delay lines and smoothers have to be set up again, and the added latency
+//! changes. None of that can happen inside `process()`.
+//!
+//! [`OversamplingParameter`] is an ordinary enum parameter (Off/2x/4x/8x).
+//! The descriptor reports the selected factor from
+//! [`Descriptor::oversampling()`](crate::Descriptor::oversampling):
+//!
+//! ```ignore
+//! #[derive(Parameters)]
+//! pub struct SaturatorParameters {
+//!     #[parameter(id = "oversampling", name = "Oversampling")]
+//!     pub oversampling: OversamplingParameter,
+//! }
+//!
+//! impl Descriptor for SaturatorDescriptor {
+//!     // ...
+//!     fn oversampling(parameters: &Self::Parameters) -> Oversampling {
+//!         parameters.oversampling.get()
+//!     }
+//! }
+//! ```
+//!
+//! # Host Integration
+//!
+//! The wrappers run the processor at the host rate times the factor, inside
+//! the same [`SampleRateAdapter`](crate::SampleRateAdapter) used for
+//! unsupported host rates. `prepare()` sees the oversampled rate and block
+//! size, and the adapter's filter latency is added to the reported latency.
+//!
+//! When the factor changes while prepared, the audio thread keeps running
+//! the old setup. The wrappers re-prepare on the main thread and tell the
+//! host the latency changed:
+//!
+//! | Format | Re-preparation | Notification |
+//! |--------|----------------|--------------|
+//! | VST3 | In `setActive(false)` after the host restarts the processor | `restartComponent(kLatencyChanged)` |
+//! | AUv2 | Immediately, under the plugin lock | Property change of `kAudioUnitProperty_Latency` |
+//! | AUv3 | Immediately, under the plugin lock | KVO change of `latency` |
+//!
+//! Changes are picked up after state loads, host edits (VST3) and, while
+//! the editor is open, GUI edits and automation.
+
+use crate::parameter_types::{EnumParameter, EnumParameterValue};
+
+/// Oversampling factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Oversampling {
+    /// Run at the host rate.
+    #[default]
+    Off,
+    /// Run at twice the host rate.
+    X2,
+    /// Run at four times the host rate.
+    X4,
+    /// Run at eight times the host rate.
+    X8,
+}
+
+impl Oversampling {
+    /// Rate multiplier (1, 2, 4 or 8).
+    pub const fn factor(self) -> u32 {
+        match self {
+            Self::Off => 1,
+            Self::X2 => 2,
+            Self::X4 => 4,
+            Self::X8 => 8,
+        }
+    }
+
+    /// Processing rate for `sample_rate`.
+    pub fn apply(self, sample_rate: f64) -> f64 {
+        sample_rate * self.factor() as f64
+    }
+}
+
+const NAMES: &[&str] = &["Off", "2x", "4x", "8x"];
+
+impl EnumParameterValue for Oversampling {
+    const COUNT: usize = 4;
+    const DEFAULT_INDEX: usize = 0;
+
+    fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Self::Off),
+            1 => Some(Self::X2),
+            2 => Some(Self::X4),
+            3 => Some(Self::X8),
+            _ => None,
+        }
+    }
+
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn default_value() -> Self {
+        Self::Off
+    }
+
+    fn name(index: usize) -> &'static str {
+        NAMES.get(index).copied().unwrap_or("")
+    }
+
+    fn names() -> &'static [&'static str] {
+        NAMES
+    }
+}
+
+/// Enum parameter selecting an [`Oversampling`] factor.
+///
+/// Recognized by `#[derive(Parameters)]` like any `EnumParameter`.
+pub type OversamplingParameter = EnumParameter<Oversampling>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_names_and_factors_agree() {
+        for index in 0..Oversampling::COUNT {
+            let value = Oversampling::from_index(index).unwrap();
+            assert_eq!(value.to_index(), index);
+            assert_eq!(value.factor(), 1 << index);
+        }
+        assert_eq!(Oversampling::from_index(4), None);
+        assert_eq!(Oversampling::name(2), "4x");
+        assert_eq!(Oversampling::X2.apply(48000.0), 96000.0);
+
+        let parameter = OversamplingParameter::new("Oversampling");
+        assert_eq!(parameter.get(), Oversampling::Off);
+    }
+}
@@ -0,0 +1,361 @@
+//! Latency-compensated parallel processing lanes.
+//!
+//! Parallel routing inside a single plugin (parallel compression, multiband
+//! "dry + processed" blends, NY-style drum buses) duplicates the input into
+//! several chains and sums them afterwards. When the chains have different
+//! latencies (e.g. one uses a lookahead limiter), summing them directly causes
+//! comb filtering. [`ParallelSplit`] delays the faster lanes so every lane is
+//! aligned to the slowest one, and reports the resulting latency.
+//!
+//! All buffers are allocated up front, so [`ParallelSplit::split()`] and
+//! [`ParallelSplit::sum_into()`] are real-time safe.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::ParallelSplit;
+//!
+//! struct ParallelCompProcessor {
+//!     split: ParallelSplit,
+//!     compressor: LookaheadCompressor, // 64 samples latency
+//! }
+//!
+//! impl Descriptor for ParallelCompDescriptor {
+//!     type Setup = (MaxBufferSize, MainOutputChannels);
+//!
+//!     fn prepare(self, (max, channels): Self::Setup) -> ParallelCompProcessor {
+//!         let mut split = ParallelSplit::new(2, channels.0 as usize, max.0, 1024);
+//!         split.set_lane_latency(1, 64);
+//!         // ...
+//!     }
+//! }
+//!
+//! impl Processor for ParallelCompProcessor {
+//!     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!         self.split.split(buffer);
+//!         // Lane 0 stays dry, lane 1 is compressed
+//!         self.compressor.process(self.split.lane_mut(1));
+//!         self.split.set_lane_gain(1, self.parameters.blend.get() as f32);
+//!         self.split.sum_into(buffer);
+//!     }
+//!
+//!     fn latency_samples(&self) -> u32 {
+//!         self.split.latency_samples()
+//!     }
+//! }
+//! ```
+
+use crate::buffer::Buffer;
+use crate::sample::Sample;
+
+// =============================================================================
+// Lane
+// =============================================================================
+
+/// Audio for one lane of a [`ParallelSplit`] (or one band of a
+/// [`Crossover`](crate::crossover::Crossover)) during the current block.
+///
+/// Channels are pre-allocated to the maximum block size; accessors return
+/// slices trimmed to the current block length.
+pub struct Lane<S: Sample = f32> {
+    pub(crate) channels: Vec<Vec<S>>,
+    pub(crate) num_samples: usize,
+}
+
+impl<S: Sample> Lane<S> {
+    pub(crate) fn new(num_channels: usize, max_block_size: usize) -> Self {
+        Self {
+            channels: vec![vec![S::ZERO; max_block_size]; num_channels],
+            num_samples: 0,
+        }
+    }
+
+    /// Number of channels in this lane.
+    #[inline]
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Number of samples in the current block.
+    #[inline]
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    /// Get a channel's samples for the current block.
+    #[inline]
+    pub fn channel(&self, channel: usize) -> &[S] {
+        &self.channels[channel][..self.num_samples]
+    }
+
+    /// Get a channel's samples mutably for the current block.
+    #[inline]
+    pub fn channel_mut(&mut self, channel: usize) -> &mut [S] {
+        let num_samples = self.num_samples;
+        &mut self.channels[channel][..num_samples]
+    }
+
+    /// Iterate over all channels mutably.
+    pub fn channels_mut(&mut self) -> impl Iterator<Item = &mut [S]> + '_ {
+        let num_samples = self.num_samples;
+        self.channels.iter_mut().map(move |c| &mut c[..num_samples])
+    }
+}
+
+// =============================================================================
+// Compensation delay
+// =============================================================================
+
+/// Fixed-capacity delay line used to align a lane with the slowest lane.
+struct CompensationDelay<S: Sample> {
+    buffer: Vec<S>,
+    write_pos: usize,
+}
+
+impl<S: Sample> CompensationDelay<S> {
+    fn new(max_delay: usize) -> Self {
+        Self {
+            buffer: vec![S::ZERO; max_delay + 1],
+            write_pos: 0,
+        }
+    }
+
+    /// Push one sample and return the sample written `delay` samples ago.
+    #[inline]
+    fn process(&mut self, input: S, delay: usize) -> S {
+        let len = self.buffer.len();
+        self.buffer[self.write_pos] = input;
+        let read_pos = (self.write_pos + len - delay) % len;
+        self.write_pos = (self.write_pos + 1) % len;
+        self.buffer[read_pos]
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(S::ZERO);
+        self.write_pos = 0;
+    }
+}
+
+// =============================================================================
+// ParallelSplit
+// =============================================================================
+
+/// Splits a buffer into N parallel lanes and sums them back with latency
+/// compensation and per-lane gain.
+pub struct ParallelSplit<S: Sample = f32> {
+    lanes: Vec<Lane<S>>,
+    latencies: Vec<u32>,
+    gains: Vec<S>,
+    /// Per lane, per channel compensation delay lines.
+    delays: Vec<Vec<CompensationDelay<S>>>,
+    max_block_size: usize,
+    max_latency: u32,
+}
+
+impl<S: Sample> ParallelSplit<S> {
+    /// Create a split with pre-allocated lanes.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_lanes` - Number of parallel lanes
+    /// * `num_channels` - Channels per lane
+    /// * `max_block_size` - Largest block passed to [`split()`](Self::split)
+    /// * `max_latency` - Largest lane latency that can be compensated
+    pub fn new(num_lanes: usize, num_channels: usize, max_block_size: usize, max_latency: u32) -> Self {
+        let lanes = (0..num_lanes)
+            .map(|_| Lane::new(num_channels, max_block_size))
+            .collect();
+        let delays = (0..num_lanes)
+            .map(|_| {
+                (0..num_channels)
+                    .map(|_| CompensationDelay::new(max_latency as usize))
+                    .collect()
+            })
+            .collect();
+        Self {
+            lanes,
+            latencies: vec![0; num_lanes],
+            gains: vec![S::ONE; num_lanes],
+            delays,
+            max_block_size,
+            max_latency,
+        }
+    }
+
+    /// Number of lanes.
+    #[inline]
+    pub fn num_lanes(&self) -> usize {
+        self.lanes.len()
+    }
+
+    /// Declare the latency (in samples) introduced by a lane's chain.
+    ///
+    /// Clamped to the `max_latency` given at construction.
+    pub fn set_lane_latency(&mut self, lane: usize, samples: u32) {
+        if let Some(latency) = self.latencies.get_mut(lane) {
+            *latency = samples.min(self.max_latency);
+        }
+    }
+
+    /// Set the linear gain applied to a lane when summing.
+    pub fn set_lane_gain(&mut self, lane: usize, gain: S) {
+        if let Some(g) = self.gains.get_mut(lane) {
+            *g = gain;
+        }
+    }
+
+    /// Total latency of the summed output: the latency of the slowest lane.
+    ///
+    /// Return this (plus any serial latency) from `Processor::latency_samples()`.
+    pub fn latency_samples(&self) -> u32 {
+        self.latencies.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Get a lane for processing.
+    #[inline]
+    pub fn lane(&self, lane: usize) -> &Lane<S> {
+        &self.lanes[lane]
+    }
+
+    /// Get a lane mutably for processing.
+    #[inline]
+    pub fn lane_mut(&mut self, lane: usize) -> &mut Lane<S> {
+        &mut self.lanes[lane]
+    }
+
+    /// Copy the buffer's input into every lane.
+    ///
+    /// Missing input channels are filled with silence. Blocks longer than
+    /// `max_block_size` are truncated.
+    pub fn split(&mut self, buffer: &Buffer<S>) {
+        let num_samples = buffer.num_samples().min(self.max_block_size);
+        let num_inputs = buffer.num_input_channels();
+        for lane in &mut self.lanes {
+            lane.num_samples = num_samples;
+            for (ch, channel) in lane.channels.iter_mut().enumerate() {
+                let dest = &mut channel[..num_samples];
+                if ch < num_inputs {
+                    dest.copy_from_slice(&buffer.input(ch)[..num_samples]);
+                } else {
+                    dest.fill(S::ZERO);
+                }
+            }
+        }
+    }
+
+    /// Sum all lanes into the buffer's output, aligned to the slowest lane.
+    pub fn sum_into(&mut self, buffer: &mut Buffer<S>) {
+        let total_latency = self.latency_samples();
+        let num_outputs = buffer.num_output_channels();
+
+        for ch in 0..num_outputs {
+            let output = buffer.output(ch);
+            let num_samples = output
+                .len()
+                .min(self.lanes.first().map_or(0, |l| l.num_samples));
+            output[..num_samples].fill(S::ZERO);
+
+            for (lane_idx, lane) in self.lanes.iter().enumerate() {
+                let Some(channel) = lane.channels.get(ch) else {
+                    continue;
+                };
+                let gain = self.gains[lane_idx];
+                let delay = (total_latency - self.latencies[lane_idx]) as usize;
+                let line = &mut self.delays[lane_idx][ch];
+                for (out, &sample) in output[..num_samples].iter_mut().zip(&channel[..num_samples]) {
+                    *out = *out + line.process(sample, delay) * gain;
+                }
+            }
+        }
+    }
+
+    /// Clear lane audio and compensation delay state.
+    ///
+    /// Call from `Processor::set_active(true)`.
+    pub fn reset(&mut self) {
+        for lane in &mut self.lanes {
+            for channel in &mut lane.channels {
+                channel.fill(S::ZERO);
+            }
+        }
+        for line in self.delays.iter_mut().flatten() {
+            line.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse(len: usize) -> Vec<f32> {
+        let mut v = vec![0.0; len];
+        v[0] = 1.0;
+        v
+    }
+
+    #[test]
+    fn test_equal_latency_sums_lanes() {
+        let mut split = ParallelSplit::<f32>::new(2, 1, 8, 16);
+        let input = impulse(8);
+        let mut output = vec![0.0f32; 8];
+        {
+            let mut buffer = Buffer::new([input.as_slice()], [output.as_mut_slice()], 8);
+            split.split(&buffer);
+            split.set_lane_gain(1, 0.5);
+            split.sum_into(&mut buffer);
+        }
+        assert_eq!(output[0], 1.5);
+        assert!(output[1..].iter().all(|&s| s == 0.0));
+        assert_eq!(split.latency_samples(), 0);
+    }
+
+    #[test]
+    fn test_faster_lane_is_delayed() {
+        let mut split = ParallelSplit::<f32>::new(2, 1, 8, 16);
+        split.set_lane_latency(1, 3);
+        assert_eq!(split.latency_samples(), 3);
+
+        let input = impulse(8);
+        let mut output = vec![0.0f32; 8];
+        {
+            let mut buffer = Buffer::new([input.as_slice()], [output.as_mut_slice()], 8);
+            split.split(&buffer);
+            // Simulate lane 1's chain introducing 3 samples of latency
+            let lane = split.lane_mut(1).channel_mut(0);
+            lane.copy_from_slice(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+            split.sum_into(&mut buffer);
+        }
+        // Dry lane delayed by 3 samples lines up with the processed lane
+        assert_eq!(output[3], 2.0);
+        assert_eq!(output.iter().sum::<f32>(), 2.0);
+    }
+
+    #[test]
+    fn test_delay_carries_across_blocks() {
+        let mut split = ParallelSplit::<f32>::new(2, 1, 4, 16);
+        split.set_lane_latency(1, 6);
+        split.set_lane_gain(1, 0.0);
+
+        let mut collected = Vec::new();
+        for block in 0..3 {
+            let input = if block == 0 { impulse(4) } else { vec![0.0; 4] };
+            let mut output = vec![0.0f32; 4];
+            {
+                let mut buffer = Buffer::new([input.as_slice()], [output.as_mut_slice()], 4);
+                split.split(&buffer);
+                split.sum_into(&mut buffer);
+            }
+            collected.extend(output);
+        }
+        assert_eq!(collected[6], 1.0);
+        assert_eq!(collected.iter().sum::<f32>(), 1.0);
+    }
+
+    #[test]
+    fn test_latency_clamped_to_max() {
+        let mut split = ParallelSplit::<f32>::new(1, 2, 8, 10);
+        split.set_lane_latency(0, 100);
+        assert_eq!(split.latency_samples(), 10);
+    }
+}
@@ -0,0 +1,468 @@
+//! Constraints between parameters.
+//!
+//! Some parameters only make sense in a certain relation to each other: an
+//! envelope's attack should not exceed its release, a band's low edge should
+//! stay below its high edge. Declare these relations on the descriptor with
+//! [`Descriptor::parameter_constraints()`] and the wrapper enforces them
+//! whenever a parameter is set, so the DSP never sees an invalid combination:
+//!
+//! ```ignore
+//! fn parameter_constraints(&self) -> ParameterConstraints {
+//!     ParameterConstraints::new()
+//!         .with(Constraint::ordered("attack", "release"))
+//!         .with(Constraint::ordered("low_freq", "high_freq").with_min_gap(10.0).pushing())
+//! }
+//! ```
+//!
+//! An ordered constraint keeps `lower + min_gap <= upper` in plain values
+//! (Hz, ms, dB, ...). When a change violates it, the constraint's
+//! [`ConstraintMode`] decides how it is resolved:
+//!
+//! | Mode | Resolution |
+//! |------|------------|
+//! | [`ConstraintMode::Clamp`] (default) | The changed parameter is clamped against the other one. |
+//! | [`ConstraintMode::Push`] | The other parameter moves along. If it hits the end of its range, the changed parameter is clamped. |
+//!
+//! Pushed parameters are enforced in turn, so chains like
+//! `attack <= decay <= release` work. Each constraint is resolved at most a
+//! bounded number of times per change, so contradictory declarations cannot
+//! loop.
+//!
+//! # Host Integration
+//!
+//! Every value the wrapper adjusts is reported back so GUIs and hosts stay
+//! consistent:
+//!
+//! | Format | Notification |
+//! |--------|--------------|
+//! | VST3 | In `process()` through the output parameter changes; for host and GUI edits through `performEdit` / `kParamValuesChanged`. |
+//! | AU | The AUv3 parameter tree is updated from the Rust values; the WebView picks up the new values on its next poll. |
+//!
+//! Saved states and presets are checked as a whole after loading, with the
+//! lower parameter of each pair taking precedence.
+//!
+//! [`Descriptor::parameter_constraints()`]: crate::plugin::Descriptor::parameter_constraints
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::parameter_store::ParameterStore;
+use crate::types::{ParameterId, ParameterValue};
+
+/// Maximum number of parameters queued while resolving one change.
+///
+/// Chains longer than this stop being followed; the remaining parameters are
+/// still checked on their next change.
+const MAX_PENDING: usize = 16;
+
+// =============================================================================
+// Definition
+// =============================================================================
+
+/// How a violated constraint is resolved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConstraintMode {
+    /// Clamp the parameter that was changed.
+    #[default]
+    Clamp,
+    /// Move the other parameter along.
+    Push,
+}
+
+/// An ordering between two parameters: `lower + min_gap <= upper`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constraint {
+    lower: &'static str,
+    upper: &'static str,
+    min_gap: f64,
+    mode: ConstraintMode,
+}
+
+impl Constraint {
+    /// Keep `lower <= upper`, by parameter string ID.
+    pub fn ordered(lower: &'static str, upper: &'static str) -> Self {
+        Self {
+            lower,
+            upper,
+            min_gap: 0.0,
+            mode: ConstraintMode::Clamp,
+        }
+    }
+
+    /// Require at least `gap` between the two, in the parameters' plain units.
+    pub fn with_min_gap(mut self, gap: f64) -> Self {
+        self.min_gap = gap.max(0.0);
+        self
+    }
+
+    /// Set how violations are resolved.
+    pub fn with_mode(mut self, mode: ConstraintMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Shorthand for `with_mode(ConstraintMode::Push)`.
+    pub fn pushing(self) -> Self {
+        self.with_mode(ConstraintMode::Push)
+    }
+
+    /// String ID of the lower parameter.
+    pub fn lower(&self) -> &'static str {
+        self.lower
+    }
+
+    /// String ID of the upper parameter.
+    pub fn upper(&self) -> &'static str {
+        self.upper
+    }
+
+    /// Minimum distance between the two in plain units.
+    pub fn min_gap(&self) -> f64 {
+        self.min_gap
+    }
+
+    /// Resolution mode.
+    pub fn mode(&self) -> ConstraintMode {
+        self.mode
+    }
+}
+
+/// Constraints declared by a plugin.
+///
+/// Returned by `Descriptor::parameter_constraints()`. Empty by default, which
+/// disables enforcement.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterConstraints {
+    constraints: Vec<Constraint>,
+}
+
+impl ParameterConstraints {
+    /// Create an empty constraint list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a constraint.
+    pub fn with(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Constraints in declaration order.
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Returns true if no constraints are declared.
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+}
+
+// =============================================================================
+// Runtime State
+// =============================================================================
+
+/// A constraint with its parameters resolved to IDs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ResolvedConstraint {
+    lower: ParameterId,
+    upper: ParameterId,
+    min_gap: f64,
+    mode: ConstraintMode,
+}
+
+/// Per-instance constraint enforcement.
+///
+/// Cloning is cheap (reference counted), so the wrapper can share one state
+/// between its host interface, its audio path and its WebView IPC context.
+/// Enforcement does not allocate and is safe on the audio thread.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintState {
+    inner: Arc<ConstraintStateInner>,
+}
+
+#[derive(Debug, Default)]
+struct ConstraintStateInner {
+    constraints: Vec<ResolvedConstraint>,
+    /// Set whenever enforcement adjusts a value; consumed by the wrapper.
+    adjusted: AtomicBool,
+}
+
+impl ConstraintState {
+    /// Resolve `constraints` against the plugin's parameters.
+    ///
+    /// Constraints naming an unknown string ID are dropped (and logged).
+    pub fn new(constraints: &ParameterConstraints, store: &dyn ParameterStore) -> Self {
+        let resolve = |string_id: &str| -> Option<ParameterId> {
+            let id = (0..store.count())
+                .filter_map(|i| store.info(i))
+                .find(|info| info.string_id == string_id)
+                .map(|info| info.id);
+            if id.is_none() {
+                log::warn!("Constraint parameter '{}' not found", string_id);
+            }
+            id
+        };
+        Self::from_resolved(
+            constraints
+                .constraints
+                .iter()
+                .filter_map(|c| {
+                    Some(ResolvedConstraint {
+                        lower: resolve(c.lower)?,
+                        upper: resolve(c.upper)?,
+                        min_gap: c.min_gap,
+                        mode: c.mode,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn from_resolved(constraints: Vec<ResolvedConstraint>) -> Self {
+        Self {
+            inner: Arc::new(ConstraintStateInner {
+                constraints,
+                adjusted: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Returns true if the plugin declares no constraints.
+    pub fn is_empty(&self) -> bool {
+        self.inner.constraints.is_empty()
+    }
+
+    /// Returns true if `id` takes part in any constraint.
+    pub fn involves(&self, id: ParameterId) -> bool {
+        self.inner
+            .constraints
+            .iter()
+            .any(|c| c.lower == id || c.upper == id)
+    }
+
+    /// Enforce the constraints after `changed` was set.
+    ///
+    /// Calls `adjusted` with the ID and new normalized value of every
+    /// parameter that had to move, which may include `changed` itself, and
+    /// marks the state for [`take_adjusted()`](Self::take_adjusted).
+    pub fn enforce(
+        &self,
+        store: &dyn ParameterStore,
+        changed: ParameterId,
+        mut adjusted: impl FnMut(ParameterId, ParameterValue),
+    ) {
+        if !self.involves(changed) {
+            return;
+        }
+        let mut pending = [0 as ParameterId; MAX_PENDING];
+        pending[0] = changed;
+        let mut len = 1;
+        // Each constraint may fire a few times per change; more means the
+        // declarations contradict each other.
+        let mut budget = self.inner.constraints.len() * 2;
+
+        while len > 0 && budget > 0 {
+            len -= 1;
+            let id = pending[len];
+            for constraint in &self.inner.constraints {
+                if constraint.lower != id && constraint.upper != id {
+                    continue;
+                }
+                if budget == 0 {
+                    break;
+                }
+                budget -= 1;
+                resolve(store, constraint, id, |moved, value| {
+                    self.inner.adjusted.store(true, Ordering::Release);
+                    adjusted(moved, value);
+                    if len < MAX_PENDING && !pending[..len].contains(&moved) {
+                        pending[len] = moved;
+                        len += 1;
+                    }
+                });
+            }
+        }
+    }
+
+    /// Enforce every constraint, e.g. after loading a state or preset.
+    ///
+    /// The lower parameter of each pair is treated as the changed one.
+    pub fn enforce_all(
+        &self,
+        store: &dyn ParameterStore,
+        mut adjusted: impl FnMut(ParameterId, ParameterValue),
+    ) {
+        for constraint in &self.inner.constraints {
+            self.enforce(store, constraint.lower, &mut adjusted);
+        }
+    }
+
+    /// Returns true once after enforcement adjusted a value.
+    ///
+    /// Wrappers that can't notify the host where they enforce (the audio
+    /// thread, the AU bridge) poll this to resync the host's values.
+    pub fn take_adjusted(&self) -> bool {
+        self.inner.adjusted.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Resolve one constraint after `changed` was set.
+fn resolve(
+    store: &dyn ParameterStore,
+    constraint: &ResolvedConstraint,
+    changed: ParameterId,
+    mut adjusted: impl FnMut(ParameterId, ParameterValue),
+) {
+    let plain = |id| store.normalized_to_plain(id, store.get_normalized(id));
+    let satisfied = || plain(constraint.lower) + constraint.min_gap <= plain(constraint.upper) + 1e-9;
+    let mut set_plain = |id, value| {
+        let previous = store.get_normalized(id);
+        store.set_normalized(id, store.plain_to_normalized(id, value));
+        let normalized = store.get_normalized(id);
+        if normalized != previous {
+            adjusted(id, normalized);
+        }
+    };
+
+    if satisfied() {
+        return;
+    }
+    let changed_is_lower = changed == constraint.lower;
+
+    if constraint.mode == ConstraintMode::Push {
+        let (other, target) = if changed_is_lower {
+            (constraint.upper, plain(constraint.lower) + constraint.min_gap)
+        } else {
+            (constraint.lower, plain(constraint.upper) - constraint.min_gap)
+        };
+        set_plain(other, target);
+        if satisfied() {
+            return;
+        }
+    }
+
+    let target = if changed_is_lower {
+        plain(constraint.upper) - constraint.min_gap
+    } else {
+        plain(constraint.lower) + constraint.min_gap
+    };
+    set_plain(changed, target);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameter_info::ParameterInfo;
+    use std::sync::atomic::AtomicU64;
+
+    /// Three parameters over 0..100 in plain units.
+    struct Store {
+        infos: Vec<ParameterInfo>,
+        values: [AtomicU64; 3],
+    }
+
+    impl Store {
+        fn new() -> Self {
+            Self {
+                infos: (0..3).map(|id| ParameterInfo::new(id, "p")).collect(),
+                values: Default::default(),
+            }
+        }
+
+        fn plain(&self, id: ParameterId) -> f64 {
+            self.normalized_to_plain(id, self.get_normalized(id))
+        }
+
+        fn set_plain(&self, id: ParameterId, plain: f64) {
+            self.set_normalized(id, self.plain_to_normalized(id, plain));
+        }
+    }
+
+    impl ParameterStore for Store {
+        fn count(&self) -> usize {
+            3
+        }
+        fn info(&self, index: usize) -> Option<&ParameterInfo> {
+            self.infos.get(index)
+        }
+        fn get_normalized(&self, id: ParameterId) -> ParameterValue {
+            f64::from_bits(self.values[id as usize].load(Ordering::Relaxed))
+        }
+        fn set_normalized(&self, id: ParameterId, value: ParameterValue) {
+            self.values[id as usize].store(value.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+        }
+        fn normalized_to_string(&self, _id: ParameterId, normalized: ParameterValue) -> String {
+            normalized.to_string()
+        }
+        fn string_to_normalized(&self, _id: ParameterId, _string: &str) -> Option<ParameterValue> {
+            None
+        }
+        fn normalized_to_plain(&self, _id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+            normalized * 100.0
+        }
+        fn plain_to_normalized(&self, _id: ParameterId, plain: ParameterValue) -> ParameterValue {
+            plain / 100.0
+        }
+    }
+
+    fn ordered(lower: ParameterId, upper: ParameterId, min_gap: f64, mode: ConstraintMode) -> ResolvedConstraint {
+        ResolvedConstraint { lower, upper, min_gap, mode }
+    }
+
+    #[test]
+    fn test_clamp_and_push() {
+        let store = Store::new();
+        let state = ConstraintState::from_resolved(vec![
+            ordered(0, 1, 0.0, ConstraintMode::Clamp),
+            ordered(1, 2, 10.0, ConstraintMode::Push),
+        ]);
+        store.set_plain(1, 50.0);
+        store.set_plain(2, 80.0);
+
+        // Clamp: raising 0 past 1 clamps 0.
+        store.set_plain(0, 70.0);
+        let mut moved = Vec::new();
+        state.enforce(&store, 0, |id, _| moved.push(id));
+        assert_eq!(moved, vec![0]);
+        assert!((store.plain(0) - 50.0).abs() < 1e-9);
+
+        // Push: raising 1 to 75 pushes 2 to 85.
+        store.set_plain(1, 75.0);
+        moved.clear();
+        state.enforce(&store, 1, |id, _| moved.push(id));
+        assert_eq!(moved, vec![2]);
+        assert!((store.plain(2) - 85.0).abs() < 1e-9);
+
+        // Push past the end of the range clamps the changed parameter.
+        store.set_plain(1, 100.0);
+        state.enforce(&store, 1, |_, _| {});
+        assert!((store.plain(2) - 100.0).abs() < 1e-9);
+        assert!((store.plain(1) - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chained_push_and_enforce_all() {
+        let store = Store::new();
+        let state = ConstraintState::from_resolved(vec![
+            ordered(0, 1, 0.0, ConstraintMode::Push),
+            ordered(1, 2, 0.0, ConstraintMode::Push),
+        ]);
+
+        // 0 pushes 1, which pushes 2.
+        store.set_plain(0, 40.0);
+        let mut moved = Vec::new();
+        state.enforce(&store, 0, |id, _| moved.push(id));
+        assert_eq!(moved, vec![1, 2]);
+        assert!((store.plain(2) - 40.0).abs() < 1e-9);
+
+        // A loaded state is fixed up as a whole.
+        store.set_plain(0, 10.0);
+        store.set_plain(1, 30.0);
+        store.set_plain(2, 20.0);
+        state.enforce_all(&store, |_, _| {});
+        assert!(store.plain(0) <= store.plain(1) && store.plain(1) <= store.plain(2));
+        assert!((store.plain(2) - 30.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,366 @@
+//! Host exposure of internal parameters.
+//!
+//! Large modular plugins can declare far more parameters than hosts handle
+//! well (automation menus with thousands of entries, AU hosts with hard
+//! limits). Parameters marked internal (`#[parameter(..., internal)]` or
+//! `.internal()`) stay GUI-only until the user *promotes* them, for example
+//! via a "Show in host" item in the plugin's context menu.
+//!
+//! [`ParameterExposure`] is the per-instance list of parameters the wrapper
+//! reports to the host: every non-internal parameter plus the promoted ones,
+//! in declaration order. The GUI always sees the full parameter set.
+//!
+//! # Persistence
+//!
+//! The promoted set is saved with the plugin state. When the plugin has
+//! internal parameters, the wrapper appends a small trailer to the bytes
+//! returned by `Processor::save_state()` and strips it again before calling
+//! `Processor::load_state()`, so plugin code never sees it:
+//!
+//! ```text
+//! [plugin state][id: u32 LE]*[count: u32 LE]["BXPO"]
+//! ```
+//!
+//! Plugins without internal parameters are unaffected: no trailer is written
+//! and the host sees every parameter, as before.
+//!
+//! # GUI Invokes
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`PROMOTE_PARAMETER_INVOKE`] | `[id]` | `true` if the host list changed |
+//! | [`DEMOTE_PARAMETER_INVOKE`] | `[id]` | `true` if the host list changed |
+//! | [`PROMOTED_PARAMETERS_INVOKE`] | `[]` | Promoted parameter IDs |
+//!
+//! After a change the wrapper asks the host to rescan its parameter list.
+//!
+//! **Not audio-thread safe.** The list is behind a mutex. Hosts only query
+//! it from the main thread, and the audio thread addresses parameters by ID.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::parameter_store::ParameterStore;
+use crate::types::ParameterId;
+
+/// Built-in WebView invoke method that promotes an internal parameter.
+pub const PROMOTE_PARAMETER_INVOKE: &str = "_beamer/promoteParameter";
+
+/// Built-in WebView invoke method that demotes a promoted parameter.
+pub const DEMOTE_PARAMETER_INVOKE: &str = "_beamer/demoteParameter";
+
+/// Built-in WebView invoke method that lists promoted parameter IDs.
+pub const PROMOTED_PARAMETERS_INVOKE: &str = "_beamer/promotedParameters";
+
+/// Magic bytes ending the promoted-set state trailer.
+const STATE_TRAILER_MAGIC: &[u8; 4] = b"BXPO";
+
+/// Per-instance list of parameters exposed to the host.
+///
+/// Cloning is cheap (reference counted), so the wrapper can share one list
+/// between its host interface and its WebView IPC context.
+#[derive(Clone, Debug, Default)]
+pub struct ParameterExposure {
+    inner: Arc<ExposureInner>,
+}
+
+#[derive(Debug, Default)]
+struct ExposureInner {
+    /// `(id, is_internal)` for every parameter, in store order.
+    parameters: Vec<(ParameterId, bool)>,
+    /// True if any parameter is internal.
+    has_internal: bool,
+    state: Mutex<ExposureState>,
+    /// Set whenever the exposed list changes; consumed by the wrapper.
+    changed: AtomicBool,
+}
+
+#[derive(Debug, Default)]
+struct ExposureState {
+    /// Promoted internal parameter IDs, in promotion order.
+    promoted: Vec<ParameterId>,
+    /// Store indices exposed to the host, in store order.
+    exposed: Vec<usize>,
+}
+
+impl ParameterExposure {
+    /// Build the exposure list for `store`, with nothing promoted.
+    pub fn new(store: &dyn ParameterStore) -> Self {
+        Self::from_parameters(
+            (0..store.count())
+                .filter_map(|i| store.info(i).map(|info| (info.id, info.flags.is_internal)))
+                .collect(),
+        )
+    }
+
+    fn from_parameters(parameters: Vec<(ParameterId, bool)>) -> Self {
+        let has_internal = parameters.iter().any(|&(_, internal)| internal);
+        let exposure = Self {
+            inner: Arc::new(ExposureInner {
+                parameters,
+                has_internal,
+                state: Mutex::new(ExposureState::default()),
+                changed: AtomicBool::new(false),
+            }),
+        };
+        exposure.with_state(|state| exposure.rebuild(state));
+        exposure.inner.changed.store(false, Ordering::Relaxed);
+        exposure
+    }
+
+    /// Returns true if the plugin declares internal parameters.
+    pub fn has_internal(&self) -> bool {
+        self.inner.has_internal
+    }
+
+    /// Number of parameters exposed to the host.
+    pub fn count(&self) -> usize {
+        self.with_state(|state| state.exposed.len())
+    }
+
+    /// Map a host parameter index to a [`ParameterStore`] index.
+    pub fn store_index(&self, host_index: usize) -> Option<usize> {
+        self.with_state(|state| state.exposed.get(host_index).copied())
+    }
+
+    /// Returns true if the parameter is currently visible to the host.
+    pub fn is_exposed(&self, id: ParameterId) -> bool {
+        match self.inner.parameters.iter().find(|&&(pid, _)| pid == id) {
+            Some(&(_, false)) => true,
+            Some(&(_, true)) => self.with_state(|state| state.promoted.contains(&id)),
+            None => false,
+        }
+    }
+
+    /// Promoted internal parameter IDs, in promotion order.
+    pub fn promoted(&self) -> Vec<ParameterId> {
+        self.with_state(|state| state.promoted.clone())
+    }
+
+    /// Expose an internal parameter to the host.
+    ///
+    /// Returns true if the host list changed. Promoting a parameter that is
+    /// not internal, unknown or already promoted does nothing.
+    pub fn promote(&self, id: ParameterId) -> bool {
+        if !self.is_internal(id) {
+            return false;
+        }
+        self.with_state(|state| {
+            if state.promoted.contains(&id) {
+                return false;
+            }
+            state.promoted.push(id);
+            self.rebuild(state);
+            true
+        })
+    }
+
+    /// Hide a promoted parameter from the host again.
+    ///
+    /// Returns true if the host list changed.
+    pub fn demote(&self, id: ParameterId) -> bool {
+        self.with_state(|state| {
+            let before = state.promoted.len();
+            state.promoted.retain(|&p| p != id);
+            if state.promoted.len() == before {
+                return false;
+            }
+            self.rebuild(state);
+            true
+        })
+    }
+
+    /// Returns true once after the exposed list changed.
+    ///
+    /// Wrappers poll this to ask the host to rescan its parameter list.
+    pub fn take_changed(&self) -> bool {
+        self.inner.changed.swap(false, Ordering::AcqRel)
+    }
+
+    /// Append the promoted-set trailer to a saved plugin state.
+    ///
+    /// Does nothing when the plugin has no internal parameters.
+    pub fn append_state(&self, data: &mut Vec<u8>) {
+        if !self.inner.has_internal {
+            return;
+        }
+        let promoted = self.promoted();
+        for id in &promoted {
+            data.extend_from_slice(&id.to_le_bytes());
+        }
+        data.extend_from_slice(&(promoted.len() as u32).to_le_bytes());
+        data.extend_from_slice(STATE_TRAILER_MAGIC);
+    }
+
+    /// Apply and strip the promoted-set trailer from a state blob.
+    ///
+    /// Returns the length of the plugin's own state. A state without a
+    /// trailer (older sessions, or a plugin without internal parameters)
+    /// resets the promoted set and is passed through unchanged.
+    pub fn load_state(&self, data: &[u8]) -> usize {
+        if !self.inner.has_internal {
+            return data.len();
+        }
+
+        let (plugin_len, promoted) = parse_trailer(data).unwrap_or((data.len(), Vec::new()));
+        self.with_state(|state| {
+            let promoted: Vec<ParameterId> = promoted
+                .into_iter()
+                .filter(|&id| self.is_internal(id))
+                .collect();
+            if promoted != state.promoted {
+                state.promoted = promoted;
+                self.rebuild(state);
+            }
+        });
+        plugin_len
+    }
+
+    fn is_internal(&self, id: ParameterId) -> bool {
+        self.inner
+            .parameters
+            .iter()
+            .any(|&(pid, internal)| pid == id && internal)
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut ExposureState) -> R) -> R {
+        let mut state = self
+            .inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut state)
+    }
+
+    fn rebuild(&self, state: &mut ExposureState) {
+        state.exposed = self
+            .inner
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, &(id, internal))| !internal || state.promoted.contains(&id))
+            .map(|(index, _)| index)
+            .collect();
+        self.inner.changed.store(true, Ordering::Release);
+    }
+}
+
+/// Handle the built-in exposure WebView calls.
+///
+/// Returns `None` if `method` is not one of the exposure invokes.
+pub fn exposure_invoke(
+    exposure: &ParameterExposure,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    let id = || {
+        args.first()
+            .and_then(|v| v.as_u64())
+            .map(|v| v as ParameterId)
+    };
+    match method {
+        PROMOTE_PARAMETER_INVOKE => Some(id().is_some_and(|id| exposure.promote(id)).into()),
+        DEMOTE_PARAMETER_INVOKE => Some(id().is_some_and(|id| exposure.demote(id)).into()),
+        PROMOTED_PARAMETERS_INVOKE => Some(exposure.promoted().into()),
+        _ => None,
+    }
+}
+
+/// Parse `[id]*[count][magic]` from the end of `data`.
+fn parse_trailer(data: &[u8]) -> Option<(usize, Vec<ParameterId>)> {
+    let magic_start = data.len().checked_sub(STATE_TRAILER_MAGIC.len())?;
+    if &data[magic_start..] != STATE_TRAILER_MAGIC {
+        return None;
+    }
+    let count_start = magic_start.checked_sub(4)?;
+    let count = u32::from_le_bytes(data[count_start..magic_start].try_into().ok()?) as usize;
+    let ids_start = count_start.checked_sub(count.checked_mul(4)?)?;
+    let ids = data[ids_start..count_start]
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    Some((ids_start, ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parameters with IDs 100, 101, ... and the given internal flags.
+    fn exposure(internal: &[bool]) -> ParameterExposure {
+        ParameterExposure::from_parameters(
+            internal
+                .iter()
+                .enumerate()
+                .map(|(i, &internal)| (i as ParameterId + 100, internal))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_internal_parameters_hidden_until_promoted() {
+        let exposure = exposure(&[false, true, false, true]);
+        assert_eq!(exposure.count(), 2);
+        assert_eq!(exposure.store_index(1), Some(2));
+        assert!(!exposure.is_exposed(101));
+
+        assert!(exposure.promote(103));
+        assert!(exposure.take_changed());
+        assert_eq!(exposure.count(), 3);
+        // Host order follows declaration order, not promotion order.
+        assert_eq!(exposure.store_index(2), Some(3));
+        assert!(exposure.is_exposed(103));
+
+        // Only internal, not-yet-promoted parameters can be promoted.
+        assert!(!exposure.promote(103));
+        assert!(!exposure.promote(100));
+        assert!(!exposure.take_changed());
+
+        assert!(exposure.demote(103));
+        assert_eq!(exposure.count(), 2);
+        assert!(!exposure.demote(103));
+    }
+
+    #[test]
+    fn test_state_trailer_roundtrip() {
+        let exposure = exposure(&[false, true, true]);
+        exposure.promote(102);
+        exposure.promote(101);
+
+        let mut data = vec![1, 2, 3];
+        exposure.append_state(&mut data);
+
+        let restored = self::exposure(&[false, true, true]);
+        assert_eq!(restored.load_state(&data), 3);
+        assert_eq!(restored.promoted(), vec![102, 101]);
+        assert!(restored.take_changed());
+
+        // A state without a trailer clears the promoted set.
+        assert_eq!(restored.load_state(&[1, 2, 3]), 3);
+        assert!(restored.promoted().is_empty());
+    }
+
+    #[test]
+    fn test_no_trailer_without_internal_parameters() {
+        let exposure = exposure(&[false, false]);
+        let mut data = vec![7];
+        exposure.append_state(&mut data);
+        assert_eq!(data, vec![7]);
+        assert_eq!(exposure.load_state(b"xBXPO"), 5);
+    }
+
+    #[test]
+    fn test_invoke() {
+        let exposure = exposure(&[true]);
+        let args = [serde_json::Value::from(100)];
+        assert_eq!(
+            exposure_invoke(&exposure, PROMOTE_PARAMETER_INVOKE, &args),
+            Some(serde_json::Value::Bool(true))
+        );
+        assert_eq!(
+            exposure_invoke(&exposure, PROMOTED_PARAMETERS_INVOKE, &[]),
+            Some(serde_json::json!([100]))
+        );
+        assert_eq!(exposure_invoke(&exposure, "other", &[]), None);
+    }
+}
@@ -22,6 +22,22 @@
 //! //     ├── attack
 //! //     └── release
 //! ```
+//!
+//! # GUI Layout Hints
+//!
+//! Groups carry [`GroupHints`] for editors that lay out parameters by group:
+//! a sort order among siblings, whether the section starts collapsed, and an
+//! icon name. Hosts ignore them. Nested groups declare them in the
+//! attribute:
+//!
+//! ```ignore
+//! #[nested(group = "Modulation", order = 2, collapsed, icon = "wave")]
+//! pub modulation: ModulationParameters,
+//! ```
+//!
+//! The WebView runtime lists the groups with their hints through
+//! `__BEAMER__.parameterGroups()` ([`PARAMETER_GROUPS_INVOKE`]), and each
+//! parameter's `groupId` places it in a group.
 
 /// Parameter group ID type.
 ///
@@ -34,6 +50,52 @@ pub type GroupId = i32;
 /// The root group (ID 0) always exists and contains ungrouped parameters.
 pub const ROOT_GROUP_ID: GroupId = 0;
 
+/// Built-in WebView invoke method that lists the parameter groups.
+pub const PARAMETER_GROUPS_INVOKE: &str = "_beamer/parameterGroups";
+
+/// Layout hints for GUIs that present a group as a section or panel.
+///
+/// Hints don't affect the host's parameter list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupHints {
+    /// Sort key among sibling groups (ascending; ties keep declaration order).
+    pub order: i32,
+    /// Whether the section starts collapsed.
+    pub collapsed: bool,
+    /// Icon name for the section header, or `""` for none. The GUI decides
+    /// what names mean.
+    pub icon: &'static str,
+}
+
+impl GroupHints {
+    /// No hints: declaration order, expanded, no icon.
+    pub const fn new() -> Self {
+        Self {
+            order: 0,
+            collapsed: false,
+            icon: "",
+        }
+    }
+
+    /// Set the sort key among sibling groups.
+    pub const fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Start the section collapsed.
+    pub const fn collapsed(mut self) -> Self {
+        self.collapsed = true;
+        self
+    }
+
+    /// Set the icon name.
+    pub const fn with_icon(mut self, icon: &'static str) -> Self {
+        self.icon = icon;
+        self
+    }
+}
+
 /// Information about a parameter group.
 ///
 /// Groups form a tree structure via parent_id references:
@@ -48,21 +110,30 @@ pub struct GroupInfo {
     pub name: &'static str,
     /// Parent group ID (ROOT_GROUP_ID for top-level groups).
     pub parent_id: GroupId,
+    /// GUI layout hints (order, collapsed, icon).
+    pub hints: GroupHints,
 }
 
 impl GroupInfo {
     /// Create a new group info.
     pub const fn new(id: GroupId, name: &'static str, parent_id: GroupId) -> Self {
-        Self { id, name, parent_id }
+        Self {
+            id,
+            name,
+            parent_id,
+            hints: GroupHints::new(),
+        }
     }
 
     /// Create the root group.
     pub const fn root() -> Self {
-        Self {
-            id: ROOT_GROUP_ID,
-            name: "",
-            parent_id: ROOT_GROUP_ID,
-        }
+        Self::new(ROOT_GROUP_ID, "", ROOT_GROUP_ID)
+    }
+
+    /// Set the GUI layout hints.
+    pub const fn with_hints(mut self, hints: GroupHints) -> Self {
+        self.hints = hints;
+        self
     }
 }
 
@@ -95,6 +166,34 @@ pub trait ParameterGroups {
         }
     }
 
+    /// Whether the group's bypass switch is on.
+    ///
+    /// Overridden by `#[derive(Parameters)]` for groups declared with
+    /// `#[nested(group = "...", controls)]`. See [`GroupControls`](crate::GroupControls).
+    fn is_group_bypassed(&self, _group_id: GroupId) -> bool {
+        false
+    }
+
+    /// Whether the group's solo switch is on.
+    fn is_group_soloed(&self, _group_id: GroupId) -> bool {
+        false
+    }
+
+    /// Whether any group is soloed.
+    fn any_group_soloed(&self) -> bool {
+        false
+    }
+
+    /// Whether the group should be heard.
+    ///
+    /// A group is inactive when it is bypassed, or when another group is
+    /// soloed and this one is not. Call this from `process()` to skip or mute
+    /// a band.
+    fn is_group_active(&self, group_id: GroupId) -> bool {
+        !self.is_group_bypassed(group_id)
+            && (self.is_group_soloed(group_id) || !self.any_group_soloed())
+    }
+
     /// Find group ID by name (linear search).
     fn find_group_by_name(&self, name: &str) -> Option<GroupId> {
         for i in 0..self.group_count() {
@@ -107,3 +206,62 @@ pub trait ParameterGroups {
         None
     }
 }
+
+/// JSON list of the groups (without the root) for the WebView, in
+/// [`ParameterGroups::group_info()`] order.
+///
+/// Each entry is `{ id, name, parentId, order, collapsed, icon }`, with
+/// `icon` `null` when not set.
+pub fn groups_json(groups: &dyn ParameterGroups) -> serde_json::Value {
+    let entries = (1..groups.group_count())
+        .filter_map(|index| groups.group_info(index))
+        .map(|info| {
+            serde_json::json!({
+                "id": info.id,
+                "name": info.name,
+                "parentId": info.parent_id,
+                "order": info.hints.order,
+                "collapsed": info.hints.collapsed,
+                "icon": (!info.hints.icon.is_empty()).then_some(info.hints.icon),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TwoGroups;
+
+    impl ParameterGroups for TwoGroups {
+        fn group_count(&self) -> usize {
+            3
+        }
+
+        fn group_info(&self, index: usize) -> Option<GroupInfo> {
+            match index {
+                0 => Some(GroupInfo::root()),
+                1 => Some(GroupInfo::new(1, "Filter", ROOT_GROUP_ID)),
+                2 => Some(
+                    GroupInfo::new(2, "Modulation", ROOT_GROUP_ID)
+                        .with_hints(GroupHints::new().with_order(-1).collapsed().with_icon("wave")),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn groups_json_lists_hints_without_root() {
+        let json = groups_json(&TwoGroups);
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "id": 1, "name": "Filter", "parentId": 0, "order": 0, "collapsed": false, "icon": null },
+                { "id": 2, "name": "Modulation", "parentId": 0, "order": -1, "collapsed": true, "icon": "wave" },
+            ])
+        );
+    }
+}
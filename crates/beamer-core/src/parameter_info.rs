@@ -90,7 +90,11 @@ pub enum ParameterUnit {
 }
 
 /// Flags controlling parameter behavior.
+///
+/// Non-exhaustive: start from [`ParameterFlags::default()`] and set fields,
+/// so new flags can be added without breaking plugins.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct ParameterFlags {
     /// Parameter can be automated by the host.
     pub can_automate: bool,
@@ -104,6 +108,16 @@ pub struct ParameterFlags {
     /// Parameter is hidden from the DAW's parameter list.
     /// Used for internal parameters like MIDI CC emulation.
     pub is_hidden: bool,
+    /// Parameter value is written to and restored from plugin state.
+    /// False for transient or `no_save` parameters (audition toggles,
+    /// UI-only state), which always start at their default.
+    pub is_persistent: bool,
+    /// Parameter may be changed by preset randomization.
+    /// False for bypass and for parameters declared with `randomize = "off"`.
+    pub is_randomizable: bool,
+    /// Parameter is GUI-only unless the user promotes it to the host.
+    /// See [`ParameterExposure`](crate::parameter_exposure::ParameterExposure).
+    pub is_internal: bool,
 }
 
 impl Default for ParameterFlags {
@@ -114,12 +128,19 @@ impl Default for ParameterFlags {
             is_bypass: false,
             is_list: false,
             is_hidden: false,
+            is_persistent: true,
+            is_randomizable: true,
+            is_internal: false,
         }
     }
 }
 
 /// Metadata describing a single parameter.
+///
+/// Non-exhaustive: build it with [`ParameterInfo::new()`] and the `with_*`
+/// methods, so new metadata can be added without breaking plugins.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ParameterInfo {
     /// Unique parameter identifier.
     pub id: ParameterId,
@@ -145,6 +166,9 @@ pub struct ParameterInfo {
     pub flags: ParameterFlags,
     /// Parameter group ID. ROOT_GROUP_ID (0) for ungrouped parameters.
     pub group_id: GroupId,
+    /// Normalized `(min, max)` range used by preset randomization.
+    /// Defaults to the full range `(0.0, 1.0)`.
+    pub randomize_range: (ParameterValue, ParameterValue),
 }
 
 impl ParameterInfo {
@@ -165,8 +189,12 @@ impl ParameterInfo {
                 is_bypass: false,
                 is_list: false,
                 is_hidden: false,
+                is_persistent: true,
+                is_randomizable: true,
+                is_internal: false,
             },
             group_id: ROOT_GROUP_ID,
+            randomize_range: (0.0, 1.0),
         }
     }
 
@@ -249,8 +277,12 @@ impl ParameterInfo {
                 is_bypass: true,
                 is_list: false,
                 is_hidden: false,
+                is_persistent: true,
+                is_randomizable: false,
+                is_internal: false,
             },
             group_id: ROOT_GROUP_ID,
+            randomize_range: (0.0, 1.0),
         }
     }
 
@@ -268,4 +300,10 @@ impl ParameterInfo {
         self.group_id = group_id;
         self
     }
+
+    /// Set the normalized range used by preset randomization.
+    pub const fn with_randomize_range(mut self, min: ParameterValue, max: ParameterValue) -> Self {
+        self.randomize_range = (min, max);
+        self
+    }
 }
@@ -0,0 +1,362 @@
+//! Parameter change log for session debugging.
+//!
+//! When a user reports that "the plugin changed its own settings", the
+//! question is who changed them: host automation, the GUI, a MIDI
+//! controller, a preset or a restored session. [`ParameterLog`] records
+//! every change with its source and a timestamp into a fixed-size ring
+//! buffer that the GUI can dump.
+//!
+//! # Enabling
+//!
+//! The log is opt-in: the descriptor creates one with
+//! [`ParameterLog::new()`] and returns it from
+//! [`Descriptor::parameter_log()`](crate::Descriptor::parameter_log). The
+//! wrappers record:
+//!
+//! | Source | Recorded when |
+//! |--------|---------------|
+//! | [`Automation`](ParameterChangeSource::Automation) | The host delivers a value with a process/render call |
+//! | [`Host`](ParameterChangeSource::Host) | The host sets a value outside processing (generic editor, undo, control surface) |
+//! | [`Gui`](ParameterChangeSource::Gui) | The WebView GUI sets a value |
+//! | [`MidiCc`](ParameterChangeSource::MidiCc) | A VST3 MIDI CC emulation parameter changes |
+//! | [`Preset`](ParameterChangeSource::Preset) | A factory preset is applied |
+//! | [`State`](ParameterChangeSource::State) | The host restores a state |
+//!
+//! Plugins that map MIDI themselves (MIDI learn) can record their own
+//! changes with [`ParameterLog::record()`] on their clone of the log. On VST3, CCs mapped through
+//! `IMidiMapping` reach the plugin as parameter changes and are logged as
+//! automation.
+//!
+//! # Real-Time Safety
+//!
+//! [`record()`](ParameterLog::record) is lock-free and allocation-free, so
+//! it is safe on the audio thread. The oldest entries are overwritten once
+//! the buffer is full. Reading ([`entries()`](ParameterLog::entries))
+//! allocates and belongs on the main thread.
+//!
+//! # GUI Access
+//!
+//! The WebView runtime exposes the log as
+//! `__BEAMER__.getParameterLog(limit)` and `__BEAMER__.clearParameterLog()`.
+
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::parameter_store::ParameterStore;
+use crate::types::ParameterId;
+
+/// Built-in WebView invoke method that returns the most recent entries.
+pub const PARAMETER_LOG_INVOKE: &str = "_beamer/parameterLog";
+
+/// Built-in WebView invoke method that clears the log.
+pub const CLEAR_PARAMETER_LOG_INVOKE: &str = "_beamer/clearParameterLog";
+
+/// Who changed a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ParameterChangeSource {
+    /// Host automation, delivered with audio processing.
+    Automation = 0,
+    /// Host edit outside processing.
+    Host = 1,
+    /// The plugin GUI.
+    Gui = 2,
+    /// A MIDI controller.
+    MidiCc = 3,
+    /// A factory preset.
+    Preset = 4,
+    /// A restored state.
+    State = 5,
+}
+
+impl ParameterChangeSource {
+    /// Short lowercase name, as used in the JSON dump.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Automation => "automation",
+            Self::Host => "host",
+            Self::Gui => "gui",
+            Self::MidiCc => "midiCc",
+            Self::Preset => "preset",
+            Self::State => "state",
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Automation,
+            1 => Self::Host,
+            2 => Self::Gui,
+            3 => Self::MidiCc,
+            4 => Self::Preset,
+            5 => Self::State,
+            _ => return None,
+        })
+    }
+}
+
+/// One recorded change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterLogEntry {
+    /// When the change was recorded.
+    pub time: SystemTime,
+    /// Who made the change.
+    pub source: ParameterChangeSource,
+    /// Parameter ID. For [`ParameterChangeSource::Preset`] the preset
+    /// index; unused (0) for [`ParameterChangeSource::State`].
+    pub id: ParameterId,
+    /// New normalized value. `NaN` for preset and state entries.
+    pub value: f64,
+}
+
+/// Ring buffer slot, written under a per-slot sequence number.
+#[derive(Debug, Default)]
+struct Slot {
+    /// `2 * (n + 1)` once entry `n` is complete, odd while it is written.
+    sequence: AtomicU64,
+    /// Microseconds since the log was created.
+    micros: AtomicU64,
+    /// Parameter ID in the low 32 bits, source in the next 8.
+    id_source: AtomicU64,
+    value: AtomicU64,
+}
+
+#[derive(Debug)]
+struct LogInner {
+    slots: Box<[Slot]>,
+    /// Number of entries ever recorded.
+    head: AtomicU64,
+    /// Entries before this number were cleared.
+    cleared: AtomicU64,
+    start: Instant,
+    start_time: SystemTime,
+}
+
+/// Lock-free log of parameter changes.
+///
+/// Cloning is cheap (reference counted), so the plugin, the wrapper and
+/// its WebView IPC context share one log. The default log is disabled and
+/// records nothing.
+#[derive(Clone, Debug, Default)]
+pub struct ParameterLog {
+    inner: Option<Arc<LogInner>>,
+}
+
+impl ParameterLog {
+    /// Create a log holding the last `capacity` changes.
+    ///
+    /// Allocates; create it with the descriptor. A capacity of 0 gives a
+    /// disabled log.
+    pub fn new(capacity: usize) -> Self {
+        if capacity == 0 {
+            return Self::default();
+        }
+        Self {
+            inner: Some(Arc::new(LogInner {
+                slots: (0..capacity).map(|_| Slot::default()).collect(),
+                head: AtomicU64::new(0),
+                cleared: AtomicU64::new(0),
+                start: Instant::now(),
+                start_time: SystemTime::now(),
+            })),
+        }
+    }
+
+    /// Returns true if changes are recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Record a change of parameter `id` to the normalized `value`.
+    ///
+    /// Real-time safe.
+    #[inline]
+    pub fn record(&self, id: ParameterId, value: f64, source: ParameterChangeSource) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let n = inner.head.fetch_add(1, Ordering::Relaxed);
+        let slot = &inner.slots[(n % inner.slots.len() as u64) as usize];
+
+        slot.sequence.store(2 * n + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        slot.micros
+            .store(inner.start.elapsed().as_micros() as u64, Ordering::Relaxed);
+        slot.id_source
+            .store(id as u64 | (source as u64) << 32, Ordering::Relaxed);
+        slot.value.store(value.to_bits(), Ordering::Relaxed);
+        slot.sequence.store(2 * (n + 1), Ordering::Release);
+    }
+
+    /// Record that factory preset `index` was applied.
+    #[inline]
+    pub fn record_preset(&self, index: usize) {
+        self.record(index as ParameterId, f64::NAN, ParameterChangeSource::Preset);
+    }
+
+    /// Record that the host restored a state.
+    #[inline]
+    pub fn record_state(&self) {
+        self.record(0, f64::NAN, ParameterChangeSource::State);
+    }
+
+    /// Forget all recorded entries.
+    pub fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            inner
+                .cleared
+                .store(inner.head.load(Ordering::Acquire), Ordering::Release);
+        }
+    }
+
+    /// The most recent entries, oldest first, at most `limit` of them.
+    ///
+    /// Entries being overwritten while this runs are skipped.
+    pub fn entries(&self, limit: usize) -> Vec<ParameterLogEntry> {
+        let Some(inner) = &self.inner else {
+            return Vec::new();
+        };
+        let head = inner.head.load(Ordering::Acquire);
+        let first = head
+            .saturating_sub(inner.slots.len() as u64)
+            .max(inner.cleared.load(Ordering::Acquire))
+            .max(head.saturating_sub(limit as u64));
+
+        (first..head)
+            .filter_map(|n| {
+                let slot = &inner.slots[(n % inner.slots.len() as u64) as usize];
+                let expected = 2 * (n + 1);
+                if slot.sequence.load(Ordering::Acquire) != expected {
+                    return None;
+                }
+                let micros = slot.micros.load(Ordering::Relaxed);
+                let id_source = slot.id_source.load(Ordering::Relaxed);
+                let value = f64::from_bits(slot.value.load(Ordering::Relaxed));
+                fence(Ordering::Acquire);
+                if slot.sequence.load(Ordering::Relaxed) != expected {
+                    return None;
+                }
+                Some(ParameterLogEntry {
+                    time: inner.start_time + std::time::Duration::from_micros(micros),
+                    source: ParameterChangeSource::from_u8((id_source >> 32) as u8)?,
+                    id: id_source as u32,
+                    value,
+                })
+            })
+            .collect()
+    }
+}
+
+/// JSON dump of the most recent entries, with parameter names and display
+/// text from `parameters`.
+pub fn parameter_log_json(
+    log: &ParameterLog,
+    parameters: &dyn ParameterStore,
+    limit: usize,
+) -> serde_json::Value {
+    let entries = log
+        .entries(limit)
+        .into_iter()
+        .map(|entry| {
+            let time = entry
+                .time
+                .duration_since(UNIX_EPOCH)
+                .map_or(0.0, |d| d.as_secs_f64() * 1000.0);
+            let mut json = serde_json::json!({
+                "time": time,
+                "source": entry.source.as_str(),
+                "id": entry.id,
+            });
+            if matches!(
+                entry.source,
+                ParameterChangeSource::Preset | ParameterChangeSource::State
+            ) {
+                return json;
+            }
+            json["value"] = serde_json::Value::from(entry.value);
+            if let Some(info) = parameters.info_by_id(entry.id) {
+                json["stringId"] = serde_json::Value::from(info.string_id);
+                json["name"] = serde_json::Value::from(info.name);
+                json["text"] =
+                    serde_json::Value::from(parameters.normalized_to_string(entry.id, entry.value));
+            }
+            json
+        })
+        .collect();
+    serde_json::Value::Array(entries)
+}
+
+/// Handle the built-in parameter log WebView calls.
+///
+/// Returns `None` if `method` is not a parameter log method.
+/// [`PARAMETER_LOG_INVOKE`] takes an optional entry limit.
+pub fn parameter_log_invoke(
+    log: &ParameterLog,
+    parameters: &dyn ParameterStore,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    match method {
+        PARAMETER_LOG_INVOKE => {
+            let limit = args
+                .first()
+                .and_then(|v| v.as_u64())
+                .map_or(usize::MAX, |limit| limit as usize);
+            Some(parameter_log_json(log, parameters, limit))
+        }
+        CLEAR_PARAMETER_LOG_INVOKE => {
+            log.clear();
+            Some(serde_json::Value::Null)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameter_store::NoParameters;
+
+    #[test]
+    fn test_ring_keeps_most_recent() {
+        let log = ParameterLog::new(4);
+        for i in 0..6 {
+            log.record(i, i as f64 / 10.0, ParameterChangeSource::Gui);
+        }
+        log.record_preset(3);
+
+        let entries = log.entries(usize::MAX);
+        let ids: Vec<_> = entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [3, 4, 5, 3]);
+        assert_eq!(entries[2].value, 0.5);
+        assert_eq!(entries[3].source, ParameterChangeSource::Preset);
+        assert!(entries.windows(2).all(|w| w[0].time <= w[1].time));
+
+        assert_eq!(log.entries(2).len(), 2);
+        log.clear();
+        assert!(log.entries(usize::MAX).is_empty());
+        log.record(7, 1.0, ParameterChangeSource::Automation);
+        assert_eq!(log.entries(usize::MAX).len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_and_invoke() {
+        let disabled = ParameterLog::default();
+        disabled.record(1, 0.5, ParameterChangeSource::Host);
+        assert!(disabled.entries(10).is_empty());
+
+        let log = ParameterLog::new(8);
+        log.record_state();
+        log.record(9, 0.25, ParameterChangeSource::MidiCc);
+        let json = parameter_log_invoke(&log, &NoParameters, PARAMETER_LOG_INVOKE, &[]).unwrap();
+        assert_eq!(json[0]["source"], "state");
+        assert_eq!(json[1]["source"], "midiCc");
+        assert_eq!(json[1]["value"], 0.25);
+        assert!(json[1].get("name").is_none(), "unknown parameter");
+
+        parameter_log_invoke(&log, &NoParameters, CLEAR_PARAMETER_LOG_INVOKE, &[]);
+        assert!(log.entries(10).is_empty());
+        assert!(parameter_log_invoke(&log, &NoParameters, "other", &[]).is_none());
+    }
+}
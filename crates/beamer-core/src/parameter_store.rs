@@ -217,6 +217,10 @@ pub fn params_to_init_json(store: &dyn ParameterStore) -> String {
                 format: store.formatter_kind(info.id),
                 units: info.units,
                 steps: info.step_count,
+                randomize: (info.flags.is_randomizable && !info.flags.is_bypass)
+                    .then_some([info.randomize_range.0, info.randomize_range.1]),
+                internal: info.flags.is_internal,
+                group_id: info.group_id,
             })
         })
         .collect();
@@ -238,4 +242,10 @@ struct ParamInitEntry {
     format: &'static str,
     units: &'static str,
     steps: i32,
+    /// Normalized randomization range, or `null` if excluded.
+    randomize: Option<[f64; 2]>,
+    /// GUI-only unless promoted to the host.
+    internal: bool,
+    /// Parameter group (0 for ungrouped).
+    group_id: i32,
 }
@@ -32,17 +32,60 @@
 //! - [`IntParameter`] - Discrete integer values
 //! - [`BoolParameter`] - Toggle/boolean values
 //! - [`EnumParameter`] - Discrete enum choices (use with `#[derive(EnumParameter)]`)
+//! - [`TimeInSamples`] - Times saved in seconds and read in samples at the current rate
 
 use std::ops::RangeInclusive;
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
 
 use crate::parameter_format::Formatter;
-use crate::parameter_groups::{GroupId, GroupInfo, ParameterGroups, ROOT_GROUP_ID};
+use crate::parameter_groups::{GroupHints, GroupId, GroupInfo, ParameterGroups, ROOT_GROUP_ID};
 use crate::parameter_info::{ParameterFlags, ParameterInfo, ParameterUnit};
 use crate::parameter_range::{LinearMapper, LogMapper, LogOffsetMapper, PowerMapper, RangeMapper};
+#[cfg(feature = "smoothing")]
 use crate::smoothing::{Smoother, SmoothingStyle};
 use crate::types::{ParameterId, ParameterValue};
 
+#[cfg(not(feature = "smoothing"))]
+use no_smoothing::Smoother;
+
+/// Stand-in without the `smoothing` feature: parameters never have a
+/// smoother, so the smoothed accessors return the raw value.
+#[cfg(not(feature = "smoothing"))]
+mod no_smoothing {
+    #[derive(Clone)]
+    pub enum Smoother {}
+
+    impl Smoother {
+        pub fn set_sample_rate(&mut self, _: f64) {
+            match *self {}
+        }
+        pub fn set_target(&mut self, _: f64) {
+            match *self {}
+        }
+        pub fn current(&self) -> f64 {
+            match *self {}
+        }
+        pub fn tick(&mut self) -> f64 {
+            match *self {}
+        }
+        pub fn skip(&mut self, _: usize) {
+            match *self {}
+        }
+        pub fn fill(&mut self, _: &mut [f64]) {
+            match *self {}
+        }
+        pub fn fill_f32(&mut self, _: &mut [f32]) {
+            match *self {}
+        }
+        pub fn is_smoothing(&self) -> bool {
+            match *self {}
+        }
+        pub fn reset(&mut self, _: f64) {
+            match *self {}
+        }
+    }
+}
+
 // =============================================================================
 // ParameterRef Trait - Type-erased parameter access
 // =============================================================================
@@ -122,6 +165,32 @@ pub trait ParameterRef: Send + Sync {
     /// This is used by the `#[derive(Parameters)]` macro to generate the
     /// `ParameterStore::info()` implementation.
     fn info(&self) -> &ParameterInfo;
+
+    /// Whether the parameter is currently locked against randomization.
+    ///
+    /// Lock state is runtime-only (not saved). Types without lock storage
+    /// always report `false`.
+    fn is_randomize_locked(&self) -> bool {
+        false
+    }
+
+    /// Lock or unlock the parameter against randomization.
+    ///
+    /// This is lock-free and safe to call from any thread.
+    fn set_randomize_locked(&self, _locked: bool) {}
+
+    /// Whether [`Parameters::randomize()`] may change this parameter.
+    ///
+    /// False for bypass, read-only, hidden, `randomize = "off"` and
+    /// runtime-locked parameters.
+    fn can_randomize(&self) -> bool {
+        let flags = self.flags();
+        flags.is_randomizable
+            && !flags.is_bypass
+            && !flags.is_readonly
+            && !flags.is_hidden
+            && !self.is_randomize_locked()
+    }
 }
 
 // =============================================================================
@@ -212,6 +281,14 @@ pub trait Parameters: Send + Sync + ParameterGroups {
         None
     }
 
+    /// GUI layout hints of a nested group by index.
+    ///
+    /// Generated from `#[nested(group = "...", order = ..., collapsed, icon = "...")]`.
+    /// Default returns no hints.
+    fn nested_group_hints(&self, _index: usize) -> GroupHints {
+        GroupHints::new()
+    }
+
     /// Recursively assign group IDs to all nested groups.
     ///
     /// This method traverses the nested group hierarchy and assigns
@@ -280,7 +357,9 @@ pub trait Parameters: Send + Sync + ParameterGroups {
                 let group_id = next_id;
                 next_id += 1;
 
-                groups.push(GroupInfo::new(group_id, name, parent_id));
+                groups.push(
+                    GroupInfo::new(group_id, name, parent_id).with_hints(self.nested_group_hints(i)),
+                );
 
                 // Recurse into nested groups
                 next_id = nested.collect_groups(groups, next_id, group_id);
@@ -343,6 +422,17 @@ pub trait Parameters: Send + Sync + ParameterGroups {
         data
     }
 
+    /// Stable content hash of the parameter state.
+    ///
+    /// Hashes the bytes [`save_state()`](Self::save_state) writes, so two
+    /// parameter sets hash equal exactly when they would save equal. Use it
+    /// for "modified since the last preset" indicators and dirty tracking;
+    /// see [`state_hash`](crate::state_hash) for hashing custom state.
+    /// Allocates, so call it on the main thread.
+    fn state_hash(&self) -> u64 {
+        crate::state_hash::hash_state(&self.save_state())
+    }
+
     /// Load a single parameter by its path.
     ///
     /// This is called during state restoration to route each (path, value) pair
@@ -414,6 +504,40 @@ pub trait Parameters: Send + Sync + ParameterGroups {
         Ok(())
     }
 
+    // =========================================================================
+    // Randomization
+    // =========================================================================
+
+    /// Randomize all parameters that allow it, including nested groups.
+    ///
+    /// `random` must return uniformly distributed values in `0.0..1.0`; each
+    /// value is mapped into the parameter's `randomize_range`. Parameters for
+    /// which [`ParameterRef::can_randomize()`] is false are left untouched.
+    ///
+    /// Values are written like [`ParameterRef::set_normalized()`]. When
+    /// randomizing from a GUI, report the changes to the host as edits.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut seed = 0x2545_f491_u32;
+    /// parameters.randomize(&mut || {
+    ///     seed ^= seed << 13;
+    ///     seed ^= seed >> 17;
+    ///     seed ^= seed << 5;
+    ///     seed as f64 / u32::MAX as f64
+    /// });
+    /// ```
+    fn randomize(&self, random: &mut dyn FnMut() -> f64) {
+        for parameter in self.iter() {
+            if !parameter.can_randomize() {
+                continue;
+            }
+            let (min, max) = parameter.info().randomize_range;
+            parameter.set_normalized(min + (max - min) * random().clamp(0.0, 1.0));
+        }
+    }
+
     // =========================================================================
     // Smoothing Support
     // =========================================================================
@@ -448,6 +572,17 @@ pub trait Parameters: Send + Sync + ParameterGroups {
         // Default no-op. The #[derive(Parameters)] macro generates an override
         // that calls reset_smoothing on each parameter field.
     }
+
+    /// Advance the automatic smoothers over a `process()` block of
+    /// `samples`.
+    ///
+    /// Called by the wrappers before every `process()` call, so parameters
+    /// declared with `smooth_ms` need no smoothing code in the plugin. See
+    /// [`FloatParameter::smoothed_values()`].
+    fn advance_smoothing(&mut self, _samples: usize) {
+        // Default no-op. The #[derive(Parameters)] macro generates an override
+        // that calls advance_smoothing on each parameter field.
+    }
 }
 
 // =============================================================================
@@ -486,12 +621,17 @@ pub struct FloatParameter {
     info: ParameterInfo,
     /// Atomic storage for normalized value (0.0-1.0)
     value: AtomicU64,
+    /// Runtime lock excluding this parameter from randomization
+    randomize_locked: AtomicBool,
     /// Range mapper for normalized ↔ plain value conversion
     range: Box<dyn RangeMapper>,
     /// Formatter for display string conversion
     formatter: Formatter,
     /// Optional smoother for avoiding zipper noise
     smoother: Option<Smoother>,
+    /// Smoother state at the start of the current block, for smoothers the
+    /// wrapper advances (`None` for manual smoothing)
+    block_start: Option<Smoother>,
     /// Whether this parameter stores dB values (for as_linear() optimization)
     is_db: bool,
     /// Optional step size for discrete stepping. None = continuous.
@@ -525,11 +665,14 @@ impl FloatParameter {
                 step_count: 0,
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicU64::new(default_normalized.to_bits()),
+            randomize_locked: AtomicBool::new(false),
             range: Box::new(mapper),
             formatter: Formatter::Float { precision: 2 },
             smoother: None,
+            block_start: None,
             is_db: false,
             step_size: None,
         }
@@ -584,11 +727,14 @@ impl FloatParameter {
                 step_count: 0,
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicU64::new(default_normalized.to_bits()),
+            randomize_locked: AtomicBool::new(false),
             range: Box::new(mapper),
             formatter,
             smoother: None,
+            block_start: None,
             is_db: true,
             step_size: None,
         }
@@ -633,11 +779,14 @@ impl FloatParameter {
                 step_count: 0,
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicU64::new(default_normalized.to_bits()),
+            randomize_locked: AtomicBool::new(false),
             range: Box::new(mapper),
             formatter,
             smoother: None,
+            block_start: None,
             is_db: true,
             step_size: None,
         }
@@ -685,11 +834,14 @@ impl FloatParameter {
                 step_count: 0,
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicU64::new(default_normalized.to_bits()),
+            randomize_locked: AtomicBool::new(false),
             range: Box::new(mapper),
             formatter,
             smoother: None,
+            block_start: None,
             is_db: true,
             step_size: None,
         }
@@ -731,11 +883,14 @@ impl FloatParameter {
                 step_count: 0,
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicU64::new(default_normalized.to_bits()),
+            randomize_locked: AtomicBool::new(false),
             range: Box::new(mapper),
             formatter,
             smoother: None,
+            block_start: None,
             is_db: false,
             step_size: None,
         }
@@ -893,6 +1048,50 @@ impl FloatParameter {
         self
     }
 
+    /// Exclude this parameter from saved state.
+    ///
+    /// The parameter stays automatable but is not written by `save_state()`
+    /// and ignored by `load_state()`.
+    pub fn no_save(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self
+    }
+
+    /// Make this a transient parameter: not saved and not automatable.
+    ///
+    /// Use for momentary controls such as "audition" toggles or UI-only
+    /// state that should never be recorded by the host.
+    pub fn transient(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self.info.flags.can_automate = false;
+        self
+    }
+
+    /// Exclude this parameter from preset randomization.
+    pub fn no_randomize(mut self) -> Self {
+        self.info.flags.is_randomizable = false;
+        self
+    }
+
+    /// Keep this parameter GUI-only until the user promotes it to the host.
+    ///
+    /// For large modular plugins whose full parameter set exceeds what hosts
+    /// handle well. Internal parameters are still saved with the state.
+    pub fn internal(mut self) -> Self {
+        self.info.flags.is_internal = true;
+        self
+    }
+
+    /// Limit preset randomization to a sub-range, in plain units.
+    ///
+    /// Use to keep "Random" musical, e.g. output gain within -12..=0 dB.
+    pub fn with_randomize_range(mut self, range: RangeInclusive<f64>) -> Self {
+        let a = self.plain_to_normalized(*range.start());
+        let b = self.plain_to_normalized(*range.end());
+        self.info.randomize_range = (a.min(b), a.max(b));
+        self
+    }
+
     /// Set the unit type hint for AU hosts.
     ///
     /// This is typically set automatically by the constructor (e.g., `db()` sets `Decibels`),
@@ -1096,6 +1295,7 @@ impl FloatParameter {
     /// let gain = FloatParameter::db("Gain", 0.0, -60.0..=12.0)
     ///     .with_smoother(SmoothingStyle::Exponential(5.0));  // 5ms
     /// ```
+    #[cfg(feature = "smoothing")]
     pub fn with_smoother(mut self, style: SmoothingStyle) -> Self {
         let current = self.get();
         let mut smoother = Smoother::new(style);
@@ -1104,6 +1304,37 @@ impl FloatParameter {
         self
     }
 
+    /// Add smoothing that the wrapper advances automatically.
+    ///
+    /// Before every `process()` call the wrapper moves the smoother towards
+    /// the current value over the block, so `process()` only reads the ramp
+    /// with [`smoothed_values()`](Self::smoothed_values). Set by
+    /// `#[parameter(smooth_ms = ...)]`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let level = FloatParameter::new("Level", 1.0, 0.0..=2.0)
+    ///     .with_auto_smoother(SmoothingStyle::Exponential(20.0));
+    ///
+    /// // In process()
+    /// let mut level = self.parameters.level.smoothed_values();
+    /// for sample in output {
+    ///     *sample *= level.next_f32();
+    /// }
+    /// ```
+    #[cfg(feature = "smoothing")]
+    pub fn with_auto_smoother(mut self, style: SmoothingStyle) -> Self {
+        self = self.with_smoother(style);
+        self.block_start = self.smoother.clone();
+        self
+    }
+
+    /// Whether the wrapper advances this parameter's smoother.
+    pub fn is_auto_smoothed(&self) -> bool {
+        self.block_start.is_some()
+    }
+
     /// Set sample rate for smoothing.
     ///
     /// Call this from `Processor::setup()`. If using oversampling,
@@ -1113,6 +1344,38 @@ impl FloatParameter {
         if let Some(ref mut smoother) = self.smoother {
             smoother.set_sample_rate(sample_rate);
             smoother.set_target(current_value);
+            if let Some(ref mut block_start) = self.block_start {
+                block_start.clone_from(smoother);
+            }
+        }
+    }
+
+    /// Per-sample smoothed values for the current `process()` block.
+    ///
+    /// For a parameter with an automatic smoother (`smooth_ms`), the
+    /// iterator yields the ramp the wrapper computed for this block, one
+    /// value per sample. Otherwise it yields [`smoothed()`](Self::smoothed)
+    /// for every sample. The iterator never ends; take as many values as
+    /// the block has samples.
+    // The stand-in smoother without the `smoothing` feature is uninhabited
+    #[cfg_attr(not(feature = "smoothing"), allow(unreachable_code))]
+    pub fn smoothed_values(&self) -> SmoothedValues {
+        match &self.block_start {
+            Some(block_start) => SmoothedValues { smoother: Some(block_start.clone()), value: 0.0 },
+            None => SmoothedValues { smoother: None, value: self.smoothed() },
+        }
+    }
+
+    /// Move the automatic smoother over a block of `samples`.
+    ///
+    /// Called by the wrappers through [`Parameters::advance_smoothing()`].
+    /// Does nothing for parameters without an automatic smoother.
+    pub fn advance_smoothing(&mut self, samples: usize) {
+        let current_value = self.get();
+        if let (Some(smoother), Some(block_start)) = (&mut self.smoother, &mut self.block_start) {
+            smoother.set_target(current_value);
+            block_start.clone_from(smoother);
+            smoother.skip(samples);
         }
     }
 
@@ -1211,10 +1474,49 @@ impl FloatParameter {
         let current_value = self.get();
         if let Some(ref mut smoother) = self.smoother {
             smoother.reset(current_value);
+            if let Some(ref mut block_start) = self.block_start {
+                block_start.clone_from(smoother);
+            }
+        }
+    }
+}
+
+/// Per-sample smoothed parameter values, from
+/// [`FloatParameter::smoothed_values()`].
+pub struct SmoothedValues {
+    smoother: Option<Smoother>,
+    value: f64,
+}
+
+impl SmoothedValues {
+    /// The next value as f32.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        self.tick() as f32
+    }
+
+    #[inline]
+    fn tick(&mut self) -> f64 {
+        match &mut self.smoother {
+            Some(smoother) => smoother.tick(),
+            None => self.value,
         }
     }
 }
 
+impl Iterator for SmoothedValues {
+    type Item = f64;
+
+    #[inline]
+    fn next(&mut self) -> Option<f64> {
+        Some(self.tick())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
 impl ParameterRef for FloatParameter {
     fn id(&self) -> ParameterId {
         self.info.id
@@ -1286,6 +1588,14 @@ impl ParameterRef for FloatParameter {
     fn info(&self) -> &ParameterInfo {
         &self.info
     }
+
+    fn is_randomize_locked(&self) -> bool {
+        self.randomize_locked.load(Ordering::Relaxed)
+    }
+
+    fn set_randomize_locked(&self, locked: bool) {
+        self.randomize_locked.store(locked, Ordering::Relaxed);
+    }
 }
 
 // FloatParameter is automatically Send + Sync because:
@@ -1294,6 +1604,268 @@ impl ParameterRef for FloatParameter {
 // - All other fields (&'static str, f64, Formatter, ParameterFlags) are Send + Sync
 // No unsafe impl needed - the compiler verifies this automatically.
 
+// =============================================================================
+// TimeInSamples - Time saved in seconds, read in samples
+// =============================================================================
+
+/// Time parameter that is saved in seconds and read in samples.
+///
+/// A value kept in samples (a recorded loop length, a lookahead) means
+/// something else once the project is reopened at another sample rate.
+/// `TimeInSamples` stores seconds, so hosts, presets and saved state see a
+/// rate-independent time, and converts to samples with the processing rate
+/// the wrapper sets after every `prepare()` (see
+/// [`Parameters::set_sample_rate()`]). Read the length in `prepare()` or
+/// `process()`:
+///
+/// ```ignore
+/// #[derive(Parameters)]
+/// pub struct LooperParameters {
+///     #[parameter(id = "loop_length", name = "Loop Length", default = 2.0, range = 0.0..=60.0)]
+///     pub loop_length: TimeInSamples,
+/// }
+///
+/// // After recording: saved as seconds
+/// self.parameters.loop_length.set_samples(recorded_samples);
+///
+/// // After reopening at another rate: converted back
+/// let loop_samples = self.parameters.loop_length.samples();
+/// ```
+///
+/// Before the first `set_sample_rate()` the rate is unknown: [`samples()`](Self::samples)
+/// returns 0 and [`set_samples()`](Self::set_samples) does nothing.
+pub struct TimeInSamples {
+    /// The time in seconds, as seen by the host and saved with the state
+    seconds: FloatParameter,
+    /// Processing rate from the last `set_sample_rate()` (0 = unknown)
+    sample_rate: f64,
+}
+
+impl TimeInSamples {
+    /// Create a time parameter, with the default and range in seconds.
+    ///
+    /// The parameter ID defaults to 0 and should be set via [`with_id`](Self::with_id)
+    /// or the `#[derive(Parameters)]` macro.
+    pub fn new(name: &'static str, default_s: f64, range_s: RangeInclusive<f64>) -> Self {
+        Self {
+            seconds: FloatParameter::seconds(name, default_s, range_s),
+            sample_rate: 0.0,
+        }
+    }
+
+    /// Set the parameter ID.
+    pub fn with_id(mut self, id: ParameterId) -> Self {
+        self.seconds = self.seconds.with_id(id);
+        self
+    }
+
+    /// Set the string identifier from `#[parameter(id = "...")]`.
+    pub fn with_string_id(mut self, string_id: &'static str) -> Self {
+        self.seconds = self.seconds.with_string_id(string_id);
+        self
+    }
+
+    /// Set the short name for constrained UIs.
+    pub fn with_short_name(mut self, short: &'static str) -> Self {
+        self.seconds = self.seconds.with_short_name(short);
+        self
+    }
+
+    /// Set the group ID (parameter group) for this parameter.
+    pub fn with_group(mut self, group_id: GroupId) -> Self {
+        self.seconds = self.seconds.with_group(group_id);
+        self
+    }
+
+    /// Set the group ID in-place (for runtime assignment by parent structs).
+    pub fn set_group_id(&mut self, group_id: GroupId) {
+        self.seconds.set_group_id(group_id);
+    }
+
+    /// Make the parameter read-only (display only, not automatable).
+    pub fn readonly(mut self) -> Self {
+        self.seconds = self.seconds.readonly();
+        self
+    }
+
+    /// Disable automation for this parameter.
+    pub fn non_automatable(mut self) -> Self {
+        self.seconds = self.seconds.non_automatable();
+        self
+    }
+
+    /// Exclude this parameter from saved state.
+    pub fn no_save(mut self) -> Self {
+        self.seconds = self.seconds.no_save();
+        self
+    }
+
+    /// Make this a transient parameter: not saved and not automatable.
+    pub fn transient(mut self) -> Self {
+        self.seconds = self.seconds.transient();
+        self
+    }
+
+    /// Exclude this parameter from preset randomization.
+    pub fn no_randomize(mut self) -> Self {
+        self.seconds = self.seconds.no_randomize();
+        self
+    }
+
+    /// Keep this parameter GUI-only until the user promotes it to the host.
+    pub fn internal(mut self) -> Self {
+        self.seconds = self.seconds.internal();
+        self
+    }
+
+    /// Limit preset randomization to a sub-range, in seconds.
+    pub fn with_randomize_range(mut self, range: RangeInclusive<f64>) -> Self {
+        self.seconds = self.seconds.with_randomize_range(range);
+        self
+    }
+
+    /// Get the parameter info.
+    pub fn info(&self) -> &ParameterInfo {
+        self.seconds.info()
+    }
+
+    // === Value access ===
+
+    /// The time in seconds.
+    #[inline]
+    pub fn get(&self) -> f64 {
+        self.seconds.get()
+    }
+
+    /// Set the time in seconds.
+    #[inline]
+    pub fn set(&self, seconds: f64) {
+        self.seconds.set(seconds);
+    }
+
+    /// The time in samples at the processing rate, rounded to the nearest
+    /// sample.
+    #[inline]
+    pub fn samples(&self) -> usize {
+        (self.get() * self.sample_rate).round().max(0.0) as usize
+    }
+
+    /// Set the time in samples at the processing rate.
+    ///
+    /// Stored as seconds, clamped to the parameter's range.
+    #[inline]
+    pub fn set_samples(&self, samples: usize) {
+        if self.sample_rate > 0.0 {
+            self.set(samples as f64 / self.sample_rate);
+        }
+    }
+
+    /// The processing rate used for the conversion (0 before the first
+    /// `set_sample_rate()`).
+    #[inline]
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Set the rate that [`samples()`](Self::samples) converts with.
+    ///
+    /// Called by the wrappers after `prepare()` through
+    /// [`Parameters::set_sample_rate()`], with the processing rate.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// No-op for compatibility with the `#[derive(Parameters)]` macro.
+    #[inline]
+    pub fn reset_smoothing(&mut self) {
+        // No-op: TimeInSamples doesn't support smoothing
+    }
+
+    /// No-op for compatibility with the `#[derive(Parameters)]` macro.
+    #[inline]
+    pub fn advance_smoothing(&mut self, _samples: usize) {
+        // No-op: TimeInSamples doesn't support smoothing
+    }
+}
+
+impl ParameterRef for TimeInSamples {
+    fn id(&self) -> ParameterId {
+        self.seconds.id()
+    }
+
+    fn name(&self) -> &'static str {
+        self.seconds.name()
+    }
+
+    fn short_name(&self) -> &'static str {
+        self.seconds.short_name()
+    }
+
+    fn units(&self) -> &'static str {
+        self.seconds.units()
+    }
+
+    fn flags(&self) -> &ParameterFlags {
+        self.seconds.flags()
+    }
+
+    fn default_normalized(&self) -> ParameterValue {
+        self.seconds.default_normalized()
+    }
+
+    fn step_count(&self) -> i32 {
+        ParameterRef::step_count(&self.seconds)
+    }
+
+    fn get_normalized(&self) -> ParameterValue {
+        self.seconds.get_normalized()
+    }
+
+    fn set_normalized(&self, value: ParameterValue) {
+        self.seconds.set_normalized(value);
+    }
+
+    fn get_plain(&self) -> ParameterValue {
+        self.seconds.get_plain()
+    }
+
+    fn set_plain(&self, value: ParameterValue) {
+        self.seconds.set_plain(value);
+    }
+
+    fn display_normalized(&self, normalized: ParameterValue) -> String {
+        self.seconds.display_normalized(normalized)
+    }
+
+    fn parse(&self, s: &str) -> Option<ParameterValue> {
+        self.seconds.parse(s)
+    }
+
+    fn normalized_to_plain(&self, normalized: ParameterValue) -> ParameterValue {
+        self.seconds.normalized_to_plain(normalized)
+    }
+
+    fn plain_to_normalized(&self, plain: ParameterValue) -> ParameterValue {
+        self.seconds.plain_to_normalized(plain)
+    }
+
+    fn formatter_kind(&self) -> &'static str {
+        ParameterRef::formatter_kind(&self.seconds)
+    }
+
+    fn info(&self) -> &ParameterInfo {
+        self.seconds.info()
+    }
+
+    fn is_randomize_locked(&self) -> bool {
+        self.seconds.is_randomize_locked()
+    }
+
+    fn set_randomize_locked(&self, locked: bool) {
+        self.seconds.set_randomize_locked(locked);
+    }
+}
+
 // =============================================================================
 // IntParameter - Integer parameter with atomic storage
 // =============================================================================
@@ -1316,6 +1888,8 @@ pub struct IntParameter {
     info: ParameterInfo,
     /// Atomic storage for the integer value
     value: AtomicI64,
+    /// Runtime lock excluding this parameter from randomization
+    randomize_locked: AtomicBool,
     /// Minimum value
     min: i64,
     /// Maximum value
@@ -1366,8 +1940,10 @@ impl IntParameter {
                 step_count,
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicI64::new(default.clamp(min, max)),
+            randomize_locked: AtomicBool::new(false),
             min,
             max,
             formatter: Formatter::Float { precision: 0 },
@@ -1444,6 +2020,50 @@ impl IntParameter {
         self
     }
 
+    /// Exclude this parameter from saved state.
+    ///
+    /// The parameter stays automatable but is not written by `save_state()`
+    /// and ignored by `load_state()`.
+    pub fn no_save(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self
+    }
+
+    /// Make this a transient parameter: not saved and not automatable.
+    ///
+    /// Use for momentary controls such as "audition" toggles or UI-only
+    /// state that should never be recorded by the host.
+    pub fn transient(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self.info.flags.can_automate = false;
+        self
+    }
+
+    /// Exclude this parameter from preset randomization.
+    pub fn no_randomize(mut self) -> Self {
+        self.info.flags.is_randomizable = false;
+        self
+    }
+
+    /// Keep this parameter GUI-only until the user promotes it to the host.
+    ///
+    /// For large modular plugins whose full parameter set exceeds what hosts
+    /// handle well. Internal parameters are still saved with the state.
+    pub fn internal(mut self) -> Self {
+        self.info.flags.is_internal = true;
+        self
+    }
+
+    /// Limit preset randomization to a sub-range, in plain units.
+    ///
+    /// Use to keep "Random" musical, e.g. output gain within -12..=0 dB.
+    pub fn with_randomize_range(mut self, range: RangeInclusive<i64>) -> Self {
+        let a = self.plain_to_normalized(*range.start() as f64);
+        let b = self.plain_to_normalized(*range.end() as f64);
+        self.info.randomize_range = (a.min(b), a.max(b));
+        self
+    }
+
     /// Set the unit type hint for AU hosts.
     ///
     /// This is typically set automatically by the constructor (e.g., `semitones()` sets
@@ -1540,6 +2160,12 @@ impl IntParameter {
     pub fn reset_smoothing(&mut self) {
         // No-op: IntParameter doesn't support smoothing
     }
+
+    /// No-op for compatibility with the `#[derive(Parameters)]` macro.
+    #[inline]
+    pub fn advance_smoothing(&mut self, _samples: usize) {
+        // No-op: IntParameter doesn't support smoothing
+    }
 }
 
 impl ParameterRef for IntParameter {
@@ -1617,6 +2243,14 @@ impl ParameterRef for IntParameter {
     fn info(&self) -> &ParameterInfo {
         &self.info
     }
+
+    fn is_randomize_locked(&self) -> bool {
+        self.randomize_locked.load(Ordering::Relaxed)
+    }
+
+    fn set_randomize_locked(&self, locked: bool) {
+        self.randomize_locked.store(locked, Ordering::Relaxed);
+    }
 }
 
 // =============================================================================
@@ -1645,6 +2279,8 @@ pub struct BoolParameter {
     info: ParameterInfo,
     /// Atomic storage for the boolean value
     value: AtomicBool,
+    /// Runtime lock excluding this parameter from randomization
+    randomize_locked: AtomicBool,
     /// Formatter for display string conversion
     formatter: Formatter,
 }
@@ -1672,8 +2308,10 @@ impl BoolParameter {
                 step_count: 1, // Toggle
                 flags: ParameterFlags::default(),
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicBool::new(default),
+            randomize_locked: AtomicBool::new(false),
             formatter: Formatter::Boolean,
         }
     }
@@ -1705,10 +2343,15 @@ impl BoolParameter {
                     is_bypass: true,
                     is_list: false,
                     is_hidden: false,
+                    is_persistent: true,
+                    is_randomizable: false,
+                    is_internal: false,
                 },
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: AtomicBool::new(false),
+            randomize_locked: AtomicBool::new(false),
             formatter: Formatter::Boolean,
         }
     }
@@ -1762,6 +2405,40 @@ impl BoolParameter {
         self
     }
 
+    /// Exclude this parameter from saved state.
+    ///
+    /// The parameter stays automatable but is not written by `save_state()`
+    /// and ignored by `load_state()`.
+    pub fn no_save(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self
+    }
+
+    /// Make this a transient parameter: not saved and not automatable.
+    ///
+    /// Use for momentary controls such as "audition" toggles or UI-only
+    /// state that should never be recorded by the host.
+    pub fn transient(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self.info.flags.can_automate = false;
+        self
+    }
+
+    /// Exclude this parameter from preset randomization.
+    pub fn no_randomize(mut self) -> Self {
+        self.info.flags.is_randomizable = false;
+        self
+    }
+
+    /// Keep this parameter GUI-only until the user promotes it to the host.
+    ///
+    /// For large modular plugins whose full parameter set exceeds what hosts
+    /// handle well. Internal parameters are still saved with the state.
+    pub fn internal(mut self) -> Self {
+        self.info.flags.is_internal = true;
+        self
+    }
+
     /// Set the unit type hint for AU hosts.
     ///
     /// BoolParameter defaults to `Boolean` which renders as a checkbox.
@@ -1814,6 +2491,12 @@ impl BoolParameter {
     pub fn reset_smoothing(&mut self) {
         // No-op: BoolParameter doesn't support smoothing
     }
+
+    /// No-op for compatibility with the `#[derive(Parameters)]` macro.
+    #[inline]
+    pub fn advance_smoothing(&mut self, _samples: usize) {
+        // No-op: BoolParameter doesn't support smoothing
+    }
 }
 
 impl ParameterRef for BoolParameter {
@@ -1884,6 +2567,14 @@ impl ParameterRef for BoolParameter {
     fn info(&self) -> &ParameterInfo {
         &self.info
     }
+
+    fn is_randomize_locked(&self) -> bool {
+        self.randomize_locked.load(Ordering::Relaxed)
+    }
+
+    fn set_randomize_locked(&self, locked: bool) {
+        self.randomize_locked.store(locked, Ordering::Relaxed);
+    }
 }
 
 // =============================================================================
@@ -1982,6 +2673,8 @@ pub struct EnumParameter<E: EnumParameterValue> {
     info: ParameterInfo,
     /// Atomic storage for the variant index
     value: std::sync::atomic::AtomicUsize,
+    /// Runtime lock excluding this parameter from randomization
+    randomize_locked: AtomicBool,
     /// Phantom data for the enum type
     _marker: std::marker::PhantomData<E>,
 }
@@ -2044,8 +2737,10 @@ impl<E: EnumParameterValue> EnumParameter<E> {
                     ..ParameterFlags::default()
                 },
                 group_id: ROOT_GROUP_ID,
+                randomize_range: (0.0, 1.0),
             },
             value: std::sync::atomic::AtomicUsize::new(default_index),
+            randomize_locked: AtomicBool::new(false),
             _marker: std::marker::PhantomData,
         }
     }
@@ -2099,6 +2794,40 @@ impl<E: EnumParameterValue> EnumParameter<E> {
         self
     }
 
+    /// Exclude this parameter from saved state.
+    ///
+    /// The parameter stays automatable but is not written by `save_state()`
+    /// and ignored by `load_state()`.
+    pub fn no_save(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self
+    }
+
+    /// Make this a transient parameter: not saved and not automatable.
+    ///
+    /// Use for momentary controls such as "audition" toggles or UI-only
+    /// state that should never be recorded by the host.
+    pub fn transient(mut self) -> Self {
+        self.info.flags.is_persistent = false;
+        self.info.flags.can_automate = false;
+        self
+    }
+
+    /// Exclude this parameter from preset randomization.
+    pub fn no_randomize(mut self) -> Self {
+        self.info.flags.is_randomizable = false;
+        self
+    }
+
+    /// Keep this parameter GUI-only until the user promotes it to the host.
+    ///
+    /// For large modular plugins whose full parameter set exceeds what hosts
+    /// handle well. Internal parameters are still saved with the state.
+    pub fn internal(mut self) -> Self {
+        self.info.flags.is_internal = true;
+        self
+    }
+
     /// Set the unit type hint for AU hosts.
     ///
     /// EnumParameter defaults to `Indexed` which renders as a dropdown.
@@ -2158,6 +2887,12 @@ impl<E: EnumParameterValue> EnumParameter<E> {
     pub fn reset_smoothing(&mut self) {
         // No-op: EnumParameter doesn't support smoothing
     }
+
+    /// No-op for compatibility with the `#[derive(Parameters)]` macro.
+    #[inline]
+    pub fn advance_smoothing(&mut self, _samples: usize) {
+        // No-op: EnumParameter doesn't support smoothing
+    }
 }
 
 impl<E: EnumParameterValue> ParameterRef for EnumParameter<E> {
@@ -2239,6 +2974,14 @@ impl<E: EnumParameterValue> ParameterRef for EnumParameter<E> {
     fn info(&self) -> &ParameterInfo {
         &self.info
     }
+
+    fn is_randomize_locked(&self) -> bool {
+        self.randomize_locked.load(Ordering::Relaxed)
+    }
+
+    fn set_randomize_locked(&self, locked: bool) {
+        self.randomize_locked.store(locked, Ordering::Relaxed);
+    }
 }
 
 // EnumParameter<E> is Send + Sync because:
@@ -2394,6 +3137,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "smoothing")]
     fn test_step_size_with_smoother() {
         let mut param = FloatParameter::new("Test", 0.0, 0.0..=10.0)
             .with_step_size(1.0)
@@ -2406,6 +3150,31 @@ mod tests {
         assert!((param.get() - 5.0).abs() < 1e-10);
     }
 
+    #[test]
+    #[cfg(feature = "smoothing")]
+    fn test_auto_smoother_ramps_per_block() {
+        let mut param = FloatParameter::new("Test", 0.0, 0.0..=10.0)
+            .with_auto_smoother(crate::smoothing::SmoothingStyle::Linear(4.0));
+        assert!(param.is_auto_smoothed());
+        param.set_sample_rate(1000.0);
+
+        param.set(8.0);
+        param.advance_smoothing(2);
+        let first: Vec<f64> = param.smoothed_values().take(2).collect();
+        assert_eq!(first, [2.0, 4.0]);
+        // Reading again yields the same block
+        assert_eq!(param.smoothed_values().next(), Some(2.0));
+
+        param.advance_smoothing(3);
+        let second: Vec<f64> = param.smoothed_values().take(3).collect();
+        assert_eq!(second, [6.0, 8.0, 8.0]);
+
+        // Manual smoothers and plain parameters yield a constant
+        let manual = FloatParameter::new("Test", 3.0, 0.0..=10.0);
+        assert!(!manual.is_auto_smoothed());
+        assert_eq!(manual.smoothed_values().take(2).collect::<Vec<_>>(), [3.0, 3.0]);
+    }
+
     #[test]
     fn test_snap_to_step_helper() {
         // Basic snapping
@@ -2732,4 +3501,109 @@ mod tests {
         assert_eq!(ParameterUnit::Ratio as u32, 25);
         assert_eq!(ParameterUnit::CustomUnit as u32, 26);
     }
+
+    #[test]
+    fn test_parameters_persistent_by_default() {
+        let param = FloatParameter::new("Gain", 0.5, 0.0..=1.0);
+        assert!(param.info().flags.is_persistent);
+        assert!(param.info().flags.can_automate);
+    }
+
+    #[test]
+    fn test_no_save_keeps_automation() {
+        let param = BoolParameter::new("Audition", false).no_save();
+        assert!(!param.info().flags.is_persistent);
+        assert!(param.info().flags.can_automate);
+    }
+
+    #[test]
+    fn test_transient_disables_save_and_automation() {
+        let param = IntParameter::new("Page", 0, 0..=3).transient();
+        assert!(!param.info().flags.is_persistent);
+        assert!(!param.info().flags.can_automate);
+    }
+
+    #[test]
+    fn test_randomize_range_normalized() {
+        let param = FloatParameter::db("Output", 0.0, -60.0..=12.0).with_randomize_range(-12.0..=0.0);
+        let (min, max) = param.info().randomize_range;
+        assert!((param.normalized_to_plain(min) - -12.0).abs() < 1e-9);
+        assert!((param.normalized_to_plain(max) - 0.0).abs() < 1e-9);
+
+        let param = IntParameter::new("Voices", 4, 0..=16).with_randomize_range(2..=8);
+        assert_eq!(param.info().randomize_range, (0.125, 0.5));
+    }
+
+    #[test]
+    fn test_can_randomize() {
+        assert!(FloatParameter::new("Mix", 0.5, 0.0..=1.0).can_randomize());
+        assert!(!BoolParameter::bypass().can_randomize());
+        assert!(!FloatParameter::new("Meter", 0.0, 0.0..=1.0).readonly().can_randomize());
+        assert!(!IntParameter::new("Mode", 0, 0..=3).no_randomize().can_randomize());
+
+        let param = FloatParameter::new("Cutoff", 0.5, 0.0..=1.0);
+        param.set_randomize_locked(true);
+        assert!(!param.can_randomize());
+        param.set_randomize_locked(false);
+        assert!(param.can_randomize());
+    }
+
+    struct RandomizeParameters {
+        gain: FloatParameter,
+        locked: FloatParameter,
+        bypass: BoolParameter,
+    }
+
+    impl ParameterGroups for RandomizeParameters {}
+
+    impl Parameters for RandomizeParameters {
+        fn count(&self) -> usize {
+            3
+        }
+
+        fn iter(&self) -> Box<dyn Iterator<Item = &dyn ParameterRef> + '_> {
+            Box::new([&self.gain as &dyn ParameterRef, &self.locked, &self.bypass].into_iter())
+        }
+
+        fn by_id(&self, _id: ParameterId) -> Option<&dyn ParameterRef> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_parameters_randomize_respects_constraints() {
+        let parameters = RandomizeParameters {
+            gain: FloatParameter::new("Gain", 0.0, 0.0..=10.0).with_randomize_range(2.0..=4.0),
+            locked: FloatParameter::new("Locked", 5.0, 0.0..=10.0),
+            bypass: BoolParameter::bypass(),
+        };
+        parameters.locked.set_randomize_locked(true);
+
+        parameters.randomize(&mut || 1.0);
+        assert!((parameters.gain.get() - 4.0).abs() < 1e-9);
+        assert_eq!(parameters.locked.get(), 5.0);
+        assert!(!parameters.bypass.get());
+
+        parameters.randomize(&mut || 0.5);
+        assert!((parameters.gain.get() - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_in_samples_follows_sample_rate() {
+        let mut length = TimeInSamples::new("Length", 1.0, 0.0..=60.0).with_id(7);
+        assert_eq!(length.samples(), 0); // rate unknown before set_sample_rate()
+        assert_eq!(length.units(), "s");
+
+        length.set_sample_rate(48000.0);
+        assert_eq!(length.samples(), 48000);
+        length.set_samples(96000);
+        assert!((length.get() - 2.0).abs() < 1e-9);
+
+        // Saved as seconds: the same normalized value at another rate
+        let saved = length.get_normalized();
+        let mut reopened = TimeInSamples::new("Length", 1.0, 0.0..=60.0).with_id(7);
+        reopened.set_normalized(saved);
+        reopened.set_sample_rate(44100.0);
+        assert_eq!(reopened.samples(), 88200);
+    }
 }
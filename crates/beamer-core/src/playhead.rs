@@ -0,0 +1,298 @@
+//! Host transport position for GUI timelines, extrapolated between blocks.
+//!
+//! The audio thread learns the transport once per block, so a playhead drawn
+//! from block positions moves in steps of the buffer size (20 ms at 1024
+//! samples and 48 kHz) and stutters against the display refresh. [`Playhead`]
+//! keeps the position, tempo and play state of the latest block together
+//! with the time it arrived; the GUI side reads a [`PlayheadPosition`]
+//! advanced to the moment of reading, so step sequencer cursors and timeline
+//! playheads move smoothly.
+//!
+//! The wrappers update the playhead of the descriptor's
+//! [`FrameMeters`](crate::FrameMeters) every block, and each
+//! `__BEAMER__.onFrame()` tick carries it as the third argument:
+//!
+//! ```javascript
+//! __BEAMER__.onFrame((time, meters, playhead) => {
+//!   if (playhead.beats !== null) drawCursor(playhead.beats % 4);
+//! });
+//! ```
+//!
+//! Extrapolation runs at the block's tempo, stops at twice the block length
+//! (a host that stops calling `process()` doesn't run the playhead away)
+//! and wraps at the cycle end while the loop is active.
+
+use std::sync::atomic::{fence, AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use crate::process_context::Transport;
+
+/// Origin of the update timestamps, shared by all instances.
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn now_ns() -> u64 {
+    epoch().elapsed().as_nanos() as u64
+}
+
+/// Unknown values are stored as NaN.
+fn store_f64(slot: &AtomicU64, value: Option<f64>) {
+    slot.store(value.unwrap_or(f64::NAN).to_bits(), Ordering::Relaxed);
+}
+
+fn load_f64(slot: &AtomicU64) -> Option<f64> {
+    let value = f64::from_bits(slot.load(Ordering::Relaxed));
+    (!value.is_nan()).then_some(value)
+}
+
+/// Transport of one block, extrapolated to the moment it was read.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayheadPosition {
+    /// Project position in quarter notes.
+    pub beats: Option<f64>,
+    /// Start of the current bar in quarter notes.
+    pub bar_start_beats: Option<f64>,
+    /// Tempo in BPM.
+    pub tempo: Option<f64>,
+    /// Time signature as (numerator, denominator).
+    pub time_signature: Option<(i32, i32)>,
+    /// Whether the transport is playing.
+    pub playing: bool,
+}
+
+impl PlayheadPosition {
+    /// The position as a JSON object for the WebView
+    /// (`{"beats":4.25,"barStart":4,"tempo":120,"timeSignature":[4,4],"playing":true}`,
+    /// unknown values as `null`).
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "beats": self.beats,
+            "barStart": self.bar_start_beats,
+            "tempo": self.tempo,
+            "timeSignature": self.time_signature.map(|(num, den)| [num, den]),
+            "playing": self.playing,
+        })
+        .to_string()
+    }
+}
+
+struct Shared {
+    /// Odd while an update is being written.
+    version: AtomicU32,
+    beats: AtomicU64,
+    bar_start: AtomicU64,
+    tempo: AtomicU64,
+    cycle_start: AtomicU64,
+    cycle_end: AtomicU64,
+    /// Numerator in the high, denominator in the low 32 bits (0 if unknown).
+    time_signature: AtomicU64,
+    playing: AtomicBool,
+    /// When the block arrived, in nanoseconds since [`epoch()`].
+    updated_ns: AtomicU64,
+    /// Duration of the block in nanoseconds.
+    block_ns: AtomicU64,
+}
+
+/// The latest block's transport, shared between the audio thread and the GUI.
+///
+/// Cloning is cheap (reference counted). Written by the wrappers; read with
+/// [`position()`](Self::position).
+#[derive(Clone)]
+pub struct Playhead {
+    shared: Arc<Shared>,
+}
+
+impl Default for Playhead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Playhead {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Playhead").field(&self.position()).finish()
+    }
+}
+
+impl Playhead {
+    /// Create a playhead with an unknown position.
+    pub fn new() -> Self {
+        let nan = || AtomicU64::new(f64::NAN.to_bits());
+        Self {
+            shared: Arc::new(Shared {
+                version: AtomicU32::new(0),
+                beats: nan(),
+                bar_start: nan(),
+                tempo: nan(),
+                cycle_start: nan(),
+                cycle_end: nan(),
+                time_signature: AtomicU64::new(0),
+                playing: AtomicBool::new(false),
+                updated_ns: AtomicU64::new(0),
+                block_ns: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Record the transport at the start of a block of `num_samples` at
+    /// `sample_rate`.
+    ///
+    /// Called by the wrappers before `process()`. Real-time safe: atomic
+    /// stores and a monotonic clock read.
+    pub fn update(&self, transport: &Transport, sample_rate: f64, num_samples: usize) {
+        let shared = &*self.shared;
+        let version = shared.version.load(Ordering::Relaxed);
+        shared.version.store(version.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        store_f64(&shared.beats, transport.project_time_beats);
+        store_f64(&shared.bar_start, transport.bar_position_beats);
+        store_f64(&shared.tempo, transport.tempo);
+        let cycle = |beats| if transport.is_cycle_active { beats } else { None };
+        store_f64(&shared.cycle_start, cycle(transport.cycle_start_beats));
+        store_f64(&shared.cycle_end, cycle(transport.cycle_end_beats));
+        let signature = match (transport.time_sig_numerator, transport.time_sig_denominator) {
+            (Some(num), Some(den)) => ((num as u32 as u64) << 32) | den as u32 as u64,
+            _ => 0,
+        };
+        shared.time_signature.store(signature, Ordering::Relaxed);
+        shared.playing.store(transport.is_playing, Ordering::Relaxed);
+        shared.updated_ns.store(now_ns(), Ordering::Relaxed);
+        let block_ns = if sample_rate > 0.0 {
+            (num_samples as f64 / sample_rate * 1e9) as u64
+        } else {
+            0
+        };
+        shared.block_ns.store(block_ns, Ordering::Relaxed);
+
+        shared.version.store(version.wrapping_add(2), Ordering::Release);
+    }
+
+    /// The latest block's transport, advanced to now.
+    pub fn position(&self) -> PlayheadPosition {
+        self.position_at(now_ns())
+    }
+
+    fn position_at(&self, now_ns: u64) -> PlayheadPosition {
+        let shared = &*self.shared;
+        let mut attempt = 0;
+        let (mut position, cycle, updated_ns, block_ns) = loop {
+            let before = shared.version.load(Ordering::Acquire);
+            let signature = shared.time_signature.load(Ordering::Relaxed);
+            let read = (
+                PlayheadPosition {
+                    beats: load_f64(&shared.beats),
+                    bar_start_beats: load_f64(&shared.bar_start),
+                    tempo: load_f64(&shared.tempo),
+                    time_signature: (signature != 0)
+                        .then_some(((signature >> 32) as i32, signature as u32 as i32)),
+                    playing: shared.playing.load(Ordering::Relaxed),
+                },
+                (load_f64(&shared.cycle_start), load_f64(&shared.cycle_end)),
+                shared.updated_ns.load(Ordering::Relaxed),
+                shared.block_ns.load(Ordering::Relaxed),
+            );
+            fence(Ordering::Acquire);
+            attempt += 1;
+            // A torn read only happens if the audio thread writes meanwhile;
+            // after a few tries the last one is close enough for drawing.
+            if (before.is_multiple_of(2) && shared.version.load(Ordering::Relaxed) == before) || attempt == 4 {
+                break read;
+            }
+        };
+
+        let (Some(beats), Some(tempo), true) = (position.beats, position.tempo, position.playing)
+        else {
+            return position;
+        };
+        let elapsed_ns = now_ns.saturating_sub(updated_ns).min(block_ns.saturating_mul(2));
+        let mut beats = beats + elapsed_ns as f64 * 1e-9 * tempo / 60.0;
+        if let (Some(start), Some(end)) = cycle {
+            if end > start && beats >= end {
+                beats = start + (beats - start) % (end - start);
+            }
+        }
+        position.beats = Some(beats);
+
+        // Keep the bar start in step with the extrapolated position
+        if let (Some(bar_start), Some((num, den))) = (position.bar_start_beats, position.time_signature) {
+            let bar_length = num as f64 * 4.0 / den.max(1) as f64;
+            if bar_length > 0.0 {
+                let bars = ((beats - bar_start) / bar_length).floor();
+                position.bar_start_beats = Some(bar_start + bars * bar_length);
+            }
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transport(beats: f64, playing: bool) -> Transport {
+        Transport {
+            tempo: Some(120.0),
+            time_sig_numerator: Some(4),
+            time_sig_denominator: Some(4),
+            project_time_beats: Some(beats),
+            bar_position_beats: Some(beats - beats % 4.0),
+            is_playing: playing,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn extrapolates_while_playing_up_to_two_blocks() {
+        let playhead = Playhead::new();
+        assert_eq!(playhead.position().beats, None);
+
+        // 0.25 s blocks at 120 BPM advance half a beat each
+        playhead.update(&transport(3.75, true), 48000.0, 12000);
+        let updated = playhead.shared.updated_ns.load(Ordering::Relaxed);
+        let at = |ms: u64| playhead.position_at(updated + ms * 1_000_000);
+
+        assert_eq!(at(0).beats, Some(3.75));
+        assert_eq!(at(100).beats, Some(3.95));
+        // Crossing the bar line moves the bar start along
+        assert_eq!(at(250).beats, Some(4.25));
+        assert_eq!(at(250).bar_start_beats, Some(4.0));
+        // Capped at two blocks
+        assert_eq!(at(5000).beats, Some(4.75));
+
+        playhead.update(&transport(8.0, false), 48000.0, 12000);
+        let updated = playhead.shared.updated_ns.load(Ordering::Relaxed);
+        assert_eq!(playhead.position_at(updated + 100_000_000).beats, Some(8.0));
+    }
+
+    #[test]
+    fn wraps_at_cycle_end_and_serializes() {
+        let playhead = Playhead::new();
+        playhead.update(
+            &Transport {
+                is_cycle_active: true,
+                cycle_start_beats: Some(0.0),
+                cycle_end_beats: Some(4.0),
+                ..transport(3.75, true)
+            },
+            48000.0,
+            12000,
+        );
+        let updated = playhead.shared.updated_ns.load(Ordering::Relaxed);
+        let position = playhead.position_at(updated + 250_000_000);
+        assert_eq!(position.beats, Some(0.25));
+        assert_eq!(position.bar_start_beats, Some(0.0));
+
+        assert_eq!(
+            position.to_json(),
+            r#"{"barStart":0.0,"beats":0.25,"playing":true,"tempo":120.0,"timeSignature":[4,4]}"#
+        );
+        assert_eq!(
+            PlayheadPosition::default().to_json(),
+            r#"{"barStart":null,"beats":null,"playing":false,"tempo":null,"timeSignature":null}"#
+        );
+    }
+}
@@ -13,18 +13,40 @@
 //! until proper configuration is available.
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::activation::Activation;
 use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::control_rate::ControlContext;
+use crate::controller_pages::ControllerPages;
 use crate::error::{PluginError, PluginResult};
+use crate::frame_meters::FrameMeters;
+use crate::gui::GuiBackend;
+use crate::gui_data::GuiDataChannels;
+use crate::host_services::HostServices;
 use crate::midi::{
     KeyswitchInfo, Midi2Controller, MidiBuffer, MidiEvent, MpeInputDeviceSettings,
     NoteExpressionTypeInfo, PhysicalUIMap,
 };
 use crate::midi_cc_config::MidiCcConfig;
+use crate::mpe::MpeConfig;
+use crate::note_ranges::NoteRanges;
+use crate::midi_clock::MidiClockOutput;
+use crate::midi_monitor::MidiMonitor;
+use crate::midi_panic::MidiPanic;
+use crate::midi_thru::MidiThru;
+use crate::notifications::Notifications;
+use crate::oversampling::Oversampling;
+use crate::quality::Quality;
+use crate::parameter_constraints::ParameterConstraints;
 use crate::parameter_groups::ParameterGroups;
+use crate::parameter_log::ParameterLog;
 use crate::parameter_store::ParameterStore;
 use crate::parameter_types::Parameters;
-use crate::process_context::ProcessContext;
+use crate::process_context::{ProcessContext, Transport};
+use crate::response_curves::ResponseCurves;
+use crate::speaker_layout::SpeakerLayout;
+use crate::transport_events::{RelocationPolicy, TransportEvent};
 use crate::webview_handler::WebViewHandler;
 
 // =============================================================================
@@ -73,6 +95,13 @@ pub trait HasParameters: Send + 'static {
     /// This is used by the default [`Processor::unprepare()`] implementation
     /// to transfer parameters from the processor back to the definition.
     fn set_parameters(&mut self, params: Self::Parameters);
+
+    /// Recompute the [`RateDependent`](crate::rate_dependent::RateDependent)
+    /// fields for `sample_rate`.
+    ///
+    /// Called by the wrappers after `prepare()` and after `load_state()`.
+    /// The derive macro recomputes every field marked `#[rate_dependent]`.
+    fn recompute_rate_dependent(&mut self, _sample_rate: f64) {}
 }
 
 // =============================================================================
@@ -93,6 +122,8 @@ pub struct HostSetup {
     pub layout: BusLayout,
     /// Processing mode (realtime vs offline)
     pub process_mode: ProcessMode,
+    /// Quality mode selected by [`Descriptor::quality()`]
+    pub quality: Quality,
 }
 
 impl HostSetup {
@@ -103,8 +134,15 @@ impl HostSetup {
             max_buffer_size,
             layout,
             process_mode,
+            quality: Quality::Normal,
         }
     }
+
+    /// Set the quality mode.
+    pub fn with_quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
 }
 
 /// Trait for plugin setup requirements.
@@ -134,7 +172,11 @@ impl HostSetup {
 /// | [`MainOutputChannels`] | `u32` | Per-channel output state |
 /// | [`AuxInputCount`] | `usize` | Sidechain-aware processing |
 /// | [`AuxOutputCount`] | `usize` | Multi-bus output |
+/// | [`AuxInputChannels<N>`](AuxInputChannels) | `u32` | Per-channel sidechain state |
+/// | [`AuxOutputChannels<N>`](AuxOutputChannels) | `u32` | Per-channel aux output state |
+/// | [`BusLayout`] | struct | Every bus and its channel count |
 /// | [`ProcessMode`] | enum | Quality settings for offline rendering |
+/// | [`Quality`] | enum | Eco/Normal/High mode from [`Descriptor::quality()`] |
 pub trait PluginSetup: Clone + Send + 'static {
     /// Extract this setup from the host-provided information.
     fn extract(host: &HostSetup) -> Self;
@@ -292,6 +334,67 @@ impl PluginSetup for AuxOutputCount {
     }
 }
 
+/// Number of channels on an auxiliary input bus, selected by index.
+///
+/// `INDEX` counts aux buses only (0 is the first bus after the main bus).
+/// The value is 0 when the bus does not exist.
+///
+/// # Example
+///
+/// ```ignore
+/// impl Descriptor for SidechainPlugin {
+///     type Setup = (SampleRate, AuxInputChannels<0>);
+///     fn prepare(self, (sr, sidechain): (SampleRate, AuxInputChannels<0>)) -> SidechainProcessor {
+///         SidechainProcessor {
+///             sample_rate: sr.0,
+///             detectors: vec![Detector::new(sr.0); sidechain.0 as usize],
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AuxInputChannels<const INDEX: usize>(pub u32);
+
+impl<const INDEX: usize> PluginSetup for AuxInputChannels<INDEX> {
+    fn extract(host: &HostSetup) -> Self {
+        AuxInputChannels(host.layout.aux_input_channels(INDEX))
+    }
+}
+
+/// Number of channels on an auxiliary output bus, selected by index.
+///
+/// `INDEX` counts aux buses only (0 is the first bus after the main bus).
+/// The value is 0 when the bus does not exist.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AuxOutputChannels<const INDEX: usize>(pub u32);
+
+impl<const INDEX: usize> PluginSetup for AuxOutputChannels<INDEX> {
+    fn extract(host: &HostSetup) -> Self {
+        AuxOutputChannels(host.layout.aux_output_channels(INDEX))
+    }
+}
+
+/// The full bus layout, for plugins with a variable number of aux buses.
+///
+/// # Example
+///
+/// ```ignore
+/// impl Descriptor for MultiOutPlugin {
+///     type Setup = (SampleRate, BusLayout);
+///     fn prepare(self, (sr, layout): (SampleRate, BusLayout)) -> MultiOutProcessor {
+///         MultiOutProcessor {
+///             sample_rate: sr.0,
+///             voices: layout.aux_output_channel_counts.iter().map(|&ch| Voice::new(ch)).collect(),
+///         }
+///     }
+/// }
+/// ```
+impl PluginSetup for BusLayout {
+    fn extract(host: &HostSetup) -> Self {
+        host.layout.clone()
+    }
+}
+
 /// Processing mode (realtime vs offline).
 ///
 /// Use for plugins that want different quality settings
@@ -385,8 +488,9 @@ where
 
 /// Bus layout information for plugins that need channel configuration.
 ///
-/// This is used internally to populate the individual setup types
-/// like [`MainInputChannels`], [`MainOutputChannels`], etc.
+/// Used internally to populate the individual setup types like
+/// [`MainInputChannels`] and [`AuxInputChannels`], and available as a setup
+/// type itself for plugins that need every bus.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct BusLayout {
     /// Number of channels on the main input bus
@@ -397,6 +501,10 @@ pub struct BusLayout {
     pub aux_input_count: usize,
     /// Number of auxiliary output buses
     pub aux_output_count: usize,
+    /// Channel count of each auxiliary input bus (`aux_input_count` entries)
+    pub aux_input_channel_counts: Vec<u32>,
+    /// Channel count of each auxiliary output bus (`aux_output_count` entries)
+    pub aux_output_channel_counts: Vec<u32>,
 }
 
 impl BusLayout {
@@ -407,24 +515,85 @@ impl BusLayout {
             main_output_channels: 2,
             aux_input_count: 0,
             aux_output_count: 0,
+            aux_input_channel_counts: Vec::new(),
+            aux_output_channel_counts: Vec::new(),
         }
     }
 
     /// Create a layout from a plugin's bus configuration.
     pub fn from_plugin<P: Descriptor>(plugin: &P) -> Self {
+        let input_buses: Vec<BusInfo> = (0..plugin.input_bus_count())
+            .filter_map(|i| plugin.input_bus_info(i))
+            .collect();
+        let output_buses: Vec<BusInfo> = (0..plugin.output_bus_count())
+            .filter_map(|i| plugin.output_bus_info(i))
+            .collect();
+        Self::from_buses(&input_buses, &output_buses)
+    }
+
+    /// Create a layout from input and output bus lists (main bus first).
+    ///
+    /// A missing main bus is reported as stereo.
+    pub fn from_buses(input_buses: &[BusInfo], output_buses: &[BusInfo]) -> Self {
+        let aux_channels = |buses: &[BusInfo]| -> Vec<u32> {
+            buses.iter().skip(1).map(|b| b.channel_count).collect()
+        };
         Self {
-            main_input_channels: plugin
-                .input_bus_info(0)
-                .map(|b| b.channel_count)
-                .unwrap_or(2),
-            main_output_channels: plugin
-                .output_bus_info(0)
-                .map(|b| b.channel_count)
-                .unwrap_or(2),
-            aux_input_count: plugin.input_bus_count().saturating_sub(1),
-            aux_output_count: plugin.output_bus_count().saturating_sub(1),
+            main_input_channels: input_buses.first().map_or(2, |b| b.channel_count),
+            main_output_channels: output_buses.first().map_or(2, |b| b.channel_count),
+            aux_input_count: input_buses.len().saturating_sub(1),
+            aux_output_count: output_buses.len().saturating_sub(1),
+            aux_input_channel_counts: aux_channels(input_buses),
+            aux_output_channel_counts: aux_channels(output_buses),
+        }
+    }
+
+    /// Channel count of aux input bus `index`, or 0 if it does not exist.
+    pub fn aux_input_channels(&self, index: usize) -> u32 {
+        self.aux_input_channel_counts.get(index).copied().unwrap_or(0)
+    }
+
+    /// Channel count of aux output bus `index`, or 0 if it does not exist.
+    pub fn aux_output_channels(&self, index: usize) -> u32 {
+        self.aux_output_channel_counts.get(index).copied().unwrap_or(0)
+    }
+
+    /// Channel count of input bus `bus` (0 = main).
+    pub fn input_bus_channels(&self, bus: usize) -> u32 {
+        match bus {
+            0 => self.main_input_channels,
+            _ => self.aux_input_channels(bus - 1),
         }
     }
+
+    /// Channel count of output bus `bus` (0 = main).
+    pub fn output_bus_channels(&self, bus: usize) -> u32 {
+        match bus {
+            0 => self.main_output_channels,
+            _ => self.aux_output_channels(bus - 1),
+        }
+    }
+
+    /// Returns true if the main input and output are each mono or stereo.
+    ///
+    /// For [`Descriptor::accepts_bus_layout`] in effects that handle both,
+    /// including mono in, stereo out.
+    pub fn main_is_mono_or_stereo(&self) -> bool {
+        matches!(self.main_input_channels, 1 | 2) && matches!(self.main_output_channels, 1 | 2)
+    }
+
+    /// Returns true if the aux buses match `declared`'s, each with at most
+    /// its declared channels (0 = unused, e.g. an unconnected sidechain).
+    ///
+    /// For [`Descriptor::accepts_bus_layout`] in plugins with optional
+    /// sidechains.
+    pub fn aux_within(&self, declared: &BusLayout) -> bool {
+        let within = |counts: &[u32], declared: &[u32]| {
+            counts.len() == declared.len() && counts.iter().zip(declared).all(|(count, max)| count <= max)
+        };
+        within(&self.aux_input_channel_counts, &declared.aux_input_channel_counts)
+            && within(&self.aux_output_channel_counts, &declared.aux_output_channel_counts)
+    }
 }
 
 // =============================================================================
@@ -452,6 +621,9 @@ pub struct BusInfo {
     pub channel_count: u32,
     /// Whether the bus is active by default.
     pub is_default_active: bool,
+    /// Speaker layout, or `None` for the usual one for `channel_count`
+    /// (see [`BusInfo::layout`]).
+    pub speaker_layout: Option<SpeakerLayout>,
 }
 
 impl Default for BusInfo {
@@ -461,6 +633,7 @@ impl Default for BusInfo {
             bus_type: BusType::Main,
             channel_count: 2,
             is_default_active: true,
+            speaker_layout: None,
         }
     }
 }
@@ -473,6 +646,7 @@ impl BusInfo {
             bus_type: BusType::Main,
             channel_count: 2,
             is_default_active: true,
+            speaker_layout: None,
         }
     }
 
@@ -483,6 +657,7 @@ impl BusInfo {
             bus_type: BusType::Main,
             channel_count: 1,
             is_default_active: true,
+            speaker_layout: None,
         }
     }
 
@@ -493,10 +668,129 @@ impl BusInfo {
             bus_type: BusType::Aux,
             channel_count,
             is_default_active: false,
+            speaker_layout: None,
+        }
+    }
+
+    /// Set the speaker layout and the matching channel count.
+    pub const fn with_speaker_layout(mut self, layout: SpeakerLayout) -> Self {
+        self.channel_count = layout.channel_count();
+        self.speaker_layout = Some(layout);
+        self
+    }
+
+    /// Speaker layout of this bus.
+    ///
+    /// The declared layout if its width matches `channel_count` (hosts may
+    /// negotiate other counts, see [`Descriptor::accepts_bus_layout`]),
+    /// otherwise the usual layout for the channel count.
+    pub fn layout(&self) -> SpeakerLayout {
+        match self.speaker_layout {
+            Some(layout) if layout.channel_count() == self.channel_count => layout,
+            _ => SpeakerLayout::from_channel_count(self.channel_count),
         }
     }
 }
 
+// =============================================================================
+// Tail Time
+// =============================================================================
+
+/// Audio tail the plugin produces after its input goes silent.
+///
+/// Reported to the host so it keeps calling `process()` long enough for
+/// reverb and delay tails to ring out. Returned by [`Processor::tail_time`].
+///
+/// | Format | Mapping |
+/// |--------|---------|
+/// | VST3 | `getTailSamples()` (`Infinite` = `kInfiniteTail`) |
+/// | AU | `kAudioUnitProperty_TailTime` / `tailTime` in seconds (`Infinite` = `INFINITY`) |
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TailTime {
+    /// A finite tail of the given number of samples (0 = no tail).
+    Samples(u32),
+    /// The tail never ends (e.g. a reverb in freeze mode). The host keeps
+    /// processing for as long as the plugin is active.
+    Infinite,
+}
+
+impl Default for TailTime {
+    fn default() -> Self {
+        TailTime::Samples(0)
+    }
+}
+
+impl TailTime {
+    /// Convert a sample count, treating `u32::MAX` as infinite.
+    pub const fn from_samples(samples: u32) -> Self {
+        if samples == u32::MAX {
+            TailTime::Infinite
+        } else {
+            TailTime::Samples(samples)
+        }
+    }
+
+    /// Tail length in samples, with `u32::MAX` for an infinite tail.
+    pub const fn to_samples(self) -> u32 {
+        match self {
+            TailTime::Samples(samples) => samples,
+            TailTime::Infinite => u32::MAX,
+        }
+    }
+
+    /// Tail length in seconds, with `f64::INFINITY` for an infinite tail.
+    ///
+    /// Returns 0.0 for a finite tail when `sample_rate` is not positive.
+    pub fn to_seconds(self, sample_rate: f64) -> f64 {
+        match self {
+            TailTime::Infinite => f64::INFINITY,
+            TailTime::Samples(_) if sample_rate <= 0.0 => 0.0,
+            TailTime::Samples(samples) => samples as f64 / sample_rate,
+        }
+    }
+
+    /// Returns true for [`TailTime::Infinite`].
+    pub const fn is_infinite(self) -> bool {
+        matches!(self, TailTime::Infinite)
+    }
+}
+
+/// Sample precision a processor handles natively, per processing path.
+///
+/// Returned by [`Processor::precision_policy`]. When the host runs at 64-bit,
+/// wrappers only convert the paths the policy marks as f32:
+///
+/// | Policy | Main bus | Aux buses | Called |
+/// |--------|----------|-----------|--------|
+/// | `F32` | converted | converted | `process()` |
+/// | `F64` | native | native | `process_f64()` |
+/// | `F64MainF32Aux` | native | converted | `process_f64_mixed()` |
+///
+/// At 32-bit host precision every policy calls `process()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PrecisionPolicy {
+    /// f32 everywhere (the default).
+    #[default]
+    F32,
+    /// f64 everywhere.
+    F64,
+    /// f64 main bus, f32 auxiliary buses (e.g. a sidechain detector that
+    /// only needs single precision).
+    F64MainF32Aux,
+}
+
+impl PrecisionPolicy {
+    /// Returns true if the main bus is processed at f64.
+    pub const fn main_is_f64(self) -> bool {
+        !matches!(self, PrecisionPolicy::F32)
+    }
+
+    /// Returns true if the auxiliary buses are processed at f64.
+    pub const fn aux_is_f64(self) -> bool {
+        matches!(self, PrecisionPolicy::F64)
+    }
+}
+
 // =============================================================================
 // Processor Trait
 // =============================================================================
@@ -689,6 +983,40 @@ pub trait Processor: HasParameters {
     /// Default implementation does nothing.
     fn set_active(&mut self, _active: bool) {}
 
+    /// Clear time-based DSP state (delay lines, reverb tails, filter
+    /// histories, envelopes) without reallocating.
+    ///
+    /// Called on the audio thread by the wrapper when the host transport
+    /// relocates and [`relocation_policy()`](Self::relocation_policy) asks for
+    /// it, and for the AU reset selector. Must be real-time safe.
+    ///
+    /// Default performs a full deactivate/activate cycle via
+    /// [`set_active()`](Self::set_active).
+    fn reset(&mut self) {
+        self.set_active(false);
+        self.set_active(true);
+    }
+
+    /// Returns what the wrapper does to DSP state on transport relocation.
+    ///
+    /// See [`crate::transport_events`] for the policies and how jumps are
+    /// detected.
+    ///
+    /// Default returns [`RelocationPolicy::Keep`] (tails continue).
+    fn relocation_policy(&self) -> RelocationPolicy {
+        RelocationPolicy::Keep
+    }
+
+    /// Called on the audio thread when the host transport starts, stops,
+    /// relocates, or wraps a loop, before the block's MIDI and audio.
+    ///
+    /// Runs after [`reset()`](Self::reset) when the policy resets. Use it to
+    /// re-seed tempo-synced phases from the new position with a
+    /// [`TimeAnchor`](crate::transport_events::TimeAnchor).
+    ///
+    /// Default implementation does nothing.
+    fn transport_event(&mut self, _event: TransportEvent, _transport: &Transport) {}
+
     /// Get the tail length in samples.
     ///
     /// This indicates how many samples of audio "tail" the plugin produces
@@ -700,12 +1028,37 @@ pub trait Processor: HasParameters {
         0
     }
 
+    /// Get the tail length as a [`TailTime`].
+    ///
+    /// This is what the format wrappers report to the host. Override it
+    /// instead of [`tail_samples()`](Self::tail_samples) when the tail can
+    /// become infinite, e.g. while a reverb's freeze mode is engaged:
+    ///
+    /// ```ignore
+    /// fn tail_time(&self) -> TailTime {
+    ///     if self.freeze {
+    ///         TailTime::Infinite
+    ///     } else {
+    ///         TailTime::Samples(self.decay_samples)
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Default converts `tail_samples()` via [`TailTime::from_samples`].
+    fn tail_time(&self) -> TailTime {
+        TailTime::from_samples(self.tail_samples())
+    }
+
     /// Get the latency in samples.
     ///
     /// If the plugin introduces processing latency (e.g., lookahead limiters),
     /// return the latency in samples here. The host can use this for delay
     /// compensation.
     ///
+    /// Hosts read it on activation. If it changes while the plugin runs,
+    /// call [`HostServices::latency_changed()`] so the host reads it again
+    /// (see [`crate::host_services`]).
+    ///
     /// Default returns 0 (no latency).
     fn latency_samples(&self) -> u32 {
         0
@@ -738,6 +1091,72 @@ pub trait Processor: HasParameters {
         64
     }
 
+    // =========================================================================
+    // Control Rate
+    // =========================================================================
+
+    /// Returns the control-rate interval in samples.
+    ///
+    /// When non-zero, the wrapper calls [`control_tick()`](Self::control_tick)
+    /// every `interval` samples and splits `process()` calls at the tick
+    /// positions, so modulation computed in a tick applies from that sample
+    /// on. See [`crate::control_rate`].
+    ///
+    /// Queried before every block, so it can follow a parameter. Default
+    /// returns 0 (no control-rate ticks).
+    fn control_interval(&self) -> usize {
+        0
+    }
+
+    /// Called on the audio thread every
+    /// [`control_interval()`](Self::control_interval) samples, before the
+    /// audio that follows the tick.
+    ///
+    /// Run envelopes, LFOs, modulation routing and meter computation here
+    /// instead of per sample. Must be real-time safe.
+    ///
+    /// Default implementation does nothing.
+    fn control_tick(&mut self, _context: ControlContext) {}
+
+    /// Called on the audio thread at the start of every block, before
+    /// control ticks and `process()`, to apply commands sent from other
+    /// threads.
+    ///
+    /// Processors with a [`CommandQueue`](crate::command::CommandQueue)
+    /// implement [`CommandHandler`](crate::command::CommandHandler) and
+    /// forward with `dispatch_commands(self)`. See [`crate::command`].
+    /// Must be real-time safe.
+    ///
+    /// Default implementation does nothing.
+    fn apply_commands(&mut self) {}
+
+    // =========================================================================
+    // Sample-Accurate Splitting
+    // =========================================================================
+
+    /// Returns true to have `process()` split at every MIDI event and
+    /// parameter change.
+    ///
+    /// The wrapper then applies each parameter change and calls
+    /// [`midi_event()`](Self::midi_event) right before the sub-block that
+    /// starts at it, so `process()` never sees an event inside its block.
+    /// See [`crate::split_block`].
+    ///
+    /// Queried before every block. Default returns false (one `process()`
+    /// call per block, with the last parameter values).
+    fn split_blocks(&self) -> bool {
+        false
+    }
+
+    /// Called on the audio thread for each MIDI input event when
+    /// [`split_blocks()`](Self::split_blocks) returns true, before the
+    /// `process()` call that starts at the event's position.
+    ///
+    /// The event's `sample_offset` is 0. Must be real-time safe.
+    ///
+    /// Default implementation does nothing.
+    fn midi_event(&mut self, _event: &MidiEvent) {}
+
     // =========================================================================
     // 64-bit Processing Support
     // =========================================================================
@@ -760,6 +1179,44 @@ pub trait Processor: HasParameters {
         false
     }
 
+    /// Sample precision per processing path.
+    ///
+    /// The default derives the policy from `supports_double_precision()`:
+    /// [`PrecisionPolicy::F64`] when it returns `true`, otherwise
+    /// [`PrecisionPolicy::F32`]. Return [`PrecisionPolicy::F64MainF32Aux`]
+    /// (and implement `process_f64_mixed()`) to keep the main path at f64
+    /// while auxiliary buses are converted to f32, so only the aux buses pay
+    /// the conversion cost.
+    ///
+    /// Queried once per block by the wrappers, so the result must not change
+    /// while the processor is prepared.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn precision_policy(&self) -> PrecisionPolicy {
+    ///     PrecisionPolicy::F64MainF32Aux
+    /// }
+    ///
+    /// fn process_f64_mixed(
+    ///     &mut self,
+    ///     buffer: &mut Buffer<f64>,
+    ///     aux: &mut AuxiliaryBuffers<f32>,
+    ///     context: &ProcessContext,
+    /// ) {
+    ///     let key = aux.sidechain().map_or(0.0, |sc| sc.rms(0));
+    ///     self.update_gain_reduction(key);
+    ///     self.apply_gain(buffer);
+    /// }
+    /// ```
+    fn precision_policy(&self) -> PrecisionPolicy {
+        if self.supports_double_precision() {
+            PrecisionPolicy::F64
+        } else {
+            PrecisionPolicy::F32
+        }
+    }
+
     /// Process an audio buffer at 64-bit (double) precision.
     ///
     /// This is the f64 equivalent of `process()`. Override this method AND
@@ -850,6 +1307,66 @@ pub trait Processor: HasParameters {
         }
     }
 
+    /// Process a 64-bit main bus with 32-bit auxiliary buses.
+    ///
+    /// Called instead of `process_f64()` when `precision_policy()` returns
+    /// [`PrecisionPolicy::F64MainF32Aux`] and the host runs at 64-bit. The
+    /// main bus is the host's f64 buffer; the wrapper converts auxiliary
+    /// inputs to f32 before the call and auxiliary outputs back afterwards.
+    ///
+    /// # Default Implementation
+    ///
+    /// The default widens the auxiliary buses back to f64 and calls
+    /// `process_f64()`. Like the `process_f64()` fallback it allocates, so
+    /// override this method whenever you return `F64MainF32Aux`.
+    fn process_f64_mixed(
+        &mut self,
+        buffer: &mut Buffer<f64>,
+        aux: &mut AuxiliaryBuffers<f32>,
+        context: &ProcessContext,
+    ) {
+        let num_samples = aux.num_samples();
+        let aux_inputs: Vec<Vec<Vec<f64>>> = (0..aux.num_input_buses())
+            .map(|bus| {
+                aux.input(bus).map_or_else(Vec::new, |input| {
+                    input
+                        .iter_inputs()
+                        .map(|channel| channel.iter().map(|&s| s as f64).collect())
+                        .collect()
+                })
+            })
+            .collect();
+        let mut aux_outputs: Vec<Vec<Vec<f64>>> = (0..aux.num_output_buses())
+            .map(|bus| {
+                let channels = aux.output(bus).map_or(0, |output| output.num_channels());
+                vec![vec![0.0; num_samples]; channels]
+            })
+            .collect();
+
+        {
+            let mut aux_f64 = AuxiliaryBuffers::new(
+                aux_inputs.iter().map(|bus| bus.iter().map(|channel| channel.as_slice())),
+                aux_outputs
+                    .iter_mut()
+                    .map(|bus| bus.iter_mut().map(|channel| channel.as_mut_slice())),
+                num_samples,
+            );
+            self.process_f64(buffer, &mut aux_f64, context);
+        }
+
+        for (bus, channels) in aux_outputs.iter().enumerate() {
+            if let Some(mut output) = aux.output(bus) {
+                for (ch, samples) in channels.iter().enumerate() {
+                    if let Some(out) = output.output_checked(ch) {
+                        for (o, &s) in out.iter_mut().zip(samples) {
+                            *o = s as f32;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Save the plugin state to bytes.
     ///
     /// This is called when the DAW saves a project or preset. The returned
@@ -871,6 +1388,11 @@ pub trait Processor: HasParameters {
     /// The default implementation delegates to `Parameters::load_state()`,
     /// which restores all parameter values. Override this method if you
     /// need to load additional state beyond parameters.
+    ///
+    /// Hosts block while this runs. If part of the state is expensive to
+    /// resolve (e.g. sample files), apply parameters here and hand the rest
+    /// to a [`StateLoader`](crate::state_loading::StateLoader), which resolves
+    /// it on a worker thread while the processor outputs silence.
     fn load_state(&mut self, data: &[u8]) -> PluginResult<()> {
         self.parameters_mut()
             .load_state(data)
@@ -881,12 +1403,28 @@ pub trait Processor: HasParameters {
     // MIDI Processing
     // =========================================================================
 
+    /// Called at the start of every block, before
+    /// [`process_midi`](Self::process_midi), with the block's length.
+    ///
+    /// `process_midi()` doesn't see the block length, which MIDI effects that
+    /// hold events across blocks need (`MidiDelay`, `MidiEcho`,
+    /// `MidiHumanize`). Store it here. `num_samples` is 0 for parameter
+    /// flush blocks. Must be real-time safe.
+    ///
+    /// Default does nothing.
+    fn begin_block(&mut self, _num_samples: usize) {}
+
     /// Process MIDI events.
     ///
     /// Called during processing with any incoming MIDI events. Plugins can
     /// transform events and add them to the output buffer, pass them through
     /// unchanged, or consume them entirely.
     ///
+    /// Hosts also send zero-length "parameter flush" blocks while no audio is
+    /// running. The wrappers apply their parameter changes and call this
+    /// method, but not [`process()`](Self::process), so events arriving then
+    /// aren't lost.
+    ///
     /// # Arguments
     /// * `input` - Slice of incoming MIDI events (sorted by sample_offset)
     /// * `output` - Buffer to write output MIDI events to
@@ -919,6 +1457,70 @@ pub trait Processor: HasParameters {
         false
     }
 
+    /// Returns the MIDI pass-through policy applied by the wrapper.
+    ///
+    /// After [`process_midi`](Self::process_midi) returns, the wrapper
+    /// forwards input events to the output according to this policy and
+    /// keeps the merged output ordered by sample offset. Plugins that use
+    /// [`MidiThru::All`] or [`MidiThru::ExceptConsumed`] should only write
+    /// *generated* events in `process_midi`, not copies of the input.
+    ///
+    /// Default returns [`MidiThru::None`] (no wrapper pass-through).
+    fn midi_thru(&self) -> MidiThru {
+        MidiThru::None
+    }
+
+    /// Returns whether this plugin consumed `event`.
+    ///
+    /// Consulted by [`MidiThru::ExceptConsumed`] after `process_midi` has
+    /// run, so the answer may depend on state updated during processing
+    /// (e.g. keyswitch ranges). Must be real-time safe.
+    ///
+    /// Default treats all note-related events as consumed.
+    fn consumes_midi(&self, event: &MidiEvent) -> bool {
+        event.is_note_event()
+    }
+
+    /// Returns the MIDI clock output policy applied by the wrapper.
+    ///
+    /// After [`process_midi`](Self::process_midi) and MIDI thru, the wrapper
+    /// appends timing clock and start/stop/continue locked to the host
+    /// transport, and/or MMC play/stop, and keeps the output ordered by
+    /// sample offset. See [`crate::midi_clock`].
+    ///
+    /// Default returns a disabled [`MidiClockOutput`] (nothing generated).
+    fn midi_clock_output(&self) -> MidiClockOutput {
+        MidiClockOutput::default()
+    }
+
+    /// Called on a MIDI panic, before the block's [`process_midi`](Self::process_midi).
+    ///
+    /// The wrapper requests it from the hidden MIDI Panic parameter, the
+    /// WebView's `__BEAMER__.midiPanic()` or
+    /// [`MidiPanic::request()`](crate::MidiPanic::request), and puts All
+    /// Sound Off and All Notes Off on every channel ahead of the block's
+    /// MIDI input afterwards. Reset what those controllers don't reach
+    /// (delay lines, arpeggiators, sustain state). Only for plugins that
+    /// [want MIDI](Self::wants_midi). Must be real-time safe.
+    ///
+    /// Default does nothing.
+    fn panic(&mut self) {}
+
+    // =========================================================================
+    // Processing Statistics
+    // =========================================================================
+
+    /// Number of voices currently sounding.
+    ///
+    /// Published as the read-only Voices parameter when
+    /// [`Config::with_processing_stats()`](crate::Config::with_processing_stats)
+    /// is set. The wrapper reads it before every `process()` call, so keep
+    /// it cheap.
+    ///
+    /// Default returns 0.
+    fn active_voices(&self) -> usize {
+        0
+    }
 }
 
 // =============================================================================
@@ -1089,6 +1691,82 @@ pub trait Descriptor: HasParameters + Default {
         }
     }
 
+    /// Identifies the bus layout selected by `parameters`.
+    ///
+    /// For plugins whose buses depend on a user choice, such as a drum
+    /// sampler offering "stereo out" or "16 outs". Read the choice from a
+    /// parameter in the bus methods above and return a value here that
+    /// changes whenever they would report a different layout.
+    ///
+    /// When the value changes after a state load or an edit from the GUI,
+    /// the wrappers ask the host to re-read the buses (VST3 `kIoChanged`,
+    /// AU element count / bus array notifications). The new layout takes
+    /// effect at the next activation, when the processor is rebuilt and
+    /// receives the old one's `save_state()` through `load_state()`. Host
+    /// automation of the parameter does not trigger a change, so mark it
+    /// non-automatable. See [`AmbisonicOrder`](crate::AmbisonicOrder) for
+    /// user-selected ambisonic bus widths.
+    ///
+    /// Default returns 0 (static layout).
+    fn bus_layout_id(parameters: &Self::Parameters) -> u32 {
+        let _ = parameters;
+        0
+    }
+
+    /// Returns true if the plugin can run with `layout` instead of the
+    /// declared buses.
+    ///
+    /// Hosts propose channel counts for each bus (VST3 `setBusArrangements`,
+    /// AU channel configurations), e.g. a mono track for a stereo effect or
+    /// an unconnected sidechain. The declared layout is always accepted;
+    /// other proposals are put to this method. `layout` has the declared
+    /// number of buses, and an aux bus with 0 channels is unused. An
+    /// accepted layout is what `prepare()` sees as [`BusLayout`] and
+    /// [`MainInputChannels`]/[`MainOutputChannels`].
+    ///
+    /// ```ignore
+    /// fn accepts_bus_layout(&self, layout: &BusLayout) -> bool {
+    ///     // Mono or stereo main, sidechain mono, stereo or unconnected
+    ///     layout.main_is_mono_or_stereo() && layout.aux_within(&BusLayout::from_plugin(self))
+    /// }
+    /// ```
+    ///
+    /// Default returns false (only the declared layout).
+    fn accepts_bus_layout(&self, layout: &BusLayout) -> bool {
+        let _ = layout;
+        false
+    }
+
+    /// Returns the oversampling factor selected by `parameters`.
+    ///
+    /// Usually reads an [`OversamplingParameter`](crate::OversamplingParameter).
+    /// The wrappers prepare the processor at the host rate times the factor
+    /// and resample around `process()`; `setup` in [`prepare()`](Self::prepare)
+    /// carries the oversampled rate. When the value changes, the processor
+    /// is prepared again outside the audio thread and the host is told the
+    /// latency changed (see the [`oversampling`](crate::oversampling) module).
+    ///
+    /// Default returns [`Oversampling::Off`].
+    fn oversampling(parameters: &Self::Parameters) -> Oversampling {
+        let _ = parameters;
+        Oversampling::Off
+    }
+
+    /// Returns the quality mode selected by `parameters`.
+    ///
+    /// Usually reads a [`QualityParameter`](crate::QualityParameter). The
+    /// processor reads the mode by adding [`Quality`] to its
+    /// [`Setup`](Self::Setup). When the value changes, the processor is
+    /// prepared again outside the audio thread, like a changed
+    /// [`oversampling()`](Self::oversampling) factor (see the
+    /// [`quality`](crate::quality) module).
+    ///
+    /// Default returns [`Quality::Normal`].
+    fn quality(parameters: &Self::Parameters) -> Quality {
+        let _ = parameters;
+        Quality::Normal
+    }
+
     /// Returns whether this plugin processes MIDI events.
     ///
     /// Override to return `true` if your plugin needs MIDI input/output.
@@ -1178,6 +1856,333 @@ pub trait Descriptor: HasParameters + Default {
         None
     }
 
+    // =========================================================================
+    // MPE (MIDI Polyphonic Expression)
+    // =========================================================================
+
+    /// Returns the MPE zones the plugin plays.
+    ///
+    /// Queried once when the wrapper is created. The wrapper then turns
+    /// member channel pitch bend, channel pressure and CC 74 into per-note
+    /// `NoteExpressionValue` events (tuning, expression, brightness) and
+    /// gives every MPE note its own note ID before `process_midi()`, so a
+    /// synth handles MPE and VST3 note expressions the same way. See
+    /// [`crate::mpe`].
+    ///
+    /// ```ignore
+    /// fn mpe_config(&self) -> Option<MpeConfig> {
+    ///     Some(MpeConfig::lower_zone(15).with_pitch_bend_range(48.0))
+    /// }
+    /// ```
+    ///
+    /// Default returns `None` (MIDI channels are passed through).
+    fn mpe_config(&self) -> Option<MpeConfig> {
+        None
+    }
+
+    // =========================================================================
+    // Note Ranges (playable range and keyboard splits)
+    // =========================================================================
+
+    /// Returns the playable note range and keyboard splits.
+    ///
+    /// Queried once when the wrapper is created. The GUI reads the ranges
+    /// through a built-in invoke (the `<beamer-keyboard>` element shades
+    /// unplayable keys and colors the splits), VST3 hosts see split names as
+    /// pitch names, and with [`NoteRanges::filtering()`] the wrapper drops
+    /// notes outside the range before `process_midi()`. See
+    /// [`crate::note_ranges`].
+    ///
+    /// ```ignore
+    /// fn note_ranges(&self) -> NoteRanges {
+    ///     NoteRanges::new(28, 67).with_split("Open strings", 28, 43).filtering()
+    /// }
+    /// ```
+    ///
+    /// Default covers all 128 notes, with no splits and no filtering.
+    fn note_ranges(&self) -> NoteRanges {
+        NoteRanges::default()
+    }
+
+    // =========================================================================
+    // MIDI Response Curves
+    // =========================================================================
+
+    /// Default response curves for note velocity and MIDI controllers.
+    ///
+    /// Queried once when the wrapper is created. Curves reshape incoming
+    /// velocities and controller values (including the ones emulated through
+    /// [`midi_cc_config()`](Self::midi_cc_config)) before `process_midi()`
+    /// and `ProcessContext::midi_cc()` see them. The GUI can change them
+    /// through built-in invokes, and changed curves are saved with the state.
+    ///
+    /// ```ignore
+    /// fn response_curves(&self) -> ResponseCurves {
+    ///     ResponseCurves::new()
+    ///         .with_curve(CurveTarget::Velocity, CurveShape::Power { exponent: 0.7 })
+    /// }
+    /// ```
+    ///
+    /// Default returns no curves; the GUI can still add some.
+    fn response_curves(&self) -> ResponseCurves {
+        ResponseCurves::new()
+    }
+
+    // =========================================================================
+    // Controller Pages (hardware knob mapping)
+    // =========================================================================
+
+    /// Pages of up to eight parameters for hardware controller knobs.
+    ///
+    /// Queried once when the wrapper is created. On VST3 the knob CCs map to
+    /// the selected page's parameters via `IMidiMapping`; the GUI can list
+    /// and select pages through built-in invokes, and the selected page is
+    /// saved with the state. See [`crate::controller_pages`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn controller_pages(&self) -> ControllerPages {
+    ///     ControllerPages::new()
+    ///         .with_page(ControllerPage::new("Oscillator").with_parameters(&["wave", "detune", "pulse_width"]))
+    ///         .with_page(ControllerPage::new("Filter").with_parameters(&["cutoff", "resonance"]))
+    /// }
+    /// ```
+    ///
+    /// Default returns no pages.
+    fn controller_pages(&self) -> ControllerPages {
+        ControllerPages::new()
+    }
+
+    // =========================================================================
+    // Parameter Constraints
+    // =========================================================================
+
+    /// Constraints between parameters, such as attack not exceeding release.
+    ///
+    /// Queried once when the wrapper is created. The wrapper enforces the
+    /// constraints whenever a parameter is set (automation, host, GUI,
+    /// state and preset loads), clamping the changed parameter or pushing
+    /// the other one, and reports adjusted values back to the host and GUI.
+    /// See [`crate::parameter_constraints`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn parameter_constraints(&self) -> ParameterConstraints {
+    ///     ParameterConstraints::new()
+    ///         .with(Constraint::ordered("attack", "release"))
+    ///         .with(Constraint::ordered("low_freq", "high_freq").with_min_gap(10.0).pushing())
+    /// }
+    /// ```
+    ///
+    /// Default returns no constraints.
+    fn parameter_constraints(&self) -> ParameterConstraints {
+        ParameterConstraints::new()
+    }
+
+    // =========================================================================
+    // Autosave (crash recovery)
+    // =========================================================================
+
+    /// Interval for crash-safe autosave of the plugin state.
+    ///
+    /// Queried once when the wrapper is created. When set, the wrapper
+    /// snapshots `save_state()` at most this often into a per-instance temp
+    /// file and, after a host crash, offers the unsaved state back through
+    /// the GUI. See [`crate::autosave`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn autosave_interval(&self) -> Option<Duration> {
+    ///     Some(Duration::from_secs(10))
+    /// }
+    /// ```
+    ///
+    /// Default returns `None` (no autosave).
+    fn autosave_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    // =========================================================================
+    // Parameter Log (session debugging)
+    // =========================================================================
+
+    /// Log of parameter changes for session debugging.
+    ///
+    /// Queried once when the wrapper is created. When enabled, the wrapper
+    /// records every parameter change with its source (automation, host,
+    /// GUI, MIDI CC, preset, state) and a timestamp, and the GUI can dump
+    /// the log. Keep a clone to record changes the plugin makes itself,
+    /// e.g. from its own MIDI learn. See [`crate::parameter_log`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn parameter_log(&self) -> ParameterLog {
+    ///     self.log.clone() // ParameterLog::new(1024), created with the descriptor
+    /// }
+    /// ```
+    ///
+    /// Default returns a disabled log.
+    fn parameter_log(&self) -> ParameterLog {
+        ParameterLog::default()
+    }
+
+    // =========================================================================
+    // MIDI Monitor (diagnostics)
+    // =========================================================================
+
+    /// Monitor of the MIDI going in and out of the plugin.
+    ///
+    /// Queried once when the wrapper is created. When enabled, the wrapper
+    /// records every event the host delivers, before any framework
+    /// processing, and every event the plugin outputs, so the GUI can show
+    /// what the host actually sends. See [`crate::midi_monitor`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn midi_monitor(&self) -> MidiMonitor {
+    ///     self.midi_monitor.clone() // MidiMonitor::new(512), created with the descriptor
+    /// }
+    /// ```
+    ///
+    /// Default returns a disabled monitor.
+    fn midi_monitor(&self) -> MidiMonitor {
+        MidiMonitor::default()
+    }
+
+    /// MIDI panic trigger, listed to hosts as the hidden MIDI Panic
+    /// parameter.
+    ///
+    /// Queried once when the wrapper is created. Override to keep a clone
+    /// and call [`MidiPanic::request()`] from plugin code. See
+    /// [`crate::midi_panic`].
+    ///
+    /// Default is enabled when the plugin [wants MIDI](Self::wants_midi).
+    fn midi_panic(&self) -> MidiPanic {
+        MidiPanic::enabled(self.wants_midi())
+    }
+
+    // =========================================================================
+    // Host Services
+    // =========================================================================
+
+    /// Handle for notifying the host, e.g. that non-parameter state changed.
+    ///
+    /// Queried once when the wrapper is created. Keep a clone (and pass it
+    /// to the processor in `prepare()`) and call
+    /// [`HostServices::mark_state_dirty()`] after loading a sample or other
+    /// state the host can't see, so the project prompts to save. The same
+    /// handle reports the host-assigned instance name and carries the
+    /// display name the plugin suggests. See [`crate::host_services`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn host_services(&self) -> HostServices {
+    ///     self.host.clone()
+    /// }
+    /// ```
+    ///
+    /// Default returns a handle the plugin has no clone of.
+    fn host_services(&self) -> HostServices {
+        HostServices::default()
+    }
+
+    // =========================================================================
+    // Activation
+    // =========================================================================
+
+    /// License or activation check for commercial plugins.
+    ///
+    /// Queried once when the wrapper is created, which then runs the check
+    /// on a background thread. Until it succeeds, the wrapper applies the
+    /// activation's [`ActivationPolicy`](crate::ActivationPolicy) to the
+    /// output, and the GUI can show the result. See [`crate::activation`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn activation(&self) -> Activation {
+    ///     Activation::new(|| license::verify(&license::path()))
+    ///         .with_policy(ActivationPolicy::Noise)
+    /// }
+    /// ```
+    ///
+    /// Default returns an activation without a check (always activated).
+    fn activation(&self) -> Activation {
+        Activation::default()
+    }
+
+    // =========================================================================
+    // Frame Meters
+    // =========================================================================
+
+    /// Meter values the WebView receives on every display refresh.
+    ///
+    /// Queried once when the wrapper is created. Keep a clone (and pass it
+    /// to the processor in `prepare()`) and write levels from `process()`;
+    /// `__BEAMER__.onFrame()` subscribers get the latest values with each
+    /// frame tick. See [`crate::frame_meters`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn frame_meters(&self) -> FrameMeters {
+    ///     self.meters.clone() // FrameMeters::new().with_peak("inputL")...
+    /// }
+    /// ```
+    ///
+    /// Default returns meters without slots (frames carry only the time).
+    fn frame_meters(&self) -> FrameMeters {
+        FrameMeters::default()
+    }
+
+    // =========================================================================
+    // GUI Data Channels
+    // =========================================================================
+
+    /// Frame streams (spectra, waveforms) for the WebView GUI.
+    ///
+    /// Queried once when the wrapper is created. The wrapper drains the
+    /// channels from its GUI sync timer at their rate and delivers the
+    /// frames as `audio-data` events. Keep clones of the channels (and pass
+    /// them to the processor in `prepare()`) to push into. See
+    /// [`crate::gui_data`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn gui_data(&self) -> GuiDataChannels {
+    ///     self.gui_data.clone() // GuiDataChannels::new().with_channel("spectrum", &self.spectrum)
+    /// }
+    /// ```
+    ///
+    /// Default returns no channels.
+    fn gui_data(&self) -> GuiDataChannels {
+        GuiDataChannels::default()
+    }
+
+    // =========================================================================
+    // Notifications
+    // =========================================================================
+
+    /// Queue for messages shown to the user in the WebView GUI.
+    ///
+    /// Queried once when the wrapper is created. Keep a clone (and pass it
+    /// to the processor in `prepare()`) and post file-load failures or
+    /// overload warnings with [`Notifications::notify()`]; the wrapper
+    /// delivers them as `beamer:notification` events and posts its own
+    /// errors to the same queue. See [`crate::notifications`].
+    ///
+    /// Default returns a new queue, used by the wrapper alone.
+    fn notifications(&self) -> Notifications {
+        Notifications::default()
+    }
+
     // =========================================================================
     // MIDI Learn (IMidiLearn)
     // =========================================================================
@@ -1384,6 +2389,28 @@ pub trait Descriptor: HasParameters + Default {
         true
     }
 
+    // =========================================================================
+    // Editor Backend
+    // =========================================================================
+
+    /// The GUI backend: the WebView from `Config.toml`, or a native editor.
+    ///
+    /// Queried once when the wrapper is created. Native editors still need
+    /// `has_gui = true` in `Config.toml`; their size comes from the editor.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// fn editor(&self) -> GuiBackend {
+    ///     GuiBackend::native(|| NativeGuiDelegate::new(Size::new(400, 300), draw_ui))
+    /// }
+    /// ```
+    ///
+    /// Default returns [`GuiBackend::WebView`].
+    fn editor(&self) -> GuiBackend {
+        GuiBackend::WebView
+    }
+
     // =========================================================================
     // WebView Handler (custom JS invoke/event handling)
     // =========================================================================
@@ -87,6 +87,32 @@ pub trait FactoryPresets: Send + Sync + 'static {
     /// Each value contains a parameter hash ID and its plain value.
     fn values(index: usize) -> &'static [PresetValue];
 
+    /// Writes the display name of a preset into `out`.
+    ///
+    /// Override for names computed at runtime, such as user presets scanned
+    /// from disk. `out` is cleared and reused by the wrappers' name cache, so
+    /// write with `push_str` rather than replacing the string. The default
+    /// copies [`info()`](Self::info).
+    ///
+    /// Returns `false` if `index >= count()`.
+    fn write_name(index: usize, out: &mut String) -> bool {
+        match Self::info(index) {
+            Some(info) => {
+                out.push_str(info.name);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Version of the preset names.
+    ///
+    /// Increment whenever names written by [`write_name()`](Self::write_name)
+    /// change, so cached names are rebuilt on the next host query.
+    fn names_version() -> u64 {
+        0
+    }
+
     /// Applies a preset to the given parameters.
     ///
     /// Only the parameters specified in the preset are modified.
@@ -195,6 +221,7 @@ mod tests {
                     default_normalized: 0.0,
                     flags: ParameterFlags::default(),
                     group_id: 0,
+                    randomize_range: (0.0, 1.0),
                 },
             }
         }
@@ -539,6 +566,20 @@ mod tests {
         assert!((params.mix.get_value() - 0.5).abs() < f64::EPSILON); // Unchanged
     }
 
+    #[test]
+    fn test_state_hash_detects_changes_since_preset() {
+        let params = MockParameters::new();
+        TestPresets::apply(0, &params);
+        let preset_hash = params.state_hash();
+
+        params.gain.set_normalized(0.75);
+        assert_ne!(params.state_hash(), preset_hash);
+
+        // Moving the value back is no change
+        params.gain.set_normalized(0.5);
+        assert_eq!(params.state_hash(), preset_hash);
+    }
+
     #[test]
     fn test_presets_apply_invalid_index_returns_false() {
         let params = MockParameters::new();
@@ -0,0 +1,523 @@
+//! Preset banks: all user presets in one shareable `.beamerbank` file.
+//!
+//! Users share complete sound sets, not single presets. A [`PresetBank`]
+//! packs the presets of the user directory of [`PresetFolders`] (with
+//! their bank subfolders) and some metadata into one file, and imports such
+//! a file back with a [`MergeStrategy`] for presets that already exist:
+//!
+//! ```ignore
+//! let info = BankInfo::new(&CONFIG, STATE_VERSION).with_name("Factory Pads");
+//! folders.export_bank(Path::new("/Users/me/Desktop/Pads.beamerbank"), &info)?;
+//!
+//! let bank = PresetBank::read(path)?
+//!     .migrate(STATE_VERSION, |from, data| upgrade_state(from, data))?;
+//! let report = folders.import_bank(bank, MergeStrategy::Rename)?;
+//! ```
+//!
+//! # Versioning
+//!
+//! The file carries its own format version ([`BANK_FORMAT_VERSION`]);
+//! files from a newer Beamer are rejected. Separately, the bank records the
+//! plugin's state version ([`BankInfo::state_version`]), the number a plugin
+//! bumps when its `save_state()` layout changes. [`PresetBank::migrate()`]
+//! upgrades the preset data of older banks before importing and rejects
+//! banks saved by a newer plugin version.
+//!
+//! # File Layout
+//!
+//! `"BEAMBANK"`, the format version and the header length (`u32`, little
+//! endian), a JSON header with the metadata and the preset list, then the
+//! preset data back to back in list order.
+//!
+//! # GUI
+//!
+//! [`preset_bank_invoke()`] handles `"presetBank/export"` and
+//! `"presetBank/import"` calls from the plugin's `WebViewHandler::on_invoke()`.
+//! Imports rescan the folders, which emits the `"beamer:presetFolders"`
+//! event to the attached WebView.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::logging::sanitize_file_name;
+use crate::preset_folders::{PresetEntry, PresetFolders};
+
+/// File extension of preset banks (without the dot).
+pub const BANK_EXTENSION: &str = "beamerbank";
+
+/// Version of the bank file layout written by this Beamer.
+pub const BANK_FORMAT_VERSION: u32 = 1;
+
+/// WebView invoke exporting the user presets: `(path, { name, author, description })`.
+pub const EXPORT_BANK_INVOKE: &str = "presetBank/export";
+
+/// WebView invoke importing a bank: `(path, "skip" | "overwrite" | "rename")`.
+pub const IMPORT_BANK_INVOKE: &str = "presetBank/import";
+
+const BANK_MAGIC: &[u8; 8] = b"BEAMBANK";
+
+/// Largest header accepted when reading, to reject garbage early.
+const MAX_HEADER_SIZE: usize = 16 * 1024 * 1024;
+
+/// How deep bank subfolders may be nested (as in [`PresetFolders`]).
+const MAX_BANK_DEPTH: usize = 4;
+
+/// Bank metadata.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BankInfo {
+    /// Bank name shown to users.
+    pub name: String,
+    /// Who made the presets.
+    pub author: String,
+    /// Free text.
+    pub description: String,
+    /// Name of the plugin the presets belong to.
+    pub plugin: String,
+    /// The plugin's state version when the bank was saved.
+    pub state_version: u32,
+}
+
+impl BankInfo {
+    /// Metadata for banks of the plugin described by `config`, whose
+    /// `save_state()` layout is at `state_version`.
+    pub fn new(config: &Config, state_version: u32) -> Self {
+        Self {
+            plugin: config.name.to_string(),
+            state_version,
+            ..Self::default()
+        }
+    }
+
+    /// Set the bank name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set the author.
+    pub fn with_author(mut self, author: &str) -> Self {
+        self.author = author.to_string();
+        self
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+}
+
+/// One preset in a bank.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BankPreset {
+    /// Preset name (file name without the extension).
+    pub name: String,
+    /// Subfolder path, `""` at the top level.
+    pub bank: String,
+    /// The preset file's contents.
+    pub data: Vec<u8>,
+    /// Last modification time of the exported file.
+    pub modified: Option<SystemTime>,
+}
+
+/// A set of presets with metadata, as stored in a `.beamerbank` file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PresetBank {
+    /// Metadata.
+    pub info: BankInfo,
+    /// When the bank was created.
+    pub created: Option<SystemTime>,
+    /// The presets, in file order.
+    pub presets: Vec<BankPreset>,
+}
+
+/// What to do when an imported preset already exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Keep the existing preset and drop the imported one.
+    Skip,
+    /// Replace the existing preset.
+    Overwrite,
+    /// Import as `"<name> (2)"`, `"<name> (3)"`, ...
+    #[default]
+    Rename,
+}
+
+impl MergeStrategy {
+    /// Parse `"skip"`, `"overwrite"` or `"rename"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(Self::Skip),
+            "overwrite" => Some(Self::Overwrite),
+            "rename" => Some(Self::Rename),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of [`PresetFolders::import_bank()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BankImportReport {
+    /// Presets written, under their final names.
+    pub imported: Vec<PresetEntry>,
+    /// Existing presets that were replaced (a subset of `imported`).
+    pub overwritten: usize,
+    /// Presets saved under a new name (a subset of `imported`).
+    pub renamed: usize,
+    /// Names (`bank/name`) of presets skipped because they existed.
+    pub skipped: Vec<String>,
+}
+
+impl BankImportReport {
+    /// `{ "imported", "overwritten", "renamed", "skipped" }` with
+    /// `imported` and `skipped` as counts.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "imported": self.imported.len(),
+            "overwritten": self.overwritten,
+            "renamed": self.renamed,
+            "skipped": self.skipped.len(),
+        })
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn to_seconds(time: Option<SystemTime>) -> Option<f64> {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs_f64())
+}
+
+fn from_seconds(value: &serde_json::Value) -> Option<SystemTime> {
+    let seconds = value.as_f64().filter(|s| s.is_finite() && *s >= 0.0)?;
+    Some(UNIX_EPOCH + Duration::from_secs_f64(seconds))
+}
+
+impl PresetBank {
+    /// Serialize the bank.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let presets: Vec<_> = self
+            .presets
+            .iter()
+            .map(|preset| {
+                serde_json::json!({
+                    "name": preset.name,
+                    "bank": preset.bank,
+                    "size": preset.data.len(),
+                    "modified": to_seconds(preset.modified),
+                })
+            })
+            .collect();
+        let header = serde_json::json!({
+            "name": self.info.name,
+            "author": self.info.author,
+            "description": self.info.description,
+            "plugin": self.info.plugin,
+            "stateVersion": self.info.state_version,
+            "created": to_seconds(self.created),
+            "presets": presets,
+        })
+        .to_string();
+
+        let payload: usize = self.presets.iter().map(|p| p.data.len()).sum();
+        let mut bytes = Vec::with_capacity(16 + header.len() + payload);
+        bytes.extend_from_slice(BANK_MAGIC);
+        bytes.extend_from_slice(&BANK_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for preset in &self.presets {
+            bytes.extend_from_slice(&preset.data);
+        }
+        bytes
+    }
+
+    /// Parse a serialized bank.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.len() < 16 || &bytes[..8] != BANK_MAGIC {
+            return Err(invalid("not a preset bank"));
+        }
+        let format = u32::from_le_bytes(bytes[8..12].try_into().unwrap_or_default());
+        if format > BANK_FORMAT_VERSION {
+            return Err(invalid(format!(
+                "preset bank format {} is newer than the supported {}",
+                format, BANK_FORMAT_VERSION
+            )));
+        }
+        let header_len = u32::from_le_bytes(bytes[12..16].try_into().unwrap_or_default()) as usize;
+        let header_end = 16usize.saturating_add(header_len);
+        if header_len > MAX_HEADER_SIZE || header_end > bytes.len() {
+            return Err(invalid("truncated preset bank header"));
+        }
+        let header: serde_json::Value =
+            serde_json::from_slice(&bytes[16..header_end]).map_err(|e| invalid(e.to_string()))?;
+        let text = |key: &str| header[key].as_str().unwrap_or_default().to_string();
+
+        let mut offset = header_end;
+        let mut presets = Vec::new();
+        for preset in header["presets"].as_array().map(Vec::as_slice).unwrap_or_default() {
+            let size = preset["size"].as_u64().unwrap_or(0) as usize;
+            let end = offset.checked_add(size).filter(|&end| end <= bytes.len());
+            let Some(end) = end else {
+                return Err(invalid("truncated preset bank data"));
+            };
+            presets.push(BankPreset {
+                name: preset["name"].as_str().unwrap_or_default().to_string(),
+                bank: preset["bank"].as_str().unwrap_or_default().to_string(),
+                data: bytes[offset..end].to_vec(),
+                modified: from_seconds(&preset["modified"]),
+            });
+            offset = end;
+        }
+
+        Ok(Self {
+            info: BankInfo {
+                name: text("name"),
+                author: text("author"),
+                description: text("description"),
+                plugin: text("plugin"),
+                state_version: header["stateVersion"].as_u64().unwrap_or(0) as u32,
+            },
+            created: from_seconds(&header["created"]),
+            presets,
+        })
+    }
+
+    /// Read a bank file.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Write the bank to `path` (through a temporary file).
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp, self.to_bytes())?;
+        fs::rename(&temp, path).inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })
+    }
+
+    /// Bring the preset data up to `state_version`.
+    ///
+    /// Banks saved at an older state version have `upgrade(version, data)`
+    /// applied to every preset, with the version the bank was saved at.
+    /// Banks from a newer plugin version are rejected, current ones are
+    /// returned unchanged.
+    pub fn migrate(
+        mut self,
+        state_version: u32,
+        mut upgrade: impl FnMut(u32, &[u8]) -> io::Result<Vec<u8>>,
+    ) -> io::Result<Self> {
+        let saved = self.info.state_version;
+        if saved > state_version {
+            return Err(invalid(format!(
+                "preset bank was saved by a newer plugin version (state {} > {})",
+                saved, state_version
+            )));
+        }
+        if saved < state_version {
+            for preset in &mut self.presets {
+                preset.data = upgrade(saved, &preset.data)?;
+            }
+            self.info.state_version = state_version;
+        }
+        Ok(self)
+    }
+}
+
+impl PresetFolders {
+    /// Collect the presets of the user directory into a bank.
+    pub fn to_bank(&self, info: &BankInfo) -> io::Result<PresetBank> {
+        let user_directory = self.user_directory();
+        let mut presets = Vec::new();
+        for entry in self.scan().into_iter().filter(|entry| entry.folder == user_directory) {
+            presets.push(BankPreset {
+                data: fs::read(&entry.path)?,
+                name: entry.name,
+                bank: entry.bank,
+                modified: entry.modified,
+            });
+        }
+        Ok(PresetBank {
+            info: info.clone(),
+            created: Some(SystemTime::now()),
+            presets,
+        })
+    }
+
+    /// Export all user presets to a bank file. Returns the number of
+    /// presets written.
+    pub fn export_bank(&self, path: &Path, info: &BankInfo) -> io::Result<usize> {
+        let bank = self.to_bank(info)?;
+        bank.write(path)?;
+        Ok(bank.presets.len())
+    }
+
+    /// Import a bank into the user directory, keeping its subfolders.
+    ///
+    /// Run [`PresetBank::migrate()`] first when the plugin's state layout
+    /// has changed. Folder names from the bank are sanitized, so a bank
+    /// can't write outside the user directory.
+    pub fn import_bank(&self, bank: PresetBank, strategy: MergeStrategy) -> io::Result<BankImportReport> {
+        let user_directory = self.user_directory();
+        let extension = self.extension();
+        let mut report = BankImportReport::default();
+        for preset in bank.presets {
+            let folder = bank_folder(&preset.bank)
+                .iter()
+                .fold(user_directory.clone(), |path, part| path.join(part));
+            let mut name = sanitize_file_name(preset.name.trim());
+            if name.is_empty() {
+                name = "Untitled".to_string();
+            }
+            let existing = folder.join(format!("{}.{}", name, extension));
+
+            if existing.exists() {
+                match strategy {
+                    MergeStrategy::Skip => {
+                        report.skipped.push(format!("{}/{}", preset.bank, name).trim_start_matches('/').to_string());
+                        continue;
+                    }
+                    MergeStrategy::Overwrite => {
+                        // Reading marks the file as known, so save() replaces it
+                        let root = user_directory.clone();
+                        self.read(&PresetEntry {
+                            name: name.clone(),
+                            bank: preset.bank.clone(),
+                            path: existing,
+                            folder: root,
+                            shared: false,
+                            modified: None,
+                        })?;
+                        report.overwritten += 1;
+                    }
+                    MergeStrategy::Rename => {
+                        name = (2..)
+                            .map(|n| format!("{} ({})", name, n))
+                            .find(|candidate| !folder.join(format!("{}.{}", candidate, extension)).exists())
+                            .unwrap_or(name);
+                        report.renamed += 1;
+                    }
+                }
+            }
+            report.imported.push(self.save(&folder, &name, &preset.data)?);
+        }
+        Ok(report)
+    }
+}
+
+/// Sanitized subfolder names of a bank path, at most [`MAX_BANK_DEPTH`] deep.
+fn bank_folder(bank: &str) -> Vec<String> {
+    bank.split(['/', '\\'])
+        .map(|part| sanitize_file_name(part.trim()))
+        .filter(|part| !part.is_empty() && part.chars().any(|c| c != '.'))
+        .take(MAX_BANK_DEPTH)
+        .collect()
+}
+
+/// Handle the preset bank WebView calls.
+///
+/// Call from `WebViewHandler::on_invoke()`; returns `None` if `method` is
+/// not a preset bank method. `info` supplies the plugin name and current
+/// state version: exports take the name, author and description from the
+/// call, and imports reject banks of other plugins or newer state versions.
+pub fn preset_bank_invoke(
+    folders: &PresetFolders,
+    info: &BankInfo,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<Result<serde_json::Value, String>> {
+    let path = args.first().and_then(|v| v.as_str()).map(Path::new);
+    let result = match method {
+        EXPORT_BANK_INVOKE => path.ok_or_else(|| "missing bank path".to_string()).and_then(|path| {
+            let meta = args.get(1);
+            let field = |key: &str| meta.and_then(|m| m[key].as_str()).unwrap_or_default().to_string();
+            let info = BankInfo {
+                name: field("name"),
+                author: field("author"),
+                description: field("description"),
+                ..info.clone()
+            };
+            folders.export_bank(path, &info).map(|count| count.into()).map_err(|e| e.to_string())
+        }),
+        IMPORT_BANK_INVOKE => path.ok_or_else(|| "missing bank path".to_string()).and_then(|path| {
+            let strategy = args.get(1).and_then(|v| v.as_str()).map_or(Some(MergeStrategy::default()), MergeStrategy::from_name);
+            let strategy = strategy.ok_or_else(|| "unknown merge strategy".to_string())?;
+            let bank = PresetBank::read(path).map_err(|e| e.to_string())?;
+            if !bank.info.plugin.is_empty() && !info.plugin.is_empty() && bank.info.plugin != info.plugin {
+                return Err(format!("the bank is for {}", bank.info.plugin));
+            }
+            let bank = bank
+                .migrate(info.state_version, |_, data| Ok(data.to_vec()))
+                .map_err(|e| e.to_string())?;
+            folders.import_bank(bank, strategy).map(|report| report.to_json()).map_err(|e| e.to_string())
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_directory(name: &str) -> std::path::PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("beamer-preset-bank-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn test_export_import_round_trip_with_strategies() {
+        let directory = temp_directory("round-trip");
+        let source = PresetFolders::with_user_directory(directory.join("source"));
+        source.save(&source.user_directory(), "Init", b"init").unwrap();
+        source.save(&source.user_directory().join("Pads"), "Warm", b"warm").unwrap();
+
+        let info = BankInfo { plugin: "Synth".into(), state_version: 2, ..Default::default() }.with_name("Mine");
+        let file = directory.join("mine.beamerbank");
+        assert_eq!(source.export_bank(&file, &info).unwrap(), 2);
+        let bank = PresetBank::read(&file).unwrap();
+        assert_eq!(bank.info, info);
+        assert_eq!((bank.presets[1].bank.as_str(), bank.presets[1].data.as_slice()), ("Pads", &b"warm"[..]));
+
+        let target = PresetFolders::with_user_directory(directory.join("target"));
+        target.save(&target.user_directory(), "Init", b"old").unwrap();
+        let report = target.import_bank(bank.clone(), MergeStrategy::Skip).unwrap();
+        assert_eq!((report.imported.len(), report.skipped.len()), (1, 1));
+
+        let report = target.import_bank(bank.clone(), MergeStrategy::Rename).unwrap();
+        assert_eq!(report.renamed, 2);
+        assert!(target.scan().iter().any(|e| e.name == "Init (2)"));
+
+        target.import_bank(bank, MergeStrategy::Overwrite).unwrap();
+        assert_eq!(fs::read(target.user_directory().join("Init.preset")).unwrap(), b"init");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_versions_and_sanitized_folders() {
+        let bank = PresetBank {
+            info: BankInfo { state_version: 1, ..Default::default() },
+            created: None,
+            presets: vec![BankPreset { name: "A".into(), bank: "../../x".into(), data: vec![1], modified: None }],
+        };
+        let mut bytes = bank.to_bytes();
+        assert_eq!(PresetBank::from_bytes(&bytes).unwrap(), bank);
+        assert!(PresetBank::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        bytes[8] = BANK_FORMAT_VERSION as u8 + 1;
+        assert!(PresetBank::from_bytes(&bytes).is_err());
+
+        let migrated = bank.clone().migrate(3, |from, data| Ok([data, &[from as u8]].concat())).unwrap();
+        assert_eq!((migrated.info.state_version, migrated.presets[0].data.as_slice()), (3, &[1u8, 1][..]));
+        assert!(bank.migrate(0, |_, data| Ok(data.to_vec())).is_err());
+
+        assert_eq!(bank_folder("../../x"), ["x"]);
+        assert_eq!(bank_folder("Pads\\Warm/"), ["Pads", "Warm"]);
+    }
+}
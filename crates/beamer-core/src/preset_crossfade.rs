@@ -0,0 +1,270 @@
+//! Declicking for preset and program changes.
+//!
+//! Applying a preset mid-playback jumps every parameter at once, which
+//! usually clicks. [`PresetCrossfade`] lets the format wrappers defer the
+//! change to the audio thread and apply it inside a short fade: the output
+//! fades out while the old parameters are still in effect, the preset is
+//! applied at silence, and the output fades back in with the new parameters.
+//!
+//! Enabled per plugin via [`Config::with_preset_crossfade`](crate::Config::with_preset_crossfade)
+//! (or `preset_crossfade_ms` in `Config.toml`). Plugin code does not interact
+//! with this type directly.
+//!
+//! # Threading
+//!
+//! [`request`](PresetCrossfade::request) may be called from any thread (host
+//! preset menus run on the main thread). [`begin`](PresetCrossfade::begin)
+//! and [`finish`](PresetCrossfade::finish) run on the audio thread, once per
+//! block, around the plugin's `process()` call. All state is atomic and no
+//! method allocates.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+
+use crate::buffer::Buffer;
+use crate::sample::Sample;
+
+/// Sentinel for "no preset pending".
+const NO_PRESET: usize = usize::MAX;
+
+const PHASE_IDLE: u8 = 0;
+const PHASE_FADING_OUT: u8 = 1;
+const PHASE_SILENT: u8 = 2;
+const PHASE_FADING_IN: u8 = 3;
+
+/// Deferred, faded preset application driven by the format wrapper.
+///
+/// The configured duration is split evenly between fade-out and fade-in.
+/// When the duration is zero the crossfade is disabled and wrappers apply
+/// presets immediately, as before.
+#[derive(Debug)]
+pub struct PresetCrossfade {
+    /// Preset index waiting to be applied (`NO_PRESET` if none).
+    pending: AtomicUsize,
+    /// Preset index captured when the fade-out started.
+    target: AtomicUsize,
+    /// Length of each fade half in samples (0 = disabled).
+    fade_samples: AtomicU32,
+    /// Current phase (`PHASE_*`).
+    phase: AtomicU8,
+    /// Position within the current fade, in samples.
+    position: AtomicU32,
+    /// Set when a preset was applied on the audio thread; consumed by
+    /// wrappers that need to notify the host from another thread.
+    applied: AtomicBool,
+}
+
+impl Default for PresetCrossfade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresetCrossfade {
+    /// Create a disabled crossfade. Call [`prepare`](Self::prepare) once the
+    /// sample rate is known.
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicUsize::new(NO_PRESET),
+            target: AtomicUsize::new(NO_PRESET),
+            fade_samples: AtomicU32::new(0),
+            phase: AtomicU8::new(PHASE_IDLE),
+            position: AtomicU32::new(0),
+            applied: AtomicBool::new(false),
+        }
+    }
+
+    /// Configure the total crossfade duration for `sample_rate`.
+    ///
+    /// Resets any fade in progress. A pending request is kept and will be
+    /// faded in on the next block.
+    pub fn prepare(&self, duration_ms: f32, sample_rate: f64) {
+        let half_seconds = (duration_ms.max(0.0) as f64 / 1000.0) / 2.0;
+        let samples = (half_seconds * sample_rate).round().max(0.0) as u32;
+        self.fade_samples.store(samples, Ordering::Relaxed);
+        self.phase.store(PHASE_IDLE, Ordering::Relaxed);
+        self.position.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns true if preset changes should go through [`request`](Self::request).
+    pub fn is_enabled(&self) -> bool {
+        self.fade_samples.load(Ordering::Relaxed) > 0
+    }
+
+    /// Returns true while a fade is running or a preset is pending.
+    pub fn is_active(&self) -> bool {
+        self.phase.load(Ordering::Relaxed) != PHASE_IDLE
+            || self.pending.load(Ordering::Acquire) != NO_PRESET
+    }
+
+    /// Request a preset change. Safe to call from any thread.
+    ///
+    /// If several requests arrive before the preset is applied, the last
+    /// one wins.
+    pub fn request(&self, index: usize) {
+        self.pending.store(index, Ordering::Release);
+    }
+
+    /// Start a block. Returns the preset index to apply *before* calling
+    /// the plugin's `process()`, if the fade-out has just completed.
+    pub fn begin(&self) -> Option<usize> {
+        match self.phase.load(Ordering::Relaxed) {
+            PHASE_IDLE => {
+                if self.pending.load(Ordering::Acquire) != NO_PRESET {
+                    self.phase.store(PHASE_FADING_OUT, Ordering::Relaxed);
+                    self.position.store(0, Ordering::Relaxed);
+                }
+                None
+            }
+            PHASE_SILENT => {
+                // A request that arrived during the fade-out supersedes the
+                // one that started it.
+                let latest = self.pending.swap(NO_PRESET, Ordering::AcqRel);
+                let index = if latest != NO_PRESET {
+                    latest
+                } else {
+                    self.target.load(Ordering::Relaxed)
+                };
+                self.target.store(NO_PRESET, Ordering::Relaxed);
+                self.phase.store(PHASE_FADING_IN, Ordering::Relaxed);
+                self.position.store(0, Ordering::Relaxed);
+                if index == NO_PRESET {
+                    return None;
+                }
+                self.applied.store(true, Ordering::Release);
+                Some(index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Finish a block by applying the fade gain to the plugin's output.
+    pub fn finish<S: Sample>(&self, buffer: &mut Buffer<S>) {
+        let phase = self.phase.load(Ordering::Relaxed);
+        if phase == PHASE_IDLE {
+            return;
+        }
+
+        let fade = self.fade_samples.load(Ordering::Relaxed).max(1);
+        let start = self.position.load(Ordering::Relaxed);
+        let num_samples = buffer.num_samples();
+
+        let gain_at = |i: usize| -> f64 {
+            let pos = start as u64 + i as u64;
+            let t = (pos as f64 / fade as f64).min(1.0);
+            match phase {
+                PHASE_FADING_OUT => 1.0 - t,
+                PHASE_FADING_IN => t,
+                _ => 0.0,
+            }
+        };
+
+        for output in buffer.outputs_mut() {
+            for (i, sample) in output.iter_mut().enumerate() {
+                *sample = *sample * S::from_f64(gain_at(i));
+            }
+        }
+
+        let end = start.saturating_add(num_samples as u32);
+        if end < fade {
+            self.position.store(end, Ordering::Relaxed);
+            return;
+        }
+
+        match phase {
+            PHASE_FADING_OUT => {
+                let index = self.pending.swap(NO_PRESET, Ordering::AcqRel);
+                if index != NO_PRESET {
+                    self.target.store(index, Ordering::Relaxed);
+                }
+                self.phase.store(PHASE_SILENT, Ordering::Relaxed);
+            }
+            PHASE_FADING_IN => self.phase.store(PHASE_IDLE, Ordering::Relaxed),
+            _ => {}
+        }
+        self.position.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns true once after a preset was applied by [`begin`](Self::begin).
+    ///
+    /// Lets wrappers notify the host of the new parameter values from a
+    /// non-audio thread.
+    pub fn take_applied(&self) -> bool {
+        self.applied.swap(false, Ordering::AcqRel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run one block of constant 1.0 through the crossfade and return the
+    /// output plus any preset applied at block start.
+    fn run_block(fade: &PresetCrossfade, len: usize) -> (Vec<f32>, Option<usize>) {
+        let input = vec![1.0f32; len];
+        let mut output = vec![1.0f32; len];
+        let applied = fade.begin();
+        {
+            let mut buffer = Buffer::new([input.as_slice()], [output.as_mut_slice()], len);
+            fade.finish(&mut buffer);
+        }
+        (output, applied)
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let fade = PresetCrossfade::new();
+        assert!(!fade.is_enabled());
+        assert!(!fade.is_active());
+    }
+
+    #[test]
+    fn test_prepare_splits_duration() {
+        let fade = PresetCrossfade::new();
+        // 10 ms at 1 kHz = 10 samples, 5 per half.
+        fade.prepare(10.0, 1000.0);
+        assert!(fade.is_enabled());
+        assert_eq!(fade.fade_samples.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_fade_out_apply_fade_in() {
+        let fade = PresetCrossfade::new();
+        fade.prepare(8.0, 1000.0); // 4 samples per half
+        fade.request(3);
+
+        // Fade-out block: gain falls from 1 toward 0, then stays silent.
+        let (out, applied) = run_block(&fade, 6);
+        assert_eq!(applied, None);
+        assert_eq!(out[0], 1.0);
+        assert!(out[1] < out[0] && out[2] < out[1]);
+        assert_eq!(&out[4..], &[0.0, 0.0]);
+
+        // Next block applies the preset and fades in.
+        let (out, applied) = run_block(&fade, 6);
+        assert_eq!(applied, Some(3));
+        assert!(fade.take_applied());
+        assert!(!fade.take_applied());
+        assert_eq!(out[0], 0.0);
+        assert!(out[1] > out[0]);
+        assert_eq!(&out[4..], &[1.0, 1.0]);
+
+        // Back to idle: unity gain, nothing pending.
+        let (out, applied) = run_block(&fade, 4);
+        assert_eq!(applied, None);
+        assert_eq!(out, vec![1.0; 4]);
+        assert!(!fade.is_active());
+    }
+
+    #[test]
+    fn test_latest_request_wins() {
+        let fade = PresetCrossfade::new();
+        fade.prepare(4.0, 1000.0); // 2 samples per half
+        fade.request(1);
+        run_block(&fade, 1); // fade-out in progress
+        fade.request(2);
+        run_block(&fade, 4); // fade-out completes
+        fade.request(5);
+        let (_, applied) = run_block(&fade, 4);
+        assert_eq!(applied, Some(5));
+    }
+}
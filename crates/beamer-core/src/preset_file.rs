@@ -0,0 +1,449 @@
+//! Host preset files: Steinberg `.vstpreset` and Apple `.aupreset`.
+//!
+//! Users trade presets as the files their host writes from its own preset
+//! menu. [`PresetFile`] reads and writes both formats, so a preset saved in
+//! Cubase loads from a Logic session's GUI and the other way round:
+//!
+//! ```ignore
+//! let preset = PresetFile::new("Warm Pad", processor.save_state()?);
+//! preset.write(Path::new("/Users/me/Desktop/Warm Pad.vstpreset"), &CONFIG)?;
+//!
+//! let preset = PresetFile::read(Path::new("Bright Lead.aupreset"), &CONFIG)?;
+//! processor.load_state(&preset.data)?;
+//! ```
+//!
+//! The preset data is the plugin state exactly as the wrapper saves it for
+//! the host (`save_state()` output plus the framework's trailers), which is
+//! what a host stores in the file. Reading checks that the file belongs to
+//! this plugin: the VST3 class ID, or the AU manufacturer and subtype.
+//!
+//! # Formats
+//!
+//! - **`.vstpreset`** - `"VST3"`, version 1, the class ID as 32 hex digits
+//!   and the offset of the chunk list, followed by the component state
+//!   (`Comp`), an `Info` chunk with the plugin name and category, and the
+//!   chunk list (all little endian).
+//! - **`.aupreset`** - An XML property list with the AU class info keys
+//!   (`type`, `subtype`, `manufacturer`, `version`, `name`) and the state
+//!   under `data` (read by AUv2) and `beamerState` (read by AUv3). Binary
+//!   property lists are not supported.
+//!
+//! # GUI Invoke
+//!
+//! Both wrappers handle these built-in calls. The format follows the file
+//! extension.
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`EXPORT_PRESET_FILE_INVOKE`] | `(path, name?)` | `null`; the name defaults to the file name |
+//! | [`IMPORT_PRESET_FILE_INVOKE`] | `(path)` | The preset name |
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::Config;
+
+/// WebView invoke saving the current state to a preset file: `(path, name?)`.
+pub const EXPORT_PRESET_FILE_INVOKE: &str = "_beamer/exportPresetFile";
+
+/// WebView invoke loading a preset file: `(path)`, returns the preset name.
+pub const IMPORT_PRESET_FILE_INVOKE: &str = "_beamer/importPresetFile";
+
+const VST3_MAGIC: &[u8; 4] = b"VST3";
+const VST3_FORMAT_VERSION: i32 = 1;
+/// Magic, version, class ID and chunk list offset.
+const VST3_HEADER_SIZE: usize = 4 + 4 + 32 + 8;
+const CHUNK_LIST_ID: &[u8; 4] = b"List";
+const COMPONENT_CHUNK_ID: &[u8; 4] = b"Comp";
+const INFO_CHUNK_ID: &[u8; 4] = b"Info";
+
+/// A host preset file format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresetFileFormat {
+    /// Steinberg `.vstpreset`.
+    Vst3,
+    /// Apple `.aupreset`.
+    Au,
+}
+
+impl PresetFileFormat {
+    /// File extension (without the dot).
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Vst3 => "vstpreset",
+            Self::Au => "aupreset",
+        }
+    }
+
+    /// The format of a path, by its extension (case-insensitive).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        [Self::Vst3, Self::Au]
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+}
+
+/// A named plugin state in a host preset file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PresetFile {
+    /// Preset name. `.vstpreset` files store none; [`read()`](Self::read)
+    /// takes it from the file name.
+    pub name: String,
+    /// The plugin state, as the wrapper saves it for the host.
+    pub data: Vec<u8>,
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl PresetFile {
+    /// A preset with the given name and state.
+    pub fn new(name: &str, data: Vec<u8>) -> Self {
+        Self {
+            name: name.to_string(),
+            data,
+        }
+    }
+
+    /// Read a preset file, in the format of its extension.
+    pub fn read(path: &Path, config: &Config) -> io::Result<Self> {
+        let format = format_of(path)?;
+        let bytes = fs::read(path)?;
+        let mut preset = Self::from_bytes(format, &bytes, config)?;
+        if preset.name.is_empty() {
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+            preset.name = stem.to_string();
+        }
+        Ok(preset)
+    }
+
+    /// Write the preset, in the format of the path's extension.
+    pub fn write(&self, path: &Path, config: &Config) -> io::Result<()> {
+        let format = format_of(path)?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_bytes(format, config))
+    }
+
+    /// Serialize to `format`.
+    pub fn to_bytes(&self, format: PresetFileFormat, config: &Config) -> Vec<u8> {
+        match format {
+            PresetFileFormat::Vst3 => self.to_vstpreset(config),
+            PresetFileFormat::Au => self.to_aupreset(config),
+        }
+    }
+
+    /// Parse a file in `format`, checking that it belongs to the plugin.
+    pub fn from_bytes(format: PresetFileFormat, bytes: &[u8], config: &Config) -> io::Result<Self> {
+        match format {
+            PresetFileFormat::Vst3 => Self::from_vstpreset(bytes, config),
+            PresetFileFormat::Au => Self::from_aupreset(bytes, config),
+        }
+    }
+
+    fn to_vstpreset(&self, config: &Config) -> Vec<u8> {
+        let info = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<MetaInfo>\n{}{}{}</MetaInfo>\n",
+            meta_attribute("MediaType", "VstPreset"),
+            meta_attribute("PlugInName", config.name),
+            meta_attribute("PlugInCategory", &config.vst3_subcategories()),
+        );
+        let chunks = [(COMPONENT_CHUNK_ID, self.data.as_slice()), (INFO_CHUNK_ID, info.as_bytes())];
+
+        let mut out = Vec::with_capacity(VST3_HEADER_SIZE + self.data.len() + info.len() + 64);
+        out.extend_from_slice(VST3_MAGIC);
+        out.extend_from_slice(&VST3_FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(vst3_class_id(config).as_bytes());
+        let list_offset = VST3_HEADER_SIZE + chunks.iter().map(|(_, data)| data.len()).sum::<usize>();
+        out.extend_from_slice(&(list_offset as i64).to_le_bytes());
+
+        let mut entries = Vec::with_capacity(chunks.len());
+        for (id, data) in chunks {
+            entries.push((id, out.len(), data.len()));
+            out.extend_from_slice(data);
+        }
+        out.extend_from_slice(CHUNK_LIST_ID);
+        out.extend_from_slice(&(entries.len() as i32).to_le_bytes());
+        for (id, offset, size) in entries {
+            out.extend_from_slice(id);
+            out.extend_from_slice(&(offset as i64).to_le_bytes());
+            out.extend_from_slice(&(size as i64).to_le_bytes());
+        }
+        out
+    }
+
+    fn from_vstpreset(bytes: &[u8], config: &Config) -> io::Result<Self> {
+        if bytes.len() < VST3_HEADER_SIZE || &bytes[..4] != VST3_MAGIC {
+            return Err(invalid("not a .vstpreset file"));
+        }
+        let class_id = std::str::from_utf8(&bytes[8..40]).map_err(|_| invalid("invalid class ID"))?;
+        if !class_id.eq_ignore_ascii_case(&vst3_class_id(config)) {
+            return Err(invalid(format!("the preset is for another plugin (class {})", class_id)));
+        }
+
+        let list = to_range(read_i64(bytes, 40)?, 8, bytes.len())?;
+        if &bytes[list.start..list.start + 4] != CHUNK_LIST_ID {
+            return Err(invalid("missing chunk list"));
+        }
+        let count = i32::from_le_bytes(bytes[list.start + 4..list.start + 8].try_into().expect("4 bytes"));
+        for index in 0..count.max(0) as usize {
+            let entry = list.start + 8 + index * 20;
+            if entry + 20 > bytes.len() {
+                return Err(invalid("truncated chunk list"));
+            }
+            if &bytes[entry..entry + 4] == COMPONENT_CHUNK_ID {
+                let offset = read_i64(bytes, entry + 4)?;
+                let size = read_i64(bytes, entry + 12)?;
+                let start = to_range(offset, 0, bytes.len())?.start;
+                let end = to_range(offset.saturating_add(size), 0, bytes.len())?.start;
+                if end < start {
+                    return Err(invalid("invalid chunk size"));
+                }
+                return Ok(Self::new("", bytes[start..end].to_vec()));
+            }
+        }
+        Err(invalid("the preset has no component state"))
+    }
+
+    fn to_aupreset(&self, config: &Config) -> Vec<u8> {
+        let data = base64_encode(&self.data);
+        let integer = |key: &str, value: u32| format!("\t<key>{key}</key>\n\t<integer>{}</integer>\n", value as i32);
+        let mut out = String::with_capacity(data.len() * 2 + 512);
+        out.push_str(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+            "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+            "<plist version=\"1.0\">\n<dict>\n",
+        ));
+        // Keys in sorted order, as Apple's serializer writes them
+        out.push_str(&format!("\t<key>beamerState</key>\n\t<data>{data}</data>\n"));
+        out.push_str(&format!("\t<key>data</key>\n\t<data>{data}</data>\n"));
+        out.push_str(&integer("manufacturer", config.manufacturer_u32()));
+        out.push_str(&format!("\t<key>name</key>\n\t<string>{}</string>\n", xml_escape(&self.name)));
+        out.push_str(&integer("subtype", config.subtype_u32()));
+        out.push_str(&integer("type", config.category.to_au_component_type()));
+        out.push_str(&integer("version", 0));
+        out.push_str("</dict>\n</plist>\n");
+        out.into_bytes()
+    }
+
+    fn from_aupreset(bytes: &[u8], config: &Config) -> io::Result<Self> {
+        if bytes.starts_with(b"bplist") {
+            return Err(invalid("binary property lists are not supported"));
+        }
+        let xml = std::str::from_utf8(bytes).map_err(|_| invalid("not a .aupreset file"))?;
+        if !xml.contains("<plist") {
+            return Err(invalid("not a .aupreset file"));
+        }
+
+        for (key, expected) in [("manufacturer", config.manufacturer_u32()), ("subtype", config.subtype_u32())] {
+            let value = plist_value(xml, key, "integer").and_then(|value| value.trim().parse::<i64>().ok());
+            if value.is_some_and(|value| value as u32 != expected) {
+                return Err(invalid(format!("the preset is for another plugin ({} differs)", key)));
+            }
+        }
+
+        let data = ["data", "beamerState", "beamer-state"]
+            .into_iter()
+            .find_map(|key| plist_value(xml, key, "data"))
+            .ok_or_else(|| invalid("the preset has no state data"))?;
+        let data = base64_decode(data).ok_or_else(|| invalid("invalid state data"))?;
+        let name = plist_value(xml, "name", "string").map(xml_unescape).unwrap_or_default();
+        Ok(Self { name, data })
+    }
+}
+
+/// Handle the built-in [`EXPORT_PRESET_FILE_INVOKE`] and
+/// [`IMPORT_PRESET_FILE_INVOKE`] WebView calls.
+///
+/// `save_state` returns the state as the wrapper saves it for the host
+/// (empty while unprepared); `load_state` applies one as a host state load
+/// would. Returns `None` for other methods.
+pub fn preset_file_invoke(
+    config: &Config,
+    method: &str,
+    args: &[serde_json::Value],
+    save_state: impl FnOnce() -> Vec<u8>,
+    load_state: impl FnOnce(&[u8]) -> Result<(), String>,
+) -> Option<Result<serde_json::Value, String>> {
+    let path = args.first().and_then(|v| v.as_str()).map(Path::new);
+    let result = match method {
+        EXPORT_PRESET_FILE_INVOKE => path.ok_or_else(|| "missing preset path".to_string()).and_then(|path| {
+            let data = save_state();
+            if data.is_empty() {
+                return Err("the plugin has no state to save yet".to_string());
+            }
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+            let name = args.get(1).and_then(|v| v.as_str()).unwrap_or(stem);
+            PresetFile::new(name, data)
+                .write(path, config)
+                .map(|()| serde_json::Value::Null)
+                .map_err(|e| e.to_string())
+        }),
+        IMPORT_PRESET_FILE_INVOKE => path.ok_or_else(|| "missing preset path".to_string()).and_then(|path| {
+            let preset = PresetFile::read(path, config).map_err(|e| e.to_string())?;
+            load_state(&preset.data)?;
+            Ok(preset.name.into())
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn format_of(path: &Path) -> io::Result<PresetFileFormat> {
+    PresetFileFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}: expected a .vstpreset or .aupreset file", path.display()),
+        )
+    })
+}
+
+/// The VST3 class ID as preset files store it: 32 uppercase hex digits.
+///
+/// Steinberg's `FUID::toString()` prints the same digits on every platform.
+fn vst3_class_id(config: &Config) -> String {
+    config.vst3_uid_parts().iter().map(|part| format!("{:08X}", part)).collect()
+}
+
+fn meta_attribute(id: &str, value: &str) -> String {
+    format!(
+        "\t<Attribute id=\"{}\" value=\"{}\" type=\"string\" flags=\"writeProtected\"></Attribute>\n",
+        id,
+        xml_escape(value)
+    )
+}
+
+fn read_i64(bytes: &[u8], at: usize) -> io::Result<i64> {
+    bytes
+        .get(at..at + 8)
+        .map(|b| i64::from_le_bytes(b.try_into().expect("8 bytes")))
+        .ok_or_else(|| invalid("truncated header"))
+}
+
+/// Check that `len` bytes at `offset` lie within a file of `file_len` bytes.
+fn to_range(offset: i64, len: usize, file_len: usize) -> io::Result<std::ops::Range<usize>> {
+    usize::try_from(offset)
+        .ok()
+        .filter(|&start| start.checked_add(len).is_some_and(|end| end <= file_len))
+        .map(|start| start..start + len)
+        .ok_or_else(|| invalid("offset out of range"))
+}
+
+/// The text of the `<tag>` element following `<key>key</key>`.
+fn plist_value<'a>(xml: &'a str, key: &str, tag: &str) -> Option<&'a str> {
+    let key_element = format!("<key>{}</key>", key);
+    let after_key = &xml[xml.find(&key_element)? + key_element.len()..];
+    let after_key = after_key.trim_start();
+    let open = format!("<{}>", tag);
+    let content = after_key.strip_prefix(open.as_str())?;
+    let close = format!("</{}>", tag);
+    Some(&content[..content.find(&close)?])
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(triple >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode base64, skipping whitespace (property lists wrap long data).
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.bytes().filter(|b| !b.is_ascii_whitespace()) {
+        if byte == b'=' {
+            break;
+        }
+        let value = BASE64_ALPHABET.iter().position(|&c| c == byte)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Category;
+
+    static CONFIG: Config = Config::new("Test Synth", Category::Instrument, "Bmer", "tsyn");
+    static OTHER: Config = Config::new("Other", Category::Instrument, "Bmer", "othr");
+
+    #[test]
+    fn test_round_trip_both_formats() {
+        let preset = PresetFile::new("Warm <Pad> & Co", (0..=255).collect());
+        for format in [PresetFileFormat::Vst3, PresetFileFormat::Au] {
+            let bytes = preset.to_bytes(format, &CONFIG);
+            let read = PresetFile::from_bytes(format, &bytes, &CONFIG).unwrap();
+            assert_eq!(read.data, preset.data);
+            assert!(PresetFile::from_bytes(format, &bytes, &OTHER).is_err());
+        }
+        let au = PresetFile::from_bytes(PresetFileFormat::Au, &preset.to_bytes(PresetFileFormat::Au, &CONFIG), &CONFIG);
+        assert_eq!(au.unwrap().name, preset.name);
+
+        let vst3 = preset.to_bytes(PresetFileFormat::Vst3, &CONFIG);
+        assert_eq!(&vst3[..4], b"VST3");
+        assert_eq!(std::str::from_utf8(&vst3[8..40]).unwrap(), vst3_class_id(&CONFIG));
+        assert_eq!(&vst3[VST3_HEADER_SIZE..VST3_HEADER_SIZE + 256], preset.data.as_slice());
+    }
+
+    #[test]
+    fn test_invoke_export_import_and_base64() {
+        let directory = std::env::temp_dir().join(format!("beamer-preset-file-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let path = directory.join("Lead.aupreset");
+        let path_arg = [serde_json::json!(path.to_str().unwrap())];
+
+        let result = preset_file_invoke(&CONFIG, EXPORT_PRESET_FILE_INVOKE, &path_arg, || b"state".to_vec(), |_| Ok(()));
+        assert_eq!(result, Some(Ok(serde_json::Value::Null)));
+        let mut loaded = Vec::new();
+        let result = preset_file_invoke(&CONFIG, IMPORT_PRESET_FILE_INVOKE, &path_arg, Vec::new, |data| {
+            loaded = data.to_vec();
+            Ok(())
+        });
+        assert_eq!(result, Some(Ok(serde_json::json!("Lead"))));
+        assert_eq!(loaded, b"state");
+        assert!(preset_file_invoke(&CONFIG, "other", &path_arg, Vec::new, |_| Ok(())).is_none());
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(base64_encode(b"beamer!"), "YmVhbWVyIQ==");
+        assert_eq!(base64_decode("YmVh\n\tbWVy\nIQ==").unwrap(), b"beamer!");
+    }
+}
@@ -0,0 +1,644 @@
+//! User preset folders, including shared and synced ones.
+//!
+//! Teams keep preset banks in network shares or cloud-synced folders so every
+//! machine sees the same presets. [`PresetFolders`] handles the parts that
+//! go wrong there:
+//!
+//! - **Search paths** - Besides the per-user directory, users add folders of
+//!   their own. They are remembered in `search_paths.json` in the user
+//!   directory.
+//! - **Change watching** - [`PresetFolders::watch()`] rescans in the
+//!   background and emits a `"beamer:presetFolders"` event when presets
+//!   appear, change or disappear, so the GUI can refresh its browser. The
+//!   rescan also updates the in-memory list behind [`PresetFolders::count()`]
+//!   and [`PresetFolders::write_name()`] and bumps
+//!   [`PresetFolders::version()`]; a `FactoryPresets` implementation built on
+//!   them has its host program list refreshed by the wrappers.
+//! - **Conflict-safe writes** - [`PresetFolders::save()`] writes through a
+//!   temporary file and never replaces a preset that changed on disk since
+//!   this instance last read or wrote it. It saves a
+//!   `"<name> (conflict N)"` copy instead.
+//!
+//! Preset files hold whatever bytes the plugin hands over, usually
+//! `save_state()` output. Presets are files with the configured extension
+//! (`preset` by default); subfolders become banks.
+//!
+//! | Platform | User directory |
+//! |----------|----------------|
+//! | macOS    | `~/Library/Audio/Presets/<vendor>/<plugin>` |
+//! | Windows  | `%APPDATA%\<vendor>\<plugin>\Presets` |
+//! | Linux    | `$XDG_DATA_HOME/<vendor>/<plugin>/presets` (or `~/.local/share`) |
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Descriptor (shared with the WebView handler)
+//! let folders = PresetFolders::new(&CONFIG);
+//! folders.watch(Duration::from_secs(2));
+//!
+//! // WebView handler
+//! folders.attach_webview(handle);
+//! folders.add_search_path("/Volumes/Studio/Presets/MyPlugin")?;
+//! let list = folders.to_json();
+//! let entry = folders.save(&folders.user_directory(), "Warm Pad", &state)?;
+//!
+//! // Host program list
+//! impl FactoryPresets for UserPresets {
+//!     fn count() -> usize { FOLDERS.count() }
+//!     fn write_name(index: usize, out: &mut String) -> bool { FOLDERS.write_name(index, out) }
+//!     fn names_version() -> u64 { FOLDERS.version() }
+//!     // ...
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::logging::sanitize_file_name;
+#[cfg(feature = "webview-bridge")]
+use crate::webview_handle::WebViewHandle;
+
+/// Event emitted to the WebView when the preset list changes.
+pub const PRESET_FOLDERS_EVENT: &str = "beamer:presetFolders";
+
+/// Default preset file extension.
+pub const DEFAULT_PRESET_EXTENSION: &str = "preset";
+
+/// File in the user directory that stores the search paths.
+const SEARCH_PATHS_FILE: &str = "search_paths.json";
+
+/// How deep banks may be nested.
+const MAX_BANK_DEPTH: usize = 4;
+
+/// Get the per-user preset directory for a plugin, if it can be determined.
+pub fn user_preset_directory(vendor: &str, plugin: &str) -> Option<PathBuf> {
+    let (vendor, plugin) = (sanitize_file_name(vendor), sanitize_file_name(plugin));
+    let env_path = |key: &str| std::env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| {
+            home.join("Library").join("Audio").join("Presets").join(vendor).join(plugin)
+        })
+    } else if cfg!(target_os = "windows") {
+        env_path("APPDATA").map(|dir| dir.join(vendor).join(plugin).join("Presets"))
+    } else {
+        env_path("XDG_DATA_HOME")
+            .or_else(|| env_path("HOME").map(|home| home.join(".local").join("share")))
+            .map(|dir| dir.join(vendor).join(plugin).join("presets"))
+    }
+}
+
+/// A preset file found by [`PresetFolders::scan()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresetEntry {
+    /// File name without the extension.
+    pub name: String,
+    /// Subfolder path relative to the folder, `""` at the top level.
+    pub bank: String,
+    /// Full path of the file.
+    pub path: PathBuf,
+    /// The folder the preset was found in.
+    pub folder: PathBuf,
+    /// True for presets from a search path rather than the user directory.
+    pub shared: bool,
+    /// Last modification time.
+    pub modified: Option<SystemTime>,
+}
+
+impl PresetEntry {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "bank": self.bank,
+            "path": self.path.to_string_lossy(),
+            "folder": self.folder.to_string_lossy(),
+            "shared": self.shared,
+            "modified": self.modified
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs_f64()),
+        })
+    }
+}
+
+/// What a file looked like when this instance last read or wrote it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self { modified: metadata.modified().ok(), len: metadata.len() })
+    }
+}
+
+struct Inner {
+    user_directory: PathBuf,
+    extension: String,
+    search_paths: Mutex<Vec<PathBuf>>,
+    /// Versions this instance has read or written, by path.
+    known: Mutex<HashMap<PathBuf, FileStamp>>,
+    /// Listing from the last scan, to detect changes.
+    listing: Mutex<Vec<(PathBuf, Option<FileStamp>)>>,
+    /// Presets found by the last rescan.
+    entries: Mutex<Vec<PresetEntry>>,
+    version: AtomicU64,
+    watching: AtomicBool,
+    #[cfg(feature = "webview-bridge")]
+    webview: Mutex<Option<WebViewHandle>>,
+}
+
+/// User and shared preset folders of a plugin.
+///
+/// Cheap to clone; keep one in the descriptor and hand clones to the
+/// WebView handler. All methods do file IO, so never call them from
+/// `process()`.
+#[derive(Clone)]
+pub struct PresetFolders {
+    inner: Arc<Inner>,
+}
+
+impl PresetFolders {
+    /// Preset folders for a plugin, with the platform's user directory (see
+    /// the module docs) and the saved search paths.
+    pub fn new(config: &Config) -> Self {
+        let directory = user_preset_directory(config.vendor, config.name)
+            .unwrap_or_else(|| std::env::temp_dir().join("beamer-presets").join(sanitize_file_name(config.name)));
+        Self::with_user_directory(directory)
+    }
+
+    /// Preset folders with an explicit user directory.
+    pub fn with_user_directory(directory: impl Into<PathBuf>) -> Self {
+        let user_directory = directory.into();
+        let search_paths = fs::read(user_directory.join(SEARCH_PATHS_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Vec<PathBuf>>(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            inner: Arc::new(Inner {
+                user_directory,
+                extension: DEFAULT_PRESET_EXTENSION.to_string(),
+                search_paths: Mutex::new(search_paths),
+                known: Mutex::new(HashMap::new()),
+                listing: Mutex::new(Vec::new()),
+                entries: Mutex::new(Vec::new()),
+                version: AtomicU64::new(0),
+                watching: AtomicBool::new(false),
+                #[cfg(feature = "webview-bridge")]
+                webview: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Use a different preset file extension (without the dot).
+    ///
+    /// Call right after creating the folders, before cloning them.
+    pub fn with_extension(mut self, extension: &str) -> Self {
+        match Arc::get_mut(&mut self.inner) {
+            Some(inner) => inner.extension = extension.trim_start_matches('.').to_string(),
+            None => log::warn!("PresetFolders::with_extension() called on shared folders"),
+        }
+        self
+    }
+
+    /// The preset file extension (without the dot).
+    pub fn extension(&self) -> &str {
+        &self.inner.extension
+    }
+
+    /// The per-user preset directory. Created on the first save.
+    pub fn user_directory(&self) -> PathBuf {
+        self.inner.user_directory.clone()
+    }
+
+    // =========================================================================
+    // Search Paths
+    // =========================================================================
+
+    /// Additional folders searched for presets, in order.
+    pub fn search_paths(&self) -> Vec<PathBuf> {
+        lock(&self.inner.search_paths).clone()
+    }
+
+    /// Replace the search paths and remember them for the next session.
+    pub fn set_search_paths(&self, paths: Vec<PathBuf>) -> io::Result<()> {
+        let mut unique: Vec<PathBuf> = Vec::with_capacity(paths.len());
+        for path in paths {
+            if path != self.inner.user_directory && !unique.contains(&path) {
+                unique.push(path);
+            }
+        }
+        let json = serde_json::to_vec_pretty(&unique).map_err(io::Error::other)?;
+        fs::create_dir_all(&self.inner.user_directory)?;
+        write_atomic(&self.inner.user_directory.join(SEARCH_PATHS_FILE), &json)?;
+        *lock(&self.inner.search_paths) = unique;
+        self.refresh();
+        Ok(())
+    }
+
+    /// Add a search path.
+    pub fn add_search_path(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+        let mut paths = self.search_paths();
+        paths.push(path.into());
+        self.set_search_paths(paths)
+    }
+
+    /// Remove a search path. Its presets are left untouched.
+    pub fn remove_search_path(&self, path: &Path) -> io::Result<()> {
+        let mut paths = self.search_paths();
+        paths.retain(|p| p != path);
+        self.set_search_paths(paths)
+    }
+
+    // =========================================================================
+    // Presets
+    // =========================================================================
+
+    /// List the presets of all folders, user directory first. Unreachable
+    /// folders (an unmounted share, say) are skipped.
+    pub fn scan(&self) -> Vec<PresetEntry> {
+        let mut entries = Vec::new();
+        let folders = std::iter::once((self.inner.user_directory.clone(), false))
+            .chain(self.search_paths().into_iter().map(|path| (path, true)));
+        for (folder, shared) in folders {
+            scan_folder(&folder, &folder, shared, &self.inner.extension, 0, &mut entries);
+        }
+        entries
+    }
+
+    /// Read a preset, remembering its version for conflict detection.
+    pub fn read(&self, entry: &PresetEntry) -> io::Result<Vec<u8>> {
+        let stamp = FileStamp::of(&entry.path);
+        let data = fs::read(&entry.path)?;
+        if let Some(stamp) = stamp {
+            lock(&self.inner.known).insert(entry.path.clone(), stamp);
+        }
+        Ok(data)
+    }
+
+    /// Save a preset as `name` in `folder` (a bank subfolder is fine).
+    ///
+    /// The file is written to a temporary file and moved into place, so
+    /// readers on other machines never see a partial preset. An existing
+    /// preset is only replaced if this instance read or wrote it and it
+    /// hasn't changed since; otherwise the data goes to a new
+    /// `"<name> (conflict N)"` file, which the returned entry names.
+    pub fn save(&self, folder: &Path, name: &str, data: &[u8]) -> io::Result<PresetEntry> {
+        fs::create_dir_all(folder)?;
+        let base = sanitize_file_name(name.trim());
+        let base = if base.is_empty() { "Untitled".to_string() } else { base };
+        let target = folder.join(format!("{}.{}", base, self.inner.extension));
+        let temp = folder.join(format!(".{}.{}.tmp", base, std::process::id()));
+        write_synced(&temp, data)?;
+
+        let replaceable = {
+            let known = lock(&self.inner.known);
+            match (FileStamp::of(&target), known.get(&target)) {
+                (None, _) => None,
+                (Some(current), Some(seen)) => Some(current == *seen),
+                (Some(_), None) => Some(false),
+            }
+        };
+        let result = match replaceable {
+            Some(true) => fs::rename(&temp, &target).map(|()| target),
+            // Link instead of rename so a file created elsewhere in the
+            // meantime isn't replaced.
+            _ => link_new(&temp, data, folder, &base, &self.inner.extension, replaceable.is_none()),
+        };
+        let _ = fs::remove_file(&temp);
+        let path = result?;
+
+        if let Some(stamp) = FileStamp::of(&path) {
+            lock(&self.inner.known).insert(path.clone(), stamp);
+        }
+        self.refresh();
+        let root = self.folder_of(&path);
+        Ok(entry_for(&path, &root, root != self.inner.user_directory))
+    }
+
+    /// Delete a preset.
+    pub fn delete(&self, entry: &PresetEntry) -> io::Result<()> {
+        fs::remove_file(&entry.path)?;
+        lock(&self.inner.known).remove(&entry.path);
+        self.refresh();
+        Ok(())
+    }
+
+    /// Incremented whenever the preset list changes. Use it as
+    /// `FactoryPresets::names_version()` when presets are listed in the host.
+    pub fn version(&self) -> u64 {
+        self.inner.version.load(Ordering::Acquire)
+    }
+
+    /// The presets found by the last rescan, in [`scan()`](Self::scan)
+    /// order. Empty until [`watch()`](Self::watch) or
+    /// [`refresh()`](Self::refresh) ran.
+    pub fn presets(&self) -> Vec<PresetEntry> {
+        lock(&self.inner.entries).clone()
+    }
+
+    /// The preset at `index` of [`presets()`](Self::presets).
+    pub fn entry(&self, index: usize) -> Option<PresetEntry> {
+        lock(&self.inner.entries).get(index).cloned()
+    }
+
+    /// Number of presets found by the last rescan. Use it as
+    /// `FactoryPresets::count()` when presets are listed in the host.
+    pub fn count(&self) -> usize {
+        lock(&self.inner.entries).len()
+    }
+
+    /// Write the host display name of the preset at `index`: `"bank/name"`,
+    /// or the name alone at the top level. Doesn't touch the disk, so it
+    /// can back `FactoryPresets::write_name()`.
+    ///
+    /// Returns `false` if `index >= count()`.
+    pub fn write_name(&self, index: usize, out: &mut String) -> bool {
+        let entries = lock(&self.inner.entries);
+        let Some(entry) = entries.get(index) else { return false };
+        if !entry.bank.is_empty() {
+            out.push_str(&entry.bank);
+            out.push('/');
+        }
+        out.push_str(&entry.name);
+        true
+    }
+
+    // =========================================================================
+    // Watching and GUI Events
+    // =========================================================================
+
+    /// Rescan every `interval` in the background and notify the WebView when
+    /// the preset list changes. Calling it again does nothing.
+    ///
+    /// The thread ends when the last clone is dropped.
+    pub fn watch(&self, interval: Duration) {
+        if self.inner.watching.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.refresh();
+        let inner = Arc::downgrade(&self.inner);
+        let spawned = std::thread::Builder::new()
+            .name("beamer-preset-watch".into())
+            .spawn(move || watch_loop(inner, interval.max(Duration::from_millis(100))));
+        if let Err(e) = spawned {
+            log::warn!("Preset folder watcher not started: {e}");
+        }
+    }
+
+    /// Rescan now. Returns true (and notifies the WebView) if the preset
+    /// list changed since the last rescan.
+    pub fn refresh(&self) -> bool {
+        let entries = self.scan();
+        let listing: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.path.clone(), FileStamp::of(&entry.path)))
+            .collect();
+        {
+            let mut previous = lock(&self.inner.listing);
+            if *previous == listing {
+                return false;
+            }
+            *previous = listing;
+            *lock(&self.inner.entries) = entries;
+        }
+        self.inner.version.fetch_add(1, Ordering::AcqRel);
+        self.notify();
+        true
+    }
+
+    /// Attach a WebView handle to receive preset list events.
+    #[cfg(feature = "webview-bridge")]
+    pub fn attach_webview(&self, handle: WebViewHandle) {
+        *lock(&self.inner.webview) = Some(handle);
+    }
+
+    /// Detach the WebView handle. Further updates are not emitted.
+    #[cfg(feature = "webview-bridge")]
+    pub fn detach_webview(&self) {
+        *lock(&self.inner.webview) = None;
+    }
+
+    /// Emit the current preset list to the attached WebView.
+    pub fn notify(&self) {
+        #[cfg(feature = "webview-bridge")]
+        {
+            let webview = lock(&self.inner.webview);
+            if let Some(handle) = webview.as_ref() {
+                handle.emit(PRESET_FOLDERS_EVENT, &self.to_json());
+            }
+        }
+    }
+
+    /// `{ "version", "userDirectory", "searchPaths", "presets": [{ "name",
+    /// "bank", "path", "folder", "shared", "modified" }] }`, with
+    /// `modified` in seconds since the Unix epoch.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": self.version(),
+            "userDirectory": self.inner.user_directory.to_string_lossy(),
+            "searchPaths": self.search_paths().iter().map(|p| p.to_string_lossy()).collect::<Vec<_>>(),
+            "presets": self.scan().iter().map(PresetEntry::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// The configured folder containing `path`.
+    fn folder_of(&self, path: &Path) -> PathBuf {
+        self.search_paths()
+            .into_iter()
+            .find(|folder| path.starts_with(folder) && !path.starts_with(&self.inner.user_directory))
+            .unwrap_or_else(|| self.inner.user_directory.clone())
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn watch_loop(inner: Weak<Inner>, interval: Duration) {
+    loop {
+        std::thread::sleep(interval);
+        match inner.upgrade() {
+            Some(inner) => {
+                PresetFolders { inner }.refresh();
+            }
+            None => return,
+        }
+    }
+}
+
+fn scan_folder(
+    root: &Path,
+    dir: &Path,
+    shared: bool,
+    extension: &str,
+    depth: usize,
+    entries: &mut Vec<PresetEntry>,
+) {
+    let Ok(read_dir) = fs::read_dir(dir) else { return };
+    let mut paths: Vec<PathBuf> = read_dir.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            if depth < MAX_BANK_DEPTH {
+                scan_folder(root, &path, shared, extension, depth + 1, entries);
+            }
+        } else if path.extension().is_some_and(|e| e.eq_ignore_ascii_case(extension)) {
+            entries.push(entry_for(&path, root, shared));
+        }
+    }
+}
+
+fn entry_for(path: &Path, root: &Path, shared: bool) -> PresetEntry {
+    let bank = path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+        .map(|bank| bank.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    PresetEntry {
+        name: path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default(),
+        bank,
+        path: path.to_path_buf(),
+        folder: root.to_path_buf(),
+        shared,
+        modified: fs::metadata(path).and_then(|m| m.modified()).ok(),
+    }
+}
+
+/// Write and flush a file to disk.
+fn write_synced(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Write a file through a temporary file in the same folder.
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    write_synced(&temp, data)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// Link `temp` to the first free name: `<base>.<ext>` if `try_base`, then
+/// `<base> (conflict N).<ext>`. File systems without hard links get `data`
+/// written to a newly created file instead.
+fn link_new(
+    temp: &Path,
+    data: &[u8],
+    folder: &Path,
+    base: &str,
+    extension: &str,
+    try_base: bool,
+) -> io::Result<PathBuf> {
+    let start = if try_base { 0 } else { 1 };
+    for n in start..1000 {
+        let file = if n == 0 {
+            format!("{}.{}", base, extension)
+        } else {
+            format!("{} (conflict {}).{}", base, n, extension)
+        };
+        let path = folder.join(file);
+        let linked = fs::hard_link(temp, &path).or_else(|e| {
+            if e.kind() == io::ErrorKind::AlreadyExists {
+                return Err(e);
+            }
+            let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&path)?;
+            file.write_all(data)?;
+            file.sync_all()
+        });
+        match linked {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "too many conflicting presets"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("beamer-preset-folders-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn test_save_never_replaces_changed_presets() {
+        let directory = temp_directory("conflict");
+        let folders = PresetFolders::with_user_directory(&directory);
+        let other = PresetFolders::with_user_directory(&directory);
+
+        let saved = folders.save(&directory, "Pad", b"one").unwrap();
+        assert_eq!(saved.name, "Pad");
+        // This instance wrote it, so it may replace it.
+        assert_eq!(folders.save(&directory, "Pad", b"two").unwrap().path, saved.path);
+
+        // Another machine never read it: its save becomes a conflict copy.
+        let conflict = other.save(&directory, "Pad", b"three").unwrap();
+        assert_eq!(conflict.name, "Pad (conflict 1)");
+        assert_eq!(fs::read(&saved.path).unwrap(), b"two");
+
+        // After reading the current version, replacing works.
+        other.read(&saved).unwrap();
+        other.save(&directory, "Pad", b"four").unwrap();
+        assert_eq!(fs::read(&saved.path).unwrap(), b"four");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_search_paths_and_refresh() {
+        let directory = temp_directory("search");
+        let shared = directory.join("shared");
+        let folders = PresetFolders::with_user_directory(directory.join("user"));
+        folders.save(&shared.join("Bass"), "Sub", b"x").unwrap();
+        assert!(folders.scan().is_empty());
+
+        folders.add_search_path(&shared).unwrap();
+        let version = folders.version();
+        let entries = folders.scan();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].name.as_str(), entries[0].bank.as_str()), ("Sub", "Bass"));
+        assert!(entries[0].shared);
+
+        // Remembered for the next session.
+        let reopened = PresetFolders::with_user_directory(directory.join("user"));
+        assert_eq!(reopened.search_paths(), vec![shared.clone()]);
+
+        fs::write(shared.join("Lead.preset"), b"y").unwrap();
+        assert!(folders.refresh());
+        assert!(!folders.refresh());
+        assert!(folders.version() > version);
+        assert_eq!(folders.to_json()["presets"].as_array().unwrap().len(), 2);
+
+        // The in-memory list follows the rescans.
+        let mut name = String::new();
+        assert_eq!(folders.count(), 2);
+        assert!(folders.write_name(0, &mut name));
+        assert_eq!(name, "Bass/Sub");
+        fs::remove_file(shared.join("Lead.preset")).unwrap();
+        assert!(folders.refresh());
+        assert_eq!(folders.count(), 1);
+        assert!(!folders.write_name(1, &mut name));
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
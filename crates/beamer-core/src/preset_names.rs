@@ -0,0 +1,167 @@
+//! Preallocated cache for preset names returned to the host.
+//!
+//! Hosts poll program names (`getProgramName`, AU factory preset lists) far
+//! more often than they change, sometimes from threads where allocation is
+//! undesirable. [`PresetNameCache`] keeps one preallocated string per preset
+//! and only rewrites it when [`FactoryPresets::names_version()`] changes, so
+//! repeated queries never allocate.
+//!
+//! The cache also remembers which preset list the host was last told about.
+//! Wrappers poll [`PresetNameCache::take_list_changed()`] and ask the host to
+//! re-read its program list when presets are added, removed or renamed at
+//! runtime (e.g. user presets from [`PresetFolders`](crate::PresetFolders)).
+
+use crate::preset::FactoryPresets;
+
+/// Bytes reserved per cached name. Longer names still work but allocate once.
+pub const PRESET_NAME_CAPACITY: usize = 128;
+
+/// Per-instance cache of preset display names.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut names = PresetNameCache::new();
+/// if let Some(name) = names.name::<MyPresets>(index) {
+///     copy_to_host(name);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PresetNameCache {
+    /// One string per preset, kept allocated between queries.
+    names: Vec<String>,
+    /// Whether `names[i]` holds the current name.
+    valid: Vec<bool>,
+    /// `names_version()` the cached names were written for.
+    version: u64,
+    /// `(count(), names_version())` of the list the host last saw.
+    listed: Option<(usize, u64)>,
+}
+
+impl PresetNameCache {
+    /// Create an empty cache. Call [`reserve()`](Self::reserve) before use on
+    /// the audio thread.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preallocate slots for `count` presets.
+    pub fn reserve(&mut self, count: usize) {
+        while self.names.len() < count {
+            self.names.push(String::with_capacity(PRESET_NAME_CAPACITY));
+            self.valid.push(false);
+        }
+    }
+
+    /// Mark all cached names stale.
+    pub fn invalidate(&mut self) {
+        self.valid.iter_mut().for_each(|valid| *valid = false);
+    }
+
+    /// Name of the preset at `index`, filling the slot on first use.
+    ///
+    /// Returns `None` if `index >= P::count()`. Slots are grown if the preset
+    /// count increased since [`reserve()`](Self::reserve).
+    pub fn name<P: FactoryPresets>(&mut self, index: usize) -> Option<&str> {
+        if index >= P::count() {
+            return None;
+        }
+
+        let version = P::names_version();
+        if version != self.version {
+            self.version = version;
+            self.invalidate();
+        }
+
+        self.reserve(index + 1);
+        if !self.valid[index] {
+            let slot = &mut self.names[index];
+            slot.clear();
+            if !P::write_name(index, slot) {
+                return None;
+            }
+            self.valid[index] = true;
+        }
+
+        Some(self.names[index].as_str())
+    }
+
+    /// Returns true once after the preset count or
+    /// [`names_version()`](FactoryPresets::names_version) changed since the
+    /// last call. The first call only records the current list.
+    ///
+    /// Wrappers call this from the UI thread and notify the host (VST3
+    /// `restartComponent(kParamTitlesChanged)`, AU factory preset
+    /// listeners).
+    pub fn take_list_changed<P: FactoryPresets>(&mut self) -> bool {
+        let current = (P::count(), P::names_version());
+        match self.listed.replace(current) {
+            Some(listed) => listed != current,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameter_store::NoParameters;
+    use crate::preset::{PresetInfo, PresetValue};
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    static VERSION: AtomicU64 = AtomicU64::new(0);
+
+    struct UserPresets;
+
+    impl FactoryPresets for UserPresets {
+        type Parameters = NoParameters;
+
+        fn count() -> usize {
+            2
+        }
+
+        fn info(_index: usize) -> Option<PresetInfo> {
+            None
+        }
+
+        fn values(_index: usize) -> &'static [PresetValue] {
+            &[]
+        }
+
+        fn write_name(index: usize, out: &mut String) -> bool {
+            WRITES.fetch_add(1, Ordering::Relaxed);
+            let prefix = if VERSION.load(Ordering::Relaxed) == 0 { "User" } else { "Renamed" };
+            out.push_str(prefix);
+            out.push(char::from(b'1' + index as u8));
+            true
+        }
+
+        fn names_version() -> u64 {
+            VERSION.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn caches_until_version_changes() {
+        let mut cache = PresetNameCache::new();
+        cache.reserve(UserPresets::count());
+        let before = cache.names[1].as_ptr();
+
+        assert_eq!(cache.name::<UserPresets>(1), Some("User2"));
+        assert_eq!(cache.name::<UserPresets>(1), Some("User2"));
+        assert_eq!(WRITES.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.names[1].as_ptr(), before);
+        assert_eq!(cache.name::<UserPresets>(2), None);
+
+        VERSION.store(1, Ordering::Relaxed);
+        assert_eq!(cache.name::<UserPresets>(1), Some("Renamed2"));
+        assert_eq!(WRITES.load(Ordering::Relaxed), 2);
+        assert_eq!(cache.names[1].as_ptr(), before);
+
+        assert!(!cache.take_list_changed::<UserPresets>());
+        VERSION.store(2, Ordering::Relaxed);
+        assert!(cache.take_list_changed::<UserPresets>());
+        assert!(!cache.take_list_changed::<UserPresets>());
+    }
+}
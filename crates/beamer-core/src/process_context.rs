@@ -32,6 +32,7 @@
 //! ```
 
 use crate::midi_cc_state::MidiCcState;
+use crate::transport_events::TimeAnchor;
 
 // =============================================================================
 // FrameRate Enum
@@ -472,6 +473,65 @@ impl<'a> ProcessContext<'a> {
             .tempo
             .map(|tempo| self.sample_rate * 60.0 / tempo)
     }
+
+    /// Samples per quarter note at the current tempo.
+    ///
+    /// Hosts report tempo in quarter notes per minute, so this equals
+    /// [`samples_per_beat()`](Self::samples_per_beat) in any time signature.
+    #[inline]
+    pub fn samples_per_quarter(&self) -> Option<f64> {
+        self.samples_per_beat()
+    }
+
+    /// Converts a length in quarter notes to samples at the current tempo.
+    ///
+    /// Returns `None` if tempo is unavailable.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let sync = self.parameters.sync.get(); // TempoSync::EighthDotted
+    /// if let Some(delay_samples) = context.beats_to_samples(sync.beats()) {
+    ///     self.delay.set_length(delay_samples);
+    /// }
+    /// ```
+    #[inline]
+    pub fn beats_to_samples(&self, beats: f64) -> Option<f64> {
+        self.samples_per_quarter().map(|spq| beats * spq)
+    }
+
+    /// Project position in quarter notes at `sample_offset` into the block.
+    ///
+    /// Returns `None` if the host provides no musical position or tempo.
+    #[inline]
+    pub fn ppq_at_sample(&self, sample_offset: usize) -> Option<f64> {
+        TimeAnchor::from_transport(&self.transport, self.sample_rate)
+            .map(|anchor| anchor.beats_at(sample_offset))
+    }
+
+    /// Position within the current bar (0.0..1.0) at the start of the block.
+    ///
+    /// Returns `None` if the host provides no musical position, bar start or
+    /// time signature.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // One-bar filter sweep that restarts on every downbeat
+    /// if let Some(phase) = context.bar_phase() {
+    ///     self.cutoff = 200.0 + phase * 4000.0;
+    /// }
+    /// ```
+    pub fn bar_phase(&self) -> Option<f64> {
+        let beats = self.transport.project_time_beats?;
+        let bar_start = self.transport.bar_position_beats?;
+        let (numerator, denominator) = self.transport.time_signature()?;
+        if numerator <= 0 || denominator <= 0 {
+            return None;
+        }
+        let bar_length = numerator as f64 * 4.0 / denominator as f64;
+        Some(((beats - bar_start) / bar_length).rem_euclid(1.0))
+    }
 }
 
 impl Default for ProcessContext<'_> {
@@ -484,3 +544,31 @@ impl Default for ProcessContext<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn musical_time_helpers() {
+        let transport = Transport {
+            tempo: Some(120.0),
+            time_sig_numerator: Some(6),
+            time_sig_denominator: Some(8),
+            project_time_beats: Some(13.5),
+            bar_position_beats: Some(12.0),
+            ..Default::default()
+        };
+        let context = ProcessContext::new(48000.0, 512, transport);
+        assert_eq!(context.samples_per_quarter(), Some(24000.0));
+        assert_eq!(context.beats_to_samples(0.75), Some(18000.0));
+        assert_eq!(context.ppq_at_sample(12000), Some(14.0));
+        // 6/8 bars are three quarter notes long
+        assert_eq!(context.bar_phase(), Some(0.5));
+
+        let empty = ProcessContext::with_empty_transport(48000.0, 512);
+        assert_eq!(empty.beats_to_samples(1.0), None);
+        assert_eq!(empty.ppq_at_sample(0), None);
+        assert_eq!(empty.bar_phase(), None);
+    }
+}
@@ -0,0 +1,428 @@
+//! Wrapper-level processing statistics as read-only parameters.
+//!
+//! Host-side monitoring and remote-control surfaces can only show what the
+//! host knows about a plugin: its parameters.
+//! [`Config::with_processing_stats()`](crate::Config::with_processing_stats)
+//! (`processing_stats = true` in `Config.toml`) publishes the plugin's health
+//! as three read-only parameters, so it can be watched without opening the
+//! GUI:
+//!
+//! | Parameter | ID | Range |
+//! |-----------|----|-------|
+//! | DSP Load | [`DSP_LOAD_PARAMETER_ID`] | 0 to 100 % of the block duration |
+//! | Voices | [`ACTIVE_VOICES_PARAMETER_ID`] | 0 to 512, from [`Processor::active_voices()`] |
+//! | Underruns | [`UNDERRUNS_PARAMETER_ID`] | 0 to 9999, saturating |
+//!
+//! DSP Load is the time `process()` takes relative to the block's duration
+//! at the host rate, averaged over about half a second. Underruns counts
+//! calls that took longer than the block's duration, each of which makes the
+//! host's buffer run dry unless it has headroom; unlike the
+//! [watchdog](crate::watchdog) it ignores
+//! [`WatchdogSettings::load`](crate::WatchdogSettings::load).
+//!
+//! # Host Integration
+//!
+//! The wrappers list the parameters after the standard trims, time every
+//! `process()` call and read the voice count before the next one. The
+//! parameters are read-only, not automatable and not saved.
+//!
+//! | Format | Updates |
+//! |--------|---------|
+//! | VST3 | Output parameter changes from `process()` when a displayed value changes |
+//! | AU | Hosts read the values on demand; while the editor is open, changes also go to the parameter listeners |
+//!
+//! [`Processor::active_voices()`]: crate::Processor::active_voices
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::parameter_info::ParameterInfo;
+use crate::parameter_store::ParameterStore;
+use crate::parameter_types::{FloatParameter, IntParameter, ParameterRef};
+use crate::types::{ParameterId, ParameterValue};
+
+/// Base parameter ID of the processing statistics.
+///
+/// Above the [standard trims](crate::STANDARD_TRIM_PARAM_BASE) and below
+/// the VST3 limit of 2³¹.
+pub const PROCESSING_STATS_PARAM_BASE: ParameterId = 0x1100_0100;
+
+/// Parameter ID of DSP Load.
+pub const DSP_LOAD_PARAMETER_ID: ParameterId = PROCESSING_STATS_PARAM_BASE;
+
+/// Parameter ID of Voices.
+pub const ACTIVE_VOICES_PARAMETER_ID: ParameterId = PROCESSING_STATS_PARAM_BASE + 1;
+
+/// Parameter ID of Underruns.
+pub const UNDERRUNS_PARAMETER_ID: ParameterId = PROCESSING_STATS_PARAM_BASE + 2;
+
+/// Highest voice count shown.
+const MAX_VOICES: i64 = 512;
+
+/// Highest underrun count shown.
+const MAX_UNDERRUNS: i64 = 9999;
+
+/// Time constant of the DSP load average in seconds.
+const LOAD_SMOOTHING_SECONDS: f64 = 0.5;
+
+/// Shared processing statistics.
+///
+/// Cheap to clone; all clones share the values. The default is disabled and
+/// has no parameters.
+#[derive(Clone, Default)]
+pub struct ProcessingStats {
+    inner: Option<Arc<StatsParameters>>,
+}
+
+struct StatsParameters {
+    load: FloatParameter,
+    voices: IntParameter,
+    underruns: IntParameter,
+    /// Host sample rate (f64 bits); nothing is timed while 0.
+    sample_rate: AtomicU64,
+    /// A displayed value changed since the last report.
+    changed: AtomicBool,
+}
+
+impl StatsParameters {
+    fn new() -> Self {
+        Self {
+            load: FloatParameter::percent("DSP Load", 0.0)
+                .with_id(DSP_LOAD_PARAMETER_ID)
+                .with_string_id("_beamer_dsp_load")
+                .with_short_name("DSP")
+                .readonly()
+                .transient()
+                .no_randomize(),
+            voices: IntParameter::new("Voices", 0, 0..=MAX_VOICES)
+                .with_id(ACTIVE_VOICES_PARAMETER_ID)
+                .with_string_id("_beamer_active_voices")
+                .with_short_name("Voices")
+                .readonly()
+                .transient()
+                .no_randomize(),
+            underruns: IntParameter::new("Underruns", 0, 0..=MAX_UNDERRUNS)
+                .with_id(UNDERRUNS_PARAMETER_ID)
+                .with_string_id("_beamer_underruns")
+                .with_short_name("Xruns")
+                .readonly()
+                .transient()
+                .no_randomize(),
+            sample_rate: AtomicU64::new(0),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    fn by_index(&self, index: usize) -> Option<&dyn ParameterRef> {
+        match index {
+            0 => Some(&self.load),
+            1 => Some(&self.voices),
+            2 => Some(&self.underruns),
+            _ => None,
+        }
+    }
+}
+
+impl ProcessingStats {
+    /// Create enabled statistics at zero.
+    pub fn new() -> Self {
+        Self {
+            inner: Some(Arc::new(StatsParameters::new())),
+        }
+    }
+
+    /// Enabled when `enabled`, the disabled default otherwise.
+    pub fn enabled(enabled: bool) -> Self {
+        if enabled {
+            Self::new()
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Returns true if the statistics are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Returns true if `id` is one of the statistics parameter IDs.
+    pub fn is_stats_parameter(id: ParameterId) -> bool {
+        (DSP_LOAD_PARAMETER_ID..=UNDERRUNS_PARAMETER_ID).contains(&id)
+    }
+
+    /// Look up a statistics parameter by ID (`None` when disabled).
+    pub fn by_id(&self, id: ParameterId) -> Option<&dyn ParameterRef> {
+        let inner = self.inner.as_deref()?;
+        if !Self::is_stats_parameter(id) {
+            return None;
+        }
+        inner.by_index((id - PROCESSING_STATS_PARAM_BASE) as usize)
+    }
+
+    /// Set the host sample rate. Called by the wrappers when processing is
+    /// set up; until then nothing is timed.
+    pub fn set_sample_rate(&self, sample_rate: f64) {
+        if let Some(inner) = self.inner.as_deref() {
+            inner.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Start timing a block of `num_samples`; the call is recorded when
+    /// the returned timer is dropped.
+    ///
+    /// Real-time safe.
+    pub fn start(&self, num_samples: usize) -> StatsTimer<'_> {
+        let start = (self.is_enabled() && num_samples > 0).then(Instant::now);
+        StatsTimer { stats: self, start, num_samples }
+    }
+
+    /// Record a `process()` call that took `elapsed` for `num_samples`.
+    ///
+    /// Real-time safe.
+    pub fn record(&self, elapsed: Duration, num_samples: usize) {
+        let Some(inner) = self.inner.as_deref() else {
+            return;
+        };
+        let sample_rate = f64::from_bits(inner.sample_rate.load(Ordering::Relaxed));
+        if sample_rate <= 0.0 || num_samples == 0 {
+            return;
+        }
+
+        let duration = num_samples as f64 / sample_rate;
+        let load = elapsed.as_secs_f64() / duration;
+        let previous = inner.load.get();
+        let coefficient = 1.0 - (-duration / LOAD_SMOOTHING_SECONDS).exp();
+        inner.load.set((previous + (load - previous) * coefficient).clamp(0.0, 1.0));
+        let mut changed = percent(inner.load.get()) != percent(previous);
+
+        if load > 1.0 {
+            let underruns = inner.underruns.get();
+            if underruns < MAX_UNDERRUNS {
+                inner.underruns.set(underruns + 1);
+                changed = true;
+            }
+        }
+        if changed {
+            inner.changed.store(true, Ordering::Release);
+        }
+    }
+
+    /// Store the processor's current voice count.
+    ///
+    /// Real-time safe.
+    pub fn set_active_voices(&self, voices: usize) {
+        let Some(inner) = self.inner.as_deref() else {
+            return;
+        };
+        let voices = (voices as i64).min(MAX_VOICES);
+        if inner.voices.get() != voices {
+            inner.voices.set(voices);
+            inner.changed.store(true, Ordering::Release);
+        }
+    }
+
+    /// Returns true once after a displayed value changed.
+    pub fn take_changed(&self) -> bool {
+        self.inner
+            .as_deref()
+            .is_some_and(|inner| inner.changed.swap(false, Ordering::Acquire))
+    }
+
+    /// Call `report` with the ID and normalized value of every statistic,
+    /// if a displayed value changed since the last report.
+    ///
+    /// Real-time safe.
+    pub fn report(&self, mut report: impl FnMut(ParameterId, ParameterValue)) {
+        let Some(inner) = self.inner.as_deref() else {
+            return;
+        };
+        if !self.take_changed() {
+            return;
+        }
+        for index in 0..3 {
+            if let Some(parameter) = inner.by_index(index) {
+                report(parameter.id(), parameter.get_normalized());
+            }
+        }
+    }
+}
+
+/// Load in whole percent, as displayed.
+fn percent(load: f64) -> i64 {
+    (load * 100.0).round() as i64
+}
+
+impl ParameterStore for ProcessingStats {
+    fn count(&self) -> usize {
+        if self.is_enabled() {
+            3
+        } else {
+            0
+        }
+    }
+
+    fn info(&self, index: usize) -> Option<&ParameterInfo> {
+        self.inner.as_deref()?.by_index(index).map(|p| p.info())
+    }
+
+    fn get_normalized(&self, id: ParameterId) -> ParameterValue {
+        self.by_id(id).map(|p| p.get_normalized()).unwrap_or(0.0)
+    }
+
+    fn set_normalized(&self, _id: ParameterId, _value: ParameterValue) {
+        // Read-only: only the wrapper's measurements change the values
+    }
+
+    fn normalized_to_string(&self, id: ParameterId, normalized: ParameterValue) -> String {
+        self.by_id(id)
+            .map(|p| p.display_normalized(normalized))
+            .unwrap_or_default()
+    }
+
+    fn string_to_normalized(&self, id: ParameterId, string: &str) -> Option<ParameterValue> {
+        self.by_id(id).and_then(|p| p.parse(string))
+    }
+
+    fn normalized_to_plain(&self, id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+        self.by_id(id).map(|p| p.normalized_to_plain(normalized)).unwrap_or(0.0)
+    }
+
+    fn plain_to_normalized(&self, id: ParameterId, plain: ParameterValue) -> ParameterValue {
+        self.by_id(id).map(|p| p.plain_to_normalized(plain)).unwrap_or(0.0)
+    }
+
+    fn formatter_kind(&self, id: ParameterId) -> &'static str {
+        self.by_id(id).map(|p| p.formatter_kind()).unwrap_or("float")
+    }
+}
+
+/// A parameter store followed by the processing statistics, as one store.
+///
+/// Used by wrappers that hand a single [`ParameterStore`] to the host
+/// bridge. IDs are routed to the statistics first, indices to the plugin
+/// first.
+pub struct StoreWithStats<'a> {
+    /// The plugin's parameters (and any other framework parameters).
+    pub plugin: &'a dyn ParameterStore,
+    /// The processing statistics (may be disabled).
+    pub stats: &'a ProcessingStats,
+}
+
+impl StoreWithStats<'_> {
+    fn store(&self, id: ParameterId) -> &dyn ParameterStore {
+        if self.stats.by_id(id).is_some() {
+            self.stats
+        } else {
+            self.plugin
+        }
+    }
+}
+
+impl ParameterStore for StoreWithStats<'_> {
+    fn count(&self) -> usize {
+        self.plugin.count() + self.stats.count()
+    }
+
+    fn info(&self, index: usize) -> Option<&ParameterInfo> {
+        let plugin_count = self.plugin.count();
+        if index < plugin_count {
+            self.plugin.info(index)
+        } else {
+            self.stats.info(index - plugin_count)
+        }
+    }
+
+    fn get_normalized(&self, id: ParameterId) -> ParameterValue {
+        self.store(id).get_normalized(id)
+    }
+
+    fn set_normalized(&self, id: ParameterId, value: ParameterValue) {
+        self.store(id).set_normalized(id, value)
+    }
+
+    fn normalized_to_string(&self, id: ParameterId, normalized: ParameterValue) -> String {
+        self.store(id).normalized_to_string(id, normalized)
+    }
+
+    fn string_to_normalized(&self, id: ParameterId, string: &str) -> Option<ParameterValue> {
+        self.store(id).string_to_normalized(id, string)
+    }
+
+    fn normalized_to_plain(&self, id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+        self.store(id).normalized_to_plain(id, normalized)
+    }
+
+    fn plain_to_normalized(&self, id: ParameterId, plain: ParameterValue) -> ParameterValue {
+        self.store(id).plain_to_normalized(id, plain)
+    }
+
+    fn formatter_kind(&self, id: ParameterId) -> &'static str {
+        self.store(id).formatter_kind(id)
+    }
+}
+
+/// Times one block until dropped. See [`ProcessingStats::start()`].
+pub struct StatsTimer<'a> {
+    stats: &'a ProcessingStats,
+    start: Option<Instant>,
+    num_samples: usize,
+}
+
+impl Drop for StatsTimer<'_> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.stats.record(start.elapsed(), self.num_samples);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn records_load_underruns_and_voices() {
+        let stats = ProcessingStats::new();
+        // Not timed before the sample rate is known
+        stats.record(100 * MS, 480);
+        assert!(!stats.take_changed());
+
+        // 480 samples at 48 kHz = 10 ms blocks at 50% load
+        stats.set_sample_rate(48000.0);
+        for _ in 0..400 {
+            stats.record(5 * MS, 480);
+        }
+        let load = stats.get_normalized(DSP_LOAD_PARAMETER_ID);
+        assert!((load - 0.5).abs() < 0.01, "{load}");
+        assert_eq!(stats.normalized_to_string(DSP_LOAD_PARAMETER_ID, 0.5), "50");
+
+        stats.record(20 * MS, 480);
+        stats.set_active_voices(3);
+        let mut reported = Vec::new();
+        stats.report(|id, value| reported.push((id, stats.normalized_to_plain(id, value))));
+        assert_eq!(reported[1], (ACTIVE_VOICES_PARAMETER_ID, 3.0));
+        assert_eq!(reported[2], (UNDERRUNS_PARAMETER_ID, 1.0));
+
+        // Nothing changed since the report
+        stats.report(|_, _| panic!("unchanged values reported"));
+    }
+
+    #[test]
+    fn store_is_read_only_and_follows_the_plugin() {
+        let stats = ProcessingStats::new();
+        stats.set_normalized(ACTIVE_VOICES_PARAMETER_ID, 1.0);
+        assert_eq!(stats.get_normalized(ACTIVE_VOICES_PARAMETER_ID), 0.0);
+
+        let info = stats.info(0).unwrap();
+        assert!(info.flags.is_readonly && !info.flags.can_automate && !info.flags.is_persistent);
+
+        let trims = crate::StandardTrims::new();
+        let store = StoreWithStats { plugin: &trims, stats: &stats };
+        assert_eq!(store.count(), 6);
+        assert_eq!(store.info(3).unwrap().id, DSP_LOAD_PARAMETER_ID);
+        assert!(ProcessingStats::default().by_id(DSP_LOAD_PARAMETER_ID).is_none());
+    }
+}
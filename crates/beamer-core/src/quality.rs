@@ -0,0 +1,165 @@
+//! Quality modes: trading processing quality for CPU (and battery).
+//!
+//! [`QualityParameter`] is an ordinary enum parameter (Eco/Normal/High).
+//! The descriptor reports the selected mode from
+//! [`Descriptor::quality()`](crate::Descriptor::quality), and the processor
+//! reads it in `prepare()` by adding [`Quality`] to its setup:
+//!
+//! ```ignore
+//! #[derive(Parameters)]
+//! pub struct ReverbParameters {
+//!     #[parameter(id = "quality", name = "Quality")]
+//!     pub quality: QualityParameter,
+//! }
+//!
+//! impl Descriptor for ReverbDescriptor {
+//!     type Setup = (SampleRate, Quality);
+//!
+//!     fn quality(parameters: &Self::Parameters) -> Quality {
+//!         parameters.quality.get()
+//!     }
+//!
+//!     // Eco runs at the host rate, High at 4x
+//!     fn oversampling(parameters: &Self::Parameters) -> Oversampling {
+//!         parameters.quality.get().oversampling(Oversampling::X2)
+//!     }
+//!
+//!     fn prepare(self, (sample_rate, quality): (SampleRate, Quality)) -> ReverbProcessor {
+//!         let fft_size = quality.fft_size(2048);
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! # Host Integration
+//!
+//! A changed mode takes the same path as a changed
+//! [`oversampling`](crate::oversampling) factor: the audio thread keeps
+//! running the prepared setup, and the wrappers prepare the processor again
+//! outside the audio thread (VST3 after restarting the processor, AU under
+//! the plugin lock) and report the new latency. Changes are picked up after
+//! state loads, host edits (VST3) and, while the editor is open, GUI edits
+//! and automation. Mark the parameter non-automatable if hosts shouldn't
+//! trigger re-preparation during playback.
+
+use crate::oversampling::Oversampling;
+use crate::parameter_types::{EnumParameter, EnumParameterValue};
+use crate::plugin::{HostSetup, PluginSetup};
+
+/// Processing quality mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Quality {
+    /// Lowest CPU use, for laptops on battery.
+    Eco,
+    /// The plugin's regular quality.
+    #[default]
+    Normal,
+    /// Highest quality, for mixdowns and powerful machines.
+    High,
+}
+
+impl Quality {
+    /// Oversampling for this mode, given the factor used at `Normal`: none
+    /// at `Eco`, one step more at `High` (up to 8x).
+    pub const fn oversampling(self, normal: Oversampling) -> Oversampling {
+        match self {
+            Self::Eco => Oversampling::Off,
+            Self::Normal => normal,
+            Self::High => match normal {
+                Oversampling::Off => Oversampling::X2,
+                Oversampling::X2 => Oversampling::X4,
+                Oversampling::X4 | Oversampling::X8 => Oversampling::X8,
+            },
+        }
+    }
+
+    /// FFT or block size for this mode, given the size used at `Normal`:
+    /// half at `Eco`, double at `High`.
+    pub const fn fft_size(self, normal: usize) -> usize {
+        match self {
+            Self::Eco => normal / 2,
+            Self::Normal => normal,
+            Self::High => normal * 2,
+        }
+    }
+
+    /// Meter or analysis update rate for this mode, given the rate used at
+    /// `Normal`: half at `Eco`, unchanged otherwise.
+    pub fn meter_rate(self, normal_hz: f64) -> f64 {
+        match self {
+            Self::Eco => normal_hz * 0.5,
+            Self::Normal | Self::High => normal_hz,
+        }
+    }
+}
+
+const NAMES: &[&str] = &["Eco", "Normal", "High"];
+
+impl EnumParameterValue for Quality {
+    const COUNT: usize = 3;
+    const DEFAULT_INDEX: usize = 1;
+
+    fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Self::Eco),
+            1 => Some(Self::Normal),
+            2 => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn default_value() -> Self {
+        Self::Normal
+    }
+
+    fn name(index: usize) -> &'static str {
+        NAMES.get(index).copied().unwrap_or("")
+    }
+
+    fn names() -> &'static [&'static str] {
+        NAMES
+    }
+}
+
+/// Enum parameter selecting a [`Quality`] mode.
+///
+/// Recognized by `#[derive(Parameters)]` like any `EnumParameter`.
+pub type QualityParameter = EnumParameter<Quality>;
+
+/// The mode from [`Descriptor::quality()`](crate::Descriptor::quality) when
+/// the processor was prepared.
+impl PluginSetup for Quality {
+    fn extract(host: &HostSetup) -> Self {
+        host.quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_and_names_agree() {
+        for (index, name) in NAMES.iter().enumerate() {
+            let value = Quality::from_index(index).unwrap();
+            assert_eq!(value.to_index(), index);
+            assert_eq!(Quality::name(index), *name);
+        }
+        assert_eq!(NAMES.len(), Quality::COUNT);
+        assert_eq!(QualityParameter::new("Quality").get(), Quality::Normal);
+    }
+
+    #[test]
+    fn scaling_follows_mode() {
+        assert_eq!(Quality::Eco.oversampling(Oversampling::X4), Oversampling::Off);
+        assert_eq!(Quality::High.oversampling(Oversampling::Off), Oversampling::X2);
+        assert_eq!(Quality::High.oversampling(Oversampling::X8), Oversampling::X8);
+        assert_eq!(Quality::Eco.fft_size(2048), 1024);
+        assert_eq!(Quality::High.fft_size(2048), 4096);
+        assert_eq!(Quality::Eco.meter_rate(60.0), 30.0);
+    }
+}
@@ -0,0 +1,142 @@
+//! Values computed from the sample rate, recomputed when it changes.
+//!
+//! Filter coefficients, envelope rates and delay lengths are computed for
+//! one sample rate. A processor that computes them once in `prepare()` and
+//! stores them keeps stale values when the project is reopened or the host
+//! changes the rate: the filter is suddenly tuned an octave off. Wrapping
+//! such values in [`RateDependent`] ties them to the function that computes
+//! them, and marking the field `#[rate_dependent]` has the framework call
+//! that function again whenever the rate can have changed:
+//!
+//! ```ignore
+//! #[derive(HasParameters)]
+//! pub struct FilterProcessor {
+//!     #[parameters]
+//!     parameters: FilterParameters,
+//!     #[rate_dependent]
+//!     coefficients: RateDependent<BiquadCoeffs, FilterParameters>,
+//! }
+//!
+//! impl Descriptor for FilterDescriptor {
+//!     type Setup = SampleRate;
+//!
+//!     fn prepare(self, setup: SampleRate) -> FilterProcessor {
+//!         let coefficients = RateDependent::new(setup.hz(), &self.parameters, |sample_rate, parameters| {
+//!             BiquadCoeffs::low_pass(parameters.cutoff.get(), 0.707, sample_rate)
+//!         });
+//!         FilterProcessor { parameters: self.parameters, coefficients }
+//!     }
+//! }
+//! ```
+//!
+//! # When Values Are Recomputed
+//!
+//! The wrappers call [`HasParameters::recompute_rate_dependent()`] with the
+//! processing rate (the host rate times the oversampling factor):
+//!
+//! - right after `prepare()`, and after state loaded before preparation
+//! - after `load_state()`, since loaded parameters may change the result
+//!
+//! Parameter changes inside `process()` are up to the processor: call
+//! [`refresh()`](RateDependent::refresh) when a parameter the value depends
+//! on has changed. The compute function is a plain `fn`, so it captures
+//! nothing and cannot depend on state other than the rate and parameters.
+//!
+//! [`HasParameters::recompute_rate_dependent()`]: crate::plugin::HasParameters::recompute_rate_dependent
+
+use std::ops::Deref;
+
+/// A value computed from the sample rate and the parameters.
+///
+/// Dereferences to the value. See the [module docs](self) for the
+/// `#[rate_dependent]` field attribute that keeps it up to date.
+pub struct RateDependent<T, P> {
+    value: T,
+    sample_rate: f64,
+    compute: fn(f64, &P) -> T,
+}
+
+impl<T, P> RateDependent<T, P> {
+    /// Compute the value for `sample_rate`.
+    pub fn new(sample_rate: f64, parameters: &P, compute: fn(f64, &P) -> T) -> Self {
+        Self {
+            value: compute(sample_rate, parameters),
+            sample_rate,
+            compute,
+        }
+    }
+
+    /// Recompute the value for `sample_rate`.
+    ///
+    /// Always recomputes, since the parameters may have changed even if the
+    /// rate did not.
+    pub fn recompute(&mut self, sample_rate: f64, parameters: &P) {
+        self.sample_rate = sample_rate;
+        self.value = (self.compute)(sample_rate, parameters);
+    }
+
+    /// Recompute the value at the current sample rate, e.g. after a
+    /// parameter it depends on changed.
+    pub fn refresh(&mut self, parameters: &P) {
+        self.value = (self.compute)(self.sample_rate, parameters);
+    }
+
+    /// The sample rate the value was computed for.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// The computed value.
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, P> Deref for RateDependent<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: std::fmt::Debug, P> std::fmt::Debug for RateDependent<T, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateDependent")
+            .field("value", &self.value)
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one_pole(sample_rate: f64, cutoff: &f64) -> f64 {
+        (-2.0 * std::f64::consts::PI * cutoff / sample_rate).exp()
+    }
+
+    #[test]
+    fn recomputes_for_new_rate() {
+        let mut coefficient = RateDependent::new(44100.0, &1000.0, one_pole);
+        let at_44k = *coefficient;
+        assert_eq!(coefficient.sample_rate(), 44100.0);
+
+        coefficient.recompute(96000.0, &1000.0);
+        assert_eq!(coefficient.sample_rate(), 96000.0);
+        assert_eq!(*coefficient.get(), one_pole(96000.0, &1000.0));
+        assert!(*coefficient > at_44k);
+
+        coefficient.recompute(44100.0, &1000.0);
+        assert_eq!(*coefficient, at_44k);
+    }
+
+    #[test]
+    fn refresh_keeps_rate() {
+        let mut coefficient = RateDependent::new(48000.0, &1000.0, one_pole);
+        coefficient.refresh(&2000.0);
+        assert_eq!(coefficient.sample_rate(), 48000.0);
+        assert_eq!(*coefficient, one_pole(48000.0, &2000.0));
+    }
+}
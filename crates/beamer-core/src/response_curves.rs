@@ -0,0 +1,613 @@
+//! Response curves for MIDI velocity and controllers.
+//!
+//! A keyboard's velocity response or an expression pedal's travel rarely
+//! suits every patch. Response curves reshape incoming values before the
+//! plugin sees them, per controller, so users can adapt their hardware
+//! without plugin-specific code. The GUI edits the curves through built-in
+//! invokes and they are saved with the plugin state.
+//!
+//! Curves apply to:
+//!
+//! - Note-on velocity ([`CurveTarget::Velocity`])
+//! - MIDI CCs, channel aftertouch and pitch bend delivered as MIDI events,
+//!   including the ones [`MidiCcConfig`](crate::MidiCcConfig) turns host
+//!   parameter changes into
+//! - The values read through [`ProcessContext::midi_cc()`](crate::ProcessContext::midi_cc)
+//!
+//! Pitch bend is shaped symmetrically around its center: the curve maps the
+//! bend amount (0 to 1) in either direction.
+//!
+//! Plugins can declare default curves with
+//! [`Descriptor::response_curves()`]:
+//!
+//! ```ignore
+//! fn response_curves(&self) -> ResponseCurves {
+//!     ResponseCurves::new()
+//!         .with_curve(CurveTarget::Velocity, CurveShape::Power { exponent: 0.6 })
+//!         .with_curve(CurveTarget::Controller(11), CurveShape::SCurve { steepness: 2.0 })
+//! }
+//! ```
+//!
+//! # Curve Shapes
+//!
+//! | Curve | Mapping |
+//! |-------|---------|
+//! | [`CurveShape::Linear`] | Unchanged |
+//! | [`CurveShape::Power`] | `x^exponent`: above 1 starts soft, below 1 starts hard |
+//! | [`CurveShape::SCurve`] | Soft at both ends (`steepness` > 1) or at the center (< 1) |
+//! | [`CurveShape::Points`] | Breakpoints, joined by straight lines or a smooth monotone spline |
+//!
+//! The audio thread reads a precomputed lookup table, so evaluating a curve
+//! is a table interpolation regardless of its shape.
+//!
+//! # Persistence
+//!
+//! When the curves differ from the plugin's defaults, the wrapper appends a
+//! trailer after any other framework trailer and strips it again before
+//! loading, so plugin code never sees it:
+//!
+//! ```text
+//! [plugin state][other trailers][curves JSON][length: u32 LE]["BXRC"]
+//! ```
+//!
+//! A state without the trailer restores the defaults.
+//!
+//! # GUI Invokes
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`RESPONSE_CURVES_INVOKE`] | `[]` | `{ [target]: curve }` for every shaped target |
+//! | [`SET_RESPONSE_CURVE_INVOKE`] | `[target, curve \| null]` | `true` if the curve was applied |
+//!
+//! Targets are `"velocity"`, `"pitchBend"`, `"aftertouch"` and `"cc0"` to
+//! `"cc127"`; curves use the JSON form of [`CurveShape`], e.g.
+//! `{ "type": "power", "exponent": 2 }` or
+//! `{ "type": "points", "points": [[0, 0], [0.5, 0.2], [1, 1]], "smooth": true }`.
+//!
+//! [`Descriptor::response_curves()`]: crate::plugin::Descriptor::response_curves
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::midi::{MidiEvent, MidiEventKind};
+use crate::midi_cc_config::{controller, MAX_CC_CONTROLLER};
+
+/// Built-in WebView invoke method that lists the curves.
+pub const RESPONSE_CURVES_INVOKE: &str = "_beamer/responseCurves";
+
+/// Built-in WebView invoke method that sets or clears a curve.
+pub const SET_RESPONSE_CURVE_INVOKE: &str = "_beamer/setResponseCurve";
+
+/// Magic bytes ending the curves state trailer.
+const STATE_TRAILER_MAGIC: &[u8; 4] = b"BXRC";
+
+/// Number of lookup table entries (one per 7-bit MIDI value, plus the end).
+const TABLE_SIZE: usize = 129;
+
+/// Curve slots: one per controller, plus velocity.
+const SLOT_COUNT: usize = MAX_CC_CONTROLLER + 1;
+
+/// Slot index of the velocity curve.
+const VELOCITY_SLOT: usize = MAX_CC_CONTROLLER;
+
+// =============================================================================
+// Curve Definition
+// =============================================================================
+
+/// What a response curve shapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CurveTarget {
+    /// Note-on velocity.
+    Velocity,
+    /// A controller: MIDI CC 0-127,
+    /// [`controller::AFTERTOUCH`] or [`controller::PITCH_BEND`].
+    Controller(u8),
+}
+
+impl CurveTarget {
+    fn slot(self) -> Option<usize> {
+        match self {
+            Self::Velocity => Some(VELOCITY_SLOT),
+            Self::Controller(c) if (c as usize) < MAX_CC_CONTROLLER => Some(c as usize),
+            Self::Controller(_) => None,
+        }
+    }
+
+    fn from_slot(slot: usize) -> Self {
+        if slot == VELOCITY_SLOT {
+            Self::Velocity
+        } else {
+            Self::Controller(slot as u8)
+        }
+    }
+}
+
+impl fmt::Display for CurveTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Velocity => f.write_str("velocity"),
+            Self::Controller(controller::PITCH_BEND) => f.write_str("pitchBend"),
+            Self::Controller(controller::AFTERTOUCH) => f.write_str("aftertouch"),
+            Self::Controller(cc) => write!(f, "cc{}", cc),
+        }
+    }
+}
+
+impl FromStr for CurveTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "velocity" => Ok(Self::Velocity),
+            "pitchBend" => Ok(Self::Controller(controller::PITCH_BEND)),
+            "aftertouch" => Ok(Self::Controller(controller::AFTERTOUCH)),
+            _ => match s.strip_prefix("cc").and_then(|n| n.parse::<u8>().ok()) {
+                Some(cc) if cc < 128 => Ok(Self::Controller(cc)),
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+/// Shape of a response curve over 0.0-1.0.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CurveShape {
+    /// Values pass through unchanged.
+    Linear,
+    /// `x^exponent`. Above 1 the response starts soft, below 1 it starts hard.
+    Power {
+        /// Exponent, clamped to 0.05-20.
+        exponent: f64,
+    },
+    /// S-shaped: `x^s / (x^s + (1-x)^s)`. Above 1 both ends are soft, below
+    /// 1 the center is.
+    SCurve {
+        /// Steepness `s`, clamped to 0.05-20.
+        steepness: f64,
+    },
+    /// Breakpoints `[x, y]` within 0.0-1.0.
+    ///
+    /// Values before the first or after the last point hold its `y`.
+    Points {
+        /// Breakpoints; sorted by `x` when evaluated.
+        points: Vec<[f64; 2]>,
+        /// Join the points with a monotone cubic spline instead of straight
+        /// lines.
+        #[serde(default)]
+        smooth: bool,
+    },
+}
+
+impl CurveShape {
+    /// Evaluate the curve at `x` (clamped to 0.0-1.0).
+    pub fn evaluate(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        let y = match self {
+            Self::Linear => x,
+            Self::Power { exponent } => x.powf(exponent.clamp(0.05, 20.0)),
+            Self::SCurve { steepness } => {
+                let s = steepness.clamp(0.05, 20.0);
+                let (a, b) = (x.powf(s), (1.0 - x).powf(s));
+                if a + b > 0.0 { a / (a + b) } else { x }
+            }
+            Self::Points { points, smooth } => evaluate_points(points, *smooth, x),
+        };
+        y.clamp(0.0, 1.0)
+    }
+
+    /// Sample the curve into a lookup table.
+    fn table(&self) -> CurveTable {
+        CurveTable(std::array::from_fn(|i| {
+            self.evaluate(i as f64 / (TABLE_SIZE - 1) as f64) as f32
+        }))
+    }
+}
+
+/// Piecewise linear or monotone cubic (Fritsch-Carlson) interpolation.
+fn evaluate_points(points: &[[f64; 2]], smooth: bool, x: f64) -> f64 {
+    let mut sorted: Vec<[f64; 2]> = points
+        .iter()
+        .filter(|p| p[0].is_finite() && p[1].is_finite())
+        .map(|p| [p[0].clamp(0.0, 1.0), p[1].clamp(0.0, 1.0)])
+        .collect();
+    sorted.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    sorted.dedup_by(|b, a| a[0] == b[0]);
+
+    let (Some(first), Some(last)) = (sorted.first(), sorted.last()) else {
+        return x;
+    };
+    if x <= first[0] {
+        return first[1];
+    }
+    if x >= last[0] {
+        return last[1];
+    }
+    let i = sorted.partition_point(|p| p[0] <= x) - 1;
+    let ([x0, y0], [x1, y1]) = (sorted[i], sorted[i + 1]);
+    let h = x1 - x0;
+    let t = (x - x0) / h;
+    if !smooth {
+        return y0 + (y1 - y0) * t;
+    }
+
+    let secant = |k: usize| (sorted[k + 1][1] - sorted[k][1]) / (sorted[k + 1][0] - sorted[k][0]);
+    let tangent = |k: usize| -> f64 {
+        if k == 0 {
+            return secant(0);
+        }
+        if k == sorted.len() - 1 {
+            return secant(k - 1);
+        }
+        let (a, b) = (secant(k - 1), secant(k));
+        // Flat at local extrema keeps the spline from overshooting.
+        if a * b <= 0.0 { 0.0 } else { 2.0 / (1.0 / a + 1.0 / b) }
+    };
+    let (m0, m1) = (tangent(i), tangent(i + 1));
+    let (t2, t3) = (t * t, t * t * t);
+    (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+        + (t3 - 2.0 * t2 + t) * h * m0
+        + (-2.0 * t3 + 3.0 * t2) * y1
+        + (t3 - t2) * h * m1
+}
+
+/// A curve sampled at [`TABLE_SIZE`] points.
+#[derive(Clone, Debug, PartialEq)]
+struct CurveTable([f32; TABLE_SIZE]);
+
+impl CurveTable {
+    #[inline]
+    fn lookup(&self, x: f32) -> f32 {
+        let pos = x.clamp(0.0, 1.0) * (TABLE_SIZE - 1) as f32;
+        let i = (pos as usize).min(TABLE_SIZE - 2);
+        let frac = pos - i as f32;
+        self.0[i] + (self.0[i + 1] - self.0[i]) * frac
+    }
+}
+
+// =============================================================================
+// Runtime State
+// =============================================================================
+
+/// Per-instance response curves.
+///
+/// Returned by `Descriptor::response_curves()` with the plugin's defaults
+/// and edited by the GUI. Cloning is cheap (reference counted), so the
+/// wrapper shares one set between its audio path, its state and its WebView
+/// IPC context. The audio thread only uses `try_lock()`; while the GUI is
+/// replacing a curve, values pass through unshaped for that block.
+#[derive(Clone, Debug, Default)]
+pub struct ResponseCurves {
+    inner: Arc<CurvesInner>,
+}
+
+#[derive(Debug, Default)]
+struct CurvesInner {
+    slots: Mutex<Slots>,
+    /// True while any curve is set, so the audio thread can skip the lock.
+    active: AtomicBool,
+}
+
+#[derive(Debug)]
+struct Slots {
+    curves: Vec<Option<(CurveShape, Box<CurveTable>)>>,
+    defaults: Vec<Option<CurveShape>>,
+}
+
+impl Default for Slots {
+    fn default() -> Self {
+        Self {
+            curves: vec![None; SLOT_COUNT],
+            defaults: vec![None; SLOT_COUNT],
+        }
+    }
+}
+
+impl Slots {
+    fn set(&mut self, slot: usize, curve: Option<CurveShape>) {
+        self.curves[slot] = curve.map(|curve| {
+            let table = Box::new(curve.table());
+            (curve, table)
+        });
+    }
+
+    fn is_default(&self) -> bool {
+        self.curves
+            .iter()
+            .zip(&self.defaults)
+            .all(|(curve, default)| curve.as_ref().map(|(c, _)| c) == default.as_ref())
+    }
+
+    fn any(&self) -> bool {
+        self.curves.iter().any(Option::is_some)
+    }
+}
+
+impl ResponseCurves {
+    /// Create a set without curves.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a default curve, restored when a state without curves is loaded.
+    pub fn with_curve(self, target: CurveTarget, curve: CurveShape) -> Self {
+        if let Some(slot) = target.slot() {
+            let mut slots = self.lock();
+            slots.defaults[slot] = Some(curve.clone());
+            slots.set(slot, Some(curve));
+            self.inner.active.store(true, Ordering::Release);
+        }
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Slots> {
+        self.inner.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set or clear (`None`) the curve for `target`.
+    ///
+    /// Returns false for an invalid target.
+    pub fn set(&self, target: CurveTarget, curve: Option<CurveShape>) -> bool {
+        let Some(slot) = target.slot() else {
+            return false;
+        };
+        let mut slots = self.lock();
+        slots.set(slot, curve);
+        self.inner.active.store(slots.any(), Ordering::Release);
+        true
+    }
+
+    /// Current curve for `target`.
+    pub fn curve(&self, target: CurveTarget) -> Option<CurveShape> {
+        let slot = target.slot()?;
+        self.lock().curves[slot].as_ref().map(|(curve, _)| curve.clone())
+    }
+
+    /// Returns true if any curve is set.
+    pub fn is_active(&self) -> bool {
+        self.inner.active.load(Ordering::Acquire)
+    }
+
+    /// Shape a single value (0.0-1.0) for `target`.
+    ///
+    /// Pitch bend is expected in its normalized form (center 0.5).
+    /// Real-time safe.
+    pub fn shape(&self, target: CurveTarget, value: f32) -> f32 {
+        if !self.is_active() {
+            return value;
+        }
+        let (Some(slot), Ok(slots)) = (target.slot(), self.inner.slots.try_lock()) else {
+            return value;
+        };
+        match &slots.curves[slot] {
+            Some((_, table)) if target == CurveTarget::Controller(controller::PITCH_BEND) => {
+                let bend = value * 2.0 - 1.0;
+                (table.lookup(bend.abs()).copysign(bend) + 1.0) * 0.5
+            }
+            Some((_, table)) => table.lookup(value),
+            None => value,
+        }
+    }
+
+    /// Shape the velocities and controller values of `events` in place.
+    ///
+    /// Real-time safe.
+    pub fn apply(&self, events: &mut [MidiEvent]) {
+        if !self.is_active() {
+            return;
+        }
+        let Ok(slots) = self.inner.slots.try_lock() else {
+            return;
+        };
+        let table = |slot: usize| slots.curves[slot].as_ref().map(|(_, table)| table);
+        for event in events {
+            match &mut event.event {
+                MidiEventKind::NoteOn(note) => {
+                    if let Some(table) = table(VELOCITY_SLOT) {
+                        note.velocity = table.lookup(note.velocity);
+                    }
+                }
+                MidiEventKind::ControlChange(cc) => {
+                    if let Some(table) = table(cc.controller as usize) {
+                        cc.value = table.lookup(cc.value);
+                    }
+                }
+                MidiEventKind::ChannelPressure(cp) => {
+                    if let Some(table) = table(controller::AFTERTOUCH as usize) {
+                        cp.pressure = table.lookup(cp.pressure);
+                    }
+                }
+                MidiEventKind::PitchBend(pb) => {
+                    if let Some(table) = table(controller::PITCH_BEND as usize) {
+                        pb.value = table.lookup(pb.value.abs()).copysign(pb.value);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `{ [target]: curve }` for every shaped target.
+    pub fn to_json(&self) -> serde_json::Value {
+        let slots = self.lock();
+        let map: serde_json::Map<String, serde_json::Value> = slots
+            .curves
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, curve)| {
+                let (curve, _) = curve.as_ref()?;
+                Some((
+                    CurveTarget::from_slot(slot).to_string(),
+                    serde_json::to_value(curve).ok()?,
+                ))
+            })
+            .collect();
+        serde_json::Value::Object(map)
+    }
+
+    /// Replace all curves with the ones in `json` (as written by
+    /// [`to_json()`](Self::to_json)). Unknown targets and curves are skipped.
+    fn load_json(&self, json: &serde_json::Value) {
+        let mut slots = self.lock();
+        for slot in 0..SLOT_COUNT {
+            slots.set(slot, None);
+        }
+        if let Some(map) = json.as_object() {
+            for (target, curve) in map {
+                let slot = target.parse::<CurveTarget>().ok().and_then(CurveTarget::slot);
+                let curve = serde_json::from_value::<CurveShape>(curve.clone()).ok();
+                if let (Some(slot), Some(curve)) = (slot, curve) {
+                    slots.set(slot, Some(curve));
+                }
+            }
+        }
+        self.inner.active.store(slots.any(), Ordering::Release);
+    }
+
+    /// Append the curves trailer to a saved plugin state.
+    ///
+    /// Does nothing while the curves match the plugin's defaults.
+    pub fn append_state(&self, data: &mut Vec<u8>) {
+        if self.lock().is_default() {
+            return;
+        }
+        let json = self.to_json().to_string();
+        data.extend_from_slice(json.as_bytes());
+        data.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        data.extend_from_slice(STATE_TRAILER_MAGIC);
+    }
+
+    /// Apply and strip the curves trailer from a state blob.
+    ///
+    /// Returns the length of the remaining state. A state without a trailer
+    /// restores the default curves and is passed through unchanged.
+    pub fn load_state(&self, data: &[u8]) -> usize {
+        match parse_trailer(data) {
+            Some((len, json)) => {
+                self.load_json(&serde_json::from_slice(json).unwrap_or_default());
+                len
+            }
+            None => {
+                let mut slots = self.lock();
+                for slot in 0..SLOT_COUNT {
+                    let default = slots.defaults[slot].clone();
+                    slots.set(slot, default);
+                }
+                self.inner.active.store(slots.any(), Ordering::Release);
+                data.len()
+            }
+        }
+    }
+}
+
+/// Handle the built-in response curve WebView calls.
+///
+/// Returns `None` if `method` is not one of the response curve invokes.
+pub fn response_curves_invoke(
+    curves: &ResponseCurves,
+    method: &str,
+    args: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    match method {
+        RESPONSE_CURVES_INVOKE => Some(curves.to_json()),
+        SET_RESPONSE_CURVE_INVOKE => {
+            let target = args.first().and_then(|v| v.as_str()).and_then(|s| s.parse().ok());
+            let curve = match args.get(1) {
+                None | Some(serde_json::Value::Null) => Ok(None),
+                Some(value) => serde_json::from_value::<CurveShape>(value.clone()).map(Some),
+            };
+            Some(match (target, curve) {
+                (Some(target), Ok(curve)) => curves.set(target, curve).into(),
+                _ => false.into(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parse `[json][length][magic]` from the end of `data`.
+fn parse_trailer(data: &[u8]) -> Option<(usize, &[u8])> {
+    let magic_start = data.len().checked_sub(STATE_TRAILER_MAGIC.len())?;
+    if &data[magic_start..] != STATE_TRAILER_MAGIC {
+        return None;
+    }
+    let len_start = magic_start.checked_sub(4)?;
+    let len = u32::from_le_bytes(data[len_start..magic_start].try_into().ok()?) as usize;
+    let json_start = len_start.checked_sub(len)?;
+    Some((json_start, &data[json_start..len_start]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_shapes_and_events() {
+        let power = CurveShape::Power { exponent: 2.0 };
+        assert!((power.evaluate(0.5) - 0.25).abs() < 1e-9);
+        let s = CurveShape::SCurve { steepness: 2.0 };
+        assert!((s.evaluate(0.5) - 0.5).abs() < 1e-9);
+        assert!(s.evaluate(0.25) < 0.25);
+        let points = CurveShape::Points {
+            points: vec![[1.0, 1.0], [0.0, 0.0], [0.5, 0.2]],
+            smooth: true,
+        };
+        assert!((points.evaluate(0.5) - 0.2).abs() < 1e-9);
+        // Monotone: no overshoot between rising points.
+        let samples: Vec<f64> = (0..=20).map(|i| points.evaluate(i as f64 / 20.0)).collect();
+        assert!(samples.windows(2).all(|w| w[1] >= w[0]));
+
+        let curves = ResponseCurves::new()
+            .with_curve(CurveTarget::Velocity, power)
+            .with_curve(CurveTarget::Controller(controller::PITCH_BEND), CurveShape::Power { exponent: 2.0 });
+        let mut events = [
+            MidiEvent::note_on(0, 0, 60, 0.5, -1, 0.0, 0),
+            MidiEvent::control_change(0, 0, 1, 0.5),
+            MidiEvent::pitch_bend(0, 0, -0.5),
+        ];
+        curves.apply(&mut events);
+        let MidiEventKind::NoteOn(note) = &events[0].event else { panic!() };
+        assert!((note.velocity - 0.25).abs() < 1e-3);
+        let MidiEventKind::ControlChange(cc) = &events[1].event else { panic!() };
+        assert_eq!(cc.value, 0.5);
+        let MidiEventKind::PitchBend(pb) = &events[2].event else { panic!() };
+        assert!((pb.value + 0.25).abs() < 1e-3);
+        // Normalized pitch bend 0.25 is a bend of -0.5.
+        assert!((curves.shape(CurveTarget::Controller(controller::PITCH_BEND), 0.25) - 0.375).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_state_trailer_and_invoke() {
+        let curves = ResponseCurves::new().with_curve(CurveTarget::Velocity, CurveShape::Linear);
+        let mut data = b"plugin".to_vec();
+        curves.append_state(&mut data);
+        assert_eq!(data, b"plugin"); // Defaults write no trailer
+
+        let args = [serde_json::json!("cc11"), serde_json::json!({ "type": "power", "exponent": 3.0 })];
+        assert_eq!(response_curves_invoke(&curves, SET_RESPONSE_CURVE_INVOKE, &args), Some(true.into()));
+        assert_eq!(
+            response_curves_invoke(&curves, SET_RESPONSE_CURVE_INVOKE, &[serde_json::json!("cc200")]),
+            Some(false.into())
+        );
+        curves.append_state(&mut data);
+
+        let restored = ResponseCurves::new().with_curve(CurveTarget::Velocity, CurveShape::Linear);
+        restored.set(CurveTarget::Velocity, None);
+        assert_eq!(restored.load_state(&data), 6);
+        assert_eq!(restored.to_json(), curves.to_json());
+        assert_eq!(
+            restored.curve(CurveTarget::Controller(11)),
+            Some(CurveShape::Power { exponent: 3.0 })
+        );
+
+        // No trailer: back to the defaults.
+        assert_eq!(restored.load_state(b"plugin"), 6);
+        assert_eq!(restored.curve(CurveTarget::Controller(11)), None);
+        assert_eq!(restored.curve(CurveTarget::Velocity), Some(CurveShape::Linear));
+        assert_eq!(response_curves_invoke(&restored, "other", &[]), None);
+    }
+}
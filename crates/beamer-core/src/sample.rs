@@ -79,6 +79,10 @@ pub trait Sample:
     fn clamp(self, min: Self, max: Self) -> Self {
         self.max(min).min(max)
     }
+
+    /// Returns true for denormal (subnormal) values, which many CPUs
+    /// process far more slowly than normal ones.
+    fn is_subnormal(self) -> bool;
 }
 
 impl Sample for f32 {
@@ -139,6 +143,11 @@ impl Sample for f32 {
     fn clamp(self, min: Self, max: Self) -> Self {
         f32::clamp(self, min, max)
     }
+
+    #[inline(always)]
+    fn is_subnormal(self) -> bool {
+        f32::is_subnormal(self)
+    }
 }
 
 impl Sample for f64 {
@@ -199,4 +208,9 @@ impl Sample for f64 {
     fn clamp(self, min: Self, max: Self) -> Self {
         f64::clamp(self, min, max)
     }
+
+    #[inline(always)]
+    fn is_subnormal(self) -> bool {
+        f64::is_subnormal(self)
+    }
 }
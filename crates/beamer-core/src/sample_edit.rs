@@ -0,0 +1,580 @@
+//! Undoable sample editing with lock-free playback snapshots.
+//!
+//! Samplers that let users trim or normalize a loaded sample have two
+//! threads on the same audio: the GUI edits it while the processor plays
+//! it. This module keeps them apart:
+//!
+//! - [`SampleSnapshot`] - An immutable, random-access view of the edited
+//!   sample. It is a list of regions over shared source buffers, so trims,
+//!   deletes and gain changes copy no audio; only fades write new frames
+//!   (copy-on-write, for the faded range alone).
+//! - [`SampleEditor`] - Applies [`SampleEdit`]s on the main thread and keeps
+//!   the undo and redo history as snapshots.
+//! - [`SampleReceiver`] - The processor's side. [`update()`](SampleReceiver::update)
+//!   picks up the latest snapshot without blocking, and the replaced one is
+//!   handed back to the editor to be freed, so the audio thread never
+//!   deallocates.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Descriptor / GUI handler (main thread)
+//! let mut editor = SampleEditor::new(SampleSnapshot::from_channels(decoded, 48000.0));
+//! let receiver = editor.receiver(); // hand to the processor in prepare()
+//!
+//! editor.apply(SampleEdit::Trim { start: 1200, end: 96000 })?;
+//! editor.apply(SampleEdit::Normalize { peak: 1.0 })?;
+//! editor.undo();
+//!
+//! // Processor::process()
+//! self.sample.update();
+//! let frames = self.sample.snapshot().read(channel, self.position, output);
+//! ```
+//!
+//! Keep the editor behind the WebView handler's `Mutex`; edits allocate and
+//! must not run on the audio thread.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Default number of undo steps kept by [`SampleEditor`].
+pub const DEFAULT_UNDO_LIMIT: usize = 64;
+
+/// Audio as loaded, shared by all regions and snapshots that use it.
+struct Source {
+    channels: Vec<Vec<f32>>,
+}
+
+/// A run of source frames played with a gain.
+#[derive(Clone)]
+struct Region {
+    source: Arc<Source>,
+    start: usize,
+    len: usize,
+    gain: f32,
+}
+
+/// Immutable view of an edited sample.
+///
+/// Cloning shares the audio. Reading is real-time safe.
+#[derive(Clone)]
+pub struct SampleSnapshot {
+    regions: Vec<Region>,
+    /// First frame of each region.
+    starts: Vec<usize>,
+    len: usize,
+    channels: usize,
+    sample_rate: f64,
+}
+
+impl fmt::Debug for SampleSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SampleSnapshot")
+            .field("len", &self.len)
+            .field("channels", &self.channels)
+            .field("sample_rate", &self.sample_rate)
+            .field("regions", &self.regions.len())
+            .finish()
+    }
+}
+
+impl SampleSnapshot {
+    /// Wrap decoded audio, one `Vec` per channel. Channels are cut to the
+    /// shortest one.
+    pub fn from_channels(mut channels: Vec<Vec<f32>>, sample_rate: f64) -> Self {
+        let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+        for channel in &mut channels {
+            channel.truncate(len);
+        }
+        let count = channels.len();
+        let source = Arc::new(Source { channels });
+        let mut snapshot = Self {
+            regions: vec![Region { source, start: 0, len, gain: 1.0 }],
+            starts: Vec::new(),
+            len,
+            channels: count,
+            sample_rate,
+        };
+        snapshot.rebuild();
+        snapshot
+    }
+
+    /// Length in frames.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the sample has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of channels.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Sample rate of the audio in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Number of regions the sample is made of (1 before any edit).
+    pub fn region_count(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// One frame of one channel, 0.0 out of range. Real-time safe.
+    pub fn frame(&self, channel: usize, frame: usize) -> f32 {
+        let mut value = [0.0];
+        self.read(channel, frame, &mut value);
+        value[0]
+    }
+
+    /// Copy frames of `channel` from `start` into `output`.
+    ///
+    /// Returns the number of frames written, less than `output.len()` at
+    /// the end of the sample; the rest of `output` is left untouched.
+    /// Real-time safe.
+    pub fn read(&self, channel: usize, start: usize, output: &mut [f32]) -> usize {
+        if channel >= self.channels || start >= self.len {
+            return 0;
+        }
+        let total = output.len().min(self.len - start);
+        let mut written = 0;
+        let mut index = self.starts.partition_point(|&s| s <= start) - 1;
+        let mut offset = start - self.starts[index];
+        while written < total {
+            let region = &self.regions[index];
+            let count = (region.len - offset).min(total - written);
+            let from = region.start + offset;
+            let source = &region.source.channels[channel][from..from + count];
+            let out = &mut output[written..written + count];
+            if region.gain == 1.0 {
+                out.copy_from_slice(source);
+            } else {
+                for (out, &sample) in out.iter_mut().zip(source) {
+                    *out = sample * region.gain;
+                }
+            }
+            written += count;
+            index += 1;
+            offset = 0;
+        }
+        written
+    }
+
+    /// Largest absolute sample value over all channels.
+    pub fn peak(&self) -> f32 {
+        self.regions
+            .iter()
+            .flat_map(|region| {
+                region.source.channels.iter().map(move |channel| {
+                    let frames = &channel[region.start..region.start + region.len];
+                    frames.iter().fold(0.0f32, |peak, &s| peak.max(s.abs())) * region.gain.abs()
+                })
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Render the edited sample into plain channel buffers (for saving).
+    pub fn to_channels(&self) -> Vec<Vec<f32>> {
+        (0..self.channels)
+            .map(|channel| {
+                let mut frames = vec![0.0; self.len];
+                self.read(channel, 0, &mut frames);
+                frames
+            })
+            .collect()
+    }
+
+    fn rebuild(&mut self) {
+        self.regions.retain(|region| region.len > 0);
+        self.starts.clear();
+        let mut position = 0;
+        for region in &self.regions {
+            self.starts.push(position);
+            position += region.len;
+        }
+        self.len = position;
+        if self.regions.is_empty() {
+            // Keep one (empty) start so lookups stay simple
+            self.starts.push(0);
+        }
+    }
+
+    /// Make `frame` a region boundary and return the index of the region
+    /// starting there.
+    fn split(&mut self, frame: usize) -> usize {
+        let index = self.starts.partition_point(|&s| s < frame);
+        if index < self.regions.len() && self.starts[index] == frame {
+            return index;
+        }
+        if frame >= self.len {
+            return self.regions.len();
+        }
+        let index = index - 1;
+        let offset = frame - self.starts[index];
+        let mut tail = self.regions[index].clone();
+        tail.start += offset;
+        tail.len -= offset;
+        self.regions[index].len = offset;
+        self.regions.insert(index + 1, tail);
+        self.starts.insert(index + 1, frame);
+        index + 1
+    }
+
+    /// Region indices covering `range` exactly.
+    fn regions_in(&mut self, range: Range<usize>) -> Range<usize> {
+        let first = self.split(range.start);
+        let end = self.split(range.end);
+        first..end
+    }
+
+    fn apply(&mut self, edit: &SampleEdit) -> Result<(), SampleEditError> {
+        let checked = |start: usize, end: usize| {
+            if start < end && end <= self.len {
+                Ok(start..end)
+            } else {
+                Err(SampleEditError::OutOfRange { start, end, len: self.len })
+            }
+        };
+        match *edit {
+            SampleEdit::Trim { start, end } => {
+                let range = checked(start, end)?;
+                let keep = self.regions_in(range);
+                self.regions.truncate(keep.end);
+                self.regions.drain(..keep.start);
+            }
+            SampleEdit::Delete { start, end } => {
+                let range = checked(start, end)?;
+                let remove = self.regions_in(range);
+                self.regions.drain(remove);
+            }
+            SampleEdit::Gain { start, end, gain } => {
+                let range = checked(start, end)?;
+                let regions = self.regions_in(range);
+                for region in &mut self.regions[regions] {
+                    region.gain *= gain;
+                }
+            }
+            SampleEdit::Silence { start, end } => {
+                let range = checked(start, end)?;
+                let regions = self.regions_in(range);
+                for region in &mut self.regions[regions] {
+                    region.gain = 0.0;
+                }
+            }
+            SampleEdit::Normalize { peak } => {
+                let current = self.peak();
+                if current <= 0.0 {
+                    return Err(SampleEditError::Silent);
+                }
+                let gain = peak / current;
+                for region in &mut self.regions {
+                    region.gain *= gain;
+                }
+            }
+            SampleEdit::FadeIn { start, end } | SampleEdit::FadeOut { start, end } => {
+                let range = checked(start, end)?;
+                let fade_in = matches!(edit, SampleEdit::FadeIn { .. });
+                let len = range.len();
+                // Copy-on-write: only the faded frames get a new buffer
+                let channels = (0..self.channels)
+                    .map(|channel| {
+                        let mut frames = vec![0.0; len];
+                        self.read(channel, range.start, &mut frames);
+                        for (i, frame) in frames.iter_mut().enumerate() {
+                            let ramp = (i as f32 + 0.5) / len as f32;
+                            *frame *= if fade_in { ramp } else { 1.0 - ramp };
+                        }
+                        frames
+                    })
+                    .collect();
+                let regions = self.regions_in(range);
+                let faded = Region {
+                    source: Arc::new(Source { channels }),
+                    start: 0,
+                    len,
+                    gain: 1.0,
+                };
+                self.regions.splice(regions, [faded]);
+            }
+        }
+        self.rebuild();
+        Ok(())
+    }
+}
+
+/// An edit on a [`SampleSnapshot`]. Ranges are `start..end` in frames of
+/// the current (already edited) sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleEdit {
+    /// Keep only `start..end`.
+    Trim { start: usize, end: usize },
+    /// Remove `start..end`.
+    Delete { start: usize, end: usize },
+    /// Multiply `start..end` by a linear gain.
+    Gain { start: usize, end: usize, gain: f32 },
+    /// Silence `start..end`.
+    Silence { start: usize, end: usize },
+    /// Scale the whole sample so its peak is `peak` (linear, 1.0 = 0 dBFS).
+    Normalize { peak: f32 },
+    /// Linear fade from silence over `start..end`.
+    FadeIn { start: usize, end: usize },
+    /// Linear fade to silence over `start..end`.
+    FadeOut { start: usize, end: usize },
+}
+
+/// Why an edit was rejected. The sample is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SampleEditError {
+    /// The range is empty or reaches past the end of the sample.
+    OutOfRange { start: usize, end: usize, len: usize },
+    /// Normalizing a silent sample.
+    Silent,
+}
+
+impl fmt::Display for SampleEditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange { start, end, len } => {
+                write!(f, "edit range {start}..{end} is outside the sample (0..{len})")
+            }
+            Self::Silent => write!(f, "cannot normalize a silent sample"),
+        }
+    }
+}
+
+impl std::error::Error for SampleEditError {}
+
+/// Snapshot exchange between the editor and the receiver.
+enum Slot {
+    Empty,
+    /// Published, not yet picked up.
+    Pending(Arc<SampleSnapshot>),
+    /// Replaced on the audio thread, to be freed by the editor.
+    #[allow(dead_code)] // only held so it is dropped on the editor's thread
+    Retired(Arc<SampleSnapshot>),
+}
+
+/// Edits a sample with undo and redo, publishing every change to the
+/// [`SampleReceiver`].
+///
+/// Main thread only: edits allocate.
+pub struct SampleEditor {
+    current: Arc<SampleSnapshot>,
+    undo: Vec<Arc<SampleSnapshot>>,
+    redo: Vec<Arc<SampleSnapshot>>,
+    undo_limit: usize,
+    slot: Arc<Mutex<Slot>>,
+}
+
+impl SampleEditor {
+    /// Start editing `sample`.
+    pub fn new(sample: SampleSnapshot) -> Self {
+        Self {
+            current: Arc::new(sample),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            undo_limit: DEFAULT_UNDO_LIMIT,
+            slot: Arc::new(Mutex::new(Slot::Empty)),
+        }
+    }
+
+    /// Keep at most `limit` undo steps.
+    pub fn with_undo_limit(mut self, limit: usize) -> Self {
+        self.undo_limit = limit;
+        self
+    }
+
+    /// The receiver for the processor, starting at the current sample.
+    ///
+    /// Create one per editor: each published snapshot is picked up once.
+    pub fn receiver(&self) -> SampleReceiver {
+        SampleReceiver {
+            current: Arc::clone(&self.current),
+            slot: Arc::clone(&self.slot),
+        }
+    }
+
+    /// The current sample.
+    pub fn snapshot(&self) -> Arc<SampleSnapshot> {
+        Arc::clone(&self.current)
+    }
+
+    /// Replace the sample (a new file was loaded) and clear the history.
+    pub fn load(&mut self, sample: SampleSnapshot) {
+        self.undo.clear();
+        self.redo.clear();
+        self.set_current(Arc::new(sample));
+    }
+
+    /// Apply an edit, making it undoable.
+    pub fn apply(&mut self, edit: SampleEdit) -> Result<(), SampleEditError> {
+        let mut edited = (*self.current).clone();
+        edited.apply(&edit)?;
+        let previous = std::mem::replace(&mut self.current, Arc::new(edited));
+        self.undo.push(previous);
+        if self.undo.len() > self.undo_limit {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.publish();
+        Ok(())
+    }
+
+    /// Revert the last edit. Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(previous) = self.undo.pop() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.current, previous);
+        self.redo.push(current);
+        self.publish();
+        true
+    }
+
+    /// Reapply the last undone edit. Returns false if there is nothing to
+    /// redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        let current = std::mem::replace(&mut self.current, next);
+        self.undo.push(current);
+        self.publish();
+        true
+    }
+
+    /// Whether [`undo()`](Self::undo) would change the sample.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether [`redo()`](Self::redo) would change the sample.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Free a snapshot the receiver has replaced. Also done by every edit.
+    pub fn collect(&self) {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*slot, Slot::Retired(_)) {
+            *slot = Slot::Empty;
+        }
+    }
+
+    fn set_current(&mut self, sample: Arc<SampleSnapshot>) {
+        self.current = sample;
+        self.publish();
+    }
+
+    fn publish(&self) {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        // A retired or never picked up snapshot is dropped here, on the
+        // editor's thread
+        *slot = Slot::Pending(Arc::clone(&self.current));
+    }
+}
+
+/// The processor's view of an edited sample.
+pub struct SampleReceiver {
+    current: Arc<SampleSnapshot>,
+    slot: Arc<Mutex<Slot>>,
+}
+
+impl SampleReceiver {
+    /// Pick up the latest published snapshot. Returns true if the sample
+    /// changed.
+    ///
+    /// Real-time safe: never blocks (if the editor is publishing, the
+    /// change arrives with the next call) and never frees a snapshot; the
+    /// replaced one goes back to the editor.
+    pub fn update(&mut self) -> bool {
+        let Ok(mut slot) = self.slot.try_lock() else {
+            return false;
+        };
+        if !matches!(*slot, Slot::Pending(_)) {
+            return false;
+        }
+        let Slot::Pending(next) = std::mem::replace(&mut *slot, Slot::Empty) else {
+            return false;
+        };
+        let previous = std::mem::replace(&mut self.current, next);
+        *slot = Slot::Retired(previous);
+        true
+    }
+
+    /// The sample as of the last [`update()`](Self::update).
+    pub fn snapshot(&self) -> &SampleSnapshot {
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(len: usize) -> SampleSnapshot {
+        let frames: Vec<f32> = (0..len).map(|i| i as f32 / 10.0).collect();
+        SampleSnapshot::from_channels(vec![frames.clone(), frames], 48000.0)
+    }
+
+    #[test]
+    fn edits_share_audio_and_undo() {
+        let mut editor = SampleEditor::new(ramp(10));
+        editor.apply(SampleEdit::Trim { start: 2, end: 8 }).unwrap();
+        editor.apply(SampleEdit::Delete { start: 1, end: 3 }).unwrap();
+        editor.apply(SampleEdit::Gain { start: 0, end: 2, gain: 2.0 }).unwrap();
+        let sample = editor.snapshot();
+        assert_eq!(sample.to_channels()[1], [0.4, 1.0, 0.6, 0.7]);
+        // No audio was copied: all regions still point at the loaded buffer
+        let source = &sample.regions[0].source;
+        assert!(sample.regions.iter().all(|r| Arc::ptr_eq(&r.source, source)));
+
+        // Random access across region boundaries
+        let mut out = [0.0; 3];
+        assert_eq!(sample.read(0, 1, &mut out), 3);
+        assert_eq!(out, [1.0, 0.6, 0.7]);
+        assert_eq!(sample.read(0, 3, &mut out), 1);
+        assert_eq!(sample.frame(0, 9), 0.0);
+
+        editor.apply(SampleEdit::Normalize { peak: 0.5 }).unwrap();
+        assert!((editor.snapshot().peak() - 0.5).abs() < 1e-6);
+        assert!(editor.undo() && editor.undo());
+        assert_eq!(editor.snapshot().to_channels()[0], [0.2, 0.5, 0.6, 0.7]);
+        assert!(editor.redo());
+        assert_eq!(editor.snapshot().frame(0, 0), 0.4);
+        assert!(editor.can_redo());
+
+        let error = editor.apply(SampleEdit::Trim { start: 3, end: 9 }).unwrap_err();
+        assert_eq!(error, SampleEditError::OutOfRange { start: 3, end: 9, len: 4 });
+        assert!(editor.can_redo(), "a rejected edit keeps the history");
+    }
+
+    #[test]
+    fn receiver_picks_up_snapshots_and_hands_back_old_ones() {
+        let mut editor = SampleEditor::new(ramp(8));
+        let mut receiver = editor.receiver();
+        let original = editor.snapshot();
+        assert!(!receiver.update());
+
+        editor.apply(SampleEdit::FadeOut { start: 4, end: 8 }).unwrap();
+        editor.apply(SampleEdit::Silence { start: 0, end: 2 }).unwrap();
+        assert!(receiver.update());
+        assert!(!receiver.update());
+        let sample = receiver.snapshot();
+        assert_eq!(sample.len(), 8);
+        assert_eq!(&sample.to_channels()[0][..4], [0.0, 0.0, 0.2, 0.3]);
+        assert!((sample.frame(0, 4) - 0.4 * 0.875).abs() < 1e-6);
+        assert_eq!(sample.region_count(), 3);
+
+        // The snapshot the receiver replaced waits in the slot for the
+        // editor, not freed on the audio thread
+        assert_eq!(Arc::strong_count(&original), 3); // test, history, slot
+        editor.collect();
+        assert_eq!(Arc::strong_count(&original), 2);
+    }
+}
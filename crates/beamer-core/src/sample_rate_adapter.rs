@@ -0,0 +1,613 @@
+//! Automatic sample rate conversion around `process()`.
+//!
+//! Some algorithms only work at specific rates (fixed oversampling factors,
+//! coefficient tables, neural models trained at 48 kHz). A plugin declares
+//! what it supports with [`Config::with_sample_rate_range`] or
+//! [`Config::with_sample_rates`] (`sample_rate_range` / `sample_rates` in
+//! `Config.toml`). When the host runs outside that set, the wrappers:
+//!
+//! 1. Prepare the processor at
+//!    [`SupportedSampleRates::processing_rate`] instead of the host rate.
+//! 2. Wrap every `process()` call in a [`SampleRateAdapter`]: host-rate
+//!    inputs are resampled to the processing rate, the processor runs on
+//!    internal buffers, and its outputs are resampled back.
+//! 3. Report the adapter's latency plus the processor's own latency
+//!    (converted to host samples) to the host.
+//!
+//! The resampler is a windowed-sinc (Blackman, 32 taps) polyphase filter with
+//! the cutoff lowered when converting down, so the processor never sees
+//! content it cannot represent. Main and auxiliary buses are converted.
+//!
+//! # Limitations
+//!
+//! - The processor sees different block sizes than the host (the resampled
+//!   length, split to fit `MaxBufferSize` scaled to the processing rate).
+//! - MIDI events are still delivered through `process_midi()` with host
+//!   sample offsets.
+//! - The transport's sample positions are scaled to the processing rate.
+//!
+//! # Real-Time Safety
+//!
+//! All buffers are allocated in [`SampleRateAdapter::new`]. Processing does
+//! not allocate as long as the host respects its declared maximum block size.
+//!
+//! [`Config::with_sample_rate_range`]: crate::Config::with_sample_rate_range
+//! [`Config::with_sample_rates`]: crate::Config::with_sample_rates
+//! [`SupportedSampleRates::processing_rate`]: crate::SupportedSampleRates::processing_rate
+
+use std::collections::VecDeque;
+
+use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::plugin::BusInfo;
+use crate::process_context::ProcessContext;
+use crate::sample::Sample;
+use crate::speaker_layout::SpeakerLayout;
+use crate::types::{MAX_AUX_BUSES, MAX_CHANNELS};
+
+/// Filter half-length in input samples. Also the look-ahead of each stage.
+const HALF_TAPS: usize = 16;
+/// Total filter length.
+const TAPS: usize = 2 * HALF_TAPS;
+/// Number of fractional positions in the kernel table.
+const PHASES: usize = 128;
+/// Passband edge as a fraction of the lower Nyquist frequency.
+const PASSBAND: f64 = 0.9;
+
+/// One conversion direction (host → processing or processing → host).
+///
+/// All channels share the read position, so they always produce the same
+/// number of output samples.
+struct Stage {
+    /// `(PHASES + 1) * TAPS` interpolation weights.
+    kernel: Vec<f64>,
+    /// Input samples advanced per output sample.
+    step: f64,
+    /// Read position in `history` of the next output sample.
+    position: f64,
+    /// Samples written per channel and not yet discarded.
+    len: usize,
+    history: Vec<Vec<f64>>,
+}
+
+impl Stage {
+    fn new(from_rate: f64, to_rate: f64, channels: usize, max_input: usize) -> Self {
+        let step = from_rate / to_rate;
+        let cutoff = PASSBAND * (1.0 / step).min(1.0);
+
+        let mut kernel = vec![0.0; (PHASES + 1) * TAPS];
+        for (phase, row) in kernel.chunks_exact_mut(TAPS).enumerate() {
+            let frac = phase as f64 / PHASES as f64;
+            for (k, weight) in row.iter_mut().enumerate() {
+                let x = k as f64 - (HALF_TAPS as f64 - 1.0) - frac;
+                *weight = cutoff * sinc(cutoff * x) * blackman(x / HALF_TAPS as f64);
+            }
+            // Unity gain at DC for every phase
+            let sum: f64 = row.iter().sum();
+            row.iter_mut().for_each(|w| *w /= sum);
+        }
+
+        let capacity = max_input + TAPS + 4;
+        let mut stage = Self {
+            kernel,
+            step,
+            position: 0.0,
+            len: 0,
+            history: (0..channels).map(|_| Vec::with_capacity(capacity)).collect(),
+        };
+        stage.reset();
+        stage
+    }
+
+    fn reset(&mut self) {
+        // Silence before the first sample, so output 0 lines up with input 0
+        for history in &mut self.history {
+            history.clear();
+            history.resize(HALF_TAPS - 1, 0.0);
+        }
+        self.len = HALF_TAPS - 1;
+        self.position = (HALF_TAPS - 1) as f64;
+    }
+
+    /// Append `num_samples` to one channel. Missing input is silence.
+    fn write<S: Sample>(&mut self, channel: usize, input: &[S], num_samples: usize) {
+        let history = &mut self.history[channel];
+        history.extend(input.iter().take(num_samples).map(|s| s.to_f64()));
+        history.resize(self.len + num_samples, 0.0);
+    }
+
+    /// Commit `num_samples` written to every channel.
+    fn advance(&mut self, num_samples: usize) {
+        self.len += num_samples;
+    }
+
+    /// Produce every output sample the written input allows.
+    ///
+    /// Returns the number of samples produced per channel.
+    fn produce(&mut self, mut emit: impl FnMut(usize, f64)) -> usize {
+        let mut produced = 0;
+        while self.position as usize + HALF_TAPS < self.len {
+            let index = self.position as usize;
+            let phase = (self.position - index as f64) * PHASES as f64;
+            let row = phase as usize;
+            let t = phase - row as f64;
+            let a = &self.kernel[row * TAPS..(row + 1) * TAPS];
+            let b = &self.kernel[(row + 1) * TAPS..(row + 2) * TAPS];
+
+            for (channel, history) in self.history.iter().enumerate() {
+                let window = &history[index + 1 - HALF_TAPS..=index + HALF_TAPS];
+                let mut acc = 0.0;
+                for k in 0..TAPS {
+                    acc += window[k] * (a[k] + (b[k] - a[k]) * t);
+                }
+                emit(channel, acc);
+            }
+            self.position += self.step;
+            produced += 1;
+        }
+
+        // Drop history the next output no longer needs
+        let consumed = (self.position as usize + 1).saturating_sub(HALF_TAPS).min(self.len);
+        for history in &mut self.history {
+            history.drain(..consumed);
+        }
+        self.len -= consumed;
+        self.position -= consumed as f64;
+        produced
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+fn blackman(t: f64) -> f64 {
+    if t.abs() > 1.0 {
+        return 0.0;
+    }
+    let pt = std::f64::consts::PI * t;
+    0.42 + 0.5 * pt.cos() + 0.08 * (2.0 * pt).cos()
+}
+
+/// Resamples host-rate buffers to the processing rate around `process()`.
+///
+/// Created by the format wrappers when the host rate is outside
+/// [`Config::sample_rates`](crate::Config::sample_rates); see the
+/// [module documentation](self).
+pub struct SampleRateAdapter<S: Sample> {
+    host_rate: f64,
+    processing_rate: f64,
+    /// Largest block passed to the processor (in processing-rate samples).
+    max_processing_block: usize,
+    /// Channel counts: `[main, aux 0, aux 1, ...]`.
+    input_layout: Vec<usize>,
+    output_layout: Vec<usize>,
+    down: Stage,
+    up: Stage,
+    /// Resampled input waiting for `process()`, per flat input channel.
+    processing_inputs: Vec<Vec<S>>,
+    /// Processor output for one chunk, per flat output channel.
+    processing_outputs: Vec<Vec<S>>,
+    /// Host-rate output waiting to be delivered, per flat output channel.
+    output_fifo: Vec<VecDeque<S>>,
+    /// Fixed delay in host samples that keeps the output FIFO from running dry.
+    latency: usize,
+}
+
+impl<S: Sample> SampleRateAdapter<S> {
+    /// Allocate an adapter for plugin buses (see [`new`](Self::new)).
+    pub fn from_buses(
+        host_rate: f64,
+        processing_rate: f64,
+        max_host_block: usize,
+        input_buses: &[BusInfo],
+        output_buses: &[BusInfo],
+    ) -> Option<Self> {
+        let channels = |buses: &[BusInfo]| -> Vec<usize> {
+            buses.iter().map(|bus| bus.channel_count as usize).collect()
+        };
+        Self::new(
+            host_rate,
+            processing_rate,
+            max_host_block,
+            &channels(input_buses),
+            &channels(output_buses),
+        )
+    }
+
+    /// Allocate an adapter for the given bus channel counts (main bus first,
+    /// then aux buses).
+    ///
+    /// `max_host_block` is the host's maximum block size. Returns `None`
+    /// when the rates are equal (no conversion needed).
+    pub fn new(
+        host_rate: f64,
+        processing_rate: f64,
+        max_host_block: usize,
+        input_channels: &[usize],
+        output_channels: &[usize],
+    ) -> Option<Self> {
+        if (host_rate - processing_rate).abs() < 0.5 || host_rate <= 0.0 || processing_rate <= 0.0 {
+            return None;
+        }
+        let ratio = processing_rate / host_rate;
+        let max_processing_block = Self::processing_block_size(max_host_block, ratio);
+        let max_pending = max_processing_block + 2;
+
+        let layout = |channels: &[usize]| -> Vec<usize> {
+            channels
+                .iter()
+                .take(MAX_AUX_BUSES + 1)
+                .map(|&count| count.min(MAX_CHANNELS))
+                .collect()
+        };
+        let input_layout = layout(input_channels);
+        let output_layout = layout(output_channels);
+        let num_inputs: usize = input_layout.iter().sum();
+        let num_outputs: usize = output_layout.iter().sum();
+
+        // Each stage lags HALF_TAPS of its own input behind; round up and
+        // allow one sample of position rounding per stage.
+        let latency = HALF_TAPS + (HALF_TAPS as f64 / ratio).ceil() as usize + 2;
+
+        let mut adapter = Self {
+            host_rate,
+            processing_rate,
+            max_processing_block,
+            down: Stage::new(host_rate, processing_rate, num_inputs, max_host_block),
+            up: Stage::new(processing_rate, host_rate, num_outputs, max_pending),
+            processing_inputs: (0..num_inputs).map(|_| Vec::with_capacity(max_pending)).collect(),
+            processing_outputs: (0..num_outputs).map(|_| vec![S::ZERO; max_processing_block]).collect(),
+            output_fifo: (0..num_outputs)
+                .map(|_| VecDeque::with_capacity(latency + 2 * max_host_block + 4))
+                .collect(),
+            input_layout,
+            output_layout,
+            latency,
+        };
+        adapter.reset();
+        Some(adapter)
+    }
+
+    /// Maximum block size to prepare the processor with, for a host maximum
+    /// of `max_host_block` samples.
+    pub fn processing_block_size(max_host_block: usize, ratio: f64) -> usize {
+        ((max_host_block as f64 * ratio).ceil() as usize + 1).max(1)
+    }
+
+    /// Call `process` through `adapter` when present, directly otherwise.
+    ///
+    /// Lets wrappers keep a single call site for both paths.
+    pub fn run(
+        adapter: Option<&mut Self>,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        match adapter {
+            Some(adapter) => adapter.process(buffer, aux, context, process),
+            None => process(buffer, aux, context),
+        }
+    }
+
+    /// Host sample rate in Hz.
+    pub fn host_rate(&self) -> f64 {
+        self.host_rate
+    }
+
+    /// Rate the processor runs at in Hz.
+    pub fn processing_rate(&self) -> f64 {
+        self.processing_rate
+    }
+
+    /// Latency to report to the host, in host samples, given the
+    /// processor's own latency in processing-rate samples.
+    pub fn latency_samples(&self, processor_latency: u32) -> u32 {
+        let processor = (processor_latency as f64 * self.host_rate / self.processing_rate).ceil();
+        self.latency as u32 + processor as u32
+    }
+
+    /// Clear all filter state and refill the output delay with silence.
+    pub fn reset(&mut self) {
+        self.down.reset();
+        self.up.reset();
+        for input in &mut self.processing_inputs {
+            input.clear();
+        }
+        for fifo in &mut self.output_fifo {
+            fifo.clear();
+            fifo.extend(std::iter::repeat_n(S::ZERO, self.latency));
+        }
+    }
+
+    /// Run `process` at the processing rate for one host block.
+    ///
+    /// Reads `buffer`/`aux` inputs, calls `process` one or more times with
+    /// processing-rate buffers and context, and writes the resampled result
+    /// to the `buffer`/`aux` outputs.
+    pub fn process(
+        &mut self,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        let num_samples = buffer.num_samples();
+        let ratio = self.processing_rate / self.host_rate;
+        let layouts = (buffer.input_layout(), buffer.output_layout());
+
+        // 1. Host inputs → processing rate
+        let mut flat = 0;
+        for (bus, &channels) in self.input_layout.iter().enumerate() {
+            let aux_input = if bus == 0 { None } else { aux.input(bus - 1) };
+            for channel in 0..channels {
+                let input = match &aux_input {
+                    Some(aux_input) => aux_input.input(channel),
+                    None if bus == 0 => buffer.input(channel),
+                    None => &[],
+                };
+                self.down.write(flat, input, num_samples);
+                flat += 1;
+            }
+        }
+        self.down.advance(num_samples);
+        let inputs = &mut self.processing_inputs;
+        let pending = self.down.produce(|channel, value| inputs[channel].push(S::from_f64(value)));
+
+        // 2. Process in chunks at the processing rate
+        let mut offset = 0;
+        while offset < pending {
+            let len = (pending - offset).min(self.max_processing_block);
+            let mut transport = context.transport;
+            let scale = |t: i64| (t as f64 * ratio) as i64 + offset as i64;
+            transport.project_time_samples = transport.project_time_samples.map(scale);
+            transport.continuous_time_samples = transport.continuous_time_samples.map(scale);
+            let chunk_context = match context.midi_cc() {
+                Some(cc) => ProcessContext::with_midi_cc(self.processing_rate, len, transport, cc),
+                None => ProcessContext::new(self.processing_rate, len, transport),
+            };
+
+            for output in &mut self.processing_outputs {
+                output[..len].fill(S::ZERO);
+            }
+            self.run_chunk(offset, len, layouts, &chunk_context, &mut process);
+
+            for (channel, output) in self.processing_outputs.iter().enumerate() {
+                self.up.write(channel, &output[..len], len);
+            }
+            self.up.advance(len);
+            let fifo = &mut self.output_fifo;
+            self.up.produce(|channel, value| fifo[channel].push_back(S::from_f64(value)));
+            offset += len;
+        }
+        for input in &mut self.processing_inputs {
+            input.clear();
+        }
+
+        // 3. Deliver host-rate output
+        let mut fifos = self.output_fifo.iter_mut();
+        for (bus, &channels) in self.output_layout.iter().enumerate() {
+            if bus == 0 {
+                for (channel, fifo) in fifos.by_ref().take(channels).enumerate() {
+                    deliver(fifo, buffer.output_checked(channel), num_samples);
+                }
+            } else {
+                let mut output = aux.output(bus - 1);
+                for (channel, fifo) in fifos.by_ref().take(channels).enumerate() {
+                    let slice = output.as_mut().and_then(|bus| bus.output_checked(channel));
+                    deliver(fifo, slice, num_samples);
+                }
+            }
+        }
+    }
+
+    fn run_chunk(
+        &mut self,
+        offset: usize,
+        len: usize,
+        layouts: (SpeakerLayout, SpeakerLayout),
+        context: &ProcessContext,
+        process: &mut impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        let (main_inputs, aux_inputs) = split_buses(&self.processing_inputs, &self.input_layout);
+        let (main_outputs, mut aux_outputs) =
+            split_buses_mut(&mut self.processing_outputs, &self.output_layout);
+
+        let mut buffer = Buffer::new(
+            main_inputs.iter().map(|ch| &ch[offset..offset + len]),
+            main_outputs.iter_mut().map(|ch| &mut ch[..len]),
+            len,
+        )
+        .with_speaker_layouts(layouts.0, layouts.1);
+        let mut aux = AuxiliaryBuffers::new(
+            aux_inputs
+                .iter()
+                .flatten()
+                .map(|bus| bus.iter().map(|ch| &ch[offset..offset + len])),
+            aux_outputs
+                .iter_mut()
+                .flatten()
+                .map(|bus| bus.iter_mut().map(|ch| &mut ch[..len])),
+            len,
+        );
+        process(&mut buffer, &mut aux, context);
+    }
+}
+
+/// Pop `num_samples` from `fifo` into `output`, padding with leading
+/// silence if the FIFO runs short.
+fn deliver<S: Sample>(fifo: &mut VecDeque<S>, output: Option<&mut [S]>, num_samples: usize) {
+    let available = fifo.len().min(num_samples);
+    match output {
+        Some(output) => {
+            let shortfall = output.len().min(num_samples).saturating_sub(available);
+            output[..shortfall].fill(S::ZERO);
+            for (sample, value) in output[shortfall..].iter_mut().zip(fifo.drain(..available)) {
+                *sample = value;
+            }
+        }
+        None => {
+            fifo.drain(..available);
+        }
+    }
+}
+
+type AuxBuses<'a, T> = [Option<&'a [Vec<T>]>; MAX_AUX_BUSES];
+type AuxBusesMut<'a, T> = [Option<&'a mut [Vec<T>]>; MAX_AUX_BUSES];
+
+/// Split flat channels into the main bus and aux buses by `layout`.
+fn split_buses<'a, T>(channels: &'a [Vec<T>], layout: &[usize]) -> (&'a [Vec<T>], AuxBuses<'a, T>) {
+    let mut aux: AuxBuses<'a, T> = [None; MAX_AUX_BUSES];
+    let main_count = layout.first().copied().unwrap_or(0);
+    let (main, mut rest) = channels.split_at(main_count);
+    for (slot, &count) in aux.iter_mut().zip(layout.iter().skip(1)) {
+        let (bus, tail) = rest.split_at(count);
+        *slot = Some(bus);
+        rest = tail;
+    }
+    (main, aux)
+}
+
+/// Mutable version of [`split_buses`].
+fn split_buses_mut<'a, T>(
+    channels: &'a mut [Vec<T>],
+    layout: &[usize],
+) -> (&'a mut [Vec<T>], AuxBusesMut<'a, T>) {
+    let mut aux: AuxBusesMut<'a, T> = std::array::from_fn(|_| None);
+    let main_count = layout.first().copied().unwrap_or(0);
+    let (main, mut rest) = channels.split_at_mut(main_count);
+    for (slot, &count) in aux.iter_mut().zip(layout.iter().skip(1)) {
+        let (bus, tail) = rest.split_at_mut(count);
+        *slot = Some(bus);
+        rest = tail;
+    }
+    (main, aux)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_context::Transport;
+
+    fn stereo() -> [usize; 1] {
+        [2]
+    }
+
+    /// Run a sine through the adapter with identity processing.
+    fn run_sine(host_rate: f64, processing_rate: f64, block: usize) -> (Vec<f32>, usize) {
+        let mut adapter =
+            SampleRateAdapter::<f32>::new(host_rate, processing_rate, block, &stereo(), &stereo()).unwrap();
+        let latency = adapter.latency_samples(0) as usize;
+        let total = block * 64;
+        let input: Vec<f32> = (0..total)
+            .map(|i| (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / host_rate).sin() as f32)
+            .collect();
+        let mut result = Vec::with_capacity(total);
+        let mut seen_rate = 0.0;
+
+        for chunk in input.chunks(block) {
+            let mut left = vec![0.0f32; chunk.len()];
+            let mut right = vec![0.0f32; chunk.len()];
+            {
+                let mut buffer = Buffer::new([chunk, chunk], [&mut left[..], &mut right[..]], chunk.len());
+                let mut aux = AuxiliaryBuffers::empty();
+                let context = ProcessContext::new(host_rate, chunk.len(), Transport::default());
+                adapter.process(&mut buffer, &mut aux, &context, |buffer, _aux, context| {
+                    seen_rate = context.sample_rate;
+                    buffer.copy_to_output();
+                });
+            }
+            result.extend_from_slice(&left);
+        }
+        assert_eq!(seen_rate, processing_rate);
+        (result, latency)
+    }
+
+    #[test]
+    fn test_equal_rates_need_no_adapter() {
+        assert!(SampleRateAdapter::<f32>::new(48000.0, 48000.0, 512, &stereo(), &stereo()).is_none());
+    }
+
+    #[test]
+    fn test_sine_survives_round_trip() {
+        for (host, processing, block) in [(96000.0, 48000.0, 256), (44100.0, 48000.0, 100), (48000.0, 44100.0, 1)] {
+            let (output, latency) = run_sine(host, processing, block);
+            // Compare against the delayed input after the filters settle
+            let start = latency + 256;
+            let mut max_error = 0.0f64;
+            for (i, &sample) in output.iter().enumerate().skip(start) {
+                let expected = (2.0 * std::f64::consts::PI * 1000.0 * (i - latency) as f64 / host).sin();
+                max_error = max_error.max((sample as f64 - expected).abs());
+            }
+            assert!(max_error < 0.05, "{host} -> {processing}: error {max_error}");
+        }
+    }
+
+    /// Run an impulse through the adapter with a processor that delays by
+    /// `processor_latency` processing-rate samples. Returns the host sample
+    /// where the output peaks and the latency the adapter reports.
+    fn impulse_delay(host_rate: f64, processing_rate: f64, processor_latency: usize) -> (usize, usize) {
+        let block = 128;
+        let mut adapter =
+            SampleRateAdapter::<f32>::new(host_rate, processing_rate, block, &stereo(), &stereo()).unwrap();
+        let reported = adapter.latency_samples(processor_latency as u32) as usize;
+        let impulse_at = 1000;
+        let total = impulse_at + reported + 4 * block;
+        let mut input = vec![0.0f32; total];
+        input[impulse_at] = 1.0;
+        let mut delay_line = VecDeque::from(vec![0.0f32; processor_latency]);
+        let mut result = Vec::with_capacity(total);
+
+        for chunk in input.chunks(block) {
+            let mut left = vec![0.0f32; chunk.len()];
+            let mut right = vec![0.0f32; chunk.len()];
+            {
+                let mut buffer = Buffer::new([chunk, chunk], [&mut left[..], &mut right[..]], chunk.len());
+                let mut aux = AuxiliaryBuffers::empty();
+                let context = ProcessContext::new(host_rate, chunk.len(), Transport::default());
+                adapter.process(&mut buffer, &mut aux, &context, |buffer, _aux, _context| {
+                    for i in 0..buffer.num_samples() {
+                        delay_line.push_back(buffer.input(0)[i]);
+                        buffer.output(0)[i] = delay_line.pop_front().unwrap_or(0.0);
+                    }
+                });
+            }
+            result.extend_from_slice(&left);
+        }
+
+        let peak = result
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .map(|(i, _)| i)
+            .unwrap();
+        (peak - impulse_at, reported)
+    }
+
+    #[test]
+    fn test_reported_latency_matches_measured_delay() {
+        for (host, processing) in [(96000.0, 48000.0), (48000.0, 96000.0), (44100.0, 48000.0), (48000.0, 44100.0)] {
+            for processor_latency in [0, 64, 333] {
+                let (measured, reported) = impulse_delay(host, processing, processor_latency);
+                // Processor latency is rounded up to whole host samples when
+                // the rates are not integer multiples
+                assert!(
+                    reported >= measured && reported - measured <= 1,
+                    "{host} -> {processing}, latency {processor_latency}: measured {measured}, reported {reported}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_latency_includes_processor_latency() {
+        let adapter = SampleRateAdapter::<f32>::new(96000.0, 48000.0, 512, &stereo(), &stereo()).unwrap();
+        let base = adapter.latency_samples(0);
+        assert_eq!(adapter.latency_samples(100), base + 200);
+    }
+}
@@ -0,0 +1,453 @@
+//! Headless parameter scripting for QA regression tests.
+//!
+//! A script is a JSON file that loads the plugin, feeds it a test signal,
+//! automates parameters and MIDI on a timeline and writes what came out, so
+//! sonic behavior can be checked without writing Rust:
+//!
+//! ```json
+//! {
+//!   "sampleRate": 48000,
+//!   "blockSize": 256,
+//!   "seconds": 2.0,
+//!   "input": { "type": "sine", "frequency": 440, "gain": 0.5 },
+//!   "events": [
+//!     { "time": 0.0, "param": "gain", "plain": -6.0 },
+//!     { "time": 0.5, "param": "drive", "value": 0.8 },
+//!     { "time": 1.0, "note": 60, "velocity": 0.9, "duration": 0.5 },
+//!     { "time": 1.5, "cc": 1, "value": 0.5 }
+//!   ],
+//!   "timeline": "automation.csv",
+//!   "output": "out/render.wav",
+//!   "meters": "out/meters.csv",
+//!   "reference": "golden/render.wav",
+//!   "tolerance": 0.0001
+//! }
+//! ```
+//!
+//! # Fields
+//!
+//! | Field | Default | Meaning |
+//! |-------|---------|---------|
+//! | `sampleRate`, `blockSize` | 48000, 512 | Processing setup (offline mode) |
+//! | `tempo` | 120 | Transport tempo, playing from sample 0 |
+//! | `seconds` | input file length, else 1 | Length of the render |
+//! | `input` | silence | `silence`, `sine` (`frequency`, `gain`), `noise` (`gain`), `impulse` or `file` (`path`, a WAV file) |
+//! | `events` | none | Parameter changes (`param` with a normalized `value` or a `plain` value), notes (`note`, `velocity`, `duration`, `channel`) and CCs (`cc`, `value`, `channel`) |
+//! | `timeline` | none | CSV with a `time,param,value` or `time,param,plain` header, merged into `events` |
+//! | `output` | none | 32-bit float WAV of the main outputs |
+//! | `meters` | none | CSV of the descriptor's [`FrameMeters`](crate::FrameMeters), one row per `meterIntervalMs` (10) |
+//! | `reference` | none | WAV the output must match within `tolerance` (1e-4) |
+//!
+//! Times are in seconds, parameters are addressed by their string ID, and
+//! relative paths are resolved against the script's directory. Blocks are
+//! split at every event, so changes land on the exact sample.
+//!
+//! # Running
+//!
+//! `cargo xtask script <package> <script.json>...` enables the `script`
+//! feature of `beamer`, which makes `export_plugin!` add a test that runs
+//! each script with [`run_file()`] and fails if an output is not finite or
+//! differs from its reference.
+//!
+//! Scripts are rendered with [`OfflineRenderer`](crate::testing::OfflineRenderer),
+//! which calls the plugin directly instead of going through a format
+//! wrapper; use it from Rust tests for the same without a JSON file.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::midi::MidiEvent;
+use crate::plugin::Descriptor;
+use crate::testing::{read_wav, write_file, OfflineRenderer, TestSignal};
+
+// =============================================================================
+// Script
+// =============================================================================
+
+/// A parsed script. See the module docs for the JSON format.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Script {
+    /// Sample rate in Hz.
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    /// Maximum block size in samples.
+    #[serde(default = "default_block_size")]
+    pub block_size: usize,
+    /// Transport tempo in BPM.
+    #[serde(default = "default_tempo")]
+    pub tempo: f64,
+    /// Length of the render in seconds.
+    #[serde(default)]
+    pub seconds: Option<f64>,
+    /// Signal fed to the main inputs.
+    #[serde(default)]
+    pub input: ScriptInput,
+    /// Parameter, note and CC events.
+    #[serde(default)]
+    pub events: Vec<ScriptEvent>,
+    /// CSV file with more parameter events.
+    #[serde(default)]
+    pub timeline: Option<PathBuf>,
+    /// WAV file the main outputs are written to.
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+    /// CSV file the frame meters are written to.
+    #[serde(default)]
+    pub meters: Option<PathBuf>,
+    /// Time between two meter rows in milliseconds.
+    #[serde(default = "default_meter_interval")]
+    pub meter_interval_ms: f64,
+    /// WAV file the output is compared against.
+    #[serde(default)]
+    pub reference: Option<PathBuf>,
+    /// Largest allowed sample difference from the reference.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+}
+
+fn default_sample_rate() -> f64 {
+    48000.0
+}
+
+fn default_block_size() -> usize {
+    512
+}
+
+fn default_tempo() -> f64 {
+    120.0
+}
+
+fn default_meter_interval() -> f64 {
+    10.0
+}
+
+fn default_tolerance() -> f64 {
+    1e-4
+}
+
+fn default_gain() -> f64 {
+    0.5
+}
+
+fn default_velocity() -> f32 {
+    0.8
+}
+
+fn default_duration() -> f64 {
+    0.5
+}
+
+/// Signal fed to the main inputs.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ScriptInput {
+    /// All zeros.
+    #[default]
+    Silence,
+    /// A sine wave on every channel.
+    Sine {
+        /// Frequency in Hz.
+        frequency: f64,
+        /// Linear amplitude.
+        #[serde(default = "default_gain")]
+        gain: f64,
+    },
+    /// Deterministic white noise, the same on every run.
+    Noise {
+        /// Linear amplitude.
+        #[serde(default = "default_gain")]
+        gain: f64,
+    },
+    /// A single full-scale sample at time 0.
+    Impulse,
+    /// A WAV file. Mono files are fed to every input channel.
+    File {
+        /// Path of the file.
+        path: PathBuf,
+    },
+}
+
+/// A timed event.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ScriptEvent {
+    /// A note on, followed by its note off after `duration` seconds.
+    Note {
+        /// Time in seconds.
+        time: f64,
+        /// MIDI note number.
+        note: u8,
+        /// Velocity (0.0-1.0).
+        #[serde(default = "default_velocity")]
+        velocity: f32,
+        /// Note length in seconds.
+        #[serde(default = "default_duration")]
+        duration: f64,
+        /// MIDI channel (0-15).
+        #[serde(default)]
+        channel: u8,
+    },
+    /// A MIDI control change.
+    Cc {
+        /// Time in seconds.
+        time: f64,
+        /// Controller number.
+        cc: u8,
+        /// Value (0.0-1.0).
+        value: f32,
+        /// MIDI channel (0-15).
+        #[serde(default)]
+        channel: u8,
+    },
+    /// A parameter change, as a normalized `value` or a `plain` value.
+    Parameter {
+        /// Time in seconds.
+        time: f64,
+        /// Parameter string ID.
+        param: String,
+        /// Normalized value (0.0-1.0).
+        #[serde(default)]
+        value: Option<f64>,
+        /// Value in the parameter's own units.
+        #[serde(default)]
+        plain: Option<f64>,
+    },
+}
+
+impl Script {
+    /// Parse a script from JSON.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid script: {}", e))
+    }
+}
+
+/// Parse a timeline CSV into parameter events.
+///
+/// The header selects whether the third column is normalized (`value`) or
+/// plain (`plain`). Empty lines and lines starting with `#` are skipped.
+pub fn parse_timeline(csv: &str) -> Result<Vec<ScriptEvent>, String> {
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let plain = match lines.next().map(|(_, header)| header.replace(' ', "")) {
+        Some(header) if header == "time,param,value" => false,
+        Some(header) if header == "time,param,plain" => true,
+        _ => return Err("timeline header must be 'time,param,value' or 'time,param,plain'".to_string()),
+    };
+
+    lines
+        .map(|(number, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [time, param, value] = fields[..] else {
+                return Err(format!("timeline line {}: expected 3 fields", number));
+            };
+            let number_at = |field: &str| {
+                field
+                    .parse::<f64>()
+                    .map_err(|_| format!("timeline line {}: '{}' is not a number", number, field))
+            };
+            let (time, value) = (number_at(time)?, number_at(value)?);
+            Ok(ScriptEvent::Parameter {
+                time,
+                param: param.to_string(),
+                value: (!plain).then_some(value),
+                plain: plain.then_some(value),
+            })
+        })
+        .collect()
+}
+
+// =============================================================================
+// Report
+// =============================================================================
+
+/// Result of a script run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScriptReport {
+    /// Samples rendered per channel.
+    pub samples: usize,
+    /// Sample rate in Hz.
+    pub sample_rate: f64,
+    /// Peak level per output channel.
+    pub peak: Vec<f32>,
+    /// RMS level per output channel.
+    pub rms: Vec<f32>,
+    /// First output sample that was NaN or infinite, as (channel, sample).
+    pub non_finite: Option<(usize, usize)>,
+    /// Largest difference from the reference, if one was given.
+    pub reference_error: Option<f64>,
+    /// Largest allowed difference from the reference.
+    pub tolerance: f64,
+}
+
+impl ScriptReport {
+    /// Returns true if the output is finite and matches the reference.
+    pub fn passed(&self) -> bool {
+        self.non_finite.is_none() && self.reference_error.is_none_or(|error| error <= self.tolerance)
+    }
+}
+
+impl fmt::Display for ScriptReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} samples at {} Hz", self.samples, self.sample_rate)?;
+        for (channel, (peak, rms)) in self.peak.iter().zip(&self.rms).enumerate() {
+            writeln!(f, "channel {}: peak {:.6}, RMS {:.6}", channel, peak, rms)?;
+        }
+        if let Some((channel, sample)) = self.non_finite {
+            writeln!(f, "non-finite output at channel {} sample {}", channel, sample)?;
+        }
+        if let Some(error) = self.reference_error {
+            writeln!(f, "reference: max difference {:e} (tolerance {:e})", error, self.tolerance)?;
+        }
+        write!(f, "{}", if self.passed() { "passed" } else { "FAILED" })
+    }
+}
+
+// =============================================================================
+// Runner
+// =============================================================================
+
+/// Read and run a script file, resolving its paths against its directory.
+///
+/// Called by the test `export_plugin!` adds with the `script` feature.
+pub fn run_file<D: Descriptor>(path: &Path) -> Result<ScriptReport, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let script = Script::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    run::<D>(&script, path.parent().unwrap_or(Path::new(".")))
+}
+
+/// Run a script, resolving relative paths against `base_dir`.
+pub fn run<D: Descriptor>(script: &Script, base_dir: &Path) -> Result<ScriptReport, String> {
+    let resolve = |path: &Path| base_dir.join(path);
+    let sample_rate = script.sample_rate;
+    let mut renderer = OfflineRenderer::<D>::new(sample_rate, script.block_size).with_tempo(script.tempo);
+
+    // Input signal
+    renderer = match &script.input {
+        ScriptInput::Silence => renderer,
+        ScriptInput::Sine { frequency, gain } => {
+            renderer.with_signal(TestSignal::Sine { frequency: *frequency, gain: *gain })
+        }
+        ScriptInput::Noise { gain } => renderer.with_signal(TestSignal::Noise { gain: *gain }),
+        ScriptInput::Impulse => renderer.with_signal(TestSignal::Impulse),
+        ScriptInput::File { path } => renderer.with_input_wav(resolve(path))?,
+    };
+    if let Some(seconds) = script.seconds {
+        renderer = renderer.with_seconds(seconds);
+    }
+    if script.meters.is_some() {
+        renderer = renderer.with_meters((script.meter_interval_ms * sample_rate / 1000.0).round() as usize);
+    }
+
+    // Events
+    let mut events = script.events.clone();
+    if let Some(timeline) = &script.timeline {
+        let path = resolve(timeline);
+        let csv = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        events.extend(parse_timeline(&csv).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    let at = |time: f64| (time * sample_rate).round().max(0.0) as usize;
+    for event in &events {
+        renderer = match event {
+            ScriptEvent::Note { time, note, velocity, duration, channel } => {
+                renderer.with_note(at(*time), *channel, *note, *velocity, at(time + duration).saturating_sub(at(*time)))
+            }
+            ScriptEvent::Cc { time, cc, value, channel } => {
+                renderer.with_midi(at(*time), MidiEvent::control_change(0, *channel, *cc, *value))
+            }
+            ScriptEvent::Parameter { time, param, value, plain } => {
+                if renderer.parameter_id(param).is_none() {
+                    return Err(format!("unknown parameter '{}'", param));
+                }
+                match (value, plain) {
+                    (Some(value), None) => renderer.with_parameter(at(*time), param, *value),
+                    (None, Some(plain)) => renderer.with_plain_parameter(at(*time), param, *plain),
+                    _ => return Err(format!("event for '{}' needs exactly one of 'value' and 'plain'", param)),
+                }
+            }
+        };
+    }
+
+    let render = renderer.render();
+
+    // Files
+    if let Some(path) = &script.output {
+        render.write_wav(resolve(path))?;
+    }
+    if let Some(path) = &script.meters {
+        let mut csv = String::from("time");
+        for name in &render.meter_names {
+            csv.push(',');
+            csv.push_str(name);
+        }
+        csv.push('\n');
+        for (at, values) in &render.meter_rows {
+            csv.push_str(&(*at as f64 / sample_rate).to_string());
+            for value in values {
+                csv.push(',');
+                csv.push_str(&value.to_string());
+            }
+            csv.push('\n');
+        }
+        write_file(&resolve(path), csv.as_bytes())?;
+    }
+
+    // Report
+    let mut report = ScriptReport {
+        samples: render.len(),
+        sample_rate,
+        peak: render.peak(),
+        rms: render.rms(),
+        non_finite: render.first_non_finite(),
+        reference_error: None,
+        tolerance: script.tolerance,
+    };
+    if let Some(path) = &script.reference {
+        let path = resolve(path);
+        let (_, reference) = read_wav(&path)?;
+        let error = render.max_difference(&reference).map_err(|e| format!("{}: {}", path.display(), e))?;
+        report.reference_error = Some(error);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_and_timeline_parse() {
+        let script = Script::from_json(
+            r#"{
+                "blockSize": 64,
+                "input": { "type": "sine", "frequency": 100 },
+                "events": [
+                    { "time": 0.5, "param": "gain", "plain": -6 },
+                    { "time": 1.0, "note": 60 },
+                    { "time": 1.5, "cc": 1, "value": 0.25, "channel": 2 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!((script.sample_rate, script.block_size, script.tolerance), (48000.0, 64, 1e-4));
+        assert_eq!(script.input, ScriptInput::Sine { frequency: 100.0, gain: 0.5 });
+        assert!(matches!(&script.events[0], ScriptEvent::Parameter { plain: Some(p), .. } if *p == -6.0));
+        assert!(matches!(script.events[1], ScriptEvent::Note { note: 60, duration, .. } if duration == 0.5));
+        assert!(matches!(script.events[2], ScriptEvent::Cc { cc: 1, channel: 2, .. }));
+        assert!(Script::from_json(r#"{ "sampleRte": 44100 }"#).is_err());
+
+        let timeline = parse_timeline("# automation\ntime, param, value\n0,gain,0.5\n\n0.25,mix,1\n").unwrap();
+        assert_eq!(
+            timeline[1],
+            ScriptEvent::Parameter { time: 0.25, param: "mix".to_string(), value: Some(1.0), plain: None }
+        );
+        assert!(parse_timeline("time,param,value\n0,gain").is_err());
+        assert!(parse_timeline("t,p,v\n").is_err());
+    }
+}
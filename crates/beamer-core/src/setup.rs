@@ -14,7 +14,11 @@
 //! | [`MainOutputChannels`] | `u32` | Per-channel output state |
 //! | [`AuxInputCount`] | `usize` | Sidechain-aware processing |
 //! | [`AuxOutputCount`] | `usize` | Multi-bus output |
+//! | [`AuxInputChannels<N>`](AuxInputChannels) | `u32` | Per-channel sidechain state |
+//! | [`AuxOutputChannels<N>`](AuxOutputChannels) | `u32` | Per-channel aux output state |
+//! | [`BusLayout`] | struct | Every bus and its channel count |
 //! | [`ProcessMode`] | enum | Quality settings for offline rendering |
+//! | [`Quality`] | enum | Eco/Normal/High mode for CPU versus quality |
 //!
 //! # Combining Types
 //!
@@ -59,11 +63,15 @@ pub use crate::plugin::{
     // Core trait
     PluginSetup,
     // Individual setup types
+    AuxInputChannels,
     AuxInputCount,
+    AuxOutputChannels,
     AuxOutputCount,
+    BusLayout,
     MainInputChannels,
     MainOutputChannels,
     MaxBufferSize,
     ProcessMode,
     SampleRate,
 };
+pub use crate::quality::Quality;
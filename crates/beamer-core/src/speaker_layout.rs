@@ -0,0 +1,188 @@
+//! Speaker layouts for surround and multichannel buses.
+//!
+//! A [`SpeakerLayout`] names the speakers of a bus in channel order, so
+//! plugins can find the LFE or center channel instead of assuming an index.
+//! Buses declare one with [`BusInfo::with_speaker_layout`](crate::BusInfo::with_speaker_layout)
+//! and the wrappers report it to the host (VST3 speaker arrangements, AU
+//! channel layout tags). In `process()`, [`Buffer`](crate::Buffer) resolves
+//! channels to speakers:
+//!
+//! ```ignore
+//! fn input_bus_info(&self, index: usize) -> Option<BusInfo> {
+//!     (index == 0).then(|| BusInfo::stereo("Input").with_speaker_layout(SpeakerLayout::Surround51))
+//! }
+//!
+//! fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!     if let Some(lfe) = buffer.output_channel_of(Speaker::Lfe) {
+//!         buffer.output(lfe).fill(0.0);
+//!     }
+//! }
+//! ```
+//!
+//! Channel orders follow the hosts: 5.1 is L R C LFE Ls Rs, 7.1 adds the
+//! rear surrounds, 7.1.4 adds the top front and top rear pairs, and
+//! ambisonics use ACN order.
+
+use crate::ambisonics::AmbisonicOrder;
+
+/// One speaker (channel) of a [`SpeakerLayout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Speaker {
+    /// The only channel of a mono bus.
+    Mono,
+    /// Front left.
+    Left,
+    /// Front right.
+    Right,
+    /// Front center.
+    Center,
+    /// Low-frequency effects.
+    Lfe,
+    /// Left surround (side surround in 7.1).
+    LeftSurround,
+    /// Right surround (side surround in 7.1).
+    RightSurround,
+    /// Left rear surround.
+    LeftRearSurround,
+    /// Right rear surround.
+    RightRearSurround,
+    /// Top front left (height).
+    TopFrontLeft,
+    /// Top front right (height).
+    TopFrontRight,
+    /// Top rear left (height).
+    TopRearLeft,
+    /// Top rear right (height).
+    TopRearRight,
+    /// Ambisonic component with this ACN index.
+    Ambisonic(u32),
+    /// Unnamed channel with this index.
+    Discrete(u32),
+}
+
+/// Speaker arrangement of a bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SpeakerLayout {
+    /// 1 channel.
+    Mono,
+    /// 2 channels: L R.
+    #[default]
+    Stereo,
+    /// 5.1, 6 channels: L R C LFE Ls Rs.
+    Surround51,
+    /// 7.1, 8 channels: L R C LFE Ls Rs Lrs Rrs.
+    Surround71,
+    /// 7.1.4, 12 channels: 7.1 plus Tfl Tfr Trl Trr.
+    Surround714,
+    /// Ambisonics of the given order, ACN channel order.
+    Ambisonic(AmbisonicOrder),
+    /// The given number of unnamed channels.
+    Discrete(u32),
+}
+
+const SURROUND_714: [Speaker; 12] = [
+    Speaker::Left,
+    Speaker::Right,
+    Speaker::Center,
+    Speaker::Lfe,
+    Speaker::LeftSurround,
+    Speaker::RightSurround,
+    Speaker::LeftRearSurround,
+    Speaker::RightRearSurround,
+    Speaker::TopFrontLeft,
+    Speaker::TopFrontRight,
+    Speaker::TopRearLeft,
+    Speaker::TopRearRight,
+];
+
+impl SpeakerLayout {
+    /// Number of channels.
+    pub const fn channel_count(self) -> u32 {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+            Self::Surround51 => 6,
+            Self::Surround71 => 8,
+            Self::Surround714 => 12,
+            Self::Ambisonic(order) => order.channel_count(),
+            Self::Discrete(count) => count,
+        }
+    }
+
+    /// The usual layout for `channel_count` channels.
+    ///
+    /// 1, 2, 6, 8 and 12 channels map to mono, stereo, 5.1, 7.1 and 7.1.4;
+    /// other counts (including ambisonic widths, which are ambiguous with
+    /// e.g. quad) are discrete.
+    pub const fn from_channel_count(channel_count: u32) -> Self {
+        match channel_count {
+            1 => Self::Mono,
+            2 => Self::Stereo,
+            6 => Self::Surround51,
+            8 => Self::Surround71,
+            12 => Self::Surround714,
+            count => Self::Discrete(count),
+        }
+    }
+
+    /// Speaker of `channel`, or `None` past the last channel.
+    pub fn speaker(self, channel: usize) -> Option<Speaker> {
+        if channel >= self.channel_count() as usize {
+            return None;
+        }
+        Some(match self {
+            Self::Mono => Speaker::Mono,
+            // The named layouts are prefixes of 7.1.4
+            Self::Stereo | Self::Surround51 | Self::Surround71 | Self::Surround714 => SURROUND_714[channel],
+            Self::Ambisonic(_) => Speaker::Ambisonic(channel as u32),
+            Self::Discrete(_) => Speaker::Discrete(channel as u32),
+        })
+    }
+
+    /// Channel carrying `speaker`, if this layout has it.
+    pub fn channel_of(self, speaker: Speaker) -> Option<usize> {
+        (0..self.channel_count() as usize).find(|&channel| self.speaker(channel) == Some(speaker))
+    }
+
+    /// Returns true if this layout has an LFE channel.
+    pub fn has_lfe(self) -> bool {
+        self.channel_of(Speaker::Lfe).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_layouts_locate_speakers() {
+        assert_eq!(SpeakerLayout::Surround51.channel_of(Speaker::Lfe), Some(3));
+        assert_eq!(SpeakerLayout::Surround51.channel_of(Speaker::Center), Some(2));
+        assert_eq!(SpeakerLayout::Surround51.channel_of(Speaker::LeftRearSurround), None);
+        assert_eq!(SpeakerLayout::Surround71.speaker(7), Some(Speaker::RightRearSurround));
+        assert_eq!(SpeakerLayout::Surround714.speaker(11), Some(Speaker::TopRearRight));
+        assert_eq!(SpeakerLayout::Surround714.speaker(12), None);
+        assert_eq!(SpeakerLayout::Stereo.speaker(1), Some(Speaker::Right));
+        assert_eq!(SpeakerLayout::Mono.speaker(0), Some(Speaker::Mono));
+        assert!(!SpeakerLayout::Stereo.has_lfe());
+
+        let ambisonic = SpeakerLayout::Ambisonic(AmbisonicOrder::Second);
+        assert_eq!(ambisonic.channel_count(), 9);
+        assert_eq!(ambisonic.speaker(8), Some(Speaker::Ambisonic(8)));
+    }
+
+    #[test]
+    fn channel_counts_round_trip() {
+        for layout in [
+            SpeakerLayout::Mono,
+            SpeakerLayout::Stereo,
+            SpeakerLayout::Surround51,
+            SpeakerLayout::Surround71,
+            SpeakerLayout::Surround714,
+            SpeakerLayout::Discrete(3),
+        ] {
+            assert_eq!(SpeakerLayout::from_channel_count(layout.channel_count()), layout);
+        }
+        assert_eq!(SpeakerLayout::from_channel_count(4), SpeakerLayout::Discrete(4));
+    }
+}
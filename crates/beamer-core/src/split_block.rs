@@ -0,0 +1,346 @@
+//! Sample-accurate event handling by splitting blocks.
+//!
+//! Writing a sample-accurate event loop means walking the MIDI events and
+//! parameter changes inside `process()` and rendering the samples between
+//! them (the drums example does this by hand). A processor that returns
+//! true from [`Processor::split_blocks`] gets this for free: the wrappers
+//! split `process()` at every MIDI event and parameter change offset, apply
+//! the parameter changes and pass the MIDI events to
+//! [`Processor::midi_event`] right before the sub-block that starts at
+//! them:
+//!
+//! ```text
+//! host block:  |<---------------------- 256 samples ---------------------->|
+//! events:           note on @40          cutoff @100        note off @200
+//! calls:       process(40) | midi_event, process(60) | cutoff, process(100) | midi_event, process(56)
+//! ```
+//!
+//! ```ignore
+//! impl Processor for MySynth {
+//!     fn split_blocks(&self) -> bool {
+//!         true
+//!     }
+//!
+//!     fn midi_event(&mut self, event: &MidiEvent) {
+//!         match &event.event {
+//!             MidiEventKind::NoteOn(on) => self.voices.note_on(on.pitch, on.velocity),
+//!             MidiEventKind::NoteOff(off) => self.voices.note_off(off.pitch),
+//!             _ => {}
+//!         }
+//!     }
+//!
+//!     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!         // No events inside this call: render the voices with the current parameters
+//!     }
+//! }
+//! ```
+//!
+//! The splitting is done by [`SplitBlockProcessor`], which the wrappers feed
+//! with the block's events and run together with the control-rate ticks
+//! ([`ControlRate`](crate::control_rate::ControlRate)), so both can be used
+//! at once.
+//!
+//! # Notes
+//!
+//! - `process_midi()` still runs once per host block, before any
+//!   `process()` call, for MIDI output, thru and transforms.
+//! - Events passed to `midi_event()` have a `sample_offset` of 0, the start
+//!   of the following `process()` call.
+//! - Parameter values seen inside a sub-block are the host's automation
+//!   points at that position; after the block the parameters hold the same
+//!   values as without splitting. AU hosts already split at parameter
+//!   events, so AU sub-blocks only split further at MIDI events.
+//! - When the wrapper resamples to another processing rate, offsets are in
+//!   host samples and are clamped to the first processing block.
+//!
+//! # Real-Time Safety
+//!
+//! All storage is allocated up front, including the boxes SysEx payloads
+//! are copied into, so recording never allocates. Events beyond the
+//! capacity are dropped from the split but still reach `process_midi()`.
+//!
+//! The wrappers only record events for processors whose
+//! [`split_blocks()`](Processor::split_blocks) returns true (see
+//! [`ControlRate::split_blocks_for`](crate::control_rate::ControlRate::split_blocks_for)).
+
+use crate::midi::{MidiEvent, MidiEventKind, SysEx, MAX_MIDI_EVENTS};
+use crate::parameter_store::ParameterStore;
+use crate::plugin::Processor;
+use crate::types::{ParameterId, ParameterValue};
+
+/// Maximum number of parameter change points split per block.
+pub const MAX_SPLIT_POINTS: usize = 1024;
+
+/// Maximum number of SysEx messages split per block.
+pub const MAX_SPLIT_SYSEX: usize = 16;
+
+/// A parameter value at an offset.
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    offset: u32,
+    /// Recording order, keeps points at the same offset in order.
+    order: u32,
+    id: ParameterId,
+    value: ParameterValue,
+}
+
+/// Value of a changed parameter before and after the block.
+#[derive(Debug, Clone, Copy)]
+struct Change {
+    id: ParameterId,
+    previous: ParameterValue,
+    last: ParameterValue,
+}
+
+/// Splits blocks at MIDI event and parameter change offsets.
+///
+/// Owned by the format wrappers (inside their [`ControlRate`](crate::control_rate::ControlRate)),
+/// one per plugin instance. The wrapper records the block's events before
+/// processing; the split is only made for processors whose
+/// [`split_blocks()`](Processor::split_blocks) returns true.
+#[derive(Debug)]
+pub struct SplitBlockProcessor {
+    points: Vec<Point>,
+    changes: Vec<Change>,
+    midi: Vec<MidiEvent>,
+    /// Preallocated SysEx boxes not used by `midi` (boxed, because
+    /// `MidiEventKind::SysEx` takes them as they are).
+    #[allow(clippy::vec_box)]
+    sysex: Vec<Box<SysEx>>,
+    next_point: usize,
+    next_midi: usize,
+}
+
+impl Default for SplitBlockProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SplitBlockProcessor {
+    /// Create a splitter with room for a full block of events.
+    pub fn new() -> Self {
+        Self {
+            points: Vec::with_capacity(MAX_SPLIT_POINTS),
+            changes: Vec::with_capacity(MAX_SPLIT_POINTS),
+            midi: Vec::with_capacity(MAX_MIDI_EVENTS),
+            sysex: (0..MAX_SPLIT_SYSEX).map(|_| Box::new(SysEx::new())).collect(),
+            next_point: 0,
+            next_midi: 0,
+        }
+    }
+
+    /// Forget the recorded events. Called by the wrappers at the start of
+    /// every block.
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.changes.clear();
+        self.clear_midi();
+        self.next_point = 0;
+    }
+
+    /// Forget the recorded MIDI, keeping the SysEx boxes for reuse.
+    fn clear_midi(&mut self) {
+        for event in self.midi.drain(..) {
+            if let MidiEventKind::SysEx(sysex) = event.event {
+                self.sysex.push(sysex);
+            }
+        }
+        self.next_midi = 0;
+    }
+
+    /// Whether no events are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty() && self.midi.is_empty()
+    }
+
+    /// Record a parameter automation point.
+    ///
+    /// `previous` is the value before the block (only the first point of a
+    /// parameter uses it). The points of one parameter are recorded one
+    /// after the other, in offset order.
+    pub fn record_parameter(&mut self, id: ParameterId, previous: ParameterValue, offset: u32, value: ParameterValue) {
+        if self.points.len() == self.points.capacity() {
+            return;
+        }
+        self.points.push(Point { offset, order: self.points.len() as u32, id, value });
+        if self.changes.last().is_none_or(|change| change.id != id) {
+            self.changes.push(Change { id, previous, last: previous });
+        }
+    }
+
+    /// Record the block's MIDI input (sorted by offset), replacing earlier
+    /// MIDI.
+    ///
+    /// SysEx payloads are copied into the preallocated boxes; SysEx beyond
+    /// [`MAX_SPLIT_SYSEX`] is dropped from the split.
+    pub fn set_midi(&mut self, events: &[MidiEvent]) {
+        self.clear_midi();
+        for event in events.iter().take(self.midi.capacity()) {
+            let kind = match &event.event {
+                MidiEventKind::SysEx(sysex) => {
+                    let Some(mut slot) = self.sysex.pop() else { continue };
+                    *slot = **sysex;
+                    MidiEventKind::SysEx(slot)
+                }
+                kind => kind.clone(),
+            };
+            self.midi.push(MidiEvent { sample_offset: event.sample_offset, event: kind });
+        }
+    }
+
+    /// Prepare the split of a block of `num_samples`: clamp the offsets into
+    /// the block and return the parameters to their values before it.
+    ///
+    /// The current values (after the wrapper applied the block's changes and
+    /// constraints) are restored by [`finish()`](Self::finish).
+    pub(crate) fn begin(&mut self, parameters: &impl ParameterStore, num_samples: usize) {
+        let last = num_samples.saturating_sub(1) as u32;
+        for point in &mut self.points {
+            point.offset = point.offset.min(last);
+        }
+        self.points.sort_unstable_by_key(|point| (point.offset, point.order));
+        for event in &mut self.midi {
+            event.sample_offset = event.sample_offset.min(last);
+        }
+        for change in &mut self.changes {
+            change.last = parameters.get_normalized(change.id);
+            parameters.set_normalized(change.id, change.previous);
+        }
+        self.next_point = 0;
+        self.next_midi = 0;
+    }
+
+    /// Apply the events at or before `offset` and return the offset of the
+    /// next one (`usize::MAX` if there is none).
+    pub(crate) fn apply<P: Processor>(&mut self, processor: &mut P, offset: usize) -> usize {
+        self.apply_parameters(processor.parameters(), offset);
+        self.apply_midi(offset, |event| processor.midi_event(event));
+        self.next_offset()
+    }
+
+    fn apply_parameters(&mut self, parameters: &impl ParameterStore, offset: usize) {
+        while let Some(point) = self.points.get(self.next_point).filter(|p| p.offset as usize <= offset) {
+            parameters.set_normalized(point.id, point.value);
+            self.next_point += 1;
+        }
+    }
+
+    fn apply_midi(&mut self, offset: usize, mut midi_event: impl FnMut(&MidiEvent)) {
+        while let Some(event) = self.midi.get_mut(self.next_midi).filter(|e| e.sample_offset as usize <= offset) {
+            event.sample_offset = 0;
+            midi_event(event);
+            self.next_midi += 1;
+        }
+    }
+
+    fn next_offset(&self) -> usize {
+        let next_point = self.points.get(self.next_point).map_or(usize::MAX, |p| p.offset as usize);
+        let next_midi = self.midi.get(self.next_midi).map_or(usize::MAX, |e| e.sample_offset as usize);
+        next_point.min(next_midi)
+    }
+
+    /// Leave every changed parameter at its value from before the split and
+    /// clear the block.
+    pub(crate) fn finish(&mut self, parameters: &impl ParameterStore) {
+        for change in &self.changes {
+            parameters.set_normalized(change.id, change.last);
+        }
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi_cc_config::MidiCcConfig;
+    use crate::midi_cc_state::MidiCcState;
+
+    fn store() -> MidiCcState {
+        MidiCcState::from_config(&MidiCcConfig::new().with_mod_wheel())
+    }
+
+    #[test]
+    fn applies_events_in_offset_order() {
+        let parameters = store();
+        let id = MidiCcState::parameter_id(1);
+        parameters.set_normalized(id, 0.1);
+
+        let mut split = SplitBlockProcessor::new();
+        split.record_parameter(id, 0.1, 64, 0.5);
+        split.record_parameter(id, 0.1, 300, 0.9);
+        parameters.set_normalized(id, 0.9);
+        split.set_midi(&[MidiEvent::note_on(16, 0, 60, 1.0, -1, 0.0, 0), MidiEvent::note_off(64, 0, 60, 0.0, -1, 0.0)]);
+
+        split.begin(&parameters, 128);
+        assert_eq!(parameters.get_normalized(id), 0.1);
+
+        let mut seen = Vec::new();
+        let mut offset = 0;
+        let mut boundaries = Vec::new();
+        while offset < 128 {
+            split.apply_parameters(&parameters, offset);
+            split.apply_midi(offset, |event| seen.push((offset, event.sample_offset)));
+            boundaries.push((offset, parameters.get_normalized(id)));
+            offset = split.next_offset().min(128);
+        }
+        // The point past the block is clamped to its last sample
+        assert_eq!(boundaries, [(0, 0.1), (16, 0.1), (64, 0.5), (127, 0.9)]);
+        assert_eq!(seen, [(16, 0), (64, 0)]);
+
+        split.finish(&parameters);
+        assert!(split.is_empty());
+        assert_eq!(parameters.get_normalized(id), 0.9);
+    }
+
+    #[test]
+    fn restores_first_previous_value_and_respects_capacity() {
+        let parameters = store();
+        let id = MidiCcState::parameter_id(1);
+        let mut split = SplitBlockProcessor::new();
+        split.record_parameter(id, 0.25, 10, 0.5);
+        split.record_parameter(id, 0.5, 20, 0.75);
+        parameters.set_normalized(id, 0.75);
+        split.begin(&parameters, 32);
+        assert_eq!(parameters.get_normalized(id), 0.25);
+        split.finish(&parameters);
+        assert_eq!(parameters.get_normalized(id), 0.75);
+
+        for offset in 0..MAX_SPLIT_POINTS as u32 + 10 {
+            split.record_parameter(id, 0.0, offset, 0.5);
+        }
+        assert_eq!(split.points.len(), MAX_SPLIT_POINTS);
+        let events = vec![MidiEvent::default(); MAX_MIDI_EVENTS + 5];
+        split.set_midi(&events);
+        assert_eq!(split.midi.len(), MAX_MIDI_EVENTS);
+    }
+
+    #[test]
+    fn sysex_is_copied_into_reused_boxes() {
+        let mut sysex = SysEx::new();
+        sysex.data[..3].copy_from_slice(&[0x7e, 0x01, 0x02]);
+        sysex.len = 3;
+        let event = |offset| MidiEvent { sample_offset: offset, event: MidiEventKind::SysEx(Box::new(sysex)) };
+        let slot = |split: &SplitBlockProcessor| match &split.midi[0].event {
+            MidiEventKind::SysEx(sysex) => (&**sysex as *const SysEx, sysex.len),
+            _ => panic!("expected SysEx"),
+        };
+
+        let mut split = SplitBlockProcessor::new();
+        split.set_midi(&[event(5)]);
+        let first = slot(&split);
+        assert_eq!(first.1, 3);
+        assert_eq!(split.sysex.len(), MAX_SPLIT_SYSEX - 1);
+
+        // The next block reuses the box instead of allocating
+        split.clear();
+        assert_eq!(split.sysex.len(), MAX_SPLIT_SYSEX);
+        split.set_midi(&[event(7)]);
+        assert_eq!(slot(&split), first);
+
+        // SysEx beyond the preallocated boxes is left out of the split
+        let events: Vec<_> = (0..MAX_SPLIT_SYSEX as u32 + 4).map(event).collect();
+        split.set_midi(&events);
+        assert_eq!(split.midi.len(), MAX_SPLIT_SYSEX);
+    }
+}
@@ -0,0 +1,469 @@
+//! Framework-owned input trim, output trim and polarity invert.
+//!
+//! Most effects want the same three gain-staging controls, and users expect
+//! them to behave (and automate) the same way across a vendor's products.
+//! [`Config::with_standard_trims()`](crate::Config::with_standard_trims)
+//! (`standard_trims = true` in `Config.toml`) adds them without any plugin
+//! code:
+//!
+//! | Parameter | ID | Range |
+//! |-----------|----|-------|
+//! | Input Trim | [`INPUT_TRIM_PARAMETER_ID`] | -24 to +24 dB |
+//! | Output Trim | [`OUTPUT_TRIM_PARAMETER_ID`] | -24 to +24 dB |
+//! | Phase Invert | [`PHASE_INVERT_PARAMETER_ID`] | Off / On |
+//!
+//! The IDs are fixed, so automation and host mappings carry over between
+//! products.
+//!
+//! # Host Integration
+//!
+//! The wrappers list the parameters after the plugin's own, apply them around
+//! `process()` with a [`TrimStage`] and save them with the state. The plugin
+//! sees the trimmed main input and its main output is trimmed and inverted
+//! afterwards; auxiliary buses are not touched. Gain changes ramp linearly
+//! over one block, so automation and polarity flips don't click.
+//!
+//! # Persistence
+//!
+//! The values are appended to the plugin state as a trailer, after any other
+//! framework trailer, and stripped again before loading:
+//!
+//! ```text
+//! [plugin state][other trailers][3 × f64 LE normalized]["BXTR"]
+//! ```
+
+use std::sync::Arc;
+
+use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::parameter_info::ParameterInfo;
+use crate::parameter_store::ParameterStore;
+use crate::parameter_types::{BoolParameter, FloatParameter, ParameterRef};
+use crate::process_context::ProcessContext;
+use crate::sample::Sample;
+use crate::types::{ParameterId, ParameterValue};
+
+/// Base parameter ID of the standard trims.
+///
+/// Above the MIDI CC emulation range
+/// ([`MIDI_CC_PARAM_BASE`](crate::MIDI_CC_PARAM_BASE)) and below the VST3
+/// limit of 2³¹.
+pub const STANDARD_TRIM_PARAM_BASE: ParameterId = 0x1100_0000;
+
+/// Parameter ID of Input Trim.
+pub const INPUT_TRIM_PARAMETER_ID: ParameterId = STANDARD_TRIM_PARAM_BASE;
+
+/// Parameter ID of Output Trim.
+pub const OUTPUT_TRIM_PARAMETER_ID: ParameterId = STANDARD_TRIM_PARAM_BASE + 1;
+
+/// Parameter ID of Phase Invert.
+pub const PHASE_INVERT_PARAMETER_ID: ParameterId = STANDARD_TRIM_PARAM_BASE + 2;
+
+/// Trim range in dB (symmetric around 0).
+const TRIM_RANGE_DB: f64 = 24.0;
+
+/// Magic bytes closing the state trailer.
+const STATE_TRAILER_MAGIC: &[u8; 4] = b"BXTR";
+
+/// Trailer length: three normalized values plus the magic.
+const STATE_TRAILER_LEN: usize = 3 * 8 + 4;
+
+/// Shared standard trim parameters.
+///
+/// Cheap to clone; all clones share the values. The default is disabled and
+/// has no parameters.
+#[derive(Clone, Default)]
+pub struct StandardTrims {
+    inner: Option<Arc<TrimParameters>>,
+}
+
+struct TrimParameters {
+    input: FloatParameter,
+    output: FloatParameter,
+    invert: BoolParameter,
+}
+
+impl TrimParameters {
+    fn new() -> Self {
+        let range = -TRIM_RANGE_DB..=TRIM_RANGE_DB;
+        Self {
+            input: FloatParameter::db("Input Trim", 0.0, range.clone())
+                .with_id(INPUT_TRIM_PARAMETER_ID)
+                .with_string_id("_beamer_input_trim")
+                .with_short_name("In Trim"),
+            output: FloatParameter::db("Output Trim", 0.0, range)
+                .with_id(OUTPUT_TRIM_PARAMETER_ID)
+                .with_string_id("_beamer_output_trim")
+                .with_short_name("Out Trim"),
+            invert: BoolParameter::new("Phase Invert", false)
+                .with_id(PHASE_INVERT_PARAMETER_ID)
+                .with_string_id("_beamer_phase_invert")
+                .with_short_name("Phase"),
+        }
+    }
+
+    fn by_index(&self, index: usize) -> Option<&dyn ParameterRef> {
+        match index {
+            0 => Some(&self.input),
+            1 => Some(&self.output),
+            2 => Some(&self.invert),
+            _ => None,
+        }
+    }
+}
+
+impl StandardTrims {
+    /// Create enabled trims at their defaults (0 dB, not inverted).
+    pub fn new() -> Self {
+        Self {
+            inner: Some(Arc::new(TrimParameters::new())),
+        }
+    }
+
+    /// Enabled when `enabled`, the disabled default otherwise.
+    pub fn enabled(enabled: bool) -> Self {
+        if enabled {
+            Self::new()
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Returns true if the trims are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Returns true if `id` is one of the standard trim parameter IDs.
+    pub fn is_trim_parameter(id: ParameterId) -> bool {
+        (INPUT_TRIM_PARAMETER_ID..=PHASE_INVERT_PARAMETER_ID).contains(&id)
+    }
+
+    /// Look up a trim parameter by ID (`None` when disabled).
+    pub fn by_id(&self, id: ParameterId) -> Option<&dyn ParameterRef> {
+        let inner = self.inner.as_deref()?;
+        if !Self::is_trim_parameter(id) {
+            return None;
+        }
+        inner.by_index((id - STANDARD_TRIM_PARAM_BASE) as usize)
+    }
+
+    /// Linear input gain.
+    pub fn input_gain(&self) -> f64 {
+        self.inner.as_ref().map_or(1.0, |inner| inner.input.as_linear())
+    }
+
+    /// Linear output gain, negative when the polarity is inverted.
+    pub fn output_gain(&self) -> f64 {
+        self.inner.as_ref().map_or(1.0, |inner| {
+            let gain = inner.output.as_linear();
+            if inner.invert.get() {
+                -gain
+            } else {
+                gain
+            }
+        })
+    }
+
+    /// Append the values as a state trailer (nothing when disabled).
+    pub fn append_state(&self, data: &mut Vec<u8>) {
+        let Some(inner) = self.inner.as_deref() else {
+            return;
+        };
+        for index in 0..3 {
+            if let Some(parameter) = inner.by_index(index) {
+                data.extend_from_slice(&parameter.get_normalized().to_le_bytes());
+            }
+        }
+        data.extend_from_slice(STATE_TRAILER_MAGIC);
+    }
+
+    /// Apply and strip the trailer from a state blob.
+    ///
+    /// Returns the length of the remaining state. A state without a trailer
+    /// resets the trims to their defaults and is passed through unchanged.
+    pub fn load_state(&self, data: &[u8]) -> usize {
+        let Some(inner) = self.inner.as_deref() else {
+            return data.len();
+        };
+        let trailer = data.len().checked_sub(STATE_TRAILER_LEN).and_then(|start| {
+            (&data[data.len() - 4..] == STATE_TRAILER_MAGIC).then_some(start)
+        });
+        for index in 0..3 {
+            let Some(parameter) = inner.by_index(index) else { continue };
+            let value = match trailer {
+                Some(start) => {
+                    let offset = start + index * 8;
+                    let bytes: [u8; 8] = data[offset..offset + 8].try_into().unwrap_or_default();
+                    f64::from_le_bytes(bytes)
+                }
+                None => parameter.default_normalized(),
+            };
+            parameter.set_normalized(value.clamp(0.0, 1.0));
+        }
+        trailer.unwrap_or(data.len())
+    }
+}
+
+impl ParameterStore for StandardTrims {
+    fn count(&self) -> usize {
+        if self.is_enabled() {
+            3
+        } else {
+            0
+        }
+    }
+
+    fn info(&self, index: usize) -> Option<&ParameterInfo> {
+        self.inner.as_deref()?.by_index(index).map(|p| p.info())
+    }
+
+    fn get_normalized(&self, id: ParameterId) -> ParameterValue {
+        self.by_id(id).map(|p| p.get_normalized()).unwrap_or(0.0)
+    }
+
+    fn set_normalized(&self, id: ParameterId, value: ParameterValue) {
+        if let Some(parameter) = self.by_id(id) {
+            parameter.set_normalized(value);
+        }
+    }
+
+    fn normalized_to_string(&self, id: ParameterId, normalized: ParameterValue) -> String {
+        self.by_id(id)
+            .map(|p| p.display_normalized(normalized))
+            .unwrap_or_default()
+    }
+
+    fn string_to_normalized(&self, id: ParameterId, string: &str) -> Option<ParameterValue> {
+        self.by_id(id).and_then(|p| p.parse(string))
+    }
+
+    fn normalized_to_plain(&self, id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+        self.by_id(id).map(|p| p.normalized_to_plain(normalized)).unwrap_or(0.0)
+    }
+
+    fn plain_to_normalized(&self, id: ParameterId, plain: ParameterValue) -> ParameterValue {
+        self.by_id(id).map(|p| p.plain_to_normalized(plain)).unwrap_or(0.0)
+    }
+
+    fn formatter_kind(&self, id: ParameterId) -> &'static str {
+        self.by_id(id).map(|p| p.formatter_kind()).unwrap_or("float")
+    }
+}
+
+/// A plugin's parameters followed by the standard trims, as one store.
+///
+/// Used by wrappers that hand a single [`ParameterStore`] to the host
+/// bridge. IDs are routed to the trims first, indices to the plugin first.
+pub struct StoreWithTrims<'a> {
+    /// The plugin's parameters.
+    pub plugin: &'a dyn ParameterStore,
+    /// The standard trims (may be disabled).
+    pub trims: &'a StandardTrims,
+}
+
+impl StoreWithTrims<'_> {
+    fn store(&self, id: ParameterId) -> &dyn ParameterStore {
+        if self.trims.by_id(id).is_some() {
+            self.trims
+        } else {
+            self.plugin
+        }
+    }
+}
+
+impl ParameterStore for StoreWithTrims<'_> {
+    fn count(&self) -> usize {
+        self.plugin.count() + self.trims.count()
+    }
+
+    fn info(&self, index: usize) -> Option<&ParameterInfo> {
+        let plugin_count = self.plugin.count();
+        if index < plugin_count {
+            self.plugin.info(index)
+        } else {
+            self.trims.info(index - plugin_count)
+        }
+    }
+
+    fn get_normalized(&self, id: ParameterId) -> ParameterValue {
+        self.store(id).get_normalized(id)
+    }
+
+    fn set_normalized(&self, id: ParameterId, value: ParameterValue) {
+        self.store(id).set_normalized(id, value)
+    }
+
+    fn normalized_to_string(&self, id: ParameterId, normalized: ParameterValue) -> String {
+        self.store(id).normalized_to_string(id, normalized)
+    }
+
+    fn string_to_normalized(&self, id: ParameterId, string: &str) -> Option<ParameterValue> {
+        self.store(id).string_to_normalized(id, string)
+    }
+
+    fn normalized_to_plain(&self, id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+        self.store(id).normalized_to_plain(id, normalized)
+    }
+
+    fn plain_to_normalized(&self, id: ParameterId, plain: ParameterValue) -> ParameterValue {
+        self.store(id).plain_to_normalized(id, plain)
+    }
+
+    fn formatter_kind(&self, id: ParameterId) -> &'static str {
+        self.store(id).formatter_kind(id)
+    }
+}
+
+/// Applies [`StandardTrims`] around `process()`.
+///
+/// Holds the trimmed copy of the main input, so the host input stays
+/// untouched. All buffers are allocated in [`new()`](Self::new).
+pub struct TrimStage<S: Sample> {
+    trims: StandardTrims,
+    /// Trimmed main input, per channel.
+    inputs: Vec<Vec<S>>,
+    /// Gains reached at the end of the previous block.
+    input_gain: f64,
+    output_gain: f64,
+}
+
+impl<S: Sample> TrimStage<S> {
+    /// Allocate a stage for `input_channels` main input channels and blocks
+    /// of up to `max_frames` samples.
+    ///
+    /// Returns `None` when `trims` is disabled.
+    pub fn new(trims: &StandardTrims, input_channels: usize, max_frames: usize) -> Option<Self> {
+        if !trims.is_enabled() {
+            return None;
+        }
+        let mut stage = Self {
+            trims: trims.clone(),
+            inputs: (0..input_channels).map(|_| vec![S::ZERO; max_frames]).collect(),
+            input_gain: 1.0,
+            output_gain: 1.0,
+        };
+        stage.reset();
+        Some(stage)
+    }
+
+    /// Call `process` through `stage` when present, directly otherwise.
+    pub fn run(
+        stage: Option<&mut Self>,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        match stage {
+            Some(stage) => stage.process(buffer, aux, context, process),
+            None => process(buffer, aux, context),
+        }
+    }
+
+    /// Jump to the current values without ramping.
+    pub fn reset(&mut self) {
+        self.input_gain = self.trims.input_gain();
+        self.output_gain = self.trims.output_gain();
+    }
+
+    /// Trim the main input, run `process`, then trim and invert the main
+    /// output.
+    pub fn process(
+        &mut self,
+        buffer: &mut Buffer<S>,
+        aux: &mut AuxiliaryBuffers<S>,
+        context: &ProcessContext,
+        mut process: impl FnMut(&mut Buffer<S>, &mut AuxiliaryBuffers<S>, &ProcessContext),
+    ) {
+        let num_samples = buffer.num_samples();
+        let capacity = self.inputs.first().map_or(0, Vec::len);
+        if num_samples > capacity {
+            // Larger than the declared maximum: pass through untrimmed
+            process(buffer, aux, context);
+            return;
+        }
+
+        let (from, to) = (self.input_gain, self.trims.input_gain());
+        self.input_gain = to;
+        let channels = buffer.num_input_channels().min(self.inputs.len());
+        for (channel, trimmed) in self.inputs.iter_mut().take(channels).enumerate() {
+            let input = buffer.input(channel);
+            for (i, (out, &sample)) in trimmed.iter_mut().zip(input).enumerate() {
+                *out = sample * S::from_f64(ramp(from, to, i, num_samples));
+            }
+        }
+
+        {
+            let inputs = self.inputs.iter().take(channels).map(|ch| &ch[..num_samples]);
+            let layouts = (buffer.input_layout(), buffer.output_layout());
+            let mut trimmed = Buffer::new(inputs, buffer.outputs_mut(), num_samples)
+                .with_speaker_layouts(layouts.0, layouts.1);
+            process(&mut trimmed, aux, context);
+        }
+
+        let (from, to) = (self.output_gain, self.trims.output_gain());
+        self.output_gain = to;
+        if from == 1.0 && to == 1.0 {
+            return;
+        }
+        for output in buffer.outputs_mut() {
+            for (i, sample) in output.iter_mut().enumerate() {
+                *sample = *sample * S::from_f64(ramp(from, to, i, num_samples));
+            }
+        }
+    }
+}
+
+/// Gain at sample `i` of a linear ramp reaching `to` at the end of the block.
+#[inline]
+fn ramp(from: f64, to: f64, i: usize, num_samples: usize) -> f64 {
+    if from == to {
+        to
+    } else {
+        from + (to - from) * (i + 1) as f64 / num_samples as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_context::Transport;
+
+    #[test]
+    fn stage_trims_input_and_inverts_output() {
+        let trims = StandardTrims::new();
+        let mut stage = TrimStage::<f32>::new(&trims, 1, 8).unwrap();
+        // -6.02 dB ≈ half, normalized from the ±24 dB range
+        let half = trims.plain_to_normalized(INPUT_TRIM_PARAMETER_ID, -6.0206);
+        trims.set_normalized(INPUT_TRIM_PARAMETER_ID, half);
+        trims.set_normalized(PHASE_INVERT_PARAMETER_ID, 1.0);
+        stage.reset();
+
+        let input = [1.0f32; 4];
+        let mut output = [0.0f32; 4];
+        let mut buffer = Buffer::new([&input[..]], [&mut output[..]], 4);
+        let mut aux = AuxiliaryBuffers::empty();
+        let context = ProcessContext::new(48000.0, 4, Transport::default());
+        stage.process(&mut buffer, &mut aux, &context, |buffer, _, _| buffer.copy_to_output());
+        for sample in output {
+            assert!((sample + 0.5).abs() < 1e-3, "{sample}");
+        }
+        assert!(StandardTrims::default().by_id(INPUT_TRIM_PARAMETER_ID).is_none());
+    }
+
+    #[test]
+    fn state_trailer_round_trips() {
+        let trims = StandardTrims::new();
+        trims.set_normalized(OUTPUT_TRIM_PARAMETER_ID, 0.75);
+        let mut data = b"plugin".to_vec();
+        trims.append_state(&mut data);
+
+        let loaded = StandardTrims::new();
+        assert_eq!(loaded.load_state(&data), 6);
+        assert_eq!(loaded.get_normalized(OUTPUT_TRIM_PARAMETER_ID), 0.75);
+
+        // No trailer: defaults, data untouched
+        assert_eq!(loaded.load_state(b"plugin"), 6);
+        assert_eq!(loaded.get_normalized(OUTPUT_TRIM_PARAMETER_ID), 0.5);
+    }
+}
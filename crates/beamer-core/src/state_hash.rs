@@ -0,0 +1,72 @@
+//! Content hash of the plugin state, for change detection.
+//!
+//! A GUI that shows "modified since the last preset" or a plugin that marks
+//! the host project dirty needs to know whether the state really changed,
+//! not just whether a parameter was touched (dragging a knob and back is no
+//! change). [`hash_state()`] hashes the saved state bytes, so it covers
+//! exactly what a save would write and stays in sync with the format:
+//!
+//! ```ignore
+//! // After loading a preset
+//! self.preset_hash = processor.parameters().state_hash();
+//!
+//! // Later
+//! let modified = processor.parameters().state_hash() != self.preset_hash;
+//! ```
+//!
+//! [`Parameters::state_hash()`](crate::parameter_types::Parameters::state_hash)
+//! hashes the parameter state. Plugins that save more than parameters hash
+//! their full state with `hash_state(&processor.save_state()?)`.
+//!
+//! The hash is 64-bit FNV-1a: stable across platforms, compiler versions
+//! and sessions, so it can be stored with a preset. Saving allocates, so
+//! hash on the main thread, not in `process()`.
+//!
+//! # GUI Invoke
+//!
+//! | Method | Args | Result |
+//! |--------|------|--------|
+//! | [`STATE_HASH_INVOKE`] | none | Hash of the full saved state as 16 hex digits, or `null` before the plugin is prepared |
+//!
+//! The hex string avoids precision loss in JavaScript numbers.
+
+use beamer_utils::fnv1a_64;
+
+/// Built-in WebView invoke method that returns the state hash.
+pub const STATE_HASH_INVOKE: &str = "_beamer/stateHash";
+
+/// Hash saved state bytes.
+pub fn hash_state(data: &[u8]) -> u64 {
+    fnv1a_64(data)
+}
+
+/// Handle the built-in [`STATE_HASH_INVOKE`] WebView call with the state as
+/// the wrapper saves it (empty while unprepared).
+pub fn state_hash_invoke(data: &[u8]) -> serde_json::Value {
+    if data.is_empty() {
+        return serde_json::Value::Null;
+    }
+    serde_json::Value::String(format!("{:016x}", hash_state(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_is_stable_and_content_based() {
+        let state = [4, b'g', b'a', b'i', b'n', 0, 0, 0, 0, 0, 0, 0xe0, 0x3f];
+        let copy = state;
+        assert_eq!(hash_state(&state), hash_state(&copy));
+        let mut changed = state;
+        changed[12] = 0x3e;
+        assert_ne!(hash_state(&state), hash_state(&changed));
+        assert_eq!(hash_state(&[]), 0xcbf29ce484222325);
+    }
+
+    #[test]
+    fn invoke_returns_hex_or_null() {
+        assert_eq!(state_hash_invoke(&[]), serde_json::Value::Null);
+        assert_eq!(state_hash_invoke(b"a"), serde_json::json!("af63dc4c8601ec8c"));
+    }
+}
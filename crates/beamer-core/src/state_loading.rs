@@ -0,0 +1,488 @@
+//! Two-phase state loading for plugins with expensive state.
+//!
+//! Sampler and convolution plugins often store references to large files in
+//! their state. Resolving those references (reading samples from disk,
+//! decoding, building lookup tables) can take seconds, far longer than a host
+//! is willing to block inside `setState()`.
+//!
+//! This module splits loading into two phases:
+//!
+//! 1. **Quick apply** - `Processor::load_state()` applies parameter values and
+//!    small settings synchronously, then hands the expensive part to a
+//!    [`StateLoader`].
+//! 2. **Background resolution** - the loader runs the resolution closure on a
+//!    worker thread, reporting progress through a shared [`StateLoadStatus`].
+//!    The processor polls [`StateLoader::try_take()`] from `process()` and
+//!    outputs silence while [`StateLoadStatus::is_loading()`] is true.
+//!
+//! # Overview
+//!
+//! - [`StateLoadStatus`] - Cloneable, lock-free loading flag and progress value
+//! - [`StateLoader`] - Spawns resolution work and hands the result back to the audio thread
+//!
+//! # Example
+//!
+//! ```ignore
+//! use beamer_core::{StateLoader, StateLoadStatus};
+//!
+//! struct SamplerProcessor {
+//!     parameters: SamplerParameters,
+//!     loader: StateLoader<SampleSet>,
+//!     samples: SampleSet,
+//! }
+//!
+//! impl Processor for SamplerProcessor {
+//!     fn load_state(&mut self, data: &[u8]) -> PluginResult<()> {
+//!         let paths = self.parameters.apply_state(data)?;
+//!         self.loader.spawn(move |status| {
+//!             let mut set = SampleSet::default();
+//!             for (i, path) in paths.iter().enumerate() {
+//!                 set.load(path);
+//!                 status.set_progress((i + 1) as f32 / paths.len() as f32);
+//!             }
+//!             set
+//!         });
+//!         Ok(())
+//!     }
+//!
+//!     fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!         if let Some(samples) = self.loader.try_take() {
+//!             self.samples = samples;
+//!         }
+//!         if self.loader.is_loading() {
+//!             buffer.clear_outputs();
+//!             return;
+//!         }
+//!         // ... normal playback ...
+//!     }
+//! }
+//! ```
+//!
+//! # GUI Progress Events
+//!
+//! When a [`WebViewHandle`] is attached via [`StateLoadStatus::attach_webview()`],
+//! every progress update emits a `"beamer:stateLoad"` event with the payload
+//! `{ "loading": bool, "progress": number }`. Listen for it in JavaScript with
+//! `__BEAMER__.on("beamer:stateLoad", cb)`.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[cfg(feature = "webview-bridge")]
+use crate::webview_handle::WebViewHandle;
+
+/// Event name emitted to the WebView on loading progress.
+pub const STATE_LOAD_EVENT: &str = "beamer:stateLoad";
+
+// =============================================================================
+// StateLoadStatus
+// =============================================================================
+
+/// Shared loading state for a background state resolution.
+///
+/// Cloning is cheap (reference counted). The loading flag and progress are
+/// plain atomics, so reading them from the audio thread is real-time safe.
+///
+/// A new status is idle with progress at 1.0.
+#[derive(Clone, Default)]
+pub struct StateLoadStatus {
+    inner: Arc<StatusInner>,
+}
+
+struct StatusInner {
+    loading: AtomicBool,
+    /// Progress in 0.0..=1.0, stored as f32 bits.
+    progress: AtomicU32,
+    /// Optional WebView for progress events. Only touched off the audio thread.
+    #[cfg(feature = "webview-bridge")]
+    webview: Mutex<Option<WebViewHandle>>,
+}
+
+impl Default for StatusInner {
+    fn default() -> Self {
+        Self {
+            loading: AtomicBool::new(false),
+            progress: AtomicU32::new(1.0f32.to_bits()),
+            #[cfg(feature = "webview-bridge")]
+            webview: Mutex::new(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for StateLoadStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateLoadStatus")
+            .field("loading", &self.is_loading())
+            .field("progress", &self.progress())
+            .finish()
+    }
+}
+
+impl StateLoadStatus {
+    /// Create a new status in the idle (not loading) state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true while a background resolution is in progress.
+    ///
+    /// Real-time safe. Processors should output silence while this is true.
+    #[inline]
+    pub fn is_loading(&self) -> bool {
+        self.inner.loading.load(Ordering::Acquire)
+    }
+
+    /// Current progress in the range 0.0 to 1.0.
+    ///
+    /// Real-time safe. Returns 1.0 when no load is in progress.
+    #[inline]
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.inner.progress.load(Ordering::Relaxed))
+    }
+
+    /// Report resolution progress (clamped to 0.0..=1.0).
+    ///
+    /// Called from the resolution closure. Emits a progress event to the
+    /// attached WebView, if any. **Not audio-thread safe.**
+    pub fn set_progress(&self, progress: f32) {
+        let progress = progress.clamp(0.0, 1.0);
+        self.inner.progress.store(progress.to_bits(), Ordering::Relaxed);
+        self.notify();
+    }
+
+    /// Attach a WebView handle to receive progress events.
+    #[cfg(feature = "webview-bridge")]
+    pub fn attach_webview(&self, handle: WebViewHandle) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = Some(handle);
+        }
+    }
+
+    /// Detach the WebView handle. Further progress updates are not emitted.
+    #[cfg(feature = "webview-bridge")]
+    pub fn detach_webview(&self) {
+        if let Ok(mut webview) = self.inner.webview.lock() {
+            *webview = None;
+        }
+    }
+
+    /// Mark the start of a background load.
+    fn begin(&self) {
+        self.inner.progress.store(0.0f32.to_bits(), Ordering::Relaxed);
+        self.inner.loading.store(true, Ordering::Release);
+        self.notify();
+    }
+
+    /// Mark the end of a background load.
+    fn finish(&self) {
+        self.inner.progress.store(1.0f32.to_bits(), Ordering::Relaxed);
+        self.inner.loading.store(false, Ordering::Release);
+        self.notify();
+    }
+
+    fn notify(&self) {
+        #[cfg(feature = "webview-bridge")]
+        {
+            let Ok(webview) = self.inner.webview.lock() else {
+                return;
+            };
+            if let Some(handle) = webview.as_ref() {
+                handle.emit(
+                    STATE_LOAD_EVENT,
+                    &serde_json::json!({
+                        "loading": self.is_loading(),
+                        "progress": self.progress(),
+                    }),
+                );
+            }
+        }
+    }
+}
+
+// =============================================================================
+// StateLoader
+// =============================================================================
+
+/// Runs expensive state resolution on a worker thread.
+///
+/// Each call to [`spawn()`](Self::spawn) starts a new resolution and
+/// supersedes any load still in flight: results from older loads are
+/// discarded, so only the most recent state is ever applied.
+pub struct StateLoader<T: Send + 'static> {
+    status: StateLoadStatus,
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+/// The latest load and its result, checked and updated under one lock.
+struct Slot<T> {
+    /// Incremented by every spawn(); workers of older loads drop their result.
+    generation: u64,
+    value: Option<T>,
+}
+
+fn lock<T>(slot: &Mutex<Slot<T>>) -> std::sync::MutexGuard<'_, Slot<T>> {
+    slot.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<T: Send + 'static> Default for StateLoader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + 'static> StateLoader<T> {
+    /// Create an idle loader.
+    pub fn new() -> Self {
+        Self {
+            status: StateLoadStatus::new(),
+            slot: Arc::new(Mutex::new(Slot { generation: 0, value: None })),
+        }
+    }
+
+    /// Get the shared status (for the GUI or the processor).
+    pub fn status(&self) -> &StateLoadStatus {
+        &self.status
+    }
+
+    /// Returns true while a background resolution is in progress.
+    #[inline]
+    pub fn is_loading(&self) -> bool {
+        self.status.is_loading()
+    }
+
+    /// Start resolving state on a worker thread.
+    ///
+    /// The closure receives the shared status for progress reporting. Its
+    /// return value is delivered through [`try_take()`](Self::try_take).
+    /// A result of an earlier load that was not taken yet is discarded.
+    /// Call from `load_state()`, never from the audio thread.
+    pub fn spawn<F>(&self, resolve: F)
+    where
+        F: FnOnce(&StateLoadStatus) -> T + Send + 'static,
+    {
+        let generation = {
+            let mut slot = lock(&self.slot);
+            slot.generation += 1;
+            slot.value = None;
+            slot.generation
+        };
+        let status = self.status.clone();
+        let slot = Arc::clone(&self.slot);
+
+        status.begin();
+        let spawned = thread::Builder::new()
+            .name("beamer-state-loader".to_string())
+            .spawn(move || {
+                // Ends the load if resolve() panics, so the processor
+                // doesn't output silence forever
+                let _unwind = FinishOnUnwind { status: &status, slot: &slot, generation };
+                let value = resolve(&status);
+                // Check and publish under the lock spawn() takes: a newer
+                // load either started before (and this result is dropped)
+                // or clears it when it starts.
+                let mut slot = lock(&slot);
+                if slot.generation != generation {
+                    return;
+                }
+                slot.value = Some(value);
+                status.finish();
+            });
+
+        if let Err(e) = spawned {
+            log::error!("Failed to spawn state loader thread: {e}");
+            self.status.finish();
+        }
+    }
+
+    /// Take the resolved state, if a load has completed.
+    ///
+    /// Real-time safe: uses `try_lock()` and returns `None` if the worker is
+    /// currently publishing its result. The value is returned exactly once.
+    pub fn try_take(&self) -> Option<T> {
+        self.slot.try_lock().ok().and_then(|mut slot| slot.value.take())
+    }
+}
+
+/// Finishes the load of its generation when dropped during a panic.
+struct FinishOnUnwind<'a, T> {
+    status: &'a StateLoadStatus,
+    slot: &'a Mutex<Slot<T>>,
+    generation: u64,
+}
+
+impl<T> Drop for FinishOnUnwind<'_, T> {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            return;
+        }
+        log::error!("State resolution panicked; the load is abandoned");
+        // A newer load owns the status; leave it loading
+        if lock(self.slot).generation == self.generation {
+            self.status.finish();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait_for<T: Send + 'static>(loader: &StateLoader<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(value) = loader.try_take() {
+                return value;
+            }
+            assert!(Instant::now() < deadline, "state loader timed out");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_idle_loader() {
+        let loader: StateLoader<u32> = StateLoader::new();
+        assert!(!loader.is_loading());
+        assert_eq!(loader.status().progress(), 1.0);
+        assert!(loader.try_take().is_none());
+    }
+
+    #[test]
+    fn test_spawn_delivers_result() {
+        let loader = StateLoader::new();
+        loader.spawn(|status| {
+            status.set_progress(0.5);
+            42u32
+        });
+        assert_eq!(wait_for(&loader), 42);
+        assert!(!loader.is_loading());
+        assert_eq!(loader.status().progress(), 1.0);
+        assert!(loader.try_take().is_none());
+    }
+
+    #[test]
+    fn test_loading_flag_while_resolving() {
+        let loader = StateLoader::new();
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        loader.spawn(move |_| {
+            rx.recv().ok();
+            7u32
+        });
+        assert!(loader.is_loading());
+        tx.send(()).unwrap();
+        assert_eq!(wait_for(&loader), 7);
+        assert!(!loader.is_loading());
+    }
+
+    #[test]
+    fn test_newer_spawn_supersedes_older() {
+        let loader = StateLoader::new();
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        loader.spawn(move |_| {
+            rx.recv().ok();
+            1u32
+        });
+        loader.spawn(|_| 2u32);
+        assert_eq!(wait_for(&loader), 2);
+        tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        assert!(loader.try_take().is_none());
+    }
+
+    #[test]
+    fn test_untaken_result_is_cleared_by_newer_spawn() {
+        let loader = StateLoader::new();
+        loader.spawn(|_| 1u32);
+        while loader.is_loading() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        // The first result is published but never taken
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        loader.spawn(move |_| {
+            rx.recv().ok();
+            2u32
+        });
+        assert!(loader.try_take().is_none());
+        tx.send(()).unwrap();
+        assert_eq!(wait_for(&loader), 2);
+    }
+
+    #[test]
+    fn test_racing_loads_deliver_only_the_newest() {
+        for _ in 0..200 {
+            let loader = StateLoader::new();
+            let (older_tx, older_rx) = std::sync::mpsc::channel::<()>();
+            loader.spawn(move |_| {
+                older_rx.recv().ok();
+                1u32
+            });
+            // Release the older load while the newer one is being spawned
+            let releaser = thread::spawn(move || older_tx.send(()).ok());
+            loader.spawn(|_| 2u32);
+            releaser.join().unwrap();
+
+            assert_eq!(wait_for(&loader), 2);
+            assert!(!loader.is_loading());
+            thread::sleep(Duration::from_millis(1));
+            assert!(loader.try_take().is_none());
+        }
+    }
+
+    #[test]
+    fn test_new_status_is_idle_at_full_progress() {
+        for status in [StateLoadStatus::new(), StateLoadStatus::default()] {
+            assert!(!status.is_loading());
+            assert_eq!(status.progress(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_panicking_resolve_finishes_the_load() {
+        let loader: StateLoader<u32> = StateLoader::new();
+        loader.spawn(|status| {
+            status.set_progress(0.5);
+            panic!("resolver failed");
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while loader.is_loading() {
+            assert!(Instant::now() < deadline, "panicked load never finished");
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(loader.status().progress(), 1.0);
+        assert!(loader.try_take().is_none());
+
+        // The loader still works afterwards
+        loader.spawn(|_| 3u32);
+        assert_eq!(wait_for(&loader), 3);
+    }
+
+    #[test]
+    fn test_panicking_older_resolve_keeps_newer_load_running() {
+        let loader = StateLoader::new();
+        let (older_tx, older_rx) = std::sync::mpsc::channel::<()>();
+        let (newer_tx, newer_rx) = std::sync::mpsc::channel::<()>();
+        loader.spawn(move |_| -> u32 {
+            older_rx.recv().ok();
+            panic!("older resolver failed");
+        });
+        loader.spawn(move |_| {
+            newer_rx.recv().ok();
+            2u32
+        });
+        older_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        assert!(loader.is_loading());
+        newer_tx.send(()).unwrap();
+        assert_eq!(wait_for(&loader), 2);
+    }
+
+    #[test]
+    fn test_progress_clamped() {
+        let status = StateLoadStatus::new();
+        status.set_progress(2.0);
+        assert_eq!(status.progress(), 1.0);
+        status.set_progress(-1.0);
+        assert_eq!(status.progress(), 0.0);
+    }
+}
@@ -0,0 +1,884 @@
+//! Buffer-size and sample-rate sweep stress test.
+//!
+//! [`run()`] drives a plugin the way a host would (prepare, activate,
+//! process, unprepare) over a grid of configurations and reports anything a
+//! host would trip over:
+//!
+//! - Sample rates from 44.1 kHz to 192 kHz
+//! - Block sizes from 1 to 8192 samples, including odd sizes
+//! - Every precision path the plugin opts into (`process()`,
+//!   `process_f64()`, `process_f64_mixed()`)
+//! - Auxiliary buses switched on and off
+//!
+//! Each configuration is checked for:
+//!
+//! | Check | Issue |
+//! |-------|-------|
+//! | Panics in `prepare()`, `process()` or state calls | [`StressIssueKind::Panic`] |
+//! | NaN or infinite output samples | [`StressIssueKind::NonFinite`] |
+//! | Heap allocations in `process_midi()`/`process()` | [`StressIssueKind::Allocation`] |
+//! | `save_state()` → `load_state()` → `save_state()` differing | [`StressIssueKind::State`] |
+//!
+//! Parameters are automated with random values while processing, and MIDI
+//! notes are sent to plugins that want MIDI. The last quarter of each
+//! configuration gets silent input, so decaying tails are exercised too.
+//!
+//! # Hotspots
+//!
+//! A processor can call [`tap()`] after each DSP stage to have the sweep
+//! count denormal and non-finite samples per stage. The report lists every
+//! stage that produced them, and which stage was the first in a block to do
+//! so, which points at the module that slows down under silence or blows up
+//! under extreme settings. The main output is tapped as `"output"`.
+//!
+//! ```ignore
+//! fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+//!     self.filter.process(buffer.output(0));
+//!     beamer::core::stress::tap("filter", buffer.output(0));
+//!     self.reverb.process(buffer.output(0));
+//!     beamer::core::stress::tap("reverb", buffer.output(0));
+//! }
+//! ```
+//!
+//! Outside the sweep a tap is a thread-local flag check.
+//!
+//! # Running
+//!
+//! `cargo xtask stress <package>` enables the `stress` feature of `beamer`,
+//! which makes `export_plugin!` add a test that runs the sweep with
+//! [`CountingAllocator`] installed as the global allocator. Without it,
+//! allocations are not checked.
+//!
+//! The harness calls the plugin directly instead of going through a format
+//! wrapper, so it exercises the plugin code rather than the VST3 or AU glue.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::midi::{MidiBuffer, MidiEvent};
+use crate::parameter_store::ParameterStore;
+use crate::plugin::{
+    BusLayout, Descriptor, HasParameters, HostSetup, PluginSetup, PrecisionPolicy, ProcessMode,
+    Processor,
+};
+use crate::preset::FactoryPresets;
+use crate::process_context::ProcessContext;
+use crate::sample::Sample;
+
+/// Sample rates swept by default.
+pub const DEFAULT_SAMPLE_RATES: [f64; 6] = [44100.0, 48000.0, 88200.0, 96000.0, 176400.0, 192000.0];
+
+/// Block sizes swept by default.
+pub const DEFAULT_BLOCK_SIZES: [usize; 16] =
+    [1, 2, 3, 7, 16, 31, 64, 127, 128, 441, 512, 1000, 1024, 2048, 4096, 8192];
+
+// =============================================================================
+// Allocation Counting
+// =============================================================================
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static ARMED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Global allocator that counts allocations made while the stress test is
+/// inside a processing call.
+///
+/// Wraps [`System`]; outside the processing calls it only forwards.
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: beamer::core::stress::CountingAllocator = beamer::core::stress::CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    #[inline]
+    fn record() {
+        INSTALLED.store(true, Ordering::Relaxed);
+        if ARMED.try_with(Cell::get).unwrap_or(false) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// SAFETY: Forwards every call to the system allocator unchanged.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::record();
+        // SAFETY: Same contract as the caller's.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::record();
+        // SAFETY: Same contract as the caller's.
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::record();
+        // SAFETY: Same contract as the caller's.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::record();
+        // SAFETY: Same contract as the caller's.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Count allocations on this thread while `f` runs.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    struct Disarm;
+    impl Drop for Disarm {
+        fn drop(&mut self) {
+            ARMED.with(|armed| armed.set(false));
+        }
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    ARMED.with(|armed| armed.set(true));
+    let _disarm = Disarm;
+    let result = f();
+    ARMED.with(|armed| armed.set(false));
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+// =============================================================================
+// Taps
+// =============================================================================
+
+/// Stage name of the main output, which the sweep taps itself.
+pub const OUTPUT_STAGE: &str = "output";
+
+/// Tap results collected on the sweep's thread.
+#[derive(Default)]
+struct TapLog {
+    run: String,
+    block: usize,
+    /// Whether a stage already produced denormals or non-finite samples in
+    /// this block, so later stages are not counted as origins.
+    denormal_seen: bool,
+    non_finite_origin: Option<&'static str>,
+    stages: Vec<StageHotspot>,
+}
+
+thread_local! {
+    static TAPS: RefCell<TapLog> = RefCell::new(TapLog::default());
+}
+
+/// Report the samples a DSP stage just produced to the stress sweep.
+///
+/// Does nothing unless called from a processing call of [`run()`], so it
+/// can stay in release builds. Taps on other threads are ignored.
+#[inline]
+pub fn tap<S: Sample>(stage: &'static str, samples: &[S]) {
+    if ARMED.try_with(Cell::get).unwrap_or(false) {
+        record_tap(stage, samples);
+    }
+}
+
+#[cold]
+fn record_tap<S: Sample>(stage: &'static str, samples: &[S]) {
+    let denormals = samples.iter().filter(|s| s.is_subnormal()).count();
+    let non_finite = samples.iter().position(|s| !s.to_f64().is_finite());
+    // The log allocates when a stage is first seen; don't count that
+    // against the plugin.
+    let armed = ARMED.with(|armed| armed.replace(false));
+    TAPS.with(|log| {
+        let mut log = log.borrow_mut();
+        let log = &mut *log;
+        let index = match log.stages.iter().position(|s| s.stage == stage) {
+            Some(index) => index,
+            None => {
+                log.stages.push(StageHotspot { stage, ..StageHotspot::default() });
+                log.stages.len() - 1
+            }
+        };
+        let hotspot = &mut log.stages[index];
+        hotspot.calls += 1;
+        if denormals > 0 {
+            hotspot.denormal_calls += 1;
+            hotspot.denormal_samples += denormals;
+            if !log.denormal_seen {
+                log.denormal_seen = true;
+                hotspot.denormal_origins += 1;
+            }
+        }
+        if let Some(i) = non_finite {
+            hotspot.non_finite_calls += 1;
+            if hotspot.first_non_finite.is_none() {
+                hotspot.first_non_finite = Some(format!("{}, block {} sample {}", log.run, log.block, i));
+            }
+            if log.non_finite_origin.is_none() {
+                log.non_finite_origin = Some(stage);
+                hotspot.non_finite_origins += 1;
+            }
+        }
+    });
+    ARMED.with(|a| a.set(armed));
+}
+
+/// Denormal and non-finite samples seen by one [`tap()`] stage over the
+/// whole sweep.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StageHotspot {
+    /// Name passed to [`tap()`].
+    pub stage: &'static str,
+    /// Times the stage was tapped.
+    pub calls: usize,
+    /// Calls that contained denormals.
+    pub denormal_calls: usize,
+    /// Denormal samples over all calls.
+    pub denormal_samples: usize,
+    /// Blocks in which this stage was the first to produce denormals.
+    pub denormal_origins: usize,
+    /// Calls that contained NaN or infinite samples.
+    pub non_finite_calls: usize,
+    /// Blocks in which this stage was the first to produce NaN or infinite
+    /// samples.
+    pub non_finite_origins: usize,
+    /// Configuration, block and sample of the first non-finite sample.
+    pub first_non_finite: Option<String>,
+}
+
+impl StageHotspot {
+    /// Returns true if the stage produced denormals or non-finite samples.
+    pub fn is_hot(&self) -> bool {
+        self.denormal_calls > 0 || self.non_finite_calls > 0
+    }
+}
+
+impl fmt::Display for StageHotspot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}': denormals in {}/{} calls ({} samples, first in {} blocks)",
+            self.stage, self.denormal_calls, self.calls, self.denormal_samples, self.denormal_origins
+        )?;
+        if let Some(first) = &self.first_non_finite {
+            write!(
+                f,
+                ", non-finite in {} calls (first in {} blocks, earliest at {})",
+                self.non_finite_calls, self.non_finite_origins, first
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Options and Report
+// =============================================================================
+
+/// What to sweep.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressOptions {
+    /// Sample rates in Hz.
+    pub sample_rates: Vec<f64>,
+    /// Block sizes in samples. Each is also the maximum block size the
+    /// plugin is prepared with.
+    pub block_sizes: Vec<usize>,
+    /// Audio processed per configuration, in seconds (at least four blocks).
+    pub seconds: f64,
+}
+
+impl Default for StressOptions {
+    fn default() -> Self {
+        Self {
+            sample_rates: DEFAULT_SAMPLE_RATES.to_vec(),
+            block_sizes: DEFAULT_BLOCK_SIZES.to_vec(),
+            seconds: 0.05,
+        }
+    }
+}
+
+impl StressOptions {
+    /// A reduced grid for a fast check.
+    pub fn quick() -> Self {
+        Self {
+            sample_rates: vec![44100.0, 96000.0, 192000.0],
+            block_sizes: vec![1, 31, 512, 8192],
+            seconds: 0.02,
+        }
+    }
+
+    /// Options from the environment, as set by `cargo xtask stress`.
+    ///
+    /// `BEAMER_STRESS_QUICK=1` starts from [`quick()`](Self::quick);
+    /// `BEAMER_STRESS_RATES` and `BEAMER_STRESS_BLOCKS` (comma-separated)
+    /// and `BEAMER_STRESS_SECONDS` override single fields.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let mut options = if var("BEAMER_STRESS_QUICK").is_some_and(|v| v != "0") {
+            Self::quick()
+        } else {
+            Self::default()
+        };
+        if let Some(rates) = var("BEAMER_STRESS_RATES") {
+            options.sample_rates = rates.split(',').filter_map(|r| r.trim().parse().ok()).collect();
+        }
+        if let Some(blocks) = var("BEAMER_STRESS_BLOCKS") {
+            options.block_sizes = blocks.split(',').filter_map(|b| b.trim().parse().ok()).collect();
+        }
+        if let Some(seconds) = var("BEAMER_STRESS_SECONDS").and_then(|s| s.parse().ok()) {
+            options.seconds = seconds;
+        }
+        options
+    }
+}
+
+/// Kind of problem found by the stress test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StressIssueKind {
+    /// The plugin panicked.
+    Panic,
+    /// An output sample was NaN or infinite.
+    NonFinite,
+    /// The processing calls allocated or freed memory.
+    Allocation,
+    /// A saved state did not survive a load and save round trip.
+    State,
+}
+
+impl fmt::Display for StressIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Panic => "panic",
+            Self::NonFinite => "non-finite output",
+            Self::Allocation => "allocation",
+            Self::State => "state",
+        })
+    }
+}
+
+/// A problem found in one configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressIssue {
+    /// The configuration, e.g. `"48000 Hz, 64 samples, f32, aux 1/1"`.
+    pub run: String,
+    /// What went wrong.
+    pub kind: StressIssueKind,
+    /// Details, e.g. where the first NaN appeared.
+    pub detail: String,
+}
+
+/// Result of [`run()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StressReport {
+    /// Configurations processed.
+    pub runs: usize,
+    /// Whether [`CountingAllocator`] was installed, i.e. allocations were
+    /// checked.
+    pub allocations_checked: bool,
+    /// Problems found, at most one per kind and configuration.
+    pub issues: Vec<StressIssue>,
+    /// Every tapped stage in the order first tapped, including
+    /// [`OUTPUT_STAGE`]. Denormals are reported here but are not issues.
+    pub hotspots: Vec<StageHotspot>,
+}
+
+impl StressReport {
+    /// Returns true if no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for StressReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "[{}] {}: {}", issue.kind, issue.run, issue.detail)?;
+        }
+        for hotspot in self.hotspots.iter().filter(|h| h.is_hot()) {
+            writeln!(f, "[hotspot] {}", hotspot)?;
+        }
+        write!(
+            f,
+            "{} configurations, {} issues{}",
+            self.runs,
+            self.issues.len(),
+            if self.allocations_checked { "" } else { " (allocations not checked)" }
+        )
+    }
+}
+
+// =============================================================================
+// Sweep
+// =============================================================================
+
+/// Processing path exercised by a run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Precision {
+    F32,
+    F64,
+    F64MainF32Aux,
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::F64MainF32Aux => "f64/f32 aux",
+        })
+    }
+}
+
+/// Small deterministic generator for signals and parameter values.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Run the sweep for a plugin.
+///
+/// Called by the test `export_plugin!` adds with the `stress` feature.
+pub fn run<D, P>(options: &StressOptions) -> StressReport
+where
+    D: Descriptor,
+    P: FactoryPresets<Parameters = D::Parameters>,
+{
+    let mut report = StressReport::default();
+    let mut descriptor = Some(D::default());
+    let full_layout = BusLayout::from_plugin(descriptor.as_ref().unwrap());
+    let aux_count = full_layout.aux_input_count + full_layout.aux_output_count;
+    let masks: Vec<u32> = if aux_count <= 3 {
+        (0..1u32 << aux_count).rev().collect()
+    } else {
+        vec![u32::MAX, 0]
+    };
+    let mut rng = Rng(0x5eed);
+    TAPS.with(|log| *log.borrow_mut() = TapLog::default());
+
+    for &sample_rate in &options.sample_rates {
+        for &block_size in &options.block_sizes {
+            for &mask in &masks {
+                let layout = masked_layout(&full_layout, mask);
+                let precisions = match descriptor.take() {
+                    Some(d) => {
+                        let (precisions, d) = precisions_of(d, sample_rate, block_size, &layout);
+                        descriptor = d;
+                        precisions
+                    }
+                    None => break,
+                };
+                for precision in precisions {
+                    let Some(d) = descriptor.take() else { break };
+                    let run = format!(
+                        "{} Hz, {} samples, {}, aux {}/{}",
+                        sample_rate,
+                        block_size,
+                        precision,
+                        active_aux(&layout),
+                        aux_count
+                    );
+                    report.runs += 1;
+                    TAPS.with(|log| log.borrow_mut().run = run.clone());
+                    let mut issues = Vec::new();
+                    descriptor = run_config(
+                        d,
+                        &layout,
+                        sample_rate,
+                        block_size,
+                        precision,
+                        options.seconds,
+                        &mut rng,
+                        &mut issues,
+                    );
+                    if descriptor.is_none() {
+                        // The plugin panicked and the instance is gone; start over.
+                        descriptor = catch_unwind(D::default).ok();
+                    }
+                    report.issues.extend(
+                        issues.into_iter().map(|(kind, detail)| StressIssue { run: run.clone(), kind, detail }),
+                    );
+                }
+            }
+        }
+    }
+    report.allocations_checked = INSTALLED.load(Ordering::Relaxed);
+    report.hotspots = TAPS.with(|log| std::mem::take(&mut log.borrow_mut().stages));
+    report
+}
+
+fn masked_layout(layout: &BusLayout, mask: u32) -> BusLayout {
+    let mut layout = layout.clone();
+    let inputs = layout.aux_input_count;
+    for (i, channels) in layout.aux_input_channel_counts.iter_mut().enumerate() {
+        if mask & (1 << i) == 0 {
+            *channels = 0;
+        }
+    }
+    for (i, channels) in layout.aux_output_channel_counts.iter_mut().enumerate() {
+        if mask & (1 << (inputs + i)) == 0 {
+            *channels = 0;
+        }
+    }
+    layout
+}
+
+fn active_aux(layout: &BusLayout) -> usize {
+    layout
+        .aux_input_channel_counts
+        .iter()
+        .chain(&layout.aux_output_channel_counts)
+        .filter(|&&channels| channels > 0)
+        .count()
+}
+
+/// Prepare once to ask the processor which precision paths it implements.
+fn precisions_of<D: Descriptor>(
+    descriptor: D,
+    sample_rate: f64,
+    block_size: usize,
+    layout: &BusLayout,
+) -> (Vec<Precision>, Option<D>) {
+    let host = HostSetup::new(sample_rate, block_size, layout.clone(), ProcessMode::Realtime);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let processor = descriptor.prepare(D::Setup::extract(&host));
+        let precisions = match processor.precision_policy() {
+            PrecisionPolicy::F32 => vec![Precision::F32],
+            PrecisionPolicy::F64 => vec![Precision::F32, Precision::F64],
+            PrecisionPolicy::F64MainF32Aux => vec![Precision::F32, Precision::F64MainF32Aux],
+        };
+        (precisions, processor.unprepare())
+    }));
+    match result {
+        Ok((precisions, descriptor)) => (precisions, Some(descriptor)),
+        // Reported by the run that follows.
+        Err(_) => (vec![Precision::F32], catch_unwind(D::default).ok()),
+    }
+}
+
+/// Channel storage for one direction of all buses.
+struct Channels<S> {
+    main: Vec<Vec<S>>,
+    aux: Vec<Vec<Vec<S>>>,
+}
+
+impl<S: Sample> Channels<S> {
+    fn new(main: u32, aux: &[u32], block_size: usize) -> Self {
+        let bus = |channels: u32| vec![vec![S::ZERO; block_size]; channels as usize];
+        Self {
+            main: bus(main),
+            aux: aux.iter().map(|&channels| bus(channels)).collect(),
+        }
+    }
+
+    fn fill(&mut self, rng: &mut Rng, phase: &mut f64, step: f64, len: usize) {
+        for i in 0..len {
+            let value = 0.5 * (*phase + step * i as f64).sin() + 0.1 * (rng.next() * 2.0 - 1.0);
+            let sample = S::from_f64(value);
+            for channel in self.main.iter_mut().chain(self.aux.iter_mut().flatten()) {
+                channel[i] = sample;
+            }
+        }
+        *phase = (*phase + step * len as f64) % std::f64::consts::TAU;
+    }
+
+    fn silence(&mut self, len: usize) {
+        for channel in self.main.iter_mut().chain(self.aux.iter_mut().flatten()) {
+            channel[..len].fill(S::ZERO);
+        }
+    }
+
+    fn first_non_finite(&self, len: usize) -> Option<String> {
+        let check = |channel: &[S]| channel[..len].iter().position(|s| !s.to_f64().is_finite());
+        for (ch, channel) in self.main.iter().enumerate() {
+            if let Some(i) = check(channel) {
+                return Some(format!("main output channel {} sample {}", ch, i));
+            }
+        }
+        for (bus, channels) in self.aux.iter().enumerate() {
+            for (ch, channel) in channels.iter().enumerate() {
+                if let Some(i) = check(channel) {
+                    return Some(format!("aux output {} channel {} sample {}", bus, ch, i));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Run one configuration. Returns the unprepared descriptor, or `None` if
+/// the plugin panicked.
+#[allow(clippy::too_many_arguments)]
+fn run_config<D: Descriptor>(
+    descriptor: D,
+    layout: &BusLayout,
+    sample_rate: f64,
+    block_size: usize,
+    precision: Precision,
+    seconds: f64,
+    rng: &mut Rng,
+    issues: &mut Vec<(StressIssueKind, String)>,
+) -> Option<D> {
+    let host = HostSetup::new(sample_rate, block_size, layout.clone(), ProcessMode::Realtime);
+    let setup = D::Setup::extract(&host);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut processor = descriptor.prepare(setup.clone());
+        processor.set_active(true);
+        match precision {
+            Precision::F32 => process_blocks::<D, f32, f32>(
+                &mut processor, layout, sample_rate, block_size, seconds, rng, issues,
+                |p, buffer, aux, context| p.process(buffer, aux, context),
+            ),
+            Precision::F64 => process_blocks::<D, f64, f64>(
+                &mut processor, layout, sample_rate, block_size, seconds, rng, issues,
+                |p, buffer, aux, context| p.process_f64(buffer, aux, context),
+            ),
+            Precision::F64MainF32Aux => process_blocks::<D, f64, f32>(
+                &mut processor, layout, sample_rate, block_size, seconds, rng, issues,
+                |p, buffer, aux, context| p.process_f64_mixed(buffer, aux, context),
+            ),
+        }
+        processor.set_active(false);
+        check_state::<D>(&processor, setup, issues);
+        processor.unprepare()
+    }));
+    match result {
+        Ok(descriptor) => Some(descriptor),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            issues.push((StressIssueKind::Panic, message));
+            None
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_blocks<D: Descriptor, M: Sample, A: Sample>(
+    processor: &mut D::Processor,
+    layout: &BusLayout,
+    sample_rate: f64,
+    block_size: usize,
+    seconds: f64,
+    rng: &mut Rng,
+    issues: &mut Vec<(StressIssueKind, String)>,
+    mut process: impl FnMut(&mut D::Processor, &mut Buffer<M>, &mut AuxiliaryBuffers<A>, &ProcessContext),
+) {
+    let mut inputs = Channels::<M>::new(layout.main_input_channels, &[], block_size);
+    let mut outputs = Channels::<M>::new(layout.main_output_channels, &[], block_size);
+    let mut aux_inputs = Channels::<A>::new(0, &layout.aux_input_channel_counts, block_size);
+    let mut aux_outputs = Channels::<A>::new(0, &layout.aux_output_channel_counts, block_size);
+    let mut midi_input = MidiBuffer::new_boxed();
+    let mut midi_output = MidiBuffer::new_boxed();
+    let wants_midi = processor.wants_midi();
+    let parameter_count = processor.parameters().count();
+
+    let total = ((sample_rate * seconds) as usize).max(block_size * 4);
+    let blocks = total.div_ceil(block_size);
+    let step = std::f64::consts::TAU * 440.0 / sample_rate;
+    let (mut phase, mut aux_phase) = (0.0, 0.0);
+    let mut note: Option<u8> = None;
+    let (mut non_finite, mut allocations) = (None, 0);
+
+    for block in 0..blocks {
+        // Automate a random parameter now and then, like a host would.
+        if parameter_count > 0 && block % 8 == 0 {
+            let parameters = processor.parameters();
+            let index = (rng.next() * parameter_count as f64) as usize % parameter_count;
+            if let Some(info) = parameters.info(index) {
+                parameters.set_normalized(info.id, rng.next());
+            }
+        }
+
+        midi_input.clear();
+        midi_output.clear();
+        if wants_midi && block % 4 == 0 {
+            if let Some(pitch) = note.take() {
+                midi_input.push(MidiEvent::note_off(0, 0, pitch, 0.0, -1, 0.0));
+            } else {
+                let pitch = 36 + (rng.next() * 48.0) as u8;
+                midi_input.push(MidiEvent::note_on(0, 0, pitch, rng.next() as f32, -1, 0.0, 0));
+                note = Some(pitch);
+            }
+        }
+
+        if block < blocks * 3 / 4 {
+            inputs.fill(rng, &mut phase, step, block_size);
+            aux_inputs.fill(rng, &mut aux_phase, step * 1.5, block_size);
+        } else {
+            inputs.silence(block_size);
+            aux_inputs.silence(block_size);
+        }
+        TAPS.with(|log| {
+            let mut log = log.borrow_mut();
+            log.block = block;
+            log.denormal_seen = false;
+            log.non_finite_origin = None;
+        });
+        let context = ProcessContext::with_empty_transport(sample_rate, block_size);
+
+        let ((), count) = count_allocations(|| {
+            processor.begin_block(block_size);
+            processor.process_midi(midi_input.as_slice(), &mut midi_output);
+            let mut buffer = Buffer::new(
+                inputs.main.iter().map(|c| &c[..block_size]),
+                outputs.main.iter_mut().map(|c| &mut c[..block_size]),
+                block_size,
+            );
+            let mut aux = AuxiliaryBuffers::new(
+                aux_inputs.aux.iter().map(|bus| bus.iter().map(|c| &c[..block_size])),
+                aux_outputs.aux.iter_mut().map(|bus| bus.iter_mut().map(|c| &mut c[..block_size])),
+                block_size,
+            );
+            process(processor, &mut buffer, &mut aux, &context);
+        });
+        allocations += count;
+        for channel in &outputs.main {
+            record_tap(OUTPUT_STAGE, &channel[..block_size]);
+        }
+
+        if non_finite.is_none() {
+            non_finite = outputs
+                .first_non_finite(block_size)
+                .or_else(|| aux_outputs.first_non_finite(block_size))
+                .map(|at| match TAPS.with(|log| log.borrow().non_finite_origin) {
+                    Some(stage) if stage != OUTPUT_STAGE => {
+                        format!("{} in block {}, first seen at tap '{}'", at, block, stage)
+                    }
+                    _ => format!("{} in block {}", at, block),
+                });
+        }
+    }
+
+    if let Some(detail) = non_finite {
+        issues.push((StressIssueKind::NonFinite, detail));
+    }
+    if allocations > 0 {
+        issues.push((
+            StressIssueKind::Allocation,
+            format!("{} allocations or frees in {} blocks", allocations, blocks),
+        ));
+    }
+}
+
+/// Save the state, load it into a fresh instance and compare.
+fn check_state<D: Descriptor>(
+    processor: &D::Processor,
+    setup: D::Setup,
+    issues: &mut Vec<(StressIssueKind, String)>,
+) {
+    let saved = match processor.save_state() {
+        Ok(saved) => saved,
+        Err(e) => {
+            issues.push((StressIssueKind::State, format!("save_state() failed: {}", e)));
+            return;
+        }
+    };
+    let mut fresh = D::default().prepare(setup);
+    if let Err(e) = fresh.load_state(&saved) {
+        issues.push((StressIssueKind::State, format!("load_state() failed: {}", e)));
+        return;
+    }
+    let (original, restored) = (processor.parameters(), fresh.parameters());
+    for index in 0..original.count() {
+        let Some(info) = original.info(index) else { continue };
+        let (a, b) = (original.get_normalized(info.id), restored.get_normalized(info.id));
+        if (a - b).abs() > 1e-6 {
+            issues.push((
+                StressIssueKind::State,
+                format!("parameter '{}' restored as {} instead of {}", info.string_id, b, a),
+            ));
+            return;
+        }
+    }
+    match fresh.save_state() {
+        Ok(resaved) if resaved == saved => {}
+        Ok(resaved) => issues.push((
+            StressIssueKind::State,
+            format!("state of {} bytes saved again as {} different bytes", saved.len(), resaved.len()),
+        )),
+        Err(e) => issues.push((StressIssueKind::State, format!("save_state() after load failed: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_and_report() {
+        let quick = StressOptions::quick();
+        assert!(quick.block_sizes.contains(&1) && quick.block_sizes.contains(&8192));
+        assert!(StressOptions::default().sample_rates.contains(&192000.0));
+
+        let mut report =
+            StressReport { runs: 2, allocations_checked: true, issues: Vec::new(), hotspots: Vec::new() };
+        assert!(report.is_ok());
+        assert_eq!(report.to_string(), "2 configurations, 0 issues");
+        report.issues.push(StressIssue {
+            run: "48000 Hz, 64 samples, f32, aux 0/0".into(),
+            kind: StressIssueKind::NonFinite,
+            detail: "main output channel 1 sample 3 in block 0".into(),
+        });
+        assert!(!report.is_ok());
+        assert!(report.to_string().starts_with("[non-finite output] 48000 Hz"));
+    }
+
+    #[test]
+    fn test_taps_find_the_first_hot_stage() {
+        tap("ignored", &[f32::NAN]); // not armed
+
+        TAPS.with(|log| *log.borrow_mut() = TapLog { run: "48000 Hz".into(), ..TapLog::default() });
+        for block in 0..2 {
+            TAPS.with(|log| {
+                let mut log = log.borrow_mut();
+                log.block = block;
+                log.denormal_seen = false;
+                log.non_finite_origin = None;
+            });
+            count_allocations(|| {
+                tap("oscillator", &[0.5f64, 0.25]);
+                tap("filter", &[1e-310f64, 0.0, 1e-320]);
+                tap("drive", &[f64::INFINITY, 1e-310]);
+                tap("drive", &[f64::NAN]);
+            });
+        }
+        let stages = TAPS.with(|log| std::mem::take(&mut log.borrow_mut().stages));
+
+        let names: Vec<_> = stages.iter().map(|s| s.stage).collect();
+        assert_eq!(names, ["oscillator", "filter", "drive"]);
+        assert!(!stages[0].is_hot());
+        let (filter, drive) = (&stages[1], &stages[2]);
+        assert_eq!((filter.denormal_calls, filter.denormal_samples, filter.denormal_origins), (2, 4, 2));
+        assert_eq!((drive.calls, drive.denormal_origins), (4, 0));
+        assert_eq!((drive.non_finite_calls, drive.non_finite_origins), (4, 2));
+        assert_eq!(drive.first_non_finite.as_deref(), Some("48000 Hz, block 0 sample 0"));
+        assert!(drive.to_string().starts_with("'drive': denormals in 2/4 calls"));
+    }
+
+    #[test]
+    fn test_masked_layout() {
+        let layout = BusLayout {
+            main_input_channels: 2,
+            main_output_channels: 2,
+            aux_input_count: 1,
+            aux_output_count: 1,
+            aux_input_channel_counts: vec![2],
+            aux_output_channel_counts: vec![2],
+        };
+        let masked = masked_layout(&layout, 0b10);
+        assert_eq!(masked.aux_input_channel_counts, vec![0]);
+        assert_eq!(masked.aux_output_channel_counts, vec![2]);
+        assert_eq!(active_aux(&masked), 1);
+    }
+}
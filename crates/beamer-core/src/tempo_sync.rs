@@ -0,0 +1,175 @@
+//! Tempo-synced note values for delays, LFOs and gates.
+//!
+//! [`TempoSync`] is a note value (1/4, 1/8 dotted, 1/16 triplet, ...) with
+//! conversions to quarter notes, seconds, samples and Hz. It doubles as an
+//! enum parameter through [`TempoSyncParameter`]:
+//!
+//! ```ignore
+//! #[derive(Parameters)]
+//! pub struct DelayParameters {
+//!     #[parameter(id = "sync", name = "Sync")]
+//!     pub sync: TempoSyncParameter,
+//! }
+//!
+//! fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, context: &ProcessContext) {
+//!     let sync = self.parameters.sync.get();
+//!     let delay_samples = context
+//!         .beats_to_samples(sync.beats())
+//!         .unwrap_or_else(|| sync.samples(120.0, context.sample_rate));
+//!     // ...
+//! }
+//! ```
+//!
+//! Note values are independent of the time signature: a quarter note is
+//! always one beat of [`Transport::tempo`](crate::Transport::tempo), which
+//! hosts report in quarter notes per minute.
+
+use crate::parameter_types::{EnumParameter, EnumParameterValue};
+
+/// A note value relative to the host tempo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TempoSync {
+    /// 1/1, four quarter notes.
+    Whole,
+    /// 1/2 dotted, three quarter notes.
+    HalfDotted,
+    /// 1/2, two quarter notes.
+    Half,
+    /// 1/2 triplet, three in the time of two halves.
+    HalfTriplet,
+    /// 1/4 dotted.
+    QuarterDotted,
+    /// 1/4, one beat.
+    #[default]
+    Quarter,
+    /// 1/4 triplet.
+    QuarterTriplet,
+    /// 1/8 dotted.
+    EighthDotted,
+    /// 1/8.
+    Eighth,
+    /// 1/8 triplet.
+    EighthTriplet,
+    /// 1/16 dotted.
+    SixteenthDotted,
+    /// 1/16.
+    Sixteenth,
+    /// 1/16 triplet.
+    SixteenthTriplet,
+    /// 1/32.
+    ThirtySecond,
+}
+
+const VALUES: [TempoSync; 14] = [
+    TempoSync::Whole,
+    TempoSync::HalfDotted,
+    TempoSync::Half,
+    TempoSync::HalfTriplet,
+    TempoSync::QuarterDotted,
+    TempoSync::Quarter,
+    TempoSync::QuarterTriplet,
+    TempoSync::EighthDotted,
+    TempoSync::Eighth,
+    TempoSync::EighthTriplet,
+    TempoSync::SixteenthDotted,
+    TempoSync::Sixteenth,
+    TempoSync::SixteenthTriplet,
+    TempoSync::ThirtySecond,
+];
+
+const NAMES: &[&str] = &[
+    "1/1", "1/2 D", "1/2", "1/2 T", "1/4 D", "1/4", "1/4 T", "1/8 D", "1/8", "1/8 T", "1/16 D",
+    "1/16", "1/16 T", "1/32",
+];
+
+impl TempoSync {
+    /// Length in quarter notes (beats).
+    pub const fn beats(self) -> f64 {
+        match self {
+            Self::Whole => 4.0,
+            Self::HalfDotted => 3.0,
+            Self::Half => 2.0,
+            Self::HalfTriplet => 4.0 / 3.0,
+            Self::QuarterDotted => 1.5,
+            Self::Quarter => 1.0,
+            Self::QuarterTriplet => 2.0 / 3.0,
+            Self::EighthDotted => 0.75,
+            Self::Eighth => 0.5,
+            Self::EighthTriplet => 1.0 / 3.0,
+            Self::SixteenthDotted => 0.375,
+            Self::Sixteenth => 0.25,
+            Self::SixteenthTriplet => 1.0 / 6.0,
+            Self::ThirtySecond => 0.125,
+        }
+    }
+
+    /// Length in seconds at `tempo` BPM.
+    pub fn seconds(self, tempo: f64) -> f64 {
+        self.beats() * 60.0 / tempo
+    }
+
+    /// Length in samples at `tempo` BPM and `sample_rate`.
+    pub fn samples(self, tempo: f64, sample_rate: f64) -> f64 {
+        self.seconds(tempo) * sample_rate
+    }
+
+    /// Rate in Hz of one cycle per note value at `tempo` BPM, for LFOs.
+    pub fn hz(self, tempo: f64) -> f64 {
+        tempo / 60.0 / self.beats()
+    }
+}
+
+impl EnumParameterValue for TempoSync {
+    const COUNT: usize = VALUES.len();
+    const DEFAULT_INDEX: usize = 5;
+
+    fn from_index(index: usize) -> Option<Self> {
+        VALUES.get(index).copied()
+    }
+
+    fn to_index(self) -> usize {
+        self as usize
+    }
+
+    fn default_value() -> Self {
+        Self::Quarter
+    }
+
+    fn name(index: usize) -> &'static str {
+        NAMES.get(index).copied().unwrap_or("")
+    }
+
+    fn names() -> &'static [&'static str] {
+        NAMES
+    }
+}
+
+/// Enum parameter selecting a [`TempoSync`] note value.
+///
+/// Recognized by `#[derive(Parameters)]` like any `EnumParameter`.
+pub type TempoSyncParameter = EnumParameter<TempoSync>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_and_names_agree() {
+        for (index, name) in NAMES.iter().enumerate() {
+            let value = TempoSync::from_index(index).unwrap();
+            assert_eq!(value.to_index(), index);
+            assert_eq!(TempoSync::name(index), *name);
+        }
+        assert_eq!(NAMES.len(), TempoSync::COUNT);
+        assert_eq!(TempoSync::from_index(TempoSync::DEFAULT_INDEX), Some(TempoSync::Quarter));
+        assert_eq!(TempoSyncParameter::new("Sync").get(), TempoSync::Quarter);
+    }
+
+    #[test]
+    fn conversions_follow_tempo() {
+        assert_eq!(TempoSync::Quarter.seconds(120.0), 0.5);
+        assert_eq!(TempoSync::EighthDotted.samples(120.0, 48000.0), 18000.0);
+        assert!((TempoSync::EighthTriplet.samples(120.0, 48000.0) - 8000.0).abs() < 1e-9);
+        assert_eq!(TempoSync::Whole.hz(120.0), 0.5);
+    }
+}
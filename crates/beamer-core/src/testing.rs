@@ -0,0 +1,1353 @@
+//! Offline rendering for golden-file regression tests.
+//!
+//! [`OfflineRenderer`] prepares a plugin without a host, feeds it a WAV file
+//! or a generated signal, plays parameter automation and MIDI on a sample
+//! timeline and returns what came out:
+//!
+//! ```ignore
+//! use beamer::core::testing::{OfflineRenderer, TestSignal};
+//!
+//! #[test]
+//! fn drive_sweep_matches_golden() {
+//!     let render = OfflineRenderer::<MyDescriptor>::new(48000.0, 256)
+//!         .with_signal(TestSignal::Sine { frequency: 220.0, gain: 0.5 })
+//!         .with_seconds(2.0)
+//!         .with_plain_parameter(0, "drive", 0.0)
+//!         .with_parameter(48000, "drive", 1.0)
+//!         .with_note(24000, 0, 60, 0.8, 12000)
+//!         .render();
+//!
+//!     assert_eq!(render.first_non_finite(), None);
+//!     render.assert_golden("tests/golden/drive_sweep.wav", 1e-5);
+//! }
+//! ```
+//!
+//! Automation lanes ([`Automation`]) join breakpoints with curves and are
+//! delivered like a host delivers them: the block's final value is set
+//! before `process()` and the points inside the block go to processors that
+//! [split blocks](crate::split_block), so tests cover zipper noise and
+//! coefficient updates under moving parameters, not only static settings:
+//!
+//! ```ignore
+//! let render = OfflineRenderer::<MyDescriptor>::new(48000.0, 256)
+//!     .with_signal(TestSignal::Noise { gain: 0.5 })
+//!     .with_automation(
+//!         "cutoff",
+//!         Automation::new()
+//!             .jump_to(0, 0.1)
+//!             .ramp_to(24000, 0.9)
+//!             .curve_to(48000, 0.2, CurveShape::Power { exponent: 3.0 }),
+//!     )
+//!     .render();
+//! ```
+//!
+//! Golden files are 32-bit float WAVs. Run the tests once with
+//! `BEAMER_BLESS=1` to write (or update) them, then commit them; later runs
+//! compare against them.
+//!
+//! Null tests render the same script twice and compare the results sample
+//! by sample. [`with_block_sizes()`](OfflineRenderer::with_block_sizes)
+//! slices the processed render like a host that varies its buffer sizes, so
+//! output that depends on where blocks start fails the test:
+//!
+//! ```ignore
+//! let script = |renderer: OfflineRenderer<MyDescriptor>| {
+//!     renderer
+//!         .with_signal(TestSignal::Noise { gain: 0.5 })
+//!         .with_note(1000, 0, 60, 0.8, 4800)
+//!         .with_automation("cutoff", Automation::new().ramp_to(48000, 1.0))
+//! };
+//! let reference = script(OfflineRenderer::new(48000.0, 512)).render();
+//! let processed = script(OfflineRenderer::new(48000.0, 512))
+//!     .with_block_sizes(vec![1, 17, 512, 64])
+//!     .render();
+//! processed.assert_nulls(&reference, 0.0);
+//! ```
+//!
+//! [`with_latency_compensation()`](OfflineRenderer::with_latency_compensation)
+//! lines the output up with the input like a host's delay compensation,
+//! following latency changes the plugin announces with
+//! [`HostServices::latency_changed()`]:
+//!
+//! ```ignore
+//! let render = OfflineRenderer::<MyDescriptor>::new(48000.0, 256)
+//!     .with_signal(TestSignal::Impulse)
+//!     .with_parameter(24000, "lookahead", 1.0)
+//!     .with_latency_compensation()
+//!     .render();
+//! assert_eq!(render.outputs[0][0], 1.0);
+//! ```
+//!
+//! Blocks are split at every event, so parameter changes and MIDI land on
+//! the exact sample. Processing runs in [`ProcessMode::Offline`] with a
+//! playing transport starting at sample 0. Like the script runner and the
+//! stress test, the renderer calls the plugin directly instead of going
+//! through a format wrapper.
+
+use std::fs;
+use std::path::Path;
+
+use crate::buffer::{AuxiliaryBuffers, Buffer};
+use crate::control_rate::ControlRate;
+use crate::frame_meters::FrameMeters;
+use crate::host_services::HostServices;
+use crate::midi::{MidiBuffer, MidiEvent};
+use crate::parameter_store::ParameterStore;
+use crate::plugin::{BusLayout, Descriptor, HasParameters, HostSetup, PluginSetup, ProcessMode, Processor};
+use crate::process_context::{ProcessContext, Transport};
+use crate::response_curves::CurveShape;
+use crate::split_block::SplitBlockProcessor;
+
+/// Environment variable that makes [`Render::assert_golden()`] write the
+/// golden file instead of comparing against it.
+pub const BLESS_ENV: &str = "BEAMER_BLESS";
+
+// =============================================================================
+// Signals
+// =============================================================================
+
+/// A generated input signal, the same on every channel and every run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum TestSignal {
+    /// All zeros.
+    #[default]
+    Silence,
+    /// A sine wave with a linear amplitude.
+    Sine { frequency: f64, gain: f64 },
+    /// Deterministic white noise with a linear amplitude.
+    Noise { gain: f64 },
+    /// A single full-scale sample at time 0.
+    Impulse,
+}
+
+impl TestSignal {
+    /// Generate `channels` channels of `len` samples.
+    pub fn generate(&self, channels: usize, len: usize, sample_rate: f64) -> Vec<Vec<f32>> {
+        let mut signal = vec![vec![0.0f32; len]; channels];
+        match *self {
+            Self::Silence => {}
+            Self::Sine { frequency, gain } => {
+                let step = std::f64::consts::TAU * frequency / sample_rate;
+                for channel in &mut signal {
+                    for (i, sample) in channel.iter_mut().enumerate() {
+                        *sample = (gain * (step * i as f64).sin()) as f32;
+                    }
+                }
+            }
+            Self::Noise { gain } => {
+                let mut state = 0x5eed_u64;
+                for channel in &mut signal {
+                    for sample in channel.iter_mut() {
+                        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                        let value = (state >> 11) as f64 / (1u64 << 53) as f64;
+                        *sample = (gain * (value * 2.0 - 1.0)) as f32;
+                    }
+                }
+            }
+            Self::Impulse => {
+                for channel in &mut signal {
+                    if let Some(first) = channel.first_mut() {
+                        *first = 1.0;
+                    }
+                }
+            }
+        }
+        signal
+    }
+}
+
+// =============================================================================
+// Automation
+// =============================================================================
+
+/// Default spacing of the automation points delivered inside a block.
+pub const DEFAULT_AUTOMATION_INTERVAL: usize = 16;
+
+/// A breakpoint of an [`Automation`] lane.
+#[derive(Clone, Debug, PartialEq)]
+struct Breakpoint {
+    at: usize,
+    value: f64,
+    /// How the value gets here from the previous breakpoint; `None` holds
+    /// the previous value and jumps.
+    curve: Option<CurveShape>,
+}
+
+/// A parameter automation lane: normalized breakpoints on the sample
+/// timeline, joined by curves.
+///
+/// The value holds the first breakpoint before it and the last one after
+/// it. Breakpoints may be added in any order; one at the same position as
+/// an earlier one goes after it, so a jump is two breakpoints at one
+/// position.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Automation {
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Automation {
+    /// An empty lane (it leaves the parameter alone).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold the previous value, then jump to `normalized` at sample `at`.
+    pub fn jump_to(self, at: usize, normalized: f64) -> Self {
+        self.with_breakpoint(at, normalized, None)
+    }
+
+    /// Move in a straight line from the previous breakpoint to
+    /// `normalized` at sample `at`.
+    pub fn ramp_to(self, at: usize, normalized: f64) -> Self {
+        self.curve_to(at, normalized, CurveShape::Linear)
+    }
+
+    /// Move from the previous breakpoint to `normalized` at sample `at`
+    /// along `curve`, which maps the segment's progress (0.0-1.0) to the
+    /// fraction of the distance covered.
+    pub fn curve_to(self, at: usize, normalized: f64, curve: CurveShape) -> Self {
+        self.with_breakpoint(at, normalized, Some(curve))
+    }
+
+    fn with_breakpoint(mut self, at: usize, normalized: f64, curve: Option<CurveShape>) -> Self {
+        let index = self.breakpoints.partition_point(|point| point.at <= at);
+        let value = normalized.clamp(0.0, 1.0);
+        self.breakpoints.insert(index, Breakpoint { at, value, curve });
+        self
+    }
+
+    /// Whether the lane has no breakpoints.
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// The normalized value at sample `position`, `None` for an empty lane.
+    pub fn value_at(&self, position: usize) -> Option<f64> {
+        let next = self.breakpoints.partition_point(|point| point.at <= position);
+        let Some(previous) = next.checked_sub(1).map(|i| &self.breakpoints[i]) else {
+            return self.breakpoints.first().map(|point| point.value);
+        };
+        let Some(next) = self.breakpoints.get(next) else {
+            return Some(previous.value);
+        };
+        Some(match &next.curve {
+            None => previous.value,
+            Some(curve) => {
+                let progress = (position - previous.at) as f64 / (next.at - previous.at) as f64;
+                previous.value + (next.value - previous.value) * curve.evaluate(progress)
+            }
+        })
+    }
+
+    /// Deliver the block `[start, end)` like a host: a point at the block
+    /// start and at every multiple of `interval` where the value changed
+    /// from `current`. Returns the block's last value.
+    fn deliver(&self, current: f64, start: usize, end: usize, interval: usize, mut point: impl FnMut(u32, f64)) -> f64 {
+        let mut last = current;
+        let mut at = start;
+        while at < end {
+            if let Some(value) = self.value_at(at).filter(|value| *value != last) {
+                point((at - start) as u32, value);
+                last = value;
+            }
+            at = (at / interval + 1) * interval;
+        }
+        last
+    }
+}
+
+/// Main input of a render.
+enum Input {
+    Signal(TestSignal),
+    /// Recorded audio; fewer channels than inputs are repeated.
+    Audio(Vec<Vec<f32>>),
+}
+
+/// What happens at a sample position.
+enum Action {
+    Parameter(u32, f64),
+    Midi(MidiEvent),
+}
+
+// =============================================================================
+// Renderer
+// =============================================================================
+
+/// Renders a plugin offline. See the module docs.
+pub struct OfflineRenderer<D: Descriptor> {
+    processor: D::Processor,
+    layout: BusLayout,
+    meters: FrameMeters,
+    host_services: HostServices,
+    sample_rate: f64,
+    block_size: usize,
+    block_sizes: Vec<usize>,
+    tempo: f64,
+    len: Option<usize>,
+    input: Input,
+    actions: Vec<(usize, Action)>,
+    automation: Vec<(u32, Automation)>,
+    automation_interval: usize,
+    meter_interval: Option<usize>,
+    compensate_latency: bool,
+}
+
+impl<D: Descriptor> OfflineRenderer<D> {
+    /// Prepare the default descriptor for `sample_rate` and blocks of up to
+    /// `block_size` samples.
+    pub fn new(sample_rate: f64, block_size: usize) -> Self {
+        Self::from_descriptor(D::default(), sample_rate, block_size)
+    }
+
+    /// Prepare a configured descriptor.
+    pub fn from_descriptor(descriptor: D, sample_rate: f64, block_size: usize) -> Self {
+        let block_size = block_size.max(1);
+        let meters = descriptor.frame_meters();
+        let host_services = descriptor.host_services();
+        let layout = BusLayout::from_plugin(&descriptor);
+        let host = HostSetup::new(sample_rate, block_size, layout.clone(), ProcessMode::Offline);
+        let mut processor = descriptor.prepare(D::Setup::extract(&host));
+        crate::parameter_types::Parameters::set_sample_rate(processor.parameters_mut(), sample_rate);
+        processor.recompute_rate_dependent(sample_rate);
+        Self {
+            processor,
+            layout,
+            meters,
+            host_services,
+            sample_rate,
+            block_size,
+            block_sizes: Vec::new(),
+            tempo: 120.0,
+            len: None,
+            input: Input::Signal(TestSignal::Silence),
+            actions: Vec::new(),
+            automation: Vec::new(),
+            automation_interval: DEFAULT_AUTOMATION_INTERVAL,
+            meter_interval: None,
+            compensate_latency: false,
+        }
+    }
+
+    /// The sample rate in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// The prepared processor, e.g. to load state before rendering.
+    pub fn processor_mut(&mut self) -> &mut D::Processor {
+        &mut self.processor
+    }
+
+    /// Set the transport tempo (default 120 BPM).
+    pub fn with_tempo(mut self, bpm: f64) -> Self {
+        self.tempo = bpm;
+        self
+    }
+
+    /// Cycle through `sizes` for the block lengths instead of always using
+    /// the prepared block size, like hosts that slice their buffers. Sizes
+    /// are clamped to 1..=block size.
+    pub fn with_block_sizes(mut self, sizes: Vec<usize>) -> Self {
+        self.block_sizes = sizes;
+        self
+    }
+
+    /// Render `len` samples. Defaults to the input audio's length, or one
+    /// second for generated signals.
+    pub fn with_length(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    /// Render `seconds` of audio.
+    pub fn with_seconds(self, seconds: f64) -> Self {
+        let len = (seconds * self.sample_rate).round().max(0.0) as usize;
+        self.with_length(len)
+    }
+
+    /// Feed a generated signal to the main inputs.
+    pub fn with_signal(mut self, signal: TestSignal) -> Self {
+        self.input = Input::Signal(signal);
+        self
+    }
+
+    /// Feed audio to the main inputs, one `Vec` per channel. Mono audio is
+    /// fed to every input channel; shorter audio is followed by silence.
+    pub fn with_input(mut self, channels: Vec<Vec<f32>>) -> Self {
+        self.input = Input::Audio(channels);
+        self
+    }
+
+    /// Feed a WAV file to the main inputs. Fails if the file can't be read
+    /// or has a different sample rate.
+    pub fn with_input_wav(self, path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let (sample_rate, channels) = read_wav(path)?;
+        if f64::from(sample_rate) != self.sample_rate {
+            return Err(format!(
+                "{} is {} Hz but the render runs at {} Hz",
+                path.display(),
+                sample_rate,
+                self.sample_rate
+            ));
+        }
+        Ok(self.with_input(channels))
+    }
+
+    /// Look up a parameter's numeric ID by its string ID.
+    pub fn parameter_id(&self, string_id: &str) -> Option<u32> {
+        let parameters = self.processor.parameters();
+        (0..parameters.count())
+            .filter_map(|i| parameters.info(i))
+            .find(|info| info.string_id == string_id)
+            .map(|info| info.id)
+    }
+
+    /// Set a parameter to a normalized value (0.0-1.0) at sample `at`.
+    ///
+    /// # Panics
+    ///
+    /// If the plugin has no parameter with this string ID.
+    pub fn with_parameter(mut self, at: usize, string_id: &str, normalized: f64) -> Self {
+        let id = self.expect_parameter(string_id);
+        self.actions.push((at, Action::Parameter(id, normalized.clamp(0.0, 1.0))));
+        self
+    }
+
+    /// Set a parameter to a value in its own units at sample `at`.
+    ///
+    /// # Panics
+    ///
+    /// If the plugin has no parameter with this string ID.
+    pub fn with_plain_parameter(mut self, at: usize, string_id: &str, plain: f64) -> Self {
+        let id = self.expect_parameter(string_id);
+        let normalized = self.processor.parameters().plain_to_normalized(id, plain);
+        self.actions.push((at, Action::Parameter(id, normalized)));
+        self
+    }
+
+    /// Send a MIDI event at sample `at` (its `sample_offset` is ignored).
+    pub fn with_midi(mut self, at: usize, event: MidiEvent) -> Self {
+        self.actions.push((at, Action::Midi(event)));
+        self
+    }
+
+    /// Play a note at sample `at` for `len` samples.
+    pub fn with_note(self, at: usize, channel: u8, pitch: u8, velocity: f32, len: usize) -> Self {
+        self.with_midi(at, MidiEvent::note_on(0, channel, pitch, velocity, -1, 0.0, 0))
+            .with_midi(at + len, MidiEvent::note_off(0, channel, pitch, 0.0, -1, 0.0))
+    }
+
+    /// Automate a parameter along a lane of normalized breakpoints.
+    ///
+    /// Each block, the value at its last delivered point is set before
+    /// `process()`, and processors that split blocks see the points at
+    /// their offsets. A later lane for the same parameter replaces the
+    /// earlier one; lanes override [`with_parameter()`](Self::with_parameter)
+    /// changes while they have breakpoints.
+    ///
+    /// # Panics
+    ///
+    /// If the plugin has no parameter with this string ID.
+    pub fn with_automation(mut self, string_id: &str, automation: Automation) -> Self {
+        let id = self.expect_parameter(string_id);
+        self.automation.retain(|(lane, _)| *lane != id);
+        if !automation.is_empty() {
+            self.automation.push((id, automation));
+        }
+        self
+    }
+
+    /// Deliver automation points every `interval` samples (default
+    /// [`DEFAULT_AUTOMATION_INTERVAL`]); 1 delivers every sample. Keep the
+    /// points per block within [`MAX_SPLIT_POINTS`](crate::split_block::MAX_SPLIT_POINTS).
+    pub fn with_automation_interval(mut self, interval: usize) -> Self {
+        self.automation_interval = interval.max(1);
+        self
+    }
+
+    /// Capture the descriptor's [`FrameMeters`] every `interval` samples.
+    pub fn with_meters(mut self, interval: usize) -> Self {
+        self.meter_interval = Some(interval.max(1));
+        self
+    }
+
+    /// Shift the output earlier by the plugin's latency, like a host's
+    /// delay compensation, so it lines up with the input.
+    ///
+    /// The latency is read when rendering starts and again after every
+    /// block in which the plugin called [`HostServices::latency_changed()`];
+    /// that block and the following ones are shifted by the new value. The
+    /// plugin is run past the end (on silent input) for as long as its
+    /// latency, so the output keeps the render length.
+    pub fn with_latency_compensation(mut self) -> Self {
+        self.compensate_latency = true;
+        self
+    }
+
+    /// Run the plugin and return the main outputs.
+    pub fn render(mut self) -> Render {
+        let sample_rate = self.sample_rate;
+        let block_size = self.block_size;
+        let input_channels = self.layout.main_input_channels as usize;
+        let total = match (self.len, &self.input) {
+            (Some(len), _) => len,
+            (None, Input::Audio(audio)) => audio.first().map_or(0, Vec::len),
+            (None, Input::Signal(_)) => sample_rate.round() as usize,
+        };
+        let mut inputs = match &self.input {
+            Input::Signal(signal) => signal.generate(input_channels, total, sample_rate),
+            Input::Audio(audio) => {
+                let mut inputs = vec![vec![0.0f32; total]; input_channels];
+                if !audio.is_empty() {
+                    for (i, channel) in inputs.iter_mut().enumerate() {
+                        let source = &audio[i % audio.len()];
+                        let len = source.len().min(total);
+                        channel[..len].copy_from_slice(&source[..len]);
+                    }
+                }
+                inputs
+            }
+        };
+        let mut actions = std::mem::take(&mut self.actions);
+        actions.sort_by_key(|(position, _)| *position);
+
+        // Storage
+        let processor = &mut self.processor;
+        let layout = &self.layout;
+        let meters = &self.meters;
+        let mut rendered = vec![vec![0.0f32; total]; layout.main_output_channels as usize];
+        let bus = |channels: &u32| vec![vec![0.0f32; block_size]; *channels as usize];
+        let aux_inputs: Vec<_> = layout.aux_input_channel_counts.iter().map(bus).collect();
+        let mut aux_outputs: Vec<_> = layout.aux_output_channel_counts.iter().map(bus).collect();
+        let mut midi_input = MidiBuffer::new_boxed();
+        let mut midi_output = MidiBuffer::new_boxed();
+        let mut control = ControlRate::new();
+        let meter_interval = self.meter_interval.filter(|_| !meters.is_empty()).unwrap_or(usize::MAX);
+        let mut meter_rows = Vec::new();
+        let mut next_meter = meter_interval;
+        let mut block_sizes = self.block_sizes.iter().map(|&size| size.clamp(1, block_size)).cycle();
+
+        processor.set_active(true);
+        let compensate_latency = self.compensate_latency;
+        let mut latency = if compensate_latency { processor.latency_samples() as usize } else { 0 };
+        // Rendered blocks as (start, end, latency)
+        let mut blocks = Vec::new();
+        let mut position = 0;
+        let mut next_action = 0;
+        while position < total + latency {
+            midi_input.clear();
+            midi_output.clear();
+            while let Some((_, action)) = actions.get(next_action).filter(|(at, _)| *at <= position) {
+                match action {
+                    Action::Parameter(id, value) => processor.parameters().set_normalized(*id, *value),
+                    Action::Midi(event) => {
+                        let mut event = event.clone();
+                        event.sample_offset = 0;
+                        midi_input.push(event);
+                    }
+                }
+                next_action += 1;
+            }
+
+            let limit = block_sizes.next().unwrap_or(block_size);
+            let mut end = (position + limit).min(total + latency).min(next_meter);
+            if let Some((at, _)) = actions.get(next_action) {
+                end = end.min(*at);
+            }
+            let len = end - position;
+            // Past the end, the plugin runs on silence until its latency is flushed
+            if end > total {
+                for channel in inputs.iter_mut().chain(rendered.iter_mut()) {
+                    channel.resize(channel.len().max(end), 0.0);
+                }
+            }
+
+            let transport = Transport {
+                tempo: Some(self.tempo),
+                time_sig_numerator: Some(4),
+                time_sig_denominator: Some(4),
+                project_time_samples: Some(position as i64),
+                project_time_beats: Some(position as f64 / sample_rate * self.tempo / 60.0),
+                continuous_time_samples: Some(position as i64),
+                is_playing: true,
+                ..Default::default()
+            };
+            let context = ProcessContext::new(sample_rate, len, transport);
+            deliver_automation(
+                processor.parameters(),
+                &self.automation,
+                position,
+                end,
+                self.automation_interval,
+                control.split_blocks_for(processor),
+            );
+            processor.begin_block(len);
+            processor.process_midi(midi_input.as_slice(), &mut midi_output);
+            if let Some(split) = control.split_blocks_for(processor) {
+                split.set_midi(midi_input.as_slice());
+            }
+            let mut buffer = Buffer::new(
+                inputs.iter().map(|c| &c[position..end]),
+                rendered.iter_mut().map(|c| &mut c[position..end]),
+                len,
+            );
+            let mut aux = AuxiliaryBuffers::new(
+                aux_inputs.iter().map(|bus| bus.iter().map(|c| &c[..len])),
+                aux_outputs.iter_mut().map(|bus| bus.iter_mut().map(|c| &mut c[..len])),
+                len,
+            );
+            control.run(processor, &mut buffer, &mut aux, &context, |p, b, a, c| p.process(b, a, c));
+
+            if compensate_latency && self.host_services.take_latency_changed() {
+                latency = processor.latency_samples() as usize;
+            }
+            blocks.push((position, end, latency));
+            position = end;
+            if position == next_meter {
+                meter_rows.push((position, (0..meters.len()).map(|i| meters.take(i)).collect()));
+                next_meter += meter_interval;
+            }
+        }
+        processor.set_active(false);
+
+        Render {
+            sample_rate,
+            outputs: compensate(&rendered, &blocks, total),
+            meter_names: (0..meters.len()).map(|i| meters.name(i).unwrap_or_default()).collect(),
+            meter_rows,
+        }
+    }
+
+    fn expect_parameter(&self, string_id: &str) -> u32 {
+        self.parameter_id(string_id)
+            .unwrap_or_else(|| panic!("unknown parameter '{}'", string_id))
+    }
+}
+
+/// Copy each rendered block `latency` samples earlier into outputs of
+/// `len` samples. Samples shifted before the start or past the end are
+/// dropped; a later block overwrites an earlier one after a latency change.
+fn compensate(rendered: &[Vec<f32>], blocks: &[(usize, usize, usize)], len: usize) -> Vec<Vec<f32>> {
+    let mut outputs = vec![vec![0.0f32; len]; rendered.len()];
+    for &(start, end, latency) in blocks {
+        let from = start.max(latency);
+        let to = end.min(len + latency);
+        if from >= to {
+            continue;
+        }
+        for (output, channel) in outputs.iter_mut().zip(rendered) {
+            output[from - latency..to - latency].copy_from_slice(&channel[from..to]);
+        }
+    }
+    outputs
+}
+
+/// Set each lane's value for the block `[start, end)` and record the
+/// points inside it for splitting, as the VST3 wrapper does with a host's
+/// parameter queues.
+fn deliver_automation(
+    parameters: &impl ParameterStore,
+    automation: &[(u32, Automation)],
+    start: usize,
+    end: usize,
+    interval: usize,
+    mut split: Option<&mut SplitBlockProcessor>,
+) {
+    for (id, lane) in automation {
+        let previous = parameters.get_normalized(*id);
+        let last = lane.deliver(previous, start, end, interval, |offset, value| {
+            if let Some(split) = split.as_deref_mut() {
+                split.record_parameter(*id, previous, offset, value);
+            }
+        });
+        if last != previous {
+            parameters.set_normalized(*id, last);
+        }
+    }
+}
+
+// =============================================================================
+// Render
+// =============================================================================
+
+/// Output of [`OfflineRenderer::render()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Render {
+    /// Sample rate in Hz.
+    pub sample_rate: f64,
+    /// Main output channels.
+    pub outputs: Vec<Vec<f32>>,
+    /// Names of the frame meters, in column order.
+    pub meter_names: Vec<&'static str>,
+    /// Frame meter values as (sample position, one value per meter).
+    pub meter_rows: Vec<(usize, Vec<f32>)>,
+}
+
+impl Render {
+    /// Samples per channel.
+    pub fn len(&self) -> usize {
+        self.outputs.first().map_or(0, Vec::len)
+    }
+
+    /// Whether nothing was rendered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Peak level per channel, ignoring non-finite samples.
+    pub fn peak(&self) -> Vec<f32> {
+        self.outputs
+            .iter()
+            .map(|c| c.iter().filter(|s| s.is_finite()).fold(0.0f32, |peak, s| peak.max(s.abs())))
+            .collect()
+    }
+
+    /// RMS level per channel, ignoring non-finite samples.
+    pub fn rms(&self) -> Vec<f32> {
+        let len = self.len().max(1) as f64;
+        self.outputs
+            .iter()
+            .map(|c| {
+                let sum: f64 = c.iter().filter(|s| s.is_finite()).map(|&s| f64::from(s) * f64::from(s)).sum();
+                (sum / len).sqrt() as f32
+            })
+            .collect()
+    }
+
+    /// First NaN or infinite sample, as (channel, sample).
+    pub fn first_non_finite(&self) -> Option<(usize, usize)> {
+        self.outputs
+            .iter()
+            .enumerate()
+            .find_map(|(channel, c)| c.iter().position(|s| !s.is_finite()).map(|i| (channel, i)))
+    }
+
+    /// Largest sample difference from `reference`. Fails if the channel
+    /// counts or lengths differ.
+    pub fn max_difference(&self, reference: &[Vec<f32>]) -> Result<f64, String> {
+        let reference_len = reference.first().map_or(0, Vec::len);
+        if reference.len() != self.outputs.len() || reference_len != self.len() {
+            return Err(format!(
+                "the reference has {} channels of {} samples, the output has {} channels of {}",
+                reference.len(),
+                reference_len,
+                self.outputs.len(),
+                self.len()
+            ));
+        }
+        Ok(self
+            .outputs
+            .iter()
+            .zip(reference)
+            .flat_map(|(a, b)| a.iter().zip(b))
+            .map(|(a, b)| f64::from((a - b).abs()))
+            .fold(0.0, f64::max))
+    }
+
+    /// Write the outputs as a 32-bit float WAV file, creating its directory.
+    pub fn write_wav(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        write_wav(path.as_ref(), self.sample_rate as u32, &self.outputs)
+    }
+
+    /// Compare against a golden WAV file, returning the largest difference.
+    ///
+    /// With `BEAMER_BLESS` set, the file is written instead and the result
+    /// is 0.
+    pub fn compare_golden(&self, path: impl AsRef<Path>) -> Result<f64, String> {
+        let path = path.as_ref();
+        if std::env::var_os(BLESS_ENV).is_some() {
+            self.write_wav(path)?;
+            return Ok(0.0);
+        }
+        if !path.exists() {
+            return Err(format!("{} does not exist (run with {}=1 to create it)", path.display(), BLESS_ENV));
+        }
+        let (_, reference) = read_wav(path)?;
+        self.max_difference(&reference).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Assert that the output matches a golden WAV file within `tolerance`.
+    ///
+    /// # Panics
+    ///
+    /// If the file is missing, differs in shape, or any sample differs by
+    /// more than `tolerance`.
+    pub fn assert_golden(&self, path: impl AsRef<Path>, tolerance: f64) {
+        let path = path.as_ref();
+        match self.compare_golden(path) {
+            Ok(error) if error <= tolerance => {}
+            Ok(error) => panic!(
+                "{}: max difference {:e} exceeds tolerance {:e} (run with {}=1 to update)",
+                path.display(),
+                error,
+                tolerance,
+                BLESS_ENV
+            ),
+            Err(error) => panic!("{}", error),
+        }
+    }
+}
+
+// =============================================================================
+// Null Tests
+// =============================================================================
+
+/// A sample that differs by more than the tolerance in a null test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NullFailure {
+    /// Output channel.
+    pub channel: usize,
+    /// Sample position.
+    pub sample: usize,
+    /// The reference render's sample.
+    pub reference: f32,
+    /// The processed render's sample.
+    pub processed: f32,
+}
+
+/// Result of [`Render::null_test()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NullReport {
+    /// Largest allowed difference per sample.
+    pub tolerance: f64,
+    /// Largest difference found (infinite if only one side is finite).
+    pub max_difference: f64,
+    /// Number of samples differing by more than the tolerance.
+    pub failures: usize,
+    /// The earliest failing sample (lowest position, then channel).
+    pub first_failure: Option<NullFailure>,
+}
+
+impl NullReport {
+    /// Whether every sample is within the tolerance.
+    pub fn passed(&self) -> bool {
+        self.failures == 0
+    }
+}
+
+impl std::fmt::Display for NullReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.first_failure {
+            None => write!(f, "nulls (max difference {:e}, tolerance {:e})", self.max_difference, self.tolerance),
+            Some(first) => write!(
+                f,
+                "{} samples exceed tolerance {:e} (max difference {:e}); first at channel {} sample {}: reference {}, processed {}",
+                self.failures,
+                self.tolerance,
+                self.max_difference,
+                first.channel,
+                first.sample,
+                first.reference,
+                first.processed
+            ),
+        }
+    }
+}
+
+/// Difference of two samples. Matching non-finite values (NaN and NaN, or
+/// infinities of the same sign) count as equal.
+fn sample_difference(reference: f32, processed: f32) -> f64 {
+    if reference == processed || (reference.is_nan() && processed.is_nan()) {
+        0.0
+    } else if reference.is_finite() && processed.is_finite() {
+        (f64::from(reference) - f64::from(processed)).abs()
+    } else {
+        f64::INFINITY
+    }
+}
+
+impl Render {
+    /// Compare against `reference` sample by sample.
+    ///
+    /// A tolerance of 0 checks for bit-exact output (`0.0` and `-0.0` count
+    /// as equal). Fails if the channel counts or lengths differ.
+    pub fn null_test(&self, reference: &Render, tolerance: f64) -> Result<NullReport, String> {
+        if reference.outputs.len() != self.outputs.len() || reference.len() != self.len() {
+            return Err(format!(
+                "the reference has {} channels of {} samples, the output has {} channels of {}",
+                reference.outputs.len(),
+                reference.len(),
+                self.outputs.len(),
+                self.len()
+            ));
+        }
+        let mut report = NullReport { tolerance, max_difference: 0.0, failures: 0, first_failure: None };
+        for (channel, (expected, actual)) in reference.outputs.iter().zip(&self.outputs).enumerate() {
+            for (sample, (&reference, &processed)) in expected.iter().zip(actual).enumerate() {
+                let difference = sample_difference(reference, processed);
+                report.max_difference = report.max_difference.max(difference);
+                if difference <= tolerance {
+                    continue;
+                }
+                report.failures += 1;
+                if report.first_failure.is_none_or(|first| sample < first.sample) {
+                    report.first_failure = Some(NullFailure { channel, sample, reference, processed });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Assert that the output nulls against `reference` within `tolerance`.
+    ///
+    /// # Panics
+    ///
+    /// If the renders differ in shape or any sample differs by more than
+    /// `tolerance`.
+    pub fn assert_nulls(&self, reference: &Render, tolerance: f64) {
+        match self.null_test(reference, tolerance) {
+            Ok(report) if report.passed() => {}
+            Ok(report) => panic!("null test failed: {}", report),
+            Err(error) => panic!("null test failed: {}", error),
+        }
+    }
+}
+
+// =============================================================================
+// WAV Files
+// =============================================================================
+
+/// Read a PCM (16, 24 or 32 bit) or float (32 or 64 bit) WAV file as its
+/// sample rate and channels.
+pub fn read_wav(path: impl AsRef<Path>) -> Result<(u32, Vec<Vec<f32>>), String> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    decode_wav(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn decode_wav(bytes: &[u8]) -> Result<(u32, Vec<Vec<f32>>), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a WAV file".to_string());
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+    let mut format = None;
+    let mut data = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let size = u32_at(at + 4) as usize;
+        let body = at + 8;
+        let end = body.saturating_add(size).min(bytes.len());
+        match &bytes[at..at + 4] {
+            b"fmt " if end - body >= 16 => {
+                let mut tag = u16_at(body);
+                if tag == 0xFFFE && end - body >= 26 {
+                    // WAVE_FORMAT_EXTENSIBLE: the sub-format GUID starts with the tag
+                    tag = u16_at(body + 24);
+                }
+                format = Some((tag, u16_at(body + 2) as usize, u32_at(body + 4), u16_at(body + 14)));
+            }
+            b"data" => data = Some(&bytes[body..end]),
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        at = body.saturating_add(size + (size & 1));
+    }
+
+    let (tag, channel_count, sample_rate, bits) = format.ok_or("missing fmt chunk")?;
+    let data = data.ok_or("missing data chunk")?;
+    if channel_count == 0 {
+        return Err("no channels".to_string());
+    }
+    let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+        (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (1, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8388608.0,
+        (1, 32) => |b| (i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64 / 2147483648.0) as f32,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        (3, 64) => |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+        _ => return Err(format!("unsupported format {} with {} bits", tag, bits)),
+    };
+
+    let sample_size = bits as usize / 8;
+    let mut channels = vec![Vec::with_capacity(data.len() / sample_size / channel_count); channel_count];
+    for frame in data.chunks_exact(sample_size * channel_count) {
+        for (channel, sample) in channels.iter_mut().zip(frame.chunks_exact(sample_size)) {
+            channel.push(decode(sample));
+        }
+    }
+    Ok((sample_rate, channels))
+}
+
+/// Write a 32-bit float WAV file, creating its directory.
+pub fn write_wav(path: impl AsRef<Path>, sample_rate: u32, channels: &[Vec<f32>]) -> Result<(), String> {
+    write_file(path.as_ref(), &encode_wav(sample_rate, channels))
+}
+
+fn encode_wav(sample_rate: u32, channels: &[Vec<f32>]) -> Vec<u8> {
+    let channel_count = channels.len().max(1) as u16;
+    let frames = channels.first().map_or(0, Vec::len);
+    let data_size = (frames * channel_count as usize * 4) as u32;
+
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&3u16.to_le_bytes());
+    bytes.extend_from_slice(&channel_count.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * u32::from(channel_count) * 4).to_le_bytes());
+    bytes.extend_from_slice(&(channel_count * 4).to_le_bytes());
+    bytes.extend_from_slice(&32u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for i in 0..frames {
+        if channels.is_empty() {
+            bytes.extend_from_slice(&0.0f32.to_le_bytes());
+        }
+        for channel in channels {
+            bytes.extend_from_slice(&channel[i].to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// Write a file, creating its directory.
+pub(crate) fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::MidiEventKind;
+    use crate::parameter_store::NoParameters;
+
+    /// Halves the input and adds a click at every note-on.
+    #[derive(Default)]
+    struct Clicker {
+        parameters: NoParameters,
+    }
+
+    struct ClickerProcessor {
+        parameters: NoParameters,
+        notes: Vec<u32>,
+        split: bool,
+    }
+
+    impl HasParameters for Clicker {
+        type Parameters = NoParameters;
+        fn parameters(&self) -> &NoParameters {
+            &self.parameters
+        }
+        fn parameters_mut(&mut self) -> &mut NoParameters {
+            &mut self.parameters
+        }
+        fn set_parameters(&mut self, parameters: NoParameters) {
+            self.parameters = parameters;
+        }
+    }
+
+    impl HasParameters for ClickerProcessor {
+        type Parameters = NoParameters;
+        fn parameters(&self) -> &NoParameters {
+            &self.parameters
+        }
+        fn parameters_mut(&mut self) -> &mut NoParameters {
+            &mut self.parameters
+        }
+        fn set_parameters(&mut self, parameters: NoParameters) {
+            self.parameters = parameters;
+        }
+    }
+
+    impl Descriptor for Clicker {
+        type Setup = ();
+        type Processor = ClickerProcessor;
+        fn prepare(self, _: ()) -> ClickerProcessor {
+            ClickerProcessor { parameters: self.parameters, notes: Vec::new(), split: false }
+        }
+    }
+
+    impl Processor for ClickerProcessor {
+        type Descriptor = Clicker;
+        fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+            for (input, output) in buffer.zip_channels() {
+                for (out, sample) in output.iter_mut().zip(input) {
+                    *out = sample * 0.5;
+                }
+                for &offset in &self.notes {
+                    output[offset as usize] += 1.0;
+                }
+            }
+            self.notes.clear();
+        }
+        fn process_midi(&mut self, input: &[MidiEvent], _output: &mut MidiBuffer) {
+            for event in input {
+                if let MidiEventKind::NoteOn(_) = event.event {
+                    self.notes.push(event.sample_offset);
+                }
+            }
+        }
+        fn split_blocks(&self) -> bool {
+            self.split
+        }
+    }
+
+    /// Delays the input by `DELAY` samples while a note is held and
+    /// announces the latency change.
+    #[derive(Default)]
+    struct Lookahead {
+        parameters: NoParameters,
+        host: HostServices,
+    }
+
+    struct LookaheadProcessor {
+        parameters: NoParameters,
+        host: HostServices,
+        history: Vec<Vec<f32>>,
+        delayed: bool,
+        announce: bool,
+    }
+
+    const DELAY: usize = 32;
+
+    impl HasParameters for Lookahead {
+        type Parameters = NoParameters;
+        fn parameters(&self) -> &NoParameters {
+            &self.parameters
+        }
+        fn parameters_mut(&mut self) -> &mut NoParameters {
+            &mut self.parameters
+        }
+        fn set_parameters(&mut self, parameters: NoParameters) {
+            self.parameters = parameters;
+        }
+    }
+
+    impl HasParameters for LookaheadProcessor {
+        type Parameters = NoParameters;
+        fn parameters(&self) -> &NoParameters {
+            &self.parameters
+        }
+        fn parameters_mut(&mut self) -> &mut NoParameters {
+            &mut self.parameters
+        }
+        fn set_parameters(&mut self, parameters: NoParameters) {
+            self.parameters = parameters;
+        }
+    }
+
+    impl Descriptor for Lookahead {
+        type Setup = ();
+        type Processor = LookaheadProcessor;
+        fn prepare(self, _: ()) -> LookaheadProcessor {
+            LookaheadProcessor {
+                parameters: self.parameters,
+                host: self.host,
+                history: vec![vec![0.0; DELAY]; 2],
+                delayed: false,
+                announce: true,
+            }
+        }
+        fn host_services(&self) -> HostServices {
+            self.host.clone()
+        }
+    }
+
+    impl Processor for LookaheadProcessor {
+        type Descriptor = Lookahead;
+        fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+            let delayed = self.delayed;
+            for ((input, output), history) in buffer.zip_channels().zip(&mut self.history) {
+                for (out, &sample) in output.iter_mut().zip(input) {
+                    history.push(sample);
+                    *out = if delayed { history[history.len() - 1 - DELAY] } else { sample };
+                }
+                history.drain(..history.len() - DELAY);
+            }
+        }
+        fn process_midi(&mut self, input: &[MidiEvent], _output: &mut MidiBuffer) {
+            for event in input {
+                let delayed = match event.event {
+                    MidiEventKind::NoteOn(_) => true,
+                    MidiEventKind::NoteOff(_) => false,
+                    _ => continue,
+                };
+                if delayed != self.delayed {
+                    self.delayed = delayed;
+                    if self.announce {
+                        self.host.latency_changed();
+                    }
+                }
+            }
+        }
+        fn latency_samples(&self) -> u32 {
+            if self.delayed { DELAY as u32 } else { 0 }
+        }
+    }
+
+    #[test]
+    fn latency_compensation_follows_announced_changes() {
+        let input: Vec<f32> = (0..1000).map(|i| (i % 13) as f32 / 13.0).collect();
+        let script = || {
+            OfflineRenderer::<Lookahead>::new(1000.0, 64)
+                .with_input(vec![input.clone()])
+                .with_note(100, 0, 60, 1.0, 400)
+                .with_note(900, 0, 60, 1.0, 200)
+        };
+
+        // Without compensation the delayed stretches lag the input
+        let raw = script().render();
+        assert_eq!(raw.outputs[0][..100], input[..100]);
+        assert_eq!(raw.outputs[0][200..500], input[200 - DELAY..500 - DELAY]);
+
+        // Delay compensation lines the output up with the input, including
+        // the tail of the last delayed stretch, flushed past the end. When
+        // the latency drops, the samples still in the delay line are lost.
+        let compensated = script().with_latency_compensation().render();
+        let mut expected = input.clone();
+        expected[500 - DELAY..500].fill(0.0);
+        assert_eq!(compensated.len(), 1000);
+        for channel in &compensated.outputs {
+            assert_eq!(*channel, expected);
+        }
+        let sliced = script().with_latency_compensation().with_block_sizes(vec![7, 64, 1]).render();
+        sliced.assert_nulls(&compensated, 0.0);
+
+        // A change the plugin doesn't announce isn't compensated
+        let mut silent = script().with_latency_compensation();
+        silent.processor_mut().announce = false;
+        assert!(silent.render().null_test(&compensated, 0.0).is_ok_and(|report| !report.passed()));
+    }
+
+    #[test]
+    fn renders_input_and_events_sample_accurately() {
+        let render = OfflineRenderer::<Clicker>::new(1000.0, 64)
+            .with_input(vec![vec![1.0; 150]])
+            .with_note(100, 0, 60, 1.0, 10)
+            .with_note(7, 0, 62, 1.0, 10)
+            .render();
+        assert_eq!(render.outputs.len(), 2);
+        assert_eq!(render.len(), 150);
+        for channel in &render.outputs {
+            assert_eq!((channel[6], channel[7], channel[8]), (0.5, 1.5, 0.5));
+            assert_eq!((channel[100], channel[101]), (1.5, 0.5));
+        }
+        assert_eq!(render.peak(), [1.5, 1.5]);
+        assert_eq!(render.first_non_finite(), None);
+
+        let silent = OfflineRenderer::<Clicker>::new(1000.0, 64).render();
+        assert_eq!(silent.len(), 1000);
+        assert_eq!(silent.rms(), [0.0, 0.0]);
+        assert!(render.max_difference(&silent.outputs).is_err());
+        assert_eq!(render.max_difference(&render.outputs), Ok(0.0));
+    }
+
+    #[test]
+    fn only_splitting_processors_record_events() {
+        let mut renderer = OfflineRenderer::<Clicker>::new(1000.0, 64);
+        let mut control = ControlRate::new();
+        let note = MidiEvent::note_on(3, 0, 60, 1.0, -1, 0.0, 0);
+        assert!(control.split_blocks_for(renderer.processor_mut()).is_none());
+        assert!(control.split_blocks_mut().is_empty());
+
+        renderer.processor_mut().split = true;
+        let split = control.split_blocks_for(renderer.processor_mut()).expect("processor splits blocks");
+        split.set_midi(&[note]);
+        assert!(!split.is_empty());
+
+        // Splitting doesn't change what the processor hears
+        let plain = OfflineRenderer::<Clicker>::new(1000.0, 64).with_note(10, 0, 60, 1.0, 5).render();
+        let mut renderer = OfflineRenderer::<Clicker>::new(1000.0, 64).with_note(10, 0, 60, 1.0, 5);
+        renderer.processor_mut().split = true;
+        renderer.render().assert_nulls(&plain, 0.0);
+    }
+
+    #[test]
+    fn automation_follows_curves_and_delivers_changes() {
+        let lane = Automation::new()
+            .ramp_to(100, 1.0)
+            .jump_to(0, 0.2)
+            .curve_to(200, 0.0, CurveShape::Power { exponent: 2.0 })
+            .jump_to(300, 0.5);
+        assert_eq!(lane.value_at(0), Some(0.2));
+        assert!((lane.value_at(50).unwrap() - 0.6).abs() < 1e-12);
+        assert!((lane.value_at(150).unwrap() - 0.75).abs() < 1e-12);
+        assert_eq!(lane.value_at(250), Some(0.0));
+        assert_eq!((lane.value_at(300), lane.value_at(1000)), (Some(0.5), Some(0.5)));
+        assert_eq!(Automation::new().value_at(0), None);
+
+        // Points on the interval grid, only where the value moved
+        let mut points = Vec::new();
+        let last = lane.deliver(0.3, 290, 340, 16, |offset, value| points.push((offset, value)));
+        assert_eq!(points, [(0, 0.0), (14, 0.5)]);
+        assert_eq!(last, 0.5);
+        points.clear();
+        lane.deliver(0.5, 400, 500, 16, |offset, value| points.push((offset, value)));
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn null_test_is_independent_of_block_sizes() {
+        let script = |renderer: OfflineRenderer<Clicker>| {
+            renderer
+                .with_signal(TestSignal::Noise { gain: 0.5 })
+                .with_length(2000)
+                .with_note(3, 0, 60, 1.0, 10)
+                .with_note(700, 0, 62, 1.0, 10)
+                .with_note(1999, 0, 64, 1.0, 1)
+        };
+        let reference = script(OfflineRenderer::new(1000.0, 256)).render();
+        let processed = script(OfflineRenderer::new(1000.0, 256))
+            .with_block_sizes(vec![1, 17, 1000, 64, 3])
+            .render();
+        let report = processed.null_test(&reference, 0.0).unwrap();
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.max_difference, 0.0);
+        processed.assert_nulls(&reference, 0.0);
+    }
+
+    #[test]
+    fn null_test_reports_the_first_difference() {
+        let reference = OfflineRenderer::<Clicker>::new(1000.0, 64)
+            .with_input(vec![vec![0.5; 300]])
+            .with_note(200, 0, 60, 1.0, 10)
+            .render();
+        // The same note, one sample late
+        let processed = OfflineRenderer::<Clicker>::new(1000.0, 64)
+            .with_input(vec![vec![0.5; 300]])
+            .with_note(201, 0, 60, 1.0, 10)
+            .render();
+        let report = processed.null_test(&reference, 1e-6).unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.failures, 4);
+        assert_eq!(report.max_difference, 1.0);
+        assert_eq!(
+            report.first_failure,
+            Some(NullFailure { channel: 0, sample: 200, reference: 1.25, processed: 0.25 })
+        );
+        // Within a loose enough tolerance
+        assert!(processed.null_test(&reference, 1.0).unwrap().passed());
+
+        let mut broken = reference.clone();
+        broken.outputs[1][5] = f32::NAN;
+        let report = broken.null_test(&reference, 1.0).unwrap();
+        assert_eq!((report.failures, report.max_difference), (1, f64::INFINITY));
+        assert!(broken.null_test(&broken, 0.0).unwrap().passed());
+
+        let short = OfflineRenderer::<Clicker>::new(1000.0, 64).with_length(299).render();
+        assert!(short.null_test(&reference, 1.0).is_err());
+    }
+
+    #[test]
+    fn wav_round_trip() {
+        let channels = vec![vec![0.0, 0.5, -1.0], vec![0.25, -0.25, 1.0]];
+        let (sample_rate, decoded) = decode_wav(&encode_wav(44100, &channels)).unwrap();
+        assert_eq!(sample_rate, 44100);
+        assert_eq!(decoded, channels);
+
+        // 16-bit PCM, mono
+        let mut pcm = encode_wav(48000, &[vec![0.0; 2]]);
+        pcm[20..22].copy_from_slice(&1u16.to_le_bytes());
+        pcm[34..36].copy_from_slice(&16u16.to_le_bytes());
+        pcm[44..48].copy_from_slice(&[0x00, 0x40, 0x00, 0xC0]);
+        assert_eq!(decode_wav(&pcm).unwrap().1, vec![vec![0.5, -0.5, 0.0, 0.0]]);
+        assert!(decode_wav(b"RIFF\0\0\0\0WAVE").is_err());
+    }
+}
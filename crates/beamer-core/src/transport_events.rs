@@ -0,0 +1,371 @@
+//! Transport relocation handling for time-based DSP.
+//!
+//! Hosts jump the playback position whenever the user locates, scrubs, or
+//! cycle playback wraps. Delay lines, reverbs, and tempo-synced LFOs keep
+//! whatever they held before the jump, so a ping-pong delay keeps echoing
+//! the old bar and an LFO that was counting samples is now out of phase with
+//! the grid.
+//!
+//! Beamer gives plugins one framework-supported policy for this:
+//!
+//! 1. The wrapper runs a [`TransportTracker`] on every block's
+//!    [`Transport`] and detects [`TransportEvent`]s (start, stop, relocate,
+//!    loop wrap) before MIDI and audio processing.
+//! 2. Depending on [`Processor::relocation_policy`], it calls
+//!    [`Processor::reset`] to clear time-based state.
+//! 3. It then calls [`Processor::transport_event`] so the plugin can re-seed
+//!    phases from the new position, typically with a [`TimeAnchor`].
+//!
+//! | Policy | Locate/scrub | Loop wrap |
+//! |--------|--------------|-----------|
+//! | [`RelocationPolicy::Keep`] (default) | tails continue | tails continue |
+//! | [`RelocationPolicy::ResetOnLocate`] | `reset()` | tails continue |
+//! | [`RelocationPolicy::Reset`] | `reset()` | `reset()` |
+//!
+//! Detection needs `project_time_samples` from the host; without it only
+//! start/stop are reported. A loop wrap is a backwards jump to inside the
+//! cycle range while cycle playback is active.
+//!
+//! # Example: Tempo-Synced Delay
+//!
+//! ```ignore
+//! impl Processor for SyncedDelay {
+//!     fn relocation_policy(&self) -> RelocationPolicy {
+//!         RelocationPolicy::ResetOnLocate
+//!     }
+//!
+//!     fn reset(&mut self) {
+//!         self.delay_line.clear();
+//!     }
+//!
+//!     fn transport_event(&mut self, event: TransportEvent, transport: &Transport) {
+//!         if let TransportEvent::Relocated { .. } = event {
+//!             if let Some(anchor) = TimeAnchor::from_transport(transport, self.sample_rate) {
+//!                 // Restart the 1/8-note LFO in phase with the new position
+//!                 self.lfo_phase = anchor.phase_at(0.5, 0);
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::plugin::Processor;
+use crate::process_context::Transport;
+
+/// A change in host transport state, detected between two process blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportEvent {
+    /// Playback started.
+    Started,
+    /// Playback stopped.
+    Stopped,
+    /// The playback position jumped (locate, scrub, or a seek while
+    /// stopped). Positions are project time in samples.
+    Relocated {
+        /// Where the block would have started without the jump.
+        from_samples: i64,
+        /// Where the block actually starts.
+        to_samples: i64,
+    },
+    /// Cycle playback wrapped from the loop end back to the loop start.
+    LoopWrapped,
+}
+
+/// What the wrapper does to time-based DSP state on relocation.
+///
+/// Returned by [`Processor::relocation_policy`]. [`Processor::transport_event`]
+/// is called for every event regardless of policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RelocationPolicy {
+    /// Never reset. Tails ring on across jumps, as on a hardware unit.
+    #[default]
+    Keep,
+    /// Reset on locate/scrub, but keep tails continuous across loop wraps.
+    ResetOnLocate,
+    /// Reset on every position jump, including loop wraps.
+    Reset,
+}
+
+impl RelocationPolicy {
+    /// Returns true if this policy resets DSP state for `event`.
+    pub fn resets_on(&self, event: &TransportEvent) -> bool {
+        match (self, event) {
+            (Self::Keep, _) => false,
+            (Self::ResetOnLocate, TransportEvent::Relocated { .. }) => true,
+            (Self::Reset, TransportEvent::Relocated { .. } | TransportEvent::LoopWrapped) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Position differences up to this many samples are treated as continuous
+/// playback (rounding in hosts that derive samples from beats).
+const JUMP_TOLERANCE_SAMPLES: i64 = 1;
+
+/// Detects [`TransportEvent`]s by comparing consecutive blocks.
+///
+/// Owned by the format wrapper; plugins only see the resulting
+/// [`Processor`] calls. Audio-thread safe (no allocation).
+#[derive(Debug, Clone, Default)]
+pub struct TransportTracker {
+    previous: Option<Previous>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Previous {
+    is_playing: bool,
+    /// Position the next block should start at if nothing jumps.
+    expected_samples: Option<i64>,
+    beats: Option<f64>,
+}
+
+impl TransportTracker {
+    /// Create a tracker with no history.
+    pub const fn new() -> Self {
+        Self { previous: None }
+    }
+
+    /// Forget the previous block. The next block reports no events.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// Compare `transport` with the previous block and return the events
+    /// in the order they happened (stop, jump, start).
+    pub fn update(
+        &mut self,
+        transport: &Transport,
+        num_samples: usize,
+    ) -> impl Iterator<Item = TransportEvent> {
+        let mut events = [None; 3];
+        let position = transport.project_time_samples;
+
+        if let Some(previous) = self.previous {
+            if previous.is_playing && !transport.is_playing {
+                events[0] = Some(TransportEvent::Stopped);
+            }
+            if let (Some(expected), Some(actual)) = (previous.expected_samples, position) {
+                if (actual - expected).abs() > JUMP_TOLERANCE_SAMPLES {
+                    events[1] = Some(if Self::is_loop_wrap(&previous, transport, actual < expected) {
+                        TransportEvent::LoopWrapped
+                    } else {
+                        TransportEvent::Relocated {
+                            from_samples: expected,
+                            to_samples: actual,
+                        }
+                    });
+                }
+            }
+            if !previous.is_playing && transport.is_playing {
+                events[2] = Some(TransportEvent::Started);
+            }
+        }
+
+        let advance = if transport.is_playing { num_samples as i64 } else { 0 };
+        self.previous = Some(Previous {
+            is_playing: transport.is_playing,
+            expected_samples: position.map(|p| p + advance),
+            beats: transport.project_time_beats,
+        });
+
+        events.into_iter().flatten()
+    }
+
+    /// Detect events and apply them to `processor`: reset according to its
+    /// [`RelocationPolicy`], then call [`Processor::transport_event`].
+    ///
+    /// Called by the format wrappers before MIDI and audio processing.
+    pub fn dispatch<P: Processor + ?Sized>(
+        &mut self,
+        processor: &mut P,
+        transport: &Transport,
+        num_samples: usize,
+    ) {
+        let policy = processor.relocation_policy();
+        for event in self.update(transport, num_samples) {
+            if policy.resets_on(&event) {
+                processor.reset();
+            }
+            processor.transport_event(event, transport);
+        }
+    }
+
+    fn is_loop_wrap(previous: &Previous, transport: &Transport, backwards: bool) -> bool {
+        if !backwards || !previous.is_playing || !transport.is_playing || !transport.is_looping() {
+            return false;
+        }
+        match (previous.beats, transport.project_time_beats, transport.cycle_range()) {
+            (Some(before), Some(now), Some((start, end))) => now >= start && now < end && before > now,
+            _ => false,
+        }
+    }
+}
+
+/// Maps sample offsets in a block to musical time, anchored at the block's
+/// host position.
+///
+/// Use it to recompute tempo-synced phases (LFOs, gates, delay taps) from the
+/// project position instead of counting samples, so they stay on the grid
+/// across relocations and loop wraps.
+///
+/// # Example
+///
+/// ```ignore
+/// if let Some(anchor) = TimeAnchor::from_transport(&context.transport, context.sample_rate) {
+///     for i in 0..context.num_samples {
+///         // Quarter-note square gate, correct even right after a locate
+///         let gate = if anchor.phase_at(1.0, i) < 0.5 { 1.0 } else { 0.0 };
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeAnchor {
+    /// Project position in quarter notes at sample offset 0.
+    beats: f64,
+    /// Quarter notes advanced per sample.
+    beats_per_sample: f64,
+}
+
+impl TimeAnchor {
+    /// Create an anchor at `beats` (quarter notes) for the given tempo and
+    /// sample rate.
+    pub fn new(beats: f64, tempo: f64, sample_rate: f64) -> Self {
+        Self {
+            beats,
+            beats_per_sample: tempo / 60.0 / sample_rate,
+        }
+    }
+
+    /// Anchor at the start of the block described by `transport`.
+    ///
+    /// Returns `None` if the host provides no musical position or tempo.
+    pub fn from_transport(transport: &Transport, sample_rate: f64) -> Option<Self> {
+        let beats = transport.project_time_beats?;
+        let tempo = transport.tempo?;
+        (tempo > 0.0 && sample_rate > 0.0).then(|| Self::new(beats, tempo, sample_rate))
+    }
+
+    /// Project position in quarter notes at `sample_offset` into the block.
+    #[inline]
+    pub fn beats_at(&self, sample_offset: usize) -> f64 {
+        self.beats + sample_offset as f64 * self.beats_per_sample
+    }
+
+    /// Phase (0.0..1.0) within a cycle of `period_beats` quarter notes at
+    /// `sample_offset`. Cycles are aligned to project position 0.
+    #[inline]
+    pub fn phase_at(&self, period_beats: f64, sample_offset: usize) -> f64 {
+        if period_beats <= 0.0 {
+            return 0.0;
+        }
+        self.beats_at(sample_offset).rem_euclid(period_beats) / period_beats
+    }
+
+    /// Samples from `sample_offset` until the next cycle boundary of
+    /// `period_beats` quarter notes.
+    #[inline]
+    pub fn samples_to_next(&self, period_beats: f64, sample_offset: usize) -> f64 {
+        let remaining = (1.0 - self.phase_at(period_beats, sample_offset)) * period_beats;
+        self.beats_to_samples(remaining)
+    }
+
+    /// Convert a duration in quarter notes to samples at the anchor's tempo.
+    #[inline]
+    pub fn beats_to_samples(&self, beats: f64) -> f64 {
+        beats / self.beats_per_sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(samples: i64, beats: f64) -> Transport {
+        Transport {
+            is_playing: true,
+            project_time_samples: Some(samples),
+            project_time_beats: Some(beats),
+            ..Default::default()
+        }
+    }
+
+    fn events(tracker: &mut TransportTracker, transport: &Transport) -> Vec<TransportEvent> {
+        tracker.update(transport, 512).collect()
+    }
+
+    #[test]
+    fn test_continuous_playback_has_no_events() {
+        let mut tracker = TransportTracker::new();
+        assert!(events(&mut tracker, &playing(0, 0.0)).is_empty());
+        assert!(events(&mut tracker, &playing(512, 0.5)).is_empty());
+        assert!(events(&mut tracker, &playing(1024, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn test_start_stop_and_relocate() {
+        let mut tracker = TransportTracker::new();
+        let stopped = Transport {
+            project_time_samples: Some(0),
+            ..Default::default()
+        };
+        events(&mut tracker, &stopped);
+
+        // Seek while stopped, then start from the new position
+        let sought = Transport {
+            project_time_samples: Some(48_000),
+            ..Default::default()
+        };
+        assert_eq!(
+            events(&mut tracker, &sought),
+            [TransportEvent::Relocated { from_samples: 0, to_samples: 48_000 }]
+        );
+        assert_eq!(events(&mut tracker, &playing(48_000, 2.0)), [TransportEvent::Started]);
+
+        // Locate during playback, then stop with a return to zero
+        assert_eq!(
+            events(&mut tracker, &playing(96_000, 4.0)),
+            [TransportEvent::Relocated { from_samples: 48_512, to_samples: 96_000 }]
+        );
+        assert_eq!(
+            events(&mut tracker, &stopped),
+            [
+                TransportEvent::Stopped,
+                TransportEvent::Relocated { from_samples: 96_512, to_samples: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loop_wrap() {
+        let cycling = |samples, beats| Transport {
+            is_cycle_active: true,
+            cycle_start_beats: Some(4.0),
+            cycle_end_beats: Some(8.0),
+            ..playing(samples, beats)
+        };
+        let mut tracker = TransportTracker::new();
+        events(&mut tracker, &cycling(191_488, 7.98));
+        assert_eq!(events(&mut tracker, &cycling(96_000, 4.0)), [TransportEvent::LoopWrapped]);
+    }
+
+    #[test]
+    fn test_policy() {
+        let relocated = TransportEvent::Relocated { from_samples: 0, to_samples: 1 };
+        assert!(!RelocationPolicy::Keep.resets_on(&relocated));
+        assert!(RelocationPolicy::ResetOnLocate.resets_on(&relocated));
+        assert!(!RelocationPolicy::ResetOnLocate.resets_on(&TransportEvent::LoopWrapped));
+        assert!(RelocationPolicy::Reset.resets_on(&TransportEvent::LoopWrapped));
+        assert!(!RelocationPolicy::Reset.resets_on(&TransportEvent::Started));
+    }
+
+    #[test]
+    fn test_time_anchor() {
+        // 120 BPM at 48 kHz: one quarter note is 24000 samples
+        let anchor = TimeAnchor::new(3.5, 120.0, 48_000.0);
+        assert!((anchor.beats_at(12_000) - 4.0).abs() < 1e-9);
+        assert!((anchor.phase_at(1.0, 0) - 0.5).abs() < 1e-9);
+        assert!((anchor.phase_at(4.0, 0) - 0.875).abs() < 1e-9);
+        assert!((anchor.samples_to_next(1.0, 0) - 12_000.0).abs() < 1e-6);
+        assert_eq!(TimeAnchor::from_transport(&Transport::default(), 48_000.0), None);
+    }
+}
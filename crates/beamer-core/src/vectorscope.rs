@@ -0,0 +1,269 @@
+//! Stereo field data for vectorscope and Lissajous displays.
+//!
+//! A vectorscope needs far more data than a level meter: hundreds of sample
+//! pairs per frame instead of one number. [`Vectorscope`] takes every n-th
+//! left/right pair on the audio thread into a lock-free ring, and the frame
+//! clock (see [`FrameMeters`](crate::FrameMeters)) sends the pairs written
+//! since the previous frame to the WebView, thinned to at most `max_points`,
+//! together with the peak level and a held peak:
+//!
+//! ```ignore
+//! use beamer_core::{FrameMeters, Vectorscope};
+//!
+//! // In the descriptor (keep a clone for the processor):
+//! scope: Vectorscope::new(512).with_decimation(4),
+//! meters: FrameMeters::new().with_scope("scope", scope.clone()),
+//!
+//! // In process(), after processing:
+//! self.scope.push_outputs(buffer);
+//! ```
+//!
+//! ```html
+//! <beamer-vectorscope source="scope" mode="vectorscope" autoscale></beamer-vectorscope>
+//! ```
+//!
+//! Each frame's meters then hold `{"points":[l0,r0,l1,r1,...],"peak":..,"peakHold":..}`
+//! under the scope's name. Pushing is real-time safe (relaxed atomic stores);
+//! the ring holds four frames' worth of points, and older points are
+//! overwritten while no frames are read (editor closed).
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::buffer::Buffer;
+use crate::sample::Sample;
+
+/// Default number of frames a peak is held.
+pub const DEFAULT_PEAK_HOLD_FRAMES: u32 = 60;
+
+/// Ring capacity in frames of points.
+const RING_FRAMES: usize = 4;
+
+struct Inner {
+    /// Left and right f32 bits packed per point.
+    ring: Box<[AtomicU64]>,
+    /// Points written so far (audio thread).
+    written: AtomicUsize,
+    /// Points consumed by frames so far (main thread).
+    read: AtomicUsize,
+    /// Samples per point.
+    decimation: AtomicUsize,
+    /// Samples since the last point (audio thread).
+    phase: AtomicUsize,
+    /// Peak since the last frame, as f32 bits.
+    peak: AtomicU32,
+    /// Held peak, as f32 bits (main thread).
+    held: AtomicU32,
+    /// Frames left before the held peak drops (main thread).
+    hold_left: AtomicU32,
+    hold_frames: AtomicU32,
+    max_points: usize,
+}
+
+/// Decimated left/right sample pairs for a stereo field display.
+///
+/// Cloning is cheap (reference counted); the processor pushes into one clone
+/// and the descriptor's [`FrameMeters`](crate::FrameMeters) reads another.
+#[derive(Clone)]
+pub struct Vectorscope {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for Vectorscope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vectorscope")
+            .field("max_points", &self.inner.max_points)
+            .field("decimation", &self.decimation())
+            .finish()
+    }
+}
+
+impl Vectorscope {
+    /// Create a scope sending at most `max_points` pairs per frame.
+    pub fn new(max_points: usize) -> Self {
+        let max_points = max_points.max(1);
+        Self {
+            inner: Arc::new(Inner {
+                ring: (0..max_points * RING_FRAMES).map(|_| AtomicU64::new(0)).collect(),
+                written: AtomicUsize::new(0),
+                read: AtomicUsize::new(0),
+                decimation: AtomicUsize::new(1),
+                phase: AtomicUsize::new(0),
+                peak: AtomicU32::new(0),
+                held: AtomicU32::new(0),
+                hold_left: AtomicU32::new(0),
+                hold_frames: AtomicU32::new(DEFAULT_PEAK_HOLD_FRAMES),
+                max_points,
+            }),
+        }
+    }
+
+    /// Keep one pair every `samples` samples (default 1).
+    ///
+    /// At 48 kHz and 60 frames per second a block of 800 samples arrives
+    /// per frame; decimating by 2 to 4 keeps the trace dense without
+    /// thinning at higher sample rates.
+    pub fn with_decimation(self, samples: usize) -> Self {
+        self.set_decimation(samples);
+        self
+    }
+
+    /// Hold the peak for `frames` frames before it drops to the current
+    /// peak (default [`DEFAULT_PEAK_HOLD_FRAMES`], about a second).
+    pub fn with_peak_hold(self, frames: u32) -> Self {
+        self.inner.hold_frames.store(frames, Ordering::Relaxed);
+        self
+    }
+
+    /// Change the decimation, e.g. after the sample rate changed.
+    pub fn set_decimation(&self, samples: usize) {
+        self.inner.decimation.store(samples.max(1), Ordering::Relaxed);
+    }
+
+    /// Samples per pair.
+    pub fn decimation(&self) -> usize {
+        self.inner.decimation.load(Ordering::Relaxed)
+    }
+
+    /// Maximum pairs per frame.
+    pub fn max_points(&self) -> usize {
+        self.inner.max_points
+    }
+
+    /// Add a block of left/right samples. Real-time safe.
+    pub fn push<S: Sample>(&self, left: &[S], right: &[S]) {
+        let inner = &*self.inner;
+        let decimation = inner.decimation.load(Ordering::Relaxed);
+        let mut phase = inner.phase.load(Ordering::Relaxed);
+        let mut written = inner.written.load(Ordering::Relaxed);
+        let mut peak = 0.0f32;
+        for (&l, &r) in left.iter().zip(right) {
+            let (l, r) = (l.to_f32(), r.to_f32());
+            // f32::max ignores NaN
+            peak = peak.max(l.abs()).max(r.abs());
+            phase += 1;
+            if phase >= decimation {
+                phase = 0;
+                let packed = (u64::from(l.to_bits()) << 32) | u64::from(r.to_bits());
+                inner.ring[written % inner.ring.len()].store(packed, Ordering::Relaxed);
+                written = written.wrapping_add(1);
+            }
+        }
+        inner.phase.store(phase, Ordering::Relaxed);
+        inner.written.store(written, Ordering::Release);
+        // Non-negative floats order like their bit patterns.
+        inner.peak.fetch_max(peak.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Add the first two output channels of `buffer` (a mono output counts
+    /// as both). Real-time safe.
+    pub fn push_outputs<S: Sample>(&self, buffer: &mut Buffer<S>) {
+        let mut outputs = buffer.outputs_mut();
+        let Some(left) = outputs.next() else {
+            return;
+        };
+        match outputs.next() {
+            Some(right) => self.push(left, right),
+            None => self.push(left, left),
+        }
+    }
+
+    /// Read the pairs since the previous frame as a JSON object, resetting
+    /// the frame peak.
+    ///
+    /// Called by [`FrameMeters::frame_json()`](crate::FrameMeters::frame_json)
+    /// on the main thread. Non-finite samples are sent as `0`.
+    pub fn frame_json(&self) -> String {
+        let inner = &*self.inner;
+        let written = inner.written.load(Ordering::Acquire);
+        let read = inner.read.swap(written, Ordering::Relaxed);
+        let available = written.wrapping_sub(read).min(inner.ring.len());
+        // Thin evenly, so the points span the whole frame
+        let stride = available.div_ceil(inner.max_points).max(1);
+
+        let mut json = String::with_capacity(32 + available.min(inner.max_points) * 16);
+        json.push_str("{\"points\":[");
+        for (i, n) in (written.wrapping_sub(available)..)
+            .step_by(stride)
+            .take(available.div_ceil(stride))
+            .enumerate()
+        {
+            let packed = inner.ring[n % inner.ring.len()].load(Ordering::Relaxed);
+            let l = finite(f32::from_bits((packed >> 32) as u32));
+            let r = finite(f32::from_bits(packed as u32));
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(json, "{l:.4},{r:.4}");
+        }
+
+        let peak = finite(f32::from_bits(inner.peak.swap(0, Ordering::Relaxed)));
+        let held = f32::from_bits(inner.held.load(Ordering::Relaxed));
+        let hold_left = inner.hold_left.load(Ordering::Relaxed);
+        let held = if peak >= held || hold_left == 0 {
+            inner.hold_left.store(inner.hold_frames.load(Ordering::Relaxed), Ordering::Relaxed);
+            peak
+        } else {
+            inner.hold_left.store(hold_left - 1, Ordering::Relaxed);
+            held
+        };
+        inner.held.store(held.to_bits(), Ordering::Relaxed);
+        let _ = write!(json, "],\"peak\":{peak},\"peakHold\":{held}}}");
+        json
+    }
+}
+
+fn finite(value: f32) -> f32 {
+    if value.is_finite() {
+        value
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(json: &str) -> Vec<f64> {
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value["points"].as_array().unwrap().iter().map(|v| v.as_f64().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_decimates_and_thins_points() {
+        let scope = Vectorscope::new(4).with_decimation(2);
+        let left: Vec<f32> = (1..=8).map(|i| i as f32 / 10.0).collect();
+        let right: Vec<f32> = left.iter().map(|l| -l).collect();
+        scope.push(&left, &right);
+        // Every second pair: 0.2, 0.4, 0.6, 0.8
+        assert_eq!(points(&scope.frame_json()), [0.2, -0.2, 0.4, -0.4, 0.6, -0.6, 0.8, -0.8]);
+        assert!(points(&scope.frame_json()).is_empty());
+
+        // 12 points since the last frame, max 4: every third
+        let scope = Vectorscope::new(4);
+        let ramp: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        scope.push(&ramp, &ramp);
+        let thinned = points(&scope.frame_json());
+        assert_eq!(thinned.len(), 8);
+        assert_eq!(thinned[2], 3.0);
+    }
+
+    #[test]
+    fn test_peak_hold() {
+        let scope = Vectorscope::new(8).with_peak_hold(2);
+        scope.push(&[0.5f32, f32::NAN], &[-0.9f32, 0.1]);
+        let frame: serde_json::Value = serde_json::from_str(&scope.frame_json()).unwrap();
+        assert_eq!(frame["peak"].as_f64().unwrap() as f32, 0.9);
+
+        scope.push(&[0.2f32], &[0.1f32]);
+        for _ in 0..2 {
+            let frame: serde_json::Value = serde_json::from_str(&scope.frame_json()).unwrap();
+            assert_eq!(frame["peakHold"].as_f64().unwrap() as f32, 0.9);
+        }
+        let frame: serde_json::Value = serde_json::from_str(&scope.frame_json()).unwrap();
+        assert_eq!(frame["peak"], 0.0);
+        assert_eq!(frame["peakHold"], 0.0);
+    }
+}
@@ -0,0 +1,379 @@
+//! Deadline watchdog for `process()`.
+//!
+//! A block of 256 samples at 48 kHz has to be processed in 5.3 ms, and
+//! much less in practice since the host and other plugins share the audio
+//! thread. When a preset or sample rate is too heavy for the machine, the
+//! user hears dropouts without knowing which plugin causes them.
+//!
+//! The wrappers time every `process()` call with a [`ProcessWatchdog`].
+//! A call that takes longer than the block's duration (times
+//! [`WatchdogSettings::load`]) is an overrun. When
+//! [`WatchdogSettings::repeats`] overruns fall within one second of audio,
+//! the watchdog raises a report:
+//!
+//! - **Log** - a warning with the overrun count, worst time and block size,
+//!   written when the wrappers collect the report on the main thread.
+//! - **GUI event** - `"beamer:overrun"` with [`OverrunReport::to_json()`]
+//!   as payload, while an editor is open:
+//!
+//!   ```javascript
+//!   __BEAMER__.on("beamer:overrun", (r) =>
+//!     console.warn(`${r.overruns} overruns, worst ${r.worstMs} ms of ${r.deadlineMs} ms`));
+//!   ```
+//!
+//! - **Notification** (opt-in with [`WatchdogSettings::with_notification()`]) -
+//!   a warning in the GUI's notification area.
+//!
+//! Configure it with [`Config::with_watchdog()`](crate::Config::with_watchdog).
+//! The watchdog runs by default and costs two clock reads per block.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::notifications::{Notifications, NotifyLevel};
+
+/// Event emitted to the WebView when overruns repeat.
+pub const OVERRUN_EVENT: &str = "beamer:overrun";
+
+/// Notification posted with [`WatchdogSettings::with_notification()`].
+const OVERRUN_MESSAGE: &str =
+    "Audio processing can't keep up. Try a larger buffer size, a lower sample rate or a lighter preset.";
+
+/// When the watchdog reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchdogSettings {
+    /// Whether `process()` is timed at all.
+    pub enabled: bool,
+    /// Share of the block duration a call may take (1.0 = the whole block).
+    pub load: f64,
+    /// Overruns within one second of audio that raise a report.
+    pub repeats: u32,
+    /// Also post a GUI notification.
+    pub notify: bool,
+}
+
+impl WatchdogSettings {
+    /// Report 3 overruns of the full block duration within a second,
+    /// without a GUI notification.
+    pub const fn new() -> Self {
+        Self {
+            enabled: true,
+            load: 1.0,
+            repeats: 3,
+            notify: false,
+        }
+    }
+
+    /// Don't time `process()`.
+    pub const fn disabled() -> Self {
+        Self { enabled: false, ..Self::new() }
+    }
+
+    /// Count calls over `load` times the block duration as overruns
+    /// (e.g. 0.7 to warn before the host drops out).
+    pub const fn with_load(mut self, load: f64) -> Self {
+        self.load = load;
+        self
+    }
+
+    /// Report after `repeats` overruns within one second of audio.
+    pub const fn with_repeats(mut self, repeats: u32) -> Self {
+        self.repeats = repeats;
+        self
+    }
+
+    /// Also show a warning in the GUI's notification area.
+    pub const fn with_notification(mut self) -> Self {
+        self.notify = true;
+        self
+    }
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Overruns since the previous report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverrunReport {
+    /// Overruns since the previous report.
+    pub overruns: u64,
+    /// Overruns since the watchdog was created.
+    pub total: u64,
+    /// Slowest `process()` call since the previous report.
+    pub worst: Duration,
+    /// Deadline of that call.
+    pub deadline: Duration,
+    /// Block size of that call in samples.
+    pub block_size: u32,
+    /// Sample rate in Hz.
+    pub sample_rate: f64,
+}
+
+impl OverrunReport {
+    /// `{ "overruns", "total", "worstMs", "deadlineMs", "blockSize", "sampleRate" }`.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "overruns": self.overruns,
+            "total": self.total,
+            "worstMs": self.worst.as_secs_f64() * 1000.0,
+            "deadlineMs": self.deadline.as_secs_f64() * 1000.0,
+            "blockSize": self.block_size,
+            "sampleRate": self.sample_rate,
+        })
+    }
+}
+
+struct Shared {
+    settings: WatchdogSettings,
+    notifications: Option<Notifications>,
+    sample_rate: AtomicU64,
+    /// Audio-thread state: samples and overruns in the current window.
+    window_samples: AtomicU64,
+    window_overruns: AtomicU32,
+    /// Since the previous report.
+    overruns: AtomicU64,
+    worst_ns: AtomicU64,
+    worst_deadline_ns: AtomicU64,
+    worst_block: AtomicU32,
+    total: AtomicU64,
+    /// A report is waiting for [`ProcessWatchdog::take_report()`].
+    pending: AtomicBool,
+}
+
+/// Times `process()` calls against their deadline.
+///
+/// Cheap to clone (reference counted). The wrappers own one per instance,
+/// call [`measure()`](Self::measure) on the audio thread and
+/// [`take_report()`](Self::take_report) on the main thread.
+#[derive(Clone)]
+pub struct ProcessWatchdog {
+    shared: Arc<Shared>,
+}
+
+impl Default for ProcessWatchdog {
+    fn default() -> Self {
+        Self::new(WatchdogSettings::new())
+    }
+}
+
+impl ProcessWatchdog {
+    /// Create a watchdog with `settings`.
+    pub fn new(settings: WatchdogSettings) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                settings,
+                notifications: None,
+                sample_rate: AtomicU64::new(0),
+                window_samples: AtomicU64::new(0),
+                window_overruns: AtomicU32::new(0),
+                overruns: AtomicU64::new(0),
+                worst_ns: AtomicU64::new(0),
+                worst_deadline_ns: AtomicU64::new(0),
+                worst_block: AtomicU32::new(0),
+                total: AtomicU64::new(0),
+                pending: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Post the GUI notification to `notifications` (when the settings ask
+    /// for it). Call right after creating the watchdog, before cloning it.
+    pub fn with_notifications(mut self, notifications: Notifications) -> Self {
+        match Arc::get_mut(&mut self.shared) {
+            Some(shared) => shared.notifications = Some(notifications),
+            None => log::warn!("ProcessWatchdog::with_notifications() called on a shared watchdog"),
+        }
+        self
+    }
+
+    /// The settings.
+    pub fn settings(&self) -> WatchdogSettings {
+        self.shared.settings
+    }
+
+    /// Set the host sample rate. Called by the wrappers when processing is
+    /// set up; until then nothing is timed.
+    pub fn set_sample_rate(&self, sample_rate: f64) {
+        self.shared.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+        self.shared.window_samples.store(0, Ordering::Relaxed);
+        self.shared.window_overruns.store(0, Ordering::Relaxed);
+    }
+
+    /// Run `process` for a block of `num_samples` and time it.
+    ///
+    /// Real-time safe.
+    pub fn measure<R>(&self, num_samples: usize, process: impl FnOnce() -> R) -> R {
+        let _timer = self.start(num_samples);
+        process()
+    }
+
+    /// Start timing a block of `num_samples`; the call is recorded when
+    /// the returned timer is dropped. For functions with several returns.
+    ///
+    /// Real-time safe.
+    pub fn start(&self, num_samples: usize) -> WatchdogTimer<'_> {
+        let start = (self.shared.settings.enabled && num_samples > 0).then(Instant::now);
+        WatchdogTimer { watchdog: self, start, num_samples }
+    }
+
+    /// Record a `process()` call that took `elapsed` for `num_samples`.
+    ///
+    /// Real-time safe: atomics only (the optional notification uses
+    /// [`Notifications::notify_static()`]).
+    pub fn record(&self, elapsed: Duration, num_samples: usize) {
+        let shared = &*self.shared;
+        let sample_rate = f64::from_bits(shared.sample_rate.load(Ordering::Relaxed));
+        if !shared.settings.enabled || sample_rate <= 0.0 {
+            return;
+        }
+
+        // Start a new window every second of audio
+        let window = shared.window_samples.load(Ordering::Relaxed) + num_samples as u64;
+        if window as f64 > sample_rate {
+            shared.window_samples.store(num_samples as u64, Ordering::Relaxed);
+            shared.window_overruns.store(0, Ordering::Relaxed);
+        } else {
+            shared.window_samples.store(window, Ordering::Relaxed);
+        }
+
+        let deadline = Duration::from_nanos((num_samples as f64 * 1e9 / sample_rate * shared.settings.load) as u64);
+        if elapsed <= deadline {
+            return;
+        }
+        shared.total.fetch_add(1, Ordering::Relaxed);
+        shared.overruns.fetch_add(1, Ordering::Relaxed);
+        let elapsed_ns = elapsed.as_nanos() as u64;
+        if elapsed_ns > shared.worst_ns.load(Ordering::Relaxed) {
+            shared.worst_ns.store(elapsed_ns, Ordering::Relaxed);
+            shared.worst_deadline_ns.store(deadline.as_nanos() as u64, Ordering::Relaxed);
+            shared.worst_block.store(num_samples as u32, Ordering::Relaxed);
+        }
+
+        // Report once per window when the overruns repeat
+        let count = shared.window_overruns.load(Ordering::Relaxed) + 1;
+        shared.window_overruns.store(count, Ordering::Relaxed);
+        if count == shared.settings.repeats.max(1) {
+            shared.pending.store(true, Ordering::Release);
+            if shared.settings.notify {
+                if let Some(notifications) = &shared.notifications {
+                    notifications.notify_static(NotifyLevel::Warning, OVERRUN_MESSAGE);
+                }
+            }
+        }
+    }
+
+    /// Take the pending report, if overruns repeated since the last one,
+    /// and write it to the log.
+    ///
+    /// Called by the wrappers on the main thread.
+    pub fn take_report(&self) -> Option<OverrunReport> {
+        let shared = &*self.shared;
+        if !shared.pending.swap(false, Ordering::Acquire) {
+            return None;
+        }
+        let report = OverrunReport {
+            overruns: shared.overruns.swap(0, Ordering::Relaxed),
+            total: shared.total.load(Ordering::Relaxed),
+            worst: Duration::from_nanos(shared.worst_ns.swap(0, Ordering::Relaxed)),
+            deadline: Duration::from_nanos(shared.worst_deadline_ns.load(Ordering::Relaxed)),
+            block_size: shared.worst_block.load(Ordering::Relaxed),
+            sample_rate: f64::from_bits(shared.sample_rate.load(Ordering::Relaxed)),
+        };
+        log::warn!(
+            "process() overran its deadline {} times ({} in total), worst {:.2} ms of {:.2} ms for {} samples at {} Hz",
+            report.overruns,
+            report.total,
+            report.worst.as_secs_f64() * 1000.0,
+            report.deadline.as_secs_f64() * 1000.0,
+            report.block_size,
+            report.sample_rate,
+        );
+        Some(report)
+    }
+
+    /// Script that delivers the pending report to the JS runtime as an
+    /// [`OVERRUN_EVENT`], or `None` if there is none.
+    pub fn take_script(&self) -> Option<String> {
+        self.take_report().map(|report| {
+            format!(
+                "window.__BEAMER__._onEvent({},{})",
+                serde_json::to_string(OVERRUN_EVENT).unwrap_or_default(),
+                report.to_json()
+            )
+        })
+    }
+}
+
+/// Times one block until dropped. See [`ProcessWatchdog::start()`].
+pub struct WatchdogTimer<'a> {
+    watchdog: &'a ProcessWatchdog,
+    start: Option<Instant>,
+    num_samples: usize,
+}
+
+impl Drop for WatchdogTimer<'_> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.watchdog.record(start.elapsed(), self.num_samples);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn reports_repeated_overruns_once_per_window() {
+        let watchdog = ProcessWatchdog::default();
+        // Not timed before the sample rate is known
+        watchdog.record(100 * MS, 480);
+        assert!(watchdog.take_report().is_none());
+
+        // 480 samples at 48 kHz = 10 ms deadline
+        watchdog.set_sample_rate(48000.0);
+        watchdog.record(12 * MS, 480);
+        watchdog.record(5 * MS, 480);
+        watchdog.record(15 * MS, 480);
+        assert!(watchdog.take_report().is_none());
+        watchdog.record(11 * MS, 480);
+        watchdog.record(11 * MS, 480);
+
+        let report = watchdog.take_report().unwrap();
+        assert_eq!((report.overruns, report.total, report.block_size), (4, 4, 480));
+        assert_eq!((report.worst, report.deadline), (15 * MS, 10 * MS));
+        assert!(watchdog.take_report().is_none());
+
+        // The rest of the window doesn't report again
+        for _ in 0..5 {
+            watchdog.record(11 * MS, 480);
+        }
+        assert!(watchdog.take_report().is_none());
+    }
+
+    #[test]
+    fn load_share_and_notification() {
+        let notifications = Notifications::new();
+        let watchdog = ProcessWatchdog::new(WatchdogSettings::new().with_load(0.5).with_repeats(1).with_notification())
+            .with_notifications(notifications.clone());
+        watchdog.set_sample_rate(48000.0);
+        watchdog.record(6 * MS, 480);
+        assert_eq!(notifications.pending(), 1);
+
+        let script = watchdog.take_script().unwrap();
+        assert!(script.starts_with("window.__BEAMER__._onEvent(\"beamer:overrun\","));
+        assert!(script.contains("\"deadlineMs\":5.0"));
+
+        let disabled = ProcessWatchdog::new(WatchdogSettings::disabled());
+        disabled.set_sample_rate(48000.0);
+        assert_eq!(disabled.measure(480, || 7), 7);
+        disabled.record(100 * MS, 480);
+        assert!(disabled.take_report().is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! The native editor: egui in a platform child window.
+
+use std::ffi::c_void;
+use std::time::{Duration, Instant};
+
+use beamer_core::{EditorHost, GuiConstraints, GuiDelegate, NativeEditor, ParentWindow, Size};
+
+use crate::painter::Painter;
+use crate::params::{Edit, Params};
+use crate::platform::{Window, WindowEvent};
+
+/// The plugin's UI function, run once per frame.
+type UiFn = Box<dyn FnMut(&egui::Context, &Params<'_>) + Send>;
+
+/// A [`NativeEditor`] drawing the plugin's egui UI.
+///
+/// Return it from [`Descriptor::editor()`](beamer_core::Descriptor::editor)
+/// through [`GuiBackend::native()`](beamer_core::GuiBackend::native). The UI
+/// function runs on the UI thread whenever a frame is drawn.
+pub struct NativeGuiDelegate {
+    size: Size,
+    constraints: GuiConstraints,
+    /// The UI function, moved into the editor while open.
+    ui: Option<UiFn>,
+    editor: Option<Box<Editor>>,
+}
+
+// SAFETY: The wrapper creates, drives and drops the delegate on the UI
+// thread; the window and GPU handles of an open editor never leave it.
+unsafe impl Send for NativeGuiDelegate {}
+// SAFETY: See above.
+unsafe impl Sync for NativeGuiDelegate {}
+
+impl NativeGuiDelegate {
+    /// Create an editor of `size` drawing `ui`. Resizable down to `size`
+    /// by default.
+    pub fn new(size: Size, ui: impl FnMut(&egui::Context, &Params<'_>) + Send + 'static) -> Self {
+        Self {
+            size,
+            constraints: GuiConstraints { min: size, ..GuiConstraints::default() },
+            ui: Some(Box::new(ui)),
+            editor: None,
+        }
+    }
+
+    /// Set the size constraints.
+    pub fn with_constraints(mut self, constraints: GuiConstraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+}
+
+impl GuiDelegate for NativeGuiDelegate {
+    fn gui_size(&self) -> Size {
+        self.size
+    }
+
+    fn gui_constraints(&self) -> GuiConstraints {
+        self.constraints
+    }
+
+    fn gui_resized(&mut self, new_size: Size) {
+        self.size = new_size;
+        if let Some(editor) = &mut self.editor {
+            editor.resize(new_size);
+        }
+    }
+}
+
+impl NativeEditor for NativeGuiDelegate {
+    unsafe fn open(&mut self, parent: ParentWindow, host: Box<dyn EditorHost>) -> bool {
+        if self.editor.is_some() {
+            return false;
+        }
+        let Some(ui) = self.ui.take() else { return false };
+        // SAFETY: parent is valid until close() per the caller's contract.
+        match unsafe { Editor::open(parent, self.size, host, ui) } {
+            Ok(editor) => {
+                self.editor = Some(editor);
+                true
+            }
+            Err((ui, e)) => {
+                log::error!("Failed to open the editor: {e}");
+                self.ui = Some(ui);
+                false
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(editor) = self.editor.take() {
+            self.ui = Some(editor.close());
+        }
+    }
+
+    fn idle(&mut self) {
+        // The macOS and Windows windows run their own frame timer.
+        #[cfg(target_os = "linux")]
+        if let Some(editor) = &mut self.editor {
+            editor.frame();
+        }
+    }
+}
+
+impl Drop for NativeGuiDelegate {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// An open editor. Boxed: the window's event callback points to it.
+struct Editor {
+    // Dropped before the window it draws into.
+    painter: Painter,
+    window: Window,
+    context: egui::Context,
+    host: Box<dyn EditorHost>,
+    ui: UiFn,
+    size: Size,
+    events: Vec<egui::Event>,
+    modifiers: egui::Modifiers,
+    /// Normalized values at the last frame, to redraw on automation.
+    last_values: Vec<f64>,
+    /// When egui wants the next frame. None: on input only.
+    repaint_at: Option<Instant>,
+    epoch: Instant,
+}
+
+impl Editor {
+    /// Open the window and the painter. Hands the UI function back on
+    /// failure.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be valid until the editor is closed.
+    unsafe fn open(
+        parent: ParentWindow,
+        size: Size,
+        host: Box<dyn EditorHost>,
+        ui: UiFn,
+    ) -> Result<Box<Self>, (UiFn, String)> {
+        // SAFETY: parent is valid per the caller's contract.
+        let window = match unsafe { Window::open(parent, size) } {
+            Ok(window) => window,
+            Err(e) => return Err((ui, e)),
+        };
+        let (display, raw_window) = window.handles();
+        // SAFETY: The painter is dropped before the window.
+        let painter = match unsafe { Painter::new(display, raw_window, window.physical_size(size)) } {
+            Ok(painter) => painter,
+            Err(e) => return Err((ui, e)),
+        };
+
+        let mut editor = Box::new(Self {
+            painter,
+            window,
+            context: egui::Context::default(),
+            host,
+            ui,
+            size,
+            events: Vec::new(),
+            modifiers: egui::Modifiers::NONE,
+            last_values: Vec::new(),
+            repaint_at: Some(Instant::now()),
+            epoch: Instant::now(),
+        });
+        let context = std::ptr::from_mut(&mut *editor).cast::<c_void>();
+        // SAFETY: The editor is boxed, so the context stays valid until the
+        // window (which stops the callbacks when dropped) is dropped with it.
+        unsafe { editor.window.start(on_window_event, context) };
+        Ok(editor)
+    }
+
+    /// Close the window and hand the UI function back.
+    fn close(self: Box<Self>) -> UiFn {
+        let Self { painter, window, ui, .. } = *self;
+        drop(painter);
+        drop(window);
+        ui
+    }
+
+    fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.window.set_size(size);
+        self.painter.resize(self.window.physical_size(size));
+        self.repaint_at = Some(Instant::now());
+    }
+
+    fn handle(&mut self, event: WindowEvent) {
+        match event {
+            WindowEvent::Input(event) => self.events.push(event),
+            WindowEvent::Frame => self.frame(),
+        }
+    }
+
+    /// Draw a frame if there is input, a parameter moved or egui asked
+    /// for one.
+    fn frame(&mut self) {
+        let exposed = self.window.poll(&mut self.events);
+        let parameters_changed = self.take_parameters_changed();
+        let repaint_due = self.repaint_at.is_some_and(|at| Instant::now() >= at);
+        if self.events.is_empty() && !exposed && !parameters_changed && !repaint_due {
+            return;
+        }
+
+        for event in &self.events {
+            match event {
+                egui::Event::Key { modifiers, .. } | egui::Event::PointerButton { modifiers, .. } => {
+                    self.modifiers = *modifiers;
+                }
+                _ => {}
+            }
+        }
+
+        let pixels_per_point = self.window.pixels_per_point();
+        let [width, height] = self.window.physical_size(self.size);
+        let mut input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(width as f32, height as f32) / pixels_per_point,
+            )),
+            time: Some(self.epoch.elapsed().as_secs_f64()),
+            modifiers: self.modifiers,
+            events: std::mem::take(&mut self.events),
+            focused: true,
+            ..Default::default()
+        };
+        input.viewports.entry(egui::ViewportId::ROOT).or_default().native_pixels_per_point =
+            Some(pixels_per_point);
+
+        let mut input = Some(input);
+        let mut output = None;
+        let mut edits = Vec::new();
+        let (context, ui) = (&self.context, &mut self.ui);
+        self.host.with_parameters(&mut |store| {
+            let Some(input) = input.take() else { return };
+            let params = Params::new(store);
+            output = Some(context.run(input, |ctx| ui(ctx, &params)));
+            edits = params.take_edits();
+        });
+        let Some(output) = output else { return };
+
+        for edit in edits {
+            match edit {
+                Edit::Begin(id) => self.host.begin_edit(id),
+                Edit::Set(id, value) => self.host.set_parameter(id, value),
+                Edit::End(id) => self.host.end_edit(id),
+            }
+        }
+        // Don't redraw for the edits just made.
+        self.take_parameters_changed();
+
+        let primitives = self.context.tessellate(output.shapes, output.pixels_per_point);
+        self.painter.paint(&primitives, &output.textures_delta, output.pixels_per_point);
+
+        self.repaint_at = output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map(|viewport| viewport.repaint_delay)
+            .filter(|delay| *delay < Duration::from_secs(3600))
+            .map(|delay| Instant::now() + delay);
+    }
+
+    /// Whether any parameter moved since the last call (automation,
+    /// presets, other views).
+    fn take_parameters_changed(&mut self) -> bool {
+        let last_values = &mut self.last_values;
+        let mut changed = false;
+        self.host.with_parameters(&mut |store| {
+            last_values.resize(store.count(), f64::NAN);
+            for (index, last) in last_values.iter_mut().enumerate() {
+                let Some(info) = store.info(index) else { continue };
+                let value = store.get_normalized(info.id);
+                if value != *last {
+                    *last = value;
+                    changed = true;
+                }
+            }
+        });
+        changed
+    }
+}
+
+/// Window event callback; `context` is the boxed [`Editor`].
+///
+/// # Safety
+///
+/// `context` must point to a live editor not otherwise borrowed.
+unsafe fn on_window_event(context: *mut c_void, event: WindowEvent) {
+    // SAFETY: Per the function contract; the window calls back from the
+    // UI thread's event loop, never from inside a frame.
+    let editor = unsafe { &mut *context.cast::<Editor>() };
+    editor.handle(event);
+}
@@ -0,0 +1,51 @@
+//! Native egui GUI support for Beamer audio plugins.
+//!
+//! An alternative to the WebView editor for plugins that don't want to ship
+//! web assets. [`NativeGuiDelegate`] opens a child window in the host's
+//! editor window, renders egui into it with wgpu (Metal, Direct3D 12 or
+//! Vulkan) and binds widgets to the plugin's parameters through
+//! [`Params`]:
+//!
+//! ```ignore
+//! use beamer::prelude::*;
+//! use beamer_egui::{egui, NativeGuiDelegate};
+//!
+//! impl Descriptor for GainDescriptor {
+//!     fn editor(&self) -> GuiBackend {
+//!         GuiBackend::native(|| {
+//!             NativeGuiDelegate::new(Size::new(320, 200), |ctx, params| {
+//!                 egui::CentralPanel::default().show(ctx, |ui| {
+//!                     params.slider(ui, GAIN_ID);
+//!                 });
+//!             })
+//!         })
+//!     }
+//!     // ...
+//! }
+//! ```
+//!
+//! `Config.toml` still needs `has_gui = true` and no `gui_assets`; the
+//! editor size comes from the delegate.
+//!
+//! # Platforms
+//!
+//! | Platform | Window | Frames |
+//! |----------|--------|--------|
+//! | macOS | `NSView` subview | `NSTimer`, 60Hz |
+//! | Windows | Child `HWND` | `WM_TIMER`, 60Hz |
+//! | Linux | X11 child window (Xlib loaded at runtime) | Host run loop via [`NativeEditor::idle()`](beamer_core::NativeEditor::idle) |
+//!
+//! Frames are only drawn when there is input, a parameter changed or egui
+//! asked for a repaint.
+
+mod delegate;
+mod painter;
+mod params;
+mod platform;
+
+pub use delegate::NativeGuiDelegate;
+pub use params::Params;
+
+/// The egui version the editor is built with. Use this re-export so the
+/// plugin's widgets match.
+pub use egui;
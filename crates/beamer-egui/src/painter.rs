@@ -0,0 +1,155 @@
+//! wgpu surface and egui renderer for one editor window.
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use egui_wgpu::wgpu;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+
+/// Draws egui output into a window's surface.
+pub struct Painter {
+    renderer: egui_wgpu::Renderer,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Painter {
+    /// Create a surface on the window and a device that can present to it.
+    ///
+    /// # Safety
+    ///
+    /// The handles must be valid and outlive the painter.
+    pub unsafe fn new(display: RawDisplayHandle, window: RawWindowHandle, size: [u32; 2]) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        // SAFETY: The handles outlive the painter per the caller's contract.
+        let surface = unsafe {
+            instance.create_surface_unsafe(wgpu::SurfaceTargetUnsafe::RawHandle {
+                raw_display_handle: display,
+                raw_window_handle: window,
+            })
+        }
+        .map_err(|e| format!("surface: {e}"))?;
+
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+        }))
+        .map_err(|e| format!("adapter: {e}"))?;
+        let (device, queue) = block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("beamer-egui"),
+            ..Default::default()
+        }))
+        .map_err(|e| format!("device: {e}"))?;
+
+        // egui blends in gamma space, so prefer a non-sRGB format.
+        let capabilities = surface.get_capabilities(&adapter);
+        let format = capabilities
+            .formats
+            .iter()
+            .copied()
+            .find(|format| !format.is_srgb())
+            .or_else(|| capabilities.formats.first().copied())
+            .ok_or("surface has no formats")?;
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size[0].max(1),
+            height: size[1].max(1),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: capabilities.alpha_modes.first().copied().unwrap_or(wgpu::CompositeAlphaMode::Auto),
+            view_formats: Vec::new(),
+        };
+        surface.configure(&device, &config);
+        let renderer = egui_wgpu::Renderer::new(&device, format, egui_wgpu::RendererOptions::default());
+
+        Ok(Self { renderer, surface, config, device, queue })
+    }
+
+    /// Resize the surface to `size` physical pixels.
+    pub fn resize(&mut self, size: [u32; 2]) {
+        if size[0] == 0 || size[1] == 0 || size == [self.config.width, self.config.height] {
+            return;
+        }
+        self.config.width = size[0];
+        self.config.height = size[1];
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Upload texture changes and draw one frame.
+    pub fn paint(
+        &mut self,
+        primitives: &[egui::ClippedPrimitive],
+        textures: &egui::TexturesDelta,
+        pixels_per_point: f32,
+    ) {
+        for (id, delta) in &textures.set {
+            self.renderer.update_texture(&self.device, &self.queue, *id, delta);
+        }
+
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => Some(frame),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                None
+            }
+            Err(e) => {
+                log::warn!("Failed to get the editor surface: {e}");
+                None
+            }
+        };
+
+        if let Some(frame) = frame {
+            let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let screen = egui_wgpu::ScreenDescriptor {
+                size_in_pixels: [self.config.width, self.config.height],
+                pixels_per_point,
+            };
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            let callbacks =
+                self.renderer.update_buffers(&self.device, &self.queue, &mut encoder, primitives, &screen);
+            {
+                let mut pass = encoder
+                    .begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("beamer-egui"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &view,
+                            depth_slice: None,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    })
+                    .forget_lifetime();
+                self.renderer.render(&mut pass, primitives, &screen);
+            }
+            self.queue.submit(callbacks.into_iter().chain([encoder.finish()]));
+            frame.present();
+        }
+
+        for id in &textures.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
+
+/// Wait for a wgpu future. Native wgpu resolves them immediately.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
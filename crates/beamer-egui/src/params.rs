@@ -0,0 +1,234 @@
+//! Parameter binding for egui widgets.
+
+use std::cell::RefCell;
+
+use beamer_core::{ParameterId, ParameterStore, ParameterValue};
+
+/// A GUI edit, applied to the host after the frame's UI has run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Edit {
+    Begin(ParameterId),
+    Set(ParameterId, ParameterValue),
+    End(ParameterId),
+}
+
+/// The plugin's parameters, as seen by the editor's UI function.
+///
+/// Reads go straight to the parameter store. Edits are queued and sent to
+/// the host (with begin/end gestures) when the frame's UI returns, so the
+/// widgets below report drags as one host gesture.
+pub struct Params<'a> {
+    store: &'a dyn ParameterStore,
+    edits: RefCell<Vec<Edit>>,
+}
+
+impl<'a> Params<'a> {
+    pub(crate) fn new(store: &'a dyn ParameterStore) -> Self {
+        Self { store, edits: RefCell::new(Vec::new()) }
+    }
+
+    /// The queued edits, oldest first.
+    pub(crate) fn take_edits(self) -> Vec<Edit> {
+        self.edits.into_inner()
+    }
+
+    /// The parameter store, for values and text beyond the helpers below.
+    pub fn store(&self) -> &dyn ParameterStore {
+        self.store
+    }
+
+    /// Normalized value, including edits made earlier in this frame.
+    pub fn value(&self, id: ParameterId) -> ParameterValue {
+        self.edits
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|edit| match *edit {
+                Edit::Set(edited, value) if edited == id => Some(value),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.store.get_normalized(id))
+    }
+
+    /// Display text of the current value (e.g. "-6.0 dB").
+    pub fn text(&self, id: ParameterId) -> String {
+        self.store.normalized_to_string(id, self.value(id))
+    }
+
+    /// Parameter name, or an empty string for an unknown ID.
+    pub fn name(&self, id: ParameterId) -> &'static str {
+        self.store.info_by_id(id).map_or("", |info| info.name)
+    }
+
+    /// Start an edit gesture.
+    pub fn begin_edit(&self, id: ParameterId) {
+        self.edits.borrow_mut().push(Edit::Begin(id));
+    }
+
+    /// Set a normalized value. Call between [`begin_edit()`](Self::begin_edit)
+    /// and [`end_edit()`](Self::end_edit).
+    pub fn set(&self, id: ParameterId, normalized: ParameterValue) {
+        self.edits.borrow_mut().push(Edit::Set(id, normalized.clamp(0.0, 1.0)));
+    }
+
+    /// End an edit gesture.
+    pub fn end_edit(&self, id: ParameterId) {
+        self.edits.borrow_mut().push(Edit::End(id));
+    }
+
+    /// Set a value as one complete gesture (clicks, typed values).
+    pub fn set_once(&self, id: ParameterId, normalized: ParameterValue) {
+        self.begin_edit(id);
+        self.set(id, normalized);
+        self.end_edit(id);
+    }
+
+    /// A labelled slider over the normalized range, showing the parameter's
+    /// display text. Stepped parameters snap to their steps; double-click
+    /// resets to the default.
+    pub fn slider(&self, ui: &mut egui::Ui, id: ParameterId) -> egui::Response {
+        let Some(info) = self.store.info_by_id(id) else {
+            return ui.label("?");
+        };
+        let store = self.store;
+        let mut value = self.value(id);
+        let mut slider = egui::Slider::new(&mut value, 0.0..=1.0)
+            .text(info.name)
+            .custom_formatter(move |normalized, _| store.normalized_to_string(id, normalized))
+            .custom_parser(move |text| store.string_to_normalized(id, text));
+        if info.step_count > 0 {
+            slider = slider.step_by(1.0 / f64::from(info.step_count));
+        }
+        let response = ui.add(slider);
+
+        if response.double_clicked() {
+            self.set_once(id, info.default_normalized);
+        } else if response.drag_started() {
+            self.begin_edit(id);
+            if response.changed() {
+                self.set(id, value);
+            }
+        } else if response.changed() {
+            if response.dragged() {
+                self.set(id, value);
+            } else {
+                self.set_once(id, value);
+            }
+        }
+        if response.drag_stopped() {
+            self.end_edit(id);
+        }
+        response
+    }
+
+    /// A labelled checkbox for an on/off parameter (on above 0.5).
+    pub fn toggle(&self, ui: &mut egui::Ui, id: ParameterId) -> egui::Response {
+        let mut on = self.value(id) > 0.5;
+        let response = ui.checkbox(&mut on, self.name(id));
+        if response.changed() {
+            self.set_once(id, if on { 1.0 } else { 0.0 });
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beamer_core::ParameterInfo;
+
+    struct Store {
+        info: [ParameterInfo; 1],
+        value: std::sync::Mutex<f64>,
+    }
+
+    impl ParameterStore for Store {
+        fn count(&self) -> usize {
+            1
+        }
+
+        fn info(&self, index: usize) -> Option<&ParameterInfo> {
+            self.info.get(index)
+        }
+
+        fn get_normalized(&self, _id: ParameterId) -> ParameterValue {
+            *self.value.lock().unwrap()
+        }
+
+        fn set_normalized(&self, _id: ParameterId, value: ParameterValue) {
+            *self.value.lock().unwrap() = value;
+        }
+
+        fn normalized_to_string(&self, _id: ParameterId, normalized: ParameterValue) -> String {
+            format!("{:.0}%", normalized * 100.0)
+        }
+
+        fn string_to_normalized(&self, _id: ParameterId, string: &str) -> Option<ParameterValue> {
+            string.trim_end_matches('%').parse::<f64>().ok().map(|v| v / 100.0)
+        }
+
+        fn normalized_to_plain(&self, _id: ParameterId, normalized: ParameterValue) -> ParameterValue {
+            normalized
+        }
+
+        fn plain_to_normalized(&self, _id: ParameterId, plain: ParameterValue) -> ParameterValue {
+            plain
+        }
+    }
+
+    fn store() -> Store {
+        Store {
+            info: [ParameterInfo::new(7, "Bypass").with_steps(1)],
+            value: std::sync::Mutex::new(0.0),
+        }
+    }
+
+    #[test]
+    fn test_edits_queue_and_shadow_the_store() {
+        let store = store();
+        let params = Params::new(&store);
+        assert_eq!(params.name(7), "Bypass");
+        assert_eq!(params.text(7), "0%");
+
+        params.begin_edit(7);
+        params.set(7, 1.5);
+        assert_eq!(params.value(7), 1.0);
+        assert_eq!(params.text(7), "100%");
+        params.end_edit(7);
+
+        assert_eq!(store.get_normalized(7), 0.0);
+        assert_eq!(params.take_edits(), [Edit::Begin(7), Edit::Set(7, 1.0), Edit::End(7)]);
+    }
+
+    #[test]
+    fn test_toggle_click_is_one_gesture() {
+        let store = store();
+        let ctx = egui::Context::default();
+        let rect = std::cell::Cell::new(egui::Rect::NOTHING);
+        let frame = |events: Vec<egui::Event>| {
+            let params = Params::new(&store);
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(200.0, 100.0))),
+                events,
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    rect.set(params.toggle(ui, 7).rect);
+                });
+            });
+            params.take_edits()
+        };
+
+        assert!(frame(Vec::new()).is_empty());
+        let pos = rect.get().center();
+        let button = |pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        assert!(frame(vec![egui::Event::PointerMoved(pos), button(true)]).is_empty());
+        assert_eq!(frame(vec![button(false)]), [Edit::Begin(7), Edit::Set(7, 1.0), Edit::End(7)]);
+    }
+}
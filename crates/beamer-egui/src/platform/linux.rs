@@ -0,0 +1,281 @@
+//! X11 child window.
+//!
+//! The window is created on a display connection of its own inside the
+//! host's window (VST3 passes its X11 window ID). Hosts don't hand their
+//! event loop to plugins, so events are read from that connection when the
+//! host's run loop calls `NativeEditor::idle()`.
+
+use std::ffi::{c_char, c_uint, c_void};
+use std::ptr::NonNull;
+
+use beamer_core::{ParentWindow, Size};
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle, XlibDisplayHandle, XlibWindowHandle};
+
+use super::linux_x11::{self as x11, xlib, Display, KeySym, XEvent, XInputEvent, XWindow, Xlib};
+use super::{key_for_char, EventCallback};
+
+/// Events the window listens to.
+const EVENT_MASK: std::ffi::c_long = x11::KEY_PRESS_MASK
+    | x11::KEY_RELEASE_MASK
+    | x11::BUTTON_PRESS_MASK
+    | x11::BUTTON_RELEASE_MASK
+    | x11::LEAVE_WINDOW_MASK
+    | x11::POINTER_MOTION_MASK
+    | x11::EXPOSURE_MASK
+    | x11::FOCUS_CHANGE_MASK;
+
+/// An X11 child window on its own display connection.
+pub struct LinuxWindow {
+    xlib: &'static Xlib,
+    display: Display,
+    screen: i32,
+    window: XWindow,
+}
+
+impl LinuxWindow {
+    /// Create the child window in `parent`.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be an X11 window that outlives the window.
+    pub unsafe fn open(parent: ParentWindow, size: Size) -> Result<Self, String> {
+        let ParentWindow::X11(parent) = parent else {
+            return Err("expected an X11 parent window".to_string());
+        };
+        let xlib = xlib()?;
+        // SAFETY: Xlib functions on a display connection of our own; the
+        // parent is a valid window per the caller's contract.
+        unsafe {
+            let display = (xlib.XOpenDisplay)(std::ptr::null());
+            if display.is_null() {
+                return Err("could not open the X11 display".to_string());
+            }
+            let screen = (xlib.XDefaultScreen)(display);
+            let window = (xlib.XCreateSimpleWindow)(
+                display,
+                parent as XWindow,
+                0,
+                0,
+                size.width.max(1),
+                size.height.max(1),
+                0,
+                0,
+                0,
+            );
+            (xlib.XSelectInput)(display, window, EVENT_MASK);
+            (xlib.XMapWindow)(display, window);
+            (xlib.XFlush)(display);
+            Ok(Self { xlib, display, screen, window })
+        }
+    }
+
+    /// Display and window handles for the GPU surface.
+    pub fn handles(&self) -> (RawDisplayHandle, RawWindowHandle) {
+        (
+            RawDisplayHandle::Xlib(XlibDisplayHandle::new(NonNull::new(self.display), self.screen)),
+            RawWindowHandle::Xlib(XlibWindowHandle::new(self.window)),
+        )
+    }
+
+    /// Sizes from VST3 on Linux are in pixels.
+    pub fn physical_size(&self, size: Size) -> [u32; 2] {
+        [size.width, size.height]
+    }
+
+    pub fn pixels_per_point(&self) -> f32 {
+        1.0
+    }
+
+    pub fn set_size(&self, size: Size) {
+        // SAFETY: display and window are valid until drop.
+        unsafe {
+            (self.xlib.XResizeWindow)(self.display, self.window, size.width.max(1), size.height.max(1));
+            (self.xlib.XFlush)(self.display);
+        }
+    }
+
+    /// Nothing to start: frames follow `NativeEditor::idle()`.
+    ///
+    /// # Safety
+    ///
+    /// Trivially safe; unsafe for the same signature on all platforms.
+    pub unsafe fn start(&mut self, _callback: EventCallback, _context: *mut c_void) {}
+
+    /// Read all pending events into `events`. Returns whether the window
+    /// must be redrawn (it was exposed).
+    pub fn poll(&mut self, events: &mut Vec<egui::Event>) -> bool {
+        let xlib = self.xlib;
+        let mut exposed = false;
+        // SAFETY: display is valid until drop; XNextEvent fills the event.
+        while unsafe { (xlib.XPending)(self.display) } > 0 {
+            let mut event = XEvent::zeroed();
+            // SAFETY: See above.
+            unsafe { (xlib.XNextEvent)(self.display, &mut event) };
+            // SAFETY: Every XEvent starts with its type.
+            let kind = unsafe { event.kind };
+            match kind {
+                x11::MOTION_NOTIFY => {
+                    // SAFETY: Motion events have the input event layout.
+                    let input = unsafe { event.input };
+                    events.push(egui::Event::PointerMoved(position(&input)));
+                }
+                x11::BUTTON_PRESS | x11::BUTTON_RELEASE => {
+                    // SAFETY: Button events have the input event layout.
+                    let input = unsafe { event.input };
+                    let pressed = kind == x11::BUTTON_PRESS;
+                    if pressed {
+                        // Take keyboard focus from the host for text fields.
+                        // SAFETY: display and window are valid.
+                        unsafe { (xlib.XSetInputFocus)(self.display, self.window, x11::REVERT_TO_PARENT, 0) };
+                    }
+                    if let Some(event) = button_event(&input, pressed) {
+                        events.push(event);
+                    }
+                }
+                x11::KEY_PRESS | x11::KEY_RELEASE => {
+                    // SAFETY: Key events have the input event layout.
+                    let mut input = unsafe { event.input };
+                    self.key_events(&mut input, kind == x11::KEY_PRESS, events);
+                }
+                x11::LEAVE_NOTIFY => events.push(egui::Event::PointerGone),
+                x11::FOCUS_IN | x11::FOCUS_OUT => events.push(egui::Event::WindowFocused(kind == x11::FOCUS_IN)),
+                x11::EXPOSE => exposed = true,
+                _ => {}
+            }
+        }
+        exposed
+    }
+
+    fn key_events(&self, input: &mut XInputEvent, pressed: bool, events: &mut Vec<egui::Event>) {
+        let mut text = [0 as c_char; 32];
+        let mut keysym: KeySym = 0;
+        // SAFETY: input is a key event; the buffer length is passed along.
+        let len = unsafe {
+            (self.xlib.XLookupString)(input, text.as_mut_ptr(), text.len() as i32, &mut keysym, std::ptr::null_mut())
+        };
+        let modifiers = modifiers(input.state);
+        let key = key_for_keysym(keysym);
+        if let Some(key) = key {
+            events.push(egui::Event::Key { key, physical_key: None, pressed, repeat: false, modifiers });
+        }
+        if pressed && !modifiers.ctrl && len > 0 {
+            // XLookupString() returns Latin-1.
+            let text: String = text[..len as usize].iter().map(|&byte| char::from(byte as u8)).collect();
+            if !text.chars().any(char::is_control) {
+                events.push(egui::Event::Text(text));
+            }
+        }
+    }
+}
+
+impl Drop for LinuxWindow {
+    fn drop(&mut self) {
+        // SAFETY: display and window were created in open().
+        unsafe {
+            (self.xlib.XDestroyWindow)(self.display, self.window);
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+fn position(input: &XInputEvent) -> egui::Pos2 {
+    egui::pos2(input.x as f32, input.y as f32)
+}
+
+fn modifiers(state: c_uint) -> egui::Modifiers {
+    let ctrl = state & x11::CONTROL_MASK != 0;
+    egui::Modifiers {
+        alt: state & x11::MOD1_MASK != 0,
+        ctrl,
+        shift: state & x11::SHIFT_MASK != 0,
+        mac_cmd: false,
+        command: ctrl,
+    }
+}
+
+/// Pointer button or wheel event. Buttons 4-7 are the wheel.
+fn button_event(input: &XInputEvent, pressed: bool) -> Option<egui::Event> {
+    let modifiers = modifiers(input.state);
+    let button = match input.detail {
+        1 => egui::PointerButton::Primary,
+        2 => egui::PointerButton::Middle,
+        3 => egui::PointerButton::Secondary,
+        4..=7 if pressed => {
+            let delta = match input.detail {
+                4 => egui::vec2(0.0, 1.0),
+                5 => egui::vec2(0.0, -1.0),
+                6 => egui::vec2(1.0, 0.0),
+                _ => egui::vec2(-1.0, 0.0),
+            };
+            return Some(egui::Event::MouseWheel { unit: egui::MouseWheelUnit::Line, delta, modifiers });
+        }
+        _ => return None,
+    };
+    Some(egui::Event::PointerButton { pos: position(input), button, pressed, modifiers })
+}
+
+fn key_for_keysym(keysym: KeySym) -> Option<egui::Key> {
+    use egui::Key;
+
+    Some(match keysym {
+        0xff08 => Key::Backspace,
+        0xff09 => Key::Tab,
+        0xff0d | 0xff8d => Key::Enter,
+        0xff1b => Key::Escape,
+        0xff50 => Key::Home,
+        0xff51 => Key::ArrowLeft,
+        0xff52 => Key::ArrowUp,
+        0xff53 => Key::ArrowRight,
+        0xff54 => Key::ArrowDown,
+        0xff55 => Key::PageUp,
+        0xff56 => Key::PageDown,
+        0xff57 => Key::End,
+        0xff63 => Key::Insert,
+        0xffff => Key::Delete,
+        // Latin-1 keysyms are the characters themselves.
+        0x20..=0x7e => return key_for_char(char::from(keysym as u8)),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keysyms_and_wheel_buttons() {
+        assert_eq!(key_for_keysym(0xff0d), Some(egui::Key::Enter));
+        assert_eq!(key_for_keysym(u64::from(b'q')), Some(egui::Key::Q));
+        assert_eq!(key_for_keysym(0xffe1), None);
+
+        let mut input = XInputEvent {
+            kind: x11::BUTTON_PRESS,
+            serial: 0,
+            send_event: 0,
+            display: std::ptr::null_mut(),
+            window: 0,
+            root: 0,
+            subwindow: 0,
+            time: 0,
+            x: 10,
+            y: 20,
+            x_root: 0,
+            y_root: 0,
+            state: x11::SHIFT_MASK,
+            detail: 5,
+            same_screen: 1,
+        };
+        let Some(egui::Event::MouseWheel { delta, modifiers, .. }) = button_event(&input, true) else {
+            panic!("expected a wheel event");
+        };
+        assert_eq!(delta, egui::vec2(0.0, -1.0));
+        assert!(modifiers.shift);
+        assert!(button_event(&input, false).is_none());
+
+        input.detail = 3;
+        let Some(egui::Event::PointerButton { pos, button, .. }) = button_event(&input, true) else {
+            panic!("expected a button event");
+        };
+        assert_eq!((pos, button), (egui::pos2(10.0, 20.0), egui::PointerButton::Secondary));
+    }
+}
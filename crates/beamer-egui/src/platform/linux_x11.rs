@@ -0,0 +1,151 @@
+//! Xlib functions, loaded at runtime.
+//!
+//! Like WebKitGTK in `beamer-webview`, Xlib is opened with `dlopen()` when
+//! the first editor opens, so plugin binaries load on systems without it.
+
+use std::ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void, CStr};
+use std::sync::OnceLock;
+
+const LIBRARY: &CStr = c"libX11.so.6";
+
+const RTLD_NOW: c_int = 2;
+
+#[link(name = "dl")]
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+/// `Display*`
+pub type Display = *mut c_void;
+/// `Window` (an XID)
+pub type XWindow = c_ulong;
+/// `KeySym`
+pub type KeySym = c_ulong;
+
+pub const KEY_PRESS: c_int = 2;
+pub const KEY_RELEASE: c_int = 3;
+pub const BUTTON_PRESS: c_int = 4;
+pub const BUTTON_RELEASE: c_int = 5;
+pub const MOTION_NOTIFY: c_int = 6;
+pub const LEAVE_NOTIFY: c_int = 8;
+pub const FOCUS_IN: c_int = 9;
+pub const FOCUS_OUT: c_int = 10;
+pub const EXPOSE: c_int = 12;
+
+pub const KEY_PRESS_MASK: c_long = 1 << 0;
+pub const KEY_RELEASE_MASK: c_long = 1 << 1;
+pub const BUTTON_PRESS_MASK: c_long = 1 << 2;
+pub const BUTTON_RELEASE_MASK: c_long = 1 << 3;
+pub const LEAVE_WINDOW_MASK: c_long = 1 << 5;
+pub const POINTER_MOTION_MASK: c_long = 1 << 6;
+pub const EXPOSURE_MASK: c_long = 1 << 15;
+pub const FOCUS_CHANGE_MASK: c_long = 1 << 21;
+
+pub const SHIFT_MASK: c_uint = 1 << 0;
+pub const CONTROL_MASK: c_uint = 1 << 2;
+pub const MOD1_MASK: c_uint = 1 << 3;
+
+/// `RevertToParent`
+pub const REVERT_TO_PARENT: c_int = 2;
+
+/// The prefix shared by `XKeyEvent`, `XButtonEvent`, `XMotionEvent` and
+/// `XCrossingEvent`. `detail` is the keycode or button (unused for motion
+/// and crossing events).
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XInputEvent {
+    pub kind: c_int,
+    pub serial: c_ulong,
+    pub send_event: c_int,
+    pub display: Display,
+    pub window: XWindow,
+    pub root: XWindow,
+    pub subwindow: XWindow,
+    pub time: c_ulong,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: c_uint,
+    pub detail: c_uint,
+    pub same_screen: c_int,
+}
+
+/// `XEvent`
+#[repr(C)]
+pub union XEvent {
+    pub kind: c_int,
+    pub input: XInputEvent,
+    pad: [c_long; 24],
+}
+
+impl XEvent {
+    pub fn zeroed() -> Self {
+        Self { pad: [0; 24] }
+    }
+}
+
+macro_rules! functions {
+    ($($name:ident: fn $args:tt $(-> $ret:ty)?;)*) => {
+        /// The loaded functions.
+        #[allow(non_snake_case)]
+        pub struct Xlib {
+            $(pub $name: unsafe extern "C" fn $args $(-> $ret)?,)*
+        }
+
+        impl Xlib {
+            /// Resolve every function from `handle`, or name the first
+            /// missing one.
+            unsafe fn resolve(handle: *mut c_void) -> Result<Self, String> {
+                Ok(Self {
+                    $($name: {
+                        let name = concat!(stringify!($name), "\0");
+                        // SAFETY: handle is a dlopen() handle; name is
+                        // null-terminated.
+                        let symbol = unsafe { dlsym(handle, name.as_ptr().cast()) };
+                        if symbol.is_null() {
+                            return Err(format!("missing symbol {}", stringify!($name)));
+                        }
+                        // SAFETY: The symbol has the declared C signature.
+                        unsafe {
+                            std::mem::transmute::<*mut c_void, unsafe extern "C" fn $args $(-> $ret)?>(symbol)
+                        }
+                    },)*
+                })
+            }
+        }
+    };
+}
+
+functions! {
+    XOpenDisplay: fn(*const c_char) -> Display;
+    XCloseDisplay: fn(Display) -> c_int;
+    XDefaultScreen: fn(Display) -> c_int;
+    XCreateSimpleWindow: fn(Display, XWindow, c_int, c_int, c_uint, c_uint, c_uint, c_ulong, c_ulong) -> XWindow;
+    XDestroyWindow: fn(Display, XWindow) -> c_int;
+    XSelectInput: fn(Display, XWindow, c_long) -> c_int;
+    XMapWindow: fn(Display, XWindow) -> c_int;
+    XResizeWindow: fn(Display, XWindow, c_uint, c_uint) -> c_int;
+    XSetInputFocus: fn(Display, XWindow, c_int, c_ulong) -> c_int;
+    XFlush: fn(Display) -> c_int;
+    XPending: fn(Display) -> c_int;
+    XNextEvent: fn(Display, *mut XEvent) -> c_int;
+    XLookupString: fn(*mut XInputEvent, *mut c_char, c_int, *mut KeySym, *mut c_void) -> c_int;
+}
+
+/// The functions, loaded on first use.
+pub fn xlib() -> Result<&'static Xlib, String> {
+    static XLIB: OnceLock<Result<Xlib, String>> = OnceLock::new();
+    XLIB.get_or_init(|| {
+        // SAFETY: Plain dlopen() of a system library.
+        let handle = unsafe { dlopen(LIBRARY.as_ptr(), RTLD_NOW) };
+        if handle.is_null() {
+            return Err("Xlib (libX11.so.6) not found".to_string());
+        }
+        // SAFETY: handle is a valid dlopen() handle.
+        unsafe { Xlib::resolve(handle) }
+    })
+    .as_ref()
+    .map_err(Clone::clone)
+}
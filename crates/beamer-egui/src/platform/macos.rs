@@ -0,0 +1,416 @@
+//! NSView child view.
+//!
+//! The view is an instance of `BeamerEguiView`, an NSView subclass built
+//! with `ClassBuilder`. Like the WebView's IPC classes the name is shared by
+//! all Beamer plugins in a process: the methods only forward to the
+//! per-instance callback stored in ivars. An NSTimer on the main run loop
+//! delivers frame ticks.
+
+use std::ffi::{c_void, CStr};
+use std::ptr::NonNull;
+
+use beamer_core::{ParentWindow, Size};
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject, Bool, ClassBuilder, Sel};
+use objc2::{msg_send, sel, MainThreadMarker};
+use objc2_foundation::{NSPoint, NSRect, NSSize};
+use raw_window_handle::{AppKitDisplayHandle, AppKitWindowHandle, RawDisplayHandle, RawWindowHandle};
+
+use super::{key_for_char, EventCallback, WindowEvent};
+
+const CALLBACK_IVAR: &CStr = c"_beamerEguiCallback";
+const CONTEXT_IVAR: &CStr = c"_beamerEguiContext";
+
+/// Seconds between frame ticks.
+const FRAME_INTERVAL: f64 = 1.0 / 60.0;
+
+// NSEventModifierFlags
+const SHIFT_FLAG: usize = 1 << 17;
+const CONTROL_FLAG: usize = 1 << 18;
+const OPTION_FLAG: usize = 1 << 19;
+const COMMAND_FLAG: usize = 1 << 20;
+
+/// NSTrackingMouseEnteredAndExited | NSTrackingMouseMoved |
+/// NSTrackingActiveAlways | NSTrackingInVisibleRect
+const TRACKING_OPTIONS: usize = 0x1 | 0x2 | 0x80 | 0x200;
+
+/// What a view method received.
+#[derive(Clone, Copy)]
+enum Kind {
+    ButtonDown(egui::PointerButton),
+    ButtonUp(egui::PointerButton),
+    Moved,
+    Exited,
+    Scroll,
+    KeyDown,
+    KeyUp,
+    Tick,
+}
+
+/// An NSView added to the host's view.
+pub struct MacosWindow {
+    view: Retained<AnyObject>,
+    timer: Option<Retained<AnyObject>>,
+}
+
+impl MacosWindow {
+    /// Create the view in `parent`.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be an NSView that outlives the window. Must be called
+    /// from the main thread.
+    pub unsafe fn open(parent: ParentWindow, size: Size) -> Result<Self, String> {
+        let ParentWindow::AppKit(parent) = parent else {
+            return Err("expected an NSView parent".to_string());
+        };
+        if parent.is_null() {
+            return Err("null parent view".to_string());
+        }
+        if MainThreadMarker::new().is_none() {
+            return Err("must be called from the main thread".to_string());
+        }
+
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(size.width as f64, size.height as f64));
+        let cls = view_class();
+        // SAFETY: alloc/init of the NSView subclass on the main thread; the
+        // parent is a valid NSView per the caller's contract.
+        unsafe {
+            let view: *mut AnyObject = msg_send![cls, alloc];
+            let view: *mut AnyObject = msg_send![view, initWithFrame: frame];
+            let view = Retained::from_raw(view).ok_or("NSView init returned nil")?;
+            let _: () = msg_send![&*view, setWantsLayer: true];
+
+            let tracking: *mut AnyObject = msg_send![AnyClass::get(c"NSTrackingArea").ok_or("no NSTrackingArea")?, alloc];
+            let tracking: *mut AnyObject = msg_send![
+                tracking,
+                initWithRect: NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0)),
+                options: TRACKING_OPTIONS,
+                owner: &*view,
+                userInfo: std::ptr::null::<AnyObject>()
+            ];
+            if let Some(tracking) = Retained::from_raw(tracking) {
+                let _: () = msg_send![&*view, addTrackingArea: &*tracking];
+            }
+
+            let parent = parent.cast::<AnyObject>();
+            let _: () = msg_send![parent, addSubview: &*view];
+            Ok(Self { view, timer: None })
+        }
+    }
+
+    /// Deliver events to `callback` and start the frame timer.
+    ///
+    /// # Safety
+    ///
+    /// `context` must stay valid for `callback` until the window is dropped.
+    pub unsafe fn start(&mut self, callback: EventCallback, context: *mut c_void) {
+        let cls = view_class();
+        // SAFETY: The ivars exist on the class; the view is only touched on
+        // the main thread.
+        unsafe {
+            if let (Some(callback_ivar), Some(context_ivar)) =
+                (cls.instance_variable(CALLBACK_IVAR), cls.instance_variable(CONTEXT_IVAR))
+            {
+                *callback_ivar.load_ptr::<*const c_void>(&self.view) = callback as *const c_void;
+                *context_ivar.load_ptr::<*mut c_void>(&self.view) = context;
+            }
+
+            // The timer retains the view until invalidated in drop().
+            let timer: *mut AnyObject = msg_send![
+                AnyClass::get(c"NSTimer").expect("NSTimer exists"),
+                scheduledTimerWithTimeInterval: FRAME_INTERVAL,
+                target: &*self.view,
+                selector: sel!(beamerEguiTick:),
+                userInfo: std::ptr::null::<AnyObject>(),
+                repeats: true
+            ];
+            // Autoreleased; keep it to invalidate it.
+            self.timer = Retained::retain(timer);
+        }
+    }
+
+    /// Input arrives through the callback.
+    pub fn poll(&mut self, _events: &mut Vec<egui::Event>) -> bool {
+        false
+    }
+
+    /// Display and window handles for the GPU surface.
+    pub fn handles(&self) -> (RawDisplayHandle, RawWindowHandle) {
+        let view = NonNull::from(&*self.view).cast::<c_void>();
+        (
+            RawDisplayHandle::AppKit(AppKitDisplayHandle::new()),
+            RawWindowHandle::AppKit(AppKitWindowHandle::new(view)),
+        )
+    }
+
+    /// Size in backing pixels of `size` points.
+    pub fn physical_size(&self, size: Size) -> [u32; 2] {
+        let points = NSSize::new(size.width as f64, size.height as f64);
+        // SAFETY: convertSizeToBacking: on a valid view.
+        let pixels: NSSize = unsafe { msg_send![&*self.view, convertSizeToBacking: points] };
+        [pixels.width.round() as u32, pixels.height.round() as u32]
+    }
+
+    pub fn pixels_per_point(&self) -> f32 {
+        let [width, _] = self.physical_size(Size::new(100, 100));
+        width as f32 / 100.0
+    }
+
+    pub fn set_size(&self, size: Size) {
+        let size = NSSize::new(size.width as f64, size.height as f64);
+        // SAFETY: setFrameSize: on a valid view, on the main thread.
+        unsafe {
+            let _: () = msg_send![&*self.view, setFrameSize: size];
+        }
+    }
+}
+
+impl Drop for MacosWindow {
+    fn drop(&mut self) {
+        // SAFETY: Main thread; the timer and view are valid. Clearing the
+        // callback first means no event reaches a closed editor.
+        unsafe {
+            if let Some(timer) = self.timer.take() {
+                let _: () = msg_send![&*timer, invalidate];
+            }
+            if let Some(callback_ivar) = view_class().instance_variable(CALLBACK_IVAR) {
+                *callback_ivar.load_ptr::<*const c_void>(&self.view) = std::ptr::null();
+            }
+            let _: () = msg_send![&*self.view, removeFromSuperview];
+        }
+    }
+}
+
+/// Get or register the `BeamerEguiView` class.
+fn view_class() -> &'static AnyClass {
+    let c_name = c"BeamerEguiView";
+
+    if let Some(existing) = AnyClass::get(c_name) {
+        return existing;
+    }
+
+    let superclass = AnyClass::get(c"NSView").expect("NSView exists");
+    let mut builder = match ClassBuilder::new(c_name, superclass) {
+        Some(b) => b,
+        None => {
+            return AnyClass::get(c_name).expect("class must exist after ClassBuilder::new returned None");
+        }
+    };
+
+    builder.add_ivar::<*const c_void>(CALLBACK_IVAR);
+    builder.add_ivar::<*mut c_void>(CONTEXT_IVAR);
+
+    type EventMethod = unsafe extern "C-unwind" fn(*mut AnyObject, Sel, *mut AnyObject);
+    type FlagMethod = unsafe extern "C-unwind" fn(*mut AnyObject, Sel) -> Bool;
+
+    macro_rules! forward {
+        ($($sel:ident => $kind:expr),* $(,)?) => {
+            $({
+                unsafe extern "C-unwind" fn method(this: *mut AnyObject, _cmd: Sel, event: *mut AnyObject) {
+                    // SAFETY: AppKit passes a valid receiver and event.
+                    unsafe { forward(this, $kind, event) };
+                }
+                // SAFETY: NSResponder event method signature.
+                unsafe { builder.add_method(sel!($sel), method as EventMethod) };
+            })*
+        };
+    }
+
+    use egui::PointerButton::{Middle, Primary, Secondary};
+    forward! {
+        mouseDown: => Kind::ButtonDown(Primary),
+        mouseUp: => Kind::ButtonUp(Primary),
+        mouseDragged: => Kind::Moved,
+        rightMouseDown: => Kind::ButtonDown(Secondary),
+        rightMouseUp: => Kind::ButtonUp(Secondary),
+        rightMouseDragged: => Kind::Moved,
+        otherMouseDown: => Kind::ButtonDown(Middle),
+        otherMouseUp: => Kind::ButtonUp(Middle),
+        otherMouseDragged: => Kind::Moved,
+        mouseMoved: => Kind::Moved,
+        mouseExited: => Kind::Exited,
+        scrollWheel: => Kind::Scroll,
+        keyDown: => Kind::KeyDown,
+        keyUp: => Kind::KeyUp,
+        beamerEguiTick: => Kind::Tick,
+    }
+
+    unsafe extern "C-unwind" fn yes(_this: *mut AnyObject, _cmd: Sel) -> Bool {
+        Bool::YES
+    }
+    unsafe extern "C-unwind" fn yes_for_event(_this: *mut AnyObject, _cmd: Sel, _event: *mut AnyObject) -> Bool {
+        Bool::YES
+    }
+    // SAFETY: NSView overrides with matching signatures.
+    unsafe {
+        builder.add_method(sel!(isFlipped), yes as FlagMethod);
+        builder.add_method(sel!(acceptsFirstResponder), yes as FlagMethod);
+        builder.add_method(
+            sel!(acceptsFirstMouse:),
+            yes_for_event as unsafe extern "C-unwind" fn(*mut AnyObject, Sel, *mut AnyObject) -> Bool,
+        );
+    }
+
+    builder.register()
+}
+
+/// Decode `event` and hand it to the view's callback.
+///
+/// # Safety
+///
+/// `this` must be a `BeamerEguiView`; `event` an NSEvent (or the timer).
+unsafe fn forward(this: *mut AnyObject, kind: Kind, event: *mut AnyObject) {
+    // SAFETY: AppKit provides a valid receiver.
+    let this: &AnyObject = unsafe { &*this };
+    let (Some(callback_ivar), Some(context_ivar)) =
+        (this.class().instance_variable(CALLBACK_IVAR), this.class().instance_variable(CONTEXT_IVAR))
+    else {
+        return;
+    };
+    // SAFETY: The ivars were written in start() and cleared in drop().
+    let (callback, context) = unsafe {
+        (*callback_ivar.load_ptr::<*const c_void>(this), *context_ivar.load_ptr::<*mut c_void>(this))
+    };
+    if callback.is_null() {
+        return;
+    }
+    // SAFETY: Set from an EventCallback in start().
+    let callback: EventCallback = unsafe { std::mem::transmute(callback) };
+
+    if let Kind::Tick = kind {
+        // SAFETY: context is valid while the callback is set.
+        unsafe { callback(context, WindowEvent::Frame) };
+        return;
+    }
+
+    // SAFETY: event is a valid NSEvent for every other kind.
+    let events = unsafe { decode(this, kind, &*event) };
+    for event in events {
+        // SAFETY: See above.
+        unsafe { callback(context, WindowEvent::Input(event)) };
+    }
+}
+
+/// egui events for an NSEvent.
+///
+/// # Safety
+///
+/// `view` must be the view receiving `event`.
+unsafe fn decode(view: &AnyObject, kind: Kind, event: &AnyObject) -> Vec<egui::Event> {
+    // SAFETY: NSEvent accessors on a valid event.
+    let flags: usize = unsafe { msg_send![event, modifierFlags] };
+    let modifiers = egui::Modifiers {
+        alt: flags & OPTION_FLAG != 0,
+        ctrl: flags & CONTROL_FLAG != 0,
+        shift: flags & SHIFT_FLAG != 0,
+        mac_cmd: flags & COMMAND_FLAG != 0,
+        command: flags & COMMAND_FLAG != 0,
+    };
+    let position = || {
+        // SAFETY: See above; the view is flipped, so y grows downwards.
+        unsafe {
+            let location: NSPoint = msg_send![event, locationInWindow];
+            let location: NSPoint = msg_send![view, convertPoint: location, fromView: std::ptr::null::<AnyObject>()];
+            egui::pos2(location.x as f32, location.y as f32)
+        }
+    };
+
+    match kind {
+        Kind::ButtonDown(button) | Kind::ButtonUp(button) => {
+            let pressed = matches!(kind, Kind::ButtonDown(_));
+            if pressed {
+                // Take keyboard focus from the host for text fields.
+                // SAFETY: The view is in a window while it gets events.
+                unsafe {
+                    let window: *mut AnyObject = msg_send![view, window];
+                    if !window.is_null() {
+                        let _: Bool = msg_send![window, makeFirstResponder: view];
+                    }
+                }
+            }
+            let pos = position();
+            vec![
+                egui::Event::PointerMoved(pos),
+                egui::Event::PointerButton { pos, button, pressed, modifiers },
+            ]
+        }
+        Kind::Moved => vec![egui::Event::PointerMoved(position())],
+        Kind::Exited => vec![egui::Event::PointerGone],
+        Kind::Scroll => {
+            // SAFETY: NSEvent accessors on a scroll event.
+            let (x, y, precise): (f64, f64, Bool) = unsafe {
+                (
+                    msg_send![event, scrollingDeltaX],
+                    msg_send![event, scrollingDeltaY],
+                    msg_send![event, hasPreciseScrollingDeltas],
+                )
+            };
+            let unit = if precise.as_bool() { egui::MouseWheelUnit::Point } else { egui::MouseWheelUnit::Line };
+            vec![egui::Event::MouseWheel { unit, delta: egui::vec2(x as f32, y as f32), modifiers }]
+        }
+        Kind::KeyDown | Kind::KeyUp => {
+            let pressed = matches!(kind, Kind::KeyDown);
+            // SAFETY: NSEvent accessors on a key event.
+            let (code, repeat): (u16, Bool) = unsafe { (msg_send![event, keyCode], msg_send![event, isARepeat]) };
+            // SAFETY: See above.
+            let unmodified = unsafe { string(msg_send![event, charactersIgnoringModifiers]) };
+            let key = key_for_code(code).or_else(|| unmodified.chars().next().and_then(key_for_char));
+
+            let mut events = Vec::new();
+            if let Some(key) = key {
+                events.push(egui::Event::Key { key, physical_key: None, pressed, repeat: repeat.as_bool(), modifiers });
+            }
+            if pressed && !modifiers.command && !modifiers.ctrl {
+                // SAFETY: See above.
+                let text = unsafe { string(msg_send![event, characters]) };
+                // Function keys arrive as private-use characters.
+                if !text.is_empty() && !text.chars().any(|c| c.is_control() || ('\u{f700}'..='\u{f8ff}').contains(&c)) {
+                    events.push(egui::Event::Text(text));
+                }
+            }
+            events
+        }
+        Kind::Tick => Vec::new(),
+    }
+}
+
+/// Copy an NSString.
+///
+/// # Safety
+///
+/// `string` must be null or an NSString.
+unsafe fn string(string: *mut AnyObject) -> String {
+    if string.is_null() {
+        return String::new();
+    }
+    // SAFETY: UTF8String on a valid NSString; valid until autoreleased.
+    let utf8: *const std::ffi::c_char = unsafe { msg_send![string, UTF8String] };
+    if utf8.is_null() {
+        return String::new();
+    }
+    // SAFETY: Null-terminated per UTF8String.
+    unsafe { CStr::from_ptr(utf8) }.to_string_lossy().into_owned()
+}
+
+/// egui key for a virtual key code that isn't a character.
+fn key_for_code(code: u16) -> Option<egui::Key> {
+    use egui::Key;
+
+    Some(match code {
+        0x24 | 0x4c => Key::Enter,
+        0x30 => Key::Tab,
+        0x33 => Key::Backspace,
+        0x35 => Key::Escape,
+        0x73 => Key::Home,
+        0x74 => Key::PageUp,
+        0x75 => Key::Delete,
+        0x77 => Key::End,
+        0x79 => Key::PageDown,
+        0x7b => Key::ArrowLeft,
+        0x7c => Key::ArrowRight,
+        0x7d => Key::ArrowDown,
+        0x7e => Key::ArrowUp,
+        _ => return None,
+    })
+}
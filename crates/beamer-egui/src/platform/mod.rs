@@ -0,0 +1,58 @@
+//! Platform child windows.
+//!
+//! Each platform provides a `Window` with the same methods:
+//!
+//! - `open(parent, size)`: create the child window (no events yet)
+//! - `start(callback, context)`: deliver input and frame ticks to
+//!   `callback` from then on
+//! - `poll(events)`: collect pending input (platforms without callbacks)
+//!   and tell whether the window must be redrawn
+//! - `handles()`, `physical_size()`, `pixels_per_point()`, `set_size()`
+//!
+//! The window stops its callbacks and is destroyed when dropped.
+
+use std::ffi::c_void;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+#[cfg(target_os = "linux")]
+mod linux_x11;
+
+#[cfg(target_os = "macos")]
+pub use macos::MacosWindow as Window;
+
+#[cfg(target_os = "windows")]
+pub use windows::WindowsWindow as Window;
+
+#[cfg(target_os = "linux")]
+pub use linux::LinuxWindow as Window;
+
+/// Something for the editor from the window.
+#[derive(Debug)]
+// The X11 window is polled instead.
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub enum WindowEvent {
+    /// Input, in points.
+    Input(egui::Event),
+    /// Time for the next frame (60Hz).
+    Frame,
+}
+
+/// Receives the window's events.
+///
+/// # Safety
+///
+/// `context` is the pointer passed to `start()`.
+pub type EventCallback = unsafe fn(context: *mut c_void, event: WindowEvent);
+
+/// egui key for a printable character (letters, digits, punctuation).
+pub(crate) fn key_for_char(c: char) -> Option<egui::Key> {
+    egui::Key::from_name(c.encode_utf8(&mut [0; 4]))
+}
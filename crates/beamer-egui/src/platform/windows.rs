@@ -0,0 +1,424 @@
+//! Win32 child window.
+//!
+//! The window class is registered once per plugin module, so two Beamer
+//! plugins in one process never share a window procedure. A `WM_TIMER`
+//! delivers frame ticks.
+
+use std::ffi::c_void;
+use std::num::NonZeroIsize;
+use std::sync::OnceLock;
+
+use beamer_core::{ParentWindow, Size};
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle, Win32WindowHandle, WindowsDisplayHandle};
+
+use super::{key_for_char, EventCallback, WindowEvent};
+
+type Hwnd = *mut c_void;
+type Hinstance = *mut c_void;
+type Wparam = usize;
+type Lparam = isize;
+type Lresult = isize;
+type WndProc = unsafe extern "system" fn(Hwnd, u32, Wparam, Lparam) -> Lresult;
+
+#[repr(C)]
+struct WndClassExW {
+    cb_size: u32,
+    style: u32,
+    wnd_proc: WndProc,
+    cls_extra: i32,
+    wnd_extra: i32,
+    instance: Hinstance,
+    icon: *mut c_void,
+    cursor: *mut c_void,
+    background: *mut c_void,
+    menu_name: *const u16,
+    class_name: *const u16,
+    icon_small: *mut c_void,
+}
+
+#[repr(C)]
+struct TrackMouseEventInfo {
+    cb_size: u32,
+    flags: u32,
+    hwnd: Hwnd,
+    hover_time: u32,
+}
+
+#[link(name = "user32")]
+extern "system" {
+    fn RegisterClassExW(class: *const WndClassExW) -> u16;
+    fn CreateWindowExW(
+        ex_style: u32,
+        class_name: *const u16,
+        window_name: *const u16,
+        style: u32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        parent: Hwnd,
+        menu: *mut c_void,
+        instance: Hinstance,
+        param: *mut c_void,
+    ) -> Hwnd;
+    fn DestroyWindow(hwnd: Hwnd) -> i32;
+    fn DefWindowProcW(hwnd: Hwnd, msg: u32, wparam: Wparam, lparam: Lparam) -> Lresult;
+    fn SetWindowLongPtrW(hwnd: Hwnd, index: i32, value: isize) -> isize;
+    fn GetWindowLongPtrW(hwnd: Hwnd, index: i32) -> isize;
+    fn SetWindowPos(hwnd: Hwnd, after: Hwnd, x: i32, y: i32, width: i32, height: i32, flags: u32) -> i32;
+    fn SetTimer(hwnd: Hwnd, id: usize, elapse: u32, func: *const c_void) -> usize;
+    fn KillTimer(hwnd: Hwnd, id: usize) -> i32;
+    fn SetFocus(hwnd: Hwnd) -> Hwnd;
+    fn SetCapture(hwnd: Hwnd) -> Hwnd;
+    fn ReleaseCapture() -> i32;
+    fn TrackMouseEvent(info: *mut TrackMouseEventInfo) -> i32;
+    fn GetKeyState(key: i32) -> i16;
+    fn GetDpiForWindow(hwnd: Hwnd) -> u32;
+    fn LoadCursorW(instance: Hinstance, name: *const u16) -> *mut c_void;
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetModuleHandleExW(flags: u32, name: *const c_void, module: *mut Hinstance) -> i32;
+}
+
+const WS_CHILD: u32 = 0x4000_0000;
+const WS_VISIBLE: u32 = 0x1000_0000;
+const WS_CLIPSIBLINGS: u32 = 0x0400_0000;
+const WS_CLIPCHILDREN: u32 = 0x0200_0000;
+const CS_OWNDC: u32 = 0x0020;
+const GWLP_USERDATA: i32 = -21;
+const SWP_NOMOVE: u32 = 0x0002;
+const SWP_NOZORDER: u32 = 0x0004;
+const SWP_NOACTIVATE: u32 = 0x0010;
+const TME_LEAVE: u32 = 0x0002;
+const IDC_ARROW: usize = 32512;
+/// GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | _UNCHANGED_REFCOUNT
+const MODULE_FROM_ADDRESS: u32 = 0x4 | 0x2;
+/// DLGC_WANTALLKEYS | DLGC_WANTCHARS
+const WANT_ALL_KEYS: Lresult = 0x4 | 0x80;
+
+const WM_ERASEBKGND: u32 = 0x0014;
+const WM_GETDLGCODE: u32 = 0x0087;
+const WM_KEYDOWN: u32 = 0x0100;
+const WM_KEYUP: u32 = 0x0101;
+const WM_CHAR: u32 = 0x0102;
+const WM_SYSKEYDOWN: u32 = 0x0104;
+const WM_SYSKEYUP: u32 = 0x0105;
+const WM_TIMER: u32 = 0x0113;
+const WM_MOUSEMOVE: u32 = 0x0200;
+const WM_LBUTTONDOWN: u32 = 0x0201;
+const WM_LBUTTONUP: u32 = 0x0202;
+const WM_RBUTTONDOWN: u32 = 0x0204;
+const WM_RBUTTONUP: u32 = 0x0205;
+const WM_MBUTTONDOWN: u32 = 0x0207;
+const WM_MBUTTONUP: u32 = 0x0208;
+const WM_MOUSEWHEEL: u32 = 0x020a;
+const WM_MOUSEHWHEEL: u32 = 0x020e;
+const WM_MOUSELEAVE: u32 = 0x02a3;
+
+const VK_SHIFT: i32 = 0x10;
+const VK_CONTROL: i32 = 0x11;
+const VK_MENU: i32 = 0x12;
+
+const FRAME_TIMER: usize = 1;
+/// Milliseconds between frame ticks.
+const FRAME_INTERVAL: u32 = 16;
+/// Wheel delta of one notch.
+const WHEEL_DELTA: f32 = 120.0;
+
+/// Where the window procedure sends events, in `GWLP_USERDATA`.
+struct Target {
+    callback: EventCallback,
+    context: *mut c_void,
+    dpi_scale: f32,
+    tracking_mouse: bool,
+}
+
+/// A child window in the host's window.
+pub struct WindowsWindow {
+    hwnd: Hwnd,
+    instance: Hinstance,
+    target: Option<Box<Target>>,
+}
+
+impl WindowsWindow {
+    /// Create the child window in `parent`.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be a window that outlives the window. Must be called
+    /// from the parent's thread.
+    pub unsafe fn open(parent: ParentWindow, size: Size) -> Result<Self, String> {
+        let ParentWindow::Win32(parent) = parent else {
+            return Err("expected a Win32 parent window".to_string());
+        };
+        let (instance, class_name) = window_class()?;
+        let instance = instance as Hinstance;
+        // SAFETY: The class is registered; parent is valid per the caller's
+        // contract.
+        let hwnd = unsafe {
+            CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                std::ptr::null(),
+                WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS | WS_CLIPCHILDREN,
+                0,
+                0,
+                size.width as i32,
+                size.height as i32,
+                parent,
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null_mut(),
+            )
+        };
+        if hwnd.is_null() {
+            return Err("CreateWindowExW failed".to_string());
+        }
+        Ok(Self { hwnd, instance, target: None })
+    }
+
+    /// Deliver events to `callback` and start the frame timer.
+    ///
+    /// # Safety
+    ///
+    /// `context` must stay valid for `callback` until the window is dropped.
+    pub unsafe fn start(&mut self, callback: EventCallback, context: *mut c_void) {
+        let mut target = Box::new(Target {
+            callback,
+            context,
+            dpi_scale: self.pixels_per_point(),
+            tracking_mouse: false,
+        });
+        // SAFETY: The target is boxed and outlives the window's user data,
+        // which drop() clears first.
+        unsafe {
+            SetWindowLongPtrW(self.hwnd, GWLP_USERDATA, std::ptr::from_mut(&mut *target) as isize);
+            SetTimer(self.hwnd, FRAME_TIMER, FRAME_INTERVAL, std::ptr::null());
+        }
+        self.target = Some(target);
+    }
+
+    /// Input arrives through the callback.
+    pub fn poll(&mut self, _events: &mut Vec<egui::Event>) -> bool {
+        false
+    }
+
+    /// Display and window handles for the GPU surface.
+    pub fn handles(&self) -> (RawDisplayHandle, RawWindowHandle) {
+        let mut handle = Win32WindowHandle::new(NonZeroIsize::new(self.hwnd as isize).expect("window exists"));
+        handle.hinstance = NonZeroIsize::new(self.instance as isize);
+        (RawDisplayHandle::Windows(WindowsDisplayHandle::new()), RawWindowHandle::Win32(handle))
+    }
+
+    /// Sizes from VST3 on Windows are in pixels.
+    pub fn physical_size(&self, size: Size) -> [u32; 2] {
+        [size.width, size.height]
+    }
+
+    pub fn pixels_per_point(&self) -> f32 {
+        // SAFETY: hwnd is valid until drop.
+        match unsafe { GetDpiForWindow(self.hwnd) } {
+            0 => 1.0,
+            dpi => dpi as f32 / 96.0,
+        }
+    }
+
+    pub fn set_size(&self, size: Size) {
+        // SAFETY: hwnd is valid until drop.
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                std::ptr::null_mut(),
+                0,
+                0,
+                size.width as i32,
+                size.height as i32,
+                SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
+    }
+}
+
+impl Drop for WindowsWindow {
+    fn drop(&mut self) {
+        // SAFETY: hwnd was created in open(); no message reaches the target
+        // once the user data is cleared.
+        unsafe {
+            KillTimer(self.hwnd, FRAME_TIMER);
+            SetWindowLongPtrW(self.hwnd, GWLP_USERDATA, 0);
+            DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+/// This module's handle and the registered class name.
+fn window_class() -> Result<(usize, &'static [u16]), String> {
+    static CLASS: OnceLock<Result<usize, String>> = OnceLock::new();
+    static NAME: OnceLock<Vec<u16>> = OnceLock::new();
+    let name = NAME.get_or_init(|| "BeamerEguiWindow\0".encode_utf16().collect());
+
+    let instance = CLASS
+        .get_or_init(|| {
+            let mut instance: Hinstance = std::ptr::null_mut();
+            // SAFETY: Looks up the module containing window_proc.
+            let found = unsafe {
+                GetModuleHandleExW(MODULE_FROM_ADDRESS, window_proc as *const c_void, &mut instance)
+            };
+            if found == 0 {
+                return Err("GetModuleHandleExW failed".to_string());
+            }
+            let class = WndClassExW {
+                cb_size: size_of::<WndClassExW>() as u32,
+                style: CS_OWNDC,
+                wnd_proc: window_proc,
+                cls_extra: 0,
+                wnd_extra: 0,
+                instance,
+                icon: std::ptr::null_mut(),
+                // SAFETY: Loads a system cursor.
+                cursor: unsafe { LoadCursorW(std::ptr::null_mut(), IDC_ARROW as *const u16) },
+                background: std::ptr::null_mut(),
+                menu_name: std::ptr::null(),
+                class_name: name.as_ptr(),
+                icon_small: std::ptr::null_mut(),
+            };
+            // SAFETY: class is fully initialized; the name is static.
+            if unsafe { RegisterClassExW(&class) } == 0 {
+                return Err("RegisterClassExW failed".to_string());
+            }
+            Ok(instance as usize)
+        })
+        .clone()?;
+    Ok((instance, name))
+}
+
+/// The window procedure: forwards messages to the window's target.
+unsafe extern "system" fn window_proc(hwnd: Hwnd, msg: u32, wparam: Wparam, lparam: Lparam) -> Lresult {
+    // SAFETY: hwnd is our window; the user data is null or a live Target.
+    let target = unsafe { GetWindowLongPtrW(hwnd, GWLP_USERDATA) } as *mut Target;
+    if target.is_null() {
+        // SAFETY: Default handling for our window.
+        return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
+    }
+    // SAFETY: Set in start(), cleared in drop().
+    let target = unsafe { &mut *target };
+
+    let modifiers = modifiers();
+    let pos = || {
+        let x = (lparam & 0xffff) as i16 as f32;
+        let y = ((lparam >> 16) & 0xffff) as i16 as f32;
+        egui::pos2(x, y) / target.dpi_scale
+    };
+    let button = |button, pressed| egui::Event::PointerButton { pos: pos(), button, pressed, modifiers };
+
+    let event = match msg {
+        WM_TIMER if wparam == FRAME_TIMER => {
+            // Follow per-monitor DPI changes.
+            // SAFETY: hwnd is valid.
+            if let dpi @ 1.. = unsafe { GetDpiForWindow(hwnd) } {
+                target.dpi_scale = dpi as f32 / 96.0;
+            }
+            // SAFETY: The context is valid while the target is set.
+            unsafe { (target.callback)(target.context, WindowEvent::Frame) };
+            return 0;
+        }
+        WM_ERASEBKGND => return 1,
+        WM_GETDLGCODE => return WANT_ALL_KEYS,
+        WM_MOUSEMOVE => {
+            if !target.tracking_mouse {
+                let mut info = TrackMouseEventInfo {
+                    cb_size: size_of::<TrackMouseEventInfo>() as u32,
+                    flags: TME_LEAVE,
+                    hwnd,
+                    hover_time: 0,
+                };
+                // SAFETY: info is initialized.
+                target.tracking_mouse = unsafe { TrackMouseEvent(&mut info) } != 0;
+            }
+            egui::Event::PointerMoved(pos())
+        }
+        WM_MOUSELEAVE => {
+            target.tracking_mouse = false;
+            egui::Event::PointerGone
+        }
+        WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN => {
+            // SAFETY: hwnd is valid. Take keyboard focus for text fields and
+            // keep getting moves while dragging outside the window.
+            unsafe {
+                SetFocus(hwnd);
+                SetCapture(hwnd);
+            }
+            button(pointer_button(msg), true)
+        }
+        WM_LBUTTONUP | WM_RBUTTONUP | WM_MBUTTONUP => {
+            // SAFETY: Releases the capture taken on button down.
+            unsafe { ReleaseCapture() };
+            button(pointer_button(msg), false)
+        }
+        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+            let notches = ((wparam >> 16) & 0xffff) as i16 as f32 / WHEEL_DELTA;
+            let delta = if msg == WM_MOUSEWHEEL { egui::vec2(0.0, notches) } else { egui::vec2(-notches, 0.0) };
+            egui::Event::MouseWheel { unit: egui::MouseWheelUnit::Line, delta, modifiers }
+        }
+        WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => {
+            let Some(key) = key_for_vk(wparam) else {
+                // SAFETY: Default handling for our window.
+                return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
+            };
+            let pressed = msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN;
+            let repeat = pressed && lparam & (1 << 30) != 0;
+            egui::Event::Key { key, physical_key: None, pressed, repeat, modifiers }
+        }
+        WM_CHAR => match char::from_u32(wparam as u32) {
+            Some(c) if !c.is_control() && !modifiers.ctrl => egui::Event::Text(c.to_string()),
+            _ => return 0,
+        },
+        // SAFETY: Default handling for our window.
+        _ => return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+    };
+    // SAFETY: The context is valid while the target is set.
+    unsafe { (target.callback)(target.context, WindowEvent::Input(event)) };
+    0
+}
+
+fn pointer_button(msg: u32) -> egui::PointerButton {
+    match msg {
+        WM_RBUTTONDOWN | WM_RBUTTONUP => egui::PointerButton::Secondary,
+        WM_MBUTTONDOWN | WM_MBUTTONUP => egui::PointerButton::Middle,
+        _ => egui::PointerButton::Primary,
+    }
+}
+
+fn modifiers() -> egui::Modifiers {
+    // SAFETY: Reads the calling thread's keyboard state.
+    let down = |key| unsafe { GetKeyState(key) } < 0;
+    let ctrl = down(VK_CONTROL);
+    egui::Modifiers { alt: down(VK_MENU), ctrl, shift: down(VK_SHIFT), mac_cmd: false, command: ctrl }
+}
+
+fn key_for_vk(vk: Wparam) -> Option<egui::Key> {
+    use egui::Key;
+
+    Some(match vk {
+        0x08 => Key::Backspace,
+        0x09 => Key::Tab,
+        0x0d => Key::Enter,
+        0x1b => Key::Escape,
+        0x20 => Key::Space,
+        0x21 => Key::PageUp,
+        0x22 => Key::PageDown,
+        0x23 => Key::End,
+        0x24 => Key::Home,
+        0x25 => Key::ArrowLeft,
+        0x26 => Key::ArrowUp,
+        0x27 => Key::ArrowRight,
+        0x28 => Key::ArrowDown,
+        0x2d => Key::Insert,
+        0x2e => Key::Delete,
+        // Digits and letters are their ASCII codes.
+        0x30..=0x39 | 0x41..=0x5a => return key_for_char(char::from(vk as u8)),
+        _ => return None,
+    })
+}
@@ -7,7 +7,7 @@ use proc_macro2::TokenStream;
 use quote::quote;
 
 use crate::ir::{
-    FieldIR, ParameterDefault, ParameterFieldIR, ParameterKind, ParametersIR, SmoothingStyle,
+    FieldIR, ParameterDefault, ParameterFieldIR, ParameterKind, ParametersIR, RandomizeSpec, SmoothingStyle,
 };
 
 /// Generate all code for the derive macro.
@@ -19,6 +19,7 @@ pub fn generate(ir: &ParametersIR) -> TokenStream {
     let vst3_parameters_impl = generate_parameter_store_impl(ir);
     let parameters_impl = generate_parameters_impl(ir);
     let set_group_ids_impl = generate_set_group_ids(ir);
+    let group_controls_impl = generate_group_controls(ir);
     let default_impl = generate_default_impl(ir);
 
     quote! {
@@ -29,6 +30,7 @@ pub fn generate(ir: &ParametersIR) -> TokenStream {
         #vst3_parameters_impl
         #parameters_impl
         #set_group_ids_impl
+        #group_controls_impl
         #default_impl
     }
 }
@@ -139,15 +141,30 @@ fn generate_groups_impl(ir: &ParametersIR) -> TokenStream {
         .map(|(idx, group_name)| {
             let group_id = (idx + 1) as i32;
             quote! {
-                #idx => Some(::beamer::core::parameter_groups::GroupInfo {
-                    id: #group_id,
-                    name: #group_name,
-                    parent_id: 0, // All flat groups are children of root
-                }),
+                // All flat groups are children of root
+                #idx => Some(::beamer::core::parameter_groups::GroupInfo::new(#group_id, #group_name, 0)),
             }
         })
         .collect();
 
+    // Bypass/solo queries delegate to the #[group_controls] field
+    let group_control_hooks = match &ir.group_controls {
+        Some(field) => quote! {
+            fn is_group_bypassed(&self, group_id: ::beamer::core::parameter_groups::GroupId) -> bool {
+                ::beamer::core::parameter_groups::ParameterGroups::is_group_bypassed(&self.#field, group_id)
+            }
+
+            fn is_group_soloed(&self, group_id: ::beamer::core::parameter_groups::GroupId) -> bool {
+                ::beamer::core::parameter_groups::ParameterGroups::is_group_soloed(&self.#field, group_id)
+            }
+
+            fn any_group_soloed(&self) -> bool {
+                ::beamer::core::parameter_groups::ParameterGroups::any_group_soloed(&self.#field)
+            }
+        },
+        None => quote! {},
+    };
+
     if has_nested {
         // Flat groups + nested groups: combine static flat groups with dynamic nested collection
         quote! {
@@ -182,6 +199,8 @@ fn generate_groups_impl(ir: &ParametersIR) -> TokenStream {
                     let nested_idx = index - 1 - #flat_group_count;
                     units.get(nested_idx).cloned()
                 }
+
+                #group_control_hooks
             }
         }
     } else {
@@ -265,6 +284,18 @@ fn generate_set_group_ids(ir: &ParametersIR) -> TokenStream {
         quote! {}
     };
 
+    // Group controls follow the runtime group IDs, so bind them last
+    let controls_init = match &ir.group_controls {
+        Some(field) => quote! {
+            let groups: Vec<::beamer::core::parameter_groups::GroupInfo> =
+                (0..::beamer::core::parameter_groups::ParameterGroups::group_count(self))
+                    .filter_map(|i| ::beamer::core::parameter_groups::ParameterGroups::group_info(self, i))
+                    .collect();
+            self.#field.bind_groups(&groups);
+        },
+        None => quote! {},
+    };
+
     quote! {
         impl #impl_generics #struct_name #ty_generics #where_clause {
             /// Initialize group IDs for all parameters.
@@ -286,6 +317,7 @@ fn generate_set_group_ids(ir: &ParametersIR) -> TokenStream {
                 #(#flat_group_assignments)*
                 // Initialize nested groups
                 #nested_init
+                #controls_init
             }
         }
     }
@@ -349,6 +381,7 @@ fn generate_parameters_impl(ir: &ParametersIR) -> TokenStream {
     let nested_discovery_impl = generate_nested_discovery(ir);
     let set_sample_rate_impl = generate_set_sample_rate(ir);
     let reset_smoothing_impl = generate_reset_smoothing(ir);
+    let advance_smoothing_impl = generate_advance_smoothing(ir);
 
     quote! {
         impl #impl_generics ::beamer::core::parameter_types::Parameters for #struct_name #ty_generics #where_clause {
@@ -379,6 +412,7 @@ fn generate_parameters_impl(ir: &ParametersIR) -> TokenStream {
             #set_sample_rate_impl
 
             #reset_smoothing_impl
+            #advance_smoothing_impl
         }
     }
 }
@@ -443,6 +477,37 @@ fn generate_nested_discovery(ir: &ParametersIR) -> TokenStream {
         })
         .collect();
 
+    // Generate match arms for nested_group_hints(), for groups declaring any
+    let hint_match_arms: Vec<TokenStream> = ir
+        .nested_fields()
+        .enumerate()
+        .filter(|(_, nested)| nested.has_hints())
+        .map(|(idx, nested)| {
+            let order = nested.order;
+            let collapsed = nested.collapsed;
+            let icon = nested.icon.as_deref().unwrap_or("");
+            quote! {
+                #idx => ::beamer::core::parameter_groups::GroupHints {
+                    order: #order,
+                    collapsed: #collapsed,
+                    icon: #icon,
+                },
+            }
+        })
+        .collect();
+    let hints_fn = if hint_match_arms.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn nested_group_hints(&self, index: usize) -> ::beamer::core::parameter_groups::GroupHints {
+                match index {
+                    #(#hint_match_arms)*
+                    _ => ::beamer::core::parameter_groups::GroupHints::new(),
+                }
+            }
+        }
+    };
+
     quote! {
         fn nested_count(&self) -> usize {
             #nested_count
@@ -461,6 +526,8 @@ fn generate_nested_discovery(ir: &ParametersIR) -> TokenStream {
                 _ => None,
             }
         }
+
+        #hints_fn
     }
 }
 
@@ -478,8 +545,12 @@ fn generate_count(ir: &ParametersIR) -> TokenStream {
             })
             .collect();
 
+        let controls_count = ir.group_controls.as_ref().map(|field| {
+            quote! { + ::beamer::core::parameter_types::Parameters::count(&self.#field) }
+        });
+
         quote! {
-            #parameter_count #(+ #nested_counts)*
+            #parameter_count #(+ #nested_counts)* #controls_count
         }
     } else {
         quote! { #parameter_count }
@@ -504,6 +575,11 @@ fn generate_iter(ir: &ParametersIR) -> TokenStream {
         })
         .collect();
 
+    // Group control switches come after all other parameters
+    let controls_chain = ir.group_controls.as_ref().map(|field| {
+        quote! { .chain(::beamer::core::parameter_types::Parameters::iter(&self.#field)) }
+    });
+
     if parameter_iters.is_empty() && nested_chains.is_empty() {
         quote! { Box::new(::std::iter::empty()) }
     } else if parameter_iters.is_empty() {
@@ -518,13 +594,14 @@ fn generate_iter(ir: &ParametersIR) -> TokenStream {
             })
             .collect();
         quote! {
-            Box::new(self.#first_nested.iter() #(#rest_nested)*)
+            Box::new(self.#first_nested.iter() #(#rest_nested)* #controls_chain)
         }
     } else {
         quote! {
             Box::new(
                 [#(#parameter_iters),*].into_iter()
                     #(#nested_chains)*
+                    #controls_chain
             )
         }
     }
@@ -547,10 +624,11 @@ fn generate_by_id(ir: &ParametersIR) -> TokenStream {
 
     let nested_lookups: Vec<TokenStream> = ir
         .nested_fields()
-        .map(|nested| {
-            let field = &nested.field_name;
+        .map(|nested| &nested.field_name)
+        .chain(ir.group_controls.as_ref())
+        .map(|field| {
             quote! {
-                if let Some(parameter) = self.#field.by_id(id) {
+                if let Some(parameter) = ::beamer::core::parameter_types::Parameters::by_id(&self.#field, id) {
                     return Some(parameter);
                 }
             }
@@ -585,16 +663,19 @@ fn generate_save_state(ir: &ParametersIR) -> TokenStream {
             let field = &parameter.field_name;
             let id_str = &parameter.string_id;
             quote! {
-                // Build path: prefix + "/" + id (or just id if prefix is empty)
-                let path = if prefix.is_empty() {
-                    #id_str.to_string()
-                } else {
-                    format!("{}/{}", prefix, #id_str)
-                };
-                let path_bytes = path.as_bytes();
-                data.push(path_bytes.len() as u8);
-                data.extend_from_slice(path_bytes);
-                data.extend_from_slice(&self.#field.get_normalized().to_le_bytes());
+                // Transient and no_save parameters are not persisted
+                if ::beamer::core::parameter_types::ParameterRef::flags(&self.#field).is_persistent {
+                    // Build path: prefix + "/" + id (or just id if prefix is empty)
+                    let path = if prefix.is_empty() {
+                        #id_str.to_string()
+                    } else {
+                        format!("{}/{}", prefix, #id_str)
+                    };
+                    let path_bytes = path.as_bytes();
+                    data.push(path_bytes.len() as u8);
+                    data.extend_from_slice(path_bytes);
+                    data.extend_from_slice(&self.#field.get_normalized().to_le_bytes());
+                }
             }
         })
         .collect();
@@ -617,6 +698,12 @@ fn generate_save_state(ir: &ParametersIR) -> TokenStream {
         })
         .collect();
 
+    let controls_save = ir.group_controls.as_ref().map(|field| {
+        quote! {
+            ::beamer::core::parameter_types::Parameters::save_state_prefixed(&self.#field, data, prefix);
+        }
+    });
+
     let parameter_count = ir.parameter_count();
     // Estimate capacity: ~20 bytes per parameter (path_len + avg 10 char path + 8 byte f64)
     let estimated_capacity = parameter_count * 20;
@@ -625,6 +712,7 @@ fn generate_save_state(ir: &ParametersIR) -> TokenStream {
         fn save_state_prefixed(&self, data: &mut Vec<u8>, prefix: &str) {
             #(#parameter_saves)*
             #(#nested_saves)*
+            #controls_save
         }
 
         fn save_state(&self) -> Vec<u8> {
@@ -648,7 +736,10 @@ fn generate_load_state(ir: &ParametersIR) -> TokenStream {
             let id_str = &parameter.string_id;
             quote! {
                 #id_str => {
-                    self.#field.set_normalized(value.clamp(0.0, 1.0));
+                    // Ignore values for parameters excluded from state
+                    if ::beamer::core::parameter_types::ParameterRef::flags(&self.#field).is_persistent {
+                        self.#field.set_normalized(value.clamp(0.0, 1.0));
+                    }
                     true
                 }
             }
@@ -682,13 +773,21 @@ fn generate_load_state(ir: &ParametersIR) -> TokenStream {
         }
     };
 
+    // Group control paths ("Low.bypass") have no slash, so they fall through here
+    let direct_fallback = match &ir.group_controls {
+        Some(field) => quote! {
+            ::beamer::core::parameter_types::Parameters::load_state_path(&mut self.#field, path, value)
+        },
+        None => quote! { false },
+    };
+
     let direct_matching = if direct_match_arms.is_empty() {
-        quote! { false }
+        direct_fallback
     } else {
         quote! {
             match path {
                 #(#direct_match_arms)*
-                _ => false
+                _ => #direct_fallback
             }
         }
     };
@@ -710,6 +809,13 @@ fn generate_load_state(ir: &ParametersIR) -> TokenStream {
         }
 
         fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+            // Transient and no_save parameters always start at their default
+            for parameter in ::beamer::core::parameter_types::Parameters::iter(self) {
+                if !parameter.flags().is_persistent {
+                    parameter.set_normalized(parameter.default_normalized());
+                }
+            }
+
             if data.is_empty() {
                 return Ok(());
             }
@@ -877,6 +983,14 @@ fn generate_info(ir: &ParametersIR) -> TokenStream {
             })
             .collect();
 
+        let controls_info = ir.group_controls.as_ref().map(|field| {
+            quote! {
+                if let Some(parameter) = ::beamer::core::parameter_types::Parameters::iter(&self.#field).nth(adjusted_index) {
+                    return Some(::beamer::core::parameter_types::ParameterRef::info(parameter));
+                }
+            }
+        });
+
         quote! {
             fn info(&self, index: usize) -> Option<&::beamer::core::parameter_info::ParameterInfo> {
                 // First check direct parameters
@@ -886,6 +1000,7 @@ fn generate_info(ir: &ParametersIR) -> TokenStream {
                         // Adjust index for nested parameters
                         let mut adjusted_index = index - #parameter_count;
                         #(#nested_infos)*
+                        #controls_info
                         None
                     }
                 }
@@ -969,6 +1084,81 @@ fn generate_reset_smoothing(ir: &ParametersIR) -> TokenStream {
     }
 }
 
+/// Generate the `advance_smoothing()` method for the Parameters trait.
+fn generate_advance_smoothing(ir: &ParametersIR) -> TokenStream {
+    // Only parameters with automatic smoothing need advancing
+    let parameter_calls: Vec<TokenStream> = ir
+        .parameter_fields()
+        .filter(|parameter| parameter.attributes.smoothing.as_ref().is_some_and(|s| s.auto))
+        .map(|parameter| {
+            let field = &parameter.field_name;
+            quote! { self.#field.advance_smoothing(samples); }
+        })
+        .collect();
+
+    // Generate calls for nested fields
+    let nested_calls: Vec<TokenStream> = ir
+        .nested_fields()
+        .map(|nested| {
+            let field = &nested.field_name;
+            quote! { self.#field.advance_smoothing(samples); }
+        })
+        .collect();
+
+    if parameter_calls.is_empty() && nested_calls.is_empty() {
+        // No automatic smoothers = use default no-op
+        quote! {}
+    } else {
+        quote! {
+            fn advance_smoothing(&mut self, samples: usize) {
+                #(#parameter_calls)*
+                #(#nested_calls)*
+            }
+        }
+    }
+}
+
+/// Generate the `group_controls()` constructor for the `#[group_controls]` field.
+///
+/// Creates a bypass and solo switch for every `#[nested(..., controls)]` group.
+fn generate_group_controls(ir: &ParametersIR) -> TokenStream {
+    if ir.group_controls.is_none() {
+        return quote! {};
+    }
+
+    let struct_name = &ir.struct_name;
+    let (impl_generics, ty_generics, where_clause) = ir.generics.split_for_impl();
+
+    let switches: Vec<TokenStream> = ir
+        .controlled_groups()
+        .map(|nested| {
+            let group_name = &nested.group_name;
+            let bypass_name = format!("{} Bypass", group_name);
+            let solo_name = format!("{} Solo", group_name);
+            let [bypass_id, solo_id] = nested.control_string_ids();
+            quote! {
+                ::beamer::core::group_controls::GroupSwitch::new(
+                    #group_name, #bypass_name, #solo_name, #bypass_id, #solo_id,
+                )
+            }
+        })
+        .collect();
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Create the bypass/solo switches for groups declared with `controls`.
+            ///
+            /// Use this to initialize the `#[group_controls]` field in a manual
+            /// `Default` implementation, then call `set_group_ids()`.
+            pub fn group_controls() -> ::beamer::core::group_controls::GroupControls {
+                ::beamer::core::group_controls::GroupControls::new(vec![
+                    #(#switches),*
+                ])
+            }
+        }
+    }
+}
+
 // =============================================================================
 // Default Implementation Generation
 // =============================================================================
@@ -1000,6 +1190,10 @@ fn generate_default_impl(ir: &ParametersIR) -> TokenStream {
         })
         .collect();
 
+    let controls_init = ir.group_controls.as_ref().map(|field| {
+        quote! { , #field: Self::group_controls() }
+    });
+
     // Add set_group_ids() call if there are groups (flat or nested)
     let group_id_init = if ir.has_nested() || ir.has_flat_groups() {
         quote! {
@@ -1014,6 +1208,7 @@ fn generate_default_impl(ir: &ParametersIR) -> TokenStream {
             fn default() -> Self {
                 let mut parameters = Self {
                     #(#field_inits),*
+                    #controls_init
                 };
                 #group_id_init
                 parameters
@@ -1044,6 +1239,7 @@ fn generate_constructor(parameter: &ParameterFieldIR) -> TokenStream {
         crate::ir::ParameterType::Int => generate_int_constructor(parameter),
         crate::ir::ParameterType::Bool => generate_bool_constructor(parameter),
         crate::ir::ParameterType::Enum => generate_enum_constructor(parameter),
+        crate::ir::ParameterType::TimeInSamples => generate_time_in_samples_constructor(parameter),
     }
 }
 
@@ -1173,6 +1369,22 @@ fn generate_enum_constructor(parameter: &ParameterFieldIR) -> TokenStream {
     }
 }
 
+/// Generate constructor for TimeInSamples (default and range in seconds).
+fn generate_time_in_samples_constructor(parameter: &ParameterFieldIR) -> TokenStream {
+    let name = parameter.attributes.name.as_ref().expect("TimeInSamples requires name");
+    let default = match &parameter.attributes.default {
+        Some(ParameterDefault::Float(v)) => *v,
+        Some(ParameterDefault::Int(v)) => *v as f64,
+        _ => 0.0,
+    };
+    let range = parameter.attributes.range.as_ref().expect("TimeInSamples requires range");
+    let start = range.start;
+    let end = range.end;
+    quote! {
+        ::beamer::core::parameter_types::TimeInSamples::new(#name, #default, #start..=#end)
+    }
+}
+
 /// Generate the builder method chain (.with_id(), .with_short_name(), .with_smoother(), .with_step_size()).
 fn generate_builder_chain(parameter: &ParameterFieldIR, struct_name: &syn::Ident) -> TokenStream {
     let const_name = parameter.const_name();
@@ -1205,7 +1417,11 @@ fn generate_builder_chain(parameter: &ParameterFieldIR, struct_name: &syn::Ident
                     quote! { ::beamer::core::smoothing::SmoothingStyle::Linear(#time_ms) }
                 }
             };
-            quote! { .with_smoother(#style) }
+            if s.auto {
+                quote! { .with_auto_smoother(#style) }
+            } else {
+                quote! { .with_smoother(#style) }
+            }
         })
     } else {
         None
@@ -1220,11 +1436,41 @@ fn generate_builder_chain(parameter: &ParameterFieldIR, struct_name: &syn::Ident
         None
     };
 
+    // Optional: .transient() / .no_save()
+    let persistence = if parameter.attributes.transient {
+        Some(quote! { .transient() })
+    } else if parameter.attributes.no_save {
+        Some(quote! { .no_save() })
+    } else {
+        None
+    };
+
+    // Optional: .internal()
+    let internal = parameter.attributes.internal.then(|| quote! { .internal() });
+
+    // Optional: .no_randomize() / .with_randomize_range()
+    let randomize = parameter.attributes.randomize.as_ref().map(|spec| match spec {
+        RandomizeSpec::Off => quote! { .no_randomize() },
+        RandomizeSpec::Range(range) if parameter.parameter_type == crate::ir::ParameterType::Int => {
+            let start = range.start as i64;
+            let end = range.end as i64;
+            quote! { .with_randomize_range(#start..=#end) }
+        }
+        RandomizeSpec::Range(range) => {
+            let start = range.start;
+            let end = range.end;
+            quote! { .with_randomize_range(#start..=#end) }
+        }
+    });
+
     quote! {
         #with_id
         #with_string_id
         #with_short_name
         #with_smoother
         #with_step_size
+        #persistence
+        #internal
+        #randomize
     }
 }
@@ -37,9 +37,92 @@ pub struct ConfigFile {
     /// Maximum size of each SysEx message in bytes (default: 512).
     #[serde(default)]
     pub sysex_buffer_size: Option<usize>,
+    /// Declicking fade around host preset/program changes, in milliseconds.
+    #[serde(default)]
+    pub preset_crossfade_ms: Option<f32>,
+    /// Add the framework's input trim, output trim and phase invert parameters.
+    #[serde(default)]
+    pub standard_trims: Option<bool>,
+    /// Publish DSP load, voice count and underruns as read-only parameters.
+    #[serde(default)]
+    pub processing_stats: Option<bool>,
     /// Background color shown while web content loads (hex string, e.g. "#1a1a2e").
     #[serde(default)]
     pub gui_background_color: Option<String>,
+    /// Default log filter (e.g. "info" or "warn,my_plugin=debug").
+    #[serde(default)]
+    pub log_filter: Option<String>,
+    /// Exact sample rates the DSP supports (e.g. [44100, 48000]).
+    #[serde(default)]
+    pub sample_rates: Option<Vec<u32>>,
+    /// Supported sample rate range as [min, max] in Hz.
+    #[serde(default)]
+    pub sample_rate_range: Option<(u32, u32)>,
+    /// Additional named editor views (e.g. a compact meter view).
+    #[serde(default)]
+    pub gui_views: Option<Vec<GuiViewEntry>>,
+    /// Conversion of the main input to the output's width: "average",
+    /// "equal-power", "sum" or "first".
+    #[serde(default)]
+    pub channel_mix: Option<String>,
+    /// Deadline watchdog for `process()` (`[watchdog]` table).
+    #[serde(default)]
+    pub watchdog: Option<WatchdogEntry>,
+    /// Values used by the AU wrapper only (`[au]` table).
+    #[serde(default)]
+    pub au: Option<FormatOverrideEntry>,
+    /// Values used by the VST3 wrapper only (`[vst3]` table).
+    #[serde(default)]
+    pub vst3: Option<FormatOverrideEntry>,
+}
+
+/// The `[au]` and `[vst3]` tables in Config.toml.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FormatOverrideEntry {
+    /// Plugin display name in this format.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Subcategory strings in this format.
+    #[serde(default)]
+    pub subcategories: Option<Vec<String>>,
+    /// Number of SysEx output slots per process block.
+    #[serde(default)]
+    pub sysex_slots: Option<usize>,
+    /// Maximum size of each SysEx message in bytes.
+    #[serde(default)]
+    pub sysex_buffer_size: Option<usize>,
+    /// Declicking fade around host preset/program changes, in milliseconds.
+    #[serde(default)]
+    pub preset_crossfade_ms: Option<f32>,
+}
+
+/// The `[watchdog]` table in Config.toml.
+#[derive(Deserialize)]
+pub struct WatchdogEntry {
+    /// Time `process()` at all (default true).
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Share of the block duration a call may take (default 1.0).
+    #[serde(default)]
+    pub load: Option<f64>,
+    /// Overruns within one second that raise a report (default 3).
+    #[serde(default)]
+    pub repeats: Option<u32>,
+    /// Also show a GUI notification (default false).
+    #[serde(default)]
+    pub notify: Option<bool>,
+}
+
+/// An additional editor view from `[[gui_views]]` in Config.toml.
+#[derive(Deserialize)]
+pub struct GuiViewEntry {
+    /// View name requested by the host (e.g. "mini").
+    pub name: String,
+    /// Entry page within the webview assets (e.g. "mini.html").
+    pub page: String,
+    /// Initial view size as [width, height] in pixels.
+    pub size: (u32, u32),
 }
 
 /// Presets file from Presets.toml.
@@ -87,6 +170,27 @@ fn validate_uuid(uuid: &str, field: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn validate_gui_views(views: &[GuiViewEntry]) -> Result<(), String> {
+    for (i, view) in views.iter().enumerate() {
+        if view.name.is_empty() || view.name == "editor" {
+            return Err(format!(
+                "gui_views name must be non-empty and not \"editor\" (the main view), got {:?}",
+                view.name
+            ));
+        }
+        if views[..i].iter().any(|other| other.name == view.name) {
+            return Err(format!("duplicate gui_views name {:?}", view.name));
+        }
+        if view.page.is_empty() {
+            return Err(format!("gui_views {:?} must set a page (e.g. \"mini.html\")", view.name));
+        }
+        if view.size.0 == 0 || view.size.1 == 0 {
+            return Err(format!("gui_views {:?} size must be non-zero", view.name));
+        }
+    }
+    Ok(())
+}
+
 impl ConfigFile {
     /// Validate the config file contents.
     pub fn validate(&self) -> Result<(), String> {
@@ -121,6 +225,56 @@ impl ConfigFile {
                     .to_string(),
             );
         }
+        if let Some(views) = &self.gui_views {
+            validate_gui_views(views)?;
+        }
+        for (table, ms) in [
+            ("", self.preset_crossfade_ms),
+            ("au.", self.au.as_ref().and_then(|au| au.preset_crossfade_ms)),
+            ("vst3.", self.vst3.as_ref().and_then(|vst3| vst3.preset_crossfade_ms)),
+        ] {
+            if let Some(ms) = ms {
+                if !(0.0..=1000.0).contains(&ms) {
+                    return Err(format!(
+                        "{table}preset_crossfade_ms must be between 0 and 1000, got {ms}"
+                    ));
+                }
+            }
+        }
+        for (table, entry) in [("au", &self.au), ("vst3", &self.vst3)] {
+            if entry.as_ref().and_then(|entry| entry.name.as_deref()) == Some("") {
+                return Err(format!("{table}.name must not be empty"));
+            }
+        }
+        if self.sample_rates.is_some() && self.sample_rate_range.is_some() {
+            return Err("set either sample_rates or sample_rate_range, not both".to_string());
+        }
+        if let Some(rates) = &self.sample_rates {
+            if rates.is_empty() || rates.contains(&0) {
+                return Err("sample_rates must list at least one non-zero rate".to_string());
+            }
+        }
+        if let Some((min, max)) = self.sample_rate_range {
+            if min == 0 || min > max {
+                return Err(format!(
+                    "sample_rate_range must be [min, max] with 0 < min <= max, got [{min}, {max}]"
+                ));
+            }
+        }
+        if let Some(mix) = &self.channel_mix {
+            let valid_mixes = ["average", "equal-power", "sum", "first"];
+            if !valid_mixes.contains(&mix.as_str()) {
+                return Err(format!("channel_mix must be one of {:?}, got {:?}", valid_mixes, mix));
+            }
+        }
+        if let Some(watchdog) = &self.watchdog {
+            if watchdog.load.is_some_and(|load| !(load > 0.0 && load.is_finite())) {
+                return Err(format!("watchdog.load must be positive, got {:?}", watchdog.load));
+            }
+            if watchdog.repeats == Some(0) {
+                return Err("watchdog.repeats must be at least 1".to_string());
+            }
+        }
         Ok(())
     }
 }
@@ -0,0 +1,212 @@
+//! `process_graph!` - declarative routing of processor stages.
+//!
+//! Parses chains like `main -> eq -> comp -> out;` and
+//! `sidechain -> follower -> comp.sidechain;`, validates the routing and
+//! expands to direct calls of the `beamer::core::graph` traits.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, Token};
+
+const MAIN: &str = "main";
+const SIDECHAIN: &str = "sidechain";
+const OUT: &str = "out";
+
+/// Where a chain ends.
+enum Sink {
+    /// The main output.
+    Out,
+    /// The sidechain input of a main chain stage.
+    Sidechain(Ident),
+}
+
+struct Chain {
+    source: Ident,
+    stages: Vec<Ident>,
+    sink: Sink,
+}
+
+struct Graph {
+    chains: Vec<Chain>,
+}
+
+impl Parse for Chain {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let source: Ident = input.parse()?;
+        if source != MAIN && source != SIDECHAIN {
+            return Err(syn::Error::new(
+                source.span(),
+                "chains start at `main` (main input) or `sidechain` (sidechain bus)",
+            ));
+        }
+
+        let mut stages = Vec::new();
+        loop {
+            if input.is_empty() || input.peek(Token![;]) {
+                return Err(input.error("chains end at `out` or `<stage>.sidechain`"));
+            }
+            input.parse::<Token![->]>()?;
+            let node: Ident = input.parse()?;
+
+            if input.peek(Token![.]) {
+                input.parse::<Token![.]>()?;
+                let port: Ident = input.parse()?;
+                if port != SIDECHAIN {
+                    return Err(syn::Error::new(
+                        port.span(),
+                        format!("unknown input `{}` (stages have a `sidechain` input)", port),
+                    ));
+                }
+                return Ok(Self {
+                    source,
+                    stages,
+                    sink: Sink::Sidechain(node),
+                });
+            }
+            if node == OUT {
+                return Ok(Self {
+                    source,
+                    stages,
+                    sink: Sink::Out,
+                });
+            }
+            if node == MAIN || node == SIDECHAIN {
+                return Err(syn::Error::new(
+                    node.span(),
+                    format!("`{}` is an input and can only start a chain", node),
+                ));
+            }
+            stages.push(node);
+        }
+    }
+}
+
+impl Parse for Graph {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let chains = input.parse_terminated(Chain::parse, Token![;])?;
+        Ok(Self {
+            chains: chains.into_iter().collect(),
+        })
+    }
+}
+
+/// Check the routing: one main chain, stages used once, sidechains keying
+/// main chain stages.
+fn validate(graph: &Graph) -> syn::Result<()> {
+    let mut main_chains = graph.chains.iter().filter(|c| matches!(c.sink, Sink::Out));
+    let Some(main) = main_chains.next() else {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "the graph needs a `main -> ... -> out` chain",
+        ));
+    };
+    if let Some(second) = main_chains.next() {
+        return Err(syn::Error::new(
+            second.source.span(),
+            "only one chain can end at `out`",
+        ));
+    }
+    if main.source != MAIN {
+        return Err(syn::Error::new(
+            main.source.span(),
+            "the chain ending at `out` must start at `main`",
+        ));
+    }
+
+    let mut seen: Vec<&Ident> = Vec::new();
+    for stage in graph.chains.iter().flat_map(|c| &c.stages) {
+        if seen.contains(&stage) {
+            return Err(syn::Error::new(
+                stage.span(),
+                format!("`{}` is used more than once", stage),
+            ));
+        }
+        seen.push(stage);
+    }
+
+    let mut keyed: Vec<&Ident> = Vec::new();
+    for chain in &graph.chains {
+        let Sink::Sidechain(target) = &chain.sink else {
+            continue;
+        };
+        if !main.stages.contains(target) {
+            return Err(syn::Error::new(
+                target.span(),
+                format!("`{}` is not a stage of the main chain", target),
+            ));
+        }
+        if keyed.contains(&target) {
+            return Err(syn::Error::new(
+                target.span(),
+                format!("`{}.sidechain` has more than one source", target),
+            ));
+        }
+        // The main chain borrows the buffer mutably; a detector copies the
+        // dry input out first.
+        if chain.source == MAIN && chain.stages.is_empty() {
+            return Err(syn::Error::new(
+                target.span(),
+                "route `main` to a sidechain through a detector stage",
+            ));
+        }
+        keyed.push(target);
+    }
+    Ok(())
+}
+
+/// Expand `process_graph!`.
+pub fn process_graph_impl(input: TokenStream) -> syn::Result<TokenStream> {
+    let graph: Graph = syn::parse2(input)?;
+    validate(&graph)?;
+
+    let core = quote!(::beamer::core::graph);
+    let mut keys = Vec::new();
+    let mut key_names = Vec::new();
+    for chain in &graph.chains {
+        let Sink::Sidechain(target) = &chain.sink else {
+            continue;
+        };
+        let source = if chain.source == MAIN {
+            quote_spanned!(chain.source.span()=> #core::KeySignal::main_input(&buffer))
+        } else {
+            quote_spanned!(chain.source.span()=> #core::KeySignal::sidechain(&aux))
+        };
+        let detects = chain.stages.iter().map(|stage| {
+            quote_spanned!(stage.span()=> let key = #core::Detector::detect(&mut self.#stage, &key);)
+        });
+        let name = quote::format_ident!("__beamer_key_{}", target);
+        keys.push(quote! {
+            let #name = {
+                let key = #source;
+                #(#detects)*
+                key
+            };
+        });
+        key_names.push((target.clone(), name));
+    }
+
+    let main = graph
+        .chains
+        .iter()
+        .find(|c| matches!(c.sink, Sink::Out))
+        .expect("validated");
+    let stages = main.stages.iter().map(|stage| {
+        match key_names.iter().find(|(target, _)| target == stage) {
+            Some((_, key)) => quote_spanned! {stage.span()=>
+                #core::KeyedBlock::process_keyed(&mut self.#stage, buffer, &#key);
+            },
+            None => quote_spanned! {stage.span()=>
+                #core::Block::process(&mut self.#stage, buffer);
+            },
+        }
+    });
+
+    Ok(quote! {
+        {
+            #(#keys)*
+            buffer.copy_to_output();
+            #(#stages)*
+        }
+    })
+}
@@ -2,6 +2,7 @@
 //!
 //! This module provides the `#[derive(HasParameters)]` macro that automatically
 //! implements the `HasParameters` trait for structs with a `#[parameters]` field.
+//! Fields marked `#[rate_dependent]` are recomputed in `recompute_rate_dependent()`.
 
 use proc_macro2::TokenStream;
 use quote::quote;
@@ -45,10 +46,15 @@ pub fn derive_has_parameters_impl(input: DeriveInput) -> syn::Result<TokenStream
         }
     };
 
-    // Find the field marked with #[parameters]
+    // Find the field marked with #[parameters] and the #[rate_dependent] fields
     let mut parameters_field: Option<(&Ident, &Type)> = None;
+    let mut rate_dependent_fields: Vec<&Ident> = Vec::new();
 
     for field in fields {
+        if field.attrs.iter().any(|attr| attr.path().is_ident("rate_dependent")) {
+            rate_dependent_fields.push(field.ident.as_ref().expect("Named fields must have identifiers"));
+        }
+
         let has_parameters_attr = field.attrs.iter().any(|attr| attr.path().is_ident("parameters"));
 
         if has_parameters_attr {
@@ -80,6 +86,22 @@ pub fn derive_has_parameters_impl(input: DeriveInput) -> syn::Result<TokenStream
         )
     })?;
 
+    let recompute_rate_dependent = if rate_dependent_fields.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn recompute_rate_dependent(&mut self, sample_rate: f64) {
+                #(
+                    ::beamer::core::rate_dependent::RateDependent::recompute(
+                        &mut self.#rate_dependent_fields,
+                        sample_rate,
+                        &self.#field_name,
+                    );
+                )*
+            }
+        }
+    };
+
     Ok(quote! {
         impl #impl_generics ::beamer::core::plugin::HasParameters for #struct_name #ty_generics #where_clause {
             type Parameters = #field_type;
@@ -95,6 +117,8 @@ pub fn derive_has_parameters_impl(input: DeriveInput) -> syn::Result<TokenStream
             fn set_parameters(&mut self, params: Self::Parameters) {
                 self.#field_name = params;
             }
+
+            #recompute_rate_dependent
         }
     })
 }
@@ -9,7 +9,7 @@ use quote::{format_ident, quote};
 
 use beamer_utils::fnv1a_32;
 
-use crate::config_file::{ConfigFile, PresetsFile};
+use crate::config_file::{ConfigFile, FormatOverrideEntry, PresetsFile};
 
 /// Map a category string from Config.toml to the corresponding token stream.
 fn category_tokens(category: &str) -> TokenStream {
@@ -79,6 +79,19 @@ fn scan_webview_dir(
     Ok(files)
 }
 
+/// Check that every `[[gui_views]]` page exists in the embedded assets.
+fn check_view_pages(config: &ConfigFile, assets: &[String]) -> Result<(), String> {
+    for view in config.gui_views.iter().flatten() {
+        if !assets.contains(&view.page) {
+            return Err(format!(
+                "gui_views {:?}: page {:?} not found in webview assets",
+                view.name, view.page
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn scan_dir_recursive(
     base: &std::path::Path,
     dir: &std::path::Path,
@@ -191,6 +204,7 @@ fn generate_config(config: &ConfigFile, manifest_dir: &str) -> Result<TokenStrea
     } else if has_package_json && dist_dir.exists() {
         // Framework project with built output: embed from dist/
         let assets = scan_webview_dir(&dist_dir, manifest_dir)?;
+        check_view_pages(config, &assets)?;
         if assets.is_empty() {
             (None, None, false)
         } else {
@@ -200,6 +214,7 @@ fn generate_config(config: &ConfigFile, manifest_dir: &str) -> Result<TokenStrea
     } else if !has_package_json && webview_dir.exists() {
         // Plain HTML project: embed from webview/
         let assets = scan_webview_dir(&webview_dir, manifest_dir)?;
+        check_view_pages(config, &assets)?;
         if assets.is_empty() {
             (None, None, false)
         } else {
@@ -223,6 +238,21 @@ fn generate_config(config: &ConfigFile, manifest_dir: &str) -> Result<TokenStrea
         quote! { .with_gui_size(#w, #h) }
     });
 
+    let gui_views = config.gui_views.as_ref().map(|views| {
+        let entries: Vec<TokenStream> = views
+            .iter()
+            .map(|view| {
+                let name = &view.name;
+                let page = &view.page;
+                let (w, h) = view.size;
+                quote! {
+                    ::beamer::core::GuiView { name: #name, page: #page, width: #w, height: #h }
+                }
+            })
+            .collect();
+        quote! { .with_gui_views(&[#(#entries),*]) }
+    });
+
     let vst3_id = config.vst3_id.as_ref().map(|id| {
         quote! { .with_vst3_id(#id) }
     });
@@ -238,6 +268,53 @@ fn generate_config(config: &ConfigFile, manifest_dir: &str) -> Result<TokenStrea
         quote! { .with_sysex_buffer_size(#size) }
     });
 
+    let preset_crossfade = config.preset_crossfade_ms.map(|ms| {
+        quote! { .with_preset_crossfade(#ms) }
+    });
+
+    let standard_trims = (config.standard_trims == Some(true)).then(|| {
+        quote! { .with_standard_trims() }
+    });
+
+    let processing_stats = (config.processing_stats == Some(true)).then(|| {
+        quote! { .with_processing_stats() }
+    });
+
+    let sample_rates = config.sample_rates.as_ref().map(|rates| {
+        let rates = rates.iter().map(|&rate| rate as f64);
+        quote! { .with_sample_rates(&[#(#rates),*]) }
+    });
+    let sample_rate_range = config.sample_rate_range.map(|(min, max)| {
+        let (min, max) = (min as f64, max as f64);
+        quote! { .with_sample_rate_range(#min, #max) }
+    });
+
+    let channel_mix = config.channel_mix.as_deref().map(|mix| {
+        let variant = match mix {
+            "equal-power" => quote! { EqualPower },
+            "sum" => quote! { Sum },
+            "first" => quote! { First },
+            _ => quote! { Average },
+        };
+        quote! { .with_channel_mix(::beamer::core::ChannelMix::#variant) }
+    });
+
+    let watchdog = config.watchdog.as_ref().map(|watchdog| {
+        let base = if watchdog.enabled == Some(false) {
+            quote! { ::beamer::core::WatchdogSettings::disabled() }
+        } else {
+            quote! { ::beamer::core::WatchdogSettings::new() }
+        };
+        let load = watchdog.load.map(|load| quote! { .with_load(#load) });
+        let repeats = watchdog.repeats.map(|repeats| quote! { .with_repeats(#repeats) });
+        let notify = (watchdog.notify == Some(true)).then(|| quote! { .with_notification() });
+        quote! { .with_watchdog(#base #load #repeats #notify) }
+    });
+
+    let log_filter = config.log_filter.as_ref().map(|filter| {
+        quote! { .with_log_filter(#filter) }
+    });
+
     let gui_background_color = config
         .gui_background_color
         .as_deref()
@@ -259,15 +336,18 @@ fn generate_config(config: &ConfigFile, manifest_dir: &str) -> Result<TokenStrea
         })
         .transpose()?;
 
-    let subcategories = if let Some(subs) = &config.subcategories {
-        let sub_tokens: Vec<TokenStream> = subs
-            .iter()
-            .map(|s| subcategory_tokens(s))
-            .collect::<Result<Vec<_>, _>>()?;
-        Some(quote! { .with_subcategories(&[#(#sub_tokens),*]) })
-    } else {
-        None
-    };
+    let subcategories = config.subcategories.as_deref().map(subcategories_tokens).transpose()?;
+
+    let au_override = config
+        .au
+        .as_ref()
+        .map(|au| format_override_tokens(au).map(|apply| quote! { .override_au(#apply) }))
+        .transpose()?;
+    let vst3_override = config
+        .vst3
+        .as_ref()
+        .map(|vst3| format_override_tokens(vst3).map(|apply| quote! { .override_vst3(#apply) }))
+        .transpose()?;
 
     Ok(quote! {
         #assets_static
@@ -285,16 +365,48 @@ fn generate_config(config: &ConfigFile, manifest_dir: &str) -> Result<TokenStrea
         #has_gui
         #gui_source
         #gui_size
+        #gui_views
         #vst3_id
         #vst3_controller_id
         #sysex_slots
         #sysex_buffer_size
+        #preset_crossfade
+        #standard_trims
+        #processing_stats
         #subcategories
         #gui_background_color
+        #log_filter
+        #sample_rates
+        #sample_rate_range
+        #channel_mix
+        #watchdog
+        #au_override
+        #vst3_override
         ;
     })
 }
 
+/// Generate `.with_subcategories(...)` from subcategory strings.
+fn subcategories_tokens(subs: &[String]) -> Result<TokenStream, String> {
+    let sub_tokens: Vec<TokenStream> = subs
+        .iter()
+        .map(|s| subcategory_tokens(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote! { .with_subcategories(&[#(#sub_tokens),*]) })
+}
+
+/// Generate the override function for an `[au]` or `[vst3]` table.
+fn format_override_tokens(entry: &FormatOverrideEntry) -> Result<TokenStream, String> {
+    let name = entry.name.as_ref().map(|name| quote! { .with_name(#name) });
+    let subcategories = entry.subcategories.as_deref().map(subcategories_tokens).transpose()?;
+    let sysex_slots = entry.sysex_slots.map(|slots| quote! { .with_sysex_slots(#slots) });
+    let sysex_buffer_size = entry.sysex_buffer_size.map(|size| quote! { .with_sysex_buffer_size(#size) });
+    let preset_crossfade = entry.preset_crossfade_ms.map(|ms| quote! { .with_preset_crossfade(#ms) });
+    Ok(quote! {
+        |config| config #name #subcategories #sysex_slots #sysex_buffer_size #preset_crossfade
+    })
+}
+
 /// Generate the FactoryPresets implementation from a parsed PresetsFile.
 fn generate_presets(presets: &PresetsFile, descriptor: &syn::Ident) -> Result<TokenStream, String> {
     let count = presets.preset.len();
@@ -31,6 +31,14 @@ pub struct ParameterAttributes {
     pub group: Option<String>,
     /// Step size for discrete float parameters.
     pub step: Option<f64>,
+    /// Transient parameter: not saved and not automatable.
+    pub transient: bool,
+    /// Excluded from saved state (still automatable).
+    pub no_save: bool,
+    /// GUI-only until promoted to the host.
+    pub internal: bool,
+    /// Preset randomization constraint.
+    pub randomize: Option<RandomizeSpec>,
 }
 
 impl ParameterAttributes {
@@ -49,6 +57,9 @@ impl ParameterAttributes {
                 self.bypass || (self.name.is_some() && self.default.is_some())
             }
             ParameterType::Enum => self.name.is_some(),
+            ParameterType::TimeInSamples => {
+                self.name.is_some() && self.default.is_some() && self.range.is_some()
+            }
         }
     }
 }
@@ -72,6 +83,15 @@ pub struct RangeSpec {
     pub span: Span,
 }
 
+/// Randomization constraint parsed from `randomize = "off"` or `randomize = start..=end`.
+#[derive(Debug, Clone)]
+pub enum RandomizeSpec {
+    /// Never randomized.
+    Off,
+    /// Randomized within a sub-range, in plain units.
+    Range(RangeSpec),
+}
+
 /// Parameter kind that determines the constructor and formatting.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParameterKind {
@@ -125,13 +145,16 @@ impl ParameterKind {
     }
 }
 
-/// Smoothing specification parsed from `smoothing = "exp:5.0"`.
+/// Smoothing specification parsed from `smoothing = "exp:5.0"` or
+/// `smooth_ms = 20.0`.
 #[derive(Debug, Clone)]
 pub struct SmoothingSpec {
     /// Smoothing style
     pub style: SmoothingStyle,
     /// Time in milliseconds
     pub time_ms: f64,
+    /// Advanced by the wrapper (`smooth_ms`) instead of the plugin
+    pub auto: bool,
     /// Span for error reporting
     pub span: Span,
 }
@@ -169,6 +192,8 @@ pub struct ParametersIR {
     pub generics: syn::Generics,
     /// All fields in the struct
     pub fields: Vec<FieldIR>,
+    /// Field marked `#[group_controls]`, holding per-group bypass/solo switches
+    pub group_controls: Option<syn::Ident>,
     /// Span for error reporting
     pub span: Span,
 }
@@ -226,10 +251,33 @@ pub struct NestedFieldIR {
     pub group_id: i32,
     /// Parent group ID (0 for top-level, parent's group_id for nested-within-nested)
     pub parent_group_id: i32,
+    /// Whether `controls` was given: generate bypass/solo switches for this group
+    pub controls: bool,
+    /// GUI sort key from `order = ...` (0 if not given)
+    pub order: i32,
+    /// Whether `collapsed` was given: the GUI section starts collapsed
+    pub collapsed: bool,
+    /// GUI icon name from `icon = "..."`
+    pub icon: Option<String>,
     /// Span for error reporting
     pub span: Span,
 }
 
+impl NestedFieldIR {
+    /// Whether any GUI layout hint was given.
+    pub fn has_hints(&self) -> bool {
+        self.order != 0 || self.collapsed || self.icon.is_some()
+    }
+
+    /// String IDs of the bypass and solo switches generated by `controls`.
+    pub fn control_string_ids(&self) -> [String; 2] {
+        [
+            format!("{}.bypass", self.group_name),
+            format!("{}.solo", self.group_name),
+        ]
+    }
+}
+
 /// The type of a parameter field.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParameterType {
@@ -237,6 +285,8 @@ pub enum ParameterType {
     Int,
     Bool,
     Enum,
+    /// `TimeInSamples`: default and range in seconds.
+    TimeInSamples,
 }
 
 impl ParametersIR {
@@ -273,6 +323,11 @@ impl ParametersIR {
         self.parameter_fields().all(|p| p.has_declarative_attributes())
     }
 
+    /// Iterate over nested fields declared with `controls`.
+    pub fn controlled_groups(&self) -> impl Iterator<Item = &NestedFieldIR> {
+        self.nested_fields().filter(|n| n.controls)
+    }
+
     /// Check if any parameters have flat group attributes.
     pub fn has_flat_groups(&self) -> bool {
         self.parameter_fields().any(|p| p.attributes.group.is_some())
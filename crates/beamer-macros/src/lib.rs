@@ -55,11 +55,13 @@ use proc_macro::TokenStream;
 mod codegen;
 mod config_file;
 mod enum_parameter;
+mod graph;
 mod has_parameters;
 mod init;
 mod ir;
 mod parse;
 mod range_eval;
+mod rpc;
 mod validate;
 
 /// Derive macro for implementing parameter traits.
@@ -78,15 +80,25 @@ mod validate;
 /// ## Declarative (enables auto-generated Default)
 /// - `name = "..."` - Display name
 /// - `default = <value>` - Default value (float, int, or bool)
-/// - `range = start..=end` - Value range (for FloatParameter/IntParameter)
+/// - `range = start..=end` - Value range (for FloatParameter/IntParameter, seconds for TimeInSamples)
 /// - `kind = "..."` - Unit type: db, db_log, db_log_offset, hz, ms, seconds, percent, pan, ratio, linear, semitones
 /// - `short_name = "..."` - Short name for constrained UIs
 /// - `smoothing = "exp:5.0"` - Parameter smoothing (exp or linear)
+/// - `smooth_ms = 20.0` - Exponential smoothing advanced by the wrapper (read with `smoothed_values()`)
 /// - `bypass` - Mark as bypass parameter (BoolParameter only)
 /// - `group = "..."` - Visual grouping in DAW without nested struct
+/// - `transient` - Not saved in state and not automatable (audition toggles, UI state)
+/// - `no_save` - Excluded from saved state but still automatable
+/// - `internal` - GUI-only until the user promotes it to the host (large parameter sets)
+/// - `randomize = "off"` / `randomize = start..=end` - Exclude from or limit preset randomization
 ///
 /// ## Nested Groups
 /// - `#[nested(group = "...")]` - For fields containing nested parameter structs
+/// - `#[nested(group = "...", controls)]` - Also generate "<Group> Bypass" and
+///   "<Group> Solo" parameters, stored in the `#[group_controls]` field
+///   (type `GroupControls`) and queried with `is_group_active(group_id)`
+/// - `order = N`, `collapsed`, `icon = "..."` - GUI layout hints for the group
+///   (sort key among siblings, start collapsed, icon name); see `GroupHints`
 ///
 /// # Example
 ///
@@ -103,7 +115,7 @@ mod validate;
 ///     pub output: OutputParameters,
 /// }
 /// ```
-#[proc_macro_derive(Parameters, attributes(parameter, nested))]
+#[proc_macro_derive(Parameters, attributes(parameter, nested, group_controls))]
 pub fn derive_parameters(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
 
@@ -198,6 +210,10 @@ pub fn derive_enum_parameter(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// Fields of type `RateDependent<T, Parameters>` marked `#[rate_dependent]`
+/// are recomputed by the generated `recompute_rate_dependent()`, which the
+/// wrappers call after `prepare()` and `load_state()`.
+///
 /// # Requirements
 ///
 /// - The struct must have named fields
@@ -219,7 +235,7 @@ pub fn derive_enum_parameter(input: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
-#[proc_macro_derive(HasParameters, attributes(parameters))]
+#[proc_macro_derive(HasParameters, attributes(parameters, rate_dependent))]
 pub fn derive_has_parameters(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
 
@@ -289,3 +305,101 @@ pub fn export(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     }
 }
+
+/// Attribute macro that exposes an impl block's methods to the WebView.
+///
+/// Place `#[beamer::rpc]` on an inherent impl block. Every `pub fn` taking
+/// `&self` becomes callable from JavaScript under its camelCase name.
+/// Associated functions without a receiver (like `pub fn new()`) are left
+/// alone; `&mut self` and `self` methods are a compile error. The macro
+/// generates:
+/// - `dispatch_rpc(&self, method, args)` that deserializes the JSON arguments,
+///   calls the method and serializes the result
+/// - a `WebViewHandler` impl whose `on_invoke` calls `dispatch_rpc`
+/// - `RPC_CLIENT_JS` and `RPC_CLIENT_DTS` constants holding the typed
+///   JavaScript client
+///
+/// Arguments must implement `serde::Deserialize` and return values
+/// `serde::Serialize`. Returning `Result<T, E>` rejects the JS Promise with
+/// `E::to_string()` on error.
+///
+/// # Options
+///
+/// - `handler = false` - Skip the `WebViewHandler` impl (call `dispatch_rpc`
+///   from your own `on_invoke` to combine it with `on_event`)
+/// - `client = true` - Also write the client to `beamer-rpc.js` and
+///   `beamer-rpc.d.ts` in the webview directory (`webview/src/` for
+///   framework projects, `webview/` otherwise). Off by default, so builds
+///   never touch the source tree unless asked to
+/// - `out_dir = "..."` - Write the client to this directory instead
+///   (relative to the crate root, implies `client = true`)
+///
+/// # Example
+///
+/// ```ignore
+/// struct DemoHandler;
+///
+/// #[beamer::rpc(client = true)]
+/// impl DemoHandler {
+///     /// Plugin name and version.
+///     pub fn get_info(&self) -> PluginInfo { /* ... */ }
+///
+///     pub fn load_sample(&self, path: String) -> Result<u32, String> { /* ... */ }
+/// }
+/// ```
+///
+/// ```typescript
+/// import { getInfo, loadSample } from "./beamer-rpc";
+///
+/// const info = await getInfo();
+/// const id: number = await loadSample("/path/to/kick.wav");
+/// ```
+#[proc_macro_attribute]
+pub fn rpc(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::ItemImpl);
+    match rpc::rpc_impl(attr.into(), input.clone()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let error_tokens = err.to_compile_error();
+            quote::quote! {
+                #input
+                #error_tokens
+            }
+            .into()
+        }
+    }
+}
+
+/// Declarative routing of processor stages.
+///
+/// Each line is a chain from an input to a sink. Stages are fields of
+/// `self`; the macro expects the `process()` parameters to be named
+/// `buffer` and (when `sidechain` is used) `aux`.
+///
+/// | Chain | Meaning |
+/// |-------|---------|
+/// | `main -> a -> b -> out` | Copy the input to the output, then run `Block::process()` of `a` and `b` on it |
+/// | `sidechain -> d -> b.sidechain` | Run `Detector::detect()` of `d` on the sidechain bus and key `b` with the result (`KeyedBlock::process_keyed()`) |
+/// | `main -> d -> b.sidechain` | Same, keyed by the dry main input |
+///
+/// Routing mistakes are compile errors: a missing or second `out` chain,
+/// a stage used twice, a sidechain target off the main chain, or a stage
+/// without the trait its position needs.
+///
+/// # Example
+///
+/// ```ignore
+/// fn process(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+///     process_graph! {
+///         main -> eq -> comp -> out;
+///         sidechain -> follower -> comp.sidechain;
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn process_graph(input: TokenStream) -> TokenStream {
+    match graph::process_graph_impl(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -9,7 +9,7 @@ use syn::{Data, DeriveInput, Field, Fields};
 use beamer_utils::fnv1a_32;
 use crate::ir::{
     FieldIR, NestedFieldIR, ParameterAttributes, ParameterDefault, ParameterFieldIR, ParameterKind, ParameterType,
-    ParametersIR, RangeSpec, SmoothingSpec, SmoothingStyle,
+    ParametersIR, RandomizeSpec, RangeSpec, SmoothingSpec, SmoothingStyle,
 };
 use crate::range_eval;
 
@@ -50,7 +50,18 @@ pub fn parse(input: DeriveInput) -> syn::Result<ParametersIR> {
 
     // Parse each field
     let mut parsed_fields = Vec::new();
+    let mut group_controls: Option<syn::Ident> = None;
     for field in fields {
+        if field.attrs.iter().any(|attr| attr.path().is_ident("group_controls")) {
+            if group_controls.is_some() {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "only one #[group_controls] field is allowed",
+                ));
+            }
+            group_controls = field.ident.clone();
+            continue;
+        }
         if let Some(field_ir) = parse_field(field)? {
             parsed_fields.push(field_ir);
         }
@@ -71,6 +82,7 @@ pub fn parse(input: DeriveInput) -> syn::Result<ParametersIR> {
         struct_name: input.ident.clone(),
         generics: input.generics.clone(),
         fields: parsed_fields,
+        group_controls,
         span: Span::call_site(),
     })
 }
@@ -91,7 +103,11 @@ fn parse_field(field: &Field) -> syn::Result<Option<FieldIR>> {
     if let Some(type_name) = extract_type_name(&field.ty) {
         if matches!(
             type_name.as_str(),
-            "FloatParameter" | "IntParameter" | "BoolParameter" | "EnumParameter"
+            "FloatParameter"
+                | "IntParameter"
+                | "BoolParameter"
+                | "EnumParameter"
+                | "OversamplingParameter"
         ) {
             return Err(syn::Error::new_spanned(
                 field,
@@ -154,8 +170,17 @@ fn parse_parameter_field(field: &Field, attr: &syn::Attribute) -> syn::Result<Pa
             attributes.short_name = Some(value.value());
             Ok(())
         } else if meta.path.is_ident("smoothing") {
+            if attributes.smoothing.is_some() {
+                return Err(meta.error("use either smoothing or smooth_ms, not both"));
+            }
             attributes.smoothing = Some(parse_smoothing_spec(&meta)?);
             Ok(())
+        } else if meta.path.is_ident("smooth_ms") {
+            if attributes.smoothing.is_some() {
+                return Err(meta.error("use either smoothing or smooth_ms, not both"));
+            }
+            attributes.smoothing = Some(parse_smooth_ms(&meta)?);
+            Ok(())
         } else if meta.path.is_ident("bypass") {
             // bypass can be `bypass` (flag) or `bypass = true`
             if meta.input.peek(syn::Token![=]) {
@@ -165,6 +190,18 @@ fn parse_parameter_field(field: &Field, attr: &syn::Attribute) -> syn::Result<Pa
                 attributes.bypass = true;
             }
             Ok(())
+        } else if meta.path.is_ident("transient") {
+            attributes.transient = true;
+            Ok(())
+        } else if meta.path.is_ident("no_save") {
+            attributes.no_save = true;
+            Ok(())
+        } else if meta.path.is_ident("internal") {
+            attributes.internal = true;
+            Ok(())
+        } else if meta.path.is_ident("randomize") {
+            attributes.randomize = Some(parse_randomize_spec(&meta)?);
+            Ok(())
         } else if meta.path.is_ident("group") {
             let value: syn::LitStr = meta.value()?.parse()?;
             attributes.group = Some(value.value());
@@ -193,7 +230,7 @@ fn parse_parameter_field(field: &Field, attr: &syn::Attribute) -> syn::Result<Pa
             Ok(())
         } else {
             Err(meta.error(
-                "unknown attribute. Expected: id, name, default, range, kind, short_name, smoothing, bypass, group, step"
+                "unknown attribute. Expected: id, name, default, range, kind, short_name, smoothing, smooth_ms, bypass, group, step, transient, no_save, internal, randomize"
             ))
         }
     })?;
@@ -223,7 +260,7 @@ fn parse_parameter_field(field: &Field, attr: &syn::Attribute) -> syn::Result<Pa
     let parameter_type = extract_parameter_type(&field.ty).ok_or_else(|| {
         syn::Error::new_spanned(
             &field.ty,
-            "#[parameter] can only be used on FloatParameter, IntParameter, BoolParameter, or EnumParameter fields",
+            "#[parameter] can only be used on FloatParameter, IntParameter, BoolParameter, EnumParameter, or TimeInSamples fields",
         )
     })?;
 
@@ -328,6 +365,21 @@ fn parse_range_spec(meta: &syn::meta::ParseNestedMeta) -> syn::Result<RangeSpec>
     })
 }
 
+/// Parse a randomization constraint from `randomize = "off"` or `randomize = -12.0..=0.0`.
+fn parse_randomize_spec(meta: &syn::meta::ParseNestedMeta) -> syn::Result<RandomizeSpec> {
+    if meta.input.peek(syn::Token![=]) && meta.input.peek2(syn::LitStr) {
+        let value: syn::LitStr = meta.value()?.parse()?;
+        return match value.value().as_str() {
+            "off" => Ok(RandomizeSpec::Off),
+            other => Err(syn::Error::new_spanned(
+                &value,
+                format!("unknown randomize value '{}'. Expected \"off\" or a range like `-12.0..=0.0`", other),
+            )),
+        };
+    }
+    Ok(RandomizeSpec::Range(parse_range_spec(meta)?))
+}
+
 /// Parse a smoothing specification from `smoothing = "exp:5.0"`.
 fn parse_smoothing_spec(meta: &syn::meta::ParseNestedMeta) -> syn::Result<SmoothingSpec> {
     let value: syn::LitStr = meta.value()?.parse()?;
@@ -356,11 +408,31 @@ fn parse_smoothing_spec(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Smooth
     Ok(SmoothingSpec {
         style,
         time_ms,
+        auto: false,
+        span,
+    })
+}
+
+/// Parse an automatic smoothing time from `smooth_ms = 20.0`.
+fn parse_smooth_ms(meta: &syn::meta::ParseNestedMeta) -> syn::Result<SmoothingSpec> {
+    let value: syn::Lit = meta.value()?.parse()?;
+    let span = value.span();
+    let time_ms = match &value {
+        syn::Lit::Float(lit) => lit.base10_parse::<f64>()?,
+        syn::Lit::Int(lit) => lit.base10_parse::<f64>()?,
+        _ => return Err(syn::Error::new(span, "smooth_ms must be a number (e.g. smooth_ms = 20.0)")),
+    };
+
+    Ok(SmoothingSpec {
+        style: SmoothingStyle::Exponential,
+        time_ms,
+        auto: true,
         span,
     })
 }
 
-/// Parse a field with `#[nested(group = "...")]` attribute.
+/// Parse a field with `#[nested(group = "...")]` attribute (optionally with
+/// `controls` and the GUI hints `order = N`, `collapsed`, `icon = "..."`).
 fn parse_nested_field(field: &Field, attr: &syn::Attribute) -> syn::Result<NestedFieldIR> {
     let field_name = field
         .ident
@@ -369,14 +441,41 @@ fn parse_nested_field(field: &Field, attr: &syn::Attribute) -> syn::Result<Neste
 
     // Parse the attribute using syn 2.x API
     let mut group_name: Option<String> = None;
+    let mut controls = false;
+    let mut order = 0;
+    let mut collapsed = false;
+    let mut icon: Option<String> = None;
 
     attr.parse_nested_meta(|meta| {
         if meta.path.is_ident("group") {
             let value: syn::LitStr = meta.value()?.parse()?;
             group_name = Some(value.value());
             Ok(())
+        } else if meta.path.is_ident("controls") {
+            controls = true;
+            Ok(())
+        } else if meta.path.is_ident("order") {
+            let expr: syn::Expr = meta.value()?.parse()?;
+            order = match &expr {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(i), .. }) => i.base10_parse::<i32>()?,
+                syn::Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => match &*unary.expr {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(i), .. }) => -i.base10_parse::<i32>()?,
+                    _ => return Err(syn::Error::new_spanned(&expr, "order must be an integer literal")),
+                },
+                _ => return Err(syn::Error::new_spanned(&expr, "order must be an integer literal")),
+            };
+            Ok(())
+        } else if meta.path.is_ident("collapsed") {
+            collapsed = true;
+            Ok(())
+        } else if meta.path.is_ident("icon") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            icon = Some(value.value());
+            Ok(())
         } else {
-            Err(meta.error("expected `group = \"...\"`"))
+            Err(meta.error(
+                "expected `group = \"...\"`, `controls`, `order = N`, `collapsed` or `icon = \"...\"`",
+            ))
         }
     })?;
 
@@ -407,6 +506,10 @@ fn parse_nested_field(field: &Field, attr: &syn::Attribute) -> syn::Result<Neste
         group_name,
         group_id: 0,         // Assigned later by assign_group_ids()
         parent_group_id: 0,  // Assigned later by assign_group_ids()
+        controls,
+        order,
+        collapsed,
+        icon,
         span: attr.path().segments[0].ident.span(),
     })
 }
@@ -451,7 +554,13 @@ fn extract_parameter_type(ty: &syn::Type) -> Option<ParameterType> {
         "FloatParameter" => Some(ParameterType::Float),
         "IntParameter" => Some(ParameterType::Int),
         "BoolParameter" => Some(ParameterType::Bool),
-        "EnumParameter" => Some(ParameterType::Enum),
+        // Aliases of EnumParameter<Oversampling>, <Quality>, <TempoSync> and <AmbisonicOrder>
+        "EnumParameter"
+        | "OversamplingParameter"
+        | "QualityParameter"
+        | "TempoSyncParameter"
+        | "AmbisonicOrderParameter" => Some(ParameterType::Enum),
+        "TimeInSamples" => Some(ParameterType::TimeInSamples),
         _ => None,
     }
 }
@@ -0,0 +1,549 @@
+//! The `#[beamer::rpc]` attribute macro implementation.
+//!
+//! Turns the methods of an inherent impl block into WebView invoke targets:
+//! generates the `on_invoke` dispatch on the Rust side and a typed JavaScript
+//! client (`beamer-rpc.js` + `beamer-rpc.d.ts`). The client is always exposed
+//! as constants on the handler type and is only written to disk when the
+//! `client` or `out_dir` option asks for it.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{FnArg, GenericArgument, ImplItem, ItemImpl, Pat, PathArguments, ReturnType, Type};
+
+/// File name stem of the generated JavaScript client.
+const CLIENT_STEM: &str = "beamer-rpc";
+
+/// Header written at the top of every generated client file.
+const GENERATED_HEADER: &str = "// Generated by #[beamer::rpc]. Do not edit.\n";
+
+/// Parsed `#[beamer::rpc(...)]` options.
+struct RpcOptions {
+    /// Generate the `WebViewHandler` impl (default: true).
+    handler: bool,
+    /// Write the client into the default webview directory (default: false).
+    client: bool,
+    /// Output directory for the client, relative to CARGO_MANIFEST_DIR.
+    /// Setting it implies `client = true`.
+    out_dir: Option<String>,
+}
+
+impl syn::parse::Parse for RpcOptions {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut options = RpcOptions {
+            handler: true,
+            client: false,
+            out_dir: None,
+        };
+        let args = syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated(input)?;
+        for arg in args {
+            let key = arg
+                .path
+                .get_ident()
+                .map(|i| i.to_string())
+                .unwrap_or_default();
+            let syn::Expr::Lit(syn::ExprLit { lit, .. }) = &arg.value else {
+                return Err(syn::Error::new_spanned(&arg.value, "expected a literal"));
+            };
+            match (key.as_str(), lit) {
+                ("handler", syn::Lit::Bool(b)) => options.handler = b.value,
+                ("client", syn::Lit::Bool(b)) => options.client = b.value,
+                ("out_dir", syn::Lit::Str(s)) => options.out_dir = Some(s.value()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &arg.path,
+                        "unknown rpc option (expected `handler = bool`, `client = bool` or `out_dir = \"...\"`)",
+                    ))
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A single RPC method extracted from the impl block.
+struct RpcMethod {
+    ident: syn::Ident,
+    js_name: String,
+    args: Vec<(syn::Ident, Type)>,
+    output: Option<Type>,
+    returns_result: bool,
+    docs: Vec<String>,
+}
+
+/// Convert a snake_case Rust identifier to camelCase.
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.trim_start_matches('_').chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Get the single generic type argument of a path segment (e.g. `T` in `Vec<T>`).
+fn generic_args(segment: &syn::PathSegment) -> Vec<&Type> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|a| match a {
+                GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Map a Rust type to its TypeScript equivalent. Unknown types map to `unknown`.
+fn ts_type(ty: &Type) -> String {
+    match ty {
+        Type::Reference(r) => ts_type(&r.elem),
+        Type::Paren(p) => ts_type(&p.elem),
+        Type::Slice(s) => format!("{}[]", ts_type_wrapped(&s.elem)),
+        Type::Array(a) => format!("{}[]", ts_type_wrapped(&a.elem)),
+        Type::Tuple(t) if t.elems.is_empty() => "void".to_string(),
+        Type::Tuple(t) => {
+            let elems: Vec<String> = t.elems.iter().map(ts_type).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        Type::Path(p) => {
+            let Some(segment) = p.path.segments.last() else {
+                return "unknown".to_string();
+            };
+            let args = generic_args(segment);
+            match (segment.ident.to_string().as_str(), args.as_slice()) {
+                (
+                    "f32" | "f64" | "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32"
+                    | "u64" | "usize",
+                    _,
+                ) => "number".to_string(),
+                ("bool", _) => "boolean".to_string(),
+                ("String" | "str" | "char" | "PathBuf", _) => "string".to_string(),
+                ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+                    format!("{}[]", ts_type_wrapped(inner))
+                }
+                ("Option", [inner]) => format!("{} | null", ts_type(inner)),
+                ("Box" | "Arc" | "Rc", [inner]) => ts_type(inner),
+                ("HashMap" | "BTreeMap", [_, value]) => format!("Record<string, {}>", ts_type(value)),
+                _ => "unknown".to_string(),
+            }
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Like [`ts_type`], but parenthesizes union types so `[]` binds correctly.
+fn ts_type_wrapped(ty: &Type) -> String {
+    let ts = ts_type(ty);
+    if ts.contains('|') {
+        format!("({ts})")
+    } else {
+        ts
+    }
+}
+
+/// If `ty` is `Result<T, E>`, return `T`.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(p) = ty else { return None };
+    let segment = p.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    generic_args(segment).first().copied()
+}
+
+fn extract_docs(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }),
+                ..
+            }) => Some(s.value().trim().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse a `pub fn` of the impl block.
+///
+/// Returns `Ok(None)` for associated functions without a receiver (such as
+/// `pub fn new()`), which are left as they are and not exposed to JavaScript.
+fn parse_method(method: &syn::ImplItemFn) -> syn::Result<Option<RpcMethod>> {
+    let sig = &method.sig;
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_none() => {}
+        Some(FnArg::Receiver(r)) => {
+            return Err(syn::Error::new_spanned(
+                r,
+                "rpc methods must take `&self` (WebViewHandler is called through a shared reference)",
+            ))
+        }
+        _ => return Ok(None),
+    }
+
+    let mut args = Vec::new();
+    for input in sig.inputs.iter().skip(1) {
+        let FnArg::Typed(pat_type) = input else { continue };
+        let Pat::Ident(pat_ident) = pat_type.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                &pat_type.pat,
+                "rpc arguments must be simple identifiers",
+            ));
+        };
+        if let Type::Reference(_) = pat_type.ty.as_ref() {
+            return Err(syn::Error::new_spanned(
+                &pat_type.ty,
+                "rpc arguments must be owned types (they are deserialized from JSON)",
+            ));
+        }
+        args.push((pat_ident.ident.clone(), (*pat_type.ty).clone()));
+    }
+
+    let output = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some((**ty).clone()),
+    };
+    let returns_result = output.as_ref().and_then(result_ok_type).is_some();
+
+    Ok(Some(RpcMethod {
+        ident: sig.ident.clone(),
+        js_name: camel_case(&sig.ident.to_string()),
+        args,
+        output,
+        returns_result,
+        docs: extract_docs(&method.attrs),
+    }))
+}
+
+/// Generate the `dispatch_rpc` match arm for a method.
+fn dispatch_arm(method: &RpcMethod) -> TokenStream {
+    let js_name = &method.js_name;
+    let ident = &method.ident;
+    let arg_idents: Vec<syn::Ident> = (0..method.args.len())
+        .map(|i| format_ident!("__arg{}", i))
+        .collect();
+    let arg_decls = method.args.iter().enumerate().map(|(i, (name, ty))| {
+        let var = &arg_idents[i];
+        let name_str = name.to_string();
+        quote! {
+            let #var: #ty = ::beamer::core::serde_json::from_value(
+                args.get(#i).cloned().unwrap_or(::beamer::core::serde_json::Value::Null),
+            )
+            .map_err(|e| format!("{}: invalid argument `{}`: {}", #js_name, #name_str, e))?;
+        }
+    });
+
+    let call = quote! { self.#ident(#(#arg_idents),*) };
+    let body = if method.returns_result {
+        quote! { #call.map_err(|e| e.to_string())? }
+    } else {
+        call
+    };
+
+    quote! {
+        #js_name => {
+            #(#arg_decls)*
+            let __result = #body;
+            ::beamer::core::serde_json::to_value(__result)
+                .map_err(|e| format!("{}: failed to serialize result: {}", #js_name, e))
+        }
+    }
+}
+
+/// Render the JavaScript client module.
+fn client_js(methods: &[RpcMethod]) -> String {
+    let mut js = String::from(GENERATED_HEADER);
+    for method in methods {
+        let params: Vec<String> = method
+            .args
+            .iter()
+            .map(|(name, _)| camel_case(&name.to_string()))
+            .collect();
+        let mut invoke_args = vec![format!("\"{}\"", method.js_name)];
+        invoke_args.extend(params.iter().cloned());
+        js.push_str(&format!(
+            "\nexport function {}({}) {{\n  return window.__BEAMER__.invoke({});\n}}\n",
+            method.js_name,
+            params.join(", "),
+            invoke_args.join(", ")
+        ));
+    }
+    js
+}
+
+/// Render the TypeScript declarations for the client module.
+fn client_dts(methods: &[RpcMethod]) -> String {
+    let mut dts = String::from(GENERATED_HEADER);
+    for method in methods {
+        dts.push('\n');
+        if !method.docs.is_empty() {
+            dts.push_str("/**\n");
+            for line in &method.docs {
+                dts.push_str(&format!(" * {line}\n"));
+            }
+            dts.push_str(" */\n");
+        }
+        let params: Vec<String> = method
+            .args
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", camel_case(&name.to_string()), ts_type(ty)))
+            .collect();
+        let ret = match &method.output {
+            None => "void".to_string(),
+            Some(ty) => ts_type(result_ok_type(ty).unwrap_or(ty)),
+        };
+        dts.push_str(&format!(
+            "export declare function {}({}): Promise<{}>;\n",
+            method.js_name,
+            params.join(", "),
+            ret
+        ));
+    }
+    dts
+}
+
+/// Pick the directory the client is written to, relative to the manifest dir.
+///
+/// Framework projects (with `package.json`) get `webview/src/` so the bundler
+/// picks the files up. Plain HTML projects get `webview/`. Returns `None` when
+/// the crate has no webview directory.
+fn default_out_dir(manifest_dir: &std::path::Path) -> Option<std::path::PathBuf> {
+    let webview = manifest_dir.join("webview");
+    if webview.join("package.json").exists() && webview.join("src").is_dir() {
+        Some(webview.join("src"))
+    } else if webview.is_dir() {
+        Some(webview)
+    } else {
+        None
+    }
+}
+
+/// Write a file only when its contents change, so dev servers and cargo's
+/// asset tracking are not triggered by identical rewrites.
+fn write_if_changed(path: &std::path::Path, contents: &str) -> std::io::Result<()> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)
+}
+
+/// Expand `#[beamer::rpc]`.
+pub fn rpc_impl(attr: TokenStream, item: ItemImpl) -> syn::Result<TokenStream> {
+    let options: RpcOptions = syn::parse2(attr)?;
+
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(syn::Error::new_spanned(
+            path,
+            "#[beamer::rpc] must be placed on an inherent impl block",
+        ));
+    }
+
+    let methods = item
+        .items
+        .iter()
+        .filter_map(|i| match i {
+            ImplItem::Fn(f) if matches!(f.vis, syn::Visibility::Public(_)) => Some(f),
+            _ => None,
+        })
+        .filter_map(|f| parse_method(f).transpose())
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    let arms = methods.iter().map(dispatch_arm);
+    let js = client_js(&methods);
+    let dts = client_dts(&methods);
+
+    // Emit the client only when asked to, so plain builds (docs.rs, sandboxed
+    // or read-only checkouts) never touch the source tree. Errors surface as
+    // a compile error rather than a silently stale client.
+    if options.client || options.out_dir.is_some() {
+        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+        let manifest_dir = std::path::Path::new(&manifest_dir);
+        let dir = match &options.out_dir {
+            Some(dir) => manifest_dir.join(dir),
+            None => default_out_dir(manifest_dir).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    self_ty,
+                    "`client = true` needs a `webview/` directory next to Cargo.toml (or set `out_dir`)",
+                )
+            })?,
+        };
+        for (ext, contents) in [("js", &js), ("d.ts", &dts)] {
+            let path = dir.join(format!("{CLIENT_STEM}.{ext}"));
+            write_if_changed(&path, contents).map_err(|e| {
+                syn::Error::new_spanned(
+                    self_ty,
+                    format!("failed to write {}: {}", path.display(), e),
+                )
+            })?;
+        }
+    }
+
+    let handler_impl = options.handler.then(|| {
+        quote! {
+            impl #impl_generics ::beamer::core::WebViewHandler for #self_ty #where_clause {
+                fn on_invoke(
+                    &self,
+                    method: &str,
+                    args: &[::beamer::core::serde_json::Value],
+                ) -> ::std::result::Result<::beamer::core::serde_json::Value, ::std::string::String> {
+                    self.dispatch_rpc(method, args)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #item
+
+        impl #impl_generics #self_ty #where_clause {
+            /// JavaScript client for the RPC methods (the contents of `beamer-rpc.js`).
+            pub const RPC_CLIENT_JS: &'static str = #js;
+
+            /// TypeScript declarations for the RPC client (the contents of `beamer-rpc.d.ts`).
+            pub const RPC_CLIENT_DTS: &'static str = #dts;
+
+            /// Dispatch a WebView invoke call to the matching RPC method.
+            pub fn dispatch_rpc(
+                &self,
+                method: &str,
+                args: &[::beamer::core::serde_json::Value],
+            ) -> ::std::result::Result<::beamer::core::serde_json::Value, ::std::string::String> {
+                match method {
+                    #(#arms)*
+                    _ => Err(format!("unknown rpc method: {}", method)),
+                }
+            }
+        }
+
+        #handler_impl
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(item: syn::ImplItemFn) -> RpcMethod {
+        parse_method(&item).unwrap().expect("method takes &self")
+    }
+
+    #[test]
+    fn test_camel_case() {
+        assert_eq!(camel_case("get_info"), "getInfo");
+        assert_eq!(camel_case("load_sample_slot"), "loadSampleSlot");
+        assert_eq!(camel_case("record"), "record");
+        assert_eq!(camel_case("_private_name"), "privateName");
+    }
+
+    #[test]
+    fn test_ts_type() {
+        let ts = |ty: Type| ts_type(&ty);
+        assert_eq!(ts(syn::parse_quote!(f32)), "number");
+        assert_eq!(ts(syn::parse_quote!(String)), "string");
+        assert_eq!(ts(syn::parse_quote!(())), "void");
+        assert_eq!(ts(syn::parse_quote!(Vec<Option<u32>>)), "(number | null)[]");
+        assert_eq!(ts(syn::parse_quote!((bool, &str))), "[boolean, string]");
+        assert_eq!(ts(syn::parse_quote!(HashMap<String, Vec<f64>>)), "Record<string, number[]>");
+        assert_eq!(ts(syn::parse_quote!(Arc<PathBuf>)), "string");
+        assert_eq!(ts(syn::parse_quote!(MyStruct)), "unknown");
+    }
+
+    #[test]
+    fn test_dispatch_arm() {
+        let plain = method(syn::parse_quote! {
+            pub fn set_gain(&self, gain_db: f32) -> bool { true }
+        });
+        let expected = quote! {
+            "setGain" => {
+                let __arg0: f32 = ::beamer::core::serde_json::from_value(
+                    args.get(0usize).cloned().unwrap_or(::beamer::core::serde_json::Value::Null),
+                )
+                .map_err(|e| format!("{}: invalid argument `{}`: {}", "setGain", "gain_db", e))?;
+                let __result = self.set_gain(__arg0);
+                ::beamer::core::serde_json::to_value(__result)
+                    .map_err(|e| format!("{}: failed to serialize result: {}", "setGain", e))
+            }
+        };
+        assert_eq!(dispatch_arm(&plain).to_string(), expected.to_string());
+
+        let fallible = method(syn::parse_quote! {
+            pub fn reset(&self) -> Result<(), String> { Ok(()) }
+        });
+        assert!(dispatch_arm(&fallible)
+            .to_string()
+            .contains("let __result = self . reset () . map_err (| e | e . to_string ()) ?"));
+    }
+
+    #[test]
+    fn test_client_output() {
+        let methods = [
+            method(syn::parse_quote! {
+                /// Load a sample into a slot.
+                pub fn load_sample(&self, file_path: String, slot: u32) -> Result<f64, String> { Ok(0.0) }
+            }),
+            method(syn::parse_quote! {
+                pub fn stop(&self) {}
+            }),
+        ];
+
+        assert_eq!(
+            client_js(&methods),
+            "// Generated by #[beamer::rpc]. Do not edit.\n\
+             \n\
+             export function loadSample(filePath, slot) {\n  \
+             return window.__BEAMER__.invoke(\"loadSample\", filePath, slot);\n\
+             }\n\
+             \n\
+             export function stop() {\n  \
+             return window.__BEAMER__.invoke(\"stop\");\n\
+             }\n"
+        );
+        assert_eq!(
+            client_dts(&methods),
+            "// Generated by #[beamer::rpc]. Do not edit.\n\
+             \n\
+             /**\n \
+             * Load a sample into a slot.\n \
+             */\n\
+             export declare function loadSample(filePath: string, slot: number): Promise<number>;\n\
+             \n\
+             export declare function stop(): Promise<void>;\n"
+        );
+    }
+
+    #[test]
+    fn test_associated_functions_are_skipped() {
+        let item: ItemImpl = syn::parse_quote! {
+            impl Handler {
+                pub fn new() -> Self { Handler }
+                pub fn level(&self) -> f32 { 0.0 }
+            }
+        };
+        let output = rpc_impl(TokenStream::new(), item).unwrap().to_string();
+        assert!(output.contains("\"level\" =>"));
+        assert!(!output.contains("\"new\" =>"));
+
+        let item: ItemImpl = syn::parse_quote! {
+            impl Handler {
+                pub fn set_level(&mut self, level: f32) {}
+            }
+        };
+        let err = rpc_impl(TokenStream::new(), item).unwrap_err();
+        assert!(err.to_string().contains("must take `&self`"));
+    }
+}
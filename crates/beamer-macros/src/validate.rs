@@ -4,13 +4,18 @@
 
 use std::collections::HashMap;
 
-use crate::ir::{FieldIR, ParameterDefault, ParameterFieldIR, ParameterKind, ParameterType, ParametersIR};
+use beamer_utils::fnv1a_32;
+
+use crate::ir::{
+    FieldIR, ParameterDefault, ParameterFieldIR, ParameterKind, ParameterType, ParametersIR, RandomizeSpec,
+};
 
 /// Validate the IR for semantic correctness.
 pub fn validate(ir: &ParametersIR) -> syn::Result<()> {
     check_unique_string_ids(ir)?;
     check_no_hash_collisions(ir)?;
     validate_parameter_attributes(ir)?;
+    check_group_controls(ir)?;
     Ok(())
 }
 
@@ -60,6 +65,55 @@ fn check_no_hash_collisions(ir: &ParametersIR) -> syn::Result<()> {
     Ok(())
 }
 
+/// Check that `controls` groups and the `#[group_controls]` field come together
+/// and that the generated switch IDs don't collide with other parameters.
+fn check_group_controls(ir: &ParametersIR) -> syn::Result<()> {
+    let Some(field) = &ir.group_controls else {
+        if let Some(nested) = ir.controlled_groups().next() {
+            return Err(syn::Error::new(
+                nested.span,
+                format!(
+                    "group \"{}\" uses `controls` but the struct has no #[group_controls] field \
+                     (add `#[group_controls] pub controls: GroupControls`)",
+                    nested.group_name
+                ),
+            ));
+        }
+        return Ok(());
+    };
+
+    if ir.controlled_groups().next().is_none() {
+        return Err(syn::Error::new(
+            field.span(),
+            "#[group_controls] field requires at least one #[nested(group = \"...\", controls)] field",
+        ));
+    }
+
+    let mut seen: HashMap<u32, String> = ir
+        .parameter_fields()
+        .map(|parameter| (parameter.hash_id, parameter.string_id.clone()))
+        .collect();
+
+    for nested in ir.controlled_groups() {
+        for string_id in nested.control_string_ids() {
+            let hash = fnv1a_32(&string_id);
+            if let Some(first_id) = seen.get(&hash) {
+                return Err(syn::Error::new(
+                    nested.span,
+                    format!(
+                        "group control \"{}\" collides with \"{}\" (both hash to 0x{:08x}). \
+                         Rename the group or the parameter.",
+                        string_id, first_id, hash
+                    ),
+                ));
+            }
+            seen.insert(hash, string_id);
+        }
+    }
+
+    Ok(())
+}
+
 // =============================================================================
 // Declarative Attribute Validation
 // =============================================================================
@@ -90,6 +144,8 @@ fn validate_single_parameter(parameter: &ParameterFieldIR) -> syn::Result<()> {
 
     // Validate step size constraints
     validate_step_size(parameter)?;
+    validate_persistence(parameter)?;
+    validate_randomize_range(parameter)?;
 
     Ok(())
 }
@@ -144,7 +200,7 @@ fn validate_default_in_range(parameter: &ParameterFieldIR) -> syn::Result<()> {
 /// FloatParameter shows decimals, IntParameter shows integers.
 fn format_number(value: f64, parameter_type: ParameterType) -> String {
     match parameter_type {
-        ParameterType::Float => {
+        ParameterType::Float | ParameterType::TimeInSamples => {
             if value.fract() == 0.0 {
                 format!("{:.1}", value) // Show at least one decimal: 100.0
             } else {
@@ -211,6 +267,12 @@ fn validate_kind_type_consistency(parameter: &ParameterFieldIR) -> syn::Result<(
                 "EnumParameter should not have a 'kind' attribute",
             ));
         }
+        (ParameterType::TimeInSamples, _) => {
+            return Err(syn::Error::new(
+                parameter.span,
+                "TimeInSamples is always in seconds and should not have a 'kind' attribute",
+            ));
+        }
         _ => {}
     }
 
@@ -242,3 +304,135 @@ fn validate_step_size(parameter: &ParameterFieldIR) -> syn::Result<()> {
 
     Ok(())
 }
+
+/// Validate transient/no_save constraints.
+fn validate_persistence(parameter: &ParameterFieldIR) -> syn::Result<()> {
+    let attributes = &parameter.attributes;
+    if attributes.bypass && (attributes.transient || attributes.no_save) {
+        return Err(syn::Error::new(
+            parameter.span,
+            "bypass parameters must be saved and automatable (remove transient/no_save)",
+        ));
+    }
+    if attributes.bypass && attributes.internal {
+        return Err(syn::Error::new(
+            parameter.span,
+            "bypass parameters must be exposed to the host (remove internal)",
+        ));
+    }
+    if attributes.transient && attributes.no_save {
+        return Err(syn::Error::new(
+            parameter.span,
+            "transient already implies no_save (remove no_save)",
+        ));
+    }
+    Ok(())
+}
+
+/// Validate that a randomize range fits a numeric parameter's range.
+fn validate_randomize_range(parameter: &ParameterFieldIR) -> syn::Result<()> {
+    let Some(RandomizeSpec::Range(spec)) = &parameter.attributes.randomize else {
+        return Ok(());
+    };
+
+    let type_name = match parameter.parameter_type {
+        ParameterType::Bool => Some("BoolParameter"),
+        ParameterType::Enum => Some("EnumParameter"),
+        ParameterType::Float | ParameterType::Int | ParameterType::TimeInSamples => None,
+    };
+    if let Some(type_name) = type_name {
+        return Err(syn::Error::new(
+            spec.span,
+            format!(
+                "`randomize = <range>` is not supported on {type_name}; only FloatParameter, IntParameter \
+                 and TimeInSamples take a randomize range (use randomize = \"off\")"
+            ),
+        ));
+    }
+
+    if spec.start > spec.end {
+        return Err(syn::Error::new(
+            spec.span,
+            format!(
+                "randomize range start ({}) must not exceed end ({})",
+                format_number(spec.start, parameter.parameter_type),
+                format_number(spec.end, parameter.parameter_type),
+            ),
+        ));
+    }
+
+    let bounds = match &parameter.attributes.range {
+        Some(range) => Some((range.start, range.end)),
+        None => parameter.attributes.kind.and_then(|k| k.fixed_range()),
+    };
+    if let Some((start, end)) = bounds {
+        if spec.start < start || spec.end > end {
+            return Err(syn::Error::new(
+                spec.span,
+                format!(
+                    "randomize range {}..={} is outside range {}..={}",
+                    format_number(spec.start, parameter.parameter_type),
+                    format_number(spec.end, parameter.parameter_type),
+                    format_number(start, parameter.parameter_type),
+                    format_number(end, parameter.parameter_type),
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate_struct(input: syn::DeriveInput) -> syn::Result<()> {
+        validate(&crate::parse::parse(input)?)
+    }
+
+    #[test]
+    fn test_randomize_range_rejected_on_bool_and_enum() {
+        let error = validate_struct(syn::parse_quote! {
+            struct P {
+                #[parameter(id = "on", name = "On", default = false, randomize = 0.0..=1.0)]
+                on: BoolParameter,
+            }
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("`randomize = <range>` is not supported on BoolParameter"));
+
+        let error = validate_struct(syn::parse_quote! {
+            struct P {
+                #[parameter(id = "mode", name = "Mode", randomize = 0.0..=2.0)]
+                mode: EnumParameter<Mode>,
+            }
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("`randomize = <range>` is not supported on EnumParameter"));
+    }
+
+    #[test]
+    fn test_randomize_off_accepted_on_bool_and_enum() {
+        validate_struct(syn::parse_quote! {
+            struct P {
+                #[parameter(id = "on", name = "On", default = false, randomize = "off")]
+                on: BoolParameter,
+                #[parameter(id = "mode", name = "Mode", randomize = "off")]
+                mode: EnumParameter<Mode>,
+            }
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_randomize_range_accepted_on_float() {
+        validate_struct(syn::parse_quote! {
+            struct P {
+                #[parameter(id = "gain", name = "Gain", default = 0.0, range = -60.0..=12.0, randomize = -12.0..=0.0)]
+                gain: FloatParameter,
+            }
+        })
+        .unwrap();
+    }
+}
@@ -65,6 +65,35 @@ pub const fn fnv1a_32(s: &str) -> u32 {
     hash
 }
 
+/// Compute FNV-1a 64-bit hash of a byte slice.
+///
+/// The 64-bit variant of [`fnv1a_32`] for arbitrary data, such as
+/// serialized plugin state. Like the 32-bit version it is stable across
+/// platforms and compiler versions, so hashes can be stored and compared
+/// between sessions.
+///
+/// # Examples
+///
+/// ```
+/// use beamer_utils::fnv1a_64;
+///
+/// assert_eq!(fnv1a_64(b""), 0xcbf29ce484222325);
+/// ```
+#[inline]
+pub const fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    hash
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,4 +132,11 @@ mod tests {
         let id2 = fnv1a_32("frequency");
         assert_ne!(id1, id2);
     }
+
+    #[test]
+    fn test_fnv1a_64_vectors() {
+        // Known test vectors for "" and "a"
+        assert_eq!(fnv1a_64(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63dc4c8601ec8c);
+    }
 }
@@ -15,4 +15,4 @@
 
 pub mod hash;
 
-pub use hash::fnv1a_32;
+pub use hash::{fnv1a_32, fnv1a_64};
@@ -4,13 +4,17 @@
 
 use std::ffi::c_void;
 use std::marker::PhantomData;
+use std::sync::OnceLock;
 
-use beamer_core::Config;
+use beamer_core::{Config, PluginFormat};
 use vst3::com_scrape_types::MakeHeader;
 use vst3::{Class, ComWrapper, Steinberg::*};
 
 use crate::util::{copy_cstring, copy_wstring};
 
+/// Config with the VST3 override applied, resolved by the first factory.
+static FACTORY_CONFIG: OnceLock<&'static Config> = OnceLock::new();
+
 /// VST3 Plugin Factory.
 ///
 /// Generic over the component type C. Creates combined component instances
@@ -25,8 +29,13 @@ pub struct Factory<C> {
 impl<C> Factory<C> {
     /// Create a new factory with the given configuration.
     ///
-    /// Computes the VST3 TUIDs from the unified Config.
+    /// Applies the config's VST3 override and computes the VST3 TUIDs from
+    /// the unified Config.
     pub fn new(config: &'static Config) -> Self {
+        let config = *FACTORY_CONFIG.get_or_init(|| config.resolve(PluginFormat::Vst3));
+        beamer_core::logging::init(config);
+        beamer_core::host_trace::init();
+
         let parts = config.vst3_uid_parts();
         let component_uid = vst3::uid(parts[0], parts[1], parts[2], parts[3]);
         let controller_uid = config.vst3_controller_uid_parts().map(|p| {
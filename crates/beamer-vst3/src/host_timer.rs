@@ -0,0 +1,282 @@
+//! Main-thread timer that runs for the whole plugin lifetime, independent of
+//! the editor.
+//!
+//! Host notifications the audio thread raises (e.g.
+//! `HostServices::latency_changed()`) must be sent from the main thread.
+//! The editor's sync timer forwards them while it is open; this timer
+//! forwards them while it is closed.
+//!
+//! | Platform | Timer |
+//! |----------|-------|
+//! | macOS | `CFRunLoopTimer` on the main run loop |
+//! | Windows | `SetTimer()` on the thread that calls `initialize()` |
+//! | Linux | `IRunLoop` of the host context, if the host offers one |
+
+use std::ffi::c_void;
+
+use vst3::Steinberg::*;
+
+/// Interval between ticks, in milliseconds.
+const INTERVAL_MS: u32 = 50;
+
+/// Called on the main thread with the context passed to [`HostTimer::start()`].
+pub type TickFn = unsafe fn(context: *const c_void);
+
+/// A repeating main-thread timer. Stopped when dropped.
+pub struct HostTimer {
+    #[cfg(target_os = "macos")]
+    timer: cf::CFRunLoopTimerRef,
+    #[cfg(target_os = "macos")]
+    _tick: Box<Tick>,
+    #[cfg(target_os = "windows")]
+    id: usize,
+    #[cfg(target_os = "linux")]
+    run_loop: vst3::ComPtr<Linux::IRunLoop>,
+    #[cfg(target_os = "linux")]
+    handler: vst3::ComPtr<Linux::ITimerHandler>,
+}
+
+/// A tick function and its context.
+#[derive(Clone, Copy)]
+struct Tick {
+    tick: TickFn,
+    context: *const c_void,
+}
+
+impl Tick {
+    fn fire(&self) {
+        // SAFETY: The timer is stopped before the context is dropped (see start()).
+        unsafe { (self.tick)(self.context) };
+    }
+}
+
+impl HostTimer {
+    /// Call `tick(context)` on the main thread every 50 ms until dropped.
+    /// Returns None if there is no main-thread timer (Linux hosts without
+    /// `IRunLoop`).
+    ///
+    /// # Safety
+    ///
+    /// Must be called on the main thread. `host_context` must be null or the
+    /// host context passed to `initialize()`. `context` must stay valid for
+    /// `tick` until the returned timer is dropped.
+    #[cfg(target_os = "macos")]
+    pub unsafe fn start(_host_context: *mut FUnknown, tick: TickFn, context: *const c_void) -> Option<Self> {
+        let tick = Box::new(Tick { tick, context });
+        let mut timer_context = cf::CFRunLoopTimerContext {
+            version: 0,
+            info: std::ptr::from_ref(tick.as_ref()) as *mut c_void,
+            retain: std::ptr::null(),
+            release: std::ptr::null(),
+            copy_description: std::ptr::null(),
+        };
+        let interval = f64::from(INTERVAL_MS) / 1000.0;
+        // SAFETY: The context is copied by CFRunLoopTimerCreate; info points
+        // to the boxed tick, which lives as long as the timer.
+        let timer = unsafe {
+            cf::CFRunLoopTimerCreate(
+                std::ptr::null(),
+                cf::CFAbsoluteTimeGetCurrent() + interval,
+                interval,
+                0,
+                0,
+                timer_fired,
+                &mut timer_context,
+            )
+        };
+        if timer.is_null() {
+            return None;
+        }
+        // SAFETY: timer is a valid CFRunLoopTimer; the main run loop and
+        // kCFRunLoopCommonModes are always available.
+        unsafe { cf::CFRunLoopAddTimer(cf::CFRunLoopGetMain(), timer, cf::kCFRunLoopCommonModes) };
+        Some(Self { timer, _tick: tick })
+    }
+
+    /// See the macOS variant.
+    ///
+    /// # Safety
+    ///
+    /// See the macOS variant.
+    #[cfg(target_os = "windows")]
+    pub unsafe fn start(_host_context: *mut FUnknown, tick: TickFn, context: *const c_void) -> Option<Self> {
+        // SAFETY: A thread timer without a window; the callback runs on this
+        // thread's message loop.
+        let id = unsafe { win32::SetTimer(std::ptr::null_mut(), 0, INTERVAL_MS, Some(timer_proc)) };
+        if id == 0 {
+            return None;
+        }
+        TICKS.with(|ticks| ticks.borrow_mut().push((id, Tick { tick, context })));
+        Some(Self { id })
+    }
+
+    /// See the macOS variant.
+    ///
+    /// # Safety
+    ///
+    /// See the macOS variant.
+    #[cfg(target_os = "linux")]
+    pub unsafe fn start(host_context: *mut FUnknown, tick: TickFn, context: *const c_void) -> Option<Self> {
+        use Linux::IRunLoopTrait;
+
+        // SAFETY: host_context is null or a valid COM pointer per the caller's contract.
+        let run_loop = unsafe { vst3::ComRef::from_raw(host_context) }?.cast::<Linux::IRunLoop>()?;
+        let handler = vst3::ComWrapper::new(TimerHandler { tick: Tick { tick, context } })
+            .to_com_ptr::<Linux::ITimerHandler>()?;
+        // SAFETY: run_loop and handler are valid COM pointers.
+        if unsafe { run_loop.registerTimer(handler.as_ptr(), u64::from(INTERVAL_MS)) } != kResultOk {
+            return None;
+        }
+        Some(Self { run_loop, handler })
+    }
+
+    /// See the macOS variant.
+    ///
+    /// # Safety
+    ///
+    /// See the macOS variant.
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    pub unsafe fn start(_host_context: *mut FUnknown, _tick: TickFn, _context: *const c_void) -> Option<Self> {
+        None
+    }
+}
+
+impl Drop for HostTimer {
+    fn drop(&mut self) {
+        #[cfg(target_os = "macos")]
+        {
+            // SAFETY: timer was created in start() and is released once.
+            unsafe {
+                cf::CFRunLoopTimerInvalidate(self.timer);
+                cf::CFRelease(self.timer);
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            // SAFETY: Same id as returned by SetTimer().
+            unsafe { win32::KillTimer(std::ptr::null_mut(), self.id) };
+            TICKS.with(|ticks| ticks.borrow_mut().retain(|(id, _)| *id != self.id));
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            use Linux::IRunLoopTrait;
+
+            // SAFETY: Same run loop and handler as registered in start().
+            unsafe { self.run_loop.unregisterTimer(self.handler.as_ptr()) };
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// macOS run loop timer
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+extern "C" fn timer_fired(_timer: cf::CFRunLoopTimerRef, info: *mut c_void) {
+    // SAFETY: info is the boxed Tick owned by the HostTimer, which
+    // invalidates the timer before dropping it.
+    unsafe { &*(info as *const Tick) }.fire();
+}
+
+#[cfg(target_os = "macos")]
+mod cf {
+    use std::ffi::c_void;
+
+    pub type CFRunLoopRef = *mut c_void;
+    pub type CFRunLoopTimerRef = *mut c_void;
+    pub type CFStringRef = *const c_void;
+    pub type CFRunLoopTimerCallBack = extern "C" fn(timer: CFRunLoopTimerRef, info: *mut c_void);
+
+    #[repr(C)]
+    pub struct CFRunLoopTimerContext {
+        pub version: isize,
+        pub info: *mut c_void,
+        pub retain: *const c_void,
+        pub release: *const c_void,
+        pub copy_description: *const c_void,
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        pub static kCFRunLoopCommonModes: CFStringRef;
+        pub fn CFAbsoluteTimeGetCurrent() -> f64;
+        pub fn CFRunLoopGetMain() -> CFRunLoopRef;
+        pub fn CFRunLoopTimerCreate(
+            allocator: *const c_void,
+            fire_date: f64,
+            interval: f64,
+            flags: usize,
+            order: isize,
+            callout: CFRunLoopTimerCallBack,
+            context: *mut CFRunLoopTimerContext,
+        ) -> CFRunLoopTimerRef;
+        pub fn CFRunLoopAddTimer(run_loop: CFRunLoopRef, timer: CFRunLoopTimerRef, mode: CFStringRef);
+        pub fn CFRunLoopTimerInvalidate(timer: CFRunLoopTimerRef);
+        pub fn CFRelease(cf: *const c_void);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Win32 thread timer
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+thread_local! {
+    /// Running timers of this thread by id. Thread timers carry no context.
+    static TICKS: std::cell::RefCell<Vec<(usize, Tick)>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn timer_proc(_hwnd: *mut c_void, _msg: u32, id: usize, _time: u32) {
+    TICKS.with(|ticks| {
+        // Copy the tick out so it may start or stop timers
+        let tick = ticks.borrow().iter().find(|(timer, _)| *timer == id).map(|(_, tick)| *tick);
+        if let Some(tick) = tick {
+            tick.fire();
+        }
+    });
+}
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use std::ffi::c_void;
+
+    pub type TimerProc = unsafe extern "system" fn(hwnd: *mut c_void, msg: u32, id: usize, time: u32);
+
+    #[link(name = "user32")]
+    extern "system" {
+        pub fn SetTimer(hwnd: *mut c_void, id: usize, elapse_ms: u32, timer_proc: Option<TimerProc>) -> usize;
+        pub fn KillTimer(hwnd: *mut c_void, id: usize) -> i32;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linux run loop timer
+// ---------------------------------------------------------------------------
+
+/// `ITimerHandler` called by the host's run loop.
+#[cfg(target_os = "linux")]
+struct TimerHandler {
+    tick: Tick,
+}
+
+// SAFETY: The host calls onTimer() on the UI thread only.
+#[cfg(target_os = "linux")]
+unsafe impl Send for TimerHandler {}
+// SAFETY: See above.
+#[cfg(target_os = "linux")]
+unsafe impl Sync for TimerHandler {}
+
+#[cfg(target_os = "linux")]
+impl vst3::Class for TimerHandler {
+    type Interfaces = (Linux::ITimerHandler,);
+}
+
+#[cfg(target_os = "linux")]
+impl Linux::ITimerHandlerTrait for TimerHandler {
+    unsafe fn onTimer(&self) {
+        self.tick.fire();
+    }
+}
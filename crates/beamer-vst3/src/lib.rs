@@ -43,6 +43,8 @@
 
 pub mod export;
 pub mod factory;
+mod host_timer;
+pub mod native_view;
 pub mod processor;
 pub mod util;
 #[cfg(feature = "webview")]
@@ -0,0 +1,435 @@
+//! IPlugView wrapper for native editors (`GuiBackend::Native`).
+//!
+//! The editor draws into a child window of the host's window and reports
+//! parameter edits through [`EditorHost`], which forwards them to the
+//! host like the WebView's `param:begin`/`param:set`/`param:end` messages.
+
+use std::cell::UnsafeCell;
+use std::ffi::c_void;
+
+use beamer_core::{
+    AutomationPreview, ConstraintState, EditorHost, NativeEditor, ParameterChangeSource, ParameterExposure,
+    ParameterId, ParameterLog, ParameterStore, ParameterValue, ParentWindow, Size,
+};
+use vst3::Steinberg::Vst::{IComponentHandler, IComponentHandlerTrait};
+use vst3::Steinberg::*;
+use vst3::Class;
+
+/// Forwards a native editor's edits to the owning processor and the host.
+struct Vst3EditorHost {
+    /// Parameter store of the owning processor.
+    params: *const dyn ParameterStore,
+    /// Component handler for host notification, if the host set one.
+    handler: Option<vst3::ComPtr<IComponentHandler>>,
+    /// Host-visible parameter list. Edits to hidden parameters are applied
+    /// without host notification.
+    parameter_exposure: ParameterExposure,
+    parameter_constraints: ConstraintState,
+    automation_preview: AutomationPreview,
+    parameter_log: ParameterLog,
+}
+
+impl Vst3EditorHost {
+    /// The handler, if `id` is visible to the host.
+    fn handler_for(&self, id: ParameterId) -> Option<&vst3::ComPtr<IComponentHandler>> {
+        self.handler.as_ref().filter(|_| self.parameter_exposure.is_exposed(id))
+    }
+}
+
+impl EditorHost for Vst3EditorHost {
+    fn with_parameters(&self, f: &mut dyn FnMut(&dyn ParameterStore)) {
+        // SAFETY: params points to the plugin's parameters, which outlive
+        // the view.
+        f(unsafe { &*self.params });
+    }
+
+    fn begin_edit(&self, id: ParameterId) {
+        self.automation_preview.begin_gesture(id);
+        if let Some(handler) = self.handler_for(id) {
+            // SAFETY: handler is a valid COM pointer.
+            unsafe { handler.beginEdit(id) };
+        }
+    }
+
+    fn set_parameter(&self, id: ParameterId, normalized: ParameterValue) {
+        // SAFETY: See with_parameters().
+        let params = unsafe { &*self.params };
+        params.set_normalized(id, normalized);
+        self.automation_preview.user_value(id, normalized);
+        self.parameter_log.record(id, normalized, ParameterChangeSource::Gui);
+        // Enforce constraints before telling the host, so it records the
+        // value the plugin actually uses.
+        let mut pushed = Vec::new();
+        self.parameter_constraints.enforce(params, id, |adjusted, _| {
+            if adjusted != id {
+                pushed.push(adjusted);
+            }
+        });
+        if let Some(handler) = self.handler_for(id) {
+            // SAFETY: handler is a valid COM pointer.
+            unsafe { handler.performEdit(id, params.get_normalized(id)) };
+        }
+        // Parameters pushed along get a complete edit of their own.
+        for pushed_id in pushed {
+            if let Some(handler) = self.handler_for(pushed_id) {
+                // SAFETY: handler is a valid COM pointer.
+                unsafe {
+                    handler.beginEdit(pushed_id);
+                    handler.performEdit(pushed_id, params.get_normalized(pushed_id));
+                    handler.endEdit(pushed_id);
+                }
+            }
+        }
+    }
+
+    fn end_edit(&self, id: ParameterId) {
+        self.automation_preview.end_gesture(id);
+        if let Some(handler) = self.handler_for(id) {
+            // SAFETY: handler is a valid COM pointer.
+            unsafe { handler.endEdit(id) };
+        }
+    }
+}
+
+/// VST3 IPlugView implementation around a [`NativeEditor`].
+pub struct NativePlugView {
+    /// Boxed, so the Linux idle timer can point to it.
+    editor: UnsafeCell<Box<dyn NativeEditor>>,
+    params: *const dyn ParameterStore,
+    handler: Option<vst3::ComPtr<IComponentHandler>>,
+    parameter_exposure: ParameterExposure,
+    parameter_constraints: ConstraintState,
+    automation_preview: AutomationPreview,
+    parameter_log: ParameterLog,
+    size: UnsafeCell<Size>,
+    frame: UnsafeCell<*mut IPlugFrame>,
+    /// Timer on the host's run loop driving the editor's frames.
+    #[cfg(target_os = "linux")]
+    idle_timer: UnsafeCell<Option<IdleTimer>>,
+}
+
+// SAFETY: VST3 IPlugView methods are called from the UI thread only.
+unsafe impl Send for NativePlugView {}
+// SAFETY: VST3 IPlugView methods are called from the UI thread only.
+unsafe impl Sync for NativePlugView {}
+
+impl NativePlugView {
+    /// Create a view for `editor`.
+    ///
+    /// # Safety
+    ///
+    /// `params` must remain valid for the lifetime of this view (it points
+    /// to the plugin's parameter struct which outlives the editor).
+    /// `component_handler` must be a valid COM pointer or null; the view
+    /// holds its own reference.
+    pub unsafe fn new(
+        editor: Box<dyn NativeEditor>,
+        params: *const dyn ParameterStore,
+        component_handler: *mut IComponentHandler,
+        parameter_exposure: ParameterExposure,
+        parameter_constraints: ConstraintState,
+        automation_preview: AutomationPreview,
+        parameter_log: ParameterLog,
+    ) -> Self {
+        let size = editor.gui_size();
+        // SAFETY: component_handler is null or valid per the caller's contract.
+        let handler = unsafe { vst3::ComRef::from_raw(component_handler) }.map(|handler| handler.to_com_ptr());
+        Self {
+            editor: UnsafeCell::new(editor),
+            params,
+            handler,
+            parameter_exposure,
+            parameter_constraints,
+            automation_preview,
+            parameter_log,
+            size: UnsafeCell::new(size),
+            frame: UnsafeCell::new(std::ptr::null_mut()),
+            #[cfg(target_os = "linux")]
+            idle_timer: UnsafeCell::new(None),
+        }
+    }
+
+    /// A host for the editor opened in attached().
+    fn editor_host(&self) -> Vst3EditorHost {
+        Vst3EditorHost {
+            params: self.params,
+            handler: self.handler.clone(),
+            parameter_exposure: self.parameter_exposure.clone(),
+            parameter_constraints: self.parameter_constraints.clone(),
+            automation_preview: self.automation_preview.clone(),
+            parameter_log: self.parameter_log.clone(),
+        }
+    }
+}
+
+impl Class for NativePlugView {
+    type Interfaces = (IPlugView,);
+}
+
+#[allow(non_snake_case)]
+impl IPlugViewTrait for NativePlugView {
+    unsafe fn isPlatformTypeSupported(&self, r#type: FIDString) -> tresult {
+        if r#type.is_null() {
+            return kResultFalse;
+        }
+        // SAFETY: type_ is non-null and host provides null-terminated C string.
+        let type_str = unsafe { std::ffi::CStr::from_ptr(r#type) };
+
+        #[cfg(target_os = "macos")]
+        // SAFETY: kPlatformTypeNSView is a static null-terminated byte literal.
+        let supported = type_str == unsafe { std::ffi::CStr::from_ptr(kPlatformTypeNSView) };
+
+        #[cfg(target_os = "windows")]
+        // SAFETY: kPlatformTypeHWND is a static null-terminated byte literal.
+        let supported = type_str == unsafe { std::ffi::CStr::from_ptr(kPlatformTypeHWND) };
+
+        #[cfg(target_os = "linux")]
+        // SAFETY: kPlatformTypeX11EmbedWindowID is a static null-terminated byte literal.
+        let supported = type_str == unsafe { std::ffi::CStr::from_ptr(kPlatformTypeX11EmbedWindowID) };
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        let supported = false;
+
+        if supported { kResultOk } else { kResultFalse }
+    }
+
+    unsafe fn attached(&self, parent: *mut c_void, r#type: FIDString) -> tresult {
+        // SAFETY: r#type is forwarded from the host and we are in an unsafe fn.
+        if parent.is_null() || unsafe { self.isPlatformTypeSupported(r#type) } != kResultOk {
+            return kResultFalse;
+        }
+
+        #[cfg(target_os = "macos")]
+        let parent = ParentWindow::AppKit(parent);
+        #[cfg(target_os = "windows")]
+        let parent = ParentWindow::Win32(parent);
+        // The X11 window ID is passed in the pointer.
+        #[cfg(target_os = "linux")]
+        let parent = ParentWindow::X11(parent as u64);
+
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let editor = unsafe { &mut *self.editor.get() };
+        // SAFETY: The host's window outlives the view until removed().
+        if !unsafe { editor.open(parent, Box::new(self.editor_host())) } {
+            return kResultFalse;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // SAFETY: The frame is null or the host's IPlugFrame; the editor
+            // is boxed and outlives the timer (removed() and drop()).
+            let timer = unsafe { IdleTimer::start(*self.frame.get(), &mut **editor) };
+            if timer.is_none() {
+                log::warn!("Host provides no IRunLoop; the editor will not update.");
+            }
+            // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+            unsafe { *self.idle_timer.get() = timer };
+        }
+
+        editor.gui_opened();
+        kResultOk
+    }
+
+    unsafe fn removed(&self) -> tresult {
+        #[cfg(target_os = "linux")]
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        unsafe {
+            *self.idle_timer.get() = None;
+        }
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let editor = unsafe { &mut *self.editor.get() };
+        editor.gui_closed();
+        editor.close();
+        kResultOk
+    }
+
+    unsafe fn onWheel(&self, _distance: f32) -> tresult {
+        kResultFalse
+    }
+
+    unsafe fn onKeyDown(&self, _key: char16, _keyCode: int16, _modifiers: int16) -> tresult {
+        kResultFalse
+    }
+
+    unsafe fn onKeyUp(&self, _key: char16, _keyCode: int16, _modifiers: int16) -> tresult {
+        kResultFalse
+    }
+
+    unsafe fn getSize(&self, size: *mut ViewRect) -> tresult {
+        if size.is_null() {
+            return kInvalidArgument;
+        }
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let current = unsafe { *self.size.get() };
+        // SAFETY: size is non-null (checked above) and host guarantees validity.
+        let rect = unsafe { &mut *size };
+        rect.left = 0;
+        rect.top = 0;
+        rect.right = current.width as i32;
+        rect.bottom = current.height as i32;
+        kResultOk
+    }
+
+    unsafe fn onSize(&self, newSize: *mut ViewRect) -> tresult {
+        if newSize.is_null() {
+            return kInvalidArgument;
+        }
+        // SAFETY: newSize is non-null (checked above) and host guarantees validity.
+        let rect = unsafe { &*newSize };
+        let new_size = Size::new((rect.right - rect.left).max(0) as u32, (rect.bottom - rect.top).max(0) as u32);
+
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        unsafe { *self.size.get() = new_size };
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let editor = unsafe { &mut *self.editor.get() };
+        editor.gui_resized(new_size);
+        kResultOk
+    }
+
+    unsafe fn onFocus(&self, _state: TBool) -> tresult {
+        kResultOk
+    }
+
+    unsafe fn setFrame(&self, frame: *mut IPlugFrame) -> tresult {
+        let frame_ptr = self.frame.get();
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let old_frame = unsafe { *frame_ptr };
+
+        // Release old frame reference.
+        if !old_frame.is_null() {
+            // SAFETY: old_frame is a valid COM object. IPlugFrame inherits FUnknown.
+            unsafe {
+                let unknown = old_frame as *mut FUnknown;
+                ((*(*unknown).vtbl).release)(unknown);
+            };
+        }
+
+        // AddRef new frame.
+        if !frame.is_null() {
+            // SAFETY: frame is a valid COM object provided by the host.
+            unsafe {
+                let unknown = frame as *mut FUnknown;
+                ((*(*unknown).vtbl).addRef)(unknown);
+            };
+        }
+
+        // SAFETY: Single-threaded access guaranteed by VST3.
+        unsafe { *frame_ptr = frame };
+        kResultOk
+    }
+
+    unsafe fn canResize(&self) -> tresult {
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let editor = unsafe { &*self.editor.get() };
+        if editor.gui_constraints().resizable { kResultOk } else { kResultFalse }
+    }
+
+    unsafe fn checkSizeConstraint(&self, rect: *mut ViewRect) -> tresult {
+        if rect.is_null() {
+            return kInvalidArgument;
+        }
+        // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
+        let editor = unsafe { &*self.editor.get() };
+        let constraints = editor.gui_constraints();
+
+        // SAFETY: rect is non-null (checked above) and host guarantees validity.
+        let r = unsafe { &mut *rect };
+        let width = ((r.right - r.left).max(0) as u32).clamp(constraints.min.width, constraints.max.width);
+        let height = ((r.bottom - r.top).max(0) as u32).clamp(constraints.min.height, constraints.max.height);
+        r.right = r.left + width as i32;
+        r.bottom = r.top + height as i32;
+        kResultOk
+    }
+}
+
+// Close the editor and release the frame when dropped, in case removed()
+// was not called by the host.
+impl Drop for NativePlugView {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            *self.idle_timer.get_mut() = None;
+        }
+        self.editor.get_mut().close();
+
+        let frame = *self.frame.get_mut();
+        if !frame.is_null() {
+            // SAFETY: frame is a valid COM object. We hold a reference from setFrame.
+            unsafe {
+                let unknown = frame as *mut FUnknown;
+                ((*(*unknown).vtbl).release)(unknown);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linux run loop timer
+// ---------------------------------------------------------------------------
+
+/// A 60Hz timer on the host's `IRunLoop` calling [`NativeEditor::idle()`].
+/// Unregistered when dropped.
+#[cfg(target_os = "linux")]
+struct IdleTimer {
+    run_loop: vst3::ComPtr<Linux::IRunLoop>,
+    handler: vst3::ComPtr<Linux::ITimerHandler>,
+}
+
+#[cfg(target_os = "linux")]
+impl IdleTimer {
+    /// Register a timer driving `editor`. Returns None if the host has no
+    /// run loop.
+    ///
+    /// # Safety
+    ///
+    /// `frame` must be null or a valid IPlugFrame. `editor` must outlive the
+    /// returned timer.
+    unsafe fn start(frame: *mut IPlugFrame, editor: *mut dyn NativeEditor) -> Option<Self> {
+        use Linux::IRunLoopTrait;
+
+        // SAFETY: frame is null or a valid COM pointer per the caller's contract.
+        let run_loop = unsafe { vst3::ComRef::from_raw(frame) }?.cast::<Linux::IRunLoop>()?;
+        let handler = vst3::ComWrapper::new(IdleTimerHandler { editor }).to_com_ptr::<Linux::ITimerHandler>()?;
+        // SAFETY: run_loop and handler are valid COM pointers.
+        if unsafe { run_loop.registerTimer(handler.as_ptr(), 16) } != kResultOk {
+            return None;
+        }
+        Some(Self { run_loop, handler })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for IdleTimer {
+    fn drop(&mut self) {
+        use Linux::IRunLoopTrait;
+
+        // SAFETY: Same run loop and handler as registered in start().
+        unsafe { self.run_loop.unregisterTimer(self.handler.as_ptr()) };
+    }
+}
+
+/// `ITimerHandler` called by the host's run loop.
+#[cfg(target_os = "linux")]
+struct IdleTimerHandler {
+    editor: *mut dyn NativeEditor,
+}
+
+// SAFETY: The host calls onTimer() on the UI thread only.
+#[cfg(target_os = "linux")]
+unsafe impl Send for IdleTimerHandler {}
+// SAFETY: See above.
+#[cfg(target_os = "linux")]
+unsafe impl Sync for IdleTimerHandler {}
+
+#[cfg(target_os = "linux")]
+impl Class for IdleTimerHandler {
+    type Interfaces = (Linux::ITimerHandler,);
+}
+
+#[cfg(target_os = "linux")]
+impl Linux::ITimerHandlerTrait for IdleTimerHandler {
+    unsafe fn onTimer(&self) {
+        // SAFETY: The timer is unregistered before the editor is dropped.
+        unsafe { &mut *self.editor }.idle();
+    }
+}
@@ -27,17 +27,21 @@ use log::warn;
 use vst3::{Class, ComRef, Steinberg::Vst::*, Steinberg::*};
 
 use beamer_core::{
-    AuxiliaryBuffers, Buffer, BusInfo as CoreBusInfo, BusLayout,
-    BusType as CoreBusType, CachedBusConfig, CachedBusInfo, ChordInfo, ConversionBuffers,
-    Descriptor, FactoryPresets, FrameRate as CoreFrameRate, HasParameters, MidiBuffer, MidiCcState,
-    MidiEvent, MidiEventKind, NoPresets, NoteExpressionInt, NoteExpressionText,
-    NoteExpressionValue as CoreNoteExpressionValue, ParameterStore, Config, PluginSetup,
-    ProcessBufferStorage, ProcessContext as CoreProcessContext, Processor, ScaleInfo, SysEx,
-    SysExOutputPool, Transport, WebViewHandler, MAX_BUSES, MAX_CHANNELS, MAX_CHORD_NAME_SIZE,
-    MAX_EXPRESSION_TEXT_SIZE, MAX_SCALE_NAME_SIZE, MAX_SYSEX_SIZE,
+    AmbisonicOrder, AuxiliaryBuffers, Buffer, BusInfo as CoreBusInfo, BusLayout,
+    BusType as CoreBusType, CachedBusConfig, CachedBusInfo, ChordInfo, ConstraintState, ControlRate, ControllerPageState, ConversionBuffers, ResponseCurves,
+    descriptor_snapshot, negotiate_bus_layout, Descriptor, FactoryPresets, FrameRate as CoreFrameRate, HasParameters, MidiBuffer, MidiCcState, MpeState, NoteRanges,
+    MidiEvent, MidiEventKind, NoPresets, ParameterChangeSource, ParameterLog, MidiDirection, MidiMonitor, MidiPanic, NoteExpressionInt, NoteExpressionText,
+    NoteExpressionValue as CoreNoteExpressionValue, Oversampling, ParameterExposure, ParameterStore, Config, Quality,
+    ActivationGate, AutomationPreview, Autosave, CrashGuard, FrameMeters, GuiBackend, GuiDataChannels, HostServices, Notifications, NotifyLevel, MidiClockGenerator, PluginSetup, PresetCrossfade,
+    PrecisionPolicy, PresetNameCache, ProcessBufferStorage, ProcessContext as CoreProcessContext, ProcessWatchdog, ProcessingStats, Processor, SampleRateAdapter,
+    ChannelMixStage, ScaleInfo, SpeakerLayout, StandardTrims, SysEx, SysExOutputPool, TrimStage, Transport, TransportTracker, WebViewHandler, MAX_BUSES, MAX_CHANNELS,
+    MAX_CHORD_NAME_SIZE, MAX_EXPRESSION_TEXT_SIZE, MAX_SCALE_NAME_SIZE, MAX_SYSEX_SIZE,
 };
 
+use beamer_core::host_trace::{self, HostCall};
+
 use crate::factory::ComponentFactory;
+use crate::host_timer::HostTimer;
 use crate::util::{copy_wstring, len_wstring};
 
 // VST3 event type constants
@@ -165,6 +169,31 @@ fn validate_speaker_arrangement(arrangement: SpeakerArrangement) -> Result<(), S
     Ok(())
 }
 
+/// Report a parameter value to the host through the output parameter changes.
+///
+/// Used for values the plugin changes inside `process()` (preset crossfades,
+/// constraint enforcement), where `restartComponent` can't be called.
+///
+/// # Safety
+///
+/// `process_data` must be the `ProcessData` passed to the current `process()`
+/// call.
+unsafe fn report_output_parameter(process_data: &ProcessData, id: u32, value: f64) {
+    // SAFETY: outputParameterChanges may be null; ComRef::from_raw handles this.
+    let Some(output_changes) = (unsafe { ComRef::from_raw(process_data.outputParameterChanges) }) else {
+        return;
+    };
+    let mut queue_index = 0;
+    // SAFETY: output_changes is valid ComRef; id and index are valid pointers.
+    let queue = unsafe { output_changes.addParameterData(&id, &mut queue_index) };
+    // SAFETY: addParameterData may return null; ComRef::from_raw handles this.
+    if let Some(queue) = unsafe { ComRef::from_raw(queue) } {
+        let mut point_index = 0;
+        // SAFETY: queue is valid ComRef; point_index is a valid pointer.
+        let _ = unsafe { queue.addPoint(0, value, &mut point_index) };
+    }
+}
+
 
 // =============================================================================
 // Setup Extraction
@@ -174,7 +203,14 @@ fn validate_speaker_arrangement(arrangement: SpeakerArrangement) -> Result<(), S
 ///
 /// Creates a HostSetup with all available information, then uses the
 /// `PluginSetup::extract` method to extract only what the plugin needs.
-fn build_setup<S: PluginSetup>(setup: &ProcessSetup, bus_layout: &BusLayout) -> S {
+/// `sample_rate` is the processing rate, which differs from the host rate
+/// when `Config::sample_rates` forces resampling.
+fn build_setup<S: PluginSetup>(
+    setup: &ProcessSetup,
+    sample_rate: f64,
+    bus_layout: &BusLayout,
+    quality: Quality,
+) -> S {
     use beamer_core::{HostSetup, ProcessMode};
 
     // Convert VST3 process mode to our ProcessMode
@@ -184,12 +220,19 @@ fn build_setup<S: PluginSetup>(setup: &ProcessSetup, bus_layout: &BusLayout) ->
         _ => ProcessMode::Realtime,      // kRealtime (0) or unknown
     };
 
+    let max_buffer_size = setup.maxSamplesPerBlock as usize;
+    let max_buffer_size = if sample_rate == setup.sampleRate {
+        max_buffer_size
+    } else {
+        SampleRateAdapter::<f32>::processing_block_size(max_buffer_size, sample_rate / setup.sampleRate)
+    };
     let host_setup = HostSetup::new(
-        setup.sampleRate,
-        setup.maxSamplesPerBlock as usize,
+        sample_rate,
+        max_buffer_size,
         bus_layout.clone(),
         process_mode,
-    );
+    )
+    .with_quality(quality);
 
     S::extract(&host_setup)
 }
@@ -202,6 +245,16 @@ fn build_setup<S: PluginSetup>(setup: &ProcessSetup, bus_layout: &BusLayout) ->
 // to avoid collision with vst3::Steinberg::Vst::BusInfo used in COM interfaces.
 // We use CoreBusInfo throughout this module for the beamer type.
 
+/// Buses negotiated in setBusArrangements (`Descriptor::accepts_bus_layout`).
+struct BusArrangement {
+    /// Channel counts of each bus.
+    layout: BusLayout,
+    /// Speaker layout the host named for each input bus, if recognized.
+    input_speakers: Vec<Option<SpeakerLayout>>,
+    /// Speaker layout the host named for each output bus, if recognized.
+    output_speakers: Vec<Option<SpeakerLayout>>,
+}
+
 /// Internal state machine for plugin lifecycle.
 ///
 /// The wrapper manages two states:
@@ -292,6 +345,8 @@ where
     max_block_size: UnsafeCell<usize>,
     /// Current symbolic sample size (kSample32 or kSample64)
     symbolic_sample_size: UnsafeCell<i32>,
+    /// Process mode from the last setupProcessing()
+    process_mode: UnsafeCell<i32>,
     /// MIDI input buffer (reused each process call to avoid stack overflow)
     midi_input: UnsafeCell<MidiBuffer>,
     /// MIDI output buffer (reused each process call)
@@ -310,11 +365,111 @@ where
     /// Current factory preset index (0-based, or -1 for no preset / custom state)
     /// Used for the program change parameter exposed to the host
     current_preset_index: UnsafeCell<i32>,
+    /// Preallocated program names so repeated getProgramName() calls don't allocate.
+    preset_names: UnsafeCell<PresetNameCache>,
+    /// Declicking fade for program changes (`Config::preset_crossfade_ms`).
+    /// Atomic internally; requested from the controller, driven by process().
+    preset_crossfade: PresetCrossfade,
+    /// Transport-locked MIDI clock/MMC output (`Processor::midi_clock_output`).
+    midi_clock: UnsafeCell<MidiClockGenerator>,
+    /// Detects transport start/stop/relocation for `Processor::relocation_policy`.
+    transport_tracker: UnsafeCell<TransportTracker>,
+    /// Per-note MPE tracking (`Descriptor::mpe_config`).
+    mpe_state: UnsafeCell<Option<MpeState>>,
+    /// Playable note range and splits (`Descriptor::note_ranges`).
+    note_ranges: NoteRanges,
+    /// Resamplers around process() when the host rate is outside
+    /// `Config::sample_rates` (f32 also serves the f64→f32 conversion path).
+    resampler_f32: UnsafeCell<Option<SampleRateAdapter<f32>>>,
+    resampler_f64: UnsafeCell<Option<SampleRateAdapter<f64>>>,
+    /// Input/output trim and polarity (`Config::standard_trims`), listed
+    /// after the plugin's parameters and applied around process().
+    standard_trims: StandardTrims,
+    trims_f32: UnsafeCell<Option<TrimStage<f32>>>,
+    trims_f64: UnsafeCell<Option<TrimStage<f64>>>,
+    /// Read-only DSP load, voice count and underruns
+    /// (`Config::processing_stats`), listed after the standard trims.
+    processing_stats: ProcessingStats,
+    /// Main input converted to the output width inside process()
+    /// (`Config::channel_mix`).
+    channel_mix_f32: UnsafeCell<Option<ChannelMixStage<f32>>>,
+    channel_mix_f64: UnsafeCell<Option<ChannelMixStage<f64>>>,
+    /// Control-rate ticks around process() (`Processor::control_interval`).
+    control_rate: UnsafeCell<ControlRate>,
+    /// Speaker layouts of the prepared main input and output buses.
+    speaker_layouts: UnsafeCell<(SpeakerLayout, SpeakerLayout)>,
+    /// Bus layout the host was last told about (`Descriptor::bus_layout_id`).
+    bus_layout_id: UnsafeCell<u32>,
+    /// A different layout was selected while prepared; the processor is
+    /// rebuilt with it at the next setupProcessing().
+    bus_layout_pending: UnsafeCell<bool>,
+    /// Buses negotiated in setBusArrangements; `None` uses the declared ones.
+    bus_arrangement: UnsafeCell<Option<BusArrangement>>,
+    /// Oversampling the processor was prepared with (`Descriptor::oversampling`).
+    oversampling: UnsafeCell<Oversampling>,
+    /// Quality mode the processor was prepared with (`Descriptor::quality`).
+    quality: UnsafeCell<Quality>,
+    /// Another factor or quality mode was selected while prepared; the
+    /// processor is prepared again when the host deactivates it.
+    oversampling_pending: UnsafeCell<bool>,
+    /// Safe mode for states that repeatedly crash on load. Shared with the
+    /// WebView so the GUI can report it.
+    crash_guard: CrashGuard,
+    /// User parameters visible to the host (internal ones hidden until
+    /// promoted). Shared with the WebView for promote/demote.
+    parameter_exposure: ParameterExposure,
+    /// Hardware controller pages. Knob CCs map to the selected page via
+    /// IMidiMapping; shared with the WebView for page selection.
+    controller_pages: ControllerPageState,
+    /// Constraints between parameters, enforced after every set. Shared
+    /// with the WebView for GUI edits.
+    parameter_constraints: ConstraintState,
+    /// Velocity and controller response curves, applied to MIDI input and
+    /// CC readouts. Shared with the WebView for editing.
+    response_curves: ResponseCurves,
+    /// Host automation versus GUI values, fed from process() and shared
+    /// with the WebView.
+    automation_preview: AutomationPreview,
+    /// Crash-safe autosave (disabled unless the descriptor opts in). Shared
+    /// with the WebView for the recovery prompt.
+    autosave: Autosave,
+    /// Parameter change log (disabled unless the descriptor opts in). Shared
+    /// with the WebView for the log dump.
+    parameter_log: ParameterLog,
+    /// MIDI monitor (disabled unless the descriptor opts in). Shared with
+    /// the WebView, which streams it.
+    midi_monitor: MidiMonitor,
+    /// MIDI panic trigger (enabled for plugins that want MIDI), listed as a
+    /// hidden parameter after the processing statistics. Shared with the
+    /// WebView.
+    midi_panic: MidiPanic,
+    /// Plugin requests for the host (state dirty mark), shared with the
+    /// descriptor.
+    host_services: HostServices,
+    /// License activation check and the output policy until it succeeds.
+    /// Shared with the WebView for the activation status.
+    activation: ActivationGate,
+    /// Meters sent with each GUI frame, shared with the descriptor.
+    frame_meters: FrameMeters,
+    /// Frame streams for the GUI, shared with the descriptor.
+    gui_data: GuiDataChannels,
+    /// Messages for the GUI, shared with the descriptor and the WebView.
+    notifications: Notifications,
+    /// Deadline watchdog for `process()` (`Config::watchdog`), shared with
+    /// the WebView for overrun reports.
+    watchdog: ProcessWatchdog,
+    /// Instance ID in the host call trace (`host-trace` feature).
+    trace_id: u32,
     /// Component handler for notifying host of parameter changes
     /// Stored as raw pointer - host manages lifetime, we just AddRef/Release
     component_handler: UnsafeCell<*mut IComponentHandler>,
+    /// Main-thread timer forwarding latency changes while the editor is
+    /// closed. Runs from initialize() to terminate().
+    host_timer: UnsafeCell<Option<HostTimer>>,
     /// Custom WebView message handler (invoke/event routing).
     webview_handler: Option<Arc<dyn WebViewHandler>>,
+    /// Editor backend chosen by the descriptor (`Descriptor::editor()`).
+    editor: GuiBackend,
     /// Marker for the plugin type and preset collection
     _marker: PhantomData<(P, Presets)>,
 }
@@ -350,10 +505,38 @@ where
         let plugin = P::default();
 
         // Create MidiCcState from plugin's config (framework-managed)
-        let midi_cc_state = plugin.midi_cc_config().map(|cfg| MidiCcState::from_config(&cfg));
+        let response_curves = plugin.response_curves();
+        let midi_cc_state = plugin
+            .midi_cc_config()
+            .map(|cfg| MidiCcState::from_config(&cfg).with_response_curves(response_curves.clone()));
+        let mpe_state = plugin.mpe_config().map(MpeState::new);
+        let note_ranges = plugin.note_ranges();
 
         // Capture the WebView handler (if any) before the descriptor is consumed.
         let webview_handler = plugin.webview_handler();
+        let editor = plugin.editor();
+        let parameter_exposure = ParameterExposure::new(plugin.parameters());
+        let controller_pages = ControllerPageState::new(&plugin.controller_pages(), plugin.parameters());
+        let parameter_constraints =
+            ConstraintState::new(&plugin.parameter_constraints(), plugin.parameters());
+        let automation_preview = AutomationPreview::new(plugin.parameters());
+        let autosave = Autosave::new(config, plugin.autosave_interval());
+        let parameter_log = plugin.parameter_log();
+        let midi_monitor = plugin.midi_monitor();
+        let midi_panic = plugin.midi_panic();
+        let host_services = plugin.host_services();
+        let activation = ActivationGate::new(plugin.activation());
+        activation.activation().start();
+        let frame_meters = plugin.frame_meters();
+        let gui_data = plugin.gui_data();
+        let notifications = plugin.notifications();
+        let watchdog = ProcessWatchdog::new(config.watchdog).with_notifications(notifications.clone());
+        let bus_layout_id = P::bus_layout_id(plugin.parameters());
+        let mut preset_names = PresetNameCache::new();
+        preset_names.reserve(Presets::count());
+        preset_names.take_list_changed::<Presets>();
+        let trace_id = host_trace::next_instance_id();
+        host_trace::trace(trace_id, HostCall::Create);
 
         Self {
             state: UnsafeCell::new(PluginState::Unprepared {
@@ -364,6 +547,7 @@ where
             sample_rate: UnsafeCell::new(44100.0),
             max_block_size: UnsafeCell::new(1024),
             symbolic_sample_size: UnsafeCell::new(SymbolicSampleSizes_::kSample32 as i32),
+            process_mode: UnsafeCell::new(0),
             midi_input: UnsafeCell::new(MidiBuffer::new()),
             midi_output: UnsafeCell::new(MidiBuffer::new()),
             sysex_output_pool: UnsafeCell::new(SysExOutputPool::with_capacity(
@@ -375,8 +559,49 @@ where
             buffer_storage_f64: UnsafeCell::new(ProcessBufferStorage::new()),
             midi_cc_state,
             current_preset_index: UnsafeCell::new(0), // Default to first preset
+            preset_names: UnsafeCell::new(preset_names),
+            preset_crossfade: PresetCrossfade::new(),
+            midi_clock: UnsafeCell::new(MidiClockGenerator::new()),
+            transport_tracker: UnsafeCell::new(TransportTracker::new()),
+            mpe_state: UnsafeCell::new(mpe_state),
+            note_ranges,
+            resampler_f32: UnsafeCell::new(None),
+            resampler_f64: UnsafeCell::new(None),
+            standard_trims: StandardTrims::enabled(config.standard_trims),
+            processing_stats: ProcessingStats::enabled(config.processing_stats),
+            trims_f32: UnsafeCell::new(None),
+            trims_f64: UnsafeCell::new(None),
+            channel_mix_f32: UnsafeCell::new(None),
+            channel_mix_f64: UnsafeCell::new(None),
+            control_rate: UnsafeCell::new(ControlRate::new()),
+            speaker_layouts: UnsafeCell::new((SpeakerLayout::Stereo, SpeakerLayout::Stereo)),
+            bus_layout_id: UnsafeCell::new(bus_layout_id),
+            bus_layout_pending: UnsafeCell::new(false),
+            bus_arrangement: UnsafeCell::new(None),
+            oversampling: UnsafeCell::new(Oversampling::Off),
+            quality: UnsafeCell::new(Quality::Normal),
+            oversampling_pending: UnsafeCell::new(false),
+            crash_guard: CrashGuard::new(config),
+            parameter_exposure,
+            controller_pages,
+            parameter_constraints,
+            response_curves,
+            automation_preview,
+            autosave,
+            parameter_log,
+            midi_monitor,
+            midi_panic,
+            host_services,
+            activation,
+            frame_meters,
+            gui_data,
+            notifications,
+            watchdog,
+            trace_id,
             component_handler: UnsafeCell::new(std::ptr::null_mut()),
+            host_timer: UnsafeCell::new(None),
             webview_handler,
+            editor,
             _marker: PhantomData,
         }
     }
@@ -499,9 +724,103 @@ where
     // Bus Info Access (works in both states)
     // =========================================================================
 
+    /// Descriptor reporting a newly selected bus layout while the prepared
+    /// processor still runs the old one (see `check_bus_layout`).
+    unsafe fn pending_bus_descriptor(&self) -> Option<P> {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if !unsafe { *self.bus_layout_pending.get() } {
+            return None;
+        }
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        match unsafe { &*self.state.get() } {
+            PluginState::Prepared { processor, .. } => {
+                Some(descriptor_snapshot::<P>(processor.parameters()))
+            }
+            PluginState::Unprepared { .. } => None,
+        }
+    }
+
+    /// Ask the host to re-read the buses if the parameters now select a
+    /// different layout (`Descriptor::bus_layout_id`).
+    unsafe fn check_bus_layout(&self) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let id = P::bus_layout_id(unsafe { self.parameters() });
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let known = unsafe { &mut *self.bus_layout_id.get() };
+        if id == *known {
+            return;
+        }
+        *known = id;
+        // The negotiated channel counts belonged to the old buses
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe { *self.bus_arrangement.get() = None };
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if matches!(unsafe { &*self.state.get() }, PluginState::Prepared { .. }) {
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            unsafe { *self.bus_layout_pending.get() = true };
+        }
+        self.restart_component(RestartFlags_::kIoChanged);
+    }
+
+    /// Rate the DSP runs at: the supported rate for `host_rate` times the
+    /// prepared oversampling factor.
+    unsafe fn processing_rate(&self, host_rate: f64) -> f64 {
+        let rate = self.config.sample_rates.processing_rate(host_rate);
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe { *self.oversampling.get() }.apply(rate)
+    }
+
+    /// Ask the host to restart the processor if the parameters now select a
+    /// different oversampling factor or quality mode
+    /// (`Descriptor::oversampling`, `Descriptor::quality`).
+    ///
+    /// The audio thread keeps the prepared setup; the new one is prepared
+    /// in setActive(false), after which the host reads the new latency.
+    unsafe fn check_oversampling(&self) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if !matches!(unsafe { &*self.state.get() }, PluginState::Prepared { .. }) {
+            return;
+        }
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let parameters = unsafe { self.parameters() };
+        let selected = (P::oversampling(parameters), P::quality(parameters));
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let pending = unsafe { &mut *self.oversampling_pending.get() };
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if *pending || selected == unsafe { (*self.oversampling.get(), *self.quality.get()) } {
+            return;
+        }
+        *pending = true;
+        self.restart_component(RestartFlags_::kLatencyChanged);
+    }
+
+    /// Prepare the processor again with the stored setup after another
+    /// oversampling factor or quality mode was selected.
+    unsafe fn apply_pending_oversampling(&self) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if !unsafe { *self.oversampling_pending.get() } {
+            return;
+        }
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let mut setup = unsafe {
+            ProcessSetup {
+                processMode: *self.process_mode.get(),
+                symbolicSampleSize: *self.symbolic_sample_size.get(),
+                maxSamplesPerBlock: *self.max_block_size.get() as i32,
+                sampleRate: *self.sample_rate.get(),
+            }
+        };
+        // SAFETY: setup is a valid ProcessSetup on the stack.
+        unsafe { self.setupProcessing(&mut setup) };
+    }
+
     /// Get input bus count (works in both states).
     #[inline]
     unsafe fn input_bus_count(&self) -> usize {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if let Some(plugin) = unsafe { self.pending_bus_descriptor() } {
+            return plugin.input_bus_count();
+        }
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
             PluginState::Unprepared { plugin, .. } => plugin.input_bus_count(),
@@ -512,6 +831,10 @@ where
     /// Get output bus count (works in both states).
     #[inline]
     unsafe fn output_bus_count(&self) -> usize {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if let Some(plugin) = unsafe { self.pending_bus_descriptor() } {
+            return plugin.output_bus_count();
+        }
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
             PluginState::Unprepared { plugin, .. } => plugin.output_bus_count(),
@@ -523,9 +846,15 @@ where
     /// Returns beamer_core::BusInfo (not vst3::BusInfo).
     #[inline]
     unsafe fn core_input_bus_info(&self, index: usize) -> Option<CoreBusInfo> {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if let Some(plugin) = unsafe { self.pending_bus_descriptor() } {
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            return unsafe { self.arranged_input_bus(&plugin, index) };
+        }
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
-            PluginState::Unprepared { plugin, .. } => plugin.input_bus_info(index),
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            PluginState::Unprepared { plugin, .. } => unsafe { self.arranged_input_bus(plugin, index) },
             PluginState::Prepared { input_buses, .. } => input_buses.get(index).cloned(),
         }
     }
@@ -534,13 +863,49 @@ where
     /// Returns beamer_core::BusInfo (not vst3::BusInfo).
     #[inline]
     unsafe fn core_output_bus_info(&self, index: usize) -> Option<CoreBusInfo> {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if let Some(plugin) = unsafe { self.pending_bus_descriptor() } {
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            return unsafe { self.arranged_output_bus(&plugin, index) };
+        }
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
-            PluginState::Unprepared { plugin, .. } => plugin.output_bus_info(index),
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            PluginState::Unprepared { plugin, .. } => unsafe { self.arranged_output_bus(plugin, index) },
             PluginState::Prepared { output_buses, .. } => output_buses.get(index).cloned(),
         }
     }
 
+    /// Declared input bus `index` with the channel count and speakers
+    /// negotiated in setBusArrangements, if any.
+    unsafe fn arranged_input_bus(&self, plugin: &P, index: usize) -> Option<CoreBusInfo> {
+        let info = plugin.input_bus_info(index)?;
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        Some(match unsafe { &*self.bus_arrangement.get() } {
+            Some(arrangement) => arranged_bus(
+                info,
+                arrangement.layout.input_bus_channels(index),
+                arrangement.input_speakers.get(index).copied().flatten(),
+            ),
+            None => info,
+        })
+    }
+
+    /// Declared output bus `index` with the channel count and speakers
+    /// negotiated in setBusArrangements, if any.
+    unsafe fn arranged_output_bus(&self, plugin: &P, index: usize) -> Option<CoreBusInfo> {
+        let info = plugin.output_bus_info(index)?;
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        Some(match unsafe { &*self.bus_arrangement.get() } {
+            Some(arrangement) => arranged_bus(
+                info,
+                arrangement.layout.output_bus_channels(index),
+                arrangement.output_speakers.get(index).copied().flatten(),
+            ),
+            None => info,
+        })
+    }
+
     // =========================================================================
     // Parameter Access (works in both states)
     // =========================================================================
@@ -562,6 +927,22 @@ where
         }
     }
 
+    /// Parameters answering host calls for `id`: the standard trims,
+    /// processing statistics and MIDI panic for their IDs, the plugin's
+    /// parameters otherwise.
+    unsafe fn host_store(&self, id: u32) -> &dyn ParameterStore {
+        if self.standard_trims.by_id(id).is_some() {
+            &self.standard_trims
+        } else if self.processing_stats.by_id(id).is_some() {
+            &self.processing_stats
+        } else if self.midi_panic.is_enabled() && MidiPanic::is_panic_parameter(id) {
+            &self.midi_panic
+        } else {
+            // SAFETY: Caller upholds the single-threaded access guarantee.
+            unsafe { self.parameters() }
+        }
+    }
+
     /// Get mutable parameters (works in both states).
     ///
     /// # Safety
@@ -581,6 +962,188 @@ where
         }
     }
 
+    /// Ask the host to re-read parameter values or the parameter list.
+    ///
+    /// Does nothing until the host has installed a component handler.
+    fn restart_component(&self, flags: int32) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let handler = unsafe { *self.component_handler.get() };
+        if !handler.is_null() {
+            // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
+            unsafe {
+                ((*(*handler).vtbl).restartComponent)(handler, flags);
+            }
+        }
+    }
+
+    /// Forward a state dirty mark from the plugin via
+    /// `IComponentHandler2::setDirty`.
+    ///
+    /// The mark stays pending until the host has set a component handler.
+    fn flush_state_dirty(&self) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let handler = unsafe { *self.component_handler.get() };
+        if handler.is_null() || !self.host_services.take_state_dirty() {
+            return;
+        }
+        // SAFETY: handler is non-null and is a valid COM pointer.
+        if let Some(handler) = unsafe { ComRef::from_raw(handler) } {
+            if let Some(handler2) = handler.cast::<IComponentHandler2>() {
+                // SAFETY: handler2 is a valid IComponentHandler2 reference.
+                unsafe { handler2.setDirty(1) };
+            }
+        }
+    }
+
+    /// Ask the host to re-read the latency after the plugin announced a
+    /// change (`HostServices::latency_changed()`).
+    ///
+    /// The change stays pending until the host has set a component handler.
+    fn flush_latency_changed(&self) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let handler = unsafe { *self.component_handler.get() };
+        if handler.is_null() || !self.host_services.take_latency_changed() {
+            return;
+        }
+        self.restart_component(RestartFlags_::kLatencyChanged);
+    }
+
+    /// Tick of the host timer: forward notifications the audio thread
+    /// raised, with or without an editor open.
+    ///
+    /// # Safety
+    ///
+    /// `context` must point to this processor and be called on the main thread.
+    unsafe fn host_timer_tick(context: *const c_void) {
+        // SAFETY: The timer is stopped in terminate() or dropped with the processor.
+        let processor = unsafe { &*(context as *const Self) };
+        processor.flush_latency_changed();
+    }
+
+    /// Ask the host to re-read the program list after presets were added,
+    /// removed or renamed at runtime (`FactoryPresets::names_version()`).
+    ///
+    /// The change stays pending until the host has set a component handler.
+    fn check_preset_list(&self) {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let handler = unsafe { *self.component_handler.get() };
+        if handler.is_null() {
+            return;
+        }
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if !unsafe { &mut *self.preset_names.get() }.take_list_changed::<Presets>() {
+            return;
+        }
+        // SAFETY: handler is non-null and is a valid COM pointer.
+        if let Some(handler) = unsafe { ComRef::from_raw(handler) } {
+            if let Some(unit_handler) = handler.cast::<IUnitHandler>() {
+                // SAFETY: unit_handler is a valid IUnitHandler reference.
+                unsafe { unit_handler.notifyProgramListChange(FACTORY_PRESETS_LIST_ID, -1) };
+            }
+        }
+        // The program change parameter's step count follows the list.
+        self.restart_component(RestartFlags_::kParamTitlesChanged);
+    }
+
+    /// Serialize the state as getState() writes it: the processor state
+    /// followed by the exposure, controller page, standard trim and response
+    /// curve trailers.
+    ///
+    /// Empty while unprepared (there is no processor state yet).
+    unsafe fn state_blob(&self) -> Result<Vec<u8>, ()> {
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        match unsafe { &*self.state.get() } {
+            PluginState::Unprepared { .. } => Ok(Vec::new()),
+            PluginState::Prepared { processor, .. } => {
+                let mut data = processor.save_state().map_err(|_| ())?;
+                self.parameter_exposure.append_state(&mut data);
+                self.controller_pages.append_state(&mut data);
+                self.standard_trims.append_state(&mut data);
+                self.response_curves.append_state(&mut data);
+                Ok(data)
+            }
+        }
+    }
+
+    /// Apply a state blob written by [`state_blob()`](Self::state_blob).
+    unsafe fn apply_state_blob(&self, mut buffer: Vec<u8>) -> tresult {
+        // Restore the response curves, standard trims, the selected
+        // controller page and the promoted parameter set, stripping their
+        // trailers in reverse order.
+        let curves_len = self.response_curves.load_state(&buffer);
+        buffer.truncate(curves_len);
+        let trims_len = self.standard_trims.load_state(&buffer);
+        buffer.truncate(trims_len);
+        let pages_len = self.controller_pages.load_state(&buffer);
+        buffer.truncate(pages_len);
+        if self.controller_pages.take_changed() {
+            self.restart_component(RestartFlags_::kMidiCCAssignmentChanged);
+        }
+        let plugin_len = self.parameter_exposure.load_state(&buffer);
+        buffer.truncate(plugin_len);
+        if self.parameter_exposure.take_changed() {
+            self.restart_component(RestartFlags_::kParamTitlesChanged);
+        }
+
+        // Load state based on current state
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        match unsafe { &mut *self.state.get() } {
+            PluginState::Unprepared { pending_state, .. } => {
+                // Store for deferred loading when prepare() is called
+                *pending_state = Some(buffer);
+                self.parameter_log.record_state();
+                kResultOk
+            }
+            PluginState::Prepared { processor, .. } => {
+                match self.crash_guard.load_state(processor, &buffer) {
+                    Ok(()) => {
+                        // Apply current sample rate and reset smoothers
+                        use beamer_core::parameter_types::Parameters;
+                        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                        let sample_rate = unsafe { *self.sample_rate.get() };
+                        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                        let sample_rate = unsafe { self.processing_rate(sample_rate) };
+                        if sample_rate > 0.0 {
+                            processor.parameters_mut().set_sample_rate(sample_rate);
+                            processor.recompute_rate_dependent(sample_rate);
+                        }
+                        processor.parameters_mut().reset_smoothing();
+                        self.parameter_constraints.enforce_all(processor.parameters(), |_, _| {});
+                        self.parameter_log.record_state();
+                        // The loaded state may select another bus layout
+                        // or oversampling factor
+                        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                        unsafe {
+                            self.check_bus_layout();
+                            self.check_oversampling();
+                        }
+                        self.flush_latency_changed();
+                        kResultOk
+                    }
+                    Err(e) => {
+                        self.notifications.notify(
+                            NotifyLevel::Error,
+                            format!("The saved state could not be loaded: {e}"),
+                        );
+                        kResultFalse
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hand the autosave a snapshot if one is due.
+    unsafe fn autosave_snapshot(&self) {
+        if self.autosave.is_due() {
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            if let Ok(data) = unsafe { self.state_blob() } {
+                if !data.is_empty() {
+                    self.autosave.snapshot(data);
+                }
+            }
+        }
+    }
+
     // =========================================================================
     // Processor Method Access (works in both states)
     // =========================================================================
@@ -600,12 +1163,20 @@ where
     /// Get latency samples (works in both states).
     ///
     /// Returns 0 when unprepared (conservative default), processor's value when prepared.
+    /// Includes the resampler delay when the host rate is not supported.
     #[inline]
     unsafe fn latency_samples(&self) -> u32 {
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
             PluginState::Unprepared { .. } => 0,
-            PluginState::Prepared { processor, .. } => processor.latency_samples(),
+            PluginState::Prepared { processor, .. } => {
+                let latency = processor.latency_samples();
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                match unsafe { &*self.resampler_f32.get() } {
+                    Some(resampler) => resampler.latency_samples(latency),
+                    None => latency,
+                }
+            }
         }
     }
 
@@ -618,23 +1189,44 @@ where
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
             PluginState::Unprepared { .. } => 0,
-            PluginState::Prepared { processor, .. } => processor.tail_samples(),
+            PluginState::Prepared { processor, .. } => processor.tail_time().to_samples(),
         }
     }
 
-    /// Check if processor supports double precision (works in both states).
+    /// Check if processor runs its main bus at double precision (works in both states).
     ///
-    /// Returns false when unprepared (conservative default), processor's value when prepared.
+    /// Returns false when unprepared (conservative default), processor's
+    /// `precision_policy().main_is_f64()` when prepared.
     #[inline]
     #[allow(dead_code)] // API method for potential future use
     unsafe fn supports_double_precision(&self) -> bool {
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
             PluginState::Unprepared { .. } => false,
-            PluginState::Prepared { processor, .. } => processor.supports_double_precision(),
+            PluginState::Prepared { processor, .. } => processor.precision_policy().main_is_f64(),
         }
     }
 
+    /// Conversion buffers for the buses `policy` processes at f32.
+    ///
+    /// Mixed-precision aux conversion runs inside the resampler, so it is
+    /// sized for the processing-rate block (`ratio` = processing / host rate).
+    fn allocate_conversion_buffers(
+        policy: PrecisionPolicy,
+        input_buses: &[CoreBusInfo],
+        output_buses: &[CoreBusInfo],
+        max_frames: usize,
+        ratio: f64,
+    ) -> ConversionBuffers {
+        let frames = match policy {
+            PrecisionPolicy::F64MainF32Aux => {
+                max_frames.max(SampleRateAdapter::<f64>::processing_block_size(max_frames, ratio))
+            }
+            _ => max_frames,
+        };
+        ConversionBuffers::allocate_for_policy(policy, input_buses, output_buses, frames)
+    }
+
     // =========================================================================
     // Audio Processing Helpers
     // =========================================================================
@@ -655,10 +1247,11 @@ where
     // refactoring to a macro-based approach.
     //
     // TODO: Null buffer handling - Currently we skip null channel pointers.
-    // This is correct for VST3's parameter flushing (numSamples=0). Some hosts
-    // may send null buffers with non-zero numSamples. Consider adding internal
-    // buffer fallback like beamer-au does for instruments if this becomes an
-    // issue. For now, VST3 hosts are generally compliant.
+    // Parameter flushes (numSamples=0) never reach these paths; process()
+    // returns after parameters and MIDI. Some hosts may send null buffers
+    // with non-zero numSamples. Consider adding internal buffer fallback like
+    // beamer-au does for instruments if this becomes an issue. For now, VST3
+    // hosts are generally compliant.
     // =========================================================================
 
     /// Process audio at 32-bit (f32) precision.
@@ -795,15 +1388,37 @@ where
         });
 
         // Construct buffers and process
-        let mut buffer = Buffer::new(main_in_iter, main_out_iter, num_samples);
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let (input_layout, output_layout) = unsafe { *self.speaker_layouts.get() };
+        let mut buffer = Buffer::new(main_in_iter, main_out_iter, num_samples)
+            .with_speaker_layouts(input_layout, output_layout);
         let mut aux = AuxiliaryBuffers::new(aux_in_iter, aux_out_iter, num_samples);
 
-        processor.process(&mut buffer, &mut aux, context);
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let resampler = unsafe { &mut *self.resampler_f32.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let trims = unsafe { &mut *self.trims_f32.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let channel_mix = unsafe { &mut *self.channel_mix_f32.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let control = unsafe { &mut *self.control_rate.get() };
+        TrimStage::run(trims.as_mut(), &mut buffer, &mut aux, context, |b, a, c| {
+            SampleRateAdapter::run(resampler.as_mut(), b, a, c, |b, a, c| {
+                ChannelMixStage::run(channel_mix.as_mut(), b, a, c, |b, a, c| {
+                    control.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                })
+            })
+        });
+        self.preset_crossfade.finish(&mut buffer);
+        self.activation.finish(&mut buffer);
     }
 
     /// Process audio at 64-bit (f64) precision with native plugin support.
     ///
-    /// Used when host uses kSample64 and processor.supports_double_precision() is true.
+    /// Used when host uses kSample64 and the processor's precision policy runs
+    /// the main bus at f64. With `PrecisionPolicy::F64MainF32Aux` only the aux
+    /// buses are converted (inside the resampler, if one is active) and
+    /// `process_f64_mixed()` is called instead of `process_f64()`.
     /// Uses pre-allocated ProcessBufferStorage - no heap allocations.
     #[inline]
     unsafe fn process_audio_f64_native(
@@ -812,6 +1427,7 @@ where
         num_samples: usize,
         processor: &mut P::Processor,
         context: &CoreProcessContext,
+        policy: PrecisionPolicy,
     ) {
         // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
         let storage = unsafe { &mut *self.buffer_storage_f64.get() };
@@ -935,16 +1551,43 @@ where
         });
 
         // Construct buffers and process
-        let mut buffer: Buffer<f64> = Buffer::new(main_in_iter, main_out_iter, num_samples);
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let (input_layout, output_layout) = unsafe { *self.speaker_layouts.get() };
+        let mut buffer: Buffer<f64> = Buffer::new(main_in_iter, main_out_iter, num_samples)
+            .with_speaker_layouts(input_layout, output_layout);
         let mut aux: AuxiliaryBuffers<f64> =
             AuxiliaryBuffers::new(aux_in_iter, aux_out_iter, num_samples);
 
-        processor.process_f64(&mut buffer, &mut aux, context);
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let resampler = unsafe { &mut *self.resampler_f64.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let conv = unsafe { &mut *self.conversion_buffers.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let trims = unsafe { &mut *self.trims_f64.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let channel_mix = unsafe { &mut *self.channel_mix_f64.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let control = unsafe { &mut *self.control_rate.get() };
+        TrimStage::run(trims.as_mut(), &mut buffer, &mut aux, context, |b, a, c| {
+            SampleRateAdapter::run(resampler.as_mut(), b, a, c, |b, a, c| {
+                ChannelMixStage::run(channel_mix.as_mut(), b, a, c, |b, a, c| {
+                    control.run(processor, b, a, c, |p, b, a, c| {
+                        if policy.aux_is_f64() {
+                            p.process_f64(b, a, c)
+                        } else {
+                            conv.with_f32_aux(a, |a| p.process_f64_mixed(b, a, c))
+                        }
+                    })
+                })
+            })
+        });
+        self.preset_crossfade.finish(&mut buffer);
+        self.activation.finish(&mut buffer);
     }
 
     /// Process audio at 64-bit (f64) with conversion to/from f32.
     ///
-    /// Used when host uses kSample64 but processor.supports_double_precision() is false.
+    /// Used when host uses kSample64 and the processor's precision policy is `PrecisionPolicy::F32`.
     /// Converts f64→f32, calls process(), converts f32→f64.
     #[inline]
     unsafe fn process_audio_f64_converted(
@@ -1025,10 +1668,29 @@ where
             .map(|bus| bus.iter_mut().map(|v| &mut v[..num_samples]));
 
         // Construct f32 buffers and process
-        let mut buffer = Buffer::new(main_input_iter, main_output_iter, num_samples);
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let (input_layout, output_layout) = unsafe { *self.speaker_layouts.get() };
+        let mut buffer = Buffer::new(main_input_iter, main_output_iter, num_samples)
+            .with_speaker_layouts(input_layout, output_layout);
         let mut aux = AuxiliaryBuffers::new(aux_input_iter, aux_output_iter, num_samples);
 
-        processor.process(&mut buffer, &mut aux, context);
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let resampler = unsafe { &mut *self.resampler_f32.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let trims = unsafe { &mut *self.trims_f32.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let channel_mix = unsafe { &mut *self.channel_mix_f32.get() };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let control = unsafe { &mut *self.control_rate.get() };
+        TrimStage::run(trims.as_mut(), &mut buffer, &mut aux, context, |b, a, c| {
+            SampleRateAdapter::run(resampler.as_mut(), b, a, c, |b, a, c| {
+                ChannelMixStage::run(channel_mix.as_mut(), b, a, c, |b, a, c| {
+                    control.run(processor, b, a, c, |p, b, a, c| p.process(b, a, c))
+                })
+            })
+        });
+        self.preset_crossfade.finish(&mut buffer);
+        self.activation.finish(&mut buffer);
 
         // Convert main output f32 → f64
         if process_data.numOutputs > 0 && !process_data.outputs.is_null() {
@@ -1111,6 +1773,7 @@ where
         IKeyswitchController,
         INoteExpressionPhysicalUIMapping,
         IVst3WrapperMPESupport,
+        ChannelContext::IInfoListener,
     );
 }
 
@@ -1122,11 +1785,21 @@ impl<P: Descriptor + 'static, Presets> IPluginBaseTrait for Vst3Processor<P, Pre
 where
     Presets: FactoryPresets<Parameters = P::Parameters>,
 {
-    unsafe fn initialize(&self, _context: *mut FUnknown) -> tresult {
+    unsafe fn initialize(&self, context: *mut FUnknown) -> tresult {
+        host_trace::trace(self.trace_id, HostCall::Initialize);
+        // SAFETY: VST3 calls initialize() on the main thread with its host
+        // context. The processor stays at this address inside its COM
+        // wrapper and outlives the timer.
+        let timer = unsafe { HostTimer::start(context, Self::host_timer_tick, std::ptr::from_ref(self).cast()) };
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe { *self.host_timer.get() = timer };
         kResultOk
     }
 
     unsafe fn terminate(&self) -> tresult {
+        host_trace::trace(self.trace_id, HostCall::Terminate);
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe { *self.host_timer.get() = None };
         kResultOk
     }
 }
@@ -1268,19 +1941,59 @@ where
     unsafe fn activateBus(
         &self,
         _media_type: MediaType,
-        _dir: BusDirection,
-        _index: i32,
-        _state: TBool,
+        dir: BusDirection,
+        index: i32,
+        state: TBool,
     ) -> tresult {
+        host_trace::trace(
+            self.trace_id,
+            HostCall::ActivateBus {
+                input: dir as BusDirections == BusDirections_::kInput,
+                index: index as u32,
+                active: state != 0,
+            },
+        );
         kResultOk
     }
 
     unsafe fn setActive(&self, state: TBool) -> tresult {
+        host_trace::trace(self.trace_id, HostCall::SetActive(state != 0));
+        // Deactivated after kLatencyChanged: switch to the new oversampling
+        // factor before the host asks for the latency.
+        if state == 0 {
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            unsafe { self.apply_pending_oversampling() };
+            // Log overruns that no open editor collected
+            let _ = self.watchdog.take_report();
+        }
         // set_active is only meaningful when prepared (processor exists)
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         if let PluginState::Prepared { processor, .. } = unsafe { &mut *self.state.get() } {
             processor.set_active(state != 0);
         }
+        // Activation already cleared DSP state; don't report the first block as a jump
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe { &mut *self.transport_tracker.get() }.reset();
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if let Some(mpe_state) = unsafe { &mut *self.mpe_state.get() } {
+            mpe_state.reset();
+        }
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe {
+            if let Some(resampler) = &mut *self.resampler_f32.get() {
+                resampler.reset();
+            }
+            if let Some(resampler) = &mut *self.resampler_f64.get() {
+                resampler.reset();
+            }
+            if let Some(trims) = &mut *self.trims_f32.get() {
+                trims.reset();
+            }
+            if let Some(trims) = &mut *self.trims_f64.get() {
+                trims.reset();
+            }
+            (*self.control_rate.get()).reset();
+        }
         // When unprepared, silently succeed (host may call this before setupProcessing)
         kResultOk
     }
@@ -1316,36 +2029,17 @@ where
 
             buffer.extend_from_slice(&chunk[..bytes_read as usize]);
         }
+        host_trace::trace(self.trace_id, HostCall::SetState { bytes: buffer.len() });
 
         if buffer.is_empty() {
             return kResultOk;
         }
 
-        // Load state based on current state
+        // Offer edits made on top of this state before a crash.
+        self.autosave.claim(&buffer);
+
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
-        match unsafe { &mut *self.state.get() } {
-            PluginState::Unprepared { pending_state, .. } => {
-                // Store for deferred loading when prepare() is called
-                *pending_state = Some(buffer);
-                kResultOk
-            }
-            PluginState::Prepared { processor, .. } => {
-                match processor.load_state(&buffer) {
-                    Ok(()) => {
-                        // Apply current sample rate and reset smoothers
-                        use beamer_core::parameter_types::Parameters;
-                        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
-                        let sample_rate = unsafe { *self.sample_rate.get() };
-                        if sample_rate > 0.0 {
-                            processor.parameters_mut().set_sample_rate(sample_rate);
-                        }
-                        processor.parameters_mut().reset_smoothing();
-                        kResultOk
-                    }
-                    Err(_) => kResultFalse,
-                }
-            }
-        }
+        unsafe { self.apply_state_blob(buffer) }
     }
 
     unsafe fn getState(&self, state: *mut IBStream) -> tresult {
@@ -1353,25 +2047,20 @@ where
             return kInvalidArgument;
         }
 
-        // Get state from processor (only available when prepared)
+        // Get state from processor (only available when prepared; some
+        // hosts call this before prepare and get an empty success)
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
-        let data: Vec<u8> = match unsafe { &*self.state.get() } {
-            PluginState::Unprepared { .. } => {
-                // When unprepared, we can't save processor state
-                // Return empty success (some hosts call this before prepare)
-                return kResultOk;
-            }
-            PluginState::Prepared { processor, .. } => {
-                match processor.save_state() {
-                    Ok(d) => d,
-                    Err(_) => return kResultFalse,
-                }
-            }
+        let Ok(data) = (unsafe { self.state_blob() }) else {
+            return kResultFalse;
         };
+        host_trace::trace(self.trace_id, HostCall::GetState { bytes: data.len() });
 
         if data.is_empty() {
             return kResultOk;
         }
+        self.autosave.record_host_state(&data);
+        // The saved state already holds any change marked dirty.
+        self.host_services.take_state_dirty();
 
         // Write to IBStream
         // SAFETY: state is non-null and host guarantees it points to valid IBStream.
@@ -1397,6 +2086,60 @@ where
     }
 }
 
+// =============================================================================
+// WebView state access (autosave)
+// =============================================================================
+
+#[cfg(feature = "webview")]
+impl<P: Descriptor + 'static, Presets> crate::webview::StateAccess for Vst3Processor<P, Presets>
+where
+    Presets: FactoryPresets<Parameters = P::Parameters>,
+{
+    fn snapshot_state(&self) {
+        // SAFETY: The WebView calls this from the UI thread, like the host's
+        // controller calls. No aliasing.
+        unsafe { self.autosave_snapshot() };
+    }
+
+    fn flush_state_dirty(&self) {
+        Vst3Processor::flush_state_dirty(self);
+    }
+
+    fn flush_latency_changed(&self) {
+        Vst3Processor::flush_latency_changed(self);
+    }
+
+    fn check_oversampling(&self) {
+        // SAFETY: The WebView calls this from the UI thread, like the host's
+        // controller calls. No aliasing.
+        unsafe { Vst3Processor::check_oversampling(self) };
+    }
+
+    fn check_preset_list(&self) {
+        Vst3Processor::check_preset_list(self);
+    }
+
+    fn restore_state(&self, data: Vec<u8>) -> bool {
+        // SAFETY: The WebView calls this from the UI thread, like the host's
+        // controller calls. No aliasing.
+        let applied = unsafe { self.apply_state_blob(data) } == kResultOk;
+        if applied {
+            self.restart_component(RestartFlags_::kParamValuesChanged);
+        }
+        applied
+    }
+
+    fn state_blob(&self) -> Vec<u8> {
+        // SAFETY: The WebView calls this from the UI thread, like the host's
+        // controller calls. No aliasing.
+        unsafe { Vst3Processor::state_blob(self) }.unwrap_or_default()
+    }
+
+    fn plugin_config(&self) -> &'static Config {
+        self.config
+    }
+}
+
 // =============================================================================
 // IAudioProcessor implementation
 // =============================================================================
@@ -1425,49 +2168,84 @@ where
         if (num_ins > 0 && inputs.is_null()) || (num_outs > 0 && outputs.is_null()) {
             return kInvalidArgument;
         }
+        host_trace::trace(self.trace_id, {
+            let main_channels = |arrangements: *mut SpeakerArrangement, count: i32| {
+                if count > 0 {
+                    // SAFETY: non-null when the count is positive (checked above).
+                    unsafe { *arrangements }.count_ones()
+                } else {
+                    0
+                }
+            };
+            HostCall::SetBusArrangements {
+                inputs: num_ins as u32,
+                outputs: num_outs as u32,
+                main_input_channels: main_channels(inputs, num_ins),
+                main_output_channels: main_channels(outputs, num_outs),
+            }
+        });
 
-        // Check if the requested arrangement matches our bus configuration
-        // SAFETY: VST3 guarantees single-threaded access for this call.
-        let input_count = unsafe { self.input_bus_count() };
-        // SAFETY: VST3 guarantees single-threaded access for this call.
-        let output_count = unsafe { self.output_bus_count() };
-        if num_ins as usize != input_count || num_outs as usize != output_count {
-            return kResultFalse;
-        }
-
-        // Validate each input bus
+        // Early rejection: channel count exceeds compile-time limits
+        let mut input_channels = Vec::with_capacity(num_ins as usize);
+        let mut input_speakers = Vec::with_capacity(num_ins as usize);
         for i in 0..num_ins as usize {
-            // Early rejection: channel count exceeds compile-time limits
             // SAFETY: inputs is non-null (checked above) and host guarantees validity for num_ins.
             let requested = unsafe { *inputs.add(i) };
             if validate_speaker_arrangement(requested).is_err() {
                 return kResultFalse;
             }
-
-            // SAFETY: VST3 guarantees single-threaded access for this call.
-            if let Some(info) = unsafe { self.core_input_bus_info(i) } {
-                let expected = channel_count_to_speaker_arrangement(info.channel_count);
-                if requested != expected {
-                    return kResultFalse;
-                }
-            }
+            input_channels.push(requested.count_ones());
+            input_speakers.push(speaker_layout_from_arrangement(requested));
         }
-
-        // Validate each output bus
+        let mut output_channels = Vec::with_capacity(num_outs as usize);
+        let mut output_speakers = Vec::with_capacity(num_outs as usize);
         for i in 0..num_outs as usize {
-            // Early rejection: channel count exceeds compile-time limits
             // SAFETY: outputs is non-null (checked above) and host guarantees validity for num_outs.
             let requested = unsafe { *outputs.add(i) };
             if validate_speaker_arrangement(requested).is_err() {
                 return kResultFalse;
             }
+            output_channels.push(requested.count_ones());
+            output_speakers.push(speaker_layout_from_arrangement(requested));
+        }
+
+        // Accept the declared channel counts, or others the plugin agrees to
+        // (`Descriptor::accepts_bus_layout`). The bus counts must match.
+        // SAFETY: VST3 guarantees single-threaded access for this call.
+        let state = unsafe { &*self.state.get() };
+        let layout = match state {
+            PluginState::Unprepared { plugin, .. } => {
+                negotiate_bus_layout(plugin, &input_channels, &output_channels)
+            }
+            PluginState::Prepared { processor, .. } => negotiate_bus_layout(
+                &descriptor_snapshot::<P>(processor.parameters()),
+                &input_channels,
+                &output_channels,
+            ),
+        };
+        let Some(layout) = layout else {
+            return kResultFalse;
+        };
+        // SAFETY: VST3 guarantees single-threaded access for this call.
+        unsafe {
+            *self.bus_arrangement.get() = Some(BusArrangement { layout, input_speakers, output_speakers });
+        }
 
+        // Prepared with other buses: prepare again at the next setupProcessing()
+        if let PluginState::Prepared { processor, input_buses, output_buses, .. } = state {
+            let plugin = descriptor_snapshot::<P>(processor.parameters());
+            let same = |arranged: Option<CoreBusInfo>, prepared: Option<&CoreBusInfo>| {
+                arranged.map(|bus| bus.layout()) == prepared.map(CoreBusInfo::layout)
+            };
             // SAFETY: VST3 guarantees single-threaded access for this call.
-            if let Some(info) = unsafe { self.core_output_bus_info(i) } {
-                let expected = channel_count_to_speaker_arrangement(info.channel_count);
-                if requested != expected {
-                    return kResultFalse;
-                }
+            let changed = unsafe {
+                (0..input_buses.len()).any(|i| !same(self.arranged_input_bus(&plugin, i), input_buses.get(i)))
+                    || (0..output_buses.len())
+                        .any(|i| !same(self.arranged_output_bus(&plugin, i), output_buses.get(i)))
+            };
+            if changed {
+                // SAFETY: VST3 guarantees single-threaded access for this call.
+                unsafe { *self.bus_layout_pending.get() = true };
             }
         }
 
@@ -1498,7 +2276,7 @@ where
 
         if let Some(info) = info {
             // SAFETY: arr is non-null (checked above) and host guarantees validity.
-            unsafe { *arr = channel_count_to_speaker_arrangement(info.channel_count) };
+            unsafe { *arr = speaker_layout_to_arrangement(info.layout()) };
             kResultOk
         } else {
             kInvalidArgument
@@ -1525,6 +2303,15 @@ where
 
         // SAFETY: setup is non-null and host guarantees it points to valid ProcessSetup.
         let setup = unsafe { &*setup };
+        host_trace::trace(
+            self.trace_id,
+            HostCall::SetupProcessing {
+                sample_rate: setup.sampleRate,
+                max_block_size: setup.maxSamplesPerBlock as u32,
+                double_precision: setup.symbolicSampleSize == SymbolicSampleSizes_::kSample64 as i32,
+                offline: setup.processMode == ProcessModes_::kOffline as i32,
+            },
+        );
 
         // Store setup parameters
         // SAFETY: VST3 guarantees single-threaded access during setupProcessing(). No aliasing.
@@ -1532,33 +2319,88 @@ where
             *self.sample_rate.get() = setup.sampleRate;
             *self.max_block_size.get() = setup.maxSamplesPerBlock as usize;
             *self.symbolic_sample_size.get() = setup.symbolicSampleSize;
+            *self.process_mode.get() = setup.processMode;
         }
+        self.preset_crossfade
+            .prepare(self.config.preset_crossfade_ms, setup.sampleRate);
+        self.activation.prepare(setup.sampleRate);
+        self.watchdog.set_sample_rate(setup.sampleRate);
+        self.processing_stats.set_sample_rate(setup.sampleRate);
 
         // Handle state transition
         // SAFETY: VST3 guarantees single-threaded access during setupProcessing(). No aliasing.
         let state = unsafe { &mut *self.state.get() };
+
+        // A new bus layout or oversampling factor was selected: go back to
+        // the descriptor so it is prepared below with the new choice.
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let bus_layout_pending = unsafe { &mut *self.bus_layout_pending.get() };
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let oversampling_pending = unsafe { &mut *self.oversampling_pending.get() };
+        let layout_changed = std::mem::take(bus_layout_pending);
+        if layout_changed | std::mem::take(oversampling_pending) {
+            let old_state = std::mem::replace(
+                state,
+                PluginState::Unprepared { plugin: P::default(), pending_state: None },
+            );
+            if let PluginState::Prepared { processor, .. } = old_state {
+                // A new bus layout rebuilds the processor; its full state is
+                // loaded into the new one below
+                let pending_state = if layout_changed { processor.save_state().ok() } else { None };
+                *state = PluginState::Unprepared {
+                    plugin: processor.unprepare(),
+                    pending_state,
+                };
+            }
+        }
+
+        // The oversampling factor and quality mode only change while unprepared
+        if let PluginState::Unprepared { plugin, .. } = &*state {
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            unsafe {
+                *self.oversampling.get() = P::oversampling(plugin.parameters());
+                *self.quality.get() = P::quality(plugin.parameters());
+            }
+        }
+        // Rate the DSP runs at; differs from the host rate only when
+        // resampling or oversampling
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let processing_rate = unsafe { self.processing_rate(setup.sampleRate) };
+
         match state {
             PluginState::Unprepared { plugin, pending_state } => {
-                // Cache bus info before consuming the plugin
+                // Cache bus info before consuming the plugin, with the
+                // channel counts negotiated in setBusArrangements
                 let input_bus_count = plugin.input_bus_count();
                 let output_bus_count = plugin.output_bus_count();
                 let input_buses: Vec<CoreBusInfo> = (0..input_bus_count)
-                    .filter_map(|i| plugin.input_bus_info(i))
+                    // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                    .filter_map(|i| unsafe { self.arranged_input_bus(plugin, i) })
                     .collect();
                 let output_buses: Vec<CoreBusInfo> = (0..output_bus_count)
-                    .filter_map(|i| plugin.output_bus_info(i))
+                    // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                    .filter_map(|i| unsafe { self.arranged_output_bus(plugin, i) })
                     .collect();
 
-                let bus_layout = BusLayout::from_plugin(plugin);
+                let bus_layout = BusLayout::from_buses(&input_buses, &output_buses);
+                let main_layout = |buses: &[CoreBusInfo]| buses.first().map_or(SpeakerLayout::Stereo, CoreBusInfo::layout);
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                unsafe { *self.speaker_layouts.get() = (main_layout(&input_buses), main_layout(&output_buses)) };
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                unsafe { *self.bus_layout_id.get() = P::bus_layout_id(plugin.parameters()) };
 
-                // Validate plugin's bus configuration against compile-time limits
-                if let Err(msg) = CachedBusConfig::from_plugin(plugin).validate() {
+                // Validate the bus configuration against compile-time limits
+                let bus_config = CachedBusConfig::new(
+                    input_buses.iter().map(CachedBusInfo::from_bus_info).collect(),
+                    output_buses.iter().map(CachedBusInfo::from_bus_info).collect(),
+                );
+                if let Err(msg) = bus_config.validate() {
                     log::error!("Plugin bus configuration exceeds limits: {}", msg);
                     return kResultFalse;
                 }
 
                 // Build the plugin setup
-                let plugin_setup = build_setup::<P::Setup>(setup, &bus_layout);
+                let plugin_setup = build_setup::<P::Setup>(setup, processing_rate, &bus_layout, P::quality(plugin.parameters()));
 
                 // Take ownership of the plugin and any pending state
                 let plugin = std::mem::take(plugin);
@@ -1569,17 +2411,15 @@ where
 
                 // Apply any pending state that was set before preparation
                 if let Some(data) = pending {
-                    let _ = processor.load_state(&data);
-                    // Update parameters sample rate after loading
-                    use beamer_core::Parameters;
-                    processor.parameters_mut().set_sample_rate(setup.sampleRate);
+                    let _ = self.crash_guard.load_state(&mut processor, &data);
+                    self.parameter_constraints.enforce_all(processor.parameters(), |_, _| {});
                 }
+                // Smoothers and TimeInSamples parameters follow the processing rate
+                use beamer_core::Parameters;
+                processor.parameters_mut().set_sample_rate(processing_rate);
+                processor.recompute_rate_dependent(processing_rate);
 
                 // Pre-allocate buffer storage based on bus config
-                let bus_config = CachedBusConfig::new(
-                    input_buses.iter().map(CachedBusInfo::from_bus_info).collect(),
-                    output_buses.iter().map(CachedBusInfo::from_bus_info).collect(),
-                );
                 let max_frames = setup.maxSamplesPerBlock as usize;
                 // SAFETY: VST3 guarantees single-threaded access. No aliasing.
                 unsafe {
@@ -1589,14 +2429,17 @@ where
                         ProcessBufferStorage::allocate_from_config(&bus_config, max_frames);
                 }
 
-                // Pre-allocate conversion buffers for f64→f32 processing
-                if setup.symbolicSampleSize == SymbolicSampleSizes_::kSample64 as i32
-                    && !processor.supports_double_precision()
-                {
+                // Pre-allocate conversion buffers for the buses processed at f32
+                if setup.symbolicSampleSize == SymbolicSampleSizes_::kSample64 as i32 {
                     // SAFETY: VST3 guarantees single-threaded access. No aliasing.
                     unsafe {
-                        *self.conversion_buffers.get() =
-                            ConversionBuffers::allocate_from_buses(&input_buses, &output_buses, setup.maxSamplesPerBlock as usize);
+                        *self.conversion_buffers.get() = Self::allocate_conversion_buffers(
+                            processor.precision_policy(),
+                            &input_buses,
+                            &output_buses,
+                            max_frames,
+                            processing_rate / setup.sampleRate,
+                        );
                     }
                 }
 
@@ -1613,18 +2456,7 @@ where
                 let current_sample_rate = unsafe { *self.sample_rate.get() };
                 if (current_sample_rate - setup.sampleRate).abs() > 0.001 {
                     // Sample rate changed - unprepare and re-prepare
-                    let bus_layout = BusLayout {
-                        main_input_channels: input_buses
-                            .first()
-                            .map(|b| b.channel_count)
-                            .unwrap_or(2),
-                        main_output_channels: output_buses
-                            .first()
-                            .map(|b| b.channel_count)
-                            .unwrap_or(2),
-                        aux_input_count: input_buses.len().saturating_sub(1),
-                        aux_output_count: output_buses.len().saturating_sub(1),
-                    };
+                    let bus_layout = BusLayout::from_buses(input_buses, output_buses);
 
                     // Take ownership of the processor
                     // SAFETY: mem::zeroed is used as a placeholder that will be immediately overwritten.
@@ -1637,30 +2469,95 @@ where
                     let plugin = old_processor.unprepare();
 
                     // Build new setup and re-prepare
-                    let plugin_setup = build_setup::<P::Setup>(setup, &bus_layout);
-                    let new_processor = plugin.prepare(plugin_setup);
+                    let plugin_setup = build_setup::<P::Setup>(setup, processing_rate, &bus_layout, P::quality(plugin.parameters()));
+                    let mut new_processor = plugin.prepare(plugin_setup);
+                    use beamer_core::Parameters;
+                    new_processor.parameters_mut().set_sample_rate(processing_rate);
 
                     // Pre-allocate conversion buffers if needed
-                    if setup.symbolicSampleSize == SymbolicSampleSizes_::kSample64 as i32
-                        && !new_processor.supports_double_precision()
-                    {
+                    if setup.symbolicSampleSize == SymbolicSampleSizes_::kSample64 as i32 {
                         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
                         unsafe {
-                            *self.conversion_buffers.get() =
-                                ConversionBuffers::allocate_from_buses(input_buses, output_buses, setup.maxSamplesPerBlock as usize);
+                            *self.conversion_buffers.get() = Self::allocate_conversion_buffers(
+                                new_processor.precision_policy(),
+                                input_buses,
+                                output_buses,
+                                setup.maxSamplesPerBlock as usize,
+                                processing_rate / setup.sampleRate,
+                            );
                         }
                     }
 
                     *processor = new_processor;
+                    processor.recompute_rate_dependent(processing_rate);
                 }
                 // If sample rate hasn't changed, nothing to do
             }
         }
 
+        // State loaded during preparation may select another bus layout
+        // or oversampling factor
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        unsafe {
+            self.check_bus_layout();
+            self.check_oversampling();
+        }
+        // The host reads the latency when it activates the processor, so a
+        // change announced while preparing needs no restart
+        self.host_services.take_latency_changed();
+
+        // (Re)allocate resamplers for the current rate, block size and buses
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        if let PluginState::Prepared { input_buses, output_buses, .. } = unsafe { &*self.state.get() } {
+            let max_frames = setup.maxSamplesPerBlock as usize;
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            unsafe {
+                *self.resampler_f32.get() = SampleRateAdapter::from_buses(
+                    setup.sampleRate,
+                    processing_rate,
+                    max_frames,
+                    input_buses,
+                    output_buses,
+                );
+                *self.resampler_f64.get() = SampleRateAdapter::from_buses(
+                    setup.sampleRate,
+                    processing_rate,
+                    max_frames,
+                    input_buses,
+                    output_buses,
+                );
+            }
+            let main_inputs = input_buses.first().map_or(0, |bus| bus.channel_count);
+            let main_outputs = output_buses.first().map_or(0, |bus| bus.channel_count);
+            let mix_frames = max_frames.max(SampleRateAdapter::<f32>::processing_block_size(
+                max_frames,
+                processing_rate / setup.sampleRate,
+            ));
+            let channel_mix = self.config.channel_mix;
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            unsafe {
+                *self.trims_f32.get() = TrimStage::new(&self.standard_trims, main_inputs, max_frames);
+                *self.trims_f64.get() = TrimStage::new(&self.standard_trims, main_inputs, max_frames);
+                *self.channel_mix_f32.get() =
+                    ChannelMixStage::new(channel_mix, main_inputs, main_outputs, mix_frames);
+                *self.channel_mix_f64.get() =
+                    ChannelMixStage::new(channel_mix, main_inputs, main_outputs, mix_frames);
+                (*self.control_rate.get()).reset();
+            }
+            if processing_rate != setup.sampleRate {
+                log::info!(
+                    "Host rate {} Hz not supported, resampling to {} Hz",
+                    setup.sampleRate,
+                    processing_rate
+                );
+            }
+        }
+
         kResultOk
     }
 
-    unsafe fn setProcessing(&self, _state: TBool) -> tresult {
+    unsafe fn setProcessing(&self, state: TBool) -> tresult {
+        host_trace::trace(self.trace_id, HostCall::SetProcessing(state != 0));
         kResultOk
     }
 
@@ -1672,9 +2569,22 @@ where
         // SAFETY: data is non-null and host guarantees it points to valid ProcessData.
         let process_data = unsafe { &*data };
         let num_samples = process_data.numSamples as usize;
-
-        if num_samples == 0 {
-            return kResultOk;
+        // Times the whole call, until it returns
+        let _watchdog = self.watchdog.start(num_samples);
+        let _stats = self.processing_stats.start(num_samples);
+
+        // numSamples == 0 is a parameter flush: hosts use it to deliver
+        // parameter changes (and sometimes MIDI) while no audio is running.
+        // Buffers may be null and the transport doesn't advance, so only
+        // parameters and MIDI are handled.
+        let is_flush = num_samples == 0;
+
+        // Automation points and MIDI, only for processors that split blocks
+        // at events
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let mut split_blocks = unsafe { (*self.control_rate.get()).split_blocks_for(self.processor()) };
+        if let Some(split_blocks) = split_blocks.as_deref_mut() {
+            split_blocks.clear();
         }
 
         // 1. Handle incoming parameter changes from host
@@ -1701,13 +2611,89 @@ where
                         if unsafe { queue.getPoint(point_count - 1, &mut sample_offset, &mut value) }
                             == kResultTrue
                         {
-                            parameters.set_normalized(parameter_id, value);
+                            host_trace::record(
+                                self.trace_id,
+                                HostCall::ProcessParameter {
+                                    id: parameter_id,
+                                    value,
+                                    sample_offset: sample_offset as u32,
+                                },
+                            );
+                            if let Some(trim) = self.standard_trims.by_id(parameter_id) {
+                                trim.set_normalized(value);
+                            } else if self.midi_panic.is_enabled() && MidiPanic::is_panic_parameter(parameter_id) {
+                                // Any point set On triggers; the trigger
+                                // then reads Off again
+                                for j in 0..point_count {
+                                    let (mut offset, mut point) = (0, 0.0);
+                                    // SAFETY: queue is valid, offset and point are valid pointers.
+                                    if unsafe { queue.getPoint(j, &mut offset, &mut point) } == kResultTrue {
+                                        self.midi_panic.set_normalized(parameter_id, point);
+                                    }
+                                }
+                                if self.midi_panic.take_released() {
+                                    // SAFETY: process_data is valid for the duration of process().
+                                    unsafe { report_output_parameter(process_data, parameter_id, 0.0) };
+                                }
+                            } else {
+                                if let Some(split_blocks) = split_blocks
+                                    .as_deref_mut()
+                                    .filter(|_| !MidiCcState::is_midi_cc_parameter(parameter_id))
+                                {
+                                    let previous = parameters.get_normalized(parameter_id);
+                                    for j in 0..point_count {
+                                        let (mut offset, mut point) = (0, 0.0);
+                                        // SAFETY: queue is valid, offset and point are valid pointers.
+                                        if unsafe { queue.getPoint(j, &mut offset, &mut point) } == kResultTrue {
+                                            split_blocks.record_parameter(parameter_id, previous, offset.max(0) as u32, point);
+                                        }
+                                    }
+                                }
+                                parameters.set_normalized(parameter_id, value);
+                                // Report values moved by a constraint back to
+                                // the host.
+                                self.parameter_constraints.enforce(parameters, parameter_id, |id, value| {
+                                    if self.parameter_exposure.is_exposed(id) {
+                                        // SAFETY: process_data is valid for the duration of process().
+                                        unsafe { report_output_parameter(process_data, id, value) };
+                                    }
+                                });
+                            }
+                            self.automation_preview.host_value(parameter_id, value);
+                            let source = if MidiCcState::is_midi_cc_parameter(parameter_id) {
+                                ParameterChangeSource::MidiCc
+                            } else {
+                                ParameterChangeSource::Automation
+                            };
+                            self.parameter_log.record(parameter_id, value, source);
                         }
                     }
                 }
             }
         }
 
+        // 1.5. Apply a program change queued for the declicking crossfade.
+        // The fade-out completed last block; apply at silence, then report the
+        // new values to the host since restartComponent can't be called here.
+        if let Some(preset_index) = self.preset_crossfade.begin() {
+            // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+            let parameters = unsafe { self.parameters() };
+            Presets::apply(preset_index, parameters);
+            self.parameter_constraints.enforce_all(parameters, |_, _| {});
+            self.parameter_log.record_preset(preset_index);
+
+            for i in 0..parameters.count() {
+                let Some(info) = parameters.info(i) else { continue };
+                if info.flags.is_internal {
+                    continue;
+                }
+                // SAFETY: process_data is valid for the duration of process().
+                unsafe {
+                    report_output_parameter(process_data, info.id, parameters.get_normalized(info.id))
+                };
+            }
+        }
+
         // 2. Handle MIDI events (reuse pre-allocated buffer to avoid stack overflow)
         // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
         let midi_input = unsafe { &mut *self.midi_input.get() };
@@ -1783,6 +2769,36 @@ where
             );
         }
 
+        // Record what the host delivered, before any framework processing
+        self.midi_monitor.record_all(MidiDirection::Input, midi_input.iter());
+
+        // 2.55. MIDI panic: All Sound Off and All Notes Off on every channel
+        // ahead of the block's events
+        let panicked = self.midi_panic.take();
+        if panicked {
+            let host_events = midi_input.len();
+            for event in beamer_core::panic_events(0) {
+                midi_input.push(event);
+            }
+            let panic_events = midi_input.len() - host_events;
+            midi_input.as_mut_slice().rotate_right(panic_events);
+        }
+
+        // 2.6. Shape velocities and controller values with the response curves
+        self.response_curves.apply(midi_input.as_mut_slice());
+
+        // 2.7. Drop notes outside the playable range, if the plugin asked for it
+        if self.note_ranges.is_filtering() {
+            midi_input.retain(|e| self.note_ranges.allows(e));
+        }
+
+        // 2.8. Collapse MPE member channels into per-note events
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let midi_events = match unsafe { &mut *self.mpe_state.get() } {
+            Some(mpe_state) => mpe_state.process(midi_input.as_slice()),
+            None => midi_input.as_slice(),
+        };
+
         // Clear and prepare MIDI output buffer and SysEx pool
         // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
         let midi_output = unsafe { &mut *self.midi_output.get() };
@@ -1824,10 +2840,60 @@ where
         }
         // NOTE: Don't clear again - fallback events occupy slots 0..N, new events append after
 
-        // Process MIDI events (process_midi is on Processor)
+        // 3. Extract transport info from VST3 ProcessContext
+        // SAFETY: processContext may be null; extract_transport handles this.
+        let transport = unsafe { extract_transport(process_data.processContext) };
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let sample_rate = unsafe { *self.sample_rate.get() };
+
         // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
         let processor = unsafe { self.processor_mut() };
-        processor.process_midi(midi_input.as_slice(), midi_output);
+
+        // Processing statistics: the voice count left by the previous block,
+        // then any displayed value that changed
+        self.processing_stats.set_active_voices(processor.active_voices());
+        self.processing_stats.report(|id, value| {
+            // SAFETY: process_data is valid for the duration of process().
+            unsafe { report_output_parameter(process_data, id, value) }
+        });
+
+        // Transport events and the plugin's relocation policy, before MIDI and audio
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let transport_tracker = unsafe { &mut *self.transport_tracker.get() };
+        if !is_flush {
+            transport_tracker.dispatch(processor, &transport, num_samples);
+            self.frame_meters.playhead().update(&transport, sample_rate, num_samples);
+        }
+
+        // The processor's panic hook, before it sees the panic events
+        if panicked {
+            processor.panic();
+        }
+
+        // Process MIDI events (process_midi is on Processor)
+        processor.begin_block(num_samples);
+        processor.process_midi(midi_events, midi_output);
+        if let Some(split_blocks) = split_blocks {
+            split_blocks.set_midi(midi_events);
+        }
+        processor.midi_thru().apply(midi_events, midi_output, |e| {
+            processor.consumes_midi(e)
+        });
+
+        // MIDI clock/MMC locked to the host transport (only MMC reaches VST3 hosts)
+        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
+        let midi_clock = unsafe { &mut *self.midi_clock.get() };
+        if !is_flush {
+            midi_clock.generate(
+                processor.midi_clock_output(),
+                &transport,
+                sample_rate,
+                num_samples,
+                midi_output,
+            );
+        }
+
+        self.midi_monitor.record_all(MidiDirection::Output, midi_output.iter());
 
         // Write output MIDI events
         // SAFETY: outputEvents may be null; ComRef::from_raw handles this.
@@ -1856,11 +2922,10 @@ where
             );
         }
 
-        // 3. Extract transport info from VST3 ProcessContext
-        // SAFETY: processContext may be null; extract_transport handles this.
-        let transport = unsafe { extract_transport(process_data.processContext) };
-        // SAFETY: VST3 guarantees single-threaded access during process(). No aliasing.
-        let sample_rate = unsafe { *self.sample_rate.get() };
+        if is_flush {
+            return kResultOk;
+        }
+
         let context = if let Some(cc_state) = self.midi_cc_state.as_ref() {
             CoreProcessContext::with_midi_cc(sample_rate, num_samples, transport, cc_state)
         } else {
@@ -1875,10 +2940,11 @@ where
 
         if symbolic_sample_size == SymbolicSampleSizes_::kSample64 as i32 {
             // 64-bit processing path
-            if processor.supports_double_precision() {
-                // Native f64: extract f64 buffers and call process_f64()
+            let policy = processor.precision_policy();
+            if policy.main_is_f64() {
+                // Native f64 main bus: call process_f64() or process_f64_mixed()
                 // SAFETY: process_data is valid, processor is valid mutable reference.
-                unsafe { self.process_audio_f64_native(process_data, num_samples, processor, &context) };
+                unsafe { self.process_audio_f64_native(process_data, num_samples, processor, &context, policy) };
             } else {
                 // Conversion: f64→f32, process, f32→f64
                 // SAFETY: process_data is valid, processor is valid mutable reference.
@@ -1894,13 +2960,16 @@ where
     }
 
     unsafe fn getTailSamples(&self) -> u32 {
-        // tail_samples and bypass_ramp_samples are on Processor
+        // tail_time and bypass_ramp_samples are on Processor.
+        // An infinite tail maps to kInfiniteTail (u32::MAX), which the
+        // saturating add preserves.
         // SAFETY: VST3 guarantees single-threaded access. No aliasing.
         match unsafe { &*self.state.get() } {
             PluginState::Unprepared { .. } => 0,
-            PluginState::Prepared { processor, .. } => {
-                processor.tail_samples().saturating_add(processor.bypass_ramp_samples())
-            }
+            PluginState::Prepared { processor, .. } => processor
+                .tail_time()
+                .to_samples()
+                .saturating_add(processor.bypass_ramp_samples()),
         }
     }
 }
@@ -1959,17 +3028,20 @@ where
     }
 
     unsafe fn getParameterCount(&self) -> i32 {
-        // SAFETY: VST3 guarantees single-threaded access for this call.
-        let user_parameters = unsafe { self.parameters() }.count();
+        let user_parameters = self.parameter_exposure.count();
         // MIDI CC state is framework-owned, always available
         let cc_parameters = self
             .midi_cc_state
             .as_ref()
             .map(|s| s.enabled_count())
             .unwrap_or(0);
+        let trim_parameters = self.standard_trims.count();
+        let stats_parameters = self.processing_stats.count();
+        let panic_parameters = self.midi_panic.count();
         // Add program change parameter if we have factory presets
         let preset_parameter = if Presets::count() > 0 { 1 } else { 0 };
-        (user_parameters + cc_parameters + preset_parameter) as i32
+        (user_parameters + trim_parameters + stats_parameters + panic_parameters + cc_parameters + preset_parameter)
+            as i32
     }
 
     unsafe fn getParameterInfo(&self, parameter_index: i32, info: *mut ParameterInfo) -> tresult {
@@ -1979,11 +3051,12 @@ where
 
         // SAFETY: VST3 guarantees single-threaded access for this call.
         let parameters = unsafe { self.parameters() };
-        let user_parameter_count = parameters.count();
+        let user_parameter_count = self.parameter_exposure.count();
 
-        // User-defined parameters first
+        // User-defined parameters first (internal ones only once promoted)
         if (parameter_index as usize) < user_parameter_count {
-            if let Some(parameter_info) = parameters.info(parameter_index as usize) {
+            let store_index = self.parameter_exposure.store_index(parameter_index as usize);
+            if let Some(parameter_info) = store_index.and_then(|i| parameters.info(i)) {
                 // SAFETY: info is non-null (checked above) and host guarantees validity.
                 let info = unsafe { &mut *info };
                 info.id = parameter_info.id;
@@ -2016,6 +3089,69 @@ where
             return kInvalidArgument;
         }
 
+        // Standard trims (framework-owned, `Config::standard_trims`)
+        let trim_parameter_count = self.standard_trims.count();
+        let trim_index = parameter_index as usize - user_parameter_count;
+        if trim_index < trim_parameter_count {
+            if let Some(parameter_info) = self.standard_trims.info(trim_index) {
+                // SAFETY: info is non-null (checked above) and host guarantees validity.
+                let info = unsafe { &mut *info };
+                info.id = parameter_info.id;
+                copy_wstring(parameter_info.name, &mut info.title);
+                copy_wstring(parameter_info.short_name, &mut info.shortTitle);
+                copy_wstring(parameter_info.units, &mut info.units);
+                info.stepCount = parameter_info.step_count;
+                info.defaultNormalizedValue = parameter_info.default_normalized;
+                info.unitId = parameter_info.group_id;
+                info.flags = ParameterInfo_::ParameterFlags_::kCanAutomate;
+                return kResultOk;
+            }
+            return kInvalidArgument;
+        }
+        let user_parameter_count = user_parameter_count + trim_parameter_count;
+
+        // Processing statistics (framework-owned, `Config::processing_stats`)
+        let stats_parameter_count = self.processing_stats.count();
+        let stats_index = parameter_index as usize - user_parameter_count;
+        if stats_index < stats_parameter_count {
+            if let Some(parameter_info) = self.processing_stats.info(stats_index) {
+                // SAFETY: info is non-null (checked above) and host guarantees validity.
+                let info = unsafe { &mut *info };
+                info.id = parameter_info.id;
+                copy_wstring(parameter_info.name, &mut info.title);
+                copy_wstring(parameter_info.short_name, &mut info.shortTitle);
+                copy_wstring(parameter_info.units, &mut info.units);
+                info.stepCount = parameter_info.step_count;
+                info.defaultNormalizedValue = parameter_info.default_normalized;
+                info.unitId = parameter_info.group_id;
+                info.flags = ParameterInfo_::ParameterFlags_::kIsReadOnly;
+                return kResultOk;
+            }
+            return kInvalidArgument;
+        }
+        let user_parameter_count = user_parameter_count + stats_parameter_count;
+
+        // MIDI panic trigger (framework-owned, for plugins that want MIDI)
+        let panic_parameter_count = self.midi_panic.count();
+        let panic_index = parameter_index as usize - user_parameter_count;
+        if panic_index < panic_parameter_count {
+            if let Some(parameter_info) = self.midi_panic.info(panic_index) {
+                // SAFETY: info is non-null (checked above) and host guarantees validity.
+                let info = unsafe { &mut *info };
+                info.id = parameter_info.id;
+                copy_wstring(parameter_info.name, &mut info.title);
+                copy_wstring(parameter_info.short_name, &mut info.shortTitle);
+                copy_wstring(parameter_info.units, &mut info.units);
+                info.stepCount = parameter_info.step_count;
+                info.defaultNormalizedValue = parameter_info.default_normalized;
+                info.unitId = parameter_info.group_id;
+                info.flags = ParameterInfo_::ParameterFlags_::kCanAutomate | ParameterInfo_::ParameterFlags_::kIsHidden;
+                return kResultOk;
+            }
+            return kInvalidArgument;
+        }
+        let user_parameter_count = user_parameter_count + panic_parameter_count;
+
         // Hidden MIDI CC parameters (framework-owned state)
         let cc_parameter_count = self
             .midi_cc_state
@@ -2087,9 +3223,11 @@ where
                 let preset_index = (value_normalized * step_count).round() as usize;
                 let preset_index = preset_index.min(preset_count - 1);
 
-                if let Some(preset_info) = Presets::info(preset_index) {
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                let preset_names = unsafe { &mut *self.preset_names.get() };
+                if let Some(preset_name) = preset_names.name::<Presets>(preset_index) {
                     // SAFETY: string is non-null (checked above) and host guarantees validity.
-                    copy_wstring(preset_info.name, unsafe { &mut *string });
+                    copy_wstring(preset_name, unsafe { &mut *string });
                     return kResultOk;
                 }
             }
@@ -2099,7 +3237,7 @@ where
         }
 
         // SAFETY: VST3 guarantees single-threaded access for this call.
-        let parameters = unsafe { self.parameters() };
+        let parameters = unsafe { self.host_store(id) };
         let display = parameters.normalized_to_string(id, value_normalized);
         // SAFETY: string is non-null (checked above) and host guarantees validity.
         copy_wstring(&display, unsafe { &mut *string });
@@ -2123,10 +3261,12 @@ where
             // Handle program change parameter (preset name to value)
             if id == PROGRAM_CHANGE_PARAM_ID {
                 let preset_count = Presets::count();
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                let preset_names = unsafe { &mut *self.preset_names.get() };
                 // Find preset by name
                 for i in 0..preset_count {
-                    if let Some(preset_info) = Presets::info(i) {
-                        if preset_info.name == s {
+                    if let Some(preset_name) = preset_names.name::<Presets>(i) {
+                        if preset_name == s {
                             let step_count = (preset_count - 1).max(1) as f64;
                             // SAFETY: value_normalized is non-null (checked above).
                             unsafe { *value_normalized = (i as f64) / step_count };
@@ -2138,7 +3278,7 @@ where
             }
 
             // SAFETY: VST3 guarantees single-threaded access for this call.
-            let parameters = unsafe { self.parameters() };
+            let parameters = unsafe { self.host_store(id) };
             if let Some(value) = parameters.string_to_normalized(id, &s) {
                 // SAFETY: value_normalized is non-null (checked above).
                 unsafe { *value_normalized = value };
@@ -2159,7 +3299,7 @@ where
             return 0.0;
         }
         // SAFETY: VST3 guarantees single-threaded access for this call.
-        unsafe { self.parameters() }.normalized_to_plain(id, value_normalized)
+        unsafe { self.host_store(id) }.normalized_to_plain(id, value_normalized)
     }
 
     unsafe fn plainParamToNormalized(&self, id: u32, plain_value: f64) -> f64 {
@@ -2173,7 +3313,7 @@ where
             return 0.0;
         }
         // SAFETY: VST3 guarantees single-threaded access for this call.
-        unsafe { self.parameters() }.plain_to_normalized(id, plain_value)
+        unsafe { self.host_store(id) }.plain_to_normalized(id, plain_value)
     }
 
     unsafe fn getParamNormalized(&self, id: u32) -> f64 {
@@ -2199,10 +3339,11 @@ where
         }
 
         // SAFETY: VST3 guarantees single-threaded access for this call.
-        unsafe { self.parameters() }.get_normalized(id)
+        unsafe { self.host_store(id) }.get_normalized(id)
     }
 
     unsafe fn setParamNormalized(&self, id: u32, value: f64) -> tresult {
+        host_trace::trace(self.trace_id, HostCall::SetParameter { id, value });
         // Check if this is a MIDI CC parameter
         if MidiCcState::is_midi_cc_parameter(id) {
             if let Some(cc_state) = self.midi_cc_state.as_ref() {
@@ -2211,6 +3352,12 @@ where
             }
         }
 
+        // The panic trigger fires from the parameter change in process();
+        // the controller side only mirrors the value, which stays Off
+        if self.midi_panic.is_enabled() && MidiPanic::is_panic_parameter(id) {
+            return kResultOk;
+        }
+
         // Check if this is the program change parameter (preset selection)
         if id == PROGRAM_CHANGE_PARAM_ID {
             let preset_count = Presets::count();
@@ -2221,29 +3368,34 @@ where
                 let preset_index = (value * step_count).round() as usize;
                 let preset_index = preset_index.min(preset_count - 1);
 
+                // While processing, hand the change to the audio thread so it
+                // is applied inside the declicking fade. process() reports the
+                // new values through outputParameterChanges.
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                let prepared = matches!(unsafe { &*self.state.get() }, PluginState::Prepared { .. });
+                if prepared && self.preset_crossfade.is_enabled() {
+                    self.preset_crossfade.request(preset_index);
+                    // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                    unsafe { *self.current_preset_index.get() = preset_index as i32 };
+                    return kResultOk;
+                }
+
                 // Always apply unconditionally - never skip with "if changed" guard.
                 // Hosts may re-send the same preset index (e.g., user clicks preset 0
                 // when it's already selected) and skipping would break preset 0 on
                 // fresh load when current_preset_index is initialized to 0.
                 // SAFETY: VST3 guarantees single-threaded access for this call.
-                Presets::apply(preset_index, unsafe { self.parameters() });
+                let parameters = unsafe { self.parameters() };
+                Presets::apply(preset_index, parameters);
+                self.parameter_constraints.enforce_all(parameters, |_, _| {});
+                self.parameter_log.record_preset(preset_index);
 
                 // Store the current preset index
                 // SAFETY: VST3 guarantees single-threaded access. No aliasing.
                 unsafe { *self.current_preset_index.get() = preset_index as i32 };
 
                 // Notify host that parameter values changed so UI refreshes
-                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
-                let handler = unsafe { *self.component_handler.get() };
-                if !handler.is_null() {
-                    // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
-                    unsafe {
-                        ((*(*handler).vtbl).restartComponent)(
-                            handler,
-                            RestartFlags_::kParamValuesChanged,
-                        );
-                    }
-                }
+                self.restart_component(RestartFlags_::kParamValuesChanged);
 
                 return kResultOk;
             }
@@ -2251,7 +3403,29 @@ where
         }
 
         // SAFETY: VST3 guarantees single-threaded access for this call.
-        unsafe { self.parameters() }.set_normalized(id, value);
+        let parameters = unsafe { self.host_store(id) };
+        // Hosts echo values already delivered through process(); only log
+        // actual changes.
+        if parameters.get_normalized(id) != value {
+            self.parameter_log.record(id, value, ParameterChangeSource::Host);
+        }
+        parameters.set_normalized(id, value);
+        // Values a constraint moved (possibly this one) need a re-read.
+        let mut adjusted = false;
+        self.parameter_constraints.enforce(parameters, id, |_, _| adjusted = true);
+        if adjusted {
+            self.restart_component(RestartFlags_::kParamValuesChanged);
+        }
+        // SAFETY: VST3 guarantees single-threaded access for this call.
+        unsafe {
+            self.check_bus_layout();
+            self.check_oversampling();
+        }
+        // SAFETY: VST3 guarantees single-threaded access for this call.
+        unsafe { self.autosave_snapshot() };
+        self.flush_state_dirty();
+        self.flush_latency_changed();
+        self.check_preset_list();
         kResultOk
     }
 
@@ -2286,8 +3460,40 @@ where
 
         // SAFETY: name is non-null (checked above) and is null-terminated C string.
         let name_str = unsafe { std::ffi::CStr::from_ptr(name) }.to_str().unwrap_or("");
-        if name_str != "editor" || !self.config.has_gui {
+        // "editor" is the main view; other names select additional views
+        // declared in Config.toml (e.g. a compact "mini" meter view).
+        let Some(gui_view) = self.config.gui_view(name_str) else {
             return std::ptr::null_mut();
+        };
+
+        // A native editor replaces the main WebView editor; additional
+        // views stay WebViews.
+        if gui_view.name == beamer_core::EDITOR_VIEW {
+            if let Some(editor) = self.editor.create_native() {
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                let params: *const dyn beamer_core::ParameterStore =
+                    unsafe { self.parameters() as &dyn beamer_core::ParameterStore };
+                // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+                let component_handler = unsafe { *self.component_handler.get() };
+                // SAFETY: params points to the plugin's parameter struct which
+                // outlives the view; the handler is valid or null.
+                let view = unsafe {
+                    crate::native_view::NativePlugView::new(
+                        editor,
+                        params,
+                        component_handler,
+                        self.parameter_exposure.clone(),
+                        self.parameter_constraints.clone(),
+                        self.automation_preview.clone(),
+                        self.parameter_log.clone(),
+                    )
+                };
+                let wrapper = vst3::ComWrapper::new(view);
+                return match wrapper.to_com_ptr::<IPlugView>() {
+                    Some(ptr) => ptr.into_raw(),
+                    None => std::ptr::null_mut(),
+                };
+            }
         }
 
         #[cfg(feature = "webview")]
@@ -2301,6 +3507,7 @@ where
             let config = WebViewConfig {
                 plugin_code: self.config.subtype.0,
                 assets: self.config.gui_assets,
+                page: gui_view.page,
                 url: self.config.gui_url,
                 dev_tools: cfg!(debug_assertions),
                 background_color: self.config.gui_background_color,
@@ -2309,10 +3516,10 @@ where
                 callback_context: std::ptr::null_mut(),
             };
             debug_assert!(
-                self.config.gui_width > 0 && self.config.gui_height > 0,
+                gui_view.width > 0 && gui_view.height > 0,
                 "gui_size must be set when has_gui is true"
             );
-            let size = beamer_core::Size::new(self.config.gui_width, self.config.gui_height);
+            let size = gui_view.size();
             let constraints = beamer_core::GuiConstraints {
                 min: size,
                 ..beamer_core::GuiConstraints::default()
@@ -2325,6 +3532,9 @@ where
                 unsafe { self.parameters() as &dyn beamer_core::ParameterStore };
             // SAFETY: VST3 guarantees single-threaded access. No aliasing.
             let component_handler = unsafe { *self.component_handler.get() };
+            // The group layout is fixed, so list it once for the GUI.
+            // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+            let groups = beamer_core::parameter_groups::groups_json(unsafe { self.parameters() });
 
             // SAFETY: params points to the plugin's parameter struct which outlives the view.
             let view = unsafe {
@@ -2334,6 +3544,25 @@ where
                     params,
                     component_handler,
                     self.webview_handler.clone(),
+                    self.crash_guard.clone(),
+                    self.parameter_exposure.clone(),
+                    self.controller_pages.clone(),
+                    self.parameter_constraints.clone(),
+                    self.response_curves.clone(),
+                    self.note_ranges.clone(),
+                    self.host_services.clone(),
+                    self.automation_preview.clone(),
+                    self.autosave.clone(),
+                    self.parameter_log.clone(),
+                    self.midi_monitor.clone(),
+                    self.midi_panic.clone(),
+                    self.activation.activation().clone(),
+                    self.frame_meters.clone(),
+                    self.gui_data.clone(),
+                    self.notifications.clone(),
+                    self.watchdog.clone(),
+                    groups,
+                    self as &dyn crate::webview::StateAccess,
                 )
             };
             let wrapper = vst3::ComWrapper::new(view);
@@ -2432,9 +3661,15 @@ where
             return kInvalidArgument;
         }
 
-        if let Some(preset_info) = Presets::info(program_index as usize) {
+        if program_index < 0 {
+            return kInvalidArgument;
+        }
+
+        // SAFETY: VST3 guarantees single-threaded access. No aliasing.
+        let preset_names = unsafe { &mut *self.preset_names.get() };
+        if let Some(preset_name) = preset_names.name::<Presets>(program_index as usize) {
             // SAFETY: name is non-null (checked above) and host guarantees validity.
-            copy_wstring(preset_info.name, unsafe { &mut *name });
+            copy_wstring(preset_name, unsafe { &mut *name });
             kResultOk
         } else {
             kInvalidArgument
@@ -2452,17 +3687,35 @@ where
     }
 
     unsafe fn hasProgramPitchNames(&self, _list_id: i32, _program_index: i32) -> tresult {
-        kResultFalse
+        // Keyboard splits name their keys, for every program
+        if self.note_ranges.splits().is_empty() {
+            kResultFalse
+        } else {
+            kResultTrue
+        }
     }
 
     unsafe fn getProgramPitchName(
         &self,
         _list_id: i32,
         _program_index: i32,
-        _midi_pitch: i16,
-        _name: *mut String128,
+        midi_pitch: i16,
+        name: *mut String128,
     ) -> tresult {
-        kNotImplemented
+        if name.is_null() {
+            return kInvalidArgument;
+        }
+        let split = u8::try_from(midi_pitch)
+            .ok()
+            .and_then(|pitch| self.note_ranges.split_for(pitch));
+        match split {
+            Some(split) => {
+                // SAFETY: name is non-null (checked above) and host guarantees validity.
+                copy_wstring(&split.name, unsafe { &mut *name });
+                kResultOk
+            }
+            None => kResultFalse,
+        }
     }
 
     unsafe fn getSelectedUnit(&self) -> i32 {
@@ -2525,7 +3778,14 @@ where
             }
         }
 
-        // 2. Check framework-owned MIDI CC state (omni channel - ignore channel parameter)
+        // 2. Controller page knobs follow the selected page (omni channel)
+        if let Some(parameter_id) = self.controller_pages.cc_to_parameter(controller) {
+            // SAFETY: id is non-null (checked above) and host guarantees validity.
+            unsafe { *id = parameter_id };
+            return kResultOk;
+        }
+
+        // 3. Check framework-owned MIDI CC state (omni channel - ignore channel parameter)
         if let Some(cc_state) = self.midi_cc_state.as_ref() {
             if cc_state.has_controller(controller) {
                 // SAFETY: id is non-null (checked above) and host guarantees validity.
@@ -2958,6 +4218,42 @@ where
     }
 }
 
+// =============================================================================
+// IInfoListener implementation (VST3 SDK 3.6.5)
+// =============================================================================
+
+/// `ChannelContext::kChannelNameKey`: the name of the track or channel the
+/// plugin is inserted on.
+const CHANNEL_NAME_KEY: &[u8] = b"channel name\0";
+
+impl<P: Descriptor + 'static, Presets> ChannelContext::IInfoListenerTrait for Vst3Processor<P, Presets>
+where
+    Presets: FactoryPresets<Parameters = P::Parameters>,
+{
+    unsafe fn setChannelContextInfos(&self, list: *mut IAttributeList) -> tresult {
+        // SAFETY: list may be null; ComRef::from_raw handles this.
+        let Some(list) = (unsafe { ComRef::from_raw(list) }) else {
+            return kInvalidArgument;
+        };
+        let mut name = [0 as TChar; 128];
+        // SAFETY: list is a valid ComRef; name is writable for its size in bytes.
+        let result = unsafe {
+            list.getString(
+                CHANNEL_NAME_KEY.as_ptr() as IAttrID,
+                name.as_mut_ptr(),
+                std::mem::size_of_val(&name) as u32,
+            )
+        };
+        // Hosts send only the keys that changed; keep the name otherwise.
+        if result == kResultOk {
+            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            let name = String::from_utf16_lossy(&name[..len]);
+            self.host_services.set_instance_name(Some(&name));
+        }
+        kResultOk
+    }
+}
+
 // =============================================================================
 // Helper functions
 // =============================================================================
@@ -3019,16 +4315,83 @@ fn utf16_to_utf8(utf16: &[u16], utf8_buf: &mut [u8]) -> usize {
     utf8_pos
 }
 
-/// Convert a channel count to the corresponding VST3 speaker arrangement.
-fn channel_count_to_speaker_arrangement(channel_count: u32) -> SpeakerArrangement {
-    match channel_count {
-        1 => SpeakerArr::kMono,
-        2 => SpeakerArr::kStereo,
+// Speaker bits from the SDK's vstspeaker.h (`kSpeakerL`, ...).
+const SPEAKER_L: SpeakerArrangement = 1 << 0;
+const SPEAKER_R: SpeakerArrangement = 1 << 1;
+const SPEAKER_C: SpeakerArrangement = 1 << 2;
+const SPEAKER_LFE: SpeakerArrangement = 1 << 3;
+const SPEAKER_LS: SpeakerArrangement = 1 << 4;
+const SPEAKER_RS: SpeakerArrangement = 1 << 5;
+const SPEAKER_SL: SpeakerArrangement = 1 << 9;
+const SPEAKER_SR: SpeakerArrangement = 1 << 10;
+const SPEAKER_TFL: SpeakerArrangement = 1 << 12;
+const SPEAKER_TFR: SpeakerArrangement = 1 << 14;
+const SPEAKER_TRL: SpeakerArrangement = 1 << 15;
+const SPEAKER_TRR: SpeakerArrangement = 1 << 17;
+
+/// `k51`
+const ARRANGEMENT_51: SpeakerArrangement = SPEAKER_L | SPEAKER_R | SPEAKER_C | SPEAKER_LFE | SPEAKER_LS | SPEAKER_RS;
+/// `k71CineSideFill` (7.1 Music)
+const ARRANGEMENT_71: SpeakerArrangement = ARRANGEMENT_51 | SPEAKER_SL | SPEAKER_SR;
+/// `k71_4`
+const ARRANGEMENT_714: SpeakerArrangement = ARRANGEMENT_71 | SPEAKER_TFL | SPEAKER_TFR | SPEAKER_TRL | SPEAKER_TRR;
+
+/// Speaker bit of ambisonic component `acn` (`kSpeakerACN0` ... `kSpeakerACN24`).
+const fn ambisonic_speaker(acn: u32) -> SpeakerArrangement {
+    match acn {
+        0 => 1 << 20,
+        1..=3 => 1 << (20 + acn),
+        _ => 1 << (34 + acn),
+    }
+}
+
+/// Ambisonic arrangement of `order` (`kAmbi1stOrderACN` ...).
+fn ambisonic_arrangement(order: AmbisonicOrder) -> SpeakerArrangement {
+    (0..order.channel_count()).fold(0, |arrangement, acn| arrangement | ambisonic_speaker(acn))
+}
+
+/// Convert a speaker layout to the corresponding VST3 speaker arrangement.
+fn speaker_layout_to_arrangement(layout: SpeakerLayout) -> SpeakerArrangement {
+    match layout {
+        SpeakerLayout::Mono => SpeakerArr::kMono,
+        SpeakerLayout::Stereo => SpeakerArr::kStereo,
+        SpeakerLayout::Surround51 => ARRANGEMENT_51,
+        SpeakerLayout::Surround71 => ARRANGEMENT_71,
+        SpeakerLayout::Surround714 => ARRANGEMENT_714,
+        SpeakerLayout::Ambisonic(order) => ambisonic_arrangement(order),
         // For other channel counts, create a bitmask with that many speakers
-        n => (1u64 << n) - 1,
+        SpeakerLayout::Discrete(n) => (1u64 << n) - 1,
+    }
+}
+
+/// `info` with a negotiated channel count and, if the host named one, speaker layout.
+fn arranged_bus(info: CoreBusInfo, channel_count: u32, speakers: Option<SpeakerLayout>) -> CoreBusInfo {
+    let info = CoreBusInfo { channel_count, ..info };
+    match speakers {
+        Some(layout) if layout.channel_count() == channel_count => info.with_speaker_layout(layout),
+        _ => info,
     }
 }
 
+/// Convert a VST3 speaker arrangement to a speaker layout, if it is one of
+/// the named layouts.
+fn speaker_layout_from_arrangement(arrangement: SpeakerArrangement) -> Option<SpeakerLayout> {
+    let named = [
+        SpeakerLayout::Mono,
+        SpeakerLayout::Stereo,
+        SpeakerLayout::Surround51,
+        SpeakerLayout::Surround71,
+        SpeakerLayout::Surround714,
+        SpeakerLayout::Ambisonic(AmbisonicOrder::First),
+        SpeakerLayout::Ambisonic(AmbisonicOrder::Second),
+        SpeakerLayout::Ambisonic(AmbisonicOrder::Third),
+        SpeakerLayout::Ambisonic(AmbisonicOrder::Fourth),
+    ];
+    named
+        .into_iter()
+        .find(|&layout| speaker_layout_to_arrangement(layout) == arrangement)
+}
+
 /// Convert a MIDI CC parameter value to a MidiEvent.
 ///
 /// This is used to convert parameter changes from IMidiMapping back to MIDI events.
@@ -3371,6 +4734,8 @@ fn convert_midi_to_vst3(midi: &MidiEvent, sysex_pool: &mut SysExOutputPool) -> O
         // Plugins receive these from the DAW but don't generate them.
         MidiEventKind::ChordInfo(_) => return None,
         MidiEventKind::ScaleInfo(_) => return None,
+        // VST3 has no event type for MIDI clock or start/stop/continue.
+        MidiEventKind::SystemRealtime(_) => return None,
 
         // TODO: NoteExpressionText output not yet implemented.
         // Some vocal/granular synths emit phoneme or waveform text data.
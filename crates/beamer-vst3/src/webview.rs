@@ -4,14 +4,47 @@ use std::cell::UnsafeCell;
 use std::ffi::c_void;
 use std::fmt::Write;
 use std::sync::Arc;
+use std::time::Instant;
 
-use beamer_core::{GuiConstraints, GuiDelegate, ParameterStore, Size, WebViewHandler};
+use beamer_core::{
+    Activation, AutomationPreview, Autosave, ConstraintState, ControllerPageState, CrashGuard, FrameMeters, GuiConstraints, GuiDataChannels, GuiDelegate, ParameterChangeSource, ParameterExposure,
+    HostServices, MidiMonitor, MidiPanic, NoteRanges, Notifications, ParameterLog, ParameterStore, ProcessWatchdog, ResponseCurves,
+    Size, WebViewHandler,
+};
 use beamer_webview::platform::PlatformWebView;
 pub use beamer_webview::WebViewConfig;
-use vst3::Steinberg::Vst::IComponentHandler;
+use vst3::Steinberg::Vst::{IComponentHandler, RestartFlags_};
 use vst3::Steinberg::*;
 use vst3::Class;
 
+/// Access to the owning processor's state, for autosave recovery, state
+/// hashing, preset files and dirty notifications.
+///
+/// Implemented by the VST3 processor. All methods run on the UI thread.
+pub trait StateAccess {
+    /// Take an autosave snapshot if one is due.
+    fn snapshot_state(&self);
+    /// Forward a pending state dirty mark to the host.
+    fn flush_state_dirty(&self);
+    /// Ask the host to re-read the latency if the plugin announced a
+    /// change.
+    fn flush_latency_changed(&self);
+    /// Ask the host to restart the processor if another oversampling
+    /// factor was selected (GUI edits and automation reach the parameters
+    /// without a host call).
+    fn check_oversampling(&self);
+    /// Ask the host to re-read the program list if presets were added,
+    /// removed or renamed (e.g. user presets changed on disk).
+    fn check_preset_list(&self);
+    /// Apply a recovered state blob as `setState()` would. Returns `false`
+    /// if the blob was rejected.
+    fn restore_state(&self, data: Vec<u8>) -> bool;
+    /// The state as `getState()` writes it (empty while unprepared).
+    fn state_blob(&self) -> Vec<u8>;
+    /// The plugin configuration (identifiers for preset files).
+    fn plugin_config(&self) -> &'static beamer_core::Config;
+}
+
 /// Shared context between WebViewPlugView and its IPC callbacks.
 ///
 /// This struct is heap-allocated and pinned. Raw pointers to it are passed
@@ -23,6 +56,50 @@ struct IpcContext {
     handler: *mut IComponentHandler,
     /// Custom WebView message handler (invoke/event routing).
     webview_handler: Option<Arc<dyn WebViewHandler>>,
+    /// Crash guard of the owning processor (for the safe mode invoke).
+    crash_guard: CrashGuard,
+    /// Host-visible parameter list of the owning processor. Edits to
+    /// parameters the host can't see are applied without host notification.
+    parameter_exposure: ParameterExposure,
+    /// Controller page selection of the owning processor.
+    controller_pages: ControllerPageState,
+    /// Parameter constraints of the owning processor.
+    parameter_constraints: ConstraintState,
+    /// MIDI response curves of the owning processor.
+    response_curves: ResponseCurves,
+    /// Note ranges of the owning processor.
+    note_ranges: NoteRanges,
+    /// Instance and display names of the owning processor.
+    host_services: HostServices,
+    /// Host automation versus GUI values of the owning processor.
+    automation_preview: AutomationPreview,
+    /// Autosave of the owning processor (for the recovery prompt).
+    autosave: Autosave,
+    /// Parameter change log of the owning processor.
+    parameter_log: ParameterLog,
+    /// MIDI monitor of the owning processor.
+    midi_monitor: MidiMonitor,
+    /// MIDI panic trigger of the owning processor.
+    midi_panic: MidiPanic,
+    /// License activation of the owning processor.
+    activation: Activation,
+    /// Meters sent with each `__BEAMER__.onFrame()` tick.
+    frame_meters: FrameMeters,
+    /// Frame streams drained by the sync timer.
+    gui_data: GuiDataChannels,
+    /// Frames are wanted but no display link is available, so the sync
+    /// timer sends them.
+    timer_frames: bool,
+    /// Time origin for frames sent by the sync timer.
+    frame_epoch: Instant,
+    /// Messages for the GUI of the owning processor.
+    notifications: Notifications,
+    /// Deadline watchdog of the owning processor (overrun reports).
+    watchdog: ProcessWatchdog,
+    /// Parameter groups with their layout hints (`groups_json()`).
+    groups: serde_json::Value,
+    /// State access of the owning processor.
+    state_access: *const dyn StateAccess,
     /// Cached parameter values from the last sync tick.
     /// Index corresponds to ParameterStore::info(index).
     last_values: Vec<f64>,
@@ -30,7 +107,16 @@ struct IpcContext {
     /// Set in attached(), cleared in removed().
     webview: *const PlatformWebView,
     /// NSTimer handle for parameter sync. Null when not running.
+    #[cfg(target_os = "macos")]
     sync_timer: *mut objc2::runtime::AnyObject,
+    /// Parent HWND whose timer drives parameter sync (the timer id is the
+    /// IpcContext address). Null when not running.
+    #[cfg(target_os = "windows")]
+    sync_timer_window: *mut c_void,
+    /// Timer on the host's run loop driving parameter sync and GTK events.
+    /// None when not running.
+    #[cfg(target_os = "linux")]
+    run_loop_timer: Option<RunLoopTimer>,
 }
 
 /// VST3 IPlugView implementation backed by a platform WebView.
@@ -82,7 +168,7 @@ impl WebViewPlugView {
     ///
     /// `params` must be a valid pointer that remains valid for the lifetime
     /// of this view (it points to the plugin's parameter struct which
-    /// outlives the editor).
+    /// outlives the editor). The same holds for `state_access`.
     /// `component_handler` is the IComponentHandler pointer (may be null initially).
     /// If non-null, this function AddRefs it; the view owns a reference until dropped.
     pub unsafe fn new(
@@ -91,6 +177,25 @@ impl WebViewPlugView {
         params: *const dyn ParameterStore,
         component_handler: *mut IComponentHandler,
         webview_handler: Option<Arc<dyn WebViewHandler>>,
+        crash_guard: CrashGuard,
+        parameter_exposure: ParameterExposure,
+        controller_pages: ControllerPageState,
+        parameter_constraints: ConstraintState,
+        response_curves: ResponseCurves,
+        note_ranges: NoteRanges,
+        host_services: HostServices,
+        automation_preview: AutomationPreview,
+        autosave: Autosave,
+        parameter_log: ParameterLog,
+        midi_monitor: MidiMonitor,
+        midi_panic: MidiPanic,
+        activation: Activation,
+        frame_meters: FrameMeters,
+        gui_data: GuiDataChannels,
+        notifications: Notifications,
+        watchdog: ProcessWatchdog,
+        groups: serde_json::Value,
+        state_access: *const dyn StateAccess,
     ) -> Self {
         let size = delegate.gui_size();
 
@@ -114,9 +219,35 @@ impl WebViewPlugView {
                 params,
                 handler: component_handler,
                 webview_handler,
+                crash_guard,
+                parameter_exposure,
+                controller_pages,
+                parameter_constraints,
+                response_curves,
+                note_ranges,
+                host_services,
+                automation_preview,
+                autosave,
+                parameter_log,
+                midi_monitor,
+                midi_panic,
+                activation,
+                frame_meters,
+                gui_data,
+                timer_frames: false,
+                frame_epoch: Instant::now(),
+                notifications,
+                watchdog,
+                groups,
+                state_access,
                 last_values,
                 webview: std::ptr::null(),
+                #[cfg(target_os = "macos")]
                 sync_timer: std::ptr::null_mut(),
+                #[cfg(target_os = "windows")]
+                sync_timer_window: std::ptr::null_mut(),
+                #[cfg(target_os = "linux")]
+                run_loop_timer: None,
             })),
         }
     }
@@ -179,12 +310,36 @@ unsafe extern "C-unwind" fn on_message(context: *mut c_void, json: *const u8, le
             let Some(id) = msg.get("id").and_then(|v| v.as_u64()).map(|v| v as u32) else { return };
             let Some(value) = msg.get("value").and_then(|v| v.as_f64()) else { return };
             params.set_normalized(id, value);
-            if !ipc.handler.is_null() {
+            ipc.automation_preview.user_value(id, value);
+            ipc.parameter_log.record(id, value, ParameterChangeSource::Gui);
+            // Enforce constraints before telling the host, so it records the
+            // value the plugin actually uses.
+            let mut pushed = Vec::new();
+            ipc.parameter_constraints.enforce(params, id, |adjusted, _| {
+                if adjusted != id {
+                    pushed.push(adjusted);
+                }
+            });
+            let value = params.get_normalized(id);
+            if !ipc.handler.is_null() && ipc.parameter_exposure.is_exposed(id) {
                 // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
                 unsafe {
                     ((*(*ipc.handler).vtbl).performEdit)(ipc.handler, id, value);
                 }
             }
+            // Parameters pushed along get a complete edit of their own.
+            for pushed_id in pushed {
+                if ipc.handler.is_null() || !ipc.parameter_exposure.is_exposed(pushed_id) {
+                    continue;
+                }
+                let pushed_value = params.get_normalized(pushed_id);
+                // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
+                unsafe {
+                    ((*(*ipc.handler).vtbl).beginEdit)(ipc.handler, pushed_id);
+                    ((*(*ipc.handler).vtbl).performEdit)(ipc.handler, pushed_id, pushed_value);
+                    ((*(*ipc.handler).vtbl).endEdit)(ipc.handler, pushed_id);
+                }
+            }
             // Echo the authoritative values back to JS immediately so
             // displayText updates without waiting for the next poll tick.
             if !ipc.webview.is_null() {
@@ -211,7 +366,8 @@ unsafe extern "C-unwind" fn on_message(context: *mut c_void, json: *const u8, le
         }
         "param:begin" => {
             let Some(id) = msg.get("id").and_then(|v| v.as_u64()).map(|v| v as u32) else { return };
-            if !ipc.handler.is_null() {
+            ipc.automation_preview.begin_gesture(id);
+            if !ipc.handler.is_null() && ipc.parameter_exposure.is_exposed(id) {
                 // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
                 unsafe {
                     ((*(*ipc.handler).vtbl).beginEdit)(ipc.handler, id);
@@ -220,7 +376,8 @@ unsafe extern "C-unwind" fn on_message(context: *mut c_void, json: *const u8, le
         }
         "param:end" => {
             let Some(id) = msg.get("id").and_then(|v| v.as_u64()).map(|v| v as u32) else { return };
-            if !ipc.handler.is_null() {
+            ipc.automation_preview.end_gesture(id);
+            if !ipc.handler.is_null() && ipc.parameter_exposure.is_exposed(id) {
                 // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
                 unsafe {
                     ((*(*ipc.handler).vtbl).endEdit)(ipc.handler, id);
@@ -243,6 +400,105 @@ unsafe extern "C-unwind" fn on_message(context: *mut c_void, json: *const u8, le
                     },
                     _ => Ok(serde_json::Value::Null),
                 }
+            } else if method == beamer_core::logging::LOG_TAIL_INVOKE {
+                Ok(beamer_core::logging::log_tail_invoke(&args))
+            } else if method == beamer_core::crash_guard::SAFE_MODE_INVOKE {
+                Ok(beamer_core::crash_guard::safe_mode_invoke(&ipc.crash_guard))
+            } else if method == beamer_core::parameter_groups::PARAMETER_GROUPS_INVOKE {
+                Ok(ipc.groups.clone())
+            } else if method == beamer_core::state_hash::STATE_HASH_INVOKE {
+                // SAFETY: state_access points to the owning processor, which outlives the view.
+                let data = unsafe { &*ipc.state_access }.state_blob();
+                Ok(beamer_core::state_hash::state_hash_invoke(&data))
+            } else if method == beamer_core::automation_preview::AUTOMATION_STATE_INVOKE {
+                Ok(beamer_core::automation_preview::automation_state_invoke(
+                    &ipc.automation_preview,
+                    &args,
+                ))
+            } else if let Some(value) =
+                beamer_core::parameter_exposure::exposure_invoke(&ipc.parameter_exposure, method, &args)
+            {
+                // Promote/demote changed the host list: ask the host to rescan.
+                if ipc.parameter_exposure.take_changed() && !ipc.handler.is_null() {
+                    // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
+                    unsafe {
+                        ((*(*ipc.handler).vtbl).restartComponent)(
+                            ipc.handler,
+                            RestartFlags_::kParamTitlesChanged,
+                        );
+                    }
+                }
+                Ok(value)
+            } else if let Some(value) = beamer_core::controller_pages::controller_pages_invoke(
+                &ipc.controller_pages,
+                method,
+                &args,
+            ) {
+                // A new page remaps the knob CCs.
+                if ipc.controller_pages.take_changed() && !ipc.handler.is_null() {
+                    // SAFETY: handler is non-null and is valid COM pointer with valid vtbl.
+                    unsafe {
+                        ((*(*ipc.handler).vtbl).restartComponent)(
+                            ipc.handler,
+                            RestartFlags_::kMidiCCAssignmentChanged,
+                        );
+                    }
+                }
+                Ok(value)
+            } else if let Some(value) =
+                beamer_core::note_ranges::note_ranges_invoke(&ipc.note_ranges, method)
+            {
+                Ok(value)
+            } else if let Some(value) = beamer_core::response_curves::response_curves_invoke(
+                &ipc.response_curves,
+                method,
+                &args,
+            ) {
+                Ok(value)
+            } else if let Some(value) =
+                beamer_core::host_services::host_services_invoke(&ipc.host_services, method, &args)
+            {
+                Ok(value)
+            } else if let Some(value) =
+                beamer_core::autosave::autosave_invoke(&ipc.autosave, method, &args)
+            {
+                // The user chose to restore the autosaved state.
+                if let Some(data) = ipc.autosave.take_accepted() {
+                    // SAFETY: state_access points to the owning processor, which outlives the view.
+                    unsafe { &*ipc.state_access }.restore_state(data);
+                }
+                Ok(value)
+            } else if method == beamer_core::preset_file::EXPORT_PRESET_FILE_INVOKE
+                || method == beamer_core::preset_file::IMPORT_PRESET_FILE_INVOKE
+            {
+                // SAFETY: state_access points to the owning processor, which outlives the view.
+                let state_access = unsafe { &*ipc.state_access };
+                beamer_core::preset_file::preset_file_invoke(
+                    state_access.plugin_config(),
+                    method,
+                    &args,
+                    || state_access.state_blob(),
+                    |data| {
+                        if state_access.restore_state(data.to_vec()) {
+                            Ok(())
+                        } else {
+                            Err("the plugin rejected the preset state".to_string())
+                        }
+                    },
+                )
+                .unwrap_or(Ok(serde_json::Value::Null))
+            } else if let Some(value) =
+                beamer_core::parameter_log::parameter_log_invoke(&ipc.parameter_log, params, method, &args)
+            {
+                Ok(value)
+            } else if let Some(value) =
+                beamer_core::midi_monitor::midi_monitor_invoke(&ipc.midi_monitor, method, &args)
+            {
+                Ok(value)
+            } else if let Some(value) = beamer_core::midi_panic::midi_panic_invoke(&ipc.midi_panic, method) {
+                Ok(value)
+            } else if let Some(value) = beamer_core::activation::activation_invoke(&ipc.activation, method) {
+                Ok(value)
             } else {
                 match &ipc.webview_handler {
                     Some(handler) => handler.on_invoke(method, &args),
@@ -267,6 +523,22 @@ unsafe extern "C-unwind" fn on_message(context: *mut c_void, json: *const u8, le
                 webview.evaluate_js(&js);
             }
         }
+        "frames" => {
+            if ipc.webview.is_null() {
+                return;
+            }
+            // SAFETY: webview pointer is valid for the view lifetime.
+            let webview = unsafe { &*ipc.webview };
+            let enabled = msg.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+            ipc.timer_frames = if enabled {
+                // SAFETY: context is the IpcContext, which outlives the
+                // webview (removed() detaches it first, stopping the clock).
+                !unsafe { webview.start_frame_clock(on_frame, context) }
+            } else {
+                webview.stop_frame_clock();
+                false
+            };
+        }
         "event" => {
             let Some(name) = msg.get("name").and_then(|v| v.as_str()) else { return };
             let data = msg.get("data").cloned().unwrap_or(serde_json::Value::Null);
@@ -303,7 +575,25 @@ unsafe extern "C-unwind" fn on_loaded(context: *mut c_void) {
     webview.evaluate_js(&js);
 }
 
+/// Frame clock callback: delivers one animation frame with the latest meters.
+unsafe extern "C-unwind" fn on_frame(context: *mut c_void, time_ms: f64) {
+    if context.is_null() {
+        return;
+    }
+
+    // SAFETY: context is a valid IpcContext pointer (set in on_message()).
+    let ipc = unsafe { &*(context as *const IpcContext) };
+    if ipc.webview.is_null() {
+        return;
+    }
+
+    // SAFETY: webview is non-null (checked above) and valid for the view lifetime.
+    let webview = unsafe { &*ipc.webview };
+    webview.evaluate_js(&ipc.frame_meters.frame_script(time_ms));
+}
+
 /// NSTimer callback for 60Hz parameter sync.
+#[cfg(target_os = "macos")]
 unsafe extern "C-unwind" fn sync_timer_fired(
     _this: *mut objc2::runtime::AnyObject,
     _cmd: objc2::runtime::Sel,
@@ -323,6 +613,32 @@ unsafe extern "C-unwind" fn sync_timer_fired(
 
     // SAFETY: ptr is a valid IpcContext pointer stored in the NSValue.
     let ipc = unsafe { &mut *(ptr as *mut IpcContext) };
+    // SAFETY: ipc's pointers are valid while its webview pointer is set.
+    unsafe { sync_tick(ipc) };
+}
+
+/// Win32 timer callback for 60Hz parameter sync. The timer id is the
+/// IpcContext address.
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn sync_timer_proc(_hwnd: *mut c_void, _msg: u32, id: usize, _time: u32) {
+    if id == 0 {
+        return;
+    }
+    // SAFETY: id is the IpcContext address passed to SetTimer(); the timer
+    // is killed before the context is dropped.
+    let ipc = unsafe { &mut *(id as *mut IpcContext) };
+    // SAFETY: ipc's pointers are valid while its webview pointer is set.
+    unsafe { sync_tick(ipc) };
+}
+
+/// One parameter sync tick: frames without a display link, changed
+/// parameters, pending scripts and autosave.
+///
+/// # Safety
+///
+/// `ipc`'s parameter, webview and state access pointers must be valid
+/// (or the webview pointer null).
+unsafe fn sync_tick(ipc: &mut IpcContext) {
     // Guard against timer firing after webview detach but before invalidation.
     if ipc.webview.is_null() {
         return;
@@ -333,6 +649,12 @@ unsafe extern "C-unwind" fn sync_timer_fired(
     // SAFETY: webview is non-null (checked above) and valid for the view lifetime.
     let webview = unsafe { &*ipc.webview };
 
+    // Without a display link, frames follow this timer.
+    if ipc.timer_frames {
+        let time_ms = ipc.frame_epoch.elapsed().as_secs_f64() * 1000.0;
+        webview.evaluate_js(&ipc.frame_meters.frame_script(time_ms));
+    }
+
     // Poll and push changed parameters.
     let mut script = String::new();
     let mut any_changed = false;
@@ -360,6 +682,34 @@ unsafe extern "C-unwind" fn sync_timer_fired(
         script.push_str("})");
         webview.evaluate_js(&script);
     }
+
+    if let Some(script) = ipc.watchdog.take_script() {
+        webview.evaluate_js(&script);
+    }
+    if let Some(script) = ipc.notifications.take_script() {
+        webview.evaluate_js(&script);
+    }
+    if let Some(script) = ipc.gui_data.take_script() {
+        webview.evaluate_js(&script);
+    }
+
+    // Autosave, forward dirty marks, latency, oversampling and preset list changes
+    // while the editor is open, and announce a recovery found by a
+    // setState() after the page loaded.
+    // SAFETY: state_access points to the owning processor, which outlives the view.
+    let state_access = unsafe { &*ipc.state_access };
+    state_access.snapshot_state();
+    state_access.flush_state_dirty();
+    state_access.flush_latency_changed();
+    state_access.check_oversampling();
+    state_access.check_preset_list();
+    if ipc.autosave.take_changed() {
+        let recovery = beamer_core::autosave::recovery_json(ipc.autosave.recovery().as_ref());
+        webview.evaluate_js(&format!(
+            "window.__BEAMER__._onEvent({},{recovery})",
+            serde_json::to_string(beamer_core::AUTOSAVE_EVENT).unwrap_or_default(),
+        ));
+    }
 }
 
 #[allow(non_snake_case)]
@@ -379,7 +729,11 @@ impl IPlugViewTrait for WebViewPlugView {
         // SAFETY: kPlatformTypeHWND is a static null-terminated byte literal.
         let supported = type_str == unsafe { std::ffi::CStr::from_ptr(kPlatformTypeHWND) };
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(target_os = "linux")]
+        // SAFETY: kPlatformTypeX11EmbedWindowID is a static null-terminated byte literal.
+        let supported = type_str == unsafe { std::ffi::CStr::from_ptr(kPlatformTypeX11EmbedWindowID) };
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         let supported = false;
 
         if supported { kResultOk } else { kResultFalse }
@@ -463,6 +817,28 @@ impl IPlugViewTrait for WebViewPlugView {
                     ipc.sync_timer = timer;
                 }
 
+                #[cfg(target_os = "windows")]
+                {
+                    // SAFETY: parent is the host's HWND, owned by this thread.
+                    // The timer id is the IpcContext address, which outlives
+                    // the timer (removed() and drop() kill it).
+                    if unsafe { win32::SetTimer(parent, ipc_ptr as usize, 16, Some(sync_timer_proc)) } != 0 {
+                        ipc.sync_timer_window = parent;
+                    } else {
+                        log::warn!("Failed to start the parameter sync timer.");
+                    }
+                }
+
+                #[cfg(target_os = "linux")]
+                {
+                    // SAFETY: The frame is null or the host's IPlugFrame (set
+                    // in setFrame()); the handler's context outlives the timer.
+                    ipc.run_loop_timer = unsafe { RunLoopTimer::start(*self.frame.get(), ipc_ptr as *mut IpcContext) };
+                    if ipc.run_loop_timer.is_none() {
+                        log::warn!("Host provides no IRunLoop; the editor will not update.");
+                    }
+                }
+
                 // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
                 let delegate = unsafe { &mut *self.delegate.get() };
                 delegate.gui_opened();
@@ -498,9 +874,18 @@ impl IPlugViewTrait for WebViewPlugView {
                 ipc.sync_timer = std::ptr::null_mut();
             }
         }
+        #[cfg(target_os = "windows")]
+        // SAFETY: Same window and id as in attached().
+        unsafe { kill_sync_timer(ipc) };
+        #[cfg(target_os = "linux")]
+        {
+            ipc.run_loop_timer = None;
+        }
 
-        // Clear webview pointer before detaching.
+        // Clear webview pointer before detaching (which also stops the
+        // frame clock).
         ipc.webview = std::ptr::null();
+        ipc.timer_frames = false;
 
         // SAFETY: VST3 guarantees single-threaded access for IPlugView methods.
         let platform = unsafe { &mut *self.platform.get() };
@@ -562,7 +947,7 @@ impl IPlugViewTrait for WebViewPlugView {
         if let Some(webview) = platform.as_ref() {
             #[cfg(target_os = "macos")]
             webview.set_frame(0, 0, width as i32, height as i32);
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
             webview.set_bounds(0, 0, width as i32, height as i32);
         }
 
@@ -646,6 +1031,13 @@ impl Drop for WebViewPlugView {
                 ipc.sync_timer = std::ptr::null_mut();
             }
         }
+        #[cfg(target_os = "windows")]
+        // SAFETY: Same window and id as in attached().
+        unsafe { kill_sync_timer(ipc) };
+        #[cfg(target_os = "linux")]
+        {
+            ipc.run_loop_timer = None;
+        }
 
         // Clear webview pointer to prevent stale dereferences.
         ipc.webview = std::ptr::null();
@@ -688,6 +1080,115 @@ impl GuiDelegate for StaticGuiDelegate {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Win32 timer
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use std::ffi::c_void;
+
+    pub type TimerProc = unsafe extern "system" fn(hwnd: *mut c_void, msg: u32, id: usize, time: u32);
+
+    #[link(name = "user32")]
+    extern "system" {
+        pub fn SetTimer(hwnd: *mut c_void, id: usize, elapse_ms: u32, timer_proc: Option<TimerProc>) -> usize;
+        pub fn KillTimer(hwnd: *mut c_void, id: usize) -> i32;
+    }
+}
+
+/// Stop the sync timer started in `attached()`, if running.
+///
+/// # Safety
+///
+/// `ipc.sync_timer_window` must be null or the window the timer was set on.
+#[cfg(target_os = "windows")]
+unsafe fn kill_sync_timer(ipc: &mut IpcContext) {
+    if !ipc.sync_timer_window.is_null() {
+        // SAFETY: Same window and id as passed to SetTimer().
+        unsafe { win32::KillTimer(ipc.sync_timer_window, std::ptr::from_mut(ipc) as usize) };
+        ipc.sync_timer_window = std::ptr::null_mut();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linux run loop timer
+// ---------------------------------------------------------------------------
+
+/// A 60Hz timer on the host's `IRunLoop`, which VST3 hosts on Linux offer
+/// through the `IPlugFrame`. Unregistered when dropped.
+#[cfg(target_os = "linux")]
+struct RunLoopTimer {
+    run_loop: vst3::ComPtr<Linux::IRunLoop>,
+    handler: vst3::ComPtr<Linux::ITimerHandler>,
+}
+
+#[cfg(target_os = "linux")]
+impl RunLoopTimer {
+    /// Register a timer that pumps GTK events and runs the sync tick for
+    /// `ipc`. Returns None if the host has no run loop.
+    ///
+    /// # Safety
+    ///
+    /// `frame` must be null or a valid IPlugFrame. `ipc` must outlive the
+    /// returned timer.
+    unsafe fn start(frame: *mut IPlugFrame, ipc: *mut IpcContext) -> Option<Self> {
+        use Linux::IRunLoopTrait;
+
+        // SAFETY: frame is null or a valid COM pointer per the caller's contract.
+        let run_loop = unsafe { vst3::ComRef::from_raw(frame) }?.cast::<Linux::IRunLoop>()?;
+        let handler = vst3::ComWrapper::new(SyncTimerHandler { ipc }).to_com_ptr::<Linux::ITimerHandler>()?;
+        // SAFETY: run_loop and handler are valid COM pointers.
+        if unsafe { run_loop.registerTimer(handler.as_ptr(), 16) } != kResultOk {
+            return None;
+        }
+        Some(Self { run_loop, handler })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for RunLoopTimer {
+    fn drop(&mut self) {
+        use Linux::IRunLoopTrait;
+
+        // SAFETY: Same run loop and handler as registered in start().
+        unsafe { self.run_loop.unregisterTimer(self.handler.as_ptr()) };
+    }
+}
+
+/// `ITimerHandler` called by the host's run loop.
+#[cfg(target_os = "linux")]
+struct SyncTimerHandler {
+    ipc: *mut IpcContext,
+}
+
+// SAFETY: The host calls onTimer() on the UI thread only.
+#[cfg(target_os = "linux")]
+unsafe impl Send for SyncTimerHandler {}
+// SAFETY: See above.
+#[cfg(target_os = "linux")]
+unsafe impl Sync for SyncTimerHandler {}
+
+#[cfg(target_os = "linux")]
+impl Class for SyncTimerHandler {
+    type Interfaces = (Linux::ITimerHandler,);
+}
+
+#[cfg(target_os = "linux")]
+impl Linux::ITimerHandlerTrait for SyncTimerHandler {
+    unsafe fn onTimer(&self) {
+        // SAFETY: The timer is unregistered before the IpcContext is dropped.
+        let ipc = unsafe { &mut *self.ipc };
+        if !ipc.webview.is_null() {
+            // Hosts don't run a GLib main loop; WebKitGTK needs one.
+            // SAFETY: webview is valid while the pointer is set.
+            unsafe { &*ipc.webview }.pump_events();
+        }
+        // SAFETY: ipc's pointers are valid while its webview pointer is set.
+        unsafe { sync_tick(ipc) };
+    }
+}
+
 // ---------------------------------------------------------------------------
 // NSTimer helper class
 // ---------------------------------------------------------------------------
@@ -4,6 +4,11 @@
 //! create and manage a WKWebView without reimplementing the setup logic.
 //! The same Rust platform code is shared by both Vst3 (via direct Rust calls)
 //! and Au (via these C-ABI functions).
+//!
+//! Every function here loads the main editor page
+//! ([`EDITOR_PAGE`](beamer_core::gui::EDITOR_PAGE)). Additional named views
+//! (`gui_views` in `Config.toml`) are VST3-only: AU hosts have no API to
+//! request a view by name, so the AU wrappers only ever open the main editor.
 
 #[cfg(target_os = "macos")]
 mod macos_ffi {
@@ -74,6 +79,7 @@ mod macos_ffi {
                 plugin_code: code,
                 assets: Some(assets_ref),
                 url: None,
+                page: beamer_core::gui::EDITOR_PAGE,
                 dev_tools,
                 background_color: bg,
                 message_callback: None,
@@ -135,6 +141,7 @@ mod macos_ffi {
                 plugin_code: code,
                 assets: None,
                 url: Some(url_str),
+                page: beamer_core::gui::EDITOR_PAGE,
                 dev_tools,
                 background_color: bg,
                 message_callback: None,
@@ -223,6 +230,56 @@ mod macos_ffi {
         }));
     }
 
+    /// Start calling `callback` once per display refresh.
+    ///
+    /// Replaces a running frame clock. Returns `false` if no display link
+    /// could be created.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid pointer from `beamer_webview_create`
+    /// - `context` must remain valid until `beamer_webview_stop_frame_clock`
+    ///   or `beamer_webview_destroy`
+    /// - Must be called from the main thread
+    #[no_mangle]
+    pub extern "C" fn beamer_webview_start_frame_clock(
+        handle: *mut c_void,
+        callback: Option<crate::FrameCallback>,
+        context: *mut c_void,
+    ) -> bool {
+        let Some(callback) = callback else { return false };
+        if handle.is_null() {
+            return false;
+        }
+
+        catch_unwind(AssertUnwindSafe(|| {
+            // SAFETY: caller guarantees handle is valid.
+            let webview = unsafe { &*(handle as *const MacosWebView) };
+            // SAFETY: caller guarantees context outlives the clock.
+            unsafe { webview.start_frame_clock(callback, context) }
+        }))
+        .unwrap_or(false)
+    }
+
+    /// Stop the frame clock. No frame callback runs after this returns.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid pointer from `beamer_webview_create`
+    /// - Must be called from the main thread
+    #[no_mangle]
+    pub extern "C" fn beamer_webview_stop_frame_clock(handle: *mut c_void) {
+        if handle.is_null() {
+            return;
+        }
+
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            // SAFETY: caller guarantees handle is valid.
+            let webview = unsafe { &*(handle as *const MacosWebView) };
+            webview.stop_frame_clock();
+        }));
+    }
+
     /// Create a WebView with IPC callbacks.
     ///
     /// Extended version of `beamer_webview_create` that accepts message and
@@ -272,6 +329,7 @@ mod macos_ffi {
                 plugin_code: code,
                 assets: Some(assets_ref),
                 url: None,
+                page: beamer_core::gui::EDITOR_PAGE,
                 dev_tools,
                 background_color: bg,
                 message_callback,
@@ -330,6 +388,7 @@ mod macos_ffi {
                 plugin_code: code,
                 assets: None,
                 url: Some(url_str),
+                page: beamer_core::gui::EDITOR_PAGE,
                 dev_tools,
                 background_color: bg,
                 message_callback,
@@ -26,15 +26,26 @@ pub type MessageCallback =
 /// Called on the main thread.
 pub type LoadedCallback = unsafe extern "C-unwind" fn(context: *mut c_void);
 
+/// Callback fired once per display refresh while the frame clock runs.
+///
+/// `time_ms` is the refresh time in milliseconds on a monotonic clock.
+/// Called on the main thread. Refreshes that arrive while the previous
+/// call is still queued are dropped, so a busy main thread never builds
+/// a backlog.
+pub type FrameCallback = unsafe extern "C-unwind" fn(context: *mut c_void, time_ms: f64);
+
 /// Configuration for a WebView GUI.
 pub struct WebViewConfig<'a> {
     /// 4-byte plugin subtype code used to generate a unique ObjC class name
     /// per plugin type so multiple plugins can coexist in the same host process.
     pub plugin_code: [u8; 4],
     /// Embedded web assets. When set, the WebView navigates to
-    /// `beamer://localhost/index.html` and a per-instance scheme handler
+    /// `beamer://localhost/<page>` and a per-instance scheme handler
     /// serves files from this table.
     pub assets: Option<&'static EmbeddedAssets>,
+    /// Entry page to load, relative to the asset root or dev server URL.
+    /// [`EDITOR_PAGE`](beamer_core::gui::EDITOR_PAGE) for the main editor view.
+    pub page: &'a str,
     /// Dev server URL. When set, the WebView navigates here instead of
     /// using the custom scheme handler. The lifetime allows FFI paths to
     /// pass a short-lived reference without claiming `'static`.
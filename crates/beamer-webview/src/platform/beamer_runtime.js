@@ -3,6 +3,7 @@
   var paramById = {};
   var pendingParamSubs = {};
   var eventListeners = {};
+  var frameListeners = [];
   var invokeCallbacks = {};
   var nextCallId = 0;
   var readyResolve;
@@ -11,15 +12,188 @@
   // JSON.stringify is intentional: postMessage accepts any plist-compatible
   // type, but the native side expects a plain UTF-8 string so it can be
   // forwarded through the C-ABI callback as raw bytes. Passing an object
-  // would give an NSDictionary, which is harder to bridge.
-  var nativeHandler = window.webkit
+  // would give an NSDictionary, which is harder to bridge. WebView2 on
+  // Windows takes the same string through window.chrome.webview.
+  var nativeHandler = (window.webkit
     && window.webkit.messageHandlers
-    && window.webkit.messageHandlers.beamer;
+    && window.webkit.messageHandlers.beamer)
+    || (window.chrome && window.chrome.webview);
 
   function post(msg) {
     if (nativeHandler) nativeHandler.postMessage(JSON.stringify(msg));
   }
 
+  // Poll the license check until it finishes, then announce the result.
+  function watchActivation() {
+    window.__BEAMER__.activation().then(function(status) {
+      if (!status || !status.enabled) return;
+      if (status.running || status.state === "pending") {
+        setTimeout(watchActivation, 250);
+      } else {
+        window.__BEAMER__._onEvent("beamer:activation", status);
+      }
+    });
+  }
+
+  // Poll the MIDI monitor for one subscriber, from its cursor on, while it
+  // stays subscribed. Stops if the plugin has no monitor.
+  function watchMidiMonitor(sub) {
+    window.__BEAMER__.getMidiMonitor(sub.next, sub.filter).then(function(read) {
+      if (!sub.active || !read || !read.enabled) return;
+      sub.next = read.next;
+      if (read.entries.length > 0 || read.dropped > 0) sub.cb(read.entries, read.dropped);
+      setTimeout(function() { if (sub.active) watchMidiMonitor(sub); }, 100);
+    });
+  }
+
+  // Unbind function of every element bound by bindAccessibility().
+  var accessibilityBindings = new WeakMap();
+
+  // Mirror a parameter onto an element's ARIA attributes so screen readers
+  // (VoiceOver, Narrator) announce its name, role and value. Toggles become
+  // switches; everything else is a slider driven by the arrow keys. Binding
+  // an element again returns its existing unbind function.
+  function bindAccessibility(element, stringId) {
+    var p = paramMap[stringId];
+    if (!p) return function() {};
+    var bound = accessibilityBindings.get(element);
+    if (bound) return bound;
+    var info = p.info;
+    var toggle = info.steps === 1;
+    element.setAttribute("role", toggle ? "switch" : "slider");
+    if (!element.hasAttribute("aria-label")) {
+      element.setAttribute("aria-label", info.name);
+    }
+    if (!element.hasAttribute("tabindex")) element.setAttribute("tabindex", "0");
+
+    function update() {
+      if (toggle) {
+        element.setAttribute("aria-checked", p.value >= 0.5 ? "true" : "false");
+        return;
+      }
+      var text = p.displayText;
+      if (info.units && text.indexOf(info.units) < 0) text += " " + info.units;
+      element.setAttribute("aria-valuemin", String(info.min));
+      element.setAttribute("aria-valuemax", String(info.max));
+      element.setAttribute("aria-valuenow", String(p.plainValue));
+      element.setAttribute("aria-valuetext", text);
+    }
+
+    function nudge(value) {
+      value = Math.min(1, Math.max(0, value));
+      window.__BEAMER__.params.beginEdit(stringId);
+      window.__BEAMER__.params.set(stringId, value);
+      window.__BEAMER__.params.endEdit(stringId);
+    }
+
+    function onKey(e) {
+      var step = info.steps > 0 ? 1 / info.steps : 0.01;
+      var value = p.value;
+      switch (e.key) {
+        case "ArrowUp": case "ArrowRight": value += step; break;
+        case "ArrowDown": case "ArrowLeft": value -= step; break;
+        case "PageUp": value += step * 10; break;
+        case "PageDown": value -= step * 10; break;
+        case "Home": value = 0; break;
+        case "End": value = 1; break;
+        case " ": case "Enter":
+          if (!toggle) return;
+          value = p.value >= 0.5 ? 0 : 1;
+          break;
+        default: return;
+      }
+      e.preventDefault();
+      nudge(value);
+    }
+
+    var off = window.__BEAMER__.params.on(stringId, update);
+    element.addEventListener("keydown", onKey);
+    update();
+    function unbind() {
+      if (accessibilityBindings.get(element) !== unbind) return;
+      accessibilityBindings.delete(element);
+      off();
+      element.removeEventListener("keydown", onKey);
+    }
+    accessibilityBindings.set(element, unbind);
+    return unbind;
+  }
+
+  // Bind every element carrying `data-beamer-param`, now and as the page
+  // renders more of them, and unbind them when the page removes them.
+  function observeAccessibility() {
+    function marked(root) {
+      if (!root.querySelectorAll) return [];
+      var elements = Array.prototype.slice.call(root.querySelectorAll("[data-beamer-param]"));
+      if (root.dataset && root.dataset.beamerParam) elements.unshift(root);
+      return elements;
+    }
+    function scan(root) {
+      marked(root).forEach(function(el) {
+        bindAccessibility(el, el.dataset.beamerParam);
+      });
+    }
+    function release(root) {
+      // Moved elements are removed and added again; keep them bound
+      if (root.isConnected) return;
+      marked(root).forEach(function(el) {
+        var unbind = accessibilityBindings.get(el);
+        if (unbind) unbind();
+      });
+    }
+    scan(document);
+    if (typeof MutationObserver === "undefined") return;
+    new MutationObserver(function(mutations) {
+      mutations.forEach(function(m) {
+        m.removedNodes.forEach(release);
+        m.addedNodes.forEach(scan);
+      });
+    }).observe(document.documentElement, {childList: true, subtree: true});
+  }
+
+  // Built-in rendering for `beamer:notification` when the page doesn't
+  // subscribe: a stack of toasts in the bottom-right corner that dismiss
+  // themselves (click to dismiss early).
+  var NOTIFICATION_EVENT = "beamer:notification";
+  var TOAST_COLORS = {info: "#2f6fd6", warning: "#b7791f", error: "#c53030"};
+  var toastStack = null;
+
+  function showToast(n) {
+    if (!document.body) {
+      document.addEventListener("DOMContentLoaded", function() { showToast(n); });
+      return;
+    }
+    if (!toastStack) {
+      toastStack = document.createElement("div");
+      toastStack.style.cssText = "position:fixed;right:12px;bottom:12px;"
+        + "z-index:2147483647;display:flex;flex-direction:column;gap:6px;"
+        + "max-width:60%;pointer-events:none;"
+        + "font:12px -apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif";
+      document.body.appendChild(toastStack);
+    }
+    var toast = document.createElement("div");
+    toast.setAttribute("role", n.level === "error" ? "alert" : "status");
+    toast.style.cssText = "padding:8px 12px;border-radius:6px;color:#fff;"
+      + "box-shadow:0 2px 8px rgba(0,0,0,.35);pointer-events:auto;cursor:pointer;"
+      + "background:" + (TOAST_COLORS[n.level] || TOAST_COLORS.info);
+    toast.textContent = n.count > 1 ? n.message + " (\u00d7" + n.count + ")" : n.message;
+    function dismiss() {
+      if (toast.parentNode) toast.parentNode.removeChild(toast);
+    }
+    toast.addEventListener("click", dismiss);
+    setTimeout(dismiss, n.level === "error" ? 8000 : 4000);
+    toastStack.appendChild(toast);
+  }
+
+  function dispatch(name, data) {
+    var cbs = eventListeners[name];
+    if (cbs && cbs.length) {
+      cbs.forEach(function(cb) { cb(data); });
+    } else if (name === NOTIFICATION_EVENT) {
+      showToast(data);
+    }
+  }
+
   window.__BEAMER__ = {
     ready: readyPromise,
 
@@ -43,6 +217,15 @@
         p.info.value = value;
         post({type:"param:set", id:p.id, value:value});
       },
+      // Clamped set that skips values the parameter already has, for
+      // controls that report every pointer move.
+      setNormalized: function(stringId, value) {
+        var p = paramMap[stringId];
+        if (!p) return;
+        value = Math.min(1, Math.max(0, +value));
+        if (value === p.value) return;
+        window.__BEAMER__.params.set(stringId, value);
+      },
       beginEdit: function(stringId) {
         var p = paramMap[stringId];
         if (p) post({type:"param:begin", id:p.id});
@@ -71,13 +254,54 @@
           p.listeners = p.listeners.filter(function(f){return f!==cb;});
         };
       },
+      // Like on(), but the callback receives {value, plainValue,
+      // displayText} and runs once with the current state when ready.
+      subscribe: function(stringId, cb) {
+        function state() {
+          var p = paramMap[stringId];
+          return {value: p.value, plainValue: p.plainValue, displayText: p.displayText};
+        }
+        var live = true;
+        var off = window.__BEAMER__.params.on(stringId, function() { cb(state()); });
+        readyPromise.then(function() {
+          if (live && paramMap[stringId]) cb(state());
+        });
+        return function() {
+          live = false;
+          off();
+        };
+      },
       all: function() {
         return Object.values(paramMap).map(function(p) { return p.info; });
       },
       info: function(stringId) {
         var p = paramMap[stringId];
         return p ? p.info : undefined;
-      }
+      },
+      promote: function(stringId) {
+        var p = paramMap[stringId];
+        if (!p) return Promise.resolve(false);
+        return window.__BEAMER__.invoke("_beamer/promoteParameter", p.id);
+      },
+      demote: function(stringId) {
+        var p = paramMap[stringId];
+        if (!p) return Promise.resolve(false);
+        return window.__BEAMER__.invoke("_beamer/demoteParameter", p.id);
+      },
+      promoted: function() {
+        return window.__BEAMER__.invoke("_beamer/promotedParameters")
+          .then(function(ids) {
+            return ids
+              .filter(function(id) { return paramById[id]; })
+              .map(function(id) { return paramById[id].info.stringId; });
+          });
+      },
+      automation: function(stringId) {
+        var p = paramMap[stringId];
+        if (!p) return Promise.resolve(null);
+        return window.__BEAMER__.invoke("_beamer/automationState", p.id);
+      },
+      bindAccessibility: bindAccessibility
     },
 
     invoke: function(method) {
@@ -89,6 +313,128 @@
       });
     },
 
+    getLogTail: function(lines) {
+      return window.__BEAMER__.invoke("_beamer/logTail", lines);
+    },
+
+    isSafeMode: function() {
+      return window.__BEAMER__.invoke("_beamer/safeMode");
+    },
+
+    stateHash: function() {
+      return window.__BEAMER__.invoke("_beamer/stateHash");
+    },
+
+    exportPreset: function(path, name) {
+      return window.__BEAMER__.invoke("_beamer/exportPresetFile", path, name);
+    },
+
+    importPreset: function(path) {
+      return window.__BEAMER__.invoke("_beamer/importPresetFile", path);
+    },
+
+    controllerPages: function() {
+      return window.__BEAMER__.invoke("_beamer/controllerPages")
+        .then(function(state) {
+          state.pages.forEach(function(page) {
+            page.parameters = page.parameters.map(function(id) {
+              return id !== null && paramById[id] ? paramById[id].info.stringId : null;
+            });
+          });
+          return state;
+        });
+    },
+
+    selectControllerPage: function(index) {
+      return window.__BEAMER__.invoke("_beamer/selectControllerPage", index);
+    },
+
+    responseCurves: function() {
+      return window.__BEAMER__.invoke("_beamer/responseCurves");
+    },
+
+    setResponseCurve: function(target, curve) {
+      return window.__BEAMER__.invoke("_beamer/setResponseCurve", target, curve || null);
+    },
+
+    noteRanges: function() {
+      return window.__BEAMER__.invoke("_beamer/noteRanges");
+    },
+
+    instanceName: function() {
+      return window.__BEAMER__.invoke("_beamer/instanceName");
+    },
+
+    setDisplayName: function(name) {
+      return window.__BEAMER__.invoke("_beamer/setDisplayName", name || null);
+    },
+
+    autosaveRecovery: function() {
+      return window.__BEAMER__.invoke("_beamer/autosave");
+    },
+
+    resolveAutosave: function(restore) {
+      return window.__BEAMER__.invoke("_beamer/resolveAutosave", !!restore);
+    },
+
+    parameterGroups: function() {
+      return window.__BEAMER__.invoke("_beamer/parameterGroups");
+    },
+
+    getParameterLog: function(limit) {
+      return window.__BEAMER__.invoke("_beamer/parameterLog", limit);
+    },
+
+    clearParameterLog: function() {
+      return window.__BEAMER__.invoke("_beamer/clearParameterLog");
+    },
+
+    getMidiMonitor: function(since, filter, limit) {
+      return window.__BEAMER__.invoke("_beamer/midiMonitor", since || 0, filter || null, limit);
+    },
+
+    clearMidiMonitor: function() {
+      return window.__BEAMER__.invoke("_beamer/clearMidiMonitor");
+    },
+
+    midiPanic: function() {
+      return window.__BEAMER__.invoke("_beamer/midiPanic");
+    },
+
+    // Streams monitored MIDI as cb(entries, dropped), starting with the
+    // entries already recorded.
+    onMidiMonitor: function(cb, filter) {
+      var sub = {cb: cb, filter: filter || null, next: 0, active: true};
+      readyPromise.then(function() { if (sub.active) watchMidiMonitor(sub); });
+      return function() { sub.active = false; };
+    },
+
+    activation: function() {
+      return window.__BEAMER__.invoke("_beamer/activation");
+    },
+
+    retryActivation: function() {
+      return window.__BEAMER__.invoke("_beamer/retryActivation")
+        .then(function(status) {
+          watchActivation();
+          return status;
+        });
+    },
+
+    // The native view runs its display-synced frame clock only while
+    // someone listens.
+    onFrame: function(cb) {
+      frameListeners.push(cb);
+      if (frameListeners.length === 1) post({type:"frames", enabled:true});
+      return function() {
+        var before = frameListeners.length;
+        frameListeners = frameListeners.filter(function(f){return f!==cb;});
+        if (before > 0 && frameListeners.length === 0) {
+          post({type:"frames", enabled:false});
+        }
+      };
+    },
+
     on: function(name, cb) {
       if (!eventListeners[name]) eventListeners[name] = [];
       eventListeners[name].push(cb);
@@ -114,6 +460,16 @@
         paramById[p.id] = entry;
       });
       readyResolve();
+      observeAccessibility();
+
+      // Announce an autosave found before the page loaded.
+      window.__BEAMER__.autosaveRecovery().then(function(recovery) {
+        if (recovery && recovery.available) {
+          window.__BEAMER__._onEvent("beamer:autosaveRecovery", recovery);
+        }
+      });
+
+      watchActivation();
     },
 
     _onParams: function(changed) {
@@ -143,9 +499,252 @@
       }
     },
 
+    _onFrame: function(time, meters, playhead) {
+      frameListeners.forEach(function(cb) { cb(time, meters, playhead); });
+    },
+
     _onEvent: function(name, data) {
-      var cbs = eventListeners[name];
-      if (cbs) cbs.forEach(function(cb) { cb(data); });
+      if (name === NOTIFICATION_EVENT) {
+        // Let the page subscribe after ready before falling back to a toast.
+        readyPromise.then(function() {
+          setTimeout(function() { dispatch(name, data); }, 0);
+        });
+        return;
+      }
+      dispatch(name, data);
     }
   };
+
+  // <beamer-vectorscope source="scope" mode="vectorscope" autoscale>
+  // draws a Vectorscope from FrameMeters::with_scope(). mode is
+  // "vectorscope" (mid up, side across) or "lissajous" (left across, right
+  // up); autoscale follows the held peak. Colors come from the CSS
+  // properties --beamer-scope-color and --beamer-scope-grid.
+  if (window.customElements && !customElements.get("beamer-vectorscope")) {
+    customElements.define("beamer-vectorscope", class extends HTMLElement {
+      connectedCallback() {
+        if (!this.canvas) {
+          this.canvas = document.createElement("canvas");
+          this.canvas.style.width = "100%";
+          this.canvas.style.height = "100%";
+          this.canvas.style.display = "block";
+          this.appendChild(this.canvas);
+        }
+        if (!this.style.display) this.style.display = "block";
+        this.off = window.__BEAMER__.onFrame(this.draw.bind(this));
+      }
+
+      disconnectedCallback() {
+        if (this.off) this.off();
+        this.off = null;
+      }
+
+      draw(time, meters) {
+        var frame = meters[this.getAttribute("source") || "scope"];
+        var canvas = this.canvas;
+        var ratio = window.devicePixelRatio || 1;
+        var width = Math.round(canvas.clientWidth * ratio);
+        var height = Math.round(canvas.clientHeight * ratio);
+        if (canvas.width !== width || canvas.height !== height) {
+          canvas.width = width;
+          canvas.height = height;
+        }
+        var ctx = canvas.getContext("2d");
+        var style = getComputedStyle(this);
+        var color = style.getPropertyValue("--beamer-scope-color").trim() || "#7fd4ff";
+        var grid = style.getPropertyValue("--beamer-scope-grid").trim() || "rgba(255,255,255,0.15)";
+        var cx = width / 2, cy = height / 2, radius = Math.min(cx, cy);
+
+        // Fade the previous traces instead of clearing, for persistence.
+        ctx.globalCompositeOperation = "destination-out";
+        ctx.fillStyle = "rgba(0,0,0,0.35)";
+        ctx.fillRect(0, 0, width, height);
+        ctx.globalCompositeOperation = "source-over";
+
+        var lissajous = this.getAttribute("mode") === "lissajous";
+        ctx.strokeStyle = grid;
+        ctx.lineWidth = ratio;
+        ctx.beginPath();
+        if (lissajous) {
+          ctx.moveTo(cx - radius, cy); ctx.lineTo(cx + radius, cy);
+          ctx.moveTo(cx, cy - radius); ctx.lineTo(cx, cy + radius);
+        } else {
+          ctx.moveTo(cx - radius, cy - radius); ctx.lineTo(cx + radius, cy + radius);
+          ctx.moveTo(cx + radius, cy - radius); ctx.lineTo(cx - radius, cy + radius);
+        }
+        ctx.stroke();
+        if (!frame || !frame.points) return;
+
+        var scale = radius;
+        if (this.hasAttribute("autoscale")) scale /= Math.max(frame.peakHold, 0.05);
+        var points = frame.points;
+        var size = 1.5 * ratio;
+        ctx.fillStyle = color;
+        for (var i = 0; i + 1 < points.length; i += 2) {
+          var l = points[i], r = points[i + 1], x, y;
+          if (lissajous) {
+            x = l; y = r;
+          } else {
+            x = (r - l) * Math.SQRT1_2; y = (l + r) * Math.SQRT1_2;
+          }
+          ctx.fillRect(cx + x * scale - size / 2, cy - y * scale - size / 2, size, size);
+        }
+      }
+    });
+  }
+
+  // <beamer-keyboard low="36" high="96"> draws a keyboard from
+  // noteRanges(): keys outside the playable range are shaded and each split
+  // is tinted and labeled. low/high set the displayed keys (default: the
+  // playable range widened to whole octaves). Pressing a key dispatches a
+  // "beamer-note" event with detail { pitch, on }. Colors come from the CSS
+  // properties --beamer-keyboard-disabled and --beamer-keyboard-split-N.
+  if (window.customElements && !customElements.get("beamer-keyboard")) {
+    var BLACK_KEYS = [false, true, false, true, false, false, true, false, true, false, true, false];
+    customElements.define("beamer-keyboard", class extends HTMLElement {
+      connectedCallback() {
+        if (!this.canvas) {
+          this.canvas = document.createElement("canvas");
+          this.canvas.style.width = "100%";
+          this.canvas.style.height = "100%";
+          this.canvas.style.display = "block";
+          this.appendChild(this.canvas);
+          this.canvas.addEventListener("pointerdown", this.press.bind(this));
+          this.canvas.addEventListener("pointerup", this.release.bind(this));
+          this.canvas.addEventListener("pointerleave", this.release.bind(this));
+        }
+        if (!this.style.display) this.style.display = "block";
+        var self = this;
+        window.__BEAMER__.noteRanges().then(function(ranges) {
+          self.ranges = ranges;
+          self.draw();
+        });
+        if (window.ResizeObserver) {
+          this.observer = new ResizeObserver(function() { self.draw(); });
+          this.observer.observe(this);
+        }
+      }
+
+      disconnectedCallback() {
+        if (this.observer) this.observer.disconnect();
+        this.observer = null;
+      }
+
+      // Displayed keys as { pitch, black, x, width } in canvas pixels.
+      layout(width) {
+        var ranges = this.ranges || {low: 0, high: 127};
+        var low = this.hasAttribute("low") ? +this.getAttribute("low") : ranges.low - ranges.low % 12;
+        var high = this.hasAttribute("high") ? +this.getAttribute("high") : Math.min(127, ranges.high + 11 - ranges.high % 12);
+        var whites = 0;
+        for (var p = low; p <= high; p++) if (!BLACK_KEYS[p % 12]) whites++;
+        var whiteWidth = width / Math.max(whites, 1);
+        var keys = [], x = 0;
+        for (var pitch = low; pitch <= high; pitch++) {
+          if (BLACK_KEYS[pitch % 12]) {
+            keys.push({pitch: pitch, black: true, x: x - whiteWidth * 0.3, width: whiteWidth * 0.6});
+          } else {
+            keys.push({pitch: pitch, black: false, x: x, width: whiteWidth});
+            x += whiteWidth;
+          }
+        }
+        return keys;
+      }
+
+      splitIndex(pitch) {
+        var splits = this.ranges ? this.ranges.splits : [];
+        for (var i = 0; i < splits.length; i++) {
+          if (pitch >= splits[i].low && pitch <= splits[i].high) return i;
+        }
+        return -1;
+      }
+
+      draw() {
+        var canvas = this.canvas;
+        var ratio = window.devicePixelRatio || 1;
+        var width = Math.round(canvas.clientWidth * ratio);
+        var height = Math.round(canvas.clientHeight * ratio);
+        if (!width || !height) return;
+        canvas.width = width;
+        canvas.height = height;
+        var ctx = canvas.getContext("2d");
+        var style = getComputedStyle(this);
+        var disabled = style.getPropertyValue("--beamer-keyboard-disabled").trim() || "rgba(0,0,0,0.45)";
+        var ranges = this.ranges || {low: 0, high: 127, splits: []};
+        var labelHeight = ranges.splits.length ? 14 * ratio : 0;
+        var keyHeight = height - labelHeight;
+        var keys = this.layout(width);
+        var self = this;
+
+        ctx.clearRect(0, 0, width, height);
+        // White keys first, then black keys on top.
+        [false, true].forEach(function(black) {
+          keys.forEach(function(key) {
+            if (key.black !== black) return;
+            var h = black ? keyHeight * 0.62 : keyHeight;
+            ctx.fillStyle = key.pitch === self.pressed ? "#7fd4ff" : (black ? "#222" : "#f4f4f4");
+            ctx.fillRect(key.x, labelHeight, key.width, h);
+            var split = self.splitIndex(key.pitch);
+            if (split >= 0) {
+              ctx.fillStyle = style.getPropertyValue("--beamer-keyboard-split-" + split).trim() ||
+                "hsla(" + (split * 137) % 360 + ",70%,55%,0.35)";
+              ctx.fillRect(key.x, labelHeight, key.width, h);
+            }
+            if (key.pitch < ranges.low || key.pitch > ranges.high) {
+              ctx.fillStyle = disabled;
+              ctx.fillRect(key.x, labelHeight, key.width, h);
+            }
+            if (!black) {
+              ctx.strokeStyle = "#888";
+              ctx.lineWidth = ratio;
+              ctx.strokeRect(key.x, labelHeight, key.width, h);
+            }
+          });
+        });
+
+        // Split names above their first displayed key.
+        ctx.font = 10 * ratio + "px sans-serif";
+        ctx.textBaseline = "top";
+        ranges.splits.forEach(function(split, i) {
+          var first = keys.find(function(key) { return key.pitch >= split.low && key.pitch <= split.high; });
+          if (!first) return;
+          ctx.fillStyle = style.getPropertyValue("--beamer-keyboard-split-" + i).trim() ||
+            "hsl(" + (i * 137) % 360 + ",70%,55%)";
+          ctx.fillText(split.name, first.x + 2 * ratio, 2 * ratio);
+        });
+      }
+
+      keyAt(event) {
+        var rect = this.canvas.getBoundingClientRect();
+        var ratio = window.devicePixelRatio || 1;
+        var x = (event.clientX - rect.left) * ratio;
+        var y = (event.clientY - rect.top) * ratio;
+        var keys = this.layout(this.canvas.width);
+        var labelHeight = this.ranges && this.ranges.splits.length ? 14 * ratio : 0;
+        var blackBottom = labelHeight + (this.canvas.height - labelHeight) * 0.62;
+        var hit = null;
+        keys.forEach(function(key) {
+          if (x < key.x || x >= key.x + key.width) return;
+          if (key.black && y < blackBottom) hit = key;
+          else if (!key.black && (!hit || !hit.black)) hit = key;
+        });
+        return hit;
+      }
+
+      press(event) {
+        var key = this.keyAt(event);
+        if (!key) return;
+        this.pressed = key.pitch;
+        this.draw();
+        this.dispatchEvent(new CustomEvent("beamer-note", {detail: {pitch: key.pitch, on: true}, bubbles: true}));
+      }
+
+      release() {
+        if (this.pressed === undefined) return;
+        var pitch = this.pressed;
+        this.pressed = undefined;
+        this.draw();
+        this.dispatchEvent(new CustomEvent("beamer-note", {detail: {pitch: pitch, on: false}, bubbles: true}));
+      }
+    });
+  }
 })();
@@ -0,0 +1,363 @@
+//! Linux WebKitGTK implementation.
+//!
+//! The WebView lives in a `GtkPlug` created inside the X11 window the host
+//! passes to `IPlugView::attached()`. GTK needs its main loop to run, which
+//! no plugin host does for us: the format wrapper calls
+//! [`pump_events()`](LinuxWebView::pump_events) from the host's run loop
+//! timer instead.
+
+use std::ffi::{c_char, c_int, c_ulong, c_void, CStr, CString};
+
+use crate::assets::EmbeddedAssets;
+use crate::error::{Result, WebViewError};
+use crate::mime::mime_for_path;
+use crate::platform::linux_gtk::{
+    gtk, GdkRgba, Gtk, INJECT_AT_DOCUMENT_START, INJECT_TOP_FRAME, LOAD_FINISHED,
+};
+use crate::{FrameCallback, LoadedCallback, MessageCallback, WebViewConfig};
+
+/// Injected JavaScript runtime that creates `window.__BEAMER__`.
+///
+/// The same script as on macOS; WebKitGTK provides the same
+/// `window.webkit.messageHandlers` API.
+const BEAMER_RUNTIME_JS: &str = include_str!("beamer_runtime.js");
+
+/// Most GLib main loop iterations per [`LinuxWebView::pump_events()`]
+/// call, so a flood of events can't stall the host's UI thread.
+const MAX_ITERATIONS_PER_PUMP: usize = 64;
+
+/// Callbacks and context handed to the GTK signal handlers.
+struct Callbacks {
+    message: Option<MessageCallback>,
+    loaded: Option<LoadedCallback>,
+    context: *mut c_void,
+}
+
+/// Linux WebView backed by WebKitGTK.
+pub struct LinuxWebView {
+    gtk: &'static Gtk,
+    /// The `GtkPlug` embedded in the host's window. Null once detached.
+    plug: *mut c_void,
+    webview: *mut c_void,
+    content_manager: *mut c_void,
+    web_context: *mut c_void,
+    /// Signal handler ids on `content_manager` and `webview`.
+    message_handler: c_ulong,
+    load_handler: c_ulong,
+    /// Heap-allocated so signal handlers can hold a stable pointer.
+    callbacks: Box<Callbacks>,
+}
+
+impl LinuxWebView {
+    /// Attach a WebKitGTK view to the given parent X11 window.
+    ///
+    /// Fails if WebKitGTK is not installed.
+    ///
+    /// # Safety
+    ///
+    /// `parent` must be an X11 window id provided by the VST3 host (cast to
+    /// a pointer). Must be called from the host's UI thread.
+    pub unsafe fn attach_to_parent(parent: *mut c_void, config: &WebViewConfig<'_>) -> Result<Self> {
+        if parent.is_null() {
+            return Err(WebViewError::CreationFailed("null parent window".into()));
+        }
+        let gtk = gtk().map_err(WebViewError::CreationFailed)?;
+
+        // SAFETY: GTK is initialized and we are on its thread. Every object
+        // created here is owned by the returned view.
+        unsafe {
+            let web_context = (gtk.webkit_web_context_new)();
+            if let Some(assets) = config.assets {
+                (gtk.webkit_web_context_register_uri_scheme)(
+                    web_context,
+                    c"beamer".as_ptr(),
+                    serve_asset,
+                    assets as *const EmbeddedAssets as *mut c_void,
+                    std::ptr::null(),
+                );
+            }
+
+            let content_manager = (gtk.webkit_user_content_manager_new)();
+            let mut callbacks = Box::new(Callbacks {
+                message: config.message_callback,
+                loaded: config.loaded_callback,
+                context: config.callback_context,
+            });
+            let callbacks_ptr = &mut *callbacks as *mut Callbacks as *mut c_void;
+
+            let mut message_handler = 0;
+            if config.message_callback.is_some() {
+                let source = CString::new(BEAMER_RUNTIME_JS)
+                    .map_err(|_| WebViewError::CreationFailed("runtime script contains NUL".into()))?;
+                let script = (gtk.webkit_user_script_new)(
+                    source.as_ptr(),
+                    INJECT_TOP_FRAME,
+                    INJECT_AT_DOCUMENT_START,
+                    std::ptr::null(),
+                    std::ptr::null(),
+                );
+                (gtk.webkit_user_content_manager_add_script)(content_manager, script);
+                (gtk.webkit_user_script_unref)(script);
+
+                (gtk.webkit_user_content_manager_register_script_message_handler)(
+                    content_manager,
+                    c"beamer".as_ptr(),
+                );
+                message_handler = (gtk.g_signal_connect_data)(
+                    content_manager,
+                    c"script-message-received::beamer".as_ptr(),
+                    on_script_message as *const c_void,
+                    callbacks_ptr,
+                    std::ptr::null(),
+                    0,
+                );
+            }
+
+            let webview = (gtk.g_object_new)(
+                (gtk.webkit_web_view_get_type)(),
+                c"web-context".as_ptr(),
+                web_context,
+                c"user-content-manager".as_ptr(),
+                content_manager,
+                std::ptr::null::<c_char>(),
+            );
+            if webview.is_null() {
+                (gtk.g_object_unref)(content_manager);
+                (gtk.g_object_unref)(web_context);
+                return Err(WebViewError::CreationFailed("WebKitWebView creation failed".into()));
+            }
+
+            let mut load_handler = 0;
+            if config.loaded_callback.is_some() {
+                load_handler = (gtk.g_signal_connect_data)(
+                    webview,
+                    c"load-changed".as_ptr(),
+                    on_load_changed as *const c_void,
+                    callbacks_ptr,
+                    std::ptr::null(),
+                    0,
+                );
+            }
+
+            let settings = (gtk.webkit_web_view_get_settings)(webview);
+            (gtk.webkit_settings_set_enable_developer_extras)(settings, config.dev_tools as c_int);
+
+            // Transparent by default like the WKWebView, or the configured
+            // color while web content loads.
+            let [r, g, b, a] = config.background_color;
+            let color = GdkRgba {
+                red: r as f64 / 255.0,
+                green: g as f64 / 255.0,
+                blue: b as f64 / 255.0,
+                alpha: a as f64 / 255.0,
+            };
+            (gtk.webkit_web_view_set_background_color)(webview, &color);
+
+            // The X11 window id travels in the pointer.
+            let plug = (gtk.gtk_plug_new)(parent as c_ulong);
+            (gtk.gtk_container_add)(plug, webview);
+            (gtk.gtk_widget_show_all)(plug);
+
+            let this = Self {
+                gtk,
+                plug,
+                webview,
+                content_manager,
+                web_context,
+                message_handler,
+                load_handler,
+                callbacks,
+            };
+
+            let url = if let Some(url) = config.url {
+                // The main page is the dev server root; other views load
+                // their page relative to it.
+                if config.page == beamer_core::gui::EDITOR_PAGE {
+                    url.to_string()
+                } else {
+                    format!("{}/{}", url.trim_end_matches('/'), config.page)
+                }
+            } else if config.assets.is_some() {
+                format!("beamer://localhost/{}", config.page)
+            } else {
+                return Ok(this);
+            };
+            let url = CString::new(url)
+                .map_err(|_| WebViewError::CreationFailed("URL contains NUL".into()))?;
+            (gtk.webkit_web_view_load_uri)(this.webview, url.as_ptr());
+            Ok(this)
+        }
+    }
+
+    /// Update the WebView size. The position is fixed at the parent's
+    /// origin.
+    pub fn set_bounds(&self, _x: i32, _y: i32, width: i32, height: i32) {
+        if self.plug.is_null() {
+            return;
+        }
+        // SAFETY: plug and webview are live GTK widgets on this thread.
+        unsafe {
+            (self.gtk.gtk_widget_set_size_request)(self.webview, width.max(1), height.max(1));
+            (self.gtk.gtk_window_resize)(self.plug, width.max(1), height.max(1));
+        }
+    }
+
+    /// Evaluate JavaScript in the WebView.
+    ///
+    /// Must be called from the UI thread. Fire-and-forget (the result is
+    /// ignored).
+    pub fn evaluate_js(&self, script: &str) {
+        if self.plug.is_null() {
+            return;
+        }
+        let Ok(script) = CString::new(script) else {
+            log::warn!("script contains NUL; not evaluated");
+            return;
+        };
+        // SAFETY: webview is a live WebKitWebView on this thread.
+        unsafe {
+            (self.gtk.webkit_web_view_run_javascript)(
+                self.webview,
+                script.as_ptr(),
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null_mut(),
+            );
+        }
+    }
+
+    /// Run pending GTK and WebKit events. Called by the format wrapper from
+    /// the host's run loop timer, since hosts don't run a GLib main loop.
+    pub fn pump_events(&self) {
+        for _ in 0..MAX_ITERATIONS_PER_PUMP {
+            // SAFETY: GTK is initialized and we are on its thread.
+            if unsafe { (self.gtk.g_main_context_iteration)(std::ptr::null_mut(), 0) } == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Call `callback` once per display refresh. GTK's frame clock only
+    /// advances while events are pumped, so this always returns `false`
+    /// and callers fall back to their own timer.
+    ///
+    /// # Safety
+    ///
+    /// `context` must remain valid until the clock is stopped.
+    pub unsafe fn start_frame_clock(&self, _callback: FrameCallback, _context: *mut c_void) -> bool {
+        false
+    }
+
+    /// Stop the frame clock.
+    pub fn stop_frame_clock(&self) {}
+
+    /// Open the Web Inspector, if dev tools are enabled.
+    pub fn open_dev_tools(&self) {
+        if self.plug.is_null() {
+            return;
+        }
+        // SAFETY: webview is a live WebKitWebView on this thread.
+        unsafe {
+            let inspector = (self.gtk.webkit_web_view_get_inspector)(self.webview);
+            (self.gtk.webkit_web_inspector_show)(inspector);
+        }
+    }
+
+    /// Remove the WebView from its parent and clean up IPC handlers.
+    pub fn detach(&mut self) {
+        if self.plug.is_null() {
+            return;
+        }
+        // SAFETY: All objects are live and owned by this view; the signal
+        // handlers are disconnected before the callbacks are dropped.
+        unsafe {
+            if self.message_handler != 0 {
+                (self.gtk.g_signal_handler_disconnect)(self.content_manager, self.message_handler);
+            }
+            if self.load_handler != 0 {
+                (self.gtk.g_signal_handler_disconnect)(self.webview, self.load_handler);
+            }
+            // Destroying the plug destroys the WebView inside it.
+            (self.gtk.gtk_widget_destroy)(self.plug);
+            (self.gtk.g_object_unref)(self.content_manager);
+            (self.gtk.g_object_unref)(self.web_context);
+        }
+        self.plug = std::ptr::null_mut();
+        self.webview = std::ptr::null_mut();
+        self.message_handler = 0;
+        self.load_handler = 0;
+        self.callbacks.message = None;
+        self.callbacks.loaded = None;
+    }
+}
+
+impl Drop for LinuxWebView {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+/// `script-message-received::beamer` handler: forwards the JSON string.
+unsafe extern "C" fn on_script_message(_manager: *mut c_void, result: *mut c_void, data: *mut c_void) {
+    let Ok(gtk) = gtk() else { return };
+    // SAFETY: data is the view's Callbacks, alive while the handler is connected.
+    let callbacks = unsafe { &*(data as *const Callbacks) };
+    let Some(callback) = callbacks.message else { return };
+    // SAFETY: result is the signal's WebKitJavascriptResult; the string is
+    // freed with g_free.
+    unsafe {
+        let value = (gtk.webkit_javascript_result_get_js_value)(result);
+        let json = (gtk.jsc_value_to_string)(value);
+        if json.is_null() {
+            return;
+        }
+        let bytes = CStr::from_ptr(json).to_bytes();
+        callback(callbacks.context, bytes.as_ptr(), bytes.len());
+        (gtk.g_free)(json.cast());
+    }
+}
+
+/// `load-changed` handler: reports finished loads.
+unsafe extern "C" fn on_load_changed(_webview: *mut c_void, event: c_int, data: *mut c_void) {
+    if event != LOAD_FINISHED {
+        return;
+    }
+    // SAFETY: data is the view's Callbacks, alive while the handler is connected.
+    let callbacks = unsafe { &*(data as *const Callbacks) };
+    if let Some(loaded) = callbacks.loaded {
+        // SAFETY: loaded and context are valid per the config's contract.
+        unsafe { loaded(callbacks.context) };
+    }
+}
+
+/// `beamer://` scheme handler: serves embedded assets.
+unsafe extern "C" fn serve_asset(request: *mut c_void, data: *mut c_void) {
+    let Ok(gtk) = gtk() else { return };
+    // SAFETY: data is the &'static EmbeddedAssets registered with the scheme.
+    let assets = unsafe { &*(data as *const EmbeddedAssets) };
+    // SAFETY: request is the scheme request; the path is owned by it.
+    let path = unsafe {
+        let path = (gtk.webkit_uri_scheme_request_get_path)(request);
+        if path.is_null() { String::new() } else { CStr::from_ptr(path).to_string_lossy().into_owned() }
+    };
+    let path = path.strip_prefix('/').unwrap_or(&path);
+    let path = if path.is_empty() { "index.html" } else { path };
+
+    // WebKitGTK's 4.0/4.1 API can't set a status code, so a missing asset
+    // is a plain-text "Not Found" body.
+    let (body, mime): (&'static [u8], _) = match assets.get(path) {
+        Some(data) => (data, mime_for_path(path)),
+        None => {
+            log::warn!("asset not found: {path}");
+            (b"Not Found", "text/plain")
+        }
+    };
+    let mime = CString::new(mime).unwrap_or_default();
+    // SAFETY: body is 'static, so the stream may reference it without a
+    // destroy notify.
+    unsafe {
+        let stream = (gtk.g_memory_input_stream_new_from_data)(body.as_ptr().cast(), body.len() as isize, std::ptr::null());
+        (gtk.webkit_uri_scheme_request_finish)(request, stream, body.len() as i64, mime.as_ptr());
+        (gtk.g_object_unref)(stream);
+    }
+}
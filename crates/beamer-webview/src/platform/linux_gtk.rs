@@ -0,0 +1,143 @@
+//! WebKitGTK, GTK and GLib functions, loaded at runtime.
+//!
+//! Linking WebKitGTK would make every plugin binary depend on it, even for
+//! hosts that never open the editor, and break loading on systems without
+//! it. The library is opened with `dlopen()` the first time an editor is
+//! created instead; a missing library fails that editor only.
+
+use std::ffi::{c_char, c_int, c_uint, c_ulong, c_void, CStr};
+use std::sync::OnceLock;
+
+/// WebKitGTK sonames to try, newest ABI first. Both expose the functions
+/// below with the same signatures.
+const LIBRARIES: [&CStr; 2] = [c"libwebkit2gtk-4.1.so.0", c"libwebkit2gtk-4.0.so.37"];
+
+const RTLD_NOW: c_int = 2;
+const RTLD_GLOBAL: c_int = 0x100;
+
+#[link(name = "dl")]
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+pub type GBoolean = c_int;
+pub type GCallback = *const c_void;
+
+/// `GdkRGBA`
+#[repr(C)]
+pub struct GdkRgba {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+    pub alpha: f64,
+}
+
+/// `WebKitURISchemeRequestCallback`
+pub type UriSchemeCallback = unsafe extern "C" fn(request: *mut c_void, user_data: *mut c_void);
+
+/// `WEBKIT_LOAD_FINISHED`
+pub const LOAD_FINISHED: c_int = 3;
+/// `WEBKIT_USER_CONTENT_INJECT_TOP_FRAME`
+pub const INJECT_TOP_FRAME: c_int = 1;
+/// `WEBKIT_USER_SCRIPT_INJECT_AT_DOCUMENT_START`
+pub const INJECT_AT_DOCUMENT_START: c_int = 0;
+
+macro_rules! functions {
+    ($($name:ident: fn $args:tt $(-> $ret:ty)?;)*) => {
+        /// The loaded functions.
+        pub struct Gtk {
+            $(pub $name: unsafe extern "C" fn $args $(-> $ret)?,)*
+        }
+
+        impl Gtk {
+            /// Resolve every function from `handle`, or name the first
+            /// missing one.
+            unsafe fn resolve(handle: *mut c_void) -> Result<Self, String> {
+                Ok(Self {
+                    $($name: {
+                        let name = concat!(stringify!($name), "\0");
+                        // SAFETY: handle is a dlopen() handle; name is
+                        // null-terminated.
+                        let symbol = unsafe { dlsym(handle, name.as_ptr().cast()) };
+                        if symbol.is_null() {
+                            return Err(format!("missing symbol {}", stringify!($name)));
+                        }
+                        // SAFETY: The symbol has the declared C signature.
+                        unsafe {
+                            std::mem::transmute::<*mut c_void, unsafe extern "C" fn $args $(-> $ret)?>(symbol)
+                        }
+                    },)*
+                })
+            }
+        }
+    };
+}
+
+functions! {
+    gdk_set_allowed_backends: fn(*const c_char);
+    gtk_init_check: fn(*mut c_int, *mut *mut *mut c_char) -> GBoolean;
+    gtk_plug_new: fn(c_ulong) -> *mut c_void;
+    gtk_container_add: fn(*mut c_void, *mut c_void);
+    gtk_widget_show_all: fn(*mut c_void);
+    gtk_widget_set_size_request: fn(*mut c_void, c_int, c_int);
+    gtk_window_resize: fn(*mut c_void, c_int, c_int);
+    gtk_widget_destroy: fn(*mut c_void);
+    g_main_context_iteration: fn(*mut c_void, GBoolean) -> GBoolean;
+    g_object_new: fn(usize, *const c_char, ...) -> *mut c_void;
+    g_object_unref: fn(*mut c_void);
+    g_signal_connect_data: fn(*mut c_void, *const c_char, GCallback, *mut c_void, *const c_void, c_uint) -> c_ulong;
+    g_signal_handler_disconnect: fn(*mut c_void, c_ulong);
+    g_free: fn(*mut c_void);
+    g_memory_input_stream_new_from_data: fn(*const c_void, isize, *const c_void) -> *mut c_void;
+    webkit_web_view_get_type: fn() -> usize;
+    webkit_web_context_new: fn() -> *mut c_void;
+    webkit_web_context_register_uri_scheme: fn(*mut c_void, *const c_char, UriSchemeCallback, *mut c_void, *const c_void);
+    webkit_uri_scheme_request_get_path: fn(*mut c_void) -> *const c_char;
+    webkit_uri_scheme_request_finish: fn(*mut c_void, *mut c_void, i64, *const c_char);
+    webkit_user_content_manager_new: fn() -> *mut c_void;
+    webkit_user_content_manager_register_script_message_handler: fn(*mut c_void, *const c_char) -> GBoolean;
+    webkit_user_content_manager_add_script: fn(*mut c_void, *mut c_void);
+    webkit_user_script_new: fn(*const c_char, c_int, c_int, *const *const c_char, *const *const c_char) -> *mut c_void;
+    webkit_user_script_unref: fn(*mut c_void);
+    webkit_javascript_result_get_js_value: fn(*mut c_void) -> *mut c_void;
+    jsc_value_to_string: fn(*mut c_void) -> *mut c_char;
+    webkit_web_view_get_settings: fn(*mut c_void) -> *mut c_void;
+    webkit_settings_set_enable_developer_extras: fn(*mut c_void, GBoolean);
+    webkit_web_view_set_background_color: fn(*mut c_void, *const GdkRgba);
+    webkit_web_view_load_uri: fn(*mut c_void, *const c_char);
+    webkit_web_view_run_javascript: fn(*mut c_void, *const c_char, *mut c_void, *const c_void, *mut c_void);
+    webkit_web_view_get_inspector: fn(*mut c_void) -> *mut c_void;
+    webkit_web_inspector_show: fn(*mut c_void);
+}
+
+/// The functions, loaded and GTK initialized on first use.
+///
+/// Must be called from the host's UI thread, which then becomes GTK's
+/// thread.
+pub fn gtk() -> Result<&'static Gtk, String> {
+    static GTK: OnceLock<Result<Gtk, String>> = OnceLock::new();
+    GTK.get_or_init(|| {
+        let handle = LIBRARIES
+            .iter()
+            // SAFETY: Plain dlopen() of a system library.
+            .map(|name| unsafe { dlopen(name.as_ptr(), RTLD_NOW | RTLD_GLOBAL) })
+            .find(|handle| !handle.is_null())
+            .ok_or_else(|| "WebKitGTK (libwebkit2gtk-4.1 or -4.0) not found".to_string())?;
+        // SAFETY: handle is a valid dlopen() handle, and GTK and GLib are
+        // loaded as WebKitGTK's dependencies.
+        let gtk = unsafe { Gtk::resolve(handle)? };
+        // VST3 editors embed into X11 windows, so GTK must use its X11
+        // backend even in a Wayland session (hosts run under XWayland).
+        // SAFETY: Called before GTK is initialized, on the UI thread.
+        unsafe {
+            (gtk.gdk_set_allowed_backends)(c"x11".as_ptr());
+            if (gtk.gtk_init_check)(std::ptr::null_mut(), std::ptr::null_mut()) == 0 {
+                return Err("GTK could not be initialized (no X11 display?)".to_string());
+            }
+        }
+        Ok(gtk)
+    })
+    .as_ref()
+    .map_err(Clone::clone)
+}
@@ -1,5 +1,6 @@
 //! macOS WKWebView implementation.
 
+use std::cell::RefCell;
 use std::ffi::c_void;
 
 use objc2::encode::{Encoding, RefEncode};
@@ -20,8 +21,9 @@ use objc2_foundation::{NSNumber, NSString, NSURL, NSURLRequest};
 use objc2_web_kit::{WKURLSchemeHandler, WKWebView, WKWebViewConfiguration};
 
 use crate::error::{Result, WebViewError};
+use crate::platform::macos_frame::FrameClock;
 use crate::platform::macos_scheme::new_scheme_handler;
-use crate::WebViewConfig;
+use crate::{FrameCallback, WebViewConfig};
 
 /// Injected JavaScript runtime that creates `window.__BEAMER__`.
 ///
@@ -37,6 +39,8 @@ pub struct MacosWebView {
     _nav_delegate: Option<Retained<AnyObject>>,
     /// Retained reference to the message handler to prevent deallocation.
     _msg_handler: Option<Retained<AnyObject>>,
+    /// Display link driving `__BEAMER__.onFrame()`. None while stopped.
+    frame_clock: RefCell<Option<FrameClock>>,
 }
 
 impl MacosWebView {
@@ -195,7 +199,14 @@ impl MacosWebView {
         let _: () = unsafe { objc2::msg_send![&webview, setValue: &*value, forKey: &*key] };
 
         if let Some(url) = config.url {
-            let url_str = NSString::from_str(url);
+            // The main page is the dev server root; other views load their
+            // page relative to it.
+            let url = if config.page == beamer_core::gui::EDITOR_PAGE {
+                url.to_string()
+            } else {
+                format!("{}/{}", url.trim_end_matches('/'), config.page)
+            };
+            let url_str = NSString::from_str(&url);
             let nsurl = NSURL::URLWithString(&url_str).ok_or_else(|| {
                 WebViewError::CreationFailed(format!("invalid dev server URL: {url}"))
             })?;
@@ -203,7 +214,7 @@ impl MacosWebView {
             // SAFETY: webview and request are valid; we are on the main thread.
             unsafe { webview.loadRequest(&request) };
         } else if config.assets.is_some() {
-            let url_str = NSString::from_str("beamer://localhost/index.html");
+            let url_str = NSString::from_str(&format!("beamer://localhost/{}", config.page));
             let nsurl = NSURL::URLWithString(&url_str).ok_or_else(|| {
                 WebViewError::CreationFailed("failed to create scheme URL".into())
             })?;
@@ -218,6 +229,7 @@ impl MacosWebView {
             webview,
             _nav_delegate: nav_delegate_retained,
             _msg_handler: msg_handler_retained,
+            frame_clock: RefCell::new(None),
         })
     }
 
@@ -242,8 +254,34 @@ impl MacosWebView {
         }
     }
 
+    /// Call `callback` once per display refresh, replacing a running clock.
+    ///
+    /// Returns `false` if no display link could be created, in which case
+    /// the caller should fall back to its own timer.
+    ///
+    /// # Safety
+    ///
+    /// `context` must remain valid until [`stop_frame_clock()`](Self::stop_frame_clock)
+    /// or [`detach()`](Self::detach). Must be called from the main thread.
+    pub unsafe fn start_frame_clock(&self, callback: FrameCallback, context: *mut c_void) -> bool {
+        self.stop_frame_clock();
+        // SAFETY: forwarded from the caller's contract.
+        let clock = unsafe { FrameClock::start(callback, context) };
+        let started = clock.is_some();
+        *self.frame_clock.borrow_mut() = clock;
+        started
+    }
+
+    /// Stop the frame clock. No frame callback runs after this returns.
+    ///
+    /// Must be called from the main thread.
+    pub fn stop_frame_clock(&self) {
+        self.frame_clock.borrow_mut().take();
+    }
+
     /// Remove the WebView from its parent and clean up IPC handlers.
     pub fn detach(&mut self) {
+        self.stop_frame_clock();
         // SAFETY: Remove message handler and user scripts to break retain cycles.
         // webview and its configuration are valid on the main thread.
         unsafe {
@@ -0,0 +1,197 @@
+//! Display-synchronized frame clock backed by `CVDisplayLink`.
+//!
+//! The display link fires on a CoreVideo thread once per screen refresh.
+//! Its callback only records the time and dispatches to the main queue,
+//! where the per-instance [`FrameCallback`] runs alongside every other
+//! WebView call. A `pending` flag drops refreshes while one is still
+//! queued, so a blocked main thread catches up with a single frame instead
+//! of a burst.
+
+use std::ffi::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::FrameCallback;
+
+type CVDisplayLinkRef = *mut c_void;
+type CVReturn = i32;
+type CVOptionFlags = u64;
+type CVDisplayLinkOutputCallback = unsafe extern "C" fn(
+    link: CVDisplayLinkRef,
+    in_now: *const c_void,
+    in_output_time: *const c_void,
+    flags_in: CVOptionFlags,
+    flags_out: *mut CVOptionFlags,
+    context: *mut c_void,
+) -> CVReturn;
+
+const CV_RETURN_SUCCESS: CVReturn = 0;
+
+#[link(name = "CoreVideo", kind = "framework")]
+extern "C" {
+    fn CVDisplayLinkCreateWithActiveCGDisplays(link_out: *mut CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkSetOutputCallback(
+        link: CVDisplayLinkRef,
+        callback: CVDisplayLinkOutputCallback,
+        context: *mut c_void,
+    ) -> CVReturn;
+    fn CVDisplayLinkStart(link: CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkStop(link: CVDisplayLinkRef) -> CVReturn;
+    fn CVDisplayLinkRelease(link: CVDisplayLinkRef);
+}
+
+// libdispatch is part of libSystem, which every binary links.
+extern "C" {
+    #[allow(non_upper_case_globals)]
+    static _dispatch_main_q: c_void;
+    fn dispatch_async_f(
+        queue: *const c_void,
+        context: *mut c_void,
+        work: unsafe extern "C" fn(context: *mut c_void),
+    );
+}
+
+/// State shared between the display link thread and the main queue.
+struct Shared {
+    callback: FrameCallback,
+    context: *mut c_void,
+    start: Instant,
+    /// Time of the latest refresh in milliseconds, stored as f64 bits.
+    time_bits: AtomicU64,
+    /// A main-queue dispatch is queued and has not run yet.
+    pending: AtomicBool,
+    /// Cleared when the clock stops; queued dispatches then do nothing.
+    running: AtomicBool,
+}
+
+// SAFETY: `context` is only passed to `callback` on the main thread while
+// `running` is set, and the clock is stopped on the main thread before the
+// owner frees the context.
+unsafe impl Send for Shared {}
+// SAFETY: All mutable state is atomic; see Send above for `context`.
+unsafe impl Sync for Shared {}
+
+/// A running display link that calls a [`FrameCallback`] on the main thread.
+///
+/// Stops when dropped.
+pub struct FrameClock {
+    link: CVDisplayLinkRef,
+    /// `Arc::into_raw` of the shared state, owned by the display link.
+    shared: *const Shared,
+}
+
+impl FrameClock {
+    /// Start a clock for the active displays.
+    ///
+    /// Returns `None` if no display link could be created (for example in
+    /// a headless session).
+    ///
+    /// # Safety
+    ///
+    /// - `context` must remain valid until the clock is dropped
+    /// - Must be called from the main thread, and the clock must be dropped
+    ///   on the main thread
+    pub unsafe fn start(callback: FrameCallback, context: *mut c_void) -> Option<Self> {
+        let mut link: CVDisplayLinkRef = std::ptr::null_mut();
+        // SAFETY: link is a valid out pointer.
+        let status = unsafe { CVDisplayLinkCreateWithActiveCGDisplays(&mut link) };
+        if status != CV_RETURN_SUCCESS || link.is_null() {
+            log::warn!("Failed to create display link for the frame clock: {status}");
+            return None;
+        }
+
+        let shared = Arc::into_raw(Arc::new(Shared {
+            callback,
+            context,
+            start: Instant::now(),
+            time_bits: AtomicU64::new(0),
+            pending: AtomicBool::new(false),
+            running: AtomicBool::new(true),
+        }));
+
+        // SAFETY: link was created above; shared stays alive until drop,
+        // after the link has stopped.
+        let started = unsafe {
+            CVDisplayLinkSetOutputCallback(link, display_link_fired, shared as *mut c_void)
+                == CV_RETURN_SUCCESS
+                && CVDisplayLinkStart(link) == CV_RETURN_SUCCESS
+        };
+        if !started {
+            log::warn!("Failed to start display link for the frame clock");
+            // SAFETY: the link never ran, so nothing else references shared.
+            unsafe {
+                CVDisplayLinkRelease(link);
+                drop(Arc::from_raw(shared));
+            }
+            return None;
+        }
+
+        Some(Self { link, shared })
+    }
+}
+
+impl Drop for FrameClock {
+    fn drop(&mut self) {
+        // SAFETY: shared is the pointer from Arc::into_raw in start().
+        // CVDisplayLinkStop returns after an in-progress output callback
+        // has finished, so the link holds no reference afterwards. Queued
+        // main-queue dispatches own their own reference and see `running`
+        // cleared.
+        unsafe {
+            (*self.shared).running.store(false, Ordering::Release);
+            CVDisplayLinkStop(self.link);
+            CVDisplayLinkRelease(self.link);
+            drop(Arc::from_raw(self.shared));
+        }
+    }
+}
+
+/// CVDisplayLink output callback (CoreVideo thread).
+unsafe extern "C" fn display_link_fired(
+    _link: CVDisplayLinkRef,
+    _in_now: *const c_void,
+    _in_output_time: *const c_void,
+    _flags_in: CVOptionFlags,
+    _flags_out: *mut CVOptionFlags,
+    context: *mut c_void,
+) -> CVReturn {
+    // SAFETY: context is the Arc pointer installed in start(), kept alive
+    // until the link has stopped.
+    let shared = unsafe { &*(context as *const Shared) };
+    let time_ms = shared.start.elapsed().as_secs_f64() * 1000.0;
+    shared.time_bits.store(time_ms.to_bits(), Ordering::Relaxed);
+
+    if shared.running.load(Ordering::Acquire) && !shared.pending.swap(true, Ordering::AcqRel) {
+        // SAFETY: the queued block owns one reference, released in
+        // frame_on_main_thread(). The main queue is always valid.
+        unsafe {
+            Arc::increment_strong_count(context as *const Shared);
+            dispatch_async_f(
+                std::ptr::addr_of!(_dispatch_main_q),
+                context,
+                frame_on_main_thread,
+            );
+        }
+    }
+    CV_RETURN_SUCCESS
+}
+
+/// Main-queue half of a refresh.
+unsafe extern "C" fn frame_on_main_thread(context: *mut c_void) {
+    // SAFETY: takes over the reference added in display_link_fired().
+    let shared = unsafe { Arc::from_raw(context as *const Shared) };
+    shared.pending.store(false, Ordering::Release);
+    if !shared.running.load(Ordering::Acquire) {
+        return;
+    }
+
+    let time_ms = f64::from_bits(shared.time_bits.load(Ordering::Relaxed));
+    // A panic must not unwind into libdispatch.
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: running is set, so the owner has not dropped the clock and
+        // the context is still valid (both only change on this thread).
+        unsafe { (shared.callback)(shared.context, time_ms) };
+    }));
+}
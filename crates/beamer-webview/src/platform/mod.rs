@@ -9,11 +9,26 @@ pub mod macos_scheme;
 #[cfg(target_os = "macos")]
 pub mod macos_ipc;
 
+#[cfg(target_os = "macos")]
+pub mod macos_frame;
+
 #[cfg(target_os = "windows")]
 pub mod windows;
 
+#[cfg(target_os = "windows")]
+pub mod windows_scheme;
+
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub mod linux_gtk;
+
 #[cfg(target_os = "macos")]
 pub use macos::MacosWebView as PlatformWebView;
 
 #[cfg(target_os = "windows")]
 pub use windows::WindowsWebView as PlatformWebView;
+
+#[cfg(target_os = "linux")]
+pub use linux::LinuxWebView as PlatformWebView;
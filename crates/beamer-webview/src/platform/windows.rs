@@ -1,29 +1,325 @@
 //! Windows WebView2 implementation.
 
+use std::ffi::c_void;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use webview2_com::Microsoft::Web::WebView2::Win32::{
+    CreateCoreWebView2EnvironmentWithOptions, ICoreWebView2, ICoreWebView2Controller,
+    ICoreWebView2Controller2, ICoreWebView2Environment, ICoreWebView2EnvironmentOptions,
+    COREWEBVIEW2_COLOR,
+};
+use webview2_com::{
+    take_pwstr, AddScriptToExecuteOnDocumentCreatedCompletedHandler, CoreWebView2EnvironmentOptions,
+    CreateCoreWebView2ControllerCompletedHandler, CreateCoreWebView2EnvironmentCompletedHandler,
+    ExecuteScriptCompletedHandler, NavigationCompletedEventHandler, WebMessageReceivedEventHandler,
+};
+use windows::core::{Interface, HSTRING, PCWSTR, PWSTR};
+use windows::Win32::Foundation::{E_POINTER, HWND, RECT};
+use windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED};
+use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
 use crate::error::{Result, WebViewError};
-use crate::WebViewConfig;
+use crate::platform::windows_scheme::{add_scheme_handler, scheme_registration};
+use crate::{FrameCallback, WebViewConfig};
+
+/// Injected JavaScript runtime that creates `window.__BEAMER__`.
+///
+/// The same script as on macOS; it posts through `window.chrome.webview`
+/// when WebKit's message handlers are missing.
+const BEAMER_RUNTIME_JS: &str = include_str!("beamer_runtime.js");
 
 /// Windows WebView backed by WebView2.
 pub struct WindowsWebView {
-    _private: (),
+    controller: ICoreWebView2Controller,
+    webview: ICoreWebView2,
+    /// Event registration tokens, removed on detach.
+    message_token: Option<i64>,
+    navigation_token: Option<i64>,
+    resource_token: Option<i64>,
+}
+
+/// Map a WebView2 error to a creation error.
+fn creation_failed(what: &str, error: impl std::fmt::Debug) -> WebViewError {
+    WebViewError::CreationFailed(format!("{what}: {error:?}"))
+}
+
+/// Folder for the WebView2 user data (cache, local storage).
+///
+/// The default is next to the host executable, which is usually not
+/// writable, so every Beamer plugin shares one folder under
+/// `%LOCALAPPDATA%`. All of them create their environment with the same
+/// options, which WebView2 requires for a shared folder.
+fn user_data_folder() -> Option<PathBuf> {
+    std::env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("Beamer").join("WebView2"))
+}
+
+/// Create the WebView2 environment, pumping messages until it exists.
+fn create_environment() -> Result<ICoreWebView2Environment> {
+    let options = CoreWebView2EnvironmentOptions::default();
+    // SAFETY: Setter on a freshly created options object.
+    unsafe { options.set_scheme_registrations(vec![Some(scheme_registration())]) };
+    let options = ICoreWebView2EnvironmentOptions::from(options);
+    let user_data = user_data_folder().map(|dir| HSTRING::from(dir.as_os_str()));
+
+    let (tx, rx) = mpsc::channel();
+    CreateCoreWebView2EnvironmentCompletedHandler::wait_for_async_operation(
+        Box::new(move |handler| {
+            let user_data = user_data.as_ref().map_or(PCWSTR::null(), |dir| PCWSTR(dir.as_ptr()));
+            // SAFETY: All pointers are valid for the duration of the call.
+            unsafe { CreateCoreWebView2EnvironmentWithOptions(PCWSTR::null(), user_data, &options, &handler) }
+                .map_err(webview2_com::Error::WindowsError)
+        }),
+        Box::new(move |result, environment| {
+            result?;
+            let _ = tx.send(environment.ok_or_else(|| windows::core::Error::from(E_POINTER)));
+            Ok(())
+        }),
+    )
+    .map_err(|e| creation_failed("WebView2 environment", e))?;
+
+    rx.recv()
+        .map_err(|e| creation_failed("WebView2 environment", e))?
+        .map_err(|e| creation_failed("WebView2 environment", e))
+}
+
+/// Create a controller hosted in `parent`, pumping messages until it exists.
+fn create_controller(environment: &ICoreWebView2Environment, parent: HWND) -> Result<ICoreWebView2Controller> {
+    let environment = environment.clone();
+    let (tx, rx) = mpsc::channel();
+    CreateCoreWebView2ControllerCompletedHandler::wait_for_async_operation(
+        Box::new(move |handler| {
+            // SAFETY: parent is a valid HWND per the caller's contract.
+            unsafe { environment.CreateCoreWebView2Controller(parent, &handler) }
+                .map_err(webview2_com::Error::WindowsError)
+        }),
+        Box::new(move |result, controller| {
+            result?;
+            let _ = tx.send(controller.ok_or_else(|| windows::core::Error::from(E_POINTER)));
+            Ok(())
+        }),
+    )
+    .map_err(|e| creation_failed("WebView2 controller", e))?;
+
+    rx.recv()
+        .map_err(|e| creation_failed("WebView2 controller", e))?
+        .map_err(|e| creation_failed("WebView2 controller", e))
 }
 
 impl WindowsWebView {
     /// Attach a WebView2 to the given parent HWND.
     ///
+    /// WebView2 creates its environment and controller asynchronously; this
+    /// pumps the thread's message queue until both exist, so the host may
+    /// see messages dispatched from inside `attached()`.
+    ///
     /// # Safety
     ///
     /// `parent` must be a valid `HWND` provided by the VST3 host.
-    pub unsafe fn attach_to_parent(
-        _parent: *mut std::ffi::c_void,
-        _config: &WebViewConfig<'_>,
-    ) -> Result<Self> {
-        Err(WebViewError::PlatformNotSupported)
+    /// Must be called from the thread that owns `parent`.
+    pub unsafe fn attach_to_parent(parent: *mut c_void, config: &WebViewConfig<'_>) -> Result<Self> {
+        if parent.is_null() {
+            return Err(WebViewError::CreationFailed("null parent window".into()));
+        }
+        let parent = HWND(parent);
+
+        // WebView2 needs a single-threaded apartment. Hosts initialize COM
+        // on their UI thread already, in which case this is a no-op.
+        // SAFETY: Plain COM initialization on the current thread.
+        let _ = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+
+        let environment = create_environment()?;
+        let controller = create_controller(&environment, parent)?;
+        // SAFETY: controller is valid; we are on its thread.
+        let webview = unsafe { controller.CoreWebView2() }.map_err(|e| creation_failed("CoreWebView2", e))?;
+
+        let mut this = Self {
+            controller,
+            webview,
+            message_token: None,
+            navigation_token: None,
+            resource_token: None,
+        };
+        // SAFETY: Forwarded from the caller's contract.
+        if let Err(e) = unsafe { this.configure(parent, &environment, config) } {
+            this.detach();
+            return Err(e);
+        }
+        Ok(this)
     }
 
-    /// Update the WebView bounds.
-    pub fn set_bounds(&self, _x: i32, _y: i32, _width: i32, _height: i32) {}
+    /// Settings, IPC, scheme handler, bounds and initial navigation.
+    unsafe fn configure(
+        &mut self,
+        parent: HWND,
+        environment: &ICoreWebView2Environment,
+        config: &WebViewConfig<'_>,
+    ) -> Result<()> {
+        let webview = self.webview.clone();
+
+        // SAFETY: webview is valid; we are on its thread.
+        unsafe {
+            let settings = webview.Settings().map_err(|e| creation_failed("settings", e))?;
+            // Dev tools open with F12 or from the context menu, which is
+            // only shown alongside them.
+            let _ = settings.SetAreDevToolsEnabled(config.dev_tools);
+            let _ = settings.SetAreDefaultContextMenusEnabled(config.dev_tools);
+            let _ = settings.SetIsStatusBarEnabled(false);
+            let _ = settings.SetIsZoomControlEnabled(false);
+        }
+
+        // Background shown while web content loads. WebView2 only supports
+        // opaque or fully transparent backgrounds.
+        let [r, g, b, a] = config.background_color;
+        if r != 0 || g != 0 || b != 0 || a != 0 {
+            if let Ok(controller2) = self.controller.cast::<ICoreWebView2Controller2>() {
+                let color = COREWEBVIEW2_COLOR { A: if a == 0 { 0 } else { 255 }, R: r, G: g, B: b };
+                // SAFETY: controller2 is valid; we are on its thread.
+                let _ = unsafe { controller2.SetDefaultBackgroundColor(color) };
+            }
+        }
+
+        if let Some(assets) = config.assets {
+            // SAFETY: webview and environment are valid; we are on their thread.
+            let token = unsafe { add_scheme_handler(&webview, environment, assets) }
+                .map_err(|e| creation_failed("scheme handler", e))?;
+            self.resource_token = Some(token);
+        }
 
-    /// Remove the WebView from its parent.
-    pub fn detach(&mut self) {}
+        // Inject the __BEAMER__ runtime script at document start.
+        if let Some(callback) = config.message_callback {
+            let script_webview = webview.clone();
+            AddScriptToExecuteOnDocumentCreatedCompletedHandler::wait_for_async_operation(
+                Box::new(move |handler| {
+                    // SAFETY: script_webview is valid; we are on its thread.
+                    unsafe {
+                        script_webview.AddScriptToExecuteOnDocumentCreated(&HSTRING::from(BEAMER_RUNTIME_JS), &handler)
+                    }
+                    .map_err(webview2_com::Error::WindowsError)
+                }),
+                Box::new(|result, _id| result),
+            )
+            .map_err(|e| creation_failed("runtime script", e))?;
+
+            let context = config.callback_context as usize;
+            let handler = WebMessageReceivedEventHandler::create(Box::new(move |_, args| {
+                let Some(args) = args else { return Ok(()) };
+                let mut message = PWSTR::null();
+                // SAFETY: args is valid for the duration of the event; the
+                // runtime posts strings, so TryGetWebMessageAsString succeeds.
+                let json = unsafe {
+                    args.TryGetWebMessageAsString(&mut message)?;
+                    take_pwstr(message)
+                };
+                // SAFETY: callback and context are valid per the config's contract.
+                unsafe { callback(context as *mut c_void, json.as_ptr(), json.len()) };
+                Ok(())
+            }));
+            let mut token = 0;
+            // SAFETY: webview and handler are valid; we are on its thread.
+            unsafe { webview.add_WebMessageReceived(&handler, &mut token) }
+                .map_err(|e| creation_failed("message handler", e))?;
+            self.message_token = Some(token);
+        }
+
+        if let Some(loaded) = config.loaded_callback {
+            let context = config.callback_context as usize;
+            let handler = NavigationCompletedEventHandler::create(Box::new(move |_, _| {
+                // SAFETY: loaded and context are valid per the config's contract.
+                unsafe { loaded(context as *mut c_void) };
+                Ok(())
+            }));
+            let mut token = 0;
+            // SAFETY: webview and handler are valid; we are on its thread.
+            unsafe { webview.add_NavigationCompleted(&handler, &mut token) }
+                .map_err(|e| creation_failed("navigation handler", e))?;
+            self.navigation_token = Some(token);
+        }
+
+        let mut rect = RECT::default();
+        // SAFETY: parent is a valid HWND per the caller's contract.
+        if unsafe { GetClientRect(parent, &mut rect) }.is_ok() {
+            self.set_bounds(0, 0, rect.right - rect.left, rect.bottom - rect.top);
+        }
+        // SAFETY: controller is valid; we are on its thread.
+        let _ = unsafe { self.controller.SetIsVisible(true) };
+
+        let url = if let Some(url) = config.url {
+            // The main page is the dev server root; other views load their
+            // page relative to it.
+            if config.page == beamer_core::gui::EDITOR_PAGE {
+                url.to_string()
+            } else {
+                format!("{}/{}", url.trim_end_matches('/'), config.page)
+            }
+        } else if config.assets.is_some() {
+            format!("beamer://localhost/{}", config.page)
+        } else {
+            return Ok(());
+        };
+        // SAFETY: webview is valid; we are on its thread.
+        unsafe { webview.Navigate(&HSTRING::from(url.as_str())) }
+            .map_err(|e| creation_failed(&format!("navigation to {url}"), e))
+    }
+
+    /// Update the WebView bounds, in pixels relative to the parent window.
+    pub fn set_bounds(&self, x: i32, y: i32, width: i32, height: i32) {
+        let bounds = RECT { left: x, top: y, right: x + width.max(0), bottom: y + height.max(0) };
+        // SAFETY: controller is valid; we are on its thread.
+        if let Err(e) = unsafe { self.controller.SetBounds(bounds) } {
+            log::warn!("failed to resize WebView2: {e:?}");
+        }
+    }
+
+    /// Evaluate JavaScript in the WebView.
+    ///
+    /// Must be called from the thread that created the WebView.
+    /// Fire-and-forget (the result is ignored).
+    pub fn evaluate_js(&self, script: &str) {
+        let handler = ExecuteScriptCompletedHandler::create(Box::new(|_, _| Ok(())));
+        // SAFETY: webview is valid; we are on its thread.
+        if let Err(e) = unsafe { self.webview.ExecuteScript(&HSTRING::from(script), &handler) } {
+            log::debug!("ExecuteScript failed: {e:?}");
+        }
+    }
+
+    /// Call `callback` once per display refresh. WebView2 offers no display
+    /// link to hosted windows, so this always returns `false` and callers
+    /// fall back to their own timer.
+    ///
+    /// # Safety
+    ///
+    /// `context` must remain valid until the clock is stopped.
+    pub unsafe fn start_frame_clock(&self, _callback: FrameCallback, _context: *mut c_void) -> bool {
+        false
+    }
+
+    /// Stop the frame clock.
+    pub fn stop_frame_clock(&self) {}
+
+    /// Open the dev tools window, if dev tools are enabled.
+    pub fn open_dev_tools(&self) {
+        // SAFETY: webview is valid; we are on its thread.
+        let _ = unsafe { self.webview.OpenDevToolsWindow() };
+    }
+
+    /// Remove the WebView from its parent and clean up IPC handlers.
+    pub fn detach(&mut self) {
+        // SAFETY: webview and controller are valid; we are on their thread.
+        // Removing the handlers drops their closures, so no callback runs
+        // with a stale context afterwards.
+        unsafe {
+            if let Some(token) = self.message_token.take() {
+                let _ = self.webview.remove_WebMessageReceived(token);
+            }
+            if let Some(token) = self.navigation_token.take() {
+                let _ = self.webview.remove_NavigationCompleted(token);
+            }
+            if let Some(token) = self.resource_token.take() {
+                let _ = self.webview.remove_WebResourceRequested(token);
+            }
+            let _ = self.controller.SetIsVisible(false);
+            let _ = self.controller.Close();
+        }
+    }
 }
@@ -0,0 +1,125 @@
+//! `beamer://` resource handler for WebView2.
+//!
+//! WebView2 has no per-scheme handler object like WKWebView. The scheme is
+//! registered on the environment options, and requests for it arrive as
+//! `WebResourceRequested` events once a filter for `beamer://*` is added.
+
+use webview2_com::Microsoft::Web::WebView2::Win32::{
+    ICoreWebView2, ICoreWebView2CustomSchemeRegistration, ICoreWebView2Environment,
+    ICoreWebView2WebResourceRequestedEventArgs, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+};
+use webview2_com::{take_pwstr, CoreWebView2CustomSchemeRegistration, WebResourceRequestedEventHandler};
+use windows::core::{HSTRING, PWSTR};
+use windows::Win32::UI::Shell::SHCreateMemStream;
+
+use crate::assets::EmbeddedAssets;
+use crate::mime::mime_for_path;
+
+/// Scheme the embedded assets are served from.
+pub const SCHEME: &str = "beamer";
+
+/// Registration of the `beamer` scheme, for the environment options.
+///
+/// `beamer://localhost/` gets an authority and counts as a secure origin,
+/// like the WKWebView scheme handler on macOS.
+pub fn scheme_registration() -> ICoreWebView2CustomSchemeRegistration {
+    let registration = CoreWebView2CustomSchemeRegistration::new(SCHEME.to_string());
+    // SAFETY: Setters on a freshly created registration object.
+    unsafe {
+        registration.set_treat_as_secure(true);
+        registration.set_has_authority_component(true);
+        registration.set_allowed_origins(vec!["beamer://localhost".to_string()]);
+    }
+    registration.into()
+}
+
+/// Serve `assets` for every `beamer://` request of `webview`. Returns the
+/// event registration token.
+///
+/// # Safety
+///
+/// Must be called on the thread that created `webview`.
+pub unsafe fn add_scheme_handler(
+    webview: &ICoreWebView2,
+    environment: &ICoreWebView2Environment,
+    assets: &'static EmbeddedAssets,
+) -> windows::core::Result<i64> {
+    let filter = HSTRING::from(format!("{SCHEME}://*"));
+    // SAFETY: webview is valid and we are on its thread.
+    unsafe { webview.AddWebResourceRequestedFilter(&filter, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL)? };
+
+    let environment = environment.clone();
+    let handler = WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else { return Ok(()) };
+        // SAFETY: args is valid for the duration of the event.
+        unsafe { respond(&args, &environment, assets) }
+    }));
+    let mut token = 0;
+    // SAFETY: webview and handler are valid and we are on the webview's thread.
+    unsafe { webview.add_WebResourceRequested(&handler, &mut token)? };
+    Ok(token)
+}
+
+unsafe fn respond(
+    args: &ICoreWebView2WebResourceRequestedEventArgs,
+    environment: &ICoreWebView2Environment,
+    assets: &EmbeddedAssets,
+) -> windows::core::Result<()> {
+    let mut uri = PWSTR::null();
+    // SAFETY: args is valid; Uri() hands over a CoTaskMem string that
+    // take_pwstr frees.
+    let uri = unsafe {
+        args.Request()?.Uri(&mut uri)?;
+        take_pwstr(uri)
+    };
+    let path = asset_path(&uri);
+
+    let (status, reason, mime, body) = match assets.get(&path) {
+        Some(data) => (200, "OK", mime_for_path(&path), data),
+        None => {
+            log::warn!("asset not found: {path}");
+            (404, "Not Found", "text/plain", &b"Not Found"[..])
+        }
+    };
+    // SAFETY: SHCreateMemStream copies the body into a new stream.
+    let stream = unsafe { SHCreateMemStream(Some(body)) };
+    let headers = HSTRING::from(format!("Content-Type: {mime}\r\nAccess-Control-Allow-Origin: *"));
+    // SAFETY: environment is valid and we are on the webview's thread.
+    let response = unsafe {
+        environment.CreateWebResourceResponse(stream.as_ref(), status, &HSTRING::from(reason), &headers)?
+    };
+    // SAFETY: args is valid for the duration of the event.
+    unsafe { args.SetResponse(&response) }
+}
+
+/// Asset path for a `beamer://localhost/...` URI, with query and fragment
+/// dropped and percent escapes decoded. The root maps to `index.html`.
+fn asset_path(uri: &str) -> String {
+    let rest = uri.split_once("://").map_or(uri, |(_, rest)| rest);
+    let path = rest.split_once('/').map_or("", |(_, path)| path);
+    let path = path.split(['?', '#']).next().unwrap_or("");
+    if path.is_empty() {
+        return "index.html".to_string();
+    }
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
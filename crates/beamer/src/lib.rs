@@ -66,6 +66,84 @@ pub use beamer_macros::EnumParameter;
 pub use beamer_macros::HasParameters;
 #[cfg(feature = "derive")]
 pub use beamer_macros::export;
+#[cfg(feature = "derive")]
+pub use beamer_macros::rpc;
+#[cfg(feature = "derive")]
+pub use beamer_macros::process_graph;
+
+/// Stress test added to plugins by `export_plugin!` when the `stress`
+/// feature is on. See `beamer::core::stress`.
+#[cfg(feature = "stress")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __beamer_stress_test {
+    ($plugin:ty, $presets:ty) => {
+        #[cfg(test)]
+        mod __beamer_stress {
+            use super::*;
+
+            #[global_allocator]
+            static ALLOCATOR: $crate::core::stress::CountingAllocator =
+                $crate::core::stress::CountingAllocator;
+
+            #[test]
+            fn stress() {
+                let options = $crate::core::stress::StressOptions::from_env();
+                let report = $crate::core::stress::run::<$plugin, $presets>(&options);
+                println!("{}", report);
+                assert!(report.is_ok(), "stress test found {} issues", report.issues.len());
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "stress"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __beamer_stress_test {
+    ($plugin:ty, $presets:ty) => {};
+}
+
+/// Script runner test added to plugins by `export_plugin!` when the
+/// `script` feature is on. See `beamer::core::script`.
+#[cfg(feature = "script")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __beamer_script_test {
+    ($plugin:ty) => {
+        #[cfg(test)]
+        mod __beamer_script {
+            use super::*;
+
+            #[test]
+            fn script() {
+                let scripts = std::env::var_os("BEAMER_SCRIPT").unwrap_or_default();
+                let mut failed = 0;
+                for path in std::env::split_paths(&scripts) {
+                    println!("{}:", path.display());
+                    match $crate::core::script::run_file::<$plugin>(&path) {
+                        Ok(report) => {
+                            println!("{}", report);
+                            failed += usize::from(!report.passed());
+                        }
+                        Err(e) => {
+                            println!("error: {}", e);
+                            failed += 1;
+                        }
+                    }
+                }
+                assert_eq!(failed, 0, "{} scripts failed", failed);
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "script"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __beamer_script_test {
+    ($plugin:ty) => {};
+}
 
 /// Generate plugin entry points for all enabled formats (AU, VST3).
 ///
@@ -150,6 +228,18 @@ macro_rules! export_plugin {
             true
         }
 
+        #[cfg(all(feature = "vst3", target_os = "linux"))]
+        #[no_mangle]
+        extern "system" fn ModuleEntry(_library_handle: *mut std::ffi::c_void) -> bool {
+            true
+        }
+
+        #[cfg(all(feature = "vst3", target_os = "linux"))]
+        #[no_mangle]
+        extern "system" fn ModuleExit() -> bool {
+            true
+        }
+
         #[cfg(feature = "vst3")]
         #[no_mangle]
         extern "system" fn GetPluginFactory() -> *mut std::ffi::c_void {
@@ -164,6 +254,39 @@ macro_rules! export_plugin {
                 .unwrap()
                 .into_raw() as *mut std::ffi::c_void
         }
+
+        // === Tooling entry point ===
+        /// JSON description of the plugin for xtask, installers and CI.
+        /// The returned string is owned by the binary; do not free it.
+        #[no_mangle]
+        pub extern "C" fn beamer_describe() -> *const std::os::raw::c_char {
+            static DESCRIPTION: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+            DESCRIPTION
+                .get_or_init(|| {
+                    let json = $crate::core::describe::describe::<$plugin, $presets>(&$config);
+                    std::ffi::CString::new(json).unwrap_or_default()
+                })
+                .as_ptr()
+        }
+
+        /// Markdown parameter manual of the plugin, rendered from the
+        /// `beamer_describe()` document. Owned by the binary; do not free.
+        #[no_mangle]
+        pub extern "C" fn beamer_describe_markdown() -> *const std::os::raw::c_char {
+            static MANUAL: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+            MANUAL
+                .get_or_init(|| {
+                    let markdown = $crate::core::describe::manual::<$plugin, $presets>(&$config);
+                    std::ffi::CString::new(markdown).unwrap_or_default()
+                })
+                .as_ptr()
+        }
+
+        // === Stress test (cargo xtask stress) ===
+        $crate::__beamer_stress_test!($plugin, $presets);
+
+        // === Script runner (cargo xtask script) ===
+        $crate::__beamer_script_test!($plugin);
     };
 
     // Without presets (default to NoPresets)
@@ -188,30 +311,71 @@ pub mod prelude {
         // Asset types
         EmbeddedAsset, EmbeddedAssets,
         // Buffer types
-        AuxiliaryBuffers, AuxInput, AuxOutput, Buffer,
+        AuxiliaryBuffers, AuxInput, AuxOutput, Buffer, FixedBuffer, Frames,
         // Bypass handling
         BypassAction, BypassHandler, BypassState, CrossfadeCurve,
+        // Memory accounting and budgets
+        MemoryAccounting, MemoryCharge, MemorySubsystem,
+        // Channel downmix/upmix
+        ChannelMix,
+        // Deadline watchdog settings
+        WatchdogSettings,
         // Sample trait for generic f32/f64 processing
-        Sample,
+        Sample, PrecisionPolicy,
         // Traits
         Descriptor, GuiDelegate, HasParameters, Processor,
+        // Tail reporting
+        TailTime,
         // Plugin setup types (composable)
         PluginSetup, SampleRate, MaxBufferSize, MainInputChannels, MainOutputChannels,
-        AuxInputCount, AuxOutputCount, ProcessMode,
+        AuxInputCount, AuxOutputCount, AuxInputChannels, AuxOutputChannels, ProcessMode,
         // Bus configuration
-        BusInfo, BusType,
+        BusInfo, BusLayout, BusType,
         // GUI types
-        GuiConstraints, NoGui,
+        GuiBackend, GuiConstraints, NoGui,
         // Parameter metadata
         NoParameters, ParameterFlags, ParameterInfo,
         // Factory presets
         FactoryPresets, NoPresets, PresetInfo, PresetValue,
+        // Host preset files (.vstpreset / .aupreset)
+        PresetFile, PresetFileFormat,
         // Parameter types
         BoolParameter, EnumParameter, EnumParameterValue, FloatParameter, IntParameter, Formatter, ParameterRef, Parameters,
+        SmoothedValues, TimeInSamples,
+        // Automatable oversampling
+        Oversampling, OversamplingParameter,
+        // Eco/Normal/High quality modes
+        Quality, QualityParameter,
         // MIDI CC configuration (framework manages runtime state)
         MidiCcConfig,
-        // Parameter smoothing
-        Smoother, SmoothingStyle,
+        // MPE zones (framework tracks member channels)
+        MpeConfig,
+        // Playable note range and keyboard splits
+        NoteRanges, NoteSplit,
+        // MIDI response curves
+        CurveShape, CurveTarget, ResponseCurves,
+        // Hardware controller pages
+        ControllerPage, ControllerPages,
+        // Parameter constraints
+        Constraint, ConstraintMode, ParameterConstraints,
+        // Background state loading
+        StateLoadStatus, StateLoader,
+        // Parameter change log
+        ParameterChangeSource, ParameterLog,
+        // MIDI monitor
+        MidiDirection, MidiMonitor, MidiMonitorEntry, MidiMonitorKind,
+        // MIDI panic
+        MidiPanic,
+        // Host notifications
+        HostServices,
+        // License activation
+        Activation, ActivationPolicy, ActivationState,
+        // GUI frame meters, vectorscope and playhead
+        FrameMeters, Playhead, Vectorscope,
+        // GUI data channels
+        GuiDataChannel, GuiDataChannels, GuiFrame,
+        // GUI notifications
+        Notifications, NotifyLevel,
         // Parameter group system
         GroupId, GroupInfo, ParameterGroups, ROOT_GROUP_ID,
         // Range mapping
@@ -222,15 +386,73 @@ pub mod prelude {
         Rect, Size,
         // MIDI types
         ChannelPressure, ControlChange, MidiBuffer, MidiChannel, MidiEvent, MidiEventKind,
-        MidiNote, NoteId, NoteOff, NoteOn, PitchBend, PolyPressure, ProgramChange,
+        MidiNote, MidiThru, NoteId, NoteOff, NoteOn, PitchBend, PolyPressure, ProgramChange,
+        // Per-note expressions
+        NoteExpressionKind, NoteExpressionTypeInfo, NoteExpressionValue,
         // Process context and transport
         FrameRate, ProcessContext, Transport,
+        // Transport relocation handling
+        RelocationPolicy, TimeAnchor, TransportEvent,
+        // Tempo-synced note values
+        TempoSync, TempoSyncParameter,
+        // Ambisonic bus widths
+        AmbisonicOrder, AmbisonicOrderParameter,
+        // Speaker layouts
+        Speaker, SpeakerLayout,
+        // Control-rate ticks
+        ControlContext,
+        // Commands to the audio thread
+        dispatch_commands, CommandHandler, CommandQueue,
+        // Splitting blocks at events
+        SplitBlockProcessor,
+        // Sample-rate-dependent values
+        RateDependent,
         // FourCharCode
         FourCharCode,
         // WebView support
         WebViewHandler, serde_json,
     };
 
+    // DSP building blocks (`dsp` feature)
+    #[cfg(feature = "dsp")]
+    pub use beamer_core::{
+        // Gain matching
+        AutoGain, AutoGainMeter,
+        // Sidechain modulation
+        ModulationTarget, SidechainModulator,
+        // Parallel routing, multiband crossovers and channel gain matrices
+        ParallelSplit, Crossover, CrossoverSlope, GainMatrix,
+        // Biquad filters and frequency response
+        BiquadCoeffs, BiquadState, ResponseCurve,
+        // FFT and test-signal measurement
+        Fft, Measurement, MeasurementConfig, MeasurementHandle, MeasurementState, NoiseColor,
+        NoiseGenerator, TransferFunction,
+        // Transport-aware looping
+        LoopQuantize, Looper, LooperCommand, LooperConfig, LooperHandle, LooperState,
+        // Disk streaming for large sample libraries
+        DiskStreamConfig, DiskStreamer, RawF32File, StreamSource, StreamVoice, StreamedSample,
+        // Undoable sample editing
+        SampleEdit, SampleEditError, SampleEditor, SampleReceiver, SampleSnapshot,
+    };
+
+    // Parameter smoothing (`smoothing` feature)
+    #[cfg(feature = "smoothing")]
+    pub use beamer_core::{Smoother, SmoothingStyle};
+
+    // Group bypass/solo switches (`groups` feature)
+    #[cfg(feature = "groups")]
+    pub use beamer_core::GroupControls;
+
+    // User and shared preset folders and banks (`presets` feature)
+    #[cfg(feature = "presets")]
+    pub use beamer_core::{BankInfo, MergeStrategy, PresetBank, PresetEntry, PresetFolders};
+
+    // Controller feedback output, minimum note length and MIDI effects (`midi` feature)
+    #[cfg(feature = "midi")]
+    pub use beamer_core::{
+        ControllerFeedback, FeedbackTarget, MidiDelay, MidiEcho, MidiHumanize, MidiScheduler, NoteGate,
+    };
+
     // Plugin configuration
     pub use beamer_core::{Config, config::Category, config::Subcategory};
 
@@ -242,4 +464,9 @@ pub mod prelude {
     // because traits and derive macros live in different namespaces.
     #[cfg(feature = "derive")]
     pub use beamer_macros::{EnumParameter, HasParameters, Parameters};
+
+    // Declarative stage routing and the traits its stages implement
+    #[cfg(feature = "derive")]
+    pub use beamer_macros::process_graph;
+    pub use beamer_core::{Block, Detector, KeySignal, KeyedBlock};
 }
@@ -0,0 +1,410 @@
+//! Cross-format null test: the same plugin, input, MIDI and parameter
+//! changes rendered through the VST3 and AU wrappers must produce the same
+//! output, sample for sample.
+//!
+//! Both wrappers are driven through their real entry points without a host:
+//! `Vst3Processor::process()` with a `ProcessData` built here (input events
+//! and parameter changes included), and the AU processor with the calls its
+//! render block makes (`begin_block`, `process_midi`,
+//! `apply_parameter_events`, `process_with_midi`). A direct
+//! `OfflineRenderer` render is the reference for both.
+//!
+//! Runs on macOS, where both wrappers build:
+//! `cargo test -p beamer --features vst3,au --test cross_format_null`
+
+#![cfg(all(feature = "derive", feature = "vst3", feature = "au", target_os = "macos"))]
+
+use beamer::core::testing::{OfflineRenderer, Render};
+use beamer::prelude::*;
+
+const SAMPLE_RATE: f64 = 48_000.0;
+const MAX_BLOCK: usize = 256;
+const LENGTH: usize = 2_000;
+/// Host block sizes, cycled. Parameter changes land on block starts.
+const BLOCK_SIZES: [usize; 4] = [256, 100, 37, 200];
+
+// =============================================================================
+// Test Plugin
+// =============================================================================
+
+#[derive(Parameters)]
+struct NullParameters {
+    #[parameter(id = "gain", name = "Gain", default = 0.5, range = 0.0..=1.0)]
+    gain: FloatParameter,
+}
+
+/// Scales the input by the gain and adds a click at every note-on, so bus
+/// mapping, parameter delivery and MIDI timing all show up in the output.
+#[derive(Default, HasParameters)]
+struct NullDescriptor {
+    #[parameters]
+    parameters: NullParameters,
+}
+
+#[derive(HasParameters)]
+struct NullProcessor {
+    #[parameters]
+    parameters: NullParameters,
+    notes: Vec<u32>,
+}
+
+impl Descriptor for NullDescriptor {
+    type Setup = ();
+    type Processor = NullProcessor;
+
+    fn prepare(self, _: ()) -> NullProcessor {
+        NullProcessor { parameters: self.parameters, notes: Vec::with_capacity(64) }
+    }
+
+    fn wants_midi(&self) -> bool {
+        true
+    }
+}
+
+impl Processor for NullProcessor {
+    type Descriptor = NullDescriptor;
+
+    fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+        let gain = self.parameters.gain.get() as f32;
+        for (input, output) in buffer.zip_channels() {
+            for (out, sample) in output.iter_mut().zip(input) {
+                *out = sample * gain;
+            }
+            for &offset in &self.notes {
+                output[offset as usize] += 1.0;
+            }
+        }
+        self.notes.clear();
+    }
+
+    fn process_midi(&mut self, input: &[MidiEvent], _output: &mut MidiBuffer) {
+        for event in input {
+            if let MidiEventKind::NoteOn(_) = event.event {
+                self.notes.push(event.sample_offset);
+            }
+        }
+    }
+
+    fn wants_midi(&self) -> bool {
+        true
+    }
+}
+
+// =============================================================================
+// Script
+// =============================================================================
+
+/// Input, notes (position, pitch) and gain changes (position, normalized).
+struct Script {
+    input: Vec<Vec<f32>>,
+    notes: Vec<(usize, u8)>,
+    gains: Vec<(usize, f64)>,
+}
+
+impl Script {
+    fn new() -> Self {
+        let ramp = |channel: usize| (0..LENGTH).map(|i| ((i * (channel + 3)) % 97) as f32 / 97.0 - 0.5).collect();
+        Self {
+            input: vec![ramp(0), ramp(1)],
+            notes: vec![(5, 60), (300, 62), (301, 64), (1234, 65), (1999, 67)],
+            // Block starts of BLOCK_SIZES: 0, 256, 356, 393, 593, 849, ...
+            gains: vec![(256, 1.0), (593, 0.25), (849, 0.75)],
+        }
+    }
+
+    /// Direct render through `OfflineRenderer`, the reference.
+    fn reference(&self) -> Render {
+        let mut renderer = OfflineRenderer::<NullDescriptor>::new(SAMPLE_RATE, MAX_BLOCK).with_input(self.input.clone());
+        for &(at, pitch) in &self.notes {
+            renderer = renderer.with_note(at, 0, pitch, 1.0, 1);
+        }
+        for &(at, value) in &self.gains {
+            renderer = renderer.with_parameter(at, "gain", value);
+        }
+        renderer.render()
+    }
+
+    /// Render block by block, handing each block its input slices, the
+    /// note-ons inside it (rebased to the block) and the gain change at its
+    /// start.
+    fn render(&self, mut block: impl FnMut(&[&[f32]], &mut [&mut [f32]], &[MidiEvent], Option<f64>)) -> Render {
+        let mut outputs = vec![vec![0.0f32; LENGTH]; self.input.len()];
+        let mut sizes = BLOCK_SIZES.iter().cycle();
+        let mut start = 0;
+        while start < LENGTH {
+            let end = (start + sizes.next().unwrap()).min(LENGTH);
+            let midi: Vec<MidiEvent> = self
+                .notes
+                .iter()
+                .filter(|(at, _)| (start..end).contains(at))
+                .map(|&(at, pitch)| MidiEvent::note_on((at - start) as u32, 0, pitch, 1.0, -1, 0.0, 0))
+                .collect();
+            let gain = self.gains.iter().find(|(at, _)| *at == start).map(|&(_, value)| value);
+            let inputs: Vec<&[f32]> = self.input.iter().map(|c| &c[start..end]).collect();
+            let mut block_outputs: Vec<&mut [f32]> = outputs.iter_mut().map(|c| &mut c[start..end]).collect();
+            block(&inputs, &mut block_outputs, &midi, gain);
+            start = end;
+        }
+        Render { sample_rate: SAMPLE_RATE, outputs, meter_names: Vec::new(), meter_rows: Vec::new() }
+    }
+}
+
+fn gain_id() -> u32 {
+    OfflineRenderer::<NullDescriptor>::new(SAMPLE_RATE, MAX_BLOCK)
+        .parameter_id("gain")
+        .expect("gain parameter")
+}
+
+// =============================================================================
+// VST3 Host
+// =============================================================================
+
+mod vst3_host {
+    use std::ptr;
+
+    use beamer::core::config::Category;
+    use beamer::core::{Config, MidiEvent, MidiEventKind};
+    use beamer::vst3_impl::vst3::{Class, ComPtr, ComWrapper, Steinberg::Vst::*, Steinberg::*};
+    use beamer::vst3_impl::Vst3Processor;
+
+    use super::{gain_id, NullDescriptor, Render, Script, MAX_BLOCK, SAMPLE_RATE};
+
+    static CONFIG: Config = Config::new("Null Test", Category::Effect, "Bmtr", "null");
+
+    /// Input events of one block.
+    struct EventList {
+        events: Vec<Event>,
+    }
+
+    impl Class for EventList {
+        type Interfaces = (IEventList,);
+    }
+
+    impl IEventListTrait for EventList {
+        unsafe fn getEventCount(&self) -> int32 {
+            self.events.len() as int32
+        }
+
+        unsafe fn getEvent(&self, index: int32, event: *mut Event) -> tresult {
+            match self.events.get(index as usize) {
+                Some(source) if !event.is_null() => {
+                    // SAFETY: event is non-null and points to an Event owned by the caller.
+                    unsafe { ptr::copy_nonoverlapping(source, event, 1) };
+                    kResultOk
+                }
+                _ => kInvalidArgument,
+            }
+        }
+
+        unsafe fn addEvent(&self, _event: *mut Event) -> tresult {
+            kResultFalse
+        }
+    }
+
+    /// A single automation point at the start of the block.
+    struct ParameterQueue {
+        id: ParamID,
+        value: ParamValue,
+    }
+
+    impl Class for ParameterQueue {
+        type Interfaces = (IParamValueQueue,);
+    }
+
+    impl IParamValueQueueTrait for ParameterQueue {
+        unsafe fn getParameterId(&self) -> ParamID {
+            self.id
+        }
+
+        unsafe fn getPointCount(&self) -> int32 {
+            1
+        }
+
+        unsafe fn getPoint(&self, index: int32, sample_offset: *mut int32, value: *mut ParamValue) -> tresult {
+            if index != 0 || sample_offset.is_null() || value.is_null() {
+                return kInvalidArgument;
+            }
+            // SAFETY: Both pointers are non-null and owned by the caller.
+            unsafe {
+                *sample_offset = 0;
+                *value = self.value;
+            }
+            kResultOk
+        }
+
+        unsafe fn addPoint(&self, _sample_offset: int32, _value: ParamValue, _index: *mut int32) -> tresult {
+            kResultFalse
+        }
+    }
+
+    struct ParameterChanges {
+        queues: Vec<ComPtr<IParamValueQueue>>,
+    }
+
+    impl Class for ParameterChanges {
+        type Interfaces = (IParameterChanges,);
+    }
+
+    impl IParameterChangesTrait for ParameterChanges {
+        unsafe fn getParameterCount(&self) -> int32 {
+            self.queues.len() as int32
+        }
+
+        unsafe fn getParameterData(&self, index: int32) -> *mut IParamValueQueue {
+            self.queues.get(index as usize).map_or(ptr::null_mut(), |queue| queue.as_ptr())
+        }
+
+        unsafe fn addParameterData(&self, _id: *const ParamID, _index: *mut int32) -> *mut IParamValueQueue {
+            ptr::null_mut()
+        }
+    }
+
+    fn note_on(midi: &MidiEvent) -> Event {
+        // SAFETY: Event is a C struct with no invalid bit patterns; zeroed is a valid state.
+        let mut event: Event = unsafe { std::mem::zeroed() };
+        event.sampleOffset = midi.sample_offset as int32;
+        event.r#type = 0; // kNoteOnEvent
+        if let MidiEventKind::NoteOn(on) = &midi.event {
+            event.__field0.noteOn.channel = on.channel as int16;
+            event.__field0.noteOn.pitch = on.pitch as int16;
+            event.__field0.noteOn.velocity = on.velocity;
+            event.__field0.noteOn.noteId = -1;
+        }
+        event
+    }
+
+    /// Render the script through `Vst3Processor::process()`.
+    pub fn render(script: &Script) -> Render {
+        let processor = Vst3Processor::<NullDescriptor>::new(&CONFIG);
+        let gain_id = gain_id();
+        let mut setup = ProcessSetup {
+            processMode: ProcessModes_::kRealtime as int32,
+            symbolicSampleSize: SymbolicSampleSizes_::kSample32 as int32,
+            maxSamplesPerBlock: MAX_BLOCK as int32,
+            sampleRate: SAMPLE_RATE,
+        };
+        // SAFETY: The processor is only used from this thread, in host call order.
+        unsafe {
+            assert_eq!(processor.initialize(ptr::null_mut()), kResultOk);
+            assert_eq!(processor.setupProcessing(&mut setup), kResultOk);
+            assert_eq!(processor.setActive(1), kResultOk);
+            assert_eq!(processor.setProcessing(1), kResultOk);
+        }
+
+        let render = script.render(|inputs, outputs, midi, gain| {
+            let num_samples = inputs[0].len();
+            let mut input_ptrs: Vec<*mut f32> = inputs.iter().map(|c| c.as_ptr() as *mut f32).collect();
+            let mut output_ptrs: Vec<*mut f32> = outputs.iter_mut().map(|c| c.as_mut_ptr()).collect();
+            // SAFETY: AudioBusBuffers is a C struct; zeroed is a valid state.
+            let mut input_bus: AudioBusBuffers = unsafe { std::mem::zeroed() };
+            input_bus.numChannels = input_ptrs.len() as int32;
+            input_bus.__field0.channelBuffers32 = input_ptrs.as_mut_ptr();
+            // SAFETY: AudioBusBuffers is a C struct; zeroed is a valid state.
+            let mut output_bus: AudioBusBuffers = unsafe { std::mem::zeroed() };
+            output_bus.numChannels = output_ptrs.len() as int32;
+            output_bus.__field0.channelBuffers32 = output_ptrs.as_mut_ptr();
+
+            let events = ComWrapper::new(EventList { events: midi.iter().map(note_on).collect() })
+                .to_com_ptr::<IEventList>()
+                .unwrap();
+            let queues = gain
+                .map(|value| {
+                    ComWrapper::new(ParameterQueue { id: gain_id, value })
+                        .to_com_ptr::<IParamValueQueue>()
+                        .unwrap()
+                })
+                .into_iter()
+                .collect();
+            let changes = ComWrapper::new(ParameterChanges { queues }).to_com_ptr::<IParameterChanges>().unwrap();
+
+            // SAFETY: ProcessData is a C struct; zeroed is a valid state (null pointers).
+            let mut data: ProcessData = unsafe { std::mem::zeroed() };
+            data.processMode = ProcessModes_::kRealtime as int32;
+            data.symbolicSampleSize = SymbolicSampleSizes_::kSample32 as int32;
+            data.numSamples = num_samples as int32;
+            data.numInputs = 1;
+            data.numOutputs = 1;
+            data.inputs = &mut input_bus;
+            data.outputs = &mut output_bus;
+            data.inputEvents = events.as_ptr();
+            data.inputParameterChanges = changes.as_ptr();
+            // SAFETY: data and everything it points to live until process() returns.
+            assert_eq!(unsafe { processor.process(&mut data) }, kResultOk);
+        });
+
+        // SAFETY: Same thread, host call order.
+        unsafe {
+            processor.setProcessing(0);
+            processor.setActive(0);
+            processor.terminate();
+        }
+        render
+    }
+}
+
+// =============================================================================
+// AU Host
+// =============================================================================
+
+mod au_host {
+    use beamer::au_impl::render::MidiBuffer;
+    use beamer::au_impl::{AuParameterEvent, AuPluginInstance, AuProcessor};
+    use beamer::core::{BusType, CachedBusConfig, CachedBusInfo, ProcessContext, Transport};
+
+    use super::{gain_id, NullDescriptor, Render, Script, MAX_BLOCK, SAMPLE_RATE};
+
+    /// Render the script through the AU processor, making the calls its
+    /// render block makes.
+    pub fn render(script: &Script) -> Render {
+        let mut processor = AuProcessor::<NullDescriptor>::new();
+        let bus_config = CachedBusConfig::new(
+            vec![CachedBusInfo::new(2, BusType::Main)],
+            vec![CachedBusInfo::new(2, BusType::Main)],
+        );
+        processor
+            .allocate_render_resources(SAMPLE_RATE, MAX_BLOCK as u32, &bus_config)
+            .expect("Failed to prepare processor");
+        let gain_id = gain_id();
+        let mut midi_output = MidiBuffer::with_capacity(64);
+
+        script.render(|inputs, outputs, midi, gain| {
+            let num_samples = inputs[0].len();
+            let parameters: Vec<AuParameterEvent> = gain
+                .map(|value| AuParameterEvent {
+                    sample_offset: 0,
+                    parameter_address: gain_id as u64,
+                    value: value as f32,
+                })
+                .into_iter()
+                .collect();
+
+            processor.begin_block(num_samples);
+            midi_output.clear();
+            processor.process_midi(midi, &mut midi_output);
+            processor
+                .apply_parameter_events(&parameters, &[])
+                .expect("Failed to apply parameter events");
+            let context = ProcessContext::new(SAMPLE_RATE, num_samples, Transport::default());
+            processor
+                .process_with_midi(inputs, outputs, &[], &mut [], midi, &context)
+                .expect("Failed to process");
+        })
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[test]
+fn vst3_and_au_wrappers_null() {
+    let script = Script::new();
+    let reference = script.reference();
+    let vst3 = vst3_host::render(&script);
+    let au = au_host::render(&script);
+
+    assert!(reference.peak().iter().all(|&peak| peak > 1.0), "the notes must reach the output");
+    vst3.assert_nulls(&au, 0.0);
+    vst3.assert_nulls(&reference, 1e-6);
+    au.assert_nulls(&reference, 1e-6);
+}
@@ -0,0 +1,51 @@
+//! Saving and loading state with the derived `Parameters` implementation.
+
+#![cfg(feature = "derive")]
+
+use beamer::prelude::*;
+
+#[derive(Parameters)]
+struct OutputParameters {
+    #[parameter(id = "level", name = "Level", default = 0.0, range = -60.0..=12.0, kind = "db")]
+    level: FloatParameter,
+
+    #[parameter(id = "meter_hold", name = "Meter Hold", default = false, no_save)]
+    meter_hold: BoolParameter,
+}
+
+#[derive(Parameters)]
+struct TestParameters {
+    #[parameter(id = "drive", name = "Drive", default = 0.5, range = 0.0..=1.0)]
+    drive: FloatParameter,
+
+    #[parameter(id = "audition", name = "Audition", default = 0.25, range = 0.0..=1.0, transient)]
+    audition: FloatParameter,
+
+    #[nested(group = "Output")]
+    output: OutputParameters,
+}
+
+#[test]
+fn load_state_resets_non_persistent_parameters_to_default() {
+    let mut parameters = TestParameters::default();
+    parameters.drive.set_normalized(0.8);
+    let saved = parameters.save_state();
+
+    // Move everything away from the saved and default values
+    parameters.drive.set_normalized(0.1);
+    parameters.audition.set_normalized(0.9);
+    parameters.output.meter_hold.set_normalized(1.0);
+    assert!(!parameters.audition.flags().is_persistent);
+    assert!(!parameters.output.meter_hold.flags().is_persistent);
+
+    parameters.load_state(&saved).unwrap();
+    assert_eq!(parameters.drive.get_normalized(), 0.8);
+    assert_eq!(parameters.audition.get_normalized(), parameters.audition.default_normalized());
+    assert!(!parameters.output.meter_hold.get());
+
+    // An empty state resets them as well
+    parameters.audition.set_normalized(0.9);
+    parameters.load_state(&[]).unwrap();
+    assert_eq!(parameters.audition.get_normalized(), parameters.audition.default_normalized());
+    assert_eq!(parameters.drive.get_normalized(), 0.8);
+}
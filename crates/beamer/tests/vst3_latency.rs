@@ -0,0 +1,320 @@
+//! VST3 latency reporting: a latency change the processor announces while
+//! processing reaches the host as `restartComponent(kLatencyChanged)`, and
+//! the host reads the new value.
+//!
+//! The host is simulated with a recording `IComponentHandler` and, on Linux,
+//! a host context offering `IRunLoop`, whose timer forwards the change with
+//! no editor open.
+
+#![cfg(all(feature = "derive", feature = "vst3"))]
+
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use beamer::core::config::Category;
+use beamer::core::testing::OfflineRenderer;
+use beamer::prelude::*;
+use beamer::vst3_impl::vst3::{Class, ComPtr, ComWrapper, Steinberg::Vst::*, Steinberg::*};
+use beamer::vst3_impl::Vst3Processor;
+
+const LOOKAHEAD: u32 = 64;
+const BLOCK: usize = 64;
+
+static CONFIG: Config = Config::new("Latency Test", Category::Effect, "Bmtr", "ltcy");
+
+// =============================================================================
+// Test Plugin
+// =============================================================================
+
+#[derive(Parameters)]
+struct LatencyParameters {
+    #[parameter(id = "lookahead", name = "Lookahead", default = false)]
+    lookahead: BoolParameter,
+}
+
+/// Reports `LOOKAHEAD` samples of latency while the lookahead parameter is
+/// on, switching in `process()` like a plugin that reallocates its delay
+/// line on the audio thread.
+#[derive(Default, HasParameters)]
+struct LatencyDescriptor {
+    #[parameters]
+    parameters: LatencyParameters,
+    host: HostServices,
+}
+
+#[derive(HasParameters)]
+struct LatencyProcessor {
+    #[parameters]
+    parameters: LatencyParameters,
+    host: HostServices,
+    latency: u32,
+}
+
+impl Descriptor for LatencyDescriptor {
+    type Setup = ();
+    type Processor = LatencyProcessor;
+
+    fn prepare(self, _: ()) -> LatencyProcessor {
+        LatencyProcessor { parameters: self.parameters, host: self.host, latency: 0 }
+    }
+
+    fn host_services(&self) -> HostServices {
+        self.host.clone()
+    }
+}
+
+impl Processor for LatencyProcessor {
+    type Descriptor = LatencyDescriptor;
+
+    fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+        let latency = if self.parameters.lookahead.get() { LOOKAHEAD } else { 0 };
+        if latency != self.latency {
+            self.latency = latency;
+            self.host.latency_changed();
+        }
+        buffer.copy_to_output();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.latency
+    }
+}
+
+// =============================================================================
+// Host
+// =============================================================================
+
+/// Records the flags of every `restartComponent()` call.
+#[derive(Clone, Default)]
+struct Restarts(Arc<Mutex<Vec<int32>>>);
+
+impl Restarts {
+    fn take(&self) -> Vec<int32> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+struct ComponentHandler {
+    restarts: Restarts,
+}
+
+impl Class for ComponentHandler {
+    type Interfaces = (IComponentHandler,);
+}
+
+impl IComponentHandlerTrait for ComponentHandler {
+    unsafe fn beginEdit(&self, _id: ParamID) -> tresult {
+        kResultOk
+    }
+
+    unsafe fn performEdit(&self, _id: ParamID, _value_normalized: ParamValue) -> tresult {
+        kResultOk
+    }
+
+    unsafe fn endEdit(&self, _id: ParamID) -> tresult {
+        kResultOk
+    }
+
+    unsafe fn restartComponent(&self, flags: int32) -> tresult {
+        self.restarts.0.lock().unwrap().push(flags);
+        kResultOk
+    }
+}
+
+/// A processor set up and started like a host does, with a recording
+/// component handler.
+struct Host {
+    /// Boxed: the processor's host timer holds its address.
+    processor: Box<Vst3Processor<LatencyDescriptor>>,
+    restarts: Restarts,
+    _handler: ComPtr<IComponentHandler>,
+}
+
+impl Host {
+    fn new(context: *mut FUnknown) -> Self {
+        let processor = Box::new(Vst3Processor::<LatencyDescriptor>::new(&CONFIG));
+        let restarts = Restarts::default();
+        let handler = ComWrapper::new(ComponentHandler { restarts: restarts.clone() })
+            .to_com_ptr::<IComponentHandler>()
+            .unwrap();
+        let mut setup = ProcessSetup {
+            processMode: ProcessModes_::kRealtime as int32,
+            symbolicSampleSize: SymbolicSampleSizes_::kSample32 as int32,
+            maxSamplesPerBlock: BLOCK as int32,
+            sampleRate: 48_000.0,
+        };
+        // SAFETY: The processor is only used from this thread, in host call order.
+        unsafe {
+            assert_eq!(processor.initialize(context), kResultOk);
+            assert_eq!(processor.setComponentHandler(handler.as_ptr()), kResultOk);
+            assert_eq!(processor.setupProcessing(&mut setup), kResultOk);
+            assert_eq!(processor.setActive(1), kResultOk);
+            assert_eq!(processor.setProcessing(1), kResultOk);
+        }
+        Self { processor, restarts, _handler: handler }
+    }
+
+    /// Process one block of silence.
+    fn process(&self) {
+        let mut input = [[0.0f32; BLOCK]; 2];
+        let mut output = [[0.0f32; BLOCK]; 2];
+        let mut input_ptrs: Vec<*mut f32> = input.iter_mut().map(|c| c.as_mut_ptr()).collect();
+        let mut output_ptrs: Vec<*mut f32> = output.iter_mut().map(|c| c.as_mut_ptr()).collect();
+        // SAFETY: AudioBusBuffers is a C struct; zeroed is a valid state.
+        let mut input_bus: AudioBusBuffers = unsafe { std::mem::zeroed() };
+        input_bus.numChannels = 2;
+        input_bus.__field0.channelBuffers32 = input_ptrs.as_mut_ptr();
+        // SAFETY: AudioBusBuffers is a C struct; zeroed is a valid state.
+        let mut output_bus: AudioBusBuffers = unsafe { std::mem::zeroed() };
+        output_bus.numChannels = 2;
+        output_bus.__field0.channelBuffers32 = output_ptrs.as_mut_ptr();
+
+        // SAFETY: ProcessData is a C struct; zeroed is a valid state (null pointers).
+        let mut data: ProcessData = unsafe { std::mem::zeroed() };
+        data.processMode = ProcessModes_::kRealtime as int32;
+        data.symbolicSampleSize = SymbolicSampleSizes_::kSample32 as int32;
+        data.numSamples = BLOCK as int32;
+        data.numInputs = 1;
+        data.numOutputs = 1;
+        data.inputs = &mut input_bus;
+        data.outputs = &mut output_bus;
+        // SAFETY: data and everything it points to live until process() returns.
+        assert_eq!(unsafe { self.processor.process(&mut data) }, kResultOk);
+    }
+
+    /// Switch the lookahead like a generic editor or automation would.
+    fn set_lookahead(&self, on: bool) {
+        let id = OfflineRenderer::<LatencyDescriptor>::new(48_000.0, BLOCK)
+            .parameter_id("lookahead")
+            .expect("lookahead parameter");
+        // SAFETY: Same thread, host call order.
+        let result = unsafe { self.processor.setParamNormalized(id, if on { 1.0 } else { 0.0 }) };
+        assert_eq!(result, kResultOk);
+    }
+
+    fn latency(&self) -> u32 {
+        // SAFETY: Same thread, host call order.
+        unsafe { self.processor.getLatencySamples() }
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        // SAFETY: Same thread, host call order.
+        unsafe {
+            self.processor.setProcessing(0);
+            self.processor.setActive(0);
+            self.processor.setComponentHandler(ptr::null_mut());
+            self.processor.terminate();
+        }
+    }
+}
+
+fn latency_changed() -> int32 {
+    RestartFlags_::kLatencyChanged as int32
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[test]
+fn latency_change_from_process_restarts_the_component() {
+    let host = Host::new(ptr::null_mut());
+    host.process();
+    assert_eq!(host.latency(), 0);
+    assert!(host.restarts.take().is_empty());
+
+    // The change happens in process(); the next main-thread call forwards it
+    host.set_lookahead(true);
+    assert!(host.restarts.take().is_empty());
+    host.process();
+    assert_eq!(host.latency(), LOOKAHEAD);
+    host.set_lookahead(true);
+    assert_eq!(host.restarts.take(), [latency_changed()]);
+
+    // Announced once
+    host.set_lookahead(true);
+    assert!(host.restarts.take().is_empty());
+}
+
+#[cfg(target_os = "linux")]
+mod run_loop {
+    use super::*;
+    use beamer::vst3_impl::vst3::Steinberg::Linux::*;
+
+    /// A host context offering `IRunLoop`. The test fires the registered
+    /// timers by hand.
+    #[derive(Default)]
+    pub struct RunLoop {
+        timers: Mutex<Vec<ComPtr<ITimerHandler>>>,
+    }
+
+    impl Class for RunLoop {
+        type Interfaces = (IRunLoop,);
+    }
+
+    impl IRunLoopTrait for RunLoop {
+        unsafe fn registerEventHandler(&self, _handler: *mut IEventHandler, _fd: FileDescriptor) -> tresult {
+            kResultFalse
+        }
+
+        unsafe fn unregisterEventHandler(&self, _handler: *mut IEventHandler) -> tresult {
+            kResultFalse
+        }
+
+        unsafe fn registerTimer(&self, handler: *mut ITimerHandler, _milliseconds: TimerInterval) -> tresult {
+            // SAFETY: The plugin passes a valid handler.
+            match unsafe { beamer::vst3_impl::vst3::ComRef::from_raw(handler) } {
+                Some(handler) => {
+                    self.timers.lock().unwrap().push(handler.to_com_ptr());
+                    kResultOk
+                }
+                None => kInvalidArgument,
+            }
+        }
+
+        unsafe fn unregisterTimer(&self, handler: *mut ITimerHandler) -> tresult {
+            self.timers.lock().unwrap().retain(|timer| timer.as_ptr() != handler);
+            kResultOk
+        }
+    }
+
+    impl RunLoop {
+        pub fn fire(&self) {
+            let timers = self.timers.lock().unwrap().clone();
+            for timer in timers {
+                // SAFETY: The timer is registered and valid.
+                unsafe { timer.onTimer() };
+            }
+        }
+
+        pub fn timer_count(&self) -> usize {
+            self.timers.lock().unwrap().len()
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn run_loop_timer_forwards_latency_changes_without_an_editor() {
+    use beamer::vst3_impl::vst3::Steinberg::Linux::IRunLoop;
+
+    let run_loop = ComWrapper::new(run_loop::RunLoop::default());
+    let context = run_loop.to_com_ptr::<IRunLoop>().unwrap();
+    let host = Host::new(context.as_ptr().cast());
+    assert_eq!(run_loop.timer_count(), 1);
+
+    host.set_lookahead(true);
+    host.process();
+    assert!(host.restarts.take().is_empty());
+    // No editor, no host call: the timer forwards the change
+    run_loop.fire();
+    assert_eq!(host.restarts.take(), [latency_changed()]);
+    assert_eq!(host.latency(), LOOKAHEAD);
+    run_loop.fire();
+    assert!(host.restarts.take().is_empty());
+
+    drop(host);
+    assert_eq!(run_loop.timer_count(), 0);
+}
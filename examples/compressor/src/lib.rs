@@ -16,6 +16,7 @@
 //! - `PowerMapper` via `kind = "db_log"` for logarithmic-feel dB mapping
 //! - Linear smoothing (`smoothing = "linear:50.0"`)
 //! - Sidechain input for external key signal
+//! - Randomization constraints (`randomize = 0.0..=12.0`, `randomize = "off"`)
 //! - `SampleRate` setup for sample-rate-dependent initialization
 //!
 //! # DSP Overview
@@ -136,7 +137,8 @@ pub struct CompressorParameters {
         name = "Makeup Gain",
         default = 0.0,
         range = 0.0..=24.0,
-        kind = "db"
+        kind = "db",
+        randomize = 0.0..=12.0
     )]
     pub makeup_gain: FloatParameter,
 
@@ -153,7 +155,7 @@ pub struct CompressorParameters {
     // =========================================================================
 
     /// Use sidechain input for detection signal.
-    #[parameter(id = "sidechain", name = "Sidechain", default = false)]
+    #[parameter(id = "sidechain", name = "Sidechain", default = false, randomize = "off")]
     pub use_sidechain: BoolParameter,
 }
 
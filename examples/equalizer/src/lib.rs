@@ -14,114 +14,11 @@
 //! - `FloatParameter` with dB scaling via `kind = "db"`
 //! - Flat parameter groups via `group = "..."` attribute
 //! - Stereo bus configuration via `input_bus_info()` / `output_bus_info()` overrides
-//! - Biquad filters using standard bilinear transform mathematics
+//! - Framework `BiquadCoeffs` / `BiquadState` filters
 //! - Generic f32/f64 processing via `Sample` trait
 
 use beamer::prelude::*;
 
-/// Pi constant for filter calculations
-const PI: f64 = std::f64::consts::PI;
-
-// =============================================================================
-// Biquad Filter
-// =============================================================================
-
-/// Biquad filter state (Direct Form II Transposed).
-///
-/// Stores the two delay elements needed for the biquad difference equation.
-#[derive(Default, Clone, Copy)]
-struct BiquadState {
-    z1: f64,
-    z2: f64,
-}
-
-impl BiquadState {
-    /// Process a single sample through the filter.
-    ///
-    /// Uses Direct Form II Transposed structure:
-    /// ```text
-    /// y[n] = b0*x[n] + z1
-    /// z1 = b1*x[n] - a1*y[n] + z2
-    /// z2 = b2*x[n] - a2*y[n]
-    /// ```
-    #[inline]
-    fn process(&mut self, input: f64, coeffs: &BiquadCoeffs) -> f64 {
-        let output = coeffs.b0 * input + self.z1;
-        self.z1 = coeffs.b1 * input - coeffs.a1 * output + self.z2;
-        self.z2 = coeffs.b2 * input - coeffs.a2 * output;
-        output
-    }
-}
-
-/// Biquad filter coefficients.
-///
-/// Normalized coefficients where a0 = 1 (already divided out).
-#[derive(Clone, Copy)]
-struct BiquadCoeffs {
-    b0: f64,
-    b1: f64,
-    b2: f64,
-    a1: f64,
-    a2: f64,
-}
-
-impl Default for BiquadCoeffs {
-    /// Default to passthrough (unity gain, no filtering).
-    fn default() -> Self {
-        Self {
-            b0: 1.0,
-            b1: 0.0,
-            b2: 0.0,
-            a1: 0.0,
-            a2: 0.0,
-        }
-    }
-}
-
-impl BiquadCoeffs {
-    /// Clamp frequency to prevent instability near Nyquist.
-    ///
-    /// When filter frequency approaches Nyquist (sample_rate / 2), the bilinear
-    /// transform produces unstable or undefined coefficients. Clamping to 49%
-    /// of sample rate provides a safe margin.
-    #[inline]
-    fn clamp_frequency(freq: f64, sample_rate: f64) -> f64 {
-        freq.min(sample_rate * 0.49)
-    }
-
-    /// Calculate peaking (bell) filter coefficients.
-    ///
-    /// Derived from bilinear transform of analog parametric EQ prototype.
-    /// Q controls bandwidth (higher Q = narrower peak).
-    /// Frequency is clamped to 49% of sample rate to prevent Nyquist instability.
-    /// Q is clamped to minimum 0.01 to prevent division by zero.
-    fn peak(freq: f64, gain_db: f64, q: f64, sample_rate: f64) -> Self {
-        // Clamp frequency to prevent instability near Nyquist
-        let freq = Self::clamp_frequency(freq, sample_rate);
-
-        // Clamp Q to prevent division by zero or near-zero values
-        let q = q.max(0.01);
-
-        let a = 10.0_f64.powf(gain_db / 40.0);
-        let w0 = 2.0 * PI * freq / sample_rate;
-        let cos_w0 = w0.cos();
-        let sin_w0 = w0.sin();
-
-        // Bandwidth parameter: alpha = sin(w0) / (2*Q)
-        let alpha = sin_w0 / (2.0 * q);
-
-        let a0 = 1.0 + alpha / a;
-
-        Self {
-            b0: (1.0 + alpha * a) / a0,
-            b1: (-2.0 * cos_w0) / a0,
-            b2: (1.0 - alpha * a) / a0,
-            a1: (-2.0 * cos_w0) / a0,
-            a2: (1.0 - alpha / a) / a0,
-        }
-    }
-}
-
 // =============================================================================
 // Parameters
 // =============================================================================
@@ -0,0 +1,247 @@
+//! Beamer Looper - Example transport-synced loop recorder.
+//!
+//! Records the input into a loop, plays it back under the live signal and
+//! overdubs on top. While the host plays, commands wait for the next bar (or
+//! beat), so loops start on the grid and come out in whole bars. The loop is
+//! saved with the project and restored on a background thread.
+//!
+//! # Features Demonstrated
+//!
+//! - `LooperHandle` / `Looper` with a preallocated 60 second loop buffer
+//! - Bar and beat quantization from the host transport
+//! - `Processor::transport_event()` to keep playback in phase after a locate
+//! - Custom `save_state()` / `load_state()` with a `StateLoader` restoring the loop
+//! - `#[beamer::rpc]` with a plain HTML GUI polling the loop waveform
+//!
+//! # Usage
+//!
+//! Press Record, play something, and press Record (or Play) again to close
+//! the loop. Overdub layers on top; Stop and Clear do what they say.
+
+use std::sync::Arc;
+
+use beamer::prelude::*;
+
+/// Longest loop in seconds.
+const MAX_LOOP_SECONDS: f64 = 60.0;
+
+// =============================================================================
+// Enum Types for Parameter Choices
+// =============================================================================
+
+/// Grid that loop commands snap to while the host is playing.
+#[derive(Copy, Clone, PartialEq, EnumParameter)]
+pub enum Quantize {
+    /// Act immediately
+    #[name = "Off"]
+    Off,
+    /// Next beat
+    #[name = "Beat"]
+    Beat,
+    /// Next bar line
+    #[default]
+    #[name = "Bar"]
+    Bar,
+}
+
+impl Quantize {
+    fn to_loop_quantize(self) -> LoopQuantize {
+        match self {
+            Quantize::Off => LoopQuantize::Off,
+            Quantize::Beat => LoopQuantize::Beat,
+            Quantize::Bar => LoopQuantize::Bar,
+        }
+    }
+}
+
+// =============================================================================
+// Parameters
+// =============================================================================
+
+/// Looper parameters.
+#[derive(Parameters)]
+pub struct LooperParameters {
+    /// Command quantization (Off, Beat, Bar).
+    #[parameter(id = "quantize", name = "Quantize")]
+    pub quantize: EnumParameter<Quantize>,
+
+    /// Level of the loop in the output.
+    #[parameter(id = "loop_level", name = "Loop Level", default = 0.0, range = -60.0..=6.0, kind = "db")]
+    pub loop_level: FloatParameter,
+}
+
+// =============================================================================
+// Descriptor
+// =============================================================================
+
+/// Looper descriptor (unprepared state).
+///
+/// Owns the looper handle so the loop survives re-preparation and can be
+/// shared with the WebView handler.
+#[beamer::export]
+#[derive(HasParameters)]
+pub struct LooperDescriptor {
+    #[parameters]
+    pub parameters: LooperParameters,
+
+    looper: LooperHandle,
+}
+
+impl Default for LooperDescriptor {
+    fn default() -> Self {
+        Self {
+            parameters: LooperParameters::default(),
+            looper: LooperHandle::new(LooperConfig::default().with_max_length(MAX_LOOP_SECONDS)),
+        }
+    }
+}
+
+impl Descriptor for LooperDescriptor {
+    type Setup = (SampleRate, MainOutputChannels);
+    type Processor = LooperProcessor;
+
+    fn prepare(mut self, (sample_rate, channels): (SampleRate, MainOutputChannels)) -> LooperProcessor {
+        self.parameters.set_sample_rate(sample_rate.hz());
+
+        LooperProcessor {
+            parameters: self.parameters,
+            recorder: Looper::new(&self.looper, sample_rate.hz(), channels.0 as usize),
+            looper: self.looper,
+            loader: StateLoader::new(),
+        }
+    }
+
+    fn webview_handler(&self) -> Option<Arc<dyn WebViewHandler>> {
+        Some(Arc::new(LooperHandler {
+            looper: self.looper.clone(),
+        }))
+    }
+}
+
+// =============================================================================
+// WebView Handler
+// =============================================================================
+
+/// Loop transport for the GUI.
+///
+/// The GUI polls `status()` to follow the playhead and the waveform while
+/// recording.
+struct LooperHandler {
+    looper: LooperHandle,
+}
+
+#[beamer::rpc(client = true)]
+impl LooperHandler {
+    /// Start recording, or close the recording and play it.
+    pub fn record(&self) {
+        self.looper.record();
+    }
+
+    /// Play the loop, or end an overdub.
+    pub fn play(&self) {
+        self.looper.play();
+    }
+
+    /// Toggle overdubbing.
+    pub fn overdub(&self) {
+        self.looper.overdub();
+    }
+
+    /// Stop playback.
+    pub fn stop(&self) {
+        self.looper.stop();
+    }
+
+    /// Erase the loop.
+    pub fn clear(&self) {
+        self.looper.clear();
+    }
+
+    /// State, pending command, position, length and waveform.
+    pub fn status(&self) -> serde_json::Value {
+        self.looper.status_json()
+    }
+}
+
+// =============================================================================
+// Processor
+// =============================================================================
+
+/// Looper processor (prepared state).
+#[derive(HasParameters)]
+pub struct LooperProcessor {
+    #[parameters]
+    pub parameters: LooperParameters,
+
+    /// Audio-thread side of the looper.
+    recorder: Looper,
+    /// Kept for `unprepare()` and for saving the loop.
+    looper: LooperHandle,
+    /// Restores a saved loop off the host's thread.
+    loader: StateLoader<bool>,
+}
+
+impl LooperProcessor {
+    /// Generic processing implementation for both f32 and f64.
+    fn process_generic<S: Sample>(&mut self, buffer: &mut Buffer<S>, context: &ProcessContext) {
+        // The result only matters to the worker; take it so the slot is free
+        let _ = self.loader.try_take();
+
+        self.recorder.set_quantize(self.parameters.quantize.get().to_loop_quantize());
+        self.recorder.set_level(self.parameters.loop_level.as_linear() as f32);
+        self.recorder.process(buffer, context);
+    }
+}
+
+impl Processor for LooperProcessor {
+    type Descriptor = LooperDescriptor;
+
+    fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, context: &ProcessContext) {
+        self.process_generic(buffer, context);
+    }
+
+    fn supports_double_precision(&self) -> bool {
+        true
+    }
+
+    fn process_f64(&mut self, buffer: &mut Buffer<f64>, _aux: &mut AuxiliaryBuffers<f64>, context: &ProcessContext) {
+        self.process_generic(buffer, context);
+    }
+
+    fn transport_event(&mut self, event: TransportEvent, transport: &Transport) {
+        self.recorder.transport_event(event, transport);
+    }
+
+    /// Parameters, prefixed with their length, followed by the loop audio.
+    fn save_state(&self) -> PluginResult<Vec<u8>> {
+        let parameters = self.parameters.save_state();
+        let mut data = Vec::with_capacity(4 + parameters.len());
+        data.extend_from_slice(&(parameters.len() as u32).to_le_bytes());
+        data.extend_from_slice(&parameters);
+        data.extend_from_slice(&self.looper.save());
+        Ok(data)
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> PluginResult<()> {
+        let (parameters, audio) = data
+            .get(..4)
+            .map(|len| u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+            .and_then(|len| Some((data.get(4..4 + len)?, &data[4 + len..])))
+            .ok_or_else(|| PluginError::StateError("truncated looper state".to_string()))?;
+        self.parameters.load_state(parameters).map_err(PluginError::StateError)?;
+
+        if !audio.is_empty() {
+            let audio = audio.to_vec();
+            let looper = self.looper.clone();
+            self.loader.spawn(move |status| looper.restore(&audio, status));
+        }
+        Ok(())
+    }
+
+    fn unprepare(self) -> LooperDescriptor {
+        LooperDescriptor {
+            parameters: self.parameters,
+            looper: self.looper,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// Generated by #[beamer::rpc]. Do not edit.
+
+/**
+ * Start recording, or close the recording and play it.
+ */
+export declare function record(): Promise<void>;
+
+/**
+ * Play the loop, or end an overdub.
+ */
+export declare function play(): Promise<void>;
+
+/**
+ * Toggle overdubbing.
+ */
+export declare function overdub(): Promise<void>;
+
+/**
+ * Stop playback.
+ */
+export declare function stop(): Promise<void>;
+
+/**
+ * Erase the loop.
+ */
+export declare function clear(): Promise<void>;
+
+/**
+ * State, pending command, position, length and waveform.
+ */
+export declare function status(): Promise<unknown>;
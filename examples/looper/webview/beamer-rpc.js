@@ -0,0 +1,25 @@
+// Generated by #[beamer::rpc]. Do not edit.
+
+export function record() {
+  return window.__BEAMER__.invoke("record");
+}
+
+export function play() {
+  return window.__BEAMER__.invoke("play");
+}
+
+export function overdub() {
+  return window.__BEAMER__.invoke("overdub");
+}
+
+export function stop() {
+  return window.__BEAMER__.invoke("stop");
+}
+
+export function clear() {
+  return window.__BEAMER__.invoke("clear");
+}
+
+export function status() {
+  return window.__BEAMER__.invoke("status");
+}
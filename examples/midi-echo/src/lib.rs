@@ -0,0 +1,191 @@
+//! Beamer MIDI Echo - Example MIDI effect built from the MIDI effect utilities.
+//!
+//! Humanizes incoming notes, then repeats them with decaying velocity. The
+//! result goes to the plugin's MIDI output, so put it in front of an
+//! instrument (or record its output) to hear the echoes.
+//!
+//! # Features Demonstrated
+//!
+//! - `MidiHumanize` with a fixed seed, so renders are repeatable
+//! - `MidiEcho` repeating notes across block boundaries via `MidiScheduler`
+//! - `Processor::begin_block()` to give `process_midi()` the block length
+//! - `Processor::panic()` and `set_active()` dropping pending echoes
+//! - MIDI output on both formats (VST3 event output bus, AU MIDI output)
+//!
+//! # Signal Flow
+//!
+//! ```text
+//! MIDI in → MidiHumanize → scratch buffer → MidiEcho → MIDI out
+//! ```
+
+use beamer::prelude::*;
+
+/// Seed of the humanize random sequence.
+const HUMANIZE_SEED: u64 = 0x5eed;
+
+// =============================================================================
+// Parameters
+// =============================================================================
+
+/// Echo parameters.
+#[derive(Parameters)]
+pub struct EchoParameters {
+    /// Time between repeats.
+    #[parameter(id = "echo_time", name = "Time", default = 250.0, range = 10.0..=2000.0, kind = "ms")]
+    pub time: FloatParameter,
+
+    /// Velocity of each repeat relative to the previous one.
+    #[parameter(id = "echo_feedback", name = "Feedback", default = 0.6, range = 0.0..=1.0, kind = "percent")]
+    pub feedback: FloatParameter,
+
+    /// Maximum number of repeats per note.
+    #[parameter(id = "echo_repeats", name = "Repeats", default = 4, range = 1..=16)]
+    pub repeats: IntParameter,
+}
+
+/// Humanize parameters.
+#[derive(Parameters)]
+pub struct HumanizeParameters {
+    /// Maximum random note delay.
+    #[parameter(id = "humanize_timing", name = "Timing", default = 0.0, range = 0.0..=50.0, kind = "ms")]
+    pub timing: FloatParameter,
+
+    /// Random velocity variation.
+    #[parameter(id = "humanize_velocity", name = "Velocity", default = 0.0, range = 0.0..=0.5, kind = "percent")]
+    pub velocity: FloatParameter,
+
+    /// Random aftertouch variation.
+    #[parameter(id = "humanize_aftertouch", name = "Aftertouch", default = 0.0, range = 0.0..=0.5, kind = "percent")]
+    pub aftertouch: FloatParameter,
+}
+
+/// MIDI Echo plugin parameters.
+#[derive(Parameters)]
+pub struct MidiEchoParameters {
+    /// Global bypass - passes MIDI through unchanged
+    #[parameter(id = "bypass", bypass)]
+    pub bypass: BoolParameter,
+
+    #[nested(group = "Echo")]
+    pub echo: EchoParameters,
+
+    #[nested(group = "Humanize")]
+    pub humanize: HumanizeParameters,
+}
+
+// =============================================================================
+// Descriptor
+// =============================================================================
+
+/// MIDI Echo descriptor (unprepared state).
+#[beamer::export]
+#[derive(Default, HasParameters)]
+pub struct MidiEchoDescriptor {
+    #[parameters]
+    pub parameters: MidiEchoParameters,
+}
+
+impl Descriptor for MidiEchoDescriptor {
+    type Setup = SampleRate;
+    type Processor = MidiEchoProcessor;
+
+    fn prepare(self, setup: SampleRate) -> MidiEchoProcessor {
+        MidiEchoProcessor {
+            parameters: self.parameters,
+            sample_rate: setup.hz(),
+            block_samples: 0,
+            humanize: MidiHumanize::new(HUMANIZE_SEED),
+            echo: MidiEcho::new(0, 0.0, 0),
+            humanized: MidiBuffer::new_boxed(),
+        }
+    }
+
+    fn wants_midi(&self) -> bool {
+        true // Echoes go to the MIDI output
+    }
+}
+
+// =============================================================================
+// Processor
+// =============================================================================
+
+/// MIDI Echo processor (prepared state).
+#[derive(HasParameters)]
+pub struct MidiEchoProcessor {
+    #[parameters]
+    parameters: MidiEchoParameters,
+
+    sample_rate: f64,
+    /// Length of the current block, from `begin_block()`.
+    block_samples: usize,
+    humanize: MidiHumanize,
+    echo: MidiEcho,
+    /// Humanized events on their way to the echo (preallocated).
+    humanized: Box<MidiBuffer>,
+}
+
+impl MidiEchoProcessor {
+    /// Apply the current parameter values to the effects.
+    fn update_effects(&mut self) {
+        let humanize = &self.parameters.humanize;
+        self.humanize.set_timing_ms(humanize.timing.get(), self.sample_rate);
+        self.humanize.set_velocity(humanize.velocity.get() as f32);
+        self.humanize.set_aftertouch(humanize.aftertouch.get() as f32);
+
+        let echo = &self.parameters.echo;
+        self.echo.set_delay_samples((echo.time.get() * 0.001 * self.sample_rate).round() as u32);
+        self.echo.set_feedback(echo.feedback.get() as f32);
+        self.echo.set_max_repeats(echo.repeats.get() as u8);
+    }
+
+    /// Drop pending echoes and delayed notes.
+    fn reset_effects(&mut self) {
+        self.humanize.reset();
+        self.echo.reset();
+    }
+}
+
+impl Processor for MidiEchoProcessor {
+    type Descriptor = MidiEchoDescriptor;
+
+    fn process(&mut self, buffer: &mut Buffer, _aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+        // Pass audio through unchanged
+        buffer.copy_to_output();
+    }
+
+    fn begin_block(&mut self, num_samples: usize) {
+        self.block_samples = num_samples;
+    }
+
+    fn process_midi(&mut self, input: &[MidiEvent], output: &mut MidiBuffer) {
+        // Bypassed: new events pass through, echoes already under way finish
+        // (so none of their note-offs get stuck)
+        let input = if self.parameters.bypass.get() {
+            for event in input {
+                output.push(event.clone());
+            }
+            &[]
+        } else {
+            input
+        };
+
+        self.update_effects();
+        self.humanized.clear();
+        self.humanize.process(input, self.block_samples, &mut self.humanized);
+        self.echo.process(self.humanized.as_slice(), self.block_samples, output);
+    }
+
+    fn wants_midi(&self) -> bool {
+        true
+    }
+
+    fn panic(&mut self) {
+        self.reset_effects();
+    }
+
+    fn set_active(&mut self, active: bool) {
+        if active {
+            self.reset_effects();
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Beamer Room Analyzer - Example measurement and room correction plugin.
+//!
+//! Plays a pink noise burst on the main output, records a measurement
+//! microphone on the aux input, and shows the measured response in a WebView
+//! GUI. "Apply Correction" turns the response into a 10-band octave EQ that
+//! flattens it, applied to the main signal when Correction is enabled.
+//!
+//! # Features Demonstrated
+//!
+//! - `MeasurementHandle` / `Measurement` for test-signal orchestration
+//! - Background analysis (delay estimate + transfer function via `Fft`)
+//! - Aux input bus for the measurement microphone
+//! - Sharing state between the descriptor, the processor and a WebView handler
+//! - `#[beamer::rpc]` with a plain HTML GUI polling measurement progress
+//! - Framework `BiquadCoeffs` / `BiquadState` filters driven from the GUI thread
+//!
+//! # Usage
+//!
+//! Route a measurement microphone to the "Measurement Mic" sidechain input,
+//! press Measure in the GUI, then Apply Correction once the curve appears.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use beamer::prelude::*;
+
+/// Octave band centers of the correction EQ in Hz.
+const BAND_CENTERS: [f64; BANDS] = [31.5, 63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0];
+
+/// Number of correction bands.
+const BANDS: usize = 10;
+
+/// Q of an octave-wide peak filter.
+const OCTAVE_Q: f64 = 1.41;
+
+/// Largest correction cut in dB.
+const MAX_CUT_DB: f64 = 12.0;
+
+/// Largest correction boost in dB. Boosting room nulls wastes headroom, so
+/// boosts are limited much more than cuts.
+const MAX_BOOST_DB: f64 = 6.0;
+
+// =============================================================================
+// Parameters
+// =============================================================================
+
+/// Room analyzer parameters.
+#[derive(Parameters)]
+pub struct RoomAnalyzerParameters {
+    /// Apply the correction EQ to the main signal.
+    #[parameter(id = "correction", name = "Correction", default = false)]
+    pub correction: BoolParameter,
+
+    /// Output level after correction.
+    #[parameter(id = "output", name = "Output", default = 0.0, range = -24.0..=12.0, kind = "db")]
+    pub output: FloatParameter,
+}
+
+// =============================================================================
+// Correction Curve (shared between GUI and audio thread)
+// =============================================================================
+
+/// Correction band gains, written by the WebView handler and read by the
+/// processor. Gains are f64 bits; `version` changes on every update so the
+/// processor only recomputes coefficients when needed.
+#[derive(Default)]
+pub struct CorrectionCurve {
+    gains: [AtomicU64; BANDS],
+    version: AtomicU64,
+}
+
+impl CorrectionCurve {
+    /// Store new band gains in dB.
+    fn set(&self, gains: &[f64; BANDS]) {
+        for (slot, gain) in self.gains.iter().zip(gains) {
+            slot.store(gain.to_bits(), Ordering::Relaxed);
+        }
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Current band gains in dB.
+    fn gains(&self) -> [f64; BANDS] {
+        std::array::from_fn(|i| f64::from_bits(self.gains[i].load(Ordering::Relaxed)))
+    }
+
+    fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+/// Per-band gains that bring the measured response to the 500 Hz - 2 kHz
+/// average. Bands without a trustworthy measurement are left flat.
+fn correction_gains(response: &TransferFunction) -> [f64; BANDS] {
+    let reference = response.band_average_db(500.0, 2000.0).unwrap_or(0.0);
+    std::array::from_fn(|i| {
+        let center = BAND_CENTERS[i];
+        response
+            .band_average_db(center / std::f64::consts::SQRT_2, center * std::f64::consts::SQRT_2)
+            .map_or(0.0, |level| (reference - level).clamp(-MAX_CUT_DB, MAX_BOOST_DB))
+    })
+}
+
+// =============================================================================
+// Descriptor
+// =============================================================================
+
+/// Room analyzer descriptor (unprepared state).
+///
+/// Owns the measurement handle and correction curve so they survive
+/// re-preparation and can be shared with the WebView handler.
+#[beamer::export]
+#[derive(Default, HasParameters)]
+pub struct RoomAnalyzerDescriptor {
+    #[parameters]
+    pub parameters: RoomAnalyzerParameters,
+
+    measurement: MeasurementHandle,
+    correction: Arc<CorrectionCurve>,
+}
+
+impl Descriptor for RoomAnalyzerDescriptor {
+    type Setup = SampleRate;
+    type Processor = RoomAnalyzerProcessor;
+
+    fn prepare(mut self, sample_rate: SampleRate) -> RoomAnalyzerProcessor {
+        self.parameters.set_sample_rate(sample_rate.hz());
+
+        RoomAnalyzerProcessor {
+            parameters: self.parameters,
+            recorder: Measurement::new(&self.measurement, sample_rate.hz()),
+            measurement: self.measurement,
+            correction: self.correction,
+            // Forces a coefficient update on the first block
+            correction_version: u64::MAX,
+            sample_rate: sample_rate.hz(),
+            coeffs: [BiquadCoeffs::default(); BANDS],
+            states: [[BiquadState::default(); BANDS]; 2],
+        }
+    }
+
+    fn webview_handler(&self) -> Option<Arc<dyn WebViewHandler>> {
+        Some(Arc::new(AnalyzerHandler {
+            measurement: self.measurement.clone(),
+            correction: Arc::clone(&self.correction),
+        }))
+    }
+
+    // =========================================================================
+    // Bus Configuration: stereo main + mono measurement mic
+    // =========================================================================
+
+    fn input_bus_count(&self) -> usize {
+        2
+    }
+
+    fn input_bus_info(&self, index: usize) -> Option<BusInfo> {
+        match index {
+            0 => Some(BusInfo::stereo("Input")),
+            1 => Some(BusInfo::aux("Measurement Mic", 1)),
+            _ => None,
+        }
+    }
+}
+
+// =============================================================================
+// WebView Handler
+// =============================================================================
+
+/// Measurement control for the GUI.
+///
+/// The GUI polls `measurementStatus()` while a measurement runs, since the
+/// analysis finishes on a worker thread.
+struct AnalyzerHandler {
+    measurement: MeasurementHandle,
+    correction: Arc<CorrectionCurve>,
+}
+
+#[beamer::rpc(client = true)]
+impl AnalyzerHandler {
+    /// Play the test burst and record the measurement mic.
+    pub fn start_measurement(&self) -> bool {
+        self.measurement.start()
+    }
+
+    /// Stop a running measurement.
+    pub fn cancel_measurement(&self) {
+        self.measurement.cancel();
+    }
+
+    /// State, progress and (once done) the measured response.
+    pub fn measurement_status(&self) -> serde_json::Value {
+        self.measurement.status_json()
+    }
+
+    /// Derive correction gains from the last measurement. Returns the gains.
+    pub fn apply_correction(&self) -> Result<Vec<f64>, String> {
+        let response = self.measurement.result().ok_or("no measurement yet")?;
+        let gains = correction_gains(&response);
+        self.correction.set(&gains);
+        Ok(gains.to_vec())
+    }
+
+    /// Reset the correction EQ to flat.
+    pub fn clear_correction(&self) {
+        self.correction.set(&[0.0; BANDS]);
+    }
+
+    /// Current correction gains in dB, one per octave band.
+    pub fn correction_gains(&self) -> Vec<f64> {
+        self.correction.gains().to_vec()
+    }
+}
+
+// =============================================================================
+// Processor
+// =============================================================================
+
+/// Room analyzer processor (prepared state).
+#[derive(HasParameters)]
+pub struct RoomAnalyzerProcessor {
+    #[parameters]
+    pub parameters: RoomAnalyzerParameters,
+
+    /// Audio-thread side of the measurement.
+    recorder: Measurement,
+    /// Kept for `unprepare()` so the handle survives sample rate changes.
+    measurement: MeasurementHandle,
+    correction: Arc<CorrectionCurve>,
+    /// Correction version the coefficients were computed for.
+    correction_version: u64,
+    sample_rate: f64,
+    coeffs: [BiquadCoeffs; BANDS],
+    /// Filter state per channel and band.
+    states: [[BiquadState; BANDS]; 2],
+}
+
+impl RoomAnalyzerProcessor {
+    /// Recompute the correction filters if the GUI changed the curve.
+    fn update_coefficients(&mut self) {
+        let version = self.correction.version();
+        if version == self.correction_version {
+            return;
+        }
+        self.correction_version = version;
+        let gains = self.correction.gains();
+        let nyquist = self.sample_rate * 0.5;
+        for (coeffs, (&center, &gain)) in self.coeffs.iter_mut().zip(BAND_CENTERS.iter().zip(&gains)) {
+            *coeffs = if center < nyquist * 0.9 {
+                BiquadCoeffs::peak(center, gain, OCTAVE_Q, self.sample_rate)
+            } else {
+                BiquadCoeffs::default()
+            };
+        }
+    }
+
+    /// Generic processing implementation for both f32 and f64.
+    fn process_generic<S: Sample>(&mut self, buffer: &mut Buffer<S>, aux: &mut AuxiliaryBuffers<S>) {
+        // While measuring, the outputs carry the test signal
+        let sidechain = aux.sidechain();
+        let mic = sidechain.as_ref().map_or(&[][..], |sc| sc.input(0));
+        if self.recorder.process(buffer, mic) {
+            return;
+        }
+
+        self.update_coefficients();
+        let correction = self.parameters.correction.get();
+        let gain = self.parameters.output.as_linear();
+
+        for (ch, (input, out)) in buffer.zip_channels().enumerate() {
+            let states = &mut self.states[ch.min(1)];
+            for (in_sample, out_sample) in input.iter().zip(out.iter_mut()) {
+                let mut sample = in_sample.to_f64();
+                if correction {
+                    for (state, coeffs) in states.iter_mut().zip(&self.coeffs) {
+                        sample = state.process(sample, coeffs);
+                    }
+                }
+                *out_sample = S::from_f64(sample * gain);
+            }
+        }
+    }
+}
+
+impl Processor for RoomAnalyzerProcessor {
+    type Descriptor = RoomAnalyzerDescriptor;
+
+    fn process(&mut self, buffer: &mut Buffer, aux: &mut AuxiliaryBuffers, _context: &ProcessContext) {
+        self.process_generic(buffer, aux);
+    }
+
+    fn supports_double_precision(&self) -> bool {
+        true
+    }
+
+    fn process_f64(&mut self, buffer: &mut Buffer<f64>, aux: &mut AuxiliaryBuffers<f64>, _context: &ProcessContext) {
+        self.process_generic(buffer, aux);
+    }
+
+    fn set_active(&mut self, active: bool) {
+        if active {
+            self.states = [[BiquadState::default(); BANDS]; 2];
+        }
+    }
+
+    fn unprepare(self) -> RoomAnalyzerDescriptor {
+        RoomAnalyzerDescriptor {
+            parameters: self.parameters,
+            measurement: self.measurement,
+            correction: self.correction,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// Generated by #[beamer::rpc]. Do not edit.
+
+/**
+ * Play the test burst and record the measurement mic.
+ */
+export declare function startMeasurement(): Promise<boolean>;
+
+/**
+ * Stop a running measurement.
+ */
+export declare function cancelMeasurement(): Promise<void>;
+
+/**
+ * State, progress and (once done) the measured response.
+ */
+export declare function measurementStatus(): Promise<unknown>;
+
+/**
+ * Derive correction gains from the last measurement. Returns the gains.
+ */
+export declare function applyCorrection(): Promise<number[]>;
+
+/**
+ * Reset the correction EQ to flat.
+ */
+export declare function clearCorrection(): Promise<void>;
+
+/**
+ * Current correction gains in dB, one per octave band.
+ */
+export declare function correctionGains(): Promise<number[]>;
@@ -0,0 +1,25 @@
+// Generated by #[beamer::rpc]. Do not edit.
+
+export function startMeasurement() {
+  return window.__BEAMER__.invoke("startMeasurement");
+}
+
+export function cancelMeasurement() {
+  return window.__BEAMER__.invoke("cancelMeasurement");
+}
+
+export function measurementStatus() {
+  return window.__BEAMER__.invoke("measurementStatus");
+}
+
+export function applyCorrection() {
+  return window.__BEAMER__.invoke("applyCorrection");
+}
+
+export function clearCorrection() {
+  return window.__BEAMER__.invoke("clearCorrection");
+}
+
+export function correctionGains() {
+  return window.__BEAMER__.invoke("correctionGains");
+}
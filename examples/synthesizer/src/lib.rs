@@ -23,6 +23,7 @@
 //! - Mod wheel (CC 1) to control vibrato depth and filter cutoff
 //! - Polyphonic aftertouch (per-note vibrato control)
 //! - Channel aftertouch (global vibrato control)
+//! - `MidiThru::ExceptConsumed` to forward non-note MIDI downstream
 //! - `SampleRate` setup for sample-rate-dependent initialization
 
 use beamer::prelude::*;
@@ -45,6 +46,9 @@ const VIBRATO_RATE_HZ: f64 = 5.0;
 /// Filter cutoff modulation range in Hz (added to base cutoff when mod wheel is at max)
 const CUTOFF_MOD_RANGE: f64 = 8000.0;
 
+/// Minimum note length in milliseconds, so very short notes still sound
+const MIN_NOTE_MS: f64 = 5.0;
+
 // =============================================================================
 // Enum Types
 // =============================================================================
@@ -172,6 +176,8 @@ impl Descriptor for SynthesizerDescriptor {
             sample_rate: setup.hz(),
             time_counter: 0,
             pending_events: Vec::with_capacity(64),
+            note_gate: NoteGate::from_ms(MIN_NOTE_MS, setup.hz()),
+            gated_events: Vec::with_capacity(64),
             pitch_bend: 0.0,
             mod_wheel: 0.0,
             vibrato_phase: 0.0,
@@ -459,6 +465,10 @@ pub struct SynthesizerProcessor {
     time_counter: u64,
     /// Pending MIDI events for sample-accurate processing
     pending_events: Vec<MidiEvent>,
+    /// Holds back note-offs of very short notes
+    note_gate: NoteGate,
+    /// Pending events after the note gate
+    gated_events: Vec<MidiEvent>,
     /// Current pitch bend value (-1.0 to +1.0)
     pitch_bend: f64,
     /// Current mod wheel value (0.0 to 1.0)
@@ -539,12 +549,15 @@ impl SynthesizerProcessor {
         let waveform = self.parameters.waveform.get();
         let gain = S::from_f64(self.parameters.gain.as_linear());
 
+        // Note-offs of notes shorter than MIN_NOTE_MS move later (possibly
+        // into a later block), so zero-length notes still trigger a voice
+        self.note_gate.process(&self.pending_events, num_samples, &mut self.gated_events);
         let mut event_idx = 0;
 
         for sample_idx in 0..num_samples {
             // Process MIDI events at this sample offset (sample-accurate)
-            while event_idx < self.pending_events.len() {
-                let event = &self.pending_events[event_idx];
+            while event_idx < self.gated_events.len() {
+                let event = &self.gated_events[event_idx];
                 if event.sample_offset as usize <= sample_idx {
                     match &event.event {
                         MidiEventKind::NoteOn(note_on) => {
@@ -701,6 +714,11 @@ impl Processor for SynthesizerProcessor {
         true
     }
 
+    fn midi_thru(&self) -> MidiThru {
+        // Forward CCs, pitch bend, etc. to downstream devices; notes are played here.
+        MidiThru::ExceptConsumed
+    }
+
     fn tail_samples(&self) -> u32 {
         // Return max release time in samples (5 seconds)
         (5.0 * self.sample_rate) as u32
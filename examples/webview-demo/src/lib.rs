@@ -57,22 +57,20 @@ impl Descriptor for WebViewDemoDescriptor {
 // =============================================================================
 
 /// Handles `__BEAMER__.invoke()` calls from JavaScript.
+///
+/// `#[beamer::rpc(client = true)]` generates the `WebViewHandler` impl and
+/// writes a typed client to `webview/src/beamer-rpc.js` (with `beamer-rpc.d.ts`).
 struct DemoHandler;
 
-impl WebViewHandler for DemoHandler {
-    fn on_invoke(
-        &self,
-        method: &str,
-        _args: &[serde_json::Value],
-    ) -> Result<serde_json::Value, String> {
-        match method {
-            "getInfo" => Ok(serde_json::json!({
-                "name": "Beamer WebView Demo",
-                "version": env!("CARGO_PKG_VERSION"),
-                "framework": "Beamer",
-            })),
-            _ => Err(format!("unknown method: {method}")),
-        }
+#[beamer::rpc(client = true)]
+impl DemoHandler {
+    /// Plugin name, version and framework.
+    pub fn get_info(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": "Beamer WebView Demo",
+            "version": env!("CARGO_PKG_VERSION"),
+            "framework": "Beamer",
+        })
     }
 }
 
@@ -1,5 +1,6 @@
 import { useState } from "react";
 import Slider from "./components/Slider";
+import { getInfo } from "./beamer-rpc";
 
 interface PluginInfo {
   name: string;
@@ -12,8 +13,7 @@ function App() {
   const [pluginInfo, setPluginInfo] = useState<PluginInfo | null>(null);
 
   const openAbout = () => {
-    __BEAMER__
-      .invoke("getInfo")
+    getInfo()
       .then((result) => {
         setPluginInfo(result as PluginInfo);
         setShowAbout(true);
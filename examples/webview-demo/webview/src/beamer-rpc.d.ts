@@ -0,0 +1,6 @@
+// Generated by #[beamer::rpc]. Do not edit.
+
+/**
+ * Plugin name, version and framework.
+ */
+export declare function getInfo(): Promise<unknown>;
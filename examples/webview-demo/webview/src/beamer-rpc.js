@@ -0,0 +1,5 @@
+// Generated by #[beamer::rpc]. Do not edit.
+
+export function getInfo() {
+  return window.__BEAMER__.invoke("getInfo");
+}
@@ -254,6 +254,7 @@ function Slider({ type = "circular", paramId, size = 64, label, className }: Sli
 
         <svg
           ref={svgRef}
+          data-beamer-param={paramId}
           viewBox="0 0 64 64"
           width={size}
           height={size}
@@ -26,7 +26,7 @@ export function useBeamerControl(paramId: string): BeamerControl {
 
   const set = useCallback(
     (normalized: number) => {
-      __BEAMER__.params.set(paramId, Math.max(0, Math.min(1, normalized)));
+      __BEAMER__.params.setNormalized(paramId, normalized);
     },
     [paramId],
   );
@@ -11,6 +11,30 @@ interface BeamerParamInfo {
   max: number;
   units: string;
   steps: number;
+  /** Normalized `[min, max]` range for preset randomization, or `null` if excluded. */
+  randomize: [number, number] | null;
+  /** GUI-only parameter, exposed to the host only once promoted. */
+  internal: boolean;
+  /** Parameter group (`0` for ungrouped), see `parameterGroups()`. */
+  groupId: number;
+}
+
+interface BeamerAutomationState {
+  /** Last normalized value delivered by the host, or `null` if none yet. */
+  hostValue: number | null;
+  /** Last normalized value set from the GUI, or `null` if none yet. */
+  userValue: number | null;
+  /** Host automation currently overrides the user's value. */
+  automated: boolean;
+  /** The user is holding the control (between `beginEdit` and `endEdit`). */
+  touched: boolean;
+}
+
+interface BeamerParamState {
+  /** Normalized value (0.0 - 1.0). */
+  value: number;
+  plainValue: number;
+  displayText: string;
 }
 
 interface BeamerParams {
@@ -18,19 +42,265 @@ interface BeamerParams {
   getPlain(stringId: string): number;
   getDisplayText(stringId: string): string;
   set(stringId: string, value: number): void;
+  /** Clamp to 0.0 - 1.0 and set, unless the parameter already has the value. */
+  setNormalized(stringId: string, value: number): void;
   beginEdit(stringId: string): void;
   endEdit(stringId: string): void;
   on(stringId: string, callback: (value: number) => void): () => void;
+  /** Called with the current state once ready, then on every change. Returns an unsubscribe function. */
+  subscribe(stringId: string, callback: (state: BeamerParamState) => void): () => void;
   all(): BeamerParamInfo[];
   info(stringId: string): BeamerParamInfo | undefined;
+  /** Expose an internal parameter to the host. Resolves to `true` if the host list changed. */
+  promote(stringId: string): Promise<boolean>;
+  /** Hide a promoted parameter from the host again. */
+  demote(stringId: string): Promise<boolean>;
+  /** String IDs of the promoted parameters, in promotion order. */
+  promoted(): Promise<string[]>;
+  /** Host automation versus user value, or `null` for an unknown parameter. */
+  automation(stringId: string): Promise<BeamerAutomationState | null>;
+  /** Mirror a parameter onto the element's ARIA attributes. Returns an unbind function. */
+  bindAccessibility(element: HTMLElement, stringId: string): () => void;
+}
+
+interface BeamerControllerPage {
+  name: string;
+  /** Parameter string ID per knob, `null` for an unassigned knob. */
+  parameters: (string | null)[];
+}
+
+interface BeamerControllerPages {
+  pages: BeamerControllerPage[];
+  selected: number;
+  knobCcs: number[];
+}
+
+/** `"velocity"`, `"pitchBend"`, `"aftertouch"` or `"cc0"` to `"cc127"`. */
+type BeamerCurveTarget = string;
+
+type BeamerCurveShape =
+  | { type: "linear" }
+  | { type: "power"; exponent: number }
+  | { type: "sCurve"; steepness: number }
+  | { type: "points"; points: [number, number][]; smooth?: boolean };
+
+interface BeamerInstanceName {
+  instanceName: string | null;
+  displayName: string | null;
+}
+
+interface BeamerAutosaveRecovery {
+  available: boolean;
+  /** When the snapshot was written (milliseconds since the epoch), or `null`. */
+  savedAt: number | null;
+}
+
+interface BeamerParameterGroup {
+  id: number;
+  name: string;
+  /** Parent group ID (`0` for top-level groups). */
+  parentId: number;
+  /** Sort key among sibling groups (ascending; ties keep list order). */
+  order: number;
+  /** The section starts collapsed. */
+  collapsed: boolean;
+  /** Icon name for the section header, or `null`. */
+  icon: string | null;
+}
+
+interface BeamerActivation {
+  /** The plugin has a license check. */
+  enabled: boolean;
+  state: "pending" | "activated" | "unactivated";
+  /** A check is running. */
+  running: boolean;
+  /** Why the last check failed, or `null`. */
+  message: string | null;
+  /** What the output does while not activated. */
+  policy: "silence" | "noise" | "full";
+}
+
+interface BeamerPlayhead {
+  /** Project position in quarter notes, extrapolated to the frame. */
+  beats: number | null;
+  /** Start of the current bar in quarter notes. */
+  barStart: number | null;
+  /** Tempo in BPM. */
+  tempo: number | null;
+  /** Time signature as `[numerator, denominator]`. */
+  timeSignature: [number, number] | null;
+  playing: boolean;
+}
+
+interface BeamerParameterLogEntry {
+  /** When the change happened (milliseconds since the epoch). */
+  time: number;
+  source: "automation" | "host" | "gui" | "midiCc" | "preset" | "state";
+  /** Parameter ID, or the preset index for `"preset"` entries. */
+  id: number;
+  /** Normalized value (absent for preset and state entries). */
+  value?: number;
+  stringId?: string;
+  name?: string;
+  /** Display text of the value. */
+  text?: string;
+}
+
+type BeamerMidiMonitorKind =
+  | "noteOn" | "noteOff" | "polyPressure" | "controlChange" | "pitchBend"
+  | "channelPressure" | "programChange" | "systemRealtime" | "sysEx"
+  | "noteExpression" | "chord" | "scale";
+
+interface BeamerMidiMonitorEntry {
+  /** Position in the monitor, increasing by one per event. */
+  sequence: number;
+  /** When the event was recorded (milliseconds since the epoch). */
+  time: number;
+  direction: "input" | "output";
+  sampleOffset: number;
+  kind: BeamerMidiMonitorKind;
+  /** MIDI channel (0-15), absent for kinds without one. */
+  channel?: number;
+  /** Pitch, controller, program, status byte, SysEx length, expression type or chord/scale mask. */
+  data: number;
+  /** Velocity, controller value, bend, pressure or expression value; the root for chords and scales. */
+  value?: number;
+  /** Host note ID, if the host sent one. */
+  noteId?: number;
+}
+
+interface BeamerMidiMonitorFilter {
+  direction?: "input" | "output";
+  kinds?: BeamerMidiMonitorKind[];
+  channels?: number[];
+}
+
+interface BeamerMidiMonitorRead {
+  entries: BeamerMidiMonitorEntry[];
+  /** Cursor for the next read. */
+  next: number;
+  /** Entries overwritten before they could be read. */
+  dropped: number;
+  /** False if the plugin has no MIDI monitor. */
+  enabled: boolean;
+}
+
+interface BeamerNotification {
+  level: "info" | "warning" | "error";
+  message: string;
+  /** How many identical notifications were merged into this one. */
+  count: number;
+}
+
+/** Payload of `beamer:overrun`: repeated `process()` deadline overruns. */
+interface BeamerOverrunReport {
+  /** Overruns since the previous report. */
+  overruns: number;
+  total: number;
+  /** Slowest call and its deadline, in milliseconds. */
+  worstMs: number;
+  deadlineMs: number;
+  blockSize: number;
+  sampleRate: number;
+}
+
+/** One frame of a `Vectorscope` (`FrameMeters::with_scope()`). */
+interface BeamerScopeFrame {
+  /** Left/right pairs since the previous frame: `[l0, r0, l1, r1, ...]`. */
+  points: number[];
+  /** Largest absolute sample since the previous frame. */
+  peak: number;
+  /** Peak held for the scope's hold time. */
+  peakHold: number;
+}
+
+/** Playable note range and keyboard splits (`Descriptor::note_ranges()`). */
+interface BeamerNoteRanges {
+  /** Lowest and highest playable notes (MIDI note numbers, inclusive). */
+  low: number;
+  high: number;
+  /** Whether the wrapper drops notes outside the range. */
+  filtering: boolean;
+  splits: { name: string; low: number; high: number }[];
 }
 
 interface Beamer {
   readonly ready: Promise<void>;
   readonly params: BeamerParams;
   invoke(method: string, ...args: unknown[]): Promise<unknown>;
+  /** Messages from the plugin. Without a subscriber the runtime shows a toast. */
+  on(event: "beamer:notification", callback: (data: BeamerNotification) => void): () => void;
+  on(event: "beamer:overrun", callback: (data: BeamerOverrunReport) => void): () => void;
+  /** Frames from the plugin's GUI data channels, by channel name, oldest first. */
+  on(event: "audio-data", callback: (data: Record<string, unknown[]>) => void): () => void;
   on(event: string, callback: (data: unknown) => void): () => void;
   emit(event: string, data?: unknown): void;
+  getLogTail(lines?: number): Promise<string[]>;
+  isSafeMode(): Promise<boolean>;
+  /** Hash of the full saved state (16 hex digits), `null` before the plugin is prepared. Compare to detect changes, e.g. since the last preset. */
+  stateHash(): Promise<string | null>;
+  /** Save the current state as a `.vstpreset` or `.aupreset` file (by extension). The name defaults to the file name. */
+  exportPreset(path: string, name?: string): Promise<null>;
+  /** Load a `.vstpreset` or `.aupreset` file saved for this plugin. Resolves to the preset name. */
+  importPreset(path: string): Promise<string>;
+  /** Hardware controller pages and the selected page. */
+  controllerPages(): Promise<BeamerControllerPages>;
+  /** Select a controller page. Resolves to `true` if the selection changed. */
+  selectControllerPage(index: number): Promise<boolean>;
+  /** MIDI response curves by target. */
+  responseCurves(): Promise<Record<BeamerCurveTarget, BeamerCurveShape>>;
+  /** Set a response curve, or clear it with `null`. Resolves to `false` for an invalid target or curve. */
+  setResponseCurve(target: BeamerCurveTarget, curve: BeamerCurveShape | null): Promise<boolean>;
+  /** Playable note range and keyboard splits. */
+  noteRanges(): Promise<BeamerNoteRanges>;
+  /** Host-assigned instance name (usually the track) and the display name suggested by the plugin. */
+  instanceName(): Promise<BeamerInstanceName>;
+  /** Suggest a display name to the host, or clear it with `null`. */
+  setDisplayName(name: string | null): Promise<boolean>;
+  /** State autosaved before a host crash, if any. */
+  autosaveRecovery(): Promise<BeamerAutosaveRecovery>;
+  /** Restore (`true`) or discard the autosaved state. */
+  resolveAutosave(restore: boolean): Promise<boolean>;
+  /** Parameter groups with their layout hints (without the root group). */
+  parameterGroups(): Promise<BeamerParameterGroup[]>;
+  /** Most recent parameter changes, oldest first (empty unless the plugin enables the log). */
+  getParameterLog(limit?: number): Promise<BeamerParameterLogEntry[]>;
+  /** Forget all logged parameter changes. */
+  clearParameterLog(): Promise<null>;
+  /** Monitored MIDI from cursor `since` on (default: everything recorded). */
+  getMidiMonitor(
+    since?: number,
+    filter?: BeamerMidiMonitorFilter | null,
+    limit?: number,
+  ): Promise<BeamerMidiMonitorRead>;
+  /** Forget all monitored MIDI. */
+  clearMidiMonitor(): Promise<null>;
+  /**
+   * Stream monitored MIDI matching `filter`, starting with what is already
+   * recorded. Returns an unsubscribe function.
+   */
+  onMidiMonitor(
+    callback: (entries: BeamerMidiMonitorEntry[], dropped: number) => void,
+    filter?: BeamerMidiMonitorFilter,
+  ): () => void;
+  /**
+   * Silence every voice: All Sound Off and All Notes Off on all channels at
+   * the start of the next block. Resolves to false if the plugin takes no MIDI.
+   */
+  midiPanic(): Promise<boolean>;
+  /** Result of the license check (also sent as the `beamer:activation` event when it finishes). */
+  activation(): Promise<BeamerActivation>;
+  /** Run the license check again, e.g. after the user entered a key. */
+  retryActivation(): Promise<BeamerActivation>;
+  /**
+   * Call `callback` once per display refresh with the frame time (ms), the
+   * plugin's latest meter values and the host transport extrapolated to the
+   * frame. Returns an unsubscribe function. Type scopes with `M`, e.g.
+   * `onFrame<{ scope: BeamerScopeFrame }>(...)`.
+   */
+  onFrame<M = Record<string, number>>(
+    callback: (time: number, meters: M, playhead: BeamerPlayhead) => void,
+  ): () => void;
 
   /** @internal Called by native code to initialize parameters. */
   _onInit(params: BeamerParamInfo[]): void;
@@ -38,6 +308,8 @@ interface Beamer {
   _onParams(changed: Record<string, [number, number, string]>): void;
   /** @internal Called by native code to resolve/reject invoke promises. */
   _onResult(callId: number, result: { ok?: unknown; err?: string }): void;
+  /** @internal Called by native code once per display refresh. */
+  _onFrame(time: number, meters: Record<string, number>, playhead: BeamerPlayhead): void;
   /** @internal Called by native code to dispatch events. */
   _onEvent(name: string, data: unknown): void;
 }
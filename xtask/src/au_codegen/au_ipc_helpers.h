@@ -198,3 +198,84 @@ static void beamer_au_ipc_send_init_dump(
     beamer_webview_eval_js(webviewHandle, (const uint8_t*)utf8, strlen(utf8));
     beamer_au_free_string(json);
 }
+
+// ---------------------------------------------------------------------------
+// Frame clock
+// ---------------------------------------------------------------------------
+
+/// Deliver one animation frame with the latest meter values and playhead.
+///
+/// Evals `window.__BEAMER__._onFrame(time, meters, playhead)`. Reading the
+/// meters resets their peaks, so call this once per frame.
+static void beamer_au_ipc_send_frame(
+    BeamerAuInstanceHandle instance,
+    void* webviewHandle,
+    double timeMs
+) {
+    if (!instance || !webviewHandle) return;
+
+    char* json = beamer_au_frame_meters_json(instance);
+    char* playhead = beamer_au_frame_playhead_json(instance);
+    NSString* script = [NSString stringWithFormat:@"window.__BEAMER__._onFrame(%.17g,%s,%s)",
+                        timeMs, json ? json : "{}", playhead ? playhead : "null"];
+    const char* utf8 = [script UTF8String];
+    beamer_webview_eval_js(webviewHandle, (const uint8_t*)utf8, strlen(utf8));
+    beamer_au_free_string(json);
+    beamer_au_free_string(playhead);
+}
+
+/// Handle a "frames" IPC message by starting or stopping the display-synced
+/// frame clock.
+///
+/// Returns YES if frames are wanted but no display link is available, in
+/// which case the caller sends them from its sync timer instead.
+static BOOL beamer_au_ipc_handle_frames(
+    void* webviewHandle,
+    NSDictionary* msg,
+    void (*callback)(void* context, double timeMs),
+    void* context
+) {
+    if (!webviewHandle) return NO;
+    if (![msg[@"enabled"] boolValue]) {
+        beamer_webview_stop_frame_clock(webviewHandle);
+        return NO;
+    }
+    return !beamer_webview_start_frame_clock(webviewHandle, callback, context);
+}
+
+/// Frame time for frames sent from a sync timer, in milliseconds.
+static double beamer_au_ipc_timer_frame_time(void) {
+    return [[NSProcessInfo processInfo] systemUptime] * 1000.0;
+}
+
+// ---------------------------------------------------------------------------
+// Notifications
+// ---------------------------------------------------------------------------
+
+/// Deliver the next pending notification, if one is due, as a
+/// `beamer:notification` event. Called from the sync timer.
+static void beamer_au_ipc_send_notification(
+    BeamerAuInstanceHandle instance,
+    void* webviewHandle
+) {
+    if (!instance || !webviewHandle) return;
+
+    char* script = beamer_au_take_notification_script(instance);
+    if (!script) return;
+    beamer_webview_eval_js(webviewHandle, (const uint8_t*)script, strlen(script));
+    beamer_au_free_string(script);
+}
+
+/// Deliver the frames of the plugin's GUI data channels, if due, as an
+/// `audio-data` event. Called from the sync timer.
+static void beamer_au_ipc_send_gui_data(
+    BeamerAuInstanceHandle instance,
+    void* webviewHandle
+) {
+    if (!instance || !webviewHandle) return;
+
+    char* script = beamer_au_take_gui_data_script(instance);
+    if (!script) return;
+    beamer_webview_eval_js(webviewHandle, (const uint8_t*)script, strlen(script));
+    beamer_au_free_string(script);
+}
@@ -23,11 +23,13 @@
 @interface {{COCOA_GUI_VIEW_CLASS}} : NSView {
 @public
     void* _webviewHandle;
+    BeamerAuNativeEditorHandle _nativeEditor;
     BeamerAuInstanceHandle _rustInstance;
     AudioUnit _audioUnit;
     NSTimer* _syncTimer;
     double* _lastParamValues;
     uint32_t _paramCount;
+    BOOL _timerFrames;
 }
 - (instancetype)initWithFrame:(NSRect)frame
                  webviewHandle:(void*)handle
@@ -50,6 +52,27 @@
 // Uses the AU custom property range (64000+).
 #define kBeamerAuPropertyRustInstance 64000
 
+// Private property the view sets after the GUI promoted or demoted a
+// parameter, so the AU notifies hosts that the parameter list changed.
+#define kBeamerAuPropertyParameterListChanged 64001
+
+// Private property the view sets after a GUI edit selected a different bus
+// layout, so the AU notifies hosts that the element counts changed.
+#define kBeamerAuPropertyBusLayoutChanged 64002
+
+// Private property the view sets after the plugin marked its state dirty,
+// so the AU notifies hosts that the project needs saving.
+#define kBeamerAuPropertyStateDirty 64003
+
+// Private property the view sets after the plugin suggested another display
+// name, so the AU notifies hosts that kAudioUnitProperty_NickName changed.
+#define kBeamerAuPropertyDisplayNameChanged 64005
+
+// Private property the view sets after presets were added, removed or
+// renamed, so the AU rebuilds its list and notifies hosts that
+// kAudioUnitProperty_FactoryPresets changed.
+#define kBeamerAuPropertyPresetListChanged 64006
+
 // =============================================================================
 // MARK: - Data Structures
 // =============================================================================
@@ -99,6 +122,8 @@ typedef struct BeamerAuv2Instance {
     PropertyListener propertyListeners[MAX_PROPERTY_LISTENERS];
     UInt32 propertyListenerCount;
     pthread_mutex_t listenerMutex;
+    // Main run loop timer forwarding property changes while initialized
+    CFRunLoopTimerRef propertyTimer;
 
     // Render notifications
     RenderNotify renderNotify[MAX_RENDER_NOTIFY];
@@ -107,6 +132,9 @@ typedef struct BeamerAuv2Instance {
 
     // Host callbacks (for tempo, transport, etc.)
     HostCallbackInfo hostCallbacks;
+    // AUv3-style host blocks wrapping hostCallbacks (retained, created in Open)
+    void* musicalContextBlock;
+    void* transportStateBlock;
 
     // Factory presets
     CFArrayRef factoryPresets;         // CFArray of AUPreset pointers (NULL callbacks)
@@ -114,6 +142,13 @@ typedef struct BeamerAuv2Instance {
     uint32_t presetCount;              // Number of factory presets
     int32_t currentPresetIndex;        // -1 = no preset, >=0 = factory preset index
     CFStringRef currentPresetName;     // Current preset name for ClassInfo round-trip
+    // Storage of the list before the last rebuild. Hosts may still hold the
+    // old array, whose entries point into it, until they re-read the property.
+    AUPreset* retiredPresetStorage;
+    uint32_t retiredPresetCount;
+
+    // Host-assigned instance name (kAudioUnitProperty_ContextName, retained)
+    CFStringRef contextName;
 
     // MIDI event ring buffer (lock-free SPSC: MIDIEvent produces, Render consumes)
     AURenderEvent midiRingBuffer[BEAMER_AU_MAX_MIDI_EVENTS];
@@ -186,6 +221,156 @@ static void NotifyPropertyListeners(BeamerAuv2Instance* inst, AudioUnitPropertyI
     pthread_mutex_unlock(&inst->listenerMutex);
 }
 
+// Tell hosts the element counts changed after a loaded state or GUI edit
+// selected a different bus layout. While initialized the old counts stay in
+// effect; hosts pick up the new ones when they re-initialize.
+static void NotifyBusLayoutChanged(BeamerAuv2Instance* inst) {
+    NotifyPropertyListeners(inst, kAudioUnitProperty_ElementCount, kAudioUnitScope_Input, 0);
+    NotifyPropertyListeners(inst, kAudioUnitProperty_ElementCount, kAudioUnitScope_Output, 0);
+}
+
+// Forward a property change reported by beamer_au_dispatch_property_changes.
+static void PropertyChanged(void* context, uint32_t property) {
+    NotifyPropertyListeners((BeamerAuv2Instance*)context, property, kAudioUnitScope_Global, 0);
+}
+
+// Report every host parameter to the parameter listeners after a constraint
+// moved a value or the audio thread applied a crossfaded preset.
+static void NotifyParametersIfAdjusted(BeamerAuv2Instance* inst) {
+    if (!beamer_au_take_parameters_adjusted(inst->rustInstance)) {
+        return;
+    }
+    uint32_t count = beamer_au_get_host_parameter_count(inst->rustInstance);
+    for (uint32_t i = 0; i < count; i++) {
+        BeamerAuParameterInfo info;
+        if (!beamer_au_get_host_parameter_info(inst->rustInstance, i, &info)) continue;
+        AudioUnitParameter parameter = {inst->componentInstance, info.id, kAudioUnitScope_Global, 0};
+        AUParameterListenerNotify(NULL, NULL, &parameter);
+    }
+}
+
+static void PropertyTimerFired(CFRunLoopTimerRef timer, void* info) {
+    (void)timer;
+    BeamerAuv2Instance* inst = (BeamerAuv2Instance*)info;
+    beamer_au_dispatch_property_changes(inst->rustInstance, PropertyChanged, inst);
+    NotifyParametersIfAdjusted(inst);
+}
+
+// Forward latency changes the plugin announces while processing, and presets
+// a crossfade applied, with or without an editor open. The timer runs on the main run loop, where hosts
+// expect property notifications.
+static void StartPropertyTimer(BeamerAuv2Instance* inst) {
+    if (inst->propertyTimer) {
+        return;
+    }
+    CFRunLoopTimerContext context = {0, inst, NULL, NULL, NULL};
+    inst->propertyTimer = CFRunLoopTimerCreate(NULL, CFAbsoluteTimeGetCurrent() + 0.05, 0.05, 0, 0,
+                                               PropertyTimerFired, &context);
+    CFRunLoopAddTimer(CFRunLoopGetMain(), inst->propertyTimer, kCFRunLoopCommonModes);
+}
+
+static void StopPropertyTimer(BeamerAuv2Instance* inst) {
+    if (!inst->propertyTimer) {
+        return;
+    }
+    CFRunLoopTimerInvalidate(inst->propertyTimer);
+    CFRelease(inst->propertyTimer);
+    inst->propertyTimer = NULL;
+}
+
+// Give buses added by a bus layout change a default format.
+static void EnsureBusFormats(BeamerAuv2Instance* inst) {
+    uint32_t inputBusCount = beamer_au_get_input_bus_count(inst->rustInstance);
+    uint32_t outputBusCount = beamer_au_get_output_bus_count(inst->rustInstance);
+
+    for (uint32_t i = 0; i < inputBusCount && i < BEAMER_AU_MAX_BUSES; i++) {
+        if (inst->inputFormats[i].mChannelsPerFrame == 0) {
+            uint32_t ch = beamer_au_get_input_bus_channel_count(inst->rustInstance, i);
+            InitDefaultFormat(&inst->inputFormats[i], inst->sampleRate, ch == 0 ? 2 : ch);
+        }
+    }
+    for (uint32_t i = 0; i < outputBusCount && i < BEAMER_AU_MAX_BUSES; i++) {
+        if (inst->outputFormats[i].mChannelsPerFrame == 0) {
+            uint32_t ch = beamer_au_get_output_bus_channel_count(inst->rustInstance, i);
+            InitDefaultFormat(&inst->outputFormats[i], inst->sampleRate, ch == 0 ? 2 : ch);
+        }
+    }
+}
+
+// Free preset storage and release dynamically created CFStrings.
+static void FreePresetStorage(AUPreset* storage, uint32_t count) {
+    if (!storage) return;
+    for (uint32_t i = 0; i < count; i++) {
+        if (storage[i].presetName) {
+            CFRelease(storage[i].presetName);
+        }
+    }
+    free(storage);
+}
+
+// Build the factory presets cache from Rust.
+static void BuildFactoryPresets(BeamerAuv2Instance* inst) {
+    uint32_t presetCount = beamer_au_get_preset_count(inst->rustInstance);
+    inst->presetCount = presetCount;
+    inst->factoryPresets = NULL;
+    inst->presetStorage = NULL;
+    if (presetCount == 0) return;
+
+    // Allocate backing storage for AUPreset structs
+    inst->presetStorage = (AUPreset*)calloc(presetCount, sizeof(AUPreset));
+    if (!inst->presetStorage) {
+        inst->presetCount = 0;
+        return;
+    }
+
+    // Initialize each preset from Rust
+    for (uint32_t i = 0; i < presetCount; i++) {
+        BeamerAuPresetInfo info;
+        memset(&info, 0, sizeof(info));
+        if (beamer_au_get_preset_info(inst->rustInstance, i, &info)) {
+            inst->presetStorage[i].presetNumber = (SInt32)info.number;
+            inst->presetStorage[i].presetName = CFStringCreateWithCString(
+                kCFAllocatorDefault, info.name, kCFStringEncodingUTF8);
+        }
+    }
+
+    // Build CFArray with NULL callbacks (stores raw pointers to AUPreset)
+    CFMutableArrayRef presets = CFArrayCreateMutable(kCFAllocatorDefault, presetCount, NULL);
+    if (presets) {
+        for (uint32_t i = 0; i < presetCount; i++) {
+            CFArrayAppendValue(presets, &inst->presetStorage[i]);
+        }
+        inst->factoryPresets = presets;
+    }
+}
+
+static void ReleaseFactoryPresets(BeamerAuv2Instance* inst) {
+    if (inst->factoryPresets) {
+        CFRelease(inst->factoryPresets);
+        inst->factoryPresets = NULL;
+    }
+    FreePresetStorage(inst->presetStorage, inst->presetCount);
+    inst->presetStorage = NULL;
+    inst->presetCount = 0;
+}
+
+// Rebuild the factory presets after presets were added, removed or renamed
+// at runtime, keeping the previous storage alive for arrays hosts still hold.
+static void RebuildFactoryPresets(BeamerAuv2Instance* inst) {
+    FreePresetStorage(inst->retiredPresetStorage, inst->retiredPresetCount);
+    inst->retiredPresetStorage = inst->presetStorage;
+    inst->retiredPresetCount = inst->presetCount;
+    inst->presetStorage = NULL;
+    if (inst->factoryPresets) {
+        CFRelease(inst->factoryPresets);
+        inst->factoryPresets = NULL;
+    }
+    BuildFactoryPresets(inst);
+    if (inst->currentPresetIndex >= (int32_t)inst->presetCount) {
+        inst->currentPresetIndex = -1;
+    }
+}
+
 static OSStatus EnsureInputBufferList(BeamerAuv2Instance* inst, UInt32 channels, UInt32 frames) {
     if (channels > BEAMER_AU_MAX_CHANNELS) return kAudio_ParamError;
     UInt32 neededCapacity = frames * channels;
@@ -242,6 +427,98 @@ static void FreeInputBufferList(BeamerAuv2Instance* inst) {
     }
 }
 
+// =============================================================================
+// MARK: - Host Callback Blocks
+// =============================================================================
+
+// The Rust render path reads tempo and transport through the AUv3 host
+// blocks. These adapt kAudioUnitProperty_HostCallbacks to the same
+// signatures so AUv2 fills Transport the same way. The blocks read
+// inst->hostCallbacks on every call, so they are created once in Open and
+// pick up callbacks the host sets later.
+
+static AUHostMusicalContextBlock CreateMusicalContextBlock(BeamerAuv2Instance* inst) {
+    return [^BOOL(double* outTempo, double* outNumerator, NSInteger* outDenominator,
+                  double* outBeat, NSInteger* outSampleOffsetToNextBeat,
+                  double* outMeasureDownbeat) {
+        const HostCallbackInfo* cb = &inst->hostCallbacks;
+        if (cb->beatAndTempoProc == NULL) {
+            return NO;
+        }
+
+        Float64 beat = 0.0;
+        Float64 tempo = 0.0;
+        if (cb->beatAndTempoProc(cb->hostUserData, &beat, &tempo) != noErr) {
+            return NO;
+        }
+
+        UInt32 offsetToNextBeat = 0;
+        Float32 numerator = 0.0f;
+        UInt32 denominator = 0;
+        Float64 downbeat = 0.0;
+        if (cb->musicalTimeLocationProc == NULL ||
+            cb->musicalTimeLocationProc(cb->hostUserData, &offsetToNextBeat, &numerator,
+                                        &denominator, &downbeat) != noErr) {
+            // Tempo and beat are still valid. A zero time signature tells
+            // the Rust side that the bar information is unavailable.
+            offsetToNextBeat = 0;
+            numerator = 0.0f;
+            denominator = 0;
+            downbeat = 0.0;
+        }
+
+        if (outTempo) *outTempo = tempo;
+        if (outNumerator) *outNumerator = numerator;
+        if (outDenominator) *outDenominator = (NSInteger)denominator;
+        if (outBeat) *outBeat = beat;
+        if (outSampleOffsetToNextBeat) *outSampleOffsetToNextBeat = (NSInteger)offsetToNextBeat;
+        if (outMeasureDownbeat) *outMeasureDownbeat = downbeat;
+        return YES;
+    } copy];
+}
+
+static AUHostTransportStateBlock CreateTransportStateBlock(BeamerAuv2Instance* inst) {
+    return [^BOOL(AUHostTransportStateFlags* outFlags, double* outSamplePosition,
+                  double* outCycleStart, double* outCycleEnd) {
+        const HostCallbackInfo* cb = &inst->hostCallbacks;
+        Boolean isPlaying = false;
+        Boolean isRecording = false;
+        Boolean changed = false;
+        Boolean isCycling = false;
+        Float64 samplePosition = 0.0;
+        Float64 cycleStart = 0.0;
+        Float64 cycleEnd = 0.0;
+
+        OSStatus status;
+        if (cb->transportStateProc2 != NULL) {
+            status = cb->transportStateProc2(cb->hostUserData, &isPlaying, &isRecording, &changed,
+                                             &samplePosition, &isCycling, &cycleStart, &cycleEnd);
+        } else if (cb->transportStateProc != NULL) {
+            // Older hosts: no recording state.
+            status = cb->transportStateProc(cb->hostUserData, &isPlaying, &changed,
+                                            &samplePosition, &isCycling, &cycleStart, &cycleEnd);
+        } else {
+            return NO;
+        }
+        if (status != noErr) {
+            return NO;
+        }
+
+        if (outFlags) {
+            AUHostTransportStateFlags flags = 0;
+            if (changed) flags |= AUHostTransportStateChanged;
+            if (isPlaying) flags |= AUHostTransportStateMoving;
+            if (isRecording) flags |= AUHostTransportStateRecording;
+            if (isCycling) flags |= AUHostTransportStateCycling;
+            *outFlags = flags;
+        }
+        if (outSamplePosition) *outSamplePosition = samplePosition;
+        if (outCycleStart) *outCycleStart = cycleStart;
+        if (outCycleEnd) *outCycleEnd = cycleEnd;
+        return YES;
+    } copy];
+}
+
 // =============================================================================
 // MARK: - Factory Function
 // =============================================================================
@@ -291,6 +568,9 @@ static OSStatus BeamerAuv2Open(void* self, AudioComponentInstance ci) {
         return kAudioUnitErr_FailedInitialization;
     }
 
+    inst->musicalContextBlock = (__bridge_retained void*)CreateMusicalContextBlock(inst);
+    inst->transportStateBlock = (__bridge_retained void*)CreateTransportStateBlock(inst);
+
     // Query bus configuration from Rust and set up default formats per bus
     uint32_t inputBusCount = beamer_au_get_input_bus_count(inst->rustInstance);
     uint32_t outputBusCount = beamer_au_get_output_bus_count(inst->rustInstance);
@@ -307,44 +587,9 @@ static OSStatus BeamerAuv2Open(void* self, AudioComponentInstance ci) {
     }
 
     // Build factory presets cache
-    uint32_t presetCount = beamer_au_get_preset_count(inst->rustInstance);
-    inst->presetCount = presetCount;
     inst->currentPresetIndex = -1;
     inst->currentPresetName = NULL;
-
-    if (presetCount > 0) {
-        // Allocate backing storage for AUPreset structs
-        inst->presetStorage = (AUPreset*)calloc(presetCount, sizeof(AUPreset));
-        if (inst->presetStorage) {
-            // Initialize each preset from Rust
-            for (uint32_t i = 0; i < presetCount; i++) {
-                BeamerAuPresetInfo info;
-                memset(&info, 0, sizeof(info));
-                if (beamer_au_get_preset_info(inst->rustInstance, i, &info)) {
-                    inst->presetStorage[i].presetNumber = (SInt32)info.number;
-                    inst->presetStorage[i].presetName = CFStringCreateWithCString(
-                        kCFAllocatorDefault, info.name, kCFStringEncodingUTF8);
-                }
-            }
-
-            // Build CFArray with NULL callbacks (stores raw pointers to AUPreset)
-            CFMutableArrayRef presets = CFArrayCreateMutable(kCFAllocatorDefault, presetCount, NULL);
-            if (presets) {
-                for (uint32_t i = 0; i < presetCount; i++) {
-                    CFArrayAppendValue(presets, &inst->presetStorage[i]);
-                }
-                inst->factoryPresets = presets;
-            } else {
-                inst->factoryPresets = NULL;
-            }
-        } else {
-            inst->factoryPresets = NULL;
-            inst->presetCount = 0;
-        }
-    } else {
-        inst->factoryPresets = NULL;
-        inst->presetStorage = NULL;
-    }
+    BuildFactoryPresets(inst);
 
     return noErr;
 }
@@ -352,6 +597,7 @@ static OSStatus BeamerAuv2Open(void* self, AudioComponentInstance ci) {
 static OSStatus BeamerAuv2Close(void* self) {
     BeamerAuv2Instance* inst = (BeamerAuv2Instance*)self;
 
+    StopPropertyTimer(inst);
     if (inst->initialized) {
         beamer_au_deallocate_render_resources(inst->rustInstance);
         inst->initialized = false;
@@ -364,28 +610,32 @@ static OSStatus BeamerAuv2Close(void* self) {
 
     FreeInputBufferList(inst);
 
+    // Release host callback blocks
+    if (inst->musicalContextBlock) {
+        CFRelease(inst->musicalContextBlock);
+        inst->musicalContextBlock = NULL;
+    }
+    if (inst->transportStateBlock) {
+        CFRelease(inst->transportStateBlock);
+        inst->transportStateBlock = NULL;
+    }
+
     // Release current preset name
     if (inst->currentPresetName) {
         CFRelease(inst->currentPresetName);
         inst->currentPresetName = NULL;
     }
 
-    // Release factory presets
-    if (inst->factoryPresets) {
-        CFRelease(inst->factoryPresets);
-        inst->factoryPresets = NULL;
+    // Release host-assigned instance name
+    if (inst->contextName) {
+        CFRelease(inst->contextName);
+        inst->contextName = NULL;
     }
 
-    // Free preset storage and release dynamically created CFStrings
-    if (inst->presetStorage) {
-        for (uint32_t i = 0; i < inst->presetCount; i++) {
-            if (inst->presetStorage[i].presetName) {
-                CFRelease(inst->presetStorage[i].presetName);
-            }
-        }
-        free(inst->presetStorage);
-        inst->presetStorage = NULL;
-    }
+    // Release factory presets
+    ReleaseFactoryPresets(inst);
+    FreePresetStorage(inst->retiredPresetStorage, inst->retiredPresetCount);
+    inst->retiredPresetStorage = NULL;
 
     pthread_mutex_destroy(&inst->listenerMutex);
     pthread_mutex_destroy(&inst->renderNotifyMutex);
@@ -443,6 +693,8 @@ static OSStatus BeamerAuv2Initialize(void* self) {
         return noErr; // Already initialized
     }
 
+    EnsureBusFormats(inst);
+
     // Build bus config from current stream formats
     BeamerAuBusConfig busConfig;
     memset(&busConfig, 0, sizeof(busConfig));
@@ -495,6 +747,7 @@ static OSStatus BeamerAuv2Initialize(void* self) {
 
     if (status == noErr) {
         inst->initialized = true;
+        StartPropertyTimer(inst);
 
         // Reset MIDI ring buffer
         atomic_store_explicit(&inst->midiReadHead, 0, memory_order_relaxed);
@@ -513,6 +766,7 @@ static OSStatus BeamerAuv2Uninitialize(void* self) {
     BeamerAuv2Instance* inst = (BeamerAuv2Instance*)self;
 
     if (inst->initialized) {
+        StopPropertyTimer(inst);
         beamer_au_deallocate_render_resources(inst->rustInstance);
         inst->initialized = false;
 
@@ -550,6 +804,20 @@ static OSStatus BeamerAuv2GetPropertyInfo(void* self, AudioUnitPropertyID propID
             if (outWritable) *outWritable = true;
             return noErr;
 
+        // Channel layout of a bus (read-only, layout tag only)
+        case kAudioUnitProperty_AudioChannelLayout:
+            if (scope == kAudioUnitScope_Input) {
+                if (element >= beamer_au_get_input_bus_count(inst->rustInstance))
+                    return kAudioUnitErr_InvalidElement;
+            } else if (scope == kAudioUnitScope_Output) {
+                if (element >= beamer_au_get_output_bus_count(inst->rustInstance))
+                    return kAudioUnitErr_InvalidElement;
+            } else {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (outDataSize) *outDataSize = sizeof(AudioChannelLayout);
+            return noErr;
+
         // Sample rate
         case kAudioUnitProperty_SampleRate:
             if (outDataSize) *outDataSize = sizeof(Float64);
@@ -565,7 +833,7 @@ static OSStatus BeamerAuv2GetPropertyInfo(void* self, AudioUnitPropertyID propID
         // Parameter list
         case kAudioUnitProperty_ParameterList:
             if (scope == kAudioUnitScope_Global && element == 0) {
-                uint32_t count = beamer_au_get_parameter_count(inst->rustInstance);
+                uint32_t count = beamer_au_get_host_parameter_count(inst->rustInstance);
                 if (outDataSize) *outDataSize = count * sizeof(AudioUnitParameterID);
                 if (outWritable) *outWritable = false;
                 return noErr;
@@ -702,6 +970,24 @@ static OSStatus BeamerAuv2GetPropertyInfo(void* self, AudioUnitPropertyID propID
             if (outWritable) *outWritable = true;
             return noErr;
 
+        // Instance name set by the host (usually the track name)
+        case kAudioUnitProperty_ContextName:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (outDataSize) *outDataSize = sizeof(CFStringRef);
+            if (outWritable) *outWritable = true;
+            return noErr;
+
+        // Display name suggested by the plugin
+        case kAudioUnitProperty_NickName:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (outDataSize) *outDataSize = sizeof(CFStringRef);
+            if (outWritable) *outWritable = false;
+            return noErr;
+
         // Element count
         case kAudioUnitProperty_ElementCount:
             if (outDataSize) *outDataSize = sizeof(UInt32);
@@ -753,6 +1039,18 @@ static OSStatus BeamerAuv2GetPropertyInfo(void* self, AudioUnitPropertyID propID
             if (outWritable) *outWritable = false;
             return noErr;
 
+        case kBeamerAuPropertyParameterListChanged:
+        case kBeamerAuPropertyBusLayoutChanged:
+        case kBeamerAuPropertyStateDirty:
+        case kBeamerAuPropertyDisplayNameChanged:
+        case kBeamerAuPropertyPresetListChanged:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (outDataSize) *outDataSize = sizeof(UInt32);
+            if (outWritable) *outWritable = true;
+            return noErr;
+
         default:
             return kAudioUnitErr_InvalidProperty;
     }
@@ -784,6 +1082,36 @@ static OSStatus BeamerAuv2GetProperty(void* self, AudioUnitPropertyID propID,
             return noErr;
         }
 
+        case kAudioUnitProperty_AudioChannelLayout: {
+            if (!outData || !ioDataSize || *ioDataSize < sizeof(AudioChannelLayout)) {
+                return kAudioUnitErr_InvalidPropertyValue;
+            }
+            AudioChannelLayoutTag tag;
+            UInt32 channels;
+            if (scope == kAudioUnitScope_Input) {
+                if (element >= beamer_au_get_input_bus_count(inst->rustInstance))
+                    return kAudioUnitErr_InvalidElement;
+                tag = beamer_au_get_input_bus_layout_tag(inst->rustInstance, element);
+                channels = inst->inputFormats[element].mChannelsPerFrame;
+            } else if (scope == kAudioUnitScope_Output) {
+                if (element >= beamer_au_get_output_bus_count(inst->rustInstance))
+                    return kAudioUnitErr_InvalidElement;
+                tag = beamer_au_get_output_bus_layout_tag(inst->rustInstance, element);
+                channels = inst->outputFormats[element].mChannelsPerFrame;
+            } else {
+                return kAudioUnitErr_InvalidScope;
+            }
+            // The stream format may have been changed since resources were allocated
+            if (AudioChannelLayoutTag_GetNumberOfChannels(tag) != channels) {
+                tag = kAudioChannelLayoutTag_DiscreteInOrder | channels;
+            }
+            AudioChannelLayout* layout = (AudioChannelLayout*)outData;
+            memset(layout, 0, sizeof(AudioChannelLayout));
+            layout->mChannelLayoutTag = tag;
+            *ioDataSize = sizeof(AudioChannelLayout);
+            return noErr;
+        }
+
         case kAudioUnitProperty_SampleRate: {
             if (!outData || !ioDataSize || *ioDataSize < sizeof(Float64)) {
                 return kAudioUnitErr_InvalidPropertyValue;
@@ -806,7 +1134,8 @@ static OSStatus BeamerAuv2GetProperty(void* self, AudioUnitPropertyID propID,
             if (scope != kAudioUnitScope_Global || element != 0) {
                 return kAudioUnitErr_InvalidScope;
             }
-            uint32_t count = beamer_au_get_parameter_count(inst->rustInstance);
+            // Host-facing list: internal parameters appear only once promoted.
+            uint32_t count = beamer_au_get_host_parameter_count(inst->rustInstance);
             UInt32 needed = count * sizeof(AudioUnitParameterID);
             if (!outData || !ioDataSize || *ioDataSize < needed) {
                 if (ioDataSize) *ioDataSize = needed;
@@ -815,7 +1144,7 @@ static OSStatus BeamerAuv2GetProperty(void* self, AudioUnitPropertyID propID,
             AudioUnitParameterID* ids = (AudioUnitParameterID*)outData;
             for (uint32_t i = 0; i < count; i++) {
                 BeamerAuParameterInfo info;
-                if (beamer_au_get_parameter_info(inst->rustInstance, i, &info)) {
+                if (beamer_au_get_host_parameter_info(inst->rustInstance, i, &info)) {
                     ids[i] = info.id;
                 } else {
                     ids[i] = 0;
@@ -1140,6 +1469,37 @@ static OSStatus BeamerAuv2GetProperty(void* self, AudioUnitPropertyID propID,
             return noErr;
         }
 
+        case kAudioUnitProperty_ContextName: {
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (!outData || !ioDataSize || *ioDataSize < sizeof(CFStringRef)) {
+                return kAudioUnitErr_InvalidPropertyValue;
+            }
+            // The caller releases the returned string.
+            *(CFStringRef*)outData = inst->contextName ? (CFStringRef)CFRetain(inst->contextName) : NULL;
+            *ioDataSize = sizeof(CFStringRef);
+            return noErr;
+        }
+
+        case kAudioUnitProperty_NickName: {
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (!outData || !ioDataSize || *ioDataSize < sizeof(CFStringRef)) {
+                return kAudioUnitErr_InvalidPropertyValue;
+            }
+            char buffer[256];
+            uint32_t len = beamer_au_get_display_name(inst->rustInstance, buffer, sizeof(buffer));
+            if (len == 0) {
+                beamer_au_get_name(inst->rustInstance, buffer, sizeof(buffer));
+            }
+            // The caller releases the returned string.
+            *(CFStringRef*)outData = CFStringCreateWithCString(NULL, buffer, kCFStringEncodingUTF8);
+            *ioDataSize = sizeof(CFStringRef);
+            return noErr;
+        }
+
         case kAudioUnitProperty_PresentPreset: {
             if (scope != kAudioUnitScope_Global) {
                 return kAudioUnitErr_InvalidScope;
@@ -1272,8 +1632,8 @@ static OSStatus BeamerAuv2SetProperty(void* self, AudioUnitPropertyID propID,
                 return kAudioUnitErr_FormatNotSupported;
             }
 
-            // Validate channel count against declared capability for MAIN bus (element 0).
-            // This enforces the [N, M] capability we report in SupportedNumChannels.
+            // Validate channel count against the capabilities for the MAIN bus (element 0)
+            // we report in SupportedNumChannels.
             // Auxiliary buses (sidechain, etc.) can have any reasonable channel count.
             if (element == 0) {
                 uint32_t declaredChannels;
@@ -1283,7 +1643,18 @@ static OSStatus BeamerAuv2SetProperty(void* self, AudioUnitPropertyID propID,
                     declaredChannels = beamer_au_get_output_bus_channel_count(inst->rustInstance, 0);
                 }
                 if (declaredChannels > 0 && proposedChannels != declaredChannels) {
-                    return kAudioUnitErr_FormatNotSupported;
+                    // Other channel counts the plugin agrees to (Descriptor::accepts_bus_layout),
+                    // checked together with the current format of the other main bus
+                    uint32_t inputBusCount = beamer_au_get_input_bus_count(inst->rustInstance);
+                    uint32_t inputChannels = (scope == kAudioUnitScope_Input)
+                        ? proposedChannels
+                        : (inputBusCount > 0 ? inst->inputFormats[0].mChannelsPerFrame : 0);
+                    uint32_t outputChannels = (scope == kAudioUnitScope_Output)
+                        ? proposedChannels
+                        : inst->outputFormats[0].mChannelsPerFrame;
+                    if (!beamer_au_is_channel_config_valid(inst->rustInstance, inputChannels, outputChannels)) {
+                        return kAudioUnitErr_FormatNotSupported;
+                    }
                 }
             }
 
@@ -1367,10 +1738,13 @@ static OSStatus BeamerAuv2SetProperty(void* self, AudioUnitPropertyID propID,
         }
 
         case kAudioUnitProperty_HostCallbacks: {
-            if (!inData || inDataSize < sizeof(HostCallbackInfo)) {
+            if (!inData || inDataSize == 0) {
                 return kAudioUnitErr_InvalidPropertyValue;
             }
-            inst->hostCallbacks = *(HostCallbackInfo*)inData;
+            // Older hosts pass a shorter struct without transportStateProc2.
+            memset(&inst->hostCallbacks, 0, sizeof(HostCallbackInfo));
+            memcpy(&inst->hostCallbacks, inData,
+                   inDataSize < sizeof(HostCallbackInfo) ? inDataSize : sizeof(HostCallbackInfo));
             return noErr;
         }
 
@@ -1404,6 +1778,16 @@ static OSStatus BeamerAuv2SetProperty(void* self, AudioUnitPropertyID propID,
             }
 
             NotifyPropertyListeners(inst, propID, scope, element);
+            if (beamer_au_take_parameter_list_changed(inst->rustInstance)) {
+                NotifyPropertyListeners(inst, kAudioUnitProperty_ParameterList,
+                                        kAudioUnitScope_Global, 0);
+            }
+            if (beamer_au_take_bus_layout_changed(inst->rustInstance)) {
+                NotifyBusLayoutChanged(inst);
+            }
+            // The state may select another oversampling factor or announce
+            // a latency change itself
+            beamer_au_dispatch_property_changes(inst->rustInstance, PropertyChanged, inst);
             return noErr;
         }
 
@@ -1443,6 +1827,70 @@ static OSStatus BeamerAuv2SetProperty(void* self, AudioUnitPropertyID propID,
             return noErr;
         }
 
+        case kBeamerAuPropertyParameterListChanged:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            NotifyPropertyListeners(inst, kAudioUnitProperty_ParameterList,
+                                    kAudioUnitScope_Global, 0);
+            return noErr;
+
+        case kBeamerAuPropertyBusLayoutChanged:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            NotifyBusLayoutChanged(inst);
+            return noErr;
+
+        // Hosts treat a ClassInfo change as a modified document.
+        case kBeamerAuPropertyStateDirty:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            NotifyPropertyListeners(inst, kAudioUnitProperty_ClassInfo,
+                                    kAudioUnitScope_Global, 0);
+            return noErr;
+
+        case kBeamerAuPropertyDisplayNameChanged:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            NotifyPropertyListeners(inst, kAudioUnitProperty_NickName,
+                                    kAudioUnitScope_Global, 0);
+            return noErr;
+
+        case kBeamerAuPropertyPresetListChanged:
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            RebuildFactoryPresets(inst);
+            NotifyPropertyListeners(inst, kAudioUnitProperty_FactoryPresets,
+                                    kAudioUnitScope_Global, 0);
+            return noErr;
+
+        case kAudioUnitProperty_ContextName: {
+            if (scope != kAudioUnitScope_Global) {
+                return kAudioUnitErr_InvalidScope;
+            }
+            if (!inData || inDataSize < sizeof(CFStringRef)) {
+                return kAudioUnitErr_InvalidPropertyValue;
+            }
+            CFStringRef name = *(const CFStringRef*)inData;
+            if (inst->contextName) {
+                CFRelease(inst->contextName);
+                inst->contextName = NULL;
+            }
+            char buffer[256];
+            if (name && CFStringGetCString(name, buffer, sizeof(buffer), kCFStringEncodingUTF8)) {
+                inst->contextName = (CFStringRef)CFRetain(name);
+                beamer_au_set_instance_name(inst->rustInstance, buffer);
+            } else {
+                beamer_au_set_instance_name(inst->rustInstance, NULL);
+            }
+            NotifyPropertyListeners(inst, propID, scope, element);
+            return noErr;
+        }
+
         case kAudioUnitProperty_OfflineRender:
         case kAudioUnitProperty_InPlaceProcessing:
         case kAudioUnitProperty_ShouldAllocateBuffer:
@@ -1656,7 +2104,8 @@ static OSStatus BeamerAuv2Render(void* self, AudioUnitRenderActionFlags* ioActio
     AudioBufferList* inputData = NULL;
     uint32_t inputBusCount = beamer_au_get_input_bus_count(inst->rustInstance);
 
-    if (inputBusCount > 0) {
+    // Zero-frame renders are parameter flushes; there's no audio to pull
+    if (inputBusCount > 0 && inNumberFrames > 0) {
         if (inst->inputCallback.inputProc) {
             EnsureInputBufferList(inst, inst->inputFormats[0].mChannelsPerFrame, inNumberFrames);
             AudioUnitRenderActionFlags pullFlags = 0;
@@ -1706,8 +2155,8 @@ static OSStatus BeamerAuv2Render(void* self, AudioUnitRenderActionFlags* ioActio
         midiEventList,
         NULL,  // pull_input_block (we pre-pulled via callback/connection)
         inputData,
-        NULL,  // musical_context_block (TODO: wrap host callbacks)
-        NULL,  // transport_state_block (TODO: wrap host callbacks)
+        (__bridge AUHostMusicalContextBlock)inst->musicalContextBlock,
+        (__bridge AUHostTransportStateBlock)inst->transportStateBlock,
         NULL   // schedule_midi_block
     );
 
@@ -1745,7 +2194,9 @@ static OSStatus BeamerAuv2MIDIEvent(void* self, UInt32 inStatus, UInt32 inData1,
     AURenderEvent* event = &inst->midiRingBuffer[write];
     memset(event, 0, sizeof(AURenderEvent));
     event->MIDI.eventType = AURenderEventMIDI;
-    event->MIDI.eventSampleTime = (AUEventSampleTime)inOffsetSampleFrame;
+    // inOffsetSampleFrame is relative to the next render buffer, not an absolute
+    // sample time. Mark it as such so Rust doesn't subtract the buffer start.
+    event->MIDI.eventSampleTime = AUEventSampleTimeImmediate + (AUEventSampleTime)inOffsetSampleFrame;
     event->MIDI.cable = 0;
     event->MIDI.length = 3;
     event->MIDI.data[0] = (uint8_t)(inStatus & 0xFF);
@@ -1822,6 +2273,11 @@ static OSStatus BeamerAuv2RemoveRenderNotify(void* self, AURenderCallback proc,
 // MARK: - AUv2 WebView IPC Callbacks
 // =============================================================================
 
+static void beamer_auv2_on_frame(void* context, double timeMs) {
+    {{COCOA_GUI_VIEW_CLASS}}* self = (__bridge {{COCOA_GUI_VIEW_CLASS}}*)context;
+    beamer_au_ipc_send_frame(self->_rustInstance, self->_webviewHandle, timeMs);
+}
+
 static void beamer_auv2_on_message(void* context, const uint8_t* json, size_t len) {
     {{COCOA_GUI_VIEW_CLASS}}* self = (__bridge {{COCOA_GUI_VIEW_CLASS}}*)context;
     NSString* jsonStr = [[NSString alloc] initWithBytes:json length:len encoding:NSUTF8StringEncoding];
@@ -1843,8 +2299,14 @@ static void beamer_auv2_on_message(void* context, const uint8_t* json, size_t le
         // Echo authoritative values back to JS immediately.
         beamer_au_ipc_echo_param(self->_rustInstance, self->_webviewHandle,
                                  paramId, self->_lastParamValues, self->_paramCount);
+        if (beamer_au_take_bus_layout_changed(self->_rustInstance)) {
+            UInt32 changed = 1;
+            AudioUnitSetProperty(self->_audioUnit, kBeamerAuPropertyBusLayoutChanged,
+                                 kAudioUnitScope_Global, 0, &changed, sizeof(changed));
+        }
     } else if ([type isEqualToString:@"param:begin"]) {
         uint32_t paramId = [msg[@"id"] unsignedIntValue];
+        beamer_au_param_gesture_from_ui(self->_rustInstance, paramId, true);
         AudioUnitEvent event;
         memset(&event, 0, sizeof(event));
         event.mEventType = kAudioUnitEvent_BeginParameterChangeGesture;
@@ -1854,6 +2316,7 @@ static void beamer_auv2_on_message(void* context, const uint8_t* json, size_t le
         AUEventListenerNotify(NULL, NULL, &event);
     } else if ([type isEqualToString:@"param:end"]) {
         uint32_t paramId = [msg[@"id"] unsignedIntValue];
+        beamer_au_param_gesture_from_ui(self->_rustInstance, paramId, false);
         AudioUnitEvent event;
         memset(&event, 0, sizeof(event));
         event.mEventType = kAudioUnitEvent_EndParameterChangeGesture;
@@ -1865,8 +2328,21 @@ static void beamer_auv2_on_message(void* context, const uint8_t* json, size_t le
         if (!beamer_au_ipc_handle_builtin_invoke(self->_rustInstance, self->_webviewHandle, msg)) {
             beamer_au_ipc_handle_invoke(self->_rustInstance, self->_webviewHandle, msg);
         }
+        if (beamer_au_take_parameter_list_changed(self->_rustInstance)) {
+            UInt32 changed = 1;
+            AudioUnitSetProperty(self->_audioUnit, kBeamerAuPropertyParameterListChanged,
+                                 kAudioUnitScope_Global, 0, &changed, sizeof(changed));
+        }
+        if (beamer_au_take_bus_layout_changed(self->_rustInstance)) {
+            UInt32 changed = 1;
+            AudioUnitSetProperty(self->_audioUnit, kBeamerAuPropertyBusLayoutChanged,
+                                 kAudioUnitScope_Global, 0, &changed, sizeof(changed));
+        }
     } else if ([type isEqualToString:@"event"]) {
         beamer_au_ipc_handle_event(self->_rustInstance, msg);
+    } else if ([type isEqualToString:@"frames"]) {
+        self->_timerFrames = beamer_au_ipc_handle_frames(self->_webviewHandle, msg,
+                                                         beamer_auv2_on_frame, context);
     }
 }
 
@@ -1875,6 +2351,34 @@ static void beamer_auv2_on_loaded(void* context) {
     beamer_au_ipc_send_init_dump(self->_rustInstance, self->_webviewHandle);
 }
 
+// Native editor edits, handled like the WebView's param:set/param:begin/
+// param:end messages.
+static void beamer_auv2_on_native_edit(void* context, uint32_t kind, uint32_t paramId, double value) {
+    {{COCOA_GUI_VIEW_CLASS}}* self = (__bridge {{COCOA_GUI_VIEW_CLASS}}*)context;
+    if (kind == BEAMER_AU_EDIT_VALUE) {
+        beamer_au_param_set_from_ui(self->_rustInstance, paramId, value);
+        float auValue = beamer_au_get_parameter_value_au(self->_rustInstance, paramId);
+        AudioUnitSetParameter(self->_audioUnit, paramId,
+            kAudioUnitScope_Global, 0, auValue, 0);
+        if (beamer_au_take_bus_layout_changed(self->_rustInstance)) {
+            UInt32 changed = 1;
+            AudioUnitSetProperty(self->_audioUnit, kBeamerAuPropertyBusLayoutChanged,
+                                 kAudioUnitScope_Global, 0, &changed, sizeof(changed));
+        }
+        return;
+    }
+    beamer_au_param_gesture_from_ui(self->_rustInstance, paramId, kind == BEAMER_AU_EDIT_BEGIN);
+    AudioUnitEvent event;
+    memset(&event, 0, sizeof(event));
+    event.mEventType = kind == BEAMER_AU_EDIT_BEGIN
+        ? kAudioUnitEvent_BeginParameterChangeGesture
+        : kAudioUnitEvent_EndParameterChangeGesture;
+    event.mArgument.mParameter.mAudioUnit = self->_audioUnit;
+    event.mArgument.mParameter.mParameterID = paramId;
+    event.mArgument.mParameter.mScope = kAudioUnitScope_Global;
+    AUEventListenerNotify(NULL, NULL, &event);
+}
+
 @implementation {{COCOA_GUI_VIEW_CLASS}}
 - (instancetype)initWithFrame:(NSRect)frame
                  webviewHandle:(void*)handle
@@ -1917,10 +2421,44 @@ static void beamer_auv2_on_loaded(void* context) {
     if (_webviewHandle != NULL) {
         beamer_webview_destroy(_webviewHandle);
     }
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_close(_nativeEditor);
+    }
+}
+- (void)setFrameSize:(NSSize)newSize {
+    [super setFrameSize:newSize];
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_resize(_nativeEditor, (uint32_t)newSize.width, (uint32_t)newSize.height);
+    }
 }
 - (void)_pollParams {
-    if (!_webviewHandle || !_rustInstance) return;
+    if ((!_webviewHandle && !_nativeEditor) || !_rustInstance) return;
+    if (beamer_au_take_state_dirty(_rustInstance)) {
+        UInt32 dirty = 1;
+        AudioUnitSetProperty(_audioUnit, kBeamerAuPropertyStateDirty,
+                             kAudioUnitScope_Global, 0, &dirty, sizeof(dirty));
+    }
+    if (beamer_au_take_display_name_changed(_rustInstance)) {
+        UInt32 changed = 1;
+        AudioUnitSetProperty(_audioUnit, kBeamerAuPropertyDisplayNameChanged,
+                             kAudioUnitScope_Global, 0, &changed, sizeof(changed));
+    }
+    if (beamer_au_take_preset_list_changed(_rustInstance)) {
+        UInt32 changed = 1;
+        AudioUnitSetProperty(_audioUnit, kBeamerAuPropertyPresetListChanged,
+                             kAudioUnitScope_Global, 0, &changed, sizeof(changed));
+    }
+    // Native editors draw parameter values themselves.
+    if (!_webviewHandle) return;
+    if (_timerFrames) {
+        beamer_au_ipc_send_frame(_rustInstance, _webviewHandle, beamer_au_ipc_timer_frame_time());
+    }
+    beamer_au_ipc_send_notification(_rustInstance, _webviewHandle);
+    beamer_au_ipc_send_gui_data(_rustInstance, _webviewHandle);
     if (_paramCount == 0) return;
+    // Values moved by a parameter constraint are reported to the host's
+    // parameter listeners along with the GUI update.
+    BOOL adjusted = beamer_au_take_parameters_adjusted(_rustInstance);
 
     NSMutableString* script = [NSMutableString stringWithString:@"window.__BEAMER__._onParams({"];
     BOOL any = NO;
@@ -1931,6 +2469,10 @@ static void beamer_auv2_on_loaded(void* context) {
         double val = beamer_au_param_get_normalized(_rustInstance, info.id);
         if (val == _lastParamValues[i]) continue;
         _lastParamValues[i] = val;
+        if (adjusted) {
+            AudioUnitParameter parameter = {_audioUnit, info.id, kAudioUnitScope_Global, 0};
+            AUParameterListenerNotify(NULL, NULL, &parameter);
+        }
         double plain = beamer_au_param_get_plain(_rustInstance, info.id);
         char text[128];
         beamer_au_param_get_display_text(_rustInstance, info.id, text, sizeof(text));
@@ -1963,6 +2505,10 @@ static void beamer_auv2_on_loaded(void* context) {
         beamer_webview_destroy(_webviewHandle);
         _webviewHandle = NULL;
     }
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_close(_nativeEditor);
+        _nativeEditor = NULL;
+    }
 }
 @end
 
@@ -1993,6 +2539,25 @@ static void beamer_auv2_on_loaded(void* context) {
     beamer_au_get_gui_size(rustInstance, &width, &height);
     NSSize viewSize = NSMakeSize(width, height);
 
+    if (beamer_au_has_native_gui(rustInstance)) {
+        // The native editor draws into the GUI view directly. The view is
+        // the edit callback's context; -dealloc closes the editor first.
+        {{COCOA_GUI_VIEW_CLASS}}* guiView = [[{{COCOA_GUI_VIEW_CLASS}} alloc]
+            initWithFrame:NSMakeRect(0, 0, viewSize.width, viewSize.height)
+            webviewHandle:NULL
+             rustInstance:rustInstance
+                audioUnit:audioUnit];
+        guiView->_nativeEditor = beamer_au_native_editor_open(
+            rustInstance, (__bridge void*)guiView, beamer_auv2_on_native_edit,
+            (__bridge void*)guiView);
+        if (guiView->_nativeEditor == NULL) {
+            return nil;
+        }
+        beamer_au_native_editor_get_size(guiView->_nativeEditor, &width, &height);
+        [guiView setFrameSize:NSMakeSize(width, height)];
+        return guiView;
+    }
+
     // Create WebView via beamer-webview C-ABI (shared platform layer)
 #ifdef DEBUG
     bool devTools = true;
@@ -16,13 +16,24 @@
                                      options:(AudioComponentInstantiationOptions)options
                                        error:(NSError**)outError;
 - (BeamerAuInstanceHandle)rustInstance;
-- (void)setSettingFromWebView:(BOOL)flag;
+- (void)beginParameterGesture:(uint32_t)paramId;
+- (void)setParameterFromUI:(uint32_t)paramId value:(double)value;
+- (void)endParameterGesture:(uint32_t)paramId;
+- (void)endAllParameterGestures;
+- (void)refreshParameterListIfChanged;
+- (void)notifyStateDirtyIfMarked;
+- (void)applyOversamplingIfChanged;
+- (void)syncParametersIfAdjusted;
+- (void)notifyDisplayNameIfChanged;
+- (void)notifyPresetListIfChanged;
+- (void)setParameterChangeHandler:(void (^ _Nullable)(uint32_t paramId))handler;
 @end
 
 @interface {{EXTENSION_CLASS}} : AUViewController <AUAudioUnitFactory>
 {
 @public
     void* _webviewHandle;
+    BeamerAuNativeEditorHandle _nativeEditor;
     uint32_t _guiWidth;
     uint32_t _guiHeight;
     {{WRAPPER_CLASS}}* _wrapper;
@@ -30,11 +41,13 @@
     BOOL _webviewLoaded;
     double* _lastParamValues;
     uint32_t _paramCount;
+    BOOL _timerFrames;
 }
 @end
 
 @interface {{EXTENSION_CLASS}} ()
 - (void)_ensureWebView;
+- (BOOL)_ensureNativeEditor;
 - (void)_sendInitDump;
 - (void)_startSyncTimer;
 - (void)_pollParams;
@@ -44,6 +57,11 @@
 // MARK: - WebView IPC Callbacks
 // =============================================================================
 
+static void beamer_auv3_ext_on_frame(void* context, double timeMs) {
+    {{EXTENSION_CLASS}}* ext = (__bridge {{EXTENSION_CLASS}}*)context;
+    beamer_au_ipc_send_frame([ext->_wrapper rustInstance], ext->_webviewHandle, timeMs);
+}
+
 static void beamer_auv3_ext_on_message(void* context, const uint8_t* json, size_t len) {
     {{EXTENSION_CLASS}}* ext = (__bridge {{EXTENSION_CLASS}}*)context;
     BeamerAuInstanceHandle instance = [ext->_wrapper rustInstance];
@@ -60,38 +78,25 @@ static void beamer_auv3_ext_on_message(void* context, const uint8_t* json, size_
     if ([type isEqualToString:@"param:set"]) {
         uint32_t paramId = [msg[@"id"] unsignedIntValue];
         double value = [msg[@"value"] doubleValue];
-        beamer_au_param_set_from_ui(instance, paramId, value);
-        AUParameter* param = [ext->_wrapper.parameterTree parameterWithAddress:(AUParameterAddress)paramId];
-        if (param) {
-            float min = param.minValue;
-            float max = param.maxValue;
-            // Flag prevents the wrapper's observer from overwriting the
-            // precise f64 value in the Rust store with an f32 round-trip.
-            [ext->_wrapper setSettingFromWebView:YES];
-            param.value = min + (float)value * (max - min);
-            [ext->_wrapper setSettingFromWebView:NO];
-        }
+        // The wrapper reports the change as an automation value event.
+        [ext->_wrapper setParameterFromUI:paramId value:value];
         // Echo authoritative values back to JS immediately.
         beamer_au_ipc_echo_param(instance, ext->_webviewHandle,
                                  paramId, ext->_lastParamValues, ext->_paramCount);
     } else if ([type isEqualToString:@"param:begin"]) {
-        uint32_t paramId = [msg[@"id"] unsignedIntValue];
-        AUParameter* param = [ext->_wrapper.parameterTree parameterWithAddress:(AUParameterAddress)paramId];
-        if (param) {
-            [param setValue:param.value originator:nil atHostTime:0 eventType:AUParameterAutomationEventTypeTouch];
-        }
+        [ext->_wrapper beginParameterGesture:[msg[@"id"] unsignedIntValue]];
     } else if ([type isEqualToString:@"param:end"]) {
-        uint32_t paramId = [msg[@"id"] unsignedIntValue];
-        AUParameter* param = [ext->_wrapper.parameterTree parameterWithAddress:(AUParameterAddress)paramId];
-        if (param) {
-            [param setValue:param.value originator:nil atHostTime:0 eventType:AUParameterAutomationEventTypeRelease];
-        }
+        [ext->_wrapper endParameterGesture:[msg[@"id"] unsignedIntValue]];
     } else if ([type isEqualToString:@"invoke"]) {
         if (!beamer_au_ipc_handle_builtin_invoke(instance, ext->_webviewHandle, msg)) {
             beamer_au_ipc_handle_invoke(instance, ext->_webviewHandle, msg);
         }
+        [ext->_wrapper refreshParameterListIfChanged];
     } else if ([type isEqualToString:@"event"]) {
         beamer_au_ipc_handle_event(instance, msg);
+    } else if ([type isEqualToString:@"frames"]) {
+        ext->_timerFrames = beamer_au_ipc_handle_frames(ext->_webviewHandle, msg,
+                                                        beamer_auv3_ext_on_frame, context);
     }
 }
 
@@ -108,6 +113,19 @@ static void beamer_auv3_ext_on_loaded(void* context) {
     }
 }
 
+// Native editor edits, handled like the WebView's param:set/param:begin/
+// param:end messages.
+static void beamer_auv3_ext_on_native_edit(void* context, uint32_t kind, uint32_t paramId, double value) {
+    {{EXTENSION_CLASS}}* ext = (__bridge {{EXTENSION_CLASS}}*)context;
+    if (kind == BEAMER_AU_EDIT_BEGIN) {
+        [ext->_wrapper beginParameterGesture:paramId];
+    } else if (kind == BEAMER_AU_EDIT_VALUE) {
+        [ext->_wrapper setParameterFromUI:paramId value:value];
+    } else {
+        [ext->_wrapper endParameterGesture:paramId];
+    }
+}
+
 // =============================================================================
 // MARK: - Extension Implementation
 // =============================================================================
@@ -132,14 +150,18 @@ static void beamer_auv3_ext_on_loaded(void* context) {
 
     // Create the WebView early so content can load before the host
     // shows the window, matching the timing VST3 and AUv2 get.
-    [self _ensureWebView];
+    if (![self _ensureNativeEditor]) {
+        [self _ensureWebView];
+    }
 }
 
 - (void)viewWillAppear {
     [super viewWillAppear];
 
-    // Recreate the WebView if it was destroyed on close.
-    [self _ensureWebView];
+    // Recreate the editor if it was destroyed on close.
+    if (![self _ensureNativeEditor]) {
+        [self _ensureWebView];
+    }
 }
 
 - (void)viewDidLoad {
@@ -184,6 +206,34 @@ static void beamer_auv3_ext_on_loaded(void* context) {
     }
 }
 
+// Open the native editor if the plugin has one. Needs the audio unit, so
+// until it exists the WebView path runs and is replaced here once it does.
+- (BOOL)_ensureNativeEditor {
+    if (_nativeEditor != NULL) {
+        return YES;
+    }
+    BeamerAuInstanceHandle instance = [_wrapper rustInstance];
+    if (!instance || !beamer_au_has_native_gui(instance)) {
+        return NO;
+    }
+    if (_webviewHandle != NULL) {
+        beamer_webview_destroy(_webviewHandle);
+        _webviewHandle = NULL;
+        _webviewLoaded = NO;
+    }
+    _nativeEditor = beamer_au_native_editor_open(
+        instance, (__bridge void*)self.view, beamer_auv3_ext_on_native_edit,
+        (__bridge void*)self);
+    if (_nativeEditor == NULL) {
+        return NO;
+    }
+    uint32_t width = 0, height = 0;
+    beamer_au_native_editor_get_size(_nativeEditor, &width, &height);
+    self.preferredContentSize = NSMakeSize(width, height);
+    [self _startSyncTimer];
+    return YES;
+}
+
 - (void)_sendInitDump {
     if (!_webviewHandle || !_wrapper) return;
     BeamerAuInstanceHandle instance = [_wrapper rustInstance];
@@ -212,12 +262,33 @@ static void beamer_auv3_ext_on_loaded(void* context) {
         (void)t;
         [weakSelf _pollParams];
     }];
+
+    // Host-side changes arrive through the wrapper's parameter observer
+    // without waiting for the next tick.
+    [_wrapper setParameterChangeHandler:^(uint32_t paramId) {
+        __strong typeof(self) strongSelf = weakSelf;
+        if (strongSelf == nil || strongSelf->_webviewHandle == NULL) return;
+        beamer_au_ipc_echo_param([strongSelf->_wrapper rustInstance], strongSelf->_webviewHandle,
+                                 paramId, strongSelf->_lastParamValues, strongSelf->_paramCount);
+    }];
 }
 
 - (void)_pollParams {
-    if (!_webviewHandle || !_wrapper) return;
+    if ((!_webviewHandle && !_nativeEditor) || !_wrapper) return;
     BeamerAuInstanceHandle instance = [_wrapper rustInstance];
     if (!instance) return;
+    [_wrapper notifyStateDirtyIfMarked];
+    [_wrapper applyOversamplingIfChanged];
+    [_wrapper syncParametersIfAdjusted];
+    [_wrapper notifyDisplayNameIfChanged];
+    [_wrapper notifyPresetListIfChanged];
+    // Native editors draw parameter values themselves.
+    if (!_webviewHandle) return;
+    if (_timerFrames) {
+        beamer_au_ipc_send_frame(instance, _webviewHandle, beamer_au_ipc_timer_frame_time());
+    }
+    beamer_au_ipc_send_notification(instance, _webviewHandle);
+    beamer_au_ipc_send_gui_data(instance, _webviewHandle);
     if (_paramCount == 0) return;
 
     NSMutableString* script = [NSMutableString stringWithString:@"window.__BEAMER__._onParams({"];
@@ -246,16 +317,22 @@ static void beamer_auv3_ext_on_loaded(void* context) {
 
 - (void)viewDidLayout {
     [super viewDidLayout];
+    CGSize size = self.view.frame.size;
     if (_webviewHandle != NULL) {
-        CGSize size = self.view.frame.size;
         beamer_webview_set_frame(_webviewHandle, 0, 0,
             (int32_t)size.width, (int32_t)size.height);
     }
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_resize(_nativeEditor, (uint32_t)size.width, (uint32_t)size.height);
+    }
 }
 
 - (void)viewDidDisappear {
     [super viewDidDisappear];
 
+    [_wrapper endAllParameterGestures];
+    [_wrapper setParameterChangeHandler:nil];
+
     [_syncTimer invalidate];
     _syncTimer = nil;
     _webviewLoaded = NO;
@@ -267,6 +344,10 @@ static void beamer_auv3_ext_on_loaded(void* context) {
         beamer_webview_destroy(_webviewHandle);
         _webviewHandle = NULL;
     }
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_close(_nativeEditor);
+        _nativeEditor = NULL;
+    }
 }
 
 - (void)dealloc {
@@ -280,6 +361,10 @@ static void beamer_auv3_ext_on_loaded(void* context) {
         beamer_webview_destroy(_webviewHandle);
         _webviewHandle = NULL;
     }
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_close(_nativeEditor);
+        _nativeEditor = NULL;
+    }
 }
 
 - (AUAudioUnit *)createAudioUnitWithComponentDescription:(AudioComponentDescription)desc
@@ -298,6 +383,11 @@ static void beamer_auv3_ext_on_loaded(void* context) {
 
     _wrapper = wrapper;
 
+    // A native editor replaces the WebView created before the audio unit.
+    if (self.isViewLoaded && [self _ensureNativeEditor]) {
+        return wrapper;
+    }
+
     // If the WebView already finished loading, send the init dump and
     // start the parameter sync timer now. Otherwise the on_loaded
     // callback will handle it when the load completes.
@@ -31,6 +31,7 @@ static const AUAudioFrameCount kDefaultMaxFrames = 4096;
     double _sampleRate;
     AUAudioFrameCount _maxFrames;
     BOOL _resourcesAllocated;
+    BOOL _busLayoutPending;
     BeamerAuBusConfig _busConfig;
     NSUInteger _instanceId;
     AVAudioPCMBuffer* _inputPCMBuffer;
@@ -39,14 +40,34 @@ static const AUAudioFrameCount kDefaultMaxFrames = 4096;
     AUInternalRenderBlock _cachedInternalRenderBlock;
     NSArray<AUAudioUnitPreset*>* _factoryPresets;
     void* _webviewHandle;
+    BeamerAuNativeEditorHandle _nativeEditor;
     NSTimer* _syncTimer;
+    dispatch_source_t _propertyTimer;
     double* _lastParamValues;
     uint32_t _paramCount;
-    _Atomic BOOL _settingFromWebView;
+    BOOL _timerFrames;
+    _Atomic BOOL _suppressValueObserver;
+    NSMutableIndexSet* _activeGestures;
+    AUParameterObserverToken _parameterObserverToken;
+    void (^_parameterChangeHandler)(uint32_t paramId);
 }
 
 + (NSUInteger)nextInstanceId;
 - (BeamerAuInstanceHandle)rustInstance;
+- (void)beginParameterGesture:(uint32_t)paramId;
+- (void)setParameterFromUI:(uint32_t)paramId value:(double)value;
+- (void)endParameterGesture:(uint32_t)paramId;
+- (void)endAllParameterGestures;
+- (void)refreshParameterListIfChanged;
+- (void)refreshBusesIfChanged;
+- (void)notifyStateDirtyIfMarked;
+- (void)applyOversamplingIfChanged;
+- (void)startPropertyTimer;
+- (void)stopPropertyTimer;
+- (void)syncParametersIfAdjusted;
+- (void)notifyDisplayNameIfChanged;
+- (void)notifyPresetListIfChanged;
+- (void)setParameterChangeHandler:(void (^ _Nullable)(uint32_t paramId))handler;
 
 @end
 
@@ -74,8 +95,70 @@ static NSUInteger {{WRAPPER_CLASS}}InstanceCounter = 0;
     return _rustInstance;
 }
 
-- (void)setSettingFromWebView:(BOOL)flag {
-    _settingFromWebView = flag;
+// =============================================================================
+// MARK: - Parameter Gestures
+// =============================================================================
+
+// UI edits are reported to the host as AUParameter automation events so
+// hosts that record automation (Logic Pro) see the same touch/value/release
+// sequence as for native plugins. This mirrors VST3's beginEdit/performEdit/
+// endEdit and AUv2's Begin/EndParameterChangeGesture.
+
+- (void)_sendAutomationEvent:(AUParameter*)param
+                       value:(AUValue)value
+                        type:(AUParameterAutomationEventType)type {
+    // Flag prevents the observer from overwriting the precise f64 value in
+    // the Rust store with an f32 round-trip (touch and release events carry
+    // the current value and reach the observer too).
+    _suppressValueObserver = YES;
+    // Our own observer skips the change: the page already has the value.
+    [param setValue:value originator:_parameterObserverToken atHostTime:0 eventType:type];
+    _suppressValueObserver = NO;
+}
+
+- (void)beginParameterGesture:(uint32_t)paramId {
+    beamer_au_param_gesture_from_ui(_rustInstance, paramId, true);
+    AUParameter* param = [_parameterTree parameterWithAddress:(AUParameterAddress)paramId];
+    if (param == nil || [_activeGestures containsIndex:paramId]) {
+        return;
+    }
+    [_activeGestures addIndex:paramId];
+    [self _sendAutomationEvent:param value:param.value type:AUParameterAutomationEventTypeTouch];
+}
+
+- (void)setParameterFromUI:(uint32_t)paramId value:(double)value {
+    beamer_au_param_set_from_ui(_rustInstance, paramId, value);
+    [self syncParametersIfAdjusted];
+    AUParameter* param = [_parameterTree parameterWithAddress:(AUParameterAddress)paramId];
+    if (param == nil) {
+        return;
+    }
+    // Report the stored value, which a parameter constraint may have clamped
+    value = beamer_au_param_get_normalized(_rustInstance, paramId);
+    AUValue auValue = param.minValue + (AUValue)value * (param.maxValue - param.minValue);
+    [self _sendAutomationEvent:param value:auValue type:AUParameterAutomationEventTypeValue];
+}
+
+- (void)endParameterGesture:(uint32_t)paramId {
+    beamer_au_param_gesture_from_ui(_rustInstance, paramId, false);
+    if (![_activeGestures containsIndex:paramId]) {
+        return;
+    }
+    [_activeGestures removeIndex:paramId];
+    AUParameter* param = [_parameterTree parameterWithAddress:(AUParameterAddress)paramId];
+    if (param != nil) {
+        [self _sendAutomationEvent:param value:param.value type:AUParameterAutomationEventTypeRelease];
+    }
+}
+
+// Release gestures left open when the editor goes away mid-drag, so the
+// host does not stay in touch mode for those parameters.
+- (void)endAllParameterGestures {
+    NSIndexSet* open = [_activeGestures copy];
+    [open enumerateIndexesUsingBlock:^(NSUInteger paramId, BOOL* stop) {
+        (void)stop;
+        [self endParameterGesture:(uint32_t)paramId];
+    }];
 }
 
 - (instancetype)initWithComponentDescription:(AudioComponentDescription)componentDescription
@@ -117,12 +200,12 @@ static NSUInteger {{WRAPPER_CLASS}}InstanceCounter = 0;
     _maxFrames = kDefaultMaxFrames;
     _resourcesAllocated = NO;
     _webviewHandle = NULL;
+    _activeGestures = [[NSMutableIndexSet alloc] init];
     memset(&_busConfig, 0, sizeof(_busConfig));
 
-    AUAudioUnitPreset* defaultPreset = [[AUAudioUnitPreset alloc] init];
-    defaultPreset.number = 0;
-    defaultPreset.name = @"Default";
-    _currentPreset = defaultPreset;
+    // No preset is selected until the host (or user) picks one. Reporting
+    // factory preset 0 here would claim the default state matches it.
+    _currentPreset = nil;
 
     _rustInstance = beamer_au_create_instance();
     if (_rustInstance == NULL) {
@@ -152,6 +235,11 @@ static NSUInteger {{WRAPPER_CLASS}}InstanceCounter = 0;
 // MARK: - WebView IPC Callbacks
 // =============================================================================
 
+static void beamer_auv3_on_frame(void* context, double timeMs) {
+    {{WRAPPER_CLASS}}* self = (__bridge {{WRAPPER_CLASS}}*)context;
+    beamer_au_ipc_send_frame(self->_rustInstance, self->_webviewHandle, timeMs);
+}
+
 static void beamer_auv3_on_message(void* context, const uint8_t* json, size_t len) {
     {{WRAPPER_CLASS}}* self = (__bridge {{WRAPPER_CLASS}}*)context;
     NSString* jsonStr = [[NSString alloc] initWithBytes:json length:len encoding:NSUTF8StringEncoding];
@@ -165,38 +253,26 @@ static void beamer_auv3_on_message(void* context, const uint8_t* json, size_t le
     if ([type isEqualToString:@"param:set"]) {
         uint32_t paramId = [msg[@"id"] unsignedIntValue];
         double value = [msg[@"value"] doubleValue];
-        beamer_au_param_set_from_ui(self->_rustInstance, paramId, value);
-        AUParameter* param = [self.parameterTree parameterWithAddress:(AUParameterAddress)paramId];
-        if (param) {
-            float min = param.minValue;
-            float max = param.maxValue;
-            // Flag prevents the observer from overwriting the precise
-            // f64 value in the Rust store with an f32 round-trip.
-            self->_settingFromWebView = YES;
-            param.value = min + (float)value * (max - min);
-            self->_settingFromWebView = NO;
-        }
+        [self setParameterFromUI:paramId value:value];
         // Echo authoritative values back to JS immediately.
         beamer_au_ipc_echo_param(self->_rustInstance, self->_webviewHandle,
                                  paramId, self->_lastParamValues, self->_paramCount);
+        [self refreshBusesIfChanged];
     } else if ([type isEqualToString:@"param:begin"]) {
-        uint32_t paramId = [msg[@"id"] unsignedIntValue];
-        AUParameter* param = [self.parameterTree parameterWithAddress:(AUParameterAddress)paramId];
-        if (param) {
-            [param setValue:param.value originator:nil atHostTime:0 eventType:AUParameterAutomationEventTypeTouch];
-        }
+        [self beginParameterGesture:[msg[@"id"] unsignedIntValue]];
     } else if ([type isEqualToString:@"param:end"]) {
-        uint32_t paramId = [msg[@"id"] unsignedIntValue];
-        AUParameter* param = [self.parameterTree parameterWithAddress:(AUParameterAddress)paramId];
-        if (param) {
-            [param setValue:param.value originator:nil atHostTime:0 eventType:AUParameterAutomationEventTypeRelease];
-        }
+        [self endParameterGesture:[msg[@"id"] unsignedIntValue]];
     } else if ([type isEqualToString:@"invoke"]) {
         if (!beamer_au_ipc_handle_builtin_invoke(self->_rustInstance, self->_webviewHandle, msg)) {
             beamer_au_ipc_handle_invoke(self->_rustInstance, self->_webviewHandle, msg);
         }
+        [self refreshParameterListIfChanged];
+        [self refreshBusesIfChanged];
     } else if ([type isEqualToString:@"event"]) {
         beamer_au_ipc_handle_event(self->_rustInstance, msg);
+    } else if ([type isEqualToString:@"frames"]) {
+        self->_timerFrames = beamer_au_ipc_handle_frames(self->_webviewHandle, msg,
+                                                         beamer_auv3_on_frame, context);
     }
 }
 
@@ -205,9 +281,33 @@ static void beamer_auv3_on_loaded(void* context) {
     beamer_au_ipc_send_init_dump(self->_rustInstance, self->_webviewHandle);
 }
 
+// Native editor edits, handled like the WebView's param:set/param:begin/
+// param:end messages.
+static void beamer_auv3_on_native_edit(void* context, uint32_t kind, uint32_t paramId, double value) {
+    {{WRAPPER_CLASS}}* self = (__bridge {{WRAPPER_CLASS}}*)context;
+    if (kind == BEAMER_AU_EDIT_BEGIN) {
+        [self beginParameterGesture:paramId];
+    } else if (kind == BEAMER_AU_EDIT_VALUE) {
+        [self setParameterFromUI:paramId value:value];
+        [self refreshBusesIfChanged];
+    } else {
+        [self endParameterGesture:paramId];
+    }
+}
+
+// Properties reported by beamer_au_dispatch_property_changes, as KVO.
+static void beamer_auv3_on_property_changed(void* context, uint32_t property) {
+    {{WRAPPER_CLASS}}* self = (__bridge {{WRAPPER_CLASS}}*)context;
+    if (property == kAudioUnitProperty_Latency) {
+        [self willChangeValueForKey:@"latency"];
+        [self didChangeValueForKey:@"latency"];
+    }
+}
+
 - (void)dealloc {
     [_syncTimer invalidate];
     _syncTimer = nil;
+    [self stopPropertyTimer];
     free(_lastParamValues);
     _lastParamValues = NULL;
 
@@ -218,6 +318,10 @@ static void beamer_auv3_on_loaded(void* context) {
         beamer_webview_destroy(_webviewHandle);
         _webviewHandle = NULL;
     }
+    if (_nativeEditor != NULL) {
+        beamer_au_native_editor_close(_nativeEditor);
+        _nativeEditor = NULL;
+    }
 
     if (_rustInstance != NULL) {
         if (_resourcesAllocated) {
@@ -232,7 +336,19 @@ static void beamer_auv3_on_loaded(void* context) {
 }
 
 - (void)_pollParams {
-    if (!_webviewHandle || !_rustInstance) return;
+    if ((!_webviewHandle && !_nativeEditor) || !_rustInstance) return;
+    [self notifyStateDirtyIfMarked];
+    [self applyOversamplingIfChanged];
+    [self syncParametersIfAdjusted];
+    [self notifyDisplayNameIfChanged];
+    [self notifyPresetListIfChanged];
+    // Native editors draw parameter values themselves.
+    if (!_webviewHandle) return;
+    if (_timerFrames) {
+        beamer_au_ipc_send_frame(_rustInstance, _webviewHandle, beamer_au_ipc_timer_frame_time());
+    }
+    beamer_au_ipc_send_notification(_rustInstance, _webviewHandle);
+    beamer_au_ipc_send_gui_data(_rustInstance, _webviewHandle);
     if (_paramCount == 0) return;
 
     NSMutableString* script = [NSMutableString stringWithString:@"window.__BEAMER__._onParams({"];
@@ -270,9 +386,22 @@ static void beamer_auv3_on_loaded(void* context) {
     if (channelCount == 0) channelCount = 2;
     if (channelCount > BEAMER_AU_MAX_CHANNELS) channelCount = BEAMER_AU_MAX_CHANNELS;
 
-    AVAudioFormat* format = [[AVAudioFormat alloc]
-        initStandardFormatWithSampleRate:kDefaultSampleRate
-                                channels:(AVAudioChannelCount)channelCount];
+    AVAudioFormat* format = nil;
+    if (channelCount > 2) {
+        // Multichannel formats need a layout (5.1, 7.1.4, ambisonics, ...)
+        AudioChannelLayoutTag tag = isInput
+            ? beamer_au_get_input_bus_layout_tag(_rustInstance, index)
+            : beamer_au_get_output_bus_layout_tag(_rustInstance, index);
+        AVAudioChannelLayout* layout = [AVAudioChannelLayout layoutWithLayoutTag:tag];
+        if (layout != nil && layout.channelCount == channelCount) {
+            format = [[AVAudioFormat alloc] initStandardFormatWithSampleRate:kDefaultSampleRate
+                                                               channelLayout:layout];
+        }
+    } else {
+        format = [[AVAudioFormat alloc]
+            initStandardFormatWithSampleRate:kDefaultSampleRate
+                                    channels:(AVAudioChannelCount)channelCount];
+    }
     if (format == nil) {
         format = defaultFormat;
     }
@@ -297,6 +426,27 @@ static void beamer_auv3_on_loaded(void* context) {
     return bus;
 }
 
+- (NSArray<AUAudioUnitBus*>*)createBussesWithCount:(uint32_t)count
+                                            isInput:(BOOL)isInput
+                                              error:(NSError**)outError {
+    AVAudioFormat* stereoFormat = [[AVAudioFormat alloc]
+        initStandardFormatWithSampleRate:kDefaultSampleRate
+                                channels:2];
+
+    NSMutableArray<AUAudioUnitBus*>* buses = [[NSMutableArray alloc] initWithCapacity:count];
+    for (uint32_t i = 0; i < count; i++) {
+        AUAudioUnitBus* bus = [self createBusAtIndex:i
+                                             isInput:isInput
+                                       defaultFormat:stereoFormat
+                                               error:outError];
+        if (bus == nil) {
+            return nil;
+        }
+        [buses addObject:bus];
+    }
+    return buses;
+}
+
 - (BOOL)setupBusArraysWithError:(NSError**)outError {
     if (_rustInstance == NULL) {
         if (outError != NULL) {
@@ -317,32 +467,10 @@ static void beamer_auv3_on_loaded(void* context) {
         outputBusCount = 1;
     }
 
-    AVAudioFormat* stereoFormat = [[AVAudioFormat alloc]
-        initStandardFormatWithSampleRate:kDefaultSampleRate
-                                channels:2];
-
-    NSMutableArray<AUAudioUnitBus*>* inputBuses = [[NSMutableArray alloc] initWithCapacity:inputBusCount];
-    for (uint32_t i = 0; i < inputBusCount; i++) {
-        AUAudioUnitBus* bus = [self createBusAtIndex:i
-                                             isInput:YES
-                                       defaultFormat:stereoFormat
-                                               error:outError];
-        if (bus == nil) {
-            return NO;
-        }
-        [inputBuses addObject:bus];
-    }
-
-    NSMutableArray<AUAudioUnitBus*>* outputBuses = [[NSMutableArray alloc] initWithCapacity:outputBusCount];
-    for (uint32_t i = 0; i < outputBusCount; i++) {
-        AUAudioUnitBus* bus = [self createBusAtIndex:i
-                                             isInput:NO
-                                       defaultFormat:stereoFormat
-                                               error:outError];
-        if (bus == nil) {
-            return NO;
-        }
-        [outputBuses addObject:bus];
+    NSArray<AUAudioUnitBus*>* inputBuses = [self createBussesWithCount:inputBusCount isInput:YES error:outError];
+    NSArray<AUAudioUnitBus*>* outputBuses = [self createBussesWithCount:outputBusCount isInput:NO error:outError];
+    if (inputBuses == nil || outputBuses == nil) {
+        return NO;
     }
 
     _inputBusArray = [[AUAudioUnitBusArray alloc] initWithAudioUnit:self
@@ -355,6 +483,40 @@ static void beamer_auv3_on_loaded(void* context) {
     return YES;
 }
 
+// Swap in the buses of a layout selected by a loaded state or a GUI edit.
+// AUAudioUnitBusArray posts KVO notifications so the host re-reads them.
+// While render resources are allocated the swap waits for deallocation.
+- (void)refreshBusesIfChanged {
+    if (_rustInstance == NULL || !beamer_au_take_bus_layout_changed(_rustInstance)) {
+        return;
+    }
+    if (_resourcesAllocated) {
+        _busLayoutPending = YES;
+        return;
+    }
+    [self replaceBusArrays];
+}
+
+- (void)replaceBusArrays {
+    _busLayoutPending = NO;
+
+    uint32_t inputBusCount = beamer_au_get_input_bus_count(_rustInstance);
+    uint32_t outputBusCount = beamer_au_get_output_bus_count(_rustInstance);
+    if (inputBusCount > BEAMER_AU_MAX_BUSES) inputBusCount = BEAMER_AU_MAX_BUSES;
+    if (outputBusCount > BEAMER_AU_MAX_BUSES) outputBusCount = BEAMER_AU_MAX_BUSES;
+    if (inputBusCount == 0 && outputBusCount == 0) {
+        outputBusCount = 1;
+    }
+
+    NSArray<AUAudioUnitBus*>* inputBuses = [self createBussesWithCount:inputBusCount isInput:YES error:NULL];
+    NSArray<AUAudioUnitBus*>* outputBuses = [self createBussesWithCount:outputBusCount isInput:NO error:NULL];
+    if (inputBuses == nil || outputBuses == nil) {
+        return;
+    }
+    [_inputBusArray replaceBusses:inputBuses];
+    [_outputBusArray replaceBusses:outputBuses];
+}
+
 - (void)buildBusConfig {
     memset(&_busConfig, 0, sizeof(_busConfig));
 
@@ -517,10 +679,12 @@ static void beamer_auv3_on_loaded(void* context) {
     }
 
     _resourcesAllocated = YES;
+    [self startPropertyTimer];
     return YES;
 }
 
 - (void)deallocateRenderResources {
+    [self stopPropertyTimer];
     _inputMutableABL = NULL;
     _inputPCMBuffer = nil;
 
@@ -530,6 +694,10 @@ static void beamer_auv3_on_loaded(void* context) {
     }
 
     [super deallocateRenderResources];
+
+    if (_busLayoutPending && _rustInstance != NULL) {
+        [self replaceBusArrays];
+    }
 }
 
 - (AUInternalRenderBlock)internalRenderBlock {
@@ -558,8 +726,9 @@ static void beamer_auv3_on_loaded(void* context) {
         AUAudioFrameCount maxFrames = blockSelf->_maxFrames;
 
         AudioBufferList* inputData = NULL;
+        // Zero-frame renders are parameter flushes; there's no audio to pull
         if (inputPCMBuffer != nil && inputMutableABL != NULL && pullInputBlock != nil
-            && frameCount <= maxFrames) {
+            && frameCount > 0 && frameCount <= maxFrames) {
             const AudioBufferList* originalABL = inputPCMBuffer.audioBufferList;
             UInt32 byteSize = frameCount * sizeof(float);
 
@@ -612,7 +781,8 @@ static void beamer_auv3_on_loaded(void* context) {
         return;
     }
 
-    uint32_t paramCount = beamer_au_get_parameter_count(_rustInstance);
+    // Host-facing list: internal parameters appear only once promoted.
+    uint32_t paramCount = beamer_au_get_host_parameter_count(_rustInstance);
     uint32_t groupCount = beamer_au_get_group_count(_rustInstance);
 
     if (paramCount == 0) {
@@ -623,7 +793,7 @@ static void beamer_auv3_on_loaded(void* context) {
     NSMutableArray<NSDictionary*>* allParams = [[NSMutableArray alloc] initWithCapacity:paramCount];
     for (uint32_t i = 0; i < paramCount; i++) {
         BeamerAuParameterInfo info;
-        if (!beamer_au_get_parameter_info(_rustInstance, i, &info)) {
+        if (!beamer_au_get_host_parameter_info(_rustInstance, i, &info)) {
             continue;
         }
 
@@ -754,6 +924,104 @@ static void beamer_auv3_on_loaded(void* context) {
     [self setupParameterCallbacks];
 }
 
+// Rebuild the AUParameterTree after the GUI promoted or demoted a parameter
+// or a loaded state restored different promotions. The KVO notification on
+// parameterTree makes the host rescan its parameter list.
+- (void)refreshParameterListIfChanged {
+    if (_rustInstance == NULL || !beamer_au_take_parameter_list_changed(_rustInstance)) {
+        return;
+    }
+    [self willChangeValueForKey:@"parameterTree"];
+    [self buildParameterTree];
+    [self didChangeValueForKey:@"parameterTree"];
+}
+
+// Tell the host the project needs saving after the plugin marked its state
+// dirty. Hosts observe fullState for changes outside the parameter tree.
+- (void)notifyStateDirtyIfMarked {
+    if (_rustInstance == NULL || !beamer_au_take_state_dirty(_rustInstance)) {
+        return;
+    }
+    [self willChangeValueForKey:@"fullState"];
+    [self didChangeValueForKey:@"fullState"];
+}
+
+// Prepare the processor again after a loaded state, GUI edit or automation
+// selected another oversampling factor, and tell the host the latency
+// changed. The render block skips blocks while the processor is replaced.
+// Also forwards latency changes the plugin announced itself.
+- (void)applyOversamplingIfChanged {
+    if (_rustInstance == NULL) {
+        return;
+    }
+    beamer_au_dispatch_property_changes(_rustInstance, beamer_auv3_on_property_changed, (__bridge void*)self);
+}
+
+// Check for property changes on the main queue while render resources are
+// allocated, so latency changes announced from the render block and presets
+// applied by a crossfade reach the host with no editor open.
+- (void)startPropertyTimer {
+    if (_propertyTimer != nil) {
+        return;
+    }
+    _propertyTimer = dispatch_source_create(DISPATCH_SOURCE_TYPE_TIMER, 0, 0, dispatch_get_main_queue());
+    dispatch_source_set_timer(_propertyTimer, dispatch_time(DISPATCH_TIME_NOW, 50 * NSEC_PER_MSEC),
+                              50 * NSEC_PER_MSEC, 10 * NSEC_PER_MSEC);
+    // Use __weak to avoid a retain cycle (self -> _propertyTimer -> block -> self).
+    __weak typeof(self) weakSelf = self;
+    dispatch_source_set_event_handler(_propertyTimer, ^{
+        [weakSelf applyOversamplingIfChanged];
+        [weakSelf syncParametersIfAdjusted];
+    });
+    dispatch_resume(_propertyTimer);
+}
+
+- (void)stopPropertyTimer {
+    if (_propertyTimer == nil) {
+        return;
+    }
+    dispatch_source_cancel(_propertyTimer);
+    _propertyTimer = nil;
+}
+
+// Push the Rust values into the parameter tree after a parameter constraint
+// moved a value the host did not set (e.g. attack pushed release along) or
+// the audio thread applied a crossfaded preset.
+- (void)syncParametersIfAdjusted {
+    if (_rustInstance == NULL || !beamer_au_take_parameters_adjusted(_rustInstance)) {
+        return;
+    }
+    [self _syncParameterTreeFromRust];
+}
+
+// Tell the host to re-read audioUnitShortName after the plugin suggested
+// another display name (e.g. for the loaded preset).
+- (void)notifyDisplayNameIfChanged {
+    if (_rustInstance == NULL || !beamer_au_take_display_name_changed(_rustInstance)) {
+        return;
+    }
+    [self willChangeValueForKey:@"audioUnitShortName"];
+    [self didChangeValueForKey:@"audioUnitShortName"];
+}
+
+// Rebuild factoryPresets and tell the host to re-read them after presets
+// were added, removed or renamed at runtime (e.g. user presets on disk).
+- (void)notifyPresetListIfChanged {
+    if (_rustInstance == NULL || !beamer_au_take_preset_list_changed(_rustInstance)) {
+        return;
+    }
+    [self willChangeValueForKey:@"factoryPresets"];
+    _factoryPresets = nil;
+    [self didChangeValueForKey:@"factoryPresets"];
+}
+
+// Called on the main thread with the address of every parameter the host,
+// its automation or a generic editor changes, for a GUI living outside this
+// class (the AUv3 extension's view controller).
+- (void)setParameterChangeHandler:(void (^ _Nullable)(uint32_t paramId))handler {
+    _parameterChangeHandler = [handler copy];
+}
+
 - (void)setupParameterCallbacks {
     if (_parameterTree == nil || _rustInstance == NULL) {
         return;
@@ -761,6 +1029,27 @@ static void beamer_auv3_on_loaded(void* context) {
 
     __weak typeof(self) weakSelf = self;
 
+    // Push host-side changes to the WebView right away instead of at the
+    // next sync tick. Edits from the WebView pass this token as originator
+    // and are not reported back.
+    _parameterObserverToken = [_parameterTree tokenByAddingParameterObserver:^(AUParameterAddress address, AUValue value) {
+        (void)value;
+        dispatch_async(dispatch_get_main_queue(), ^{
+            __strong typeof(self) strongSelf = weakSelf;
+            if (strongSelf == nil) {
+                return;
+            }
+            if (strongSelf->_webviewHandle != NULL) {
+                beamer_au_ipc_echo_param(strongSelf->_rustInstance, strongSelf->_webviewHandle,
+                                         (uint32_t)address, strongSelf->_lastParamValues,
+                                         strongSelf->_paramCount);
+            }
+            if (strongSelf->_parameterChangeHandler != nil) {
+                strongSelf->_parameterChangeHandler((uint32_t)address);
+            }
+        });
+    }];
+
     _parameterTree.implementorValueProvider = ^AUValue(AUParameter* param) {
         __strong typeof(self) strongSelf = weakSelf;
         if (strongSelf == nil) {
@@ -781,10 +1070,11 @@ static void beamer_auv3_on_loaded(void* context) {
         if (strongSelf == nil) {
             return;
         }
-        // Skip when the change originated from the webview. The Rust
-        // store already has the precise f64 value; writing the f32
-        // AUValue back would introduce round-trip artifacts.
-        if (strongSelf->_settingFromWebView) {
+        // Skip when the change originated on the Rust side (webview edits,
+        // preset and state loads). The Rust store already has the precise
+        // f64 value; writing the f32 AUValue back would introduce round-trip
+        // artifacts.
+        if (strongSelf->_suppressValueObserver) {
             return;
         }
 
@@ -793,6 +1083,15 @@ static void beamer_auv3_on_loaded(void* context) {
             beamer_au_set_parameter_value_au(strongSelf->_rustInstance, (uint32_t)param.address, value);
         }
         [strongSelf->_instanceLock unlock];
+
+        // Values moved by a parameter constraint are pushed to the tree on
+        // the main thread, outside this observer.
+        dispatch_async(dispatch_get_main_queue(), ^{
+            __strong typeof(self) innerSelf = weakSelf;
+            if (innerSelf != nil) {
+                [innerSelf syncParametersIfAdjusted];
+            }
+        });
     };
 
     _parameterTree.implementorStringFromValueCallback = ^NSString* _Nonnull(AUParameter* param, const AUValue* value) {
@@ -884,47 +1183,75 @@ static void beamer_auv3_on_loaded(void* context) {
                 return;
             }
 
-            if (_parameterTree != nil) {
-                for (AUParameter* param in _parameterTree.allParameters) {
-                    AUValue newValue = beamer_au_get_parameter_value_au(_rustInstance, (uint32_t)param.address);
-                    [param setValue:newValue originator:nil];
-                }
-            }
+            [self refreshParameterListIfChanged];
+            [self refreshBusesIfChanged];
+            [self applyOversamplingIfChanged];
+            [self _syncParameterTreeFromRust];
         }
     }
 }
 
+// Push the Rust store's values into the AUParameterTree so hosts and
+// observers see the new state. The observer is suppressed so the f32
+// AUValues don't overwrite the precise values in the store.
+- (void)_syncParameterTreeFromRust {
+    if (_parameterTree == nil) {
+        return;
+    }
+    _suppressValueObserver = YES;
+    for (AUParameter* param in _parameterTree.allParameters) {
+        AUValue newValue = beamer_au_get_parameter_value_au(_rustInstance, (uint32_t)param.address);
+        [param setValue:newValue originator:nil];
+    }
+    _suppressValueObserver = NO;
+}
+
 - (AUAudioUnitPreset*)currentPreset {
     return _currentPreset;
 }
 
+// Factory presets have number >= 0 and map to FactoryPresets indices
+// (mirroring the VST3 program list). User presets have negative numbers;
+// their state is stored by AUAudioUnit's user preset support and restored
+// through fullStateForDocument.
 - (void)setCurrentPreset:(AUAudioUnitPreset*)currentPreset {
     if (currentPreset == nil) {
         _currentPreset = nil;
         return;
     }
-    _currentPreset = currentPreset;
 
     if (currentPreset.number >= 0) {
-        [self applyFactoryPreset:(int)currentPreset.number];
+        if (![self applyFactoryPreset:(int)currentPreset.number]) {
+            return;
+        }
+    } else {
+        NSError* error = nil;
+        NSDictionary<NSString*, id>* presetState = [self presetStateFor:currentPreset error:&error];
+        if (presetState == nil) {
+            return;
+        }
+        self.fullStateForDocument = presetState;
     }
+    _currentPreset = currentPreset;
 }
 
-- (void)applyFactoryPreset:(int)presetNumber {
+- (BOOL)applyFactoryPreset:(int)presetNumber {
     if (_rustInstance == NULL) {
-        return;
+        return NO;
     }
 
     if (!beamer_au_apply_preset(_rustInstance, (uint32_t)presetNumber)) {
-        return;
+        return NO;
     }
 
-    if (_parameterTree != nil) {
-        for (AUParameter* param in _parameterTree.allParameters) {
-            AUValue newValue = beamer_au_get_parameter_value_au(_rustInstance, (uint32_t)param.address);
-            [param setValue:newValue originator:nil];
-        }
+    // With a preset crossfade the audio thread applies the preset once the
+    // fade-out completes; the property timer refreshes the tree then.
+    if (beamer_au_get_preset_crossfade_ms(_rustInstance) > 0.0f && _resourcesAllocated) {
+        return YES;
     }
+
+    [self _syncParameterTreeFromRust];
+    return YES;
 }
 
 - (NSArray<AUAudioUnitPreset*>*)factoryPresets {
@@ -971,6 +1298,24 @@ static void beamer_auv3_on_loaded(void* context) {
     return (NSTimeInterval)tailSamples / _sampleRate;
 }
 
+// The instance name the host assigns (usually the track name) is passed on
+// to the plugin's HostServices.
+- (void)setContextName:(NSString*)contextName {
+    [super setContextName:contextName];
+    if (_rustInstance != NULL) {
+        beamer_au_set_instance_name(_rustInstance, contextName.length > 0 ? [contextName UTF8String] : NULL);
+    }
+}
+
+// The display name suggested by the plugin, if any.
+- (NSString*)audioUnitShortName {
+    char name[256];
+    if (_rustInstance != NULL && beamer_au_get_display_name(_rustInstance, name, sizeof(name)) > 0) {
+        return [NSString stringWithUTF8String:name];
+    }
+    return [super audioUnitShortName];
+}
+
 - (BOOL)supportsMPE {
     return NO;
 }
@@ -1053,9 +1398,15 @@ static void beamer_auv3_on_loaded(void* context) {
 
     // Destroy previous WebView if host calls this method again
     if (_webviewHandle != NULL) {
+        [self endAllParameterGestures];
         beamer_webview_destroy(_webviewHandle);
         _webviewHandle = NULL;
     }
+    if (_nativeEditor != NULL) {
+        [self endAllParameterGestures];
+        beamer_au_native_editor_close(_nativeEditor);
+        _nativeEditor = NULL;
+    }
 
     uint32_t width = 0, height = 0;
     beamer_au_get_gui_size(_rustInstance, &width, &height);
@@ -1066,6 +1417,31 @@ static void beamer_auv3_on_loaded(void* context) {
     vc.view = container;
     vc.preferredContentSize = NSMakeSize(width, height);
 
+    if (beamer_au_has_native_gui(_rustInstance)) {
+        // The native editor keeps its initial size in this view.
+        _nativeEditor = beamer_au_native_editor_open(
+            _rustInstance, (__bridge void*)container, beamer_auv3_on_native_edit,
+            (__bridge void*)self);
+        if (_nativeEditor == NULL) {
+            completionHandler(nil);
+            return;
+        }
+        beamer_au_native_editor_get_size(_nativeEditor, &width, &height);
+        [container setFrameSize:NSMakeSize(width, height)];
+        vc.preferredContentSize = NSMakeSize(width, height);
+
+        // The sync timer forwards state, latency and name changes.
+        if (_syncTimer == nil) {
+            __weak typeof(self) weakSelf = self;
+            _syncTimer = [NSTimer scheduledTimerWithTimeInterval:1.0/60.0 repeats:YES block:^(NSTimer* t) {
+                (void)t;
+                [weakSelf _pollParams];
+            }];
+        }
+        completionHandler(vc);
+        return;
+    }
+
 #ifdef DEBUG
     bool devTools = true;
 #else
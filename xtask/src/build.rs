@@ -140,10 +140,26 @@ pub fn current_target() -> &'static str {
     #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
     return "x86_64-apple-darwin";
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    return "aarch64-unknown-linux-gnu";
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    return "x86_64-unknown-linux-gnu";
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
     compile_error!("Unsupported platform");
 }
 
+/// File name of the shared library cargo builds for `package`.
+pub fn library_file_name(package: &str) -> String {
+    let lib_name = package.replace('-', "_");
+    if cfg!(target_os = "linux") {
+        format!("lib{}.so", lib_name)
+    } else {
+        format!("lib{}.dylib", lib_name)
+    }
+}
+
 /// Build for a single architecture (native, arm64, or x86_64).
 pub fn build_native(
     package: &str,
@@ -156,6 +172,8 @@ pub fn build_native(
     // Always use explicit target to prevent RUSTFLAGS leaking into build scripts
     let target = match arch {
         Arch::Native => current_target(),
+        Arch::Arm64 if cfg!(target_os = "linux") => "aarch64-unknown-linux-gnu",
+        Arch::X86_64 if cfg!(target_os = "linux") => "x86_64-unknown-linux-gnu",
         Arch::Arm64 => "aarch64-apple-darwin",
         Arch::X86_64 => "x86_64-apple-darwin",
         Arch::Universal => unreachable!("Universal should use build_universal"),
@@ -165,8 +183,7 @@ pub fn build_native(
     crate::status!("  Building {} ({})...", format.to_uppercase(), arch_name);
 
     let profile = if release { "release" } else { "debug" };
-    let lib_name = package.replace('-', "_");
-    let dylib_name = format!("lib{}.dylib", lib_name);
+    let dylib_name = library_file_name(package);
 
     // AU requires additional setup (beamer-au and ObjC code)
     let rustflags = if format == "au" {
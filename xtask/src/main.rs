@@ -1,10 +1,13 @@
 //! Build tooling for Beamer plugins.
 //!
 //! Usage: cargo xtask bundle <package> [--vst3] [--auv2] [--auv3] [--arch <arch>] [--release] [--install] [--clean]
+//!        cargo xtask new <name> [--kind effect|instrument|midi] [--webview]
 
 mod auv2;
 mod auv3;
 mod build;
+mod new_plugin;
+mod size_report;
 mod util;
 mod vst3;
 
@@ -91,6 +94,54 @@ fn main() {
             generate_uuid();
             return;
         }
+        "stress" => {
+            if args.len() < 3 {
+                print_error("stress command requires a package name");
+                print_usage();
+                std::process::exit(1);
+            }
+            if let Err(e) = stress(&args[2], &args[3..]) {
+                print_error(&e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        "script" => {
+            if args.len() < 4 {
+                print_error("script command requires a package name and a script file");
+                print_usage();
+                std::process::exit(1);
+            }
+            if let Err(e) = script(&args[2], &args[3..]) {
+                print_error(&e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        "new" => {
+            if args.len() < 3 {
+                print_error("new command requires a plugin name");
+                print_usage();
+                std::process::exit(1);
+            }
+            if let Err(e) = new_plugin::new_plugin(&args[2], &args[3..]) {
+                print_error(&e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        "size-report" => {
+            if args.len() < 3 {
+                print_error("size-report command requires a package name");
+                print_usage();
+                std::process::exit(1);
+            }
+            if let Err(e) = size_report::size_report(&args[2], &args[3..]) {
+                print_error(&e);
+                std::process::exit(1);
+            }
+            return;
+        }
         "bundle" => {
             if args.len() < 3 {
                 print_error("bundle command requires a package name");
@@ -170,7 +221,13 @@ fn print_usage() {
     eprintln!();
     eprintln!("Commands:");
     eprintln!("  generate-uuid              Generate a new UUID for plugin identification");
+    eprintln!("  new <name> [options]       Create a new plugin crate in examples/<name>");
     eprintln!("  bundle <package> [options] Build and bundle a plugin");
+    eprintln!("  stress <package> [options] Sweep sample rates, block sizes and precisions");
+    eprintln!("  script <package> <file.json>... [options]");
+    eprintln!("                             Run headless parameter scripts (QA regression tests)");
+    eprintln!("  size-report <package> [options]");
+    eprintln!("                             Show the binary size each optional beamer feature adds");
     eprintln!();
     eprintln!("Formats (at least one required):");
     eprintln!("  --auv2    Build AUv2 .component bundle (simple distribution, works with all DAWs)");
@@ -201,6 +258,142 @@ fn print_usage() {
     eprintln!("  cargo xtask bundle gain --vst3 --release --install");
     eprintln!("  cargo xtask bundle gain --auv2 --auv3 --arch universal    # Both AU formats");
     eprintln!("  cargo xtask bundle gain --auv2 --vst3 --arch universal    # AUv2 + VST3");
+    eprintln!();
+    eprintln!("Stress options:");
+    eprintln!("  --release          Test the release build (recommended)");
+    eprintln!("  --quick            Sweep a reduced grid");
+    eprintln!("  --rates <list>     Sample rates, e.g. 44100,96000 (default: 44.1k to 192k)");
+    eprintln!("  --blocks <list>    Block sizes, e.g. 1,31,512 (default: 1 to 8192)");
+    eprintln!("  --seconds <secs>   Audio processed per configuration (default: 0.05)");
+    eprintln!("  --verbose          Show detailed build output (default: quiet)");
+    eprintln!();
+    eprintln!("  cargo xtask stress gain --release");
+    eprintln!();
+    eprintln!("Script options:");
+    eprintln!("  --release          Run the release build");
+    eprintln!("  --verbose          Show detailed build output (default: quiet)");
+    eprintln!();
+    eprintln!("  cargo xtask script gain qa/gain-sweep.json --release");
+    eprintln!();
+    eprintln!("New options:");
+    eprintln!("  --kind <kind>           effect (default), instrument or midi");
+    eprintln!("  --webview               Add a plain HTML WebView GUI");
+    eprintln!("  --manufacturer <code>   Four-character manufacturer code (default: random)");
+    eprintln!("  --vendor <name>         Vendor name (default: My Company)");
+    eprintln!();
+    eprintln!("  cargo xtask new my-synth --kind instrument --webview");
+    eprintln!();
+    eprintln!("Size report options:");
+    eprintln!("  --vst3             Include the VST3 wrapper in the measured binary");
+    eprintln!("  --verbose          Show detailed build output (default: quiet)");
+    eprintln!();
+    eprintln!("  cargo xtask size-report gain");
+}
+
+// =============================================================================
+// Stress Test
+// =============================================================================
+
+/// Run the sweep test `export_plugin!` adds with beamer's `stress` feature.
+fn stress(package: &str, args: &[String]) -> Result<(), String> {
+    let mut release = false;
+    let mut verbose = false;
+    let mut env = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--release" => release = true,
+            "--verbose" | "-v" => verbose = true,
+            "--quick" => env.push(("BEAMER_STRESS_QUICK", "1".to_string())),
+            "--rates" | "--blocks" | "--seconds" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("{} requires a value", arg))?;
+                let name = match arg.as_str() {
+                    "--rates" => "BEAMER_STRESS_RATES",
+                    "--blocks" => "BEAMER_STRESS_BLOCKS",
+                    _ => "BEAMER_STRESS_SECONDS",
+                };
+                env.push((name, value.clone()));
+            }
+            _ => return Err(format!("unknown stress argument '{}'", arg)),
+        }
+    }
+
+    let profile_str = if release { "release" } else { "debug" };
+    status!("Stress testing {} ({})...", package, profile_str);
+
+    let workspace_root = get_workspace_root()?;
+    let package_dir = workspace_root.join("examples").join(package);
+    build::build_webview(&package_dir, verbose)?;
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["test", "-p", package, "--lib", "--features", "beamer/stress"])
+        .current_dir(&workspace_root)
+        .envs(env);
+    if release {
+        cmd.arg("--release");
+    }
+    if !verbose {
+        cmd.arg("--quiet");
+    }
+    cmd.args(["--", "--exact", "__beamer_stress::stress", "--nocapture"]);
+
+    let status = cmd.status().map_err(|e| format!("Failed to run cargo: {}", e))?;
+    if !status.success() {
+        return Err(format!("stress test of {} failed", package));
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Script Runner
+// =============================================================================
+
+/// Run scripts through the test `export_plugin!` adds with beamer's
+/// `script` feature.
+fn script(package: &str, args: &[String]) -> Result<(), String> {
+    let mut release = false;
+    let mut verbose = false;
+    let mut scripts = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--release" => release = true,
+            "--verbose" | "-v" => verbose = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown script argument '{}'", arg)),
+            // The test runs in the package directory, so pass absolute paths
+            _ => scripts.push(
+                std::fs::canonicalize(arg).map_err(|e| format!("{}: {}", arg, e))?,
+            ),
+        }
+    }
+    let scripts = std::env::join_paths(&scripts)
+        .map_err(|e| format!("Invalid script path: {}", e))?;
+
+    let profile_str = if release { "release" } else { "debug" };
+    status!("Running scripts for {} ({})...", package, profile_str);
+
+    let workspace_root = get_workspace_root()?;
+    let package_dir = workspace_root.join("examples").join(package);
+    build::build_webview(&package_dir, verbose)?;
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["test", "-p", package, "--lib", "--features", "beamer/script"])
+        .current_dir(&workspace_root)
+        .env("BEAMER_SCRIPT", scripts);
+    if release {
+        cmd.arg("--release");
+    }
+    if !verbose {
+        cmd.arg("--quiet");
+    }
+    cmd.args(["--", "--exact", "__beamer_script::script", "--nocapture"]);
+
+    let status = cmd.status().map_err(|e| format!("Failed to run cargo: {}", e))?;
+    if !status.success() {
+        return Err(format!("scripts for {} failed", package));
+    }
+    Ok(())
 }
 
 // =============================================================================
@@ -258,12 +451,15 @@ fn bundle(config: &BundleConfig) -> Result<(), String> {
 
     // Build and bundle VST3
     if config.build_vst3 {
+        if config.arch == Arch::Universal && !cfg!(target_os = "macos") {
+            return Err("universal binaries are only supported on macOS".to_string());
+        }
         let dylib_path = if config.arch == Arch::Universal {
             build::build_universal(&config.package, config.release, &workspace_root, "vst3", config.verbose)?
         } else {
             build::build_native(&config.package, config.release, &workspace_root, "vst3", config.arch, config.verbose)?
         };
-        vst3::bundle_vst3(&config.package, &target_dir, &dylib_path, config.install, &workspace_root, config.verbose)?;
+        vst3::bundle_vst3(&config.package, &target_dir, &dylib_path, config.install, &workspace_root, config.arch, config.verbose)?;
     }
 
     Ok(())
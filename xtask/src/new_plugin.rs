@@ -0,0 +1,265 @@
+//! Plugin crate scaffolding (`cargo xtask new`).
+//!
+//! Creates `examples/<name>/` with a manifest, a `Config.toml` with fresh
+//! four-character codes (the VST3 class IDs are derived from them), a
+//! `src/lib.rs` for the chosen kind with offline-render tests, and
+//! optionally a plain HTML WebView GUI. The crate is added to the workspace
+//! members, so `cargo test --workspace` and `cargo xtask bundle <name>`
+//! pick it up right away.
+
+use std::fs;
+use std::path::Path;
+
+use crate::util::to_pascal_case;
+
+/// The kind of plugin to generate.
+#[derive(Clone, Copy)]
+enum Kind {
+    Effect,
+    Instrument,
+    Midi,
+}
+
+impl Kind {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "effect" => Some(Self::Effect),
+            "instrument" => Some(Self::Instrument),
+            "midi" => Some(Self::Midi),
+            _ => None,
+        }
+    }
+
+    fn template(self) -> &'static str {
+        match self {
+            Self::Effect => include_str!("templates/effect.rs.in"),
+            Self::Instrument => include_str!("templates/instrument.rs.in"),
+            Self::Midi => include_str!("templates/midi.rs.in"),
+        }
+    }
+
+    /// `Config.toml` category and subcategory.
+    fn category(self) -> (&'static str, Option<&'static str>) {
+        match self {
+            Self::Effect => ("effect", Some("tools")),
+            Self::Instrument => ("instrument", Some("synth")),
+            Self::Midi => ("midi_effect", None),
+        }
+    }
+
+    /// String ID and name of the parameter the generated GUI controls.
+    fn gui_parameter(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Effect => ("gain", "Gain"),
+            Self::Instrument => ("level", "Level"),
+            Self::Midi => ("transpose", "Transpose"),
+        }
+    }
+}
+
+/// Scaffold a new plugin crate.
+pub fn new_plugin(name: &str, args: &[String]) -> Result<(), String> {
+    let mut kind = Kind::Effect;
+    let mut webview = false;
+    let mut manufacturer = None;
+    let mut vendor = "My Company".to_string();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--webview" => webview = true,
+            "--kind" | "--manufacturer" | "--vendor" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("{} requires a value", arg))?;
+                match arg.as_str() {
+                    "--kind" => {
+                        kind = Kind::from_str(value).ok_or_else(|| {
+                            format!("unknown kind '{}' (expected effect, instrument or midi)", value)
+                        })?;
+                    }
+                    "--manufacturer" => manufacturer = Some(value.clone()),
+                    _ => vendor = value.clone(),
+                }
+            }
+            _ => return Err(format!("unknown new argument '{}'", arg)),
+        }
+    }
+
+    validate_name(name)?;
+    let manufacturer = match manufacturer {
+        Some(code) => {
+            validate_manufacturer(&code)?;
+            code
+        }
+        None => random_code(true),
+    };
+    let plugin_code = random_code(false);
+
+    let workspace_root = crate::get_workspace_root()?;
+    let package_dir = workspace_root.join("examples").join(name);
+    if package_dir.exists() {
+        return Err(format!("{} already exists", package_dir.display()));
+    }
+
+    crate::status!("Creating {} ({})...", name, kind.category().0);
+
+    let display_name = to_display_name(name);
+    let type_name = to_pascal_case(name);
+    let (parameter_id, parameter_name) = kind.gui_parameter();
+    let fill = |template: &str| {
+        template
+            .replace("{{name}}", &display_name)
+            .replace("{{Type}}", &type_name)
+            .replace("{{param}}", parameter_id)
+            .replace("{{param_name}}", parameter_name)
+    };
+
+    write(&package_dir.join("Cargo.toml"), &cargo_toml(name, &display_name))?;
+    write(
+        &package_dir.join("Config.toml"),
+        &config_toml(kind, &display_name, &manufacturer, &plugin_code, &vendor, webview),
+    )?;
+    write(&package_dir.join("src").join("lib.rs"), &fill(kind.template()))?;
+    if webview {
+        write(
+            &package_dir.join("webview").join("index.html"),
+            &fill(include_str!("templates/index.html.in")),
+        )?;
+    }
+
+    add_workspace_member(&workspace_root.join("Cargo.toml"), name)?;
+
+    crate::status!("  Manufacturer code: {}", manufacturer);
+    crate::status!("  Plugin code:       {}", plugin_code);
+    crate::status!("Created {}", package_dir.display());
+    crate::status!();
+    crate::status!("Next steps:");
+    crate::status!("  cargo test -p {}", name);
+    crate::status!("  cargo xtask bundle {} --auv2 --vst3 --install", name);
+    Ok(())
+}
+
+/// Package names become crate, bundle and type names, so keep them to
+/// lowercase ASCII words separated by hyphens.
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && !name.ends_with('-')
+        && !name.contains("--")
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid plugin name '{}' (use lowercase letters, digits and hyphens, e.g. my-plugin)",
+            name
+        ))
+    }
+}
+
+/// Apple reserves all-lowercase manufacturer codes, so at least one
+/// character must be uppercase.
+fn validate_manufacturer(code: &str) -> Result<(), String> {
+    if code.len() == 4 && code.is_ascii() && code.chars().any(|c| c.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid manufacturer code '{}' (4 ASCII characters with at least one uppercase letter)",
+            code
+        ))
+    }
+}
+
+/// A random four-character code. Manufacturer codes start with an uppercase
+/// letter; plugin codes are all lowercase.
+fn random_code(manufacturer: bool) -> String {
+    uuid::Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(4)
+        .enumerate()
+        .map(|(i, byte)| {
+            let letter = (b'a' + byte % 26) as char;
+            if manufacturer && i == 0 {
+                letter.to_ascii_uppercase()
+            } else {
+                letter
+            }
+        })
+        .collect()
+}
+
+/// "my-plugin" -> "My Plugin"
+fn to_display_name(name: &str) -> String {
+    name.split('-').map(to_pascal_case).collect::<Vec<_>>().join(" ")
+}
+
+fn cargo_toml(name: &str, display_name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+description = "{display_name} plugin built with the Beamer framework"
+version.workspace = true
+edition.workspace = true
+license.workspace = true
+
+[lib]
+crate-type = ["cdylib"]
+
+[features]
+au = ["beamer/au"]
+vst3 = ["beamer/vst3"]
+
+[lints]
+workspace = true
+
+[dependencies]
+beamer = {{ workspace = true }}
+"#
+    )
+}
+
+fn config_toml(
+    kind: Kind,
+    display_name: &str,
+    manufacturer: &str,
+    plugin_code: &str,
+    vendor: &str,
+    webview: bool,
+) -> String {
+    let (category, subcategory) = kind.category();
+    let mut config = format!("name = \"{display_name}\"\ncategory = \"{category}\"\n");
+    if let Some(subcategory) = subcategory {
+        config.push_str(&format!("subcategories = [\"{subcategory}\"]\n"));
+    }
+    config.push_str(&format!(
+        "# Four-character codes identifying the plugin. The VST3 class IDs are\n\
+         # derived from them, so keep them once the plugin is released.\n\
+         manufacturer_code = \"{manufacturer}\"\n\
+         plugin_code = \"{plugin_code}\"\n\
+         vendor = \"{vendor}\"\n"
+    ));
+    if webview {
+        config.push_str("has_gui = true\ngui_size = [400, 160]\ngui_background_color = \"#1a1a2e\"\n");
+    }
+    config
+}
+
+/// Add `examples/<name>` to the workspace members, before `xtask`.
+fn add_workspace_member(manifest: &Path, name: &str) -> Result<(), String> {
+    let content = fs::read_to_string(manifest)
+        .map_err(|e| format!("Failed to read {}: {}", manifest.display(), e))?;
+    let Some(index) = content.find("    \"xtask\",\n") else {
+        crate::status!("  Add \"examples/{}\" to the workspace members in Cargo.toml", name);
+        return Ok(());
+    };
+    let mut updated = content.clone();
+    updated.insert_str(index, &format!("    \"examples/{}\",\n", name));
+    fs::write(manifest, updated).map_err(|e| format!("Failed to write {}: {}", manifest.display(), e))
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
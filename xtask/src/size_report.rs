@@ -0,0 +1,323 @@
+//! Binary size per beamer feature (`cargo xtask size-report`).
+//!
+//! Builds the plugin's release cdylib once as configured and once per
+//! optional beamer feature with that feature toggled, and prints the size
+//! differences. The builds use a scratch copy of the package under
+//! `target/size-report/`: its manifest is the plugin's own with workspace
+//! inheritance resolved and the `beamer` features replaced, and every other
+//! file of the package is linked in, so `Config.toml` and GUI assets resolve
+//! as usual. The plugin's manifest is never modified.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use toml::{Table, Value};
+
+use crate::util::PathExt;
+
+/// Optional beamer features, in the order they are reported.
+const FEATURES: [&str; 6] = ["midi", "smoothing", "groups", "presets", "webview-bridge", "dsp"];
+
+/// Measure the plugin with each optional feature toggled.
+pub fn size_report(package: &str, args: &[String]) -> Result<(), String> {
+    let mut format = None;
+    let mut verbose = false;
+    for arg in args {
+        match arg.as_str() {
+            "--vst3" => format = Some("vst3"),
+            "--verbose" | "-v" => verbose = true,
+            _ => return Err(format!("unknown size-report argument '{}'", arg)),
+        }
+    }
+
+    let workspace_root = crate::get_workspace_root()?;
+    let package_dir = workspace_root.join("examples").join(package);
+    crate::status!("Size report for {} (release{})...", package, format.map_or(String::new(), |f| format!(", {}", f)));
+    crate::build::build_webview(&package_dir, verbose)?;
+
+    let scratch = Scratch::new(&workspace_root, &package_dir, package)?;
+    let enabled = scratch.enabled_features();
+
+    let baseline = scratch.build(&enabled, format, verbose)?;
+    crate::status!();
+    crate::status!("  {:<26} {:>12}", "as configured", format_size(baseline as i64));
+    for feature in FEATURES {
+        let on = enabled.contains(&feature.to_string());
+        let features: Vec<String> = if on {
+            enabled.iter().filter(|f| *f != feature).cloned().collect()
+        } else {
+            enabled.iter().cloned().chain([feature.to_string()]).collect()
+        };
+        let label = format!("{} {}", if on { "without" } else { "with" }, feature);
+        match scratch.build(&features, format, verbose) {
+            Ok(size) => crate::status!(
+                "  {:<26} {:>12} {:>12}",
+                label,
+                format_size(size as i64),
+                format_delta(size as i64 - baseline as i64)
+            ),
+            // Most likely the plugin uses the feature
+            Err(_) => crate::status!("  {:<26} {:>12}", label, "(doesn't build)"),
+        }
+    }
+    crate::status!();
+    crate::status!("Turn features off with `default-features = false` on the beamer dependency.");
+    Ok(())
+}
+
+/// Scratch package mirroring the plugin, with a rewritable manifest.
+struct Scratch {
+    dir: PathBuf,
+    target_dir: PathBuf,
+    manifest: Table,
+    lib_name: String,
+}
+
+impl Scratch {
+    fn new(workspace_root: &Path, package_dir: &Path, package: &str) -> Result<Self, String> {
+        let root_manifest = read_toml(&workspace_root.join("Cargo.toml"))?;
+        let mut manifest = read_toml(&package_dir.join("Cargo.toml"))?;
+        let workspace = root_manifest.get("workspace").and_then(Value::as_table).cloned().unwrap_or_default();
+
+        // Resolve `field.workspace = true` package keys
+        let workspace_package = workspace.get("package").and_then(Value::as_table).cloned().unwrap_or_default();
+        if let Some(Value::Table(fields)) = manifest.get_mut("package") {
+            for (key, value) in fields.iter_mut() {
+                if is_inherited(value) {
+                    *value = workspace_package.get(key).cloned().unwrap_or(Value::String("0.0.0".into()));
+                }
+            }
+        }
+
+        // Dependencies with absolute paths and workspace entries resolved
+        let workspace_deps = workspace.get("dependencies").and_then(Value::as_table).cloned().unwrap_or_default();
+        let resolve = |deps: &mut Table| {
+            for (name, dep) in deps.iter_mut() {
+                *dep = resolve_dependency(name, dep, &workspace_deps, workspace_root, package_dir);
+            }
+        };
+        for section in ["dependencies", "build-dependencies"] {
+            if let Some(Value::Table(deps)) = manifest.get_mut(section) {
+                resolve(deps);
+            }
+        }
+        if let Some(Value::Table(targets)) = manifest.get_mut("target") {
+            for (_, target) in targets.iter_mut() {
+                let Some(target) = target.as_table_mut() else { continue };
+                for section in ["dependencies", "build-dependencies"] {
+                    if let Some(Value::Table(deps)) = target.get_mut(section) {
+                        resolve(deps);
+                    }
+                }
+                target.remove("dev-dependencies");
+            }
+        }
+        manifest.remove("dev-dependencies");
+        manifest.remove("lints");
+        // A standalone workspace with the root's release profile (LTO, strip)
+        manifest.insert("workspace".into(), Value::Table(Table::new()));
+        if let Some(profile) = root_manifest.get("profile") {
+            manifest.insert("profile".into(), profile.clone());
+        }
+
+        let dir = workspace_root.join("target").join("size-report").join(package);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        for entry in fs::read_dir(package_dir).map_err(|e| format!("Failed to read package dir: {}", e))? {
+            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+            let name = entry.file_name();
+            if name == "Cargo.toml" || name == "Cargo.lock" || name == "target" {
+                continue;
+            }
+            link(&entry.path(), &dir.join(&name))?;
+        }
+        // Same dependency versions as the workspace build
+        let lock = workspace_root.join("Cargo.lock");
+        if lock.exists() {
+            fs::copy(&lock, dir.join("Cargo.lock")).map_err(|e| format!("Failed to copy Cargo.lock: {}", e))?;
+        }
+
+        let lib_name = manifest
+            .get("lib")
+            .and_then(|lib| lib.get("name"))
+            .and_then(Value::as_str)
+            .unwrap_or(package)
+            .replace('-', "_");
+        Ok(Self {
+            dir,
+            target_dir: workspace_root.join("target").join("size-report").join("target"),
+            manifest,
+            lib_name,
+        })
+    }
+
+    fn beamer(&self) -> Option<&Table> {
+        self.manifest.get("dependencies")?.get("beamer")?.as_table()
+    }
+
+    /// Optional features the plugin's beamer dependency enables.
+    fn enabled_features(&self) -> Vec<String> {
+        let Some(beamer) = self.beamer() else {
+            return Vec::new();
+        };
+        let defaults = beamer.get("default-features").and_then(Value::as_bool).unwrap_or(true);
+        let listed = |feature: &str| {
+            beamer
+                .get("features")
+                .and_then(Value::as_array)
+                .is_some_and(|list| list.iter().any(|f| f.as_str() == Some(feature)))
+        };
+        FEATURES
+            .iter()
+            .filter(|feature| defaults || listed(feature))
+            .map(|feature| feature.to_string())
+            .collect()
+    }
+
+    /// Build with exactly `features` of the optional set and return the
+    /// cdylib size in bytes.
+    fn build(&self, features: &[String], format: Option<&str>, verbose: bool) -> Result<u64, String> {
+        let mut manifest = self.manifest.clone();
+        let Some(Value::Table(beamer)) = manifest
+            .get_mut("dependencies")
+            .and_then(Value::as_table_mut)
+            .and_then(|deps| deps.get_mut("beamer"))
+        else {
+            return Err("the package doesn't depend on beamer".to_string());
+        };
+        // Keep the plugin's other beamer features (derive, host-trace, ...)
+        let defaults = beamer.get("default-features").and_then(Value::as_bool).unwrap_or(true);
+        let mut list: Vec<Value> = beamer
+            .get("features")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|f| !f.as_str().is_some_and(|f| FEATURES.contains(&f)))
+            .collect();
+        if defaults {
+            list.push(Value::String("derive".into()));
+        }
+        list.extend(features.iter().map(|f| Value::String(f.clone())));
+        beamer.insert("default-features".into(), Value::Boolean(false));
+        beamer.insert("features".into(), Value::Array(list));
+
+        let manifest_path = self.dir.join("Cargo.toml");
+        let text = toml::to_string(&manifest).map_err(|e| format!("Failed to write manifest: {}", e))?;
+        fs::write(&manifest_path, text).map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+        crate::verbose!(verbose, "  Building with [{}]...", features.join(", "));
+        let mut cmd = Command::new("cargo");
+        cmd.args(["build", "--release", "--lib", "--manifest-path"])
+            .arg(manifest_path.to_str_safe()?)
+            .arg("--target-dir")
+            .arg(self.target_dir.to_str_safe()?);
+        if let Some(format) = format {
+            cmd.args(["--features", format]);
+        }
+        if !verbose {
+            cmd.arg("--quiet").stderr(Stdio::null());
+        }
+        let status = cmd.status().map_err(|e| format!("Failed to run cargo: {}", e))?;
+        if !status.success() {
+            return Err(format!("build with [{}] failed", features.join(", ")));
+        }
+
+        let library = self.target_dir.join("release").join(format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            self.lib_name,
+            std::env::consts::DLL_SUFFIX
+        ));
+        fs::metadata(&library)
+            .map(|meta| meta.len())
+            .map_err(|e| format!("Built library not found: {} ({})", library.display(), e))
+    }
+}
+
+fn read_toml(path: &Path) -> Result<Table, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    text.parse::<Table>().map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn is_inherited(value: &Value) -> bool {
+    value.get("workspace").and_then(Value::as_bool) == Some(true)
+}
+
+/// Inline a `workspace = true` dependency and make its path absolute.
+fn resolve_dependency(
+    name: &str,
+    dep: &Value,
+    workspace_deps: &Table,
+    workspace_root: &Path,
+    package_dir: &Path,
+) -> Value {
+    let as_table = |value: &Value| match value {
+        Value::String(version) => Table::from_iter([("version".to_string(), Value::String(version.clone()))]),
+        Value::Table(table) => table.clone(),
+        _ => Table::new(),
+    };
+    let (mut resolved, base) = if is_inherited(dep) {
+        let Some(inherited) = workspace_deps.get(name) else {
+            return dep.clone();
+        };
+        let mut resolved = as_table(inherited);
+        for (key, value) in as_table(dep) {
+            match (key.as_str(), resolved.get_mut("features")) {
+                ("workspace", _) => {}
+                ("features", Some(Value::Array(features))) => {
+                    features.extend(value.as_array().cloned().unwrap_or_default());
+                }
+                _ => {
+                    resolved.insert(key, value);
+                }
+            }
+        }
+        (resolved, workspace_root)
+    } else if dep.is_table() {
+        (as_table(dep), package_dir)
+    } else {
+        return dep.clone();
+    };
+    if let Some(Value::String(path)) = resolved.get("path") {
+        let path = base.join(path).display().to_string();
+        resolved.insert("path".into(), Value::String(path));
+    }
+    Value::Table(resolved)
+}
+
+/// Link `src` into the scratch package (copy where symlinks aren't available).
+fn link(src: &Path, dst: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(src, dst).map_err(|e| format!("Failed to link {}: {}", src.display(), e))
+    }
+    #[cfg(not(unix))]
+    {
+        if src.is_dir() {
+            crate::util::copy_dir_all(src, dst)
+        } else {
+            fs::copy(src, dst).map(|_| ()).map_err(|e| format!("Failed to copy {}: {}", src.display(), e))
+        }
+    }
+}
+
+fn format_size(bytes: i64) -> String {
+    let magnitude = bytes.unsigned_abs() as f64;
+    if magnitude >= 1024.0 * 1024.0 {
+        format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else if magnitude >= 1024.0 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn format_delta(bytes: i64) -> String {
+    if bytes > 0 {
+        format!("+{}", format_size(bytes))
+    } else {
+        format_size(bytes)
+    }
+}
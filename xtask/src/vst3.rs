@@ -1,12 +1,13 @@
 //! VST3 plugin bundling support.
 //!
-//! This module handles creating and installing VST3 plugin bundles on macOS.
+//! This module handles creating and installing VST3 plugin bundles on macOS
+//! and Linux.
 
 use std::fs;
 use std::path::Path;
 
 use crate::build::get_version_info;
-use crate::util::{install_bundle, shorten_path, to_vst3_bundle_name};
+use crate::util::{install_bundle, shorten_path, to_vst3_bundle_name, Arch};
 
 /// Creates a VST3 bundle from a compiled dylib.
 ///
@@ -20,12 +21,23 @@ use crate::util::{install_bundle, shorten_path, to_vst3_bundle_name};
 ///     │   └── PluginName (binary)
 ///     └── Resources/
 /// ```
+///
+/// On Linux the binary goes into an architecture folder instead, and there
+/// is no Info.plist or PkgInfo:
+/// ```text
+/// PluginName.vst3/
+/// └── Contents/
+///     ├── x86_64-linux/
+///     │   └── PluginName.so
+///     └── Resources/
+/// ```
 pub fn bundle_vst3(
     package: &str,
     target_dir: &Path,
     dylib_path: &Path,
     install: bool,
     workspace_root: &Path,
+    arch: Arch,
     verbose: bool,
 ) -> Result<(), String> {
     // Get version from Cargo.toml
@@ -37,7 +49,6 @@ pub fn bundle_vst3(
 
     // Create bundle directory structure
     let contents_dir = bundle_dir.join("Contents");
-    let macos_dir = contents_dir.join("MacOS");
     let resources_dir = contents_dir.join("Resources");
 
     crate::status!("  Creating VST3 bundle...");
@@ -48,24 +59,33 @@ pub fn bundle_vst3(
         fs::remove_dir_all(&bundle_dir).map_err(|e| format!("Failed to remove old bundle: {}", e))?;
     }
 
-    // Create directories
-    fs::create_dir_all(&macos_dir).map_err(|e| format!("Failed to create MacOS dir: {}", e))?;
     fs::create_dir_all(&resources_dir)
         .map_err(|e| format!("Failed to create Resources dir: {}", e))?;
 
-    // Copy dylib
-    let plugin_binary = macos_dir.join(bundle_name.trim_end_matches(".vst3"));
-    fs::copy(dylib_path, &plugin_binary)
-        .map_err(|e| format!("Failed to copy dylib: {}", e))?;
-
-    // Create Info.plist
-    let info_plist = create_vst3_info_plist(package, &bundle_name, &version_string);
-    fs::write(contents_dir.join("Info.plist"), info_plist)
-        .map_err(|e| format!("Failed to write Info.plist: {}", e))?;
-
-    // Create PkgInfo
-    fs::write(contents_dir.join("PkgInfo"), "BNDL????")
-        .map_err(|e| format!("Failed to write PkgInfo: {}", e))?;
+    let executable_name = bundle_name.trim_end_matches(".vst3");
+    if cfg!(target_os = "linux") {
+        // Copy shared object into the architecture folder
+        let binary_dir = contents_dir.join(linux_arch_dir(arch));
+        fs::create_dir_all(&binary_dir)
+            .map_err(|e| format!("Failed to create binary dir: {}", e))?;
+        fs::copy(dylib_path, binary_dir.join(format!("{}.so", executable_name)))
+            .map_err(|e| format!("Failed to copy shared object: {}", e))?;
+    } else {
+        // Copy dylib
+        let macos_dir = contents_dir.join("MacOS");
+        fs::create_dir_all(&macos_dir).map_err(|e| format!("Failed to create MacOS dir: {}", e))?;
+        fs::copy(dylib_path, macos_dir.join(executable_name))
+            .map_err(|e| format!("Failed to copy dylib: {}", e))?;
+
+        // Create Info.plist
+        let info_plist = create_vst3_info_plist(package, &bundle_name, &version_string);
+        fs::write(contents_dir.join("Info.plist"), info_plist)
+            .map_err(|e| format!("Failed to write Info.plist: {}", e))?;
+
+        // Create PkgInfo
+        fs::write(contents_dir.join("PkgInfo"), "BNDL????")
+            .map_err(|e| format!("Failed to write PkgInfo: {}", e))?;
+    }
 
     // Install if requested
     if install {
@@ -77,6 +97,16 @@ pub fn bundle_vst3(
     Ok(())
 }
 
+/// Architecture folder name of a Linux VST3 bundle, e.g. `x86_64-linux`.
+fn linux_arch_dir(arch: Arch) -> String {
+    let arch = match arch {
+        Arch::Arm64 => "aarch64",
+        Arch::X86_64 => "x86_64",
+        Arch::Native | Arch::Universal => std::env::consts::ARCH,
+    };
+    format!("{}-linux", arch)
+}
+
 /// Creates the Info.plist content for a VST3 bundle.
 fn create_vst3_info_plist(package: &str, bundle_name: &str, version: &str) -> String {
     let executable_name = bundle_name.trim_end_matches(".vst3");
@@ -115,14 +145,15 @@ fn create_vst3_info_plist(package: &str, bundle_name: &str, version: &str) -> St
 
 /// Installs a VST3 bundle to the user's plugin directory.
 ///
-/// The bundle is copied to `~/Library/Audio/Plug-Ins/VST3/`.
+/// The bundle is copied to `~/Library/Audio/Plug-Ins/VST3/` on macOS and
+/// `~/.vst3/` on Linux.
 fn install_vst3(bundle_dir: &Path, bundle_name: &str, verbose: bool) -> Result<(), String> {
-    let dest = install_bundle(
-        bundle_dir,
-        bundle_name,
-        &["Library", "Audio", "Plug-Ins", "VST3"],
-        verbose,
-    )?;
+    let install_subdir: &[&str] = if cfg!(target_os = "linux") {
+        &[".vst3"]
+    } else {
+        &["Library", "Audio", "Plug-Ins", "VST3"]
+    };
+    let dest = install_bundle(bundle_dir, bundle_name, install_subdir, verbose)?;
     crate::status!("  {} -> {}", bundle_name, shorten_path(&dest));
     Ok(())
 }